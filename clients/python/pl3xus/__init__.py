@@ -0,0 +1,20 @@
+"""Python client for pl3xus_sync servers.
+
+Subscribe to synced components, send requests and mutate components with
+plain dicts; payloads are transcoded from the shapes in the server's
+``DescribeSchema`` response, so no type definitions are needed::
+
+    import pl3xus
+
+    with pl3xus.connect("tcp://127.0.0.1:8081") as client:
+        positions = client.subscribe("RobotPosition")
+        programs = client.request("ListPrograms", {})
+        client.mutate(entity_id, "RobotPosition", {"x": 1.0, "y": 2.0, "z": 0.0})
+"""
+
+from . import filters
+from .client import Client, MutationError, Pl3xusError, Subscription, connect
+from .codec import CodecError
+
+__all__ = ["Client", "CodecError", "MutationError", "Pl3xusError", "Subscription", "connect", "filters"]
+__version__ = "0.1.1"
@@ -0,0 +1,35 @@
+# @generated by pl3xus_sync::codegen::python_protocol. Do not edit.
+# Regenerate with: PL3XUS_BLESS=1 cargo test -p pl3xus_sync --lib codegen
+
+SYNC_CLIENT_MESSAGE_TYPE = "pl3xus_sync::messages::SyncClientMessage"
+SYNC_SERVER_MESSAGE_TYPE = "pl3xus_sync::messages::SyncServerMessage"
+REQUEST_TYPE_TEMPLATE = "pl3xus::managers::network_request::RequestInternal<{}>"
+RESPONSE_TYPE_MARKER = "ResponseInternal<"
+DESCRIBE_SCHEMA_TYPE = "pl3xus_common::schema::DescribeSchema"
+BINCODE_SUBPROTOCOL = "pl3xus.bincode.v1"
+SYNC_PROTOCOL_VERSION = 4
+
+# Enum variants in declaration order; bincode encodes the index.
+SYNC_CLIENT_MESSAGE_VARIANTS = ["Subscription","Unsubscribe","Mutate","Query","QueryCancel","Undo","Redo"]
+SYNC_SERVER_MESSAGE_VARIANTS = ["Welcome","SyncBatch","MutationResponse","QueryResponse","QueryInvalidation","StateTransition","RegistryUpdated","LiveQueryUpdate"]
+SYNC_ITEM_VARIANTS = ["Snapshot","Update","ComponentRemoved","EntityRemoved"]
+MUTATION_STATUS_VARIANTS = ["Ok","Forbidden","NotFound","ValidationError","InternalError"]
+SUBSCRIPTION_FILTER_VARIANTS = ["Field","In","Entities","And","Or","Not"]
+FILTER_OP_VARIANTS = ["Eq","Ne","Lt","Le","Gt","Ge"]
+FILTER_VALUE_VARIANTS = ["Null","Bool","Int","Float","String"]
+TYPE_SHAPE_VARIANTS = ["Bool","I8","I16","I32","I64","I128","U8","U16","U32","U64","U128","F32","F64","Char","String","Bytes","Unit","Option","Seq","Tuple","Map","Struct","Newtype","Enum","Any"]
+
+# Shapes of protocol structs, in the serde JSON form of `TypeShape`.
+SHAPES = {
+    "NetworkPacket": {"Struct":{"name":"NetworkPacket","fields":[{"name":"type_name","shape":"String"},{"name":"schema_hash","shape":"U64"},{"name":"data","shape":{"Seq":"U8"}}]}},
+    "SerializableEntity": {"Struct":{"name":"SerializableEntity","fields":[{"name":"bits","shape":"U64"}]}},
+    "SubscriptionRequest": {"Struct":{"name":"SubscriptionRequest","fields":[{"name":"subscription_id","shape":"U64"},{"name":"component_type","shape":"String"},{"name":"entity","shape":{"Option":{"Struct":{"name":"SerializableEntity","fields":[{"name":"bits","shape":"U64"}]}}}},{"name":"filter","shape":{"Option":{"Enum":{"name":"SubscriptionFilter","variants":["Field","In","Entities","And","Or","Not"]}}}}]}},
+    "UnsubscribeRequest": {"Struct":{"name":"UnsubscribeRequest","fields":[{"name":"subscription_id","shape":"U64"}]}},
+    "MutateComponent": {"Struct":{"name":"MutateComponent","fields":[{"name":"request_id","shape":{"Option":"U64"}},{"name":"entity","shape":{"Struct":{"name":"SerializableEntity","fields":[{"name":"bits","shape":"U64"}]}}},{"name":"component_type","shape":"String"},{"name":"value","shape":{"Seq":"U8"}},{"name":"idempotency_key","shape":{"Option":"U64"}}]}},
+    "WelcomeMessage": {"Struct":{"name":"WelcomeMessage","fields":[{"name":"connection_id","shape":{"Struct":{"name":"ConnectionId","fields":[{"name":"id","shape":"U32"}]}}},{"name":"protocol_version","shape":"U32"},{"name":"session","shape":"U64"}]}},
+    "SubscriptionSequence": {"Struct":{"name":"SubscriptionSequence","fields":[{"name":"subscription_id","shape":"U64"},{"name":"sequence","shape":"U64"}]}},
+    "SyncBatch": {"Struct":{"name":"SyncBatch","fields":[{"name":"items","shape":{"Seq":{"Enum":{"name":"SyncItem","variants":["Snapshot","Update","ComponentRemoved","EntityRemoved"]}}}},{"name":"sent_at_ms","shape":{"Option":"F64"}},{"name":"sequences","shape":{"Seq":{"Struct":{"name":"SubscriptionSequence","fields":[{"name":"subscription_id","shape":"U64"},{"name":"sequence","shape":"U64"}]}}}}]}},
+    "MutationResponse": {"Struct":{"name":"MutationResponse","fields":[{"name":"request_id","shape":{"Option":"U64"}},{"name":"status","shape":{"Enum":{"name":"MutationStatus","variants":["Ok","Forbidden","NotFound","ValidationError","InternalError"]}}},{"name":"message","shape":{"Option":"String"}},{"name":"field_errors","shape":{"Seq":{"Struct":{"name":"FieldError","fields":[{"name":"field","shape":"String"},{"name":"message","shape":"String"}]}}}}]}},
+    "QueryInvalidation": {"Struct":{"name":"QueryInvalidation","fields":[{"name":"query_types","shape":{"Seq":"String"}},{"name":"keys","shape":{"Option":{"Seq":"String"}}}]}},
+    "SchemaDescription": {"Struct":{"name":"SchemaDescription","fields":[{"name":"components","shape":{"Seq":{"Struct":{"name":"TypeSchema","fields":[{"name":"name","shape":"String"},{"name":"type_name","shape":"String"},{"name":"shape","shape":{"Enum":{"name":"TypeShape","variants":["Bool","I8","I16","I32","I64","I128","U8","U16","U32","U64","U128","F32","F64","Char","String","Bytes","Unit","Option","Seq","Tuple","Map","Struct","Newtype","Enum","Any"]}}}]}}}},{"name":"messages","shape":{"Seq":{"Struct":{"name":"TypeSchema","fields":[{"name":"name","shape":"String"},{"name":"type_name","shape":"String"},{"name":"shape","shape":{"Enum":{"name":"TypeShape","variants":["Bool","I8","I16","I32","I64","I128","U8","U16","U32","U64","U128","F32","F64","Char","String","Bytes","Unit","Option","Seq","Tuple","Map","Struct","Newtype","Enum","Any"]}}}]}}}},{"name":"requests","shape":{"Seq":{"Struct":{"name":"RequestSchema","fields":[{"name":"name","shape":"String"},{"name":"type_name","shape":"String"},{"name":"request","shape":{"Enum":{"name":"TypeShape","variants":["Bool","I8","I16","I32","I64","I128","U8","U16","U32","U64","U128","F32","F64","Char","String","Bytes","Unit","Option","Seq","Tuple","Map","Struct","Newtype","Enum","Any"]}}},{"name":"response","shape":{"Enum":{"name":"TypeShape","variants":["Bool","I8","I16","I32","I64","I128","U8","U16","U32","U64","U128","F32","F64","Char","String","Bytes","Unit","Option","Seq","Tuple","Map","Struct","Newtype","Enum","Any"]}}}]}}}}]}},
+}
@@ -0,0 +1,534 @@
+"""Blocking client for pl3xus_sync servers.
+
+The client keeps one background thread reading from the connection. Calls
+like :meth:`Client.request` and :meth:`Client.mutate` block the caller until
+the server answers; subscription and message callbacks run on the reader
+thread.
+"""
+
+import itertools
+import json
+import random
+import socket
+import threading
+from concurrent import futures
+from urllib.parse import urlparse
+
+from . import _protocol as protocol
+from .codec import Reader, Writer, decode, encode
+from .filters import matches, write_filter
+
+__all__ = ["Client", "MutationError", "Pl3xusError", "Subscription", "connect"]
+
+DEFAULT_TIMEOUT = 10.0
+
+_HOOKS = {"SubscriptionFilter": write_filter}
+
+
+class Pl3xusError(Exception):
+    """The connection closed, timed out, or the server rejected something."""
+
+
+class MutationError(Pl3xusError):
+    """The server rejected a mutation."""
+
+    def __init__(self, response):
+        self.status = response["status"]
+        self.message = response.get("message")
+        self.field_errors = response.get("field_errors", [])
+        super().__init__(self.message or self.status)
+
+
+def connect(url, timeout=DEFAULT_TIMEOUT):
+    """Connect to ``tcp://host:port`` or ``ws://host:port/path`` and fetch the
+    server's schema.
+
+    WebSocket URLs need the ``websockets`` package (``pip install
+    pl3xus[websocket]``).
+    """
+    scheme = urlparse(url).scheme
+    if scheme == "tcp":
+        transport = _TcpTransport(url, timeout)
+    elif scheme in ("ws", "wss"):
+        transport = _WebSocketTransport(url, timeout)
+    else:
+        raise ValueError(f"unsupported URL scheme {scheme!r}; use tcp://, ws:// or wss://")
+    return Client(transport, timeout)
+
+
+# ============================================================================
+# Transports: both carry packets as an 8-byte little-endian length followed
+# by a bincode `NetworkPacket`.
+# ============================================================================
+
+
+def _frame(packet):
+    data = encode(protocol.SHAPES["NetworkPacket"], packet)
+    return len(data).to_bytes(8, "little") + data
+
+
+class _TcpTransport:
+    def __init__(self, url, timeout):
+        parsed = urlparse(url)
+        self.sock = socket.create_connection((parsed.hostname, parsed.port), timeout=timeout)
+        self.sock.settimeout(None)
+
+    def send(self, frame):
+        self.sock.sendall(frame)
+
+    def packets(self):
+        while True:
+            header = self._read_exact(8)
+            if header is None:
+                return
+            body = self._read_exact(int.from_bytes(header, "little"))
+            if body is None:
+                return
+            yield body
+
+    def _read_exact(self, n):
+        chunks = bytearray()
+        while len(chunks) < n:
+            try:
+                chunk = self.sock.recv(n - len(chunks))
+            except OSError:
+                return None
+            if not chunk:
+                return None
+            chunks += chunk
+        return bytes(chunks)
+
+    def close(self):
+        try:
+            self.sock.shutdown(socket.SHUT_RDWR)
+        except OSError:
+            pass
+        self.sock.close()
+
+
+class _WebSocketTransport:
+    def __init__(self, url, timeout):
+        try:
+            from websockets.sync.client import connect as ws_connect
+        except ImportError:
+            raise ImportError("WebSocket URLs need the websockets package: pip install pl3xus[websocket]") from None
+        self.ws = ws_connect(
+            url, open_timeout=timeout, max_size=None, subprotocols=[protocol.BINCODE_SUBPROTOCOL]
+        )
+
+    def send(self, frame):
+        self.ws.send(frame)
+
+    def packets(self):
+        try:
+            for message in self.ws:
+                # The server packs several packets into one message
+                pos = 0
+                while pos + 8 <= len(message):
+                    length = int.from_bytes(message[pos : pos + 8], "little")
+                    yield message[pos + 8 : pos + 8 + length]
+                    pos += 8 + length
+        except Exception:
+            return
+
+    def close(self):
+        self.ws.close()
+
+
+# ============================================================================
+# Client
+# ============================================================================
+
+
+class Subscription:
+    """A component subscription made with :meth:`Client.subscribe`."""
+
+    def __init__(self, client, component, filter, key, callback):
+        self._client = client
+        self.component = component
+        self.filter = filter
+        self._key = key
+        self._callback = callback
+
+    def entities(self):
+        """Current values by entity id."""
+        return self._client.entities(self.component, self.filter)
+
+    def unsubscribe(self):
+        self._client._unsubscribe(self)
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.unsubscribe()
+
+
+class _ServerSubscription:
+    def __init__(self, subscription_id, component, filter):
+        self.subscription_id = subscription_id
+        self.component = component
+        self.filter = filter
+        self.subscribers = 0
+
+
+class Client:
+    """Connection to a pl3xus_sync server. Create it with :func:`connect`.
+
+    Values are plain dicts/lists with the layout ``serde_json`` gives the
+    server's Rust types.
+    """
+
+    def __init__(self, transport, timeout=DEFAULT_TIMEOUT):
+        self.timeout = timeout
+        self.connection_id = None
+        # Random id of the server run; with connection_id, identifies this
+        # connection across server restarts
+        self.session = None
+        self._transport = transport
+        self._lock = threading.RLock()
+        self._send_lock = threading.Lock()
+        self._ids = itertools.count(1)
+        # (entity id, component name) -> bincode value
+        self._data = {}
+        self._server_subscriptions = {}
+        self._subscriptions = []
+        self._pending = {}
+        self._message_handlers = {}
+        self._components = {}
+        self._messages = {}
+        self._requests = {}
+        self._closed = False
+
+        self._reader = threading.Thread(target=self._read_loop, name="pl3xus-reader", daemon=True)
+        self._reader.start()
+        try:
+            self._load_schema()
+        except BaseException:
+            self.close()
+            raise
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
+
+    @property
+    def components(self):
+        """Names of the components the server syncs."""
+        return sorted(self._components)
+
+    @property
+    def requests(self):
+        """Names of the requests the server accepts."""
+        return sorted(self._requests)
+
+    def close(self):
+        with self._lock:
+            self._closed = True
+        self._transport.close()
+
+    # ------------------------------------------------------------------
+    # Subscriptions
+    # ------------------------------------------------------------------
+
+    def subscribe(self, component, callback=None, filter=None):
+        """Subscribe to ``component``, optionally filtered on the server
+        (see :mod:`pl3xus.filters`).
+
+        ``callback(entities)`` is called with every entity's value by entity
+        id whenever they change. Subscriptions for the same component and
+        filter share one server subscription.
+        """
+        self._component_shape(component)
+        key = component if filter is None else f"{component}?{json.dumps(filter, sort_keys=True)}"
+        subscription = Subscription(self, component, filter, key, callback)
+        packet = None
+        with self._lock:
+            shared = self._server_subscriptions.get(key)
+            if shared is None:
+                shared = _ServerSubscription(next(self._ids), component, filter)
+                self._server_subscriptions[key] = shared
+                request = {
+                    "subscription_id": shared.subscription_id,
+                    "component_type": component,
+                    "entity": None,
+                    "filter": filter,
+                }
+                packet = self._sync_packet("Subscription", "SubscriptionRequest", request)
+            shared.subscribers += 1
+            self._subscriptions.append(subscription)
+        if packet is not None:
+            self._send(packet)
+        return subscription
+
+    def _unsubscribe(self, subscription):
+        packet = None
+        with self._lock:
+            if subscription not in self._subscriptions:
+                return
+            self._subscriptions.remove(subscription)
+            shared = self._server_subscriptions[subscription._key]
+            shared.subscribers -= 1
+            if shared.subscribers == 0:
+                del self._server_subscriptions[subscription._key]
+                request = {"subscription_id": shared.subscription_id}
+                packet = self._sync_packet("Unsubscribe", "UnsubscribeRequest", request)
+        if packet is not None:
+            self._send(packet)
+
+    def entities(self, component, filter=None):
+        """Received values of ``component`` by entity id, optionally keeping
+        only those matching ``filter``. Values that fail to decode are
+        skipped."""
+        shape = self._component_shape(component)
+        with self._lock:
+            raw = [(entity, value) for (entity, name), value in self._data.items() if name == component]
+        entities = {}
+        for entity, value in raw:
+            try:
+                decoded = decode(shape, value)
+            except ValueError:
+                continue
+            if filter is None or matches(filter, decoded, entity):
+                entities[entity] = decoded
+        return entities
+
+    # ------------------------------------------------------------------
+    # Mutations, requests and messages
+    # ------------------------------------------------------------------
+
+    def mutate(self, entity, component, value, timeout=None):
+        """Set ``entity``'s ``component`` to ``value`` and wait for the server.
+
+        Returns the ``MutationResponse``; raises :class:`MutationError` if the
+        server rejected it.
+        """
+        request_id = next(self._ids)
+        mutation = {
+            "request_id": request_id,
+            "entity": {"bits": int(entity)},
+            "component_type": component,
+            "value": encode(self._component_shape(component), value),
+            "idempotency_key": random.getrandbits(64),
+        }
+        future = self._expect(request_id, None)
+        self._send(self._sync_packet("Mutate", "MutateComponent", mutation))
+        response = self._wait(request_id, future, timeout)
+        if response["status"] != "Ok":
+            raise MutationError(response)
+        return response
+
+    def request(self, name, payload=None, timeout=None):
+        """Send request ``name`` (usually the request's short type name) and
+        return the decoded response."""
+        if name not in self._requests:
+            raise Pl3xusError(f"the server has no request named {name}")
+        type_name, request_shape, response_shape = self._requests[name]
+        return self._request(type_name, request_shape, payload, lambda data: decode(response_shape, data), timeout)
+
+    def send(self, name, payload=None):
+        """Send a plain message by its short type name."""
+        if name not in self._messages:
+            raise Pl3xusError(f"the server has no message named {name}")
+        type_name, shape = self._messages[name]
+        self._send({"type_name": type_name, "schema_hash": 0, "data": encode(shape, payload)})
+
+    def on_message(self, name, callback):
+        """Call ``callback(message)`` for every message of type ``name``."""
+        with self._lock:
+            self._message_handlers.setdefault(name, []).append(callback)
+
+    def _request(self, type_name, shape, payload, decode_response, timeout):
+        request_id = next(self._ids)
+        # Same layout as `RequestInternal { id, request, idempotency_key: None }`
+        writer = Writer(_HOOKS)
+        writer.varint(request_id)
+        writer.value(shape, payload)
+        writer.out.append(0)
+        future = self._expect(request_id, decode_response)
+        self._send(
+            {
+                "type_name": protocol.REQUEST_TYPE_TEMPLATE.format(type_name),
+                "schema_hash": 0,
+                "data": bytes(writer.out),
+            }
+        )
+        return self._wait(request_id, future, timeout)
+
+    def _expect(self, request_id, decode_response):
+        future = futures.Future()
+        with self._lock:
+            if self._closed:
+                raise Pl3xusError("the connection is closed")
+            self._pending[request_id] = (decode_response, future)
+        return future
+
+    def _wait(self, request_id, future, timeout):
+        try:
+            return future.result(self.timeout if timeout is None else timeout)
+        except futures.TimeoutError:
+            with self._lock:
+                self._pending.pop(request_id, None)
+            raise Pl3xusError(f"no response within {self.timeout if timeout is None else timeout}s") from None
+
+    # ------------------------------------------------------------------
+    # Schema
+    # ------------------------------------------------------------------
+
+    def _load_schema(self):
+        description = self._request(
+            protocol.DESCRIBE_SCHEMA_TYPE,
+            "Unit",
+            None,
+            lambda data: decode(protocol.SHAPES["SchemaDescription"], data, {"TypeShape": _read_type_shape}),
+            self.timeout,
+        )
+        self._components = {schema["name"]: schema["shape"] for schema in description["components"]}
+        self._messages = {schema["name"]: (schema["type_name"], schema["shape"]) for schema in description["messages"]}
+        self._requests = {
+            schema["name"]: (schema["type_name"], schema["request"], schema["response"])
+            for schema in description["requests"]
+        }
+
+    def _component_shape(self, component):
+        if component not in self._components:
+            raise Pl3xusError(f"the server has no component named {component}")
+        return self._components[component]
+
+    # ------------------------------------------------------------------
+    # Wire
+    # ------------------------------------------------------------------
+
+    def _sync_packet(self, variant, shape, value):
+        writer = Writer(_HOOKS)
+        writer.varint(protocol.SYNC_CLIENT_MESSAGE_VARIANTS.index(variant))
+        writer.value(protocol.SHAPES[shape], value)
+        return {"type_name": protocol.SYNC_CLIENT_MESSAGE_TYPE, "schema_hash": 0, "data": bytes(writer.out)}
+
+    def _send(self, packet):
+        try:
+            with self._send_lock:
+                self._transport.send(_frame(packet))
+        except OSError as error:
+            raise Pl3xusError(f"send failed: {error}") from error
+
+    def _read_loop(self):
+        try:
+            for data in self._transport.packets():
+                try:
+                    self._handle_packet(decode(protocol.SHAPES["NetworkPacket"], data))
+                except ValueError:
+                    continue
+        finally:
+            with self._lock:
+                self._closed = True
+                pending = list(self._pending.values())
+                self._pending.clear()
+            for _, future in pending:
+                future.set_exception(Pl3xusError("the connection closed"))
+
+    def _handle_packet(self, packet):
+        type_name, data = packet["type_name"], bytes(packet["data"])
+        if type_name == protocol.SYNC_SERVER_MESSAGE_TYPE:
+            self._handle_server_message(Reader(data, {"SyncItem": _read_sync_item}))
+        elif protocol.RESPONSE_TYPE_MARKER in type_name:
+            reader = Reader(data)
+            request_id = reader.varint()
+            self._settle(request_id, data[reader.pos :])
+        else:
+            name = type_name.rsplit("::", 1)[-1]
+            with self._lock:
+                handlers = list(self._message_handlers.get(name, []))
+            if handlers and name in self._messages:
+                message = decode(self._messages[name][1], data)
+                for handler in handlers:
+                    handler(message)
+
+    def _settle(self, request_id, data):
+        with self._lock:
+            decode_response, future = self._pending.pop(request_id, (None, None))
+        if future is None:
+            return
+        try:
+            future.set_result(decode_response(data))
+        except ValueError as error:
+            future.set_exception(Pl3xusError(f"couldn't decode the response: {error}"))
+
+    def _handle_server_message(self, reader):
+        variant = protocol.SYNC_SERVER_MESSAGE_VARIANTS[reader.varint()]
+        if variant == "Welcome":
+            welcome = reader.value(protocol.SHAPES["WelcomeMessage"])
+            self.connection_id = welcome["connection_id"]["id"]
+            self.session = welcome["session"]
+        elif variant == "SyncBatch":
+            batch = reader.value(protocol.SHAPES["SyncBatch"])
+            self._apply_batch(batch["items"])
+        elif variant == "MutationResponse":
+            response = reader.value(protocol.SHAPES["MutationResponse"])
+            if response["request_id"] is not None:
+                with self._lock:
+                    _, future = self._pending.pop(response["request_id"], (None, None))
+                if future is not None:
+                    future.set_result(response)
+
+    def _apply_batch(self, items):
+        changed = set()
+        with self._lock:
+            for item in items:
+                kind, entity = item["kind"], item["entity"]
+                if kind in ("Snapshot", "Update"):
+                    self._data[(entity, item["component_type"])] = item["value"]
+                    changed.add(item["component_type"])
+                elif kind == "ComponentRemoved":
+                    if self._data.pop((entity, item["component_type"]), None) is not None:
+                        changed.add(item["component_type"])
+                elif kind == "EntityRemoved":
+                    for key in [key for key in self._data if key[0] == entity]:
+                        del self._data[key]
+                        changed.add(key[1])
+            notify = [s for s in self._subscriptions if s._callback is not None and s.component in changed]
+        for subscription in notify:
+            subscription._callback(subscription.entities())
+
+
+# ============================================================================
+# Enums with payloads, which the schema can't describe
+# ============================================================================
+
+
+def _read_sync_item(reader):
+    kind = protocol.SYNC_ITEM_VARIANTS[reader.varint()]
+    # Every variant starts with `subscription_id` and `entity: SerializableEntity { bits }`
+    item = {"kind": kind, "subscription_id": reader.varint(), "entity": reader.varint()}
+    if kind != "EntityRemoved":
+        item["component_type"] = reader.string()
+    if kind in ("Snapshot", "Update"):
+        item["value"] = reader.bytes()
+    return item
+
+
+def _read_type_shape(reader):
+    kind = protocol.TYPE_SHAPE_VARIANTS[reader.varint()]
+    if kind in ("Option", "Seq"):
+        return {kind: _read_type_shape(reader)}
+    if kind == "Tuple":
+        return {kind: [_read_type_shape(reader) for _ in range(reader.varint())]}
+    if kind == "Map":
+        key = _read_type_shape(reader)
+        return {kind: {"key": key, "value": _read_type_shape(reader)}}
+    if kind == "Struct":
+        name = reader.string()
+        fields = []
+        for _ in range(reader.varint()):
+            field = reader.string()
+            fields.append({"name": field, "shape": _read_type_shape(reader)})
+        return {kind: {"name": name, "fields": fields}}
+    if kind == "Newtype":
+        name = reader.string()
+        return {kind: {"name": name, "inner": _read_type_shape(reader)}}
+    if kind == "Enum":
+        name = reader.string()
+        return {kind: {"name": name, "variants": [reader.string() for _ in range(reader.varint())]}}
+    return kind
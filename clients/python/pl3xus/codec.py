@@ -0,0 +1,347 @@
+"""Shape-driven transcoding between JSON-style values and bincode.
+
+A pl3xus server describes every registered type with a ``TypeShape`` (its
+answer to ``DescribeSchema``). That is enough to turn the bincode bytes on the
+wire into the same dicts/lists ``serde_json`` would produce for the Rust type,
+and back. This is the Python twin of ``pl3xus_client_core::json_codec``.
+
+Shapes use the serde JSON form of ``TypeShape``: ``"U64"``,
+``{"Option": "String"}``, ``{"Struct": {"name": ..., "fields": [...]}}``, ...
+
+Enum variant payloads are not traced by the schema, so only unit variants are
+supported, except for the enums given explicit ``hooks``.
+"""
+
+import struct
+
+__all__ = ["CodecError", "Reader", "Writer", "decode", "encode"]
+
+
+class CodecError(ValueError):
+    """A value doesn't match its shape, or the bytes are malformed."""
+
+
+_INT_RANGES = {
+    "I8": (-(2**7), 2**7 - 1),
+    "I16": (-(2**15), 2**15 - 1),
+    "I32": (-(2**31), 2**31 - 1),
+    "I64": (-(2**63), 2**63 - 1),
+    "I128": (-(2**127), 2**127 - 1),
+    "U8": (0, 2**8 - 1),
+    "U16": (0, 2**16 - 1),
+    "U32": (0, 2**32 - 1),
+    "U64": (0, 2**64 - 1),
+    "U128": (0, 2**128 - 1),
+}
+
+
+def _kind(shape):
+    """Split a shape into its variant name and payload."""
+    if isinstance(shape, str):
+        return shape, None
+    ((kind, payload),) = shape.items()
+    return kind, payload
+
+
+def _is_tuple_struct(fields):
+    return bool(fields) and fields[0]["name"] == "0"
+
+
+class Writer:
+    """Appends bincode (standard config: varints, zigzag) to a buffer.
+
+    ``hooks`` maps enum names to ``fn(writer, value)`` for enums whose
+    variants carry payloads.
+    """
+
+    def __init__(self, hooks=None):
+        self.out = bytearray()
+        self.hooks = hooks or {}
+
+    def varint(self, n):
+        if n < 251:
+            self.out.append(n)
+        elif n < 2**16:
+            self.out.append(251)
+            self.out += n.to_bytes(2, "little")
+        elif n < 2**32:
+            self.out.append(252)
+            self.out += n.to_bytes(4, "little")
+        elif n < 2**64:
+            self.out.append(253)
+            self.out += n.to_bytes(8, "little")
+        else:
+            self.out.append(254)
+            self.out += n.to_bytes(16, "little")
+
+    def signed(self, n):
+        self.varint(2 * n if n >= 0 else -2 * n - 1)
+
+    def string(self, s):
+        data = s.encode("utf-8")
+        self.varint(len(data))
+        self.out += data
+
+    def bytes(self, data):
+        self.varint(len(data))
+        self.out += data
+
+    def value(self, shape, value):
+        kind, payload = _kind(shape)
+        if kind == "Bool":
+            if not isinstance(value, bool):
+                raise CodecError(f"expected a boolean, got {value!r}")
+            self.out.append(int(value))
+        elif kind in _INT_RANGES:
+            n = _integer(value, kind)
+            if kind in ("I8", "U8"):
+                self.out += n.to_bytes(1, "little", signed=kind == "I8")
+            elif kind.startswith("I"):
+                self.signed(n)
+            else:
+                self.varint(n)
+        elif kind == "F32":
+            self.out += struct.pack("<f", _float(value))
+        elif kind == "F64":
+            self.out += struct.pack("<d", _float(value))
+        elif kind == "Char":
+            if not isinstance(value, str) or len(value) != 1:
+                raise CodecError(f"expected a single character, got {value!r}")
+            self.out += value.encode("utf-8")
+        elif kind == "String":
+            if not isinstance(value, str):
+                raise CodecError(f"expected a string, got {value!r}")
+            self.string(value)
+        elif kind == "Bytes":
+            self.bytes(bytes(value))
+        elif kind == "Unit":
+            pass
+        elif kind == "Option":
+            if value is None:
+                self.out.append(0)
+            else:
+                self.out.append(1)
+                self.value(payload, value)
+        elif kind == "Seq":
+            if isinstance(value, (bytes, bytearray)) and payload == "U8":
+                self.bytes(value)
+                return
+            items = _sequence(value)
+            self.varint(len(items))
+            for item in items:
+                self.value(payload, item)
+        elif kind == "Tuple":
+            items = _sequence(value)
+            if len(items) != len(payload):
+                raise CodecError(f"expected {len(payload)} items, got {value!r}")
+            for item_shape, item in zip(payload, items):
+                self.value(item_shape, item)
+        elif kind == "Map":
+            pairs = value.items() if isinstance(value, dict) else value
+            pairs = list(pairs)
+            self.varint(len(pairs))
+            for key, item in pairs:
+                self.value(payload["key"], _map_key(payload["key"], key))
+                self.value(payload["value"], item)
+        elif kind == "Struct":
+            self._struct(payload, value)
+        elif kind == "Newtype":
+            self.value(payload["inner"], value)
+        elif kind == "Enum":
+            hook = self.hooks.get(payload["name"])
+            if hook is not None:
+                hook(self, value)
+                return
+            try:
+                index = payload["variants"].index(value)
+            except ValueError:
+                raise CodecError(f"{payload['name']} has no variant {value!r}") from None
+            self.varint(index)
+        else:
+            raise CodecError(f"values of untraced types can't be encoded ({kind})")
+
+    def _struct(self, payload, value):
+        name, fields = payload["name"], payload["fields"]
+        if not fields:
+            return
+        if _is_tuple_struct(fields) and isinstance(value, (list, tuple)):
+            if len(value) != len(fields):
+                raise CodecError(f"expected {len(fields)} items for {name}, got {value!r}")
+            for field, item in zip(fields, value):
+                self.value(field["shape"], item)
+            return
+        if not isinstance(value, dict):
+            raise CodecError(f"expected a {name} dict, got {value!r}")
+        for field in fields:
+            if field["name"] in value:
+                self.value(field["shape"], value[field["name"]])
+            elif _kind(field["shape"])[0] == "Option":
+                self.out.append(0)
+            else:
+                raise CodecError(f"{name} is missing field {field['name']}")
+
+
+class Reader:
+    """Reads bincode (standard config) from ``data``.
+
+    ``hooks`` maps enum names to ``fn(reader) -> value`` for enums whose
+    variants carry payloads.
+    """
+
+    def __init__(self, data, hooks=None):
+        self.data = memoryview(data)
+        self.pos = 0
+        self.hooks = hooks or {}
+
+    def take(self, n):
+        end = self.pos + n
+        if end > len(self.data):
+            raise CodecError("unexpected end of data")
+        chunk = self.data[self.pos : end]
+        self.pos = end
+        return bytes(chunk)
+
+    def byte(self):
+        return self.take(1)[0]
+
+    def varint(self):
+        tag = self.byte()
+        if tag < 251:
+            return tag
+        sizes = {251: 2, 252: 4, 253: 8, 254: 16}
+        if tag not in sizes:
+            raise CodecError(f"invalid integer tag {tag}")
+        return int.from_bytes(self.take(sizes[tag]), "little")
+
+    def signed(self):
+        n = self.varint()
+        return (n >> 1) ^ -(n & 1)
+
+    def string(self):
+        try:
+            return self.take(self.varint()).decode("utf-8")
+        except UnicodeDecodeError:
+            raise CodecError("invalid UTF-8 string") from None
+
+    def bytes(self):
+        return self.take(self.varint())
+
+    def value(self, shape):
+        kind, payload = _kind(shape)
+        if kind == "Bool":
+            byte = self.byte()
+            if byte > 1:
+                raise CodecError(f"invalid boolean {byte}")
+            return byte == 1
+        if kind == "I8":
+            return int.from_bytes(self.take(1), "little", signed=True)
+        if kind == "U8":
+            return self.byte()
+        if kind in _INT_RANGES:
+            return self.signed() if kind.startswith("I") else self.varint()
+        if kind == "F32":
+            return struct.unpack("<f", self.take(4))[0]
+        if kind == "F64":
+            return struct.unpack("<d", self.take(8))[0]
+        if kind == "Char":
+            first = self.data[self.pos] if self.pos < len(self.data) else 0
+            size = 1 if first < 0x80 else 2 if first < 0xE0 else 3 if first < 0xF0 else 4
+            return self.take(size).decode("utf-8")
+        if kind == "String":
+            return self.string()
+        if kind == "Bytes":
+            return list(self.bytes())
+        if kind == "Unit":
+            return None
+        if kind == "Option":
+            tag = self.byte()
+            if tag > 1:
+                raise CodecError(f"invalid option tag {tag}")
+            return self.value(payload) if tag else None
+        if kind == "Seq":
+            if payload == "U8":
+                return list(self.bytes())
+            return [self.value(payload) for _ in range(self.varint())]
+        if kind == "Tuple":
+            return [self.value(item) for item in payload]
+        if kind == "Map":
+            pairs = [(self.value(payload["key"]), self.value(payload["value"])) for _ in range(self.varint())]
+            if all(not isinstance(key, (list, dict)) and key is not None for key, _ in pairs):
+                return {key: item for key, item in pairs}
+            return [[key, item] for key, item in pairs]
+        if kind == "Struct":
+            fields = payload["fields"]
+            if not fields:
+                return None
+            if _is_tuple_struct(fields):
+                return [self.value(field["shape"]) for field in fields]
+            return {field["name"]: self.value(field["shape"]) for field in fields}
+        if kind == "Newtype":
+            return self.value(payload["inner"])
+        if kind == "Enum":
+            hook = self.hooks.get(payload["name"])
+            if hook is not None:
+                return hook(self)
+            index = self.varint()
+            if index >= len(payload["variants"]):
+                raise CodecError(f"{payload['name']} has no variant {index}")
+            return payload["variants"][index]
+        raise CodecError(f"values of untraced types can't be decoded ({kind})")
+
+
+def encode(shape, value, hooks=None):
+    """Encode ``value`` as the bincode bytes of a type with ``shape``."""
+    writer = Writer(hooks)
+    writer.value(shape, value)
+    return bytes(writer.out)
+
+
+def decode(shape, data, hooks=None):
+    """Decode the bincode bytes of a type with ``shape``."""
+    reader = Reader(data, hooks)
+    value = reader.value(shape)
+    if reader.pos != len(reader.data):
+        raise CodecError(f"{len(reader.data) - reader.pos} trailing bytes")
+    return value
+
+
+def _integer(value, kind):
+    if isinstance(value, bool):
+        raise CodecError(f"expected an integer, got {value!r}")
+    if isinstance(value, str):
+        try:
+            value = int(value)
+        except ValueError:
+            raise CodecError(f"expected an integer, got {value!r}") from None
+    if isinstance(value, float) and value.is_integer():
+        value = int(value)
+    if not isinstance(value, int):
+        raise CodecError(f"expected an integer, got {value!r}")
+    low, high = _INT_RANGES[kind]
+    if not low <= value <= high:
+        raise CodecError(f"{value} is out of range for {kind}")
+    return value
+
+
+def _float(value):
+    if isinstance(value, bool) or not isinstance(value, (int, float)):
+        raise CodecError(f"expected a number, got {value!r}")
+    return float(value)
+
+
+def _sequence(value):
+    if not isinstance(value, (list, tuple)):
+        raise CodecError(f"expected a list, got {value!r}")
+    return value
+
+
+def _map_key(shape, key):
+    """JSON object keys are strings; parse them back for non-string keys."""
+    kind, payload = _kind(shape)
+    if kind == "Newtype":
+        return _map_key(payload["inner"], key)
+    if isinstance(key, str) and kind in _INT_RANGES:
+        return int(key)
+    if isinstance(key, str) and kind == "Bool":
+        return key == "true"
+    return key
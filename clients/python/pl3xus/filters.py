@@ -0,0 +1,202 @@
+"""Server-side subscription filters.
+
+Filters are plain dicts in the serde JSON form of ``SubscriptionFilter``, so
+they can also be written by hand or loaded from config::
+
+    from pl3xus import filters
+
+    auto_and_fast = filters.all_of(filters.eq("mode", "Auto"), filters.gt("speed", 20))
+    # {"And": [{"Field": {"path": "mode", "op": "Eq", "value": {"String": "Auto"}}}, ...]}
+
+``path`` is a dot-separated field path into the component's JSON
+(``"joints.0.angle"``). The server only sends matching entities; the client
+re-checks locally with :func:`matches`.
+"""
+
+import struct
+
+from . import _protocol as protocol
+
+__all__ = [
+    "all_of",
+    "any_of",
+    "entities",
+    "eq",
+    "ge",
+    "gt",
+    "le",
+    "lt",
+    "matches",
+    "ne",
+    "negate",
+    "one_of",
+]
+
+
+def filter_value(value):
+    """The ``FilterValue`` form of a Python scalar."""
+    if value is None:
+        return "Null"
+    if isinstance(value, bool):
+        return {"Bool": value}
+    if isinstance(value, int):
+        return {"Int": value}
+    if isinstance(value, float):
+        return {"Float": value}
+    if isinstance(value, str):
+        return {"String": value}
+    raise TypeError(f"filters compare against None, bool, int, float or str, not {value!r}")
+
+
+def _field(path, op, value):
+    return {"Field": {"path": path, "op": op, "value": filter_value(value)}}
+
+
+def eq(path, value):
+    return _field(path, "Eq", value)
+
+
+def ne(path, value):
+    return _field(path, "Ne", value)
+
+
+def lt(path, value):
+    return _field(path, "Lt", value)
+
+
+def le(path, value):
+    return _field(path, "Le", value)
+
+
+def gt(path, value):
+    return _field(path, "Gt", value)
+
+
+def ge(path, value):
+    return _field(path, "Ge", value)
+
+
+def one_of(path, values):
+    """The field equals any of ``values``."""
+    return {"In": {"path": path, "values": [filter_value(value) for value in values]}}
+
+
+def entities(entity_ids):
+    """Only these entities."""
+    return {"Entities": [{"bits": int(entity_id)} for entity_id in entity_ids]}
+
+
+def all_of(*filters):
+    return {"And": list(filters)}
+
+
+def any_of(*filters):
+    return {"Or": list(filters)}
+
+
+def negate(filter):
+    return {"Not": filter}
+
+
+def _kind(value):
+    if isinstance(value, str):
+        return value, None
+    ((kind, payload),) = value.items()
+    return kind, payload
+
+
+def write_filter(writer, filter):
+    """Codec hook encoding a ``SubscriptionFilter``."""
+    kind, payload = _kind(filter)
+    writer.varint(protocol.SUBSCRIPTION_FILTER_VARIANTS.index(kind))
+    if kind == "Field":
+        writer.string(payload["path"])
+        writer.varint(protocol.FILTER_OP_VARIANTS.index(payload["op"]))
+        _write_value(writer, payload["value"])
+    elif kind == "In":
+        writer.string(payload["path"])
+        writer.varint(len(payload["values"]))
+        for value in payload["values"]:
+            _write_value(writer, value)
+    elif kind == "Entities":
+        writer.varint(len(payload))
+        for entity in payload:
+            writer.varint(entity["bits"])
+    elif kind in ("And", "Or"):
+        writer.varint(len(payload))
+        for inner in payload:
+            write_filter(writer, inner)
+    elif kind == "Not":
+        write_filter(writer, payload)
+
+
+def _write_value(writer, value):
+    kind, payload = _kind(value)
+    writer.varint(protocol.FILTER_VALUE_VARIANTS.index(kind))
+    if kind == "Bool":
+        writer.out.append(int(payload))
+    elif kind == "Int":
+        writer.signed(payload)
+    elif kind == "Float":
+        writer.out += struct.pack("<d", payload)
+    elif kind == "String":
+        writer.string(payload)
+
+
+def matches(filter, value, entity_id):
+    """Whether a decoded component ``value`` on ``entity_id`` passes ``filter``."""
+    kind, payload = _kind(filter)
+    if kind == "Field":
+        found, field = _lookup(value, payload["path"])
+        ordering = _compare(payload["value"], field) if found else None
+        if ordering is None:
+            return False
+        return {
+            "Eq": ordering == 0,
+            "Ne": ordering != 0,
+            "Lt": ordering < 0,
+            "Le": ordering <= 0,
+            "Gt": ordering > 0,
+            "Ge": ordering >= 0,
+        }[payload["op"]]
+    if kind == "In":
+        found, field = _lookup(value, payload["path"])
+        return found and any(_compare(expected, field) == 0 for expected in payload["values"])
+    if kind == "Entities":
+        return any(entity["bits"] == entity_id for entity in payload)
+    if kind == "And":
+        return all(matches(inner, value, entity_id) for inner in payload)
+    if kind == "Or":
+        return any(matches(inner, value, entity_id) for inner in payload)
+    if kind == "Not":
+        return not matches(payload, value, entity_id)
+    return False
+
+
+def _lookup(value, path):
+    if not path:
+        return True, value
+    for segment in path.split("."):
+        if isinstance(value, dict) and segment in value:
+            value = value[segment]
+        elif isinstance(value, list) and segment.isdigit() and int(segment) < len(value):
+            value = value[int(segment)]
+        else:
+            return False, None
+    return True, value
+
+
+def _compare(expected, actual):
+    """How ``actual`` orders against ``expected``, or None if incomparable."""
+    kind, payload = _kind(expected)
+    if kind == "Null":
+        return 0 if actual is None else None
+    if kind == "Bool":
+        return (actual > payload) - (actual < payload) if isinstance(actual, bool) else None
+    if kind in ("Int", "Float"):
+        if isinstance(actual, bool) or not isinstance(actual, (int, float)):
+            return None
+        return (actual > payload) - (actual < payload)
+    if kind == "String":
+        return (actual > payload) - (actual < payload) if isinstance(actual, str) else None
+    return None
@@ -0,0 +1,98 @@
+import unittest
+
+from pl3xus import _protocol as protocol
+from pl3xus import filters
+from pl3xus.client import _read_type_shape
+from pl3xus.codec import CodecError, Reader, Writer, decode, encode
+
+ROBOT = {
+    "Struct": {
+        "name": "Robot",
+        "fields": [
+            {"name": "name", "shape": "String"},
+            {"name": "mode", "shape": {"Enum": {"name": "Mode", "variants": ["Manual", "Auto"]}}},
+            {"name": "offset", "shape": "I32"},
+            {"name": "serial", "shape": "U64"},
+            {"name": "joints", "shape": {"Seq": "F32"}},
+            {"name": "tool", "shape": {"Option": "String"}},
+            {"name": "limits", "shape": {"Map": {"key": "U16", "value": "I64"}}},
+        ],
+    }
+}
+
+
+class CodecTest(unittest.TestCase):
+    def test_varints_follow_bincode_standard(self):
+        self.assertEqual(encode("U64", 250), bytes([250]))
+        self.assertEqual(encode("U64", 300), bytes([251, 44, 1]))
+        self.assertEqual(encode("U64", 2**32), bytes([253]) + (2**32).to_bytes(8, "little"))
+        self.assertEqual(encode("I32", -1), bytes([1]))
+        self.assertEqual(encode("I32", -70000), bytes([252]) + (139999).to_bytes(4, "little"))
+        self.assertEqual(encode("U8", 255), bytes([255]))
+        self.assertEqual(decode("I64", encode("I64", -(2**63))), -(2**63))
+
+    def test_struct_roundtrip(self):
+        robot = {
+            "name": "arm-1",
+            "mode": "Auto",
+            "offset": -70000,
+            "serial": 2**64 - 1,
+            "joints": [0.5, -90.0],
+            "tool": None,
+            "limits": {1: -5, 1000: 2**40},
+        }
+        data = encode(ROBOT, robot)
+        self.assertEqual(data[:7], bytes([5]) + b"arm-1" + bytes([1]))
+        self.assertEqual(decode(ROBOT, data), robot)
+
+        # Missing optional fields encode as None; JSON-style string keys are accepted
+        del robot["tool"]
+        robot["limits"] = {"1": -5, "1000": 2**40}
+        self.assertEqual(encode(ROBOT, robot), data)
+
+    def test_rejects_bad_values(self):
+        with self.assertRaisesRegex(CodecError, "no variant"):
+            encode(ROBOT["Struct"]["fields"][1]["shape"], "Teach")
+        with self.assertRaisesRegex(CodecError, "out of range"):
+            encode("U8", 256)
+        with self.assertRaisesRegex(CodecError, "missing field name"):
+            encode(ROBOT, {})
+        with self.assertRaisesRegex(CodecError, "trailing"):
+            decode("U8", bytes([1, 2]))
+
+    def test_filter_encoding(self):
+        writer = Writer({"SubscriptionFilter": filters.write_filter})
+        writer.value({"Option": {"Enum": {"name": "SubscriptionFilter", "variants": []}}}, filters.eq("mode", "Auto"))
+        # Some, Field, "mode", Eq, String, "Auto"
+        self.assertEqual(bytes(writer.out), bytes([1, 0, 4]) + b"mode" + bytes([0, 4, 4]) + b"Auto")
+
+    def test_filter_matches(self):
+        robot = {"mode": "Auto", "speed": 40, "joints": [{"angle": 1.5}], "fault": None}
+        self.assertTrue(filters.matches(filters.eq("mode", "Auto"), robot, 7))
+        self.assertTrue(filters.matches(filters.gt("speed", 20), robot, 7))
+        self.assertTrue(filters.matches(filters.le("speed", 40.0), robot, 7))
+        self.assertTrue(filters.matches(filters.lt("joints.0.angle", 2.0), robot, 7))
+        self.assertTrue(filters.matches(filters.eq("fault", None), robot, 7))
+        self.assertTrue(filters.matches(filters.one_of("mode", ["Manual", "Auto"]), robot, 7))
+        self.assertTrue(filters.matches(filters.entities([7]), robot, 7))
+        self.assertFalse(filters.matches(filters.eq("missing", 1), robot, 7))
+        self.assertFalse(filters.matches(filters.ne("mode", 3), robot, 7))
+        self.assertFalse(filters.matches(filters.negate(filters.eq("mode", "Auto")), robot, 7))
+
+    def test_read_type_shape(self):
+        writer = Writer()
+        variants = protocol.TYPE_SHAPE_VARIANTS
+        writer.varint(variants.index("Struct"))
+        writer.string("Position")
+        writer.varint(1)
+        writer.string("x")
+        writer.varint(variants.index("Option"))
+        writer.varint(variants.index("F32"))
+        shape = _read_type_shape(Reader(bytes(writer.out)))
+        self.assertEqual(
+            shape, {"Struct": {"name": "Position", "fields": [{"name": "x", "shape": {"Option": "F32"}}]}}
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -84,7 +84,7 @@ fn server_listen(
 fn server_handle_connections(mut events: MessageReader<NetworkEvent>) {
     for event in events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 info!("Client connected: {:?}", conn_id);
             }
             NetworkEvent::Disconnected(conn_id) => {
@@ -165,7 +165,7 @@ fn client_connect(
 fn client_handle_connections(mut events: MessageReader<NetworkEvent>) {
     for event in events.read() {
         match event {
-            NetworkEvent::Connected(_) => {
+            NetworkEvent::Connected(..) => {
                 info!("Connected to server!");
             }
             NetworkEvent::Disconnected(_) => {
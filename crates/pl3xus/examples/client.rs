@@ -101,7 +101,7 @@ fn handle_network_events(
     for event in new_network_events.read() {
         info!("Received event");
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 server_connection.connection_id = Some(*conn_id);
                 messages.add(SystemMessage::new(format!(
                     "Successfully connected to server! Connection ID: {}",
@@ -81,7 +81,7 @@ fn handle_connection_events(
     mut network_events: MessageReader<NetworkEvent>,
 ) {
     for event in network_events.read() {
-        if let NetworkEvent::Connected(conn_id) = event {
+        if let NetworkEvent::Connected(conn_id, _) = event {
             commands.spawn((Player(*conn_id),));
 
             // Broadcasting sends the message to all connected players! (Including the just connected one in this case)
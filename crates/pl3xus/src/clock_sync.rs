@@ -0,0 +1,268 @@
+//! NTP-style clock synchronization between clients and the server.
+//!
+//! The server side ([`ClockSyncServerPlugin`]) answers every [`ClockPing`]
+//! with a [`ClockPong`] carrying its wall-clock and monotonic time. Native
+//! clients add [`ClockSyncClientPlugin`], which pings the server periodically
+//! and keeps the estimated offset in the [`ServerClock`] resource so telemetry
+//! timestamps can be correlated with local events:
+//!
+//! ```rust,ignore
+//! // Server
+//! app.add_plugins(ClockSyncServerPlugin::<WebSocketProvider>::default());
+//!
+//! // Native client
+//! app.add_plugins(ClockSyncClientPlugin::<TcpProvider>::default());
+//!
+//! fn log_sample(clock: Res<ServerClock>, sample: Res<LatestSample>) {
+//!     if let Some(local_ms) = clock.to_local_ms(sample.server_time_ms) {
+//!         info!("sample taken {:.1} ms ago", ServerClock::local_now_ms() - local_ms);
+//!     }
+//! }
+//! ```
+
+use std::marker::PhantomData;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::managers::network::AppNetworkMessage;
+use crate::{Network, NetworkData, NetworkEvent, NetworkProvider};
+use pl3xus_common::{ClockEstimate, ClockPing, ClockPong, ClockSampler, ConnectionId};
+
+fn unix_time_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or_default()
+}
+
+/// Server start time, the epoch for [`ClockPong::server_monotonic_ms`].
+#[derive(Resource)]
+struct ServerClockEpoch(Instant);
+
+/// Plugin that answers [`ClockPing`]s from clients.
+///
+/// `Pl3xusSyncPlugin` adds it when it isn't already added, so sync servers
+/// don't need to add it themselves.
+pub struct ClockSyncServerPlugin<NP: NetworkProvider> {
+    _marker: PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for ClockSyncServerPlugin<NP> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for ClockSyncServerPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ServerClockEpoch(Instant::now()));
+        app.register_network_message::<ClockPing, NP>();
+        app.add_systems(PreUpdate, answer_clock_pings::<NP>);
+    }
+}
+
+fn answer_clock_pings<NP: NetworkProvider>(
+    mut pings: MessageReader<NetworkData<ClockPing>>,
+    epoch: Res<ServerClockEpoch>,
+    net: Res<Network<NP>>,
+) {
+    for ping in pings.read() {
+        let pong = ClockPong {
+            client_time_ms: ping.client_time_ms,
+            server_wall_ms: unix_time_ms(),
+            server_monotonic_ms: epoch.0.elapsed().as_secs_f64() * 1000.0,
+        };
+        if let Err(e) = net.send(*ping.source(), pong) {
+            debug!("Failed to answer clock ping from {}: {:?}", ping.source(), e);
+        }
+    }
+}
+
+/// Estimated server clock on a native client.
+///
+/// Empty until the first [`ClockPong`] arrives; reset when the connection to
+/// the server drops.
+#[derive(Resource, Debug, Default)]
+pub struct ServerClock {
+    sampler: ClockSampler,
+    server: Option<ConnectionId>,
+}
+
+impl ServerClock {
+    /// Local wall-clock time in milliseconds since the Unix epoch.
+    pub fn local_now_ms() -> f64 {
+        unix_time_ms()
+    }
+
+    /// The current estimate, if the clock has been synchronized.
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        self.sampler.estimate()
+    }
+
+    /// Whether at least one ping has been answered.
+    pub fn is_synced(&self) -> bool {
+        self.estimate().is_some()
+    }
+
+    /// `server_wall_time - local_wall_time`, in milliseconds.
+    pub fn offset_ms(&self) -> Option<f64> {
+        self.estimate().map(|e| e.offset_ms)
+    }
+
+    /// Round trip of the sample behind the current estimate, in milliseconds.
+    pub fn rtt_ms(&self) -> Option<f64> {
+        self.estimate().map(|e| e.rtt_ms)
+    }
+
+    /// Estimated server wall-clock time now (ms since the Unix epoch).
+    pub fn server_now_ms(&self) -> Option<f64> {
+        self.estimate().map(|e| e.to_server_ms(unix_time_ms()))
+    }
+
+    /// Estimated server monotonic time now (ms since the server started).
+    pub fn server_monotonic_now_ms(&self) -> Option<f64> {
+        self.estimate().map(|e| e.to_server_monotonic_ms(unix_time_ms()))
+    }
+
+    /// Convert a server wall-clock timestamp to local wall-clock time.
+    pub fn to_local_ms(&self, server_ms: f64) -> Option<f64> {
+        self.estimate().map(|e| e.to_local_ms(server_ms))
+    }
+}
+
+#[derive(Resource)]
+struct ClockSyncTimer(Timer);
+
+/// Plugin that keeps [`ServerClock`] synchronized on a native client.
+///
+/// A burst of pings is sent as soon as the connection is established, then
+/// one ping every `interval`.
+pub struct ClockSyncClientPlugin<NP: NetworkProvider> {
+    interval: Duration,
+    _marker: PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for ClockSyncClientPlugin<NP> {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> ClockSyncClientPlugin<NP> {
+    /// Number of pings sent right after connecting.
+    pub const INITIAL_BURST: usize = 4;
+
+    /// How often to ping the server once synchronized.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for ClockSyncClientPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerClock>();
+        app.insert_resource(ClockSyncTimer(Timer::new(self.interval, TimerMode::Repeating)));
+        app.register_network_message::<ClockPong, NP>();
+        app.add_systems(
+            PreUpdate,
+            (send_clock_pings::<NP>, receive_clock_pongs).chain(),
+        );
+    }
+}
+
+fn send_clock_pings<NP: NetworkProvider>(
+    time: Res<Time>,
+    mut timer: ResMut<ClockSyncTimer>,
+    mut events: MessageReader<NetworkEvent>,
+    mut clock: ResMut<ServerClock>,
+    net: Res<Network<NP>>,
+) {
+    let mut pings = 0;
+    for event in events.read() {
+        match event {
+            NetworkEvent::Connected(connection_id, _) => {
+                clock.sampler.reset();
+                clock.server = Some(*connection_id);
+                pings = ClockSyncClientPlugin::<NP>::INITIAL_BURST;
+            }
+            NetworkEvent::Disconnected(connection_id) if clock.server == Some(*connection_id) => {
+                clock.sampler.reset();
+                clock.server = None;
+            }
+            _ => {}
+        }
+    }
+
+    if timer.0.tick(time.delta()).just_finished() {
+        pings = pings.max(1);
+    }
+
+    let Some(server) = clock.server else {
+        return;
+    };
+    for _ in 0..pings {
+        let ping = ClockPing {
+            client_time_ms: unix_time_ms(),
+        };
+        if let Err(e) = net.send(server, ping) {
+            debug!("Failed to send clock ping to {}: {:?}", server, e);
+        }
+    }
+}
+
+fn receive_clock_pongs(mut pongs: MessageReader<NetworkData<ClockPong>>, mut clock: ResMut<ServerClock>) {
+    for pong in pongs.read() {
+        if clock.server != Some(*pong.source()) {
+            continue;
+        }
+        let estimate = clock.sampler.add_sample(pong, unix_time_ms());
+        trace!(
+            "Server clock offset {:.1} ms (rtt {:.1} ms)",
+            estimate.offset_ms,
+            estimate.rtt_ms
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_server_pongs_update_the_clock() {
+        let server = ConnectionId { id: 1 };
+        let mut app = App::new();
+        app.insert_resource(ServerClock {
+            sampler: ClockSampler::default(),
+            server: Some(server),
+        })
+        .add_message::<NetworkData<ClockPong>>()
+        .add_systems(Update, receive_clock_pongs);
+
+        let now = unix_time_ms();
+        let pong = |server_wall_ms| ClockPong {
+            client_time_ms: now,
+            server_wall_ms,
+            server_monotonic_ms: 0.0,
+        };
+        app.world_mut()
+            .write_message(NetworkData::new(&ConnectionId { id: 2 }, pong(now + 60_000.0)));
+        app.update();
+        assert!(!app.world().resource::<ServerClock>().is_synced());
+
+        app.world_mut().write_message(NetworkData::new(&server, pong(now + 1000.0)));
+        app.update();
+        let clock = app.world().resource::<ServerClock>();
+        let offset = clock.offset_ms().unwrap();
+        // Offset is measured from the midpoint of the round trip
+        assert!(offset <= 1000.0 && offset > 900.0, "{offset}");
+        assert!(clock.to_local_ms(now + 1000.0).is_some());
+    }
+}
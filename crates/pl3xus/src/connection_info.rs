@@ -0,0 +1,173 @@
+//! What providers know about each connection: the provider that accepted it,
+//! the endpoint it connected on, the peer address, the client address behind
+//! trusted proxies, and the negotiated subprotocol.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+use bevy::prelude::*;
+
+use crate::ConnectionId;
+
+/// Details a provider reports for a connection. Fields the provider doesn't
+/// know are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// The [`PROVIDER_NAME`](crate::NetworkProvider::PROVIDER_NAME) of the
+    /// provider that made the connection. Filled in by the [`Network`](crate::Network).
+    pub provider_name: Option<&'static str>,
+    /// The endpoint the connection was made on (e.g. the WebSocket path,
+    /// `/sync` or `/admin`).
+    pub endpoint: Option<String>,
+    /// The address of the socket's peer, which is the proxy when behind one.
+    pub peer_addr: Option<SocketAddr>,
+    /// The client's address. Taken from `Forwarded` or `X-Forwarded-For` when
+    /// the peer is a trusted proxy, otherwise the peer's address.
+    pub client_addr: Option<IpAddr>,
+    /// The subprotocol negotiated during the handshake (e.g.
+    /// [`BINCODE_SUBPROTOCOL`](crate::codec::BINCODE_SUBPROTOCOL)).
+    pub subprotocol: Option<String>,
+}
+
+impl ConnectionInfo {
+    /// Info for a connection from `peer_addr`, whose client is the peer.
+    pub fn from_peer(peer_addr: SocketAddr) -> Self {
+        Self {
+            peer_addr: Some(peer_addr),
+            client_addr: Some(peer_addr.ip()),
+            ..Default::default()
+        }
+    }
+}
+
+/// The client address for a connection from `peer`, given the addresses
+/// listed by proxies (see [`forwarded_addrs`]), nearest proxy last.
+///
+/// Addresses are only believed while they were added by `trusted_proxies`:
+/// walking back from `peer`, the first address that isn't a trusted proxy is
+/// the client.
+pub fn forwarded_client(peer: IpAddr, trusted_proxies: &[IpAddr], forwarded: &[IpAddr]) -> IpAddr {
+    let mut client = peer;
+    for addr in forwarded.iter().rev() {
+        if !trusted_proxies.contains(&client) {
+            break;
+        }
+        client = *addr;
+    }
+    client
+}
+
+/// The client addresses listed by a `Forwarded` header (its `for=` values),
+/// or if there is none, an `X-Forwarded-For` header. Obfuscated and unknown
+/// addresses are skipped.
+pub fn forwarded_addrs(forwarded: Option<&str>, x_forwarded_for: Option<&str>) -> Vec<IpAddr> {
+    match (forwarded, x_forwarded_for) {
+        (Some(forwarded), _) => forwarded
+            .split(',')
+            .flat_map(|element| element.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .filter(|(key, _)| key.eq_ignore_ascii_case("for"))
+            .filter_map(|(_, value)| parse_addr(value))
+            .collect(),
+        (None, Some(x_forwarded_for)) => x_forwarded_for.split(',').filter_map(parse_addr).collect(),
+        (None, None) => Vec::new(),
+    }
+}
+
+/// Parse `192.0.2.1`, `192.0.2.1:4711`, `2001:db8::1`, `[2001:db8::1]:4711`,
+/// optionally quoted.
+fn parse_addr(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+    value
+        .parse::<IpAddr>()
+        .ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| value.strip_prefix('[')?.strip_suffix(']')?.parse().ok())
+}
+
+/// The [`ConnectionInfo`] of every connection.
+///
+/// Kept up to date by the [`Pl3xusPlugin`](crate::Pl3xusPlugin), so that
+/// systems and authorization policies can treat connections differently per
+/// endpoint or address without knowing the provider.
+#[derive(Resource, Default, Debug)]
+pub struct ConnectionInfos {
+    infos: HashMap<ConnectionId, ConnectionInfo>,
+}
+
+impl ConnectionInfos {
+    /// Everything known about `connection_id`.
+    pub fn get(&self, connection_id: ConnectionId) -> Option<&ConnectionInfo> {
+        self.infos.get(&connection_id)
+    }
+
+    /// The endpoint `connection_id` connected on.
+    pub fn endpoint(&self, connection_id: ConnectionId) -> Option<&str> {
+        self.get(connection_id)?.endpoint.as_deref()
+    }
+
+    /// The name of the provider `connection_id` was made with.
+    pub fn provider_name(&self, connection_id: ConnectionId) -> Option<&'static str> {
+        self.get(connection_id)?.provider_name
+    }
+
+    /// The client address of `connection_id`.
+    pub fn client_addr(&self, connection_id: ConnectionId) -> Option<IpAddr> {
+        self.get(connection_id)?.client_addr
+    }
+
+    /// Record `info` for `connection_id`.
+    pub fn insert(&mut self, connection_id: ConnectionId, info: ConnectionInfo) {
+        self.infos.insert(connection_id, info);
+    }
+
+    /// Tag `connection_id` with `endpoint`, for connections whose provider
+    /// doesn't report one.
+    pub fn set_endpoint(&mut self, connection_id: ConnectionId, endpoint: impl Into<String>) {
+        self.infos.entry(connection_id).or_default().endpoint = Some(endpoint.into());
+    }
+
+    pub(crate) fn remove(&mut self, connection_id: ConnectionId) {
+        self.infos.remove(&connection_id);
+    }
+
+    /// Connections on `endpoint`.
+    pub fn connections_on<'a>(&'a self, endpoint: &'a str) -> impl Iterator<Item = ConnectionId> + 'a {
+        self.infos
+            .iter()
+            .filter(move |(_, info)| info.endpoint.as_deref() == Some(endpoint))
+            .map(|(connection_id, _)| *connection_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap_or(IpAddr::from([0, 0, 0, 0]))
+    }
+
+    #[test]
+    fn test_forwarded_client() {
+        let forwarded = forwarded_addrs(
+            Some(r#"for=192.0.2.60;proto=https, For="[2001:db8::1]:4711", for=_hidden, for=10.0.0.2"#),
+            Some("198.51.100.1"),
+        );
+        assert_eq!(forwarded, vec![ip("192.0.2.60"), ip("2001:db8::1"), ip("10.0.0.2")]);
+        assert_eq!(
+            forwarded_addrs(None, Some("203.0.113.7, 10.0.0.2:8080")),
+            vec![ip("203.0.113.7"), ip("10.0.0.2")]
+        );
+
+        let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+        let chain = [ip("203.0.113.7"), ip("10.0.0.2")];
+        // Through both proxies
+        assert_eq!(forwarded_client(ip("10.0.0.1"), &proxies, &chain), ip("203.0.113.7"));
+        // A client can't spoof its address by sending the header itself
+        assert_eq!(forwarded_client(ip("198.51.100.1"), &proxies, &chain), ip("198.51.100.1"));
+        // A forged entry before an untrusted hop is ignored
+        let chain = [ip("192.0.2.1"), ip("203.0.113.7")];
+        assert_eq!(forwarded_client(ip("10.0.0.1"), &proxies, &chain), ip("203.0.113.7"));
+    }
+}
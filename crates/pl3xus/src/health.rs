@@ -0,0 +1,304 @@
+//! Health and readiness reports for HTTP probes.
+//!
+//! [`HealthPlugin`] keeps a [`HealthState`] up to date with the connection
+//! count and the result of every check registered with
+//! [`AppHealthExt::health_check`]. Providers that can answer plain HTTP on
+//! their listener (such as `pl3xus_websockets`, when given the state in its
+//! settings) serve it there, and with the `tcp` feature it can also be served
+//! on a separate port with [`serve_health`].
+//!
+//! - `GET /healthz` answers 200 while the app keeps updating the report, and
+//!   503 once it stalls.
+//! - `GET /readyz` answers 200 when every check passes, and 503 otherwise.
+//!
+//! Both return the report as JSON:
+//!
+//! ```json
+//! {"status":"ok","ready":true,"connections":2,"checks":{"database":{"ok":true,"detail":null}}}
+//! ```
+//!
+//! ```rust,ignore
+//! app.add_plugins(HealthPlugin::<WebSocketProvider>::default())
+//!     .health_check("database", |world| match world.get_resource::<Database>() {
+//!         Some(_) => HealthCheck::ok(),
+//!         None => HealthCheck::failed("not open"),
+//!     });
+//! ```
+
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{Network, NetworkProvider};
+
+/// How often [`HealthPlugin`] refreshes the report.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `/healthz` fails once the report is older than this.
+const STALE_AFTER: Duration = Duration::from_secs(5);
+
+/// The result of one health check.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HealthCheck {
+    /// Whether the check passed.
+    pub ok: bool,
+    /// What was checked or why it failed.
+    pub detail: Option<String>,
+}
+
+impl HealthCheck {
+    /// Passing, without a detail.
+    pub fn ok() -> Self {
+        Self { ok: true, detail: None }
+    }
+
+    /// Passing, with a detail such as `"2/2 connected"`.
+    pub fn ok_with(detail: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// Failing because of `detail`.
+    pub fn failed(detail: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// The report served to probes.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct HealthReport {
+    /// Whether every check passes.
+    pub ready: bool,
+    /// Connected clients.
+    pub connections: usize,
+    /// Registered checks, by name.
+    pub checks: BTreeMap<String, HealthCheck>,
+    #[serde(skip)]
+    updated_at: Option<Instant>,
+}
+
+/// Shared handle to the latest [`HealthReport`], readable from network tasks.
+#[derive(Resource, Clone, Default)]
+pub struct HealthState(Arc<RwLock<HealthReport>>);
+
+impl std::fmt::Debug for HealthState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HealthState")
+    }
+}
+
+impl HealthState {
+    /// A copy of the latest report.
+    pub fn report(&self) -> HealthReport {
+        self.0.read().map(|report| report.clone()).unwrap_or_default()
+    }
+
+    fn set(&self, report: HealthReport) {
+        if let Ok(mut current) = self.0.write() {
+            *current = report;
+        }
+    }
+
+    /// Whether the app updated the report recently.
+    pub fn is_live(&self) -> bool {
+        self.report()
+            .updated_at
+            .is_some_and(|updated_at| updated_at.elapsed() < STALE_AFTER)
+    }
+
+    /// The full HTTP response to `request` (the raw request head), if it is a
+    /// plain `GET` or `HEAD` of `/healthz` or `/readyz`. WebSocket upgrades
+    /// and other paths return `None`.
+    pub fn http_response(&self, request: &[u8]) -> Option<Vec<u8>> {
+        let head = String::from_utf8_lossy(request);
+        let mut request_line = head.lines().next()?.split_whitespace();
+        let method = request_line.next()?;
+        let path = request_line.next()?.split('?').next()?;
+        if !matches!(method, "GET" | "HEAD") || head.to_ascii_lowercase().contains("upgrade: websocket") {
+            return None;
+        }
+
+        let report = self.report();
+        let live = self.is_live();
+        let healthy = match path {
+            "/healthz" => live,
+            "/readyz" => live && report.ready,
+            _ => return None,
+        };
+        let body = serde_json::json!({
+            "status": if !live { "stalled" } else if report.ready { "ok" } else { "degraded" },
+            "ready": report.ready,
+            "connections": report.connections,
+            "checks": report.checks,
+        })
+        .to_string();
+        let status = if healthy { "200 OK" } else { "503 Service Unavailable" };
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            body.len()
+        );
+        if method == "GET" {
+            response.push_str(&body);
+        }
+        Some(response.into_bytes())
+    }
+}
+
+type HealthCheckFn = Box<dyn Fn(&World) -> HealthCheck + Send + Sync>;
+
+/// Checks run for the report, by name.
+#[derive(Resource, Default)]
+pub struct HealthChecks {
+    checks: BTreeMap<String, HealthCheckFn>,
+}
+
+/// Keeps [`HealthState`] up to date.
+pub struct HealthPlugin<NP: NetworkProvider>(PhantomData<NP>);
+
+impl<NP: NetworkProvider> Default for HealthPlugin<NP> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for HealthPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HealthState>()
+            .init_resource::<HealthChecks>()
+            .add_systems(Last, update_health::<NP>);
+    }
+}
+
+/// Extension trait for registering health checks.
+pub trait AppHealthExt {
+    /// Run `check` for every report. `/readyz` fails while any check fails.
+    fn health_check(
+        &mut self,
+        name: impl Into<String>,
+        check: impl Fn(&World) -> HealthCheck + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl AppHealthExt for App {
+    fn health_check(
+        &mut self,
+        name: impl Into<String>,
+        check: impl Fn(&World) -> HealthCheck + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(HealthChecks::default)
+            .checks
+            .insert(name.into(), Box::new(check));
+        self
+    }
+}
+
+fn update_health<NP: NetworkProvider>(world: &mut World, mut last_update: Local<Option<Instant>>) {
+    if last_update.is_some_and(|last| last.elapsed() < UPDATE_INTERVAL) {
+        return;
+    }
+    *last_update = Some(Instant::now());
+
+    let checks: BTreeMap<String, HealthCheck> = world
+        .get_resource::<HealthChecks>()
+        .map(|checks| {
+            checks
+                .checks
+                .iter()
+                .map(|(name, check)| (name.clone(), check(world)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let report = HealthReport {
+        ready: checks.values().all(|check| check.ok),
+        connections: world
+            .get_resource::<Network<NP>>()
+            .map(|net| net.connection_count())
+            .unwrap_or_default(),
+        checks,
+        updated_at: Some(Instant::now()),
+    };
+    world.resource::<HealthState>().set(report);
+}
+
+/// Serve `/healthz` and `/readyz` from `state` on a separate port.
+#[cfg(feature = "tcp")]
+pub fn serve_health<RT: crate::Runtime>(
+    addr: std::net::SocketAddr,
+    state: HealthState,
+    runtime: &RT,
+) -> RT::JoinHandle {
+    use futures_lite::{AsyncReadExt, AsyncWriteExt};
+
+    crate::runtime::run_async(
+        async move {
+            let listener = match async_net::TcpListener::bind(addr).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Could not serve health checks on {}: {}", addr, e);
+                    return;
+                }
+            };
+            info!("Serving health checks on http://{}", addr);
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let Ok(read) = stream.read(&mut request).await else {
+                    continue;
+                };
+                let response = state.http_response(&request[..read]).unwrap_or_else(|| {
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                });
+                let _ = stream.write_all(&response).await;
+            }
+        },
+        runtime,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_responses() {
+        let state = HealthState::default();
+        let probe = |path: &str| {
+            let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+            state
+                .http_response(request.as_bytes())
+                .map(|response| String::from_utf8_lossy(&response).into_owned())
+        };
+        // Never updated
+        assert!(probe("/healthz").is_some_and(|response| response.starts_with("HTTP/1.1 503")));
+
+        let mut checks = BTreeMap::new();
+        checks.insert("database".to_string(), HealthCheck::failed("locked"));
+        state.set(HealthReport {
+            ready: false,
+            connections: 2,
+            checks,
+            updated_at: Some(Instant::now()),
+        });
+        assert!(probe("/healthz").is_some_and(|response| response.starts_with("HTTP/1.1 200")));
+        let ready = probe("/readyz?verbose=1").unwrap_or_default();
+        assert!(ready.starts_with("HTTP/1.1 503"));
+        assert!(ready.ends_with(
+            r#"{"checks":{"database":{"detail":"locked","ok":false}},"connections":2,"ready":false,"status":"degraded"}"#
+        ));
+
+        assert_eq!(probe("/sync"), None);
+        let upgrade = b"GET /healthz HTTP/1.1\r\nUpgrade: websocket\r\n\r\n";
+        assert_eq!(state.http_response(upgrade), None);
+    }
+}
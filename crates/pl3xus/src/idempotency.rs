@@ -0,0 +1,323 @@
+//! Server-side deduplication of retried requests by idempotency key.
+//!
+//! A client that times out waiting for a response cannot tell whether the
+//! server never got the request or only the response was lost. Retrying is
+//! only safe if the server recognizes the retry, so clients attach an
+//! idempotency key that stays the same across retries of one logical
+//! operation. [`IdempotencyCache`] remembers keys for a TTL:
+//!
+//! - the first request with a key is handled normally;
+//! - a retry while the first is still being handled waits for its outcome;
+//! - a retry after the outcome is known gets the cached outcome replayed,
+//!   without running the handler again.
+//!
+//! Keys are not scoped to a connection, so a client that reconnects and
+//! retries on its new connection is still recognized. Request keys are scoped
+//! to the request type and carry the client's random id as well as the
+//! operation's ([`IdempotencyKey`]), both drawn from a CSPRNG, so one client
+//! can't guess its way into another's cached responses. A key belongs to the
+//! connection that first sent it, and another connection can only take it
+//! over once that one has disconnected.
+//!
+//! Requests registered with `listen_for_request_message` use this
+//! automatically through the [`RequestIdempotency`] resource.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_channel::Sender;
+use bevy::prelude::Resource;
+use pl3xus_common::{ConnectionId, IdempotencyKey, NetworkPacket};
+
+/// Result of [`IdempotencyCache::begin`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyCheck<V> {
+    /// First time this key is seen: handle the request and call
+    /// [`IdempotencyCache::complete`] with the outcome.
+    New,
+    /// The original request is still being handled. The retry's request id
+    /// was recorded and will be returned by `complete`.
+    InFlight,
+    /// The original request already completed with this outcome.
+    Completed(V),
+    /// The key belongs to another connection that is still connected. The
+    /// request should be dropped; if it was a retry from a reconnected client,
+    /// a later retry succeeds once the old connection is gone.
+    Conflict,
+}
+
+#[derive(Debug)]
+enum EntryState<V, W> {
+    InFlight { waiting: Vec<W> },
+    Completed(V),
+}
+
+#[derive(Debug)]
+struct Entry<V, W> {
+    created: Instant,
+    owner: ConnectionId,
+    state: EntryState<V, W>,
+}
+
+/// Outcomes of recently handled requests, keyed by idempotency key.
+///
+/// `W` is what a retry that arrives while the original is in flight leaves
+/// behind to be answered later, e.g. its connection and request id. `K` is
+/// the key, including whatever scopes it (such as the request type).
+#[derive(Debug)]
+pub struct IdempotencyCache<V, W = (ConnectionId, u64), K = u64> {
+    ttl: Duration,
+    entries: HashMap<K, Entry<V, W>>,
+}
+
+impl<V, W, K> Default for IdempotencyCache<V, W, K> {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TTL)
+    }
+}
+
+impl<V, W, K> IdempotencyCache<V, W, K> {
+    /// How long keys are remembered by default.
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+    /// Create a cache that remembers keys for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// How long keys are remembered.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Drop entries older than the TTL.
+    pub fn prune(&mut self) {
+        let ttl = self.ttl;
+        self.entries.retain(|_, entry| entry.created.elapsed() < ttl);
+    }
+
+    /// Number of remembered keys.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<V: Clone, W, K: Eq + Hash> IdempotencyCache<V, W, K> {
+    /// Record an incoming request from `source` with an idempotency key.
+    ///
+    /// `waiter` is how to answer this attempt, if it can be answered; retries
+    /// that arrive while the original is in flight are returned by
+    /// [`complete`](Self::complete). `is_connected` tells whether the key's
+    /// current owner is still connected, in which case another connection
+    /// gets [`IdempotencyCheck::Conflict`] instead of taking the key over.
+    pub fn begin(
+        &mut self,
+        source: ConnectionId,
+        key: K,
+        waiter: Option<W>,
+        is_connected: impl Fn(ConnectionId) -> bool,
+    ) -> IdempotencyCheck<V> {
+        self.prune();
+
+        let Some(entry) = self.entries.get_mut(&key) else {
+            self.entries.insert(
+                key,
+                Entry {
+                    created: Instant::now(),
+                    owner: source,
+                    state: EntryState::InFlight { waiting: Vec::new() },
+                },
+            );
+            return IdempotencyCheck::New;
+        };
+
+        if entry.owner != source {
+            if is_connected(entry.owner) {
+                return IdempotencyCheck::Conflict;
+            }
+            entry.owner = source;
+        }
+        match &mut entry.state {
+            EntryState::InFlight { waiting } => {
+                waiting.extend(waiter);
+                IdempotencyCheck::InFlight
+            }
+            EntryState::Completed(outcome) => IdempotencyCheck::Completed(outcome.clone()),
+        }
+    }
+
+    /// Store the outcome of a request started with [`begin`](Self::begin).
+    ///
+    /// Returns the retries that arrived in the meantime and should receive
+    /// the same outcome.
+    pub fn complete(&mut self, source: ConnectionId, key: K, outcome: V) -> Vec<W> {
+        let entry = self.entries.entry(key).or_insert_with(|| Entry {
+            created: Instant::now(),
+            owner: source,
+            state: EntryState::InFlight { waiting: Vec::new() },
+        });
+        match std::mem::replace(&mut entry.state, EntryState::Completed(outcome)) {
+            EntryState::InFlight { waiting } => waiting,
+            EntryState::Completed(_) => Vec::new(),
+        }
+    }
+}
+
+/// A response as it was sent, minus the request id, so it can be replayed
+/// to a retry with a different request id.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub(crate) type_name: String,
+    pub(crate) schema_hash: u64,
+    /// Encoded response body, following the encoded request id.
+    pub(crate) body: Vec<u8>,
+}
+
+impl CachedResponse {
+    /// Build the response packet for `request_id`.
+    pub(crate) fn to_packet(&self, request_id: u64) -> NetworkPacket {
+        let mut data = bincode::serde::encode_to_vec(request_id, bincode::config::standard())
+            .unwrap_or_default();
+        data.extend_from_slice(&self.body);
+        NetworkPacket {
+            type_name: self.type_name.clone(),
+            schema_hash: self.schema_hash,
+            data,
+        }
+    }
+}
+
+/// Key of a request in [`RequestIdempotency`]: its type name and idempotency key.
+pub(crate) type RequestKey = (&'static str, IdempotencyKey);
+
+/// Idempotency cache shared by all request types.
+///
+/// Inserted automatically by `listen_for_request_message`. Insert your own
+/// before registering requests to change the TTL:
+///
+/// ```rust,ignore
+/// app.insert_resource(RequestIdempotency::new(Duration::from_secs(60)));
+/// ```
+///
+/// Entries are scoped to the request type. Retries waiting for an in-flight
+/// request are remembered by request id and the sender of the connection they
+/// arrived on.
+#[derive(Resource, Debug, Clone, Default)]
+#[allow(clippy::type_complexity)]
+pub struct RequestIdempotency(
+    pub(crate) Arc<Mutex<IdempotencyCache<CachedResponse, (u64, Sender<NetworkPacket>), RequestKey>>>,
+);
+
+impl RequestIdempotency {
+    /// Create a cache that remembers keys for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self(Arc::new(Mutex::new(IdempotencyCache::new(ttl))))
+    }
+
+    /// Number of remembered keys.
+    pub fn len(&self) -> usize {
+        self.0.lock().map(|cache| cache.len()).unwrap_or_default()
+    }
+
+    /// Whether no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST: ConnectionId = ConnectionId { id: 1 };
+    const SECOND: ConnectionId = ConnectionId { id: 2 };
+
+    fn connected(id: ConnectionId) -> impl Fn(ConnectionId) -> bool {
+        move |owner| owner == id
+    }
+
+    #[test]
+    fn test_retries_wait_for_and_replay_the_outcome() {
+        let mut cache = IdempotencyCache::<&str>::default();
+
+        assert_eq!(cache.begin(FIRST, 7, Some((FIRST, 1)), connected(FIRST)), IdempotencyCheck::New);
+        assert_eq!(cache.begin(FIRST, 7, Some((FIRST, 2)), connected(FIRST)), IdempotencyCheck::InFlight);
+
+        // The retry that arrived in flight gets the outcome too
+        assert_eq!(cache.complete(FIRST, 7, "created"), vec![(FIRST, 2)]);
+        assert_eq!(
+            cache.begin(FIRST, 7, Some((FIRST, 3)), connected(FIRST)),
+            IdempotencyCheck::Completed("created")
+        );
+
+        // Other keys are independent
+        assert_eq!(cache.begin(FIRST, 8, None, connected(FIRST)), IdempotencyCheck::New);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_retry_after_reconnect_is_recognized() {
+        let mut cache = IdempotencyCache::<&str>::default();
+        assert_eq!(cache.begin(FIRST, 7, Some((FIRST, 1)), connected(FIRST)), IdempotencyCheck::New);
+
+        // The old connection dropped before the outcome was known
+        assert_eq!(
+            cache.begin(SECOND, 7, Some((SECOND, 1)), connected(SECOND)),
+            IdempotencyCheck::InFlight
+        );
+        assert_eq!(cache.complete(FIRST, 7, "created"), vec![(SECOND, 1)]);
+        assert_eq!(
+            cache.begin(SECOND, 7, Some((SECOND, 2)), connected(SECOND)),
+            IdempotencyCheck::Completed("created")
+        );
+    }
+
+    #[test]
+    fn test_key_of_a_live_connection_is_not_shared() {
+        let mut cache = IdempotencyCache::<&str>::default();
+        let both = |owner: ConnectionId| owner == FIRST || owner == SECOND;
+        assert_eq!(cache.begin(FIRST, 7, Some((FIRST, 1)), both), IdempotencyCheck::New);
+        cache.complete(FIRST, 7, "created");
+
+        assert_eq!(cache.begin(SECOND, 7, Some((SECOND, 1)), both), IdempotencyCheck::Conflict);
+        assert_eq!(
+            cache.begin(FIRST, 7, Some((FIRST, 2)), both),
+            IdempotencyCheck::Completed("created")
+        );
+    }
+
+    #[test]
+    fn test_keys_expire_after_ttl() {
+        let mut cache = IdempotencyCache::<&str>::new(Duration::ZERO);
+        assert_eq!(cache.begin(FIRST, 7, None, connected(FIRST)), IdempotencyCheck::New);
+        cache.complete(FIRST, 7, "created");
+
+        assert_eq!(cache.begin(FIRST, 7, None, connected(FIRST)), IdempotencyCheck::New);
+    }
+
+    #[test]
+    fn test_request_keys_are_scoped_by_type_and_client() {
+        let mut cache = IdempotencyCache::<&str, (ConnectionId, u64), RequestKey>::default();
+        let key = |client| IdempotencyKey { client, key: 7 };
+        assert_eq!(cache.begin(FIRST, ("Create", key(1)), None, connected(FIRST)), IdempotencyCheck::New);
+        cache.complete(FIRST, ("Create", key(1)), "created");
+
+        // The same key from another client, or for another request type, is a new request
+        assert_eq!(cache.begin(SECOND, ("Create", key(2)), None, connected(SECOND)), IdempotencyCheck::New);
+        assert_eq!(cache.begin(FIRST, ("Delete", key(1)), None, connected(FIRST)), IdempotencyCheck::New);
+        assert_eq!(
+            cache.begin(FIRST, ("Create", key(1)), None, connected(FIRST)),
+            IdempotencyCheck::Completed("created")
+        );
+    }
+}
@@ -61,7 +61,7 @@ fn handle_world_updates(
 fn handle_connection_events(mut network_events: MessageReader<NetworkEvent>,) {
     for event in network_events.read() {
         match event {
-            &NetworkEvent::Connected(_) => info!("Connected to server!"),
+            &NetworkEvent::Connected(..) => info!("Connected to server!"),
             _ => (),
         }
     }
@@ -119,7 +119,7 @@ fn handle_connection_events(
 ) {
     for event in network_events.read() {
         match event {
-            &NetworkEvent::Connected(conn_id) => {
+            &NetworkEvent::Connected(conn_id, _) => {
                 net.send(conn_id, PlayerUpdate);
                 info!("New client connected: {:?}", conn_id);
             }
@@ -139,9 +139,9 @@ Currently, Bevy's [TaskPool](bevy::tasks::TaskPool) is the default runtime used
 // mod network_message;
 /// Contains all functionality for starting a server or client, sending, and recieving messages from clients.
 pub mod managers;
-pub use managers::{Network, network::AppNetworkMessage};
+pub use managers::{Network, network::{AppNetworkMessage, DuplicateRegistration}};
 pub use managers::registration::{register_message, register_message_unscheduled};
-pub use managers::network_request::DeferredResponder;
+pub use managers::network_request::{DeferredResponder, LocalResponse, UnfilteredRequest};
 mod runtime;
 use managers::NetworkProvider;
 pub use runtime::Pl3xusRuntime;
@@ -166,6 +166,37 @@ use std::ops::Deref;
 /// A default tcp provider to help get you started.
 pub mod tcp;
 
+/// Per-connection tracing spans, runtime per-message log level, and traffic metrics.
+pub mod network_tracing;
+pub use network_tracing::{NetworkMetrics, NetworkTracingPlugin};
+
+/// Server-side deduplication of retried requests by idempotency key.
+pub mod idempotency;
+pub use idempotency::{IdempotencyCache, IdempotencyCheck, RequestIdempotency};
+
+/// NTP-style clock synchronization between clients and the server.
+pub mod clock_sync;
+pub use clock_sync::{ClockSyncClientPlugin, ClockSyncServerPlugin, ServerClock};
+
+/// Endpoint, addresses and subprotocol of each connection.
+pub mod connection_info;
+pub use connection_info::{ConnectionInfo, ConnectionInfos};
+
+/// Hooks for logging, transforming, or vetoing packets per connection.
+pub mod packet_hooks;
+pub use packet_hooks::{AppPacketHookExt, PacketContext, PacketDirection};
+
+/// `/healthz` and `/readyz` reports for orchestrators and load balancers.
+pub mod health;
+pub use health::{AppHealthExt, HealthCheck, HealthPlugin, HealthReport, HealthState};
+
+/// Messages queued to be sent after a delay or at a tick, with cancellation.
+pub mod scheduled_messages;
+pub use scheduled_messages::{ScheduledMessage, ScheduledMessages, ScheduledMessagesPlugin};
+
+#[doc(hidden)]
+pub use tracing;
+
 struct AsyncChannel<T> {
     pub(crate) sender: Sender<T>,
     pub(crate) receiver: Receiver<T>,
@@ -182,8 +213,9 @@ impl<T> AsyncChannel<T> {
 #[derive(Debug, Message)]
 /// A network event originating from another pl3xus app
 pub enum NetworkEvent {
-    /// A new client has connected
-    Connected(ConnectionId),
+    /// A new client has connected, with what its provider reported about it
+    /// (provider name, endpoint, addresses and negotiated subprotocol)
+    Connected(ConnectionId, ConnectionInfo),
     /// A client has disconnected
     Disconnected(ConnectionId),
     /// An error occured while trying to do a network operation
@@ -251,6 +283,8 @@ struct Connection {
     map_receive_task: Box<dyn JoinHandle>,
     send_task: Box<dyn JoinHandle>,
     send_message: Sender<NetworkPacket>,
+    counters: std::sync::Arc<network_tracing::ConnectionCounters>,
+    info: ConnectionInfo,
 }
 
 impl Connection {
@@ -271,6 +305,7 @@ impl<NP: NetworkProvider + Default, RT: Runtime> Plugin for Pl3xusPlugin<NP, RT>
     fn build(&self, app: &mut App) {
         app.insert_resource(Network::new(NP::default()));
         app.add_message::<NetworkEvent>();
+        app.init_resource::<ConnectionInfos>();
         app.add_systems(
             PreUpdate,
             managers::network::handle_new_incoming_connections::<NP, RT>,
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::sync::{Arc, atomic::AtomicU32};
 
 use async_channel::{Receiver, Sender};
@@ -6,7 +7,7 @@ use bevy::prelude::Resource;
 use dashmap::DashMap;
 use futures_lite::Stream;
 
-use crate::{AsyncChannel, Connection, runtime::JoinHandle};
+use crate::{AsyncChannel, Connection, ConnectionInfo, runtime::JoinHandle};
 
 use pl3xus_common::error::NetworkError;
 use pl3xus_common::{ConnectionId, NetworkPacket};
@@ -34,9 +35,19 @@ pub struct Network<NP: NetworkProvider> {
     recv_message_map_by_hash: Arc<DashMap<u64, Vec<(ConnectionId, Vec<u8>)>>>,
     /// Maps schema hash to type name for collision detection and error messages
     hash_to_typename: Arc<DashMap<u64, &'static str>>,
+    /// Where each message and request type was registered, for duplicate detection
+    registrations: DashMap<&'static str, &'static std::panic::Location<'static>>,
+    /// What to do when a type is registered again
+    duplicate_registration: network::DuplicateRegistration,
+    /// Per-type payload limits, checked before decoding
+    payload_limits: Arc<DashMap<&'static str, network::PayloadLimit>>,
     #[cfg(feature = "cache_messages")]
     last_messages: Arc<DashMap<&'static str, Vec<u8>>>,
     established_connections: Arc<DashMap<ConnectionId, Connection>>,
+    /// Message types accepted from connections on each restricted endpoint
+    endpoint_messages: Arc<DashMap<String, HashSet<&'static str>>>,
+    /// Hooks run on every packet sent or received
+    pub(crate) packet_hooks: crate::packet_hooks::PacketHooks,
     new_connections: AsyncChannel<NP::Socket>,
     disconnected_connections: AsyncChannel<ConnectionId>,
     error_channel: AsyncChannel<NetworkError>,
@@ -107,6 +118,12 @@ pub trait NetworkProvider: 'static + Send + Sync {
     /// can be handled concurrently.
     fn split(combined: Self::Socket) -> (Self::ReadHalf, Self::WriteHalf);
 
+    /// What the provider knows about a socket: the endpoint it connected on
+    /// (e.g. the WebSocket path), its addresses and negotiated subprotocol.
+    fn connection_info(_socket: &Self::Socket) -> ConnectionInfo {
+        ConnectionInfo::default()
+    }
+
     /// Get the channel capacity from the network settings.
     /// This is used to create bounded channels for outgoing messages.
     fn channel_capacity(settings: &Self::NetworkSettings) -> usize;
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+use std::panic::Location;
 use std::sync::{
     Arc,
     atomic::{AtomicU32, Ordering},
@@ -7,15 +9,20 @@ use async_channel::{bounded, unbounded};
 use bevy::prelude::*;
 use dashmap::DashMap;
 use futures_lite::StreamExt;
-use tracing::{debug, error, trace, warn};
+use tracing::{debug, error, trace, warn, Instrument};
 
 use super::{Network, NetworkProvider};
+use crate::network_tracing::connection_span;
 use crate::{
     AsyncChannel,
     Connection,
+    ConnectionInfo,
+    ConnectionInfos,
     NetworkData,
     NetworkEvent,
     OutboundMessage,
+    PacketContext,
+    PacketDirection,
     Runtime,
     // error::NetworkError,
     // network_message::NetworkMessage,
@@ -23,7 +30,7 @@ use crate::{
 };
 use pl3xus_common::error::NetworkError;
 use pl3xus_common::{
-    ConnectionId, NetworkPacket, SubscriptionMessage, TargetedMessage,
+    ConnectionId, NetworkPacket, PayloadTooLarge, SubscriptionMessage, TargetedMessage,
     Pl3xusMessage,
 };
 #[cfg(feature = "cache_messages")]
@@ -39,15 +46,39 @@ impl<NP: NetworkProvider> std::fmt::Debug for Network<NP> {
     }
 }
 
+/// Largest payload accepted for one registered type.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PayloadLimit {
+    pub(crate) max_bytes: usize,
+    /// The payload is a `RequestInternal`, which starts with the request id.
+    pub(crate) request: bool,
+}
+
+/// What to do when a message or request type is registered twice for the
+/// same provider, e.g. by two plugins sharing a type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateRegistration {
+    /// Panic, naming where the type was registered first and again.
+    #[default]
+    Panic,
+    /// Keep the first registration and ignore later ones.
+    Ignore,
+}
+
 impl<NP: NetworkProvider> Network<NP> {
     pub(crate) fn new(_provider: NP) -> Self {
         Self {
             recv_message_map: Arc::new(DashMap::new()),
             recv_message_map_by_hash: Arc::new(DashMap::new()),
             hash_to_typename: Arc::new(DashMap::new()),
+            registrations: DashMap::new(),
+            duplicate_registration: DuplicateRegistration::default(),
+            payload_limits: Arc::new(DashMap::new()),
             #[cfg(feature = "cache_messages")]
             last_messages: Arc::new(DashMap::new()),
             established_connections: Arc::new(DashMap::new()),
+            endpoint_messages: Arc::new(DashMap::new()),
+            packet_hooks: Default::default(),
             new_connections: AsyncChannel::new(),
             disconnected_connections: AsyncChannel::new(),
             error_channel: AsyncChannel::new(),
@@ -76,6 +107,30 @@ impl<NP: NetworkProvider> Network<NP> {
         self.established_connections.contains_key(&conn_id)
     }
 
+    /// Returns a traffic snapshot for every active connection
+    pub fn connection_metrics(&self) -> Vec<(ConnectionId, crate::network_tracing::ConnectionMetrics)> {
+        self.established_connections
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().counters.snapshot()))
+            .collect()
+    }
+
+    /// Returns how full a connection's outgoing channel is, from `0.0` (empty)
+    /// to `1.0` (full, further sends fail), or `None` if it isn't connected
+    pub fn send_queue_fill(&self, conn_id: ConnectionId) -> Option<f32> {
+        let connection = self.established_connections.get(&conn_id)?;
+        let capacity = connection.send_message.capacity().unwrap_or(usize::MAX).max(1);
+        Some(connection.send_message.len() as f32 / capacity as f32)
+    }
+
+    /// Returns the ids of all active connections
+    pub fn connection_ids(&self) -> Vec<ConnectionId> {
+        self.established_connections
+            .iter()
+            .map(|entry| *entry.key())
+            .collect()
+    }
+
     /// Check if a message type is registered
     ///
     /// This is primarily useful for testing and debugging.
@@ -203,9 +258,22 @@ impl<NP: NetworkProvider> Network<NP> {
             data: bincode::serde::encode_to_vec(&message, bincode::config::standard())
                 .map_err(|_| NetworkError::Serialization)?,
         };
+        Self::send_on(connection.value(), client_id, packet)
+    }
+
+    /// Send an already encoded packet to `client_id`.
+    pub(crate) fn send_packet(&self, client_id: ConnectionId, packet: NetworkPacket) -> Result<(), NetworkError> {
+        match self.established_connections.get(&client_id) {
+            Some(connection) => Self::send_on(connection.value(), client_id, packet),
+            None => Err(NetworkError::ConnectionNotFound(client_id)),
+        }
+    }
+
+    fn send_on(connection: &Connection, client_id: ConnectionId, packet: NetworkPacket) -> Result<(), NetworkError> {
+        let len = packet.data.len();
 
         match connection.send_message.try_send(packet) {
-            Ok(_) => (),
+            Ok(_) => connection.counters.record_out(len),
             Err(err) => {
                 error!("There was an error sending a packet: {}", err);
                 return Err(NetworkError::ChannelClosed(client_id));
@@ -217,6 +285,104 @@ impl<NP: NetworkProvider> Network<NP> {
 
 
 
+    /// The endpoint `conn_id` connected on, if the provider reports one.
+    pub fn connection_endpoint(&self, conn_id: ConnectionId) -> Option<String> {
+        self.established_connections
+            .get(&conn_id)
+            .and_then(|connection| connection.info.endpoint.clone())
+    }
+
+    /// What the provider reported about `conn_id`.
+    pub fn connection_info(&self, conn_id: ConnectionId) -> Option<ConnectionInfo> {
+        self.established_connections
+            .get(&conn_id)
+            .map(|connection| connection.info.clone())
+    }
+
+    /// Accept messages named `type_name` from connections on `endpoint`,
+    /// which then only accept the message types allowed this way.
+    pub(crate) fn allow_on_endpoint(&self, endpoint: &str, type_name: &'static str) {
+        self.endpoint_messages
+            .entry(endpoint.to_string())
+            .or_default()
+            .insert(type_name);
+    }
+
+    /// Record that `type_name` is registered at `caller`.
+    ///
+    /// Returns `false` if it already was and duplicates are ignored; panics
+    /// with both call sites if duplicates aren't allowed.
+    pub(crate) fn record_registration(&self, type_name: &'static str, caller: &'static Location<'static>) -> bool {
+        let Some(first) = self.registrations.get(type_name).map(|first| *first) else {
+            self.registrations.insert(type_name, caller);
+            return true;
+        };
+        match self.duplicate_registration {
+            DuplicateRegistration::Ignore => {
+                debug!("Ignoring registration of {} at {}, already registered at {}", type_name, caller, first);
+                false
+            }
+            DuplicateRegistration::Panic => panic!(
+                "Duplicate registration of {} for {}: first registered at {}, again at {}. \
+                 If several plugins share this type, register it once or call \
+                 `app.on_duplicate_registration::<{}>(DuplicateRegistration::Ignore)`.",
+                type_name,
+                NP::PROVIDER_NAME,
+                first,
+                caller,
+                std::any::type_name::<NP>(),
+            ),
+        }
+    }
+
+    /// Set the largest payload accepted for the type registered as `type_name`.
+    pub(crate) fn set_payload_limit(&self, type_name: &'static str, limit: PayloadLimit) {
+        self.payload_limits.insert(type_name, limit);
+    }
+
+    /// Returns false, and answers the sender with [`PayloadTooLarge`], if
+    /// `data` is over the limit registered for `type_name`.
+    ///
+    /// The connection stays open; only the payload is dropped.
+    fn accept_payload(
+        &self,
+        type_name: &'static str,
+        limit: Option<PayloadLimit>,
+        source: ConnectionId,
+        data: &[u8],
+    ) -> bool {
+        let Some(limit) = limit else {
+            return true;
+        };
+        if data.len() <= limit.max_bytes {
+            return true;
+        }
+
+        // Requests start with their id, so the client can fail the right one
+        let request_id = limit
+            .request
+            .then(|| bincode::serde::decode_from_slice::<u64, _>(data, bincode::config::standard()).ok())
+            .flatten()
+            .map(|(id, _)| id);
+        warn!(
+            "Rejected {} from {:?}: {} bytes exceeds the limit of {}",
+            type_name,
+            source,
+            data.len(),
+            limit.max_bytes
+        );
+        let rejection = PayloadTooLarge {
+            type_name: type_name.to_string(),
+            request_id,
+            size: data.len() as u64,
+            limit: limit.max_bytes as u64,
+        };
+        if let Err(e) = self.send(source, rejection) {
+            debug!("Failed to send PayloadTooLarge to {:?}: {}", source, e);
+        }
+        false
+    }
+
     /// Broadcast a message to all connected clients (works for both message types)
     ///
     /// ## Example
@@ -235,7 +401,7 @@ impl<NP: NetworkProvider> Network<NP> {
             };
 
             match connection.send_message.try_send(packet) {
-                Ok(_) => (),
+                Ok(_) => connection.counters.record_out(serialized_message.len()),
                 Err(err) => {
                     warn!("Could not send to client because: {}", err);
                 }
@@ -270,7 +436,7 @@ impl<NP: NetworkProvider> Network<NP> {
             };
 
             match connection.send_message.try_send(packet) {
-                Ok(_) => (),
+                Ok(_) => connection.counters.record_out(serialized_message.len()),
                 Err(err) => {
                     warn!("Could not send to client because: {}", err);
                 }
@@ -300,6 +466,18 @@ impl<NP: NetworkProvider> Network<NP> {
         }
     }
 
+    /// Allocate the id for a new connection, skipping the reserved ids and
+    /// any still held by a live connection once the counter wraps.
+    fn next_connection_id(&mut self) -> ConnectionId {
+        loop {
+            let conn_id = ConnectionId { id: self.connection_count };
+            self.connection_count = self.connection_count.wrapping_add(1);
+            if !conn_id.is_server() && !conn_id.is_none() && !self.established_connections.contains_key(&conn_id) {
+                return conn_id;
+            }
+        }
+    }
+
     /// Disconnect a specific client
     pub fn disconnect(&self, conn_id: ConnectionId) -> Result<(), NetworkError> {
         let connection = if let Some(conn) = self.established_connections.remove(&conn_id) {
@@ -318,12 +496,24 @@ pub(crate) fn handle_new_incoming_connections<NP: NetworkProvider, RT: Runtime>(
     mut server: ResMut<Network<NP>>,
     runtime: Res<Pl3xusRuntime<RT>>,
     network_settings: Res<NP::NetworkSettings>,
+    mut infos: ResMut<ConnectionInfos>,
     mut network_events: MessageWriter<NetworkEvent>,
 ) {
     while let Ok(new_conn) = server.new_connections.receiver.try_recv() {
-        let id = server.connection_count;
-        let conn_id = ConnectionId { id };
-        server.connection_count += 1;
+        let conn_id = server.next_connection_id();
+
+        let info = ConnectionInfo {
+            provider_name: Some(NP::PROVIDER_NAME),
+            ..NP::connection_info(&new_conn)
+        };
+        // Message types this connection may send, if its endpoint is restricted
+        let allowed: Option<Arc<HashSet<&'static str>>> = info
+            .endpoint
+            .as_ref()
+            .and_then(|endpoint| server.endpoint_messages.get(endpoint))
+            .map(|allowed| Arc::new(allowed.clone()));
+        debug!("Connection {} info: {:?}", conn_id.id, info);
+        infos.insert(conn_id, info.clone());
 
         let (read_half, write_half) = NP::split(new_conn);
         let recv_message_map = server.recv_message_map.clone();
@@ -338,11 +528,20 @@ pub(crate) fn handle_new_incoming_connections<NP: NetworkProvider, RT: Runtime>(
         let (outgoing_tx, outgoing_rx) = bounded(channel_capacity);
         let (incoming_tx, incoming_rx) = unbounded(); // Incoming can stay unbounded (client -> server)
 
+        let counters = Arc::new(crate::network_tracing::ConnectionCounters::default());
+        let recv_counters = counters.clone();
+
+        // Hooks added later don't apply to this connection
+        let hooks = Some(server.packet_hooks.clone()).filter(|hooks| !hooks.is_empty());
+        let recv_hooks = hooks.clone();
+        let recv_info = info.clone();
+        let send_info = info.clone();
+
         server.established_connections.insert(
                 conn_id,
                 Connection {
                     receive_task: Box::new(run_async(async move {
-                        trace!("Starting listen task for {}", id);
+                        trace!("Starting listen task for {}", conn_id.id);
                         NP::recv_loop(read_half, incoming_tx, read_network_settings).await;
 
                         match disconnected_connections.send(conn_id).await {
@@ -351,10 +550,37 @@ pub(crate) fn handle_new_incoming_connections<NP: NetworkProvider, RT: Runtime>(
                                 error!("Could not send disconnected event, because channel is disconnected");
                             }
                         }
-                    }, &runtime.0)),
+                    }.instrument(connection_span(conn_id, NP::PROVIDER_NAME, "recv")), &runtime.0)),
                     map_receive_task: Box::new(run_async(async move{
-                        while let Ok(packet) = incoming_rx.recv().await{
+                        while let Ok(mut packet) = incoming_rx.recv().await{
+                            recv_counters.record_in(packet.data.len());
+                            crate::message_event!(
+                                message = %packet.type_name,
+                                bytes = packet.data.len(),
+                                "received message"
+                            );
+                            if let Some(hooks) = &recv_hooks {
+                                let context = PacketContext {
+                                    connection: conn_id,
+                                    info: &recv_info,
+                                    direction: PacketDirection::Inbound,
+                                };
+                                if let Err(reason) = hooks.run(&context, &mut packet) {
+                                    debug!("Dropping '{}' from connection {}: {}", packet.type_name, conn_id.id, reason);
+                                    continue;
+                                }
+                            }
                             // Hybrid lookup: try type_name first (fast path), then schema_hash (fallback)
+                            let registered_name = recv_message_map
+                                .get(&packet.type_name[..])
+                                .map(|entry| *entry.key())
+                                .or_else(|| hash_to_typename.get(&packet.schema_hash).map(|entry| *entry.value()));
+                            if let (Some(allowed), Some(name)) = (&allowed, registered_name)
+                                && !allowed.contains(name)
+                            {
+                                warn!("Dropping '{}' from connection {}: not allowed on its endpoint", name, conn_id.id);
+                                continue;
+                            }
                             if let Some(mut packets) = recv_message_map.get_mut(&packet.type_name[..]) {
                                 #[cfg(feature = "debug_messages")]
                                 {
@@ -384,30 +610,64 @@ pub(crate) fn handle_new_incoming_connections<NP: NetworkProvider, RT: Runtime>(
                                        packet.type_name, packet.schema_hash);
                             }
                         }
-                    }, &runtime.0)),
+                    }.instrument(connection_span(conn_id, NP::PROVIDER_NAME, "dispatch")), &runtime.0)),
                     send_task: Box::new(run_async(async move {
-                        trace!("Starting send task for {}", id);
-                        NP::send_loop(write_half, outgoing_rx, write_network_settings).await;
-                    }, &runtime.0)),
+                        trace!("Starting send task for {}", conn_id.id);
+                        let Some(hooks) = hooks else {
+                            NP::send_loop(write_half, outgoing_rx, write_network_settings).await;
+                            return;
+                        };
+                        // Run the hooks on each packet on its way to the provider
+                        let (hooked_tx, hooked_rx) = bounded(channel_capacity);
+                        let run_hooks = async move {
+                            while let Ok(mut packet) = outgoing_rx.recv().await {
+                                let context = PacketContext {
+                                    connection: conn_id,
+                                    info: &send_info,
+                                    direction: PacketDirection::Outbound,
+                                };
+                                match hooks.run(&context, &mut packet) {
+                                    Ok(()) => {
+                                        if hooked_tx.send(packet).await.is_err() {
+                                            break;
+                                        }
+                                    }
+                                    Err(reason) => {
+                                        debug!("Not sending '{}' to connection {}: {}", packet.type_name, conn_id.id, reason);
+                                    }
+                                }
+                            }
+                        };
+                        futures_lite::future::zip(
+                            NP::send_loop(write_half, hooked_rx, write_network_settings),
+                            run_hooks,
+                        )
+                        .await;
+                    }.instrument(connection_span(conn_id, NP::PROVIDER_NAME, "send")), &runtime.0)),
                     send_message: outgoing_tx,
+                    counters,
+                    info: info.clone(),
                     //addr: new_conn.addr,
                 },
             );
 
-        network_events.write(NetworkEvent::Connected(conn_id));
+        network_events.write(NetworkEvent::Connected(conn_id, info));
     }
 
     while let Ok(disconnected_connection) = server.disconnected_connections.receiver.try_recv() {
         server
             .established_connections
             .remove(&disconnected_connection);
+        infos.remove(disconnected_connection);
         network_events.write(NetworkEvent::Disconnected(disconnected_connection));
     }
 }
 
 // Since we can't use specialization, we'll just use type_name() for all Pl3xusMessage types
 // and have a separate path for explicit NetworkMessage types via listen_for_message
+#[track_caller]
 fn register_message_internal<T: Pl3xusMessage, NP: NetworkProvider>(app: &mut App) -> &mut App {
+    let caller = Location::caller();
     let server = app.world_mut().get_resource::<Network<NP>>()
         .expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before registering messages.");
 
@@ -415,16 +675,13 @@ fn register_message_internal<T: Pl3xusMessage, NP: NetworkProvider>(app: &mut Ap
     let schema_hash = T::schema_hash();
     let short_name = T::short_name();
 
+    if !server.record_registration(message_name, caller) {
+        return app;
+    }
+
     info!("Registered network message: {} (short: {}, hash: 0x{:016x})",
            message_name, short_name, schema_hash);
 
-    // Check for duplicate registration by type_name
-    assert!(
-        !server.recv_message_map.contains_key(message_name),
-        "Duplicate registration of message: {}",
-        message_name
-    );
-
     // Check for schema hash collision with different type
     if let Some(existing_typename) = server.hash_to_typename.get(&schema_hash) {
         let existing = *existing_typename.value();
@@ -437,8 +694,8 @@ fn register_message_internal<T: Pl3xusMessage, NP: NetworkProvider>(app: &mut Ap
         }
     }
 
-    // Register in both maps
-    server.recv_message_map.insert(message_name, Vec::new());
+    // Register in both maps (outbound registration may have added the queue already)
+    server.recv_message_map.entry(message_name).or_default();
     server.recv_message_map_by_hash.insert(schema_hash, Vec::new());
     server.hash_to_typename.insert(schema_hash, message_name);
 
@@ -453,8 +710,7 @@ fn register_auto_if_needed<T: Pl3xusMessage, NP: NetworkProvider>(app: &mut App)
         let server = app.world().get_resource::<Network<NP>>()
             .expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before registering messages.");
 
-        let message_name = T::type_name();
-        server.recv_message_map.contains_key(message_name)
+        server.registrations.contains_key(T::type_name())
     };
 
     if already_registered {
@@ -535,9 +791,49 @@ pub trait AppNetworkMessage {
     where
         T::SubscribeRequest: Pl3xusMessage,
         T::UnsubscribeRequest: Pl3xusMessage;
+
+    /// Reject payloads of `T` (plain or targeted) larger than `max_bytes`.
+    ///
+    /// Oversized payloads are dropped before decoding and the sender gets a
+    /// [`PayloadTooLarge`] message; the connection stays open. This is in
+    /// addition to the transport's overall message size limit.
+    ///
+    /// ## Example
+    /// ```rust,ignore
+    /// app.register_network_message::<UploadCsv, WebSocketProvider>()
+    ///     .limit_message_size::<UploadCsv, WebSocketProvider>(256 * 1024);
+    /// ```
+    fn limit_message_size<T: Pl3xusMessage, NP: NetworkProvider>(&mut self, max_bytes: usize) -> &mut Self;
+
+    /// Accept `T` (plain or targeted) from connections on `endpoint`.
+    ///
+    /// Once any message is allowed on an endpoint, connections on it can only
+    /// send the allowed messages (see also
+    /// [`allow_request_on_endpoint`](crate::managers::network_request::AppNetworkRequestMessage::allow_request_on_endpoint));
+    /// anything else is dropped. Endpoints without allowed messages accept
+    /// every registered message. Applies to connections opened afterwards.
+    ///
+    /// ## Example
+    /// ```rust,ignore
+    /// // Devtools connections can only use the sync protocol
+    /// app.allow_message_on_endpoint::<SyncClientMessage, WebSocketProvider>("/devtools");
+    /// ```
+    fn allow_message_on_endpoint<T: Pl3xusMessage, NP: NetworkProvider>(&mut self, endpoint: &str) -> &mut Self;
+
+    /// Choose what happens when a message or request type is registered
+    /// again for `NP`. By default this panics with both call sites.
+    ///
+    /// ## Example
+    /// ```rust,ignore
+    /// // Plugins each register the types they use, some of them shared
+    /// app.on_duplicate_registration::<WebSocketProvider>(DuplicateRegistration::Ignore)
+    ///     .add_plugins((RobotPlugin, ProgramPlugin));
+    /// ```
+    fn on_duplicate_registration<NP: NetworkProvider>(&mut self, policy: DuplicateRegistration) -> &mut Self;
 }
 
 impl AppNetworkMessage for App {
+    #[track_caller]
     fn register_network_message<T: Pl3xusMessage, NP: NetworkProvider>(&mut self) -> &mut Self {
         // Use type_name() for all Pl3xusMessage types
         // This works for both NetworkMessage and non-NetworkMessage types
@@ -644,6 +940,33 @@ impl AppNetworkMessage for App {
 
         self
     }
+
+    fn limit_message_size<T: Pl3xusMessage, NP: NetworkProvider>(&mut self, max_bytes: usize) -> &mut Self {
+        let server = self.world().get_resource::<Network<NP>>()
+            .expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before limiting message sizes.");
+
+        let limit = PayloadLimit { max_bytes, request: false };
+        server.set_payload_limit(T::type_name(), limit);
+        server.set_payload_limit(TargetedMessage::<T>::name(), limit);
+        self
+    }
+
+    fn allow_message_on_endpoint<T: Pl3xusMessage, NP: NetworkProvider>(&mut self, endpoint: &str) -> &mut Self {
+        let server = self.world().get_resource::<Network<NP>>()
+            .expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before allowing messages on endpoints.");
+
+        server.allow_on_endpoint(endpoint, T::type_name());
+        server.allow_on_endpoint(endpoint, TargetedMessage::<T>::name());
+        self
+    }
+
+    fn on_duplicate_registration<NP: NetworkProvider>(&mut self, policy: DuplicateRegistration) -> &mut Self {
+        let mut server = self.world_mut().get_resource_mut::<Network<NP>>()
+            .expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before configuring registrations.");
+
+        server.duplicate_registration = policy;
+        self
+    }
 }
 
 /// System that processes incoming messages for Pl3xusMessage types
@@ -664,9 +987,14 @@ pub(crate) fn register_message<T, NP: NetworkProvider>(
         net_res.last_messages.insert(name, newest_message.clone());
     }
 
+    let net = &*net_res;
+    let limit = net.payload_limits.get(name).map(|limit| *limit);
     let provider_name = NP::PROVIDER_NAME;
     let config = bincode::config::standard();
     events.write_batch(messages.drain(..).filter_map(move |(source, msg)| {
+        if !net.accept_payload(name, limit, source, &msg) {
+            return None;
+        }
         bincode::serde::decode_from_slice(&msg, config)
             .ok()
             .map(|(inner, _)| NetworkData { source, inner, provider_name })
@@ -706,7 +1034,10 @@ fn handle_previous_message_requests<T: Pl3xusMessage + Clone, NP: NetworkProvide
             };
 
             if let Some(connection) = server.established_connections.get(&request.source) {
-                let _ = connection.send_message.try_send(packet);
+                let len = packet.data.len();
+                if connection.send_message.try_send(packet).is_ok() {
+                    connection.counters.record_out(len);
+                }
             }
         }
     }
@@ -719,17 +1050,20 @@ pub fn register_targeted_message<T, NP: NetworkProvider>(
 ) where
     T: Pl3xusMessage,
 {
-    let mut messages = match net_res
-        .recv_message_map
-        .get_mut(TargetedMessage::<T>::name())
-    {
+    let name = TargetedMessage::<T>::name();
+    let mut messages = match net_res.recv_message_map.get_mut(name) {
         Some(messages) => messages,
         None => return,
     };
 
+    let net = &*net_res;
+    let limit = net.payload_limits.get(name).map(|limit| *limit);
     let provider_name = NP::PROVIDER_NAME;
     let config = bincode::config::standard();
     events.write_batch(messages.drain(..).filter_map(move |(source, msg)| {
+        if !net.accept_payload(name, limit, source, &msg) {
+            return None;
+        }
         match bincode::serde::decode_from_slice::<TargetedMessage<T>, _>(&msg, config) {
             Ok((inner, _)) => {
                 #[cfg(feature = "debug_messages")]
@@ -835,3 +1169,79 @@ pub(crate) fn relay_outbound<T: Pl3xusMessage + Clone, NP: NetworkProvider>(
         }
     }
 }
+
+#[cfg(all(test, feature = "tcp"))]
+mod tests {
+    use super::*;
+    use crate::tcp::{NetworkSettings, TcpProvider};
+    use crate::Pl3xusPlugin;
+    use bevy::ecs::message::Messages;
+    use bevy::tasks::{TaskPool, TaskPoolBuilder};
+
+    fn tcp_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(Pl3xusPlugin::<TcpProvider, TaskPool>::default());
+        app.insert_resource(Pl3xusRuntime(TaskPoolBuilder::new().num_threads(2).build()));
+        app.insert_resource(NetworkSettings::default());
+        app
+    }
+
+    /// Hand the server one end of a loopback TCP connection and return the
+    /// other end, which keeps the connection open while it lives.
+    fn connect(app: &mut App) -> std::net::TcpStream {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (accepted, _) = listener.accept().unwrap();
+        let socket = async_net::TcpStream::try_from(accepted).unwrap();
+        app.world()
+            .resource::<Network<TcpProvider>>()
+            .new_connections
+            .sender
+            .try_send(socket)
+            .unwrap();
+        app.update();
+        client
+    }
+
+    fn connected_events(app: &mut App) -> Vec<(ConnectionId, ConnectionInfo)> {
+        app.world_mut()
+            .resource_mut::<Messages<NetworkEvent>>()
+            .drain()
+            .filter_map(|event| match event {
+                NetworkEvent::Connected(conn_id, info) => Some((conn_id, info)),
+                NetworkEvent::Disconnected(_) | NetworkEvent::Error(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_next_connection_id_skips_reserved_and_live_ids() {
+        let mut app = tcp_app();
+        let _client = connect(&mut app);
+        let live = connected_events(&mut app)[0].0;
+        assert_eq!(live, ConnectionId { id: 1 });
+
+        let mut net = app.world_mut().resource_mut::<Network<TcpProvider>>();
+        let ids: HashSet<_> = (0..1000).map(|_| net.next_connection_id()).collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(!ids.contains(&live));
+
+        // Once the counter wraps it skips NONE, SERVER and the live connection
+        net.connection_count = u32::MAX - 1;
+        let ids: Vec<_> = (0..3).map(|_| net.next_connection_id()).collect();
+        assert_eq!(ids, vec![ConnectionId { id: u32::MAX - 1 }, ConnectionId { id: 2 }, ConnectionId { id: 3 }]);
+    }
+
+    #[test]
+    fn test_connected_event_names_the_provider() {
+        let mut app = tcp_app();
+        let _client = connect(&mut app);
+
+        let events = connected_events(&mut app);
+        assert_eq!(events.len(), 1);
+        let (conn_id, info) = &events[0];
+        assert_eq!(info.provider_name, Some(TcpProvider::PROVIDER_NAME));
+        assert_eq!(app.world().resource::<ConnectionInfos>().get(*conn_id), Some(info));
+    }
+}
@@ -189,17 +189,18 @@ use std::{fmt::Debug, marker::PhantomData, sync::atomic::AtomicU64};
 use async_channel::{Receiver, Sender};
 use bevy::{
     ecs::system::SystemParam,
-    prelude::{App, Message, MessageReader, MessageWriter, PreUpdate, Res, ResMut, Resource},
+    prelude::{App, Message, MessageReader, MessageWriter, Messages, PreUpdate, Res, ResMut, Resource},
 };
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use crate::NetworkData;
+use crate::idempotency::{CachedResponse, IdempotencyCheck, RequestIdempotency, RequestKey};
 use pl3xus_common::error::NetworkError;
-use pl3xus_common::{ConnectionId, NetworkPacket, RequestMessage, Pl3xusMessage};
+use pl3xus_common::{ConnectionId, IdempotencyKey, NetworkPacket, RequestMessage, Pl3xusMessage};
 
-use super::{Network, NetworkProvider, network::register_message};
+use super::{Network, NetworkProvider, network::{register_message, PayloadLimit}};
 
 #[derive(SystemParam, Debug)]
 /// A wrapper around [`Network`] that allows for the sending of [`RequestMessage`]'s.
@@ -215,10 +216,42 @@ impl<'w, 's, T: RequestMessage, NP: NetworkProvider> Requester<'w, 's, T, NP> {
         &self,
         client_id: ConnectionId,
         request: T,
+    ) -> Result<Response<T::ResponseMessage>, NetworkError> {
+        self.send(client_id, request, None)
+    }
+
+    /// Sends a request carrying an idempotency key.
+    ///
+    /// Use the same key when retrying the same logical operation; the server
+    /// handles it once and replays the response to retries. Generate keys with
+    /// [`pl3xus_common::random_u64`] so they can't be guessed.
+    pub fn send_request_with_key(
+        &self,
+        client_id: ConnectionId,
+        request: T,
+        idempotency_key: u64,
+    ) -> Result<Response<T::ResponseMessage>, NetworkError> {
+        self.send(client_id, request, Some(idempotency_key))
+    }
+
+    fn send(
+        &self,
+        client_id: ConnectionId,
+        request: T,
+        idempotency_key: Option<u64>,
     ) -> Result<Response<T::ResponseMessage>, NetworkError> {
         let (id, response) = self.response_map.get_responder();
-        self.server
-            .send(client_id, RequestInternal { id, request })?;
+        self.server.send(
+            client_id,
+            RequestInternal {
+                id,
+                request,
+                idempotency_key: idempotency_key.map(|key| IdempotencyKey {
+                    client: self.response_map.client,
+                    key,
+                }),
+            },
+        )?;
         Ok(response)
     }
 }
@@ -246,6 +279,8 @@ impl<T> Response<T> {
 pub struct ResponseMap<T: RequestMessage> {
     count: AtomicU64,
     map: DashMap<u64, Sender<T::ResponseMessage>>,
+    /// Random client id sent with idempotency keys.
+    client: u64,
 }
 
 impl<T: RequestMessage> Default for ResponseMap<T> {
@@ -253,6 +288,7 @@ impl<T: RequestMessage> Default for ResponseMap<T> {
         Self {
             count: Default::default(),
             map: DashMap::new(),
+            client: pl3xus_common::random_u64(),
         }
     }
 }
@@ -287,6 +323,48 @@ impl<T: RequestMessage> ResponseMap<T> {
 struct RequestInternal<T> {
     id: u64,
     request: T,
+    idempotency_key: Option<IdempotencyKey>,
+}
+
+/// Where to store the response of a request that carried an idempotency key.
+#[derive(Debug, Clone)]
+struct IdempotencyHandle {
+    cache: RequestIdempotency,
+    key: RequestKey,
+}
+
+/// Send a response packet, caching it first if the request had an idempotency
+/// key. Retries that arrived while the request was in flight get a copy.
+fn send_response(
+    response_tx: &Sender<NetworkPacket>,
+    source: ConnectionId,
+    request_id: u64,
+    packet: NetworkPacket,
+    idempotency: Option<IdempotencyHandle>,
+) -> Result<(), NetworkError> {
+    if let Some(handle) = idempotency {
+        let id_len = bincode::serde::encode_to_vec(request_id, bincode::config::standard())
+            .map(|bytes| bytes.len())
+            .unwrap_or_default();
+        let cached = CachedResponse {
+            type_name: packet.type_name.clone(),
+            schema_hash: packet.schema_hash,
+            body: packet.data[id_len..].to_vec(),
+        };
+        let waiting = handle
+            .cache
+            .0
+            .lock()
+            .map(|mut cache| cache.complete(source, handle.key, cached.clone()))
+            .unwrap_or_default();
+        for (retry_id, retry_tx) in waiting {
+            let _ = retry_tx.try_send(cached.to_packet(retry_id));
+        }
+    }
+
+    response_tx
+        .try_send(packet)
+        .map_err(|_| NetworkError::SendError)
 }
 
 /// A wrapper around a request that allows sending a response that will automatically be written
@@ -297,6 +375,7 @@ pub struct Request<T: RequestMessage> {
     source: ConnectionId,
     request_id: u64,
     response_tx: Sender<NetworkPacket>,
+    idempotency: Option<IdempotencyHandle>,
 }
 
 impl<T: RequestMessage> Request<T> {
@@ -306,6 +385,15 @@ impl<T: RequestMessage> Request<T> {
         &self.request
     }
 
+    /// Mutably access the underlying request.
+    ///
+    /// This is intended for server-side middleware that normalizes or
+    /// transforms a request before it reaches its handler.
+    #[inline(always)]
+    pub fn get_request_mut(&mut self) -> &mut T {
+        &mut self.request
+    }
+
     /// Read the source of the underlying request
     #[inline(always)]
     pub fn source(&self) -> &ConnectionId {
@@ -318,6 +406,38 @@ impl<T: RequestMessage> Request<T> {
         self.request_id
     }
 
+    /// The idempotency key the client attached, if any.
+    ///
+    /// Retries with the same key never reach handlers; they get the cached response.
+    #[inline(always)]
+    pub fn idempotency_key(&self) -> Option<u64> {
+        self.idempotency.as_ref().map(|handle| handle.key.1.key)
+    }
+
+    /// Build a request that did not arrive over a connection.
+    ///
+    /// The request goes through the same filters and handlers as a network
+    /// request from `source`, but the response is delivered to the returned
+    /// [`LocalResponse`]. Bridges (such as the `pl3xus_sync` admin plugin) use
+    /// this to serve existing requests over another channel.
+    pub fn local(source: ConnectionId, request: T) -> (Self, LocalResponse<T::ResponseMessage>) {
+        let (response_tx, response_rx) = async_channel::bounded(1);
+        let request = Self {
+            request,
+            source,
+            request_id: 0,
+            response_tx,
+            idempotency: None,
+        };
+        (
+            request,
+            LocalResponse {
+                rx: response_rx,
+                _marker: PhantomData,
+            },
+        )
+    }
+
     /// Take the responder for async response handling.
     ///
     /// This consumes the request and returns a `DeferredResponder` that can be
@@ -327,6 +447,7 @@ impl<T: RequestMessage> Request<T> {
             source: self.source,
             request_id: self.request_id,
             response_tx: self.response_tx,
+            idempotency: self.idempotency,
             _marker: PhantomData,
         }
     }
@@ -355,9 +476,53 @@ impl<T: RequestMessage> Request<T> {
             data,
         };
 
-        self.response_tx
-            .try_send(packet)
-            .map_err(|_| NetworkError::SendError)
+        send_response(&self.response_tx, self.source, self.request_id, packet, self.idempotency)
+    }
+}
+
+/// The type name and schema hash requests of type `T` are sent under, for
+/// code that looks at raw packets such as packet hooks.
+pub fn request_packet_type<T: RequestMessage>() -> (&'static str, u64) {
+    (RequestInternal::<T>::type_name(), RequestInternal::<T>::schema_hash())
+}
+
+/// A request held back for a filter stage before it reaches handlers.
+///
+/// Once an app adds `UnfilteredRequest<T>` as a message, requests of type `T`
+/// are written here instead of as [`Request<T>`]. The filter (such as the
+/// middleware and access policies of `pl3xus_sync`) drains them and writes the
+/// ones it accepts as ordinary [`Request<T>`]s, so handlers read the same
+/// message whether or not a filter is installed.
+#[derive(Debug, Message, Clone)]
+pub struct UnfilteredRequest<T: RequestMessage>(pub Request<T>);
+
+/// The response to a request built with [`Request::local`].
+#[derive(Debug)]
+pub struct LocalResponse<R> {
+    rx: Receiver<NetworkPacket>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Pl3xusMessage> LocalResponse<R> {
+    /// Poll for the response.
+    ///
+    /// Returns `Ok(None)` until the handler responds, and an error if the
+    /// request was dropped without a response.
+    pub fn try_recv(&self) -> Result<Option<R>, NetworkError> {
+        match self.rx.try_recv() {
+            Ok(packet) => {
+                let (response, _) = bincode::serde::decode_from_slice::<ResponseInternal<R>, _>(
+                    &packet.data,
+                    bincode::config::standard(),
+                )
+                .map_err(|_| NetworkError::Serialization)?;
+                Ok(Some(response.response))
+            }
+            Err(async_channel::TryRecvError::Empty) => Ok(None),
+            Err(async_channel::TryRecvError::Closed) => Err(NetworkError::Error(
+                "request was dropped without a response".to_string(),
+            )),
+        }
     }
 }
 
@@ -370,6 +535,7 @@ pub struct DeferredResponder<R: Pl3xusMessage + Clone + Debug> {
     source: ConnectionId,
     request_id: u64,
     response_tx: Sender<NetworkPacket>,
+    idempotency: Option<IdempotencyHandle>,
     _marker: PhantomData<R>,
 }
 
@@ -410,9 +576,7 @@ impl<R: Pl3xusMessage + Clone + Debug> DeferredResponder<R> {
             data,
         };
 
-        self.response_tx
-            .try_send(packet)
-            .map_err(|_| NetworkError::SendError)
+        send_response(&self.response_tx, self.source, self.request_id, packet, self.idempotency)
     }
 }
 
@@ -420,28 +584,37 @@ impl<R: Pl3xusMessage + Clone + Debug> DeferredResponder<R> {
 pub trait AppNetworkRequestMessage {
     /// Register a request message type to listen for in the app
     fn listen_for_request_message<T: RequestMessage, NP: NetworkProvider>(&mut self) -> &mut Self;
+
+    /// Reject requests of type `T` larger than `max_bytes` before decoding them.
+    ///
+    /// The client gets a [`PayloadTooLarge`](pl3xus_common::PayloadTooLarge)
+    /// carrying the request's id, so it can fail the request right away.
+    fn limit_request_size<T: RequestMessage, NP: NetworkProvider>(&mut self, max_bytes: usize) -> &mut Self;
+
+    /// Accept requests of type `T` from connections on `endpoint`, which then
+    /// only accept allowed messages and requests. See
+    /// [`AppNetworkMessage::allow_message_on_endpoint`](crate::AppNetworkMessage::allow_message_on_endpoint).
+    fn allow_request_on_endpoint<T: RequestMessage, NP: NetworkProvider>(&mut self, endpoint: &str) -> &mut Self;
 }
 
 impl AppNetworkRequestMessage for App {
+    #[track_caller]
     fn listen_for_request_message<T: RequestMessage, NP: NetworkProvider>(&mut self) -> &mut Self {
         let server = self.world_mut().get_resource::<Network<NP>>().expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before listening for server messages.");
 
         let request_name = RequestInternal::<T>::type_name();
+        if !server.record_registration(request_name, std::panic::Location::caller()) {
+            return self;
+        }
         debug!(
             "Registered a new RequestMessage: {}",
             request_name
         );
 
-        assert!(
-            !server
-                .recv_message_map
-                .contains_key(request_name),
-            "Duplicate registration of RequestMessage: {}",
-            request_name
-        );
         server
             .recv_message_map
             .insert(request_name, Vec::new());
+        self.init_resource::<RequestIdempotency>();
         self.add_message::<NetworkData<RequestInternal<T>>>();
         self.add_message::<Request<T>>();
         self.add_systems(
@@ -452,21 +625,103 @@ impl AppNetworkRequestMessage for App {
             ),
         )
     }
+
+    fn limit_request_size<T: RequestMessage, NP: NetworkProvider>(&mut self, max_bytes: usize) -> &mut Self {
+        let server = self.world().get_resource::<Network<NP>>().expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before limiting request sizes.");
+
+        server.set_payload_limit(
+            RequestInternal::<T>::type_name(),
+            PayloadLimit { max_bytes, request: true },
+        );
+        self
+    }
+
+    fn allow_request_on_endpoint<T: RequestMessage, NP: NetworkProvider>(&mut self, endpoint: &str) -> &mut Self {
+        let server = self.world().get_resource::<Network<NP>>().expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before allowing requests on endpoints.");
+
+        server.allow_on_endpoint(endpoint, RequestInternal::<T>::type_name());
+        self
+    }
 }
 
 fn create_request_handlers<T: RequestMessage, NP: NetworkProvider>(
     mut requests: MessageReader<NetworkData<RequestInternal<T>>>,
     mut requests_wrapped: MessageWriter<Request<T>>,
+    mut held: Option<ResMut<Messages<UnfilteredRequest<T>>>>,
     network: Res<Network<NP>>,
+    idempotency: Res<RequestIdempotency>,
 ) {
     for request in requests.read() {
-        if let Some(connection) = &network.established_connections.get(request.source()) {
-            requests_wrapped.write(Request {
-                request: request.request.clone(),
-                request_id: request.id,
-                response_tx: connection.send_message.clone(),
-                source: request.source,
-            });
+        // Cloned so the connection map isn't borrowed while checking other connections
+        let Some(response_tx) = network
+            .established_connections
+            .get(request.source())
+            .map(|connection| connection.send_message.clone())
+        else {
+            continue;
+        };
+
+        let handle = match request.idempotency_key {
+            None => None,
+            Some(key) => {
+                let key = (RequestInternal::<T>::type_name(), key);
+                let check = idempotency
+                    .0
+                    .lock()
+                    .map(|mut cache| {
+                        cache.begin(
+                            request.source,
+                            key,
+                            Some((request.id, response_tx.clone())),
+                            |owner| network.has_connection(owner),
+                        )
+                    })
+                    .unwrap_or(IdempotencyCheck::New);
+                match check {
+                    IdempotencyCheck::New => Some(IdempotencyHandle {
+                        cache: idempotency.clone(),
+                        key,
+                    }),
+                    IdempotencyCheck::InFlight => {
+                        debug!(
+                            "Request {} from {} is a retry of an in-flight request (key {})",
+                            request.id, request.source, key.1.key
+                        );
+                        continue;
+                    }
+                    IdempotencyCheck::Completed(cached) => {
+                        debug!(
+                            "Replaying cached response to request {} from {} (key {})",
+                            request.id, request.source, key.1.key
+                        );
+                        let _ = response_tx.try_send(cached.to_packet(request.id));
+                        continue;
+                    }
+                    IdempotencyCheck::Conflict => {
+                        debug!(
+                            "Dropping request {} from {}: key {} belongs to another connection",
+                            request.id, request.source, key.1.key
+                        );
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let wrapped = Request {
+            request: request.request.clone(),
+            request_id: request.id,
+            response_tx,
+            source: request.source,
+            idempotency: handle,
+        };
+        match held.as_mut() {
+            Some(held) => {
+                held.write(UnfilteredRequest(wrapped));
+            }
+            None => {
+                requests_wrapped.write(wrapped);
+            }
         }
     }
 }
@@ -484,26 +739,23 @@ pub trait AppNetworkResponseMessage {
 }
 
 impl AppNetworkResponseMessage for App {
+    #[track_caller]
     fn listen_for_response_message<T: RequestMessage, NP: NetworkProvider>(&mut self) -> &mut Self {
-        self.insert_resource(ResponseMap::<T>::default());
         let client = self.world_mut().get_resource::<Network<NP>>().expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before listening for server messages.");
 
         let response_name = ResponseInternal::<T::ResponseMessage>::type_name();
+        if !client.record_registration(response_name, std::panic::Location::caller()) {
+            return self;
+        }
         debug!(
             "Registered a new ResponseMessage: {}",
             response_name
         );
 
-        assert!(
-            !client
-                .recv_message_map
-                .contains_key(response_name),
-            "Duplicate registration of ResponseMessage: {}",
-            response_name
-        );
         client
             .recv_message_map
             .insert(response_name, Vec::new());
+        self.insert_resource(ResponseMap::<T>::default());
         self.add_message::<NetworkData<ResponseInternal<T::ResponseMessage>>>();
         self.add_systems(
             PreUpdate,
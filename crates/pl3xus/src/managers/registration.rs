@@ -40,6 +40,7 @@ use pl3xus_common::Pl3xusMessage;
 ///
 /// register_message::<JogCommand, WebSocketProvider, _>(&mut app, MySchedule::Notify);
 /// ```
+#[track_caller]
 pub fn register_message<T, NP, S>(app: &mut App, system_set: S)
 where
     T: Pl3xusMessage + Clone + 'static,
@@ -69,6 +70,7 @@ where
 ///
 /// register_message_unscheduled::<Ping, WebSocketProvider>(&mut app);
 /// ```
+#[track_caller]
 pub fn register_message_unscheduled<T, NP>(app: &mut App)
 where
     T: Pl3xusMessage + Clone + 'static,
@@ -0,0 +1,264 @@
+//! Structured tracing for network connections.
+//!
+//! Every connection's receive, dispatch, and send tasks run inside a
+//! `pl3xus_connection` tracing span carrying the connection id and provider
+//! name, so logs from concurrent connections can be told apart. Per-message
+//! events are emitted through [`message_event!`](crate::message_event) at a
+//! level that can be changed at runtime with [`set_message_log_level`] (or the
+//! [`SetNetworkLogLevel`] admin request), instead of flooding `info` at 60 Hz.
+//!
+//! [`NetworkTracingPlugin`] additionally publishes per-connection counters in
+//! the [`NetworkMetrics`] resource.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::managers::network_request::{AppNetworkRequestMessage, Request};
+use crate::{Network, NetworkProvider};
+use pl3xus_common::{ConnectionId, NetworkLogLevel, SetNetworkLogLevel, SetNetworkLogLevelResponse};
+
+static MESSAGE_LOG_LEVEL: AtomicU8 = AtomicU8::new(level_to_u8(NetworkLogLevel::Debug));
+
+const fn level_to_u8(level: NetworkLogLevel) -> u8 {
+    match level {
+        NetworkLogLevel::Off => 0,
+        NetworkLogLevel::Trace => 1,
+        NetworkLogLevel::Debug => 2,
+        NetworkLogLevel::Info => 3,
+    }
+}
+
+/// The level at which per-message events are currently emitted.
+pub fn message_log_level() -> NetworkLogLevel {
+    match MESSAGE_LOG_LEVEL.load(Ordering::Relaxed) {
+        0 => NetworkLogLevel::Off,
+        1 => NetworkLogLevel::Trace,
+        3 => NetworkLogLevel::Info,
+        _ => NetworkLogLevel::Debug,
+    }
+}
+
+/// Change the level at which per-message events are emitted, returning the
+/// previous level.
+pub fn set_message_log_level(level: NetworkLogLevel) -> NetworkLogLevel {
+    let previous = message_log_level();
+    MESSAGE_LOG_LEVEL.store(level_to_u8(level), Ordering::Relaxed);
+    previous
+}
+
+/// Emit a per-message tracing event at the current [`message_log_level`].
+///
+/// Intended for provider receive/send loops; accepts the same arguments as
+/// `tracing::debug!`.
+#[macro_export]
+macro_rules! message_event {
+    ($($arg:tt)+) => {
+        match $crate::network_tracing::message_log_level() {
+            $crate::NetworkLogLevel::Off => {}
+            $crate::NetworkLogLevel::Trace => $crate::tracing::trace!($($arg)+),
+            $crate::NetworkLogLevel::Debug => $crate::tracing::debug!($($arg)+),
+            $crate::NetworkLogLevel::Info => $crate::tracing::info!($($arg)+),
+        }
+    };
+}
+
+/// Create the span used for a connection's background tasks.
+pub(crate) fn connection_span(connection_id: ConnectionId, provider: &'static str, task: &'static str) -> tracing::Span {
+    tracing::info_span!("pl3xus_connection", conn_id = connection_id.id, provider, task)
+}
+
+/// Live traffic counters for one connection.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionCounters {
+    messages_in: AtomicU64,
+    messages_out: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl ConnectionCounters {
+    pub(crate) fn record_in(&self, bytes: usize) {
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_out(&self, bytes: usize) {
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> ConnectionMetrics {
+        ConnectionMetrics {
+            messages_in: self.messages_in.load(Ordering::Relaxed),
+            messages_out: self.messages_out.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Snapshot of a connection's traffic since it was established.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionMetrics {
+    /// Messages received from the peer.
+    pub messages_in: u64,
+    /// Messages queued for sending to the peer.
+    pub messages_out: u64,
+    /// Payload bytes received from the peer.
+    pub bytes_in: u64,
+    /// Payload bytes queued for sending to the peer.
+    pub bytes_out: u64,
+}
+
+/// Resource holding the latest per-connection metrics.
+///
+/// Refreshed by [`NetworkTracingPlugin`] at its configured interval.
+#[derive(Resource, Debug, Default)]
+pub struct NetworkMetrics {
+    /// Metrics keyed by connection.
+    pub connections: HashMap<ConnectionId, ConnectionMetrics>,
+}
+
+#[derive(Resource)]
+struct NetworkMetricsTimer(Timer);
+
+/// Plugin that publishes [`NetworkMetrics`] and optionally accepts
+/// [`SetNetworkLogLevel`] requests from clients.
+///
+/// ```rust,ignore
+/// app.add_plugins(
+///     NetworkTracingPlugin::<WebSocketProvider>::default()
+///         .metrics_interval(Duration::from_secs(5))
+///         .with_remote_log_control(),
+/// );
+/// ```
+///
+/// Remote log control is off by default because core pl3xus has no notion of
+/// an admin client. When enabling it on untrusted networks, register the
+/// request through `pl3xus_sync` with a message policy instead.
+pub struct NetworkTracingPlugin<NP: NetworkProvider> {
+    metrics_interval: Duration,
+    remote_log_control: bool,
+    _marker: PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for NetworkTracingPlugin<NP> {
+    fn default() -> Self {
+        Self {
+            metrics_interval: Duration::from_secs(1),
+            remote_log_control: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> NetworkTracingPlugin<NP> {
+    /// How often [`NetworkMetrics`] is refreshed (and logged at debug level).
+    pub fn metrics_interval(mut self, interval: Duration) -> Self {
+        self.metrics_interval = interval;
+        self
+    }
+
+    /// Accept [`SetNetworkLogLevel`] requests from clients.
+    pub fn with_remote_log_control(mut self) -> Self {
+        self.remote_log_control = true;
+        self
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for NetworkTracingPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkMetrics>();
+        app.insert_resource(NetworkMetricsTimer(Timer::new(
+            self.metrics_interval,
+            TimerMode::Repeating,
+        )));
+        app.add_systems(Update, update_network_metrics::<NP>);
+
+        if self.remote_log_control {
+            app.listen_for_request_message::<SetNetworkLogLevel, NP>();
+            app.add_systems(Update, handle_set_network_log_level);
+        }
+    }
+}
+
+fn update_network_metrics<NP: NetworkProvider>(
+    time: Res<Time>,
+    mut timer: ResMut<NetworkMetricsTimer>,
+    net: Res<Network<NP>>,
+    mut metrics: ResMut<NetworkMetrics>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    metrics.connections = net.connection_metrics().into_iter().collect();
+    for (connection_id, m) in &metrics.connections {
+        let _span = connection_span(*connection_id, NP::PROVIDER_NAME, "metrics").entered();
+        debug!(
+            messages_in = m.messages_in,
+            messages_out = m.messages_out,
+            bytes_in = m.bytes_in,
+            bytes_out = m.bytes_out,
+            "connection traffic"
+        );
+    }
+}
+
+/// Apply [`SetNetworkLogLevel`] requests.
+pub fn handle_set_network_log_level(mut requests: MessageReader<Request<SetNetworkLogLevel>>) {
+    for request in requests.read() {
+        let level = request.get_request().level;
+        let previous = set_message_log_level(level);
+        info!(
+            "Per-message network log level changed from {:?} to {:?} by {:?}",
+            previous,
+            level,
+            request.source()
+        );
+        if let Err(e) = request.clone().respond(SetNetworkLogLevelResponse {
+            previous,
+            current: level,
+        }) {
+            warn!("Failed to respond to SetNetworkLogLevel: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_log_level_roundtrip() {
+        let original = set_message_log_level(NetworkLogLevel::Off);
+        let mut expected_previous = NetworkLogLevel::Off;
+        for level in [NetworkLogLevel::Trace, NetworkLogLevel::Info, NetworkLogLevel::Debug] {
+            assert_eq!(set_message_log_level(level), expected_previous);
+            assert_eq!(message_log_level(), level);
+            expected_previous = level;
+        }
+        set_message_log_level(original);
+    }
+
+    #[test]
+    fn test_connection_counters() {
+        let counters = ConnectionCounters::default();
+        counters.record_in(10);
+        counters.record_in(5);
+        counters.record_out(7);
+        assert_eq!(
+            counters.snapshot(),
+            ConnectionMetrics {
+                messages_in: 2,
+                messages_out: 1,
+                bytes_in: 15,
+                bytes_out: 7,
+            }
+        );
+    }
+}
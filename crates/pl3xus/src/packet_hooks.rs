@@ -0,0 +1,139 @@
+//! Hooks that see every packet a server sends or receives.
+//!
+//! A hook runs on the connection's network task with the packet before it is
+//! written to the connection ([`PacketDirection::Outbound`]) and after it is
+//! read, before it is routed to the message queues
+//! ([`PacketDirection::Inbound`]). It can log the packet, change it in place,
+//! or veto it by returning an error, in which case the packet is dropped and
+//! later hooks don't see it.
+//!
+//! ```rust,ignore
+//! app.packet_hook::<WebSocketProvider>(|context, packet| {
+//!     if context.direction == PacketDirection::Inbound {
+//!         info!(target: "audit", "{:?} from {:?} sent {}", context.connection, context.info.client_addr, packet.type_name);
+//!     }
+//!     Ok(())
+//! });
+//! ```
+//!
+//! Hooks apply to connections opened after they are added, so add them while
+//! building the app.
+
+use std::sync::{Arc, RwLock};
+
+use bevy::prelude::*;
+
+use crate::{ConnectionId, ConnectionInfo, Network, NetworkPacket, NetworkProvider};
+
+/// Which way a packet is going, as seen from this app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketDirection {
+    /// Received from the connection
+    Inbound,
+    /// About to be written to the connection
+    Outbound,
+}
+
+/// What a hook knows about the packet it was given.
+#[derive(Debug)]
+pub struct PacketContext<'a> {
+    /// The connection the packet is from or for
+    pub connection: ConnectionId,
+    /// What the provider reported about the connection
+    pub info: &'a ConnectionInfo,
+    /// Which way the packet is going
+    pub direction: PacketDirection,
+}
+
+type PacketHook = Arc<dyn Fn(&PacketContext, &mut NetworkPacket) -> Result<(), String> + Send + Sync>;
+
+/// The hooks added to a [`Network`], in the order they run.
+#[derive(Clone, Default)]
+pub(crate) struct PacketHooks(Arc<RwLock<Vec<PacketHook>>>);
+
+impl PacketHooks {
+    pub(crate) fn add(&self, hook: PacketHook) {
+        if let Ok(mut hooks) = self.0.write() {
+            hooks.push(hook);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.read().map(|hooks| hooks.is_empty()).unwrap_or(true)
+    }
+
+    /// Run every hook on `packet`, stopping at the first veto.
+    pub(crate) fn run(&self, context: &PacketContext, packet: &mut NetworkPacket) -> Result<(), String> {
+        let Ok(hooks) = self.0.read() else {
+            return Ok(());
+        };
+        hooks.iter().try_for_each(|hook| hook(context, packet))
+    }
+}
+
+/// Extension trait for adding packet hooks.
+pub trait AppPacketHookExt {
+    /// Run `hook` on every packet sent or received by `NP` connections opened
+    /// from now on. Returning an error drops the packet.
+    fn packet_hook<NP: NetworkProvider>(
+        &mut self,
+        hook: impl Fn(&PacketContext, &mut NetworkPacket) -> Result<(), String> + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl AppPacketHookExt for App {
+    fn packet_hook<NP: NetworkProvider>(
+        &mut self,
+        hook: impl Fn(&PacketContext, &mut NetworkPacket) -> Result<(), String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world()
+            .get_resource::<Network<NP>>()
+            .expect("Could not find `Network`. Be sure to include the `Pl3xusPlugin` before adding packet hooks.")
+            .packet_hooks
+            .add(Arc::new(hook));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_hooks() {
+        let hooks = PacketHooks::default();
+        assert!(hooks.is_empty());
+        hooks.add(Arc::new(|context, packet| {
+            if context.direction == PacketDirection::Outbound {
+                packet.data.push(0);
+            }
+            Ok(())
+        }));
+        hooks.add(Arc::new(|_, packet| match packet.type_name.as_str() {
+            "Forbidden" => Err("not allowed".into()),
+            _ => Ok(()),
+        }));
+
+        let info = ConnectionInfo::default();
+        let context = |direction| PacketContext {
+            connection: ConnectionId { id: 1 },
+            info: &info,
+            direction,
+        };
+        let mut packet = NetworkPacket {
+            type_name: "Allowed".into(),
+            schema_hash: 0,
+            data: vec![1],
+        };
+        assert_eq!(hooks.run(&context(PacketDirection::Outbound), &mut packet), Ok(()));
+        assert_eq!(packet.data, vec![1, 0]);
+        assert_eq!(hooks.run(&context(PacketDirection::Inbound), &mut packet), Ok(()));
+        assert_eq!(packet.data, vec![1, 0]);
+
+        packet.type_name = "Forbidden".into();
+        assert_eq!(
+            hooks.run(&context(PacketDirection::Inbound), &mut packet),
+            Err("not allowed".to_string())
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Messages queued on the server to be sent later.
+//!
+//! [`ScheduledMessages`] holds messages for a connection, or for every
+//! connection, until a delay has passed or the app reaches a given tick.
+//! [`ScheduledMessagesPlugin`] sends them once they are due. Scheduling
+//! returns a [`ScheduledMessage`] handle that cancels the message if it
+//! hasn't been sent yet:
+//!
+//! ```rust,ignore
+//! app.add_plugins(ScheduledMessagesPlugin::<WebSocketProvider>::default());
+//!
+//! fn remind(mut scheduled: ResMut<ScheduledMessages>, mut pending: ResMut<PendingReminder>) {
+//!     pending.0 = scheduled
+//!         .send_after(pending.client, Duration::from_secs(30), Reminder { text: "Still there?".into() })
+//!         .ok();
+//! }
+//!
+//! fn on_reply(mut scheduled: ResMut<ScheduledMessages>, pending: Res<PendingReminder>) {
+//!     if let Some(reminder) = pending.0 {
+//!         scheduled.cancel(reminder);
+//!     }
+//! }
+//! ```
+//!
+//! Messages are encoded when they are scheduled. A message for a connection
+//! that has closed by the time it is due is dropped.
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+
+use crate::error::NetworkError;
+use crate::{Network, NetworkProvider};
+use pl3xus_common::{ConnectionId, NetworkPacket, Pl3xusMessage};
+
+/// Handle to a scheduled message, for [`ScheduledMessages::cancel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScheduledMessage(u64);
+
+/// Who a scheduled message goes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduledRecipient {
+    /// One connection
+    Connection(ConnectionId),
+    /// Every connection open when the message is sent
+    Broadcast,
+}
+
+/// When a scheduled message is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduledTime {
+    /// Once this instant has passed
+    At(Instant),
+    /// Once [`ScheduledMessages::tick`] reaches this tick
+    Tick(u64),
+}
+
+#[derive(Debug)]
+struct Pending {
+    handle: ScheduledMessage,
+    recipient: ScheduledRecipient,
+    due: ScheduledTime,
+    packet: NetworkPacket,
+}
+
+/// Messages waiting to be sent by [`ScheduledMessagesPlugin`].
+#[derive(Resource, Debug, Default)]
+pub struct ScheduledMessages {
+    tick: u64,
+    next_handle: u64,
+    pending: Vec<Pending>,
+}
+
+impl ScheduledMessages {
+    /// The current tick: the number of updates in which due messages were
+    /// sent so far. Messages scheduled for `tick() + 1` go out in the next
+    /// update.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Number of messages not sent yet.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no messages are waiting.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Whether `handle` is still waiting to be sent.
+    pub fn is_pending(&self, handle: ScheduledMessage) -> bool {
+        self.pending.iter().any(|pending| pending.handle == handle)
+    }
+
+    /// Queue `message` for `recipient` once `due`.
+    pub fn schedule<T: Pl3xusMessage>(
+        &mut self,
+        recipient: ScheduledRecipient,
+        due: ScheduledTime,
+        message: T,
+    ) -> Result<ScheduledMessage, NetworkError> {
+        let packet = NetworkPacket {
+            type_name: T::type_name().to_string(),
+            schema_hash: T::schema_hash(),
+            data: bincode::serde::encode_to_vec(&message, bincode::config::standard())
+                .map_err(|_| NetworkError::Serialization)?,
+        };
+        self.next_handle += 1;
+        let handle = ScheduledMessage(self.next_handle);
+        self.pending.push(Pending {
+            handle,
+            recipient,
+            due,
+            packet,
+        });
+        Ok(handle)
+    }
+
+    /// Send `message` to `client_id` after `delay`.
+    pub fn send_after<T: Pl3xusMessage>(
+        &mut self,
+        client_id: ConnectionId,
+        delay: Duration,
+        message: T,
+    ) -> Result<ScheduledMessage, NetworkError> {
+        self.schedule(
+            ScheduledRecipient::Connection(client_id),
+            ScheduledTime::At(Instant::now() + delay),
+            message,
+        )
+    }
+
+    /// Send `message` to `client_id` once the app reaches `tick`.
+    pub fn send_at_tick<T: Pl3xusMessage>(
+        &mut self,
+        client_id: ConnectionId,
+        tick: u64,
+        message: T,
+    ) -> Result<ScheduledMessage, NetworkError> {
+        self.schedule(ScheduledRecipient::Connection(client_id), ScheduledTime::Tick(tick), message)
+    }
+
+    /// Broadcast `message` after `delay`.
+    pub fn broadcast_after<T: Pl3xusMessage>(
+        &mut self,
+        delay: Duration,
+        message: T,
+    ) -> Result<ScheduledMessage, NetworkError> {
+        self.schedule(
+            ScheduledRecipient::Broadcast,
+            ScheduledTime::At(Instant::now() + delay),
+            message,
+        )
+    }
+
+    /// Broadcast `message` once the app reaches `tick`.
+    pub fn broadcast_at_tick<T: Pl3xusMessage>(
+        &mut self,
+        tick: u64,
+        message: T,
+    ) -> Result<ScheduledMessage, NetworkError> {
+        self.schedule(ScheduledRecipient::Broadcast, ScheduledTime::Tick(tick), message)
+    }
+
+    /// Cancel a message that hasn't been sent. Returns whether it was still
+    /// waiting.
+    pub fn cancel(&mut self, handle: ScheduledMessage) -> bool {
+        let before = self.pending.len();
+        self.pending.retain(|pending| pending.handle != handle);
+        self.pending.len() != before
+    }
+
+    /// Cancel every message waiting for `client_id`.
+    pub fn cancel_for(&mut self, client_id: ConnectionId) -> usize {
+        let before = self.pending.len();
+        self.pending
+            .retain(|pending| pending.recipient != ScheduledRecipient::Connection(client_id));
+        before - self.pending.len()
+    }
+
+    /// Remove the messages due at `now`, in the order they were scheduled,
+    /// and advance the tick.
+    fn take_due(&mut self, now: Instant) -> Vec<Pending> {
+        let tick = self.tick;
+        let (due, waiting) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|pending| match pending.due {
+                ScheduledTime::At(at) => at <= now,
+                ScheduledTime::Tick(due) => due <= tick,
+            });
+        self.pending = waiting;
+        self.tick += 1;
+        due
+    }
+}
+
+/// Plugin that sends [`ScheduledMessages`] over `NP` once they are due.
+pub struct ScheduledMessagesPlugin<NP: NetworkProvider> {
+    _marker: PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for ScheduledMessagesPlugin<NP> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for ScheduledMessagesPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScheduledMessages>();
+        app.add_systems(PostUpdate, send_scheduled_messages::<NP>);
+    }
+}
+
+fn send_scheduled_messages<NP: NetworkProvider>(mut scheduled: ResMut<ScheduledMessages>, net: Res<Network<NP>>) {
+    for pending in scheduled.take_due(Instant::now()) {
+        let recipients = match pending.recipient {
+            ScheduledRecipient::Connection(client_id) => vec![client_id],
+            ScheduledRecipient::Broadcast => net.connection_ids(),
+        };
+        for client_id in recipients {
+            if let Err(e) = net.send_packet(client_id, pending.packet.clone()) {
+                debug!(
+                    "Dropped scheduled {} for {:?}: {:?}",
+                    pending.packet.type_name, client_id, e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Reminder {
+        text: String,
+    }
+
+    fn reminder() -> Reminder {
+        Reminder { text: "Still there?".into() }
+    }
+
+    #[test]
+    fn test_scheduled_messages() {
+        let client = ConnectionId { id: 1 };
+        let mut scheduled = ScheduledMessages::default();
+        let now = Instant::now();
+
+        let later = scheduled.send_after(client, Duration::from_secs(60), reminder()).ok();
+        let next_tick = scheduled.send_at_tick(client, 1, reminder()).ok();
+        let now_broadcast = scheduled.broadcast_after(Duration::ZERO, reminder()).ok();
+        let cancelled = scheduled.broadcast_at_tick(0, reminder()).ok();
+        assert_eq!(scheduled.len(), 4);
+
+        assert!(cancelled.is_some_and(|handle| scheduled.cancel(handle)));
+        assert!(!cancelled.is_some_and(|handle| scheduled.cancel(handle)));
+
+        let due = |scheduled: &mut ScheduledMessages, at| {
+            scheduled.take_due(at).iter().map(|pending| pending.handle).collect::<Vec<_>>()
+        };
+        assert_eq!(due(&mut scheduled, now + Duration::from_millis(1)), Vec::from_iter(now_broadcast));
+        assert_eq!(scheduled.tick(), 1);
+        assert_eq!(due(&mut scheduled, now + Duration::from_millis(2)), Vec::from_iter(next_tick));
+
+        assert!(later.is_some_and(|handle| scheduled.is_pending(handle)));
+        assert_eq!(scheduled.cancel_for(client), 1);
+        assert!(scheduled.is_empty());
+    }
+}
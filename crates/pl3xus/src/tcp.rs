@@ -1,6 +1,7 @@
 use std::{net::SocketAddr, pin::Pin};
 
 use crate::{
+    ConnectionInfo,
     NetworkPacket,
     async_channel::{Receiver, Sender},
     async_trait,
@@ -52,7 +53,7 @@ impl NetworkProvider for TcpProvider {
         connect_info: Self::ConnectInfo,
         _: Self::NetworkSettings,
     ) -> Result<Self::Socket, NetworkError> {
-        info!("Beginning connection");
+        debug!("Beginning connection");
         let stream = TcpStream::connect(connect_info)
             .await
             .map_err(NetworkError::Connection)?;
@@ -74,7 +75,7 @@ impl NetworkProvider for TcpProvider {
     ) {
         let mut buffer = vec![0; settings.max_packet_length];
         loop {
-            info!("Reading message length");
+            crate::message_event!("Reading message length");
             let length = match read_half.read(&mut buffer[..8]).await {
                 Ok(0) => {
                     // EOF, meaning the TCP stream has closed.
@@ -103,7 +104,7 @@ impl NetworkProvider for TcpProvider {
                     break;
                 }
             };
-            info!("Message length: {}", length);
+            crate::message_event!(length, "Message length");
 
             if length > settings.max_packet_length {
                 error!(
@@ -113,7 +114,7 @@ impl NetworkProvider for TcpProvider {
                 break;
             }
 
-            info!("Reading message into buffer");
+            crate::message_event!("Reading message into buffer");
             match read_half.read_exact(&mut buffer[..length]).await {
                 Ok(()) => (),
                 Err(err) => {
@@ -124,7 +125,7 @@ impl NetworkProvider for TcpProvider {
                     break;
                 }
             }
-            info!("Message read");
+            crate::message_event!("Message read");
 
             let packet: NetworkPacket = match bincode::serde::decode_from_slice(&buffer[..length], bincode::config::standard()) {
                 Ok((packet, _)) => packet,
@@ -138,7 +139,7 @@ impl NetworkProvider for TcpProvider {
                 error!("Failed to send decoded message to pl3xus");
                 break;
             }
-            info!("Message deserialized and sent to pl3xus");
+            crate::message_event!("Message deserialized and sent to pl3xus");
         }
     }
 
@@ -227,6 +228,10 @@ impl NetworkProvider for TcpProvider {
         (combined.clone(), combined)
     }
 
+    fn connection_info(socket: &Self::Socket) -> ConnectionInfo {
+        socket.peer_addr().map(ConnectionInfo::from_peer).unwrap_or_default()
+    }
+
     fn channel_capacity(settings: &Self::NetworkSettings) -> usize {
         settings.channel_capacity
     }
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy::tasks::TaskPoolBuilder;
 use pl3xus::{
-    AppNetworkMessage, Pl3xusPlugin, Pl3xusRuntime, Network,
+    AppNetworkMessage, DuplicateRegistration, Pl3xusPlugin, Pl3xusRuntime, Network,
     ConnectionId, SubscriptionMessage,
     tcp::{TcpProvider, NetworkSettings},
 };
@@ -96,6 +96,28 @@ fn test_duplicate_registration_panics() {
     app.register_network_message::<TestMessage, TcpProvider>(); // Should panic
 }
 
+#[test]
+#[should_panic(expected = "unified_api_tests.rs:")]
+fn test_duplicate_registration_names_call_sites() {
+    let mut app = create_test_app();
+
+    app.register_network_message::<TestMessage, TcpProvider>();
+    app.register_network_message::<TestMessage, TcpProvider>();
+}
+
+#[test]
+fn test_duplicate_registration_ignored() {
+    let mut app = create_test_app();
+
+    app.on_duplicate_registration::<TcpProvider>(DuplicateRegistration::Ignore);
+    app.register_network_message::<TestMessage, TcpProvider>();
+    app.register_network_message::<TestMessage, TcpProvider>();
+
+    let net = app.world().get_resource::<Network<TcpProvider>>().unwrap();
+    let names = net.registered_message_names();
+    assert_eq!(names.iter().filter(|name| name.contains("TestMessage")).count(), 1);
+}
+
 #[test]
 fn test_send_message() {
     let mut app = create_test_app();
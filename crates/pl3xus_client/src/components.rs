@@ -9,7 +9,7 @@ use std::str::FromStr;
 
 use leptos::prelude::*;
 
-use crate::hooks::use_field_editor;
+use crate::hooks::{use_control, use_field_editor, ControlPending};
 use crate::traits::SyncComponent;
 
 /// A ready-to-use editable input component with Enter-to-apply, blur-to-revert UX.
@@ -122,3 +122,70 @@ where
     }
 }
 
+
+/// A button that takes control of an entity, or releases it when this client
+/// already has it.
+///
+/// Built on `use_control`. The button is disabled while a request is pending,
+/// shows who holds the entity, and puts the last denial reason in its tooltip.
+/// It carries a `data-control` attribute (`mine`, `other`, `pending` or
+/// `free`) for styling.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::ControlButton;
+///
+/// #[component]
+/// fn RobotToolbar(robot_id: Signal<Option<u64>>) -> impl IntoView {
+///     view! {
+///         <ControlButton entity_id=robot_id class="btn" />
+///     }
+/// }
+/// ```
+#[component]
+pub fn ControlButton(
+    /// The entity to control
+    #[prop(into)]
+    entity_id: Signal<Option<u64>>,
+    /// CSS class for the button element
+    #[prop(optional)]
+    class: Option<&'static str>,
+) -> impl IntoView {
+    let control = use_control(move || entity_id.get());
+
+    let label = move || match control.pending.get() {
+        Some(ControlPending::Taking) => "Requesting...".to_string(),
+        Some(ControlPending::Releasing) => "Releasing...".to_string(),
+        None if control.has_control.get() => "Release control".to_string(),
+        None => match control.holder.get() {
+            Some(holder) => format!("Controlled by client {}", holder),
+            None => "Take control".to_string(),
+        },
+    };
+
+    let state = move || {
+        if control.is_pending() {
+            "pending"
+        } else if control.has_control.get() {
+            "mine"
+        } else if control.controlled_by_other() {
+            "other"
+        } else {
+            "free"
+        }
+    };
+
+    view! {
+        <button
+            type="button"
+            class=class.unwrap_or("")
+            data-control=state
+            disabled=move || control.is_pending() || entity_id.get().is_none()
+            title=move || control.denial.get().unwrap_or_default()
+            on:click=move |_| control.toggle()
+        >
+            {label}
+        </button>
+    }
+}
@@ -7,9 +7,18 @@ use leptos_use::core::ConnectionReadyState;
 
 use crate::client_type_registry::ClientTypeRegistry;
 use crate::error::SyncError;
+use crate::latency::{LatencyTracker, CLOCK_SYNC_PROBES};
+use crate::reliable::{random_u64, ReliableOutbox};
 use crate::traits::SyncComponent;
+use pl3xus_client_core::{
+    component_matches, filtered_subscription_key, live_query_request, BlobCache, LiveQueryRows, QueryCache,
+    SubscriptionTracker,
+};
+pub use pl3xus_client_core::QueryCacheState;
+use pl3xus_common::LiveQuery;
 use pl3xus_sync::{
-    MutateComponent, MutationResponse, MutationStatus, SerializableEntity, SubscriptionRequest,
+    FieldError, LiveQueryUpdate, MutateComponent, MutationResponse, MutationStatus, RedoMutation, RegistryUpdate, SerializableEntity,
+    QueryCancel, QueryResponse, StateTransition, SubscriptionFilter, SubscriptionSequence, UndoMutation,
     UnsubscribeRequest, SyncClientMessage,
 };
 
@@ -35,6 +44,8 @@ pub struct MutationState {
     pub request_id: u64,
     pub status: Option<MutationStatus>,
     pub message: Option<String>,
+    /// Per-field errors when the server rejected the value during validation.
+    pub field_errors: Vec<FieldError>,
 }
 
 impl MutationState {
@@ -43,8 +54,17 @@ impl MutationState {
             request_id,
             status: None,
             message: None,
+            field_errors: Vec::new(),
         }
     }
+
+    /// Validation message for `field`, if the server rejected it.
+    pub fn field_error(&self, field: &str) -> Option<&str> {
+        self.field_errors
+            .iter()
+            .find(|e| e.field == field)
+            .map(|e| e.message.as_str())
+    }
 }
 
 /// Context providing access to the sync client.
@@ -59,7 +79,7 @@ pub struct SyncContext {
     /// Last error that occurred
     pub last_error: Signal<Option<SyncError>>,
     /// This client's own connection ID (set when server sends Welcome message)
-    /// Used to determine if we have control by comparing with EntityControl.client_id
+    /// See [`SyncContext::my_identity`] to compare it with an `EntityControl`.
     pub my_connection_id: RwSignal<Option<pl3xus_common::ConnectionId>>,
     /// Function to send messages to the server
     send: Arc<dyn Fn(&[u8]) + Send + Sync>,
@@ -72,12 +92,13 @@ pub struct SyncContext {
     /// Cache of signals for each (TypeId, params) pair
     /// Uses Weak references to allow garbage collection
     signal_cache: Arc<Mutex<HashMap<(TypeId, String), Weak<dyn Any + Send + Sync>>>>,
-    /// Subscription tracking: component_type -> (subscription_id, ref_count)
-    subscriptions: Arc<Mutex<HashMap<String, (u64, usize)>>>,
-    /// Next subscription ID
-    next_subscription_id: Arc<Mutex<u64>>,
+    /// Subscription tracking: subscription key -> (subscription_id, ref_count)
+    ///
+    /// The key is the component type, or `Component?<filter json>` for
+    /// filtered subscriptions (see [`filtered_subscription_key`]).
+    subscriptions: Arc<Mutex<SubscriptionTracker>>,
     /// Raw component data storage: (entity_id, component_name) -> raw bytes
-    /// This is the central storage that handle_sync_item updates
+    /// This is the central storage that SyncBatch items update (see `apply_sync_item`)
     /// Effects in subscribe_component watch this and deserialize to typed signals
     pub(crate) component_data: RwSignal<HashMap<(u64, String), Vec<u8>>>,
     /// Mutation state tracking: request_id -> MutationState
@@ -103,33 +124,37 @@ pub struct SyncContext {
     /// Query cache for deduplication: (query_type, query_key) -> (state_signal, ref_count)
     /// Multiple components using the same query share one state signal.
     /// The query_key is a serialized representation of the request parameters.
-    pub(crate) query_cache: Arc<Mutex<HashMap<(String, String), QueryCacheEntry>>>,
+    pub(crate) query_cache: Arc<Mutex<QueryCache<ArcRwSignal<QueryCacheState>>>>,
+    /// Live query results: query_id -> rows pushed by the server
+    pub(crate) live_queries: RwSignal<HashMap<u64, LiveQueryRows>>,
+    /// Server clock estimate, fed by `ClockPong` replies, and per-component
+    /// end-to-end latency, only populated when the server timestamps its
+    /// sync batches.
+    pub(crate) latency: RwSignal<LatencyTracker>,
+    /// Session of the server run that sent the last Welcome
+    pub(crate) server_session: Arc<Mutex<Option<u64>>>,
+    /// Last SyncBatch sequence number seen per subscription_id, for gap detection
+    pub(crate) sync_sequences: Arc<Mutex<HashMap<u64, u64>>>,
+    /// Reliable messages awaiting a server ack (resent after reconnect)
+    pub(crate) reliable: Arc<Mutex<ReliableOutbox>>,
+    /// Random id sent with request idempotency keys, so the server scopes
+    /// them to this client
+    pub(crate) idempotency_client: u64,
+    /// Most recent state machine transition: (entity_id, component_name) -> transition
+    pub(crate) state_transitions: RwSignal<HashMap<(u64, String), StateTransition>>,
+    /// Most recent types registered by the server while running
+    pub(crate) registry_update: RwSignal<Option<RegistryUpdate>>,
+    /// Blobs fetched by `use_blob`, shared by every hook referencing them
+    pub(crate) blobs: Arc<Mutex<BlobCache>>,
+    /// Bumped whenever a blob finishes, so hooks waiting on it re-check the cache
+    pub(crate) blobs_loaded: RwSignal<u64>,
 }
 
 /// Entry in the query cache for deduplication.
-#[derive(Clone)]
-pub struct QueryCacheEntry {
-    /// The shared state signal (type-erased as raw bytes for storage)
-    /// Components deserialize this to their specific QueryState<T>
-    pub state: ArcRwSignal<QueryCacheState>,
-    /// Reference count - when this reaches 0, the entry can be cleaned up
-    pub ref_count: usize,
-    /// Last invalidation counter seen - used to detect when to refetch
-    pub last_invalidation: u64,
-}
-
-/// Type-erased query state stored in the cache.
-#[derive(Clone, Debug, Default)]
-pub struct QueryCacheState {
-    /// Raw response bytes (if available)
-    pub data: Option<Vec<u8>>,
-    /// Error message (if the query failed)
-    pub error: Option<String>,
-    /// Whether the query is currently fetching
-    pub is_fetching: bool,
-    /// Whether data has ever been fetched (for showing stale data while refetching)
-    pub is_stale: bool,
-}
+///
+/// Components deserialize the shared state's raw bytes to their specific
+/// QueryState<T>.
+pub type QueryCacheEntry = pl3xus_client_core::QueryCacheEntry<ArcRwSignal<QueryCacheState>>;
 
 /// State tracking for a single request/response cycle.
 #[derive(Clone, Debug)]
@@ -176,15 +201,24 @@ impl SyncContext {
             close,
             registry,
             signal_cache: Arc::new(Mutex::new(HashMap::new())),
-            subscriptions: Arc::new(Mutex::new(HashMap::new())),
-            next_subscription_id: Arc::new(Mutex::new(0)),
+            subscriptions: Arc::new(Mutex::new(SubscriptionTracker::default())),
             component_data: RwSignal::new(HashMap::new()),
             mutations: RwSignal::new(HashMap::new()),
             next_request_id: Arc::new(Mutex::new(0)),
             incoming_messages: RwSignal::new(HashMap::new()),
             requests: RwSignal::new(HashMap::new()),
             query_invalidations: RwSignal::new(HashMap::new()),
-            query_cache: Arc::new(Mutex::new(HashMap::new())),
+            query_cache: Arc::new(Mutex::new(QueryCache::default())),
+            live_queries: RwSignal::new(HashMap::new()),
+            latency: RwSignal::new(LatencyTracker::default()),
+            server_session: Arc::new(Mutex::new(None)),
+            sync_sequences: Arc::new(Mutex::new(HashMap::new())),
+            reliable: Arc::new(Mutex::new(ReliableOutbox::default())),
+            idempotency_client: random_u64(),
+            state_transitions: RwSignal::new(HashMap::new()),
+            registry_update: RwSignal::new(None),
+            blobs: Arc::new(Mutex::new(BlobCache::default())),
+            blobs_loaded: RwSignal::new(0),
         }
     }
 
@@ -320,11 +354,7 @@ impl SyncContext {
     {
         use pl3xus_common::{NetworkPacket, TargetedMessage};
 
-        // Wrap in TargetedMessage with entity bits as string
-        let targeted = TargetedMessage {
-            target_id: entity_bits.to_string(),
-            message,
-        };
+        let targeted = TargetedMessage::new(entity_bits, message);
 
         // Serialize the targeted message to bincode
         let data = match bincode::serde::encode_to_vec(&targeted, bincode::config::standard()) {
@@ -377,6 +407,36 @@ impl SyncContext {
         }
     }
 
+    /// Ask the server for exclusive control of `entity`.
+    ///
+    /// The server answers with a `ControlResponse`; read it with
+    /// `use_message::<ControlResponse>()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let ctx = use_sync_context();
+    /// view! { <button on:click=move |_| ctx.take_control(entity_bits)>"Take Control"</button> }
+    /// ```
+    pub fn take_control(&self, entity: impl Into<SerializableEntity>) {
+        self.send(pl3xus_common::ControlRequest::take(entity));
+    }
+
+    /// Give up control of `entity`.
+    pub fn release_control(&self, entity: impl Into<SerializableEntity>) {
+        self.send(pl3xus_common::ControlRequest::release(entity));
+    }
+
+    /// This client's identity, once the server has welcomed it.
+    ///
+    /// Tracks `my_connection_id`, so it can be compared with an
+    /// `EntityControl` inside reactive closures.
+    pub fn my_identity(&self) -> Option<pl3xus_common::ConnectionIdentity> {
+        let connection = self.my_connection_id.get()?;
+        let session = (*self.server_session.lock().unwrap())?;
+        Some(pl3xus_common::ConnectionIdentity { session, connection })
+    }
+
     /// Handle an incoming message (non-sync message).
     ///
     /// This is called by the provider when it receives a NetworkPacket that is not
@@ -555,6 +615,80 @@ impl SyncContext {
         signal_clone.read_only()
     }
 
+    /// Subscribe to the entities of a component type that match `filter`.
+    ///
+    /// The filter is sent with the subscription so the server only streams
+    /// matching entities. The same filter is applied to the received data, so
+    /// the result is also correct against a server that doesn't filter.
+    ///
+    /// Calls with the same type and an equal filter share one server
+    /// subscription.
+    pub fn subscribe_component_filtered<T: SyncComponent + Clone + Default>(
+        &self,
+        filter: SubscriptionFilter,
+    ) -> ReadSignal<HashMap<u64, T>> {
+        let component_name = T::component_name();
+        let subscription_key = filtered_subscription_key(component_name, &filter);
+        let signal = RwSignal::new(HashMap::new());
+
+        if self.subscriptions.lock().unwrap().acquire(&subscription_key, Some(filter.clone())) {
+            let ctx = self.clone();
+            let subscription_key = subscription_key.clone();
+            let ready_state = self.ready_state;
+            Effect::new(move |_| {
+                if ready_state.get() == ConnectionReadyState::Open {
+                    ctx.send_subscription_request(&subscription_key, None);
+                }
+            });
+        }
+
+        // Same raw bytes -> typed signal pattern as subscribe_component, keeping
+        // only matching entities. Other subscriptions to the same type share
+        // component_data, and the server sends the update that makes an entity
+        // stop matching, so filtering here is always needed.
+        let component_data = self.component_data;
+        let registry = self.registry.clone();
+        let component_name_str = component_name.to_string();
+        let prev_bytes: StoredValue<HashMap<u64, Vec<u8>>> = StoredValue::new(HashMap::new());
+
+        Effect::new(move |_| {
+            let data_map = component_data.get();
+            let current_bytes: HashMap<u64, Vec<u8>> = data_map
+                .iter()
+                .filter(|((_, comp_name), _)| comp_name == &component_name_str)
+                .map(|((entity_id, _), bytes)| (*entity_id, bytes.clone()))
+                .collect();
+
+            if prev_bytes.with_value(|prev| *prev == current_bytes) {
+                return;
+            }
+
+            let typed_map: HashMap<u64, T> = current_bytes
+                .iter()
+                .filter_map(|(entity_id, bytes)| {
+                    let component = registry.deserialize::<T>(&component_name_str, bytes).ok()?;
+                    component_matches(&filter, *entity_id, &component).then_some((*entity_id, component))
+                })
+                .collect();
+            prev_bytes.set_value(current_bytes);
+
+            signal.try_update_untracked(|val| *val = typed_map);
+            signal.notify();
+        });
+
+        let ctx = self.clone();
+        on_cleanup(move || ctx.release_filtered_subscription(&subscription_key));
+
+        signal.read_only()
+    }
+
+    /// Drop one reference to a filtered subscription, unsubscribing after the last.
+    fn release_filtered_subscription(&self, subscription_key: &str) {
+        if let Some(subscription_id) = self.decrement_subscription(subscription_key) {
+            self.send_unsubscribe_request(subscription_id);
+        }
+    }
+
     /// Subscribe to a component type and return a reactive Store.
     ///
     /// This method provides fine-grained reactivity using the `reactive_stores` crate.
@@ -711,49 +845,21 @@ impl SyncContext {
 
     /// Increment subscription ref count. Returns true if this is the first subscription.
     fn increment_subscription(&self, component_name: &str) -> bool {
-        let mut subs = self.subscriptions.lock().unwrap();
-        if let Some((_, ref_count)) = subs.get_mut(component_name) {
-            *ref_count += 1;
-            false // Not the first subscription
-        } else {
-            // First subscription - allocate a new subscription ID
-            let subscription_id = {
-                let mut id = self.next_subscription_id.lock().unwrap();
-                let current = *id;
-                *id += 1;
-                current
-            };
-            subs.insert(component_name.to_string(), (subscription_id, 1));
-            true // First subscription
-        }
+        self.subscriptions.lock().unwrap().acquire(component_name, None)
     }
 
     /// Decrement subscription ref count. Returns Some(subscription_id) if this was the last subscription.
     fn decrement_subscription(&self, component_name: &str) -> Option<u64> {
-        let mut subs = self.subscriptions.lock().unwrap();
-        if let Some((subscription_id, ref_count)) = subs.get_mut(component_name) {
-            *ref_count -= 1;
-            if *ref_count == 0 {
-                let id = *subscription_id;
-                subs.remove(component_name);
-                return Some(id);
-            }
-        }
-        None
+        self.subscriptions.lock().unwrap().release(component_name)
     }
 
     /// Send a subscription request to the server.
-    fn send_subscription_request(&self, component_name: &str, entity: Option<SerializableEntity>) {
-        // Get the subscription ID for this component type
-        let subscription_id = {
-            let subs = self.subscriptions.lock().unwrap();
-            subs.get(component_name).map(|(id, _)| *id).unwrap_or(0)
-        };
-
-        let request = SubscriptionRequest {
-            subscription_id,
-            component_type: component_name.to_string(),
-            entity,
+    ///
+    /// `subscription_key` is the component type, or a filtered subscription
+    /// key whose filter is sent along.
+    fn send_subscription_request(&self, subscription_key: &str, entity: Option<SerializableEntity>) {
+        let Some(request) = self.subscriptions.lock().unwrap().request(subscription_key, entity) else {
+            return;
         };
 
         // Wrap in SyncClientMessage and serialize
@@ -776,6 +882,139 @@ impl SyncContext {
         }
     }
 
+    /// Send a message with reliable, ordered, exactly-once delivery.
+    ///
+    /// The server must register the message with `.reliable()`. The message is
+    /// kept until the server acknowledges it and is resent automatically after
+    /// a reconnect, so use this for commands that must not be lost, like Stop
+    /// or Abort.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let ctx = use_sync_context();
+    /// let abort = move |_| ctx.send_reliable(AbortProgram);
+    /// ```
+    pub fn send_reliable<T>(&self, message: T)
+    where
+        T: serde::Serialize + pl3xus_common::Pl3xusMessage,
+    {
+        self.send_reliable_payload(T::type_name(), &message);
+    }
+
+    /// Send a targeted message with reliable delivery.
+    ///
+    /// The server must register the message with `.targeted().reliable()`.
+    /// See [`send_reliable`](Self::send_reliable) and [`send_targeted`](Self::send_targeted).
+    pub fn send_targeted_reliable<T>(&self, entity_bits: u64, message: T)
+    where
+        T: serde::Serialize + pl3xus_common::Pl3xusMessage,
+    {
+        use pl3xus_common::TargetedMessage;
+
+        let targeted = TargetedMessage::new(entity_bits, message);
+        self.send_reliable_payload(TargetedMessage::<T>::name(), &targeted);
+    }
+
+    fn send_reliable_payload<M: serde::Serialize>(&self, message_type: &str, message: &M) {
+        let payload = match bincode::serde::encode_to_vec(message, bincode::config::standard()) {
+            Ok(bytes) => bytes,
+            Err(_e) => {
+                #[cfg(target_arch = "wasm32")]
+                leptos::logging::error!(
+                    "[SyncContext::send_reliable] Failed to serialize message '{}': {:?}",
+                    message_type,
+                    _e
+                );
+                return;
+            }
+        };
+
+        let envelope = self.reliable.lock().unwrap().wrap(message_type, payload);
+        self.send(envelope);
+    }
+
+    /// Forget a reliable message the server has acknowledged.
+    pub(crate) fn handle_reliable_ack(&self, ack: &pl3xus_common::ReliableAck) {
+        self.reliable.lock().unwrap().acknowledge(ack);
+    }
+
+    /// Resend every unacknowledged reliable message (called after reconnect).
+    pub(crate) fn resend_reliable(&self) {
+        let pending = self.reliable.lock().unwrap().pending().to_vec();
+
+        #[cfg(target_arch = "wasm32")]
+        if !pending.is_empty() {
+            leptos::logging::log!(
+                "[SyncContext] Resending {} unacknowledged reliable message(s)",
+                pending.len()
+            );
+        }
+
+        for envelope in pending {
+            self.send(envelope);
+        }
+    }
+
+    /// Number of reliable messages still waiting for a server ack.
+    pub fn pending_reliable_count(&self) -> usize {
+        self.reliable.lock().unwrap().pending().len()
+    }
+
+    /// Check the sequence numbers of an incoming SyncBatch.
+    ///
+    /// When a subscription skips a sequence number, a batch was lost (e.g. a
+    /// send failed during reconnect) and our copy of that component type may be
+    /// stale, so we resubscribe to get a fresh snapshot.
+    pub(crate) fn check_sync_sequences(&self, sequences: &[SubscriptionSequence]) {
+        let gaps = sequence_gaps(&mut self.sync_sequences.lock().unwrap(), sequences);
+        for subscription_id in gaps {
+            self.resubscribe(subscription_id);
+        }
+    }
+
+    /// Record the server session from a Welcome message.
+    ///
+    /// After a server restart the received entities, and the connection ids
+    /// in their `EntityControl`s, belong to the previous run, so drop them and
+    /// wait for fresh snapshots. The new server doesn't know our reliable
+    /// session either, so unacknowledged reliable messages move to a new one.
+    pub(crate) fn start_server_session(&self, session: u64) {
+        let previous = self.server_session.lock().unwrap().replace(session);
+        if previous.is_some_and(|previous| previous != session) {
+            self.component_data.set(HashMap::new());
+            self.reliable.lock().unwrap().restart();
+        }
+    }
+
+    /// Forget all sequence numbers (the server restarts them for a new connection).
+    pub(crate) fn reset_sync_sequences(&self) {
+        self.sync_sequences.lock().unwrap().clear();
+    }
+
+    /// Unsubscribe and subscribe again so the server sends a fresh snapshot.
+    fn resubscribe(&self, subscription_id: u64) {
+        let subscription_key = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .key_for_id(subscription_id)
+            .map(str::to_string);
+        let Some(subscription_key) = subscription_key else {
+            return;
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        leptos::logging::warn!(
+            "[SyncContext] Missed sync batch for '{}' (subscription {}), resubscribing",
+            subscription_key,
+            subscription_id
+        );
+
+        self.send_unsubscribe_request(subscription_id);
+        self.send_subscription_request(&subscription_key, None);
+    }
+
     /// Handle incoming component update from the server.
     ///
     /// This deserializes the component data and updates the appropriate signal.
@@ -898,6 +1137,7 @@ impl SyncContext {
             entity: SerializableEntity { bits: entity_id },
             component_type: component_name.to_string(),
             value: value_bytes,
+            idempotency_key: Some(random_u64()),
         });
 
         // Serialize and send
@@ -921,6 +1161,76 @@ impl SyncContext {
         request_id
     }
 
+    /// Undo the most recent mutation on an entity.
+    ///
+    /// Only components registered with `.with_undo()` on the server record
+    /// history. The undo is tracked like a mutation: the returned request_id
+    /// can be looked up in [`mutations`](Self::mutations).
+    pub fn undo(&self, entity_id: u64) -> u64 {
+        self.send_history_request(|request_id| {
+            SyncClientMessage::Undo(UndoMutation {
+                request_id: Some(request_id),
+                entity: SerializableEntity { bits: entity_id },
+                idempotency_key: Some(random_u64()),
+            })
+        })
+    }
+
+    /// Redo the most recently undone mutation on an entity.
+    ///
+    /// Returns the request_id used to track the redo.
+    pub fn redo(&self, entity_id: u64) -> u64 {
+        self.send_history_request(|request_id| {
+            SyncClientMessage::Redo(RedoMutation {
+                request_id: Some(request_id),
+                entity: SerializableEntity { bits: entity_id },
+                idempotency_key: Some(random_u64()),
+            })
+        })
+    }
+
+    fn send_history_request(&self, build: impl FnOnce(u64) -> SyncClientMessage) -> u64 {
+        let request_id = {
+            let mut next_id = self.next_request_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+
+        self.mutations.update(|map| {
+            map.insert(request_id, MutationState::new_pending(request_id));
+        });
+
+        if let Ok(bytes) = bincode::serde::encode_to_vec(build(request_id), bincode::config::standard()) {
+            (self.send)(&bytes);
+        } else {
+            self.mutations.update(|map| {
+                if let Some(state) = map.get_mut(&request_id) {
+                    state.status = Some(MutationStatus::InternalError);
+                    state.message = Some("Failed to serialize message".to_string());
+                }
+            });
+        }
+
+        request_id
+    }
+
+    /// Record a state machine transition announced by the server.
+    pub(crate) fn handle_state_transition(&self, transition: StateTransition) {
+        #[cfg(target_arch = "wasm32")]
+        leptos::logging::log!(
+            "[SyncContext] {} on entity {} changed state",
+            transition.component_type,
+            transition.entity.bits
+        );
+
+        self.state_transitions.update(|map| {
+            map.insert(
+                (transition.entity.bits, transition.component_type.clone()),
+                transition,
+            );
+        });
+    }
+
     /// Handle a mutation response from the server.
     ///
     /// This is called by the provider when a MutationResponse is received.
@@ -932,11 +1242,13 @@ impl SyncContext {
                     .and_modify(|state| {
                         state.status = Some(response.status.clone());
                         state.message = response.message.clone();
+                        state.field_errors = response.field_errors.clone();
                     })
                     .or_insert_with(|| MutationState {
                         request_id,
                         status: Some(response.status.clone()),
                         message: response.message.clone(),
+                        field_errors: response.field_errors.clone(),
                     });
             });
 
@@ -955,28 +1267,48 @@ impl SyncContext {
     /// It increments the invalidation counter for each affected query type,
     /// which triggers any watching query hooks to refetch.
     pub(crate) fn handle_query_invalidation(&self, invalidation: &pl3xus_sync::QueryInvalidation) {
-        self.query_invalidations.update(|map| {
-            if invalidation.query_types.is_empty() {
-                // Empty query_types means invalidate ALL queries
-                for counter in map.values_mut() {
-                    *counter += 1;
-                }
-                #[cfg(target_arch = "wasm32")]
-                leptos::logging::log!("[SyncContext] Invalidated all queries");
-            } else {
-                // Invalidate specific query types
-                for query_type in &invalidation.query_types {
-                    let counter = map.entry(query_type.clone()).or_insert(0);
-                    *counter += 1;
-                    #[cfg(target_arch = "wasm32")]
-                    leptos::logging::log!(
-                        "[SyncContext] Invalidated query type '{}' (counter: {})",
-                        query_type,
-                        *counter
-                    );
-                }
-            }
-        });
+        self.query_invalidations
+            .update(|map| pl3xus_client_core::apply_invalidation(map, invalidation));
+        #[cfg(target_arch = "wasm32")]
+        leptos::logging::log!("[SyncContext] Invalidated queries {:?}", invalidation.query_types);
+    }
+
+    /// Reset the server clock estimate and latency tracking, and send a burst
+    /// of clock pings to the server.
+    ///
+    /// Called by the provider when the Welcome message arrives.
+    pub(crate) fn start_clock_sync(&self) {
+        self.latency.try_update_untracked(|tracker| tracker.reset());
+        self.latency.notify();
+        self.send_clock_pings(CLOCK_SYNC_PROBES);
+    }
+
+    /// Get the latency tracker signal.
+    ///
+    /// Use [`use_latency`](crate::use_latency) in components instead.
+    pub fn latency(&self) -> ReadSignal<LatencyTracker> {
+        self.latency.read_only()
+    }
+
+    /// Send `count` clock pings to the server.
+    ///
+    /// They are answered by `ClockSyncServerPlugin`, which `Pl3xusSyncPlugin` adds.
+    pub(crate) fn send_clock_pings(&self, count: usize) {
+        for _ in 0..count {
+            self.send(LatencyTracker::clock_ping());
+        }
+    }
+
+    /// Update the server clock estimate from a `ClockPong`.
+    pub(crate) fn handle_clock_pong(&self, pong: &pl3xus_common::ClockPong) {
+        self.latency
+            .try_update_untracked(|tracker| tracker.handle_clock_pong(pong));
+        self.latency.notify();
+    }
+
+    /// Get the current server clock estimate, if synchronized (tracked).
+    pub fn server_clock_estimate(&self) -> Option<pl3xus_common::ClockEstimate> {
+        self.latency.with(|tracker| tracker.clock_offset())
     }
 
     /// Get the invalidation counter for a specific query type.
@@ -1007,65 +1339,28 @@ impl SyncContext {
         query_type: &str,
         query_key: &str,
     ) -> ArcRwSignal<QueryCacheState> {
-        let mut cache = self.query_cache.lock().unwrap();
-        let key = (query_type.to_string(), query_key.to_string());
-
-        if let Some(entry) = cache.get_mut(&key) {
-            entry.ref_count += 1;
-            #[cfg(target_arch = "wasm32")]
-            leptos::logging::log!(
-                "[QueryCache] Reusing cached query '{}' (key: '{}', refs: {})",
-                query_type,
-                query_key,
-                entry.ref_count
-            );
-            entry.state.clone()
-        } else {
-            let state = ArcRwSignal::new(QueryCacheState::default());
-            let invalidation_counter = self.query_invalidation_counter(query_type);
-            cache.insert(
-                key,
-                QueryCacheEntry {
-                    state: state.clone(),
-                    ref_count: 1,
-                    last_invalidation: invalidation_counter,
-                },
-            );
-            #[cfg(target_arch = "wasm32")]
-            leptos::logging::log!(
-                "[QueryCache] Created new query '{}' (key: '{}')",
-                query_type,
-                query_key
-            );
-            state
-        }
+        let invalidation_counter = self.query_invalidation_counter(query_type);
+        #[cfg(target_arch = "wasm32")]
+        leptos::logging::log!("[QueryCache] Acquired query '{}' (key: '{}')", query_type, query_key);
+        self.query_cache
+            .lock()
+            .unwrap()
+            .acquire(query_type, query_key, invalidation_counter, || {
+                ArcRwSignal::new(QueryCacheState::default())
+            })
     }
 
     /// Release a reference to a cached query.
     ///
     /// Decrements the reference count. When it reaches 0, the entry is removed.
     pub fn release_query_cache(&self, query_type: &str, query_key: &str) {
-        let mut cache = self.query_cache.lock().unwrap();
-        let key = (query_type.to_string(), query_key.to_string());
-
-        if let Some(entry) = cache.get_mut(&key) {
-            entry.ref_count = entry.ref_count.saturating_sub(1);
+        if self.query_cache.lock().unwrap().release(query_type, query_key) {
             #[cfg(target_arch = "wasm32")]
             leptos::logging::log!(
-                "[QueryCache] Released query '{}' (key: '{}', refs: {})",
+                "[QueryCache] Removed query '{}' (key: '{}') - no more references",
                 query_type,
-                query_key,
-                entry.ref_count
+                query_key
             );
-            if entry.ref_count == 0 {
-                cache.remove(&key);
-                #[cfg(target_arch = "wasm32")]
-                leptos::logging::log!(
-                    "[QueryCache] Removed query '{}' (key: '{}') - no more references",
-                    query_type,
-                    query_key
-                );
-            }
         }
     }
 
@@ -1073,19 +1368,98 @@ impl SyncContext {
     ///
     /// Returns true if the invalidation counter has increased since the last check.
     pub fn query_needs_refetch(&self, query_type: &str, query_key: &str) -> bool {
-        let mut cache = self.query_cache.lock().unwrap();
-        let key = (query_type.to_string(), query_key.to_string());
         let current_counter = self.query_invalidation_counter(query_type);
+        self.query_cache
+            .lock()
+            .unwrap()
+            .needs_refetch(query_type, query_key, current_counter)
+    }
+
+    /// Subscribe to live query `Q` on the server.
+    ///
+    /// Returns the query id under which [`live_queries`](Self::live_queries)
+    /// holds the result. The request is sent whenever the WebSocket opens, so
+    /// the subscription survives reconnects; pass the id to
+    /// [`cancel_live_query`](Self::cancel_live_query) when done. Most code
+    /// should use [`use_live_query`](crate::use_live_query) instead.
+    pub fn subscribe_live_query<Q: LiveQuery>(&self, query: Q) -> u64 {
+        let query_id = {
+            let mut next_id = self.next_request_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+        self.live_queries.update(|map| {
+            map.insert(query_id, LiveQueryRows::default());
+        });
+
+        let ctx = self.clone();
+        let ready_state = self.ready_state;
+        Effect::new(move |_| {
+            if ready_state.get() == ConnectionReadyState::Open {
+                // The server sends the whole result again on subscribe
+                let subscribed = ctx.live_queries.try_update_untracked(|map| match map.get_mut(&query_id) {
+                    Some(rows) => {
+                        rows.reset();
+                        true
+                    }
+                    None => false,
+                });
+                if subscribed != Some(true) {
+                    return;
+                }
+                ctx.live_queries.notify();
+
+                #[cfg(target_arch = "wasm32")]
+                leptos::logging::log!("[SyncContext] Subscribing to live query {} ({})", Q::short_name(), query_id);
 
-        if let Some(entry) = cache.get_mut(&key) {
-            if current_counter > entry.last_invalidation {
-                entry.last_invalidation = current_counter;
-                true
-            } else {
-                false
+                let message = live_query_request(query_id, &query);
+                if let Ok(bytes) = bincode::serde::encode_to_vec(&message, bincode::config::standard()) {
+                    (ctx.send)(&bytes);
+                }
             }
-        } else {
-            false
+        });
+
+        query_id
+    }
+
+    /// Stop a live query started with [`subscribe_live_query`](Self::subscribe_live_query).
+    pub fn cancel_live_query(&self, query_id: u64) {
+        let removed = self.live_queries.try_update(|map| map.remove(&query_id).is_some());
+        if removed != Some(true) {
+            return;
+        }
+        let message = SyncClientMessage::QueryCancel(QueryCancel { query_id });
+        if let Ok(bytes) = bincode::serde::encode_to_vec(&message, bincode::config::standard()) {
+            (self.send)(&bytes);
+        }
+    }
+
+    /// Results of every live query, by query id.
+    pub fn live_queries(&self) -> ReadSignal<HashMap<u64, LiveQueryRows>> {
+        self.live_queries.read_only()
+    }
+
+    /// Apply row changes pushed by the server to a live query.
+    pub(crate) fn handle_live_query_update(&self, update: &LiveQueryUpdate) {
+        self.live_queries.update(|map| {
+            if let Some(rows) = map.get_mut(&update.query_id) {
+                rows.apply(update);
+            }
+        });
+    }
+
+    /// Handle a query response from the server.
+    ///
+    /// Live queries only get one when the server couldn't run them.
+    pub(crate) fn handle_query_response(&self, response: &QueryResponse) {
+        self.live_queries.update(|map| {
+            if let Some(rows) = map.get_mut(&response.query_id) {
+                rows.fail(response);
+            }
+        });
+        #[cfg(target_arch = "wasm32")]
+        if response.error.is_some() {
+            leptos::logging::warn!("[SyncContext] Query {} failed: {:?}", response.query_id, response.error);
         }
     }
 
@@ -1334,6 +1708,38 @@ impl SyncContext {
     /// }
     /// ```
     pub fn request<R>(&self, request: R) -> u64
+    where
+        R: pl3xus_common::RequestMessage,
+    {
+        self.send_request(request, None)
+    }
+
+    /// Send a request carrying an idempotency key.
+    ///
+    /// Reuse the key when retrying the same logical operation (e.g. after a
+    /// timeout): the server runs the handler once and answers retries with the
+    /// original response. Generate keys with [`new_idempotency_key`](Self::new_idempotency_key).
+    pub fn request_with_key<R>(&self, request: R, idempotency_key: u64) -> u64
+    where
+        R: pl3xus_common::RequestMessage,
+    {
+        self.send_request(request, Some(idempotency_key))
+    }
+
+    /// Generate a fresh idempotency key.
+    pub fn new_idempotency_key() -> u64 {
+        random_u64()
+    }
+
+    /// Scope a request's idempotency key to this client.
+    fn idempotency_key(&self, key: Option<u64>) -> Option<pl3xus_common::IdempotencyKey> {
+        key.map(|key| pl3xus_common::IdempotencyKey {
+            client: self.idempotency_client,
+            key,
+        })
+    }
+
+    fn send_request<R>(&self, request: R, idempotency_key: Option<u64>) -> u64
     where
         R: pl3xus_common::RequestMessage,
     {
@@ -1345,6 +1751,7 @@ impl SyncContext {
         struct RequestInternal<T> {
             id: u64,
             request: T,
+            idempotency_key: Option<pl3xus_common::IdempotencyKey>,
         }
 
         // Generate unique request ID
@@ -1369,6 +1776,7 @@ impl SyncContext {
         let wrapped = RequestInternal {
             id: request_id,
             request,
+            idempotency_key: self.idempotency_key(idempotency_key),
         };
 
         // Create NetworkPacket with the RequestInternal type name
@@ -1461,6 +1869,23 @@ impl SyncContext {
     /// }
     /// ```
     pub fn targeted_request<R>(&self, entity_bits: u64, request: R) -> u64
+    where
+        R: pl3xus_common::RequestMessage,
+    {
+        self.send_targeted_request(entity_bits, request, None)
+    }
+
+    /// Send a targeted request carrying an idempotency key.
+    ///
+    /// See [`request_with_key`](Self::request_with_key).
+    pub fn targeted_request_with_key<R>(&self, entity_bits: u64, request: R, idempotency_key: u64) -> u64
+    where
+        R: pl3xus_common::RequestMessage,
+    {
+        self.send_targeted_request(entity_bits, request, Some(idempotency_key))
+    }
+
+    fn send_targeted_request<R>(&self, entity_bits: u64, request: R, idempotency_key: Option<u64>) -> u64
     where
         R: pl3xus_common::RequestMessage,
     {
@@ -1479,6 +1904,7 @@ impl SyncContext {
         struct RequestInternal<T> {
             id: u64,
             request: T,
+            idempotency_key: Option<pl3xus_common::IdempotencyKey>,
         }
 
         // Generate unique request ID
@@ -1509,6 +1935,7 @@ impl SyncContext {
         let wrapped = RequestInternal {
             id: request_id,
             request: targeted,
+            idempotency_key: self.idempotency_key(idempotency_key),
         };
 
         // Create NetworkPacket with the RequestInternal<TargetedRequest<R>> type name
@@ -1582,6 +2009,29 @@ impl SyncContext {
         leptos::logging::log!("[SyncContext] Request {} received response", response_id);
     }
 
+    /// Handle the server rejecting a payload over its size limit.
+    ///
+    /// Fails the request it belongs to, if any, instead of leaving it to time out.
+    pub(crate) fn handle_payload_too_large(&self, rejection: &pl3xus_common::PayloadTooLarge) {
+        leptos::logging::warn!(
+            "[SyncContext] Server rejected {}: {} bytes exceeds the limit of {}",
+            rejection.type_name,
+            rejection.size,
+            rejection.limit
+        );
+        let Some(request_id) = rejection.request_id else {
+            return;
+        };
+        self.requests.update(|map| {
+            if let Some(state) = map.get_mut(&request_id) {
+                state.status = RequestStatus::Error(format!(
+                    "Too large: {} bytes (limit {})",
+                    rejection.size, rejection.limit
+                ));
+            }
+        });
+    }
+
     /// Get a read-only signal for tracking request states.
     pub fn requests(&self) -> ReadSignal<HashMap<u64, RequestState>> {
         self.requests.read_only()
@@ -1789,3 +2239,44 @@ impl SyncContext {
     }
 }
 
+/// Record the sequence numbers of a `SyncBatch` and return the subscriptions
+/// that skipped at least one batch.
+fn sequence_gaps(last_seen: &mut HashMap<u64, u64>, sequences: &[SubscriptionSequence]) -> Vec<u64> {
+    sequences
+        .iter()
+        .filter(|seq| {
+            // Sequences start at 1 for each connection, so a missing
+            // entry means nothing has been seen yet.
+            let previous = last_seen.insert(seq.subscription_id, seq.sequence).unwrap_or(0);
+            seq.sequence > previous + 1
+        })
+        .map(|seq| seq.subscription_id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seq(subscription_id: u64, sequence: u64) -> SubscriptionSequence {
+        SubscriptionSequence {
+            subscription_id,
+            sequence,
+        }
+    }
+
+    #[test]
+    fn test_sequence_gaps() {
+        let mut last_seen = HashMap::new();
+        assert!(sequence_gaps(&mut last_seen, &[seq(1, 1), seq(2, 1)]).is_empty());
+        assert!(sequence_gaps(&mut last_seen, &[seq(1, 2)]).is_empty());
+
+        // Subscription 2 missed batch 2; subscription 1 is still in order
+        assert_eq!(sequence_gaps(&mut last_seen, &[seq(1, 3), seq(2, 3)]), [2]);
+        // The gap is only reported once
+        assert!(sequence_gaps(&mut last_seen, &[seq(2, 4)]).is_empty());
+
+        // The first batch seen for a subscription must be batch 1
+        assert_eq!(sequence_gaps(&mut last_seen, &[seq(3, 2)]), [3]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Request/message console for DevTools.
+//!
+//! Sends any type registered with `register_request` / `register_message` on
+//! the [`ClientTypeRegistry`] from a JSON payload, and matches responses back
+//! to the request that produced them.
+
+use pl3xus_common::NetworkPacket;
+use serde_json::Value as JsonValue;
+use std::collections::VecDeque;
+
+use crate::client_type_registry::{ClientTypeRegistry, ConsoleMessageKind, ConsoleType};
+
+/// Number of sent messages kept in the console history.
+const CONSOLE_HISTORY_LEN: usize = 50;
+
+/// A message sent from the console.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsoleEntry {
+    pub request_id: u64,
+    pub type_name: String,
+    pub kind: ConsoleMessageKind,
+    pub payload: JsonValue,
+    /// Client wall-clock time the message was sent (ms since the Unix epoch).
+    pub sent_at_ms: f64,
+    /// Typed response as JSON, for requests that have been answered.
+    pub response: Option<Result<JsonValue, String>>,
+    pub received_at_ms: Option<f64>,
+}
+
+impl ConsoleEntry {
+    /// Round trip time, once the response has arrived.
+    pub fn round_trip_ms(&self) -> Option<f64> {
+        self.received_at_ms.map(|received| received - self.sent_at_ms)
+    }
+}
+
+/// Sent console messages, newest last.
+#[derive(Clone, Debug, Default)]
+pub struct ConsoleLog {
+    entries: VecDeque<ConsoleEntry>,
+    next_request_id: u64,
+}
+
+impl ConsoleLog {
+    pub fn entries(&self) -> &VecDeque<ConsoleEntry> {
+        &self.entries
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Encode `payload` as `console_type` and record it. Returns the packet to
+    /// send, or why the payload doesn't fit the type.
+    pub fn send(
+        &mut self,
+        console_type: &ConsoleType,
+        payload: JsonValue,
+        now_ms: f64,
+    ) -> Result<NetworkPacket, String> {
+        self.next_request_id += 1;
+        let request_id = self.next_request_id;
+        let packet = console_type.encode(&payload, request_id)?;
+
+        if self.entries.len() == CONSOLE_HISTORY_LEN {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(ConsoleEntry {
+            request_id,
+            type_name: console_type.name.clone(),
+            kind: console_type.kind,
+            payload,
+            sent_at_ms: now_ms,
+            response: None,
+            received_at_ms: None,
+        });
+        Ok(packet)
+    }
+
+    /// Match a `ResponseInternal<..>` packet to the request that produced it.
+    ///
+    /// Returns false if the packet isn't a response to a console request.
+    pub fn handle_response(&mut self, registry: &ClientTypeRegistry, packet: &NetworkPacket, now_ms: f64) -> bool {
+        if !packet.type_name.contains("ResponseInternal<") {
+            return false;
+        }
+        // ResponseInternal is { response_id: u64, response: T }
+        let Ok((response_id, header_len)) =
+            bincode::serde::decode_from_slice::<u64, _>(&packet.data, bincode::config::standard())
+        else {
+            return false;
+        };
+        let Some(entry) = self.entries.iter_mut().find(|entry| {
+            entry.request_id == response_id && entry.kind == ConsoleMessageKind::Request && entry.response.is_none()
+        }) else {
+            return false;
+        };
+
+        let response = match registry.console_type(&entry.type_name) {
+            Some(console_type) => console_type.decode_response(&packet.data[header_len..]),
+            None => Err(format!("{} is not registered", entry.type_name)),
+        };
+        entry.response = Some(response);
+        entry.received_at_ms = Some(now_ms);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_common::RequestMessage;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct ListPrograms {
+        limit: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct ProgramList {
+        names: Vec<String>,
+    }
+
+    impl RequestMessage for ListPrograms {
+        type ResponseMessage = ProgramList;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ResponseInternal<T> {
+        response_id: u64,
+        response: T,
+    }
+
+    #[test]
+    fn test_send_request_and_match_response() {
+        let registry = ClientTypeRegistry::builder()
+            .register_request::<ListPrograms>()
+            .with_devtools_support()
+            .build();
+        let console_type = registry.console_type("ListPrograms").unwrap().clone();
+        assert_eq!(console_type.default_payload, serde_json::json!({ "limit": 0 }));
+
+        let mut log = ConsoleLog::default();
+        assert!(log.send(&console_type, serde_json::json!({ "limit": "ten" }), 0.0).is_err());
+        let packet = log.send(&console_type, serde_json::json!({ "limit": 10 }), 100.0).unwrap();
+        assert!(packet.type_name.ends_with("RequestInternal<pl3xus_client::devtools::console::tests::ListPrograms>"));
+        let request_id = log.entries()[0].request_id;
+
+        let response = ResponseInternal {
+            response_id: request_id,
+            response: ProgramList { names: vec!["weld".into()] },
+        };
+        let response_packet = NetworkPacket {
+            type_name: "pl3xus::managers::network_request::ResponseInternal<ProgramList>".into(),
+            schema_hash: 0,
+            data: bincode::serde::encode_to_vec(&response, bincode::config::standard()).unwrap(),
+        };
+        assert!(log.handle_response(&registry, &response_packet, 150.0));
+
+        let entry = &log.entries()[0];
+        assert_eq!(entry.response, Some(Ok(serde_json::json!({ "names": ["weld"] }))));
+        assert_eq!(entry.round_trip_ms(), Some(50.0));
+        // Already answered
+        assert!(!log.handle_response(&registry, &response_packet, 200.0));
+    }
+}
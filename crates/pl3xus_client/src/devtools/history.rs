@@ -0,0 +1,240 @@
+//! Component history for the DevTools timeline.
+//!
+//! Keeps a bounded ring buffer of recent values per `(entity, component)` so
+//! the inspector can chart numeric fields over time (e.g. `RobotPosition.x`)
+//! and export them to CSV when debugging oscillations or sync lag.
+
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Samples kept per `(entity, component)` unless configured otherwise.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 500;
+
+/// One recorded component value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistorySample {
+    /// Client wall-clock time the value was received (ms since the Unix epoch).
+    pub timestamp_ms: f64,
+    pub value: JsonValue,
+}
+
+/// Ring buffers of recent component values, keyed by entity bits and
+/// component type name.
+#[derive(Clone, Debug)]
+pub struct ComponentHistory {
+    capacity: usize,
+    samples: HashMap<(u64, String), VecDeque<HistorySample>>,
+}
+
+impl Default for ComponentHistory {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl ComponentHistory {
+    /// Create a history keeping at most `capacity` samples per component.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Record a new value, dropping the oldest sample when full.
+    pub fn record(&mut self, entity: u64, component_type: &str, timestamp_ms: f64, value: JsonValue) {
+        let buffer = self
+            .samples
+            .entry((entity, component_type.to_string()))
+            .or_default();
+        if buffer.len() == self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(HistorySample { timestamp_ms, value });
+    }
+
+    /// Samples for one component, oldest first.
+    pub fn samples(&self, entity: u64, component_type: &str) -> Option<&VecDeque<HistorySample>> {
+        self.samples.get(&(entity, component_type.to_string()))
+    }
+
+    /// Forget one component's history.
+    pub fn remove_component(&mut self, entity: u64, component_type: &str) {
+        self.samples.remove(&(entity, component_type.to_string()));
+    }
+
+    /// Forget all history for an entity.
+    pub fn remove_entity(&mut self, entity: u64) {
+        self.samples.retain(|(bits, _), _| *bits != entity);
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Dotted paths of the numeric fields in the latest sample, e.g. `["x", "y", "z"]`.
+    ///
+    /// A component that is itself a number yields a single empty path.
+    pub fn numeric_fields(&self, entity: u64, component_type: &str) -> Vec<String> {
+        self.samples(entity, component_type)
+            .and_then(|samples| samples.back())
+            .map(|latest| {
+                flatten(&latest.value)
+                    .into_iter()
+                    .filter(|(_, value)| value.is_number())
+                    .map(|(path, _)| path)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// `(timestamp_ms, value)` points for one numeric field.
+    ///
+    /// Samples where the field is missing or not a number are skipped.
+    pub fn series(&self, entity: u64, component_type: &str, field: &str) -> Vec<(f64, f64)> {
+        self.samples(entity, component_type)
+            .map(|samples| {
+                samples
+                    .iter()
+                    .filter_map(|sample| {
+                        lookup(&sample.value, field)
+                            .and_then(JsonValue::as_f64)
+                            .map(|value| (sample.timestamp_ms, value))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Export one component's history as CSV.
+    ///
+    /// The first column is `timestamp_ms`, followed by one column per leaf
+    /// field seen in any sample (objects and arrays are flattened to dotted
+    /// paths).
+    pub fn to_csv(&self, entity: u64, component_type: &str) -> String {
+        let Some(samples) = self.samples(entity, component_type) else {
+            return "timestamp_ms\n".to_string();
+        };
+
+        let rows: Vec<BTreeMap<String, JsonValue>> = samples.iter().map(|s| flatten(&s.value)).collect();
+        let mut columns: Vec<String> = rows.iter().flat_map(|row| row.keys().cloned()).collect();
+        columns.sort();
+        columns.dedup();
+
+        let mut csv = String::from("timestamp_ms");
+        for column in &columns {
+            csv.push(',');
+            csv.push_str(&csv_field(if column.is_empty() { "value" } else { column }));
+        }
+        csv.push('\n');
+
+        for (sample, row) in samples.iter().zip(&rows) {
+            csv.push_str(&sample.timestamp_ms.to_string());
+            for column in &columns {
+                csv.push(',');
+                match row.get(column) {
+                    Some(JsonValue::String(s)) => csv.push_str(&csv_field(s)),
+                    Some(value) => csv.push_str(&csv_field(&value.to_string())),
+                    None => {}
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Flatten a JSON value to `dotted.path -> leaf` pairs. Array elements use
+/// their index as the path segment.
+fn flatten(value: &JsonValue) -> BTreeMap<String, JsonValue> {
+    fn walk(prefix: &str, value: &JsonValue, out: &mut BTreeMap<String, JsonValue>) {
+        let join = |segment: &str| {
+            if prefix.is_empty() {
+                segment.to_string()
+            } else {
+                format!("{}.{}", prefix, segment)
+            }
+        };
+        match value {
+            JsonValue::Object(fields) => {
+                for (name, field) in fields {
+                    walk(&join(name), field, out);
+                }
+            }
+            JsonValue::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    walk(&join(&i.to_string()), item, out);
+                }
+            }
+            leaf => {
+                out.insert(prefix.to_string(), leaf.clone());
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk("", value, &mut out);
+    out
+}
+
+fn lookup<'a>(value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.').try_fold(value, |current, segment| match current {
+        JsonValue::Object(fields) => fields.get(segment),
+        JsonValue::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let mut history = ComponentHistory::with_capacity(2);
+        for i in 0..3 {
+            history.record(1, "RobotPosition", i as f64, json!({ "x": i }));
+        }
+        let samples = history.samples(1, "RobotPosition").unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].timestamp_ms, 1.0);
+    }
+
+    #[test]
+    fn test_numeric_fields_and_series() {
+        let mut history = ComponentHistory::default();
+        history.record(1, "RobotPosition", 10.0, json!({ "x": 1.5, "frame": "world", "joints": [0.1, 0.2] }));
+        history.record(1, "RobotPosition", 20.0, json!({ "x": 2.5, "frame": "world", "joints": [0.3, 0.4] }));
+
+        assert_eq!(history.numeric_fields(1, "RobotPosition"), vec!["joints.0", "joints.1", "x"]);
+        assert_eq!(history.series(1, "RobotPosition", "x"), vec![(10.0, 1.5), (20.0, 2.5)]);
+        assert_eq!(history.series(1, "RobotPosition", "joints.1"), vec![(10.0, 0.2), (20.0, 0.4)]);
+        assert!(history.series(1, "RobotPosition", "frame").is_empty());
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut history = ComponentHistory::default();
+        history.record(1, "Status", 1.0, json!({ "speed": 3, "label": "a,b" }));
+        history.record(1, "Status", 2.0, json!({ "speed": 4 }));
+        history.record(2, "Count", 5.0, json!(7));
+
+        assert_eq!(history.to_csv(1, "Status"), "timestamp_ms,label,speed\n1,\"a,b\",3\n2,,4\n");
+        assert_eq!(history.to_csv(2, "Count"), "timestamp_ms,value\n5,7\n");
+
+        history.remove_entity(1);
+        assert!(history.samples(1, "Status").is_none());
+    }
+}
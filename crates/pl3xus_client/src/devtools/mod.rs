@@ -8,6 +8,9 @@
 //! - Hierarchical entity inspector
 //! - Real-time component editing with mutations
 //! - Controlled input pattern (prevents server updates during editing)
+//! - Component history timeline with numeric charting and CSV export
+//! - Console for sending registered requests/messages as JSON
+//! - Schema validation of the type registry against the server on connect
 //! - Type registry for JSON serialization/deserialization
 //!
 //! ## Usage
@@ -36,12 +39,18 @@
 //! }
 //! ```
 
+mod console;
+mod history;
+mod schema_check;
 mod sync;
 
 #[cfg(target_arch = "wasm32")]
 mod ui;
 
 // Re-export public API
+pub use console::{ConsoleEntry, ConsoleLog};
+pub use history::{ComponentHistory, HistorySample, DEFAULT_HISTORY_CAPACITY};
+pub use schema_check::SchemaCheck;
 pub use sync::{DevtoolsSync, use_sync, MutationState};
 
 #[cfg(target_arch = "wasm32")]
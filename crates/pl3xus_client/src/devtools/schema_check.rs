@@ -0,0 +1,97 @@
+//! Schema validation for DevTools.
+//!
+//! On connect the DevTools send a `DescribeSchema` request and compare the
+//! server's registered types against the [`ClientTypeRegistry`], so a
+//! mismatched registration shows up as a warning instead of components that
+//! silently fail to decode.
+
+use pl3xus_common::{DescribeSchema, NetworkPacket, SchemaDescription};
+
+use crate::client_type_registry::{request_packet, ClientTypeRegistry, SchemaMismatch};
+
+/// Request id of the DevTools' own `DescribeSchema` request. Console requests
+/// count up from 1, so the two never collide.
+const DESCRIBE_SCHEMA_REQUEST_ID: u64 = 0;
+
+/// Outcome of comparing the registry against the server's schema.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SchemaCheck {
+    /// Not connected, or waiting for the server's response.
+    #[default]
+    Pending,
+    /// The server answered; empty if everything matches.
+    Checked(Vec<SchemaMismatch>),
+}
+
+impl SchemaCheck {
+    /// The `DescribeSchema` request to send once connected.
+    pub fn request_packet() -> NetworkPacket {
+        request_packet(DescribeSchema, DESCRIBE_SCHEMA_REQUEST_ID).expect("DescribeSchema always encodes")
+    }
+
+    /// Compare the registry against a `DescribeSchema` response.
+    ///
+    /// Returns false if the packet isn't the response to [`Self::request_packet`].
+    pub fn handle_response(&mut self, registry: &ClientTypeRegistry, packet: &NetworkPacket) -> bool {
+        if !packet.type_name.contains("ResponseInternal<") {
+            return false;
+        }
+        // ResponseInternal is { response_id: u64, response: T }
+        let Ok(((response_id, description), _)) = bincode::serde::decode_from_slice::<(u64, SchemaDescription), _>(
+            &packet.data,
+            bincode::config::standard(),
+        ) else {
+            return false;
+        };
+        if response_id != DESCRIBE_SCHEMA_REQUEST_ID {
+            return false;
+        }
+        *self = SchemaCheck::Checked(registry.validate_schema(&description));
+        true
+    }
+
+    /// Mismatches found, if the check has completed.
+    pub fn mismatches(&self) -> &[SchemaMismatch] {
+        match self {
+            SchemaCheck::Pending => &[],
+            SchemaCheck::Checked(mismatches) => mismatches,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_common::TypeSchema;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Battery {
+        level: f32,
+    }
+
+    #[test]
+    fn test_handle_describe_schema_response() {
+        let registry = ClientTypeRegistry::builder().register::<Battery>().build();
+        let request = SchemaCheck::request_packet();
+        assert!(request.type_name.ends_with("RequestInternal<pl3xus_common::schema::DescribeSchema>"));
+
+        let description = SchemaDescription {
+            components: vec![TypeSchema::of::<Battery>("Battery")],
+            ..Default::default()
+        };
+        let response = |response_id: u64| NetworkPacket {
+            type_name: "pl3xus::managers::network_request::ResponseInternal<SchemaDescription>".into(),
+            schema_hash: 0,
+            data: bincode::serde::encode_to_vec((response_id, &description), bincode::config::standard()).unwrap(),
+        };
+
+        let mut check = SchemaCheck::default();
+        // A console response, not ours
+        assert!(!check.handle_response(&registry, &response(3)));
+        assert_eq!(check, SchemaCheck::Pending);
+
+        assert!(check.handle_response(&registry, &response(DESCRIBE_SCHEMA_REQUEST_ID)));
+        assert_eq!(check, SchemaCheck::Checked(Vec::new()));
+    }
+}
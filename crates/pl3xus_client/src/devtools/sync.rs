@@ -10,6 +10,9 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::client_type_registry::ClientTypeRegistry;
+use crate::latency::{LatencyTracker, CLOCK_SYNC_PROBES};
+
+use pl3xus_common::{ClockPong, NetworkPacket, Pl3xusMessage};
 
 use pl3xus_sync::{
     MutateComponent,
@@ -48,6 +51,7 @@ pub struct DevtoolsSync {
     registry: Arc<ClientTypeRegistry>,
     mutations: RwSignal<HashMap<u64, MutationState>>,
     next_request_id: Arc<std::sync::Mutex<u64>>,
+    latency: RwSignal<LatencyTracker>,
 }
 
 /// General-purpose sync hook for wiring the pl3xus_sync wire protocol
@@ -69,6 +73,7 @@ pub fn use_sync(
         registry,
         mutations: RwSignal::new(HashMap::new()),
         next_request_id: Arc::new(std::sync::Mutex::new(1)),
+        latency: RwSignal::new(LatencyTracker::default()),
     }
 }
 
@@ -86,6 +91,11 @@ impl DevtoolsSync {
         self.mutations
     }
 
+    /// Clock offset estimate and per-component end-to-end latency.
+    pub fn latency(&self) -> RwSignal<LatencyTracker> {
+        self.latency
+    }
+
     /// Convenience accessor for a single mutation state, if known.
     pub fn mutation_state(&self, request_id: u64) -> Option<MutationState> {
         self.mutations.get().get(&request_id).cloned()
@@ -119,6 +129,7 @@ impl DevtoolsSync {
                     entity,
                     component_type,
                     value: value_bytes,
+                    idempotency_key: None,
                 });
                 (self.send)(message);
 
@@ -145,19 +156,50 @@ impl DevtoolsSync {
     }
 
     /// Handle a server-side message, updating mutation state when a
-    /// `MutationResponse` is observed.
+    /// `MutationResponse` is observed and latency tracking for timestamped
+    /// batches.
+    ///
+    /// A `Welcome` resets latency tracking; the transport is expected to send
+    /// clock pings and pass the replies to [`handle_clock_pong`](Self::handle_clock_pong).
     pub fn handle_server_message(&self, message: &SyncServerMessage) {
-        // Update reactive state for MutationResponse
-        if let SyncServerMessage::MutationResponse(response) = message {
-            if let Some(request_id) = response.request_id {
-                self.mutations.update(|map| {
-                    if let Some(state) = map.get_mut(&request_id) {
-                        state.status = Some(response.status.clone());
-                        state.message = response.message.clone();
-                    }
-                });
+        match message {
+            SyncServerMessage::MutationResponse(response) => self.handle_mutation_response(response),
+            SyncServerMessage::Welcome(_) => {
+                self.latency.update(|tracker| tracker.reset());
+            }
+            SyncServerMessage::SyncBatch(batch) if batch.sent_at_ms.is_some() => {
+                self.latency.update(|tracker| tracker.record_batch(batch));
             }
+            _ => {}
+        }
+    }
+
+    /// Packets carrying the clock pings to send after each `Welcome`.
+    pub fn clock_ping_packets() -> Vec<NetworkPacket> {
+        (0..CLOCK_SYNC_PROBES)
+            .filter_map(|_| {
+                let ping = LatencyTracker::clock_ping();
+                let data = bincode::serde::encode_to_vec(ping, bincode::config::standard()).ok()?;
+                Some(NetworkPacket {
+                    type_name: pl3xus_common::ClockPing::type_name().to_string(),
+                    schema_hash: pl3xus_common::ClockPing::schema_hash(),
+                    data,
+                })
+            })
+            .collect()
+    }
+
+    /// Update the clock offset estimate if `packet` is a `ClockPong`.
+    ///
+    /// Returns false for any other packet.
+    pub fn handle_clock_pong(&self, packet: &NetworkPacket) -> bool {
+        if packet.type_name != ClockPong::type_name() {
+            return false;
+        }
+        if let Ok((pong, _)) = bincode::serde::decode_from_slice::<ClockPong, _>(&packet.data, bincode::config::standard()) {
+            self.latency.update(|tracker| tracker.handle_clock_pong(&pong));
         }
+        true
     }
 
     /// Helper to handle a `MutationResponse` directly, for cases where
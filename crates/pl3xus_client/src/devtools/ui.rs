@@ -5,8 +5,10 @@
 //! ## Features
 //!
 //! - **World Inspector**: Hierarchical entity/component browser with live editing
+//! - **History**: Per-component value timeline with numeric field charts and CSV export
 //! - **Query Explorer**: TanStack Query-style panel showing all active queries, their states, and cache
 //! - **Mutation Explorer**: Mutation history with pending/success/error states and timing
+//! - **Console**: Send any registered request/message as JSON and view the typed response
 //!
 //! ## Usage
 //!
@@ -20,9 +22,13 @@
 //! view! { <DevTools ws_url="ws://127.0.0.1:3000/sync" registry=registry app_context=Some(ctx) /> }
 //! ```
 
-use crate::client_type_registry::ClientTypeRegistry;
+use crate::client_type_registry::{ClientTypeRegistry, ConsoleMessageKind};
 use crate::context::SyncContext;
+use crate::devtools::console::ConsoleLog;
+use crate::devtools::history::ComponentHistory;
+use crate::devtools::schema_check::SchemaCheck;
 use crate::devtools::sync::{DevtoolsSync, use_sync};
+use crate::latency::now_ms;
 
 use pl3xus_common::codec::Pl3xusBincodeCodec;
 use pl3xus_common::NetworkPacket;
@@ -59,6 +65,8 @@ pub enum DevToolsTab {
     Queries,
     /// Mutation Explorer - Mutation history
     Mutations,
+    /// Console - Send registered requests/messages as JSON
+    Console,
 }
 
     fn entity_label(id: u64, components: &HashMap<String, JsonValue>) -> String {
@@ -435,6 +443,365 @@ pub enum DevToolsTab {
         }
     }
 
+    /// Chart width/height in SVG user units.
+    const CHART_WIDTH: f64 = 300.0;
+    const CHART_HEIGHT: f64 = 80.0;
+
+    /// Scale `(timestamp_ms, value)` points into an SVG polyline `points` string.
+    fn chart_points(series: &[(f64, f64)]) -> String {
+        let (Some(first), Some(last)) = (series.first(), series.last()) else {
+            return String::new();
+        };
+        let (min, max) = series
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (_, v)| (min.min(*v), max.max(*v)));
+        let time_span = (last.0 - first.0).max(1.0);
+        let value_span = if max > min { max - min } else { 1.0 };
+
+        series
+            .iter()
+            .map(|(t, v)| {
+                let x = (t - first.0) / time_span * CHART_WIDTH;
+                let y = CHART_HEIGHT - (v - min) / value_span * CHART_HEIGHT;
+                format!("{x:.1},{y:.1}")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// History panel for the selected entity: pick a component and one of its
+    /// numeric fields to chart recent values, or export the samples to CSV.
+    #[component]
+    fn ComponentHistoryPanel(
+        entity_bits: u64,
+        component_types: Memo<Vec<String>>,
+        history: RwSignal<ComponentHistory>,
+    ) -> impl IntoView {
+        let expanded = RwSignal::new(false);
+        let selected_component = RwSignal::new(None::<String>);
+        let selected_field = RwSignal::new(None::<String>);
+        let download_ref = NodeRef::<leptos::html::A>::new();
+
+        // Fall back to the first component / numeric field when nothing is picked
+        let component = Memo::new(move |_| {
+            selected_component
+                .get()
+                .filter(|ty| component_types.get().contains(ty))
+                .or_else(|| component_types.get().first().cloned())
+        });
+        let numeric_fields = Memo::new(move |_| {
+            component
+                .get()
+                .map(|ty| history.with(|h| h.numeric_fields(entity_bits, &ty)))
+                .unwrap_or_default()
+        });
+        let field = Memo::new(move |_| {
+            let fields = numeric_fields.get();
+            selected_field
+                .get()
+                .filter(|f| fields.contains(f))
+                .or_else(|| fields.first().cloned())
+        });
+        let series = Memo::new(move |_| match (component.get(), field.get()) {
+            (Some(ty), Some(f)) => history.with(|h| h.series(entity_bits, &ty, &f)),
+            _ => Vec::new(),
+        });
+
+        let export_csv = move |_| {
+            let Some(ty) = component.get_untracked() else {
+                return;
+            };
+            let csv = history.with_untracked(|h| h.to_csv(entity_bits, &ty));
+            if let Some(anchor) = download_ref.get_untracked() {
+                let href = format!("data:text/csv;charset=utf-8,{}", js_sys::encode_uri_component(&csv));
+                anchor.set_href(&href);
+                anchor.set_download(&format!("{}_{}.csv", ty, entity_bits));
+                anchor.click();
+            }
+        };
+
+        view! {
+            <div class="border-t border-slate-800 pt-3">
+                <button
+                    class="flex items-center justify-between w-full text-left group"
+                    on:click=move |_| expanded.update(|v| *v = !*v)
+                >
+                    <span class="text-[11px] uppercase tracking-wide text-slate-500">"History"</span>
+                    <span class="text-slate-400 text-xs group-hover:text-slate-300 transition-colors">
+                        {move || if expanded.get() { "▼" } else { "▶" }}
+                    </span>
+                </button>
+                <Show when=move || expanded.get() fallback=|| view! { <></> }>
+                    <div class="mt-2 space-y-2">
+                        <div class="flex items-center gap-2">
+                            <select
+                                class="flex-1 bg-slate-950/60 border border-slate-800 rounded px-1 py-0.5 text-[11px]"
+                                on:change=move |ev| {
+                                    selected_component.set(Some(event_target_value(&ev)));
+                                    selected_field.set(None);
+                                }
+                            >
+                                <For
+                                    each=move || component_types.get()
+                                    key=|ty: &String| ty.clone()
+                                    children=move |ty: String| {
+                                        let ty_for_selected = ty.clone();
+                                        view! {
+                                            <option
+                                                value=ty.clone()
+                                                selected=move || component.get().as_ref() == Some(&ty_for_selected)
+                                            >
+                                                {ty.clone()}
+                                            </option>
+                                        }
+                                    }
+                                />
+                            </select>
+                            <select
+                                class="flex-1 bg-slate-950/60 border border-slate-800 rounded px-1 py-0.5 text-[11px]"
+                                on:change=move |ev| selected_field.set(Some(event_target_value(&ev)))
+                            >
+                                <For
+                                    each=move || numeric_fields.get()
+                                    key=|f: &String| f.clone()
+                                    children=move |f: String| {
+                                        let f_for_selected = f.clone();
+                                        let label = if f.is_empty() { "value".to_string() } else { f.clone() };
+                                        view! {
+                                            <option
+                                                value=f.clone()
+                                                selected=move || field.get().as_ref() == Some(&f_for_selected)
+                                            >
+                                                {label}
+                                            </option>
+                                        }
+                                    }
+                                />
+                            </select>
+                            <button
+                                class="px-2 py-0.5 rounded bg-slate-800 hover:bg-slate-700 text-[11px] text-slate-200"
+                                on:click=export_csv
+                            >
+                                "Export CSV"
+                            </button>
+                            <a node_ref=download_ref class="hidden"></a>
+                        </div>
+                        <Show
+                            when=move || series.with(|s| s.len() >= 2)
+                            fallback=|| view! {
+                                <div class="text-[10px] text-slate-500">"Not enough numeric samples to chart yet."</div>
+                            }
+                        >
+                            <svg
+                                class="w-full h-20 bg-slate-950/60 border border-slate-800 rounded"
+                                viewBox=format!("0 0 {CHART_WIDTH} {CHART_HEIGHT}")
+                                preserveAspectRatio="none"
+                            >
+                                <polyline
+                                    fill="none"
+                                    stroke="currentColor"
+                                    stroke-width="1.5"
+                                    class="text-indigo-400"
+                                    points=move || series.with(|s| chart_points(s))
+                                ></polyline>
+                            </svg>
+                        </Show>
+                        <div class="flex items-center justify-between text-[10px] text-slate-500 font-mono">
+                            <span>
+                                {move || series.with(|s| {
+                                    let min = s.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+                                    let max = s.iter().map(|(_, v)| *v).fold(f64::NEG_INFINITY, f64::max);
+                                    if s.is_empty() { String::new() } else { format!("min {min:.3} · max {max:.3}") }
+                                })}
+                            </span>
+                            <span>
+                                {move || {
+                                    let count = component
+                                        .get()
+                                        .and_then(|ty| history.with(|h| h.samples(entity_bits, &ty).map(|s| s.len())))
+                                        .unwrap_or(0);
+                                    format!("{count} samples")
+                                }}
+                            </span>
+                        </div>
+                    </div>
+                </Show>
+            </div>
+        }
+    }
+
+    /// Console panel - Postman-style sender for registered requests/messages
+    #[component]
+    fn ConsolePanel(
+        registry: Arc<ClientTypeRegistry>,
+        console_log: RwSignal<ConsoleLog>,
+        send_packet: Arc<dyn Fn(&NetworkPacket) + Send + Sync>,
+        connected: Signal<bool>,
+    ) -> impl IntoView {
+        let console_types = registry.console_types();
+        if console_types.is_empty() {
+            return view! {
+                <div class="flex flex-col items-center justify-center h-full text-center p-8">
+                    <h3 class="text-sm font-semibold text-slate-300 mb-2">"No Console Types Registered"</h3>
+                    <p class="text-xs text-slate-500 max-w-xs">
+                        "Register requests and messages on your ClientTypeRegistry to send them from here:"
+                    </p>
+                    <pre class="mt-3 text-[10px] font-mono bg-slate-950/60 border border-slate-800 rounded p-2 text-slate-400 text-left">
+"ClientTypeRegistry::builder()
+    .register_request::<ListPrograms>()
+    .register_message::<JogCommand>()
+    .with_devtools_support()
+    .build()"
+                    </pre>
+                </div>
+            }.into_any();
+        }
+
+        let selected_type = RwSignal::new(console_types[0].name.clone());
+        let pretty = |value: &JsonValue| serde_json::to_string_pretty(value).unwrap_or_default();
+        let payload_text = RwSignal::new(pretty(&console_types[0].default_payload));
+        let send_error = RwSignal::new(None::<String>);
+
+        let reset_payload = {
+            let registry = registry.clone();
+            move || {
+                if let Some(console_type) = registry.console_type(&selected_type.get_untracked()) {
+                    payload_text.set(pretty(&console_type.default_payload));
+                }
+                send_error.set(None);
+            }
+        };
+
+        let send = {
+            let registry = registry.clone();
+            move |_| {
+                let Some(console_type) = registry.console_type(&selected_type.get_untracked()) else {
+                    return;
+                };
+                let payload = match serde_json::from_str::<JsonValue>(&payload_text.get_untracked()) {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        send_error.set(Some(format!("Invalid JSON: {e}")));
+                        return;
+                    }
+                };
+                let mut result = Ok(());
+                console_log.update(|log| {
+                    result = log.send(console_type, payload, now_ms()).map(|packet| send_packet(&packet));
+                });
+                send_error.set(result.err());
+            }
+        };
+
+        view! {
+            <div class="h-full grid grid-cols-12 gap-4 text-xs">
+                <section class="col-span-5 flex flex-col gap-2 min-h-0">
+                    <div class="flex items-center gap-2">
+                        <select
+                            class="flex-1 bg-slate-950/60 border border-slate-800 rounded px-2 py-1"
+                            on:change={
+                                let reset_payload = reset_payload.clone();
+                                move |ev| {
+                                    selected_type.set(event_target_value(&ev));
+                                    reset_payload();
+                                }
+                            }
+                        >
+                            {console_types
+                                .iter()
+                                .map(|console_type| {
+                                    let label = match console_type.kind {
+                                        ConsoleMessageKind::Request => format!("{} (request)", console_type.name),
+                                        ConsoleMessageKind::Message => format!("{} (message)", console_type.name),
+                                    };
+                                    view! { <option value=console_type.name.clone()>{label}</option> }
+                                })
+                                .collect_view()}
+                        </select>
+                        <button
+                            class="px-2 py-1 rounded bg-slate-800 hover:bg-slate-700 text-slate-200"
+                            on:click=move |_| reset_payload()
+                        >
+                            "Reset"
+                        </button>
+                        <button
+                            class="px-3 py-1 rounded bg-emerald-500 text-slate-950 font-medium disabled:opacity-50"
+                            disabled=move || !connected.get()
+                            on:click=send
+                        >
+                            "Send"
+                        </button>
+                    </div>
+                    <textarea
+                        class="flex-1 min-h-0 bg-slate-950/60 border border-slate-800 rounded p-2 font-mono text-[11px] text-slate-200 resize-none"
+                        spellcheck="false"
+                        prop:value=move || payload_text.get()
+                        on:input=move |ev| payload_text.set(event_target_value(&ev))
+                    ></textarea>
+                    <Show when=move || send_error.get().is_some() fallback=|| view! { <></> }>
+                        <div class="text-red-400">{move || send_error.get().unwrap_or_default()}</div>
+                    </Show>
+                </section>
+
+                <section class="col-span-7 flex flex-col min-h-0">
+                    <div class="flex items-center justify-between mb-2">
+                        <h2 class="text-sm font-semibold text-slate-100">"History"</h2>
+                        <button
+                            class="px-2 py-1 text-[10px] rounded border border-white/10 bg-slate-800/50 hover:bg-slate-700/50"
+                            on:click=move |_| console_log.update(|log| log.clear())
+                        >
+                            "Clear"
+                        </button>
+                    </div>
+                    <div class="flex-1 overflow-y-auto min-h-0 space-y-2">
+                        {move || {
+                            console_log.with(|log| {
+                                log.entries()
+                                    .iter()
+                                    .rev()
+                                    .map(|entry| {
+                                        let (status_class, status) = match (&entry.kind, &entry.response) {
+                                            (ConsoleMessageKind::Message, _) => ("text-slate-400", "sent".to_string()),
+                                            (_, None) => ("text-blue-400", "pending".to_string()),
+                                            (_, Some(Ok(_))) => (
+                                                "text-emerald-400",
+                                                entry.round_trip_ms().map(|ms| format!("{ms:.0} ms")).unwrap_or_default(),
+                                            ),
+                                            (_, Some(Err(_))) => ("text-red-400", "decode error".to_string()),
+                                        };
+                                        let response = match &entry.response {
+                                            Some(Ok(value)) => Some(pretty(value)),
+                                            Some(Err(e)) => Some(e.clone()),
+                                            None => None,
+                                        };
+                                        view! {
+                                            <div class="border border-slate-800 rounded-md p-2 space-y-1">
+                                                <div class="flex items-center justify-between">
+                                                    <span class="text-indigo-300 font-medium">
+                                                        {format!("#{} {}", entry.request_id, entry.type_name)}
+                                                    </span>
+                                                    <span class=format!("text-[10px] {status_class}")>{status}</span>
+                                                </div>
+                                                <pre class="bg-slate-950/60 border border-slate-800 rounded p-1 font-mono text-[10px] whitespace-pre-wrap break-all text-slate-400">
+                                                    {pretty(&entry.payload)}
+                                                </pre>
+                                                {response.map(|response| view! {
+                                                    <pre class="bg-slate-950/60 border border-slate-800 rounded p-1 font-mono text-[10px] whitespace-pre-wrap break-all text-slate-200">
+                                                        {response}
+                                                    </pre>
+                                                })}
+                                            </div>
+                                        }
+                                    })
+                                    .collect_view()
+                            })
+                        }}
+                    </div>
+                </section>
+            </div>
+        }.into_any()
+    }
+
     /// Query Explorer panel - shows all active queries, their states, and cache
     #[component]
     fn QueryExplorer(app_context: Option<SyncContext>) -> impl IntoView {
@@ -605,7 +972,7 @@ pub enum DevToolsTab {
                             };
 
                             let cache = cache.lock().unwrap();
-                            let Some(entry) = cache.get(&(type_name.clone(), key.clone())) else {
+                            let Some(entry) = cache.get(&type_name, &key) else {
                                 return view! { <div class="text-slate-500">"Query not found"</div> }.into_any();
                             };
 
@@ -900,6 +1267,15 @@ pub enum DevToolsTab {
         // Live entity/component view built from incoming SyncBatch items.
         let entities = RwSignal::new(HashMap::<u64, HashMap<String, JsonValue>>::new());
 
+        // Recent values per (entity, component) for the history timeline.
+        let history = RwSignal::new(ComponentHistory::default());
+
+        // Requests/messages sent from the Console tab and their responses.
+        let console_log = RwSignal::new(ConsoleLog::default());
+
+        // Registry vs server schema, checked on every connect.
+        let schema_check = RwSignal::new(SchemaCheck::default());
+
         // Client-side subscription tracking so we can render and cancel them.
         let next_subscription_id = RwSignal::new(0_u64);
         let subscriptions = RwSignal::new(Vec::<SubscriptionRequest>::new());
@@ -936,6 +1312,10 @@ pub enum DevToolsTab {
                 console::log_1(&format!("[DevTools] raw_message signal fired, packet present: {}", packet_opt.is_some()).into());
 
                 packet_opt.as_ref().and_then(|packet| {
+                    // Responses to console requests are routed separately
+                    if !packet.type_name.contains("SyncServerMessage") {
+                        return None;
+                    }
                     console::log_1(&format!("[DevTools] Received NetworkPacket: type_name={}, schema_hash={}, data_len={}", packet.type_name, packet.schema_hash, packet.data.len()).into());
 
                     // Use bincode v2 serde API with standard config
@@ -955,6 +1335,37 @@ pub enum DevToolsTab {
             })
         });
 
+        // Route request responses to the schema check and the console.
+        {
+            let registry = registry.clone();
+            Effect::new(move |_| {
+                raw_message.with(|packet| {
+                    if let Some(packet) = packet {
+                        let mut checked = false;
+                        schema_check.maybe_update(|check| {
+                            checked = check.handle_response(&registry, packet);
+                            checked
+                        });
+                        if checked {
+                            schema_check.with_untracked(|check| {
+                                for mismatch in check.mismatches() {
+                                    console::warn_1(&format!("[DevTools] Schema mismatch: {}", mismatch).into());
+                                }
+                            });
+                        } else {
+                            console_log.maybe_update(|log| log.handle_response(&registry, packet, now_ms()));
+                        }
+                    }
+                });
+            });
+        }
+
+        // Raw packet sender for the console.
+        let send_packet: Arc<dyn Fn(&NetworkPacket) + Send + Sync> = {
+            let raw_send = raw_send.clone();
+            Arc::new(move |packet: &NetworkPacket| raw_send(packet))
+        };
+
         // Wrap send to serialize SyncClientMessage into NetworkPacket
         let send = move |msg: &SyncClientMessage| {
             let packet = NetworkPacket {
@@ -977,6 +1388,15 @@ pub enum DevToolsTab {
         // Provide the DevtoolsSync via context so other components can use it
         provide_context(sync.get_untracked());
 
+        // Feed clock ping replies to the latency tracker.
+        Effect::new(move |_| {
+            raw_message.with(|packet| {
+                if let Some(packet) = packet {
+                    sync.get_untracked().handle_clock_pong(packet);
+                }
+            });
+        });
+
         // React to incoming server messages: update mutation state and
         // maintain a simple entity/component projection.
         {
@@ -985,6 +1405,7 @@ pub enum DevToolsTab {
             let set_last_incoming = set_last_incoming;
             let set_message_flash = set_message_flash;
             let registry = registry.clone();
+            let send_packet = send_packet.clone();
             Effect::new(move |_| {
                 message.with(|msg| {
                     if let Some(msg) = msg {
@@ -999,7 +1420,14 @@ pub enum DevToolsTab {
                         }, std::time::Duration::from_millis(300));
 
                         sync.get().handle_server_message(msg);
+                        if let SyncServerMessage::Welcome(_) = msg {
+                            // Estimate the clock offset behind the latency readout
+                            for packet in DevtoolsSync::clock_ping_packets() {
+                                send_packet(&packet);
+                            }
+                        }
                         if let SyncServerMessage::SyncBatch(batch) = msg {
+                            let received_ms = now_ms();
                             entities.update(|map| {
                                 for item in &batch.items {
                                     match item {
@@ -1008,6 +1436,7 @@ pub enum DevToolsTab {
                                             // Use the type registry to deserialize component data
                                             match registry.deserialize_to_json(component_type, value) {
                                                 Ok(json_value) => {
+                                                    history.update_untracked(|history| history.record(entity.bits, component_type, received_ms, json_value.clone()));
                                                     map.entry(entity.bits)
                                                         .or_default()
                                                         .insert(component_type.clone(), json_value);
@@ -1018,6 +1447,7 @@ pub enum DevToolsTab {
                                             }
                                         }
                                         SyncItem::ComponentRemoved { entity, component_type, .. } => {
+                                            history.update_untracked(|history| history.remove_component(entity.bits, component_type));
                                             if let Some(entry) = map.get_mut(&entity.bits) {
                                                 entry.remove(component_type);
                                                 if entry.is_empty() {
@@ -1026,11 +1456,14 @@ pub enum DevToolsTab {
                                             }
                                         }
                                         SyncItem::EntityRemoved { entity, .. } => {
+                                            history.update_untracked(|history| history.remove_entity(entity.bits));
                                             map.remove(&entity.bits);
                                         }
                                     }
                                 }
                             });
+                            // One notification per batch rather than per item
+                            history.notify();
                         }
                     }
                 });
@@ -1044,19 +1477,23 @@ pub enum DevToolsTab {
             let selected_entity = selected_entity;
             let auto_subscription_id = auto_subscription_id;
             let next_subscription_id = next_subscription_id;
+            let send_packet = send_packet.clone();
             Effect::new(move |_| {
                 let state = ready_state.get();
                 if state == ConnectionReadyState::Open && auto_subscription_id.get().is_none() {
                     let id = next_subscription_id.get() + 1;
                     next_subscription_id.set(id);
-                    let req = SubscriptionRequest { subscription_id: id, component_type: "*".to_string(), entity: None };
+                    let req = SubscriptionRequest { subscription_id: id, component_type: "*".to_string(), entity: None, filter: None };
                     sync.get().send_raw(SyncClientMessage::Subscription(req.clone()));
                     auto_subscription_id.set(Some(id));
                     subscriptions.update(|subs| subs.push(req));
+                    send_packet(&SchemaCheck::request_packet());
                 } else if state != ConnectionReadyState::Open && auto_subscription_id.get().is_some() {
                     auto_subscription_id.set(None);
                     subscriptions.update(|subs| subs.clear());
                     entities.set(HashMap::new());
+                    history.update(|history| history.clear());
+                    schema_check.set(SchemaCheck::Pending);
                     selected_entity.set(None);
                 }
             });
@@ -1070,6 +1507,60 @@ pub enum DevToolsTab {
             ConnectionReadyState::Closed => "Closed",
         };
 
+        // End-to-end latency (worst component average), with a per-component
+        // breakdown in the tooltip. Empty unless the server timestamps batches.
+        let latency = sync.get_untracked().latency();
+        let latency_label = move || {
+            latency.with(|tracker| {
+                tracker
+                    .components()
+                    .values()
+                    .map(|stats| stats.avg_ms)
+                    .reduce(f64::max)
+                    .map(|avg| format!("latency {:.1} ms", avg))
+            })
+        };
+        let schema_label = move || {
+            schema_check.with(|check| match check {
+                SchemaCheck::Pending => None,
+                SchemaCheck::Checked(mismatches) if mismatches.is_empty() => Some("schema ok".to_string()),
+                SchemaCheck::Checked(mismatches) => Some(format!("{} schema mismatches", mismatches.len())),
+            })
+        };
+        let schema_details = move || {
+            schema_check.with(|check| {
+                check
+                    .mismatches()
+                    .iter()
+                    .map(|mismatch| mismatch.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+        };
+        let latency_breakdown = move || {
+            latency.with(|tracker| {
+                let mut lines: Vec<String> = tracker
+                    .components()
+                    .iter()
+                    .map(|(name, stats)| {
+                        format!(
+                            "{}: avg {:.1} ms, last {:.1} ms, max {:.1} ms ({} samples)",
+                            name, stats.avg_ms, stats.last_ms, stats.max_ms, stats.samples
+                        )
+                    })
+                    .collect();
+                lines.sort();
+                if let Some(clock) = tracker.clock_offset() {
+                    lines.push(format!(
+                        "clock offset {:.1} ms (±{:.1} ms)",
+                        clock.offset_ms,
+                        clock.rtt_ms / 2.0
+                    ));
+                }
+                lines.join("\n")
+            })
+        };
+
         // View mode: true = tree view, false = flat view
         let tree_view_mode = RwSignal::new(true);
 
@@ -1255,12 +1746,52 @@ pub enum DevToolsTab {
                                         }
                                     </span>
                                 </button>
+                                <button
+                                    class=move || {
+                                        let base = "px-3 py-1.5 text-xs font-medium rounded-md transition-colors";
+                                        if active_tab.get() == DevToolsTab::Console {
+                                            format!("{base} bg-indigo-600 text-white")
+                                        } else {
+                                            format!("{base} text-slate-400 hover:text-slate-200 hover:bg-slate-800")
+                                        }
+                                    }
+                                    on:click=move |_| active_tab.set(DevToolsTab::Console)
+                                >
+                                    <span class="flex items-center gap-1.5">
+                                        <svg class="w-3.5 h-3.5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                                            <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M8 9l3 3-3 3m5 0h3M5 20h14a2 2 0 002-2V6a2 2 0 00-2-2H5a2 2 0 00-2 2v12a2 2 0 002 2z"></path>
+                                        </svg>
+                                        "Console"
+                                    </span>
+                                </button>
                             </nav>
                         </div>
                         <div class="flex items-center gap-3 text-xs">
                             <span class="px-2 py-1 rounded-full border border-slate-700 bg-slate-900">
                                 {move || format!("{} · {}", connection_label(), ws_url)}
                             </span>
+                            <Show when=move || latency_label().is_some()>
+                                <span
+                                    class="px-2 py-1 rounded-full border border-slate-700 bg-slate-900 font-mono"
+                                    title=latency_breakdown
+                                >
+                                    {move || latency_label().unwrap_or_default()}
+                                </span>
+                            </Show>
+                            <Show when=move || schema_label().is_some()>
+                                <span
+                                    class=move || {
+                                        if schema_check.with(|check| check.mismatches().is_empty()) {
+                                            "px-2 py-1 rounded-full border border-slate-700 bg-slate-900"
+                                        } else {
+                                            "px-2 py-1 rounded-full border border-amber-500 bg-amber-500/10 text-amber-300"
+                                        }
+                                    }
+                                    title=schema_details
+                                >
+                                    {move || schema_label().unwrap_or_default()}
+                                </span>
+                            </Show>
                             <button
                                 class="px-3 py-1 rounded bg-emerald-500 text-slate-950 font-medium disabled:opacity-50"
                                 on:click=move |_| open()
@@ -1302,6 +1833,8 @@ pub enum DevToolsTab {
                     // Tab content based on active tab
                     {
                         let ctx = app_context_for_tabs.clone();
+                        let registry = registry.clone();
+                        let send_packet = send_packet.clone();
                         move || match active_tab.get() {
                         DevToolsTab::Queries => {
                             view! {
@@ -1317,6 +1850,18 @@ pub enum DevToolsTab {
                                 </div>
                             }.into_any()
                         }
+                        DevToolsTab::Console => {
+                            view! {
+                                <div class="h-full rounded-2xl border border-white/5 bg-slate-900/70 backdrop-blur-sm shadow-lg shadow-black/40 p-4">
+                                    <ConsolePanel
+                                        registry=registry.clone()
+                                        console_log=console_log
+                                        send_packet=send_packet.clone()
+                                        connected=Signal::derive(move || ready_state.get() == ConnectionReadyState::Open)
+                                    />
+                                </div>
+                            }.into_any()
+                        }
                         DevToolsTab::World => {
                             // World Inspector (original content)
                             view! {
@@ -1647,6 +2192,7 @@ pub enum DevToolsTab {
                                                 }
                                             />
                                             </div>
+                                            <ComponentHistoryPanel entity_bits=id component_types=component_types history=history />
                                         </div>
                                     }.into_any()
                                 }}
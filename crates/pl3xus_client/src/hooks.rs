@@ -1,13 +1,21 @@
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use leptos::prelude::*;
 use leptos::html::Input;
 use leptos::web_sys;
 
 use crate::context::{MutationState, RequestState, RequestStatus, SyncConnection, SyncContext};
+use crate::latency::LatencyTracker;
 use crate::traits::SyncComponent;
+use pl3xus_client_core::BlobProgress;
+use pl3xus_common::{
+    ActionState, BlobRef, ConnectionId, ControlResponse, ControlResponseKind, EntityActions, EntityControl, FetchBlob,
+    LiveQuery, StableId, UndoHistory,
+};
+use pl3xus_sync::{FieldError, RegistryUpdate, SubscriptionFilter};
 
 #[cfg(feature = "stores")]
 use reactive_stores::Store;
@@ -116,9 +124,9 @@ pub fn use_sync_component<T: SyncComponent + Clone + Default + 'static>() -> Rea
 /// # Performance
 ///
 /// The filter runs on every update of the underlying component data. For most
-/// use cases this is very fast (< 1μs per entity). If you have thousands of
-/// entities and performance becomes an issue, consider using server-side
-/// query filtering (future feature).
+/// use cases this is very fast (< 1μs per entity). The server still sends every
+/// entity; if you have thousands of entities and only need a few, use
+/// [`use_components_filtered`] so the server filters before sending.
 ///
 /// # Panics
 ///
@@ -187,6 +195,40 @@ where
     })
 }
 
+/// Hook to subscribe to the entities of a component type that match a
+/// server-side [`SubscriptionFilter`].
+///
+/// Unlike [`use_components_where`], the filter is sent to the server, which
+/// only streams matching entities. The client applies the same filter to what
+/// it receives, so servers that don't support filters still give the right
+/// result, just without the bandwidth savings.
+///
+/// # Panics
+///
+/// Panics if called outside of a `SyncProvider` context.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::{use_components_filtered, SubscriptionFilter};
+///
+/// #[component]
+/// fn FaultedRobots() -> impl IntoView {
+///     // Only robots in a fault state are sent by the server
+///     let faulted = use_components_filtered::<RobotStatus>(SubscriptionFilter::eq("state", "Fault"));
+///
+///     view! {
+///         <p>{move || format!("{} robots faulted", faulted.get().len())}</p>
+///     }
+/// }
+/// ```
+pub fn use_components_filtered<T: SyncComponent + Clone + Default + 'static>(
+    filter: SubscriptionFilter,
+) -> ReadSignal<HashMap<u64, T>> {
+    let ctx = expect_context::<SyncContext>();
+    ctx.subscribe_component_filtered::<T>(filter)
+}
+
 /// Deprecated: Use [`use_components_where`] instead.
 #[deprecated(since = "0.2.0", note = "Use use_components_where instead")]
 pub fn use_sync_component_where<T, F>(
@@ -410,6 +452,53 @@ pub fn use_sync_connection() -> SyncConnection {
     use_connection()
 }
 
+/// Hook to get the list of clients currently connected to the server.
+///
+/// Subscribes to the `ClientPresence` entities published by the server's
+/// `ClientPresencePlugin` and returns them ordered by connection time. Use this
+/// to show "who else is connected" and which client holds control.
+///
+/// To report a display name for this client, send a `SetClientIdentity`
+/// message with [`SyncContext::send`].
+///
+/// # Panics
+///
+/// Panics if called outside of a `SyncProvider` context.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::use_presence;
+///
+/// #[component]
+/// fn SessionList() -> impl IntoView {
+///     let presence = use_presence();
+///
+///     view! {
+///         <ul>
+///             <For
+///                 each=move || presence.get()
+///                 key=|client| client.connection_id.id
+///                 children=|client| {
+///                     let name = client.identity.clone().unwrap_or_else(|| format!("Client {}", client.connection_id.id));
+///                     let badge = if client.holds_control() { " (in control)" } else { "" };
+///                     view! { <li>{name}{badge}</li> }
+///                 }
+///             />
+///         </ul>
+///     }
+/// }
+/// ```
+pub fn use_presence() -> Signal<Vec<pl3xus_common::ClientPresence>> {
+    let clients = use_components::<pl3xus_common::ClientPresence>();
+
+    Signal::derive(move || {
+        let mut list: Vec<_> = clients.get().into_values().collect();
+        list.sort_by_key(|client| (client.connected_at_ms, client.connection_id.id));
+        list
+    })
+}
+
 /// Hook to subscribe to a component type with fine-grained reactivity using stores.
 ///
 /// This returns a `Store<HashMap<u64, T>>` that provides fine-grained reactive access
@@ -526,6 +615,49 @@ where
     use_entity_component(entity_id_fn)
 }
 
+/// Hook resolving a [`StableId`] alias to the entity currently carrying it.
+///
+/// Entity bits change across server restarts; aliases don't. Persist the alias
+/// (e.g. in a bookmark) and resolve it here. Returns `None` while no synced
+/// entity has the alias. Requires `StableIdPlugin` on the server.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let robot = use_stable_id("robot-1");
+/// let (status, _) = use_entity_component::<RobotStatus, _>(move || robot.get());
+/// ```
+pub fn use_stable_id(id: impl Into<String>) -> Memo<Option<u64>> {
+    let id = id.into();
+    let aliases = use_components::<StableId>();
+
+    Memo::new(move |_| {
+        aliases.with(|aliases| {
+            aliases
+                .iter()
+                .find(|(_, alias)| alias.as_str() == id)
+                .map(|(entity_id, _)| *entity_id)
+        })
+    })
+}
+
+/// Hook to subscribe to a component of the entity with a [`StableId`] alias.
+///
+/// Shorthand for [`use_stable_id`] followed by [`use_entity_component`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let (status, exists) = use_entity_by_stable_id::<RobotStatus>("robot-1");
+/// ```
+pub fn use_entity_by_stable_id<T>(id: impl Into<String>) -> (ReadSignal<T>, ReadSignal<bool>)
+where
+    T: SyncComponent + Clone + Default + 'static,
+{
+    let entity_id = use_stable_id(id);
+    use_entity_component::<T, _>(move || entity_id.get())
+}
+
 /// Hook to subscribe to a specific entity's component as a Store for fine-grained reactivity.
 ///
 /// Unlike `use_component_store` which returns `Store<HashMap<u64, T>>`, this returns
@@ -612,6 +744,9 @@ pub struct MutComponentHandle<T: SyncComponent + Clone + Default + 'static> {
     pub exists: ReadSignal<bool>,
     /// Current mutation state (as a Memo for derived reactivity).
     pub mutation_state: Memo<ComponentMutationState>,
+    /// Per-field validation errors from the last mutation, empty unless the
+    /// server rejected it during validation.
+    pub field_errors: Memo<Vec<FieldError>>,
     /// Stored mutate function (StoredValue is Copy).
     mutate_fn: StoredValue<Box<dyn Fn(T) + Send + Sync>>,
 }
@@ -633,6 +768,18 @@ impl<T: SyncComponent + Clone + Default + 'static> MutComponentHandle<T> {
     pub fn mutate(&self, new_value: T) {
         self.mutate_fn.with_value(|f| f(new_value));
     }
+
+    /// Validation message for `field` from the last mutation, if any.
+    ///
+    /// Reactive: use it in a view to show the message next to the input.
+    pub fn field_error(&self, field: &str) -> Option<String> {
+        self.field_errors.with(|errors| {
+            errors
+                .iter()
+                .find(|e| e.field == field)
+                .map(|e| e.message.clone())
+        })
+    }
 }
 
 /// Hook to subscribe to a component with mutation capability.
@@ -641,6 +788,7 @@ impl<T: SyncComponent + Clone + Default + 'static> MutComponentHandle<T> {
 /// - Current component value from server
 /// - Entity existence check
 /// - Mutation state tracking (Idle, Pending, Success, Error)
+/// - Per-field validation errors
 /// - Mutation callback
 ///
 /// # Type Parameters
@@ -654,6 +802,7 @@ impl<T: SyncComponent + Clone + Default + 'static> MutComponentHandle<T> {
 /// - `value`: Current component value
 /// - `exists`: Whether the entity exists
 /// - `mutation_state`: Current mutation state
+/// - `field_errors` / `field_error(name)`: Validation errors from the server
 /// - `mutate(new_value)`: Method to send mutation
 ///
 /// # Example
@@ -707,7 +856,38 @@ where
     });
 
     // Derive mutation state from the mutations signal
-    let mutation_state = Memo::new(move |_| {
+    let mutation_state = track_mutation_state(mutations, current_request_id);
+
+    let field_errors = Memo::new(move |_| {
+        current_request_id
+            .get()
+            .and_then(|req_id| mutations.with(|map| map.get(&req_id).map(|s| s.field_errors.clone())))
+            .unwrap_or_default()
+    });
+
+    // Create the mutate function and store it
+    let mutate_fn: Box<dyn Fn(T) + Send + Sync> = Box::new(move |new_value: T| {
+        if let Some(entity_id) = entity_id_signal.get_untracked() {
+            let request_id = ctx.mutate(entity_id, new_value);
+            set_current_request_id.set(Some(request_id));
+        }
+    });
+
+    MutComponentHandle {
+        value,
+        exists,
+        mutation_state,
+        field_errors,
+        mutate_fn: StoredValue::new(mutate_fn),
+    }
+}
+
+/// Derive the state of the request currently tracked in `current_request_id`.
+fn track_mutation_state(
+    mutations: ReadSignal<HashMap<u64, MutationState>>,
+    current_request_id: ReadSignal<Option<u64>>,
+) -> Memo<ComponentMutationState> {
+    Memo::new(move |_| {
         match current_request_id.get() {
             None => ComponentMutationState::Idle,
             Some(req_id) => {
@@ -724,24 +904,490 @@ where
                 }
             }
         }
+    })
+}
+
+/// Return type for `use_undo` hook.
+///
+/// This handle is `Copy`, so it can be used directly in multiple closures without cloning.
+pub struct UndoHandle {
+    /// Undo/redo depth for the entity, as reported by the server.
+    pub history: ReadSignal<UndoHistory>,
+    /// State of the last undo or redo sent through this handle.
+    pub state: Memo<ComponentMutationState>,
+    undo_fn: StoredValue<Box<dyn Fn() + Send + Sync>>,
+    redo_fn: StoredValue<Box<dyn Fn() + Send + Sync>>,
+}
+
+impl Clone for UndoHandle {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Copy for UndoHandle {}
+
+impl UndoHandle {
+    /// Whether the entity has a mutation to undo (reactive).
+    pub fn can_undo(&self) -> bool {
+        self.history.with(|h| h.can_undo())
+    }
+
+    /// Whether the entity has an undone mutation to redo (reactive).
+    pub fn can_redo(&self) -> bool {
+        self.history.with(|h| h.can_redo())
+    }
+
+    /// Undo the most recent mutation on the entity.
+    pub fn undo(&self) {
+        self.undo_fn.with_value(|f| f());
+    }
+
+    /// Redo the most recently undone mutation on the entity.
+    pub fn redo(&self) {
+        self.redo_fn.with_value(|f| f());
+    }
+}
+
+/// Hook for undo/redo of mutations on an entity.
+///
+/// Works with components registered with `.with_undo()` (or `#[sync(undo)]`)
+/// on the server. Availability comes from the entity's synced `UndoHistory`,
+/// and undo/redo go through the same authorization and handlers as regular
+/// mutations.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::use_undo;
+///
+/// #[component]
+/// fn JogSettingsToolbar(robot_id: Signal<Option<u64>>) -> impl IntoView {
+///     let history = use_undo(move || robot_id.get());
+///
+///     view! {
+///         <button on:click=move |_| history.undo() disabled=move || !history.can_undo()>"Undo"</button>
+///         <button on:click=move |_| history.redo() disabled=move || !history.can_redo()>"Redo"</button>
+///     }
+/// }
+/// ```
+pub fn use_undo<F>(entity_id_fn: F) -> UndoHandle
+where
+    F: Fn() -> Option<u64> + Clone + 'static,
+{
+    let ctx = expect_context::<SyncContext>();
+    let (history, _exists) = ctx.subscribe_entity_component::<UndoHistory, F>(entity_id_fn.clone());
+    let mutations = ctx.mutations();
+
+    let (current_request_id, set_current_request_id) = signal(None::<u64>);
+
+    let (entity_id_signal, set_entity_id) = signal(None::<u64>);
+    Effect::new(move |_| {
+        set_entity_id.set(entity_id_fn());
     });
 
-    // Create the mutate function and store it
-    let mutate_fn: Box<dyn Fn(T) + Send + Sync> = Box::new(move |new_value: T| {
+    let state = track_mutation_state(mutations, current_request_id);
+
+    let undo_ctx = ctx.clone();
+    let undo_fn: Box<dyn Fn() + Send + Sync> = Box::new(move || {
         if let Some(entity_id) = entity_id_signal.get_untracked() {
-            let request_id = ctx.mutate(entity_id, new_value);
-            set_current_request_id.set(Some(request_id));
+            set_current_request_id.set(Some(undo_ctx.undo(entity_id)));
+        }
+    });
+    let redo_fn: Box<dyn Fn() + Send + Sync> = Box::new(move || {
+        if let Some(entity_id) = entity_id_signal.get_untracked() {
+            set_current_request_id.set(Some(ctx.redo(entity_id)));
         }
     });
 
-    MutComponentHandle {
-        value,
-        exists,
-        mutation_state,
-        mutate_fn: StoredValue::new(mutate_fn),
+    UndoHandle {
+        history,
+        state,
+        undo_fn: StoredValue::new(undo_fn),
+        redo_fn: StoredValue::new(redo_fn),
+    }
+}
+
+/// A control request sent through a [`ControlHandle`] that the server hasn't answered yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlPending {
+    /// Waiting for control to be granted (or for approval).
+    Taking,
+    /// Waiting for control to be released.
+    Releasing,
+}
+
+/// Return type for `use_control` hook.
+///
+/// This handle is `Copy`, so it can be used directly in multiple closures without cloning.
+pub struct ControlHandle {
+    /// The connection currently in control of the entity, if any.
+    pub holder: Memo<Option<ConnectionId>>,
+    /// Whether this client (or one of its sub-connections) is in control.
+    pub has_control: Memo<bool>,
+    /// The request sent through this handle that is still waiting for an answer.
+    pub pending: ReadSignal<Option<ControlPending>>,
+    /// Why the last take was refused, cleared by the next `take()`.
+    pub denial: ReadSignal<Option<String>>,
+    take_fn: StoredValue<Box<dyn Fn() + Send + Sync>>,
+    release_fn: StoredValue<Box<dyn Fn() + Send + Sync>>,
+}
+
+impl Clone for ControlHandle {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Copy for ControlHandle {}
+
+impl ControlHandle {
+    /// Whether another client is in control (reactive).
+    pub fn controlled_by_other(&self) -> bool {
+        self.holder.with(|holder| holder.is_some()) && !self.has_control.get()
+    }
+
+    /// Whether a take or release is waiting for the server (reactive).
+    pub fn is_pending(&self) -> bool {
+        self.pending.with(|pending| pending.is_some())
+    }
+
+    /// Request control of the entity.
+    pub fn take(&self) {
+        self.take_fn.with_value(|f| f());
+    }
+
+    /// Give up control of the entity.
+    pub fn release(&self) {
+        self.release_fn.with_value(|f| f());
+    }
+
+    /// Release control if this client has it, take it otherwise.
+    pub fn toggle(&self) {
+        if self.has_control.get_untracked() {
+            self.release();
+        } else {
+            self.take();
+        }
+    }
+}
+
+/// How a control response settles a request pending on a [`ControlHandle`]
+/// for `entity_id`.
+///
+/// Returns `None` if the response doesn't answer the request, otherwise the
+/// denial to report (if any).
+fn settle_control_request(pending: ControlPending, entity_id: u64, response: ControlResponse) -> Option<Option<String>> {
+    // Responses about other entities answer other handles
+    if response.entity.map(|entity| entity.bits) != Some(entity_id) {
+        return None;
+    }
+    match response.kind {
+        ControlResponseKind::Taken | ControlResponseKind::Released | ControlResponseKind::NotControlled => Some(None),
+        ControlResponseKind::AlreadyControlled { by_client } if pending == ControlPending::Taking => {
+            Some(Some(format!("Controlled by client {}", by_client)))
+        }
+        ControlResponseKind::Denied { reason } if pending == ControlPending::Taking => Some(Some(reason)),
+        ControlResponseKind::Error(message) => Some(Some(message)),
+        _ => None,
+    }
+}
+
+/// Hook bundling everything needed to take and release control of an entity.
+///
+/// Reads the entity's synced `EntityControl` and follows the server's
+/// `ControlResponse`s about its entity to requests sent through the handle.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::use_control;
+///
+/// #[component]
+/// fn RobotControl(robot_id: Signal<Option<u64>>) -> impl IntoView {
+///     let control = use_control(move || robot_id.get());
+///
+///     view! {
+///         <button on:click=move |_| control.toggle() disabled=move || control.is_pending()>
+///             {move || if control.has_control.get() { "Release" } else { "Take control" }}
+///         </button>
+///         <span class="error">{move || control.denial.get()}</span>
+///     }
+/// }
+/// ```
+pub fn use_control<F>(entity_id_fn: F) -> ControlHandle
+where
+    F: Fn() -> Option<u64> + Clone + 'static,
+{
+    let ctx = expect_context::<SyncContext>();
+    let (control, _exists) = ctx.subscribe_entity_component::<EntityControl, F>(entity_id_fn.clone());
+    let responses = ctx.subscribe_message::<ControlResponse>();
+
+    let (pending, set_pending) = signal(None::<ControlPending>);
+    let (denial, set_denial) = signal(None::<String>);
+
+    let (entity_id_signal, set_entity_id) = signal(None::<u64>);
+    Effect::new(move |_| {
+        set_entity_id.set(entity_id_fn());
+    });
+
+    let holder = Memo::new(move |_| control.with(|control| control.holder().map(|holder| holder.connection)));
+    let identity_ctx = ctx.clone();
+    let has_control = Memo::new(move |_| {
+        identity_ctx
+            .my_identity()
+            .is_some_and(|identity| control.with(|control| control.has_control(identity)))
+    });
+
+    // The synced state settles a pending request even if its response is missed
+    Effect::new(move |_| {
+        let settled = match pending.get() {
+            Some(ControlPending::Taking) => has_control.get(),
+            Some(ControlPending::Releasing) => !has_control.get(),
+            None => false,
+        };
+        if settled {
+            set_pending.set(None);
+        }
+    });
+
+    // Responses only concern this handle while it has a request in flight
+    Effect::new(move |_| {
+        let response = responses.get();
+        let (Some(current), Some(entity_id)) = (pending.get_untracked(), entity_id_signal.get_untracked()) else {
+            return;
+        };
+        if let Some(denial) = settle_control_request(current, entity_id, response) {
+            if denial.is_some() {
+                set_denial.set(denial);
+            }
+            set_pending.set(None);
+        }
+    });
+
+    let take_ctx = ctx.clone();
+    let take_fn: Box<dyn Fn() + Send + Sync> = Box::new(move || {
+        if let Some(entity_id) = entity_id_signal.get_untracked() {
+            set_denial.set(None);
+            set_pending.set(Some(ControlPending::Taking));
+            take_ctx.take_control(entity_id);
+        }
+    });
+    let release_fn: Box<dyn Fn() + Send + Sync> = Box::new(move || {
+        if let Some(entity_id) = entity_id_signal.get_untracked() {
+            set_pending.set(Some(ControlPending::Releasing));
+            ctx.release_control(entity_id);
+        }
+    });
+
+    ControlHandle {
+        holder,
+        has_control,
+        pending,
+        denial,
+        take_fn: StoredValue::new(take_fn),
+        release_fn: StoredValue::new(release_fn),
+    }
+}
+
+/// Hook for the most recent state transition of a state machine component.
+///
+/// Works with components registered with `.with_state_machine(...)` on the
+/// server. Returns `(from, to)` of the last transition seen on the entity
+/// since this client connected, or `None` before the first one.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let transition = use_state_transition::<SystemState, _>(move || system_id.get());
+///
+/// Effect::new(move |_| {
+///     if let Some((SystemState::Running, SystemState::Completed)) = transition.get() {
+///         show_toast("Program completed");
+///     }
+/// });
+/// ```
+pub fn use_state_transition<T, F>(entity_id_fn: F) -> Memo<Option<(T, T)>>
+where
+    T: SyncComponent + Clone + PartialEq + 'static,
+    F: Fn() -> Option<u64> + Send + Sync + 'static,
+{
+    let ctx = expect_context::<SyncContext>();
+    let transitions = ctx.state_transitions;
+
+    Memo::new(move |_| {
+        let entity_id = entity_id_fn()?;
+        transitions.with(|map| {
+            let transition = map.get(&(entity_id, T::component_name().to_string()))?;
+            let decode = |bytes: &[u8]| {
+                bincode::serde::decode_from_slice::<T, _>(bytes, bincode::config::standard())
+                    .ok()
+                    .map(|(value, _)| value)
+            };
+            Some((decode(&transition.from)?, decode(&transition.to)?))
+        })
+    })
+}
+
+/// Hook for the types the server registered after it started.
+///
+/// Returns the most recent [`RegistryUpdate`], or `None` if the server
+/// hasn't registered anything since this client connected. Subscriptions to
+/// the new component types need no action; this is for UIs that list what
+/// the server offers, e.g. device panels for newly discovered device types.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let update = use_registry_update();
+///
+/// Effect::new(move |_| {
+///     if let Some(update) = update.get() {
+///         log!("New component types: {:?}", update.components);
+///     }
+/// });
+/// ```
+pub fn use_registry_update() -> ReadSignal<Option<RegistryUpdate>> {
+    expect_context::<SyncContext>().registry_update.read_only()
+}
+
+/// Hook for the content of a blob referenced by a synced component.
+///
+/// Fetches the blob in chunks when the reference changes and returns `None`
+/// until it is complete. Blobs are cached by hash for the whole
+/// `SyncProvider`, so hooks showing the same blob share one transfer.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let snapshot = use_entity_component::<CameraSnapshot, _>(move || camera_id.get());
+/// let image = use_blob(move || Some(snapshot.0.get().image));
+///
+/// view! {
+///     <Show when=move || image.get().is_some()>
+///         <img src=move || to_object_url(&image.get().unwrap(), "image/png") />
+///     </Show>
+/// }
+/// ```
+pub fn use_blob<F>(blob_fn: F) -> Signal<Option<Arc<[u8]>>>
+where
+    F: Fn() -> Option<BlobRef> + Send + Sync + 'static,
+{
+    let ctx = expect_context::<SyncContext>();
+    let data = RwSignal::new(None::<Arc<[u8]>>);
+    // (request id, blob hash) of the chunk being fetched by this hook
+    let pending = RwSignal::new(None::<(u64, String)>);
+
+    {
+        let ctx = ctx.clone();
+        Effect::new(move |_| {
+            ctx.blobs_loaded.track();
+            let blob = blob_fn().filter(|blob| !blob.is_empty());
+            let Some(blob) = blob else {
+                data.set(None);
+                return;
+            };
+            let mut cache = ctx.blobs.lock().unwrap();
+            if let Some(bytes) = cache.get(&blob.hash) {
+                data.set(Some(bytes));
+                return;
+            }
+            data.set(None);
+            // Another hook may already be fetching it; blobs_loaded wakes us up
+            if let Some(request) = cache.start(&blob) {
+                drop(cache);
+                pending.set(Some((ctx.request(request), blob.hash)));
+            }
+        });
+    }
+
+    Effect::new(move |_| {
+        let Some((id, hash)) = pending.get() else {
+            return;
+        };
+        let status = ctx.requests.with(|requests| requests.get(&id).map(|state| state.status.clone()));
+        let progress = match status {
+            None | Some(RequestStatus::Pending) => return,
+            Some(RequestStatus::Success) => match ctx.get_response::<FetchBlob>(id) {
+                Some(chunk) => ctx.blobs.lock().unwrap().receive(chunk),
+                None => Err(format!("Undecodable chunk of blob {}", hash)),
+            },
+            Some(RequestStatus::Error(e)) => Err(e),
+        };
+        match progress {
+            Ok(BlobProgress::Next(request)) => pending.set(Some((ctx.request(request), hash))),
+            Ok(BlobProgress::Complete(_)) => {
+                pending.set(None);
+                ctx.blobs_loaded.update(|n| *n += 1);
+            }
+            Err(e) => {
+                leptos::logging::warn!("[use_blob] Failed to fetch blob {}: {}", hash, e);
+                ctx.blobs.lock().unwrap().cancel(&hash);
+                pending.set(None);
+            }
+        }
+    });
+
+    data.into()
+}
+
+/// Return type for `use_action` hook.
+///
+/// This handle is `Copy`, so it can be used directly in multiple closures without cloning.
+#[derive(Clone, Copy)]
+pub struct ActionHandle {
+    /// Current state of the action. Actions the server hasn't declared are disabled.
+    pub state: Memo<ActionState>,
+}
+
+impl ActionHandle {
+    /// Whether the action can be performed (reactive).
+    pub fn enabled(&self) -> bool {
+        self.state.with(|s| s.enabled)
+    }
+
+    /// Why the action is disabled, if it is (reactive).
+    pub fn reason(&self) -> Option<String> {
+        self.state.with(|s| s.reason.clone())
     }
 }
 
+/// Hook to gate a button on a server-declared action.
+///
+/// Reads the named action from the entity's synced `EntityActions`, which the
+/// server maintains with `derive_actions` (see `pl3xus_sync::actions`).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::use_action;
+///
+/// #[component]
+/// fn StartButton(system_id: Signal<Option<u64>>) -> impl IntoView {
+///     let start = use_action("start", move || system_id.get());
+///
+///     view! {
+///         <button disabled=move || !start.enabled() title=move || start.reason()>
+///             "Start"
+///         </button>
+///     }
+/// }
+/// ```
+pub fn use_action<F>(name: impl Into<String>, entity_id_fn: F) -> ActionHandle
+where
+    F: Fn() -> Option<u64> + Clone + 'static,
+{
+    let ctx = expect_context::<SyncContext>();
+    let (actions, _exists) = ctx.subscribe_entity_component::<EntityActions, F>(entity_id_fn);
+    let name = name.into();
+
+    let state = Memo::new(move |_| {
+        actions.with(|actions| actions.get(&name).cloned().unwrap_or_default())
+    });
+
+    ActionHandle { state }
+}
+
 /// Hook to access the SyncContext directly.
 ///
 /// This provides access to the full SyncContext API, including mutation methods.
@@ -830,6 +1476,94 @@ where
     }
 }
 
+/// Hook to receive server notifications, muting unwanted categories on the server.
+///
+/// Sends `preferences` to the server whenever the connection opens (so they
+/// survive reconnects); the server then stops delivering notifications in the
+/// muted categories to this client. The returned signal holds the latest
+/// notification that passes the preferences.
+///
+/// # Panics
+///
+/// Panics if called outside of a `SyncProvider` context.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::{use_notifications, NotificationPreferences};
+///
+/// #[component]
+/// fn Toasts() -> impl IntoView {
+///     let notification = use_notifications(NotificationPreferences::muting(["debug", "status"]));
+///
+///     view! { <div class="toast">{move || notification.get().message}</div> }
+/// }
+/// ```
+pub fn use_notifications(
+    preferences: pl3xus_common::NotificationPreferences,
+) -> ReadSignal<pl3xus_common::ServerNotification> {
+    let ctx = expect_context::<SyncContext>();
+    let ready_state = ctx.connection().ready_state;
+    let incoming = ctx.subscribe_message::<pl3xus_common::ServerNotification>();
+    let (latest, set_latest) = signal(pl3xus_common::ServerNotification::default());
+
+    let ctx_for_prefs = ctx.clone();
+    let prefs_for_send = preferences.clone();
+    Effect::new(move |_| {
+        if ready_state.get() == crate::ConnectionReadyState::Open {
+            ctx_for_prefs.send(prefs_for_send.clone());
+        }
+    });
+
+    Effect::new(move |_| {
+        let notification = incoming.get();
+        // sequence 0 is the "no notification yet" default
+        if notification.sequence != 0 && preferences.allows(&notification) {
+            set_latest.set(notification);
+        }
+    });
+
+    latest
+}
+
+/// Hook to acknowledge server notifications.
+///
+/// Returns a function that sends a `NotificationAck` for a notification that
+/// has `requires_ack` set; it does nothing for other notifications. Call it
+/// once the user has actually seen the alert (e.g. when a toast is dismissed).
+/// Unacknowledged notifications are resent by the server after a reconnect.
+///
+/// # Panics
+///
+/// Panics if called outside of a `SyncProvider` context.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::{use_message, use_notification_ack, ServerNotification};
+///
+/// #[component]
+/// fn AlertBanner() -> impl IntoView {
+///     let notification = use_message::<ServerNotification>();
+///     let ack = use_notification_ack();
+///
+///     view! {
+///         <div>{move || notification.get().message}</div>
+///         <button on:click=move |_| ack(&notification.get_untracked())>"Dismiss"</button>
+///     }
+/// }
+/// ```
+pub fn use_notification_ack() -> impl Fn(&pl3xus_common::ServerNotification) + Clone {
+    let ctx = expect_context::<SyncContext>();
+    move |notification: &pl3xus_common::ServerNotification| {
+        if notification.requires_ack {
+            ctx.send(pl3xus_common::NotificationAck {
+                sequence: notification.sequence,
+            });
+        }
+    }
+}
+
 /// Hook to access mutation state tracking.
 ///
 /// This returns a read-only signal containing all mutation states, allowing
@@ -882,6 +1616,98 @@ pub fn use_mutations() -> ReadSignal<HashMap<u64, MutationState>> {
     ctx.mutations()
 }
 
+/// Hook to read end-to-end sync latency per component type.
+///
+/// Latency is measured from the server send time to the moment the client
+/// processes the batch, corrected for clock offset. The map stays empty unless
+/// the server enables `SyncSettings::include_timestamps`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::use_latency;
+///
+/// #[component]
+/// fn LatencyBadge() -> impl IntoView {
+///     let latency = use_latency();
+///
+///     view! {
+///         <span>
+///             {move || latency.with(|l| {
+///                 l.components()
+///                     .get("RobotPosition")
+///                     .map(|stats| format!("{:.1} ms", stats.avg_ms))
+///                     .unwrap_or_default()
+///             })}
+///         </span>
+///     }
+/// }
+/// ```
+pub fn use_latency() -> ReadSignal<LatencyTracker> {
+    let ctx = expect_context::<SyncContext>();
+    ctx.latency()
+}
+
+/// Estimated server clock, as returned by [`use_server_time`].
+#[derive(Clone)]
+pub struct ServerTime {
+    ctx: SyncContext,
+}
+
+impl ServerTime {
+    /// The current estimate, if synchronized (tracked).
+    pub fn estimate(&self) -> Option<pl3xus_common::ClockEstimate> {
+        self.ctx.server_clock_estimate()
+    }
+
+    /// `server_wall_time - local_wall_time` in milliseconds (tracked).
+    pub fn offset_ms(&self) -> Option<f64> {
+        self.estimate().map(|e| e.offset_ms)
+    }
+
+    /// Estimated server wall-clock time now, in ms since the Unix epoch (tracked).
+    pub fn now_ms(&self) -> Option<f64> {
+        self.estimate()
+            .map(|e| e.to_server_ms(crate::latency::now_ms()))
+    }
+
+    /// Convert a server timestamp (ms since the Unix epoch) to local time (tracked).
+    pub fn to_local_ms(&self, server_ms: f64) -> Option<f64> {
+        self.estimate().map(|e| e.to_local_ms(server_ms))
+    }
+}
+
+/// Hook to read the estimated server clock.
+///
+/// Use it to correlate server-stamped telemetry with local events. The clock
+/// is synchronized once per connection; set `clock_sync_interval` on
+/// `SyncProvider` to follow drift on long-lived connections.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::use_server_time;
+///
+/// #[component]
+/// fn SampleAge(sample_time_ms: f64) -> impl IntoView {
+///     let server_time = use_server_time();
+///
+///     view! {
+///         <span>
+///             {move || server_time
+///                 .now_ms()
+///                 .map(|now| format!("{:.0} ms ago", now - sample_time_ms))
+///                 .unwrap_or_else(|| "clock not synced".to_string())}
+///         </span>
+///     }
+/// }
+/// ```
+pub fn use_server_time() -> ServerTime {
+    ServerTime {
+        ctx: expect_context::<SyncContext>(),
+    }
+}
+
 /// Deprecated: Use [`use_mutations`] instead.
 #[deprecated(since = "0.2.0", note = "Use use_mutations instead")]
 pub fn use_sync_mutations() -> ReadSignal<HashMap<u64, MutationState>> {
@@ -1415,6 +2241,18 @@ pub fn use_request<R>() -> (
     impl Fn(R) + Clone,
     Signal<UseRequestState<R::ResponseMessage>>,
 )
+where
+    R: pl3xus_common::RequestMessage + Clone + 'static,
+{
+    let (fetch, state) = use_keyed_request::<R>();
+    (move |request: R| fetch(request, None), state)
+}
+
+/// `use_request` with an optional idempotency key per call.
+fn use_keyed_request<R>() -> (
+    impl Fn(R, Option<u64>) + Clone,
+    Signal<UseRequestState<R::ResponseMessage>>,
+)
 where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
@@ -1472,10 +2310,13 @@ where
     };
 
     // Create the fetch function
-    let fetch = move |request: R| {
+    let fetch = move |request: R, idempotency_key: Option<u64>| {
         #[cfg(target_arch = "wasm32")]
         leptos::logging::log!("[use_request] fetch called for request type: {}", R::request_name());
-        let id = ctx.request(request);
+        let id = match idempotency_key {
+            Some(key) => ctx.request_with_key(request, key),
+            None => ctx.request(request),
+        };
         #[cfg(target_arch = "wasm32")]
         leptos::logging::log!("[use_request] request sent with id: {}", id);
         current_request_id.set(Some(id));
@@ -1646,6 +2487,18 @@ pub fn use_targeted_request<R>() -> (
     impl Fn(u64, R) + Clone,
     Signal<UseRequestState<R::ResponseMessage>>,
 )
+where
+    R: pl3xus_common::RequestMessage + Clone + 'static,
+{
+    let (send, state) = use_keyed_targeted_request::<R>();
+    (move |entity_bits: u64, request: R| send(entity_bits, request, None), state)
+}
+
+/// `use_targeted_request` with an optional idempotency key per call.
+fn use_keyed_targeted_request<R>() -> (
+    impl Fn(u64, R, Option<u64>) + Clone,
+    Signal<UseRequestState<R::ResponseMessage>>,
+)
 where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
@@ -1703,14 +2556,17 @@ where
     };
 
     // Create the send function
-    let send = move |entity_bits: u64, request: R| {
+    let send = move |entity_bits: u64, request: R, idempotency_key: Option<u64>| {
         #[cfg(target_arch = "wasm32")]
         leptos::logging::log!(
             "[use_targeted_request] sending request type: {} to entity: {}",
             R::request_name(),
             entity_bits
         );
-        let id = ctx.targeted_request(entity_bits, request);
+        let id = match idempotency_key {
+            Some(key) => ctx.targeted_request_with_key(entity_bits, request, key),
+            None => ctx.targeted_request(entity_bits, request),
+        };
         #[cfg(target_arch = "wasm32")]
         leptos::logging::log!("[use_targeted_request] request sent with id: {}", id);
         current_request_id.set(Some(id));
@@ -1800,6 +2656,7 @@ where
 ///
 /// This provides an ergonomic API for mutations (write operations) with:
 /// - A `send` method to trigger the mutation
+/// - A `retry` method that resends the last mutation under the same idempotency key
 /// - State accessors (`is_loading`, `is_idle`, `is_success`, `is_error`)
 /// - Access to response data and errors
 ///
@@ -1827,6 +2684,7 @@ where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
     send_fn: StoredValue<Box<dyn Fn(R) + Send + Sync>>,
+    retry_fn: StoredValue<Box<dyn Fn() + Send + Sync>>,
     state: Signal<UseRequestState<R::ResponseMessage>>,
 }
 
@@ -1848,10 +2706,21 @@ where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
     /// Send the mutation request.
+    ///
+    /// Each call is a new operation with a fresh idempotency key.
     pub fn send(&self, request: R) {
         self.send_fn.with_value(|f| f(request));
     }
 
+    /// Resend the last mutation (e.g. after a timeout).
+    ///
+    /// The retry carries the same idempotency key, so the server applies the
+    /// mutation at most once and answers with the original response if it
+    /// already handled it. Does nothing if nothing has been sent yet.
+    pub fn retry(&self) {
+        self.retry_fn.with_value(|f| f());
+    }
+
     /// Returns true if the mutation is currently in flight.
     pub fn is_loading(&self) -> bool {
         self.state.get().is_loading()
@@ -1940,7 +2809,7 @@ pub fn use_mutation<R>(
 where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
-    let (send, state) = use_request::<R>();
+    let (send, state) = use_keyed_request::<R>();
 
     // Track whether the current response has been processed
     let processed = RwSignal::new(false);
@@ -1971,10 +2840,27 @@ where
         }
     });
 
-    // Store the send function in a StoredValue to make the handle Copy
-    let send_fn = StoredValue::new(Box::new(send) as Box<dyn Fn(R) + Send + Sync>);
+    // Remember the last request and its key so `retry` resends the same operation
+    let last = StoredValue::new(None::<(R, u64)>);
+    let send_new = {
+        let send = send.clone();
+        move |request: R| {
+            let key = SyncContext::new_idempotency_key();
+            last.set_value(Some((request.clone(), key)));
+            send(request, Some(key));
+        }
+    };
+    let retry = move || {
+        if let Some((request, key)) = last.get_value() {
+            send(request, Some(key));
+        }
+    };
+
+    // Store the send functions in StoredValues to make the handle Copy
+    let send_fn = StoredValue::new(Box::new(send_new) as Box<dyn Fn(R) + Send + Sync>);
+    let retry_fn = StoredValue::new(Box::new(retry) as Box<dyn Fn() + Send + Sync>);
 
-    MutationHandle { send_fn, state }
+    MutationHandle { send_fn, retry_fn, state }
 }
 
 /// Handle returned by `use_mutation_targeted` for sending entity-targeted mutations.
@@ -2002,6 +2888,7 @@ where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
     send_fn: StoredValue<Box<dyn Fn(u64, R) + Send + Sync>>,
+    retry_fn: StoredValue<Box<dyn Fn() + Send + Sync>>,
     state: Signal<UseRequestState<R::ResponseMessage>>,
 }
 
@@ -2023,10 +2910,19 @@ where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
     /// Send the mutation request to a specific entity.
+    ///
+    /// Each call is a new operation with a fresh idempotency key.
     pub fn send(&self, entity_id: u64, request: R) {
         self.send_fn.with_value(|f| f(entity_id, request));
     }
 
+    /// Resend the last mutation to the same entity under the same idempotency key.
+    ///
+    /// See [`MutationHandle::retry`].
+    pub fn retry(&self) {
+        self.retry_fn.with_value(|f| f());
+    }
+
     /// Returns true if the mutation is currently in flight.
     pub fn is_loading(&self) -> bool {
         self.state.get().is_loading()
@@ -2115,7 +3011,7 @@ pub fn use_mutation_targeted<R>(
 where
     R: pl3xus_common::RequestMessage + Clone + 'static,
 {
-    let (send, state) = use_targeted_request::<R>();
+    let (send, state) = use_keyed_targeted_request::<R>();
 
     // Track whether the current response has been processed
     let processed = RwSignal::new(false);
@@ -2146,10 +3042,27 @@ where
         }
     });
 
-    // Store the send function in a StoredValue to make the handle Copy
-    let send_fn = StoredValue::new(Box::new(send) as Box<dyn Fn(u64, R) + Send + Sync>);
+    // Remember the last request and its key so `retry` resends the same operation
+    let last = StoredValue::new(None::<(u64, R, u64)>);
+    let send_new = {
+        let send = send.clone();
+        move |entity_id: u64, request: R| {
+            let key = SyncContext::new_idempotency_key();
+            last.set_value(Some((entity_id, request.clone(), key)));
+            send(entity_id, request, Some(key));
+        }
+    };
+    let retry = move || {
+        if let Some((entity_id, request, key)) = last.get_value() {
+            send(entity_id, request, Some(key));
+        }
+    };
+
+    // Store the send functions in StoredValues to make the handle Copy
+    let send_fn = StoredValue::new(Box::new(send_new) as Box<dyn Fn(u64, R) + Send + Sync>);
+    let retry_fn = StoredValue::new(Box::new(retry) as Box<dyn Fn() + Send + Sync>);
 
-    TargetedMutationHandle { send_fn, state }
+    TargetedMutationHandle { send_fn, retry_fn, state }
 }
 
 // =============================================================================
@@ -2569,6 +3482,93 @@ where
     }
 }
 
+/// Handle returned by `use_live_query`.
+///
+/// This handle is `Copy`, so it can be used directly in closures without cloning.
+pub struct LiveQueryHandle<Q: LiveQuery> {
+    /// Current rows, in the order the server first sent them
+    pub rows: Memo<Vec<Q::Row>>,
+    /// Whether the first result hasn't arrived yet
+    pub is_loading: Memo<bool>,
+    /// Why the server refused the query, if it did
+    pub error: Memo<Option<String>>,
+}
+
+impl<Q: LiveQuery> Clone for LiveQueryHandle<Q> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Q: LiveQuery> Copy for LiveQueryHandle<Q> {}
+
+impl<Q: LiveQuery> LiveQueryHandle<Q> {
+    /// Get the current rows.
+    pub fn rows(&self) -> Vec<Q::Row> {
+        self.rows.get()
+    }
+
+    /// Returns true until the first result arrives.
+    pub fn is_loading(&self) -> bool {
+        self.is_loading.get()
+    }
+
+    /// Get the current error, if any.
+    pub fn error(&self) -> Option<String> {
+        self.error.get()
+    }
+}
+
+/// Hook for a live query: the server pushes row changes instead of the client refetching.
+///
+/// Unlike [`use_query`], there is no refetch or invalidation: the server keeps
+/// the query registered and sends only the rows that were added, updated or
+/// removed whenever its data changes. The server registers the query with
+/// `app.live_query::<Q, _, _>(...)`. Use [`use_live_query_with`] for queries
+/// with parameters.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::use_live_query;
+///
+/// let programs = use_live_query::<ListPrograms>();
+///
+/// view! {
+///     <Show when=move || !programs.is_loading() fallback=|| view! { <span>"Loading..."</span> }>
+///         <For each=move || programs.rows() key=|p| p.id let:program>
+///             <div>{program.name.clone()}</div>
+///         </For>
+///     </Show>
+/// }
+/// ```
+pub fn use_live_query<Q: LiveQuery + Default>() -> LiveQueryHandle<Q> {
+    use_live_query_with(Q::default())
+}
+
+/// Hook for a live query with parameters.
+///
+/// See [`use_live_query`].
+pub fn use_live_query_with<Q: LiveQuery>(query: Q) -> LiveQueryHandle<Q> {
+    let ctx = use_sync_context();
+    let query_id = ctx.subscribe_live_query(query);
+    let live_queries = ctx.live_queries;
+
+    let rows = Memo::new(move |_| {
+        live_queries.with(|map| map.get(&query_id).map(|rows| rows.decode::<Q>()).unwrap_or_default())
+    });
+    let is_loading = Memo::new(move |_| {
+        live_queries.with(|map| map.get(&query_id).is_some_and(|rows| !rows.is_loaded()))
+    });
+    let error = Memo::new(move |_| {
+        live_queries.with(|map| map.get(&query_id).and_then(|rows| rows.error().map(str::to_string)))
+    });
+
+    on_cleanup(move || ctx.cancel_live_query(query_id));
+
+    LiveQueryHandle { rows, is_loading, error }
+}
+
 /// Hook for fetching data with a reactive key parameter.
 ///
 /// Unlike `use_query`, this hook watches a signal for the request parameters.
@@ -2959,4 +3959,55 @@ where
         refetch_fn,
         state: state.into(),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settle_control_request() {
+        use ControlPending::{Releasing, Taking};
+        const ROBOT: u64 = 42;
+        let response = |kind| ControlResponse {
+            sequence: 1,
+            entity: Some(ROBOT.into()),
+            kind,
+        };
+
+        assert_eq!(settle_control_request(Taking, ROBOT, response(ControlResponseKind::Taken)), Some(None));
+        assert_eq!(settle_control_request(Releasing, ROBOT, response(ControlResponseKind::NotControlled)), Some(None));
+        assert_eq!(
+            settle_control_request(Taking, ROBOT, response(ControlResponseKind::Denied { reason: "Locked".to_string() })),
+            Some(Some("Locked".to_string()))
+        );
+        assert_eq!(
+            settle_control_request(Releasing, ROBOT, response(ControlResponseKind::Error("Unknown entity".to_string()))),
+            Some(Some("Unknown entity".to_string()))
+        );
+
+        // Denials of a take don't settle a release
+        let by_client = ConnectionId { id: 7 };
+        assert!(settle_control_request(Taking, ROBOT, response(ControlResponseKind::AlreadyControlled { by_client })).is_some());
+        assert_eq!(
+            settle_control_request(Releasing, ROBOT, response(ControlResponseKind::AlreadyControlled { by_client })),
+            None
+        );
+
+        // Requests from other clients are not an answer
+        assert_eq!(settle_control_request(Taking, ROBOT, response(ControlResponseKind::ControlRequested { by_client })), None);
+        assert_eq!(settle_control_request(Taking, ROBOT, response(ControlResponseKind::None)), None);
+
+        // A take waiting for approval stays pending
+        assert_eq!(settle_control_request(Taking, ROBOT, response(ControlResponseKind::AwaitingApproval)), None);
+
+        // Responses about another entity, or none, belong to another handle
+        let denied = || ControlResponseKind::Denied { reason: "Locked".to_string() };
+        assert_eq!(settle_control_request(Taking, ROBOT + 1, response(denied())), None);
+        let unnamed = ControlResponse {
+            entity: None,
+            ..response(denied())
+        };
+        assert_eq!(settle_control_request(Taking, ROBOT, unnamed), None);
+    }
+}
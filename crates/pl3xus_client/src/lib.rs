@@ -107,39 +107,79 @@
 //! - ✅ User input preservation while focused
 //! - ✅ Enter key to apply mutation
 //! - ✅ Blur (click away) to revert to server value
+//!
+//! ## Server-Side Rendering
+//!
+//! The default `csr` feature targets client-only apps. Leptos SSR apps (e.g.
+//! cargo-leptos with Axum) disable default features and enable `ssr` for the
+//! server build and `hydrate` for the browser build:
+//!
+//! ```toml
+//! [features]
+//! ssr = ["leptos/ssr", "pl3xus_client/ssr"]
+//! hydrate = ["leptos/hydrate", "pl3xus_client/hydrate"]
+//!
+//! [dependencies]
+//! pl3xus_client = { version = "0.1", default-features = false }
+//! ```
+//!
+//! No other changes are needed. On the server, `SyncProvider` never opens a
+//! WebSocket and every hook renders its empty or loading state (no entities,
+//! `None` for single values, a disconnected connection). The browser hydrates
+//! that same state and only then connects and subscribes.
 
 // Module declarations
-mod client_type_registry;
 mod components;
 mod context;
-mod error;
 mod hooks;
 mod provider;
-mod traits;
+
+// Framework-agnostic pieces shared with the other frontend adapters
+use pl3xus_client_core::{client_type_registry, error, latency, reliable, traits};
 
 // Re-exports
-pub use client_type_registry::{ClientTypeRegistry, ClientTypeRegistryBuilder};
-pub use components::SyncFieldInput;
+pub use client_type_registry::{
+    ClientTypeRegistry, ClientTypeRegistryBuilder, ConsoleMessageKind, ConsoleType, SchemaMismatch, SchemaTypeKind,
+};
+pub use components::{ControlButton, SyncFieldInput};
 pub use context::{MutationState, RequestState, RequestStatus, SyncConnection, SyncContext, QueryCacheEntry, QueryCacheState};
 pub use error::SyncError;
+pub use latency::{ComponentLatency, LatencyTracker};
 
 // New hook names (preferred)
 pub use hooks::{
-    use_components, use_components_where, use_connection, use_sync_context,
+    use_components, use_components_filtered, use_components_where, use_connection, use_presence, use_sync_context,
     use_entity, use_entity_component, use_entity_reactive,
+    use_stable_id, use_entity_by_stable_id,
     use_field_editor, use_message, use_mutations, use_untracked,
     use_request, use_request_with_handler, use_request_state,
     use_targeted_request, use_targeted_request_with_handler,
-    UseRequestState, use_send_targeted,
+    UseRequestState, use_send_targeted, use_notifications, use_notification_ack,
     // TanStack Query-inspired mutation API
     use_mutation, use_mutation_targeted,
     MutationHandle, TargetedMutationHandle,
     // TanStack Query-inspired query API with server-side invalidation
     use_query, use_query_keyed, use_query_targeted, QueryHandle, QueryState,
+    // Live queries with rows pushed by the server
+    use_live_query, use_live_query_with, LiveQueryHandle,
     // Query client for global query management
     use_query_client, QueryClient,
     // Component mutation hooks (for synced components with server-side handlers)
     use_mut_component, MutComponentHandle, ComponentMutationState,
+    // Undo/redo of component mutations
+    use_undo, UndoHandle,
+    // Taking and releasing control of an entity
+    use_control, ControlHandle, ControlPending,
+    // Server-declared action availability
+    use_action, ActionHandle,
+    // State machine transitions announced by the server
+    use_state_transition,
+    // Blobs referenced by synced components, fetched out of band
+    use_blob,
+    // Types registered by the server while running
+    use_registry_update,
+    // End-to-end sync latency and server clock
+    use_latency, use_server_time, ServerTime,
 };
 
 // Deprecated hook names (for backwards compatibility)
@@ -163,13 +203,39 @@ pub use provider::SyncProvider;
 pub use traits::SyncComponent;
 
 // Re-export mutation types from pl3xus_sync for convenience
-pub use pl3xus_sync::MutationStatus;
+pub use pl3xus_sync::{FieldError, FilterOp, FilterValue, MutationStatus, RegistryUpdate, SubscriptionFilter};
 
 // Re-export control types from pl3xus_common for client-side use
 pub use pl3xus_common::{ControlRequest, ControlResponse, EntityControl, ConnectionId};
 
+// Re-export presence types from pl3xus_common for client-side use
+pub use pl3xus_common::{ClientPresence, SetClientIdentity};
+
+// Re-export undo types for client-side use
+pub use pl3xus_common::UndoHistory;
+
+// Re-export action availability types for client-side use
+pub use pl3xus_common::{ActionState, EntityActions};
+
+// Re-export stable entity aliases for client-side use
+pub use pl3xus_common::StableId;
+
+// Re-export archetype spawn/despawn requests (send with `use_request`)
+pub use pl3xus_common::{
+    ComponentOverride, DespawnEntityRequest, DespawnEntityResponse, SpawnEntityRequest, SpawnEntityResponse,
+};
+
 // Re-export notification types from pl3xus_common for client-side use
-pub use pl3xus_common::{NotificationLevel, ServerNotification};
+pub use pl3xus_common::{NotificationAck, NotificationLevel, NotificationPreferences, ServerNotification};
+
+// Re-export clock sync types
+pub use pl3xus_common::{ClockEstimate, ClockPing, ClockPong};
+
+// Live query trait (see `use_live_query`)
+pub use pl3xus_common::LiveQuery;
+
+// Reliable delivery types (see `SyncContext::send_reliable`)
+pub use pl3xus_common::{ReliableAck, ReliableEnvelope};
 
 // Re-export ConnectionReadyState for convenience
 pub use leptos_use::core::ConnectionReadyState;
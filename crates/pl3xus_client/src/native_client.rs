@@ -13,13 +13,13 @@
 //! This API is deprecated because:
 //! - It's not currently used in any examples or production code
 //! - The base `pl3xus` crate provides better Bevy-to-Bevy networking
-//! - Future versions will provide a dedicated Bevy-to-Bevy sync engine
+//! - `pl3xus_sync::Pl3xusSyncClientPlugin` mirrors server entities into a native Bevy client
 //!
 //! ## Migration Path
 //!
 //! - **For Bevy clients**: Use the base `pl3xus` crate with `TcpProvider` or `WebSocketProvider`
 //! - **For WASM/Leptos clients**: Use `pl3xus_client::SyncProvider` and hooks
-//! - **For future Bevy-to-Bevy sync**: Wait for the upcoming Bevy sync engine
+//! - **For Bevy-to-Bevy sync**: Use `pl3xus_sync::Pl3xusSyncClientPlugin`
 //!
 //! ## Current Use Cases
 //!
@@ -36,6 +36,7 @@ use serde_json::Value as JsonValue;
 use crate::client_type_registry::ClientTypeRegistry;
 
 use pl3xus_sync::{
+    FieldError,
     MutateComponent,
     MutationResponse,
     MutationStatus,
@@ -50,6 +51,8 @@ pub struct NativeMutationState {
     pub request_id: u64,
     pub status: Option<MutationStatus>,
     pub message: Option<String>,
+    /// Per-field errors when the server rejected the value during validation.
+    pub field_errors: Vec<FieldError>,
 }
 
 impl NativeMutationState {
@@ -58,6 +61,7 @@ impl NativeMutationState {
             request_id,
             status: None,
             message: None,
+            field_errors: Vec::new(),
         }
     }
 }
@@ -173,6 +177,7 @@ impl NativeSyncClient {
                     entity,
                     component_type,
                     value: value_bytes,
+                    idempotency_key: Some(crate::reliable::random_u64()),
                 });
                 (self.send)(message);
 
@@ -192,6 +197,7 @@ impl NativeSyncClient {
                         request_id,
                         status: Some(MutationStatus::InternalError),
                         message: Some(format!("Serialization failed: {:?}", err)),
+                        field_errors: Vec::new(),
                     },
                 );
 
@@ -218,11 +224,13 @@ impl NativeSyncClient {
                 .and_modify(|state| {
                     state.status = Some(response.status.clone());
                     state.message = response.message.clone();
+                    state.field_errors = response.field_errors.clone();
                 })
                 .or_insert_with(|| NativeMutationState {
                     request_id,
                     status: Some(response.status.clone()),
                     message: response.message.clone(),
+                    field_errors: response.field_errors.clone(),
                 });
         }
     }
@@ -1,81 +1,34 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use leptos::prelude::*;
-use leptos_use::{use_websocket_with_options, DummyEncoder, UseWebSocketOptions, UseWebSocketReturn};
-use pl3xus_common::codec::Pl3xusBincodeCodec;
-use pl3xus_common::NetworkPacket;
+use leptos_use::{
+    use_idle, use_websocket_with_options, use_window_focus, DummyEncoder, UseIdleReturn, UseWebSocketOptions,
+    UseWebSocketReturn,
+};
+use pl3xus_common::codec::{Pl3xusBincodeCodec, BATCH_SUBPROTOCOL, BINCODE_SUBPROTOCOL};
+use pl3xus_common::{ClockPong, ControlKeepalive, NetworkPacket, PayloadTooLarge, Pl3xusMessage, ReliableAck};
 
 use crate::client_type_registry::ClientTypeRegistry;
 use crate::context::SyncContext;
 use crate::error::SyncError;
+use pl3xus_client_core::{apply_sync_item, FrameDecoder};
 use pl3xus_sync::{SyncClientMessage, SyncServerMessage};
 
-/// Decode all length-prefixed NetworkPackets from a byte buffer.
-/// The server may batch multiple messages into a single WebSocket frame.
-/// Each message is prefixed with an 8-byte little-endian length.
-fn decode_all_packets(data: &[u8]) -> Vec<NetworkPacket> {
-    let mut packets = Vec::new();
-    let mut offset = 0;
-
-    while offset + 8 <= data.len() {
-        // Read 8-byte length prefix
-        let length_bytes: [u8; 8] = match data[offset..offset + 8].try_into() {
-            Ok(b) => b,
-            Err(_) => break,
-        };
-        let length = u64::from_le_bytes(length_bytes) as usize;
-        offset += 8;
-
-        // Check if we have enough data for this message
-        if offset + length > data.len() {
-            #[cfg(target_arch = "wasm32")]
-            leptos::logging::warn!(
-                "[decode_all_packets] Incomplete message: need {} bytes, have {}",
-                length,
-                data.len() - offset
-            );
-            break;
-        }
+/// Default interval between control keepalives.
+const DEFAULT_CONTROL_KEEPALIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(20);
 
-        // Decode the NetworkPacket
-        match bincode::serde::decode_from_slice::<NetworkPacket, _>(
-            &data[offset..offset + length],
-            bincode::config::standard(),
-        ) {
-            Ok((packet, _)) => {
-                packets.push(packet);
-            }
-            Err(_e) => {
-                #[cfg(target_arch = "wasm32")]
-                leptos::logging::warn!(
-                    "[decode_all_packets] Failed to decode packet at offset {}: {:?}",
-                    offset,
-                    _e
-                );
-                break;
-            }
-        }
-
-        offset += length;
-    }
-
-    #[cfg(target_arch = "wasm32")]
-    if packets.len() > 1 {
-        leptos::logging::log!(
-            "[decode_all_packets] Decoded {} packets from {} bytes",
-            packets.len(),
-            data.len()
-        );
-    }
-
-    packets
-}
+/// Default time without input after which the user counts as idle.
+const DEFAULT_IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
 
 /// Provider component that sets up WebSocket connection and provides SyncContext.
 ///
 /// This component should wrap your application or the part of your application
 /// that needs access to synchronized ECS data.
 ///
+/// The WebSocket is only opened from an effect, i.e. in the browser once the
+/// app has hydrated. Under Leptos SSR the provider and all hooks render their
+/// empty/disconnected state on the server, which the client then hydrates.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -107,6 +60,19 @@ pub fn SyncProvider(
     /// Whether to automatically connect on mount (default: true)
     #[prop(optional)]
     auto_connect: Option<bool>,
+    /// Ping the server at this interval to keep [`use_server_time`](crate::use_server_time)
+    /// synchronized (default: off, which only syncs the clock once per connection).
+    #[prop(optional)]
+    clock_sync_interval: Option<std::time::Duration>,
+    /// Send a `ControlKeepalive` at this interval while the window is focused
+    /// and the user isn't idle, so held control doesn't time out (default: 20s).
+    /// Set to zero to disable.
+    #[prop(optional)]
+    control_keepalive_interval: Option<std::time::Duration>,
+    /// Time without mouse, keyboard or touch input after which the user counts
+    /// as idle and keepalives stop (default: 5 minutes).
+    #[prop(optional)]
+    idle_timeout: Option<std::time::Duration>,
     /// Child components
     children: Children,
 ) -> impl IntoView {
@@ -193,6 +159,7 @@ pub fn SyncProvider(
     // Set up WebSocket connection using NetworkPacket wrapper
     // Use on_message_raw_bytes to handle batched messages from the server
     let ctx_for_callback = ctx.clone();
+    let frames = Arc::new(Mutex::new(FrameDecoder::default()));
     let UseWebSocketReturn {
         ready_state,
         send: raw_send,
@@ -208,7 +175,9 @@ pub fn SyncProvider(
     >(
         &url,
         UseWebSocketOptions::default()
-            .immediate(auto_connect)
+            .immediate(false)
+            // Servers that know batched frames pick them; older ones plain bincode
+            .protocols(Some(vec![BATCH_SUBPROTOCOL.to_string(), BINCODE_SUBPROTOCOL.to_string()]))
             .on_open(move |_| {
                 #[cfg(target_arch = "wasm32")]
                 leptos::logging::log!("[SyncProvider] WebSocket opened!");
@@ -219,7 +188,7 @@ pub fn SyncProvider(
             })
             .on_message_raw_bytes(Arc::new(move |data: &[u8]| {
                 // Decode all packets from the raw bytes (handles batched messages)
-                let packets = decode_all_packets(data);
+                let packets = frames.lock().unwrap().decode(data);
 
                 for packet in packets {
                     #[cfg(target_arch = "wasm32")]
@@ -229,7 +198,7 @@ pub fn SyncProvider(
                         packet.data.len()
                     );
 
-                    handle_packet(&ctx_for_callback, &packet, &last_error);
+                    handle_packet(&ctx_for_callback, &packet);
                 }
             })),
     );
@@ -251,16 +220,65 @@ pub fn SyncProvider(
         ready_state_signal.set(state);
     });
 
+    // Connect from an effect rather than during render: effects never run on
+    // the server, and in the browser they run after hydration, so SSR output
+    // and the hydrated view both start disconnected.
+    if auto_connect {
+        Effect::new(move |_| {
+            if let Some(open) = open_fn.get_value() {
+                open();
+            }
+        });
+    }
+
+    // Clock sync: the Welcome message starts a burst of pings, then one per interval
+    if let Some(interval) = clock_sync_interval {
+        // Started from an effect so no timer is created during SSR
+        let ctx_for_interval = ctx.clone();
+        Effect::new(move |_| {
+            let ctx_for_interval = ctx_for_interval.clone();
+            set_interval(
+                move || {
+                    if ready_state_signal.get_untracked() == leptos_use::core::ConnectionReadyState::Open {
+                        ctx_for_interval.send_clock_pings(1);
+                    }
+                },
+                interval,
+            );
+        });
+    }
+
+    // Control keepalives: only while someone is actually at the app
+    let control_keepalive_interval = control_keepalive_interval.unwrap_or(DEFAULT_CONTROL_KEEPALIVE_INTERVAL);
+    if !control_keepalive_interval.is_zero() {
+        let focused = use_window_focus();
+        let UseIdleReturn { idle, .. } =
+            use_idle(idle_timeout.unwrap_or(DEFAULT_IDLE_TIMEOUT).as_millis() as u64);
+
+        // Started from an effect so no timer is created during SSR
+        let ctx_for_keepalive = ctx.clone();
+        Effect::new(move |_| {
+            let ctx_for_keepalive = ctx_for_keepalive.clone();
+            set_interval(
+                move || {
+                    if ready_state_signal.get_untracked() == leptos_use::core::ConnectionReadyState::Open
+                        && focused.get_untracked()
+                        && !idle.get_untracked()
+                    {
+                        ctx_for_keepalive.send(ControlKeepalive);
+                    }
+                },
+                control_keepalive_interval,
+            );
+        });
+    }
+
     // Render children
     children()
 }
 
 /// Handle a single NetworkPacket by routing it to the appropriate handler.
-fn handle_packet(
-    ctx: &SyncContext,
-    packet: &NetworkPacket,
-    last_error: &RwSignal<Option<SyncError>>,
-) {
+fn handle_packet(ctx: &SyncContext, packet: &NetworkPacket) {
     // Check type_name first to determine how to deserialize
     // This prevents misinterpreting ResponseInternal data as SyncServerMessage
     if packet.type_name.contains("SyncServerMessage") {
@@ -273,7 +291,7 @@ fn handle_packet(
                 #[cfg(target_arch = "wasm32")]
                 leptos::logging::log!("[SyncProvider] Successfully deserialized SyncServerMessage");
 
-                handle_server_message(ctx, server_msg, last_error);
+                handle_server_message(ctx, server_msg);
             }
             Err(_e) => {
                 #[cfg(target_arch = "wasm32")]
@@ -317,6 +335,29 @@ fn handle_packet(
                 packet.data.len()
             );
         }
+    } else if packet.type_name == ClockPong::type_name() {
+        if let Ok((pong, _)) = bincode::serde::decode_from_slice::<ClockPong, _>(
+            &packet.data,
+            bincode::config::standard(),
+        ) {
+            ctx.handle_clock_pong(&pong);
+        }
+    } else if packet.type_name == ReliableAck::type_name() {
+        if let Ok((ack, _)) = bincode::serde::decode_from_slice::<ReliableAck, _>(
+            &packet.data,
+            bincode::config::standard(),
+        ) {
+            ctx.handle_reliable_ack(&ack);
+        }
+    } else if packet.type_name == PayloadTooLarge::type_name() {
+        if let Ok((rejection, _)) = bincode::serde::decode_from_slice::<PayloadTooLarge, _>(
+            &packet.data,
+            bincode::config::standard(),
+        ) {
+            ctx.handle_payload_too_large(&rejection);
+        }
+        // Still delivered to use_message subscribers
+        ctx.handle_incoming_message(packet.type_name.clone(), packet.data.clone());
     } else {
         // Treat as arbitrary Pl3xusMessage
         #[cfg(target_arch = "wasm32")]
@@ -330,11 +371,7 @@ fn handle_packet(
 }
 
 /// Handle incoming server messages.
-fn handle_server_message(
-    ctx: &SyncContext,
-    msg: SyncServerMessage,
-    last_error: &RwSignal<Option<SyncError>>,
-) {
+fn handle_server_message(ctx: &SyncContext, msg: SyncServerMessage) {
     match msg {
         SyncServerMessage::Welcome(welcome) => {
             // Store our connection ID so we can compare with EntityControl
@@ -342,126 +379,59 @@ fn handle_server_message(
             // when updating signals inside Effects (per research/LESSONS_LEARNED.md)
             #[cfg(target_arch = "wasm32")]
             leptos::logging::log!("Received Welcome message with connection ID: {:?}", welcome.connection_id);
+            if welcome.protocol_version != pl3xus_sync::SYNC_PROTOCOL_VERSION {
+                leptos::logging::warn!(
+                    "Server speaks sync protocol {}, this client {}",
+                    welcome.protocol_version,
+                    pl3xus_sync::SYNC_PROTOCOL_VERSION
+                );
+            }
+            ctx.start_server_session(welcome.session);
             ctx.my_connection_id.try_update_untracked(|id| *id = Some(welcome.connection_id));
             ctx.my_connection_id.notify();
+            ctx.start_clock_sync();
+            ctx.reset_sync_sequences();
+            ctx.resend_reliable();
         }
         SyncServerMessage::SyncBatch(batch) => {
-            // Process each sync item in the batch
-            for item in batch.items {
-                if let Err(e) = handle_sync_item(ctx, item) {
-                    // Use try_update_untracked + notify to avoid reactive graph issues
-                    last_error.try_update_untracked(|err| *err = Some(e));
-                    last_error.notify();
+            ctx.check_sync_sequences(&batch.sequences);
+            if batch.sent_at_ms.is_some() {
+                ctx.latency.try_update_untracked(|tracker| tracker.record_batch(&batch));
+                ctx.latency.notify();
+            }
+            // Store the raw bytes; the Effects in subscribe_component deserialize
+            // them to typed signals. Use try_update_untracked + notify to avoid
+            // reactive graph issues.
+            let mut changed = false;
+            ctx.component_data.try_update_untracked(|data| {
+                for item in batch.items {
+                    changed |= !apply_sync_item(data, item).is_empty();
                 }
+            });
+            if changed {
+                ctx.component_data.notify();
             }
         }
         SyncServerMessage::MutationResponse(response) => {
             // Handle mutation response
             ctx.handle_mutation_response(&response);
         }
-        SyncServerMessage::QueryResponse(_response) => {
-            // TODO: Handle query responses when we implement queries
+        SyncServerMessage::QueryResponse(response) => {
+            ctx.handle_query_response(&response);
+        }
+        SyncServerMessage::LiveQueryUpdate(update) => {
+            ctx.handle_live_query_update(&update);
         }
         SyncServerMessage::QueryInvalidation(invalidation) => {
             // Handle query cache invalidation
             ctx.handle_query_invalidation(&invalidation);
         }
-    }
-}
-
-/// Handle a single sync item.
-fn handle_sync_item(
-    ctx: &SyncContext,
-    item: pl3xus_sync::SyncItem,
-) -> Result<(), SyncError> {
-    use pl3xus_sync::SyncItem;
-
-    #[cfg(target_arch = "wasm32")]
-    let is_snapshot = matches!(&item, SyncItem::Snapshot { .. });
-
-    match item {
-        SyncItem::Snapshot {
-            subscription_id: _,
-            entity,
-            component_type,
-            value,
-        } | SyncItem::Update {
-            subscription_id: _,
-            entity,
-            component_type,
-            value,
-        } => {
-            let entity_id = entity.bits;
-
-            // Log for debugging
-            #[cfg(target_arch = "wasm32")]
-            {
-                leptos::logging::log!(
-                    "[SyncProvider] Received {} for entity {} component {} ({} bytes)",
-                    if is_snapshot { "Snapshot" } else { "Update" },
-                    entity_id,
-                    component_type,
-                    value.len()
-                );
-            }
-
-            // Update the component_data signal with raw bytes
-            // The Effect in subscribe_component will deserialize and update typed signals
-            // Use try_update_untracked + notify to avoid reactive graph issues
-            ctx.component_data.try_update_untracked(|data| {
-                data.insert((entity_id, component_type.clone()), value);
-            });
-            ctx.component_data.notify();
-
-            Ok(())
-        }
-        SyncItem::ComponentRemoved {
-            subscription_id: _,
-            entity,
-            component_type,
-        } => {
-            let entity_id = entity.bits;
-
-            #[cfg(target_arch = "wasm32")]
-            {
-                leptos::logging::log!(
-                    "[SyncProvider] Component {} removed from entity {}",
-                    component_type,
-                    entity_id
-                );
-            }
-
-            // Remove the component from component_data
-            // Use try_update_untracked + notify to avoid reactive graph issues
-            ctx.component_data.try_update_untracked(|data| {
-                data.remove(&(entity_id, component_type.clone()));
-            });
-            ctx.component_data.notify();
-
-            Ok(())
+        SyncServerMessage::StateTransition(transition) => {
+            ctx.handle_state_transition(transition);
         }
-        SyncItem::EntityRemoved {
-            subscription_id: _,
-            entity,
-        } => {
-            let entity_id = entity.bits;
-
-            #[cfg(target_arch = "wasm32")]
-            {
-                leptos::logging::log!(
-                    "[SyncProvider] Entity {} removed",
-                    entity_id
-                );
-            }
-
-            // Remove all components for this entity
-            // Use try_update_untracked + notify to avoid reactive graph issues
-            ctx.component_data.try_update_untracked(|data| {
-                data.retain(|(eid, _), _| *eid != entity_id);
-            });
-            ctx.component_data.notify();
-
-            Ok(())
+        SyncServerMessage::RegistryUpdated(update) => {
+            // Subscriptions to the new types are served by the server as is
+            ctx.registry_update.set(Some(update));
         }
     }
 }
@@ -0,0 +1,176 @@
+//! Client-side cache of blobs fetched by [`BlobRef`].
+//!
+//! [`BlobCache`] drives the chunked [`FetchBlob`] exchange for each blob and
+//! keeps finished blobs by content hash, so a blob referenced by several
+//! entities or repeated updates is fetched once. Frontends send the requests
+//! it returns and feed it the [`BlobChunk`] responses.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use pl3xus_common::{BlobChunk, BlobRef, FetchBlob};
+
+/// What to do after receiving a chunk.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlobProgress {
+    /// Send this request for the next chunk.
+    Next(FetchBlob),
+    /// The blob is complete and cached.
+    Complete(Arc<[u8]>),
+}
+
+/// Finished blobs by hash, plus the ones being fetched.
+#[derive(Debug)]
+pub struct BlobCache {
+    blobs: HashMap<String, Arc<[u8]>>,
+    /// Hashes in the order they were last used, oldest first.
+    order: VecDeque<String>,
+    in_flight: HashMap<String, Vec<u8>>,
+    total_bytes: usize,
+    max_bytes: usize,
+}
+
+impl Default for BlobCache {
+    fn default() -> Self {
+        Self::new(64 * 1024 * 1024)
+    }
+}
+
+impl BlobCache {
+    /// Create a cache keeping at most `max_bytes` of finished blobs.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            blobs: HashMap::new(),
+            order: VecDeque::new(),
+            in_flight: HashMap::new(),
+            total_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// A cached blob's content.
+    pub fn get(&mut self, hash: &str) -> Option<Arc<[u8]>> {
+        let data = self.blobs.get(hash)?.clone();
+        self.touch(hash);
+        Some(data)
+    }
+
+    /// Returns true while `hash` is being fetched.
+    pub fn is_fetching(&self, hash: &str) -> bool {
+        self.in_flight.contains_key(hash)
+    }
+
+    /// The first request for `blob`, or `None` if it is cached, already being
+    /// fetched, or empty.
+    pub fn start(&mut self, blob: &BlobRef) -> Option<FetchBlob> {
+        if blob.is_empty() || self.blobs.contains_key(&blob.hash) || self.is_fetching(&blob.hash) {
+            return None;
+        }
+        self.in_flight
+            .insert(blob.hash.clone(), Vec::with_capacity(blob.size.min(u32::MAX as u64) as usize));
+        Some(FetchBlob::first(blob))
+    }
+
+    /// Add a received chunk.
+    ///
+    /// Fails, and stops fetching the blob, if the server reported an error or
+    /// the chunk doesn't continue what was received so far.
+    pub fn receive(&mut self, chunk: BlobChunk) -> Result<BlobProgress, String> {
+        if let Some(error) = chunk.error {
+            self.in_flight.remove(&chunk.hash);
+            return Err(error);
+        }
+        let Some(data) = self.in_flight.get_mut(&chunk.hash) else {
+            return Err(format!("Blob {} isn't being fetched", chunk.hash));
+        };
+        if chunk.offset != data.len() as u64 || (chunk.data.is_empty() && !chunk.is_last()) {
+            self.in_flight.remove(&chunk.hash);
+            return Err(format!("Unexpected chunk at offset {} of blob {}", chunk.offset, chunk.hash));
+        }
+
+        data.extend_from_slice(&chunk.data);
+        if !chunk.is_last() {
+            return Ok(BlobProgress::Next(FetchBlob {
+                offset: data.len() as u64,
+                max_len: pl3xus_common::BLOB_CHUNK_SIZE,
+                hash: chunk.hash,
+            }));
+        }
+
+        let data: Arc<[u8]> = self.in_flight.remove(&chunk.hash).unwrap_or_default().into();
+        self.insert(chunk.hash, data.clone());
+        Ok(BlobProgress::Complete(data))
+    }
+
+    /// Stop fetching a blob, e.g. after its request failed.
+    pub fn cancel(&mut self, hash: &str) {
+        self.in_flight.remove(hash);
+    }
+
+    /// Total size of the cached blobs in bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    fn insert(&mut self, hash: String, data: Arc<[u8]>) {
+        self.total_bytes += data.len();
+        self.blobs.insert(hash.clone(), data);
+        self.order.push_back(hash);
+        // Evict the least recently used, but never the blob just added
+        while self.total_bytes > self.max_bytes && self.order.len() > 1 {
+            if let Some(evicted) = self.order.pop_front()
+                && let Some(data) = self.blobs.remove(&evicted)
+            {
+                self.total_bytes -= data.len();
+            }
+        }
+    }
+
+    fn touch(&mut self, hash: &str) {
+        if let Some(position) = self.order.iter().position(|stored| stored == hash)
+            && let Some(hash) = self.order.remove(position)
+        {
+            self.order.push_back(hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(offset: u64, data: &[u8]) -> BlobChunk {
+        BlobChunk {
+            hash: "abc".into(),
+            offset,
+            total_size: 5,
+            data: data.to_vec(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_fetch_in_chunks() {
+        let blob = BlobRef {
+            hash: "abc".into(),
+            size: 5,
+            content_type: None,
+        };
+        let mut cache = BlobCache::default();
+        assert_eq!(cache.start(&blob).map(|request| request.offset), Some(0));
+        assert!(cache.start(&blob).is_none());
+
+        let next = cache.receive(chunk(0, &[1, 2, 3])).unwrap();
+        assert!(matches!(next, BlobProgress::Next(FetchBlob { offset: 3, .. })));
+        let done = cache.receive(chunk(3, &[4, 5])).unwrap();
+        assert_eq!(done, BlobProgress::Complete(Arc::from(&[1, 2, 3, 4, 5][..])));
+        assert_eq!(cache.get("abc").as_deref(), Some(&[1, 2, 3, 4, 5][..]));
+        assert!(cache.start(&blob).is_none());
+
+        // Chunks out of order fail the fetch
+        let mut cache = BlobCache::default();
+        cache.start(&blob);
+        assert!(cache.receive(chunk(3, &[4, 5])).is_err());
+        assert!(!cache.is_fetching("abc"));
+    }
+}
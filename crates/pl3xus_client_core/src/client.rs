@@ -0,0 +1,407 @@
+//! Protocol state for frontends without their own context.
+//!
+//! [`SyncClientCore`] owns everything about a sync connection except the
+//! socket and the reactive cells: subscriptions, received component data and
+//! request ids. A frontend adapter feeds it incoming frames, sends the packets
+//! it returns, and updates its own signals from the [`ClientEvent`]s.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pl3xus_common::{ConnectionId, ConnectionIdentity, NetworkPacket, PayloadTooLarge, Pl3xusMessage, RequestMessage};
+use pl3xus_sync::{
+    LiveQueryUpdate, MutateComponent, MutationResponse, QueryInvalidation, QueryResponse, RegistryUpdate,
+    SerializableEntity, SubscriptionFilter, SyncClientMessage, SyncServerMessage, UnsubscribeRequest,
+};
+use serde_json::Value as JsonValue;
+
+use crate::client_type_registry::{request_packet, ClientTypeRegistry};
+use crate::component_data::{apply_sync_item, decode_components, ComponentData};
+use crate::error::SyncError;
+use crate::json_codec::SchemaCodec;
+use crate::packet::{sync_packet, FrameDecoder};
+use crate::reliable::random_u64;
+use crate::subscriptions::{filtered_subscription_key, SubscriptionTracker};
+use crate::traits::SyncComponent;
+
+/// Something the frontend may need to react to.
+#[derive(Clone, Debug)]
+pub enum ClientEvent {
+    /// The server assigned this client's connection id.
+    Welcome(ConnectionId),
+    /// Received values of these component types changed.
+    ComponentsChanged(Vec<String>),
+    MutationResponse(MutationResponse),
+    QueryInvalidation(QueryInvalidation),
+    /// Row changes of a live query, to apply with
+    /// [`LiveQueryRows::apply`](crate::LiveQueryRows::apply).
+    LiveQueryUpdate(LiveQueryUpdate),
+    /// Result of a one-shot query, or why a query failed.
+    QueryResponse(QueryResponse),
+    /// Response body for a request sent with [`SyncClientCore::request`].
+    Response { request_id: u64, data: Vec<u8> },
+    /// The server refused a request without running it, e.g. because it was
+    /// over the request type's size limit.
+    RequestRejected { request_id: u64, reason: String },
+    /// The server registered new types while running.
+    RegistryUpdated(RegistryUpdate),
+    /// Any other message, by short type name.
+    Message { type_name: String, data: Vec<u8> },
+}
+
+/// Framework-agnostic state of one sync connection.
+pub struct SyncClientCore {
+    registry: Arc<ClientTypeRegistry>,
+    subscriptions: SubscriptionTracker,
+    data: ComponentData,
+    identity: Option<ConnectionIdentity>,
+    next_request_id: u64,
+    frames: FrameDecoder,
+}
+
+impl SyncClientCore {
+    pub fn new(registry: Arc<ClientTypeRegistry>) -> Self {
+        Self {
+            registry,
+            subscriptions: SubscriptionTracker::default(),
+            data: ComponentData::new(),
+            identity: None,
+            next_request_id: 0,
+            frames: FrameDecoder::default(),
+        }
+    }
+
+    pub fn registry(&self) -> &Arc<ClientTypeRegistry> {
+        &self.registry
+    }
+
+    /// This client's connection id, once the server has welcomed it.
+    pub fn connection_id(&self) -> Option<ConnectionId> {
+        self.identity.map(|identity| identity.connection)
+    }
+
+    /// This client's connection id qualified by the server run, once welcomed.
+    ///
+    /// Compare identities rather than bare ids when state may outlive a
+    /// server restart.
+    pub fn identity(&self) -> Option<ConnectionIdentity> {
+        self.identity
+    }
+
+    /// Raw values of every received component.
+    pub fn data(&self) -> &ComponentData {
+        &self.data
+    }
+
+    /// Subscribe to a component type, optionally filtered on the server.
+    ///
+    /// Returns the subscription key to pass to [`unsubscribe`](Self::unsubscribe),
+    /// and the packet to send if this is the first subscriber for that key.
+    /// Packets produced while disconnected can be dropped:
+    /// [`subscription_packets`](Self::subscription_packets) resends them all.
+    pub fn subscribe(
+        &mut self,
+        component_name: &str,
+        filter: Option<SubscriptionFilter>,
+    ) -> (String, Option<NetworkPacket>) {
+        let key = match &filter {
+            Some(filter) => filtered_subscription_key(component_name, filter),
+            None => component_name.to_string(),
+        };
+        let packet = self
+            .subscriptions
+            .acquire(&key, filter)
+            .then(|| self.subscriptions.request(&key, None))
+            .flatten()
+            .map(|request| sync_packet(&SyncClientMessage::Subscription(request)));
+        (key, packet)
+    }
+
+    /// Drop one subscriber. Returns the unsubscribe packet after the last one.
+    pub fn unsubscribe(&mut self, key: &str) -> Option<NetworkPacket> {
+        let subscription_id = self.subscriptions.release(key)?;
+        Some(sync_packet(&SyncClientMessage::Unsubscribe(UnsubscribeRequest {
+            subscription_id,
+        })))
+    }
+
+    /// Subscription packets for every active subscription. Send these when
+    /// the socket (re)opens.
+    pub fn subscription_packets(&self) -> Vec<NetworkPacket> {
+        self.subscriptions
+            .requests()
+            .into_iter()
+            .map(|request| sync_packet(&SyncClientMessage::Subscription(request)))
+            .collect()
+    }
+
+    /// Decoded values of `T` by entity id, keeping only those matching `filter`.
+    pub fn components<T: SyncComponent>(&self, filter: Option<&SubscriptionFilter>) -> HashMap<u64, T> {
+        decode_components(&self.data, &self.registry, filter)
+    }
+
+    /// Build a mutation of `entity_id`'s `T`. Returns its request id, matched
+    /// by the eventual [`ClientEvent::MutationResponse`].
+    pub fn mutate<T: SyncComponent>(&mut self, entity_id: u64, value: &T) -> Result<(u64, NetworkPacket), SyncError> {
+        let component_name = T::component_name();
+        let value = bincode::serde::encode_to_vec(value, bincode::config::standard()).map_err(|e| {
+            SyncError::SerializationFailed {
+                component_name: component_name.to_string(),
+                error: e.to_string(),
+            }
+        })?;
+        Ok(self.mutation_packet(entity_id, component_name, value))
+    }
+
+    /// [`mutate`](Self::mutate) with a JSON value, encoded from the server's schema.
+    pub fn mutate_json(
+        &mut self,
+        codec: &SchemaCodec,
+        entity_id: u64,
+        component_name: &str,
+        value: &JsonValue,
+    ) -> Result<(u64, NetworkPacket), SyncError> {
+        let value = codec
+            .encode_component(component_name, value)
+            .map_err(|error| SyncError::SerializationFailed {
+                component_name: component_name.to_string(),
+                error,
+            })?;
+        Ok(self.mutation_packet(entity_id, component_name, value))
+    }
+
+    fn mutation_packet(&mut self, entity_id: u64, component_name: &str, value: Vec<u8>) -> (u64, NetworkPacket) {
+        let request_id = self.next_request_id();
+        let packet = sync_packet(&SyncClientMessage::Mutate(MutateComponent {
+            request_id: Some(request_id),
+            entity: SerializableEntity { bits: entity_id },
+            component_type: component_name.to_string(),
+            value,
+            idempotency_key: Some(random_u64()),
+        }));
+        (request_id, packet)
+    }
+
+    /// Build a request. Returns its request id, matched by the eventual
+    /// [`ClientEvent::Response`].
+    pub fn request<R: RequestMessage>(&mut self, request: R) -> Result<(u64, NetworkPacket), SyncError> {
+        let request_id = self.next_request_id();
+        let packet = request_packet(request, request_id).map_err(|error| SyncError::SerializationFailed {
+            component_name: R::request_name().to_string(),
+            error,
+        })?;
+        Ok((request_id, packet))
+    }
+
+    /// [`request`](Self::request) with a JSON payload, encoded from the
+    /// server's schema. Decode the response with
+    /// [`SchemaCodec::decode_response`].
+    pub fn request_json(
+        &mut self,
+        codec: &SchemaCodec,
+        request_name: &str,
+        request: &JsonValue,
+    ) -> Result<(u64, NetworkPacket), SyncError> {
+        let request_id = self.next_request_id();
+        let packet =
+            codec
+                .encode_request(request_name, request, request_id)
+                .map_err(|error| SyncError::SerializationFailed {
+                    component_name: request_name.to_string(),
+                    error,
+                })?;
+        Ok((request_id, packet))
+    }
+
+    fn next_request_id(&mut self) -> u64 {
+        self.next_request_id += 1;
+        self.next_request_id
+    }
+
+    /// Handle one incoming WebSocket frame.
+    pub fn handle_frame(&mut self, frame: &[u8]) -> Vec<ClientEvent> {
+        self.frames
+            .decode(frame)
+            .into_iter()
+            .flat_map(|packet| self.handle_packet(packet))
+            .collect()
+    }
+
+    /// Handle one incoming packet.
+    pub fn handle_packet(&mut self, packet: NetworkPacket) -> Vec<ClientEvent> {
+        if packet.type_name.contains("SyncServerMessage") {
+            let Ok((message, _)) =
+                bincode::serde::decode_from_slice::<SyncServerMessage, _>(&packet.data, bincode::config::standard())
+            else {
+                return Vec::new();
+            };
+            return self.handle_server_message(message);
+        }
+
+        if packet.type_name.contains("ResponseInternal<") {
+            // ResponseInternal is { response_id: u64, response: T }
+            let Ok((request_id, header_len)) =
+                bincode::serde::decode_from_slice::<u64, _>(&packet.data, bincode::config::standard())
+            else {
+                return Vec::new();
+            };
+            return vec![ClientEvent::Response {
+                request_id,
+                data: packet.data[header_len..].to_vec(),
+            }];
+        }
+
+        if packet.type_name == PayloadTooLarge::type_name() {
+            let rejection =
+                bincode::serde::decode_from_slice::<PayloadTooLarge, _>(&packet.data, bincode::config::standard());
+            if let Ok((PayloadTooLarge { request_id: Some(request_id), size, limit, .. }, _)) = rejection {
+                return vec![ClientEvent::RequestRejected {
+                    request_id,
+                    reason: format!("Too large: {} bytes (limit {})", size, limit),
+                }];
+            }
+        }
+
+        let type_name = packet.type_name.rsplit("::").next().unwrap_or(&packet.type_name).to_string();
+        vec![ClientEvent::Message {
+            type_name,
+            data: packet.data,
+        }]
+    }
+
+    fn handle_server_message(&mut self, message: SyncServerMessage) -> Vec<ClientEvent> {
+        match message {
+            SyncServerMessage::Welcome(welcome) => {
+                let mut events = vec![ClientEvent::Welcome(welcome.connection_id)];
+                // Entities (and the connection ids in their EntityControl)
+                // from a previous server run are stale
+                if self.identity.is_some_and(|identity| identity.session != welcome.session) {
+                    let mut cleared: Vec<String> = Vec::new();
+                    for (_, component_type) in self.data.drain().map(|(key, _)| key) {
+                        if !cleared.contains(&component_type) {
+                            cleared.push(component_type);
+                        }
+                    }
+                    if !cleared.is_empty() {
+                        events.push(ClientEvent::ComponentsChanged(cleared));
+                    }
+                }
+                self.identity = Some(welcome.identity());
+                events
+            }
+            SyncServerMessage::SyncBatch(batch) => {
+                let mut changed = Vec::new();
+                for item in batch.items {
+                    for component_type in apply_sync_item(&mut self.data, item) {
+                        if !changed.contains(&component_type) {
+                            changed.push(component_type);
+                        }
+                    }
+                }
+                if changed.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![ClientEvent::ComponentsChanged(changed)]
+                }
+            }
+            SyncServerMessage::MutationResponse(response) => vec![ClientEvent::MutationResponse(response)],
+            SyncServerMessage::QueryInvalidation(invalidation) => vec![ClientEvent::QueryInvalidation(invalidation)],
+            SyncServerMessage::RegistryUpdated(update) => vec![ClientEvent::RegistryUpdated(update)],
+            SyncServerMessage::LiveQueryUpdate(update) => vec![ClientEvent::LiveQueryUpdate(update)],
+            SyncServerMessage::QueryResponse(response) => vec![ClientEvent::QueryResponse(response)],
+            SyncServerMessage::StateTransition(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::encode_frame;
+    use pl3xus_sync::{SyncBatch, SyncItem, WelcomeMessage};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Battery {
+        level: f32,
+    }
+
+    fn server_packet(message: &SyncServerMessage) -> NetworkPacket {
+        NetworkPacket {
+            type_name: std::any::type_name::<SyncServerMessage>().to_string(),
+            schema_hash: 0,
+            data: bincode::serde::encode_to_vec(message, bincode::config::standard()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_subscribe_and_receive_batch() {
+        let registry = ClientTypeRegistry::builder().register::<Battery>().build();
+        let mut client = SyncClientCore::new(registry);
+
+        let (key, packet) = client.subscribe("Battery", None);
+        assert!(packet.is_some());
+        assert!(client.subscribe("Battery", None).1.is_none());
+        assert_eq!(client.subscription_packets().len(), 1);
+
+        let welcome = server_packet(&SyncServerMessage::Welcome(WelcomeMessage {
+            connection_id: ConnectionId { id: 4 },
+            protocol_version: pl3xus_sync::SYNC_PROTOCOL_VERSION,
+            session: 99,
+        }));
+        let batch = server_packet(&SyncServerMessage::SyncBatch(SyncBatch {
+            items: vec![SyncItem::Snapshot {
+                subscription_id: 0,
+                entity: SerializableEntity { bits: 9 },
+                component_type: "Battery".into(),
+                value: bincode::serde::encode_to_vec(Battery { level: 0.5 }, bincode::config::standard()).unwrap(),
+            }],
+            sent_at_ms: None,
+            sequences: Vec::new(),
+        }));
+        let mut frame = encode_frame(&welcome);
+        frame.extend(encode_frame(&batch));
+
+        let events = client.handle_frame(&frame);
+        assert!(matches!(events[0], ClientEvent::Welcome(ConnectionId { id: 4 })));
+        assert!(matches!(&events[1], ClientEvent::ComponentsChanged(types) if types == &["Battery"]));
+        assert_eq!(client.components::<Battery>(None)[&9], Battery { level: 0.5 });
+        assert_eq!(client.identity().map(|identity| identity.session), Some(99));
+
+        // A restarted server's entities replace the previous run's
+        let restarted = server_packet(&SyncServerMessage::Welcome(WelcomeMessage {
+            connection_id: ConnectionId { id: 4 },
+            protocol_version: pl3xus_sync::SYNC_PROTOCOL_VERSION,
+            session: 100,
+        }));
+        let events = client.handle_frame(&encode_frame(&restarted));
+        assert!(matches!(&events[1], ClientEvent::ComponentsChanged(types) if types == &["Battery"]));
+        assert!(client.components::<Battery>(None).is_empty());
+
+        assert!(client.unsubscribe(&key).is_none());
+        assert!(client.unsubscribe(&key).is_some());
+    }
+
+    #[test]
+    fn test_payload_too_large_rejects_request() {
+        let mut client = SyncClientCore::new(ClientTypeRegistry::builder().build());
+        let rejection = |request_id| NetworkPacket {
+            type_name: PayloadTooLarge::type_name().to_string(),
+            schema_hash: 0,
+            data: bincode::serde::encode_to_vec(
+                PayloadTooLarge { type_name: "UploadCsv".into(), request_id, size: 2048, limit: 1024 },
+                bincode::config::standard(),
+            )
+            .unwrap(),
+        };
+
+        let events = client.handle_packet(rejection(Some(7)));
+        assert!(matches!(
+            &events[0],
+            ClientEvent::RequestRejected { request_id: 7, reason } if reason.contains("2048")
+        ));
+        // Rejected messages have no request to fail
+        let events = client.handle_packet(rejection(None));
+        assert!(matches!(&events[0], ClientEvent::Message { type_name, .. } if type_name == "PayloadTooLarge"));
+    }
+}
@@ -0,0 +1,804 @@
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use pl3xus_common::{
+    describe_type, ManifestComponent, NetworkPacket, Pl3xusMessage, RequestMessage, SchemaDescription, TypeManifest,
+    TypeShape, TypeVisitor,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::error::SyncError;
+use crate::schema_default::default_json;
+use crate::traits::SyncComponent;
+
+/// Function type for deserializing bincode bytes to JSON.
+type JsonDeserializeFn = fn(&[u8]) -> Result<serde_json::Value, bincode::error::DecodeError>;
+
+/// Function type for serializing JSON to bincode bytes.
+type JsonSerializeFn = fn(&serde_json::Value) -> Result<Vec<u8>, bincode::error::EncodeError>;
+
+/// Function type for encoding a JSON payload as a packet, given a request id.
+type ConsoleEncodeFn = fn(&serde_json::Value, u64) -> Result<NetworkPacket, String>;
+
+/// Function type for decoding a response body to JSON.
+type ConsoleDecodeFn = fn(&[u8]) -> Result<serde_json::Value, String>;
+
+/// Whether a console type is sent as a request (and awaits a response) or as
+/// a plain message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsoleMessageKind {
+    Request,
+    Message,
+}
+
+/// A request or message type the DevTools console can send as JSON.
+///
+/// Registered with [`ClientTypeRegistryBuilder::register_request`] or
+/// [`ClientTypeRegistryBuilder::register_message`].
+#[derive(Clone)]
+pub struct ConsoleType {
+    /// Short type name (e.g. `ListPrograms`).
+    pub name: String,
+    pub kind: ConsoleMessageKind,
+    /// Payload with every field present, derived from the type's schema.
+    pub default_payload: serde_json::Value,
+    /// Shape of the request or message.
+    pub shape: TypeShape,
+    /// Shape of the response, for requests.
+    pub response_shape: Option<TypeShape>,
+    encode: ConsoleEncodeFn,
+    decode_response: Option<ConsoleDecodeFn>,
+}
+
+impl ConsoleType {
+    /// Encode `payload` as a packet ready to send. `request_id` correlates the
+    /// response and is ignored for plain messages.
+    pub fn encode(&self, payload: &serde_json::Value, request_id: u64) -> Result<NetworkPacket, String> {
+        (self.encode)(payload, request_id)
+    }
+
+    /// Decode a response body (without the request id prefix) to JSON.
+    pub fn decode_response(&self, data: &[u8]) -> Result<serde_json::Value, String> {
+        match self.decode_response {
+            Some(decode) => decode(data),
+            None => Err(format!("{} is not a request", self.name)),
+        }
+    }
+}
+
+/// Wire wrapper the server expects around requests.
+#[derive(Serialize, Deserialize)]
+struct RequestInternal<T> {
+    id: u64,
+    request: T,
+    idempotency_key: Option<u64>,
+}
+
+/// Encode `request` as the packet the server expects for request id `request_id`.
+pub fn request_packet<R: RequestMessage>(request: R, request_id: u64) -> Result<NetworkPacket, String> {
+    let wrapped = RequestInternal {
+        id: request_id,
+        request,
+        idempotency_key: None,
+    };
+    let data = bincode::serde::encode_to_vec(&wrapped, bincode::config::standard()).map_err(|e| e.to_string())?;
+    Ok(NetworkPacket {
+        type_name: format!("pl3xus::managers::network_request::RequestInternal<{}>", R::type_name()),
+        schema_hash: R::schema_hash(),
+        data,
+    })
+}
+
+/// What kind of registration a [`SchemaMismatch`] refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaTypeKind {
+    Component,
+    Message,
+    Request,
+    Response,
+}
+
+impl fmt::Display for SchemaTypeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Component => "component",
+            Self::Message => "message",
+            Self::Request => "request",
+            Self::Response => "response",
+        })
+    }
+}
+
+/// A client registration that doesn't match the server's schema.
+///
+/// Returned by [`ClientTypeRegistry::validate_schema`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaMismatch {
+    /// Registered on the client but not on the server.
+    NotOnServer { kind: SchemaTypeKind, name: String },
+    /// Registered on both sides with different shapes; data of this type will
+    /// fail to decode (or decode into the wrong fields).
+    ShapeDiffers {
+        kind: SchemaTypeKind,
+        name: String,
+        client: TypeShape,
+        server: TypeShape,
+    },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotOnServer { kind, name } => write!(f, "{} `{}` is not registered on the server", kind, name),
+            Self::ShapeDiffers {
+                kind,
+                name,
+                client,
+                server,
+            } => write!(f, "{} `{}` differs: client has {}, server has {}", kind, name, client, server),
+        }
+    }
+}
+
+/// Type-erased bincode deserializer for one component type.
+type Deserializer = Arc<dyn Fn(&[u8]) -> Result<Box<dyn Any + Send + Sync>, bincode::error::DecodeError> + Send + Sync>;
+
+/// Unified client-side type registry for component deserialization and DevTools support.
+///
+/// This registry provides two modes of operation:
+/// 1. **Concrete type deserialization** - For reactive hooks and normal client usage
+/// 2. **JSON conversion** - For DevTools UI (enabled via `.with_devtools_support()`)
+///
+/// The JSON converters are only kept if `.with_devtools_support()` is called on the builder,
+/// ensuring zero overhead for applications that don't use DevTools.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::{ClientTypeRegistry, SyncComponent};
+///
+/// // Without DevTools
+/// let registry = ClientTypeRegistry::builder()
+///     .register::<Position>()
+///     .register::<Velocity>()
+///     .build();
+///
+/// // With DevTools - just add .with_devtools_support()
+/// let registry = ClientTypeRegistry::builder()
+///     .register::<Position>()
+///     .register::<Velocity>()
+///     .with_devtools_support()  // Enable DevTools
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct ClientTypeRegistry {
+    /// Map from component type name to deserializer function (for concrete types)
+    deserializers: Arc<HashMap<String, Deserializer>>,
+
+    /// Map from component type name to TypeId (for type checking)
+    type_ids: Arc<HashMap<String, TypeId>>,
+
+    /// JSON converters (only present if .with_devtools_support() was called on builder)
+    json_converters: Arc<RwLock<HashMap<String, (JsonDeserializeFn, JsonSerializeFn)>>>,
+
+    /// Whether JSON support is enabled (set by .with_devtools_support() on builder)
+    json_support_enabled: bool,
+
+    /// Request/message types for the DevTools console (only kept with DevTools support)
+    console_types: Arc<BTreeMap<String, ConsoleType>>,
+
+    /// Map from component type name to its serde shape (for schema validation)
+    shapes: Arc<HashMap<String, TypeShape>>,
+}
+
+impl ClientTypeRegistry {
+    /// Create a new empty registry.
+    ///
+    /// Most users should use `ClientTypeRegistry::builder()` instead.
+    pub fn new() -> Self {
+        Self {
+            deserializers: Arc::new(HashMap::new()),
+            type_ids: Arc::new(HashMap::new()),
+            json_converters: Arc::new(RwLock::new(HashMap::new())),
+            json_support_enabled: false,
+            console_types: Arc::new(BTreeMap::new()),
+            shapes: Arc::new(HashMap::new()),
+        }
+    }
+
+    /// Create a new builder for constructing a registry.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let registry = ClientTypeRegistry::builder()
+    ///     .register::<Position>()
+    ///     .register::<Velocity>()
+    ///     .build();
+    /// ```
+    pub fn builder() -> ClientTypeRegistryBuilder {
+        ClientTypeRegistryBuilder::new()
+    }
+
+    /// Deserialize component data into the concrete type T.
+    ///
+    /// This is used by reactive hooks and normal client code.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyncError::TypeNotRegistered` if the component type is not registered.
+    /// Returns `SyncError::DeserializationFailed` if deserialization fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let position: Position = registry.deserialize("Position", &bytes)?;
+    /// ```
+    pub fn deserialize<T: 'static>(&self, name: &str, data: &[u8]) -> Result<T, SyncError> {
+        let deserializer = self.deserializers.get(name)
+            .ok_or_else(|| SyncError::TypeNotRegistered {
+                component_name: name.to_string(),
+            })?;
+
+        let boxed = deserializer(data)
+            .map_err(|e: bincode::error::DecodeError| SyncError::DeserializationFailed {
+                component_name: name.to_string(),
+                error: format!("{:?}", e),
+            })?;
+
+        boxed.downcast::<T>()
+            .map(|b| *b)
+            .map_err(|_| SyncError::DeserializationFailed {
+                component_name: name.to_string(),
+                error: "Type mismatch".to_string(),
+            })
+    }
+
+    /// Check if a component type is registered.
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.deserializers.contains_key(name)
+    }
+
+    /// Get the TypeId for a registered component type.
+    pub fn get_type_id(&self, name: &str) -> Option<TypeId> {
+        self.type_ids.get(name).copied()
+    }
+
+    /// Get all registered type names.
+    ///
+    /// This is useful for DevTools to enumerate available component types.
+    pub fn registered_types(&self) -> Vec<String> {
+        self.deserializers.keys().cloned().collect()
+    }
+
+    /// Deserialize component data to JSON for DevTools display.
+    ///
+    /// This method lazily initializes the JSON converter for the given type name
+    /// on first use. Subsequent calls reuse the cached converter.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the type is not registered or deserialization fails.
+    pub fn deserialize_to_json(&self, name: &str, data: &[u8]) -> Result<serde_json::Value, SyncError> {
+        // Check if JSON converter exists
+        {
+            let converters = self.json_converters.read().unwrap();
+            if let Some((deserializer, _)) = converters.get(name) {
+                return deserializer(data)
+                    .map_err(|e| SyncError::DeserializationFailed {
+                        component_name: name.to_string(),
+                        error: format!("{:?}", e),
+                    });
+            }
+        }
+
+        // Type not registered at all
+        Err(SyncError::TypeNotRegistered {
+            component_name: name.to_string(),
+        })
+    }
+
+    /// Serialize JSON data to bincode bytes for DevTools mutations.
+    ///
+    /// This method lazily initializes the JSON converter for the given type name
+    /// on first use. Subsequent calls reuse the cached converter.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the type is not registered or serialization fails.
+    pub fn serialize_from_json(&self, name: &str, json: &serde_json::Value) -> Result<Vec<u8>, SyncError> {
+        // Check if JSON converter exists
+        {
+            let converters = self.json_converters.read().unwrap();
+            if let Some((_, serializer)) = converters.get(name) {
+                return serializer(json)
+                    .map_err(|e| SyncError::SerializationFailed {
+                        component_name: name.to_string(),
+                        error: format!("{:?}", e),
+                    });
+            }
+        }
+
+        // Type not registered at all
+        Err(SyncError::TypeNotRegistered {
+            component_name: name.to_string(),
+        })
+    }
+
+    /// Check if a type has JSON converters registered.
+    ///
+    /// This is useful for DevTools to determine if a component can be edited.
+    pub fn has_json_support(&self, name: &str) -> bool {
+        let converters = self.json_converters.read().unwrap();
+        converters.contains_key(name)
+    }
+
+    /// Check if DevTools support is enabled for this registry.
+    ///
+    /// Returns true if `.with_devtools_support()` was called on the builder.
+    /// DevTools should check this and log an error if false.
+    pub fn is_devtools_support_enabled(&self) -> bool {
+        self.json_support_enabled
+    }
+
+    /// Request and message types the DevTools console can send, sorted by name.
+    pub fn console_types(&self) -> Vec<ConsoleType> {
+        self.console_types.values().cloned().collect()
+    }
+
+    /// Look up a console type by short name.
+    pub fn console_type(&self, name: &str) -> Option<&ConsoleType> {
+        self.console_types.get(name)
+    }
+
+    /// Serde shape of a registered component type.
+    pub fn component_shape(&self, name: &str) -> Option<&TypeShape> {
+        self.shapes.get(name)
+    }
+
+    /// Compare this registry against the server's registered types, as
+    /// returned by a `DescribeSchema` request.
+    ///
+    /// Checks every registered component, plus console requests and messages
+    /// when DevTools support is enabled. Types only the server knows about are
+    /// not reported.
+    pub fn validate_schema(&self, server: &SchemaDescription) -> Vec<SchemaMismatch> {
+        fn compare(
+            mismatches: &mut Vec<SchemaMismatch>,
+            kind: SchemaTypeKind,
+            name: &str,
+            client: &TypeShape,
+            server: Option<&TypeShape>,
+        ) {
+            match server {
+                None => mismatches.push(SchemaMismatch::NotOnServer {
+                    kind,
+                    name: name.to_string(),
+                }),
+                Some(server) if server != client => mismatches.push(SchemaMismatch::ShapeDiffers {
+                    kind,
+                    name: name.to_string(),
+                    client: client.clone(),
+                    server: server.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        let mut mismatches = Vec::new();
+
+        let mut components: Vec<_> = self.shapes.iter().collect();
+        components.sort_by_key(|(name, _)| name.as_str());
+        for (name, shape) in components {
+            let server_shape = server.components.iter().find(|c| &c.name == name).map(|c| &c.shape);
+            compare(&mut mismatches, SchemaTypeKind::Component, name, shape, server_shape);
+        }
+
+        for console_type in self.console_types.values() {
+            match console_type.kind {
+                ConsoleMessageKind::Message => {
+                    let server_shape = server
+                        .messages
+                        .iter()
+                        .find(|m| m.name == console_type.name)
+                        .map(|m| &m.shape);
+                    compare(&mut mismatches, SchemaTypeKind::Message, &console_type.name, &console_type.shape, server_shape);
+                }
+                ConsoleMessageKind::Request => {
+                    let server_request = server.requests.iter().find(|r| r.name == console_type.name);
+                    compare(
+                        &mut mismatches,
+                        SchemaTypeKind::Request,
+                        &console_type.name,
+                        &console_type.shape,
+                        server_request.map(|r| &r.request),
+                    );
+                    if let (Some(server_request), Some(response_shape)) = (server_request, &console_type.response_shape) {
+                        compare(
+                            &mut mismatches,
+                            SchemaTypeKind::Response,
+                            &console_type.name,
+                            response_shape,
+                            Some(&server_request.response),
+                        );
+                    }
+                }
+            }
+        }
+
+        mismatches
+    }
+}
+
+impl Default for ClientTypeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for constructing a `ClientTypeRegistry`.
+///
+/// This provides a fluent API for registering multiple component types.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_client::ClientTypeRegistry;
+///
+/// // Without DevTools
+/// let registry = ClientTypeRegistry::builder()
+///     .register::<Position>()
+///     .register::<Velocity>()
+///     .build();
+///
+/// // With DevTools - just add .with_devtools_support()
+/// let registry = ClientTypeRegistry::builder()
+///     .register::<Position>()
+///     .register::<Velocity>()
+///     .with_devtools_support()
+///     .build();
+/// ```
+pub struct ClientTypeRegistryBuilder {
+    deserializers: HashMap<String, Deserializer>,
+    type_ids: HashMap<String, TypeId>,
+    json_converters: HashMap<String, (JsonDeserializeFn, JsonSerializeFn)>,
+    json_support_enabled: bool,
+    console_types: BTreeMap<String, ConsoleType>,
+    shapes: HashMap<String, TypeShape>,
+}
+
+impl ClientTypeRegistryBuilder {
+    /// Create a new builder.
+    pub fn new() -> Self {
+        Self {
+            deserializers: HashMap::new(),
+            type_ids: HashMap::new(),
+            json_converters: HashMap::new(),
+            json_support_enabled: false,
+            console_types: BTreeMap::new(),
+            shapes: HashMap::new(),
+        }
+    }
+
+    /// Register a component type for deserialization.
+    ///
+    /// This registers the type for both concrete deserialization (used by reactive hooks)
+    /// and JSON conversion (used by DevTools if `.with_devtools_support()` is called).
+    ///
+    /// The JSON converters are always created during registration, but will be dropped
+    /// during `.build()` unless `.with_devtools_support()` was called.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Without DevTools
+    /// let registry = ClientTypeRegistry::builder()
+    ///     .register::<Position>()
+    ///     .register::<Velocity>()
+    ///     .build();
+    ///
+    /// // With DevTools - just add .with_devtools_support()
+    /// let registry = ClientTypeRegistry::builder()
+    ///     .register::<Position>()
+    ///     .register::<Velocity>()
+    ///     .with_devtools_support()
+    ///     .build();
+    /// ```
+    pub fn register<T>(mut self) -> Self
+    where
+        T: SyncComponent + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
+    {
+        let name = T::component_name().to_string();
+
+        self.type_ids.insert(name.clone(), TypeId::of::<T>());
+        self.shapes.insert(name.clone(), describe_type::<T>());
+
+        // Register concrete deserializer (always needed)
+        self.deserializers.insert(
+            name.clone(),
+            Arc::new(|data| {
+                let (component, _) = bincode::serde::decode_from_slice::<T, _>(data, bincode::config::standard())?;
+                Ok(Box::new(component) as Box<dyn Any + Send + Sync>)
+            }),
+        );
+
+        // Register JSON converters (will be dropped if .with_devtools_support() not called)
+        let deserializer: JsonDeserializeFn = |data: &[u8]| {
+            let (value, _): (T, _) = bincode::serde::decode_from_slice(data, bincode::config::standard())?;
+            serde_json::to_value(&value)
+                .map_err(|e| bincode::error::DecodeError::OtherString(format!("JSON serialization failed: {}", e)))
+        };
+
+        let serializer: JsonSerializeFn = |json: &serde_json::Value| {
+            let value: T = serde_json::from_value(json.clone())
+                .map_err(|e| bincode::error::EncodeError::OtherString(format!("JSON deserialization failed: {}", e)))?;
+            bincode::serde::encode_to_vec(&value, bincode::config::standard())
+        };
+
+        self.json_converters.insert(name, (deserializer, serializer));
+
+        self
+    }
+
+    /// Register a request type for the DevTools console.
+    ///
+    /// The console can then send `R` as JSON and show the typed response.
+    /// Like the JSON converters, this is dropped unless
+    /// `.with_devtools_support()` is called.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let registry = ClientTypeRegistry::builder()
+    ///     .register::<Position>()
+    ///     .register_request::<ListPrograms>()
+    ///     .register_message::<JogCommand>()
+    ///     .with_devtools_support()
+    ///     .build();
+    /// ```
+    pub fn register_request<R: RequestMessage>(mut self) -> Self {
+        let encode: ConsoleEncodeFn = |payload, request_id| {
+            let request: R = serde_json::from_value(payload.clone()).map_err(|e| e.to_string())?;
+            request_packet(request, request_id)
+        };
+        let decode: ConsoleDecodeFn = |data| {
+            let (response, _): (R::ResponseMessage, _) =
+                bincode::serde::decode_from_slice(data, bincode::config::standard()).map_err(|e| e.to_string())?;
+            serde_json::to_value(&response).map_err(|e| e.to_string())
+        };
+
+        self.console_types.insert(R::request_name().to_string(), ConsoleType {
+            name: R::request_name().to_string(),
+            kind: ConsoleMessageKind::Request,
+            default_payload: default_json::<R>(),
+            shape: describe_type::<R>(),
+            response_shape: Some(describe_type::<R::ResponseMessage>()),
+            encode,
+            decode_response: Some(decode),
+        });
+        self
+    }
+
+    /// Register a plain (fire-and-forget) message type for the DevTools console.
+    pub fn register_message<M: Pl3xusMessage>(mut self) -> Self {
+        let encode: ConsoleEncodeFn = |payload, _| {
+            let message: M = serde_json::from_value(payload.clone()).map_err(|e| e.to_string())?;
+            let data = bincode::serde::encode_to_vec(&message, bincode::config::standard()).map_err(|e| e.to_string())?;
+            Ok(NetworkPacket {
+                type_name: M::type_name().to_string(),
+                schema_hash: M::schema_hash(),
+                data,
+            })
+        };
+
+        self.console_types.insert(M::short_name().to_string(), ConsoleType {
+            name: M::short_name().to_string(),
+            kind: ConsoleMessageKind::Message,
+            default_payload: default_json::<M>(),
+            shape: describe_type::<M>(),
+            response_shape: None,
+            encode,
+            decode_response: None,
+        });
+        self
+    }
+
+    /// Register every type listed in a shared [`TypeManifest`].
+    ///
+    /// Components are registered with [`register`](Self::register), messages
+    /// and requests with [`register_message`](Self::register_message) and
+    /// [`register_request`](Self::register_request).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // In the shared crate
+    /// pl3xus_common::type_manifest! {
+    ///     pub struct RobotTypes {
+    ///         components: [RobotPosition, RobotStatus],
+    ///         requests: [ListPrograms],
+    ///     }
+    /// }
+    ///
+    /// // In the client
+    /// let registry = ClientTypeRegistry::builder()
+    ///     .register_manifest::<RobotTypes>()
+    ///     .with_devtools_support()
+    ///     .build();
+    /// ```
+    pub fn register_manifest<M: TypeManifest>(self) -> Self {
+        let mut registrar = ManifestRegistrar(self);
+        M::visit(&mut registrar);
+        registrar.0
+    }
+
+    /// Enable DevTools support for this registry.
+    ///
+    /// Call this method to keep the JSON converters that were registered during `.register::<T>()`.
+    /// If this method is not called, the JSON converters will be dropped during `.build()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let registry = ClientTypeRegistry::builder()
+    ///     .register::<Position>()
+    ///     .register::<Velocity>()
+    ///     .with_devtools_support()  // Enable DevTools
+    ///     .build();
+    /// ```
+    pub fn with_devtools_support(mut self) -> Self {
+        self.json_support_enabled = true;
+        self
+    }
+
+    /// Build the final `ClientTypeRegistry` wrapped in an `Arc`.
+    ///
+    /// The registry is wrapped in an Arc because it needs to be shared
+    /// across multiple reactive contexts and potentially with DevTools.
+    ///
+    /// If `.with_devtools_support()` was not called, the JSON converters will be dropped
+    /// to avoid overhead for applications that don't use DevTools.
+    pub fn build(self) -> Arc<ClientTypeRegistry> {
+        let (json_converters, console_types) = if self.json_support_enabled {
+            (self.json_converters, self.console_types)
+        } else {
+            (HashMap::new(), BTreeMap::new())
+        };
+
+        Arc::new(ClientTypeRegistry {
+            deserializers: Arc::new(self.deserializers),
+            type_ids: Arc::new(self.type_ids),
+            json_converters: Arc::new(RwLock::new(json_converters)),
+            json_support_enabled: self.json_support_enabled,
+            console_types: Arc::new(console_types),
+            shapes: Arc::new(self.shapes),
+        })
+    }
+}
+
+impl Default for ClientTypeRegistryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Registers the types of a manifest on a builder.
+struct ManifestRegistrar(ClientTypeRegistryBuilder);
+
+impl TypeVisitor for ManifestRegistrar {
+    fn component<T: ManifestComponent>(&mut self) {
+        self.0 = std::mem::take(&mut self.0).register::<T>();
+    }
+
+    fn message<T: Pl3xusMessage + Clone>(&mut self) {
+        self.0 = std::mem::take(&mut self.0).register_message::<T>();
+    }
+
+    fn request<T: RequestMessage>(&mut self) {
+        self.0 = std::mem::take(&mut self.0).register_request::<T>();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_common::{RequestSchema, TypeSchema};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, bevy::prelude::Component)]
+    struct RobotPosition {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, bevy::prelude::Component)]
+    struct Battery {
+        level: f32,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct ListPrograms;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct ProgramList {
+        names: Vec<String>,
+    }
+
+    impl RequestMessage for ListPrograms {
+        type ResponseMessage = ProgramList;
+    }
+
+    mod server {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        pub struct RobotPosition {
+            pub x: f64,
+            pub y: f64,
+            pub z: f64,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        pub struct ProgramList {
+            pub names: Vec<String>,
+        }
+    }
+
+    pl3xus_common::type_manifest! {
+        struct Types {
+            components: [RobotPosition, Battery],
+            requests: [ListPrograms],
+        }
+    }
+
+    #[test]
+    fn test_register_manifest() {
+        let registry = ClientTypeRegistry::builder()
+            .register_manifest::<Types>()
+            .with_devtools_support()
+            .build();
+        assert!(registry.is_registered("RobotPosition"));
+        assert!(registry.is_registered("Battery"));
+        assert!(registry.console_type("ListPrograms").is_some());
+    }
+
+    #[test]
+    fn test_validate_schema_reports_mismatches() {
+        let registry = ClientTypeRegistry::builder()
+            .register::<RobotPosition>()
+            .register::<Battery>()
+            .register_request::<ListPrograms>()
+            .with_devtools_support()
+            .build();
+
+        let server = SchemaDescription {
+            components: vec![TypeSchema::of::<server::RobotPosition>("RobotPosition")],
+            messages: Vec::new(),
+            requests: vec![RequestSchema {
+                response: describe_type::<server::ProgramList>(),
+                ..RequestSchema::of::<ListPrograms>()
+            }],
+        };
+
+        let mismatches = registry.validate_schema(&server);
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(
+            mismatches[0],
+            SchemaMismatch::NotOnServer {
+                kind: SchemaTypeKind::Component,
+                name: "Battery".to_string(),
+            }
+        );
+        assert!(matches!(
+            &mismatches[1],
+            SchemaMismatch::ShapeDiffers { kind: SchemaTypeKind::Component, name, .. } if name == "RobotPosition"
+        ));
+        assert_eq!(
+            mismatches[1].to_string(),
+            "component `RobotPosition` differs: client has RobotPosition { x: f64, y: f64 }, \
+             server has RobotPosition { x: f64, y: f64, z: f64 }"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Received component values.
+//!
+//! Frontends keep every component value the server sends in one map of raw
+//! bytes and decode per type on demand, so hooks for different types only
+//! pay for the types they use.
+
+use std::collections::HashMap;
+
+use pl3xus_sync::{SubscriptionFilter, SyncItem};
+
+use crate::client_type_registry::ClientTypeRegistry;
+use crate::subscriptions::component_matches;
+use crate::traits::SyncComponent;
+
+/// Raw component values: (entity_id, component type name) -> bincode bytes.
+pub type ComponentData = HashMap<(u64, String), Vec<u8>>;
+
+/// Apply one sync item. Returns the component types whose data changed.
+pub fn apply_sync_item(data: &mut ComponentData, item: SyncItem) -> Vec<String> {
+    match item {
+        SyncItem::Snapshot {
+            entity,
+            component_type,
+            value,
+            ..
+        }
+        | SyncItem::Update {
+            entity,
+            component_type,
+            value,
+            ..
+        } => {
+            data.insert((entity.bits, component_type.clone()), value);
+            vec![component_type]
+        }
+        SyncItem::ComponentRemoved {
+            entity, component_type, ..
+        } => match data.remove(&(entity.bits, component_type.clone())) {
+            Some(_) => vec![component_type],
+            None => Vec::new(),
+        },
+        SyncItem::EntityRemoved { entity, .. } => {
+            let mut removed = Vec::new();
+            data.retain(|(entity_id, component_type), _| {
+                if *entity_id != entity.bits {
+                    return true;
+                }
+                removed.push(component_type.clone());
+                false
+            });
+            removed
+        }
+    }
+}
+
+/// Decode every received value of `T`, optionally keeping only those that
+/// match `filter`. Values that fail to decode are skipped.
+pub fn decode_components<T: SyncComponent>(
+    data: &ComponentData,
+    registry: &ClientTypeRegistry,
+    filter: Option<&SubscriptionFilter>,
+) -> HashMap<u64, T> {
+    let component_name = T::component_name();
+    data.iter()
+        .filter(|((_, name), _)| name == component_name)
+        .filter_map(|((entity_id, name), bytes)| {
+            let component = registry.deserialize::<T>(name, bytes).ok()?;
+            filter
+                .is_none_or(|filter| component_matches(filter, *entity_id, &component))
+                .then_some((*entity_id, component))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_sync::SerializableEntity;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Battery {
+        level: f32,
+    }
+
+    fn update(entity: u64, level: f32) -> SyncItem {
+        SyncItem::Update {
+            subscription_id: 0,
+            entity: SerializableEntity { bits: entity },
+            component_type: "Battery".into(),
+            value: bincode::serde::encode_to_vec(Battery { level }, bincode::config::standard()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_apply_and_decode() {
+        let registry = ClientTypeRegistry::builder().register::<Battery>().build();
+        let mut data = ComponentData::new();
+
+        assert_eq!(apply_sync_item(&mut data, update(1, 0.9)), vec!["Battery"]);
+        apply_sync_item(&mut data, update(2, 0.1));
+        let all = decode_components::<Battery>(&data, &registry, None);
+        assert_eq!(all.len(), 2);
+
+        let low = SubscriptionFilter::lt("level", 0.5);
+        let filtered = decode_components::<Battery>(&data, &registry, Some(&low));
+        assert_eq!(filtered.into_keys().collect::<Vec<_>>(), vec![2]);
+
+        let removed = apply_sync_item(
+            &mut data,
+            SyncItem::EntityRemoved {
+                subscription_id: 0,
+                entity: SerializableEntity { bits: 1 },
+            },
+        );
+        assert_eq!(removed, vec!["Battery"]);
+        assert_eq!(data.len(), 1);
+    }
+}
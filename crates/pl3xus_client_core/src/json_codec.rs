@@ -0,0 +1,559 @@
+//! JSON payloads for clients without the Rust types.
+//!
+//! A server answers [`DescribeSchema`](pl3xus_common::DescribeSchema) with the
+//! [`TypeShape`] of every registered type. That is enough to transcode between
+//! serde's JSON representation and the bincode bytes on the wire, which is how
+//! the JavaScript SDK (`pl3xus_js`) talks to a server.
+//!
+//! Enum variant payloads and [`TypeShape::Any`] aren't traced by the schema,
+//! so values containing them can't be transcoded; only unit variants work.
+
+use std::collections::HashMap;
+
+use pl3xus_common::{NetworkPacket, RequestSchema, SchemaDescription, TypeSchema, TypeShape};
+use pl3xus_sync::SubscriptionFilter;
+use serde_json::{Map, Number, Value as JsonValue};
+
+use crate::component_data::ComponentData;
+use crate::subscriptions::component_matches;
+
+/// Encode `value` as the bincode bytes of a type with `shape`.
+pub fn encode_json(shape: &TypeShape, value: &JsonValue) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    write_value(shape, value, &mut out)?;
+    Ok(out)
+}
+
+/// Decode the bincode bytes of a type with `shape` to JSON.
+pub fn decode_json(shape: &TypeShape, data: &[u8]) -> Result<JsonValue, String> {
+    let mut reader = Reader { data, pos: 0 };
+    let value = reader.value(shape)?;
+    if reader.pos != data.len() {
+        return Err(format!("{} trailing bytes", data.len() - reader.pos));
+    }
+    Ok(value)
+}
+
+/// Transcodes JSON payloads for every type a server described.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaCodec {
+    components: HashMap<String, TypeSchema>,
+    messages: HashMap<String, TypeSchema>,
+    requests: HashMap<String, RequestSchema>,
+}
+
+impl From<SchemaDescription> for SchemaCodec {
+    fn from(description: SchemaDescription) -> Self {
+        Self {
+            components: by_name(description.components),
+            messages: by_name(description.messages),
+            requests: description
+                .requests
+                .into_iter()
+                .map(|request| (request.name.clone(), request))
+                .collect(),
+        }
+    }
+}
+
+fn by_name(types: Vec<TypeSchema>) -> HashMap<String, TypeSchema> {
+    types.into_iter().map(|schema| (schema.name.clone(), schema)).collect()
+}
+
+impl SchemaCodec {
+    pub fn component(&self, name: &str) -> Option<&TypeSchema> {
+        self.components.get(name)
+    }
+
+    pub fn message(&self, name: &str) -> Option<&TypeSchema> {
+        self.messages.get(name)
+    }
+
+    pub fn request(&self, name: &str) -> Option<&RequestSchema> {
+        self.requests.get(name)
+    }
+
+    pub fn encode_component(&self, name: &str, value: &JsonValue) -> Result<Vec<u8>, String> {
+        let schema = self.component(name).ok_or_else(|| unknown("component", name))?;
+        encode_json(&schema.shape, value)
+    }
+
+    pub fn decode_component(&self, name: &str, data: &[u8]) -> Result<JsonValue, String> {
+        let schema = self.component(name).ok_or_else(|| unknown("component", name))?;
+        decode_json(&schema.shape, data)
+    }
+
+    /// Every received value of component `name` as JSON, keyed by entity id,
+    /// optionally keeping only those that match `filter`. Values that fail to
+    /// decode are skipped.
+    pub fn decode_components(
+        &self,
+        data: &ComponentData,
+        name: &str,
+        filter: Option<&SubscriptionFilter>,
+    ) -> Map<String, JsonValue> {
+        data.iter()
+            .filter(|((_, component), _)| component == name)
+            .filter_map(|((entity_id, _), bytes)| {
+                let value = self.decode_component(name, bytes).ok()?;
+                filter
+                    .is_none_or(|filter| component_matches(filter, *entity_id, &value))
+                    .then(|| (entity_id.to_string(), value))
+            })
+            .collect()
+    }
+
+    /// Encode a plain message as a packet ready to send.
+    pub fn encode_message(&self, name: &str, value: &JsonValue) -> Result<NetworkPacket, String> {
+        let schema = self.message(name).ok_or_else(|| unknown("message", name))?;
+        Ok(NetworkPacket {
+            type_name: schema.type_name.clone(),
+            schema_hash: 0,
+            data: encode_json(&schema.shape, value)?,
+        })
+    }
+
+    pub fn decode_message(&self, name: &str, data: &[u8]) -> Result<JsonValue, String> {
+        let schema = self.message(name).ok_or_else(|| unknown("message", name))?;
+        decode_json(&schema.shape, data)
+    }
+
+    /// Encode request `name` as the packet the server expects for request id
+    /// `request_id`.
+    pub fn encode_request(&self, name: &str, value: &JsonValue, request_id: u64) -> Result<NetworkPacket, String> {
+        let schema = self.request(name).ok_or_else(|| unknown("request", name))?;
+        // Same layout as `RequestInternal { id, request, idempotency_key: None }`
+        let mut data = Vec::new();
+        write_varint(&mut data, request_id.into());
+        write_value(&schema.request, value, &mut data)?;
+        data.push(0);
+        Ok(NetworkPacket {
+            type_name: format!("pl3xus::managers::network_request::RequestInternal<{}>", schema.type_name),
+            schema_hash: 0,
+            data,
+        })
+    }
+
+    /// Decode a response body (without the request id prefix) to JSON.
+    pub fn decode_response(&self, name: &str, data: &[u8]) -> Result<JsonValue, String> {
+        let schema = self.request(name).ok_or_else(|| unknown("request", name))?;
+        decode_json(&schema.response, data)
+    }
+}
+
+fn unknown(kind: &str, name: &str) -> String {
+    format!("the server has no {} named {}", kind, name)
+}
+
+// ============================================================================
+// Encoding (bincode standard config: varint integers, zigzag for signed)
+// ============================================================================
+
+fn write_value(shape: &TypeShape, value: &JsonValue, out: &mut Vec<u8>) -> Result<(), String> {
+    match shape {
+        TypeShape::Bool => out.push(value.as_bool().ok_or_else(|| expected("a boolean", value))? as u8),
+        TypeShape::I8 => out.push(ranged::<i8>(value)? as u8),
+        TypeShape::I16 => write_signed(out, ranged::<i16>(value)?.into()),
+        TypeShape::I32 => write_signed(out, ranged::<i32>(value)?.into()),
+        TypeShape::I64 => write_signed(out, ranged::<i64>(value)?.into()),
+        TypeShape::I128 => write_signed(out, integer(value)?),
+        TypeShape::U8 => out.push(ranged::<u8>(value)?),
+        TypeShape::U16 => write_varint(out, ranged::<u16>(value)?.into()),
+        TypeShape::U32 => write_varint(out, ranged::<u32>(value)?.into()),
+        TypeShape::U64 => write_varint(out, ranged::<u64>(value)?.into()),
+        TypeShape::U128 => write_varint(out, ranged::<u128>(value)?),
+        TypeShape::F32 => out.extend((float(value)? as f32).to_le_bytes()),
+        TypeShape::F64 => out.extend(float(value)?.to_le_bytes()),
+        TypeShape::Char => {
+            let mut chars = value.as_str().map(str::chars).ok_or_else(|| expected("a character", value))?;
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return Err(expected("a single character", value));
+            };
+            out.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        TypeShape::String => {
+            let s = value.as_str().ok_or_else(|| expected("a string", value))?;
+            write_varint(out, s.len() as u128);
+            out.extend(s.as_bytes());
+        }
+        TypeShape::Bytes => {
+            let items = value.as_array().ok_or_else(|| expected("an array of bytes", value))?;
+            write_varint(out, items.len() as u128);
+            for item in items {
+                out.push(ranged::<u8>(item)?);
+            }
+        }
+        TypeShape::Unit => {}
+        TypeShape::Option(inner) => match value {
+            JsonValue::Null => out.push(0),
+            value => {
+                out.push(1);
+                write_value(inner, value, out)?;
+            }
+        },
+        TypeShape::Seq(inner) => {
+            let items = value.as_array().ok_or_else(|| expected("an array", value))?;
+            write_varint(out, items.len() as u128);
+            for item in items {
+                write_value(inner, item, out)?;
+            }
+        }
+        TypeShape::Tuple(shapes) => {
+            let items = value.as_array().ok_or_else(|| expected("an array", value))?;
+            if items.len() != shapes.len() {
+                return Err(expected(&format!("an array of {} items", shapes.len()), value));
+            }
+            for (shape, item) in shapes.iter().zip(items) {
+                write_value(shape, item, out)?;
+            }
+        }
+        TypeShape::Map { key, value: value_shape } => match value {
+            JsonValue::Object(entries) => {
+                write_varint(out, entries.len() as u128);
+                for (name, item) in entries {
+                    write_value(key, &map_key(key, name), out)?;
+                    write_value(value_shape, item, out)?;
+                }
+            }
+            // Maps with non-scalar keys as `[[key, value], ...]`
+            JsonValue::Array(pairs) => {
+                write_varint(out, pairs.len() as u128);
+                for pair in pairs {
+                    let [k, v] = pair.as_array().map(Vec::as_slice).unwrap_or_default() else {
+                        return Err(expected("a [key, value] pair", pair));
+                    };
+                    write_value(key, k, out)?;
+                    write_value(value_shape, v, out)?;
+                }
+            }
+            value => return Err(expected("an object", value)),
+        },
+        TypeShape::Struct { name, fields } => {
+            if fields.is_empty() {
+                return Ok(());
+            }
+            if let (true, Some(items)) = (is_tuple_struct(fields), value.as_array()) {
+                if items.len() != fields.len() {
+                    return Err(expected(&format!("an array of {} items", fields.len()), value));
+                }
+                for (field, item) in fields.iter().zip(items) {
+                    write_value(&field.shape, item, out)?;
+                }
+                return Ok(());
+            }
+            let object = value.as_object().ok_or_else(|| expected(&format!("a {} object", name), value))?;
+            for field in fields {
+                match (object.get(&field.name), &field.shape) {
+                    (Some(item), shape) => write_value(shape, item, out)?,
+                    (None, TypeShape::Option(_)) => out.push(0),
+                    (None, _) => return Err(format!("{} is missing field {}", name, field.name)),
+                }
+            }
+        }
+        TypeShape::Newtype { inner, .. } => write_value(inner, value, out)?,
+        TypeShape::Enum { name, variants } => {
+            let variant = value
+                .as_str()
+                .ok_or_else(|| expected(&format!("a {} variant name", name), value))?;
+            let index = variants
+                .iter()
+                .position(|v| v == variant)
+                .ok_or_else(|| format!("{} has no variant {}", name, variant))?;
+            write_varint(out, index as u128);
+        }
+        TypeShape::Any => return Err("values of untraced types can't be encoded from JSON".to_string()),
+    }
+    Ok(())
+}
+
+fn write_varint(out: &mut Vec<u8>, n: u128) {
+    if n < 251 {
+        out.push(n as u8);
+    } else if let Ok(n) = u16::try_from(n) {
+        out.push(251);
+        out.extend(n.to_le_bytes());
+    } else if let Ok(n) = u32::try_from(n) {
+        out.push(252);
+        out.extend(n.to_le_bytes());
+    } else if let Ok(n) = u64::try_from(n) {
+        out.push(253);
+        out.extend(n.to_le_bytes());
+    } else {
+        out.push(254);
+        out.extend(n.to_le_bytes());
+    }
+}
+
+fn write_signed(out: &mut Vec<u8>, n: i128) {
+    write_varint(out, ((n << 1) ^ (n >> 127)) as u128);
+}
+
+fn integer(value: &JsonValue) -> Result<i128, String> {
+    let n = match value {
+        JsonValue::Number(n) => n.as_i64().map(i128::from).or_else(|| n.as_u64().map(i128::from)),
+        // 128-bit integers don't fit a JSON number
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    };
+    n.ok_or_else(|| expected("an integer", value))
+}
+
+fn ranged<T: TryFrom<i128>>(value: &JsonValue) -> Result<T, String> {
+    T::try_from(integer(value)?).map_err(|_| format!("{} is out of range", value))
+}
+
+fn float(value: &JsonValue) -> Result<f64, String> {
+    value.as_f64().ok_or_else(|| expected("a number", value))
+}
+
+/// JSON object keys are strings; parse them back for non-string map keys.
+fn map_key(shape: &TypeShape, name: &str) -> JsonValue {
+    match shape {
+        TypeShape::String | TypeShape::Char => JsonValue::String(name.to_string()),
+        TypeShape::Newtype { inner, .. } => map_key(inner, name),
+        _ => serde_json::from_str(name).unwrap_or_else(|_| JsonValue::String(name.to_string())),
+    }
+}
+
+fn is_tuple_struct(fields: &[pl3xus_common::FieldShape]) -> bool {
+    fields.first().is_some_and(|field| field.name == "0")
+}
+
+fn expected(what: &str, value: &JsonValue) -> String {
+    format!("expected {}, got {}", what, value)
+}
+
+// ============================================================================
+// Decoding
+// ============================================================================
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("unexpected end of data")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u128, String> {
+        Ok(match self.byte()? {
+            n @ 0..=250 => n.into(),
+            251 => u16::from_le_bytes(self.array()?).into(),
+            252 => u32::from_le_bytes(self.array()?).into(),
+            253 => u64::from_le_bytes(self.array()?).into(),
+            254 => u128::from_le_bytes(self.array()?),
+            tag => return Err(format!("invalid integer tag {}", tag)),
+        })
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        usize::try_from(self.varint()?).map_err(|_| "length out of range".to_string())
+    }
+
+    fn signed(&mut self) -> Result<JsonValue, String> {
+        let n = self.varint()?;
+        let n = ((n >> 1) as i128) ^ -((n & 1) as i128);
+        Ok(i64::try_from(n).map_or_else(|_| JsonValue::String(n.to_string()), JsonValue::from))
+    }
+
+    fn unsigned(&mut self) -> Result<JsonValue, String> {
+        let n = self.varint()?;
+        Ok(u64::try_from(n).map_or_else(|_| JsonValue::String(n.to_string()), JsonValue::from))
+    }
+
+    fn value(&mut self, shape: &TypeShape) -> Result<JsonValue, String> {
+        Ok(match shape {
+            TypeShape::Bool => match self.byte()? {
+                0 => JsonValue::Bool(false),
+                1 => JsonValue::Bool(true),
+                byte => return Err(format!("invalid boolean {}", byte)),
+            },
+            TypeShape::I8 => (self.byte()? as i8).into(),
+            TypeShape::I16 | TypeShape::I32 | TypeShape::I64 | TypeShape::I128 => self.signed()?,
+            TypeShape::U8 => self.byte()?.into(),
+            TypeShape::U16 | TypeShape::U32 | TypeShape::U64 | TypeShape::U128 => self.unsigned()?,
+            TypeShape::F32 => number(f32::from_le_bytes(self.array()?).into()),
+            TypeShape::F64 => number(f64::from_le_bytes(self.array()?)),
+            TypeShape::Char => {
+                let len = match self.data.get(self.pos) {
+                    Some(0..0x80) => 1,
+                    Some(0xE0..0xF0) => 3,
+                    Some(0xF0..) => 4,
+                    _ => 2,
+                };
+                let bytes = self.take(len)?;
+                std::str::from_utf8(bytes)
+                    .map_err(|_| "invalid character".to_string())?
+                    .into()
+            }
+            TypeShape::String => {
+                let len = self.len()?;
+                std::str::from_utf8(self.take(len)?)
+                    .map_err(|_| "invalid UTF-8 string".to_string())?
+                    .into()
+            }
+            TypeShape::Bytes => {
+                let len = self.len()?;
+                self.take(len)?.iter().map(|byte| JsonValue::from(*byte)).collect()
+            }
+            TypeShape::Unit => JsonValue::Null,
+            TypeShape::Option(inner) => match self.byte()? {
+                0 => JsonValue::Null,
+                1 => self.value(inner)?,
+                tag => return Err(format!("invalid option tag {}", tag)),
+            },
+            TypeShape::Seq(inner) => {
+                let len = self.len()?;
+                (0..len).map(|_| self.value(inner)).collect::<Result<_, _>>()?
+            }
+            TypeShape::Tuple(shapes) => shapes.iter().map(|shape| self.value(shape)).collect::<Result<_, _>>()?,
+            TypeShape::Map { key, value } => {
+                let len = self.len()?;
+                let pairs = (0..len)
+                    .map(|_| Ok((self.value(key)?, self.value(value)?)))
+                    .collect::<Result<Vec<_>, String>>()?;
+                if pairs.iter().all(|(k, _)| !k.is_array() && !k.is_object() && !k.is_null()) {
+                    let entries = pairs.into_iter().map(|(k, v)| match k {
+                        JsonValue::String(s) => (s, v),
+                        k => (k.to_string(), v),
+                    });
+                    JsonValue::Object(entries.collect())
+                } else {
+                    pairs.into_iter().map(|(k, v)| JsonValue::Array(vec![k, v])).collect()
+                }
+            }
+            TypeShape::Struct { fields, .. } => {
+                if fields.is_empty() {
+                    JsonValue::Null
+                } else if is_tuple_struct(fields) {
+                    fields.iter().map(|field| self.value(&field.shape)).collect::<Result<_, _>>()?
+                } else {
+                    let mut object = Map::new();
+                    for field in fields {
+                        object.insert(field.name.clone(), self.value(&field.shape)?);
+                    }
+                    JsonValue::Object(object)
+                }
+            }
+            TypeShape::Newtype { inner, .. } => self.value(inner)?,
+            TypeShape::Enum { name, variants } => {
+                let index = self.varint()?;
+                let variant = usize::try_from(index).ok().and_then(|index| variants.get(index));
+                variant
+                    .ok_or_else(|| format!("{} has no variant {}", name, index))?
+                    .as_str()
+                    .into()
+            }
+            TypeShape::Any => return Err("values of untraced types can't be decoded to JSON".to_string()),
+        })
+    }
+}
+
+/// NaN and infinities have no JSON form; serde_json writes them as null too.
+fn number(n: f64) -> JsonValue {
+    Number::from_f64(n).map_or(JsonValue::Null, JsonValue::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_common::{describe_type, DescribeSchema, RequestSchema};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    enum Mode {
+        Manual,
+        Auto,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Meters(f64);
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Robot {
+        name: String,
+        mode: Mode,
+        reach: Meters,
+        joints: Vec<f32>,
+        offset: i32,
+        serial: u64,
+        tool: Option<String>,
+        flags: (bool, u8, char),
+        limits: BTreeMap<u16, i64>,
+    }
+
+    fn robot() -> Robot {
+        Robot {
+            name: "arm-1".into(),
+            mode: Mode::Auto,
+            reach: Meters(1.25),
+            joints: vec![0.5, -90.0, 300.0],
+            offset: -70_000,
+            serial: u64::MAX,
+            tool: None,
+            flags: (true, 255, 'é'),
+            limits: BTreeMap::from([(1, -5), (1000, 1 << 40)]),
+        }
+    }
+
+    #[test]
+    fn test_matches_bincode() {
+        let shape = describe_type::<Robot>();
+        let json = serde_json::to_value(robot()).unwrap();
+        let bytes = bincode::serde::encode_to_vec(robot(), bincode::config::standard()).unwrap();
+
+        assert_eq!(encode_json(&shape, &json).unwrap(), bytes);
+        assert_eq!(decode_json(&shape, &bytes).unwrap(), json);
+    }
+
+    #[test]
+    fn test_missing_optional_field_and_bad_input() {
+        let shape = describe_type::<Robot>();
+        let mut json = serde_json::to_value(robot()).unwrap();
+        json.as_object_mut().unwrap().remove("tool");
+        let bytes = encode_json(&shape, &json).unwrap();
+        let (decoded, _): (Robot, usize) =
+            bincode::serde::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, robot());
+
+        json["mode"] = "Teach".into();
+        assert!(encode_json(&shape, &json).unwrap_err().contains("no variant Teach"));
+        json.as_object_mut().unwrap().remove("name");
+        assert!(encode_json(&shape, &json).unwrap_err().contains("missing field name"));
+        assert!(decode_json(&shape, &bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_request_packet_layout() {
+        let codec = SchemaCodec::from(SchemaDescription {
+            components: Vec::new(),
+            messages: Vec::new(),
+            requests: vec![RequestSchema::of::<DescribeSchema>()],
+        });
+        let packet = codec.encode_request("DescribeSchema", &JsonValue::Null, 300).unwrap();
+        let expected = crate::client_type_registry::request_packet(DescribeSchema, 300).unwrap();
+        assert_eq!(packet.type_name, expected.type_name);
+        assert_eq!(packet.data, expected.data);
+        assert!(codec.encode_request("Missing", &JsonValue::Null, 1).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! End-to-end sync latency measurement.
+//!
+//! When the server has `SyncSettings::include_timestamps` enabled, every
+//! `SyncBatch` carries the server send time. The client estimates the offset
+//! between its clock and the server's from the [`ClockPong`] replies to a
+//! burst of [`ClockPing`]s sent after the `Welcome` message, using the same
+//! [`ClockSampler`] as the rest of the clock sync, then converts each batch
+//! timestamp to local time to get the latency per component type.
+
+use std::collections::HashMap;
+
+use pl3xus_common::{ClockEstimate, ClockPing, ClockPong, ClockSampler};
+use pl3xus_sync::{SyncBatch, SyncItem};
+
+/// Number of pings sent per clock sync. The one with the lowest round trip wins.
+pub const CLOCK_SYNC_PROBES: usize = 5;
+
+/// Weight of the newest sample in [`ComponentLatency::avg_ms`].
+const AVERAGE_WEIGHT: f64 = 0.1;
+
+/// Latency statistics for one component type.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ComponentLatency {
+    /// Latency of the most recent update, in milliseconds.
+    pub last_ms: f64,
+    /// Exponential moving average of the latency, in milliseconds.
+    pub avg_ms: f64,
+    /// Highest latency seen, in milliseconds.
+    pub max_ms: f64,
+    /// Number of updates measured.
+    pub samples: u64,
+}
+
+impl ComponentLatency {
+    fn record(&mut self, latency_ms: f64) {
+        self.avg_ms = if self.samples == 0 {
+            latency_ms
+        } else {
+            self.avg_ms + (latency_ms - self.avg_ms) * AVERAGE_WEIGHT
+        };
+        self.last_ms = latency_ms;
+        self.max_ms = self.max_ms.max(latency_ms);
+        self.samples += 1;
+    }
+}
+
+/// Clock offset estimate and per-component latency statistics.
+#[derive(Clone, Debug, Default)]
+pub struct LatencyTracker {
+    clock: ClockSampler,
+    components: HashMap<String, ComponentLatency>,
+}
+
+impl LatencyTracker {
+    /// Build a clock ping stamped with the current local time.
+    pub fn clock_ping() -> ClockPing {
+        ClockPing {
+            client_time_ms: now_ms(),
+        }
+    }
+
+    /// Update the clock offset from a ping reply received just now.
+    pub fn handle_clock_pong(&mut self, pong: &ClockPong) {
+        self.clock.add_sample(pong, now_ms());
+    }
+
+    /// Record the latency of every component in a timestamped batch.
+    ///
+    /// Does nothing until the clock offset is known or if the batch has no timestamp.
+    pub fn record_batch(&mut self, batch: &SyncBatch) {
+        let (Some(sent_at_ms), Some(clock)) = (batch.sent_at_ms, self.clock.estimate()) else {
+            return;
+        };
+        let latency_ms = (now_ms() - clock.to_local_ms(sent_at_ms)).max(0.0);
+
+        for item in &batch.items {
+            if let SyncItem::Update { component_type, .. } | SyncItem::Snapshot { component_type, .. } = item {
+                match self.components.get_mut(component_type) {
+                    Some(stats) => stats.record(latency_ms),
+                    None => {
+                        let mut stats = ComponentLatency::default();
+                        stats.record(latency_ms);
+                        self.components.insert(component_type.clone(), stats);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Current clock offset estimate, if any ping has been answered.
+    pub fn clock_offset(&self) -> Option<ClockEstimate> {
+        self.clock.estimate()
+    }
+
+    /// Latency statistics keyed by component type name.
+    pub fn components(&self) -> &HashMap<String, ComponentLatency> {
+        &self.components
+    }
+
+    /// Forget the clock offset and all statistics (e.g. on reconnect).
+    pub fn reset(&mut self) {
+        self.clock.reset();
+        self.components.clear();
+    }
+}
+
+/// Local wall-clock time in milliseconds since the Unix epoch.
+pub fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_sync::SerializableEntity;
+
+    #[test]
+    fn test_latency_uses_clock_pong_offset() {
+        let mut tracker = LatencyTracker::default();
+        assert!(tracker.clock_offset().is_none());
+
+        // Server clock one second ahead of ours
+        let ping = LatencyTracker::clock_ping();
+        tracker.handle_clock_pong(&ClockPong {
+            client_time_ms: ping.client_time_ms,
+            server_wall_ms: ping.client_time_ms + 1000.0,
+            server_monotonic_ms: 0.0,
+        });
+        let clock = tracker.clock_offset().unwrap();
+        assert!((clock.offset_ms - 1000.0).abs() < 50.0, "{:?}", clock);
+
+        let batch = SyncBatch {
+            items: vec![SyncItem::Update {
+                subscription_id: 1,
+                entity: SerializableEntity { bits: 1 },
+                component_type: "RobotPosition".to_string(),
+                value: Vec::new(),
+            }],
+            sent_at_ms: Some(now_ms() + 1000.0 - 200.0),
+            sequences: Vec::new(),
+        };
+        tracker.record_batch(&batch);
+        let stats = tracker.components()["RobotPosition"];
+        assert!((stats.last_ms - 200.0).abs() < 50.0, "{:?}", stats);
+
+        tracker.reset();
+        assert!(tracker.clock_offset().is_none() && tracker.components().is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+//! # Pl3xus Client Core
+//!
+//! The framework-agnostic half of the `pl3xus_sync` client: everything a web
+//! frontend needs to speak the sync protocol, without any reactive runtime.
+//!
+//! - [`ClientTypeRegistry`]: component/message/request types the client knows
+//! - [`SubscriptionTracker`]: one server subscription per component type (and
+//!   filter), shared by every hook using it
+//! - [`ComponentData`]: received component values, decoded per type on demand
+//! - [`QueryCache`]: deduplicated request state with server-driven invalidation
+//! - [`LiveQueryRows`]: a live query's result, kept up to date from the rows
+//!   the server pushes
+//! - [`BlobCache`]: blobs referenced by [`BlobRef`](pl3xus_common::BlobRef),
+//!   fetched in chunks and cached by content hash
+//! - [`SchemaCodec`]: JSON payloads transcoded from the server's
+//!   [`DescribeSchema`](pl3xus_common::DescribeSchema) response, for clients
+//!   without the Rust types
+//! - [`SyncClientCore`]: all of the above for one connection, for adapters
+//!   that don't need finer control
+//!
+//! `pl3xus_client` builds its Leptos hooks on these pieces; `pl3xus_dioxus`
+//! and `pl3xus_yew` are thin adapters over [`SyncClientCore`] and the browser
+//! WebSocket transport behind the `web` feature; `pl3xus_js` wraps the same
+//! pieces for JavaScript.
+
+pub mod blob_cache;
+pub mod client_type_registry;
+pub mod error;
+pub mod latency;
+pub mod reliable;
+mod schema_default;
+pub mod traits;
+
+pub mod client;
+pub mod component_data;
+pub mod json_codec;
+pub mod live_query;
+pub mod packet;
+pub mod query_cache;
+pub mod subscriptions;
+#[cfg(feature = "web")]
+pub mod web;
+
+pub use blob_cache::{BlobCache, BlobProgress};
+pub use client::{ClientEvent, SyncClientCore};
+pub use client_type_registry::{
+    ClientTypeRegistry, ClientTypeRegistryBuilder, ConsoleMessageKind, ConsoleType, SchemaMismatch, SchemaTypeKind,
+};
+pub use component_data::{apply_sync_item, decode_components, ComponentData};
+pub use error::SyncError;
+pub use json_codec::{decode_json, encode_json, SchemaCodec};
+pub use live_query::{live_query_request, LiveQueryRows};
+pub use latency::{ComponentLatency, LatencyTracker};
+pub use packet::{decode_frame, encode_frame, message_packet, sync_packet, FrameDecoder};
+pub use query_cache::{apply_invalidation, QueryCache, QueryCacheEntry, QueryCacheState};
+pub use subscriptions::{component_matches, filtered_subscription_key, SubscriptionTracker};
+pub use traits::SyncComponent;
+#[cfg(feature = "web")]
+pub use web::{SocketEvent, SyncSocket};
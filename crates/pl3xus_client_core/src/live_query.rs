@@ -0,0 +1,150 @@
+//! Client side of live queries.
+//!
+//! A live query is sent once as a [`QueryRequest`] in [`QueryMode::Subscribe`];
+//! the server then pushes [`LiveQueryUpdate`]s with the rows that changed.
+//! [`LiveQueryRows`] applies them to a local copy of the result, kept encoded
+//! so it can be stored without knowing the row type.
+
+use pl3xus_common::LiveQuery;
+use pl3xus_sync::{LiveQueryRow, LiveQueryUpdate, QueryMode, QueryRequest, QueryResponse, SyncClientMessage};
+
+/// Subscribe request for `query`, to be answered with [`LiveQueryUpdate`]s
+/// for `query_id`.
+pub fn live_query_request<Q: LiveQuery>(query_id: u64, query: &Q) -> SyncClientMessage {
+    SyncClientMessage::Query(QueryRequest {
+        query_id,
+        namespace: Q::short_name().to_string(),
+        params: serde_json::to_string(query).unwrap_or_default(),
+        mode: QueryMode::Subscribe,
+    })
+}
+
+/// Local copy of a live query's result.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LiveQueryRows {
+    /// Rows in the order the server first sent them; added rows go last.
+    rows: Vec<LiveQueryRow>,
+    /// Whether the first result has arrived.
+    loaded: bool,
+    /// Why the server refused the query, if it did.
+    error: Option<String>,
+}
+
+impl LiveQueryRows {
+    /// Apply the rows added, updated and removed since the last update.
+    pub fn apply(&mut self, update: &LiveQueryUpdate) {
+        self.rows.retain(|row| !update.removed.contains(&row.key));
+        for changed in &update.updated {
+            if let Some(row) = self.rows.iter_mut().find(|row| row.key == changed.key) {
+                row.row = changed.row.clone();
+            }
+        }
+        self.rows.extend(update.added.iter().cloned());
+        self.loaded = true;
+        self.error = None;
+    }
+
+    /// Record a failed query. Live queries only get a [`QueryResponse`] when
+    /// the server couldn't run them.
+    pub fn fail(&mut self, response: &QueryResponse) {
+        self.rows.clear();
+        self.loaded = true;
+        self.error = Some(
+            response
+                .error
+                .clone()
+                .unwrap_or_else(|| format!("Query failed: {:?}", response.status)),
+        );
+    }
+
+    /// Forget the result, e.g. before subscribing again after a reconnect.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Decode every row as `Q::Row`, skipping rows that don't decode.
+    pub fn decode<Q: LiveQuery>(&self) -> Vec<Q::Row> {
+        self.rows
+            .iter()
+            .filter_map(|row| serde_json::from_str(&row.row).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_sync::QueryStatus;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    struct ListPrograms;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct ProgramInfo {
+        id: i64,
+        name: String,
+    }
+
+    impl LiveQuery for ListPrograms {
+        type Row = ProgramInfo;
+
+        fn row_key(row: &ProgramInfo) -> String {
+            row.id.to_string()
+        }
+    }
+
+    fn row(id: i64, name: &str) -> LiveQueryRow {
+        LiveQueryRow {
+            key: id.to_string(),
+            row: serde_json::to_string(&ProgramInfo { id, name: name.to_string() }).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_apply_updates() {
+        let mut rows = LiveQueryRows::default();
+        assert!(!rows.is_loaded());
+
+        rows.apply(&LiveQueryUpdate {
+            query_id: 1,
+            added: vec![row(1, "a"), row(2, "b")],
+            ..Default::default()
+        });
+        rows.apply(&LiveQueryUpdate {
+            query_id: 1,
+            added: vec![row(3, "d")],
+            updated: vec![row(2, "c")],
+            removed: vec!["1".to_string()],
+        });
+
+        let names: Vec<String> = rows.decode::<ListPrograms>().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["c", "d"]);
+        assert!(rows.is_loaded() && rows.error().is_none());
+
+        rows.fail(&QueryResponse {
+            query_id: 1,
+            status: QueryStatus::NotFound,
+            rows: None,
+            error: None,
+        });
+        assert!(rows.is_empty());
+        assert_eq!(rows.error(), Some("Query failed: NotFound"));
+    }
+}
@@ -0,0 +1,176 @@
+//! WebSocket framing of [`NetworkPacket`]s.
+//!
+//! Each packet on the wire is bincode-encoded and prefixed with its length as
+//! an 8-byte little-endian integer. The server may batch several packets into
+//! one WebSocket frame. Clients that offer
+//! [`BATCH_SUBPROTOCOL`](pl3xus_common::codec::BATCH_SUBPROTOCOL) may instead
+//! get [`PacketBatch`] frames, whose interned type names only
+//! [`FrameDecoder`] can resolve.
+
+use pl3xus_common::codec::{PacketBatch, TypeTable, BATCH_MARKER};
+use pl3xus_common::{NetworkPacket, Pl3xusMessage};
+use pl3xus_sync::SyncClientMessage;
+
+/// Decode all length-prefixed packets in one WebSocket frame.
+///
+/// Stops at the first incomplete or undecodable packet, including a
+/// [`PacketBatch`]; use a [`FrameDecoder`] on connections that may get them.
+pub fn decode_frame(data: &[u8]) -> Vec<NetworkPacket> {
+    let mut packets = Vec::new();
+    let mut offset = 0;
+
+    while let Some(length) = read_length(data, offset) {
+        offset += 8;
+        let Some((packet, length)) = decode_at::<NetworkPacket>(data, offset, length) else {
+            break;
+        };
+        packets.push(packet);
+        offset += length;
+    }
+
+    packets
+}
+
+/// Decoder for the frames of one connection, plain or batched.
+///
+/// Keeps the type names the server interned so far; use a new decoder (or
+/// [`reset`](Self::reset)) for each connection.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    types: TypeTable,
+}
+
+impl FrameDecoder {
+    /// Decode all packets in one WebSocket frame.
+    ///
+    /// Stops at the first incomplete or undecodable packet.
+    pub fn decode(&mut self, data: &[u8]) -> Vec<NetworkPacket> {
+        let mut packets = Vec::new();
+        let mut offset = 0;
+
+        while let Some(length) = read_length(data, offset) {
+            offset += 8;
+            if length != BATCH_MARKER {
+                let Some((packet, length)) = decode_at::<NetworkPacket>(data, offset, length) else {
+                    break;
+                };
+                packets.push(packet);
+                offset += length;
+                continue;
+            }
+
+            let Some(length) = read_length(data, offset) else {
+                break;
+            };
+            offset += 8;
+            let Some((batch, length)) = decode_at::<PacketBatch>(data, offset, length) else {
+                break;
+            };
+            packets.extend(self.types.unpack(batch));
+            offset += length;
+        }
+
+        packets
+    }
+
+    /// Forget the interned types, for a new connection.
+    pub fn reset(&mut self) {
+        self.types.clear();
+    }
+}
+
+fn read_length(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = <[u8; 8]>::try_from(data.get(offset..offset + 8)?).ok()?;
+    Some(u64::from_le_bytes(bytes))
+}
+
+/// Decode the `length` bytes at `offset`, returning the value and its length.
+fn decode_at<T: serde::de::DeserializeOwned>(data: &[u8], offset: usize, length: u64) -> Option<(T, usize)> {
+    let length = usize::try_from(length).ok()?;
+    let bytes = data.get(offset..offset.checked_add(length)?)?;
+    let (value, _) = bincode::serde::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+    Some((value, length))
+}
+
+/// Encode one packet as a WebSocket frame.
+pub fn encode_frame(packet: &NetworkPacket) -> Vec<u8> {
+    let encoded = bincode::serde::encode_to_vec(packet, bincode::config::standard()).unwrap_or_default();
+    let mut frame = Vec::with_capacity(8 + encoded.len());
+    frame.extend_from_slice(&(encoded.len() as u64).to_le_bytes());
+    frame.extend_from_slice(&encoded);
+    frame
+}
+
+/// Wrap a sync protocol message in a packet.
+pub fn sync_packet(message: &SyncClientMessage) -> NetworkPacket {
+    NetworkPacket {
+        type_name: std::any::type_name::<SyncClientMessage>().to_string(),
+        schema_hash: 0,
+        data: bincode::serde::encode_to_vec(message, bincode::config::standard()).unwrap_or_default(),
+    }
+}
+
+/// Wrap an application message in a packet routed by its type name.
+pub fn message_packet<T: Pl3xusMessage>(message: &T) -> Result<NetworkPacket, bincode::error::EncodeError> {
+    Ok(NetworkPacket {
+        type_name: T::type_name().to_string(),
+        schema_hash: T::schema_hash(),
+        data: bincode::serde::encode_to_vec(message, bincode::config::standard())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_common::codec::TypeInterner;
+    use pl3xus_sync::UnsubscribeRequest;
+
+    #[test]
+    fn test_frame_roundtrip_with_batched_packets() {
+        let first = sync_packet(&SyncClientMessage::Unsubscribe(UnsubscribeRequest { subscription_id: 3 }));
+        let second = NetworkPacket {
+            type_name: "app::Ping".into(),
+            schema_hash: 7,
+            data: vec![1, 2, 3],
+        };
+
+        let mut frame = encode_frame(&first);
+        frame.extend(encode_frame(&second));
+        let packets = decode_frame(&frame);
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].type_name, first.type_name);
+        assert_eq!(packets[1].data, vec![1, 2, 3]);
+
+        // A truncated trailing packet is dropped
+        assert_eq!(decode_frame(&frame[..frame.len() - 1]).len(), 1);
+    }
+
+    #[test]
+    fn test_frame_decoder_resolves_interned_types() {
+        let mut interner = TypeInterner::default();
+        let packet = |value| NetworkPacket {
+            type_name: "app::Ping".into(),
+            schema_hash: 7,
+            data: vec![value],
+        };
+        let mut decoder = FrameDecoder::default();
+
+        // Plain packets and batches may share a frame
+        let mut frame = encode_frame(&sync_packet(&SyncClientMessage::Unsubscribe(UnsubscribeRequest {
+            subscription_id: 3,
+        })));
+        frame.extend(interner.encode(vec![packet(1), packet(2)]).unwrap());
+        let packets = decoder.decode(&frame);
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[2].type_name, "app::Ping");
+        assert_eq!(packets[2].data, vec![2]);
+
+        // Later batches only carry the type id
+        let packets = decoder.decode(&interner.encode(vec![packet(3)]).unwrap());
+        assert_eq!(packets[0].type_name, "app::Ping");
+        assert_eq!(packets[0].schema_hash, 7);
+
+        // The stateless decoder stops at a batch
+        assert_eq!(decode_frame(&frame).len(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+//! Query deduplication and server-driven invalidation.
+//!
+//! Components using the same query (same request type and parameters) share
+//! one cached state. The cache is generic over the state handle so each
+//! frontend can store its own reactive cell (a Leptos signal, a Dioxus
+//! signal, ...) around a [`QueryCacheState`].
+
+use std::collections::HashMap;
+
+use pl3xus_sync::QueryInvalidation;
+
+/// Type-erased query state stored in the cache.
+#[derive(Clone, Debug, Default)]
+pub struct QueryCacheState {
+    /// Raw response bytes (if available)
+    pub data: Option<Vec<u8>>,
+    /// Error message (if the query failed)
+    pub error: Option<String>,
+    /// Whether the query is currently fetching
+    pub is_fetching: bool,
+    /// Whether data has ever been fetched (for showing stale data while refetching)
+    pub is_stale: bool,
+}
+
+/// Entry in the query cache for deduplication.
+#[derive(Clone)]
+pub struct QueryCacheEntry<S> {
+    /// The shared state handle
+    pub state: S,
+    /// Reference count - when this reaches 0, the entry is removed
+    pub ref_count: usize,
+    /// Last invalidation counter seen - used to detect when to refetch
+    pub last_invalidation: u64,
+}
+
+/// Cached queries keyed by (query_type, query_key).
+///
+/// The query_key is a serialized representation of the request parameters.
+pub struct QueryCache<S> {
+    entries: HashMap<(String, String), QueryCacheEntry<S>>,
+}
+
+impl<S> Default for QueryCache<S> {
+    fn default() -> Self {
+        Self { entries: HashMap::new() }
+    }
+}
+
+impl<S: Clone> QueryCache<S> {
+    /// Get the shared state for a query, creating it with `make` if this is
+    /// the first reference. `invalidation_counter` is the query type's current
+    /// counter, recorded so only later invalidations trigger a refetch.
+    pub fn acquire(
+        &mut self,
+        query_type: &str,
+        query_key: &str,
+        invalidation_counter: u64,
+        make: impl FnOnce() -> S,
+    ) -> S {
+        let entry = self
+            .entries
+            .entry((query_type.to_string(), query_key.to_string()))
+            .and_modify(|entry| entry.ref_count += 1)
+            .or_insert_with(|| QueryCacheEntry {
+                state: make(),
+                ref_count: 1,
+                last_invalidation: invalidation_counter,
+            });
+        entry.state.clone()
+    }
+
+    /// Release a reference. Returns true if the entry was removed.
+    pub fn release(&mut self, query_type: &str, query_key: &str) -> bool {
+        let key = (query_type.to_string(), query_key.to_string());
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return false;
+        };
+        entry.ref_count = entry.ref_count.saturating_sub(1);
+        if entry.ref_count > 0 {
+            return false;
+        }
+        self.entries.remove(&key);
+        true
+    }
+
+    /// Whether the query type has been invalidated since this query last
+    /// checked. Records `current_counter` as seen.
+    pub fn needs_refetch(&mut self, query_type: &str, query_key: &str, current_counter: u64) -> bool {
+        let key = (query_type.to_string(), query_key.to_string());
+        match self.entries.get_mut(&key) {
+            Some(entry) if current_counter > entry.last_invalidation => {
+                entry.last_invalidation = current_counter;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get(&self, query_type: &str, query_key: &str) -> Option<&QueryCacheEntry<S>> {
+        self.entries.get(&(query_type.to_string(), query_key.to_string()))
+    }
+
+    /// Number of cached queries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All cached queries.
+    pub fn iter(&self) -> impl Iterator<Item = (&(String, String), &QueryCacheEntry<S>)> {
+        self.entries.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&(String, String), &mut QueryCacheEntry<S>)> {
+        self.entries.iter_mut()
+    }
+}
+
+/// Bump the invalidation counter of each query type named in `invalidation`,
+/// or of every known query type if it names none.
+pub fn apply_invalidation(counters: &mut HashMap<String, u64>, invalidation: &QueryInvalidation) {
+    if invalidation.query_types.is_empty() {
+        for counter in counters.values_mut() {
+            *counter += 1;
+        }
+        return;
+    }
+    for query_type in &invalidation.query_types {
+        *counters.entry(query_type.clone()).or_insert(0) += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_entry_and_refetch() {
+        let mut cache = QueryCache::<u32>::default();
+        assert_eq!(cache.acquire("ListPrograms", "{}", 0, || 1), 1);
+        // Second user shares the first state
+        assert_eq!(cache.acquire("ListPrograms", "{}", 0, || 2), 1);
+
+        let mut counters = HashMap::new();
+        apply_invalidation(
+            &mut counters,
+            &QueryInvalidation {
+                query_types: vec!["ListPrograms".into()],
+                keys: None,
+            },
+        );
+        assert!(cache.needs_refetch("ListPrograms", "{}", counters["ListPrograms"]));
+        assert!(!cache.needs_refetch("ListPrograms", "{}", counters["ListPrograms"]));
+
+        assert!(!cache.release("ListPrograms", "{}"));
+        assert!(cache.release("ListPrograms", "{}"));
+        assert_eq!(cache.iter().count(), 0);
+    }
+}
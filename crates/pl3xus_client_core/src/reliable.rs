@@ -0,0 +1,131 @@
+//! Client side of reliable delivery.
+//!
+//! Messages registered on the server with `.reliable()` are sent wrapped in a
+//! [`ReliableEnvelope`] carrying a per-type sequence number. Each envelope is
+//! kept until the server acknowledges it and is resent after a reconnect, so
+//! a Stop or Abort clicked while the connection is down still arrives. The
+//! server drops duplicates, so resending is always safe.
+//!
+//! Acks are cumulative: an ack for sequence N covers every envelope of that
+//! type up to N. After a server restart the outbox starts a new session and
+//! numbers its unacknowledged envelopes from 1 again (see [`ReliableOutbox::restart`]).
+
+use std::collections::HashMap;
+
+use pl3xus_common::{ReliableAck, ReliableEnvelope};
+
+pub use pl3xus_common::random_u64;
+
+/// Unacknowledged reliable messages and the next sequence number per type.
+#[derive(Debug)]
+pub struct ReliableOutbox {
+    session_id: u64,
+    next_sequence: HashMap<String, u64>,
+    /// Unacknowledged envelopes, in send order.
+    pending: Vec<ReliableEnvelope>,
+}
+
+impl Default for ReliableOutbox {
+    fn default() -> Self {
+        Self {
+            session_id: random_u64(),
+            next_sequence: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl ReliableOutbox {
+    /// Wrap a serialized message in the next envelope for its type and keep a
+    /// copy until it is acknowledged.
+    pub fn wrap(&mut self, message_type: &str, payload: Vec<u8>) -> ReliableEnvelope {
+        let sequence = self.next_sequence.entry(message_type.to_string()).or_insert(0);
+        *sequence += 1;
+
+        let envelope = ReliableEnvelope {
+            session_id: self.session_id,
+            sequence: *sequence,
+            message_type: message_type.to_string(),
+            payload,
+        };
+        self.pending.push(envelope.clone());
+        envelope
+    }
+
+    /// Forget the envelopes the server has acknowledged (all of the ack's
+    /// type up to its sequence).
+    pub fn acknowledge(&mut self, ack: &ReliableAck) {
+        if ack.session_id != self.session_id {
+            return;
+        }
+        self.pending
+            .retain(|e| !(e.message_type == ack.message_type && e.sequence <= ack.sequence));
+    }
+
+    /// Start a new session, e.g. after the server restarted and lost the old
+    /// one. Unacknowledged envelopes are renumbered from 1 in send order.
+    pub fn restart(&mut self) {
+        self.session_id = random_u64();
+        self.next_sequence.clear();
+        for envelope in &mut self.pending {
+            let sequence = self.next_sequence.entry(envelope.message_type.clone()).or_insert(0);
+            *sequence += 1;
+            envelope.session_id = self.session_id;
+            envelope.sequence = *sequence;
+        }
+    }
+
+    /// Envelopes still waiting for an ack, in send order.
+    pub fn pending(&self) -> &[ReliableEnvelope] {
+        &self.pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ack(outbox: &ReliableOutbox, message_type: &str, sequence: u64) -> ReliableAck {
+        ReliableAck {
+            session_id: outbox.session_id,
+            message_type: message_type.to_string(),
+            sequence,
+        }
+    }
+
+    #[test]
+    fn test_acks_are_cumulative_per_type() {
+        let mut outbox = ReliableOutbox::default();
+        for _ in 0..3 {
+            outbox.wrap("app::Stop", vec![]);
+        }
+        outbox.wrap("app::Abort", vec![]);
+
+        outbox.acknowledge(&ack(&outbox, "app::Stop", 2));
+        let pending: Vec<_> = outbox.pending().iter().map(|e| (e.message_type.as_str(), e.sequence)).collect();
+        assert_eq!(pending, [("app::Stop", 3), ("app::Abort", 1)]);
+
+        // Acks for another session are ignored
+        outbox.acknowledge(&ReliableAck {
+            session_id: outbox.session_id ^ 1,
+            ..ack(&outbox, "app::Abort", 1)
+        });
+        assert_eq!(outbox.pending().len(), 2);
+    }
+
+    #[test]
+    fn test_restart_renumbers_pending_envelopes() {
+        let mut outbox = ReliableOutbox::default();
+        for _ in 0..3 {
+            outbox.wrap("app::Stop", vec![]);
+        }
+        outbox.acknowledge(&ack(&outbox, "app::Stop", 1));
+        let old_session = outbox.session_id;
+
+        outbox.restart();
+        assert_ne!(outbox.session_id, old_session);
+        let sequences: Vec<_> = outbox.pending().iter().map(|e| (e.session_id, e.sequence)).collect();
+        assert_eq!(sequences, [(outbox.session_id, 1), (outbox.session_id, 2)]);
+        assert_eq!(outbox.wrap("app::Stop", vec![]).sequence, 3);
+    }
+}
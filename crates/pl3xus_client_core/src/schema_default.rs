@@ -0,0 +1,249 @@
+//! Placeholder values derived from a type's `Deserialize` impl.
+//!
+//! Used to pre-fill JSON payloads in the DevTools console: the type is
+//! deserialized from a deserializer that answers every request with an empty
+//! value (zero, empty string, `None`, first enum variant, ...), then serialized
+//! back to JSON. No `Default` impl is needed on the type.
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// A JSON value of the shape `T` expects, or `null` if one can't be built
+/// (e.g. a type whose `Deserialize` impl rejects empty values).
+pub(crate) fn default_json<T: Serialize + DeserializeOwned>() -> JsonValue {
+    T::deserialize(EmptyDeserializer)
+        .ok()
+        .and_then(|value| serde_json::to_value(value).ok())
+        .unwrap_or(JsonValue::Null)
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+struct EmptyDeserializer;
+
+macro_rules! empty_number {
+    ($($method:ident => $visit:ident($value:expr)),+ $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit($value)
+            }
+        )+
+    };
+}
+
+impl<'de> de::Deserializer<'de> for EmptyDeserializer {
+    type Error = Error;
+
+    /// Self-describing types (e.g. `serde_json::Value`) become `null`.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    empty_number! {
+        deserialize_bool => visit_bool(false),
+        deserialize_i8 => visit_i8(0),
+        deserialize_i16 => visit_i16(0),
+        deserialize_i32 => visit_i32(0),
+        deserialize_i64 => visit_i64(0),
+        deserialize_i128 => visit_i128(0),
+        deserialize_u8 => visit_u8(0),
+        deserialize_u16 => visit_u16(0),
+        deserialize_u32 => visit_u32(0),
+        deserialize_u64 => visit_u64(0),
+        deserialize_u128 => visit_u128(0),
+        deserialize_f32 => visit_f32(0.0),
+        deserialize_f64 => visit_f64(0.0),
+        deserialize_char => visit_char(' '),
+        deserialize_str => visit_str(""),
+        deserialize_string => visit_string(String::new()),
+        deserialize_bytes => visit_bytes(&[]),
+        deserialize_byte_buf => visit_byte_buf(Vec::new()),
+        deserialize_identifier => visit_str(""),
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(EmptySeq(0))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(EmptySeq(len))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(EmptySeq(len))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(EmptyFields(&[]))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(EmptyFields(fields))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let variant = variants
+            .first()
+            .ok_or_else(|| Error("enum has no variants".to_string()))?;
+        visitor.visit_enum(FirstVariant(variant))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// A sequence of `len` empty elements.
+struct EmptySeq(usize);
+
+impl<'de> de::SeqAccess<'de> for EmptySeq {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.0 == 0 {
+            return Ok(None);
+        }
+        self.0 -= 1;
+        seed.deserialize(EmptyDeserializer).map(Some)
+    }
+}
+
+/// Every declared field, each with an empty value.
+struct EmptyFields(&'static [&'static str]);
+
+impl<'de> de::MapAccess<'de> for EmptyFields {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        let Some((field, rest)) = self.0.split_first() else {
+            return Ok(None);
+        };
+        self.0 = rest;
+        seed.deserialize(field.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(EmptyDeserializer)
+    }
+}
+
+struct FirstVariant(&'static str);
+
+impl<'de> de::EnumAccess<'de> for FirstVariant {
+    type Error = Error;
+    type Variant = EmptyDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, EmptyDeserializer), Error> {
+        let variant = seed.deserialize(self.0.into_deserializer())?;
+        Ok((variant, EmptyDeserializer))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EmptyDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(EmptyDeserializer)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(EmptySeq(len))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(EmptyFields(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize)]
+    enum Frame {
+        World,
+        #[allow(dead_code)]
+        User(u8),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct MoveTo {
+        x: f64,
+        speed: Option<u32>,
+        frame: Frame,
+        name: String,
+        waypoints: Vec<(f32, f32)>,
+        pose: [f32; 2],
+        extra: JsonValue,
+    }
+
+    #[test]
+    fn test_default_json_fills_every_field() {
+        assert_eq!(
+            default_json::<MoveTo>(),
+            json!({
+                "x": 0.0,
+                "speed": null,
+                "frame": "World",
+                "name": "",
+                "waypoints": [],
+                "pose": [0.0, 0.0],
+                "extra": null,
+            })
+        );
+        assert_eq!(default_json::<u64>(), json!(0));
+    }
+}
@@ -0,0 +1,147 @@
+//! Subscription deduplication.
+//!
+//! Any number of hooks can use the same component type (or the same type and
+//! [`SubscriptionFilter`]); they share one server subscription, keyed by a
+//! subscription key, which is cancelled when the last of them goes away.
+
+use std::collections::HashMap;
+
+use pl3xus_sync::{SerializableEntity, SubscriptionFilter, SubscriptionRequest};
+use serde::Serialize;
+
+/// Reference-counted server subscriptions by subscription key.
+///
+/// The key is the component type, or `Component?<filter json>` for filtered
+/// subscriptions (see [`filtered_subscription_key`]).
+#[derive(Debug, Default)]
+pub struct SubscriptionTracker {
+    /// subscription key -> (subscription_id, ref_count)
+    subscriptions: HashMap<String, (u64, usize)>,
+    /// Filters of filtered subscriptions, by subscription key
+    filters: HashMap<String, SubscriptionFilter>,
+    next_subscription_id: u64,
+}
+
+impl SubscriptionTracker {
+    /// Take a reference to the subscription for `key`.
+    ///
+    /// Returns true for the first reference, whose caller should send
+    /// [`request`](Self::request) once connected.
+    pub fn acquire(&mut self, key: &str, filter: Option<SubscriptionFilter>) -> bool {
+        if let Some((_, ref_count)) = self.subscriptions.get_mut(key) {
+            *ref_count += 1;
+            return false;
+        }
+        let subscription_id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.subscriptions.insert(key.to_string(), (subscription_id, 1));
+        if let Some(filter) = filter {
+            self.filters.insert(key.to_string(), filter);
+        }
+        true
+    }
+
+    /// Drop a reference. Returns the subscription id when this was the last
+    /// one, so the caller can unsubscribe.
+    pub fn release(&mut self, key: &str) -> Option<u64> {
+        let (subscription_id, ref_count) = self.subscriptions.get_mut(key)?;
+        *ref_count -= 1;
+        if *ref_count > 0 {
+            return None;
+        }
+        let subscription_id = *subscription_id;
+        self.subscriptions.remove(key);
+        self.filters.remove(key);
+        Some(subscription_id)
+    }
+
+    /// The request subscribing `key`, if it is active.
+    pub fn request(&self, key: &str, entity: Option<SerializableEntity>) -> Option<SubscriptionRequest> {
+        let (subscription_id, _) = self.subscriptions.get(key)?;
+        let component_type = key.split_once('?').map_or(key, |(component_name, _)| component_name);
+        Some(SubscriptionRequest {
+            subscription_id: *subscription_id,
+            component_type: component_type.to_string(),
+            entity,
+            filter: self.filters.get(key).cloned(),
+        })
+    }
+
+    /// Requests for every active subscription, to resubscribe after a reconnect.
+    pub fn requests(&self) -> Vec<SubscriptionRequest> {
+        self.subscriptions
+            .keys()
+            .filter_map(|key| self.request(key, None))
+            .collect()
+    }
+
+    /// Key of the subscription with this id.
+    pub fn key_for_id(&self, subscription_id: u64) -> Option<&str> {
+        self.subscriptions
+            .iter()
+            .find(|(_, (id, _))| *id == subscription_id)
+            .map(|(key, _)| key.as_str())
+    }
+
+    /// Whether `key` has any references.
+    pub fn is_active(&self, key: &str) -> bool {
+        self.subscriptions.contains_key(key)
+    }
+}
+
+/// Subscription key of a filtered subscription: `Component?<filter json>`.
+pub fn filtered_subscription_key(component_name: &str, filter: &SubscriptionFilter) -> String {
+    format!("{}?{}", component_name, serde_json::to_string(filter).unwrap_or_default())
+}
+
+/// Apply a subscription filter to a received component.
+///
+/// Clients always re-check filters locally: servers without filter support
+/// send every entity, and the server sends the update that makes an entity
+/// stop matching.
+pub fn component_matches<T: Serialize>(filter: &SubscriptionFilter, entity_id: u64, component: &T) -> bool {
+    serde_json::to_value(component).is_ok_and(|json| filter.matches(&json, SerializableEntity { bits: entity_id }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_subscription_lifecycle() {
+        let mut tracker = SubscriptionTracker::default();
+        assert!(tracker.acquire("Position", None));
+        assert!(!tracker.acquire("Position", None));
+
+        let request = tracker.request("Position", None).unwrap();
+        assert_eq!(request.component_type, "Position");
+        assert_eq!(tracker.key_for_id(request.subscription_id), Some("Position"));
+
+        assert_eq!(tracker.release("Position"), None);
+        assert_eq!(tracker.release("Position"), Some(request.subscription_id));
+        assert!(!tracker.is_active("Position"));
+        assert!(tracker.request("Position", None).is_none());
+    }
+
+    #[test]
+    fn test_filtered_subscription_request() {
+        let mut tracker = SubscriptionTracker::default();
+        let filter = SubscriptionFilter::eq("mode", "Auto");
+        let key = filtered_subscription_key("Robot", &filter);
+        assert!(tracker.acquire("Robot", None));
+        assert!(tracker.acquire(&key, Some(filter.clone())));
+
+        let request = tracker.request(&key, None).unwrap();
+        assert_eq!(request.component_type, "Robot");
+        assert_eq!(request.filter, Some(filter));
+        assert_eq!(tracker.requests().len(), 2);
+
+        #[derive(Serialize)]
+        struct Robot {
+            mode: &'static str,
+        }
+        let filter = request.filter.unwrap();
+        assert!(component_matches(&filter, 1, &Robot { mode: "Auto" }));
+        assert!(!component_matches(&filter, 1, &Robot { mode: "Manual" }));
+    }
+}
@@ -0,0 +1,129 @@
+//! Browser WebSocket transport for [`SyncClientCore`].
+//!
+//! [`SyncSocket`] does the parts every browser frontend needs: binary frames,
+//! resubscribing when the socket opens and feeding incoming frames to the
+//! shared client. The adapter only turns the resulting [`SocketEvent`]s into
+//! its own reactive updates.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use pl3xus_common::codec::{BATCH_SUBPROTOCOL, BINCODE_SUBPROTOCOL};
+use pl3xus_common::NetworkPacket;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{Array, ArrayBuffer, Uint8Array};
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+use crate::client::{ClientEvent, SyncClientCore};
+use crate::error::SyncError;
+use crate::packet::encode_frame;
+
+/// Something that happened on a [`SyncSocket`].
+#[derive(Clone, Debug)]
+pub enum SocketEvent {
+    /// The socket opened and every active subscription was sent.
+    Open,
+    Closed,
+    /// The shared client handled an incoming packet.
+    Client(ClientEvent),
+}
+
+/// A browser WebSocket driving a shared [`SyncClientCore`].
+///
+/// Dropping it closes the connection.
+pub struct SyncSocket {
+    socket: WebSocket,
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut()>,
+}
+
+impl SyncSocket {
+    /// Connect to `url`.
+    ///
+    /// The subscriptions of `core` are (re)sent whenever the socket opens, so
+    /// subscribing before the connection is up is fine.
+    pub fn connect(
+        url: &str,
+        core: Rc<RefCell<SyncClientCore>>,
+        on_event: impl FnMut(SocketEvent) + 'static,
+    ) -> Result<Self, SyncError> {
+        // Servers that know batched frames pick them; older ones plain bincode
+        let subprotocols = Array::of2(&BATCH_SUBPROTOCOL.into(), &BINCODE_SUBPROTOCOL.into());
+        let socket = WebSocket::new_with_str_sequence(url, &subprotocols).map_err(websocket_error)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+        let on_event = Rc::new(RefCell::new(on_event));
+
+        let on_open = {
+            let socket = socket.clone();
+            let core = core.clone();
+            let on_event = on_event.clone();
+            Closure::<dyn FnMut()>::new(move || {
+                let packets = core.borrow().subscription_packets();
+                for packet in packets {
+                    let _ = socket.send_with_u8_array(&encode_frame(&packet));
+                }
+                (on_event.borrow_mut())(SocketEvent::Open);
+            })
+        };
+
+        let on_message = {
+            let on_event = on_event.clone();
+            Closure::<dyn FnMut(MessageEvent)>::new(move |message: MessageEvent| {
+                let Ok(buffer) = message.data().dyn_into::<ArrayBuffer>() else {
+                    return;
+                };
+                let frame = Uint8Array::new(&buffer).to_vec();
+                // Release the client before handing out events; handlers may use it
+                let events = core.borrow_mut().handle_frame(&frame);
+                for event in events {
+                    (on_event.borrow_mut())(SocketEvent::Client(event));
+                }
+            })
+        };
+
+        let on_close = Closure::<dyn FnMut()>::new(move || (on_event.borrow_mut())(SocketEvent::Closed));
+
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Ok(Self {
+            socket,
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.socket.ready_state() == WebSocket::OPEN
+    }
+
+    /// Send one packet.
+    pub fn send(&self, packet: &NetworkPacket) -> Result<(), SyncError> {
+        if !self.is_open() {
+            return Err(SyncError::NotConnected);
+        }
+        self.socket
+            .send_with_u8_array(&encode_frame(packet))
+            .map_err(websocket_error)
+    }
+}
+
+impl Drop for SyncSocket {
+    fn drop(&mut self) {
+        // The closures are freed with this struct; detach them first
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        let _ = self.socket.close();
+    }
+}
+
+fn websocket_error(error: JsValue) -> SyncError {
+    SyncError::WebSocketError {
+        message: format!("{:?}", error),
+    }
+}
@@ -0,0 +1,86 @@
+//! Large binary data transferred outside of sync batches.
+//!
+//! Components that conceptually hold big payloads (point clouds, camera
+//! snapshots) carry a [`BlobRef`] instead of the bytes. The server keeps the
+//! bytes by content hash, and clients fetch them on demand in chunks with
+//! [`FetchBlob`], caching them by hash. Sync batches stay small, and a blob
+//! referenced by several entities or updates is only transferred once.
+//!
+//! ```rust,ignore
+//! #[derive(Component, Serialize, Deserialize, Clone, Debug)]
+//! struct CameraSnapshot {
+//!     taken_at_ms: f64,
+//!     image: BlobRef,
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::RequestMessage;
+
+/// Default number of bytes per [`BlobChunk`].
+pub const BLOB_CHUNK_SIZE: u32 = 256 * 1024;
+
+/// Reference to a blob, by the hash of its content.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlobRef {
+    /// Hex-encoded BLAKE3 hash of the content.
+    pub hash: String,
+    /// Size of the content in bytes.
+    pub size: u64,
+    /// MIME type of the content, if known (e.g. `image/png`).
+    pub content_type: Option<String>,
+}
+
+impl BlobRef {
+    /// Returns true for the default reference, which points at no blob.
+    pub fn is_empty(&self) -> bool {
+        self.hash.is_empty()
+    }
+}
+
+/// Request a chunk of a blob.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FetchBlob {
+    /// [`BlobRef::hash`] of the blob.
+    pub hash: String,
+    /// Offset of the first byte to send.
+    pub offset: u64,
+    /// Most bytes to send. The server may send fewer.
+    pub max_len: u32,
+}
+
+impl FetchBlob {
+    /// Request the first chunk of `blob`.
+    pub fn first(blob: &BlobRef) -> Self {
+        Self {
+            hash: blob.hash.clone(),
+            offset: 0,
+            max_len: BLOB_CHUNK_SIZE,
+        }
+    }
+}
+
+/// Response to [`FetchBlob`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlobChunk {
+    pub hash: String,
+    /// Offset of `data` in the blob.
+    pub offset: u64,
+    /// Size of the whole blob.
+    pub total_size: u64,
+    pub data: Vec<u8>,
+    /// Set when the server doesn't have the blob (e.g. it was evicted).
+    pub error: Option<String>,
+}
+
+impl BlobChunk {
+    /// Returns true if this is the blob's last chunk.
+    pub fn is_last(&self) -> bool {
+        self.offset + self.data.len() as u64 >= self.total_size
+    }
+}
+
+impl RequestMessage for FetchBlob {
+    type ResponseMessage = BlobChunk;
+}
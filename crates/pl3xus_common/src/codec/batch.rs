@@ -0,0 +1,177 @@
+//! Batched frames with interned type names.
+//!
+//! In the plain framing every packet carries its full type name, so a frame
+//! of 100 component updates repeats `pl3xus_sync::messages::SyncServerMessage`
+//! 100 times. On connections that negotiated [`BATCH_SUBPROTOCOL`], the
+//! server instead sends everything queued for one write as a [`PacketBatch`]:
+//! each type is named once per connection, the first time it is sent, and
+//! later packets refer to it by a small integer id.
+//!
+//! A batch frame starts with [`BATCH_MARKER`] in place of a packet length,
+//! followed by the length-prefixed bincode batch, so readers can tell it from
+//! plain packets in the same stream.
+//!
+//! [`BATCH_SUBPROTOCOL`]: super::BATCH_SUBPROTOCOL
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::NetworkError, NetworkPacket};
+
+/// Written where a packet length would be to announce a [`PacketBatch`].
+pub const BATCH_MARKER: u64 = u64::MAX;
+
+/// A type first sent in this batch, and the id later packets use for it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InternedType {
+    pub id: u32,
+    pub type_name: String,
+    pub schema_hash: u64,
+}
+
+/// A packet whose type is referred to by its interned id.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BatchedPacket {
+    pub type_id: u32,
+    pub data: Vec<u8>,
+}
+
+/// Several packets sent as one frame.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PacketBatch {
+    /// Types used for the first time on this connection.
+    pub types: Vec<InternedType>,
+    pub packets: Vec<BatchedPacket>,
+}
+
+/// Sending side of one connection: assigns ids to types as they are first sent.
+#[derive(Debug, Default)]
+pub struct TypeInterner {
+    ids: HashMap<(String, u64), u32>,
+}
+
+impl TypeInterner {
+    /// Pack `packets` into a batch, declaring the types this connection hasn't seen.
+    pub fn batch(&mut self, packets: impl IntoIterator<Item = NetworkPacket>) -> PacketBatch {
+        let mut batch = PacketBatch::default();
+        for packet in packets {
+            let next_id = self.ids.len() as u32;
+            let type_id = *self
+                .ids
+                .entry((packet.type_name.clone(), packet.schema_hash))
+                .or_insert_with(|| {
+                    batch.types.push(InternedType {
+                        id: next_id,
+                        type_name: packet.type_name,
+                        schema_hash: packet.schema_hash,
+                    });
+                    next_id
+                });
+            batch.packets.push(BatchedPacket {
+                type_id,
+                data: packet.data,
+            });
+        }
+        batch
+    }
+
+    /// Pack `packets` into one batch frame.
+    pub fn encode(&mut self, packets: impl IntoIterator<Item = NetworkPacket>) -> Result<Vec<u8>, NetworkError> {
+        encode_batch_frame(&self.batch(packets))
+    }
+}
+
+/// Receiving side of one connection: the types declared so far.
+#[derive(Debug, Default)]
+pub struct TypeTable {
+    types: HashMap<u32, (String, u64)>,
+}
+
+impl TypeTable {
+    /// Record the batch's new types and expand its packets.
+    ///
+    /// Packets of types that were never declared are dropped.
+    pub fn unpack(&mut self, batch: PacketBatch) -> Vec<NetworkPacket> {
+        for declared in batch.types {
+            self.types
+                .insert(declared.id, (declared.type_name, declared.schema_hash));
+        }
+        batch
+            .packets
+            .into_iter()
+            .filter_map(|packet| {
+                let (type_name, schema_hash) = self.types.get(&packet.type_id)?;
+                Some(NetworkPacket {
+                    type_name: type_name.clone(),
+                    schema_hash: *schema_hash,
+                    data: packet.data,
+                })
+            })
+            .collect()
+    }
+
+    /// Forget every type, e.g. when the connection is replaced.
+    pub fn clear(&mut self) {
+        self.types.clear();
+    }
+}
+
+/// Frame a batch: [`BATCH_MARKER`], the batch's length, then the batch.
+pub fn encode_batch_frame(batch: &PacketBatch) -> Result<Vec<u8>, NetworkError> {
+    let encoded =
+        bincode::serde::encode_to_vec(batch, bincode::config::standard()).map_err(|_| NetworkError::Serialization)?;
+    let mut frame = Vec::with_capacity(16 + encoded.len());
+    frame.extend_from_slice(&BATCH_MARKER.to_le_bytes());
+    frame.extend_from_slice(&(encoded.len() as u64).to_le_bytes());
+    frame.extend_from_slice(&encoded);
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(value: u8) -> NetworkPacket {
+        NetworkPacket {
+            type_name: "pl3xus_sync::messages::SyncServerMessage".to_string(),
+            schema_hash: 0x1234567890abcdef,
+            data: vec![value; 4],
+        }
+    }
+
+    #[test]
+    fn test_batch_roundtrip_interns_types() {
+        let mut interner = TypeInterner::default();
+        let mut table = TypeTable::default();
+
+        let first = interner.batch(vec![update(1), update(2)]);
+        assert_eq!(first.types.len(), 1);
+        let unpacked = table.unpack(first);
+        assert_eq!(unpacked.len(), 2);
+        assert_eq!(unpacked[1].type_name, update(2).type_name);
+        assert_eq!(unpacked[1].schema_hash, update(2).schema_hash);
+        assert_eq!(unpacked[1].data, update(2).data);
+
+        // Known types aren't declared again
+        let second = interner.batch(vec![update(3)]);
+        assert!(second.types.is_empty());
+        assert_eq!(table.unpack(second)[0].data, vec![3; 4]);
+
+        // A table that missed the declaration drops the packet
+        assert!(TypeTable::default().unpack(interner.batch(vec![update(4)])).is_empty());
+    }
+
+    #[test]
+    fn test_batch_frame_is_smaller_than_plain_packets() {
+        let packets: Vec<NetworkPacket> = (0..100).map(update).collect();
+        let plain: usize = packets
+            .iter()
+            .map(|packet| 8 + bincode::serde::encode_to_vec(packet, bincode::config::standard()).unwrap().len())
+            .sum();
+        let batched = TypeInterner::default().encode(packets).unwrap();
+
+        assert_eq!(batched[..8], BATCH_MARKER.to_le_bytes());
+        assert!(batched.len() * 4 < plain, "{} vs {}", batched.len(), plain);
+    }
+}
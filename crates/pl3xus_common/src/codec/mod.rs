@@ -1,4 +1,27 @@
+pub mod batch;
 pub mod binary;
 
 // Re-export the codecs for convenience
+pub use batch::{encode_batch_frame, BatchedPacket, InternedType, PacketBatch, TypeInterner, TypeTable, BATCH_MARKER};
 pub use binary::{Pl3xusBincodeCodec, Pl3xusBincodeSingleMsgCodec};
+
+/// WebSocket subprotocol for the framing of [`Pl3xusBincodeCodec`]: bincode
+/// [`NetworkPacket`](crate::NetworkPacket)s with a length prefix.
+///
+/// Clients may offer it during the handshake; a server that negotiates it
+/// reports it in the connection's `pl3xus::ConnectionInfo`.
+pub const BINCODE_SUBPROTOCOL: &str = "pl3xus.bincode.v1";
+
+/// WebSocket subprotocol for [`PacketBatch`] frames from the server.
+///
+/// Like [`BINCODE_SUBPROTOCOL`], except that the server sends all packets
+/// queued for one write as a single batch with interned type names. Clients
+/// still send plain packets.
+pub const BATCH_SUBPROTOCOL: &str = "pl3xus.batch.v1";
+
+/// WebSocket subprotocol reserved for JSON packets.
+///
+/// Servers don't accept it by default: the JSON clients (`pl3xus_js` and the
+/// Python client) transcode payloads with the server's schema and still use
+/// [`BINCODE_SUBPROTOCOL`] on the wire.
+pub const JSON_SUBPROTOCOL: &str = "pl3xus.json.v1";
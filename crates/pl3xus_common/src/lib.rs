@@ -8,6 +8,20 @@ pub mod codec;
 
 pub mod error;
 
+pub mod manifest;
+pub use manifest::{ManifestComponent, TypeManifest, TypeVisitor};
+
+pub mod blob;
+pub use blob::{BlobChunk, BlobRef, FetchBlob, BLOB_CHUNK_SIZE};
+
+pub mod spawn;
+pub use spawn::{ComponentOverride, DespawnEntityRequest, DespawnEntityResponse, SpawnEntityRequest, SpawnEntityResponse};
+
+pub mod schema;
+pub use schema::{
+    describe_type, DescribeSchema, FieldShape, RequestSchema, SchemaDescription, TypeSchema, TypeShape,
+};
+
 use serde::{Deserialize, Serialize};
 
 use std::fmt::Debug;
@@ -15,6 +29,22 @@ use std::fmt::Display;
 
 pub use pl3xus_macros::SubscribeById;
 
+/// Random `u64` from the platform's CSPRNG (the operating system on native
+/// targets, `crypto.getRandomValues` in the browser).
+///
+/// Used for ids that must not be guessable, such as idempotency keys.
+pub fn random_u64() -> u64 {
+    let mut bytes = [0u8; 8];
+    #[cfg(target_arch = "wasm32")]
+    web_sys::window()
+        .and_then(|window| window.crypto().ok())
+        .and_then(|crypto| crypto.get_random_values_with_u8_array(&mut bytes).ok())
+        .expect("browser crypto unavailable");
+    #[cfg(not(target_arch = "wasm32"))]
+    getrandom::fill(&mut bytes).expect("operating system random number generator unavailable");
+    u64::from_le_bytes(bytes)
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 /// [`NetworkPacket`]s are untyped packets to be sent over the wire
 ///
@@ -44,6 +74,11 @@ impl Debug for NetworkPacket {
 
 #[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy, Debug)]
 /// A [`ConnectionId`] denotes a single connection
+///
+/// Servers hand out ids from 1 upward and never give an id to two live
+/// connections. Ids only identify a connection within one server run; pair
+/// them with the session from the sync handshake ([`ConnectionIdentity`]) to
+/// tell connections from different runs apart.
 pub struct ConnectionId {
     /// The key of the connection.
     pub id: u32,
@@ -53,10 +88,20 @@ impl ConnectionId {
     /// Represents the server's connection ID
     pub const SERVER: Self = ConnectionId { id: 0 };
 
+    /// No connection, e.g. the connection of [`ConnectionIdentity::NONE`].
+    ///
+    /// Never assigned to a connection.
+    pub const NONE: Self = ConnectionId { id: u32::MAX };
+
     /// Returns true if this ConnectionId represents the server
     pub fn is_server(&self) -> bool {
         self.id == Self::SERVER.id
     }
+
+    /// Returns true if this is [`ConnectionId::NONE`].
+    pub fn is_none(&self) -> bool {
+        self.id == Self::NONE.id
+    }
 }
 
 impl Display for ConnectionId {
@@ -65,20 +110,131 @@ impl Display for ConnectionId {
     }
 }
 
+/// A connection qualified by the server run it belongs to.
+///
+/// Connection ids start over when the server restarts, so a client that
+/// remembers its id across a reconnect could mistake another client's control
+/// for its own. The session is random per server run and sent in the sync
+/// handshake; two identities are the same connection only if both parts match.
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ConnectionIdentity {
+    /// Random id of the server run that assigned `connection`.
+    pub session: u64,
+    /// The connection within that run.
+    pub connection: ConnectionId,
+}
+
+impl ConnectionIdentity {
+    /// No connection, e.g. the holder of an uncontrolled [`EntityControl`].
+    pub const NONE: Self = ConnectionIdentity {
+        session: 0,
+        connection: ConnectionId::NONE,
+    };
+
+    /// Returns true if this is [`ConnectionIdentity::NONE`].
+    pub fn is_none(&self) -> bool {
+        self.connection.is_none()
+    }
+}
+
+impl Display for ConnectionIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} in session {:016x}", self.connection, self.session)
+    }
+}
+
+// ============================================================================
+// Entity Identifiers
+// ============================================================================
+
+/// Bevy-agnostic entity identifier used on the wire.
+///
+/// Internally this just uses Bevy's opaque `Entity` bits representation so that
+/// we don't rely on any particular layout (row/generation, etc.).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SerializableEntity {
+    /// The entity's `Entity::to_bits()`.
+    pub bits: u64,
+}
+
+impl SerializableEntity {
+    /// A dangling entity that can be used to signal "spawn a new entity" in mutations.
+    /// This uses the same bit pattern as Bevy's Entity::PLACEHOLDER.
+    pub const DANGLING: Self = Self { bits: u64::MAX };
+}
+
+impl From<u64> for SerializableEntity {
+    fn from(bits: u64) -> Self {
+        Self { bits }
+    }
+}
+
+#[cfg(feature = "ecs")]
+impl From<bevy::prelude::Entity> for SerializableEntity {
+    fn from(e: bevy::prelude::Entity) -> Self {
+        Self { bits: e.to_bits() }
+    }
+}
+
+#[cfg(feature = "ecs")]
+impl SerializableEntity {
+    /// The entity these bits belong to.
+    pub fn to_entity(self) -> bevy::prelude::Entity {
+        bevy::prelude::Entity::from_bits(self.bits)
+    }
+}
+
 // ============================================================================
 // Control Types (shared between server and client)
 // ============================================================================
 
 /// Request to take or release control of an entity.
 ///
-/// Used with `ExclusiveControlPlugin` on the server.
+/// Used with `ExclusiveControlPlugin` on the server. Build requests with
+/// [`ControlRequest::take`] and [`ControlRequest::release`].
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
 pub enum ControlRequest {
-    /// Request to take control of the specified entity (entity.to_bits()).
+    /// Deprecated: use [`ControlRequest::TakeEntity`]. Still accepted by
+    /// servers so that older clients keep working.
     Take(u64),
-    /// Request to release control of the specified entity (entity.to_bits()).
+    /// Deprecated: use [`ControlRequest::ReleaseEntity`]. Still accepted by
+    /// servers so that older clients keep working.
     Release(u64),
+    /// Request to take control of the entity.
+    TakeEntity(SerializableEntity),
+    /// Request to release control of the entity.
+    ReleaseEntity(SerializableEntity),
+}
+
+impl ControlRequest {
+    /// Take control of `entity`.
+    pub fn take(entity: impl Into<SerializableEntity>) -> Self {
+        Self::TakeEntity(entity.into())
+    }
+
+    /// Release control of `entity`.
+    pub fn release(entity: impl Into<SerializableEntity>) -> Self {
+        Self::ReleaseEntity(entity.into())
+    }
+
+    /// The entity control is requested for, in either format.
+    pub fn entity(&self) -> SerializableEntity {
+        match *self {
+            Self::Take(bits) | Self::Release(bits) => SerializableEntity { bits },
+            Self::TakeEntity(entity) | Self::ReleaseEntity(entity) => entity,
+        }
+    }
+
+    /// Whether this requests taking control, rather than releasing it.
+    pub fn is_take(&self) -> bool {
+        matches!(self, Self::Take(_) | Self::TakeEntity(_))
+    }
+
+    /// Whether this uses the deprecated `u64` variants.
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, Self::Take(_) | Self::Release(_))
+    }
 }
 
 /// Response to a control request.
@@ -91,6 +247,10 @@ pub enum ControlRequest {
 pub struct ControlResponse {
     /// Unique sequence number to distinguish otherwise identical responses.
     pub sequence: u64,
+    /// The entity the response concerns, so a client with several requests in
+    /// flight can tell which one it answers.
+    #[serde(default)]
+    pub entity: Option<SerializableEntity>,
     /// The actual response variant.
     pub kind: ControlResponseKind,
 }
@@ -120,6 +280,22 @@ pub enum ControlResponseKind {
     },
     /// An error occurred.
     Error(String),
+    /// The entity's control policy doesn't allow this client to take control.
+    Denied {
+        /// Why control was denied.
+        reason: String,
+    },
+    /// The entity's control policy requires approval; the request is held
+    /// until the server approves (then `Taken`) or denies it (then `Denied`).
+    AwaitingApproval,
+    /// Control of `entity` will be released for inactivity unless the client
+    /// sends a [`ControlKeepalive`] (or takes control again) within `seconds_remaining`.
+    TimeoutWarning {
+        /// The entity whose control is about to time out.
+        entity: SerializableEntity,
+        /// Seconds until control is released.
+        seconds_remaining: f32,
+    },
 }
 
 /// Component that tracks which client has control of an entity.
@@ -139,11 +315,12 @@ pub enum ControlResponseKind {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "ecs", derive(bevy::prelude::Component))]
 pub struct EntityControl {
-    /// The client that currently has control.
-    pub client_id: ConnectionId,
+    /// The client that currently has control, or [`ConnectionIdentity::NONE`].
+    pub client: ConnectionIdentity,
     /// Sub-connections that share control with the primary client.
     /// These are related connections (like additional browser tabs) that
-    /// are authorized to send commands on behalf of the controlling client.
+    /// are authorized to send commands on behalf of the controlling client,
+    /// in the same session as `client`.
     #[serde(default)]
     pub sub_connection_ids: Vec<ConnectionId>,
     /// Timestamp of last activity (for timeout detection).
@@ -153,7 +330,7 @@ pub struct EntityControl {
 impl Default for EntityControl {
     fn default() -> Self {
         Self {
-            client_id: ConnectionId { id: 0 },
+            client: ConnectionIdentity::NONE,
             sub_connection_ids: Vec::new(),
             last_activity: 0.0,
         }
@@ -162,17 +339,26 @@ impl Default for EntityControl {
 
 impl EntityControl {
     /// Check if the given connection has control (either as primary or sub-connection).
-    pub fn has_control(&self, connection_id: ConnectionId) -> bool {
-        // client_id 0 means "no controller" - nobody has control
-        if self.client_id.id == 0 {
+    pub fn has_control(&self, identity: ConnectionIdentity) -> bool {
+        if !self.is_controlled() || identity.session != self.client.session {
             return false;
         }
-        self.client_id == connection_id || self.sub_connection_ids.contains(&connection_id)
+        self.client.connection == identity.connection || self.sub_connection_ids.contains(&identity.connection)
+    }
+
+    /// Check if the given connection is the primary controller.
+    pub fn held_by(&self, identity: ConnectionIdentity) -> bool {
+        self.is_controlled() && self.client == identity
     }
 
     /// Check if any client has control of this entity.
     pub fn is_controlled(&self) -> bool {
-        self.client_id.id != 0
+        !self.client.is_none()
+    }
+
+    /// The controlling client, if any.
+    pub fn holder(&self) -> Option<ConnectionIdentity> {
+        self.is_controlled().then_some(self.client)
     }
 }
 
@@ -180,6 +366,36 @@ impl EntityControl {
 // Sub-Connection Types (for related connections like multiple browser tabs)
 // ============================================================================
 
+/// Sent by clients while their user is present, so that control held by a
+/// client that is only monitoring doesn't time out for inactivity.
+///
+/// `pl3xus_client`'s `SyncProvider` sends it periodically while the page is
+/// focused and the user isn't idle.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
+pub struct ControlKeepalive;
+
+/// Request a one-time token that lets another connection join this one as a
+/// sub-connection. The server answers with a [`SubConnectionToken`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
+pub struct RequestSubConnectionToken;
+
+/// Token issued to a parent connection for [`AssociateSubConnection`].
+///
+/// Hand it to the related connection out of band, e.g. in the URL of a new
+/// tab or over a `BroadcastChannel`. It can be used once.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
+pub struct SubConnectionToken {
+    /// The parent connection the token was issued to.
+    pub parent_connection_id: ConnectionId,
+    /// Opaque token to pass in [`AssociateSubConnection::token`].
+    pub token: String,
+    /// Seconds until the token expires.
+    pub expires_in_secs: f32,
+}
+
 /// Request to associate a sub-connection with a parent connection.
 ///
 /// When a client opens a related connection (like a second browser tab),
@@ -187,21 +403,33 @@ impl EntityControl {
 /// permissions. Messages from sub-connections are authorized as if they
 /// came from the parent connection.
 ///
+/// The server only accepts the association with a token the parent obtained
+/// through [`RequestSubConnectionToken`], so a client can't claim to belong
+/// to an arbitrary connection.
+///
 /// # Example Flow
 ///
 /// 1. User opens main app in Tab 1, gets ConnectionId 5
-/// 2. User opens a second tab (Tab 2), gets ConnectionId 7
-/// 3. Tab 2 sends `AssociateSubConnection { parent_connection_id: 5 }`
-/// 4. Server updates SubConnections resource for connection 5 to include 7
-/// 5. When Tab 1 takes control of an entity, Tab 2 can also send commands
+/// 2. Tab 1 sends `RequestSubConnectionToken` and receives a [`SubConnectionToken`]
+/// 3. Tab 1 opens Tab 2 with the token (e.g. `?parent=5&token=...`); Tab 2 gets ConnectionId 7
+/// 4. Tab 2 sends `AssociateSubConnection { parent_connection_id: 5, token }`
+/// 5. Server checks and consumes the token and adds 7 to 5's sub-connections
+/// 6. When Tab 1 takes control of an entity, Tab 2 can also send commands
+///
+/// When the parent disconnects, its sub-connections are dropped and told so
+/// with an unsuccessful [`AssociateSubConnectionResponse`].
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
 pub struct AssociateSubConnection {
     /// The parent connection that this sub-connection should be associated with.
     pub parent_connection_id: ConnectionId,
+    /// Token from the parent's [`SubConnectionToken`].
+    pub token: String,
 }
 
 /// Response to a sub-connection association request.
+///
+/// Also sent unprompted with `success: false` when the parent disconnects.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
 pub struct AssociateSubConnectionResponse {
@@ -213,6 +441,415 @@ pub struct AssociateSubConnectionResponse {
     pub parent_connection_id: ConnectionId,
 }
 
+// ============================================================================
+// Presence Types (shared between server and client)
+// ============================================================================
+
+/// Presence information for a connected client.
+///
+/// The server keeps one entity with this component per connected client
+/// (see `ClientPresencePlugin` in pl3xus_sync) and syncs it read-only, so UIs
+/// can show who else is connected and who holds control.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Component))]
+pub struct ClientPresence {
+    /// The client's connection.
+    pub connection_id: ConnectionId,
+    /// Human-readable identity reported by the client via [`SetClientIdentity`].
+    pub identity: Option<String>,
+    /// When the client connected, in milliseconds since the Unix epoch.
+    pub connected_at_ms: u64,
+    /// Number of active component subscriptions held by the client.
+    pub subscription_count: u32,
+    /// Entity bits of the entities the client currently controls.
+    pub controlled_entities: Vec<u64>,
+}
+
+impl Default for ClientPresence {
+    fn default() -> Self {
+        Self {
+            connection_id: ConnectionId::NONE,
+            identity: None,
+            connected_at_ms: 0,
+            subscription_count: 0,
+            controlled_entities: Vec::new(),
+        }
+    }
+}
+
+impl ClientPresence {
+    /// Check if this client controls any entity.
+    pub fn holds_control(&self) -> bool {
+        !self.controlled_entities.is_empty()
+    }
+}
+
+/// Message sent by a client to set the identity shown in its [`ClientPresence`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
+pub struct SetClientIdentity {
+    /// Display name or user identifier for this client.
+    pub identity: String,
+}
+
+// ============================================================================
+// Action Availability Types (shared between server and client)
+// ============================================================================
+
+/// Whether a named action can currently be performed, and why not.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct ActionState {
+    pub enabled: bool,
+    /// Why the action is disabled, for tooltips. `None` when enabled.
+    pub reason: Option<String>,
+}
+
+impl ActionState {
+    /// An action that can be performed.
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            reason: None,
+        }
+    }
+
+    /// An action that cannot be performed, with the reason shown to users.
+    pub fn disabled(reason: impl Into<String>) -> Self {
+        Self {
+            enabled: false,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Named actions (e.g. "start", "pause", "unload") available on an entity.
+///
+/// The server decides which actions are available and syncs this component
+/// read-only, so clients gate their buttons on the server's state machine
+/// instead of duplicating it. See `ActionsPlugin` in pl3xus_sync.
+///
+/// When the `ecs` feature is enabled, this type also derives `Component`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Component))]
+pub struct EntityActions {
+    pub actions: std::collections::BTreeMap<String, ActionState>,
+}
+
+impl EntityActions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare `name` as enabled.
+    pub fn enable(mut self, name: impl Into<String>) -> Self {
+        self.set(name, ActionState::enabled());
+        self
+    }
+
+    /// Declare `name` as disabled with a reason.
+    pub fn disable(mut self, name: impl Into<String>, reason: impl Into<String>) -> Self {
+        self.set(name, ActionState::disabled(reason));
+        self
+    }
+
+    /// Declare `name` as enabled if `enabled`, otherwise disabled with `reason`.
+    pub fn when(self, name: impl Into<String>, enabled: bool, reason: impl Into<String>) -> Self {
+        if enabled {
+            self.enable(name)
+        } else {
+            self.disable(name, reason)
+        }
+    }
+
+    /// Set the state of `name`.
+    pub fn set(&mut self, name: impl Into<String>, state: ActionState) {
+        self.actions.insert(name.into(), state);
+    }
+
+    /// Get the state of `name`, if declared.
+    pub fn get(&self, name: &str) -> Option<&ActionState> {
+        self.actions.get(name)
+    }
+
+    /// Whether `name` is declared and enabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|state| state.enabled)
+    }
+
+    /// Why `name` is disabled, if it is.
+    pub fn reason(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|state| state.reason.as_deref())
+    }
+
+    /// Copy every action declared in `other` into `self`, keeping the rest.
+    pub fn merge(&mut self, other: &EntityActions) {
+        for (name, state) in &other.actions {
+            self.actions.insert(name.clone(), state.clone());
+        }
+    }
+}
+
+// ============================================================================
+// Stable Entity IDs (shared between server and client)
+// ============================================================================
+
+/// Application-chosen name of an entity that survives server restarts.
+///
+/// Entity bits change whenever the server restarts, so clients that store
+/// references to entities (bookmarks, saved layouts) should store this alias
+/// instead and resolve it to the current entity. See `StableIdPlugin` in
+/// pl3xus_sync.
+///
+/// When the `ecs` feature is enabled, this type also derives `Component`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Component))]
+pub struct StableId(pub String);
+
+impl StableId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for StableId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// ============================================================================
+// Undo Types (shared between server and client)
+// ============================================================================
+
+/// Undo/redo availability for an entity.
+///
+/// Maintained by pl3xus_sync on entities whose components were registered
+/// with undo support, and synced read-only so clients can enable or disable
+/// their undo and redo buttons.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Component))]
+pub struct UndoHistory {
+    /// Number of mutations that can be undone.
+    pub undo_depth: u32,
+    /// Number of undone mutations that can be redone.
+    pub redo_depth: u32,
+}
+
+impl UndoHistory {
+    /// Whether there is a mutation to undo.
+    pub fn can_undo(&self) -> bool {
+        self.undo_depth > 0
+    }
+
+    /// Whether there is an undone mutation to redo.
+    pub fn can_redo(&self) -> bool {
+        self.redo_depth > 0
+    }
+}
+
+// ============================================================================
+// Network Tracing Types (shared between server and client)
+// ============================================================================
+
+/// Level at which pl3xus emits per-message tracing events.
+///
+/// Connection-level events (connect, disconnect, errors) are always logged;
+/// this only controls the high-frequency per-message events.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum NetworkLogLevel {
+    /// Per-message events are not emitted.
+    Off,
+    /// Emit per-message events at `TRACE`.
+    Trace,
+    /// Emit per-message events at `DEBUG`.
+    #[default]
+    Debug,
+    /// Emit per-message events at `INFO` (useful for short debugging sessions).
+    Info,
+}
+
+/// Admin request to change the per-message log level at runtime.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SetNetworkLogLevel {
+    /// The new level.
+    pub level: NetworkLogLevel,
+}
+
+/// Response to [`SetNetworkLogLevel`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SetNetworkLogLevelResponse {
+    /// The level before the change.
+    pub previous: NetworkLogLevel,
+    /// The level now in effect.
+    pub current: NetworkLogLevel,
+}
+
+impl RequestMessage for SetNetworkLogLevel {
+    type ResponseMessage = SetNetworkLogLevelResponse;
+}
+
+// ============================================================================
+// Clock Synchronization Types (shared between server and client)
+// ============================================================================
+
+/// Clock probe sent periodically by a client.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ClockPing {
+    /// Client wall-clock time when the probe was sent (ms since the Unix epoch).
+    pub client_time_ms: f64,
+}
+
+/// Server reply to a [`ClockPing`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ClockPong {
+    /// `client_time_ms` from the ping, unchanged.
+    pub client_time_ms: f64,
+    /// Server wall-clock time when the reply was sent (ms since the Unix epoch).
+    pub server_wall_ms: f64,
+    /// Server monotonic time when the reply was sent (ms since the server started).
+    pub server_monotonic_ms: f64,
+}
+
+/// Structured rejection of a payload larger than its registered limit.
+///
+/// The server sends this instead of decoding the payload, and keeps the
+/// connection open. For requests, `request_id` lets the client fail the
+/// pending request right away instead of waiting for a timeout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PayloadTooLarge {
+    /// Full type name of the rejected message or request.
+    pub type_name: String,
+    /// The request's id, if the payload was a request.
+    pub request_id: Option<u64>,
+    /// Size of the payload in bytes.
+    pub size: u64,
+    /// The registered limit in bytes.
+    pub limit: u64,
+}
+
+/// Estimated relation between the local clock and the server's clocks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClockEstimate {
+    /// `server_wall_time - local_wall_time`, in milliseconds.
+    pub offset_ms: f64,
+    /// `server_monotonic_time - local_wall_time`, in milliseconds.
+    pub monotonic_offset_ms: f64,
+    /// Round trip of the sample this estimate came from, in milliseconds.
+    /// The estimate is accurate to within half of this.
+    pub rtt_ms: f64,
+}
+
+impl ClockEstimate {
+    /// Convert a local wall-clock time to server wall-clock time.
+    pub fn to_server_ms(&self, local_ms: f64) -> f64 {
+        local_ms + self.offset_ms
+    }
+
+    /// Convert a server wall-clock time to local wall-clock time.
+    pub fn to_local_ms(&self, server_ms: f64) -> f64 {
+        server_ms - self.offset_ms
+    }
+
+    /// Convert a local wall-clock time to server monotonic time.
+    pub fn to_server_monotonic_ms(&self, local_ms: f64) -> f64 {
+        local_ms + self.monotonic_offset_ms
+    }
+}
+
+/// NTP-style clock filter over the most recent [`ClockPong`] samples.
+///
+/// Each sample assumes the reply was sent halfway through the round trip. The
+/// sample with the lowest round trip in the window is the least affected by
+/// queueing delay, so it is used as the estimate. Old samples fall out of the
+/// window, which lets the estimate follow clock drift.
+#[derive(Clone, Debug, Default)]
+pub struct ClockSampler {
+    samples: std::collections::VecDeque<ClockEstimate>,
+    best: Option<ClockEstimate>,
+}
+
+impl ClockSampler {
+    /// Number of samples kept.
+    pub const WINDOW: usize = 8;
+
+    /// Add a reply received at local wall-clock time `received_ms` and return
+    /// the updated estimate.
+    pub fn add_sample(&mut self, pong: &ClockPong, received_ms: f64) -> ClockEstimate {
+        let rtt_ms = (received_ms - pong.client_time_ms).max(0.0);
+        let midpoint = (pong.client_time_ms + received_ms) / 2.0;
+        let sample = ClockEstimate {
+            offset_ms: pong.server_wall_ms - midpoint,
+            monotonic_offset_ms: pong.server_monotonic_ms - midpoint,
+            rtt_ms,
+        };
+
+        if self.samples.len() == Self::WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+
+        let best = self
+            .samples
+            .iter()
+            .copied()
+            .reduce(|a, b| if b.rtt_ms < a.rtt_ms { b } else { a })
+            .unwrap_or(sample);
+        self.best = Some(best);
+        best
+    }
+
+    /// The current estimate, if any sample has been added.
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        self.best
+    }
+
+    /// Forget all samples (e.g. after reconnecting).
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.best = None;
+    }
+}
+
+// ============================================================================
+// Reliable Delivery Types (shared between server and client)
+// ============================================================================
+
+/// Envelope for a message type registered with `.reliable()` on the server.
+///
+/// The sender numbers messages per (`session_id`, `message_type`) starting at
+/// 1 and keeps each one until a [`ReliableAck`] covers it, resending
+/// unacknowledged messages after a reconnect. The receiver delivers
+/// them in sequence order and drops duplicates, so each message is handled
+/// exactly once even if it was sent more than once.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReliableEnvelope {
+    /// Random id chosen by the sender, stable across reconnects.
+    pub session_id: u64,
+    /// Sequence number within (`session_id`, `message_type`).
+    pub sequence: u64,
+    /// Registered name of the wrapped message (`type_name()`, or
+    /// `TargetedMessage::<T>::name()` for targeted messages).
+    pub message_type: String,
+    /// Bincode-encoded message.
+    pub payload: Vec<u8>,
+}
+
+/// Cumulative acknowledgement of [`ReliableEnvelope`]s.
+///
+/// Covers every message of `message_type` in the session up to `sequence`.
+/// Sent once messages have been delivered, and again for every duplicate;
+/// messages buffered until an earlier one arrives are not acknowledged.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReliableAck {
+    pub session_id: u64,
+    pub message_type: String,
+    pub sequence: u64,
+}
+
 // ============================================================================
 // Server Notification Types (shared between server and client)
 // ============================================================================
@@ -256,6 +893,14 @@ pub struct ServerNotification {
     pub level: NotificationLevel,
     /// Optional context (e.g., the message type that was rejected).
     pub context: Option<String>,
+    /// Whether the client must acknowledge this notification with a
+    /// [`NotificationAck`]. Unacknowledged notifications are resent on reconnect.
+    #[serde(default)]
+    pub requires_ack: bool,
+    /// Optional category (e.g., "status", "debug", "alarm") that clients can
+    /// mute with [`NotificationPreferences`].
+    #[serde(default)]
+    pub category: Option<String>,
 }
 
 impl ServerNotification {
@@ -266,6 +911,8 @@ impl ServerNotification {
             message: message.into(),
             level: NotificationLevel::Info,
             context: None,
+            requires_ack: false,
+            category: None,
         }
     }
 
@@ -276,6 +923,8 @@ impl ServerNotification {
             message: message.into(),
             level: NotificationLevel::Success,
             context: None,
+            requires_ack: false,
+            category: None,
         }
     }
 
@@ -286,6 +935,8 @@ impl ServerNotification {
             message: message.into(),
             level: NotificationLevel::Warning,
             context: None,
+            requires_ack: false,
+            category: None,
         }
     }
 
@@ -296,6 +947,8 @@ impl ServerNotification {
             message: message.into(),
             level: NotificationLevel::Error,
             context: None,
+            requires_ack: false,
+            category: None,
         }
     }
 
@@ -304,4 +957,132 @@ impl ServerNotification {
         self.context = Some(context.into());
         self
     }
+
+    /// Require the client to acknowledge this notification.
+    pub fn requiring_ack(mut self) -> Self {
+        self.requires_ack = true;
+        self
+    }
+
+    /// Set the notification category.
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+}
+
+/// Notification preferences reported by a client.
+///
+/// The server skips notifications whose category is muted for that client
+/// instead of delivering them and leaving the client to filter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
+pub struct NotificationPreferences {
+    /// Categories the client does not want to receive.
+    pub muted_categories: Vec<String>,
+}
+
+impl NotificationPreferences {
+    /// Create preferences that mute the given categories.
+    pub fn muting<I, S>(categories: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            muted_categories: categories.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Check whether a notification passes these preferences.
+    ///
+    /// Notifications without a category, and notifications that require an
+    /// acknowledgement, are always allowed.
+    pub fn allows(&self, notification: &ServerNotification) -> bool {
+        if notification.requires_ack {
+            return true;
+        }
+        match &notification.category {
+            Some(category) => !self.muted_categories.iter().any(|c| c == category),
+            None => true,
+        }
+    }
+}
+
+/// Acknowledgement sent by a client after it has shown a notification that
+/// had `requires_ack` set.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
+pub struct NotificationAck {
+    /// Sequence number of the acknowledged [`ServerNotification`].
+    pub sequence: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pong(client_time_ms: f64, server_wall_ms: f64) -> ClockPong {
+        ClockPong {
+            client_time_ms,
+            server_wall_ms,
+            server_monotonic_ms: server_wall_ms - 1_000_000.0,
+        }
+    }
+
+    #[test]
+    fn test_clock_sampler_prefers_lowest_round_trip() {
+        let mut sampler = ClockSampler::default();
+        assert!(sampler.estimate().is_none());
+
+        // Server 500 ms ahead, reply sent halfway through a 40 ms round trip
+        let estimate = sampler.add_sample(&pong(1000.0, 1520.0), 1040.0);
+        assert_eq!(estimate.offset_ms, 500.0);
+        assert_eq!(estimate.rtt_ms, 40.0);
+        assert_eq!(estimate.monotonic_offset_ms, 500.0 - 1_000_000.0);
+        assert_eq!(estimate.to_local_ms(estimate.to_server_ms(1234.0)), 1234.0);
+
+        // A slower, skewed sample doesn't replace the better one
+        let estimate = sampler.add_sample(&pong(2000.0, 2700.0), 2200.0);
+        assert_eq!(estimate.offset_ms, 500.0);
+
+        // Once the good sample leaves the window, the best remaining one wins
+        for i in 0..ClockSampler::WINDOW {
+            let sent = 3000.0 + i as f64 * 100.0;
+            sampler.add_sample(&pong(sent, sent + 30.0 + 510.0), sent + 60.0);
+        }
+        let estimate = sampler.estimate().unwrap();
+        assert_eq!(estimate.offset_ms, 510.0);
+        assert_eq!(estimate.rtt_ms, 60.0);
+
+        sampler.reset();
+        assert!(sampler.estimate().is_none());
+    }
+
+    #[test]
+    fn test_entity_control_compares_identities() {
+        let identity = |session, id| ConnectionIdentity {
+            session,
+            connection: ConnectionId { id },
+        };
+        let control = EntityControl {
+            client: identity(7, 1),
+            sub_connection_ids: vec![ConnectionId { id: 2 }],
+            last_activity: 0.0,
+        };
+        assert!(control.has_control(identity(7, 1)));
+        assert!(control.has_control(identity(7, 2)));
+        assert!(control.held_by(identity(7, 1)));
+        assert!(!control.held_by(identity(7, 2)));
+
+        // The same ids from another server run are different connections
+        assert!(!control.has_control(identity(8, 1)));
+        assert!(!control.has_control(identity(8, 2)));
+        assert!(!control.held_by(identity(8, 1)));
+
+        let uncontrolled = EntityControl::default();
+        assert_eq!(uncontrolled.holder(), None);
+        assert!(!uncontrolled.has_control(ConnectionIdentity::NONE));
+        assert!(!uncontrolled.held_by(ConnectionIdentity::NONE));
+    }
 }
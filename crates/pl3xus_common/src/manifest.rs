@@ -0,0 +1,201 @@
+//! Shared type manifests.
+//!
+//! A [`type_manifest!`](crate::type_manifest) declares every component,
+//! message and request type of an application once, in the crate shared by the
+//! server and the client. Each side then registers the whole manifest instead
+//! of keeping its own `register::<T>()` list in sync by hand:
+//!
+//! ```rust,ignore
+//! // shared crate
+//! pl3xus_common::type_manifest! {
+//!     pub struct RobotTypes {
+//!         components: [RobotPosition, RobotStatus],
+//!         messages: [JogCommand],
+//!         requests: [ListPrograms],
+//!     }
+//! }
+//!
+//! // server
+//! app.register_manifest::<RobotTypes, WebSocketProvider>();
+//!
+//! // client
+//! let registry = ClientTypeRegistry::builder()
+//!     .register_manifest::<RobotTypes>()
+//!     .build();
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+use crate::{Pl3xusMessage, RequestMessage};
+
+/// A component type that can be listed in a manifest.
+///
+/// With the `ecs` feature (enabled by the server runtime) it must also be a
+/// Bevy `Component`, so shared crates derive `Component` behind their server
+/// feature as usual.
+#[cfg(feature = "ecs")]
+pub trait ManifestComponent:
+    bevy::prelude::Component + Serialize + DeserializeOwned + Clone + Debug + Send + Sync + 'static
+{
+}
+
+#[cfg(feature = "ecs")]
+impl<T> ManifestComponent for T where
+    T: bevy::prelude::Component + Serialize + DeserializeOwned + Clone + Debug + Send + Sync + 'static
+{
+}
+
+/// A component type that can be listed in a manifest.
+#[cfg(not(feature = "ecs"))]
+pub trait ManifestComponent: Serialize + DeserializeOwned + Clone + Debug + Send + Sync + 'static {}
+
+#[cfg(not(feature = "ecs"))]
+impl<T> ManifestComponent for T where T: Serialize + DeserializeOwned + Clone + Debug + Send + Sync + 'static {}
+
+/// Receives each type listed in a [`TypeManifest`].
+///
+/// Implemented by the server and client registration helpers.
+pub trait TypeVisitor {
+    fn component<T: ManifestComponent>(&mut self);
+    fn message<T: Pl3xusMessage + Clone>(&mut self);
+    fn request<T: RequestMessage>(&mut self);
+}
+
+/// A declarative list of an application's types, generated by
+/// [`type_manifest!`](crate::type_manifest).
+pub trait TypeManifest {
+    /// Visit every listed type, in declaration order.
+    fn visit<V: TypeVisitor>(visitor: &mut V);
+}
+
+/// Declare a [`TypeManifest`] from lists of component, message and request types.
+///
+/// Types are listed by name, so import them first. Each list is optional, but
+/// they must appear in the order `components`, `messages`, `requests`. Listing
+/// a type twice in the same list is a compile error:
+///
+/// ```compile_fail
+/// # use serde::{Deserialize, Serialize};
+/// # #[derive(Serialize, Deserialize, Clone, Debug)]
+/// # struct Battery { level: f32 }
+/// pl3xus_common::type_manifest! {
+///     struct Types {
+///         components: [Battery, Battery],
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! type_manifest {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(components: [$($component:ident),* $(,)?] $(,)?)?
+            $(messages: [$($message:ident),* $(,)?] $(,)?)?
+            $(requests: [$($request:ident),* $(,)?] $(,)?)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default)]
+        $vis struct $name;
+
+        impl $crate::manifest::TypeManifest for $name {
+            fn visit<V: $crate::manifest::TypeVisitor>(visitor: &mut V) {
+                $($(visitor.component::<$component>();)*)?
+                $($(visitor.message::<$message>();)*)?
+                $($(visitor.request::<$request>();)*)?
+            }
+        }
+
+        // Duplicate names in a list become duplicate variants, which rustc
+        // rejects with "the name `X` is defined multiple times".
+        #[allow(non_camel_case_types, dead_code)]
+        const _: () = {
+            enum Components { $($($component,)*)? }
+            enum Messages { $($($message,)*)? }
+            enum Requests { $($($request,)*)? }
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[cfg_attr(feature = "ecs", derive(bevy::prelude::Component))]
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Battery {
+        level: f32,
+    }
+
+    #[cfg_attr(feature = "ecs", derive(bevy::prelude::Component))]
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Pose {
+        x: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Jog {
+        axis: u8,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct ListPrograms;
+
+    impl RequestMessage for ListPrograms {
+        type ResponseMessage = Vec<String>;
+    }
+
+    crate::type_manifest! {
+        /// Test manifest.
+        struct Types {
+            components: [Battery, Pose],
+            messages: [Jog],
+            requests: [ListPrograms],
+        }
+    }
+
+    crate::type_manifest! {
+        struct RequestsOnly {
+            requests: [ListPrograms]
+        }
+    }
+
+    #[derive(Default)]
+    struct Names(Vec<String>);
+
+    impl TypeVisitor for Names {
+        fn component<T: ManifestComponent>(&mut self) {
+            self.0.push(format!("component {}", T::short_name()));
+        }
+
+        fn message<T: Pl3xusMessage + Clone>(&mut self) {
+            self.0.push(format!("message {}", T::short_name()));
+        }
+
+        fn request<T: RequestMessage>(&mut self) {
+            self.0.push(format!("request {}", T::request_name()));
+        }
+    }
+
+    #[test]
+    fn test_manifest_visits_in_order() {
+        let mut names = Names::default();
+        Types::visit(&mut names);
+        assert_eq!(
+            names.0,
+            vec![
+                "component Battery",
+                "component Pose",
+                "message Jog",
+                "request ListPrograms",
+            ]
+        );
+
+        let mut names = Names::default();
+        RequestsOnly::visit(&mut names);
+        assert_eq!(names.0, vec!["request ListPrograms"]);
+    }
+}
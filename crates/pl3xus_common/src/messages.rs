@@ -190,6 +190,20 @@ pub trait RequestMessage: Pl3xusMessage + Clone + Debug {
     }
 }
 
+/// Idempotency key attached to a request.
+///
+/// `client` is a random id the sender picks once (per page load or process)
+/// and `key` is random per logical operation. The server only treats a
+/// request as a retry if it has the same request type and both ids, so one
+/// client can't replay or collide with another client's keys.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey {
+    /// Random id of the sending client, the same for all of its requests.
+    pub client: u64,
+    /// Random id of the operation, the same across its retries.
+    pub key: u64,
+}
+
 /// Trait for request types whose response can be constructed as an error.
 ///
 /// This is used by authorization middleware to send error responses when
@@ -259,6 +273,45 @@ pub trait HasSuccess {
     fn is_success(&self) -> bool;
 }
 
+/// Trait for queries whose result is a list of rows that clients can follow live.
+///
+/// A client subscribes to the query once; the server keeps it registered and
+/// pushes the rows that were added, updated or removed whenever its data
+/// changes, instead of the client refetching the whole result.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+/// struct ListPrograms;
+///
+/// #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+/// struct ProgramInfo { id: i64, name: String }
+///
+/// impl LiveQuery for ListPrograms {
+///     type Row = ProgramInfo;
+///
+///     fn row_key(row: &ProgramInfo) -> String {
+///         row.id.to_string()
+///     }
+/// }
+/// ```
+pub trait LiveQuery: Pl3xusMessage + Clone + Debug {
+    /// One row of the result.
+    type Row: Serialize + DeserializeOwned + Clone + PartialEq + Debug + Send + Sync + 'static;
+
+    /// Identifies `row` across updates, e.g. its database id.
+    fn row_key(row: &Self::Row) -> String;
+}
+
+/// Wire envelope for a message directed at one entity.
+///
+/// The target travels next to the payload, so message types don't need their
+/// own entity field, and the server extracts it the same way for every
+/// targeted message before authorization. `target_id` holds the entity's bits
+/// as a decimal string (what the TypeScript and Python clients send too);
+/// build and read it with [`TargetedMessage::new`] and
+/// [`TargetedMessage::entity_bits`].
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(bound = "T: Pl3xusMessage")]
 pub struct TargetedMessage<T: Pl3xusMessage> {
@@ -266,7 +319,37 @@ pub struct TargetedMessage<T: Pl3xusMessage> {
     pub message: T,
 }
 
+/// The entity bits in a `target_id`, or `None` if it isn't a number.
+pub fn parse_target_id(target_id: &str) -> Option<u64> {
+    target_id.trim().parse().ok()
+}
+
+/// The entity in a `target_id`, or `None` if it isn't valid entity bits.
+#[cfg(feature = "ecs")]
+pub fn parse_target_entity(target_id: &str) -> Option<bevy::prelude::Entity> {
+    bevy::prelude::Entity::try_from_bits(parse_target_id(target_id)?)
+}
+
 impl<T: Pl3xusMessage> TargetedMessage<T> {
+    /// Direct `message` at the entity with `entity_bits`.
+    pub fn new(entity_bits: u64, message: T) -> Self {
+        Self {
+            target_id: entity_bits.to_string(),
+            message,
+        }
+    }
+
+    /// The target's entity bits, or `None` if `target_id` isn't a number.
+    pub fn entity_bits(&self) -> Option<u64> {
+        parse_target_id(&self.target_id)
+    }
+
+    /// The target entity, or `None` if `target_id` isn't valid entity bits.
+    #[cfg(feature = "ecs")]
+    pub fn target_entity(&self) -> Option<bevy::prelude::Entity> {
+        parse_target_entity(&self.target_id)
+    }
+
     pub fn name() -> &'static str {
         // Use a global cache with lazy initialization
         use std::any::TypeId;
@@ -616,4 +699,25 @@ mod tests {
         assert!(ListRobots::type_name().contains("ListRobots"));
         assert_eq!(ListRobots::short_name(), "ListRobots");
     }
+
+    #[test]
+    fn test_targeted_message_entity() {
+        #[derive(Serialize, Deserialize)]
+        struct Move {
+            x: f32,
+        }
+
+        let targeted = TargetedMessage::new(1 << 32 | 7, Move { x: 1.0 });
+        assert_eq!(targeted.target_id, "4294967303");
+        assert_eq!(targeted.entity_bits(), Some(1 << 32 | 7));
+        assert_eq!(parse_target_id("robot"), None);
+
+        #[cfg(feature = "ecs")]
+        {
+            use bevy::prelude::Entity;
+            assert_eq!(targeted.target_entity().map(Entity::to_bits), Some(1 << 32 | 7));
+            // Not a valid entity: the row bits can't be zero
+            assert_eq!(parse_target_entity("0"), None);
+        }
+    }
 }
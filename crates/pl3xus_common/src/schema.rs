@@ -0,0 +1,659 @@
+//! Runtime schema introspection.
+//!
+//! [`describe_type`] derives a [`TypeShape`] (field names and types) from a
+//! type's serde `Deserialize` impl, so a server can describe its registered
+//! components, messages and requests and a client can compare them against its
+//! own registrations. The shape is traced by driving the `Deserialize` impl with
+//! a deserializer that records every call it receives; no extra derive is
+//! needed on the type.
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::RequestMessage;
+
+/// How deep nested types are traced before giving up with [`TypeShape::Any`].
+///
+/// Bounds recursive types such as `struct Node { children: Vec<Node> }`.
+const MAX_DEPTH: usize = 16;
+
+/// The serde data model shape of a type.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum TypeShape {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    String,
+    Bytes,
+    Unit,
+    Option(Box<TypeShape>),
+    Seq(Box<TypeShape>),
+    Tuple(Vec<TypeShape>),
+    Map {
+        key: Box<TypeShape>,
+        value: Box<TypeShape>,
+    },
+    /// A struct. Tuple structs use `"0"`, `"1"`, ... as field names.
+    Struct {
+        name: String,
+        fields: Vec<FieldShape>,
+    },
+    Newtype {
+        name: String,
+        inner: Box<TypeShape>,
+    },
+    /// An enum, by variant name. Variant payloads are not traced.
+    Enum {
+        name: String,
+        variants: Vec<String>,
+    },
+    /// Self-describing (e.g. `serde_json::Value`) or nested too deeply to trace.
+    Any,
+}
+
+/// One field of a [`TypeShape::Struct`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldShape {
+    pub name: String,
+    pub shape: TypeShape,
+}
+
+impl fmt::Display for TypeShape {
+    /// Rust-like rendering, e.g. `Option<Vec<f64>>` or `RobotPosition { x: f64, y: f64 }`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool => f.write_str("bool"),
+            Self::I8 => f.write_str("i8"),
+            Self::I16 => f.write_str("i16"),
+            Self::I32 => f.write_str("i32"),
+            Self::I64 => f.write_str("i64"),
+            Self::I128 => f.write_str("i128"),
+            Self::U8 => f.write_str("u8"),
+            Self::U16 => f.write_str("u16"),
+            Self::U32 => f.write_str("u32"),
+            Self::U64 => f.write_str("u64"),
+            Self::U128 => f.write_str("u128"),
+            Self::F32 => f.write_str("f32"),
+            Self::F64 => f.write_str("f64"),
+            Self::Char => f.write_str("char"),
+            Self::String => f.write_str("String"),
+            Self::Bytes => f.write_str("Vec<u8>"),
+            Self::Unit => f.write_str("()"),
+            Self::Option(inner) => write!(f, "Option<{}>", inner),
+            Self::Seq(inner) => write!(f, "Vec<{}>", inner),
+            Self::Tuple(items) => {
+                f.write_str("(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str(")")
+            }
+            Self::Map { key, value } => write!(f, "Map<{}, {}>", key, value),
+            Self::Struct { name, fields } => {
+                write!(f, "{} {{", name)?;
+                for (i, field) in fields.iter().enumerate() {
+                    f.write_str(if i > 0 { ", " } else { " " })?;
+                    write!(f, "{}: {}", field.name, field.shape)?;
+                }
+                f.write_str(if fields.is_empty() { "}" } else { " }" })
+            }
+            Self::Newtype { name, inner } => write!(f, "{}({})", name, inner),
+            Self::Enum { name, variants } => write!(f, "{} {{ {} }}", name, variants.join(" | ")),
+            Self::Any => f.write_str("any"),
+        }
+    }
+}
+
+/// Trace the shape of `T` from its `Deserialize` impl.
+///
+/// Types whose `Deserialize` impl rejects placeholder values (e.g. validating
+/// newtypes) are described up to the point where they failed, with
+/// [`TypeShape::Any`] for whatever couldn't be reached.
+pub fn describe_type<T: DeserializeOwned>() -> TypeShape {
+    let mut shape = TypeShape::Any;
+    let _ = T::deserialize(Tracer {
+        out: &mut shape,
+        depth: 0,
+    });
+    shape
+}
+
+// ============================================================================
+// DescribeSchema request
+// ============================================================================
+
+/// A registered component or message type.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TypeSchema {
+    /// Short type name, as used in sync messages (e.g. `RobotPosition`).
+    pub name: String,
+    /// Full type path.
+    pub type_name: String,
+    pub shape: TypeShape,
+}
+
+impl TypeSchema {
+    pub fn of<T: DeserializeOwned + 'static>(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            type_name: std::any::type_name::<T>().to_string(),
+            shape: describe_type::<T>(),
+        }
+    }
+}
+
+/// A registered request type and its response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RequestSchema {
+    /// Request name (`RequestMessage::request_name`).
+    pub name: String,
+    /// Full type path of the request.
+    pub type_name: String,
+    pub request: TypeShape,
+    pub response: TypeShape,
+}
+
+impl RequestSchema {
+    pub fn of<R: RequestMessage>() -> Self {
+        Self {
+            name: R::request_name().to_string(),
+            type_name: R::type_name().to_string(),
+            request: describe_type::<R>(),
+            response: describe_type::<R::ResponseMessage>(),
+        }
+    }
+}
+
+/// Ask the server for the types it has registered.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DescribeSchema;
+
+/// Response to [`DescribeSchema`], sorted by name.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SchemaDescription {
+    pub components: Vec<TypeSchema>,
+    pub messages: Vec<TypeSchema>,
+    pub requests: Vec<RequestSchema>,
+}
+
+impl RequestMessage for DescribeSchema {
+    type ResponseMessage = SchemaDescription;
+}
+
+// ============================================================================
+// Tracing deserializer
+// ============================================================================
+
+#[derive(Debug)]
+struct TraceError(String);
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl de::Error for TraceError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        TraceError(msg.to_string())
+    }
+}
+
+/// Records the shape requested by the visitor into `out`, answering with a
+/// placeholder value so tracing can continue.
+struct Tracer<'a> {
+    out: &'a mut TypeShape,
+    depth: usize,
+}
+
+impl Tracer<'_> {
+    fn child(depth: usize, out: &mut TypeShape) -> Tracer<'_> {
+        Tracer { out, depth: depth + 1 }
+    }
+
+    /// Elements traced for a sequence of unknown length: one, unless too deep.
+    fn seq_len(&self) -> usize {
+        if self.depth < MAX_DEPTH { 1 } else { 0 }
+    }
+}
+
+macro_rules! trace_primitive {
+    ($($method:ident => $shape:ident, $visit:ident($value:expr)),+ $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+                *self.out = TypeShape::$shape;
+                visitor.$visit($value)
+            }
+        )+
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = TraceError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        *self.out = TypeShape::Any;
+        visitor.visit_unit()
+    }
+
+    trace_primitive! {
+        deserialize_bool => Bool, visit_bool(false),
+        deserialize_i8 => I8, visit_i8(0),
+        deserialize_i16 => I16, visit_i16(0),
+        deserialize_i32 => I32, visit_i32(0),
+        deserialize_i64 => I64, visit_i64(0),
+        deserialize_i128 => I128, visit_i128(0),
+        deserialize_u8 => U8, visit_u8(0),
+        deserialize_u16 => U16, visit_u16(0),
+        deserialize_u32 => U32, visit_u32(0),
+        deserialize_u64 => U64, visit_u64(0),
+        deserialize_u128 => U128, visit_u128(0),
+        deserialize_f32 => F32, visit_f32(0.0),
+        deserialize_f64 => F64, visit_f64(0.0),
+        deserialize_char => Char, visit_char(' '),
+        deserialize_str => String, visit_str(""),
+        deserialize_string => String, visit_string(String::new()),
+        deserialize_bytes => Bytes, visit_bytes(&[]),
+        deserialize_byte_buf => Bytes, visit_byte_buf(Vec::new()),
+        deserialize_identifier => String, visit_str(""),
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        *self.out = TypeShape::Unit;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        let mut inner = TypeShape::Any;
+        let result = if self.depth < MAX_DEPTH {
+            visitor.visit_some(Tracer::child(self.depth, &mut inner))
+        } else {
+            visitor.visit_none()
+        };
+        *self.out = TypeShape::Option(Box::new(inner));
+        result
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, TraceError> {
+        *self.out = TypeShape::Struct {
+            name: name.to_string(),
+            fields: Vec::new(),
+        };
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        let mut inner = TypeShape::Any;
+        let result = visitor.visit_newtype_struct(Tracer::child(self.depth, &mut inner));
+        *self.out = TypeShape::Newtype {
+            name: name.to_string(),
+            inner: Box::new(inner),
+        };
+        result
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        let mut items = Vec::new();
+        let result = visitor.visit_seq(TraceSeq {
+            items: &mut items,
+            remaining: self.seq_len(),
+            depth: self.depth,
+        });
+        *self.out = TypeShape::Seq(Box::new(items.pop().unwrap_or(TypeShape::Any)));
+        result
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, TraceError> {
+        let mut items = Vec::new();
+        let result = visitor.visit_seq(TraceSeq {
+            items: &mut items,
+            remaining: len,
+            depth: self.depth,
+        });
+        *self.out = TypeShape::Tuple(items);
+        result
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        let mut items = Vec::new();
+        let result = visitor.visit_seq(TraceSeq {
+            items: &mut items,
+            remaining: len,
+            depth: self.depth,
+        });
+        *self.out = TypeShape::Struct {
+            name: name.to_string(),
+            fields: items
+                .into_iter()
+                .enumerate()
+                .map(|(i, shape)| FieldShape {
+                    name: i.to_string(),
+                    shape,
+                })
+                .collect(),
+        };
+        result
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        let mut key = TypeShape::Any;
+        let mut value = TypeShape::Any;
+        let result = visitor.visit_map(TraceMap {
+            key: &mut key,
+            value: &mut value,
+            remaining: self.seq_len(),
+            depth: self.depth,
+        });
+        *self.out = TypeShape::Map {
+            key: Box::new(key),
+            value: Box::new(value),
+        };
+        result
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        let mut traced = Vec::new();
+        let result = visitor.visit_map(TraceFields {
+            names: fields,
+            fields: &mut traced,
+            depth: self.depth,
+        });
+        *self.out = TypeShape::Struct {
+            name: name.to_string(),
+            fields: traced,
+        };
+        result
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        *self.out = TypeShape::Enum {
+            name: name.to_string(),
+            variants: variants.iter().map(|v| v.to_string()).collect(),
+        };
+        let variant = variants
+            .first()
+            .ok_or_else(|| TraceError("enum has no variants".to_string()))?;
+        visitor.visit_enum(TraceVariant {
+            variant,
+            depth: self.depth,
+        })
+    }
+}
+
+struct TraceSeq<'a> {
+    items: &'a mut Vec<TypeShape>,
+    remaining: usize,
+    depth: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for TraceSeq<'_> {
+    type Error = TraceError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, TraceError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let mut shape = TypeShape::Any;
+        let result = seed.deserialize(Tracer::child(self.depth, &mut shape));
+        self.items.push(shape);
+        result.map(Some)
+    }
+}
+
+struct TraceMap<'a> {
+    key: &'a mut TypeShape,
+    value: &'a mut TypeShape,
+    remaining: usize,
+    depth: usize,
+}
+
+impl<'de> de::MapAccess<'de> for TraceMap<'_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, TraceError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(Tracer::child(self.depth, self.key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, TraceError> {
+        seed.deserialize(Tracer::child(self.depth, self.value))
+    }
+}
+
+/// Every declared struct field, each traced in turn.
+struct TraceFields<'a> {
+    names: &'static [&'static str],
+    fields: &'a mut Vec<FieldShape>,
+    depth: usize,
+}
+
+impl<'de> de::MapAccess<'de> for TraceFields<'_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, TraceError> {
+        let Some(name) = self.names.get(self.fields.len()) else {
+            return Ok(None);
+        };
+        // Placeholder until the value is traced
+        self.fields.push(FieldShape {
+            name: name.to_string(),
+            shape: TypeShape::Any,
+        });
+        seed.deserialize(name.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, TraceError> {
+        let field = self
+            .fields
+            .last_mut()
+            .ok_or_else(|| TraceError("value before key".to_string()))?;
+        seed.deserialize(Tracer::child(self.depth, &mut field.shape))
+    }
+}
+
+/// Selects the first variant; its payload is traced into a discarded shape.
+struct TraceVariant {
+    variant: &'static str,
+    depth: usize,
+}
+
+impl<'de> de::EnumAccess<'de> for TraceVariant {
+    type Error = TraceError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), TraceError> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for TraceVariant {
+    type Error = TraceError;
+
+    fn unit_variant(self) -> Result<(), TraceError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, TraceError> {
+        seed.deserialize(Tracer::child(self.depth, &mut TypeShape::Any))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, TraceError> {
+        visitor.visit_seq(TraceSeq {
+            items: &mut Vec::new(),
+            remaining: len,
+            depth: self.depth,
+        })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        visitor.visit_map(TraceFields {
+            names: fields,
+            fields: &mut Vec::new(),
+            depth: self.depth,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Serialize, Deserialize)]
+    #[allow(dead_code)]
+    enum Frame {
+        World,
+        User(u8),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[allow(dead_code)]
+    struct Meters(f64);
+
+    #[derive(Serialize, Deserialize)]
+    #[allow(dead_code)]
+    struct Node {
+        label: String,
+        children: Vec<Node>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[allow(dead_code)]
+    struct MoveTo {
+        x: Meters,
+        speed: Option<u32>,
+        frame: Frame,
+        waypoints: Vec<(f32, f32)>,
+        tags: HashMap<String, bool>,
+    }
+
+    fn field(name: &str, shape: TypeShape) -> FieldShape {
+        FieldShape {
+            name: name.to_string(),
+            shape,
+        }
+    }
+
+    #[test]
+    fn test_describe_struct() {
+        let shape = describe_type::<MoveTo>();
+        assert_eq!(
+            shape,
+            TypeShape::Struct {
+                name: "MoveTo".to_string(),
+                fields: vec![
+                    field(
+                        "x",
+                        TypeShape::Newtype {
+                            name: "Meters".to_string(),
+                            inner: Box::new(TypeShape::F64),
+                        }
+                    ),
+                    field("speed", TypeShape::Option(Box::new(TypeShape::U32))),
+                    field(
+                        "frame",
+                        TypeShape::Enum {
+                            name: "Frame".to_string(),
+                            variants: vec!["World".to_string(), "User".to_string()],
+                        }
+                    ),
+                    field(
+                        "waypoints",
+                        TypeShape::Seq(Box::new(TypeShape::Tuple(vec![TypeShape::F32, TypeShape::F32])))
+                    ),
+                    field(
+                        "tags",
+                        TypeShape::Map {
+                            key: Box::new(TypeShape::String),
+                            value: Box::new(TypeShape::Bool),
+                        }
+                    ),
+                ],
+            }
+        );
+        assert_eq!(
+            shape.to_string(),
+            "MoveTo { x: Meters(f64), speed: Option<u32>, frame: Frame { World | User }, \
+             waypoints: Vec<(f32, f32)>, tags: Map<String, bool> }"
+        );
+    }
+
+    #[test]
+    fn test_describe_recursive_type_terminates() {
+        let TypeShape::Struct { fields, .. } = describe_type::<Node>() else {
+            panic!("expected a struct");
+        };
+        assert_eq!(fields[0], field("label", TypeShape::String));
+        assert!(matches!(fields[1].shape, TypeShape::Seq(_)));
+    }
+
+    #[test]
+    fn test_schema_description_roundtrip() {
+        let description = SchemaDescription {
+            components: vec![TypeSchema::of::<MoveTo>("MoveTo")],
+            messages: Vec::new(),
+            requests: vec![RequestSchema::of::<DescribeSchema>()],
+        };
+        let bytes = bincode::serde::encode_to_vec(&description, bincode::config::standard()).unwrap();
+        let (decoded, _): (SchemaDescription, usize) =
+            bincode::serde::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, description);
+        assert_eq!(description.requests[0].name, "DescribeSchema");
+        assert_eq!(
+            description.requests[0].request,
+            TypeShape::Struct {
+                name: "DescribeSchema".to_string(),
+                fields: Vec::new(),
+            }
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Client requests to spawn and despawn entities of server-defined archetypes.
+//!
+//! The server registers named archetypes (a bundle of components with default
+//! values, plus who may spawn it). Clients send a [`SpawnEntityRequest`]
+//! naming the archetype, optionally overriding some of its synced components,
+//! and get the new entity back in the [`SpawnEntityResponse`]. Archetypes the
+//! server marks as despawnable can be removed again with a
+//! [`DespawnEntityRequest`].
+//!
+//! ```rust,ignore
+//! let request = SpawnEntityRequest::new("robot")
+//!     .with_override("RobotName", &RobotName("Cell 4".into()))?;
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::RequestMessage;
+
+/// Spawn an entity from a server-defined archetype.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpawnEntityRequest {
+    /// Name the archetype was registered under.
+    pub archetype: String,
+    /// Initial values replacing the archetype's defaults.
+    pub overrides: Vec<ComponentOverride>,
+}
+
+/// Initial value of one synced component of a spawned entity.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ComponentOverride {
+    /// Short type name of the component, as used by subscriptions.
+    pub component_type: String,
+    /// Bincode-encoded component value.
+    pub value: Vec<u8>,
+}
+
+impl SpawnEntityRequest {
+    /// Request the archetype's defaults.
+    pub fn new(archetype: impl Into<String>) -> Self {
+        Self {
+            archetype: archetype.into(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Replace the archetype's default value of `component_type`.
+    pub fn with_override<T: Serialize>(
+        mut self,
+        component_type: impl Into<String>,
+        value: &T,
+    ) -> Result<Self, bincode::error::EncodeError> {
+        self.overrides.push(ComponentOverride {
+            component_type: component_type.into(),
+            value: bincode::serde::encode_to_vec(value, bincode::config::standard())?,
+        });
+        Ok(self)
+    }
+}
+
+/// Response to [`SpawnEntityRequest`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpawnEntityResponse {
+    /// Bits of the spawned entity.
+    pub entity: Option<u64>,
+    /// Why nothing was spawned.
+    pub error: Option<String>,
+}
+
+impl RequestMessage for SpawnEntityRequest {
+    type ResponseMessage = SpawnEntityResponse;
+}
+
+/// Despawn an entity spawned from an archetype, with its children.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DespawnEntityRequest {
+    /// Bits of the entity.
+    pub entity: u64,
+}
+
+/// Response to [`DespawnEntityRequest`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DespawnEntityResponse {
+    /// Bits of every despawned entity: the requested one and its descendants.
+    pub despawned: Vec<u64>,
+    /// Why nothing was despawned.
+    pub error: Option<String>,
+}
+
+impl RequestMessage for DespawnEntityRequest {
+    type ResponseMessage = DespawnEntityResponse;
+}
@@ -0,0 +1,41 @@
+//! # Pl3xus DevTools (egui)
+//!
+//! A native counterpart of the Leptos DevTools widget in `pl3xus_client`: an
+//! entity/component inspector with live editing, drawn with egui inside any
+//! Bevy app.
+//!
+//! - **Client mode** subscribes to every synced component over an existing
+//!   pl3xus connection and edits through `SyncClientMessage::Mutate`, the same
+//!   wire messages the web DevTools use, so server authorization applies.
+//! - **Local mode** inspects the server's own world by reading the sync
+//!   registry directly; edits are applied without going through the network.
+//!
+//! Component values travel as bincode, so each inspected type is registered
+//! with [`DevtoolsAppExt::devtools_component`] to convert it to and from JSON.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use bevy_egui::EguiPlugin;
+//! use pl3xus_devtools_egui::{DevtoolsAppExt, DevtoolsEguiPlugin};
+//!
+//! // Native client connected to a pl3xus server
+//! app.add_plugins(EguiPlugin::default())
+//!     .add_plugins(DevtoolsEguiPlugin::<WebSocketProvider>::client())
+//!     .devtools_component::<RobotPosition>()
+//!     .devtools_component::<JogSettings>();
+//!
+//! // Or, on the server itself
+//! app.add_plugins(DevtoolsEguiPlugin::<WebSocketProvider>::local());
+//! ```
+//!
+//! The panel is toggled with F12 (see [`DevtoolsEguiPlugin::with_toggle_key`]).
+
+mod plugin;
+mod registry;
+mod state;
+mod ui;
+
+pub use plugin::{DevtoolsEguiPlugin, DevtoolsMode};
+pub use registry::{DevtoolsAppExt, DevtoolsTypeRegistry};
+pub use state::{DevtoolsMutation, DevtoolsState};
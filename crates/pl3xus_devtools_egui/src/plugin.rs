@@ -0,0 +1,281 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy_egui::EguiPrimaryContextPass;
+use pl3xus::managers::NetworkProvider;
+use pl3xus::{AppNetworkMessage, ConnectionId, Network, NetworkData, NetworkEvent, Pl3xusMessage};
+use pl3xus_sync::{
+    MutateComponent, MutationOrigin, MutationStatus, QueuedMutation, SubscriptionRequest, SyncClientMessage,
+    SyncItem, SyncRegistry, SyncServerMessage,
+};
+
+use crate::registry::DevtoolsTypeRegistry;
+use crate::state::DevtoolsState;
+use crate::ui::devtools_ui;
+
+/// Subscription id used for the devtools wildcard subscription.
+///
+/// Chosen from the top of the range so it does not collide with ids picked by
+/// the client's own subscriptions.
+const DEVTOOLS_SUBSCRIPTION_ID: u64 = u64::MAX - 0xDE;
+
+/// Where the devtools get their data from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevtoolsMode {
+    /// Subscribe over the pl3xus connection of a native client.
+    Client,
+    /// Read the local world through the sync registry (server-side).
+    Local,
+}
+
+#[derive(Resource, Clone, Copy)]
+struct DevtoolsConfig {
+    toggle_key: KeyCode,
+}
+
+/// Adds the egui devtools panel.
+///
+/// Requires `bevy_egui::EguiPlugin`. In [`DevtoolsMode::Client`] the app must
+/// also have a `Pl3xusPlugin` for `NP`; in [`DevtoolsMode::Local`] it must have
+/// a `Pl3xusSyncPlugin`.
+pub struct DevtoolsEguiPlugin<NP: NetworkProvider> {
+    mode: DevtoolsMode,
+    toggle_key: KeyCode,
+    refresh_interval: Duration,
+    _marker: PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> DevtoolsEguiPlugin<NP> {
+    /// Inspect the server this client is connected to.
+    pub fn client() -> Self {
+        Self::new(DevtoolsMode::Client)
+    }
+
+    /// Inspect this app's own world.
+    pub fn local() -> Self {
+        Self::new(DevtoolsMode::Local)
+    }
+
+    fn new(mode: DevtoolsMode) -> Self {
+        Self {
+            mode,
+            toggle_key: KeyCode::F12,
+            refresh_interval: Duration::from_millis(250),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Key that shows and hides the panel (default F12).
+    pub fn with_toggle_key(mut self, key: KeyCode) -> Self {
+        self.toggle_key = key;
+        self
+    }
+
+    /// How often local mode re-reads the world (default 250ms).
+    ///
+    /// Client mode is push-based and ignores this.
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for DevtoolsEguiPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<DevtoolsTypeRegistry>() {
+            app.init_resource::<DevtoolsTypeRegistry>();
+        }
+        app.init_resource::<DevtoolsState>()
+            .insert_resource(DevtoolsConfig {
+                toggle_key: self.toggle_key,
+            })
+            .add_systems(Update, toggle_devtools)
+            .add_systems(EguiPrimaryContextPass, devtools_ui);
+
+        match self.mode {
+            DevtoolsMode::Client => {
+                let registered = app
+                    .world()
+                    .get_resource::<Network<NP>>()
+                    .is_some_and(|net| net.is_message_registered(SyncServerMessage::type_name()));
+                if !registered {
+                    app.register_network_message::<SyncServerMessage, NP>();
+                }
+                app.add_systems(
+                    Update,
+                    (
+                        handle_connection_events::<NP>,
+                        handle_server_messages,
+                        send_mutations::<NP>,
+                    )
+                        .chain(),
+                );
+            }
+            DevtoolsMode::Local => {
+                app.add_systems(
+                    Update,
+                    (
+                        apply_local_mutations,
+                        refresh_local_state.run_if(on_timer(self.refresh_interval)),
+                    )
+                        .chain(),
+                );
+            }
+        }
+    }
+}
+
+fn toggle_devtools(keys: Res<ButtonInput<KeyCode>>, config: Res<DevtoolsConfig>, mut state: ResMut<DevtoolsState>) {
+    if keys.just_pressed(config.toggle_key) {
+        state.open = !state.open;
+    }
+}
+
+/// Subscribe to everything on connect; forget it all on disconnect.
+fn handle_connection_events<NP: NetworkProvider>(
+    mut events: MessageReader<NetworkEvent>,
+    net: Res<Network<NP>>,
+    mut state: ResMut<DevtoolsState>,
+) {
+    for event in events.read() {
+        match event {
+            NetworkEvent::Connected(connection_id, _) => {
+                state.connection = Some(*connection_id);
+                let subscription = SyncClientMessage::Subscription(SubscriptionRequest {
+                    subscription_id: DEVTOOLS_SUBSCRIPTION_ID,
+                    component_type: "*".to_string(),
+                    entity: None,
+                    filter: None,
+                });
+                if let Err(e) = net.send(*connection_id, subscription) {
+                    warn!("[DevTools] Failed to subscribe: {}", e);
+                }
+            }
+            NetworkEvent::Disconnected(connection_id) => {
+                if state.connection == Some(*connection_id) {
+                    state.connection = None;
+                    state.clear();
+                }
+            }
+            NetworkEvent::Error(_) => {}
+        }
+    }
+}
+
+fn handle_server_messages(
+    mut messages: MessageReader<NetworkData<SyncServerMessage>>,
+    registry: Res<DevtoolsTypeRegistry>,
+    mut state: ResMut<DevtoolsState>,
+) {
+    for message in messages.read() {
+        match &**message {
+            SyncServerMessage::SyncBatch(batch) => {
+                for item in batch.items.iter().filter(|item| item.subscription_id() == DEVTOOLS_SUBSCRIPTION_ID) {
+                    state.apply_item(&registry, item);
+                }
+            }
+            SyncServerMessage::MutationResponse(response) => state.handle_mutation_response(response),
+            _ => {}
+        }
+    }
+}
+
+fn send_mutations<NP: NetworkProvider>(net: Res<Network<NP>>, mut state: ResMut<DevtoolsState>) {
+    let outgoing = state.take_outgoing();
+    let Some(connection) = state.connection else {
+        for mutation in outgoing {
+            state.set_status(mutation.request_id, MutationStatus::InternalError, Some("Not connected".into()));
+        }
+        return;
+    };
+
+    for mutation in outgoing {
+        let message = SyncClientMessage::Mutate(MutateComponent {
+            request_id: Some(mutation.request_id),
+            entity: mutation.entity,
+            component_type: mutation.component_type,
+            value: mutation.value,
+            idempotency_key: None,
+        });
+        if let Err(e) = net.send(connection, message) {
+            state.set_status(mutation.request_id, MutationStatus::InternalError, Some(e.to_string()));
+        }
+    }
+}
+
+/// Rebuild the entity list from every registered component.
+fn refresh_local_state(world: &mut World) {
+    let Some(snapshots) = world
+        .get_resource::<SyncRegistry>()
+        .map(|registry| {
+            registry
+                .components
+                .iter()
+                .map(|reg| (reg.type_name.clone(), reg.snapshot_all))
+                .collect::<Vec<_>>()
+        })
+    else {
+        return;
+    };
+
+    let mut items = Vec::new();
+    for (component_type, snapshot_all) in snapshots {
+        for (entity, value) in snapshot_all(world) {
+            items.push(SyncItem::Snapshot {
+                subscription_id: DEVTOOLS_SUBSCRIPTION_ID,
+                entity,
+                component_type: component_type.clone(),
+                value,
+            });
+        }
+    }
+
+    world.resource_scope(|world, mut state: Mut<DevtoolsState>| {
+        let registry = world.resource::<DevtoolsTypeRegistry>();
+        state.entities.clear();
+        for item in &items {
+            state.apply_item(registry, item);
+        }
+    });
+}
+
+/// Apply edits directly to the local world, bypassing the network.
+fn apply_local_mutations(world: &mut World) {
+    let outgoing = world.resource_mut::<DevtoolsState>().take_outgoing();
+    if outgoing.is_empty() {
+        return;
+    }
+
+    for mutation in outgoing {
+        let apply = world.get_resource::<SyncRegistry>().and_then(|registry| {
+            registry
+                .components
+                .iter()
+                .find(|reg| reg.type_name == mutation.component_type)
+                .map(|reg| reg.apply_mutation)
+        });
+        let status = match apply {
+            Some(apply) => apply(
+                world,
+                &QueuedMutation {
+                    connection_id: ConnectionId::SERVER,
+                    request_id: Some(mutation.request_id),
+                    entity: mutation.entity,
+                    component_type: mutation.component_type,
+                    value: mutation.value,
+                    idempotency_key: None,
+                    origin: MutationOrigin::Mutate,
+                },
+            ),
+            None => MutationStatus::NotFound,
+        };
+        world
+            .resource_mut::<DevtoolsState>()
+            .set_status(mutation.request_id, status, None);
+    }
+
+    // Show the result right away instead of waiting for the next refresh
+    refresh_local_state(world);
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+type DecodeFn = fn(&[u8]) -> Result<JsonValue, String>;
+type EncodeFn = fn(&JsonValue) -> Result<Vec<u8>, String>;
+
+/// Converts wire values of registered component types to and from JSON.
+///
+/// Keys are the short type names used by `pl3xus_sync` on the wire.
+#[derive(Resource, Default)]
+pub struct DevtoolsTypeRegistry {
+    types: HashMap<String, (DecodeFn, EncodeFn)>,
+}
+
+fn decode_typed<T: Serialize + DeserializeOwned>(bytes: &[u8]) -> Result<JsonValue, String> {
+    let (value, _): (T, _) =
+        bincode::serde::decode_from_slice(bytes, bincode::config::standard()).map_err(|e| e.to_string())?;
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+fn encode_typed<T: Serialize + DeserializeOwned>(value: &JsonValue) -> Result<Vec<u8>, String> {
+    let value: T = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    bincode::serde::encode_to_vec(value, bincode::config::standard()).map_err(|e| e.to_string())
+}
+
+/// Short type name as used on the wire (matches `sync_component`).
+pub(crate) fn wire_type_name<T>() -> String {
+    let full_type_name = std::any::type_name::<T>();
+    full_type_name.rsplit("::").next().unwrap_or(full_type_name).to_string()
+}
+
+impl DevtoolsTypeRegistry {
+    pub fn register<T: Serialize + DeserializeOwned>(&mut self) {
+        self.types.insert(wire_type_name::<T>(), (decode_typed::<T>, encode_typed::<T>));
+    }
+
+    pub fn is_registered(&self, component_type: &str) -> bool {
+        self.types.contains_key(component_type)
+    }
+
+    /// Decode a wire value to JSON.
+    pub fn decode(&self, component_type: &str, bytes: &[u8]) -> Result<JsonValue, String> {
+        let (decode, _) = self
+            .types
+            .get(component_type)
+            .ok_or_else(|| format!("{} is not registered with the devtools", component_type))?;
+        decode(bytes)
+    }
+
+    /// Encode a JSON value for the wire.
+    pub fn encode(&self, component_type: &str, value: &JsonValue) -> Result<Vec<u8>, String> {
+        let (_, encode) = self
+            .types
+            .get(component_type)
+            .ok_or_else(|| format!("{} is not registered with the devtools", component_type))?;
+        encode(value)
+    }
+}
+
+/// Extension trait for registering inspectable component types.
+pub trait DevtoolsAppExt {
+    /// Make `T` readable and editable in the devtools.
+    ///
+    /// Components that are synced but not registered are listed without a value.
+    fn devtools_component<T: Serialize + DeserializeOwned>(&mut self) -> &mut Self;
+}
+
+impl DevtoolsAppExt for App {
+    fn devtools_component<T: Serialize + DeserializeOwned>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(DevtoolsTypeRegistry::default)
+            .register::<T>();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct JogSettings {
+        speed: f32,
+        step: u32,
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut registry = DevtoolsTypeRegistry::default();
+        registry.register::<JogSettings>();
+        assert!(registry.is_registered("JogSettings"));
+
+        let bytes = bincode::serde::encode_to_vec(JogSettings { speed: 2.5, step: 10 }, bincode::config::standard())
+            .unwrap();
+        let json = registry.decode("JogSettings", &bytes).unwrap();
+        assert_eq!(json, serde_json::json!({ "speed": 2.5, "step": 10 }));
+
+        let edited = serde_json::json!({ "speed": 2.5, "step": 20 });
+        let encoded = registry.encode("JogSettings", &edited).unwrap();
+        let (decoded, _): (JogSettings, _) =
+            bincode::serde::decode_from_slice(&encoded, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, JogSettings { speed: 2.5, step: 20 });
+
+        assert!(registry.encode("JogSettings", &serde_json::json!({ "speed": "fast" })).is_err());
+        assert!(registry.decode("Unknown", &bytes).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use bevy::prelude::*;
+use pl3xus::ConnectionId;
+use pl3xus_sync::{MutationResponse, MutationStatus, SerializableEntity, SyncItem};
+use serde_json::Value as JsonValue;
+
+use crate::registry::DevtoolsTypeRegistry;
+
+/// Number of mutations kept in the mutation log.
+const MUTATION_LOG_LEN: usize = 50;
+
+/// A mutation sent from the devtools and its outcome.
+#[derive(Debug, Clone)]
+pub struct DevtoolsMutation {
+    pub request_id: u64,
+    pub entity: SerializableEntity,
+    pub component_type: String,
+    /// `None` while waiting for the server
+    pub status: Option<MutationStatus>,
+    pub message: Option<String>,
+}
+
+/// A mutation waiting to be sent by the transport.
+#[derive(Debug, Clone)]
+pub(crate) struct OutgoingMutation {
+    pub request_id: u64,
+    pub entity: SerializableEntity,
+    pub component_type: String,
+    pub value: Vec<u8>,
+}
+
+/// Everything the devtools panel shows, built from sync items.
+#[derive(Resource, Default)]
+pub struct DevtoolsState {
+    /// Entity bits -> component type -> value (or why it cannot be shown)
+    pub entities: BTreeMap<u64, BTreeMap<String, Result<JsonValue, String>>>,
+    /// Recent mutations, newest last
+    pub mutations: VecDeque<DevtoolsMutation>,
+    /// Server connection (client mode)
+    pub connection: Option<ConnectionId>,
+    /// Whether the panel is shown
+    pub open: bool,
+    pub(crate) selected: Option<u64>,
+    pub(crate) filter: String,
+    /// Values being edited, so incoming updates don't overwrite them
+    pub(crate) drafts: HashMap<(u64, String), JsonValue>,
+    outgoing: Vec<OutgoingMutation>,
+    next_request_id: u64,
+}
+
+impl DevtoolsState {
+    /// Apply one sync item from the server (or a local snapshot).
+    pub fn apply_item(&mut self, registry: &DevtoolsTypeRegistry, item: &SyncItem) {
+        match item {
+            SyncItem::Snapshot { entity, component_type, value, .. }
+            | SyncItem::Update { entity, component_type, value, .. } => {
+                self.entities
+                    .entry(entity.bits)
+                    .or_default()
+                    .insert(component_type.clone(), registry.decode(component_type, value));
+            }
+            SyncItem::ComponentRemoved { entity, component_type, .. } => {
+                if let Some(components) = self.entities.get_mut(&entity.bits) {
+                    components.remove(component_type);
+                }
+                self.drafts.remove(&(entity.bits, component_type.clone()));
+            }
+            SyncItem::EntityRemoved { entity, .. } => {
+                self.entities.remove(&entity.bits);
+                self.drafts.retain(|(bits, _), _| *bits != entity.bits);
+                if self.selected == Some(entity.bits) {
+                    self.selected = None;
+                }
+            }
+        }
+    }
+
+    /// Forget all entities, e.g. after a disconnect.
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.drafts.clear();
+        self.selected = None;
+    }
+
+    /// Queue a full-value mutation of `component_type` on `entity`.
+    pub fn mutate(
+        &mut self,
+        registry: &DevtoolsTypeRegistry,
+        entity: u64,
+        component_type: &str,
+        value: &JsonValue,
+    ) -> u64 {
+        self.next_request_id += 1;
+        let request_id = self.next_request_id;
+        let entity = SerializableEntity { bits: entity };
+
+        let (status, message) = match registry.encode(component_type, value) {
+            Ok(value) => {
+                self.outgoing.push(OutgoingMutation {
+                    request_id,
+                    entity,
+                    component_type: component_type.to_string(),
+                    value,
+                });
+                (None, None)
+            }
+            Err(e) => (Some(MutationStatus::ValidationError), Some(e)),
+        };
+
+        if self.mutations.len() == MUTATION_LOG_LEN {
+            self.mutations.pop_front();
+        }
+        self.mutations.push_back(DevtoolsMutation {
+            request_id,
+            entity,
+            component_type: component_type.to_string(),
+            status,
+            message,
+        });
+        request_id
+    }
+
+    pub(crate) fn take_outgoing(&mut self) -> Vec<OutgoingMutation> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Record the outcome of a mutation.
+    pub fn handle_mutation_response(&mut self, response: &MutationResponse) {
+        let Some(request_id) = response.request_id else {
+            return;
+        };
+        self.set_status(request_id, response.status.clone(), response.message.clone());
+    }
+
+    pub(crate) fn set_status(&mut self, request_id: u64, status: MutationStatus, message: Option<String>) {
+        if let Some(mutation) = self.mutations.iter_mut().find(|m| m.request_id == request_id) {
+            mutation.status = Some(status);
+            mutation.message = message;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Speed(f32);
+
+    fn encoded(speed: f32) -> Vec<u8> {
+        bincode::serde::encode_to_vec(Speed(speed), bincode::config::standard()).unwrap()
+    }
+
+    #[test]
+    fn test_apply_items_and_mutate() {
+        let mut registry = DevtoolsTypeRegistry::default();
+        registry.register::<Speed>();
+        let mut state = DevtoolsState::default();
+        let entity = SerializableEntity { bits: 42 };
+
+        state.apply_item(&registry, &SyncItem::Snapshot {
+            subscription_id: 1,
+            entity,
+            component_type: "Speed".into(),
+            value: encoded(1.0),
+        });
+        state.apply_item(&registry, &SyncItem::Snapshot {
+            subscription_id: 1,
+            entity,
+            component_type: "Unregistered".into(),
+            value: vec![1, 2, 3],
+        });
+        assert_eq!(state.entities[&42]["Speed"], Ok(serde_json::json!(1.0)));
+        assert!(state.entities[&42]["Unregistered"].is_err());
+
+        let request_id = state.mutate(&registry, 42, "Speed", &serde_json::json!(2.0));
+        let outgoing = state.take_outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].value, encoded(2.0));
+
+        state.handle_mutation_response(&MutationResponse {
+            request_id: Some(request_id),
+            status: MutationStatus::Forbidden,
+            message: Some("not in control".into()),
+            field_errors: Vec::new(),
+        });
+        assert!(matches!(state.mutations[0].status, Some(MutationStatus::Forbidden)));
+
+        state.apply_item(&registry, &SyncItem::EntityRemoved { subscription_id: 1, entity });
+        assert!(state.entities.is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use pl3xus_sync::MutationStatus;
+use serde_json::Value as JsonValue;
+
+use crate::registry::DevtoolsTypeRegistry;
+use crate::state::DevtoolsState;
+
+pub(crate) fn devtools_ui(
+    mut contexts: EguiContexts,
+    registry: Res<DevtoolsTypeRegistry>,
+    mut state: ResMut<DevtoolsState>,
+) -> Result {
+    if !state.open {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+
+    let mut open = true;
+    egui::Window::new("pl3xus DevTools")
+        .open(&mut open)
+        .default_size([720.0, 480.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} entities", state.entities.len()));
+                ui.separator();
+                ui.label("Filter:");
+                ui.text_edit_singleline(&mut state.filter);
+            });
+            ui.separator();
+
+            egui::TopBottomPanel::bottom("pl3xus_devtools_mutations")
+                .resizable(true)
+                .default_height(100.0)
+                .show_inside(ui, |ui| mutation_log(ui, &state));
+            egui::SidePanel::left("pl3xus_devtools_entities")
+                .resizable(true)
+                .default_width(220.0)
+                .show_inside(ui, |ui| entity_list(ui, &mut state));
+            egui::CentralPanel::default().show_inside(ui, |ui| component_inspector(ui, &registry, &mut state));
+        });
+    state.open = open;
+
+    Ok(())
+}
+
+fn entity_label(bits: u64) -> String {
+    format!("Entity {}", Entity::from_bits(bits))
+}
+
+fn entity_list(ui: &mut egui::Ui, state: &mut DevtoolsState) {
+    let filter = state.filter.to_lowercase();
+    let mut selected = state.selected;
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (bits, components) in &state.entities {
+            let label = entity_label(*bits);
+            let matches = filter.is_empty()
+                || label.to_lowercase().contains(&filter)
+                || components.keys().any(|name| name.to_lowercase().contains(&filter));
+            if !matches {
+                continue;
+            }
+            if ui.selectable_label(selected == Some(*bits), label).clicked() {
+                selected = Some(*bits);
+            }
+        }
+    });
+
+    state.selected = selected;
+}
+
+fn component_inspector(ui: &mut egui::Ui, registry: &DevtoolsTypeRegistry, state: &mut DevtoolsState) {
+    let Some(bits) = state.selected else {
+        ui.label("Select an entity");
+        return;
+    };
+    let Some(components) = state.entities.get(&bits).cloned() else {
+        return;
+    };
+
+    ui.heading(entity_label(bits));
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (component_type, value) in components {
+            egui::CollapsingHeader::new(&component_type)
+                .id_salt(("pl3xus_devtools_component", bits, &component_type))
+                .default_open(true)
+                .show(ui, |ui| {
+                    let current = match value {
+                        Ok(value) => value,
+                        Err(e) => {
+                            ui.colored_label(egui::Color32::GRAY, e);
+                            return;
+                        }
+                    };
+
+                    let key = (bits, component_type.clone());
+                    let has_draft = state.drafts.contains_key(&key);
+                    let mut edited = state.drafts.get(&key).cloned().unwrap_or_else(|| current.clone());
+                    if json_editor(ui, &mut edited, &component_type) {
+                        state.drafts.insert(key.clone(), edited);
+                    }
+
+                    if has_draft {
+                        ui.horizontal(|ui| {
+                            if ui.button("Apply").clicked()
+                                && let Some(draft) = state.drafts.remove(&key)
+                            {
+                                state.mutate(registry, bits, &component_type, &draft);
+                            }
+                            if ui.button("Revert").clicked() {
+                                state.drafts.remove(&key);
+                            }
+                        });
+                    }
+                });
+        }
+    });
+}
+
+/// Edit a JSON value in place, returning whether it changed.
+fn json_editor(ui: &mut egui::Ui, value: &mut JsonValue, id: &str) -> bool {
+    match value {
+        JsonValue::Null => {
+            ui.label("null");
+            false
+        }
+        JsonValue::Bool(b) => ui.checkbox(b, "").changed(),
+        JsonValue::Number(n) => {
+            if let Some(mut v) = n.as_u64() {
+                let changed = ui.add(egui::DragValue::new(&mut v)).changed();
+                *value = JsonValue::from(v);
+                changed
+            } else if let Some(mut v) = n.as_i64() {
+                let changed = ui.add(egui::DragValue::new(&mut v)).changed();
+                *value = JsonValue::from(v);
+                changed
+            } else {
+                let mut v = n.as_f64().unwrap_or_default();
+                let changed = ui.add(egui::DragValue::new(&mut v).speed(0.01)).changed();
+                if changed {
+                    *value = JsonValue::from(v);
+                }
+                changed
+            }
+        }
+        JsonValue::String(s) => ui.text_edit_singleline(s).changed(),
+        JsonValue::Array(items) => {
+            let mut changed = false;
+            egui::CollapsingHeader::new(format!("[{}]", items.len()))
+                .id_salt(id)
+                .show(ui, |ui| {
+                    for (i, item) in items.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{}:", i));
+                            changed |= json_editor(ui, item, &format!("{}[{}]", id, i));
+                        });
+                    }
+                });
+            changed
+        }
+        JsonValue::Object(fields) => {
+            let mut changed = false;
+            ui.vertical(|ui| {
+                for (name, field) in fields.iter_mut() {
+                    let field_id = format!("{}.{}", id, name);
+                    if field.is_object() || field.is_array() {
+                        egui::CollapsingHeader::new(name.as_str())
+                            .id_salt(&field_id)
+                            .show(ui, |ui| changed |= json_editor(ui, field, &field_id));
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{}:", name));
+                            changed |= json_editor(ui, field, &field_id);
+                        });
+                    }
+                }
+            });
+            changed
+        }
+    }
+}
+
+fn mutation_log(ui: &mut egui::Ui, state: &DevtoolsState) {
+    ui.label("Mutations");
+    egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+        for mutation in &state.mutations {
+            let (color, status) = match &mutation.status {
+                None => (egui::Color32::GRAY, "pending".to_string()),
+                Some(MutationStatus::Ok) => (egui::Color32::GREEN, "ok".to_string()),
+                Some(status) => (egui::Color32::RED, format!("{:?}", status)),
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "#{} {} on {}",
+                    mutation.request_id,
+                    mutation.component_type,
+                    entity_label(mutation.entity.bits)
+                ));
+                ui.colored_label(color, status);
+                if let Some(message) = &mutation.message {
+                    ui.label(message);
+                }
+            });
+        }
+    });
+}
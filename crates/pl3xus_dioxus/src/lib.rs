@@ -0,0 +1,176 @@
+//! # Pl3xus Dioxus
+//!
+//! Dioxus hooks for `pl3xus_sync`, built on `pl3xus_client_core`. Covers
+//! connecting, component subscriptions (shared between hooks, like the Leptos
+//! client) and mutations:
+//!
+//! ```rust,ignore
+//! use dioxus::prelude::*;
+//! use pl3xus_dioxus::{use_components, use_sync_provider, ClientTypeRegistry};
+//!
+//! fn App() -> Element {
+//!     use_sync_provider("ws://localhost:8082/sync", || {
+//!         ClientTypeRegistry::builder().register::<Position>().build()
+//!     });
+//!     rsx! { Positions {} }
+//! }
+//!
+//! fn Positions() -> Element {
+//!     let positions = use_components::<Position>();
+//!     rsx! {
+//!         for (id, position) in positions.read().iter() {
+//!             div { "Entity {id}: {position:?}" }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+use pl3xus_client_core::{ClientEvent, SocketEvent, SyncClientCore, SyncSocket};
+use pl3xus_common::{ConnectionId, NetworkPacket};
+
+pub use pl3xus_client_core::{ClientTypeRegistry, SyncComponent, SyncError};
+pub use pl3xus_sync::{FilterOp, FilterValue, SubscriptionFilter};
+
+/// Handle to the sync connection, provided as context by [`use_sync_provider`].
+#[derive(Clone)]
+pub struct SyncClient {
+    core: Rc<RefCell<SyncClientCore>>,
+    socket: Rc<RefCell<Option<SyncSocket>>>,
+    /// Bumped per component type whenever its received values change
+    revisions: Signal<HashMap<String, u64>>,
+    connected: Signal<bool>,
+    last_error: Signal<Option<SyncError>>,
+}
+
+impl SyncClient {
+    fn new(registry: Arc<ClientTypeRegistry>) -> Self {
+        Self {
+            core: Rc::new(RefCell::new(SyncClientCore::new(registry))),
+            socket: Rc::new(RefCell::new(None)),
+            revisions: Signal::new(HashMap::new()),
+            connected: Signal::new(false),
+            last_error: Signal::new(None),
+        }
+    }
+
+    /// Open (or reopen) the connection to `url`.
+    pub fn connect(&self, url: &str) {
+        let mut revisions = self.revisions;
+        let mut connected = self.connected;
+        let result = SyncSocket::connect(url, self.core.clone(), move |event| match event {
+            SocketEvent::Open => connected.set(true),
+            SocketEvent::Closed => connected.set(false),
+            SocketEvent::Client(ClientEvent::ComponentsChanged(component_types)) => {
+                let mut revisions = revisions.write();
+                for component_type in component_types {
+                    *revisions.entry(component_type).or_insert(0) += 1;
+                }
+            }
+            SocketEvent::Client(_) => {}
+        });
+        match result {
+            Ok(socket) => *self.socket.borrow_mut() = Some(socket),
+            Err(error) => {
+                let mut last_error = self.last_error;
+                last_error.set(Some(error));
+            }
+        }
+    }
+
+    /// Whether the socket is open. Reactive.
+    pub fn is_connected(&self) -> bool {
+        (self.connected)()
+    }
+
+    /// The last connection error, if any. Reactive.
+    pub fn last_error(&self) -> Option<SyncError> {
+        self.last_error.cloned()
+    }
+
+    /// This client's connection id, once the server has welcomed it.
+    pub fn connection_id(&self) -> Option<ConnectionId> {
+        self.core.borrow().connection_id()
+    }
+
+    /// Send a mutation of `entity_id`'s `T`. Returns its request id.
+    pub fn mutate<T: SyncComponent>(&self, entity_id: u64, value: &T) -> Result<u64, SyncError> {
+        let (request_id, packet) = self.core.borrow_mut().mutate(entity_id, value)?;
+        self.send(&packet)?;
+        Ok(request_id)
+    }
+
+    fn subscribe(&self, component_name: &str, filter: Option<SubscriptionFilter>) -> String {
+        let (key, packet) = self.core.borrow_mut().subscribe(component_name, filter);
+        // Not connected yet: the socket sends it on open
+        if let Some(packet) = packet {
+            let _ = self.send(&packet);
+        }
+        key
+    }
+
+    fn unsubscribe(&self, key: &str) {
+        let packet = self.core.borrow_mut().unsubscribe(key);
+        if let Some(packet) = packet {
+            let _ = self.send(&packet);
+        }
+    }
+
+    fn send(&self, packet: &NetworkPacket) -> Result<(), SyncError> {
+        match self.socket.borrow().as_ref() {
+            Some(socket) => socket.send(packet),
+            None => Err(SyncError::NotConnected),
+        }
+    }
+}
+
+/// Connect to a pl3xus_sync server and provide the [`SyncClient`] to
+/// descendants. Call once, near the root.
+pub fn use_sync_provider(url: &str, registry: impl FnOnce() -> Arc<ClientTypeRegistry>) -> SyncClient {
+    use_context_provider(|| {
+        let client = SyncClient::new(registry());
+        client.connect(url);
+        client
+    })
+}
+
+/// The [`SyncClient`] provided by [`use_sync_provider`].
+pub fn use_sync_client() -> SyncClient {
+    use_context::<SyncClient>()
+}
+
+/// All entities with component `T`, by entity id.
+///
+/// Subscribes while the component is mounted; hooks for the same type share
+/// one server subscription.
+pub fn use_components<T: SyncComponent + PartialEq>() -> Memo<HashMap<u64, T>> {
+    use_subscription(None)
+}
+
+/// The entities with component `T` matching `filter`, by entity id.
+///
+/// The server only sends matching entities; see [`SubscriptionFilter`].
+pub fn use_components_filtered<T: SyncComponent + PartialEq>(filter: SubscriptionFilter) -> Memo<HashMap<u64, T>> {
+    use_subscription(Some(filter))
+}
+
+fn use_subscription<T: SyncComponent + PartialEq>(filter: Option<SubscriptionFilter>) -> Memo<HashMap<u64, T>> {
+    let client = use_sync_client();
+    let key = use_hook(|| client.subscribe(T::component_name(), filter.clone()));
+    use_drop({
+        let client = client.clone();
+        let key = key.clone();
+        move || client.unsubscribe(&key)
+    });
+
+    use_memo(move || {
+        // Re-run only when this type's values change
+        let _revision = client.revisions.read().get(T::component_name()).copied();
+        client.core.borrow().components::<T>(filter.as_ref())
+    })
+}
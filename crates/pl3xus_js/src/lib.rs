@@ -0,0 +1,495 @@
+//! # Pl3xus JS
+//!
+//! JavaScript/TypeScript SDK for `pl3xus_sync` servers: a wasm-bindgen
+//! wrapper around `pl3xus_client_core` that takes and returns plain JSON
+//! values, so existing dashboards can subscribe, mutate and send requests
+//! without writing Rust. Payloads are transcoded with the shapes from the
+//! server's `DescribeSchema` response (see [`SchemaCodec`]).
+//!
+//! ```js
+//! import init, { Pl3xusClient } from "pl3xus_js";
+//!
+//! await init();
+//! const client = new Pl3xusClient("ws://localhost:8082/sync");
+//! await client.ready();
+//!
+//! const handle = client.subscribe("Position", (entities) => render(entities));
+//! await client.mutate(entityId, "Position", { x: 1.0, y: 2.0 });
+//! const programs = await client.request("ListPrograms", null);
+//! client.unsubscribe(handle);
+//! ```
+//!
+//! Build with `wasm-pack build crates/pl3xus_js --target web`; the generated
+//! package includes TypeScript declarations.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use js_sys::{Function, Promise, JSON};
+use pl3xus_client_core::{ClientEvent, ClientTypeRegistry, SchemaCodec, SocketEvent, SyncClientCore, SyncSocket};
+use pl3xus_common::{DescribeSchema, NetworkPacket, SchemaDescription};
+use pl3xus_sync::{MutationResponse, MutationStatus, SubscriptionFilter};
+use serde_json::Value as JsonValue;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &str = r#"
+/** Entities with a component, by entity id (a decimal string). */
+export type Entities<T = any> = Record<string, T>;
+
+/**
+ * A server-side subscription filter, in its serde JSON form, e.g.
+ * `{ Field: { path: "mode", op: "Eq", value: { String: "Auto" } } }`.
+ */
+export type SubscriptionFilter = Record<string, any>;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "(entities: Entities) => void")]
+    pub type EntitiesCallback;
+
+    #[wasm_bindgen(typescript_type = "(message: any) => void")]
+    pub type MessageCallback;
+
+    #[wasm_bindgen(typescript_type = "(connected: boolean) => void")]
+    pub type ConnectionCallback;
+
+    #[wasm_bindgen(typescript_type = "SubscriptionFilter")]
+    pub type Filter;
+
+    #[wasm_bindgen(typescript_type = "string | number | bigint")]
+    pub type EntityId;
+}
+
+/// Connection to a pl3xus_sync server.
+///
+/// Component values, mutations, requests and messages are plain JSON values
+/// with the same layout serde_json gives the server's Rust types.
+#[wasm_bindgen]
+pub struct Pl3xusClient {
+    shared: Rc<Shared>,
+}
+
+struct Shared {
+    core: Rc<RefCell<SyncClientCore>>,
+    socket: RefCell<Option<SyncSocket>>,
+    state: RefCell<State>,
+}
+
+#[derive(Default)]
+struct State {
+    codec: Option<Rc<SchemaCodec>>,
+    schema_request: Option<u64>,
+    waiting_for_schema: Vec<Deferred>,
+    subscriptions: HashMap<u32, Subscription>,
+    next_subscription: u32,
+    requests: HashMap<u64, (String, Deferred)>,
+    mutations: HashMap<u64, Deferred>,
+    message_handlers: HashMap<String, Vec<Function>>,
+    on_connection_change: Option<Function>,
+}
+
+struct Subscription {
+    component: String,
+    key: String,
+    filter: Option<SubscriptionFilter>,
+    callback: Function,
+}
+
+/// The settle functions of a pending promise.
+struct Deferred {
+    resolve: Function,
+    reject: Function,
+}
+
+impl Deferred {
+    fn new() -> (Promise, Self) {
+        let mut deferred = None;
+        let promise = Promise::new(&mut |resolve, reject| deferred = Some(Deferred { resolve, reject }));
+        // Promise executors run synchronously
+        (promise, deferred.expect("promise executor was not called"))
+    }
+
+    fn resolve(&self, value: &JsValue) {
+        let _ = self.resolve.call1(&JsValue::NULL, value);
+    }
+
+    fn reject(&self, error: impl Display) {
+        let _ = self
+            .reject
+            .call1(&JsValue::NULL, &js_sys::Error::new(&error.to_string()).into());
+    }
+}
+
+#[wasm_bindgen]
+impl Pl3xusClient {
+    /// Connect to `url` and fetch the server's schema.
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: &str) -> Result<Pl3xusClient, JsError> {
+        let shared = Rc::new(Shared {
+            core: Rc::new(RefCell::new(SyncClientCore::new(ClientTypeRegistry::builder().build()))),
+            socket: RefCell::new(None),
+            state: RefCell::default(),
+        });
+        let weak = Rc::downgrade(&shared);
+        let socket = SyncSocket::connect(url, shared.core.clone(), move |event| {
+            if let Some(shared) = weak.upgrade() {
+                shared.handle_event(event);
+            }
+        })
+        .map_err(js_error)?;
+        *shared.socket.borrow_mut() = Some(socket);
+        Ok(Self { shared })
+    }
+
+    /// Resolves once the server's schema has arrived. Mutations, requests and
+    /// messages need it; subscriptions deliver their first values after it.
+    pub fn ready(&self) -> Promise {
+        let mut state = self.shared.state.borrow_mut();
+        if state.codec.is_some() {
+            return Promise::resolve(&JsValue::UNDEFINED);
+        }
+        let (promise, deferred) = Deferred::new();
+        state.waiting_for_schema.push(deferred);
+        promise
+    }
+
+    /// Whether the socket is open.
+    #[wasm_bindgen(getter)]
+    pub fn connected(&self) -> bool {
+        self.shared.socket.borrow().as_ref().is_some_and(SyncSocket::is_open)
+    }
+
+    /// Call `callback` whenever the socket opens or closes.
+    #[wasm_bindgen(js_name = onConnectionChange)]
+    pub fn on_connection_change(&self, callback: ConnectionCallback) {
+        self.shared.state.borrow_mut().on_connection_change = Some(callback.unchecked_into());
+    }
+
+    /// Call `callback` with every entity that has `component`, now and
+    /// whenever they change. Subscriptions for the same component and filter
+    /// share one server subscription. Returns a handle for
+    /// [`unsubscribe`](Self::unsubscribe).
+    pub fn subscribe(
+        &self,
+        component: &str,
+        callback: EntitiesCallback,
+        filter: Option<Filter>,
+    ) -> Result<u32, JsError> {
+        let filter = filter
+            .map(|filter| from_js::<SubscriptionFilter>(&filter))
+            .transpose()?;
+        let (key, packet) = self.shared.core.borrow_mut().subscribe(component, filter.clone());
+        // Not connected yet: the socket sends it on open
+        if let Some(packet) = packet {
+            let _ = self.shared.send(&packet);
+        }
+
+        let subscription = Subscription {
+            component: component.to_string(),
+            key,
+            filter,
+            callback: callback.unchecked_into(),
+        };
+        let handle = {
+            let mut state = self.shared.state.borrow_mut();
+            state.next_subscription += 1;
+            state.next_subscription
+        };
+        self.shared.notify(&subscription);
+        self.shared.state.borrow_mut().subscriptions.insert(handle, subscription);
+        Ok(handle)
+    }
+
+    /// Stop a subscription made with [`subscribe`](Self::subscribe).
+    pub fn unsubscribe(&self, handle: u32) {
+        let Some(subscription) = self.shared.state.borrow_mut().subscriptions.remove(&handle) else {
+            return;
+        };
+        let packet = self.shared.core.borrow_mut().unsubscribe(&subscription.key);
+        if let Some(packet) = packet {
+            let _ = self.shared.send(&packet);
+        }
+    }
+
+    /// Set `entity`'s `component` to `value`. Resolves with the server's
+    /// `MutationResponse` when it is accepted and rejects otherwise.
+    pub fn mutate(&self, entity: EntityId, component: &str, value: JsValue) -> Result<Promise, JsError> {
+        let entity_id = entity_id(&entity)?;
+        let value = from_js::<JsonValue>(&value)?;
+        let codec = self.shared.codec()?;
+        let (request_id, packet) = self
+            .shared
+            .core
+            .borrow_mut()
+            .mutate_json(&codec, entity_id, component, &value)
+            .map_err(js_error)?;
+        self.shared.send(&packet)?;
+
+        let (promise, deferred) = Deferred::new();
+        self.shared.state.borrow_mut().mutations.insert(request_id, deferred);
+        Ok(promise)
+    }
+
+    /// Send request `name` (its `RequestMessage::request_name`, usually the
+    /// short type name). Resolves with the response.
+    pub fn request(&self, name: &str, payload: JsValue) -> Result<Promise, JsError> {
+        let payload = from_js::<JsonValue>(&payload)?;
+        let codec = self.shared.codec()?;
+        let (request_id, packet) = self
+            .shared
+            .core
+            .borrow_mut()
+            .request_json(&codec, name, &payload)
+            .map_err(js_error)?;
+        self.shared.send(&packet)?;
+
+        let (promise, deferred) = Deferred::new();
+        self.shared
+            .state
+            .borrow_mut()
+            .requests
+            .insert(request_id, (name.to_string(), deferred));
+        Ok(promise)
+    }
+
+    /// Send a plain message by its short type name.
+    pub fn send(&self, name: &str, payload: JsValue) -> Result<(), JsError> {
+        let payload = from_js::<JsonValue>(&payload)?;
+        let packet = self.shared.codec()?.encode_message(name, &payload).map_err(js_error)?;
+        self.shared.send(&packet)
+    }
+
+    /// Call `callback` with every message of type `name` the server sends.
+    #[wasm_bindgen(js_name = onMessage)]
+    pub fn on_message(&self, name: &str, callback: MessageCallback) {
+        self.shared
+            .state
+            .borrow_mut()
+            .message_handlers
+            .entry(name.to_string())
+            .or_default()
+            .push(callback.unchecked_into());
+    }
+
+    /// Close the connection. Pending mutations and requests are rejected.
+    pub fn close(&self) {
+        let socket = self.shared.socket.borrow_mut().take();
+        drop(socket);
+        self.shared.connection_changed(false);
+    }
+}
+
+impl Shared {
+    fn send(&self, packet: &NetworkPacket) -> Result<(), JsError> {
+        match self.socket.borrow().as_ref() {
+            Some(socket) => socket.send(packet).map_err(js_error),
+            None => Err(JsError::new("the connection is closed")),
+        }
+    }
+
+    fn codec(&self) -> Result<Rc<SchemaCodec>, JsError> {
+        self.state
+            .borrow()
+            .codec
+            .clone()
+            .ok_or_else(|| JsError::new("the server's schema hasn't arrived yet; await client.ready() first"))
+    }
+
+    fn handle_event(&self, event: SocketEvent) {
+        match event {
+            SocketEvent::Open => {
+                if self.state.borrow().codec.is_none() {
+                    self.request_schema();
+                }
+                self.connection_changed(true);
+            }
+            // The current schema keeps working until the new one arrives
+            SocketEvent::Client(ClientEvent::RegistryUpdated(_)) => self.request_schema(),
+            SocketEvent::Closed => self.connection_changed(false),
+            SocketEvent::Client(ClientEvent::ComponentsChanged(component_types)) => {
+                self.notify_where(|subscription| component_types.contains(&subscription.component));
+            }
+            SocketEvent::Client(ClientEvent::Response { request_id, data }) => self.handle_response(request_id, &data),
+            SocketEvent::Client(ClientEvent::RequestRejected { request_id, reason }) => {
+                let deferred = self.state.borrow_mut().requests.remove(&request_id);
+                if let Some((_, deferred)) = deferred {
+                    deferred.reject(&reason);
+                }
+            }
+            SocketEvent::Client(ClientEvent::MutationResponse(response)) => self.handle_mutation_response(response),
+            SocketEvent::Client(ClientEvent::Message { type_name, data }) => self.handle_message(&type_name, &data),
+            SocketEvent::Client(_) => {}
+        }
+    }
+
+    fn request_schema(&self) {
+        let request = self.core.borrow_mut().request(DescribeSchema);
+        if let Ok((request_id, packet)) = request {
+            self.state.borrow_mut().schema_request = Some(request_id);
+            let _ = self.send(&packet);
+        }
+    }
+
+    fn connection_changed(&self, connected: bool) {
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            if !connected {
+                let reason = "the connection closed";
+                for (_, (_, deferred)) in state.requests.drain() {
+                    deferred.reject(reason);
+                }
+                for (_, deferred) in state.mutations.drain() {
+                    deferred.reject(reason);
+                }
+            }
+            state.on_connection_change.clone()
+        };
+        if let Some(callback) = callback {
+            let _ = callback.call1(&JsValue::NULL, &connected.into());
+        }
+    }
+
+    fn handle_response(&self, request_id: u64, data: &[u8]) {
+        let mut state = self.state.borrow_mut();
+        if state.schema_request == Some(request_id) {
+            let description =
+                bincode::serde::decode_from_slice::<SchemaDescription, _>(data, bincode::config::standard());
+            let Ok((description, _)) = description else {
+                for deferred in state.waiting_for_schema.drain(..) {
+                    deferred.reject("the server sent a schema this client can't read");
+                }
+                return;
+            };
+            state.schema_request = None;
+            state.codec = Some(Rc::new(SchemaCodec::from(description)));
+            let waiting = std::mem::take(&mut state.waiting_for_schema);
+            drop(state);
+
+            // Values that arrived before the schema can be decoded now
+            self.notify_where(|_| true);
+            for deferred in waiting {
+                deferred.resolve(&JsValue::UNDEFINED);
+            }
+            return;
+        }
+
+        let Some((name, deferred)) = state.requests.remove(&request_id) else {
+            return;
+        };
+        let codec = state.codec.clone();
+        drop(state);
+        let response = codec
+            .ok_or_else(|| "the server's schema hasn't arrived yet".to_string())
+            .and_then(|codec| codec.decode_response(&name, data));
+        match response.map(|response| to_js(&response)) {
+            Ok(response) => deferred.resolve(&response),
+            Err(error) => deferred.reject(error),
+        }
+    }
+
+    fn handle_mutation_response(&self, response: MutationResponse) {
+        let Some(request_id) = response.request_id else {
+            return;
+        };
+        let Some(deferred) = self.state.borrow_mut().mutations.remove(&request_id) else {
+            return;
+        };
+        if matches!(response.status, MutationStatus::Ok) {
+            let json = serde_json::to_value(&response).unwrap_or_default();
+            deferred.resolve(&to_js(&json));
+        } else {
+            let message = response.message.unwrap_or_else(|| format!("{:?}", response.status));
+            deferred.reject(message);
+        }
+    }
+
+    fn handle_message(&self, type_name: &str, data: &[u8]) {
+        let (handlers, codec) = {
+            let state = self.state.borrow();
+            (state.message_handlers.get(type_name).cloned(), state.codec.clone())
+        };
+        let (Some(handlers), Some(codec)) = (handlers, codec) else {
+            return;
+        };
+        let Ok(message) = codec.decode_message(type_name, data) else {
+            return;
+        };
+        let message = to_js(&message);
+        for handler in handlers {
+            let _ = handler.call1(&JsValue::NULL, &message);
+        }
+    }
+
+    /// Call the callbacks of the subscriptions matching `predicate` with
+    /// their current entities.
+    fn notify_where(&self, predicate: impl Fn(&Subscription) -> bool) {
+        let state = self.state.borrow();
+        let batch: Vec<_> = state
+            .subscriptions
+            .values()
+            .filter(|subscription| predicate(subscription))
+            .map(|subscription| (subscription.callback.clone(), self.entities(&state, subscription)))
+            .collect();
+        // Callbacks may subscribe or unsubscribe
+        drop(state);
+        for (callback, entities) in batch {
+            if let Some(entities) = entities {
+                let _ = callback.call1(&JsValue::NULL, &entities);
+            }
+        }
+    }
+
+    fn notify(&self, subscription: &Subscription) {
+        let entities = self.entities(&self.state.borrow(), subscription);
+        if let Some(entities) = entities {
+            let _ = subscription.callback.call1(&JsValue::NULL, &entities);
+        }
+    }
+
+    /// The entities for `subscription`, or `None` before the schema arrives.
+    fn entities(&self, state: &State, subscription: &Subscription) -> Option<JsValue> {
+        let codec = state.codec.as_ref()?;
+        let core = self.core.borrow();
+        let entities = codec.decode_components(core.data(), &subscription.component, subscription.filter.as_ref());
+        Some(to_js(&JsonValue::Object(entities)))
+    }
+}
+
+fn js_error(error: impl Display) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn from_js<T: serde::de::DeserializeOwned>(value: &JsValue) -> Result<T, JsError> {
+    if value.is_undefined() {
+        return serde_json::from_value(JsonValue::Null).map_err(js_error);
+    }
+    let json = JSON::stringify(value)
+        .map_err(|_| JsError::new("value can't be converted to JSON"))?
+        .as_string()
+        .unwrap_or_default();
+    serde_json::from_str(&json).map_err(js_error)
+}
+
+fn to_js(value: &JsonValue) -> JsValue {
+    JSON::parse(&value.to_string()).unwrap_or(JsValue::NULL)
+}
+
+/// Entity ids are `u64`s, beyond what a JS number holds exactly; accept
+/// them as strings and bigints too.
+fn entity_id(entity: &JsValue) -> Result<u64, JsError> {
+    let id = if let Some(id) = entity.as_string() {
+        id.parse().ok()
+    } else if entity.is_bigint() {
+        js_sys::BigInt::from(entity.clone()).to_string(10).ok().and_then(|id| String::from(id).parse().ok())
+    } else {
+        entity
+            .as_f64()
+            .filter(|id| id.fract() == 0.0 && (0.0..=9_007_199_254_740_991.0).contains(id))
+            .map(|id| id as u64)
+    };
+    id.ok_or_else(|| JsError::new("entity ids must be decimal strings, bigints or safe integers"))
+}
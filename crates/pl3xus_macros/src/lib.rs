@@ -23,6 +23,14 @@ use syn::{parse_macro_input, Data, DeriveInput, Fields, Meta, Expr, ExprLit, Lit
 /// #[derive(Invalidates)]
 /// #[invalidates("ListPrograms", "GetProgram")]
 /// pub struct DeleteProgram { ... }
+///
+/// // Keyed invalidation: only the cached GetProgram for this program_id.
+/// // The key is an expression over `self` (the request) and `response`,
+/// // and the type must implement `RequestMessage`.
+/// #[derive(Invalidates)]
+/// #[invalidates("ListPrograms")]
+/// #[invalidates("GetProgram", key = "self.program_id")]
+/// pub struct UpdateProgramSettings { ... }
 /// ```
 ///
 /// This generates an implementation of the `Invalidates` trait:
@@ -39,39 +47,105 @@ pub fn derive_invalidates(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let name = &ast.ident;
 
-    // Find the #[invalidates(...)] attribute
-    let mut query_types: Vec<String> = Vec::new();
+    // Collect every #[invalidates(...)] attribute
+    let mut queries: Vec<(String, Option<Expr>)> = Vec::new();
 
     for attr in &ast.attrs {
-        if attr.path().is_ident("invalidates") {
-            // Parse the attribute arguments
-            if let Meta::List(meta_list) = &attr.meta {
-                // Parse as comma-separated string literals
-                let result = meta_list.parse_args_with(
-                    syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated,
-                );
-
-                if let Ok(exprs) = result {
-                    for expr in exprs {
-                        if let Expr::Lit(ExprLit { lit: Lit::Str(lit_str), .. }) = expr {
-                            query_types.push(lit_str.value());
-                        }
+        if attr.path().is_ident("invalidates")
+            && let Err(e) = parse_invalidates(attr, &mut queries)
+        {
+            return e.to_compile_error().into();
+        }
+    }
+
+    invalidates_impl(name, &queries).into()
+}
+
+/// Parse one `#[invalidates("Query", ..., key = "expr")]` attribute.
+///
+/// The key, if present, applies to every query type in the attribute.
+fn parse_invalidates(attr: &syn::Attribute, queries: &mut Vec<(String, Option<Expr>)>) -> syn::Result<()> {
+    let Meta::List(meta_list) = &attr.meta else {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "expected `#[invalidates(\"Query\", ...)]`",
+        ));
+    };
+    let exprs = meta_list.parse_args_with(
+        syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated,
+    )?;
+
+    let mut query_types = Vec::new();
+    let mut key = None;
+    for expr in exprs {
+        match expr {
+            Expr::Lit(ExprLit { lit: Lit::Str(lit_str), .. }) => query_types.push(lit_str.value()),
+            Expr::Assign(assign) => {
+                let is_key = matches!(&*assign.left, Expr::Path(path) if path.path.is_ident("key"));
+                match &*assign.right {
+                    Expr::Lit(ExprLit { lit: Lit::Str(lit_str), .. }) if is_key => {
+                        key = Some(lit_str.parse::<Expr>()?);
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            assign,
+                            "expected `key = \"expression\"`",
+                        ));
                     }
                 }
             }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "expected a query type string literal or `key = \"expression\"`",
+                ));
+            }
         }
     }
+    if query_types.is_empty() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "expected at least one query type, e.g. `#[invalidates(\"ListPrograms\")]`",
+        ));
+    }
 
-    // Generate the trait implementation
-    let expanded = quote! {
+    queries.extend(query_types.into_iter().map(|query_type| (query_type, key.clone())));
+    Ok(())
+}
+
+/// Generate `impl pl3xus_sync::Invalidates`, with `invalidation_keys` when
+/// any query is keyed.
+fn invalidates_impl(name: &syn::Ident, queries: &[(String, Option<Expr>)]) -> proc_macro2::TokenStream {
+    let query_types = queries.iter().map(|(query_type, _)| query_type);
+    let keyed: Vec<_> = queries
+        .iter()
+        .filter_map(|(query_type, key)| key.as_ref().map(|key| quote! { (#query_type, (#key).to_string()) }))
+        .collect();
+
+    let keys_fn = (!keyed.is_empty()).then(|| {
+        quote! {
+            fn invalidation_keys(
+                &self,
+                response: &<Self as pl3xus_common::RequestMessage>::ResponseMessage,
+            ) -> Vec<(&'static str, String)>
+            where
+                Self: pl3xus_common::RequestMessage,
+            {
+                let _ = response;
+                vec![#(#keyed),*]
+            }
+        }
+    });
+
+    quote! {
         impl pl3xus_sync::Invalidates for #name {
             fn invalidates() -> &'static [&'static str] {
                 &[#(#query_types),*]
             }
-        }
-    };
 
-    expanded.into()
+            #keys_fn
+        }
+    }
 }
 
 // =============================================================================
@@ -259,3 +333,660 @@ pub fn derive_has_success(input: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+// =============================================================================
+// Pl3xusRequest Derive Macro
+// =============================================================================
+
+/// Derive macro that generates request registration from attributes.
+///
+/// This implements `pl3xus_sync::RegisterRequest`, so a request type can be
+/// registered with `T::register_request::<NP>(&mut app)` or in bulk with
+/// `pl3xus_sync::register_requests!`. Like `Invalidates`, this is a
+/// server-side derive; shared type crates should gate it behind their
+/// server feature with `cfg_attr`.
+///
+/// # Attributes
+///
+/// - `#[response(ResponseType)]`: also implement `pl3xus_common::RequestMessage`
+///   with the given response type. Omit this if `RequestMessage` is implemented
+///   manually (e.g. in a shared crate that is also compiled for the client).
+/// - `#[targeted]`: register as a targeted request (`TargetedRequest<T>`).
+/// - `#[targeted(default_policy)]`: targeted, using the `DefaultEntityAccessPolicy`.
+/// - `#[error_response]`: finish with `.with_error_response()` (requires `ErrorResponse`).
+/// - `#[invalidates("Query", ...)]`: also implement `pl3xus_sync::Invalidates`.
+///   Add `key = "self.field"` for keyed invalidation, as with `#[derive(Invalidates)]`.
+///   Do not combine with `#[derive(Invalidates)]`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Serialize, Deserialize, Clone, Debug, Pl3xusRequest)]
+/// #[response(CreateProgramResponse)]
+/// #[invalidates("ListPrograms")]
+/// pub struct CreateProgram { ... }
+///
+/// #[derive(Serialize, Deserialize, Clone, Debug, Pl3xusRequest)]
+/// #[targeted(default_policy)]
+/// #[error_response]
+/// pub struct SetSpeedOverride { ... }
+/// ```
+#[proc_macro_derive(Pl3xusRequest, attributes(response, targeted, error_response, invalidates))]
+pub fn derive_pl3xus_request(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let name = &ast.ident;
+
+    let mut response_type: Option<syn::Type> = None;
+    let mut targeted = false;
+    let mut default_policy = false;
+    let mut error_response = false;
+    let mut queries: Vec<(String, Option<Expr>)> = Vec::new();
+
+    for attr in &ast.attrs {
+        if attr.path().is_ident("response") {
+            match attr.parse_args::<syn::Type>() {
+                Ok(ty) => response_type = Some(ty),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else if attr.path().is_ident("targeted") {
+            targeted = true;
+            if let Meta::List(meta_list) = &attr.meta {
+                let result = meta_list.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("default_policy") {
+                        default_policy = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `default_policy`"))
+                    }
+                });
+                if let Err(e) = result {
+                    return e.to_compile_error().into();
+                }
+            }
+        } else if attr.path().is_ident("error_response") {
+            error_response = true;
+        } else if attr.path().is_ident("invalidates")
+            && let Err(e) = parse_invalidates(attr, &mut queries)
+        {
+            return e.to_compile_error().into();
+        }
+    }
+
+    let request_message_impl = response_type.map(|ty| {
+        quote! {
+            impl pl3xus_common::RequestMessage for #name {
+                type ResponseMessage = #ty;
+            }
+        }
+    });
+
+    let invalidates_impl = (!queries.is_empty()).then(|| invalidates_impl(name, &queries));
+
+    let targeted_call = targeted.then(|| quote! { let reg = reg.targeted(); });
+    let policy_call = default_policy.then(|| quote! { let reg = reg.with_default_entity_policy(); });
+    let finish_call = if error_response {
+        quote! { reg.with_error_response(); }
+    } else {
+        quote! { reg.register(); }
+    };
+
+    let expanded = quote! {
+        #request_message_impl
+
+        #invalidates_impl
+
+        impl pl3xus_sync::RegisterRequest for #name {
+            fn register_request<NP: pl3xus_sync::NetworkProvider>(app: &mut ::bevy::prelude::App) {
+                let reg = <::bevy::prelude::App as pl3xus_sync::AppRequestRegistrationExt>::request::<Self, NP>(app);
+                #targeted_call
+                #policy_call
+                #finish_call
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+// =============================================================================
+// SyncComponent Derive Macro
+// =============================================================================
+
+/// Derive macro that generates component sync registration from attributes.
+///
+/// This implements `pl3xus_sync::RegisterSyncComponent`, so the component can
+/// be registered with `T::register_sync::<NP>(app)` instead of a hand-written
+/// `sync_component_builder` chain.
+///
+/// # Attributes
+///
+/// All options go in one or more `#[sync(...)]` attributes:
+///
+/// - `read_only`: clients cannot mutate the component.
+/// - `denial_message = "..."`: custom message for rejected mutations.
+/// - `handler = path::to_system`: route mutations to a handler system.
+/// - `targeted`: mutations require entity-level authorization.
+/// - `default_policy`: use the `DefaultEntityAccessPolicy` (with `targeted`).
+/// - `rate_hz = 10`: broadcast changes at most this many times per second.
+/// - `field_policy`: enforce `#[sync_field(...)]` annotations (requires `#[derive(SyncFields)]`).
+/// - `validate`: check `#[validate(...)]` constraints on mutations (requires `#[derive(ValidateMutation)]`).
+/// - `undo`: record mutations so clients can undo and redo them.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Component, Serialize, Deserialize, Clone, Debug, SyncComponent)]
+/// #[sync(read_only, denial_message = "RobotStatus is read-only", rate_hz = 10)]
+/// pub struct RobotStatus { ... }
+///
+/// #[derive(Component, Serialize, Deserialize, Clone, Debug, SyncComponent)]
+/// #[sync(targeted, default_policy, handler = handle_jog_settings_mutation)]
+/// pub struct JogSettingsState { ... }
+/// ```
+#[proc_macro_derive(SyncComponent, attributes(sync))]
+pub fn derive_sync_component(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    sync_component_impl(&ast)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn sync_component_impl(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+
+    let mut read_only = false;
+    let mut denial_message: Option<syn::LitStr> = None;
+    let mut handler: Option<syn::Path> = None;
+    let mut targeted = false;
+    let mut default_policy = false;
+    let mut rate_hz: Option<syn::Lit> = None;
+    let mut field_policy = false;
+    let mut validate = false;
+    let mut undo = false;
+
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("sync") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("read_only") {
+                read_only = true;
+            } else if meta.path.is_ident("targeted") {
+                targeted = true;
+            } else if meta.path.is_ident("default_policy") {
+                default_policy = true;
+            } else if meta.path.is_ident("denial_message") {
+                denial_message = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("handler") {
+                handler = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("rate_hz") {
+                rate_hz = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("field_policy") {
+                field_policy = true;
+            } else if meta.path.is_ident("validate") {
+                validate = true;
+            } else if meta.path.is_ident("undo") {
+                undo = true;
+            } else {
+                return Err(meta.error(
+                    "expected one of `read_only`, `denial_message`, `handler`, `targeted`, `default_policy`, `rate_hz`, `field_policy`, `validate`, `undo`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+
+    if default_policy && !targeted {
+        return Err(syn::Error::new_spanned(name, "`default_policy` requires `targeted`"));
+    }
+
+    let read_only_call = read_only.then(|| quote! { let builder = builder.read_only(); });
+    let denial_call = denial_message.map(|msg| quote! { let builder = builder.with_denial_message(#msg); });
+    let handler_call = handler.map(|path| quote! { let builder = builder.with_handler::<NP, _, _>(#path); });
+    let targeted_call = targeted.then(|| quote! { let builder = builder.targeted(); });
+    let policy_call = default_policy.then(|| quote! { let builder = builder.with_default_entity_policy(); });
+    let rate_call = rate_hz.map(|lit| quote! { let builder = builder.rate_hz((#lit) as f32); });
+    let field_policy_call = field_policy.then(|| quote! { let builder = builder.with_field_policy(); });
+    let validate_call = validate.then(|| quote! { let builder = builder.validated(); });
+    let undo_call = undo.then(|| quote! { let builder = builder.with_undo(); });
+
+    Ok(quote! {
+        impl pl3xus_sync::RegisterSyncComponent for #name {
+            fn register_sync<NP: pl3xus_sync::NetworkProvider>(app: &mut ::bevy::prelude::App) {
+                let builder = <::bevy::prelude::App as pl3xus_sync::AppPl3xusSyncExt>::sync_component_builder::<Self>(app);
+                #read_only_call
+                #denial_call
+                #handler_call
+                #targeted_call
+                #policy_call
+                #rate_call
+                #field_policy_call
+                #validate_call
+                #undo_call
+                builder.build();
+            }
+        }
+    })
+}
+
+// =============================================================================
+// SyncFields Derive Macro
+// =============================================================================
+
+/// Derive macro for field-level sync policies.
+///
+/// Implements `pl3xus_sync::SyncFieldPolicy` from `#[sync_field(...)]`
+/// annotations on named fields. Enable enforcement with `.with_field_policy()`
+/// on the sync builder (or `#[sync(field_policy)]` with `SyncComponent`).
+///
+/// # Field Attributes
+///
+/// - `#[sync_field(read_only)]`: clients may not change this field
+///   (requires `PartialEq`).
+/// - `#[sync_field(redact)]`: the field is reset to its default before being
+///   sent to clients, and clients may not set it (requires `Default + PartialEq`).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Component, Serialize, Deserialize, Clone, Debug, SyncFields)]
+/// pub struct DriverState {
+///     pub speed_override: u32,
+///     #[sync_field(read_only)]
+///     pub connected: bool,
+///     #[sync_field(redact)]
+///     pub internal_error_code: i32,
+/// }
+/// ```
+#[proc_macro_derive(SyncFields, attributes(sync_field))]
+pub fn derive_sync_fields(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    sync_fields_impl(&ast)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn sync_fields_impl(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+
+    let fields = match &ast.data {
+        Data::Struct(data_struct) => match &data_struct.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(name, "SyncFields requires a struct with named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(name, "SyncFields can only be derived for structs")),
+    };
+
+    let mut read_only: Vec<(syn::Ident, syn::Type)> = Vec::new();
+    let mut redacted: Vec<(syn::Ident, syn::Type)> = Vec::new();
+
+    for field in fields {
+        let Some(ident) = field.ident.clone() else {
+            continue;
+        };
+        for attr in &field.attrs {
+            if !attr.path().is_ident("sync_field") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("read_only") {
+                    read_only.push((ident.clone(), field.ty.clone()));
+                    Ok(())
+                } else if meta.path.is_ident("redact") {
+                    redacted.push((ident.clone(), field.ty.clone()));
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `read_only` or `redact`"))
+                }
+            })?;
+        }
+    }
+
+    let redact_idents: Vec<_> = redacted.iter().map(|(i, _)| i).collect();
+    let redact_types: Vec<_> = redacted.iter().map(|(_, t)| t).collect();
+    let redact_names: Vec<String> = redacted.iter().map(|(i, _)| i.to_string()).collect();
+    let read_only_idents: Vec<_> = read_only.iter().map(|(i, _)| i).collect();
+    let read_only_names: Vec<String> = read_only.iter().map(|(i, _)| i.to_string()).collect();
+
+    Ok(quote! {
+        impl pl3xus_sync::SyncFieldPolicy for #name {
+            fn redacted(&self) -> Self {
+                #[allow(unused_mut)]
+                let mut value = ::core::clone::Clone::clone(self);
+                #( value.#redact_idents = <#redact_types as ::core::default::Default>::default(); )*
+                value
+            }
+
+            #[allow(unused_mut)]
+            fn merge_mutation(current: ::core::option::Option<&Self>, mut proposed: Self) -> ::core::result::Result<Self, ::std::string::String> {
+                #(
+                    if proposed.#redact_idents != <#redact_types as ::core::default::Default>::default() {
+                        return ::core::result::Result::Err(::std::format!("Field `{}` cannot be set by clients", #redact_names));
+                    }
+                )*
+                if let ::core::option::Option::Some(current) = current {
+                    #(
+                        if proposed.#read_only_idents != current.#read_only_idents {
+                            return ::core::result::Result::Err(::std::format!("Field `{}` is read-only", #read_only_names));
+                        }
+                    )*
+                    #( proposed.#redact_idents = ::core::clone::Clone::clone(&current.#redact_idents); )*
+                }
+                ::core::result::Result::Ok(proposed)
+            }
+        }
+    })
+}
+
+// =============================================================================
+// ValidateMutation Derive Macro
+// =============================================================================
+
+/// Derive macro for field constraints checked on client mutations.
+///
+/// Implements `pl3xus_sync::ValidateMutation` from `#[validate(...)]`
+/// annotations on named fields. Enable checking with `.validated()` on the
+/// sync builder (or `#[sync(validate)]` with `SyncComponent`).
+///
+/// # Field Attributes
+///
+/// - `#[validate(range(min = 0, max = 100))]`: numeric bounds, inclusive.
+///   Either bound may be omitted. NaN is always rejected.
+/// - `#[validate(length(min = 1, max = 32))]`: bounds on `.len()`, for strings
+///   and collections.
+///
+/// Both accept `message = "..."` to replace the generated error message.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Component, Serialize, Deserialize, Clone, Debug, ValidateMutation)]
+/// pub struct JogSettingsState {
+///     #[validate(range(min = 0.1, max = 100.0))]
+///     pub cartesian_jog_speed: f64,
+///     #[validate(range(min = 1, max = 100, message = "must be a percentage"))]
+///     pub speed_override: u32,
+///     #[validate(length(max = 32))]
+///     pub label: String,
+/// }
+/// ```
+#[proc_macro_derive(ValidateMutation, attributes(validate))]
+pub fn derive_validate_mutation(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    validate_mutation_impl(&ast)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn validate_mutation_impl(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+
+    let fields = match &ast.data {
+        Data::Struct(data_struct) => match &data_struct.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(name, "ValidateMutation requires a struct with named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(name, "ValidateMutation can only be derived for structs")),
+    };
+
+    let mut checks = Vec::new();
+
+    for field in fields {
+        let Some(ident) = field.ident.clone() else {
+            continue;
+        };
+        let ty = &field.ty;
+        let field_name = ident.to_string();
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("validate") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                let is_range = meta.path.is_ident("range");
+                if !is_range && !meta.path.is_ident("length") {
+                    return Err(meta.error("expected `range(...)` or `length(...)`"));
+                }
+
+                let mut min: Option<Expr> = None;
+                let mut max: Option<Expr> = None;
+                let mut message: Option<syn::LitStr> = None;
+                meta.parse_nested_meta(|bound| {
+                    if bound.path.is_ident("min") {
+                        min = Some(bound.value()?.parse()?);
+                    } else if bound.path.is_ident("max") {
+                        max = Some(bound.value()?.parse()?);
+                    } else if bound.path.is_ident("message") {
+                        message = Some(bound.value()?.parse()?);
+                    } else {
+                        return Err(bound.error("expected `min`, `max` or `message`"));
+                    }
+                    Ok(())
+                })?;
+                if min.is_none() && max.is_none() {
+                    return Err(meta.error("expected at least one of `min` or `max`"));
+                }
+
+                let (value, bound_ty, prefix) = if is_range {
+                    (quote! { self.#ident }, quote! { #ty }, "must be")
+                } else {
+                    (quote! { self.#ident.len() }, quote! { usize }, "length must be")
+                };
+                let within = match (&min, &max) {
+                    (Some(min), Some(max)) => quote! { #value >= (#min) as #bound_ty && #value <= (#max) as #bound_ty },
+                    (Some(min), None) => quote! { #value >= (#min) as #bound_ty },
+                    (None, Some(max)) => quote! { #value <= (#max) as #bound_ty },
+                    (None, None) => unreachable!(),
+                };
+                let error_message = match (message, &min, &max) {
+                    (Some(message), _, _) => quote! { ::std::string::String::from(#message) },
+                    (None, Some(min), Some(max)) => {
+                        let fmt = format!("{prefix} between {{}} and {{}}");
+                        quote! { ::std::format!(#fmt, #min, #max) }
+                    }
+                    (None, Some(min), None) => {
+                        let fmt = format!("{prefix} at least {{}}");
+                        quote! { ::std::format!(#fmt, #min) }
+                    }
+                    (None, None, Some(max)) => {
+                        let fmt = format!("{prefix} at most {{}}");
+                        quote! { ::std::format!(#fmt, #max) }
+                    }
+                    (None, None, None) => unreachable!(),
+                };
+
+                // Written as a negated "within bounds" check so NaN fails.
+                checks.push(quote! {
+                    if !(#within) {
+                        errors.push(pl3xus_sync::FieldError::new(#field_name, #error_message));
+                    }
+                });
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(quote! {
+        impl pl3xus_sync::ValidateMutation for #name {
+            #[allow(clippy::unnecessary_cast, clippy::nonminimal_bool, clippy::neg_cmp_op_on_partial_ord)]
+            fn validate(&self) -> ::core::result::Result<(), ::std::vec::Vec<pl3xus_sync::FieldError>> {
+                #[allow(unused_mut)]
+                let mut errors = ::std::vec::Vec::new();
+                #( #checks )*
+                if errors.is_empty() {
+                    ::core::result::Result::Ok(())
+                } else {
+                    ::core::result::Result::Err(errors)
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(attr: syn::Attribute) -> syn::Result<Vec<(String, Option<Expr>)>> {
+        let mut queries = Vec::new();
+        parse_invalidates(&attr, &mut queries).map(|()| queries)
+    }
+
+    fn error(attr: syn::Attribute) -> String {
+        parse(attr).err().expect("expected an error").to_string()
+    }
+
+    #[test]
+    fn test_parse_invalidates() {
+        let queries = parse(syn::parse_quote!(#[invalidates("ListPrograms", "GetProgram", key = "self.id")])).unwrap();
+        let names: Vec<_> = queries.iter().map(|(query, _)| query.as_str()).collect();
+        assert_eq!(names, ["ListPrograms", "GetProgram"]);
+        assert!(queries.iter().all(|(_, key)| key.is_some()));
+
+        let expanded = invalidates_impl(&syn::parse_quote!(CreateProgram), &queries).to_string();
+        assert!(expanded.contains("invalidation_keys"), "{expanded}");
+    }
+
+    #[test]
+    fn test_invalid_invalidates_entries_are_errors() {
+        assert!(error(syn::parse_quote!(#[invalidates(ListPrograms)])).contains("string literal"));
+        assert!(error(syn::parse_quote!(#[invalidates("ListPrograms", 1)])).contains("string literal"));
+        assert!(error(syn::parse_quote!(#[invalidates("ListPrograms", id = "self.id")])).contains("key = "));
+        assert!(error(syn::parse_quote!(#[invalidates("ListPrograms", key = 1)])).contains("key = "));
+        // Keys must parse as expressions
+        assert!(parse(syn::parse_quote!(#[invalidates("ListPrograms", key = "self.")])).is_err());
+        // Arguments that aren't expressions at all
+        assert!(parse(syn::parse_quote!(#[invalidates("ListPrograms" struct)])).is_err());
+        assert!(error(syn::parse_quote!(#[invalidates])).contains("expected `#[invalidates"));
+        assert!(error(syn::parse_quote!(#[invalidates(key = "self.id")])).contains("at least one query type"));
+    }
+
+    fn sync_component(ast: DeriveInput) -> syn::Result<String> {
+        sync_component_impl(&ast).map(|expanded| expanded.to_string())
+    }
+
+    #[test]
+    fn test_sync_component_attributes_become_builder_calls() {
+        let expanded = sync_component(syn::parse_quote! {
+            #[sync(read_only, denial_message = "RobotStatus is read-only")]
+            #[sync(rate_hz = 10)]
+            struct RobotStatus;
+        })
+        .unwrap();
+        assert!(expanded.contains("impl pl3xus_sync :: RegisterSyncComponent for RobotStatus"), "{expanded}");
+        assert!(expanded.contains("builder . read_only ()"), "{expanded}");
+        assert!(expanded.contains("with_denial_message (\"RobotStatus is read-only\")"), "{expanded}");
+        assert!(expanded.contains("rate_hz ((10) as f32)"), "{expanded}");
+        assert!(!expanded.contains("targeted"), "{expanded}");
+
+        let expanded = sync_component(syn::parse_quote! {
+            #[sync(targeted, default_policy, handler = handle_jog_settings_mutation)]
+            struct JogSettingsState;
+        })
+        .unwrap();
+        assert!(expanded.contains("with_handler :: < NP , _ , _ > (handle_jog_settings_mutation)"), "{expanded}");
+        assert!(expanded.contains("builder . targeted ()"), "{expanded}");
+        assert!(expanded.contains("with_default_entity_policy ()"), "{expanded}");
+        assert!(!expanded.contains("read_only"), "{expanded}");
+    }
+
+    #[test]
+    fn test_invalid_sync_attributes_are_errors() {
+        let error = sync_component(syn::parse_quote! {
+            #[sync(read_only, rate = 10)]
+            struct RobotStatus;
+        })
+        .err()
+        .expect("expected an error");
+        assert!(error.to_string().contains("expected one of"), "{error}");
+
+        let error = sync_component(syn::parse_quote! {
+            #[sync(default_policy)]
+            struct JogSettingsState;
+        })
+        .err()
+        .expect("expected an error");
+        assert!(error.to_string().contains("requires `targeted`"), "{error}");
+
+        assert!(sync_component(syn::parse_quote! {
+            #[sync(denial_message = 1)]
+            struct RobotStatus;
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_sync_fields_expansion() {
+        let expanded = sync_fields_impl(&syn::parse_quote! {
+            struct DriverState {
+                speed_override: u32,
+                #[sync_field(read_only)]
+                connected: bool,
+                #[sync_field(redact)]
+                internal_error_code: i32,
+            }
+        })
+        .unwrap()
+        .to_string();
+        assert!(expanded.contains("value . internal_error_code = < i32 as :: core :: default :: Default > :: default ()"), "{expanded}");
+        assert!(expanded.contains("proposed . connected != current . connected"), "{expanded}");
+        assert!(expanded.contains("\"connected\""), "{expanded}");
+        assert!(!expanded.contains("speed_override"), "{expanded}");
+
+        let error = |ast: DeriveInput| sync_fields_impl(&ast).err().expect("expected an error").to_string();
+        assert!(error(syn::parse_quote!(struct Pose(f64, f64);)).contains("named fields"));
+        assert!(error(syn::parse_quote!(enum Mode { Jog })).contains("only be derived for structs"));
+        assert!(error(syn::parse_quote! {
+            struct DriverState {
+                #[sync_field(hidden)]
+                internal_error_code: i32,
+            }
+        })
+        .contains("expected `read_only` or `redact`"));
+    }
+
+    #[test]
+    fn test_validate_mutation_expansion() {
+        let expanded = validate_mutation_impl(&syn::parse_quote! {
+            struct JogSettingsState {
+                #[validate(range(min = 0.1, max = 100.0))]
+                cartesian_jog_speed: f64,
+                #[validate(range(min = 1, max = 100, message = "must be a percentage"))]
+                speed_override: u32,
+                #[validate(length(max = 32))]
+                label: String,
+            }
+        })
+        .unwrap()
+        .to_string();
+        assert!(expanded.contains("self . cartesian_jog_speed >= (0.1) as f64 && self . cartesian_jog_speed <= (100.0) as f64"), "{expanded}");
+        assert!(expanded.contains("\"must be between {} and {}\" , 0.1 , 100.0"), "{expanded}");
+        assert!(expanded.contains(":: std :: string :: String :: from (\"must be a percentage\")"), "{expanded}");
+        assert!(expanded.contains("self . label . len () <= (32) as usize"), "{expanded}");
+        assert!(expanded.contains("\"length must be at most {}\""), "{expanded}");
+
+        let error = |ast: DeriveInput| validate_mutation_impl(&ast).err().expect("expected an error").to_string();
+        assert!(error(syn::parse_quote! {
+            struct Jog {
+                #[validate(range())]
+                speed: f64,
+            }
+        })
+        .contains("at least one of `min` or `max`"));
+        assert!(error(syn::parse_quote! {
+            struct Jog {
+                #[validate(range(min = 0, step = 1))]
+                speed: f64,
+            }
+        })
+        .contains("expected `min`, `max` or `message`"));
+        assert!(error(syn::parse_quote! {
+            struct Jog {
+                #[validate(pattern = "[a-z]+")]
+                label: String,
+            }
+        })
+        .contains("expected `range(...)` or `length(...)`"));
+        assert!(error(syn::parse_quote!(struct Jog(f64);)).contains("named fields"));
+    }
+}
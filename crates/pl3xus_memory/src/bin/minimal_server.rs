@@ -87,7 +87,7 @@ fn handle_connection_events(
 ) {
     for event in network_events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 println!("Client connected: {}", conn_id);
 
                 // Spawn an entity to track this client
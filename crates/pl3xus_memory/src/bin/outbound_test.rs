@@ -75,7 +75,7 @@ fn log_events(
 ) {
     for event in events.read() {
         match event {
-            NetworkEvent::Connected(id) => println!("Client connected: {}", id),
+            NetworkEvent::Connected(id, _) => println!("Client connected: {}", id),
             NetworkEvent::Disconnected(id) => println!("Client disconnected: {}", id),
             NetworkEvent::Error(err) => println!("Network error: {:?}", err),
         }
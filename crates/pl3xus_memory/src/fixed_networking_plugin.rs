@@ -139,10 +139,10 @@ pub fn handle_new_connection_events(
 ) {
     for event in network_events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 let client = Client {
                     id: *conn_id,
-                    in_control: (conn_id.id == 0),
+                    in_control: conn_id.is_server(),
                 };
 
                 // Spawn the new client entity
@@ -9,6 +9,8 @@
 //! - Connection cleanup
 //! - Message queue monitoring
 //! - Resource cleanup
+//! - Memory budgets with configurable eviction
+//! - `pl3xus_sync` leak detection (subscriptions and queues of disconnected clients)
 //!
 //! ## Usage
 //!
@@ -25,16 +27,22 @@
 //! ```
 
 mod connection_cleanup;
+mod memory_budget;
 mod memory_diagnostic;
 mod memory_monitor;
 mod message_cleanup;
 mod plugin;
+#[cfg(feature = "sync")]
+mod sync_leak_detection;
 
 pub use connection_cleanup::*;
+pub use memory_budget::*;
 pub use memory_diagnostic::*;
 pub use memory_monitor::*;
 pub use message_cleanup::*;
 pub use plugin::*;
+#[cfg(feature = "sync")]
+pub use sync_leak_detection::*;
 
 /// Re-export the main plugin
 pub use plugin::NetworkMemoryPlugin;
@@ -0,0 +1,493 @@
+//! Memory budget enforcement.
+//!
+//! An application inserts a [`MemoryBudget`] with limits for the buffers it
+//! cares about. When a buffer exceeds its limit, entries are evicted according
+//! to the configured [`EvictionPolicy`] and operators are told about it with a
+//! `ServerNotification` (category `"memory"`), rate limited to one per category
+//! per `notification_interval`.
+//!
+//! Built-in enforcement (with the `sync` feature):
+//! - [`BudgetCategory::MessageQueue`]: pending sync items per connection in
+//!   `pl3xus_sync::ConflationQueue`.
+//! - [`BudgetCategory::ReplayBuffer`]: unacknowledged notifications in
+//!   `pl3xus_sync::notifications::PendingNotifications`.
+//!
+//! Any other buffer (e.g. an application's console history) can be brought
+//! under the budget by implementing [`BudgetedBuffer`] for its resource:
+//!
+//! ```rust,ignore
+//! impl BudgetedBuffer for ConsoleHistory {
+//!     fn budget_len(&self) -> usize {
+//!         self.entries.len()
+//!     }
+//!
+//!     fn evict(&mut self, count: usize, policy: EvictionPolicy) -> usize {
+//!         match policy {
+//!             EvictionPolicy::DropOldest => self.entries.drain(..count).count(),
+//!             EvictionPolicy::DropNewest => { /* ... */ }
+//!             EvictionPolicy::Clear => { /* ... */ }
+//!         }
+//!     }
+//! }
+//!
+//! app.insert_resource(MemoryBudget::default().console_history(BudgetLimit::new(5_000)))
+//!     .budget_buffer::<ConsoleHistory>(BudgetCategory::ConsoleHistory);
+//! ```
+
+use bevy::prelude::*;
+use pl3xus::managers::NetworkProvider;
+use pl3xus::Network;
+use pl3xus_common::ServerNotification;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Notification category used for budget diagnostics.
+pub const MEMORY_NOTIFICATION_CATEGORY: &str = "memory";
+
+/// What to drop when a buffer exceeds its budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Drop the oldest entries until the buffer fits.
+    #[default]
+    DropOldest,
+    /// Drop the newest entries until the buffer fits.
+    DropNewest,
+    /// Drop everything in the buffer.
+    Clear,
+}
+
+/// Buffers that can be placed under a [`MemoryBudget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BudgetCategory {
+    /// Sync items waiting to be sent to a connection.
+    MessageQueue,
+    /// Notifications kept until acknowledged, for redelivery.
+    ReplayBuffer,
+    /// An application's console log history.
+    ConsoleHistory,
+}
+
+impl BudgetCategory {
+    /// Name used in logs and notifications.
+    pub fn label(&self) -> &'static str {
+        match self {
+            BudgetCategory::MessageQueue => "message queue",
+            BudgetCategory::ReplayBuffer => "replay buffer",
+            BudgetCategory::ConsoleHistory => "console history",
+        }
+    }
+}
+
+/// Limit for one buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetLimit {
+    /// Most entries the buffer may hold.
+    pub max_entries: usize,
+    /// What to drop once it holds more.
+    pub policy: EvictionPolicy,
+}
+
+impl BudgetLimit {
+    /// Limit to `max_entries`, dropping the oldest entries past it.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            policy: EvictionPolicy::default(),
+        }
+    }
+
+    /// Use `policy` instead of dropping the oldest entries.
+    pub fn with_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+/// Memory limits set by the application. Buffers without a limit are not enforced.
+#[derive(Resource)]
+pub struct MemoryBudget {
+    /// Limit on pending sync items per connection.
+    pub message_queue: Option<BudgetLimit>,
+    /// Limit on unacknowledged notifications kept for redelivery.
+    pub replay_buffer: Option<BudgetLimit>,
+    /// Limit on console history entries.
+    pub console_history: Option<BudgetLimit>,
+    /// Minimum time between two diagnostic notifications for the same category.
+    pub notification_interval: Duration,
+    /// Send diagnostic notifications to clients (evictions are always logged).
+    pub notify_clients: bool,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self {
+            message_queue: None,
+            replay_buffer: None,
+            console_history: None,
+            notification_interval: Duration::from_secs(10),
+            notify_clients: true,
+        }
+    }
+}
+
+impl MemoryBudget {
+    /// Limit pending sync items per connection.
+    pub fn message_queue(mut self, limit: BudgetLimit) -> Self {
+        self.message_queue = Some(limit);
+        self
+    }
+
+    /// Limit unacknowledged notifications kept for redelivery.
+    pub fn replay_buffer(mut self, limit: BudgetLimit) -> Self {
+        self.replay_buffer = Some(limit);
+        self
+    }
+
+    /// Limit console history entries.
+    pub fn console_history(mut self, limit: BudgetLimit) -> Self {
+        self.console_history = Some(limit);
+        self
+    }
+
+    /// The limit for `category`, if it has one.
+    pub fn limit(&self, category: BudgetCategory) -> Option<BudgetLimit> {
+        match category {
+            BudgetCategory::MessageQueue => self.message_queue,
+            BudgetCategory::ReplayBuffer => self.replay_buffer,
+            BudgetCategory::ConsoleHistory => self.console_history,
+        }
+    }
+}
+
+/// Evictions performed since the last diagnostic notification.
+#[derive(Resource, Default, Debug)]
+pub struct MemoryBudgetEvictions {
+    pending: HashMap<BudgetCategory, usize>,
+    last_notified: HashMap<BudgetCategory, Instant>,
+    /// Total entries evicted per category since startup.
+    pub totals: HashMap<BudgetCategory, usize>,
+}
+
+impl MemoryBudgetEvictions {
+    /// Count `evicted` entries dropped from `category`, to be reported by
+    /// [`notify_budget_evictions`].
+    pub fn record(&mut self, category: BudgetCategory, evicted: usize) {
+        if evicted == 0 {
+            return;
+        }
+        *self.pending.entry(category).or_default() += evicted;
+        *self.totals.entry(category).or_default() += evicted;
+    }
+}
+
+/// A resource whose size can be enforced by [`MemoryBudget`].
+pub trait BudgetedBuffer: Resource {
+    /// Number of entries currently held.
+    fn budget_len(&self) -> usize;
+
+    /// Evict `count` entries according to `policy`, returning how many were removed.
+    fn evict(&mut self, count: usize, policy: EvictionPolicy) -> usize;
+}
+
+/// Enforce the budget for a [`BudgetedBuffer`] resource.
+pub fn enforce_buffer_budget<R: BudgetedBuffer>(
+    category: BudgetCategory,
+) -> impl FnMut(Res<MemoryBudget>, Option<ResMut<R>>, ResMut<MemoryBudgetEvictions>) {
+    move |budget, buffer, mut evictions| {
+        let (Some(limit), Some(mut buffer)) = (budget.limit(category), buffer) else {
+            return;
+        };
+        let len = buffer.budget_len();
+        if len <= limit.max_entries {
+            return;
+        }
+        let evicted = buffer.evict(len - limit.max_entries, limit.policy);
+        evictions.record(category, evicted);
+    }
+}
+
+/// Order of the budget systems in `PostUpdate`.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryBudgetSet {
+    /// Buffers over their limit are trimmed.
+    Enforce,
+    /// Evictions are logged and reported to clients.
+    Notify,
+}
+
+/// Extension trait for putting application buffers under the [`MemoryBudget`].
+pub trait AppMemoryBudgetExt {
+    /// Enforce the [`MemoryBudget`] limit for `category` on resource `R`.
+    fn budget_buffer<R: BudgetedBuffer>(&mut self, category: BudgetCategory) -> &mut Self;
+}
+
+impl AppMemoryBudgetExt for App {
+    fn budget_buffer<R: BudgetedBuffer>(&mut self, category: BudgetCategory) -> &mut Self {
+        self.add_systems(
+            PostUpdate,
+            enforce_buffer_budget::<R>(category).in_set(MemoryBudgetSet::Enforce),
+        )
+    }
+}
+
+#[cfg(feature = "sync")]
+impl BudgetedBuffer for pl3xus_sync::notifications::PendingNotifications {
+    fn budget_len(&self) -> usize {
+        self.len()
+    }
+
+    fn evict(&mut self, count: usize, policy: EvictionPolicy) -> usize {
+        match policy {
+            EvictionPolicy::DropOldest => self.evict_oldest(count),
+            EvictionPolicy::DropNewest => self.evict_newest(count),
+            EvictionPolicy::Clear => {
+                let len = self.len();
+                self.clear();
+                len
+            }
+        }
+    }
+}
+
+/// Enforce the per-connection message queue limit on the sync conflation queue.
+///
+/// Ordered (non-conflatable) items are evicted first according to the policy;
+/// conflated updates have no ordering and are evicted in arbitrary order.
+#[cfg(feature = "sync")]
+pub fn enforce_message_queue_budget(
+    budget: Res<MemoryBudget>,
+    queue: Option<ResMut<pl3xus_sync::ConflationQueue>>,
+    mut evictions: ResMut<MemoryBudgetEvictions>,
+) {
+    let (Some(limit), Some(mut queue)) = (budget.message_queue, queue) else {
+        return;
+    };
+
+    let connections: Vec<_> = queue
+        .pending
+        .keys()
+        .chain(queue.non_conflatable.keys())
+        .copied()
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    for connection_id in connections {
+        let count = queue.pending_count(connection_id);
+        if count <= limit.max_entries {
+            continue;
+        }
+
+        if limit.policy == EvictionPolicy::Clear {
+            queue.drain_for_connection(connection_id);
+            evictions.record(BudgetCategory::MessageQueue, count);
+            continue;
+        }
+
+        let mut excess = count - limit.max_entries;
+        if let Some(ordered) = queue.non_conflatable.get_mut(&connection_id) {
+            let n = excess.min(ordered.len());
+            match limit.policy {
+                EvictionPolicy::DropNewest => ordered.truncate(ordered.len() - n),
+                _ => {
+                    ordered.drain(..n);
+                }
+            }
+            excess -= n;
+        }
+        if excess > 0
+            && let Some(conflated) = queue.pending.get_mut(&connection_id)
+        {
+            let keys: Vec<_> = conflated.keys().take(excess).cloned().collect();
+            for key in keys {
+                conflated.remove(&key);
+            }
+        }
+
+        evictions.record(
+            BudgetCategory::MessageQueue,
+            count - queue.pending_count(connection_id),
+        );
+    }
+}
+
+/// Log evictions and notify clients, at most once per category per interval.
+pub fn notify_budget_evictions<NP: NetworkProvider>(
+    budget: Res<MemoryBudget>,
+    mut evictions: ResMut<MemoryBudgetEvictions>,
+    network: Option<Res<Network<NP>>>,
+) {
+    if evictions.pending.is_empty() {
+        return;
+    }
+
+    let interval = budget.notification_interval;
+    let due: Vec<BudgetCategory> = evictions
+        .pending
+        .keys()
+        .filter(|category| {
+            evictions
+                .last_notified
+                .get(category)
+                .is_none_or(|last| last.elapsed() >= interval)
+        })
+        .copied()
+        .collect();
+
+    for category in due {
+        let Some(evicted) = evictions.pending.remove(&category) else {
+            continue;
+        };
+        evictions.last_notified.insert(category, Instant::now());
+
+        let message = format!(
+            "Memory budget exceeded: dropped {} {} entries",
+            evicted,
+            category.label()
+        );
+        warn!("{}", message);
+
+        if budget.notify_clients
+            && let Some(network) = network.as_ref()
+        {
+            network.broadcast(
+                ServerNotification::warning(message).with_category(MEMORY_NOTIFICATION_CATEGORY),
+            );
+        }
+    }
+}
+
+/// Add the budget resources and systems, notifying clients over `NP`.
+pub fn register_memory_budget_plugin<NP: NetworkProvider>(app: &mut App) {
+    app.init_resource::<MemoryBudget>()
+        .init_resource::<MemoryBudgetEvictions>()
+        .configure_sets(
+            PostUpdate,
+            (MemoryBudgetSet::Enforce, MemoryBudgetSet::Notify).chain(),
+        )
+        .add_systems(
+            PostUpdate,
+            notify_budget_evictions::<NP>.in_set(MemoryBudgetSet::Notify),
+        );
+
+    #[cfg(feature = "sync")]
+    {
+        app.add_systems(
+            PostUpdate,
+            enforce_message_queue_budget.in_set(MemoryBudgetSet::Enforce),
+        );
+        app.budget_buffer::<pl3xus_sync::notifications::PendingNotifications>(
+            BudgetCategory::ReplayBuffer,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_websockets::WebSocketProvider;
+    use std::collections::VecDeque;
+
+    #[derive(Resource, Default)]
+    struct ConsoleHistory(VecDeque<u32>);
+
+    impl BudgetedBuffer for ConsoleHistory {
+        fn budget_len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn evict(&mut self, count: usize, policy: EvictionPolicy) -> usize {
+            match policy {
+                EvictionPolicy::DropOldest => self.0.drain(..count).count(),
+                EvictionPolicy::DropNewest => self.0.drain(self.0.len() - count..).count(),
+                EvictionPolicy::Clear => self.0.drain(..).count(),
+            }
+        }
+    }
+
+    fn app_with_history(limit: BudgetLimit) -> App {
+        let mut app = App::new();
+        register_memory_budget_plugin::<WebSocketProvider>(&mut app);
+        app.insert_resource(MemoryBudget::default().console_history(limit))
+            .insert_resource(ConsoleHistory((0..10).collect()))
+            .budget_buffer::<ConsoleHistory>(BudgetCategory::ConsoleHistory);
+        app
+    }
+
+    fn history(app: &App) -> Vec<u32> {
+        app.world().resource::<ConsoleHistory>().0.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_buffer_trimmed_by_policy() {
+        let mut app = app_with_history(BudgetLimit::new(4));
+        app.update();
+        assert_eq!(history(&app), [6, 7, 8, 9]);
+
+        let mut app = app_with_history(BudgetLimit::new(4).with_policy(EvictionPolicy::DropNewest));
+        app.update();
+        assert_eq!(history(&app), [0, 1, 2, 3]);
+
+        let mut app = app_with_history(BudgetLimit::new(4).with_policy(EvictionPolicy::Clear));
+        app.update();
+        assert!(history(&app).is_empty());
+    }
+
+    #[test]
+    fn test_evictions_reported_once_per_interval() {
+        let mut app = app_with_history(BudgetLimit::new(4));
+        app.update();
+        let evictions = app.world().resource::<MemoryBudgetEvictions>();
+        assert_eq!(evictions.totals.get(&BudgetCategory::ConsoleHistory), Some(&6));
+        assert!(evictions.pending.is_empty());
+
+        // Evicted again within the interval: counted, but held back
+        app.world_mut().resource_mut::<ConsoleHistory>().0.extend(10..12);
+        app.update();
+        let evictions = app.world().resource::<MemoryBudgetEvictions>();
+        assert_eq!(evictions.totals.get(&BudgetCategory::ConsoleHistory), Some(&8));
+        assert_eq!(evictions.pending.get(&BudgetCategory::ConsoleHistory), Some(&2));
+    }
+
+    #[test]
+    fn test_within_budget_untouched() {
+        let mut app = app_with_history(BudgetLimit::new(10));
+        app.update();
+        assert_eq!(history(&app).len(), 10);
+        assert!(app.world().resource::<MemoryBudgetEvictions>().totals.is_empty());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_message_queue_limited_per_connection() {
+        use pl3xus_common::ConnectionId;
+        use pl3xus_sync::{ConflationQueue, SerializableEntity, SyncItem};
+
+        let mut app = App::new();
+        register_memory_budget_plugin::<WebSocketProvider>(&mut app);
+        app.insert_resource(MemoryBudget::default().message_queue(BudgetLimit::new(2)));
+        let mut queue = ConflationQueue::new(30.0);
+        let busy = ConnectionId { id: 1 };
+        let quiet = ConnectionId { id: 2 };
+        for bits in 0..5 {
+            let entity = SerializableEntity { bits };
+            queue.enqueue(busy, SyncItem::EntityRemoved { subscription_id: 1, entity }, true);
+        }
+        queue.enqueue(
+            quiet,
+            SyncItem::EntityRemoved {
+                subscription_id: 1,
+                entity: SerializableEntity { bits: 0 },
+            },
+            true,
+        );
+        app.insert_resource(queue);
+        app.update();
+
+        let queue = app.world().resource::<ConflationQueue>();
+        assert_eq!(queue.pending_count(busy), 2);
+        assert_eq!(queue.pending_count(quiet), 1);
+        let evictions = app.world().resource::<MemoryBudgetEvictions>();
+        assert_eq!(evictions.totals.get(&BudgetCategory::MessageQueue), Some(&3));
+    }
+}
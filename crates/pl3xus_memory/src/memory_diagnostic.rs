@@ -80,7 +80,7 @@ pub fn monitor_memory_usage(
 pub fn monitor_network_events(mut network_events: MessageReader<NetworkEvent>) {
     for event in network_events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 println!("Connection established: {:?}", conn_id);
             }
             NetworkEvent::Disconnected(conn_id) => {
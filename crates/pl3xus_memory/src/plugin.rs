@@ -1,14 +1,19 @@
 use bevy::prelude::*;
+use pl3xus_websockets::WebSocketProvider;
 
 use crate::connection_cleanup::*;
+use crate::memory_budget::*;
 use crate::memory_diagnostic::*;
 use crate::memory_monitor::*;
 use crate::message_cleanup::*;
+#[cfg(feature = "sync")]
+use crate::sync_leak_detection::*;
 
 /// A Bevy plugin that provides memory leak detection and prevention for pl3xus.
 ///
 /// This plugin adds systems to monitor memory usage, clean up stale connections,
-/// and prevent message queue accumulation.
+/// and prevent message queue accumulation. With the `sync` feature it also
+/// watches `pl3xus_sync` state for entries left behind by disconnected clients.
 pub struct NetworkMemoryPlugin;
 
 impl Plugin for NetworkMemoryPlugin {
@@ -18,6 +23,9 @@ impl Plugin for NetworkMemoryPlugin {
         register_connection_cleanup_plugin(app);
         register_message_cleanup_plugin(app);
         register_memory_monitor_plugin(app);
+        register_memory_budget_plugin::<WebSocketProvider>(app);
+        #[cfg(feature = "sync")]
+        register_sync_leak_detection_plugin::<WebSocketProvider>(app);
 
         // Add the force GC system
         app.add_systems(Update, force_gc);
@@ -0,0 +1,253 @@
+//! Leak detection for `pl3xus_sync` internals.
+//!
+//! Tracks the size of the sync registry maps, queues and live query caches per
+//! connection, warns when entries belonging to clients that are no longer
+//! connected are still around, and removes them when a
+//! `NetworkEvent::Disconnected` arrives.
+
+use bevy::prelude::*;
+use pl3xus::managers::NetworkProvider;
+use pl3xus::{Network, NetworkEvent};
+use pl3xus_common::ConnectionId;
+use pl3xus_sync::{
+    ConflationQueue, LiveQueryCacheSizes, MutationQueue, MutationResponseQueue, SnapshotQueue,
+    SubscriptionManager,
+};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Configuration for sync leak detection.
+#[derive(Resource)]
+pub struct SyncLeakConfig {
+    pub check_interval: Duration,
+    pub last_check: Instant,
+    /// Warn when the total number of tracked entries grows by more than this
+    /// fraction between two checks.
+    pub growth_warning_threshold: f64,
+    /// Remove entries for disconnected clients found during a check, in
+    /// addition to the cleanup done on `NetworkEvent::Disconnected`.
+    pub purge_stale_entries: bool,
+}
+
+impl Default for SyncLeakConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(10),
+            last_check: Instant::now(),
+            growth_warning_threshold: 0.5,
+            purge_stale_entries: true,
+        }
+    }
+}
+
+/// Sync state held for a single connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncConnectionUsage {
+    pub subscriptions: usize,
+    pub conflation_pending: usize,
+    pub snapshot_pending: usize,
+    pub mutation_pending: usize,
+    pub mutation_responses_pending: usize,
+    /// Rows cached on the server for the connection's live queries.
+    pub live_query_rows: usize,
+}
+
+impl SyncConnectionUsage {
+    pub fn total(&self) -> usize {
+        self.subscriptions
+            + self.conflation_pending
+            + self.snapshot_pending
+            + self.mutation_pending
+            + self.mutation_responses_pending
+            + self.live_query_rows
+    }
+}
+
+/// Latest per-connection sync usage, refreshed every check interval.
+#[derive(Resource, Default, Debug)]
+pub struct SyncLeakStats {
+    pub per_connection: HashMap<ConnectionId, SyncConnectionUsage>,
+    /// Connections that had sync state but were no longer connected at the last check.
+    pub stale_connections: Vec<ConnectionId>,
+    pub last_total: usize,
+}
+
+/// The `pl3xus_sync` resources inspected and cleaned up by this module.
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct SyncState<'w> {
+    subscriptions: Option<ResMut<'w, SubscriptionManager>>,
+    conflation: Option<ResMut<'w, ConflationQueue>>,
+    snapshots: Option<ResMut<'w, SnapshotQueue>>,
+    mutations: Option<ResMut<'w, MutationQueue>>,
+    responses: Option<ResMut<'w, MutationResponseQueue>>,
+    live_queries: Option<ResMut<'w, LiveQueryCacheSizes>>,
+}
+
+impl SyncState<'_> {
+    fn usage(&self) -> HashMap<ConnectionId, SyncConnectionUsage> {
+        let mut usage: HashMap<ConnectionId, SyncConnectionUsage> = HashMap::new();
+
+        if let Some(subscriptions) = self.subscriptions.as_ref() {
+            for sub in &subscriptions.subscriptions {
+                usage.entry(sub.connection_id).or_default().subscriptions += 1;
+            }
+        }
+        if let Some(conflation) = self.conflation.as_ref() {
+            let connections: HashSet<ConnectionId> = conflation
+                .pending
+                .keys()
+                .chain(conflation.non_conflatable.keys())
+                .copied()
+                .collect();
+            for connection_id in connections {
+                usage.entry(connection_id).or_default().conflation_pending =
+                    conflation.pending_count(connection_id);
+            }
+        }
+        if let Some(snapshots) = self.snapshots.as_ref() {
+            for request in &snapshots.pending {
+                usage.entry(request.connection_id).or_default().snapshot_pending += 1;
+            }
+        }
+        if let Some(mutations) = self.mutations.as_ref() {
+            for mutation in &mutations.pending {
+                usage.entry(mutation.connection_id).or_default().mutation_pending += 1;
+            }
+        }
+        if let Some(responses) = self.responses.as_ref() {
+            for response in &responses.pending {
+                usage
+                    .entry(response.connection_id)
+                    .or_default()
+                    .mutation_responses_pending += 1;
+            }
+        }
+        if let Some(live_queries) = self.live_queries.as_ref() {
+            for (connection_id, rows) in live_queries.per_connection() {
+                usage.entry(connection_id).or_default().live_query_rows = rows;
+            }
+        }
+
+        usage
+    }
+
+    /// Remove every entry belonging to `connection_id`, returning how many were removed.
+    fn remove_connection(&mut self, connection_id: ConnectionId) -> usize {
+        let mut removed = 0;
+
+        if let Some(subscriptions) = self.subscriptions.as_mut() {
+            let before = subscriptions.subscriptions.len();
+            subscriptions.remove_all_for_connection(connection_id);
+            removed += before - subscriptions.subscriptions.len();
+        }
+        if let Some(conflation) = self.conflation.as_mut() {
+            removed += conflation.pending_count(connection_id);
+            conflation.pending.remove(&connection_id);
+            conflation.non_conflatable.remove(&connection_id);
+        }
+        if let Some(snapshots) = self.snapshots.as_mut() {
+            let before = snapshots.pending.len();
+            snapshots.pending.retain(|r| r.connection_id != connection_id);
+            removed += before - snapshots.pending.len();
+        }
+        if let Some(mutations) = self.mutations.as_mut() {
+            let before = mutations.pending.len();
+            mutations.pending.retain(|m| m.connection_id != connection_id);
+            removed += before - mutations.pending.len();
+        }
+        if let Some(responses) = self.responses.as_mut() {
+            let before = responses.pending.len();
+            responses.pending.retain(|r| r.connection_id != connection_id);
+            removed += before - responses.pending.len();
+        }
+        if let Some(live_queries) = self.live_queries.as_mut() {
+            // Dropped the next time the live queries run
+            removed += live_queries
+                .per_connection()
+                .get(&connection_id)
+                .copied()
+                .unwrap_or_default();
+            live_queries.evict(connection_id);
+        }
+
+        removed
+    }
+}
+
+/// Remove sync state for clients as soon as they disconnect.
+pub fn cleanup_disconnected_sync_state(
+    mut events: MessageReader<NetworkEvent>,
+    mut state: SyncState,
+) {
+    for event in events.read() {
+        if let NetworkEvent::Disconnected(connection_id) = event {
+            let removed = state.remove_connection(*connection_id);
+            if removed > 0 {
+                debug!(
+                    "Removed {} sync entries for disconnected connection {:?}",
+                    removed, connection_id
+                );
+            }
+        }
+    }
+}
+
+/// Periodically measure sync state per connection and warn about leaks.
+pub fn detect_sync_leaks<NP: NetworkProvider>(
+    mut config: ResMut<SyncLeakConfig>,
+    mut stats: ResMut<SyncLeakStats>,
+    mut state: SyncState,
+    network: Option<Res<Network<NP>>>,
+) {
+    if config.last_check.elapsed() < config.check_interval {
+        return;
+    }
+    config.last_check = Instant::now();
+
+    let usage = state.usage();
+    let total: usize = usage.values().map(SyncConnectionUsage::total).sum();
+
+    if let Some(network) = network {
+        let connected: HashSet<ConnectionId> = network.connection_ids().into_iter().collect();
+        let stale: Vec<ConnectionId> = usage
+            .keys()
+            .filter(|id| !id.is_server() && !connected.contains(id))
+            .copied()
+            .collect();
+
+        for connection_id in &stale {
+            warn!(
+                "Sync state for disconnected connection {:?} was not cleaned up: {:?}",
+                connection_id, usage[connection_id]
+            );
+            if config.purge_stale_entries {
+                state.remove_connection(*connection_id);
+            }
+        }
+        stats.stale_connections = stale;
+    }
+
+    if stats.last_total > 0 {
+        let growth = (total as f64 - stats.last_total as f64) / stats.last_total as f64;
+        if growth > config.growth_warning_threshold {
+            warn!(
+                "Sync state grew from {} to {} entries ({:.0}%) since last check",
+                stats.last_total,
+                total,
+                growth * 100.0
+            );
+        }
+    }
+
+    stats.last_total = total;
+    stats.per_connection = usage;
+}
+
+pub fn register_sync_leak_detection_plugin<NP: NetworkProvider>(app: &mut App) {
+    app.init_resource::<SyncLeakConfig>()
+        .init_resource::<SyncLeakStats>()
+        .add_systems(
+            Update,
+            (cleanup_disconnected_sync_state, detect_sync_leaks::<NP>).chain(),
+        );
+}
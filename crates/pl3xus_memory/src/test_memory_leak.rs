@@ -69,7 +69,7 @@ fn handle_network_events(
 ) {
     for event in network_events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 println!("New client connected: {:?}", conn_id);
             }
             NetworkEvent::Disconnected(conn_id) => {
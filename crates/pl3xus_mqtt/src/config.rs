@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// MQTT quality of service level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MqttQos {
+    /// Fire and forget
+    #[default]
+    AtMostOnce,
+    /// Acknowledged, may be delivered more than once
+    AtLeastOnce,
+    /// Delivered exactly once
+    ExactlyOnce,
+}
+
+impl From<MqttQos> for rumqttc::QoS {
+    fn from(qos: MqttQos) -> Self {
+        match qos {
+            MqttQos::AtMostOnce => rumqttc::QoS::AtMostOnce,
+            MqttQos::AtLeastOnce => rumqttc::QoS::AtLeastOnce,
+            MqttQos::ExactlyOnce => rumqttc::QoS::ExactlyOnce,
+        }
+    }
+}
+
+/// Broker connection settings for the [`MqttBridgePlugin`](crate::MqttBridgePlugin).
+#[derive(Resource, Debug, Clone)]
+pub struct MqttBridgeConfig {
+    /// Broker host name or address
+    pub host: String,
+    /// Broker port
+    pub port: u16,
+    /// MQTT client identifier (must be unique per broker)
+    pub client_id: String,
+    /// Optional username and password
+    pub credentials: Option<(String, String)>,
+    /// Keep-alive interval
+    pub keep_alive: Duration,
+    /// Delay before the first reconnect attempt; doubled after each failure
+    pub reconnect_delay: Duration,
+    /// Upper bound for the reconnect delay
+    pub max_reconnect_delay: Duration,
+    /// Capacity of the outgoing request queue
+    pub queue_capacity: usize,
+}
+
+impl MqttBridgeConfig {
+    pub fn new(host: impl Into<String>, port: u16, client_id: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            client_id: client_id.into(),
+            credentials: None,
+            keep_alive: Duration::from_secs(15),
+            reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(30),
+            queue_capacity: 256,
+        }
+    }
+
+    pub fn with_credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    pub fn with_reconnect_delay(mut self, initial: Duration, max: Duration) -> Self {
+        self.reconnect_delay = initial;
+        self.max_reconnect_delay = max.max(initial);
+        self
+    }
+
+    pub(crate) fn mqtt_options(&self) -> rumqttc::MqttOptions {
+        let mut options = rumqttc::MqttOptions::new(&self.client_id, &self.host, self.port);
+        options.set_keep_alive(self.keep_alive);
+        if let Some((username, password)) = &self.credentials {
+            options.set_credentials(username, password);
+        }
+        options
+    }
+}
+
+impl Default for MqttBridgeConfig {
+    fn default() -> Self {
+        Self::new("localhost", 1883, "pl3xus")
+    }
+}
+
+/// Options for a component published with
+/// [`mqtt_publish_component`](crate::AppMqttBridgeExt::mqtt_publish_component).
+#[derive(Debug, Clone)]
+pub struct MqttPublishOptions {
+    /// Topic template; `{entity}` is replaced with the entity's bits
+    pub topic: String,
+    /// Quality of service
+    pub qos: MqttQos,
+    /// Ask the broker to keep the last value for new subscribers
+    pub retain: bool,
+}
+
+impl MqttPublishOptions {
+    pub fn new(topic: impl Into<String>) -> Self {
+        Self {
+            topic: topic.into(),
+            qos: MqttQos::AtMostOnce,
+            retain: false,
+        }
+    }
+
+    pub fn with_qos(mut self, qos: MqttQos) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    pub fn retained(mut self) -> Self {
+        self.retain = true;
+        self
+    }
+
+    /// Topic for `entity`.
+    pub fn topic_for(&self, entity: Entity) -> String {
+        self.topic.replace("{entity}", &entity.to_bits().to_string())
+    }
+}
+
+impl From<&str> for MqttPublishOptions {
+    fn from(topic: &str) -> Self {
+        Self::new(topic)
+    }
+}
+
+impl From<String> for MqttPublishOptions {
+    fn from(topic: String) -> Self {
+        Self::new(topic)
+    }
+}
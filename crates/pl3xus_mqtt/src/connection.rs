@@ -0,0 +1,90 @@
+//! Background thread that owns the broker connection.
+//!
+//! `rumqttc` reconnects when its event loop is polled again after an error,
+//! so the thread keeps polling with an exponential backoff in between and
+//! re-subscribes to the inbound topics after every `ConnAck`.
+
+use std::thread;
+
+use bevy::log::{debug, info, warn};
+use pl3xus::async_channel::Sender;
+use rumqttc::{Client, Connection, ConnectionError, Event, Packet, QoS};
+
+use crate::config::MqttBridgeConfig;
+
+/// Events sent from the connection thread to the bridge.
+#[derive(Debug)]
+pub(crate) enum ConnectionEvent {
+    Connected,
+    Disconnected(String),
+    Publish { topic: String, payload: Vec<u8> },
+}
+
+/// Start the connection thread and return the client used to publish.
+pub(crate) fn spawn_connection(
+    config: &MqttBridgeConfig,
+    subscriptions: Vec<(String, QoS)>,
+    events: Sender<ConnectionEvent>,
+) -> Client {
+    let (client, connection) = Client::new(config.mqtt_options(), config.queue_capacity);
+    let thread_client = client.clone();
+    let config = config.clone();
+
+    thread::Builder::new()
+        .name("pl3xus_mqtt".to_string())
+        .spawn(move || run_connection(connection, thread_client, config, subscriptions, events))
+        .expect("failed to spawn MQTT connection thread");
+
+    client
+}
+
+fn run_connection(
+    mut connection: Connection,
+    client: Client,
+    config: MqttBridgeConfig,
+    subscriptions: Vec<(String, QoS)>,
+    events: Sender<ConnectionEvent>,
+) {
+    let mut delay = config.reconnect_delay;
+
+    for notification in connection.iter() {
+        let event = match notification {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                info!("MQTT bridge connected to {}:{}", config.host, config.port);
+                delay = config.reconnect_delay;
+                for (topic, qos) in &subscriptions {
+                    if let Err(e) = client.try_subscribe(topic.as_str(), *qos) {
+                        warn!("MQTT bridge failed to subscribe to {}: {}", topic, e);
+                    }
+                }
+                ConnectionEvent::Connected
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => ConnectionEvent::Publish {
+                topic: publish.topic,
+                payload: publish.payload.to_vec(),
+            },
+            Ok(_) => continue,
+            // Every client handle was dropped: the app is shutting down
+            Err(ConnectionError::RequestsDone) => break,
+            Err(e) => {
+                warn!(
+                    "MQTT bridge connection to {}:{} failed: {} (retrying in {:?})",
+                    config.host, config.port, e, delay
+                );
+                let sent = events.send_blocking(ConnectionEvent::Disconnected(e.to_string()));
+                thread::sleep(delay);
+                delay = (delay * 2).min(config.max_reconnect_delay);
+                if sent.is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        if events.send_blocking(event).is_err() {
+            break;
+        }
+    }
+
+    debug!("MQTT bridge connection thread stopped");
+}
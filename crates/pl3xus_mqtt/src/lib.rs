@@ -0,0 +1,40 @@
+//! # Pl3xus MQTT
+//!
+//! Bridges a pl3xus server to an MQTT broker, so factory systems can consume
+//! telemetry and send commands without speaking the pl3xus wire protocol.
+//!
+//! - **Outbound**: selected components are serialized as JSON and published
+//!   whenever they change, with `{entity}` in the topic replaced per entity.
+//! - **Inbound**: JSON published on subscribed topics is decoded into a
+//!   registered network message and emitted as `NetworkData<T>`, so the same
+//!   handlers serve WebSocket clients and MQTT.
+//! - **Reconnects**: the connection is retried with exponential backoff;
+//!   subscriptions are restored and every published component is sent again
+//!   once the broker accepts the connection.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use bevy::prelude::*;
+//! use pl3xus_mqtt::{AppMqttBridgeExt, MqttBridgeConfig, MqttBridgePlugin, MqttPublishOptions};
+//!
+//! App::new()
+//!     .add_plugins(MqttBridgePlugin::new(
+//!         MqttBridgeConfig::new("broker.local", 1883, "cell-3").with_credentials("cell", "secret"),
+//!     ))
+//!     .mqtt_publish_component::<RobotPosition>(
+//!         MqttPublishOptions::new("cell-3/robot/{entity}/position").retained(),
+//!     )
+//!     .mqtt_inbound_message::<JogCommand>("cell-3/robot/jog")
+//!     .run();
+//! ```
+
+mod config;
+mod connection;
+mod plugin;
+
+pub use config::{MqttBridgeConfig, MqttPublishOptions, MqttQos};
+pub use plugin::{
+    topic_matches, AppMqttBridgeExt, MqttBridge, MqttBridgePlugin, MqttBridgeSet,
+    MqttBridgeStatus, MQTT_CONNECTION_ID, MQTT_PROVIDER_NAME,
+};
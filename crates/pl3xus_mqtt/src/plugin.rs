@@ -0,0 +1,317 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use pl3xus::async_channel::{self, Receiver, Sender};
+use pl3xus::{ConnectionId, NetworkData, Pl3xusMessage};
+use rumqttc::{Client, QoS};
+use serde::Serialize;
+
+use crate::config::{MqttBridgeConfig, MqttPublishOptions, MqttQos};
+use crate::connection::{spawn_connection, ConnectionEvent};
+
+/// Connection id used as the source of messages received over MQTT.
+///
+/// Handlers that reply to the source of a message will find no such client;
+/// MQTT-originated messages are fire-and-forget.
+pub const MQTT_CONNECTION_ID: ConnectionId = ConnectionId { id: u32::MAX };
+
+/// Provider name reported by [`NetworkData::provider_name`] for MQTT messages.
+pub const MQTT_PROVIDER_NAME: &str = "MqttBridge";
+
+/// System sets of the MQTT bridge.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MqttBridgeSet {
+    /// Drains broker events (`PreUpdate`)
+    Receive,
+    /// Maps inbound topics to network messages (`PreUpdate`, after `Receive`)
+    Route,
+    /// Publishes changed components (`PostUpdate`)
+    Publish,
+}
+
+/// Connection state of the bridge, for diagnostics and UI.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MqttBridgeStatus {
+    /// True between a `ConnAck` and the next connection error
+    pub connected: bool,
+    /// Last connection error
+    pub last_error: Option<String>,
+    /// Messages handed to the client for publishing
+    pub published: u64,
+    /// Messages received on subscribed topics
+    pub received: u64,
+}
+
+/// The bridge's client handle and per-frame inbox.
+#[derive(Resource)]
+pub struct MqttBridge {
+    client: Option<Client>,
+    events: Receiver<ConnectionEvent>,
+    events_tx: Sender<ConnectionEvent>,
+    subscriptions: Vec<(String, QoS)>,
+    /// Publish every component on the next frame, not only changed ones
+    republish: bool,
+    /// Messages received this frame, as (topic, payload)
+    inbox: Vec<(String, Vec<u8>)>,
+}
+
+impl Default for MqttBridge {
+    fn default() -> Self {
+        let (events_tx, events) = async_channel::unbounded();
+        Self {
+            client: None,
+            events,
+            events_tx,
+            subscriptions: Vec::new(),
+            republish: false,
+            inbox: Vec::new(),
+        }
+    }
+}
+
+impl MqttBridge {
+    /// Queue a raw publish. Returns false if the bridge is not running or the
+    /// outgoing queue is full.
+    pub fn publish(&self, topic: impl Into<String>, qos: MqttQos, retain: bool, payload: Vec<u8>) -> bool {
+        let Some(client) = &self.client else {
+            return false;
+        };
+        match client.try_publish(topic, qos.into(), retain, payload) {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("MQTT publish dropped: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Messages received this frame on topics matching `filter`.
+    pub fn received<'a>(&'a self, filter: &'a str) -> impl Iterator<Item = (&'a str, &'a [u8])> + 'a {
+        self.inbox
+            .iter()
+            .filter(move |(topic, _)| topic_matches(filter, topic))
+            .map(|(topic, payload)| (topic.as_str(), payload.as_slice()))
+    }
+}
+
+/// Returns true if `topic` matches the MQTT topic `filter` (`+` and `#` wildcards).
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut levels = topic.split('/');
+    for pattern in filter.split('/') {
+        match (pattern, levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (pattern, Some(level)) if pattern == level => {}
+            _ => return false,
+        }
+    }
+    levels.next().is_none()
+}
+
+/// Bridges pl3xus to an MQTT broker.
+///
+/// Components registered with [`AppMqttBridgeExt::mqtt_publish_component`] are
+/// published as JSON whenever they change (and all at once after every
+/// reconnect). Topics registered with [`AppMqttBridgeExt::mqtt_inbound_message`]
+/// are decoded from JSON and emitted as [`NetworkData`] messages, so existing
+/// network message handlers receive them unchanged.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// app.add_plugins(MqttBridgePlugin::new(MqttBridgeConfig::new("broker.local", 1883, "cell-3")))
+///     .mqtt_publish_component::<RobotPosition>("cell-3/robot/{entity}/position")
+///     .mqtt_inbound_message::<JogCommand>("cell-3/robot/jog");
+/// ```
+pub struct MqttBridgePlugin {
+    config: MqttBridgeConfig,
+}
+
+impl MqttBridgePlugin {
+    pub fn new(config: MqttBridgeConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Plugin for MqttBridgePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .init_resource::<MqttBridge>()
+            .init_resource::<MqttBridgeStatus>()
+            .configure_sets(PreUpdate, (MqttBridgeSet::Receive, MqttBridgeSet::Route).chain())
+            .configure_sets(PostUpdate, MqttBridgeSet::Publish)
+            // Startup runs after every plugin registered its topics
+            .add_systems(Startup, start_bridge)
+            .add_systems(PreUpdate, drain_connection_events.in_set(MqttBridgeSet::Receive))
+            .add_systems(PostUpdate, clear_republish.after(MqttBridgeSet::Publish));
+    }
+}
+
+fn start_bridge(config: Res<MqttBridgeConfig>, mut bridge: ResMut<MqttBridge>) {
+    info!(
+        "Starting MQTT bridge to {}:{} ({} inbound topics)",
+        config.host,
+        config.port,
+        bridge.subscriptions.len()
+    );
+    let client = spawn_connection(&config, bridge.subscriptions.clone(), bridge.events_tx.clone());
+    bridge.client = Some(client);
+}
+
+fn drain_connection_events(mut bridge: ResMut<MqttBridge>, mut status: ResMut<MqttBridgeStatus>) {
+    bridge.inbox.clear();
+    while let Ok(event) = bridge.events.try_recv() {
+        match event {
+            ConnectionEvent::Connected => {
+                status.connected = true;
+                status.last_error = None;
+                bridge.republish = true;
+            }
+            ConnectionEvent::Disconnected(error) => {
+                status.connected = false;
+                status.last_error = Some(error);
+            }
+            ConnectionEvent::Publish { topic, payload } => {
+                status.received += 1;
+                bridge.inbox.push((topic, payload));
+            }
+        }
+    }
+}
+
+fn clear_republish(mut bridge: ResMut<MqttBridge>) {
+    bridge.republish = false;
+}
+
+/// Publish options of component `T`.
+#[derive(Resource)]
+struct MqttPublishRoute<T> {
+    options: MqttPublishOptions,
+    _component: PhantomData<fn() -> T>,
+}
+
+fn publish_component<T: Component + Serialize>(
+    bridge: Res<MqttBridge>,
+    route: Res<MqttPublishRoute<T>>,
+    mut status: ResMut<MqttBridgeStatus>,
+    query: Query<(Entity, Ref<T>)>,
+) {
+    // Changes while disconnected are covered by the republish on reconnect
+    if !status.connected {
+        return;
+    }
+    let options = &route.options;
+    for (entity, value) in query.iter() {
+        if !bridge.republish && !value.is_changed() {
+            continue;
+        }
+        let payload = match serde_json::to_vec(&*value) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("MQTT bridge could not serialize {}: {}", std::any::type_name::<T>(), e);
+                continue;
+            }
+        };
+        if bridge.publish(options.topic_for(entity), options.qos, options.retain, payload) {
+            status.published += 1;
+        }
+    }
+}
+
+fn route_inbound<M: Pl3xusMessage>(
+    filter: String,
+) -> impl FnMut(Res<MqttBridge>, MessageWriter<NetworkData<M>>) {
+    move |bridge, mut messages| {
+        for (topic, payload) in bridge.received(&filter) {
+            match serde_json::from_slice::<M>(payload) {
+                Ok(message) => {
+                    messages.write(NetworkData::with_provider(&MQTT_CONNECTION_ID, message, MQTT_PROVIDER_NAME));
+                }
+                Err(e) => warn!("MQTT message on {} is not a valid {}: {}", topic, M::short_name(), e),
+            }
+        }
+    }
+}
+
+/// Extension trait for choosing what the MQTT bridge publishes and receives.
+///
+/// Requires [`MqttBridgePlugin`] to be added first.
+pub trait AppMqttBridgeExt {
+    /// Publish component `T` as JSON to `options.topic` whenever it changes.
+    ///
+    /// `{entity}` in the topic is replaced with the entity's bits, so one
+    /// registration covers every entity with the component. Registering the
+    /// same component again replaces its topic.
+    fn mqtt_publish_component<T>(&mut self, options: impl Into<MqttPublishOptions>) -> &mut Self
+    where
+        T: Component + Serialize;
+
+    /// Decode JSON published on topics matching `filter` as `M` and emit it as
+    /// `NetworkData<M>`, as if a client had sent it.
+    fn mqtt_inbound_message<M>(&mut self, filter: impl Into<String>) -> &mut Self
+    where
+        M: Pl3xusMessage;
+}
+
+impl AppMqttBridgeExt for App {
+    fn mqtt_publish_component<T>(&mut self, options: impl Into<MqttPublishOptions>) -> &mut Self
+    where
+        T: Component + Serialize,
+    {
+        assert!(
+            self.world().contains_resource::<MqttBridge>(),
+            "Could not find `MqttBridge`. Be sure to add the `MqttBridgePlugin` before registering topics."
+        );
+        let options = options.into();
+        debug!("MQTT bridge publishing {} to {}", std::any::type_name::<T>(), options.topic);
+        if self.world().contains_resource::<MqttPublishRoute<T>>() {
+            warn!("{} is already published over MQTT; replacing its topic", std::any::type_name::<T>());
+            self.insert_resource(MqttPublishRoute::<T> { options, _component: PhantomData });
+            return self;
+        }
+        self.insert_resource(MqttPublishRoute::<T> { options, _component: PhantomData })
+            .add_systems(PostUpdate, publish_component::<T>.in_set(MqttBridgeSet::Publish))
+    }
+
+    fn mqtt_inbound_message<M>(&mut self, filter: impl Into<String>) -> &mut Self
+    where
+        M: Pl3xusMessage,
+    {
+        let filter = filter.into();
+        let mut bridge = self
+            .world_mut()
+            .get_resource_mut::<MqttBridge>()
+            .expect("Could not find `MqttBridge`. Be sure to add the `MqttBridgePlugin` before registering topics.");
+        bridge.subscriptions.push((filter.clone(), QoS::AtLeastOnce));
+
+        debug!("MQTT bridge routing {} to {}", filter, M::type_name());
+        self.add_message::<NetworkData<M>>();
+        self.add_systems(PreUpdate, route_inbound::<M>(filter).in_set(MqttBridgeSet::Route))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_matches_wildcards() {
+        assert!(topic_matches("cell/robot/jog", "cell/robot/jog"));
+        assert!(!topic_matches("cell/robot/jog", "cell/robot"));
+        assert!(!topic_matches("cell/robot", "cell/robot/jog"));
+        assert!(topic_matches("cell/+/jog", "cell/robot/jog"));
+        assert!(!topic_matches("cell/+/jog", "cell/robot/stop"));
+        assert!(topic_matches("cell/#", "cell/robot/jog"));
+        assert!(topic_matches("#", "anything/at/all"));
+    }
+
+    #[test]
+    fn test_topic_for_entity() {
+        let options = MqttPublishOptions::new("cell/robot/{entity}/position");
+        let entity = Entity::from_raw_u32(7).unwrap();
+        assert_eq!(
+            options.topic_for(entity),
+            format!("cell/robot/{}/position", entity.to_bits())
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Flattening of serialized components into OPC UA variables.
+//!
+//! Components are serialized with `serde_json` and flattened into one leaf
+//! per scalar field. Nested structs and sequences become dotted paths
+//! (`position.x`, `heaters.1.current`), so every field is a single
+//! browseable variable without modelling custom OPC UA types.
+
+use opcua::types::{UAString, Variant};
+use serde_json::Value;
+
+/// A scalar field value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl From<FieldValue> for Variant {
+    fn from(value: FieldValue) -> Self {
+        match value {
+            FieldValue::Null => Variant::Empty,
+            FieldValue::Bool(value) => Variant::Boolean(value),
+            FieldValue::Int(value) => Variant::Int64(value),
+            FieldValue::Float(value) => Variant::Double(value),
+            FieldValue::String(value) => Variant::String(UAString::from(value)),
+        }
+    }
+}
+
+/// Flatten a serialized component into `(path, value)` leaves.
+///
+/// A component that serializes to a scalar (newtypes, unit enums) has a
+/// single leaf named `value`.
+pub fn flatten_fields(value: &Value) -> Vec<(String, FieldValue)> {
+    let mut fields = Vec::new();
+    match value {
+        Value::Object(_) | Value::Array(_) => flatten_into(value, String::new(), &mut fields),
+        scalar => fields.push(("value".to_string(), scalar_value(scalar))),
+    }
+    fields
+}
+
+fn flatten_into(value: &Value, path: String, fields: &mut Vec<(String, FieldValue)>) {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten_into(value, child(key), fields);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                flatten_into(value, child(&index.to_string()), fields);
+            }
+        }
+        scalar => fields.push((path, scalar_value(scalar))),
+    }
+}
+
+fn scalar_value(value: &Value) -> FieldValue {
+    match value {
+        Value::Bool(value) => FieldValue::Bool(*value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => FieldValue::Int(value),
+            None => FieldValue::Float(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(value) => FieldValue::String(value.clone()),
+        _ => FieldValue::Null,
+    }
+}
+
+/// The last path segment of a type name, without generics
+/// (`my_crate::robot::RobotPosition` -> `RobotPosition`).
+pub fn short_type_name(type_name: &str) -> &str {
+    let base = type_name.split('<').next().unwrap_or(type_name);
+    base.rsplit("::").next().unwrap_or(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_nested_fields() {
+        let value = json!({
+            "online": true,
+            "status": "idle",
+            "position": { "x": 1.5, "y": -2 },
+            "heaters": [{ "current": 24.5 }],
+            "error": null,
+        });
+        let fields = flatten_fields(&value);
+
+        assert!(fields.contains(&("online".to_string(), FieldValue::Bool(true))));
+        assert!(fields.contains(&("status".to_string(), FieldValue::String("idle".into()))));
+        assert!(fields.contains(&("position.x".to_string(), FieldValue::Float(1.5))));
+        assert!(fields.contains(&("position.y".to_string(), FieldValue::Int(-2))));
+        assert!(fields.contains(&("heaters.0.current".to_string(), FieldValue::Float(24.5))));
+        assert!(fields.contains(&("error".to_string(), FieldValue::Null)));
+        assert_eq!(fields.len(), 6);
+    }
+
+    #[test]
+    fn test_scalar_component_and_type_names() {
+        assert_eq!(flatten_fields(&json!(42)), vec![("value".to_string(), FieldValue::Int(42))]);
+        assert_eq!(short_type_name("my_crate::robot::RobotPosition"), "RobotPosition");
+        assert_eq!(short_type_name("my_crate::Wrapper<other::Inner>"), "Wrapper");
+    }
+}
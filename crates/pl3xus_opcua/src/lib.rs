@@ -0,0 +1,28 @@
+//! # Pl3xus OPC UA
+//!
+//! An optional OPC UA server facade for pl3xus servers. Components exposed
+//! with [`AppOpcUaExt::opcua_expose_component`] are published as a read-only
+//! address space, so SCADA systems can browse live robot state without
+//! speaking the pl3xus wire protocol.
+//!
+//! The address space mirrors the ECS: one folder per entity, one folder per
+//! component, one variable per field. Values are written when the component
+//! changes and nodes are removed with the component.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use bevy::prelude::*;
+//! use pl3xus_opcua::{AppOpcUaExt, OpcUaFacadeConfig, OpcUaFacadePlugin};
+//!
+//! App::new()
+//!     .add_plugins(OpcUaFacadePlugin::new(OpcUaFacadeConfig::new("0.0.0.0", 4840)))
+//!     .opcua_expose_component::<RobotPosition>()
+//!     .run();
+//! ```
+
+mod fields;
+mod plugin;
+
+pub use fields::{flatten_fields, short_type_name, FieldValue};
+pub use plugin::{AppOpcUaExt, OpcUaFacade, OpcUaFacadeConfig, OpcUaFacadePlugin, OPCUA_NAMESPACE_URI};
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::thread;
+
+use bevy::prelude::*;
+use opcua::server::prelude::*;
+use opcua::sync::RwLock;
+use serde::Serialize;
+
+use crate::fields::{flatten_fields, short_type_name};
+
+/// Namespace URI of the nodes created by the facade.
+pub const OPCUA_NAMESPACE_URI: &str = "urn:pl3xus:components";
+
+/// Settings for the [`OpcUaFacadePlugin`].
+#[derive(Debug, Clone)]
+pub struct OpcUaFacadeConfig {
+    /// Application name reported to clients
+    pub application_name: String,
+    /// Address to listen on
+    pub host: String,
+    /// Port to listen on (4840 is the registered OPC UA port)
+    pub port: u16,
+    /// Name of the root folder under `Objects`
+    pub root_folder: String,
+}
+
+impl OpcUaFacadeConfig {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_application_name(mut self, name: impl Into<String>) -> Self {
+        self.application_name = name.into();
+        self
+    }
+
+    pub fn with_root_folder(mut self, name: impl Into<String>) -> Self {
+        self.root_folder = name.into();
+        self
+    }
+}
+
+impl Default for OpcUaFacadeConfig {
+    fn default() -> Self {
+        Self {
+            application_name: "pl3xus".to_string(),
+            host: "0.0.0.0".to_string(),
+            port: 4840,
+            root_folder: "Pl3xus".to_string(),
+        }
+    }
+}
+
+/// Handle to the facade's address space.
+///
+/// The server thread serves reads from the same address space, so writes here
+/// are visible to OPC UA clients immediately.
+#[derive(Resource)]
+pub struct OpcUaFacade {
+    address_space: Arc<RwLock<AddressSpace>>,
+    namespace: u16,
+    root: NodeId,
+    /// Nodes created so far, to add new fields without browsing the address space
+    nodes: HashSet<NodeId>,
+}
+
+impl OpcUaFacade {
+    fn node_id(&self, path: &str) -> NodeId {
+        NodeId::new(self.namespace, path)
+    }
+
+    /// Create the folder at `path` under `parent` unless it exists.
+    fn ensure_folder(&mut self, address_space: &mut AddressSpace, path: &str, name: &str, parent: &NodeId) -> NodeId {
+        let node_id = self.node_id(path);
+        if self.nodes.insert(node_id.clone()) {
+            address_space.add_folder_with_id(&node_id, name, name, parent);
+        }
+        node_id
+    }
+
+    /// Write the fields of `component` on `entity`, creating missing nodes.
+    fn write_component(&mut self, entity: Entity, component: &str, value: &serde_json::Value) {
+        let address_space = self.address_space.clone();
+        let mut address_space = address_space.write();
+        let now = DateTime::now();
+
+        let root = self.root.clone();
+        let entity_path = entity.to_bits().to_string();
+        let entity_folder = self.ensure_folder(&mut address_space, &entity_path, &entity_path, &root);
+        let component_path = format!("{}/{}", entity_path, component);
+        let component_folder = self.ensure_folder(&mut address_space, &component_path, component, &entity_folder);
+
+        for (field, value) in flatten_fields(value) {
+            let node_id = self.node_id(&format!("{}/{}", component_path, field));
+            let value = Variant::from(value);
+            if self.nodes.insert(node_id.clone()) {
+                // Variables are created read-only (CurrentRead access level)
+                let variable = Variable::new(&node_id, field.as_str(), field.as_str(), value);
+                address_space.add_variables(vec![variable], &component_folder);
+            } else {
+                address_space.set_variable_value(node_id, value, &now, &now);
+            }
+        }
+    }
+
+    /// Remove the nodes of `component` on `entity`.
+    fn remove_component(&mut self, entity: Entity, component: &str) {
+        let component_path = format!("{}/{}", entity.to_bits(), component);
+        let node_id = self.node_id(&component_path);
+        if !self.nodes.remove(&node_id) {
+            return;
+        }
+        let prefix = format!("{}/", component_path);
+        self.nodes.retain(|node| match &node.identifier {
+            Identifier::String(path) => !path.as_ref().starts_with(&prefix),
+            _ => true,
+        });
+        self.address_space.write().delete(&node_id, true);
+    }
+}
+
+/// Exposes components as a read-only OPC UA address space.
+///
+/// Each exposed component appears as
+/// `Objects/<root>/<entity>/<Component>/<field>`, with one variable per
+/// scalar field (see [`flatten_fields`](crate::flatten_fields)). Node ids are
+/// strings in the [`OPCUA_NAMESPACE_URI`] namespace, e.g.
+/// `ns=2;s=4294967297/RobotPosition/x`, so SCADA tags stay stable across
+/// restarts as long as entities are spawned in the same order.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// app.add_plugins(OpcUaFacadePlugin::new(OpcUaFacadeConfig::new("0.0.0.0", 4840)))
+///     .opcua_expose_component::<RobotPosition>()
+///     .opcua_expose_component::<RobotStatus>();
+/// ```
+pub struct OpcUaFacadePlugin {
+    config: OpcUaFacadeConfig,
+}
+
+impl OpcUaFacadePlugin {
+    pub fn new(config: OpcUaFacadeConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Plugin for OpcUaFacadePlugin {
+    fn build(&self, app: &mut App) {
+        let config = &self.config;
+        let Some(server) = ServerBuilder::new_anonymous(&config.application_name)
+            .application_uri(format!("urn:{}", config.application_name))
+            .host_and_port(&config.host, config.port)
+            .server()
+        else {
+            error!("Invalid OPC UA server configuration; the OPC UA facade is disabled");
+            return;
+        };
+
+        let address_space = server.address_space();
+        let (namespace, root) = {
+            let mut address_space = address_space.write();
+            let namespace = address_space
+                .register_namespace(OPCUA_NAMESPACE_URI)
+                .expect("failed to register the pl3xus OPC UA namespace");
+            let root = NodeId::new(namespace, config.root_folder.as_str());
+            address_space.add_folder_with_id(
+                &root,
+                config.root_folder.as_str(),
+                config.root_folder.as_str(),
+                &NodeId::objects_folder_id(),
+            );
+            (namespace, root)
+        };
+
+        info!("Starting OPC UA facade on opc.tcp://{}:{}/", config.host, config.port);
+        thread::Builder::new()
+            .name("pl3xus_opcua".to_string())
+            .spawn(move || server.run())
+            .expect("failed to spawn OPC UA server thread");
+
+        app.insert_resource(OpcUaFacade {
+            address_space,
+            namespace,
+            nodes: HashSet::from([root.clone()]),
+            root,
+        });
+    }
+}
+
+fn expose_component<T: Component + Serialize>(
+    facade: Option<ResMut<OpcUaFacade>>,
+    changed: Query<(Entity, &T), Changed<T>>,
+    mut removed: RemovedComponents<T>,
+) {
+    let Some(mut facade) = facade else {
+        return;
+    };
+    let component = short_type_name(std::any::type_name::<T>());
+
+    for (entity, value) in changed.iter() {
+        match serde_json::to_value(value) {
+            Ok(value) => facade.write_component(entity, component, &value),
+            Err(e) => warn!("OPC UA facade could not serialize {}: {}", component, e),
+        }
+    }
+    for entity in removed.read() {
+        facade.remove_component(entity, component);
+    }
+}
+
+/// Extension trait for choosing which components the OPC UA facade exposes.
+pub trait AppOpcUaExt {
+    /// Expose component `T` on every entity that has it.
+    ///
+    /// Typically called for the same components registered with
+    /// `sync_component`, so SCADA clients see what pl3xus clients see.
+    fn opcua_expose_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + Serialize;
+}
+
+impl AppOpcUaExt for App {
+    fn opcua_expose_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + Serialize,
+    {
+        debug!("OPC UA facade exposing {}", std::any::type_name::<T>());
+        self.add_systems(PostUpdate, expose_component::<T>)
+    }
+}
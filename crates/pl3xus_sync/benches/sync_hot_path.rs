@@ -0,0 +1,173 @@
+//! Benchmarks for the sync hot path.
+//!
+//! Run with `cargo bench -p pl3xus_sync`. All scenarios use fixed entity
+//! counts and component values so results are comparable between runs.
+//!
+//! - `sync_batch`: bincode encode/decode of `SyncServerMessage::SyncBatch`.
+//! - `change_detection`: one `App::update` with 10k synced entities, with all
+//!   or 1% of them changed.
+//! - `send_loop`: the TCP provider's send loop batching packets over loopback.
+
+use std::hint::black_box;
+use std::io::Read;
+use std::net::{SocketAddr, TcpListener};
+
+use bevy::prelude::*;
+use bevy::tasks::TaskPoolBuilder;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use pl3xus::managers::NetworkProvider;
+use pl3xus::tcp::{NetworkSettings, TcpProvider};
+use pl3xus::{Pl3xusPlugin, Pl3xusRuntime};
+use pl3xus_common::NetworkPacket;
+use pl3xus_sync::{
+    AppPl3xusSyncExt, Pl3xusSyncPlugin, SerializableEntity, SyncBatch, SyncItem, SyncServerMessage,
+};
+use serde::{Deserialize, Serialize};
+
+const ENTITY_COUNT: usize = 10_000;
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+struct BenchPose {
+    x: f32,
+    y: f32,
+    z: f32,
+    w: f32,
+    p: f32,
+    r: f32,
+}
+
+fn pose(i: usize) -> BenchPose {
+    let f = i as f32;
+    BenchPose {
+        x: f,
+        y: f * 0.5,
+        z: f * 0.25,
+        w: 0.0,
+        p: 90.0,
+        r: 180.0,
+    }
+}
+
+fn sync_batch(items: usize) -> SyncServerMessage {
+    let items = (0..items)
+        .map(|i| SyncItem::Update {
+            subscription_id: 1,
+            entity: SerializableEntity { bits: i as u64 },
+            component_type: "BenchPose".to_string(),
+            value: bincode::serde::encode_to_vec(pose(i), bincode::config::standard()).unwrap(),
+        })
+        .collect();
+    SyncServerMessage::SyncBatch(SyncBatch { items, sent_at_ms: None, sequences: Vec::new() })
+}
+
+fn bench_sync_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sync_batch");
+    for items in [1, 100, 1_000] {
+        let message = sync_batch(items);
+        let encoded = bincode::serde::encode_to_vec(&message, bincode::config::standard()).unwrap();
+
+        group.throughput(Throughput::Elements(items as u64));
+        group.bench_with_input(BenchmarkId::new("encode", items), &message, |b, message| {
+            b.iter(|| bincode::serde::encode_to_vec(black_box(message), bincode::config::standard()).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decode", items), &encoded, |b, encoded| {
+            b.iter(|| {
+                let (message, _): (SyncServerMessage, usize) =
+                    bincode::serde::decode_from_slice(black_box(encoded), bincode::config::standard())
+                        .unwrap();
+                message
+            })
+        });
+    }
+    group.finish();
+}
+
+fn sync_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(Pl3xusPlugin::<TcpProvider, bevy::tasks::TaskPool>::default())
+        .insert_resource(Pl3xusRuntime(TaskPoolBuilder::new().build()))
+        .insert_resource(NetworkSettings::default())
+        .add_plugins(Pl3xusSyncPlugin::<TcpProvider>::default())
+        .sync_component::<BenchPose>(None);
+
+    for i in 0..ENTITY_COUNT {
+        app.world_mut().spawn(pose(i));
+    }
+    // Flush the initial `Added` changes.
+    app.update();
+    app
+}
+
+fn bench_change_detection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("change_detection");
+    group.throughput(Throughput::Elements(ENTITY_COUNT as u64));
+
+    for (name, stride) in [("all_changed", 1), ("one_percent_changed", 100)] {
+        let mut app = sync_app();
+        group.bench_function(BenchmarkId::new(name, ENTITY_COUNT), |b| {
+            b.iter(|| {
+                let world = app.world_mut();
+                let mut query = world.query::<&mut BenchPose>();
+                for (i, mut pose) in query.iter_mut(world).enumerate() {
+                    if i % stride == 0 {
+                        pose.x += 1.0;
+                    }
+                }
+                app.update();
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Connect a loopback TCP pair, draining the read side on a background thread.
+fn loopback_writer() -> async_net::TcpStream {
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let writer = std::net::TcpStream::connect(addr).unwrap();
+    let (mut reader, _) = listener.accept().unwrap();
+
+    std::thread::spawn(move || {
+        let mut buf = vec![0u8; 64 * 1024];
+        while matches!(reader.read(&mut buf), Ok(n) if n > 0) {}
+    });
+
+    async_net::TcpStream::try_from(writer).unwrap()
+}
+
+fn bench_send_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("send_loop");
+    let writer = loopback_writer();
+    let payload = bincode::serde::encode_to_vec(sync_batch(10), bincode::config::standard()).unwrap();
+
+    for packets in [1, 64, 512] {
+        group.throughput(Throughput::Elements(packets as u64));
+        group.bench_function(BenchmarkId::new("batched_packets", packets), |b| {
+            b.iter(|| {
+                let (tx, rx) = async_channel::bounded(packets);
+                for _ in 0..packets {
+                    tx.try_send(NetworkPacket {
+                        type_name: "SyncServerMessage".to_string(),
+                        schema_hash: 0,
+                        data: payload.clone(),
+                    })
+                    .unwrap();
+                }
+                drop(tx);
+
+                // The loop drains everything queued in one batch, then exits
+                // once the channel is closed.
+                futures_lite::future::block_on(TcpProvider::send_loop(
+                    writer.clone(),
+                    rx,
+                    NetworkSettings::default(),
+                ));
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_sync_batch, bench_change_detection, bench_send_loop);
+criterion_main!(benches);
@@ -0,0 +1,126 @@
+//! Server-driven UI action availability.
+//!
+//! Whether "Start", "Pause" or "Unload" is currently possible is decided by the
+//! server's state machine. Rather than each client re-deriving it from raw
+//! state, the server publishes an [`EntityActions`] component per entity that
+//! names each action with an enabled flag and, when disabled, a reason:
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::actions::{AppActionsExt, EntityActions};
+//!
+//! app.derive_actions::<BufferState>(|buffer| {
+//!     let running = buffer.is_running();
+//!     EntityActions::new()
+//!         .when("start", !running, "A program is already running")
+//!         .when("pause", running, "Nothing is running")
+//!         .when("stop", running, "Nothing is running")
+//! });
+//! ```
+//!
+//! `derive_actions` recomputes the actions whenever the source component
+//! changes and merges them into the entity's `EntityActions`, so several
+//! sources can contribute different action names to the same entity. The
+//! component is only marked changed when an action's state actually changes,
+//! so clients receive an update only when a button needs to change.
+//!
+//! Systems can also insert or modify `EntityActions` directly. On the client,
+//! `use_action("start", entity)` in `pl3xus_client` returns the state of one
+//! action.
+
+use bevy::prelude::*;
+
+use crate::registry::ComponentSyncConfig;
+use crate::systems::SyncSet;
+use crate::AppPl3xusSyncExt;
+
+pub use pl3xus_common::{ActionState, EntityActions};
+
+/// Plugin that syncs [`EntityActions`] to clients as a read-only component.
+///
+/// Added automatically by [`AppActionsExt::derive_actions`].
+#[derive(Default)]
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.sync_component::<EntityActions>(Some(ComponentSyncConfig::read_only_with_message(
+            "EntityActions is managed by the server",
+        )));
+    }
+}
+
+/// Extension trait for deriving [`EntityActions`] from component state.
+pub trait AppActionsExt {
+    /// Recompute the actions of every entity with component `C` whenever `C`
+    /// changes, and merge them into its [`EntityActions`].
+    fn derive_actions<C: Component>(&mut self, actions: fn(&C) -> EntityActions) -> &mut Self;
+}
+
+impl AppActionsExt for App {
+    fn derive_actions<C: Component>(&mut self, actions: fn(&C) -> EntityActions) -> &mut Self {
+        if !self.is_plugin_added::<ActionsPlugin>() {
+            self.add_plugins(ActionsPlugin);
+        }
+        self.add_systems(
+            Update,
+            update_actions_from::<C>(actions).before(SyncSet::DetectChanges),
+        )
+    }
+}
+
+type ActionSourceQuery<'w, 's, C> =
+    Query<'w, 's, (Entity, &'static C, Option<&'static mut EntityActions>), Changed<C>>;
+
+fn update_actions_from<C: Component>(
+    actions: fn(&C) -> EntityActions,
+) -> impl FnMut(Commands, ActionSourceQuery<C>) {
+    move |mut commands, mut query| {
+        for (entity, source, current) in query.iter_mut() {
+            let declared = actions(source);
+            match current {
+                Some(mut current) => {
+                    let mut next = current.clone();
+                    next.merge(&declared);
+                    current.set_if_neq(next);
+                }
+                None => {
+                    commands.entity(entity).insert(declared);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[derive(Component)]
+    struct Buffer {
+        running: bool,
+    }
+
+    fn buffer_actions(buffer: &Buffer) -> EntityActions {
+        EntityActions::new().when("start", !buffer.running, "A program is already running")
+    }
+
+    #[test]
+    fn test_derived_actions_merge_into_entity_actions() {
+        let mut world = World::new();
+        let merged = world
+            .spawn((Buffer { running: false }, EntityActions::new().enable("unload")))
+            .id();
+        let inserted = world.spawn(Buffer { running: true }).id();
+
+        world.run_system_once(update_actions_from::<Buffer>(buffer_actions)).unwrap();
+
+        // Actions declared elsewhere are kept
+        let actions = world.get::<EntityActions>(merged).unwrap();
+        assert!(actions.is_enabled("start") && actions.is_enabled("unload"));
+
+        let actions = world.get::<EntityActions>(inserted).unwrap();
+        assert!(!actions.is_enabled("start"));
+        assert_eq!(actions.reason("start"), Some("A program is already running"));
+    }
+}
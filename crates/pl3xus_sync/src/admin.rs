@@ -0,0 +1,1188 @@
+//! Admin requests for headless debugging.
+//!
+//! `AdminPlugin` serves a small set of introspection requests used by the
+//! `pl3xus-cli` binary: connected clients, subscriptions, component snapshots
+//! as JSON, registered requests sent as JSON, forced control release, the
+//! server console log, export/import of whole-world snapshots, and changing
+//! runtime settings without a restart.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::admin::{console_log_layer, AdminPlugin, AppAdminExt};
+//!
+//! app.add_plugins(DefaultPlugins.set(LogPlugin {
+//!         custom_layer: console_log_layer,
+//!         ..default()
+//!     }))
+//!     .add_plugins(AdminPlugin::<WebSocketProvider>::new()
+//!         .with_policy(MessageAccessPolicy::from_fn(|_, source| allow_admin(source))))
+//!     // Let `pl3xus-cli request ListPrograms '{}'` reach the existing handler
+//!     .admin_request::<ListPrograms>();
+//! ```
+//!
+//! Admin requests can read every synced component and release any control, so
+//! only the server itself may send them until a policy set with
+//! [`AdminPlugin::with_policy`] lets clients in.
+//!
+//! Importing a world snapshot spawns entities, so it has its own policy, which
+//! only lets the server itself import unless set with
+//! [`AdminPlugin::with_import_policy`]:
+//!
+//! ```rust,ignore
+//! AdminPlugin::<WebSocketProvider>::new()
+//!     .with_import_policy(MessageAccessPolicy::from_fn(|_, source| allow_admin(source)))
+//! ```
+//!
+//! [`UpdateServerSettings`] changes the settings registered with
+//! [`AppAdminExt::runtime_setting`], plus the built-in `sync_rate_hz` and
+//! `log_level` (which needs [`console_log_layer`]). Like imports, it only
+//! answers the server unless set with [`AdminPlugin::with_settings_policy`]:
+//!
+//! ```rust,ignore
+//! AdminPlugin::<WebSocketProvider>::new()
+//!     .with_settings_policy(MessageAccessPolicy::from_fn(|_, source| allow_admin(source)))
+//! ```
+//!
+//! Registering a setting:
+//!
+//! ```rust,ignore
+//! app.runtime_setting(
+//!     "channel_warning_threshold",
+//!     |world| Some(world.resource::<NetworkSettings>().channel_warning_threshold.to_string()),
+//!     |world, value| {
+//!         let threshold: u8 = value.parse().map_err(|_| format!("Invalid percentage '{}'", value))?;
+//!         world.resource_mut::<NetworkSettings>().channel_warning_threshold = threshold.min(100);
+//!         Ok(())
+//!     },
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+
+use pl3xus_common::{ConnectionId, ErrorResponse, RequestMessage};
+use serde::{Deserialize, Serialize};
+
+use crate::messages::SerializableEntity;
+
+/// List connected clients.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminListConnections;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminConnectionsResponse {
+    pub connections: Vec<AdminConnectionInfo>,
+    pub error: Option<String>,
+}
+
+/// One connected client.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AdminConnectionInfo {
+    pub connection_id: ConnectionId,
+    /// Identity reported by the client (requires `ClientPresencePlugin`).
+    pub identity: Option<String>,
+    pub subscriptions: u32,
+    /// Entity bits of the entities the client controls.
+    pub controlled_entities: Vec<u64>,
+    pub messages_in: u64,
+    pub messages_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// Component values not sent because a newer value replaced them.
+    pub conflated: u64,
+}
+
+/// List component subscriptions, optionally for one connection.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminListSubscriptions {
+    pub connection_id: Option<ConnectionId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminSubscriptionsResponse {
+    pub subscriptions: Vec<AdminSubscriptionInfo>,
+    pub error: Option<String>,
+}
+
+/// One component subscription.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AdminSubscriptionInfo {
+    pub connection_id: ConnectionId,
+    pub subscription_id: u64,
+    pub component_type: String,
+    pub entity: Option<SerializableEntity>,
+}
+
+/// Read the current value of a synced component as JSON.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminSnapshot {
+    /// Registered component type name (e.g. `RobotPosition`).
+    pub component_type: String,
+    /// Only this entity, instead of every entity with the component.
+    pub entity: Option<SerializableEntity>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminSnapshotResponse {
+    /// `(entity, JSON value)` pairs.
+    pub entities: Vec<(SerializableEntity, String)>,
+    pub error: Option<String>,
+}
+
+/// Send a registered request, with the payload and response as JSON.
+///
+/// Only requests enabled with [`AppAdminExt::admin_request`] can be sent. The
+/// request goes through the same policies and handlers as one sent by a
+/// regular client from the admin connection.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminJsonRequest {
+    /// Request name (e.g. `ListPrograms`).
+    pub request_type: String,
+    /// The request, as JSON.
+    pub payload: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminJsonResponse {
+    /// The response, as JSON.
+    pub payload: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Release control of an entity, whoever holds it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminReleaseControl {
+    /// Entity bits.
+    pub entity: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminReleaseControlResponse {
+    /// The client that held control, if any.
+    pub released_from: Option<ConnectionId>,
+    pub error: Option<String>,
+}
+
+/// Every synced component of every entity, as exported by
+/// [`AdminExportWorldSnapshot`].
+///
+/// Serialized as JSON, it can be edited by hand, checked into a repository to
+/// seed demo environments, or attached to bug reports.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct WorldSnapshot {
+    pub entities: Vec<WorldSnapshotEntity>,
+}
+
+/// One entity of a [`WorldSnapshot`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct WorldSnapshotEntity {
+    /// Entity bits on the exporting server. Imported entities get new ones.
+    pub entity: u64,
+    /// Component values as JSON, by registered component type name.
+    pub components: BTreeMap<String, serde_json::Value>,
+}
+
+/// Export every synced component of every entity as a [`WorldSnapshot`].
+///
+/// Fields hidden by field policies are left out, as in [`AdminSnapshot`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminExportWorldSnapshot;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminExportWorldSnapshotResponse {
+    /// The [`WorldSnapshot`] as JSON.
+    pub snapshot: String,
+    pub error: Option<String>,
+}
+
+/// Spawn the entities of a [`WorldSnapshot`] with their components.
+///
+/// Existing entities are left alone. Components of types not registered for
+/// sync, or that fail to decode, are skipped and reported.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminImportWorldSnapshot {
+    /// The [`WorldSnapshot`] as JSON.
+    pub snapshot: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminImportWorldSnapshotResponse {
+    /// `(snapshot entity, spawned entity)` bits for each imported entity.
+    pub spawned: Vec<(u64, u64)>,
+    /// Components that were not imported, with the reason.
+    pub skipped: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Read console log entries after a sequence number.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminTailConsole {
+    /// Return entries with a greater sequence number (0 for all retained entries).
+    pub after: u64,
+    /// Maximum number of entries, counted from the newest.
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AdminConsoleResponse {
+    pub entries: Vec<AdminLogEntry>,
+    /// Sequence number of the newest entry, to pass as `after` next time.
+    pub last_sequence: u64,
+    pub error: Option<String>,
+}
+
+/// One console log entry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AdminLogEntry {
+    pub sequence: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// Change runtime settings, by name, without restarting the server.
+///
+/// Values are given as strings (`"20"`, `"debug"`). Settings not mentioned
+/// keep their value, so an empty request just reads them. Settings are
+/// applied in name order; if one is rejected, those before it stay applied.
+/// After a change, the effective settings are broadcast to every client as
+/// [`ServerSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct UpdateServerSettings {
+    pub settings: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct UpdateServerSettingsResponse {
+    /// The effective settings after the update.
+    pub settings: ServerSettings,
+    pub error: Option<String>,
+}
+
+/// Effective values of the runtime settings, by name.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ServerSettings {
+    pub settings: BTreeMap<String, String>,
+}
+
+macro_rules! admin_request {
+    ($($request:ty => $response:ident),+ $(,)?) => {
+        $(
+            impl RequestMessage for $request {
+                type ResponseMessage = $response;
+            }
+
+            impl ErrorResponse for $request {
+                fn error_response(error: String) -> Self::ResponseMessage {
+                    $response {
+                        error: Some(error),
+                        ..Default::default()
+                    }
+                }
+            }
+        )+
+    };
+}
+
+admin_request! {
+    AdminListConnections => AdminConnectionsResponse,
+    AdminListSubscriptions => AdminSubscriptionsResponse,
+    AdminSnapshot => AdminSnapshotResponse,
+    AdminJsonRequest => AdminJsonResponse,
+    AdminReleaseControl => AdminReleaseControlResponse,
+    AdminTailConsole => AdminConsoleResponse,
+    AdminExportWorldSnapshot => AdminExportWorldSnapshotResponse,
+    AdminImportWorldSnapshot => AdminImportWorldSnapshotResponse,
+    UpdateServerSettings => UpdateServerSettingsResponse,
+}
+
+#[cfg(feature = "runtime")]
+pub use runtime::*;
+
+#[cfg(feature = "runtime")]
+mod runtime {
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use bevy::ecs::message::Messages;
+    use bevy::log::tracing::level_filters::LevelFilter;
+    use bevy::log::tracing::subscriber::Interest;
+    use bevy::log::tracing::{self, Metadata, Subscriber, field::Field, field::Visit};
+    use bevy::log::tracing_subscriber::{Layer, layer::Context};
+    use bevy::log::BoxedLayer;
+    use bevy::prelude::*;
+    use pl3xus::managers::network_request::{Request, UnfilteredRequest};
+    use pl3xus::{DeferredResponder, Network};
+    use pl3xus_common::{ClientPresence, ConnectionId, EntityControl, RequestMessage};
+
+    use super::*;
+    use crate::authorization::{AppRequestRegistrationExt, MessageAccessPolicy};
+    use crate::control::ExclusiveControlConfig;
+    use crate::registry::{ConflationQueue, SubscriptionManager, SyncRegistry, SyncSettings};
+    use crate::NetworkProvider;
+
+    /// Number of console entries kept for [`AdminTailConsole`].
+    const CONSOLE_CAPACITY: usize = 1000;
+
+    /// Shared buffer of recent console log entries.
+    ///
+    /// Filled by [`console_log_layer`]; applications can also [`push`](Self::push)
+    /// their own entries.
+    #[derive(Resource, Clone, Default)]
+    pub struct AdminConsoleLog {
+        inner: Arc<Mutex<ConsoleBuffer>>,
+    }
+
+    #[derive(Default)]
+    struct ConsoleBuffer {
+        entries: VecDeque<AdminLogEntry>,
+        last_sequence: u64,
+    }
+
+    impl AdminConsoleLog {
+        /// Append an entry, dropping the oldest one when full.
+        pub fn push(&self, level: impl Into<String>, target: impl Into<String>, message: impl Into<String>) {
+            let timestamp_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default();
+            let Ok(mut buffer) = self.inner.lock() else {
+                return;
+            };
+            buffer.last_sequence += 1;
+            let entry = AdminLogEntry {
+                sequence: buffer.last_sequence,
+                timestamp_ms,
+                level: level.into(),
+                target: target.into(),
+                message: message.into(),
+            };
+            if buffer.entries.len() == CONSOLE_CAPACITY {
+                buffer.entries.pop_front();
+            }
+            buffer.entries.push_back(entry);
+        }
+
+        /// Entries newer than `after`, at most `limit` of them (the newest).
+        pub fn entries_after(&self, after: u64, limit: usize) -> (Vec<AdminLogEntry>, u64) {
+            let Ok(buffer) = self.inner.lock() else {
+                return (Vec::new(), after);
+            };
+            let newer: Vec<_> = buffer
+                .entries
+                .iter()
+                .filter(|entry| entry.sequence > after)
+                .cloned()
+                .collect();
+            let skip = newer.len().saturating_sub(limit);
+            (newer.into_iter().skip(skip).collect(), buffer.last_sequence)
+        }
+    }
+
+    struct ConsoleLayer(AdminConsoleLog);
+
+    #[derive(Default)]
+    struct MessageVisitor(String);
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            use std::fmt::Write;
+            if field.name() == "message" {
+                let _ = write!(self.0, "{:?}", value);
+            } else {
+                let _ = write!(self.0, " {}={:?}", field.name(), value);
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for ConsoleLayer {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            let metadata = event.metadata();
+            self.0.push(metadata.level().as_str(), metadata.target(), visitor.0);
+        }
+    }
+
+    /// Levels by how verbose they are, indexed by the value in [`RuntimeLogLevel`].
+    const LEVELS: [LevelFilter; 6] = [
+        LevelFilter::OFF,
+        LevelFilter::ERROR,
+        LevelFilter::WARN,
+        LevelFilter::INFO,
+        LevelFilter::DEBUG,
+        LevelFilter::TRACE,
+    ];
+
+    /// Most verbose level let through by [`console_log_layer`], changed with
+    /// the `log_level` runtime setting.
+    ///
+    /// It can only hide events: those filtered out by `LogPlugin` stay hidden,
+    /// so set its level to the most verbose one you may need.
+    #[derive(Resource, Clone)]
+    pub struct RuntimeLogLevel(Arc<AtomicU8>);
+
+    impl Default for RuntimeLogLevel {
+        fn default() -> Self {
+            Self(Arc::new(AtomicU8::new((LEVELS.len() - 1) as u8)))
+        }
+    }
+
+    impl RuntimeLogLevel {
+        pub fn get(&self) -> LevelFilter {
+            LEVELS[self.0.load(Ordering::Relaxed) as usize]
+        }
+
+        pub fn set(&self, level: LevelFilter) {
+            let index = LEVELS.iter().position(|l| *l == level).unwrap_or_default();
+            self.0.store(index as u8, Ordering::Relaxed);
+            // Callsites cached their interest under the previous level
+            tracing::callsite::rebuild_interest_cache();
+        }
+
+        /// The level in effect, which `LogPlugin` may limit further.
+        pub fn effective(&self) -> LevelFilter {
+            self.get().min(LevelFilter::current())
+        }
+    }
+
+    struct LevelLayer(RuntimeLogLevel);
+
+    impl<S: Subscriber> Layer<S> for LevelLayer {
+        fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+            if *metadata.level() <= self.0.get() {
+                Interest::always()
+            } else {
+                Interest::never()
+            }
+        }
+
+        fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+            *metadata.level() <= self.0.get()
+        }
+    }
+
+    /// A `LogPlugin::custom_layer` that copies log events into [`AdminConsoleLog`]
+    /// and filters them by [`RuntimeLogLevel`].
+    pub fn console_log_layer(app: &mut App) -> Option<BoxedLayer> {
+        let console = app
+            .world_mut()
+            .get_resource_or_insert_with(AdminConsoleLog::default)
+            .clone();
+        let level = app
+            .world_mut()
+            .get_resource_or_insert_with(RuntimeLogLevel::default)
+            .clone();
+        Some(Box::new(ConsoleLayer(console).and_then(LevelLayer(level))))
+    }
+
+    type SettingGetter = Box<dyn Fn(&World) -> Option<String> + Send + Sync>;
+    type SettingSetter = Box<dyn Fn(&mut World, &str) -> Result<(), String> + Send + Sync>;
+
+    /// Settings that [`UpdateServerSettings`] can change, by name.
+    #[derive(Resource, Default)]
+    pub struct RuntimeSettings {
+        settings: BTreeMap<String, (SettingGetter, SettingSetter)>,
+    }
+
+    impl RuntimeSettings {
+        /// Current values of the settings. Settings whose getter returns
+        /// `None` are not available on this server and are left out.
+        pub fn effective(&self, world: &World) -> ServerSettings {
+            ServerSettings {
+                settings: self
+                    .settings
+                    .iter()
+                    .filter_map(|(name, (get, _))| Some((name.clone(), get(world)?)))
+                    .collect(),
+            }
+        }
+    }
+
+    /// Apply `changes` to the registered settings, returning the effective
+    /// settings and the first error, if any.
+    ///
+    /// Unknown names are rejected before anything is applied.
+    pub fn update_settings(world: &mut World, changes: &BTreeMap<String, String>) -> (ServerSettings, Option<String>) {
+        world.resource_scope(|world, settings: Mut<RuntimeSettings>| {
+            let unknown: Vec<&str> = changes
+                .keys()
+                .filter(|name| !settings.settings.contains_key(*name))
+                .map(String::as_str)
+                .collect();
+            let error = if !unknown.is_empty() {
+                Some(format!("Unknown settings: {}", unknown.join(", ")))
+            } else {
+                changes.iter().find_map(|(name, value)| {
+                    let (_, set) = &settings.settings[name];
+                    set(world, value).err().map(|e| format!("{}: {}", name, e))
+                })
+            };
+            (settings.effective(world), error)
+        })
+    }
+
+    fn register_builtin_settings(app: &mut App) {
+        app.runtime_setting(
+            "sync_rate_hz",
+            |world| {
+                let settings = world.get_resource::<SyncSettings>()?;
+                Some(settings.max_update_rate_hz.unwrap_or_default().to_string())
+            },
+            |world, value| {
+                let hz: f32 = value
+                    .parse()
+                    .ok()
+                    .filter(|hz: &f32| hz.is_finite() && *hz >= 0.0)
+                    .ok_or_else(|| format!("Invalid rate '{}' (0 for unlimited)", value))?;
+                let mut settings = world
+                    .get_resource_mut::<SyncSettings>()
+                    .ok_or("Sync is not installed")?;
+                settings.max_update_rate_hz = (hz > 0.0).then_some(hz);
+                if hz > 0.0
+                    && let Some(mut queue) = world.get_resource_mut::<ConflationQueue>()
+                {
+                    queue.flush_timer.set_duration(Duration::from_secs_f32(1.0 / hz));
+                }
+                Ok(())
+            },
+        )
+        .runtime_setting(
+            "log_level",
+            |world| {
+                let level = world.get_resource::<RuntimeLogLevel>()?;
+                Some(level.effective().to_string().to_lowercase())
+            },
+            |world, value| {
+                let level: LevelFilter = value
+                    .parse()
+                    .map_err(|_| format!("Invalid level '{}' (off, error, warn, info, debug or trace)", value))?;
+                world
+                    .get_resource::<RuntimeLogLevel>()
+                    .ok_or("Needs console_log_layer as the LogPlugin custom layer")?
+                    .set(level);
+                Ok(())
+            },
+        );
+    }
+
+    type PendingJson = Box<dyn Fn() -> Option<Result<String, String>> + Send + Sync>;
+    type JsonBridge = fn(&mut World, ConnectionId, &str) -> Result<PendingJson, String>;
+
+    /// Requests that can be sent as JSON with [`AdminJsonRequest`], by name.
+    #[derive(Resource, Default)]
+    pub struct AdminJsonBridges {
+        bridges: HashMap<String, JsonBridge>,
+    }
+
+    impl AdminJsonBridges {
+        /// Names of the requests that can be sent as JSON.
+        pub fn request_types(&self) -> Vec<String> {
+            let mut names: Vec<_> = self.bridges.keys().cloned().collect();
+            names.sort();
+            names
+        }
+    }
+
+    /// JSON requests waiting for their handler to respond.
+    #[derive(Resource, Default)]
+    struct PendingJsonRequests(Vec<(DeferredResponder<AdminJsonResponse>, PendingJson)>);
+
+    fn bridge_request<T: RequestMessage>(
+        world: &mut World,
+        source: ConnectionId,
+        payload: &str,
+    ) -> Result<PendingJson, String> {
+        let request: T = serde_json::from_str(payload)
+            .map_err(|e| format!("Invalid {} payload: {}", T::request_name(), e))?;
+        let (request, response) = Request::local(source, request);
+        // Requests registered with middleware or a policy go through it first
+        if world.contains_resource::<Messages<UnfilteredRequest<T>>>() {
+            world.write_message(UnfilteredRequest(request));
+        } else {
+            world.write_message(request);
+        }
+        Ok(Box::new(move || match response.try_recv() {
+            Ok(None) => None,
+            Ok(Some(response)) => Some(serde_json::to_string(&response).map_err(|e| e.to_string())),
+            Err(e) => Some(Err(e.to_string())),
+        }))
+    }
+
+    /// Serves the admin requests used by `pl3xus-cli`.
+    pub struct AdminPlugin<NP: NetworkProvider> {
+        policy: MessageAccessPolicy,
+        import_policy: MessageAccessPolicy,
+        settings_policy: MessageAccessPolicy,
+        _marker: std::marker::PhantomData<NP>,
+    }
+
+    impl<NP: NetworkProvider> AdminPlugin<NP> {
+        /// Serve the admin requests to the server only; use
+        /// [`with_policy`](Self::with_policy) to let admin clients connect.
+        pub fn new() -> Self {
+            Self {
+                policy: MessageAccessPolicy::server_only(),
+                import_policy: MessageAccessPolicy::server_only(),
+                settings_policy: MessageAccessPolicy::server_only(),
+                _marker: std::marker::PhantomData,
+            }
+        }
+
+        /// Choose which connections can send admin requests (by default,
+        /// only the server).
+        pub fn with_policy(mut self, policy: MessageAccessPolicy) -> Self {
+            self.policy = policy;
+            self
+        }
+
+        /// Choose which connections can import world snapshots (by default,
+        /// only the server).
+        pub fn with_import_policy(mut self, policy: MessageAccessPolicy) -> Self {
+            self.import_policy = policy;
+            self
+        }
+
+        /// Choose which connections can read and change runtime settings (by
+        /// default, only the server).
+        pub fn with_settings_policy(mut self, policy: MessageAccessPolicy) -> Self {
+            self.settings_policy = policy;
+            self
+        }
+    }
+
+    impl<NP: NetworkProvider> Default for AdminPlugin<NP> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<NP: NetworkProvider> Plugin for AdminPlugin<NP> {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<AdminConsoleLog>()
+                .init_resource::<AdminJsonBridges>()
+                .init_resource::<PendingJsonRequests>();
+
+            app.request::<AdminListConnections, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<AdminListSubscriptions, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<AdminSnapshot, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<AdminJsonRequest, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<AdminReleaseControl, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<AdminTailConsole, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<AdminExportWorldSnapshot, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<AdminImportWorldSnapshot, NP>()
+                .with_message_policy(self.import_policy.clone())
+                .with_error_response();
+            app.request::<UpdateServerSettings, NP>()
+                .with_message_policy(self.settings_policy.clone())
+                .with_error_response();
+            register_builtin_settings(app);
+
+            app.add_systems(
+                Update,
+                (
+                    handle_list_connections::<NP>,
+                    handle_list_subscriptions,
+                    handle_snapshots,
+                    handle_json_requests,
+                    poll_json_requests,
+                    handle_release_control,
+                    handle_tail_console,
+                    handle_export_world,
+                    handle_import_world,
+                    handle_update_settings::<NP>,
+                ),
+            );
+        }
+    }
+
+    /// Extension trait for exposing requests to [`AdminJsonRequest`].
+    pub trait AppAdminExt {
+        /// Allow `T` to be sent as JSON by admin clients.
+        ///
+        /// `T` must already be registered with `request::<T, NP>()`.
+        fn admin_request<T: RequestMessage>(&mut self) -> &mut Self;
+
+        /// Let [`UpdateServerSettings`] change the setting `name`.
+        ///
+        /// `get` returns the current value, or `None` if the setting is not
+        /// available. `set` parses and applies a new value, and should leave
+        /// the setting alone if it rejects it.
+        fn runtime_setting(
+            &mut self,
+            name: impl Into<String>,
+            get: impl Fn(&World) -> Option<String> + Send + Sync + 'static,
+            set: impl Fn(&mut World, &str) -> Result<(), String> + Send + Sync + 'static,
+        ) -> &mut Self;
+    }
+
+    impl AppAdminExt for App {
+        fn admin_request<T: RequestMessage>(&mut self) -> &mut Self {
+            self.world_mut()
+                .get_resource_or_insert_with(AdminJsonBridges::default)
+                .bridges
+                .insert(T::request_name().to_string(), bridge_request::<T>);
+            self
+        }
+
+        fn runtime_setting(
+            &mut self,
+            name: impl Into<String>,
+            get: impl Fn(&World) -> Option<String> + Send + Sync + 'static,
+            set: impl Fn(&mut World, &str) -> Result<(), String> + Send + Sync + 'static,
+        ) -> &mut Self {
+            self.world_mut()
+                .get_resource_or_insert_with(RuntimeSettings::default)
+                .settings
+                .insert(name.into(), (Box::new(get), Box::new(set)));
+            self
+        }
+    }
+
+    fn handle_list_connections<NP: NetworkProvider>(
+        mut requests: MessageReader<Request<AdminListConnections>>,
+        net: Res<Network<NP>>,
+        subscriptions: Option<Res<SubscriptionManager>>,
+        conflation: Option<Res<ConflationQueue>>,
+        presence: Query<&ClientPresence>,
+        controls: Query<(Entity, &EntityControl)>,
+    ) {
+        if requests.is_empty() {
+            return;
+        }
+
+        let mut connections: Vec<_> = net
+            .connection_metrics()
+            .into_iter()
+            .map(|(connection_id, metrics)| AdminConnectionInfo {
+                connection_id,
+                identity: presence
+                    .iter()
+                    .find(|p| p.connection_id == connection_id)
+                    .and_then(|p| p.identity.clone()),
+                subscriptions: subscriptions
+                    .as_ref()
+                    .map(|s| s.subscriptions.iter().filter(|e| e.connection_id == connection_id).count() as u32)
+                    .unwrap_or_default(),
+                controlled_entities: controls
+                    .iter()
+                    .filter(|(_, control)| control.client.connection == connection_id)
+                    .map(|(entity, _)| entity.to_bits())
+                    .collect(),
+                messages_in: metrics.messages_in,
+                messages_out: metrics.messages_out,
+                bytes_in: metrics.bytes_in,
+                bytes_out: metrics.bytes_out,
+                conflated: conflation
+                    .as_ref()
+                    .and_then(|queue| queue.stats.by_connection.get(&connection_id).copied())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        connections.sort_by_key(|c| c.connection_id.id);
+
+        for request in requests.read() {
+            let _ = request.clone().respond(AdminConnectionsResponse {
+                connections: connections.clone(),
+                error: None,
+            });
+        }
+    }
+
+    fn handle_list_subscriptions(
+        mut requests: MessageReader<Request<AdminListSubscriptions>>,
+        subscriptions: Option<Res<SubscriptionManager>>,
+    ) {
+        for request in requests.read() {
+            let filter = request.get_request().connection_id;
+            let subscriptions = subscriptions
+                .iter()
+                .flat_map(|s| s.subscriptions.iter())
+                .filter(|entry| filter.is_none_or(|id| entry.connection_id == id))
+                .map(|entry| AdminSubscriptionInfo {
+                    connection_id: entry.connection_id,
+                    subscription_id: entry.subscription_id,
+                    component_type: entry.component_type.clone(),
+                    entity: entry.entity,
+                })
+                .collect();
+            let _ = request.clone().respond(AdminSubscriptionsResponse {
+                subscriptions,
+                error: None,
+            });
+        }
+    }
+
+    fn handle_snapshots(world: &mut World) {
+        let requests: Vec<_> = world
+            .resource_mut::<Messages<Request<AdminSnapshot>>>()
+            .drain()
+            .collect();
+
+        for request in requests {
+            let AdminSnapshot { component_type, entity } = request.get_request().clone();
+            let snapshot_fn = world.get_resource::<SyncRegistry>().and_then(|registry| {
+                registry
+                    .components
+                    .iter()
+                    .find(|reg| reg.type_name == component_type)
+                    .map(|reg| reg.snapshot_json)
+            });
+
+            let response = match snapshot_fn {
+                Some(snapshot_fn) => AdminSnapshotResponse {
+                    entities: snapshot_fn(world)
+                        .into_iter()
+                        .filter(|(e, _)| entity.is_none_or(|target| target == *e))
+                        .collect(),
+                    error: None,
+                },
+                None => AdminSnapshotResponse {
+                    entities: Vec::new(),
+                    error: Some(format!("Component type '{}' is not registered for sync", component_type)),
+                },
+            };
+            let _ = request.respond(response);
+        }
+    }
+
+    fn handle_json_requests(world: &mut World) {
+        let requests: Vec<_> = world
+            .resource_mut::<Messages<Request<AdminJsonRequest>>>()
+            .drain()
+            .collect();
+
+        for request in requests {
+            let source = *request.source();
+            let AdminJsonRequest { request_type, payload } = request.get_request().clone();
+            let bridge = world.resource::<AdminJsonBridges>().bridges.get(&request_type).copied();
+
+            let Some(bridge) = bridge else {
+                let available = world.resource::<AdminJsonBridges>().request_types().join(", ");
+                let _ = request.respond(AdminJsonRequest::error_response(format!(
+                    "Request '{}' is not available to admin clients (available: {})",
+                    request_type, available
+                )));
+                continue;
+            };
+
+            match bridge(world, source, &payload) {
+                Ok(pending) => world
+                    .resource_mut::<PendingJsonRequests>()
+                    .0
+                    .push((request.take_responder(), pending)),
+                Err(e) => {
+                    let _ = request.respond(AdminJsonRequest::error_response(e));
+                }
+            }
+        }
+    }
+
+    fn poll_json_requests(mut pending: ResMut<PendingJsonRequests>) {
+        pending.0.retain(|(responder, poll)| {
+            let Some(result) = poll() else {
+                return true;
+            };
+            let response = match result {
+                Ok(payload) => AdminJsonResponse {
+                    payload: Some(payload),
+                    error: None,
+                },
+                Err(e) => AdminJsonRequest::error_response(e),
+            };
+            let _ = responder.clone().respond(response);
+            false
+        });
+    }
+
+    fn handle_release_control(
+        mut requests: MessageReader<Request<AdminReleaseControl>>,
+        mut controls: Query<(&mut EntityControl, Option<&Children>)>,
+        config: Option<Res<ExclusiveControlConfig>>,
+        mut commands: Commands,
+    ) {
+        let propagate = config.is_some_and(|c| c.propagate_to_children);
+
+        for request in requests.read() {
+            let bits = request.get_request().entity;
+            let Some(entity) = Entity::try_from_bits(bits) else {
+                let _ = request.clone().respond(AdminReleaseControl::error_response(format!("Invalid entity {}", bits)));
+                continue;
+            };
+            let response = match controls.get_mut(entity) {
+                Ok((mut control, children)) => {
+                    let holder = control.holder().map(|holder| holder.connection);
+                    *control = EntityControl::default();
+                    if propagate && let Some(children) = children {
+                        for child in children.iter() {
+                            commands.entity(child).insert(EntityControl::default());
+                        }
+                    }
+                    if let Some(holder) = holder {
+                        info!("[Admin] {:?} released control of {:?} from {:?}", request.source(), entity, holder);
+                    }
+                    AdminReleaseControlResponse {
+                        released_from: holder,
+                        error: None,
+                    }
+                }
+                Err(_) => AdminReleaseControl::error_response(format!("Entity {:?} has no EntityControl", entity)),
+            };
+            let _ = request.clone().respond(response);
+        }
+    }
+
+    fn handle_tail_console(
+        mut requests: MessageReader<Request<AdminTailConsole>>,
+        console: Res<AdminConsoleLog>,
+    ) {
+        for request in requests.read() {
+            let AdminTailConsole { after, limit } = request.get_request().clone();
+            let (entries, last_sequence) = console.entries_after(after, limit as usize);
+            let _ = request.clone().respond(AdminConsoleResponse {
+                entries,
+                last_sequence,
+                error: None,
+            });
+        }
+    }
+
+    /// Collect every synced component of every entity.
+    pub fn export_world(world: &mut World) -> WorldSnapshot {
+        let snapshot_fns: Vec<_> = world
+            .get_resource::<SyncRegistry>()
+            .map(|registry| {
+                registry
+                    .components
+                    .iter()
+                    .map(|reg| (reg.type_name.clone(), reg.snapshot_json))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut entities: BTreeMap<u64, BTreeMap<String, serde_json::Value>> = BTreeMap::new();
+        for (type_name, snapshot_fn) in snapshot_fns {
+            for (entity, json) in snapshot_fn(world) {
+                let Ok(value) = serde_json::from_str(&json) else {
+                    continue;
+                };
+                entities.entry(entity.bits).or_default().insert(type_name.clone(), value);
+            }
+        }
+
+        WorldSnapshot {
+            entities: entities
+                .into_iter()
+                .map(|(entity, components)| WorldSnapshotEntity { entity, components })
+                .collect(),
+        }
+    }
+
+    /// Spawn the entities of `snapshot`, returning the response sent to
+    /// [`AdminImportWorldSnapshot`].
+    pub fn import_world(world: &mut World, snapshot: WorldSnapshot) -> AdminImportWorldSnapshotResponse {
+        let insert_fns: HashMap<_, _> = world
+            .get_resource::<SyncRegistry>()
+            .map(|registry| {
+                registry
+                    .components
+                    .iter()
+                    .map(|reg| (reg.type_name.clone(), reg.insert_json))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut response = AdminImportWorldSnapshotResponse::default();
+        for WorldSnapshotEntity { entity, components } in snapshot.entities {
+            let spawned = world.spawn_empty().id();
+            let mut inserted = 0;
+            for (type_name, value) in components {
+                let result = match insert_fns.get(&type_name) {
+                    Some(insert_fn) => insert_fn(world, spawned, value),
+                    None => Err("not registered for sync".to_string()),
+                };
+                match result {
+                    Ok(()) => inserted += 1,
+                    Err(e) => response.skipped.push(format!("{} on entity {}: {}", type_name, entity, e)),
+                }
+            }
+            if inserted == 0 {
+                world.despawn(spawned);
+                continue;
+            }
+            response.spawned.push((entity, spawned.to_bits()));
+        }
+        response
+    }
+
+    fn handle_export_world(world: &mut World) {
+        let requests: Vec<_> = world
+            .resource_mut::<Messages<Request<AdminExportWorldSnapshot>>>()
+            .drain()
+            .collect();
+        if requests.is_empty() {
+            return;
+        }
+
+        let response = match serde_json::to_string(&export_world(world)) {
+            Ok(snapshot) => AdminExportWorldSnapshotResponse { snapshot, error: None },
+            Err(e) => AdminExportWorldSnapshot::error_response(e.to_string()),
+        };
+        for request in requests {
+            let _ = request.respond(response.clone());
+        }
+    }
+
+    fn handle_import_world(world: &mut World) {
+        let requests: Vec<_> = world
+            .resource_mut::<Messages<Request<AdminImportWorldSnapshot>>>()
+            .drain()
+            .collect();
+
+        for request in requests {
+            let response = match serde_json::from_str::<WorldSnapshot>(&request.get_request().snapshot) {
+                Ok(snapshot) => {
+                    let response = import_world(world, snapshot);
+                    info!(
+                        "[Admin] {:?} imported {} entities ({} components skipped)",
+                        request.source(),
+                        response.spawned.len(),
+                        response.skipped.len()
+                    );
+                    response
+                }
+                Err(e) => AdminImportWorldSnapshot::error_response(format!("Invalid snapshot: {}", e)),
+            };
+            let _ = request.respond(response);
+        }
+    }
+
+    fn handle_update_settings<NP: NetworkProvider>(world: &mut World) {
+        let requests: Vec<_> = world
+            .resource_mut::<Messages<Request<UpdateServerSettings>>>()
+            .drain()
+            .collect();
+
+        for request in requests {
+            let before = world.resource_scope(|world, settings: Mut<RuntimeSettings>| settings.effective(world));
+            let (settings, error) = update_settings(world, &request.get_request().settings);
+            if settings != before {
+                info!("[Admin] {:?} updated settings: {:?}", request.source(), settings.settings);
+                if let Some(net) = world.get_resource::<Network<NP>>() {
+                    net.broadcast(settings.clone());
+                }
+            }
+            let _ = request.respond(UpdateServerSettingsResponse { settings, error });
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_console_log_keeps_newest_entries() {
+            let console = AdminConsoleLog::default();
+            for i in 0..CONSOLE_CAPACITY + 5 {
+                console.push("INFO", "test", format!("line {}", i));
+            }
+
+            let (entries, last) = console.entries_after(0, usize::MAX);
+            assert_eq!(entries.len(), CONSOLE_CAPACITY);
+            assert_eq!(entries[0].sequence, 6);
+            assert_eq!(last, CONSOLE_CAPACITY as u64 + 5);
+
+            let (entries, _) = console.entries_after(last - 3, 2);
+            let messages: Vec<_> = entries.iter().map(|e| e.message.as_str()).collect();
+            assert_eq!(messages, vec![format!("line {}", CONSOLE_CAPACITY + 3), format!("line {}", CONSOLE_CAPACITY + 4)]);
+        }
+
+        #[test]
+        fn test_update_settings() {
+            let mut app = App::new();
+            app.insert_resource(SyncSettings::default())
+                .insert_resource(ConflationQueue::new(30.0))
+                .insert_resource(RuntimeLogLevel::default());
+            register_builtin_settings(&mut app);
+
+            let changes = BTreeMap::from([("sync_rate_hz".to_string(), "4".to_string())]);
+            let (settings, error) = update_settings(app.world_mut(), &changes);
+            assert_eq!(error, None);
+            assert_eq!(settings.settings["sync_rate_hz"], "4");
+            assert_eq!(
+                app.world().resource::<ConflationQueue>().flush_timer.duration(),
+                Duration::from_millis(250)
+            );
+
+            let changes = BTreeMap::from([
+                ("log_level".to_string(), "loud".to_string()),
+                ("sync_rate_hz".to_string(), "0".to_string()),
+            ]);
+            let (settings, error) = update_settings(app.world_mut(), &changes);
+            assert!(error.unwrap().starts_with("log_level: Invalid level"));
+            // Settings are applied in name order, so the rate was not reached
+            assert_eq!(settings.settings["sync_rate_hz"], "4");
+
+            let changes = BTreeMap::from([
+                ("sync_rate_hz".to_string(), "0".to_string()),
+                ("tick".to_string(), "1".to_string()),
+            ]);
+            let (_, error) = update_settings(app.world_mut(), &changes);
+            assert_eq!(error.as_deref(), Some("Unknown settings: tick"));
+            assert_eq!(app.world().resource::<SyncSettings>().max_update_rate_hz, Some(4.0));
+
+            let changes = BTreeMap::from([("log_level".to_string(), "warn".to_string())]);
+            update_settings(app.world_mut(), &changes);
+            assert_eq!(app.world().resource::<RuntimeLogLevel>().get(), LevelFilter::WARN);
+        }
+
+        #[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Battery {
+            level: f32,
+        }
+
+        #[test]
+        fn test_world_snapshot_round_trip() {
+            use crate::AppPl3xusSyncExt;
+
+            let mut app = App::new();
+            app.sync_component::<Battery>(None);
+            let robot = app.world_mut().spawn(Battery { level: 0.5 }).id();
+            app.world_mut().spawn(Name::new("not synced"));
+
+            let snapshot = export_world(app.world_mut());
+            assert_eq!(snapshot.entities.len(), 1);
+            assert_eq!(snapshot.entities[0].entity, robot.to_bits());
+
+            let mut snapshot: WorldSnapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+            snapshot.entities[0]
+                .components
+                .insert("Unknown".to_string(), serde_json::json!({}));
+            let response = import_world(app.world_mut(), snapshot);
+            assert_eq!(response.spawned.len(), 1);
+            assert_eq!(response.skipped.len(), 1);
+
+            let copy = Entity::from_bits(response.spawned[0].1);
+            assert_ne!(copy, robot);
+            assert_eq!(app.world().get::<Battery>(copy), Some(&Battery { level: 0.5 }));
+        }
+    }
+}
@@ -13,6 +13,8 @@
 //!
 //! - [`EntityAccessPolicy`]: For targeted messages - checks if client can access entity
 //! - [`MessageAccessPolicy`]: For non-targeted messages - checks if client can send message type
+//! - [`Middleware`]: Ordered interceptors (logging, rate limiting, validation) that run
+//!   before the policy check and can inspect, deny, or transform the payload
 //!
 //! ## Builder Pattern
 //!
@@ -46,6 +48,9 @@
 
 use bevy::prelude::*;
 use pl3xus_common::ConnectionId;
+
+use crate::rate_limit::{check_rate_limit, limit_packets, RateLimitKind, TokenBucket};
+use crate::systems::SyncSet;
 use std::any::TypeId;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -319,6 +324,51 @@ impl MessageAccessPolicy {
         })
     }
 
+    /// Only the server and clients connected on one of `endpoints` (see
+    /// [`ConnectionInfos`](pl3xus::ConnectionInfos)).
+    pub fn endpoints<S: Into<String>>(endpoints: impl IntoIterator<Item = S>) -> Self {
+        let endpoints: Vec<String> = endpoints.into_iter().map(Into::into).collect();
+        Self::from_fn(move |world, source| {
+            let endpoint = world
+                .get_resource::<pl3xus::ConnectionInfos>()
+                .and_then(|infos| infos.endpoint(source));
+            if source.is_server() || endpoint.is_some_and(|endpoint| endpoints.iter().any(|e| e == endpoint)) {
+                Ok(())
+            } else {
+                Err(format!("Not allowed from endpoint {}", endpoint.unwrap_or("(none)")))
+            }
+        })
+    }
+
+    /// Pick the policy by the endpoint the client connected on (see
+    /// [`ConnectionInfos`](pl3xus::ConnectionInfos)), using `default`
+    /// for other endpoints and the server.
+    ///
+    /// ```rust,ignore
+    /// app.insert_resource(DefaultMessageAccessPolicy(MessageAccessPolicy::by_endpoint(
+    ///     [("/admin", MessageAccessPolicy::allow_all())],
+    ///     MessageAccessPolicy::server_only(),
+    /// )));
+    /// ```
+    pub fn by_endpoint<S: Into<String>>(
+        policies: impl IntoIterator<Item = (S, MessageAccessPolicy)>,
+        default: MessageAccessPolicy,
+    ) -> Self {
+        let policies: HashMap<String, MessageAccessPolicy> =
+            policies.into_iter().map(|(endpoint, policy)| (endpoint.into(), policy)).collect();
+        Self::from_fn(move |world, source| {
+            let policy = world
+                .get_resource::<pl3xus::ConnectionInfos>()
+                .and_then(|infos| infos.endpoint(source))
+                .and_then(|endpoint| policies.get(endpoint))
+                .unwrap_or(&default);
+            match policy.check(world, source) {
+                AuthResult::Authorized => Ok(()),
+                AuthResult::Denied(reason) => Err(reason),
+            }
+        })
+    }
+
     /// Check if the message is authorized.
     pub fn check(&self, world: &World, source: ConnectionId) -> AuthResult {
         let ctx = MessageAccessContext { world, source };
@@ -372,6 +422,333 @@ impl MessageAccessPolicies {
 #[derive(Resource, Clone)]
 pub struct DefaultMessageAccessPolicy(pub MessageAccessPolicy);
 
+// ============================================================================
+// MIDDLEWARE (ordered interceptors run before handlers)
+// ============================================================================
+
+/// Context passed to each middleware in a chain.
+pub struct MiddlewareContext<'a> {
+    /// Read-only access to the ECS world for querying state.
+    pub world: &'a World,
+    /// The client that sent the message or request.
+    pub source: ConnectionId,
+    /// The short type name of the message or request.
+    pub type_name: &'static str,
+    /// The target entity, for targeted messages and requests.
+    pub target_entity: Option<Entity>,
+}
+
+/// Trait for interceptors that run before a message or request reaches its handler.
+///
+/// Interceptors receive the payload mutably, so they can inspect it, deny it
+/// (by returning [`AuthResult::Denied`]), or transform it in place (e.g. clamp
+/// or normalize fields). Interceptors run in registration order and the chain
+/// stops at the first denial.
+///
+/// Stateful interceptors (such as rate limiters) should use interior mutability,
+/// since middleware is shared behind an `Arc`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use pl3xus_sync::authorization::{MessageInterceptor, MiddlewareContext, AuthResult};
+///
+/// struct ClampSpeed;
+///
+/// impl MessageInterceptor<SetSpeedOverride> for ClampSpeed {
+///     fn intercept(&self, _ctx: &MiddlewareContext, request: &mut SetSpeedOverride) -> AuthResult {
+///         request.speed = request.speed.clamp(0.0, 100.0);
+///         AuthResult::Authorized
+///     }
+/// }
+/// ```
+pub trait MessageInterceptor<T>: Send + Sync + 'static {
+    /// Inspect, transform, or deny the payload.
+    fn intercept(&self, ctx: &MiddlewareContext, message: &mut T) -> AuthResult;
+
+    /// Forget any state kept for `connection`, which has disconnected.
+    fn disconnected(&self, _connection: ConnectionId) {}
+}
+
+/// Wrapper for a single middleware in a chain.
+///
+/// Register middleware via the builder with `.with_middleware(...)`:
+///
+/// ```rust,ignore
+/// app.request::<CreateProgram, NP>()
+///    .with_middleware(Middleware::logging())
+///    .with_middleware(Middleware::rate_limit(5, Duration::from_secs(1)))
+///    .with_middleware(Middleware::validate(|req: &CreateProgram| {
+///        if req.name.is_empty() {
+///            Err("Program name cannot be empty".to_string())
+///        } else {
+///            Ok(())
+///        }
+///    }))
+///    .register();
+/// ```
+pub struct Middleware<T> {
+    inner: Arc<dyn MessageInterceptor<T>>,
+}
+
+impl<T> Clone for Middleware<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> Middleware<T> {
+    /// Create middleware from an interceptor implementation.
+    pub fn new<I: MessageInterceptor<T>>(interceptor: I) -> Self {
+        Self {
+            inner: Arc::new(interceptor),
+        }
+    }
+
+    /// Create middleware from a closure.
+    ///
+    /// The closure may modify the payload and returns `Err(reason)` to deny it.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(&MiddlewareContext, &mut T) -> Result<(), String> + Send + Sync + 'static,
+    {
+        struct ClosureInterceptor<F>(F);
+
+        impl<T, F> MessageInterceptor<T> for ClosureInterceptor<F>
+        where
+            F: Fn(&MiddlewareContext, &mut T) -> Result<(), String> + Send + Sync + 'static,
+        {
+            fn intercept(&self, ctx: &MiddlewareContext, message: &mut T) -> AuthResult {
+                match (self.0)(ctx, message) {
+                    Ok(()) => AuthResult::Authorized,
+                    Err(reason) => AuthResult::Denied(reason),
+                }
+            }
+        }
+
+        Self {
+            inner: Arc::new(ClosureInterceptor(f)),
+        }
+    }
+
+    /// Validate the payload without modifying it.
+    pub fn validate<F>(f: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self::from_fn(move |_, message| f(message))
+    }
+
+    /// Deny payloads with more than `max` items, as counted by `count`.
+    ///
+    /// Use this for limits the byte size doesn't capture, like the number of
+    /// instructions in an uploaded program. `what` names the items in the
+    /// denial reason.
+    ///
+    /// ```rust,ignore
+    /// Middleware::max_items("instructions", 10_000, |req: &UploadProgram| req.instructions.len())
+    /// ```
+    pub fn max_items<F>(what: &'static str, max: usize, count: F) -> Self
+    where
+        F: Fn(&T) -> usize + Send + Sync + 'static,
+    {
+        Self::validate(move |message| {
+            let items = count(message);
+            if items > max {
+                Err(format!("Too large: {} {} (limit {})", items, what, max))
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Log every payload that passes through this point of the chain.
+    ///
+    /// Each entry is emitted inside a `pl3xus_middleware` span carrying the
+    /// type name and source connection.
+    pub fn logging() -> Self {
+        Self::from_fn(|ctx, _| {
+            let span = info_span!(
+                "pl3xus_middleware",
+                type_name = ctx.type_name,
+                source = ctx.source.id
+            );
+            let _guard = span.enter();
+            match ctx.target_entity {
+                Some(entity) => info!(
+                    "[pl3xus_sync] {} from {:?} targeting {:?}",
+                    ctx.type_name, ctx.source, entity
+                ),
+                None => info!("[pl3xus_sync] {} from {:?}", ctx.type_name, ctx.source),
+            }
+            Ok(())
+        })
+    }
+
+    /// Limit each connection to bursts of `max` payloads, refilled at `max`
+    /// per `window`.
+    ///
+    /// This uses the token buckets of [`RateLimitPlugin`](crate::rate_limit::RateLimitPlugin),
+    /// kept for this middleware alone. The server (connection 0) is never rate limited.
+    pub fn rate_limit(max: u32, window: std::time::Duration) -> Self {
+        Self::new(RateLimitInterceptor {
+            max,
+            window,
+            buckets: std::sync::Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Run this middleware against a payload.
+    pub fn intercept(&self, ctx: &MiddlewareContext, message: &mut T) -> AuthResult {
+        self.inner.intercept(ctx, message)
+    }
+
+    /// Tell this middleware that `connection` has disconnected.
+    pub fn disconnected(&self, connection: ConnectionId) {
+        self.inner.disconnected(connection)
+    }
+}
+
+/// Per-connection token buckets used by [`Middleware::rate_limit`].
+struct RateLimitInterceptor {
+    max: u32,
+    window: std::time::Duration,
+    buckets: std::sync::Mutex<HashMap<ConnectionId, TokenBucket>>,
+}
+
+impl<T> MessageInterceptor<T> for RateLimitInterceptor {
+    fn intercept(&self, ctx: &MiddlewareContext, _message: &mut T) -> AuthResult {
+        if ctx.source.is_server() {
+            return AuthResult::Authorized;
+        }
+
+        let now = std::time::Instant::now();
+        // A limiter that can't count can't let anything through
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return AuthResult::Denied(format!("Rate limiter for {} is unavailable", ctx.type_name));
+        };
+        let capacity = self.max as f32;
+        let rate = capacity / self.window.as_secs_f32().max(f32::EPSILON);
+        let bucket = buckets
+            .entry(ctx.source)
+            .or_insert_with(|| TokenBucket::full(capacity, now));
+
+        if bucket.take(rate, capacity, now) {
+            AuthResult::Authorized
+        } else {
+            AuthResult::Denied(format!(
+                "Rate limit exceeded for {} ({} per {:?})",
+                ctx.type_name, self.max, self.window
+            ))
+        }
+    }
+
+    fn disconnected(&self, connection: ConnectionId) {
+        if let Ok(mut buckets) = self.buckets.lock() {
+            buckets.remove(&connection);
+        }
+    }
+}
+
+/// Resource storing the ordered middleware chain for a message or request type.
+#[derive(Resource)]
+pub struct MiddlewareChain<T: 'static> {
+    middleware: Vec<Middleware<T>>,
+}
+
+impl<T: 'static> Default for MiddlewareChain<T> {
+    fn default() -> Self {
+        Self {
+            middleware: Vec::new(),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> MiddlewareChain<T> {
+    /// Append middleware to the end of the chain.
+    pub fn push(&mut self, middleware: Middleware<T>) {
+        self.middleware.push(middleware);
+    }
+
+    /// Returns true if the chain has no middleware.
+    pub fn is_empty(&self) -> bool {
+        self.middleware.is_empty()
+    }
+
+    /// Run the chain in order, stopping at the first denial.
+    pub fn run(&self, ctx: &MiddlewareContext, message: &mut T) -> AuthResult {
+        for middleware in &self.middleware {
+            if let AuthResult::Denied(reason) = middleware.intercept(ctx, message) {
+                return AuthResult::Denied(reason);
+            }
+        }
+        AuthResult::Authorized
+    }
+
+    /// Tell every middleware in the chain that `connection` has disconnected.
+    pub fn disconnected(&self, connection: ConnectionId) {
+        for middleware in &self.middleware {
+            middleware.disconnected(connection);
+        }
+    }
+}
+
+/// Append middleware to the chain for `T`, creating the chain resource if needed.
+fn install_middleware<T: Send + Sync + 'static>(app: &mut App, middleware: Vec<Middleware<T>>) {
+    if middleware.is_empty() {
+        return;
+    }
+    if !app.world().contains_resource::<MiddlewareChain<T>>() {
+        app.init_resource::<MiddlewareChain<T>>()
+            .add_systems(PostUpdate, forget_disconnected_middleware::<T>);
+    }
+    let mut chain = app.world_mut().resource_mut::<MiddlewareChain<T>>();
+    for m in middleware {
+        chain.push(m);
+    }
+}
+
+/// Let the middleware for `T` forget connections that disconnected.
+fn forget_disconnected_middleware<T: Send + Sync + 'static>(
+    mut events: MessageReader<NetworkEvent>,
+    chain: Res<MiddlewareChain<T>>,
+) {
+    for event in events.read() {
+        if let NetworkEvent::Disconnected(connection_id) = event {
+            chain.disconnected(*connection_id);
+        }
+    }
+}
+
+/// Run the connection's rate limit, then the middleware chain for `T` (if any),
+/// against a payload.
+fn run_middleware<T: Send + Sync + 'static>(
+    world: &World,
+    source: ConnectionId,
+    kind: RateLimitKind,
+    type_name: &'static str,
+    target_entity: Option<Entity>,
+    message: &mut T,
+) -> AuthResult {
+    if let denied @ AuthResult::Denied(_) = check_rate_limit(world, source, kind, type_name) {
+        return denied;
+    }
+    match world.get_resource::<MiddlewareChain<T>>() {
+        Some(chain) => {
+            let ctx = MiddlewareContext {
+                world,
+                source,
+                type_name,
+                target_entity,
+            };
+            chain.run(&ctx, message)
+        }
+        None => AuthResult::Authorized,
+    }
+}
+
 // ============================================================================
 // AUTHORIZED MESSAGE TYPES (output of authorization middleware)
 // ============================================================================
@@ -390,6 +767,19 @@ pub struct AuthorizedTargetedMessage<T: pl3xus_common::Pl3xusMessage> {
     pub target_entity: Entity,
 }
 
+impl<T: pl3xus_common::Pl3xusMessage> AuthorizedTargetedMessage<T> {
+    /// The target entity and the payload.
+    ///
+    /// ```rust,ignore
+    /// for (robot, command) in messages.read().cloned().map(AuthorizedTargetedMessage::into_parts) {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn into_parts(self) -> (Entity, T) {
+        (self.target_entity, self.message)
+    }
+}
+
 /// A non-targeted message that has passed authorization.
 ///
 /// Systems should read this message type instead of `NetworkData<T>`
@@ -403,7 +793,7 @@ pub struct AuthorizedMessage<T: pl3xus_common::Pl3xusMessage> {
 }
 
 use bevy::ecs::message::Messages;
-use pl3xus::{Network, NetworkData};
+use pl3xus::{NetworkData, NetworkEvent};
 use crate::NetworkProvider;
 use pl3xus_common::{Pl3xusMessage, ServerNotification, TargetedMessage};
 
@@ -454,6 +844,10 @@ where
     use_default_entity_policy: bool,
     message_policy: Option<MessageAccessPolicy>,
     use_default_message_policy: bool,
+    middleware: Vec<Middleware<T>>,
+    reliable: bool,
+    logged: bool,
+    max_payload_bytes: Option<usize>,
     _marker: std::marker::PhantomData<(T, NP)>,
 }
 
@@ -464,6 +858,7 @@ where
 {
     /// Create a new message registration builder.
     pub fn new(app: &'a mut App) -> Self {
+        crate::schema::record_message::<T>(app);
         Self {
             app,
             targeted: false,
@@ -471,6 +866,10 @@ where
             use_default_entity_policy: false,
             message_policy: None,
             use_default_message_policy: false,
+            middleware: Vec::new(),
+            reliable: false,
+            logged: false,
+            max_payload_bytes: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -544,16 +943,105 @@ where
         self
     }
 
+    /// Add middleware that runs before the access policy check.
+    ///
+    /// Middleware runs in the order it is added. Adding any middleware enables
+    /// the authorization stage, so handlers should read `AuthorizedMessage<T>`
+    /// (or `AuthorizedTargetedMessage<T>` for targeted messages).
+    pub fn with_middleware(mut self, middleware: Middleware<T>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Deliver this message reliably: in order, exactly once, and resent by
+    /// the client after a reconnect until the server acknowledges it.
+    ///
+    /// Clients must send it with `send_reliable` (or `send_targeted_reliable`);
+    /// plain sends still work but get no delivery guarantee. Handlers and
+    /// policies are unchanged. See [`crate::reliable`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.message::<AbortMotion, NP>()
+    ///    .targeted()
+    ///    .with_default_entity_policy()
+    ///    .reliable()
+    ///    .register();
+    /// ```
+    pub fn reliable(mut self) -> Self {
+        self.reliable = true;
+        self
+    }
+
+    /// Record authorized messages in the command log, so they can be listed
+    /// and replayed. Requires `CommandLogPlugin`; see [`crate::command_log`].
+    ///
+    /// Like middleware, this enables the authorization stage, so handlers
+    /// should read `AuthorizedMessage<T>` (or `AuthorizedTargetedMessage<T>`).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.message::<SendPacket, NP>()
+    ///    .targeted()
+    ///    .with_default_entity_policy()
+    ///    .logged()
+    ///    .register();
+    /// ```
+    pub fn logged(mut self) -> Self {
+        self.logged = true;
+        self
+    }
+
+    /// Reject payloads larger than `max_bytes` before decoding them.
+    ///
+    /// The sender gets a `PayloadTooLarge` message and stays connected.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.message::<UploadCsv, NP>()
+    ///    .max_payload_bytes(512 * 1024)
+    ///    .register();
+    /// ```
+    pub fn max_payload_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_payload_bytes = Some(max_bytes);
+        self
+    }
+
     /// Complete the registration and add systems to the app.
     pub fn register(self) -> &'a mut App {
         use pl3xus::AppNetworkMessage;
+        use crate::reliable::{register_reliable, ReliableDelivery};
+
+        let has_middleware = !self.middleware.is_empty();
+        install_middleware::<T>(self.app, self.middleware);
+        if self.logged {
+            crate::command_log::log_message::<T>(self.app, self.targeted);
+        }
+        if let Some(max_bytes) = self.max_payload_bytes {
+            self.app.limit_message_size::<T, NP>(max_bytes);
+        }
 
         if self.targeted {
             // Register as targeted message
             self.app.register_targeted_message::<T, NP>();
+            if self.reliable {
+                register_reliable::<TargetedMessage<T>, NP>(self.app, TargetedMessage::<T>::name());
+            }
 
             // Check if we need authorization middleware
-            let needs_auth = self.entity_policy.is_some() || self.use_default_entity_policy;
+            let needs_auth = self.entity_policy.is_some()
+                || self.use_default_entity_policy
+                || has_middleware
+                || self.logged;
+            if self.use_default_entity_policy && self.entity_policy.is_none() {
+                crate::registration_checks::require_default_entity_policy(
+                    self.app,
+                    format!("Message {}", T::short_name()),
+                );
+            }
 
             if let Some(policy) = self.entity_policy {
                 // Store per-message policy
@@ -570,16 +1058,32 @@ where
                 // Add authorization middleware
                 // If no per-message policy, the middleware will use DefaultEntityAccessPolicy
                 self.app.add_message::<AuthorizedTargetedMessage<T>>();
-                self.app
-                    .add_systems(PreUpdate, authorize_targeted_messages::<T, NP>);
+                self.app.add_systems(
+                    PreUpdate,
+                    authorize_targeted_messages::<T, NP>.after(ReliableDelivery).in_set(SyncSet::Authorize),
+                );
+            } else {
+                let name = TargetedMessage::<T>::name();
+                limit_packets(self.app, RateLimitKind::Message, T::short_name(), (name, TargetedMessage::<T>::schema_hash()));
             }
-            // If no policy and not using default, just register the targeted message - no middleware
         } else {
             // Register as plain message
             self.app.register_network_message::<T, NP>();
+            if self.reliable {
+                register_reliable::<T, NP>(self.app, T::type_name());
+            }
 
             // Check if we need message authorization middleware
-            let needs_auth = self.message_policy.is_some() || self.use_default_message_policy;
+            let needs_auth = self.message_policy.is_some()
+                || self.use_default_message_policy
+                || has_middleware
+                || self.logged;
+            if self.use_default_message_policy && self.message_policy.is_none() {
+                crate::registration_checks::require_default_message_policy(
+                    self.app,
+                    format!("Message {}", T::short_name()),
+                );
+            }
 
             if let Some(policy) = self.message_policy {
                 // Store per-message policy
@@ -595,9 +1099,13 @@ where
             if needs_auth {
                 // Add authorization middleware
                 self.app.add_message::<AuthorizedMessage<T>>();
-                self.app.add_systems(PreUpdate, authorize_messages::<T, NP>);
+                self.app.add_systems(
+                    PreUpdate,
+                    authorize_messages::<T, NP>.after(ReliableDelivery).in_set(SyncSet::Authorize),
+                );
+            } else {
+                limit_packets(self.app, RateLimitKind::Message, T::short_name(), (T::type_name(), T::schema_hash()));
             }
-            // If no policy, just register the message - no middleware
         }
 
         self.app
@@ -656,6 +1164,8 @@ pub struct BatchMessageConfig {
     pub message_policy: Option<MessageAccessPolicy>,
     /// Whether to use the default message access policy.
     pub use_default_message_policy: bool,
+    /// Whether authorized messages are recorded in the command log.
+    pub logged: bool,
 }
 
 impl Default for BatchMessageConfig {
@@ -666,6 +1176,7 @@ impl Default for BatchMessageConfig {
             entity_policy: None,
             message_policy: None,
             use_default_message_policy: false,
+            logged: false,
         }
     }
 }
@@ -736,6 +1247,12 @@ where
         self.config.use_default_message_policy = true;
         self
     }
+
+    /// Record all messages in the command log (see [`MessageRegistration::logged`]).
+    pub fn logged(mut self) -> Self {
+        self.config.logged = true;
+        self
+    }
 }
 
 /// Trait for types that can be batch-registered as messages.
@@ -754,11 +1271,19 @@ where
 {
     use pl3xus::AppNetworkMessage;
 
+    crate::schema::record_message::<T>(app);
+    if config.logged {
+        crate::command_log::log_message::<T>(app, config.targeted);
+    }
+
     if config.targeted {
         // Register as targeted message
         app.register_targeted_message::<T, NP>();
 
-        let needs_auth = config.entity_policy.is_some() || config.use_default_entity_policy;
+        let needs_auth = config.entity_policy.is_some() || config.use_default_entity_policy || config.logged;
+        if config.use_default_entity_policy && config.entity_policy.is_none() {
+            crate::registration_checks::require_default_entity_policy(app, format!("Message {}", T::short_name()));
+        }
 
         if let Some(policy) = config.entity_policy.clone() {
             // Store per-message policy
@@ -772,13 +1297,19 @@ where
 
         if needs_auth {
             app.add_message::<AuthorizedTargetedMessage<T>>();
-            app.add_systems(PreUpdate, authorize_targeted_messages::<T, NP>);
+            app.add_systems(PreUpdate, authorize_targeted_messages::<T, NP>.in_set(SyncSet::Authorize));
+        } else {
+            let name = TargetedMessage::<T>::name();
+            limit_packets(app, RateLimitKind::Message, T::short_name(), (name, TargetedMessage::<T>::schema_hash()));
         }
     } else {
         // Register as plain message
         app.register_network_message::<T, NP>();
 
-        let needs_auth = config.message_policy.is_some() || config.use_default_message_policy;
+        let needs_auth = config.message_policy.is_some() || config.use_default_message_policy || config.logged;
+        if config.use_default_message_policy && config.message_policy.is_none() {
+            crate::registration_checks::require_default_message_policy(app, format!("Message {}", T::short_name()));
+        }
 
         if let Some(policy) = config.message_policy.clone() {
             if !app.world().contains_resource::<MessageAccessPolicies>() {
@@ -791,7 +1322,9 @@ where
 
         if needs_auth {
             app.add_message::<AuthorizedMessage<T>>();
-            app.add_systems(PreUpdate, authorize_messages::<T, NP>);
+            app.add_systems(PreUpdate, authorize_messages::<T, NP>.in_set(SyncSet::Authorize));
+        } else {
+            limit_packets(app, RateLimitKind::Message, T::short_name(), (T::type_name(), T::schema_hash()));
         }
     }
 }
@@ -884,7 +1417,7 @@ impl AppBatchMessageRegistrationExt for App {
 // REQUEST REGISTRATION (Request/Response Pattern)
 // ============================================================================
 
-use pl3xus::managers::network_request::{Request, AppNetworkRequestMessage};
+use pl3xus::managers::network_request::{request_packet_type, Request, AppNetworkRequestMessage, UnfilteredRequest};
 use pl3xus_common::RequestMessage;
 use serde::{Serialize, Deserialize};
 
@@ -967,6 +1500,21 @@ pub struct TargetedRequest<T: RequestMessage> {
     pub request: T,
 }
 
+impl<T: RequestMessage> TargetedRequest<T> {
+    /// Direct `request` at the entity with `entity_bits`.
+    pub fn new(entity_bits: u64, request: T) -> Self {
+        Self {
+            target_id: entity_bits.to_string(),
+            request,
+        }
+    }
+
+    /// The target entity, or `None` if `target_id` isn't valid entity bits.
+    pub fn target_entity(&self) -> Option<Entity> {
+        pl3xus_common::parse_target_entity(&self.target_id)
+    }
+}
+
 // Implement RequestMessage for TargetedRequest so it can be used with Request<T>
 impl<T: RequestMessage> RequestMessage for TargetedRequest<T> {
     type ResponseMessage = T::ResponseMessage;
@@ -1014,6 +1562,8 @@ where
     use_default_entity_policy: bool,
     message_policy: Option<MessageAccessPolicy>,
     use_default_message_policy: bool,
+    middleware: Vec<Middleware<T>>,
+    max_payload_bytes: Option<usize>,
     _marker: std::marker::PhantomData<(T, NP)>,
 }
 
@@ -1024,6 +1574,7 @@ where
 {
     /// Create a new request registration builder.
     pub fn new(app: &'a mut App) -> Self {
+        crate::schema::record_request::<T>(app);
         Self {
             app,
             targeted: false,
@@ -1031,6 +1582,8 @@ where
             use_default_entity_policy: false,
             message_policy: None,
             use_default_message_policy: false,
+            middleware: Vec::new(),
+            max_payload_bytes: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -1081,21 +1634,72 @@ where
         self
     }
 
-    /// Complete the registration and add systems to the app.
+    /// Add middleware that runs before the handler sees the request.
     ///
-    /// Note: For targeted requests with authorization, if authorization fails,
-    /// the request is dropped silently and the client will timeout.
-    /// Use [`with_error_response`] if `T` implements [`ErrorResponse`]
-    /// to send proper error responses.
-    pub fn register(self) -> &'a mut App {
-        if self.targeted {
-            // Register as targeted request
-            self.app.listen_for_request_message::<TargetedRequest<T>, NP>();
+    /// Middleware runs in the order it is added, before the access policy check.
+    /// Adding any middleware enables the authorization stage: targeted requests
+    /// are emitted as `AuthorizedRequest<T>`, while non-targeted requests that
+    /// pass are still read as `Request<T>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.request::<CreateProgram, NP>()
+    ///    .with_middleware(Middleware::rate_limit(5, Duration::from_secs(1)))
+    ///    .with_middleware(Middleware::validate(|req: &CreateProgram| validate_name(&req.name)))
+    ///    .with_error_response();
+    /// ```
+    pub fn with_middleware(mut self, middleware: Middleware<T>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
 
-            // Check if we need authorization middleware
-            let needs_auth = self.entity_policy.is_some() || self.use_default_entity_policy;
+    /// Reject requests larger than `max_bytes` before decoding them.
+    ///
+    /// The client gets a `PayloadTooLarge` message carrying the request id,
+    /// and fails the request instead of waiting for a timeout. For limits on
+    /// the decoded request, such as a maximum number of instructions, use
+    /// [`Middleware::max_items`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.request::<UploadProgram, NP>()
+    ///    .max_payload_bytes(1024 * 1024)
+    ///    .with_middleware(Middleware::max_items("instructions", 10_000, |req: &UploadProgram| {
+    ///        req.instructions.len()
+    ///    }))
+    ///    .with_error_response();
+    /// ```
+    pub fn max_payload_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_payload_bytes = Some(max_bytes);
+        self
+    }
 
-            if let Some(policy) = self.entity_policy {
+    /// Rate limit requests that skip the authorization stage as their packets arrive.
+    fn limit_request_packets(&mut self) {
+        let packet = if self.targeted {
+            request_packet_type::<TargetedRequest<T>>()
+        } else {
+            request_packet_type::<T>()
+        };
+        limit_packets(self.app, RateLimitKind::Request, T::request_name(), packet);
+    }
+
+    /// Store policies and middleware, returning whether the authorization stage is needed.
+    fn install_policies(&mut self) -> bool {
+        let has_middleware = !self.middleware.is_empty();
+        install_middleware::<T>(self.app, std::mem::take(&mut self.middleware));
+        if let Some(max_bytes) = self.max_payload_bytes {
+            if self.targeted {
+                self.app.limit_request_size::<TargetedRequest<T>, NP>(max_bytes);
+            } else {
+                self.app.limit_request_size::<T, NP>(max_bytes);
+            }
+        }
+
+        if self.targeted {
+            if let Some(policy) = self.entity_policy.take() {
                 // Store per-request policy (using the same storage as messages)
                 if !self.app.world().contains_resource::<EntityAccessPolicies>() {
                     self.app.init_resource::<EntityAccessPolicies>();
@@ -1104,20 +1708,68 @@ where
                     .world_mut()
                     .resource_mut::<EntityAccessPolicies>()
                     .insert::<TargetedRequest<T>>(policy);
+                return true;
+            }
+            if self.use_default_entity_policy {
+                crate::registration_checks::require_default_entity_policy(
+                    self.app,
+                    format!("Request {}", T::request_name()),
+                );
+            }
+            self.use_default_entity_policy || has_middleware
+        } else {
+            if let Some(policy) = self.message_policy.take() {
+                if !self.app.world().contains_resource::<MessageAccessPolicies>() {
+                    self.app.init_resource::<MessageAccessPolicies>();
+                }
+                self.app
+                    .world_mut()
+                    .resource_mut::<MessageAccessPolicies>()
+                    .insert::<T>(policy);
+                return true;
+            }
+            if self.use_default_message_policy {
+                crate::registration_checks::require_default_message_policy(
+                    self.app,
+                    format!("Request {}", T::request_name()),
+                );
             }
+            self.use_default_message_policy || has_middleware
+        }
+    }
+
+    /// Complete the registration and add systems to the app.
+    ///
+    /// Note: For requests with authorization or middleware, if a check fails,
+    /// the request is dropped and the client will timeout.
+    /// Use [`with_error_response`] if `T` implements [`ErrorResponse`]
+    /// to send proper error responses.
+    pub fn register(mut self) -> &'a mut App {
+        let needs_auth = self.install_policies();
+        if !needs_auth {
+            self.limit_request_packets();
+        }
+
+        if self.targeted {
+            // Register as targeted request
+            self.app.listen_for_request_message::<TargetedRequest<T>, NP>();
 
             if needs_auth {
                 // Add authorization middleware
+                // If no per-request policy, the middleware will use DefaultEntityAccessPolicy
                 self.app.add_message::<AuthorizedRequest<T>>();
                 self.app
-                    .add_systems(PreUpdate, authorize_targeted_requests::<T, NP>);
+                    .add_systems(PreUpdate, authorize_targeted_requests::<T, NP>.in_set(SyncSet::Authorize));
             }
         } else {
             // Register as plain request
             self.app.listen_for_request_message::<T, NP>();
 
-            // Note: Non-targeted request authorization could be added here if needed
-            // For now, non-targeted requests don't have authorization middleware
+            if needs_auth {
+                // Hold requests back until they pass the filter
+                self.app.add_message::<UnfilteredRequest<T>>();
+                self.app.add_systems(PreUpdate, filter_requests::<T>.in_set(SyncSet::Authorize));
+            }
         }
 
         self.app
@@ -1136,36 +1788,40 @@ where
     /// - The target entity ID is invalid
     /// - The target entity does not exist
     /// - Authorization is denied
+    /// - A middleware denies the request
     ///
     /// This is the recommended method for targeted requests with authorization.
-    pub fn with_error_response(self) -> &'a mut App {
+    pub fn with_error_response(mut self) -> &'a mut App {
+        let needs_auth = self.install_policies();
+        if !needs_auth {
+            self.limit_request_packets();
+        }
+
         if self.targeted {
             // Register as targeted request
             self.app.listen_for_request_message::<TargetedRequest<T>, NP>();
 
-            // Check if we need authorization middleware
-            let needs_auth = self.entity_policy.is_some() || self.use_default_entity_policy;
-
-            if let Some(policy) = self.entity_policy {
-                // Store per-request policy
-                if !self.app.world().contains_resource::<EntityAccessPolicies>() {
-                    self.app.init_resource::<EntityAccessPolicies>();
-                }
-                self.app
-                    .world_mut()
-                    .resource_mut::<EntityAccessPolicies>()
-                    .insert::<TargetedRequest<T>>(policy);
-            }
-
             if needs_auth {
                 // Add authorization middleware with error response support
                 self.app.add_message::<AuthorizedRequest<T>>();
                 self.app
-                    .add_systems(PreUpdate, authorize_targeted_requests_with_error_response::<T, NP>);
+                    .add_systems(
+                        PreUpdate,
+                        authorize_targeted_requests_with_error_response::<T, NP>.in_set(SyncSet::Authorize),
+                    );
             }
         } else {
             // Register as plain request
             self.app.listen_for_request_message::<T, NP>();
+
+            if needs_auth {
+                // Hold requests back until they pass the filter
+                self.app.add_message::<UnfilteredRequest<T>>();
+                self.app.add_systems(
+                    PreUpdate,
+                    filter_requests_with_error_response::<T>.in_set(SyncSet::Authorize),
+                );
+            }
         }
 
         self.app
@@ -1204,6 +1860,63 @@ impl AppRequestRegistrationExt for App {
     }
 }
 
+// ============================================================================
+// DERIVED REQUEST REGISTRATION
+// ============================================================================
+
+/// Trait for request types that know how to register themselves.
+///
+/// This is typically implemented via `#[derive(Pl3xusRequest)]` from
+/// `pl3xus_macros`, which reads `#[targeted]`, `#[error_response]` and
+/// `#[invalidates(...)]` attributes and generates the matching builder calls.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Serialize, Deserialize, Clone, Debug, Pl3xusRequest)]
+/// #[response(SetSpeedOverrideResponse)]
+/// #[targeted(default_policy)]
+/// #[error_response]
+/// pub struct SetSpeedOverride { pub speed: f32 }
+///
+/// // Equivalent to:
+/// // app.request::<SetSpeedOverride, NP>()
+/// //    .targeted()
+/// //    .with_default_entity_policy()
+/// //    .with_error_response();
+/// SetSpeedOverride::register_request::<NP>(&mut app);
+///
+/// // Or register several at once
+/// pl3xus_sync::register_requests!(&mut app, NP; SetSpeedOverride, ListPrograms, CreateProgram);
+/// ```
+pub trait RegisterRequest: RequestMessage + Clone + 'static {
+    /// Register this request type with the app for the given network provider.
+    fn register_request<NP: NetworkProvider>(app: &mut App);
+}
+
+/// Register several [`RegisterRequest`] types in one call.
+///
+/// The first argument is a `&mut App` (as passed to `Plugin::build`).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// pl3xus_sync::register_requests!(app, WebSocketProvider;
+///     ListPrograms,
+///     GetProgram,
+///     CreateProgram,
+/// );
+/// ```
+#[macro_export]
+macro_rules! register_requests {
+    ($app:expr, $np:ty; $($request:ty),+ $(,)?) => {{
+        let app: &mut ::bevy::prelude::App = $app;
+        $(
+            <$request as $crate::authorization::RegisterRequest>::register_request::<$np>(app);
+        )+
+    }};
+}
+
 // ============================================================================
 // BATCH REQUEST REGISTRATION
 // ============================================================================
@@ -1439,7 +2152,7 @@ impl AppBatchRequestRegistrationExt for App {
 ///
 /// This exclusive system:
 /// 1. Reads all incoming `NetworkData<TargetedMessage<T>>`
-/// 2. Parses the target_id as entity bits (u64)
+/// 2. Extracts the target entity from the envelope and checks it exists
 /// 3. Checks authorization (per-message policy, then default policy, then allow)
 /// 4. Emits `AuthorizedTargetedMessage<T>` for authorized messages
 /// 5. Sends rejection notifications to unauthorized clients
@@ -1475,10 +2188,9 @@ where
     for msg in incoming {
         let source = *msg.source();
 
-        // Parse target_id as entity bits (u64)
-        let entity = match msg.target_id.parse::<u64>() {
-            Ok(bits) => Entity::from_bits(bits),
-            Err(_) => {
+        let entity = match msg.target_entity() {
+            Some(entity) => entity,
+            None => {
                 warn!(
                     "Invalid target_id '{}' from {:?} - expected entity bits (u64)",
                     msg.target_id, source
@@ -1487,17 +2199,25 @@ where
                 continue;
             }
         };
+        if world.get_entity(entity).is_err() {
+            rejections.push((source, format!("Target entity {:?} does not exist", entity)));
+            continue;
+        }
 
-        // Check authorization
-        let auth_result = match &policy {
-            Some(p) => p.check(world, source, entity),
-            None => AuthResult::Authorized, // No policy = allow all
+        // Run middleware, then check authorization
+        let mut message = msg.message.clone();
+        let auth_result = match run_middleware(world, source, RateLimitKind::Message, T::short_name(), Some(entity), &mut message) {
+            AuthResult::Authorized => match &policy {
+                Some(p) => p.check(world, source, entity),
+                None => AuthResult::Authorized, // No policy = allow all
+            },
+            denied => denied,
         };
 
         match auth_result {
             AuthResult::Authorized => {
                 authorized_messages.push(AuthorizedTargetedMessage {
-                    message: msg.message.clone(),
+                    message,
                     source,
                     target_entity: entity,
                 });
@@ -1515,6 +2235,14 @@ where
         }
     }
 
+    if crate::command_log::is_logged::<T>(world) {
+        let entries = authorized_messages
+            .iter()
+            .filter_map(|msg| crate::command_log::message_entry(msg.source, Some(msg.target_entity), &msg.message))
+            .collect();
+        crate::command_log::record(world, entries);
+    }
+
     // Write authorized messages
     if !authorized_messages.is_empty() {
         let mut messages = world.resource_mut::<Messages<AuthorizedTargetedMessage<T>>>();
@@ -1524,19 +2252,9 @@ where
     }
 
     // Send rejection notifications to clients
-    if !rejections.is_empty() {
-        if let Some(net) = world.get_resource::<Network<NP>>() {
-            for (client_id, reason) in rejections {
-                let notification =
-                    ServerNotification::warning(reason).with_context(T::type_name().to_string());
-                if let Err(e) = net.send(client_id, notification) {
-                    warn!(
-                        "Failed to send rejection notification to {:?}: {:?}",
-                        client_id, e
-                    );
-                }
-            }
-        }
+    for (client_id, reason) in rejections {
+        let notification = ServerNotification::warning(reason).with_context(T::type_name().to_string());
+        crate::notifications::notify_connection::<NP>(world, client_id, notification);
     }
 }
 
@@ -1579,16 +2297,20 @@ where
     for msg in incoming {
         let source = *msg.source();
 
-        // Check authorization
-        let auth_result = match &policy {
-            Some(p) => p.check(world, source),
-            None => AuthResult::Authorized, // No policy = allow all
+        // Run middleware, then check authorization
+        let mut message = (*msg).clone();
+        let auth_result = match run_middleware(world, source, RateLimitKind::Message, T::short_name(), None, &mut message) {
+            AuthResult::Authorized => match &policy {
+                Some(p) => p.check(world, source),
+                None => AuthResult::Authorized, // No policy = allow all
+            },
+            denied => denied,
         };
 
         match auth_result {
             AuthResult::Authorized => {
                 authorized_messages.push(AuthorizedMessage {
-                    message: (*msg).clone(),
+                    message,
                     source,
                 });
             }
@@ -1604,6 +2326,14 @@ where
         }
     }
 
+    if crate::command_log::is_logged::<T>(world) {
+        let entries = authorized_messages
+            .iter()
+            .filter_map(|msg| crate::command_log::message_entry(msg.source, None, &msg.message))
+            .collect();
+        crate::command_log::record(world, entries);
+    }
+
     // Write authorized messages
     if !authorized_messages.is_empty() {
         let mut messages = world.resource_mut::<Messages<AuthorizedMessage<T>>>();
@@ -1613,19 +2343,9 @@ where
     }
 
     // Send rejection notifications
-    if !rejections.is_empty() {
-        if let Some(net) = world.get_resource::<Network<NP>>() {
-            for (client_id, reason) in rejections {
-                let notification =
-                    ServerNotification::warning(reason).with_context(T::type_name().to_string());
-                if let Err(e) = net.send(client_id, notification) {
-                    warn!(
-                        "Failed to send rejection notification to {:?}: {:?}",
-                        client_id, e
-                    );
-                }
-            }
-        }
+    for (client_id, reason) in rejections {
+        let notification = ServerNotification::warning(reason).with_context(T::type_name().to_string());
+        crate::notifications::notify_connection::<NP>(world, client_id, notification);
     }
 }
 
@@ -1633,7 +2353,7 @@ where
 ///
 /// This exclusive system:
 /// 1. Reads all incoming `Request<TargetedRequest<T>>`
-/// 2. Parses the target_id as entity bits (u64)
+/// 2. Extracts the target entity from the envelope
 /// 3. Checks authorization (per-request policy, then default policy, then allow)
 /// 4. Emits `AuthorizedRequest<T>` for authorized requests
 /// 5. Sends rejection responses to unauthorized clients
@@ -1665,14 +2385,13 @@ where
     // Process each request
     let mut authorized_requests = Vec::new();
 
-    for req in incoming {
+    for mut req in incoming {
         let source = *req.source();
-        let target_id_str = &req.get_request().target_id;
+        let target_id_str = req.get_request().target_id.clone();
 
-        // Parse target_id as entity bits
-        let target_entity = match target_id_str.parse::<u64>() {
-            Ok(bits) => Entity::from_bits(bits),
-            Err(_) => {
+        let target_entity = match req.get_request().target_entity() {
+            Some(entity) => entity,
+            None => {
                 warn!(
                     "Request {} from {:?}: invalid target_id '{}' - request dropped",
                     T::request_name(),
@@ -1697,10 +2416,20 @@ where
             continue;
         }
 
-        // Check authorization
-        let auth_result = match &policy {
-            Some(p) => p.check(world, source, target_entity),
-            None => AuthResult::Authorized, // No policy = allow all
+        // Run middleware, then check authorization
+        let auth_result = match run_middleware(
+            world,
+            source,
+            RateLimitKind::Request,
+            T::request_name(),
+            Some(target_entity),
+            &mut req.get_request_mut().request,
+        ) {
+            AuthResult::Authorized => match &policy {
+                Some(p) => p.check(world, source, target_entity),
+                None => AuthResult::Authorized, // No policy = allow all
+            },
+            denied => denied,
         };
 
         match auth_result {
@@ -1763,14 +2492,13 @@ where
     let mut authorized_requests = Vec::new();
     let mut error_responses: Vec<(Request<TargetedRequest<T>>, T::ResponseMessage)> = Vec::new();
 
-    for req in incoming {
+    for mut req in incoming {
         let source = *req.source();
         let target_id_str = req.get_request().target_id.clone();
 
-        // Parse target_id as entity bits
-        let target_entity = match target_id_str.parse::<u64>() {
-            Ok(bits) => Entity::from_bits(bits),
-            Err(_) => {
+        let target_entity = match req.get_request().target_entity() {
+            Some(entity) => entity,
+            None => {
                 warn!(
                     "Request {} from {:?}: invalid target_id '{}'",
                     T::request_name(),
@@ -1800,10 +2528,20 @@ where
             continue;
         }
 
-        // Check authorization
-        let auth_result = match &policy {
-            Some(p) => p.check(world, source, target_entity),
-            None => AuthResult::Authorized, // No policy = allow all
+        // Run middleware, then check authorization
+        let auth_result = match run_middleware(
+            world,
+            source,
+            RateLimitKind::Request,
+            T::request_name(),
+            Some(target_entity),
+            &mut req.get_request_mut().request,
+        ) {
+            AuthResult::Authorized => match &policy {
+                Some(p) => p.check(world, source, target_entity),
+                None => AuthResult::Authorized, // No policy = allow all
+            },
+            denied => denied,
         };
 
         match auth_result {
@@ -1838,3 +2576,296 @@ where
         }
     }
 }
+
+/// Middleware and access-policy stage for non-targeted requests.
+///
+/// This exclusive system:
+/// 1. Reads all `UnfilteredRequest<T>` held back for this stage
+/// 2. Runs the middleware chain for `T`
+/// 3. Checks the per-request or default message access policy
+/// 4. Writes the requests that pass as `Request<T>` for handlers
+///
+/// Rejected requests are dropped and the client will timeout.
+fn filter_requests<T>(world: &mut World)
+where
+    T: RequestMessage + Clone + 'static,
+{
+    let (passed, rejected) = filter_incoming_requests::<T>(world);
+
+    for (req, reason) in rejected {
+        warn!(
+            "Request {} from {:?} denied: {} - request dropped",
+            T::request_name(),
+            req.source(),
+            reason
+        );
+    }
+
+    if !passed.is_empty() {
+        let mut messages = world.resource_mut::<Messages<Request<T>>>();
+        for req in passed {
+            messages.write(req);
+        }
+    }
+}
+
+/// Middleware and access-policy stage for non-targeted requests that implement ErrorResponse.
+///
+/// This is similar to `filter_requests` but responds with `T::error_response`
+/// when a request is rejected instead of silently dropping it.
+fn filter_requests_with_error_response<T>(world: &mut World)
+where
+    T: RequestMessage + pl3xus_common::ErrorResponse + Clone + 'static,
+{
+    let (passed, rejected) = filter_incoming_requests::<T>(world);
+
+    if !passed.is_empty() {
+        let mut messages = world.resource_mut::<Messages<Request<T>>>();
+        for req in passed {
+            messages.write(req);
+        }
+    }
+
+    for (req, reason) in rejected {
+        warn!(
+            "Request {} from {:?} denied: {}",
+            T::request_name(),
+            req.source(),
+            reason
+        );
+        if let Err(e) = req.respond(T::error_response(reason)) {
+            warn!("Failed to send error response: {:?}", e);
+        }
+    }
+}
+
+/// Drain held `UnfilteredRequest<T>` and split them into passed and rejected requests.
+#[allow(clippy::type_complexity)]
+fn filter_incoming_requests<T>(world: &mut World) -> (Vec<Request<T>>, Vec<(Request<T>, String)>)
+where
+    T: RequestMessage + Clone + 'static,
+{
+    let incoming: Vec<_> = {
+        let mut messages = world.resource_mut::<Messages<UnfilteredRequest<T>>>();
+        messages.drain().map(|held| held.0).collect()
+    };
+
+    if incoming.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    // Get per-request policy, falling back to default policy
+    let policy: Option<MessageAccessPolicy> = world
+        .get_resource::<MessageAccessPolicies>()
+        .and_then(|p| p.get::<T>().cloned())
+        .or_else(|| {
+            world
+                .get_resource::<DefaultMessageAccessPolicy>()
+                .map(|d| d.0.clone())
+        });
+
+    let mut passed = Vec::new();
+    let mut rejected = Vec::new();
+
+    for mut req in incoming {
+        let source = *req.source();
+
+        let auth_result = match run_middleware(world, source, RateLimitKind::Request, T::request_name(), None, req.get_request_mut()) {
+            AuthResult::Authorized => match &policy {
+                Some(p) => p.check(world, source),
+                None => AuthResult::Authorized,
+            },
+            denied => denied,
+        };
+
+        match auth_result {
+            AuthResult::Authorized => passed.push(req),
+            AuthResult::Denied(reason) => rejected.push((req, reason)),
+        }
+    }
+
+    (passed, rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus::ConnectionInfos;
+    use std::time::Duration;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct SetSpeed {
+        percent: u32,
+    }
+
+    impl RequestMessage for SetSpeed {
+        type ResponseMessage = bool;
+    }
+
+    fn context(world: &World, id: u32) -> MiddlewareContext<'_> {
+        MiddlewareContext {
+            world,
+            source: ConnectionId { id },
+            type_name: "SetSpeed",
+            target_entity: None,
+        }
+    }
+
+    #[test]
+    fn test_middleware_chain_runs_in_order() {
+        let world = World::new();
+        let mut chain = MiddlewareChain::<Vec<&'static str>>::default();
+        chain.push(Middleware::from_fn(|_, trace: &mut Vec<&'static str>| {
+            trace.push("first");
+            Ok(())
+        }));
+        chain.push(Middleware::from_fn(|_, trace: &mut Vec<&'static str>| {
+            trace.push("second");
+            Err("denied by second".to_string())
+        }));
+        chain.push(Middleware::from_fn(|_, trace: &mut Vec<&'static str>| {
+            trace.push("third");
+            Ok(())
+        }));
+
+        let mut trace = Vec::new();
+        let result = chain.run(&context(&world, 1), &mut trace);
+
+        // The chain stops at the first denial
+        assert!(matches!(result, AuthResult::Denied(reason) if reason == "denied by second"));
+        assert_eq!(trace, ["first", "second"]);
+    }
+
+    #[test]
+    fn test_middleware_transforms_before_later_checks() {
+        let world = World::new();
+        let mut chain = MiddlewareChain::<SetSpeed>::default();
+        chain.push(Middleware::from_fn(|_, request: &mut SetSpeed| {
+            request.percent = request.percent.min(100);
+            Ok(())
+        }));
+        chain.push(Middleware::validate(|request: &SetSpeed| {
+            if request.percent > 100 {
+                Err("Speed over 100%".to_string())
+            } else {
+                Ok(())
+            }
+        }));
+        chain.push(Middleware::validate(|request: &SetSpeed| {
+            if request.percent == 0 {
+                Err("Speed must be positive".to_string())
+            } else {
+                Ok(())
+            }
+        }));
+
+        // Clamped to 100 before the validation sees it
+        let mut request = SetSpeed { percent: 250 };
+        assert!(chain.run(&context(&world, 1), &mut request).is_authorized());
+        assert_eq!(request.percent, 100);
+
+        let mut request = SetSpeed { percent: 0 };
+        assert!(!chain.run(&context(&world, 1), &mut request).is_authorized());
+    }
+
+    #[test]
+    fn test_rate_limit_middleware() {
+        let world = World::new();
+        let limit = Middleware::<SetSpeed>::rate_limit(2, Duration::from_secs(60));
+        let mut request = SetSpeed { percent: 10 };
+
+        assert!(limit.intercept(&context(&world, 1), &mut request).is_authorized());
+        assert!(limit.intercept(&context(&world, 1), &mut request).is_authorized());
+        assert!(!limit.intercept(&context(&world, 1), &mut request).is_authorized());
+
+        // Each connection has its own window, and the server has none
+        assert!(limit.intercept(&context(&world, 2), &mut request).is_authorized());
+        for _ in 0..5 {
+            assert!(limit.intercept(&context(&world, 0), &mut request).is_authorized());
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_middleware_forgets_disconnected_connections() {
+        let mut app = App::new();
+        app.add_message::<NetworkEvent>();
+        install_middleware(&mut app, vec![Middleware::<SetSpeed>::rate_limit(1, Duration::from_secs(60))]);
+        let run = |app: &App| {
+            let chain = app.world().resource::<MiddlewareChain<SetSpeed>>();
+            chain.run(&context(app.world(), 1), &mut SetSpeed { percent: 10 }).is_authorized()
+        };
+        assert!(run(&app));
+        assert!(!run(&app));
+
+        // The connection's bucket is dropped when it disconnects
+        app.world_mut().write_message(NetworkEvent::Disconnected(ConnectionId { id: 1 }));
+        app.update();
+        assert!(run(&app));
+    }
+
+    #[test]
+    fn test_poisoned_rate_limiter_denies() {
+        let world = World::new();
+        let limiter = RateLimitInterceptor {
+            max: 10,
+            window: Duration::from_secs(60),
+            buckets: std::sync::Mutex::new(HashMap::new()),
+        };
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = limiter.buckets.lock().unwrap();
+            panic!("poison the limiter");
+        }));
+
+        let mut request = SetSpeed { percent: 10 };
+        let result = MessageInterceptor::<SetSpeed>::intercept(&limiter, &context(&world, 1), &mut request);
+        assert!(!result.is_authorized());
+    }
+
+    #[test]
+    fn test_filtered_requests_reach_handlers_as_requests() {
+        let mut world = World::new();
+        world.init_resource::<Messages<UnfilteredRequest<SetSpeed>>>();
+        world.init_resource::<Messages<Request<SetSpeed>>>();
+        let mut policies = MessageAccessPolicies::default();
+        policies.insert::<SetSpeed>(MessageAccessPolicy::server_only());
+        world.insert_resource(policies);
+
+        for source in [ConnectionId::SERVER, ConnectionId { id: 1 }] {
+            let (request, _response) = Request::local(source, SetSpeed { percent: 10 });
+            world.write_message(UnfilteredRequest(request));
+        }
+        filter_requests::<SetSpeed>(&mut world);
+
+        let passed: Vec<ConnectionId> = world
+            .resource_mut::<Messages<Request<SetSpeed>>>()
+            .drain()
+            .map(|request| *request.source())
+            .collect();
+        assert_eq!(passed, [ConnectionId::SERVER]);
+        assert!(world.resource::<Messages<UnfilteredRequest<SetSpeed>>>().is_empty());
+    }
+
+    #[test]
+    fn test_endpoint_policies() {
+        let admin = ConnectionId { id: 1 };
+        let sync = ConnectionId { id: 2 };
+        let mut world = World::new();
+        let mut infos = ConnectionInfos::default();
+        infos.set_endpoint(admin, "/admin");
+        infos.set_endpoint(sync, "/sync");
+        world.insert_resource(infos);
+
+        let policy = MessageAccessPolicy::endpoints(["/admin"]);
+        assert!(policy.check(&world, admin).is_authorized());
+        assert!(!policy.check(&world, sync).is_authorized());
+        assert!(policy.check(&world, ConnectionId::SERVER).is_authorized());
+
+        let policy = MessageAccessPolicy::by_endpoint(
+            [("/sync", MessageAccessPolicy::allow_all())],
+            MessageAccessPolicy::server_only(),
+        );
+        assert!(policy.check(&world, sync).is_authorized());
+        assert!(!policy.check(&world, admin).is_authorized());
+        assert!(!policy.check(&world, ConnectionId { id: 3 }).is_authorized());
+    }
+}
@@ -0,0 +1,587 @@
+//! Admin client for pl3xus servers.
+//!
+//! Connects to a server running `AdminPlugin`, whose policy must admit this
+//! client, and runs one command:
+//!
+//! ```text
+//! pl3xus-cli [--url ws://127.0.0.1:8083] [--timeout-ms 5000] <command>
+//!
+//! connections                         connected clients and their traffic
+//! subscriptions [<connection id>]     component subscriptions
+//! snapshot <Component> [<entity>]     current component values as JSON
+//! request <Request> <json>            send a request enabled with `admin_request`
+//! control take <entity>               take control and hold it until Ctrl-C
+//! control release <entity>            release control, whoever holds it
+//! logs [--follow] [--lines <n>]       server console log
+//! export                              every synced component as a JSON world snapshot
+//! import <file>                       spawn the entities of a world snapshot
+//! settings [<name>=<value>...]        show or change runtime settings
+//! ```
+//!
+//! Entities are given as `Entity::to_bits()` values, as printed by `snapshot`.
+//!
+//! ```text
+//! cargo run -p pl3xus_sync --features cli --bin pl3xus-cli -- --url ws://robot-cell:8083 connections
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::prelude::*;
+use bevy::tasks::TaskPoolBuilder;
+use pl3xus::managers::network_request::{AppNetworkResponseMessage, Requester, Response};
+use pl3xus::{AppNetworkMessage, ConnectionId, Network, NetworkData, NetworkEvent, Pl3xusPlugin, Pl3xusRuntime};
+use pl3xus_common::{ControlRequest, ControlResponse, ControlResponseKind};
+use pl3xus_sync::admin::{
+    AdminConnectionsResponse, AdminConsoleResponse, AdminExportWorldSnapshot, AdminExportWorldSnapshotResponse,
+    AdminImportWorldSnapshot, AdminImportWorldSnapshotResponse, AdminJsonRequest, AdminJsonResponse,
+    AdminListConnections, AdminListSubscriptions, AdminReleaseControl, AdminReleaseControlResponse, AdminSnapshot,
+    AdminSnapshotResponse, AdminSubscriptionsResponse, AdminTailConsole, UpdateServerSettings,
+    UpdateServerSettingsResponse,
+};
+use pl3xus_sync::{SerializableEntity, SyncServerMessage};
+use pl3xus_websockets::{NetworkSettings, WebSocketProvider};
+
+/// How often `logs --follow` polls the server.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+const USAGE: &str = "usage: pl3xus-cli [--url <ws://host:port>] [--timeout-ms <ms>] <command>
+
+commands:
+  connections
+  subscriptions [<connection id>]
+  snapshot <Component> [<entity>]
+  request <Request> <json>
+  control take <entity>
+  control release <entity>
+  logs [--follow] [--lines <n>]
+  export
+  import <file>
+  settings [<name>=<value>...]";
+
+#[derive(Debug, Clone)]
+enum Command {
+    Connections,
+    Subscriptions { connection_id: Option<ConnectionId> },
+    Snapshot { component_type: String, entity: Option<SerializableEntity> },
+    Request { request_type: String, payload: String },
+    TakeControl { entity: u64 },
+    ReleaseControl { entity: u64 },
+    Logs { follow: bool, lines: u32 },
+    Export,
+    Import { snapshot: String },
+    Settings { changes: std::collections::BTreeMap<String, String> },
+}
+
+#[derive(Resource, Debug, Clone)]
+struct CliConfig {
+    url: url::Url,
+    timeout: Duration,
+    command: Command,
+}
+
+fn parse_entity(value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| format!("invalid entity '{}' (expected entity bits)", value))
+}
+
+impl CliConfig {
+    fn from_args() -> Result<Self, String> {
+        let mut url = url::Url::parse("ws://127.0.0.1:8083").unwrap();
+        let mut timeout = Duration::from_secs(5);
+        let mut args = std::env::args().skip(1).peekable();
+
+        while let Some(flag) = args.next_if(|arg| arg.starts_with("--")) {
+            let value = args.next().ok_or_else(|| format!("missing value for {}", flag))?;
+            match flag.as_str() {
+                "--url" => url = url::Url::parse(&value).map_err(|e| format!("invalid --url: {}", e))?,
+                "--timeout-ms" => {
+                    timeout = Duration::from_millis(value.parse().map_err(|_| "invalid --timeout-ms")?)
+                }
+                other => return Err(format!("unknown option: {}", other)),
+            }
+        }
+
+        let args: Vec<String> = args.collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let command = match args.as_slice() {
+            ["connections"] => Command::Connections,
+            ["subscriptions"] => Command::Subscriptions { connection_id: None },
+            ["subscriptions", id] => Command::Subscriptions {
+                connection_id: Some(ConnectionId {
+                    id: id.parse().map_err(|_| format!("invalid connection id '{}'", id))?,
+                }),
+            },
+            ["snapshot", component] => Command::Snapshot {
+                component_type: component.to_string(),
+                entity: None,
+            },
+            ["snapshot", component, entity] => Command::Snapshot {
+                component_type: component.to_string(),
+                entity: Some(SerializableEntity { bits: parse_entity(entity)? }),
+            },
+            ["request", request, payload] => Command::Request {
+                request_type: request.to_string(),
+                payload: payload.to_string(),
+            },
+            ["control", "take", entity] => Command::TakeControl { entity: parse_entity(entity)? },
+            ["control", "release", entity] => Command::ReleaseControl { entity: parse_entity(entity)? },
+            ["logs", options @ ..] => {
+                let mut follow = false;
+                let mut lines = 50;
+                let mut options = options.iter();
+                while let Some(option) = options.next() {
+                    match *option {
+                        "--follow" | "-f" => follow = true,
+                        "--lines" | "-n" => {
+                            lines = options
+                                .next()
+                                .and_then(|n| n.parse().ok())
+                                .ok_or("invalid --lines")?
+                        }
+                        other => return Err(format!("unknown logs option: {}", other)),
+                    }
+                }
+                Command::Logs { follow, lines }
+            }
+            ["export"] => Command::Export,
+            ["import", file] => Command::Import {
+                snapshot: std::fs::read_to_string(file).map_err(|e| format!("cannot read '{}': {}", file, e))?,
+            },
+            ["settings", changes @ ..] => Command::Settings {
+                changes: changes
+                    .iter()
+                    .map(|change| {
+                        change
+                            .split_once('=')
+                            .map(|(name, value)| (name.to_string(), value.to_string()))
+                            .ok_or_else(|| format!("invalid setting '{}' (expected <name>=<value>)", change))
+                    })
+                    .collect::<Result<_, _>>()?,
+            },
+            _ => return Err(USAGE.to_string()),
+        };
+
+        Ok(Self { url, timeout, command })
+    }
+}
+
+/// What the CLI is waiting for.
+#[derive(Default)]
+enum Pending {
+    /// Not connected yet
+    #[default]
+    Connecting,
+    Connections(Response<AdminConnectionsResponse>),
+    Subscriptions(Response<AdminSubscriptionsResponse>),
+    Snapshot(Response<AdminSnapshotResponse>),
+    Request(Response<AdminJsonResponse>),
+    Release(Response<AdminReleaseControlResponse>),
+    Logs(Response<AdminConsoleResponse>),
+    Export(Response<AdminExportWorldSnapshotResponse>),
+    Import(Response<AdminImportWorldSnapshotResponse>),
+    Settings(Response<UpdateServerSettingsResponse>),
+    /// Waiting for a `ControlResponse`
+    Control,
+    /// Control is held until the process is interrupted
+    Holding,
+    /// Waiting to poll the console again (`logs --follow`)
+    Following { next_poll: Instant },
+    /// The command finished
+    Done,
+}
+
+#[derive(Resource, Default)]
+struct CliState {
+    connection: Option<ConnectionId>,
+    pending: Pending,
+    /// When the current request was sent
+    sent_at: Option<Instant>,
+    /// Newest console entry printed so far
+    last_sequence: u64,
+}
+
+fn main() -> AppExit {
+    let config = match CliConfig::from_args() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return AppExit::error();
+        }
+    };
+
+    App::new()
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_millis(10))))
+        .add_plugins(bevy::log::LogPlugin {
+            level: bevy::log::Level::ERROR,
+            ..Default::default()
+        })
+        .add_plugins(Pl3xusPlugin::<WebSocketProvider, bevy::tasks::TaskPool>::default())
+        .insert_resource(Pl3xusRuntime(TaskPoolBuilder::new().build()))
+        .insert_resource(NetworkSettings::default())
+        .register_network_message::<SyncServerMessage, WebSocketProvider>()
+        .register_network_message::<ControlRequest, WebSocketProvider>()
+        .register_network_message::<ControlResponse, WebSocketProvider>()
+        .listen_for_response_message::<AdminListConnections, WebSocketProvider>()
+        .listen_for_response_message::<AdminListSubscriptions, WebSocketProvider>()
+        .listen_for_response_message::<AdminSnapshot, WebSocketProvider>()
+        .listen_for_response_message::<AdminJsonRequest, WebSocketProvider>()
+        .listen_for_response_message::<AdminReleaseControl, WebSocketProvider>()
+        .listen_for_response_message::<AdminTailConsole, WebSocketProvider>()
+        .listen_for_response_message::<AdminExportWorldSnapshot, WebSocketProvider>()
+        .listen_for_response_message::<AdminImportWorldSnapshot, WebSocketProvider>()
+        .listen_for_response_message::<UpdateServerSettings, WebSocketProvider>()
+        .insert_resource(config)
+        .init_resource::<CliState>()
+        .add_systems(Startup, connect)
+        .add_systems(
+            Update,
+            (handle_connection_events, send_command, poll_responses, handle_control_responses, check_timeout)
+                .chain(),
+        )
+        .run()
+}
+
+fn connect(
+    net: Res<Network<WebSocketProvider>>,
+    settings: Res<NetworkSettings>,
+    task_pool: Res<Pl3xusRuntime<bevy::tasks::TaskPool>>,
+    config: Res<CliConfig>,
+    mut state: ResMut<CliState>,
+) {
+    net.connect(config.url.clone(), &task_pool.0, &settings);
+    state.sent_at = Some(Instant::now());
+}
+
+fn handle_connection_events(
+    mut events: MessageReader<NetworkEvent>,
+    mut state: ResMut<CliState>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    for event in events.read() {
+        match event {
+            NetworkEvent::Connected(connection_id, _) => state.connection = Some(*connection_id),
+            NetworkEvent::Disconnected(_) => {
+                eprintln!("Disconnected from server");
+                exit.write(AppExit::error());
+            }
+            NetworkEvent::Error(e) => {
+                eprintln!("Network error: {}", e);
+                exit.write(AppExit::error());
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn send_command(
+    net: Res<Network<WebSocketProvider>>,
+    connections: Requester<AdminListConnections, WebSocketProvider>,
+    subscriptions: Requester<AdminListSubscriptions, WebSocketProvider>,
+    snapshots: Requester<AdminSnapshot, WebSocketProvider>,
+    requests: Requester<AdminJsonRequest, WebSocketProvider>,
+    releases: Requester<AdminReleaseControl, WebSocketProvider>,
+    logs: Requester<AdminTailConsole, WebSocketProvider>,
+    exports: Requester<AdminExportWorldSnapshot, WebSocketProvider>,
+    imports: Requester<AdminImportWorldSnapshot, WebSocketProvider>,
+    settings: Requester<UpdateServerSettings, WebSocketProvider>,
+    config: Res<CliConfig>,
+    mut state: ResMut<CliState>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some(connection) = state.connection else {
+        return;
+    };
+    let sent = match &state.pending {
+        Pending::Connecting => match &config.command {
+            Command::Connections => connections
+                .send_request(connection, AdminListConnections)
+                .map(Pending::Connections),
+            Command::Subscriptions { connection_id } => subscriptions
+                .send_request(connection, AdminListSubscriptions { connection_id: *connection_id })
+                .map(Pending::Subscriptions),
+            Command::Snapshot { component_type, entity } => snapshots
+                .send_request(
+                    connection,
+                    AdminSnapshot {
+                        component_type: component_type.clone(),
+                        entity: *entity,
+                    },
+                )
+                .map(Pending::Snapshot),
+            Command::Request { request_type, payload } => requests
+                .send_request(
+                    connection,
+                    AdminJsonRequest {
+                        request_type: request_type.clone(),
+                        payload: payload.clone(),
+                    },
+                )
+                .map(Pending::Request),
+            Command::TakeControl { entity } => net
+                .send(connection, ControlRequest::take(*entity))
+                .map(|_| Pending::Control),
+            Command::ReleaseControl { entity } => releases
+                .send_request(connection, AdminReleaseControl { entity: *entity })
+                .map(Pending::Release),
+            Command::Logs { lines, .. } => logs
+                .send_request(connection, AdminTailConsole { after: 0, limit: *lines })
+                .map(Pending::Logs),
+            Command::Export => exports
+                .send_request(connection, AdminExportWorldSnapshot)
+                .map(Pending::Export),
+            Command::Import { snapshot } => imports
+                .send_request(connection, AdminImportWorldSnapshot { snapshot: snapshot.clone() })
+                .map(Pending::Import),
+            Command::Settings { changes } => settings
+                .send_request(connection, UpdateServerSettings { settings: changes.clone() })
+                .map(Pending::Settings),
+        },
+        Pending::Following { next_poll } if Instant::now() >= *next_poll => logs
+            .send_request(
+                connection,
+                AdminTailConsole {
+                    after: state.last_sequence,
+                    limit: u32::MAX,
+                },
+            )
+            .map(Pending::Logs),
+        _ => return,
+    };
+
+    match sent {
+        Ok(pending) => {
+            state.pending = pending;
+            state.sent_at = Some(Instant::now());
+        }
+        Err(e) => {
+            eprintln!("Failed to send command: {}", e);
+            exit.write(AppExit::error());
+        }
+    }
+}
+
+/// Pretty-print a JSON string, or print it as-is if it does not parse.
+fn print_json(json: &str) {
+    match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(value) => println!("{}", serde_json::to_string_pretty(&value).unwrap_or_else(|_| json.to_string())),
+        Err(_) => println!("{}", json),
+    }
+}
+
+/// Print `error` and exit with a failure, if there is one.
+fn report_error(error: Option<String>, exit: &mut MessageWriter<AppExit>) -> bool {
+    match error {
+        Some(error) => {
+            eprintln!("Error: {}", error);
+            exit.write(AppExit::error());
+            true
+        }
+        None => false,
+    }
+}
+
+fn poll_responses(config: Res<CliConfig>, mut state: ResMut<CliState>, mut exit: MessageWriter<AppExit>) {
+    let follow = matches!(config.command, Command::Logs { follow: true, .. });
+
+    let next = match std::mem::take(&mut state.pending) {
+        Pending::Connections(response) => match response.try_recv() {
+            Err(response) => Pending::Connections(response),
+            Ok(response) => {
+                if !report_error(response.error, &mut exit) {
+                    println!(
+                        "{:<8} {:<24} {:>5} {:>10} {:>10} {:>12} {:>12} {:>10}  controls",
+                        "id", "identity", "subs", "msgs in", "msgs out", "bytes in", "bytes out", "conflated"
+                    );
+                    for c in response.connections {
+                        println!(
+                            "{:<8} {:<24} {:>5} {:>10} {:>10} {:>12} {:>12} {:>10}  {:?}",
+                            c.connection_id.id,
+                            c.identity.as_deref().unwrap_or("-"),
+                            c.subscriptions,
+                            c.messages_in,
+                            c.messages_out,
+                            c.bytes_in,
+                            c.bytes_out,
+                            c.conflated,
+                            c.controlled_entities
+                        );
+                    }
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        Pending::Subscriptions(response) => match response.try_recv() {
+            Err(response) => Pending::Subscriptions(response),
+            Ok(response) => {
+                if !report_error(response.error, &mut exit) {
+                    println!("{:<8} {:<8} {:<32} entity", "conn", "sub", "component");
+                    for s in response.subscriptions {
+                        let entity = s.entity.map(|e| e.bits.to_string()).unwrap_or_else(|| "*".to_string());
+                        println!("{:<8} {:<8} {:<32} {}", s.connection_id.id, s.subscription_id, s.component_type, entity);
+                    }
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        Pending::Snapshot(response) => match response.try_recv() {
+            Err(response) => Pending::Snapshot(response),
+            Ok(response) => {
+                if !report_error(response.error, &mut exit) {
+                    // Print as one JSON object keyed by entity bits
+                    let entities: serde_json::Map<String, serde_json::Value> = response
+                        .entities
+                        .into_iter()
+                        .map(|(entity, json)| {
+                            let value = serde_json::from_str(&json).unwrap_or(serde_json::Value::String(json));
+                            (entity.bits.to_string(), value)
+                        })
+                        .collect();
+                    print_json(&serde_json::Value::Object(entities).to_string());
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        Pending::Request(response) => match response.try_recv() {
+            Err(response) => Pending::Request(response),
+            Ok(response) => {
+                if !report_error(response.error, &mut exit) {
+                    print_json(response.payload.as_deref().unwrap_or("null"));
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        Pending::Release(response) => match response.try_recv() {
+            Err(response) => Pending::Release(response),
+            Ok(response) => {
+                if !report_error(response.error, &mut exit) {
+                    match response.released_from {
+                        Some(holder) => println!("Released control held by connection {}", holder.id),
+                        None => println!("Entity was not controlled"),
+                    }
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        Pending::Logs(response) => match response.try_recv() {
+            Err(response) => Pending::Logs(response),
+            Ok(response) if response.error.is_some() => {
+                report_error(response.error, &mut exit);
+                Pending::Done
+            }
+            Ok(response) => {
+                for entry in response.entries {
+                    println!("{} {:<5} {}: {}", entry.timestamp_ms, entry.level, entry.target, entry.message);
+                }
+                state.last_sequence = response.last_sequence;
+                if follow {
+                    state.sent_at = None;
+                    Pending::Following {
+                        next_poll: Instant::now() + FOLLOW_INTERVAL,
+                    }
+                } else {
+                    exit.write(AppExit::Success);
+                    Pending::Done
+                }
+            }
+        },
+        Pending::Export(response) => match response.try_recv() {
+            Err(response) => Pending::Export(response),
+            Ok(response) => {
+                if !report_error(response.error, &mut exit) {
+                    print_json(&response.snapshot);
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        Pending::Import(response) => match response.try_recv() {
+            Err(response) => Pending::Import(response),
+            Ok(response) => {
+                if !report_error(response.error, &mut exit) {
+                    println!("Spawned {} entities", response.spawned.len());
+                    for skipped in response.skipped {
+                        eprintln!("Skipped {}", skipped);
+                    }
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        Pending::Settings(response) => match response.try_recv() {
+            Err(response) => Pending::Settings(response),
+            Ok(response) => {
+                for (name, value) in &response.settings.settings {
+                    println!("{} = {}", name, value);
+                }
+                if !report_error(response.error, &mut exit) {
+                    exit.write(AppExit::Success);
+                }
+                Pending::Done
+            }
+        },
+        pending => pending,
+    };
+    state.pending = next;
+}
+
+fn handle_control_responses(
+    mut responses: MessageReader<NetworkData<ControlResponse>>,
+    config: Res<CliConfig>,
+    mut state: ResMut<CliState>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Command::TakeControl { entity } = config.command else {
+        return;
+    };
+
+    for response in responses.read() {
+        match &response.kind {
+            ControlResponseKind::Taken if matches!(state.pending, Pending::Control) => {
+                println!("Holding control of {}; press Ctrl-C to release", entity);
+                state.pending = Pending::Holding;
+                state.sent_at = None;
+            }
+            ControlResponseKind::AlreadyControlled { by_client } => {
+                eprintln!("Entity {} is controlled by connection {}", entity, by_client.id);
+                exit.write(AppExit::error());
+            }
+            ControlResponseKind::Error(error) => {
+                eprintln!("Error: {}", error);
+                exit.write(AppExit::error());
+            }
+            ControlResponseKind::Denied { reason } => {
+                eprintln!("Control of {} denied: {}", entity, reason);
+                exit.write(AppExit::error());
+            }
+            ControlResponseKind::AwaitingApproval => {
+                println!("Waiting for the server to approve control of {}", entity);
+                state.sent_at = None;
+            }
+            ControlResponseKind::ControlRequested { by_client } => {
+                println!("Connection {} is requesting control", by_client.id);
+            }
+            ControlResponseKind::TimeoutWarning { seconds_remaining, .. } => {
+                println!("Control of {} times out in {:.0}s", entity, seconds_remaining);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_timeout(config: Res<CliConfig>, state: Res<CliState>, mut exit: MessageWriter<AppExit>) {
+    if matches!(state.pending, Pending::Holding | Pending::Following { .. }) {
+        return;
+    }
+    if state.sent_at.is_some_and(|sent_at| sent_at.elapsed() > config.timeout) {
+        match state.connection {
+            Some(_) => eprintln!("Timed out waiting for the server to respond"),
+            None => eprintln!("Timed out connecting to {}", config.url),
+        }
+        exit.write(AppExit::error());
+    }
+}
@@ -0,0 +1,404 @@
+//! Synthetic load-testing client.
+//!
+//! Spins up N headless clients against a pl3xus server, subscribes each one to
+//! the configured components, issues mutations and requests at fixed rates, and
+//! reports latency percentiles and drop counts. Use it to size
+//! `channel_capacity` and tick rates before deploying.
+//!
+//! Mutations and requests target the `LoadPose` component and `LoadPing`
+//! request served by `sync_load_generator`:
+//!
+//! ```text
+//! cargo run -p pl3xus_sync --features load-generator --bin sync_load_generator
+//! cargo run -p pl3xus_sync --features load-generator --bin pl3xus_loadtest -- \
+//!     --url ws://127.0.0.1:8083 --clients 50 --components LoadPose \
+//!     --mutation-hz 20 --request-hz 5 --duration 30
+//! ```
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::prelude::*;
+use bevy::tasks::TaskPoolBuilder;
+use pl3xus::managers::network_request::{AppNetworkResponseMessage, Requester, Response};
+use pl3xus::{AppNetworkMessage, ConnectionId, Network, NetworkData, NetworkEvent, Pl3xusPlugin, Pl3xusRuntime};
+use pl3xus_sync::load_testing::{LoadPing, LoadPong, LoadPose};
+use pl3xus_sync::{
+    MutateComponent, MutationStatus, SerializableEntity, SubscriptionRequest, SyncClientMessage, SyncItem,
+    SyncServerMessage,
+};
+use pl3xus_websockets::{NetworkSettings, WebSocketProvider};
+
+/// Maximum number of mutation targets remembered per client.
+const MAX_TARGETS: usize = 1024;
+
+#[derive(Resource, Debug, Clone)]
+struct LoadTestConfig {
+    url: url::Url,
+    clients: usize,
+    components: Vec<String>,
+    mutation_hz: f64,
+    request_hz: f64,
+    request_payload: usize,
+    duration: Duration,
+    timeout: Duration,
+}
+
+impl LoadTestConfig {
+    fn from_args() -> Self {
+        let mut config = Self {
+            url: url::Url::parse("ws://127.0.0.1:8083").unwrap(),
+            clients: 10,
+            components: vec!["LoadPose".to_string()],
+            mutation_hz: 10.0,
+            request_hz: 1.0,
+            request_payload: 64,
+            duration: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+        };
+
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        for pair in args.chunks(2) {
+            let [flag, value] = pair else {
+                break;
+            };
+            match flag.as_str() {
+                "--url" => config.url = url::Url::parse(value).expect("invalid --url"),
+                "--clients" => config.clients = value.parse().expect("invalid --clients"),
+                "--components" => {
+                    config.components = value.split(',').map(|s| s.trim().to_string()).collect()
+                }
+                "--mutation-hz" => config.mutation_hz = value.parse().expect("invalid --mutation-hz"),
+                "--request-hz" => config.request_hz = value.parse().expect("invalid --request-hz"),
+                "--request-payload" => {
+                    config.request_payload = value.parse().expect("invalid --request-payload")
+                }
+                "--duration" => {
+                    config.duration = Duration::from_secs(value.parse().expect("invalid --duration"))
+                }
+                "--timeout-ms" => {
+                    config.timeout = Duration::from_millis(value.parse().expect("invalid --timeout-ms"))
+                }
+                other => panic!("unknown argument: {}", other),
+            }
+        }
+        config
+    }
+}
+
+/// Per-client state.
+#[derive(Default)]
+struct LoadClient {
+    targets: Vec<SerializableEntity>,
+    next_mutation: f64,
+    next_request: f64,
+    pending_mutations: HashMap<u64, Instant>,
+    pending_requests: Vec<(Instant, Response<LoadPong>)>,
+}
+
+#[derive(Default)]
+struct LatencyStats {
+    samples: Vec<Duration>,
+    sent: u64,
+    ok: u64,
+    errors: u64,
+    send_failures: u64,
+    timed_out: u64,
+}
+
+impl LatencyStats {
+    fn percentile(sorted: &[Duration], p: f64) -> Duration {
+        if sorted.is_empty() {
+            return Duration::ZERO;
+        }
+        let index = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+        sorted[index.min(sorted.len() - 1)]
+    }
+
+    fn report(&self, name: &str) {
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        println!(
+            "{:<10} sent={} ok={} errors={} send_failures={} timed_out={}",
+            name, self.sent, self.ok, self.errors, self.send_failures, self.timed_out
+        );
+        println!(
+            "{:<10} p50={:?} p90={:?} p99={:?} max={:?}",
+            "",
+            Self::percentile(&sorted, 50.0),
+            Self::percentile(&sorted, 90.0),
+            Self::percentile(&sorted, 99.0),
+            sorted.last().copied().unwrap_or_default(),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct LoadTestState {
+    started: Option<Instant>,
+    clients: HashMap<ConnectionId, LoadClient>,
+    next_request_id: u64,
+    connect_failures: u64,
+    disconnects: u64,
+    sync_items: u64,
+    mutations: LatencyStats,
+    requests: LatencyStats,
+}
+
+fn main() {
+    let config = LoadTestConfig::from_args();
+    println!(
+        "Starting {} clients against {} for {:?}",
+        config.clients, config.url, config.duration
+    );
+
+    App::new()
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_millis(1))))
+        .add_plugins(bevy::log::LogPlugin {
+            level: bevy::log::Level::WARN,
+            ..Default::default()
+        })
+        .add_plugins(Pl3xusPlugin::<WebSocketProvider, bevy::tasks::TaskPool>::default())
+        .insert_resource(Pl3xusRuntime(TaskPoolBuilder::new().build()))
+        .insert_resource(NetworkSettings::default())
+        .register_network_message::<SyncServerMessage, WebSocketProvider>()
+        .listen_for_response_message::<LoadPing, WebSocketProvider>()
+        .insert_resource(config)
+        .init_resource::<LoadTestState>()
+        .add_systems(Startup, connect_clients)
+        .add_systems(
+            Update,
+            (
+                handle_connection_events,
+                handle_server_messages,
+                send_load,
+                collect_responses,
+                finish,
+            )
+                .chain(),
+        )
+        .run();
+}
+
+fn connect_clients(
+    net: Res<Network<WebSocketProvider>>,
+    settings: Res<NetworkSettings>,
+    task_pool: Res<Pl3xusRuntime<bevy::tasks::TaskPool>>,
+    config: Res<LoadTestConfig>,
+    mut state: ResMut<LoadTestState>,
+) {
+    for _ in 0..config.clients {
+        net.connect(config.url.clone(), &task_pool.0, &settings);
+    }
+    state.started = Some(Instant::now());
+}
+
+fn handle_connection_events(
+    mut events: MessageReader<NetworkEvent>,
+    net: Res<Network<WebSocketProvider>>,
+    config: Res<LoadTestConfig>,
+    mut state: ResMut<LoadTestState>,
+) {
+    let elapsed = state.started.map(|s| s.elapsed().as_secs_f64()).unwrap_or_default();
+    for event in events.read() {
+        match event {
+            NetworkEvent::Connected(connection_id, _) => {
+                for (i, component_type) in config.components.iter().enumerate() {
+                    let subscribe = SyncClientMessage::Subscription(SubscriptionRequest {
+                        subscription_id: i as u64 + 1,
+                        component_type: component_type.clone(),
+                        entity: None,
+                        filter: None,
+                    });
+                    if let Err(e) = net.send(*connection_id, subscribe) {
+                        warn!("Failed to subscribe {:?} to {}: {:?}", connection_id, component_type, e);
+                    }
+                }
+                // Spread clients over the first period so they don't fire in lockstep.
+                let spread = state.clients.len() as f64 / config.clients.max(1) as f64;
+                state.clients.insert(
+                    *connection_id,
+                    LoadClient {
+                        next_mutation: elapsed + spread / config.mutation_hz.max(f64::EPSILON),
+                        next_request: elapsed + spread / config.request_hz.max(f64::EPSILON),
+                        ..Default::default()
+                    },
+                );
+            }
+            NetworkEvent::Disconnected(connection_id) => {
+                if state.clients.remove(connection_id).is_some() {
+                    state.disconnects += 1;
+                }
+            }
+            NetworkEvent::Error(e) => {
+                warn!("Network error: {:?}", e);
+                state.connect_failures += 1;
+            }
+        }
+    }
+}
+
+fn handle_server_messages(
+    mut messages: MessageReader<NetworkData<SyncServerMessage>>,
+    mut state: ResMut<LoadTestState>,
+) {
+    let state = &mut *state;
+    for message in messages.read() {
+        let Some(client) = state.clients.get_mut(message.source()) else {
+            continue;
+        };
+        match &**message {
+            SyncServerMessage::SyncBatch(batch) => {
+                state.sync_items += batch.items.len() as u64;
+                for item in &batch.items {
+                    if let SyncItem::Snapshot { entity, component_type, .. } = item
+                        && component_type == "LoadPose"
+                        && client.targets.len() < MAX_TARGETS
+                    {
+                        client.targets.push(*entity);
+                    }
+                }
+            }
+            SyncServerMessage::MutationResponse(response) => {
+                let Some(sent_at) = response
+                    .request_id
+                    .and_then(|id| client.pending_mutations.remove(&id))
+                else {
+                    continue;
+                };
+                state.mutations.samples.push(sent_at.elapsed());
+                match response.status {
+                    MutationStatus::Ok => state.mutations.ok += 1,
+                    _ => state.mutations.errors += 1,
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn send_load(
+    net: Res<Network<WebSocketProvider>>,
+    requester: Requester<LoadPing, WebSocketProvider>,
+    config: Res<LoadTestConfig>,
+    mut state: ResMut<LoadTestState>,
+) {
+    let Some(started) = state.started else {
+        return;
+    };
+    let elapsed = started.elapsed().as_secs_f64();
+    let state = &mut *state;
+
+    for (connection_id, client) in state.clients.iter_mut() {
+        if config.mutation_hz > 0.0 && elapsed >= client.next_mutation && !client.targets.is_empty() {
+            client.next_mutation += 1.0 / config.mutation_hz;
+
+            let request_id = state.next_request_id;
+            state.next_request_id += 1;
+            let entity = client.targets[request_id as usize % client.targets.len()];
+            let value = LoadPose {
+                x: request_id as f32,
+                ..Default::default()
+            };
+            let mutate = SyncClientMessage::Mutate(MutateComponent {
+                request_id: Some(request_id),
+                entity,
+                component_type: "LoadPose".to_string(),
+                value: bincode::serde::encode_to_vec(&value, bincode::config::standard())
+                    .expect("LoadPose encodes"),
+                idempotency_key: None,
+            });
+
+            state.mutations.sent += 1;
+            match net.send(*connection_id, mutate) {
+                Ok(()) => {
+                    client.pending_mutations.insert(request_id, Instant::now());
+                }
+                Err(_) => state.mutations.send_failures += 1,
+            }
+        }
+
+        if config.request_hz > 0.0 && elapsed >= client.next_request {
+            client.next_request += 1.0 / config.request_hz;
+
+            state.requests.sent += 1;
+            let ping = LoadPing {
+                payload: vec![0; config.request_payload],
+            };
+            match requester.send_request(*connection_id, ping) {
+                Ok(response) => client.pending_requests.push((Instant::now(), response)),
+                Err(_) => state.requests.send_failures += 1,
+            }
+        }
+    }
+}
+
+fn collect_responses(config: Res<LoadTestConfig>, mut state: ResMut<LoadTestState>) {
+    let state = &mut *state;
+    for client in state.clients.values_mut() {
+        for (sent_at, response) in std::mem::take(&mut client.pending_requests) {
+            match response.try_recv() {
+                Ok(_) => {
+                    state.requests.samples.push(sent_at.elapsed());
+                    state.requests.ok += 1;
+                }
+                Err(_) if sent_at.elapsed() > config.timeout => state.requests.timed_out += 1,
+                Err(response) => client.pending_requests.push((sent_at, response)),
+            }
+        }
+
+        let before = client.pending_mutations.len();
+        client
+            .pending_mutations
+            .retain(|_, sent_at| sent_at.elapsed() <= config.timeout);
+        state.mutations.timed_out += (before - client.pending_mutations.len()) as u64;
+    }
+}
+
+fn finish(
+    config: Res<LoadTestConfig>,
+    state: Res<LoadTestState>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some(started) = state.started else {
+        return;
+    };
+    if started.elapsed() < config.duration {
+        return;
+    }
+
+    let seconds = started.elapsed().as_secs_f64();
+    println!();
+    println!(
+        "clients: connected={} of {} disconnects={} errors={}",
+        state.clients.len(),
+        config.clients,
+        state.disconnects,
+        state.connect_failures
+    );
+    println!(
+        "sync:     {} items ({:.0}/s)",
+        state.sync_items,
+        state.sync_items as f64 / seconds
+    );
+    state.mutations.report("mutations:");
+    state.requests.report("requests:");
+    exit.write(AppExit::Success);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(LatencyStats::percentile(&[], 99.0), Duration::ZERO);
+
+        let sorted: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(LatencyStats::percentile(&sorted, 0.0), Duration::from_millis(1));
+        assert_eq!(LatencyStats::percentile(&sorted, 50.0), Duration::from_millis(6));
+        assert_eq!(LatencyStats::percentile(&sorted, 90.0), Duration::from_millis(9));
+        assert_eq!(LatencyStats::percentile(&sorted, 100.0), Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,128 @@
+//! Synthetic load generator for the sync pipeline.
+//!
+//! Starts a WebSocket server with a configurable number of synced entities and
+//! mutates a fraction of them every frame, so clients (or the devtools) can be
+//! pointed at a reproducible load. Also answers `LoadPing` requests so that
+//! `pl3xus_loadtest` can measure request latency.
+//!
+//! ```text
+//! cargo run -p pl3xus_sync --features load-generator --bin sync_load_generator -- \
+//!     --entities 10000 --changed-percent 10 --hz 60 --port 8083
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::prelude::*;
+use bevy::tasks::TaskPoolBuilder;
+use pl3xus::managers::network_request::{AppNetworkRequestMessage, Request};
+use pl3xus::{Network, Pl3xusPlugin, Pl3xusRuntime};
+use pl3xus_sync::load_testing::{LoadPing, LoadPong, LoadPose};
+use pl3xus_sync::{AppPl3xusSyncExt, Pl3xusSyncPlugin};
+use pl3xus_websockets::{NetworkSettings, WebSocketProvider};
+
+#[derive(Resource, Debug, Clone)]
+struct LoadConfig {
+    entities: usize,
+    changed_percent: usize,
+    hz: f64,
+    port: u16,
+}
+
+impl LoadConfig {
+    fn from_args() -> Self {
+        let mut config = Self {
+            entities: 10_000,
+            changed_percent: 10,
+            hz: 60.0,
+            port: 8083,
+        };
+
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        for pair in args.chunks(2) {
+            let [flag, value] = pair else {
+                break;
+            };
+            match flag.as_str() {
+                "--entities" => config.entities = value.parse().expect("invalid --entities"),
+                "--changed-percent" => {
+                    config.changed_percent = value.parse::<usize>().expect("invalid --changed-percent").min(100)
+                }
+                "--hz" => config.hz = value.parse().expect("invalid --hz"),
+                "--port" => config.port = value.parse().expect("invalid --port"),
+                other => panic!("unknown argument: {}", other),
+            }
+        }
+        config
+    }
+}
+
+fn main() {
+    let config = LoadConfig::from_args();
+    info!("Starting sync load generator: {:?}", config);
+
+    App::new()
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1.0 / config.hz,
+        ))))
+        .add_plugins(bevy::log::LogPlugin::default())
+        .add_plugins(Pl3xusPlugin::<WebSocketProvider, bevy::tasks::TaskPool>::default())
+        .insert_resource(Pl3xusRuntime(TaskPoolBuilder::new().build()))
+        .insert_resource(NetworkSettings::default())
+        .add_plugins(Pl3xusSyncPlugin::<WebSocketProvider>::default())
+        .sync_component::<LoadPose>(None)
+        .listen_for_request_message::<LoadPing, WebSocketProvider>()
+        .insert_resource(config)
+        .add_systems(Startup, (start_listening, spawn_entities))
+        .add_systems(Update, (mutate_entities, answer_pings))
+        .run();
+}
+
+fn start_listening(
+    mut net: ResMut<Network<WebSocketProvider>>,
+    settings: Res<NetworkSettings>,
+    task_pool: Res<Pl3xusRuntime<bevy::tasks::TaskPool>>,
+    config: Res<LoadConfig>,
+) {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), config.port);
+    match net.listen(addr, &task_pool.0, &settings) {
+        Ok(_) => info!("Listening on {}", addr),
+        Err(err) => panic!("Failed to listen on {}: {}", addr, err),
+    }
+}
+
+fn spawn_entities(mut commands: Commands, config: Res<LoadConfig>) {
+    commands.spawn_batch((0..config.entities).map(|i| LoadPose {
+        x: i as f32,
+        ..Default::default()
+    }));
+}
+
+fn mutate_entities(
+    mut frame: Local<usize>,
+    config: Res<LoadConfig>,
+    mut poses: Query<&mut LoadPose>,
+) {
+    *frame += 1;
+    let stride = match config.changed_percent {
+        0 => return,
+        percent => 100 / percent,
+    };
+    // Rotate which entities change so every entity is eventually touched.
+    let offset = *frame % stride;
+    for (i, mut pose) in poses.iter_mut().enumerate() {
+        if i % stride == offset {
+            pose.y = (*frame as f32 * 0.01).sin() * 100.0;
+        }
+    }
+}
+
+fn answer_pings(mut requests: MessageReader<Request<LoadPing>>) {
+    for request in requests.read() {
+        let payload_len = request.get_request().payload.len() as u32;
+        if let Err(e) = request.clone().respond(LoadPong { payload_len }) {
+            warn!("Failed to answer LoadPing: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Server-side storage and chunked transfer of blobs.
+//!
+//! Systems put large binary data into the [`BlobStore`] and put the returned
+//! [`BlobRef`] into a synced component. Clients fetch the bytes on demand
+//! with [`FetchBlob`] requests, answered by [`BlobPlugin`] in chunks of at
+//! most [`BlobPlugin::max_chunk_bytes`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::blob::{BlobPlugin, BlobStore};
+//!
+//! app.add_plugins(BlobPlugin::<WebSocketProvider>::default());
+//!
+//! fn capture(mut blobs: ResMut<BlobStore>, mut cameras: Query<&mut CameraSnapshot>) {
+//!     for mut snapshot in &mut cameras {
+//!         snapshot.image = blobs.insert(grab_png(), Some("image/png"));
+//!     }
+//! }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use pl3xus::managers::network_request::Request;
+use pl3xus_common::{BlobChunk, BlobRef, FetchBlob};
+
+use crate::{AppRequestRegistrationExt, NetworkProvider};
+
+/// Blobs by content hash, evicting the oldest once over the byte budget.
+///
+/// Evicted blobs can't be fetched any more; clients that already cached them
+/// keep their copy.
+#[derive(Resource)]
+pub struct BlobStore {
+    blobs: HashMap<String, Arc<Vec<u8>>>,
+    /// Hashes in insertion order, oldest first.
+    order: VecDeque<String>,
+    total_bytes: usize,
+    max_bytes: usize,
+}
+
+impl Default for BlobStore {
+    fn default() -> Self {
+        Self::new(256 * 1024 * 1024)
+    }
+}
+
+impl BlobStore {
+    /// Create a store holding at most `max_bytes` of blob data.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            blobs: HashMap::new(),
+            order: VecDeque::new(),
+            total_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// Store `data` and return a reference to it.
+    ///
+    /// Storing the same content again returns the same reference and marks
+    /// it as most recently stored.
+    pub fn insert(&mut self, data: Vec<u8>, content_type: Option<&str>) -> BlobRef {
+        let hash = blake3::hash(&data).to_hex().to_string();
+        let size = data.len() as u64;
+
+        if self.blobs.contains_key(&hash) {
+            self.order.retain(|stored| *stored != hash);
+        } else {
+            self.total_bytes += data.len();
+            self.blobs.insert(hash.clone(), Arc::new(data));
+        }
+        self.order.push_back(hash.clone());
+
+        // Evict the oldest, but never the blob just stored
+        while self.total_bytes > self.max_bytes && self.order.len() > 1 {
+            if let Some(evicted) = self.order.pop_front() {
+                self.forget(&evicted);
+            }
+        }
+
+        BlobRef {
+            hash,
+            size,
+            content_type: content_type.map(str::to_string),
+        }
+    }
+
+    /// The content of a stored blob.
+    pub fn get(&self, hash: &str) -> Option<Arc<Vec<u8>>> {
+        self.blobs.get(hash).cloned()
+    }
+
+    /// Remove a blob, e.g. once no component references it.
+    pub fn remove(&mut self, hash: &str) -> bool {
+        self.order.retain(|stored| stored != hash);
+        self.forget(hash)
+    }
+
+    /// Total size of the stored blobs in bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Number of stored blobs.
+    pub fn len(&self) -> usize {
+        self.blobs.len()
+    }
+
+    /// Returns true if no blobs are stored.
+    pub fn is_empty(&self) -> bool {
+        self.blobs.is_empty()
+    }
+
+    fn forget(&mut self, hash: &str) -> bool {
+        match self.blobs.remove(hash) {
+            Some(data) => {
+                self.total_bytes -= data.len();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Answer a [`FetchBlob`] request with at most `max_chunk_bytes`.
+    pub fn chunk(&self, request: &FetchBlob, max_chunk_bytes: u32) -> BlobChunk {
+        let Some(data) = self.blobs.get(&request.hash) else {
+            return BlobChunk {
+                hash: request.hash.clone(),
+                offset: request.offset,
+                error: Some(format!("Unknown blob {}", request.hash)),
+                ..Default::default()
+            };
+        };
+        let start = (request.offset as usize).min(data.len());
+        let len = request.max_len.min(max_chunk_bytes) as usize;
+        let end = start.saturating_add(len).min(data.len());
+        BlobChunk {
+            hash: request.hash.clone(),
+            offset: start as u64,
+            total_size: data.len() as u64,
+            data: data[start..end].to_vec(),
+            error: None,
+        }
+    }
+}
+
+/// Plugin answering [`FetchBlob`] requests from the [`BlobStore`].
+pub struct BlobPlugin<NP: NetworkProvider> {
+    /// Largest chunk sent per request, whatever the client asks for.
+    pub max_chunk_bytes: u32,
+    /// Byte budget of the [`BlobStore`], if the plugin creates it.
+    pub max_store_bytes: usize,
+    _marker: std::marker::PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for BlobPlugin<NP> {
+    fn default() -> Self {
+        Self {
+            max_chunk_bytes: pl3xus_common::BLOB_CHUNK_SIZE,
+            max_store_bytes: 256 * 1024 * 1024,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct BlobChunkLimit(u32);
+
+impl<NP: NetworkProvider> Plugin for BlobPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<BlobStore>() {
+            app.insert_resource(BlobStore::new(self.max_store_bytes));
+        }
+        app.insert_resource(BlobChunkLimit(self.max_chunk_bytes.max(1)));
+        app.request::<FetchBlob, NP>().register();
+        app.add_systems(Update, handle_fetch_blob);
+    }
+}
+
+fn handle_fetch_blob(
+    mut requests: MessageReader<Request<FetchBlob>>,
+    store: Res<BlobStore>,
+    limit: Res<BlobChunkLimit>,
+) {
+    for request in requests.read() {
+        let chunk = store.chunk(request.get_request(), limit.0);
+        if let Err(e) = request.clone().respond(chunk) {
+            warn!("[pl3xus_sync] Failed to respond to FetchBlob: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_chunk() {
+        let mut store = BlobStore::new(10);
+        let blob = store.insert(vec![1, 2, 3, 4, 5, 6], Some("application/octet-stream"));
+        assert_eq!(blob.size, 6);
+        assert_eq!(store.insert(vec![1, 2, 3, 4, 5, 6], None).hash, blob.hash);
+        assert_eq!(store.total_bytes(), 6);
+
+        let mut request = FetchBlob::first(&blob);
+        request.max_len = 100;
+        let chunk = store.chunk(&request, 4);
+        assert_eq!(chunk.data, vec![1, 2, 3, 4]);
+        assert!(!chunk.is_last());
+        request.offset = 4;
+        let chunk = store.chunk(&request, 4);
+        assert_eq!(chunk.data, vec![5, 6]);
+        assert!(chunk.is_last());
+
+        // Over budget: the oldest blob goes
+        let newer = store.insert(vec![7; 8], None);
+        assert!(store.get(&blob.hash).is_none());
+        assert!(store.get(&newer.hash).is_some());
+        assert!(store.chunk(&FetchBlob::first(&blob), 4).error.is_some());
+    }
+}
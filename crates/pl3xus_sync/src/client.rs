@@ -0,0 +1,381 @@
+//! Native Bevy client that mirrors server entities into the local world.
+//!
+//! [`Pl3xusSyncClientPlugin`] subscribes to every component type registered
+//! with [`AppSyncClientExt::mirror_component`] when the connection opens, and
+//! keeps one local proxy entity per server entity, tagged with
+//! [`ServerEntity`]. Snapshots and updates insert the component on the proxy,
+//! so ordinary Bevy queries (and `Changed<T>`) work on server state:
+//!
+//! ```rust,ignore
+//! app.add_plugins(Pl3xusSyncClientPlugin::<TcpProvider>::default())
+//!     .mirror_component::<RobotPosition>()
+//!     .add_systems(Update, draw_robots);
+//!
+//! fn draw_robots(robots: Query<(&ServerEntity, &RobotPosition), Changed<RobotPosition>>) { /* ... */ }
+//! ```
+//!
+//! Changes go back to the server through [`ClientMutations`]; the server's
+//! answer arrives as a normal update.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use pl3xus::managers::NetworkProvider;
+use pl3xus::{AppNetworkMessage, ConnectionId, Network, NetworkData, NetworkEvent, Pl3xusMessage};
+
+use crate::messages::{
+    MutateComponent, MutationResponse, SerializableEntity, SubscriptionRequest, SyncClientMessage, SyncItem,
+    SyncServerMessage,
+};
+use crate::registry::short_type_name;
+
+/// Marks a local proxy of a server entity.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ServerEntity(pub SerializableEntity);
+
+/// Local proxy entity of each mirrored server entity.
+#[derive(Resource, Debug, Default)]
+pub struct ServerEntityMap {
+    entities: HashMap<SerializableEntity, Entity>,
+}
+
+impl ServerEntityMap {
+    /// The local proxy of a server entity, if it has been mirrored.
+    pub fn local(&self, server: SerializableEntity) -> Option<Entity> {
+        self.entities.get(&server).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// Queued mutations to send to the server, and the server's answers.
+#[derive(Resource, Debug, Default)]
+pub struct ClientMutations {
+    outgoing: Vec<MutateComponent>,
+    responses: HashMap<u64, MutationResponse>,
+    next_request_id: u64,
+}
+
+impl ClientMutations {
+    /// Ask the server to set `entity`'s `T` to `value`. Returns the request id
+    /// matched by [`response`](Self::response).
+    ///
+    /// Sent at the end of the frame; the local proxy keeps its value until the
+    /// server's update arrives.
+    pub fn mutate<T: serde::Serialize>(&mut self, entity: ServerEntity, value: &T) -> Result<u64, String> {
+        let value = bincode::serde::encode_to_vec(value, bincode::config::standard()).map_err(|e| e.to_string())?;
+        self.next_request_id += 1;
+        self.outgoing.push(MutateComponent {
+            request_id: Some(self.next_request_id),
+            entity: entity.0,
+            component_type: short_type_name::<T>(),
+            value,
+            idempotency_key: None,
+        });
+        Ok(self.next_request_id)
+    }
+
+    /// The server's answer to a mutation, once received.
+    pub fn response(&self, request_id: u64) -> Option<&MutationResponse> {
+        self.responses.get(&request_id)
+    }
+
+    /// Take the server's answer to a mutation, once received.
+    pub fn take_response(&mut self, request_id: u64) -> Option<MutationResponse> {
+        self.responses.remove(&request_id)
+    }
+}
+
+/// How to apply received values of one mirrored component type.
+struct MirroredComponent {
+    subscription_id: u64,
+    insert: fn(&mut EntityWorldMut, &[u8]) -> Result<(), bincode::error::DecodeError>,
+    remove: fn(&mut EntityWorldMut),
+}
+
+/// Component types mirrored from the server, by type name.
+#[derive(Resource, Default)]
+struct MirroredComponents {
+    components: HashMap<String, MirroredComponent>,
+}
+
+/// The connection the mirror is fed from.
+#[derive(Resource, Debug, Default)]
+struct SyncClientConnection {
+    connection: Option<ConnectionId>,
+}
+
+/// Mirrors server entities into this app's world.
+///
+/// The app must also have a `Pl3xusPlugin` for `NP` and connect it to a
+/// server running `Pl3xusSyncPlugin`.
+#[derive(Debug, Clone)]
+pub struct Pl3xusSyncClientPlugin<NP: NetworkProvider> {
+    _marker: std::marker::PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for Pl3xusSyncClientPlugin<NP> {
+    fn default() -> Self {
+        Self {
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for Pl3xusSyncClientPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        let registered = app
+            .world()
+            .get_resource::<Network<NP>>()
+            .is_some_and(|net| net.is_message_registered(SyncServerMessage::type_name()));
+        if !registered {
+            app.register_network_message::<SyncServerMessage, NP>();
+        }
+
+        app.init_resource::<MirroredComponents>()
+            .init_resource::<ServerEntityMap>()
+            .init_resource::<ClientMutations>()
+            .init_resource::<SyncClientConnection>()
+            .add_systems(
+                Update,
+                (handle_connection_events::<NP>, apply_server_messages, send_mutations::<NP>).chain(),
+            );
+    }
+}
+
+/// Extension trait for choosing the component types a native client mirrors.
+pub trait AppSyncClientExt {
+    /// Mirror server entities' `T` onto their local proxies.
+    ///
+    /// `T` must be registered on the server under the same type name.
+    fn mirror_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static;
+}
+
+impl AppSyncClientExt for App {
+    fn mirror_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
+    {
+        let mut mirrored = self.world_mut().get_resource_or_insert_with(MirroredComponents::default);
+        let subscription_id = mirrored.components.len() as u64;
+        mirrored
+            .components
+            .entry(short_type_name::<T>())
+            .or_insert(MirroredComponent {
+                subscription_id,
+                insert: insert_component::<T>,
+                remove: remove_component::<T>,
+            });
+        self
+    }
+}
+
+fn insert_component<T>(entity: &mut EntityWorldMut, bytes: &[u8]) -> Result<(), bincode::error::DecodeError>
+where
+    T: Component + for<'de> serde::Deserialize<'de>,
+{
+    let (value, _) = bincode::serde::decode_from_slice::<T, _>(bytes, bincode::config::standard())?;
+    entity.insert(value);
+    Ok(())
+}
+
+fn remove_component<T: Component>(entity: &mut EntityWorldMut) {
+    entity.remove::<T>();
+}
+
+/// Subscribe to every mirrored type on connect; drop the mirror on disconnect.
+fn handle_connection_events<NP: NetworkProvider>(
+    mut commands: Commands,
+    mut events: MessageReader<NetworkEvent>,
+    net: Res<Network<NP>>,
+    mirrored: Res<MirroredComponents>,
+    mut connection: ResMut<SyncClientConnection>,
+    mut entities: ResMut<ServerEntityMap>,
+) {
+    for event in events.read() {
+        match event {
+            NetworkEvent::Connected(connection_id, _) => {
+                connection.connection = Some(*connection_id);
+                for (component_type, component) in &mirrored.components {
+                    let subscription = SyncClientMessage::Subscription(SubscriptionRequest {
+                        subscription_id: component.subscription_id,
+                        component_type: component_type.clone(),
+                        entity: None,
+                        filter: None,
+                    });
+                    if let Err(e) = net.send(*connection_id, subscription) {
+                        warn!("[Pl3xusSyncClient] Failed to subscribe to {}: {}", component_type, e);
+                    }
+                }
+            }
+            NetworkEvent::Disconnected(connection_id) => {
+                if connection.connection == Some(*connection_id) {
+                    connection.connection = None;
+                    // The server restarts from snapshots on reconnect
+                    for (_, local) in entities.entities.drain() {
+                        commands.entity(local).despawn();
+                    }
+                }
+            }
+            NetworkEvent::Error(_) => {}
+        }
+    }
+}
+
+fn apply_server_messages(
+    mut commands: Commands,
+    mut messages: MessageReader<NetworkData<SyncServerMessage>>,
+    mirrored: Res<MirroredComponents>,
+    mut entities: ResMut<ServerEntityMap>,
+    mut mutations: ResMut<ClientMutations>,
+) {
+    for message in messages.read() {
+        match &**message {
+            SyncServerMessage::SyncBatch(batch) => {
+                for item in &batch.items {
+                    apply_item(&mut commands, &mirrored, &mut entities, item);
+                }
+            }
+            SyncServerMessage::MutationResponse(response) => {
+                if let Some(request_id) = response.request_id {
+                    mutations.responses.insert(request_id, response.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_item(commands: &mut Commands, mirrored: &MirroredComponents, entities: &mut ServerEntityMap, item: &SyncItem) {
+    match item {
+        SyncItem::Snapshot {
+            entity,
+            component_type,
+            value,
+            ..
+        }
+        | SyncItem::Update {
+            entity,
+            component_type,
+            value,
+            ..
+        } => {
+            let Some(component) = mirrored.components.get(component_type) else {
+                return;
+            };
+            let local = *entities
+                .entities
+                .entry(*entity)
+                .or_insert_with(|| commands.spawn(ServerEntity(*entity)).id());
+            let insert = component.insert;
+            let value = value.clone();
+            let component_type = component_type.clone();
+            commands.entity(local).queue(move |mut local: EntityWorldMut| {
+                if let Err(e) = insert(&mut local, &value) {
+                    warn!("[Pl3xusSyncClient] Failed to decode {}: {}", component_type, e);
+                }
+            });
+        }
+        SyncItem::ComponentRemoved {
+            entity, component_type, ..
+        } => {
+            let (Some(component), Some(local)) = (mirrored.components.get(component_type), entities.local(*entity))
+            else {
+                return;
+            };
+            let remove = component.remove;
+            commands.entity(local).queue(move |mut local: EntityWorldMut| remove(&mut local));
+        }
+        SyncItem::EntityRemoved { entity, .. } => {
+            if let Some(local) = entities.entities.remove(entity) {
+                commands.entity(local).despawn();
+            }
+        }
+    }
+}
+
+fn send_mutations<NP: NetworkProvider>(
+    net: Res<Network<NP>>,
+    connection: Res<SyncClientConnection>,
+    mut mutations: ResMut<ClientMutations>,
+) {
+    if mutations.outgoing.is_empty() {
+        return;
+    }
+    let Some(connection_id) = connection.connection else {
+        // Keep them until connected
+        return;
+    };
+    for mutation in std::mem::take(&mut mutations.outgoing) {
+        if let Err(e) = net.send(connection_id, SyncClientMessage::Mutate(mutation)) {
+            warn!("[Pl3xusSyncClient] Failed to send mutation: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::SyncBatch;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Battery {
+        level: f32,
+    }
+
+    fn item(entity: u64, level: f32) -> SyncItem {
+        SyncItem::Update {
+            subscription_id: 0,
+            entity: SerializableEntity { bits: entity },
+            component_type: "Battery".into(),
+            value: bincode::serde::encode_to_vec(Battery { level }, bincode::config::standard()).unwrap(),
+        }
+    }
+
+    fn send(app: &mut App, items: Vec<SyncItem>) {
+        let batch = SyncServerMessage::SyncBatch(SyncBatch::new(items, false));
+        app.world_mut()
+            .write_message(NetworkData::new(&ConnectionId { id: 1 }, batch));
+        app.update();
+    }
+
+    #[test]
+    fn test_mirrors_server_entities() {
+        let mut app = App::new();
+        app.add_message::<NetworkData<SyncServerMessage>>()
+            .init_resource::<ServerEntityMap>()
+            .init_resource::<ClientMutations>()
+            .mirror_component::<Battery>()
+            .add_systems(Update, apply_server_messages);
+
+        send(&mut app, vec![item(7, 0.5), item(8, 0.9)]);
+        send(&mut app, vec![item(7, 0.25)]);
+
+        let local = app.world().resource::<ServerEntityMap>().local(SerializableEntity { bits: 7 }).unwrap();
+        assert_eq!(app.world().get::<Battery>(local), Some(&Battery { level: 0.25 }));
+        assert_eq!(
+            app.world().get::<ServerEntity>(local),
+            Some(&ServerEntity(SerializableEntity { bits: 7 }))
+        );
+        assert_eq!(app.world().resource::<ServerEntityMap>().len(), 2);
+
+        send(
+            &mut app,
+            vec![SyncItem::EntityRemoved {
+                subscription_id: 0,
+                entity: SerializableEntity { bits: 7 },
+            }],
+        );
+        assert!(app.world().get_entity(local).is_err());
+        assert_eq!(app.world().resource::<ServerEntityMap>().len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+//! Protocol constants for clients written in other languages.
+//!
+//! [`python_protocol`] renders the type names, enum variant orders and struct
+//! shapes the Python client (`clients/python`) needs to speak the sync
+//! protocol. The generated module is checked in; a test fails when it falls
+//! out of date. Regenerate it with:
+//!
+//! ```text
+//! PL3XUS_BLESS=1 cargo test -p pl3xus_sync --lib codegen
+//! ```
+
+use std::fmt::Write;
+
+use pl3xus_common::{describe_type, DescribeSchema, NetworkPacket, Pl3xusMessage, SchemaDescription, TypeShape};
+use serde::de::DeserializeOwned;
+
+use crate::filter::{FilterOp, FilterValue, SubscriptionFilter};
+use crate::messages::{
+    MutateComponent, MutationResponse, MutationStatus, QueryInvalidation, SerializableEntity, SubscriptionRequest,
+    SubscriptionSequence, SyncBatch, SyncClientMessage, SyncItem, SyncServerMessage, UnsubscribeRequest, WelcomeMessage,
+    SYNC_PROTOCOL_VERSION,
+};
+
+/// Source of the Python client's `_protocol.py`.
+pub fn python_protocol() -> String {
+    let mut out = String::new();
+    out.push_str("# @generated by pl3xus_sync::codegen::python_protocol. Do not edit.\n");
+    out.push_str("# Regenerate with: PL3XUS_BLESS=1 cargo test -p pl3xus_sync --lib codegen\n\n");
+
+    let constants = [
+        ("SYNC_CLIENT_MESSAGE_TYPE", std::any::type_name::<SyncClientMessage>().to_string()),
+        ("SYNC_SERVER_MESSAGE_TYPE", std::any::type_name::<SyncServerMessage>().to_string()),
+        (
+            "REQUEST_TYPE_TEMPLATE",
+            "pl3xus::managers::network_request::RequestInternal<{}>".to_string(),
+        ),
+        ("RESPONSE_TYPE_MARKER", "ResponseInternal<".to_string()),
+        ("DESCRIBE_SCHEMA_TYPE", DescribeSchema::type_name().to_string()),
+        ("BINCODE_SUBPROTOCOL", pl3xus_common::codec::BINCODE_SUBPROTOCOL.to_string()),
+    ];
+    for (name, value) in constants {
+        let _ = writeln!(out, "{} = {}", name, json(&value));
+    }
+    let _ = writeln!(out, "SYNC_PROTOCOL_VERSION = {}", SYNC_PROTOCOL_VERSION);
+
+    out.push_str("\n# Enum variants in declaration order; bincode encodes the index.\n");
+    let enums = [
+        ("SYNC_CLIENT_MESSAGE_VARIANTS", variants::<SyncClientMessage>()),
+        ("SYNC_SERVER_MESSAGE_VARIANTS", variants::<SyncServerMessage>()),
+        ("SYNC_ITEM_VARIANTS", variants::<SyncItem>()),
+        ("MUTATION_STATUS_VARIANTS", variants::<MutationStatus>()),
+        ("SUBSCRIPTION_FILTER_VARIANTS", variants::<SubscriptionFilter>()),
+        ("FILTER_OP_VARIANTS", variants::<FilterOp>()),
+        ("FILTER_VALUE_VARIANTS", variants::<FilterValue>()),
+        ("TYPE_SHAPE_VARIANTS", variants::<TypeShape>()),
+    ];
+    for (name, variants) in enums {
+        let _ = writeln!(out, "{} = {}", name, json(&variants));
+    }
+
+    out.push_str("\n# Shapes of protocol structs, in the serde JSON form of `TypeShape`.\n");
+    out.push_str("SHAPES = {\n");
+    let shapes = [
+        shape::<NetworkPacket>(),
+        shape::<SerializableEntity>(),
+        shape::<SubscriptionRequest>(),
+        shape::<UnsubscribeRequest>(),
+        shape::<MutateComponent>(),
+        shape::<WelcomeMessage>(),
+        shape::<SubscriptionSequence>(),
+        shape::<SyncBatch>(),
+        shape::<MutationResponse>(),
+        shape::<QueryInvalidation>(),
+        shape::<SchemaDescription>(),
+    ];
+    for (name, shape) in shapes {
+        let _ = writeln!(out, "    {}: {},", json(&name), json(&shape));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn variants<T: DeserializeOwned + 'static>() -> Vec<String> {
+    match describe_type::<T>() {
+        TypeShape::Enum { variants, .. } => variants,
+        shape => panic!("{} is not an enum: {}", std::any::type_name::<T>(), shape),
+    }
+}
+
+fn shape<T: DeserializeOwned + 'static>() -> (String, TypeShape) {
+    let name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+    (name.to_string(), describe_type::<T>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYTHON_PROTOCOL: &str = "../../clients/python/pl3xus/_protocol.py";
+
+    #[test]
+    fn test_python_protocol_is_current() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(PYTHON_PROTOCOL);
+        let generated = python_protocol();
+        if std::env::var_os("PL3XUS_BLESS").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} is out of date; regenerate it with PL3XUS_BLESS=1 cargo test -p pl3xus_sync --lib codegen",
+            path.display()
+        );
+    }
+
+    #[test]
+    fn test_protocol_variants() {
+        assert_eq!(variants::<SyncClientMessage>()[2], "Mutate");
+        assert_eq!(variants::<SyncItem>(), ["Snapshot", "Update", "ComponentRemoved", "EntityRemoved"]);
+    }
+}
@@ -0,0 +1,676 @@
+//! Server-side log of the commands clients send.
+//!
+//! With `CommandLogPlugin`, every authorized mutation and every authorized
+//! message registered with `.logged()` is recorded with who sent it, when,
+//! and what it contained:
+//!
+//! ```rust,ignore
+//! app.add_plugins(CommandLogPlugin::<WebSocketProvider>::new());
+//!
+//! app.message::<SendPacket, NP>()
+//!     .targeted()
+//!     .with_default_entity_policy()
+//!     .logged()
+//!     .register();
+//! ```
+//!
+//! Clients page through the log, newest first, with [`GetCommandLog`], and
+//! re-run an entry with [`ReplayCommand`]. A replay is sent again as if the
+//! replaying client had sent it, so it goes through the same middleware,
+//! access policy and handler as the original, is answered the same way (a
+//! rejection notification or `MutationResponse`), and is logged as a new
+//! entry. Being allowed to send `ReplayCommand` does not let a client run
+//! commands it couldn't send itself.
+//!
+//! Entries are kept in memory (the latest 1000 by default). To keep them
+//! across restarts, give the [`CommandLog`] a `CommandLogStore` backed by a
+//! database.
+
+use pl3xus_common::{ConnectionId, ErrorResponse, RequestMessage};
+use serde::{Deserialize, Serialize};
+
+/// What kind of command an entry records.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CommandKind {
+    /// A message, targeted at `target_entity` if it is set.
+    #[default]
+    Message,
+    /// A component mutation of `target_entity`.
+    Mutation,
+}
+
+/// One recorded command.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CommandLogEntry {
+    /// Increasing id, used for paging and [`ReplayCommand`].
+    pub id: u64,
+    /// When the command was authorized, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    /// The connection that sent the command.
+    pub connection_id: ConnectionId,
+    /// Display name the client reported with `SetClientIdentity` (requires
+    /// `ClientPresencePlugin`). Not verified: any client can report any name,
+    /// so attribute commands by `connection_id` and `client_addr`.
+    pub reported_identity: Option<String>,
+    /// The client's address, if the provider reports it.
+    pub client_addr: Option<String>,
+    pub kind: CommandKind,
+    /// Full message type name, or the component type name for mutations.
+    pub type_name: String,
+    /// Entity bits of the target, for targeted messages and mutations.
+    pub target_entity: Option<u64>,
+    /// The message or component value, bincode encoded.
+    pub data: Vec<u8>,
+    /// The message as JSON, for display. Not set for mutations.
+    pub json: Option<String>,
+}
+
+impl Default for CommandLogEntry {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            timestamp_ms: 0,
+            connection_id: ConnectionId::NONE,
+            reported_identity: None,
+            client_addr: None,
+            kind: CommandKind::default(),
+            type_name: String::new(),
+            target_entity: None,
+            data: Vec::new(),
+            json: None,
+        }
+    }
+}
+
+/// Fetch logged commands, newest first.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GetCommandLog {
+    /// Only entries older than this id; `None` starts at the newest entry.
+    pub before_id: Option<u64>,
+    /// Page size, clamped to 1..=500.
+    pub limit: u32,
+    /// Only commands from this connection.
+    pub connection_id: Option<ConnectionId>,
+    /// Only commands of this type.
+    pub type_name: Option<String>,
+    /// Only commands targeting this entity.
+    pub target_entity: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GetCommandLogResponse {
+    /// Matching entries, oldest first.
+    pub entries: Vec<CommandLogEntry>,
+    /// Whether older matching entries exist (pass the first id as `before_id`).
+    pub has_more: bool,
+    pub error: Option<String>,
+}
+
+/// Send a logged command again, as the requesting client.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReplayCommand {
+    pub id: u64,
+}
+
+/// Answered once the command is queued; its outcome arrives like the
+/// original's did.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReplayCommandResponse {
+    pub error: Option<String>,
+}
+
+impl RequestMessage for GetCommandLog {
+    type ResponseMessage = GetCommandLogResponse;
+}
+
+impl ErrorResponse for GetCommandLog {
+    fn error_response(error: String) -> Self::ResponseMessage {
+        GetCommandLogResponse {
+            error: Some(error),
+            ..Default::default()
+        }
+    }
+}
+
+impl RequestMessage for ReplayCommand {
+    type ResponseMessage = ReplayCommandResponse;
+}
+
+impl ErrorResponse for ReplayCommand {
+    fn error_response(error: String) -> Self::ResponseMessage {
+        ReplayCommandResponse { error: Some(error) }
+    }
+}
+
+/// Largest page returned for a [`GetCommandLog`].
+pub const MAX_COMMAND_LOG_PAGE: u32 = 500;
+
+impl GetCommandLog {
+    /// Whether `entry` passes this query's filters (ignoring paging).
+    pub fn matches(&self, entry: &CommandLogEntry) -> bool {
+        self.connection_id.is_none_or(|id| entry.connection_id == id)
+            && self.type_name.as_ref().is_none_or(|name| &entry.type_name == name)
+            && self.target_entity.is_none_or(|target| entry.target_entity == Some(target))
+            && self.before_id.is_none_or(|before| entry.id < before)
+    }
+
+    /// The page size to use.
+    pub fn page_size(&self) -> usize {
+        self.limit.clamp(1, MAX_COMMAND_LOG_PAGE) as usize
+    }
+}
+
+#[cfg(feature = "runtime")]
+pub use runtime::*;
+
+#[cfg(feature = "runtime")]
+mod runtime {
+    use std::collections::{HashMap, VecDeque};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use bevy::ecs::message::Messages;
+    use bevy::prelude::*;
+    use pl3xus::managers::network_request::Request;
+    use pl3xus::{ConnectionInfos, NetworkData};
+    use pl3xus_common::{ClientPresence, Pl3xusMessage, TargetedMessage};
+
+    use super::*;
+    use crate::authorization::{AppRequestRegistrationExt, MessageAccessPolicy};
+    use crate::messages::SerializableEntity;
+    use crate::registry::{MutationOrigin, MutationQueue, QueuedMutation};
+    use crate::NetworkProvider;
+
+    /// Where a [`CommandLog`] keeps its entries.
+    pub trait CommandLogStore: Send + Sync + 'static {
+        /// Store a new entry. Its id is higher than every stored one.
+        fn append(&mut self, entry: &CommandLogEntry) -> Result<(), String>;
+
+        /// A page of matching entries, oldest first, and whether older
+        /// matching entries exist.
+        fn page(&self, query: &GetCommandLog) -> Result<(Vec<CommandLogEntry>, bool), String>;
+
+        /// The entry with `id`, if it is still stored.
+        fn get(&self, id: u64) -> Result<Option<CommandLogEntry>, String>;
+
+        /// The highest stored id, or 0.
+        fn last_id(&self) -> u64;
+    }
+
+    /// Keeps the latest entries in memory.
+    pub struct MemoryCommandLog {
+        entries: VecDeque<CommandLogEntry>,
+        capacity: usize,
+    }
+
+    impl MemoryCommandLog {
+        /// Keep at most `capacity` entries, dropping the oldest.
+        pub fn new(capacity: usize) -> Self {
+            Self {
+                entries: VecDeque::new(),
+                capacity: capacity.max(1),
+            }
+        }
+    }
+
+    impl CommandLogStore for MemoryCommandLog {
+        fn append(&mut self, entry: &CommandLogEntry) -> Result<(), String> {
+            if self.entries.len() == self.capacity {
+                self.entries.pop_front();
+            }
+            self.entries.push_back(entry.clone());
+            Ok(())
+        }
+
+        fn page(&self, query: &GetCommandLog) -> Result<(Vec<CommandLogEntry>, bool), String> {
+            let limit = query.page_size();
+            let mut entries: Vec<_> = self
+                .entries
+                .iter()
+                .rev()
+                .filter(|entry| query.matches(entry))
+                .take(limit + 1)
+                .cloned()
+                .collect();
+            let has_more = entries.len() > limit;
+            entries.truncate(limit);
+            entries.reverse();
+            Ok((entries, has_more))
+        }
+
+        fn get(&self, id: u64) -> Result<Option<CommandLogEntry>, String> {
+            Ok(self.entries.iter().find(|entry| entry.id == id).cloned())
+        }
+
+        fn last_id(&self) -> u64 {
+            self.entries.back().map_or(0, |entry| entry.id)
+        }
+    }
+
+    /// The command log, inserted by [`CommandLogPlugin`].
+    #[derive(Resource)]
+    pub struct CommandLog {
+        store: Box<dyn CommandLogStore>,
+        next_id: u64,
+        /// Whether authorized mutations are recorded.
+        pub log_mutations: bool,
+    }
+
+    impl CommandLog {
+        /// A log kept in `store`, continuing after its last entry.
+        pub fn new(store: impl CommandLogStore) -> Self {
+            Self {
+                next_id: store.last_id() + 1,
+                store: Box::new(store),
+                log_mutations: true,
+            }
+        }
+
+        /// Keep new entries in `store` instead, e.g. once a database is open.
+        /// Entries already recorded stay in the old store.
+        pub fn set_store(&mut self, store: impl CommandLogStore) {
+            self.next_id = store.last_id() + 1;
+            self.store = Box::new(store);
+        }
+
+        /// Record `entry`, giving it the next id and the current time.
+        pub fn record(&mut self, mut entry: CommandLogEntry) -> u64 {
+            entry.id = self.next_id;
+            entry.timestamp_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default();
+            self.next_id += 1;
+            if let Err(e) = self.store.append(&entry) {
+                error!("Failed to log {} command {}: {}", entry.type_name, entry.id, e);
+            }
+            entry.id
+        }
+
+        /// A page of entries for `query`. See [`CommandLogStore::page`].
+        pub fn page(&self, query: &GetCommandLog) -> Result<(Vec<CommandLogEntry>, bool), String> {
+            self.store.page(query)
+        }
+
+        /// The entry with `id`.
+        pub fn get(&self, id: u64) -> Result<Option<CommandLogEntry>, String> {
+            self.store.get(id)
+        }
+    }
+
+    type ReplayFn = fn(&mut World, ConnectionId, &CommandLogEntry) -> Result<(), String>;
+
+    /// Message types registered with `.logged()`, by full type name, with
+    /// how to send them again.
+    #[derive(Resource, Default)]
+    pub(crate) struct LoggedMessages {
+        replay: HashMap<String, ReplayFn>,
+    }
+
+    /// Record authorized `T` messages. Called by `.logged()` registrations.
+    pub(crate) fn log_message<T: Pl3xusMessage + Clone + 'static>(app: &mut App, targeted: bool) {
+        let replay: ReplayFn = if targeted {
+            replay_targeted::<T>
+        } else {
+            replay_message::<T>
+        };
+        app.world_mut()
+            .get_resource_or_insert_with(LoggedMessages::default)
+            .replay
+            .insert(T::type_name().to_string(), replay);
+
+        crate::registration_checks::add_check(app, |world| {
+            (!world.contains_resource::<CommandLog>()).then(|| {
+                format!(
+                    "Message {} is logged, but there is no command log; add CommandLogPlugin",
+                    T::short_name()
+                )
+            })
+        });
+    }
+
+    /// Whether authorized `T` messages are recorded.
+    pub(crate) fn is_logged<T: Pl3xusMessage>(world: &World) -> bool {
+        world
+            .get_resource::<LoggedMessages>()
+            .is_some_and(|logged| logged.replay.contains_key(T::type_name()))
+            && world.contains_resource::<CommandLog>()
+    }
+
+    /// An entry for an authorized message (without id or timestamp).
+    pub(crate) fn message_entry<T: Pl3xusMessage>(
+        source: ConnectionId,
+        target_entity: Option<Entity>,
+        message: &T,
+    ) -> Option<CommandLogEntry> {
+        let data = match bincode::serde::encode_to_vec(message, bincode::config::standard()) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Could not log {}: {}", T::type_name(), e);
+                return None;
+            }
+        };
+        Some(CommandLogEntry {
+            connection_id: source,
+            kind: CommandKind::Message,
+            type_name: T::type_name().to_string(),
+            target_entity: target_entity.map(|entity| entity.to_bits()),
+            data,
+            json: serde_json::to_string(message).ok(),
+            ..Default::default()
+        })
+    }
+
+    /// Fill in where each entry came from and record them.
+    pub(crate) fn record(world: &mut World, entries: Vec<CommandLogEntry>) {
+        if entries.is_empty() {
+            return;
+        }
+        let identities: HashMap<ConnectionId, String> = world
+            .try_query::<&ClientPresence>()
+            .map(|mut query| {
+                query
+                    .iter(world)
+                    .filter_map(|presence| Some((presence.connection_id, presence.identity.clone()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|entry| CommandLogEntry {
+                reported_identity: identities.get(&entry.connection_id).cloned(),
+                client_addr: world
+                    .get_resource::<ConnectionInfos>()
+                    .and_then(|infos| infos.client_addr(entry.connection_id))
+                    .map(|addr| addr.to_string()),
+                ..entry
+            })
+            .collect();
+
+        let Some(mut log) = world.get_resource_mut::<CommandLog>() else {
+            return;
+        };
+        for entry in entries {
+            log.record(entry);
+        }
+    }
+
+    /// Record a mutation that passed authorization and validation.
+    pub(crate) fn record_mutation(world: &mut World, mutation: &QueuedMutation) {
+        if !world.get_resource::<CommandLog>().is_some_and(|log| log.log_mutations) {
+            return;
+        }
+        let entry = CommandLogEntry {
+            connection_id: mutation.connection_id,
+            kind: CommandKind::Mutation,
+            type_name: mutation.component_type.clone(),
+            target_entity: Some(mutation.entity.bits),
+            data: mutation.value.clone(),
+            ..Default::default()
+        };
+        record(world, vec![entry]);
+    }
+
+    fn decode<T: Pl3xusMessage>(entry: &CommandLogEntry) -> Result<T, String> {
+        bincode::serde::decode_from_slice::<T, _>(&entry.data, bincode::config::standard())
+            .map(|(message, _)| message)
+            .map_err(|e| format!("Could not decode {}: {}", entry.type_name, e))
+    }
+
+    fn replay_targeted<T: Pl3xusMessage>(
+        world: &mut World,
+        source: ConnectionId,
+        entry: &CommandLogEntry,
+    ) -> Result<(), String> {
+        let message = decode::<T>(entry)?;
+        let target = entry
+            .target_entity
+            .ok_or_else(|| format!("Command {} has no target", entry.id))?;
+        world
+            .resource_mut::<Messages<NetworkData<TargetedMessage<T>>>>()
+            .write(NetworkData::new(&source, TargetedMessage::new(target, message)));
+        Ok(())
+    }
+
+    fn replay_message<T: Pl3xusMessage>(
+        world: &mut World,
+        source: ConnectionId,
+        entry: &CommandLogEntry,
+    ) -> Result<(), String> {
+        let message = decode::<T>(entry)?;
+        world
+            .resource_mut::<Messages<NetworkData<T>>>()
+            .write(NetworkData::new(&source, message));
+        Ok(())
+    }
+
+    /// Queue `entry` to run again as `source`.
+    pub fn replay(world: &mut World, source: ConnectionId, entry: &CommandLogEntry) -> Result<(), String> {
+        match entry.kind {
+            CommandKind::Mutation => {
+                let entity = entry
+                    .target_entity
+                    .ok_or_else(|| format!("Command {} has no target", entry.id))?;
+                let mut queue = world
+                    .get_resource_mut::<MutationQueue>()
+                    .ok_or("Mutations are not enabled on this server")?;
+                queue.pending.push(QueuedMutation {
+                    connection_id: source,
+                    request_id: None,
+                    entity: SerializableEntity::from(Entity::from_bits(entity)),
+                    component_type: entry.type_name.clone(),
+                    value: entry.data.clone(),
+                    idempotency_key: None,
+                    origin: MutationOrigin::Mutate,
+                });
+                Ok(())
+            }
+            CommandKind::Message => {
+                let replay = world
+                    .get_resource::<LoggedMessages>()
+                    .and_then(|logged| logged.replay.get(&entry.type_name).copied())
+                    .ok_or_else(|| format!("{} can't be replayed", entry.type_name))?;
+                replay(world, source, entry)
+            }
+        }
+    }
+
+    /// Records commands and serves [`GetCommandLog`] and [`ReplayCommand`].
+    pub struct CommandLogPlugin<NP: NetworkProvider> {
+        policy: MessageAccessPolicy,
+        replay_policy: MessageAccessPolicy,
+        capacity: usize,
+        log_mutations: bool,
+        _marker: std::marker::PhantomData<NP>,
+    }
+
+    impl<NP: NetworkProvider> CommandLogPlugin<NP> {
+        /// Record commands, keeping the last 1000 in memory.
+        ///
+        /// Only the server may read or replay the log until you allow clients
+        /// with [`with_policy`](Self::with_policy) and
+        /// [`with_replay_policy`](Self::with_replay_policy): entries carry
+        /// every command's payload.
+        pub fn new() -> Self {
+            Self {
+                policy: MessageAccessPolicy::server_only(),
+                replay_policy: MessageAccessPolicy::server_only(),
+                capacity: 1000,
+                log_mutations: true,
+                _marker: std::marker::PhantomData,
+            }
+        }
+
+        /// Restrict which connections can read the log.
+        pub fn with_policy(mut self, policy: MessageAccessPolicy) -> Self {
+            self.policy = policy;
+            self
+        }
+
+        /// Restrict which connections can replay commands. Replayed commands
+        /// are still authorized as the replaying client.
+        pub fn with_replay_policy(mut self, policy: MessageAccessPolicy) -> Self {
+            self.replay_policy = policy;
+            self
+        }
+
+        /// Number of entries kept by the default in-memory store.
+        pub fn with_capacity(mut self, capacity: usize) -> Self {
+            self.capacity = capacity;
+            self
+        }
+
+        /// Only record messages registered with `.logged()`, not mutations.
+        pub fn without_mutations(mut self) -> Self {
+            self.log_mutations = false;
+            self
+        }
+    }
+
+    impl<NP: NetworkProvider> Default for CommandLogPlugin<NP> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<NP: NetworkProvider> Plugin for CommandLogPlugin<NP> {
+        fn build(&self, app: &mut App) {
+            if !app.world().contains_resource::<CommandLog>() {
+                app.insert_resource(CommandLog::new(MemoryCommandLog::new(self.capacity)));
+            }
+            app.world_mut().resource_mut::<CommandLog>().log_mutations = self.log_mutations;
+            app.init_resource::<LoggedMessages>();
+
+            app.request::<GetCommandLog, NP>()
+                .with_message_policy(self.policy.clone())
+                .with_error_response();
+            app.request::<ReplayCommand, NP>()
+                .with_message_policy(self.replay_policy.clone())
+                .with_error_response();
+
+            app.add_systems(Update, (handle_get_command_log, handle_replay_commands));
+        }
+    }
+
+    fn handle_get_command_log(
+        mut requests: MessageReader<Request<GetCommandLog>>,
+        log: Res<CommandLog>,
+    ) {
+        for request in requests.read() {
+            let response = match log.page(request.get_request()) {
+                Ok((entries, has_more)) => GetCommandLogResponse {
+                    entries,
+                    has_more,
+                    error: None,
+                },
+                Err(e) => GetCommandLog::error_response(e),
+            };
+            let _ = request.clone().respond(response);
+        }
+    }
+
+    fn handle_replay_commands(world: &mut World) {
+        let requests: Vec<_> = world
+            .resource_mut::<Messages<Request<ReplayCommand>>>()
+            .drain()
+            .collect();
+
+        for request in requests {
+            let id = request.get_request().id;
+            let source = *request.source();
+            let result = world
+                .resource::<CommandLog>()
+                .get(id)
+                .and_then(|entry| entry.ok_or_else(|| format!("No command {} in the log", id)))
+                .and_then(|entry| replay(world, source, &entry).map(|()| entry));
+            let response = match result {
+                Ok(entry) => {
+                    info!("{:?} replayed command {} ({})", source, id, entry.type_name);
+                    ReplayCommandResponse { error: None }
+                }
+                Err(e) => ReplayCommand::error_response(e),
+            };
+            let _ = request.respond(response);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Jog {
+            distance: f32,
+        }
+
+        fn entry(connection: u32, target: u64, distance: f32) -> CommandLogEntry {
+            message_entry(ConnectionId { id: connection }, Some(Entity::from_bits(target)), &Jog { distance })
+                .unwrap_or_default()
+        }
+
+        #[test]
+        fn test_command_log_paging() {
+            let mut log = CommandLog::new(MemoryCommandLog::new(3));
+            for i in 0..4 {
+                log.record(entry(i % 2, 1 << 32 | 7, i as f32));
+            }
+
+            // The oldest entry was dropped
+            let query = GetCommandLog {
+                limit: 2,
+                ..Default::default()
+            };
+            let (page, has_more) = log.page(&query).unwrap_or_default();
+            assert_eq!(page.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 4]);
+            assert!(has_more);
+            let (page, has_more) = log
+                .page(&GetCommandLog {
+                    before_id: Some(3),
+                    ..query.clone()
+                })
+                .unwrap_or_default();
+            assert_eq!(page.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2]);
+            assert!(!has_more);
+
+            let (page, _) = log
+                .page(&GetCommandLog {
+                    connection_id: Some(ConnectionId { id: 1 }),
+                    ..query
+                })
+                .unwrap_or_default();
+            assert_eq!(page.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 4]);
+            assert_eq!(page[0].json.as_deref(), Some(r#"{"distance":1.0}"#));
+
+            // A new store continues after its own entries
+            log.set_store(MemoryCommandLog::new(10));
+            assert_eq!(log.record(entry(0, 7, 0.0)), 1);
+        }
+
+        #[test]
+        fn test_replay_targeted_message() {
+            let mut world = World::new();
+            world.init_resource::<Messages<NetworkData<TargetedMessage<Jog>>>>();
+            let mut logged = LoggedMessages::default();
+            logged.replay.insert(Jog::type_name().to_string(), replay_targeted::<Jog>);
+            world.insert_resource(logged);
+
+            let replayer = ConnectionId { id: 9 };
+            let original = entry(1, 42, 2.5);
+            assert_eq!(replay(&mut world, replayer, &original), Ok(()));
+
+            let replayed: Vec<_> = world
+                .resource_mut::<Messages<NetworkData<TargetedMessage<Jog>>>>()
+                .drain()
+                .collect();
+            assert_eq!(replayed.len(), 1);
+            assert_eq!(*replayed[0].source(), replayer);
+            assert_eq!(replayed[0].target_id, "42");
+            assert_eq!(replayed[0].message, Jog { distance: 2.5 });
+
+            let unknown = CommandLogEntry {
+                type_name: "Unknown".into(),
+                ..original
+            };
+            assert!(replay(&mut world, replayer, &unknown).is_err());
+        }
+    }
+}
@@ -62,15 +62,19 @@
 //! ```
 
 use bevy::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::authorization::{DefaultEntityAccessPolicy, EntityAccessPolicy};
+use crate::messages::SerializableEntity;
+use crate::notifications::ClientRoles;
+use crate::SyncSession;
 
 // Re-export control types from pl3xus_common (with Message derive via ecs feature)
 pub use pl3xus_common::{
     AssociateSubConnection, AssociateSubConnectionResponse,
-    ConnectionId, ControlRequest, ControlResponse, ControlResponseKind, EntityControl,
+    ConnectionId, ConnectionIdentity, ControlKeepalive, ControlRequest, ControlResponse, ControlResponseKind, EntityControl,
+    RequestSubConnectionToken, SubConnectionToken,
 };
 
 // ============================================================================
@@ -136,15 +140,247 @@ impl SubConnections {
     }
 }
 
+/// Outstanding sub-connection tokens, by token.
+///
+/// Tokens are issued in response to [`RequestSubConnectionToken`] and
+/// consumed by the first [`AssociateSubConnection`] that presents them. They
+/// are 128 bits from the operating system's CSPRNG, so they can't be guessed.
+#[derive(Resource, Default, Debug)]
+pub struct SubConnectionTokens {
+    pending: HashMap<String, PendingSubConnectionToken>,
+}
+
+#[derive(Debug)]
+struct PendingSubConnectionToken {
+    parent_id: ConnectionId,
+    expires_at: f32,
+}
+
+impl SubConnectionTokens {
+    /// Issue a token for `parent_id`, valid until `expires_at` (in `Time::elapsed_secs`).
+    pub fn issue(&mut self, parent_id: ConnectionId, expires_at: f32) -> String {
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes).expect("operating system random number generator unavailable");
+        let token: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        self.pending.insert(token.clone(), PendingSubConnectionToken { parent_id, expires_at });
+        token
+    }
+
+    /// Consume a token presented for `parent_id` at time `now`.
+    pub fn redeem(&mut self, token: &str, parent_id: ConnectionId, now: f32) -> Result<(), String> {
+        self.pending.retain(|_, pending| pending.expires_at > now);
+        match self.pending.get(token) {
+            None => Err("Unknown or expired sub-connection token".to_string()),
+            Some(pending) if pending.parent_id != parent_id => {
+                Err("Sub-connection token was issued to a different connection".to_string())
+            }
+            Some(_) => {
+                self.pending.remove(token);
+                Ok(())
+            }
+        }
+    }
+
+    /// Drop every token issued to `parent_id`.
+    pub fn revoke(&mut self, parent_id: ConnectionId) {
+        self.pending.retain(|_, pending| pending.parent_id != parent_id);
+    }
+}
+
+// ============================================================================
+// CONTROL POLICIES
+// ============================================================================
+
+/// Who may take control of an entity, and for how long.
+///
+/// Entities without a `ControlPolicy` can be taken by any client, as before.
+/// Clients are allowed by the roles the server granted them in
+/// [`ClientRoles`]; names reported with `SetClientIdentity` are chosen by the
+/// client and never authorize anything.
+/// Requests the policy rejects are answered with
+/// [`ControlResponseKind::Denied`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// commands.spawn((
+///     Robot,
+///     EntityControl::default(),
+///     ControlPolicy::default()
+///         .allow_role("operator")
+///         .require_approval()
+///         .max_session_seconds(600.0),
+/// ));
+/// ```
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ControlPolicy {
+    /// Roles (see [`ClientRoles`]) that may take control. Empty allows everyone.
+    pub allowed_roles: Vec<String>,
+    /// Hold requests in [`ControlApprovals`] until the server approves them.
+    pub require_approval: bool,
+    /// Release control this many seconds after it was granted, active or not.
+    pub max_session_seconds: Option<f32>,
+}
+
+impl ControlPolicy {
+    /// Allow clients holding `role`.
+    pub fn allow_role(mut self, role: impl Into<String>) -> Self {
+        self.allowed_roles.push(role.into());
+        self
+    }
+
+    /// Hold requests until they are approved through [`ControlApprovals`].
+    pub fn require_approval(mut self) -> Self {
+        self.require_approval = true;
+        self
+    }
+
+    /// Release control `seconds` after it was granted.
+    pub fn max_session_seconds(mut self, seconds: f32) -> Self {
+        self.max_session_seconds = Some(seconds);
+        self
+    }
+
+    /// Check whether a client holding `roles` may take control.
+    pub fn permits<'a>(&self, mut roles: impl Iterator<Item = &'a str>) -> Result<(), String> {
+        if self.allowed_roles.is_empty() || roles.any(|role| self.allowed_roles.iter().any(|allowed| allowed == role)) {
+            return Ok(());
+        }
+        Err("Not allowed to take control of this entity".to_string())
+    }
+}
+
+/// When control of an entity was granted, for [`ControlPolicy::max_session_seconds`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ControlSession {
+    /// The client control was granted to.
+    pub client: ConnectionIdentity,
+    /// `Time::elapsed_secs` when control was granted.
+    pub started_at: f32,
+}
+
+/// A take request held for approval by a [`ControlPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingControlRequest {
+    /// The entity control was requested for.
+    pub entity: Entity,
+    /// The client asking for control.
+    pub client_id: ConnectionId,
+}
+
+/// Take requests waiting for approval, and the server's decisions on them.
+///
+/// Approved requests are granted the next time the control systems run,
+/// unless another client took control in the meantime.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// fn approve_operators(mut approvals: ResMut<ControlApprovals>, roles: Res<ClientRoles>) {
+///     for request in approvals.pending().to_vec() {
+///         if roles.has_role(request.client_id, "supervisor") {
+///             approvals.approve(request.entity, request.client_id);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Resource, Default, Debug)]
+pub struct ControlApprovals {
+    pending: Vec<PendingControlRequest>,
+    decisions: Vec<(PendingControlRequest, Result<(), String>)>,
+}
+
+impl ControlApprovals {
+    /// Requests waiting for a decision, oldest first.
+    pub fn pending(&self) -> &[PendingControlRequest] {
+        &self.pending
+    }
+
+    /// Grant `client_id`'s pending request for `entity`. Returns `false` if
+    /// there is no such request.
+    pub fn approve(&mut self, entity: Entity, client_id: ConnectionId) -> bool {
+        self.decide(entity, client_id, Ok(()))
+    }
+
+    /// Deny `client_id`'s pending request for `entity`. Returns `false` if
+    /// there is no such request.
+    pub fn deny(&mut self, entity: Entity, client_id: ConnectionId, reason: impl Into<String>) -> bool {
+        self.decide(entity, client_id, Err(reason.into()))
+    }
+
+    fn decide(&mut self, entity: Entity, client_id: ConnectionId, decision: Result<(), String>) -> bool {
+        let request = PendingControlRequest { entity, client_id };
+        let Some(index) = self.pending.iter().position(|pending| *pending == request) else {
+            return false;
+        };
+        self.pending.remove(index);
+        self.decisions.push((request, decision));
+        true
+    }
+
+    /// Hold `request` for approval. Returns `false` if it already is.
+    fn hold(&mut self, request: PendingControlRequest) -> bool {
+        if self.pending.contains(&request) {
+            return false;
+        }
+        self.pending.push(request);
+        true
+    }
+
+    /// Forget the requests of a disconnected client.
+    fn remove_client(&mut self, client_id: ConnectionId) {
+        self.pending.retain(|pending| pending.client_id != client_id);
+        self.decisions.retain(|(pending, _)| pending.client_id != client_id);
+    }
+}
+
+/// When each client last showed it is still there, by sending a
+/// [`ControlKeepalive`] or taking control again.
+///
+/// The inactivity timeout uses the latest of this and
+/// [`EntityControl::last_activity`], so keepalives don't touch the synced
+/// component.
+#[derive(Resource, Default, Debug)]
+pub struct ControlActivity {
+    last_seen: HashMap<ConnectionId, f32>,
+    /// Entities whose controller was warned about the timeout
+    warned: HashSet<Entity>,
+}
+
+impl ControlActivity {
+    /// Record activity from `client_id` at `now` (in `Time::elapsed_secs`).
+    pub fn touch(&mut self, client_id: ConnectionId, now: f32) {
+        self.last_seen.insert(client_id, now);
+    }
+
+    /// When `client_id` was last active, if it sent anything.
+    pub fn last_seen(&self, client_id: ConnectionId) -> Option<f32> {
+        self.last_seen.get(&client_id).copied()
+    }
+
+    /// When the holder of `control`, or one of its sub-connections, was last active.
+    pub fn last_active(&self, control: &EntityControl) -> f32 {
+        std::iter::once(control.client.connection)
+            .chain(control.sub_connection_ids.iter().copied())
+            .filter_map(|client_id| self.last_seen(client_id))
+            .fold(control.last_activity, f32::max)
+    }
+
+    fn remove_client(&mut self, client_id: ConnectionId) {
+        self.last_seen.remove(&client_id);
+    }
+}
+
 /// Global sequence counter for control responses.
 /// Each response gets a unique sequence number to ensure identical responses
 /// are treated as distinct messages by the client.
 static RESPONSE_SEQUENCE: AtomicU64 = AtomicU64::new(1);
 
-/// Create a new ControlResponse with a unique sequence number.
-fn new_response(kind: ControlResponseKind) -> ControlResponse {
+/// Create a new ControlResponse about `entity` with a unique sequence number.
+pub(crate) fn new_response(entity: impl Into<SerializableEntity>, kind: ControlResponseKind) -> ControlResponse {
     ControlResponse {
         sequence: RESPONSE_SEQUENCE.fetch_add(1, Ordering::Relaxed),
+        entity: Some(entity.into()),
         kind,
     }
 }
@@ -168,7 +404,9 @@ fn new_response(kind: ControlResponseKind) -> ControlResponse {
 #[derive(Clone, Debug)]
 pub struct ExclusiveControlPluginBuilder<NP: crate::NetworkProvider> {
     timeout_seconds: Option<f32>,
+    timeout_warning_seconds: Option<f32>,
     propagate_to_children: bool,
+    sub_connection_token_seconds: f32,
     _marker: std::marker::PhantomData<NP>,
 }
 
@@ -176,7 +414,9 @@ impl<NP: crate::NetworkProvider> Default for ExclusiveControlPluginBuilder<NP> {
     fn default() -> Self {
         Self {
             timeout_seconds: Some(1800.0), // 30 minute default
+            timeout_warning_seconds: Some(60.0),
             propagate_to_children: true,
+            sub_connection_token_seconds: 60.0,
             _marker: std::marker::PhantomData,
         }
     }
@@ -208,6 +448,17 @@ impl<NP: crate::NetworkProvider> ExclusiveControlPluginBuilder<NP> {
         self
     }
 
+    /// Warn the controlling client this many seconds before its control
+    /// times out, with [`ControlResponseKind::TimeoutWarning`].
+    ///
+    /// Default: 60.0
+    ///
+    /// Set to `0.0` to disable the warning.
+    pub fn timeout_warning_seconds(mut self, seconds: f32) -> Self {
+        self.timeout_warning_seconds = (seconds > 0.0).then_some(seconds);
+        self
+    }
+
     /// Set whether control of a parent entity grants control of children.
     ///
     /// Default: true
@@ -219,6 +470,14 @@ impl<NP: crate::NetworkProvider> ExclusiveControlPluginBuilder<NP> {
         self
     }
 
+    /// Set how long sub-connection tokens stay valid, in seconds.
+    ///
+    /// Default: 60.0
+    pub fn sub_connection_token_seconds(mut self, seconds: f32) -> Self {
+        self.sub_connection_token_seconds = seconds;
+        self
+    }
+
     /// Build the plugin.
     ///
     /// This is the final step - it creates a plugin that can be added to
@@ -227,7 +486,9 @@ impl<NP: crate::NetworkProvider> ExclusiveControlPluginBuilder<NP> {
         ExclusiveControlPlugin {
             config: ExclusiveControlConfig {
                 timeout_seconds: self.timeout_seconds,
+                timeout_warning_seconds: self.timeout_warning_seconds,
                 propagate_to_children: self.propagate_to_children,
+                sub_connection_token_seconds: self.sub_connection_token_seconds,
             },
             _marker: std::marker::PhantomData,
         }
@@ -239,17 +500,27 @@ impl<NP: crate::NetworkProvider> ExclusiveControlPluginBuilder<NP> {
 pub struct ExclusiveControlConfig {
     /// Timeout in seconds after which inactive control is released.
     /// `None` means no timeout.
+    ///
+    /// [`ControlKeepalive`] messages from the controlling client (or its
+    /// sub-connections) count as activity, as does taking control again.
     pub timeout_seconds: Option<f32>,
+    /// How many seconds before the timeout the controlling client is sent a
+    /// [`ControlResponseKind::TimeoutWarning`]. `None` means no warning.
+    pub timeout_warning_seconds: Option<f32>,
     /// Whether to propagate control to child entities.
     /// If `true`, taking control of a parent entity also grants control of all children.
     pub propagate_to_children: bool,
+    /// How long a [`SubConnectionToken`] stays valid, in seconds.
+    pub sub_connection_token_seconds: f32,
 }
 
 impl Default for ExclusiveControlConfig {
     fn default() -> Self {
         Self {
             timeout_seconds: Some(1800.0), // 30 minute default timeout
+            timeout_warning_seconds: Some(60.0),
             propagate_to_children: true,
+            sub_connection_token_seconds: 60.0,
         }
     }
 }
@@ -326,18 +597,30 @@ impl<NP: crate::NetworkProvider> Plugin for ExclusiveControlPlugin<NP> {
         // Insert the config as a resource
         app.insert_resource(self.config.clone());
 
+        // Control is held by a connection of this server run
+        app.init_resource::<SyncSession>();
+
         // Initialize sub-connections tracking
         app.init_resource::<SubConnections>();
+        app.init_resource::<SubConnectionTokens>();
+        app.init_resource::<ControlApprovals>();
+        app.init_resource::<ControlActivity>();
 
         // Register messages as Bevy messages
         app.add_message::<ControlRequest>();
         app.add_message::<ControlResponse>();
+        app.add_message::<ControlKeepalive>();
+        app.add_message::<RequestSubConnectionToken>();
+        app.add_message::<SubConnectionToken>();
         app.add_message::<AssociateSubConnection>();
         app.add_message::<AssociateSubConnectionResponse>();
 
         // Register control messages with the network provider
         app.register_network_message::<ControlRequest, NP>();
         app.register_network_message::<ControlResponse, NP>();
+        app.register_network_message::<ControlKeepalive, NP>();
+        app.register_network_message::<RequestSubConnectionToken, NP>();
+        app.register_network_message::<SubConnectionToken, NP>();
         app.register_network_message::<AssociateSubConnection, NP>();
         app.register_network_message::<AssociateSubConnectionResponse, NP>();
 
@@ -356,11 +639,14 @@ impl<NP: crate::NetworkProvider> Plugin for ExclusiveControlPlugin<NP> {
         app.add_systems(
             Update,
             (
+                handle_sub_connection_token_requests::<NP>,
                 handle_sub_connection_requests::<NP>,
                 handle_control_requests::<NP>,
                 update_entity_control_sub_connections,
                 cleanup_disconnected_control::<NP>,
-                timeout_inactive_control,
+                handle_control_keepalives,
+                timeout_inactive_control::<NP>,
+                expire_control_sessions::<NP>,
                 propagate_control_to_new_children,
                 notify_control_changes,
             )
@@ -403,6 +689,7 @@ impl Plugin for LegacyExclusiveControlPlugin {
     fn build(&self, app: &mut App) {
         // Insert the config as a resource
         app.insert_resource(self.config.clone());
+        app.init_resource::<SyncSession>();
 
         // Register messages as Bevy messages
         app.add_message::<ControlRequest>();
@@ -424,13 +711,13 @@ impl Plugin for LegacyExclusiveControlPlugin {
 ///
 /// Authorization is granted if:
 /// - The source is the server (always authorized)
-/// - The entity's EntityControl.client_id matches the source
+/// - The entity's EntityControl.client is the source in this server run
 /// - The source is in the entity's EntityControl.sub_connection_ids
 /// - If `check_hierarchy` is true: any ancestor has control matching the source
 ///
 /// Authorization is DENIED if:
 /// - The entity has no EntityControl component
-/// - The entity's EntityControl.client_id is 0 (no one has control - must take control first)
+/// - The entity's EntityControl.client is `ConnectionIdentity::NONE` (no one has control - must take control first)
 /// - The entity is controlled by a different client
 fn exclusive_control_authorization_check(
     world: &World,
@@ -445,12 +732,13 @@ fn exclusive_control_authorization_check(
 
     // Check if the source client has control of the entity.
     // Control semantics:
-    // - client_id == 0 means NO ONE has control, so NO ONE can send commands
-    // - client_id == X means client X has control, only they can send commands
+    // - client == NONE means NO ONE has control, so NO ONE can send commands
+    // - client == X means client X has control, only they can send commands
     // - Taking control is done via ControlRequest, not by sending commands
+    let identity = world.resource::<SyncSession>().identity(source);
     let check_control = |control: &EntityControl| {
-        // has_control checks both primary client_id and sub_connection_ids
-        control.has_control(source)
+        // has_control checks both primary client and sub_connection_ids
+        control.has_control(identity)
     };
 
     let (authorized, reason) = if check_hierarchy {
@@ -471,14 +759,16 @@ fn exclusive_control_authorization_check(
                 entity, source, entity_control
             );
 
-            // Check if entity (or ancestors) have no controller (client_id == 0)
-            let no_controller = crate::has_control_hierarchical::<EntityControl, _>(world, entity, |c| c.client_id.id == 0);
+            // Check if entity (or ancestors) have no controller
+            let no_controller = crate::has_control_hierarchical::<EntityControl, _>(world, entity, |c| !c.is_controlled());
             if no_controller {
                 (false, "No client has control of this entity. Take control first.".to_string())
             } else {
                 // Someone else has control - find out who
-                let controller = entity_control.map(|c| c.client_id.id).unwrap_or(0);
-                (false, format!("Entity controlled by client {} (you are {:?})", controller, source))
+                match entity_control.and_then(|c| c.holder()) {
+                    Some(controller) => (false, format!("Entity controlled by client {} (you are {:?})", controller.connection.id, source)),
+                    None => (false, format!("Entity controlled through a parent by another client (you are {:?})", source)),
+                }
             }
         }
     } else {
@@ -489,10 +779,10 @@ fn exclusive_control_authorization_check(
                     Some(control) => {
                         if check_control(control) {
                             (true, String::new())
-                        } else if control.client_id.id == 0 {
+                        } else if !control.is_controlled() {
                             (false, "No client has control of this entity. Take control first.".to_string())
                         } else {
-                            (false, format!("Entity controlled by client {} (you are {:?})", control.client_id.id, source))
+                            (false, format!("Entity controlled by client {} (you are {:?})", control.client.connection.id, source))
                         }
                     }
                     None => (false, "Entity has no control component".to_string()),
@@ -544,12 +834,21 @@ impl AppExclusiveControlExt for App {
     fn add_exclusive_control_systems<NP: crate::NetworkProvider>(&mut self) -> &mut Self {
         use pl3xus::AppNetworkMessage;
 
+        // Control is held by a connection of this server run
+        self.init_resource::<SyncSession>();
+
         // Initialize sub-connections tracking
         self.init_resource::<SubConnections>();
+        self.init_resource::<SubConnectionTokens>();
+        self.init_resource::<ControlApprovals>();
+        self.init_resource::<ControlActivity>();
 
         // Register messages with the network provider
         self.register_network_message::<ControlRequest, NP>();
         self.register_network_message::<ControlResponse, NP>();
+        self.register_network_message::<ControlKeepalive, NP>();
+        self.register_network_message::<RequestSubConnectionToken, NP>();
+        self.register_network_message::<SubConnectionToken, NP>();
         self.register_network_message::<AssociateSubConnection, NP>();
         self.register_network_message::<AssociateSubConnectionResponse, NP>();
 
@@ -557,11 +856,14 @@ impl AppExclusiveControlExt for App {
         self.add_systems(
             Update,
             (
+                handle_sub_connection_token_requests::<NP>,
                 handle_sub_connection_requests::<NP>,
                 handle_control_requests::<NP>,
                 update_entity_control_sub_connections,
                 cleanup_disconnected_control::<NP>,
-                timeout_inactive_control,
+                handle_control_keepalives,
+                timeout_inactive_control::<NP>,
+                expire_control_sessions::<NP>,
                 notify_control_changes,
             )
                 .chain(),
@@ -577,123 +879,83 @@ use pl3xus::{Network, NetworkData};
 /// System that handles control take/release requests from clients.
 ///
 /// This system:
+/// - Checks the entity's [`ControlPolicy`], if any, and holds requests that
+///   need approval in [`ControlApprovals`]
 /// - Checks if the entity is already controlled by another client
 /// - Grants or denies control based on exclusive control semantics
+/// - Grants or denies requests the server decided on in [`ControlApprovals`]
 /// - Optionally propagates control to child entities
 /// - Includes sub-connections when granting control
 /// - Sends responses back to the requesting client
+#[allow(clippy::too_many_arguments)]
 fn handle_control_requests<NP: crate::NetworkProvider>(
     mut requests: MessageReader<NetworkData<ControlRequest>>,
     mut entities: Query<(Entity, Option<&mut EntityControl>, Option<&Children>)>,
+    policies: Query<&ControlPolicy>,
+    roles: Option<Res<ClientRoles>>,
+    mut approvals: ResMut<ControlApprovals>,
+    mut activity: ResMut<ControlActivity>,
     config: Res<ExclusiveControlConfig>,
     sub_connections: Option<Res<SubConnections>>,
+    session: Res<SyncSession>,
     net: Res<Network<NP>>,
     mut commands: Commands,
     time: Res<Time>,
 ) {
+    let current_time = time.elapsed_secs();
+    // Take requests to handle this frame: (client, entity, approved)
+    let mut takes = Vec::new();
+
+    for (request, decision) in std::mem::take(&mut approvals.decisions) {
+        match decision {
+            Ok(()) => takes.push((request.client_id, request.entity, true)),
+            Err(reason) => {
+                info!("[ExclusiveControl] Control of {:?} denied to {:?}: {}", request.entity, request.client_id, reason);
+                let _ = net.send(request.client_id, new_response(request.entity, ControlResponseKind::Denied { reason }));
+            }
+        }
+    }
+
     for request in requests.read() {
         let client_id = *request.source();
-        let current_time = time.elapsed_secs();
 
         info!("[ExclusiveControl] Received control request from {:?}: {:?}", client_id, **request);
+        if request.is_legacy() {
+            debug!("[ExclusiveControl] {:?} sent a deprecated u64 ControlRequest, use ControlRequest::take/release", client_id);
+        }
 
-        match **request {
-            ControlRequest::Take(entity_bits) => {
-                let entity = Entity::from_bits(entity_bits);
-                info!("[ExclusiveControl] Take request for entity {:?} from {:?}", entity, client_id);
-
-                // Try to get the entity
-                let Ok((entity, control, children)) = entities.get_mut(entity) else {
-                    let _ = net.send(client_id, new_response(ControlResponseKind::Error("Entity not found".to_string())));
-                    continue;
-                };
-
-                // Check if already controlled by another client
-                // client_id 0 means "no controller" (default state) so it's available for taking
-                if let Some(existing_control) = control {
-                    let has_active_controller = existing_control.client_id.id != 0;
-
-                    if has_active_controller && existing_control.client_id != client_id {
-                        info!("[ExclusiveControl] Entity {:?} already controlled by {:?}, denying {:?}", entity, existing_control.client_id, client_id);
-
-                        // Notify the requesting client that control is denied
-                        let _ = net.send(
-                            client_id,
-                            new_response(ControlResponseKind::AlreadyControlled {
-                                by_client: existing_control.client_id,
-                            }),
-                        );
-
-                        // Notify the controlling client that someone else is requesting control
-                        info!("[ExclusiveControl] Notifying {:?} that {:?} is requesting control", existing_control.client_id, client_id);
-                        let _ = net.send(
-                            existing_control.client_id,
-                            new_response(ControlResponseKind::ControlRequested {
-                                by_client: client_id,
-                            }),
-                        );
-                        continue;
-                    } else if has_active_controller && existing_control.client_id == client_id {
-                        // Already controlled by this client, just update activity
-                        info!("[ExclusiveControl] Entity {:?} already controlled by {:?}, refreshing", entity, client_id);
-                        let _ = net.send(client_id, new_response(ControlResponseKind::Taken));
-                        continue;
-                    }
-                    // If no active controller (client_id == 0), fall through to grant control
-                }
-
-                // Get sub-connections for this client
-                let sub_connection_ids = sub_connections
-                    .as_ref()
-                    .map(|sc| sc.get_sub_connections(client_id))
-                    .unwrap_or_default();
-
-                // Grant control
-                info!("[ExclusiveControl] Granting control of {:?} to {:?} (with {} sub-connections)",
-                    entity, client_id, sub_connection_ids.len());
-                let control = EntityControl {
-                    client_id,
-                    sub_connection_ids,
-                    last_activity: current_time,
-                };
-                commands.entity(entity).insert(control.clone());
-
-                // Propagate to children if configured
-                if config.propagate_to_children {
-                    if let Some(children) = children {
-                        for child in children.iter() {
-                            commands.entity(child).insert(control.clone());
-                        }
-                    }
-                }
+        let SerializableEntity { bits } = request.entity();
+        let Some(entity) = Entity::try_from_bits(bits) else {
+            let _ = net.send(client_id, new_response(bits, ControlResponseKind::Error("Invalid entity".to_string())));
+            continue;
+        };
 
-                info!("[ExclusiveControl] Sending Taken response to {:?}", client_id);
-                let _ = net.send(client_id, new_response(ControlResponseKind::Taken));
+        match **request {
+            ControlRequest::TakeEntity(_) | ControlRequest::Take(_) => {
+                takes.push((client_id, entity, false));
             }
 
-            ControlRequest::Release(entity_bits) => {
-                let entity = Entity::from_bits(entity_bits);
-
+            ControlRequest::ReleaseEntity(_) | ControlRequest::Release(_) => {
                 // Try to get the entity
                 let Ok((_entity, mut control, children)) = entities.get_mut(entity) else {
-                    let _ = net.send(client_id, new_response(ControlResponseKind::Error("Entity not found".to_string())));
+                    let _ = net.send(client_id, new_response(entity, ControlResponseKind::Error("Entity not found".to_string())));
                     continue;
                 };
 
                 // Check if controlled by this client
                 if let Some(ref mut existing_control) = control {
                     // Check if there's an active controller and it's not this client
-                    if existing_control.client_id.id != 0 && existing_control.client_id != client_id {
+                    if existing_control.is_controlled() && !existing_control.held_by(session.identity(client_id)) {
                         let _ = net.send(
                             client_id,
-                            new_response(ControlResponseKind::Error("Not controlled by you".to_string())),
+                            new_response(entity, ControlResponseKind::Error("Not controlled by you".to_string())),
                         );
                         continue;
                     }
 
                     // Check if already released (no active controller)
-                    if existing_control.client_id.id == 0 {
-                        let _ = net.send(client_id, new_response(ControlResponseKind::NotControlled));
+                    if !existing_control.is_controlled() {
+                        let _ = net.send(client_id, new_response(entity, ControlResponseKind::NotControlled));
                         continue;
                     }
 
@@ -711,28 +973,180 @@ fn handle_control_requests<NP: crate::NetworkProvider>(
                         }
                     }
 
-                    let _ = net.send(client_id, new_response(ControlResponseKind::Released));
+                    let _ = net.send(client_id, new_response(entity, ControlResponseKind::Released));
                 } else {
-                    let _ = net.send(client_id, new_response(ControlResponseKind::NotControlled));
+                    let _ = net.send(client_id, new_response(entity, ControlResponseKind::NotControlled));
                 }
             }
         }
     }
+
+    for (client_id, entity, approved) in takes {
+        info!("[ExclusiveControl] Take request for entity {:?} from {:?}", entity, client_id);
+        let identity = session.identity(client_id);
+
+        // Try to get the entity
+        let Ok((entity, control, children)) = entities.get_mut(entity) else {
+            let _ = net.send(client_id, new_response(entity, ControlResponseKind::Error("Entity not found".to_string())));
+            continue;
+        };
+
+        // Check the entity's policy before anything else, so denied clients
+        // don't learn who holds control
+        let policy = policies.get(entity).ok();
+        if let Some(policy) = policy {
+            let client_roles = roles.as_ref().into_iter().flat_map(|roles| roles.roles_of(client_id));
+            if let Err(reason) = policy.permits(client_roles) {
+                info!("[ExclusiveControl] Policy of {:?} denies control to {:?}: {}", entity, client_id, reason);
+                let _ = net.send(client_id, new_response(entity, ControlResponseKind::Denied { reason }));
+                continue;
+            }
+        }
+
+        // Check if already controlled by another client
+        // An uncontrolled entity (default state) is available for taking
+        if let Some(existing_control) = control {
+            let has_active_controller = existing_control.is_controlled();
+
+            if has_active_controller && !existing_control.held_by(identity) {
+                let holder = existing_control.client.connection;
+                info!("[ExclusiveControl] Entity {:?} already controlled by {:?}, denying {:?}", entity, holder, client_id);
+
+                // Notify the requesting client that control is denied
+                let _ = net.send(
+                    client_id,
+                    new_response(entity, ControlResponseKind::AlreadyControlled {
+                        by_client: holder,
+                    }),
+                );
+
+                // Notify the controlling client that someone else is requesting control
+                info!("[ExclusiveControl] Notifying {:?} that {:?} is requesting control", holder, client_id);
+                let _ = net.send(
+                    holder,
+                    new_response(entity, ControlResponseKind::ControlRequested {
+                        by_client: client_id,
+                    }),
+                );
+                continue;
+            } else if has_active_controller && existing_control.held_by(identity) {
+                // Already controlled by this client, just update activity
+                info!("[ExclusiveControl] Entity {:?} already controlled by {:?}, refreshing", entity, client_id);
+                activity.touch(client_id, current_time);
+                let _ = net.send(client_id, new_response(entity, ControlResponseKind::Taken));
+                continue;
+            }
+            // If no active controller, fall through to grant control
+        }
+
+        // Hold the request if the policy wants someone to approve it first
+        if policy.is_some_and(|policy| policy.require_approval) && !approved {
+            if approvals.hold(PendingControlRequest { entity, client_id }) {
+                info!("[ExclusiveControl] Holding control request for {:?} from {:?} for approval", entity, client_id);
+            }
+            let _ = net.send(client_id, new_response(entity, ControlResponseKind::AwaitingApproval));
+            continue;
+        }
+
+        // Get sub-connections for this client
+        let sub_connection_ids = sub_connections
+            .as_ref()
+            .map(|sc| sc.get_sub_connections(client_id))
+            .unwrap_or_default();
+
+        // Grant control
+        info!("[ExclusiveControl] Granting control of {:?} to {:?} (with {} sub-connections)",
+            entity, client_id, sub_connection_ids.len());
+        let control = EntityControl {
+            client: identity,
+            sub_connection_ids,
+            last_activity: current_time,
+        };
+        commands
+            .entity(entity)
+            .insert((control.clone(), ControlSession { client: identity, started_at: current_time }));
+
+        // Propagate to children if configured
+        if config.propagate_to_children {
+            if let Some(children) = children {
+                for child in children.iter() {
+                    commands.entity(child).insert(control.clone());
+                }
+            }
+        }
+
+        info!("[ExclusiveControl] Sending Taken response to {:?}", client_id);
+        let _ = net.send(client_id, new_response(entity, ControlResponseKind::Taken));
+    }
+}
+
+/// System that issues sub-connection tokens.
+///
+/// A token requested by a sub-connection is issued for its parent, so every
+/// tab of a group joins the same parent.
+fn handle_sub_connection_token_requests<NP: crate::NetworkProvider>(
+    mut requests: MessageReader<NetworkData<RequestSubConnectionToken>>,
+    mut tokens: ResMut<SubConnectionTokens>,
+    sub_connections: Res<SubConnections>,
+    config: Res<ExclusiveControlConfig>,
+    time: Res<Time>,
+    net: Res<Network<NP>>,
+) {
+    for request in requests.read() {
+        let source = *request.source();
+        let parent_id = sub_connections.get_parent(source).unwrap_or(source);
+        let expires_in_secs = config.sub_connection_token_seconds;
+        let token = tokens.issue(parent_id, time.elapsed_secs() + expires_in_secs);
+
+        let _ = net.send(
+            source,
+            SubConnectionToken {
+                parent_connection_id: parent_id,
+                token,
+                expires_in_secs,
+            },
+        );
+    }
 }
 
 /// System that handles sub-connection association requests.
 ///
-/// When a client sends an `AssociateSubConnection` message, this system
-/// registers the requesting connection as a sub-connection of the specified parent.
+/// When a client sends an `AssociateSubConnection` message with a valid token,
+/// this system registers the requesting connection as a sub-connection of the
+/// parent the token was issued to.
 fn handle_sub_connection_requests<NP: crate::NetworkProvider>(
     mut requests: MessageReader<NetworkData<AssociateSubConnection>>,
     mut sub_connections: ResMut<SubConnections>,
+    mut tokens: ResMut<SubConnectionTokens>,
+    time: Res<Time>,
     net: Res<Network<NP>>,
 ) {
     for request in requests.read() {
         let sub_id = *request.source();
         let parent_id = request.parent_connection_id;
 
+        let result = if sub_id == parent_id {
+            Err("A connection can't be its own sub-connection".to_string())
+        } else {
+            tokens.redeem(&request.token, parent_id, time.elapsed_secs())
+        };
+
+        if let Err(error) = result {
+            warn!(
+                "[ExclusiveControl] Rejected sub-connection {:?} for parent {:?}: {}",
+                sub_id, parent_id, error
+            );
+            let _ = net.send(
+                sub_id,
+                AssociateSubConnectionResponse {
+                    success: false,
+                    error: Some(error),
+                    parent_connection_id: parent_id,
+                },
+            );
+            continue;
+        }
+
         info!(
             "[ExclusiveControl] Associating sub-connection {:?} with parent {:?}",
             sub_id, parent_id
@@ -769,26 +1183,43 @@ fn update_entity_control_sub_connections(
 
     for mut control in entities.iter_mut() {
         // Skip entities with no controller
-        if control.client_id.id == 0 {
+        if !control.is_controlled() {
             continue;
         }
 
         // Update sub-connection IDs from the SubConnections resource
-        let new_sub_ids = sub_connections.get_sub_connections(control.client_id);
+        let new_sub_ids = sub_connections.get_sub_connections(control.client.connection);
         if control.sub_connection_ids != new_sub_ids {
             control.sub_connection_ids = new_sub_ids;
         }
     }
 }
 
+/// System that records [`ControlKeepalive`] messages as client activity.
+fn handle_control_keepalives(
+    mut keepalives: MessageReader<NetworkData<ControlKeepalive>>,
+    mut activity: ResMut<ControlActivity>,
+    time: Res<Time>,
+) {
+    for keepalive in keepalives.read() {
+        activity.touch(*keepalive.source(), time.elapsed_secs());
+    }
+}
+
 /// System that automatically releases control from inactive clients.
 ///
 /// This system checks all entities with `EntityControl` and resets control
 /// to default if the client has been inactive for longer than the configured timeout.
 /// Skips entities that are already in default state (no active controller).
-fn timeout_inactive_control(
-    mut entities: Query<(Entity, &mut EntityControl, Option<&Children>)>,
+///
+/// Clients are warned `timeout_warning_seconds` before their control times
+/// out, and told when it was released. Children that share their parent's
+/// controller are released with it without separate messages.
+fn timeout_inactive_control<NP: crate::NetworkProvider>(
+    mut entities: Query<(Entity, &mut EntityControl, Option<&Children>, Option<&ChildOf>)>,
+    mut activity: ResMut<ControlActivity>,
     config: Res<ExclusiveControlConfig>,
+    net: Res<Network<NP>>,
     mut commands: Commands,
     time: Res<Time>,
 ) {
@@ -797,20 +1228,33 @@ fn timeout_inactive_control(
     };
 
     let current_time = time.elapsed_secs();
-
-    for (entity, mut control, children) in entities.iter_mut() {
-        // Skip if no one is in control (client_id 0 is the default "no controller" state)
-        if control.client_id.id == 0 {
+    let holders: HashMap<Entity, ConnectionIdentity> = entities
+        .iter()
+        .filter(|(_, control, _, _)| control.is_controlled())
+        .map(|(entity, control, _, _)| (entity, control.client))
+        .collect();
+
+    for (entity, mut control, children, child_of) in entities.iter_mut() {
+        // Skip if no one is in control (the default state)
+        if !control.is_controlled() {
+            activity.warned.remove(&entity);
             continue;
         }
 
-        let inactive_duration = current_time - control.last_activity;
+        // Only the top of a controlled hierarchy gets messages
+        let notify = child_of
+            .is_none_or(|child_of| holders.get(&child_of.parent()) != Some(&control.client));
+        let inactive_duration = current_time - activity.last_active(&control);
 
         if inactive_duration > timeout_seconds {
             info!(
                 "[ExclusiveControl] Releasing control from inactive client {:?} on entity {:?} (inactive for {:.1}s)",
-                control.client_id, entity, inactive_duration
+                control.client.connection, entity, inactive_duration
             );
+            if notify {
+                let _ = net.send(control.client.connection, new_response(entity, ControlResponseKind::Released));
+            }
+            activity.warned.remove(&entity);
 
             // Reset control to default (no client)
             *control = EntityControl::default();
@@ -824,6 +1268,66 @@ fn timeout_inactive_control(
                     }
                 }
             }
+        } else if config
+            .timeout_warning_seconds
+            .is_some_and(|warning_seconds| inactive_duration > timeout_seconds - warning_seconds)
+        {
+            // Warn once per idle period
+            if notify && activity.warned.insert(entity) {
+                let _ = net.send(
+                    control.client.connection,
+                    new_response(entity, ControlResponseKind::TimeoutWarning {
+                        entity: entity.into(),
+                        seconds_remaining: timeout_seconds - inactive_duration,
+                    }),
+                );
+            }
+        } else {
+            activity.warned.remove(&entity);
+        }
+    }
+}
+
+/// System that releases control held longer than the entity's
+/// [`ControlPolicy::max_session_seconds`], telling the client it was released.
+fn expire_control_sessions<NP: crate::NetworkProvider>(
+    mut entities: Query<(Entity, &mut EntityControl, &ControlSession, &ControlPolicy, Option<&Children>)>,
+    config: Res<ExclusiveControlConfig>,
+    net: Res<Network<NP>>,
+    mut commands: Commands,
+    time: Res<Time>,
+) {
+    let current_time = time.elapsed_secs();
+
+    for (entity, mut control, session, policy, children) in entities.iter_mut() {
+        let Some(max_session_seconds) = policy.max_session_seconds else {
+            continue;
+        };
+        // A session from an earlier holder doesn't limit the current one
+        if !control.is_controlled()
+            || control.client != session.client
+            || current_time - session.started_at <= max_session_seconds
+        {
+            continue;
+        }
+
+        info!(
+            "[ExclusiveControl] Releasing control from {:?} on entity {:?} after its {:.0}s session",
+            control.client.connection, entity, max_session_seconds
+        );
+        let _ = net.send(control.client.connection, new_response(entity, ControlResponseKind::Released));
+
+        // Reset control to default (no client)
+        *control = EntityControl::default();
+        commands.entity(entity).remove::<ControlSession>();
+
+        // Propagate to children if configured
+        if config.propagate_to_children {
+            if let Some(children) = children {
+                for child in children.iter() {
+                    commands.entity(child).insert(EntityControl::default());
+                }
+            }
         }
     }
 }
@@ -833,13 +1337,20 @@ fn timeout_inactive_control(
 /// This system listens for `NetworkEvent::Disconnected` events and:
 /// 1. Resets `EntityControl` components to the default (no client) state for any entities
 ///    controlled by that client
-/// 2. Removes the client from sub-connections tracking
+/// 2. Removes the client from sub-connections tracking, telling its own
+///    sub-connections that they were dropped, and revokes its tokens and
+///    pending control requests
 /// 3. Removes the client from any EntityControl sub_connection_ids lists
 fn cleanup_disconnected_control<NP: crate::NetworkProvider>(
     mut events: MessageReader<pl3xus::NetworkEvent>,
     mut entities: Query<(Entity, &mut EntityControl, Option<&Children>)>,
     config: Res<ExclusiveControlConfig>,
     mut sub_connections: ResMut<SubConnections>,
+    mut tokens: ResMut<SubConnectionTokens>,
+    mut approvals: ResMut<ControlApprovals>,
+    mut activity: ResMut<ControlActivity>,
+    session: Res<SyncSession>,
+    net: Res<Network<NP>>,
     mut commands: Commands,
 ) {
     for event in events.read() {
@@ -851,13 +1362,26 @@ fn cleanup_disconnected_control<NP: crate::NetworkProvider>(
 
             // Clean up sub-connections tracking
             // If this was a parent, remove all its sub-connections
+            for sub_id in sub_connections.get_sub_connections(*disconnected_id) {
+                let _ = net.send(
+                    sub_id,
+                    AssociateSubConnectionResponse {
+                        success: false,
+                        error: Some("Parent connection disconnected".to_string()),
+                        parent_connection_id: *disconnected_id,
+                    },
+                );
+            }
             sub_connections.remove_parent(*disconnected_id);
+            tokens.revoke(*disconnected_id);
+            approvals.remove_client(*disconnected_id);
+            activity.remove_client(*disconnected_id);
             // If this was a sub-connection, remove it from its parent
             sub_connections.remove_sub(*disconnected_id);
 
             for (entity, mut control, children) in entities.iter_mut() {
                 // Check if this client was the primary controller
-                if control.client_id == *disconnected_id {
+                if control.held_by(session.identity(*disconnected_id)) {
                     info!(
                         "[ExclusiveControl] Releasing control from disconnected client {:?} on entity {:?}",
                         disconnected_id, entity
@@ -935,3 +1459,87 @@ fn propagate_control_to_new_children(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_connection_tokens() {
+        let parent = ConnectionId { id: 5 };
+        let mut tokens = SubConnectionTokens::default();
+
+        // Tokens for the same parent and expiry are still unrelated
+        let token = tokens.issue(parent, 60.0);
+        let other = tokens.issue(parent, 60.0);
+        assert_ne!(other, token);
+        assert_eq!(token.len(), 32);
+        assert!(tokens.redeem(&token, ConnectionId { id: 6 }, 1.0).is_err());
+        assert!(tokens.redeem(&token, parent, 1.0).is_ok());
+        // Single use
+        assert!(tokens.redeem(&token, parent, 1.0).is_err());
+
+        let expired = tokens.issue(parent, 10.0);
+        assert!(tokens.redeem(&expired, parent, 10.0).is_err());
+
+        let revoked = tokens.issue(parent, 60.0);
+        tokens.revoke(parent);
+        assert!(tokens.redeem(&revoked, parent, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_control_policy_permits() {
+        let roles = |roles: &'static [&'static str]| roles.iter().copied();
+
+        assert!(ControlPolicy::default().permits(roles(&[])).is_ok());
+
+        let policy = ControlPolicy::default().allow_role("operator");
+        assert!(policy.permits(roles(&["viewer", "operator"])).is_ok());
+        assert!(policy.permits(roles(&["viewer"])).is_err());
+        assert!(policy.permits(roles(&[])).is_err());
+    }
+
+    #[test]
+    fn test_control_approvals() {
+        let entity = Entity::from_raw_u32(3).unwrap_or(Entity::PLACEHOLDER);
+        let client_id = ConnectionId { id: 5 };
+        let mut approvals = ControlApprovals::default();
+
+        assert!(approvals.hold(PendingControlRequest { entity, client_id }));
+        assert!(!approvals.hold(PendingControlRequest { entity, client_id }));
+        assert!(!approvals.approve(entity, ConnectionId { id: 6 }));
+        assert!(approvals.deny(entity, client_id, "busy"));
+        assert!(approvals.pending().is_empty());
+        // Already decided
+        assert!(!approvals.approve(entity, client_id));
+
+        approvals.hold(PendingControlRequest { entity, client_id });
+        approvals.remove_client(client_id);
+        assert!(approvals.pending().is_empty());
+        assert!(approvals.decisions.is_empty());
+    }
+
+    #[test]
+    fn test_control_activity_last_active() {
+        let client_id = ConnectionId { id: 1 };
+        let sub_connection = ConnectionId { id: 2 };
+        let control = EntityControl {
+            client: SyncSession(7).identity(client_id),
+            sub_connection_ids: vec![sub_connection],
+            last_activity: 5.0,
+        };
+
+        let mut activity = ControlActivity::default();
+        assert_eq!(activity.last_active(&control), 5.0);
+
+        activity.touch(client_id, 3.0);
+        assert_eq!(activity.last_active(&control), 5.0);
+
+        activity.touch(sub_connection, 12.0);
+        assert_eq!(activity.last_active(&control), 12.0);
+
+        activity.remove_client(sub_connection);
+        assert_eq!(activity.last_seen(sub_connection), None);
+        assert_eq!(activity.last_active(&control), 5.0);
+    }
+}
@@ -0,0 +1,206 @@
+//! Field-level read-only and redaction policies for synced components.
+//!
+//! Some components should only be partially visible or partially mutable from
+//! clients (for example, internal error codes or calibration data). Annotate
+//! the fields with `#[derive(SyncFields)]` from `pl3xus_macros` and opt the
+//! component in with `.with_field_policy()` on the sync builder:
+//!
+//! ```rust,ignore
+//! use pl3xus_macros::SyncFields;
+//!
+//! #[derive(Component, Serialize, Deserialize, Clone, Debug, SyncFields)]
+//! pub struct DriverState {
+//!     pub speed_override: u32,
+//!     #[sync_field(read_only)]
+//!     pub connected: bool,
+//!     #[sync_field(redact)]
+//!     pub internal_error_code: i32,
+//! }
+//!
+//! app.sync_component_builder::<DriverState>()
+//!     .with_field_policy()
+//!     .build();
+//! ```
+//!
+//! With a field policy installed:
+//! - Redacted fields are reset to their `Default` value in every snapshot and
+//!   update sent to clients.
+//! - Mutations that change a read-only field, or set a redacted field, are
+//!   rejected with `Forbidden`. Redacted fields keep their server-side value
+//!   when an allowed mutation is applied.
+
+use bevy::prelude::*;
+
+/// Per-field sync policy for a component type.
+///
+/// Typically implemented via `#[derive(SyncFields)]`.
+pub trait SyncFieldPolicy: Sized {
+    /// Return the wire-facing projection of this value, with redacted fields
+    /// reset to their defaults.
+    fn redacted(&self) -> Self;
+
+    /// Validate a client-proposed value against the current server value.
+    ///
+    /// Returns the value to apply (with redacted fields restored from
+    /// `current`), or an error describing the protected field that the client
+    /// tried to change. `current` is `None` when the mutation spawns a new entity.
+    fn merge_mutation(current: Option<&Self>, proposed: Self) -> Result<Self, String>;
+}
+
+/// Resource holding the type-erased field policy for component `T`.
+///
+/// Installed by `SyncComponentBuilder::with_field_policy`; the sync systems
+/// look it up when serializing and when applying mutations.
+#[derive(Resource)]
+pub struct FieldPolicy<T> {
+    redact: fn(&T) -> T,
+    merge: fn(Option<&T>, T) -> Result<T, String>,
+}
+
+impl<T: SyncFieldPolicy> FieldPolicy<T> {
+    /// Create the policy from the type's [`SyncFieldPolicy`] implementation.
+    pub fn new() -> Self {
+        Self {
+            redact: T::redacted,
+            merge: T::merge_mutation,
+        }
+    }
+}
+
+impl<T: SyncFieldPolicy> Default for FieldPolicy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FieldPolicy<T> {
+    /// Apply redaction to a value before it is sent to clients.
+    pub fn redact(&self, value: &T) -> T {
+        (self.redact)(value)
+    }
+
+    /// Validate and merge a client mutation.
+    pub fn merge(&self, current: Option<&T>, proposed: T) -> Result<T, String> {
+        (self.merge)(current, proposed)
+    }
+}
+
+/// Serialize a component for the wire, applying its field policy if one is installed.
+pub(crate) fn encode_for_wire<T>(policy: Option<&FieldPolicy<T>>, value: &T) -> Vec<u8>
+where
+    T: serde::Serialize,
+{
+    let encoded = match policy {
+        Some(policy) => {
+            bincode::serde::encode_to_vec(policy.redact(value), bincode::config::standard())
+        }
+        None => bincode::serde::encode_to_vec(value, bincode::config::standard()),
+    };
+    encoded.unwrap_or_default()
+}
+
+/// Validate a client mutation against the field policy for `T`, if any.
+///
+/// Returns the value that should actually be applied.
+pub(crate) fn enforce_field_policy<T>(
+    world: &World,
+    entity: Option<Entity>,
+    proposed: T,
+) -> Result<T, String>
+where
+    T: Component,
+{
+    let Some(policy) = world.get_resource::<FieldPolicy<T>>() else {
+        return Ok(proposed);
+    };
+    let current = entity.and_then(|e| world.get::<T>(e));
+    policy.merge(current, proposed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+    struct DriverState {
+        speed_override: u32,
+        connected: bool,
+        internal_error_code: i32,
+    }
+
+    // What `#[derive(SyncFields)]` generates for `connected` read-only and
+    // `internal_error_code` redacted
+    impl SyncFieldPolicy for DriverState {
+        fn redacted(&self) -> Self {
+            Self {
+                internal_error_code: 0,
+                ..self.clone()
+            }
+        }
+
+        fn merge_mutation(current: Option<&Self>, mut proposed: Self) -> Result<Self, String> {
+            if proposed.internal_error_code != 0 {
+                return Err("Field `internal_error_code` cannot be set by clients".to_string());
+            }
+            if let Some(current) = current {
+                if proposed.connected != current.connected {
+                    return Err("Field `connected` is read-only".to_string());
+                }
+                proposed.internal_error_code = current.internal_error_code;
+            }
+            Ok(proposed)
+        }
+    }
+
+    fn driver(speed_override: u32, connected: bool, internal_error_code: i32) -> DriverState {
+        DriverState {
+            speed_override,
+            connected,
+            internal_error_code,
+        }
+    }
+
+    #[test]
+    fn test_redacted_fields_are_not_sent() {
+        let value = driver(50, true, 17);
+        let decode = |bytes: Vec<u8>| {
+            bincode::serde::decode_from_slice::<DriverState, _>(&bytes, bincode::config::standard())
+                .unwrap()
+                .0
+        };
+
+        assert_eq!(decode(encode_for_wire(Some(&FieldPolicy::new()), &value)), driver(50, true, 0));
+        // Without a policy the value is sent as-is
+        assert_eq!(decode(encode_for_wire(None, &value)), value);
+    }
+
+    #[test]
+    fn test_mutations_merge_with_protected_fields() {
+        let mut world = World::new();
+        let entity = world.spawn(driver(50, true, 17)).id();
+
+        // No policy installed: anything goes
+        assert_eq!(
+            enforce_field_policy(&world, Some(entity), driver(80, false, 3)),
+            Ok(driver(80, false, 3))
+        );
+
+        world.insert_resource(FieldPolicy::<DriverState>::new());
+
+        // Allowed fields change; the redacted field keeps its server value
+        assert_eq!(
+            enforce_field_policy(&world, Some(entity), driver(80, true, 0)),
+            Ok(driver(80, true, 17))
+        );
+        assert!(enforce_field_policy(&world, Some(entity), driver(80, false, 0))
+            .unwrap_err()
+            .contains("`connected` is read-only"));
+        assert!(enforce_field_policy(&world, Some(entity), driver(80, true, 3))
+            .unwrap_err()
+            .contains("`internal_error_code` cannot be set"));
+
+        // Spawning mutations have no current value to compare against
+        assert_eq!(enforce_field_policy(&world, None, driver(10, false, 0)), Ok(driver(10, false, 0)));
+    }
+}
@@ -0,0 +1,281 @@
+//! Server-side subscription filters.
+//!
+//! A [`SubscriptionFilter`] sent with a subscription lets the server skip
+//! entities the client isn't interested in, instead of streaming every entity
+//! with the component and filtering on the client. Filters are evaluated
+//! against the component's JSON form (after field redaction), so paths use the
+//! serde field names. Clients apply the same filter to what they receive, so a
+//! server that ignores the filter still gives the right result:
+//!
+//! ```rust
+//! use pl3xus_sync::SubscriptionFilter;
+//!
+//! // Robots in automatic mode with more than 20% battery
+//! let filter = SubscriptionFilter::eq("mode", "Auto").and(SubscriptionFilter::gt("battery.level", 0.2));
+//! assert!(filter.matches(
+//!     &serde_json::json!({ "mode": "Auto", "battery": { "level": 0.8 } }),
+//!     pl3xus_sync::SerializableEntity { bits: 1 },
+//! ));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+
+use crate::messages::SerializableEntity;
+
+/// A literal compared against a component field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FilterValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl FilterValue {
+    fn compare(&self, json: &JsonValue) -> Option<Ordering> {
+        match (self, json) {
+            (FilterValue::Null, JsonValue::Null) => Some(Ordering::Equal),
+            (FilterValue::Bool(value), JsonValue::Bool(json)) => Some(json.cmp(value)),
+            (FilterValue::Int(value), JsonValue::Number(json)) => match json.as_i64() {
+                Some(json) => Some(json.cmp(value)),
+                None => json.as_f64()?.partial_cmp(&(*value as f64)),
+            },
+            (FilterValue::Float(value), JsonValue::Number(json)) => json.as_f64()?.partial_cmp(value),
+            (FilterValue::String(value), JsonValue::String(json)) => Some(json.as_str().cmp(value.as_str())),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(value: bool) -> Self {
+        FilterValue::Bool(value)
+    }
+}
+
+macro_rules! filter_value_from_int {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for FilterValue {
+            fn from(value: $ty) -> Self {
+                FilterValue::Int(value as i64)
+            }
+        })*
+    };
+}
+
+filter_value_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<f32> for FilterValue {
+    fn from(value: f32) -> Self {
+        FilterValue::Float(value as f64)
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(value: f64) -> Self {
+        FilterValue::Float(value)
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        FilterValue::String(value.to_string())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        FilterValue::String(value)
+    }
+}
+
+/// Comparison applied by [`SubscriptionFilter::Field`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Predicate selecting which entities a subscription receives.
+///
+/// Paths are dot-separated serde field names; numeric segments index into
+/// sequences (`"joints.0.angle"`). Unit enum variants serialize as their name,
+/// so `eq("mode", "Auto")` matches `mode: Mode::Auto`. A path that doesn't
+/// resolve, or a value of a different type, never matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SubscriptionFilter {
+    Field {
+        path: String,
+        op: FilterOp,
+        value: FilterValue,
+    },
+    /// The field equals one of `values`.
+    In { path: String, values: Vec<FilterValue> },
+    /// Only these entities.
+    Entities(Vec<SerializableEntity>),
+    And(Vec<SubscriptionFilter>),
+    Or(Vec<SubscriptionFilter>),
+    Not(Box<SubscriptionFilter>),
+}
+
+impl SubscriptionFilter {
+    fn field(path: impl Into<String>, op: FilterOp, value: impl Into<FilterValue>) -> Self {
+        SubscriptionFilter::Field {
+            path: path.into(),
+            op,
+            value: value.into(),
+        }
+    }
+
+    pub fn eq(path: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::field(path, FilterOp::Eq, value)
+    }
+
+    pub fn ne(path: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::field(path, FilterOp::Ne, value)
+    }
+
+    pub fn lt(path: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::field(path, FilterOp::Lt, value)
+    }
+
+    pub fn le(path: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::field(path, FilterOp::Le, value)
+    }
+
+    pub fn gt(path: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::field(path, FilterOp::Gt, value)
+    }
+
+    pub fn ge(path: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::field(path, FilterOp::Ge, value)
+    }
+
+    pub fn one_of<V: Into<FilterValue>>(path: impl Into<String>, values: impl IntoIterator<Item = V>) -> Self {
+        SubscriptionFilter::In {
+            path: path.into(),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn entities(entities: impl IntoIterator<Item = SerializableEntity>) -> Self {
+        SubscriptionFilter::Entities(entities.into_iter().collect())
+    }
+
+    /// Both `self` and `other` must match.
+    pub fn and(self, other: SubscriptionFilter) -> Self {
+        match self {
+            SubscriptionFilter::And(mut filters) => {
+                filters.push(other);
+                SubscriptionFilter::And(filters)
+            }
+            filter => SubscriptionFilter::And(vec![filter, other]),
+        }
+    }
+
+    /// Either `self` or `other` must match.
+    pub fn or(self, other: SubscriptionFilter) -> Self {
+        match self {
+            SubscriptionFilter::Or(mut filters) => {
+                filters.push(other);
+                SubscriptionFilter::Or(filters)
+            }
+            filter => SubscriptionFilter::Or(vec![filter, other]),
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        SubscriptionFilter::Not(Box::new(self))
+    }
+
+    /// Whether a component value (as JSON) on `entity` passes the filter.
+    pub fn matches(&self, value: &JsonValue, entity: SerializableEntity) -> bool {
+        match self {
+            SubscriptionFilter::Field { path, op, value: expected } => {
+                let Some(ordering) = lookup(value, path).and_then(|field| expected.compare(field)) else {
+                    return false;
+                };
+                match op {
+                    FilterOp::Eq => ordering == Ordering::Equal,
+                    FilterOp::Ne => ordering != Ordering::Equal,
+                    FilterOp::Lt => ordering == Ordering::Less,
+                    FilterOp::Le => ordering != Ordering::Greater,
+                    FilterOp::Gt => ordering == Ordering::Greater,
+                    FilterOp::Ge => ordering != Ordering::Less,
+                }
+            }
+            SubscriptionFilter::In { path, values } => lookup(value, path).is_some_and(|field| {
+                values
+                    .iter()
+                    .any(|expected| expected.compare(field) == Some(Ordering::Equal))
+            }),
+            SubscriptionFilter::Entities(entities) => entities.contains(&entity),
+            SubscriptionFilter::And(filters) => filters.iter().all(|filter| filter.matches(value, entity)),
+            SubscriptionFilter::Or(filters) => filters.iter().any(|filter| filter.matches(value, entity)),
+            SubscriptionFilter::Not(filter) => !filter.matches(value, entity),
+        }
+    }
+}
+
+fn lookup<'a>(value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.').try_fold(value, |value, segment| match value {
+        JsonValue::Object(map) => map.get(segment),
+        JsonValue::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ENTITY: SerializableEntity = SerializableEntity { bits: 7 };
+
+    #[test]
+    fn test_field_comparisons() {
+        let robot = json!({ "mode": "Auto", "speed": 40, "joints": [{ "angle": 1.5 }], "fault": null });
+
+        assert!(SubscriptionFilter::eq("mode", "Auto").matches(&robot, ENTITY));
+        assert!(!SubscriptionFilter::ne("mode", "Auto").matches(&robot, ENTITY));
+        assert!(SubscriptionFilter::gt("speed", 20).matches(&robot, ENTITY));
+        assert!(SubscriptionFilter::le("speed", 40.0).matches(&robot, ENTITY));
+        assert!(SubscriptionFilter::lt("joints.0.angle", 2.0).matches(&robot, ENTITY));
+        assert!(SubscriptionFilter::eq("fault", FilterValue::Null).matches(&robot, ENTITY));
+        assert!(SubscriptionFilter::one_of("mode", ["Manual", "Auto"]).matches(&robot, ENTITY));
+
+        // Missing fields and type mismatches never match
+        assert!(!SubscriptionFilter::eq("missing", 1).matches(&robot, ENTITY));
+        assert!(!SubscriptionFilter::ne("mode", 3).matches(&robot, ENTITY));
+        assert!(!SubscriptionFilter::gt("joints.1.angle", 0).matches(&robot, ENTITY));
+    }
+
+    #[test]
+    fn test_combinators_and_entities() {
+        let robot = json!({ "mode": "Auto", "speed": 40 });
+        let auto_and_fast = SubscriptionFilter::eq("mode", "Auto").and(SubscriptionFilter::gt("speed", 50));
+        assert!(!auto_and_fast.matches(&robot, ENTITY));
+        assert!(auto_and_fast.clone().negate().matches(&robot, ENTITY));
+        assert!(auto_and_fast.or(SubscriptionFilter::entities([ENTITY])).matches(&robot, ENTITY));
+        assert!(!SubscriptionFilter::entities([SerializableEntity { bits: 8 }]).matches(&robot, ENTITY));
+    }
+
+    #[test]
+    fn test_filter_roundtrips_through_bincode() {
+        let filter = SubscriptionFilter::eq("mode", "Auto").and(SubscriptionFilter::one_of("speed", [1.5, 2.0]).negate());
+        let bytes = bincode::serde::encode_to_vec(&filter, bincode::config::standard()).unwrap();
+        let (decoded, _): (SubscriptionFilter, _) =
+            bincode::serde::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, filter);
+    }
+}
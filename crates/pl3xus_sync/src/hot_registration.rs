@@ -0,0 +1,222 @@
+//! Registering synced types while the server is running.
+//!
+//! Components, messages and requests are normally registered before
+//! `App::run`. Types only known later (a plugin loaded on demand, a device
+//! type discovered at runtime) are queued in [`HotRegistrations`] instead,
+//! using the same registration calls as at startup:
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::hot_registration::HotRegistrations;
+//!
+//! fn on_device_discovered(mut hot: ResMut<HotRegistrations>) {
+//!     hot.sync_component::<GripperState>(None);
+//!     hot.add(|app| {
+//!         app.message::<OpenGripper, WebSocketProvider>().register();
+//!     });
+//! }
+//! ```
+//!
+//! Queued registrations are applied at the start of the next frame. Then
+//! every connected client receives a [`RegistryUpdate`] listing the new
+//! types, and subscriptions already made to a new component type receive
+//! their snapshot, so clients don't need to resubscribe.
+//!
+//! Registrations must not add plugins or systems to the `First` schedule,
+//! which is running while they are applied.
+
+use bevy::prelude::*;
+use pl3xus::managers::{Network, NetworkProvider};
+
+use crate::messages::{RegistryUpdate, SyncServerMessage};
+use crate::registration_checks::validate_registrations;
+use crate::registry::{ComponentSyncConfig, SnapshotQueue, SnapshotRequest, SubscriptionManager};
+use crate::schema::SchemaRegistry;
+use crate::AppPl3xusSyncExt;
+
+type Registration = Box<dyn FnOnce(&mut App) + Send + Sync>;
+
+/// Registrations queued to be applied at the start of the next frame.
+#[derive(Resource, Default)]
+pub struct HotRegistrations {
+    queued: Vec<Registration>,
+}
+
+impl HotRegistrations {
+    /// Queue registration calls to run against the app, e.g.
+    /// `app.request::<T, NP>().register()`.
+    pub fn add<F>(&mut self, register: F)
+    where
+        F: FnOnce(&mut App) + Send + Sync + 'static,
+    {
+        self.queued.push(Box::new(register));
+    }
+
+    /// Queue `app.sync_component::<T>(config)`.
+    pub fn sync_component<T>(&mut self, config: Option<ComponentSyncConfig>)
+    where
+        T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static + std::fmt::Debug + Clone,
+    {
+        self.add(move |app| {
+            app.sync_component::<T>(config);
+        });
+    }
+
+    /// Number of queued registrations.
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Returns true if nothing is queued.
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+}
+
+/// Apply queued registrations and tell every client about the new types.
+pub(crate) fn apply_hot_registrations<NP: NetworkProvider>(world: &mut World) {
+    let Some(update) = apply_queued(world) else {
+        return;
+    };
+    if let Some(net) = world.get_resource::<Network<NP>>() {
+        net.broadcast(SyncServerMessage::RegistryUpdated(update));
+    }
+}
+
+/// Run the queued registrations, returning the types they added.
+fn apply_queued(world: &mut World) -> Option<RegistryUpdate> {
+    let queued = {
+        let mut hot = world.get_resource_mut::<HotRegistrations>()?;
+        if hot.queued.is_empty() {
+            return None;
+        }
+        std::mem::take(&mut hot.queued)
+    };
+
+    let before = registered_names(world);
+    let known_problems = validate_registrations(world);
+
+    // The registration API works on an App, so lend it the world for a moment
+    let mut app = App::empty();
+    std::mem::swap(world, app.world_mut());
+    for register in queued {
+        register(&mut app);
+    }
+    std::mem::swap(world, app.world_mut());
+
+    for problem in validate_registrations(world) {
+        if !known_problems.contains(&problem) {
+            error!("[pl3xus_sync] Registration problem after hot registration: {}", problem);
+        }
+    }
+
+    let after = registered_names(world);
+    let added = |before: &[String], after: Vec<String>| -> Vec<String> {
+        after.into_iter().filter(|name| !before.contains(name)).collect()
+    };
+    let update = RegistryUpdate {
+        components: added(&before.0, after.0),
+        messages: added(&before.1, after.1),
+        requests: added(&before.2, after.2),
+    };
+    if update.is_empty() {
+        return None;
+    }
+    info!(
+        "[pl3xus_sync] Registered at runtime: components={:?}, messages={:?}, requests={:?}",
+        update.components, update.messages, update.requests
+    );
+
+    // Subscriptions were kept even though their type was unknown, so they
+    // only miss the snapshot
+    let waiting: Vec<SnapshotRequest> = world
+        .get_resource::<SubscriptionManager>()
+        .map(|subscriptions| {
+            subscriptions
+                .subscriptions
+                .iter()
+                .flat_map(|entry| {
+                    update
+                        .components
+                        .iter()
+                        .filter(|name| entry.component_type == "*" || entry.component_type == **name)
+                        .map(|name| SnapshotRequest {
+                            connection_id: entry.connection_id,
+                            subscription_id: entry.subscription_id,
+                            component_type: name.clone(),
+                            entity: entry.entity,
+                            filter: entry.filter.clone(),
+                        })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(mut snapshots) = world.get_resource_mut::<SnapshotQueue>() {
+        snapshots.pending.extend(waiting);
+    }
+
+    Some(update)
+}
+
+fn registered_names(world: &World) -> (Vec<String>, Vec<String>, Vec<String>) {
+    world
+        .get_resource::<SchemaRegistry>()
+        .map(|schema| {
+            (
+                schema.components.keys().cloned().collect(),
+                schema.messages.keys().cloned().collect(),
+                schema.requests.keys().cloned().collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{ComponentChangeEvent, ComponentRemovedEvent, EntityDespawnEvent, SubscriptionEntry};
+    use pl3xus_common::ConnectionId;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug)]
+    struct GripperState {
+        closed: bool,
+    }
+
+    #[test]
+    fn test_registers_while_running() {
+        let mut app = App::new();
+        app.init_resource::<HotRegistrations>()
+            .init_resource::<SubscriptionManager>()
+            .init_resource::<SnapshotQueue>()
+            .add_message::<ComponentChangeEvent>()
+            .add_message::<ComponentRemovedEvent>()
+            .add_message::<EntityDespawnEvent>()
+            .add_systems(First, |world: &mut World| {
+                apply_queued(world);
+            });
+        app.world_mut().resource_mut::<SubscriptionManager>().add_subscription(SubscriptionEntry {
+            connection_id: ConnectionId { id: 1 },
+            subscription_id: 7,
+            component_type: "GripperState".into(),
+            entity: None,
+            filter: None,
+            matched: Default::default(),
+            hidden: Default::default(),
+        });
+        app.update();
+
+        app.world_mut().spawn(GripperState { closed: true });
+        app.world_mut().resource_mut::<HotRegistrations>().sync_component::<GripperState>(None);
+        app.update();
+
+        assert!(app.world().resource::<HotRegistrations>().is_empty());
+        assert!(app.world().resource::<SchemaRegistry>().components.contains_key("GripperState"));
+        let pending = &app.world().resource::<SnapshotQueue>().pending;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].subscription_id, 7);
+
+        // The observer added at runtime ran in the same frame
+        let changes = app.world().resource::<Messages<ComponentChangeEvent>>();
+        assert!(changes.iter_current_update_messages().any(|change| change.component_type == "GripperState"));
+    }
+}
@@ -22,6 +22,9 @@
 //!
 //! The framework automatically broadcasts invalidations after successful mutations.
 //! For edge cases, use `broadcast_invalidations_for` as an escape hatch.
+//!
+//! Queries can also be invalidated whenever a component changes, with
+//! [`AppInvalidationExt::invalidate_on_change`].
 
 use bevy::prelude::*;
 use pl3xus_common::RequestMessage;
@@ -29,6 +32,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::messages::{QueryInvalidation, SyncServerMessage};
+use crate::systems::SyncSet;
 
 // =============================================================================
 // Invalidates Trait
@@ -62,6 +66,55 @@ pub trait Invalidates {
     /// Returns the list of query type names that should be invalidated
     /// when this mutation succeeds.
     fn invalidates() -> &'static [&'static str];
+
+    /// `(query type, key)` for each keyed query this mutation invalidates,
+    /// computed from the request and its response.
+    ///
+    /// Query types returned here are only invalidated for their keys; the
+    /// rest of [`invalidates`](Self::invalidates) are invalidated entirely.
+    /// Generated by `#[invalidates("GetProgram", key = "self.program_id")]`.
+    fn invalidation_keys(&self, _response: &<Self as RequestMessage>::ResponseMessage) -> Vec<(&'static str, String)>
+    where
+        Self: RequestMessage,
+    {
+        Vec::new()
+    }
+}
+
+/// The invalidations for a successful `request`, grouping the keys of keyed queries.
+fn request_invalidations<T>(request: &T, response: &T::ResponseMessage) -> Vec<QueryInvalidation>
+where
+    T: Invalidates + RequestMessage,
+{
+    let keyed = request.invalidation_keys(response);
+
+    let unkeyed: Vec<String> = T::invalidates()
+        .iter()
+        .filter(|query_type| !keyed.iter().any(|(keyed_type, _)| keyed_type == *query_type))
+        .map(|query_type| query_type.to_string())
+        .collect();
+
+    let mut invalidations = Vec::new();
+    if !unkeyed.is_empty() {
+        invalidations.push(QueryInvalidation {
+            query_types: unkeyed,
+            keys: None,
+        });
+    }
+
+    let mut keys_by_query: Vec<(&str, Vec<String>)> = Vec::new();
+    for (query_type, key) in keyed {
+        match keys_by_query.iter_mut().find(|(existing, _)| *existing == query_type) {
+            Some((_, keys)) => keys.push(key),
+            None => keys_by_query.push((query_type, vec![key])),
+        }
+    }
+    invalidations.extend(keys_by_query.into_iter().map(|(query_type, keys)| QueryInvalidation {
+        query_types: vec![query_type.to_string()],
+        keys: Some(keys),
+    }));
+
+    invalidations
 }
 
 // =============================================================================
@@ -94,6 +147,15 @@ impl InvalidationRules {
     pub fn add_rule(&mut self, request_type: String, rule: InvalidationRule) {
         self.rules.entry(request_type).or_default().push(rule);
     }
+
+    /// `(request type, query type)` for every rule.
+    pub fn invalidated_queries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.rules.iter().flat_map(|(request_type, rules)| {
+            rules
+                .iter()
+                .map(move |rule| (request_type.as_str(), rule.query_type.as_str()))
+        })
+    }
 }
 
 /// Builder for configuring invalidation rules.
@@ -183,6 +245,40 @@ impl<'a, T: RequestMessage> InvalidationRuleBuilder<'a, T> {
 pub trait AppInvalidationExt {
     /// Start building invalidation rules.
     fn invalidation_rules(&mut self) -> InvalidationRulesBuilder<'_>;
+
+    /// Invalidate `query_types` on every client whenever a component `T` is
+    /// added, changed or removed.
+    ///
+    /// Uses Bevy change detection, so handlers that update the data behind a
+    /// query don't need to invalidate it themselves. Changes within one frame
+    /// are sent as a single invalidation.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Touched by every handler that creates, renames or deletes a program
+    /// #[derive(Component)]
+    /// struct ProgramListChanged;
+    ///
+    /// app.invalidate_on_change::<ProgramListChanged, WebSocketProvider>(&["ListPrograms"]);
+    /// ```
+    fn invalidate_on_change<T: Component, NP: NetworkProvider>(&mut self, query_types: &[&str]) -> &mut Self;
+
+    /// Invalidate the keyed `query_type` for the key of each entity whose `T`
+    /// is added, changed or removed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.invalidate_on_change_keyed::<Program, WebSocketProvider, _>("GetProgram", |_, program| {
+    ///     program.id.to_string()
+    /// });
+    /// ```
+    fn invalidate_on_change_keyed<T, NP, F>(&mut self, query_type: &str, key_fn: F) -> &mut Self
+    where
+        T: Component,
+        NP: NetworkProvider,
+        F: Fn(Entity, &T) -> String + Send + Sync + 'static;
 }
 
 impl AppInvalidationExt for App {
@@ -193,6 +289,175 @@ impl AppInvalidationExt for App {
         }
         InvalidationRulesBuilder { app: self }
     }
+
+    fn invalidate_on_change<T: Component, NP: NetworkProvider>(&mut self, query_types: &[&str]) -> &mut Self {
+        for query_type in query_types {
+            add_component_invalidation::<T, NP>(
+                self,
+                ComponentInvalidationRule {
+                    query_type: query_type.to_string(),
+                    key_fn: None,
+                },
+            );
+        }
+        self
+    }
+
+    fn invalidate_on_change_keyed<T, NP, F>(&mut self, query_type: &str, key_fn: F) -> &mut Self
+    where
+        T: Component,
+        NP: NetworkProvider,
+        F: Fn(Entity, &T) -> String + Send + Sync + 'static,
+    {
+        add_component_invalidation::<T, NP>(
+            self,
+            ComponentInvalidationRule {
+                query_type: query_type.to_string(),
+                key_fn: Some(Arc::new(key_fn)),
+            },
+        );
+        self
+    }
+}
+
+// =============================================================================
+// Component-Change Invalidation
+// =============================================================================
+
+/// A query invalidated whenever a component of type `T` changes.
+struct ComponentInvalidationRule<T> {
+    query_type: String,
+    /// Computes the cache key of the changed entity, for keyed queries.
+    key_fn: Option<Arc<dyn Fn(Entity, &T) -> String + Send + Sync>>,
+}
+
+/// Queries invalidated by changes to component `T`.
+///
+/// Configured with [`AppInvalidationExt::invalidate_on_change`] and
+/// [`AppInvalidationExt::invalidate_on_change_keyed`].
+#[derive(Resource)]
+pub struct ComponentInvalidations<T: Component> {
+    rules: Vec<ComponentInvalidationRule<T>>,
+}
+
+impl<T: Component> Default for ComponentInvalidations<T> {
+    fn default() -> Self {
+        Self { rules: Vec::new() }
+    }
+}
+
+impl<T: Component> ComponentInvalidations<T> {
+    /// Query types invalidated by changes to `T`.
+    pub fn query_types(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.query_type.as_str())
+    }
+
+    /// The invalidations to send for this frame's changes.
+    ///
+    /// Unkeyed queries are sent together; keyed queries are sent with the
+    /// keys of the changed entities. `last_keys` remembers the keys of each
+    /// entity so removals can invalidate the key the entity last had.
+    fn collect<'w>(
+        &self,
+        changed: impl Iterator<Item = (Entity, &'w T)>,
+        removed: impl Iterator<Item = Entity>,
+        last_keys: &mut HashMap<Entity, Vec<String>>,
+    ) -> Vec<QueryInvalidation> {
+        let mut any_change = false;
+        let mut keys: Vec<Vec<String>> = vec![Vec::new(); self.rules.len()];
+
+        let mut collect_keys = |entity_keys: &[String]| {
+            let keyed = self.rules.iter().enumerate().filter(|(_, rule)| rule.key_fn.is_some());
+            for ((index, _), key) in keyed.zip(entity_keys) {
+                if !keys[index].contains(key) {
+                    keys[index].push(key.clone());
+                }
+            }
+        };
+
+        for entity in removed {
+            any_change = true;
+            if let Some(entity_keys) = last_keys.remove(&entity) {
+                collect_keys(&entity_keys);
+            }
+        }
+
+        for (entity, component) in changed {
+            any_change = true;
+            let entity_keys: Vec<String> = self
+                .rules
+                .iter()
+                .filter_map(|rule| rule.key_fn.as_ref().map(|key_fn| key_fn(entity, component)))
+                .collect();
+            if entity_keys.is_empty() {
+                continue;
+            }
+            // A changed key also invalidates the old one
+            if let Some(old_keys) = last_keys.insert(entity, entity_keys.clone()) {
+                collect_keys(&old_keys);
+            }
+            collect_keys(&entity_keys);
+        }
+
+        if !any_change {
+            return Vec::new();
+        }
+
+        let unkeyed: Vec<String> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.key_fn.is_none())
+            .map(|rule| rule.query_type.clone())
+            .collect();
+
+        let mut invalidations = Vec::new();
+        if !unkeyed.is_empty() {
+            invalidations.push(QueryInvalidation {
+                query_types: unkeyed,
+                keys: None,
+            });
+        }
+        for (rule, keys) in self.rules.iter().zip(keys) {
+            if rule.key_fn.is_some() && !keys.is_empty() {
+                invalidations.push(QueryInvalidation {
+                    query_types: vec![rule.query_type.clone()],
+                    keys: Some(keys),
+                });
+            }
+        }
+        invalidations
+    }
+}
+
+/// Broadcast the invalidations configured for `T` when any `T` is added,
+/// changed or removed.
+fn broadcast_component_invalidations<T: Component, NP: NetworkProvider>(
+    changed: Query<(Entity, &T), Changed<T>>,
+    mut removed: RemovedComponents<T>,
+    mut last_keys: Local<HashMap<Entity, Vec<String>>>,
+    invalidations: Res<ComponentInvalidations<T>>,
+    net: Res<Network<NP>>,
+) {
+    for invalidation in invalidations.collect(changed.iter(), removed.read(), &mut last_keys) {
+        debug!(
+            "📢 Auto-invalidated queries {:?} after {} changed",
+            invalidation.query_types,
+            std::any::type_name::<T>()
+        );
+        net.broadcast(SyncServerMessage::QueryInvalidation(invalidation));
+    }
+}
+
+/// Add `rule` for `T`, scheduling the broadcast system with the first rule.
+fn add_component_invalidation<T: Component, NP: NetworkProvider>(app: &mut App, rule: ComponentInvalidationRule<T>) {
+    if !app.world().contains_resource::<ComponentInvalidations<T>>() {
+        app.init_resource::<ComponentInvalidations<T>>();
+        app.add_systems(
+            Update,
+            broadcast_component_invalidations::<T, NP>.in_set(SyncSet::DetectChanges),
+        );
+    }
+    app.world_mut().resource_mut::<ComponentInvalidations<T>>().rules.push(rule);
 }
 
 // =============================================================================
@@ -294,6 +559,37 @@ pub fn broadcast_invalidations_for<T, NP>(
     }
 }
 
+/// Broadcast the invalidations for a successful `request`, including the keys
+/// of keyed queries from [`Invalidates::invalidation_keys`].
+///
+/// This is what `respond_and_invalidate` calls; use it directly when the
+/// response is sent some other way.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Invalidates)]
+/// #[invalidates("GetProgram", key = "self.program_id")]
+/// pub struct UpdateProgramSettings { ... }
+///
+/// if response.success {
+///     broadcast_request_invalidations(&net, request.get_request(), &response);
+/// }
+/// ```
+pub fn broadcast_request_invalidations<T, NP>(net: &Network<NP>, request: &T, response: &T::ResponseMessage)
+where
+    T: Invalidates + RequestMessage,
+    NP: NetworkProvider,
+{
+    for invalidation in request_invalidations(request, response) {
+        debug!(
+            "📢 Auto-invalidated queries {:?} (keys {:?}) after successful mutation",
+            invalidation.query_types, invalidation.keys
+        );
+        net.broadcast(SyncServerMessage::QueryInvalidation(invalidation));
+    }
+}
+
 // =============================================================================
 // Request Extension for Auto-Invalidation
 // =============================================================================
@@ -339,8 +635,9 @@ pub trait RequestInvalidateExt<T: RequestMessage> {
     /// Respond to the request and automatically broadcast query invalidations
     /// if the response indicates success.
     ///
-    /// This combines `respond()` and `broadcast_invalidations_for()` into a
-    /// single call, reducing boilerplate in mutation handlers.
+    /// This combines `respond()` and `broadcast_request_invalidations()` into a
+    /// single call, reducing boilerplate in mutation handlers. Keyed queries
+    /// are invalidated for the keys from [`Invalidates::invalidation_keys`].
     fn respond_and_invalidate<NP: NetworkProvider>(
         self,
         response: T::ResponseMessage,
@@ -376,11 +673,18 @@ impl<T: RequestMessage> RequestInvalidateExt<T> for Request<T> {
         T::ResponseMessage: HasSuccess,
     {
         let is_success = response.is_success();
+        let invalidations = request_invalidations(self.get_request(), &response);
         let result = self.respond(response);
 
         // Broadcast invalidations after successful response
         if result.is_ok() && is_success {
-            broadcast_invalidations_for::<T, NP>(net, None);
+            for invalidation in invalidations {
+                net.broadcast(SyncServerMessage::QueryInvalidation(invalidation));
+            }
+            debug!(
+                "📢 Auto-invalidated queries {:?} after successful mutation",
+                T::invalidates()
+            );
         }
 
         result
@@ -408,3 +712,85 @@ impl<T: RequestMessage> RequestInvalidateExt<T> for Request<T> {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct UpdateProgram {
+        program_id: i64,
+    }
+
+    impl RequestMessage for UpdateProgram {
+        type ResponseMessage = bool;
+    }
+
+    impl Invalidates for UpdateProgram {
+        fn invalidates() -> &'static [&'static str] {
+            &["ListPrograms", "GetProgram"]
+        }
+
+        fn invalidation_keys(&self, _response: &bool) -> Vec<(&'static str, String)> {
+            vec![("GetProgram", self.program_id.to_string())]
+        }
+    }
+
+    #[test]
+    fn test_request_invalidations() {
+        let invalidations = request_invalidations(&UpdateProgram { program_id: 3 }, &true);
+        assert_eq!(invalidations.len(), 2);
+        assert_eq!(invalidations[0].query_types, vec!["ListPrograms"]);
+        assert_eq!(invalidations[0].keys, None);
+        assert_eq!(invalidations[1].query_types, vec!["GetProgram"]);
+        assert_eq!(invalidations[1].keys, Some(vec!["3".to_string()]));
+    }
+
+    #[derive(Component)]
+    struct Program {
+        id: u32,
+    }
+
+    fn rules() -> ComponentInvalidations<Program> {
+        ComponentInvalidations {
+            rules: vec![
+                ComponentInvalidationRule {
+                    query_type: "ListPrograms".to_string(),
+                    key_fn: None,
+                },
+                ComponentInvalidationRule {
+                    query_type: "GetProgram".to_string(),
+                    key_fn: Some(Arc::new(|_, program: &Program| program.id.to_string())),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_component_invalidations() {
+        let rules = rules();
+        let mut last_keys = HashMap::new();
+        let entity = Entity::from_raw_u32(1).unwrap_or(Entity::PLACEHOLDER);
+
+        assert!(rules.collect(std::iter::empty(), std::iter::empty(), &mut last_keys).is_empty());
+
+        let program = Program { id: 7 };
+        let invalidations = rules.collect(std::iter::once((entity, &program)), std::iter::empty(), &mut last_keys);
+        assert_eq!(invalidations.len(), 2);
+        assert_eq!(invalidations[0].query_types, vec!["ListPrograms"]);
+        assert_eq!(invalidations[0].keys, None);
+        assert_eq!(invalidations[1].query_types, vec!["GetProgram"]);
+        assert_eq!(invalidations[1].keys, Some(vec!["7".to_string()]));
+
+        // A new key invalidates the old one too
+        let program = Program { id: 8 };
+        let invalidations = rules.collect(std::iter::once((entity, &program)), std::iter::empty(), &mut last_keys);
+        assert_eq!(invalidations[1].keys, Some(vec!["7".to_string(), "8".to_string()]));
+
+        // Removal invalidates the key the entity last had
+        let invalidations = rules.collect(std::iter::empty(), std::iter::once(entity), &mut last_keys);
+        assert_eq!(invalidations[1].keys, Some(vec!["8".to_string()]));
+        assert!(last_keys.is_empty());
+    }
+}
@@ -32,6 +32,19 @@
 //!    .register();
 //! ```
 
+/// Log at info level when [`SyncPluginConfig::verbose_logging`] is set, and at
+/// debug level otherwise.
+#[cfg(feature = "runtime")]
+macro_rules! verbose_log {
+    ($verbose:expr, $($arg:tt)+) => {
+        if $verbose {
+            bevy::log::info!($($arg)+)
+        } else {
+            bevy::log::debug!($($arg)+)
+        }
+    };
+}
+
 mod messages;
 #[cfg(feature = "runtime")]
 mod registry;
@@ -42,6 +55,26 @@ mod systems;
 #[cfg(feature = "runtime")]
 mod invalidation;
 
+/// Field-level read-only and redaction policies for synced components.
+#[cfg(feature = "runtime")]
+pub mod field_policy;
+
+/// Per-field validation of client mutations.
+#[cfg(feature = "runtime")]
+pub mod validation;
+
+/// Opt-in undo/redo for component mutations.
+#[cfg(feature = "runtime")]
+pub mod undo;
+
+/// State machine components with declared legal transitions.
+#[cfg(feature = "runtime")]
+pub mod state_machine;
+
+/// Server-driven UI action availability per entity.
+#[cfg(feature = "runtime")]
+pub mod actions;
+
 /// Pluggable authorization policies for messages.
 #[cfg(feature = "runtime")]
 pub mod authorization;
@@ -50,17 +83,94 @@ pub mod authorization;
 #[cfg(feature = "runtime")]
 pub mod control;
 
+/// Targeted server notifications with delivery acknowledgement.
+#[cfg(feature = "runtime")]
+pub mod notifications;
+
+/// Per-connection rate limits for mutations, requests and messages.
+#[cfg(feature = "runtime")]
+pub mod rate_limit;
+
+/// Client requests to spawn and despawn entities of server-defined archetypes.
+#[cfg(feature = "runtime")]
+pub mod spawn;
+
+/// Out-of-band transfer of large binary data referenced by components.
+#[cfg(feature = "runtime")]
+pub mod blob;
+
+/// Opt-in reliable, ordered delivery for critical messages.
+#[cfg(feature = "runtime")]
+pub mod reliable;
+
+/// Live queries that push row changes to subscribed clients.
+#[cfg(feature = "runtime")]
+pub mod live_query;
+
+/// Optional client presence tracking (connected sessions and control holdings).
+#[cfg(feature = "runtime")]
+pub mod presence;
+
+/// Stable string aliases for entities, surviving server restarts.
+#[cfg(feature = "runtime")]
+pub mod stable_id;
+
+/// Startup validation of registrations with actionable diagnostics.
+#[cfg(feature = "runtime")]
+pub mod registration_checks;
+
+/// Registration of synced types while the server is running.
+#[cfg(feature = "runtime")]
+pub mod hot_registration;
+
+/// Named phases and cross-plugin ordering for application systems.
+#[cfg(feature = "runtime")]
+pub mod plugin_schedule;
+
+/// Runtime schema introspection for registered types.
+#[cfg(feature = "runtime")]
+pub mod schema;
+
+/// Native Bevy client mirroring server entities into the local world.
+#[cfg(feature = "runtime")]
+pub mod client;
+
+/// Admin requests served to `pl3xus-cli`.
+pub mod admin;
+
+/// Server-side log of client commands, with paging and replay.
+pub mod command_log;
+
+/// Server-side subscription filters.
+pub mod filter;
+
+/// Generated protocol constants for clients in other languages.
+pub mod codegen;
+
+/// Types used by the `sync_load_generator` and `pl3xus_loadtest` binaries.
+#[cfg(feature = "load-generator")]
+pub mod load_testing;
+
 pub use messages::*;
+pub use filter::{FilterOp, FilterValue, SubscriptionFilter};
 #[cfg(feature = "runtime")]
 pub use registry::{
     ComponentSyncConfig,
+    VisibilityPolicy,
     SyncSettings,
+    SyncPluginConfig,
     ConflationQueue,
+    ConflationStats,
+    BackpressurePolicy,
+    SyncPriority,
+    SyncSequences,
+    SyncSession,
     ComponentRegistration,
     SyncRegistry,
     SubscriptionManager,
     SubscriptionEntry,
     MutationQueue,
+    QueuedMutation,
     SnapshotQueue,
     ComponentChangeEvent,
     EntityDespawnEvent,
@@ -75,9 +185,14 @@ pub use registry::{
     ComponentMutationQueue,
     MutationResponseQueue,
     PendingMutationResponse,
+    DeferredMutationResponder,
+    MutationIdempotency,
+    MutationOrigin,
 };
 #[cfg(feature = "runtime")]
 pub use subscription::*;
+#[cfg(feature = "runtime")]
+pub use systems::{Pl3xusSyncSystems, SyncSet};
 
 // New authorization API (v0.2+)
 #[cfg(feature = "runtime")]
@@ -96,6 +211,11 @@ pub use authorization::{
     MessageAccessPolicy,
     MessageAccessPolicies,
     DefaultMessageAccessPolicy,
+    // Middleware (ordered interceptors before handlers)
+    MiddlewareContext,
+    MessageInterceptor,
+    Middleware,
+    MiddlewareChain,
     // Authorized message types (output of authorization middleware)
     AuthorizedTargetedMessage,
     AuthorizedMessage,
@@ -112,6 +232,8 @@ pub use authorization::{
     AuthorizedRequest,
     RequestRegistration,
     AppRequestRegistrationExt,
+    // Derived request registration (`#[derive(Pl3xusRequest)]`)
+    RegisterRequest,
     // Batch request registration
     BatchRequestConfig,
     BatchRequestRegistration,
@@ -120,6 +242,51 @@ pub use authorization::{
     AppBatchRequestRegistrationExt,
 };
 
+// Reliable delivery (`MessageRegistration::reliable`)
+#[cfg(feature = "runtime")]
+pub use reliable::{ReliableAck, ReliableDelivery, ReliableEnvelope, ReliableInbox, ReliableSettings};
+
+// Field-level sync policies (`#[derive(SyncFields)]`)
+#[cfg(feature = "runtime")]
+pub use field_policy::{FieldPolicy, SyncFieldPolicy};
+
+#[cfg(feature = "runtime")]
+pub use validation::{MutationValidator, ValidateMutation};
+
+#[cfg(feature = "runtime")]
+pub use undo::{MutationHistory, UndoHistory, UndoSettings};
+
+#[cfg(feature = "runtime")]
+pub use state_machine::{StateTransitioned, SyncStateMachine, TransitionGuard};
+
+#[cfg(feature = "runtime")]
+pub use schema::SchemaRegistry;
+
+#[cfg(feature = "runtime")]
+pub use hot_registration::HotRegistrations;
+
+#[cfg(feature = "runtime")]
+pub use plugin_schedule::{AppPluginScheduleExt, PluginOrdering, PluginSchedule, PluginSet};
+
+#[cfg(feature = "runtime")]
+pub use client::{AppSyncClientExt, ClientMutations, Pl3xusSyncClientPlugin, ServerEntity, ServerEntityMap};
+
+#[cfg(feature = "runtime")]
+pub use command_log::{CommandLog, CommandLogPlugin, CommandLogStore};
+
+#[cfg(feature = "runtime")]
+pub use actions::{ActionState, ActionsPlugin, AppActionsExt, EntityActions};
+
+#[cfg(feature = "runtime")]
+pub use stable_id::{StableId, StableIdPlugin, StableIds};
+
+#[cfg(feature = "runtime")]
+pub use live_query::{AppLiveQueryExt, LiveQueries, LiveQueryCacheSizes};
+pub use pl3xus_common::LiveQuery;
+
+#[cfg(feature = "runtime")]
+pub use spawn::{AppSpawnArchetypeExt, SpawnArchetype, SpawnArchetypes, SpawnPlugin, SpawnedFrom};
+
 // Re-export DeferredResponder for async request handling
 #[cfg(feature = "runtime")]
 pub use pl3xus::DeferredResponder;
@@ -135,9 +302,12 @@ pub use invalidation::{
     InvalidationRulesBuilder,
     InvalidationRuleBuilder,
     AppInvalidationExt,
+    // Invalidation driven by component changes
+    ComponentInvalidations,
     broadcast_invalidations,
-    // New trait-based broadcast function
+    // New trait-based broadcast functions
     broadcast_invalidations_for,
+    broadcast_request_invalidations,
     // Request extension for auto-invalidation
     RequestInvalidateExt,
 };
@@ -145,29 +315,48 @@ pub use invalidation::{
 #[cfg(feature = "runtime")]
 use bevy::prelude::*;
 #[cfg(feature = "runtime")]
-use pl3xus::managers::NetworkProvider;
+pub use pl3xus::managers::NetworkProvider;
 
 /// Top-level plugin that adds sync resources, registers network messages, and
 /// installs core systems.
 #[cfg(feature = "runtime")]
 #[derive(Debug, Clone)]
 pub struct Pl3xusSyncPlugin<NP: NetworkProvider> {
+    config: SyncPluginConfig,
     _marker: std::marker::PhantomData<NP>,
 }
 
+#[cfg(feature = "runtime")]
+impl<NP: NetworkProvider> Pl3xusSyncPlugin<NP> {
+    /// Create the plugin with a custom configuration.
+    pub fn new(config: SyncPluginConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
 #[cfg(feature = "runtime")]
 impl<NP: NetworkProvider> Default for Pl3xusSyncPlugin<NP> {
     fn default() -> Self {
-        Self { _marker: std::marker::PhantomData }
+        Self::new(SyncPluginConfig::default())
     }
 }
 
 #[cfg(feature = "runtime")]
 impl<NP: NetworkProvider> Plugin for Pl3xusSyncPlugin<NP> {
     fn build(&self, app: &mut App) {
-        info!("[Pl3xusSyncPlugin::build] CALLED - about to call systems::install");
-        systems::install::<NP>(app);
-        info!("[Pl3xusSyncPlugin::build] COMPLETED - systems::install returned");
+        systems::install::<NP>(app, &self.config);
+
+        // Clients estimate the server clock (and sync latency) from ClockPong replies
+        if !app.is_plugin_added::<pl3xus::ClockSyncServerPlugin<NP>>() {
+            app.add_plugins(pl3xus::ClockSyncServerPlugin::<NP>::default());
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        registration_checks::report_registration_problems(app.world(), self.config.strict_registrations);
     }
 }
 
@@ -209,6 +398,20 @@ pub trait AppPl3xusSyncExt {
     fn sync_component_builder<T>(&mut self) -> SyncComponentBuilder<'_, T>
     where
         T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static + std::fmt::Debug + Clone;
+
+    /// Register every type listed in a shared [`TypeManifest`](pl3xus_common::TypeManifest)
+    /// with default settings.
+    ///
+    /// Components are synced with `sync_component(None)`, messages and requests
+    /// are registered without policies. Types that need custom configuration
+    /// should be left out of the manifest and registered individually.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.register_manifest::<RobotTypes, WebSocketProvider>();
+    /// ```
+    fn register_manifest<M: pl3xus_common::TypeManifest, NP: NetworkProvider>(&mut self) -> &mut Self;
 }
 
 #[cfg(feature = "runtime")]
@@ -227,6 +430,36 @@ impl AppPl3xusSyncExt for App {
     {
         SyncComponentBuilder::new(self)
     }
+
+    fn register_manifest<M: pl3xus_common::TypeManifest, NP: NetworkProvider>(&mut self) -> &mut Self {
+        M::visit(&mut ManifestRegistrar::<NP> {
+            app: self,
+            _marker: std::marker::PhantomData,
+        });
+        self
+    }
+}
+
+/// Registers the types of a manifest on the server.
+#[cfg(feature = "runtime")]
+struct ManifestRegistrar<'a, NP: NetworkProvider> {
+    app: &'a mut App,
+    _marker: std::marker::PhantomData<NP>,
+}
+
+#[cfg(feature = "runtime")]
+impl<NP: NetworkProvider> pl3xus_common::TypeVisitor for ManifestRegistrar<'_, NP> {
+    fn component<T: pl3xus_common::ManifestComponent>(&mut self) {
+        registry::register_component::<T>(self.app, None);
+    }
+
+    fn message<T: pl3xus::Pl3xusMessage + Clone>(&mut self) {
+        authorization::MessageRegistration::<T, NP>::new(self.app).register();
+    }
+
+    fn request<T: pl3xus_common::RequestMessage>(&mut self) {
+        authorization::RequestRegistration::<T, NP>::new(self.app).register();
+    }
 }
 
 /// Builder for configuring component synchronization.
@@ -281,6 +514,9 @@ where
     /// 3. Applying the mutation to the component if appropriate
     /// 4. Responding to the client via `MutationResponseQueue`
     ///
+    /// Handlers that need async work can take a `DeferredMutationResponder`
+    /// with `mutation.defer_response(&responses)` and complete it later.
+    ///
     /// # Example
     ///
     /// ```rust,ignore
@@ -372,6 +608,107 @@ where
         self
     }
 
+    /// Limit how often changes to this component are broadcast to clients.
+    ///
+    /// Changes are coalesced per entity and sent at most `hz` times per second.
+    /// Values `<= 0.0` remove the limit.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.sync_component_builder::<JointAngles>()
+    ///     .read_only()
+    ///     .rate_hz(10.0)
+    ///     .build();
+    /// ```
+    pub fn rate_hz(mut self, hz: f32) -> Self {
+        self.config = self.config.with_max_update_rate_hz(hz);
+        self
+    }
+
+    /// Set which updates give way first when a client falls behind.
+    ///
+    /// ```rust,ignore
+    /// app.sync_component_builder::<ConsoleLine>()
+    ///     .read_only()
+    ///     .priority(SyncPriority::Low)
+    ///     .build();
+    /// ```
+    pub fn priority(mut self, priority: SyncPriority) -> Self {
+        self.config = self.config.with_priority(priority);
+        self
+    }
+
+    /// Only send this component to connections allowed by `policy`.
+    ///
+    /// The policy is evaluated per (connection, entity) for every snapshot and
+    /// update, so each client only sees the entities it is allowed to see.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// app.sync_component_builder::<RobotStatus>()
+    ///     .read_only()
+    ///     .with_visibility(VisibilityPolicy::from_fn(|world, conn, entity| {
+    ///         world.get::<Owner>(entity).is_some_and(|o| o.connection == conn)
+    ///     }))
+    ///     .build();
+    /// ```
+    pub fn with_visibility(mut self, policy: VisibilityPolicy) -> Self {
+        self.config = self.config.with_visibility(policy);
+        self
+    }
+
+    /// Enforce the component's field-level read-only and redaction policy.
+    ///
+    /// Redacted fields are stripped from snapshots and updates, and mutations
+    /// touching protected fields are rejected. See [`field_policy`] for details.
+    pub fn with_field_policy(self) -> Self
+    where
+        T: SyncFieldPolicy,
+    {
+        self.app.insert_resource(FieldPolicy::<T>::new());
+        self
+    }
+
+    /// Record successful mutations of this component so clients can undo
+    /// and redo them. See [`undo`] for details.
+    pub fn with_undo(mut self) -> Self {
+        self.config = self.config.with_undo();
+        undo::enable_undo(self.app);
+        self
+    }
+
+    /// Only accept client mutations that follow `machine`'s legal transitions,
+    /// and announce every transition. See [`state_machine`] for details.
+    pub fn with_state_machine(mut self, machine: SyncStateMachine<T>) -> Self {
+        self.config = self.config.with_transition_guard(TransitionGuard::new(machine));
+        state_machine::enable_state_machine::<T>(self.app);
+        self
+    }
+
+    /// Validate client mutations with a closure before they are handled.
+    ///
+    /// Returning `Err` rejects the mutation with a `ValidationError` carrying
+    /// the given per-field errors. See [`validation`] for details.
+    pub fn with_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), Vec<FieldError>> + Send + Sync + 'static,
+    {
+        self.config = self.config.with_validator(MutationValidator::from_fn(validator));
+        self
+    }
+
+    /// Validate client mutations against the component's `#[validate(...)]`
+    /// field constraints (requires `#[derive(ValidateMutation)]`).
+    pub fn validated(mut self) -> Self
+    where
+        T: ValidateMutation,
+    {
+        self.config = self.config.with_validator(MutationValidator::derived::<T>());
+        self
+    }
+
     /// Finalize the registration and apply the configuration.
     pub fn build(self) -> &'a mut App {
         // Register the appropriate message type based on authorization mode
@@ -386,6 +723,35 @@ where
     }
 }
 
+/// Trait for component types that know how to register themselves for sync.
+///
+/// This is typically implemented via `#[derive(SyncComponent)]` from
+/// `pl3xus_macros`, which translates `#[sync(...)]` attributes into the
+/// equivalent [`SyncComponentBuilder`] calls.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(Component, Serialize, Deserialize, Clone, Debug, SyncComponent)]
+/// #[sync(targeted, default_policy, handler = handle_jog_settings_mutation)]
+/// pub struct JogSettingsState { ... }
+///
+/// // Equivalent to:
+/// // app.sync_component_builder::<JogSettingsState>()
+/// //     .with_handler::<NP, _, _>(handle_jog_settings_mutation)
+/// //     .targeted()
+/// //     .with_default_entity_policy()
+/// //     .build();
+/// JogSettingsState::register_sync::<NP>(app);
+/// ```
+#[cfg(feature = "runtime")]
+pub trait RegisterSyncComponent:
+    Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static + std::fmt::Debug + Clone
+{
+    /// Register this component type for synchronization.
+    fn register_sync<NP: NetworkProvider>(app: &mut App);
+}
+
 // =============================================================================
 // Query Invalidation API
 // =============================================================================
@@ -0,0 +1,432 @@
+//! Live queries: the server keeps a client's query registered and pushes row
+//! changes as its data changes.
+//!
+//! A [`LiveQuery`] is a query whose result is a list of keyed rows. Register
+//! how to compute it, and what makes it stale:
+//!
+//! ```rust,ignore
+//! fn list_programs(world: &mut World, _query: &ListPrograms) -> Vec<ProgramInfo> {
+//!     world
+//!         .query::<&Program>()
+//!         .iter(world)
+//!         .map(|program| ProgramInfo { id: program.id, name: program.name.clone() })
+//!         .collect()
+//! }
+//!
+//! app.live_query::<ListPrograms, WebSocketProvider, _>(list_programs)
+//!     .refresh_live_query_on_change::<ListPrograms, Program>();
+//! ```
+//!
+//! Clients subscribe with a [`QueryRequest`] in [`QueryMode::Subscribe`]
+//! (`use_live_query::<ListPrograms>()` in `pl3xus_client`). The first result
+//! is sent as a [`LiveQueryUpdate`] with every row added; whenever the query
+//! is refreshed it is evaluated again for every subscriber and only the rows
+//! that were added, updated or removed are sent. Results that didn't change
+//! send nothing. [`QueryMode::OneShot`] requests are answered once with a
+//! [`QueryResponse`], as are requests that fail.
+//!
+//! Besides `refresh_live_query_on_change`, any system can mark a query stale
+//! with `ResMut<LiveQueries<Q>>::refresh`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use pl3xus::{Network, NetworkData, NetworkEvent};
+use pl3xus_common::{ConnectionId, LiveQuery};
+
+use crate::messages::{
+    LiveQueryRow, LiveQueryUpdate, QueryMode, QueryResponse, QueryStatus, SyncClientMessage, SyncServerMessage,
+};
+use crate::systems::SyncSet;
+use crate::NetworkProvider;
+
+/// Computes the rows of a live query from the world.
+type Evaluator<Q> = Arc<dyn Fn(&mut World, &Q) -> Vec<<Q as LiveQuery>::Row> + Send + Sync>;
+
+/// One client's subscription to a live query.
+struct LiveSubscription<Q: LiveQuery> {
+    connection_id: ConnectionId,
+    query_id: u64,
+    query: Q,
+    /// The rows last sent to the client, by key.
+    rows: HashMap<String, Q::Row>,
+}
+
+/// Subscribers of live query `Q`.
+///
+/// Registered with [`AppLiveQueryExt::live_query`].
+#[derive(Resource)]
+pub struct LiveQueries<Q: LiveQuery> {
+    evaluator: Evaluator<Q>,
+    subscriptions: Vec<LiveSubscription<Q>>,
+    stale: bool,
+}
+
+impl<Q: LiveQuery> LiveQueries<Q> {
+    /// Evaluate every subscription again this frame and push what changed.
+    pub fn refresh(&mut self) {
+        self.stale = true;
+    }
+
+    /// Number of live subscriptions to `Q`.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscriptions.len()
+    }
+}
+
+/// Rows cached on the server for each connection's live query
+/// subscriptions, kept up to date as live queries run.
+///
+/// Subscriptions are dropped when their connection disconnects; [`evict`]
+/// drops them for a connection whose disconnect was missed.
+///
+/// [`evict`]: LiveQueryCacheSizes::evict
+#[derive(Resource, Default, Debug)]
+pub struct LiveQueryCacheSizes {
+    /// (query namespace, connection) -> rows cached for its subscriptions.
+    rows: HashMap<(String, ConnectionId), usize>,
+    /// Connections whose subscriptions are dropped on the next run.
+    evicted: HashSet<ConnectionId>,
+}
+
+impl LiveQueryCacheSizes {
+    /// Cached rows per connection, across every live query.
+    pub fn per_connection(&self) -> HashMap<ConnectionId, usize> {
+        let mut per_connection = HashMap::new();
+        for ((_, connection_id), rows) in &self.rows {
+            *per_connection.entry(*connection_id).or_default() += rows;
+        }
+        per_connection
+    }
+
+    /// Drop every live query subscription of `connection_id`.
+    pub fn evict(&mut self, connection_id: ConnectionId) {
+        self.evicted.insert(connection_id);
+    }
+
+    /// Record the subscriptions of live query `namespace` after a run.
+    fn update(&mut self, namespace: &str, subscriptions: impl Iterator<Item = (ConnectionId, usize)>) {
+        self.rows.retain(|(query, _), _| query != namespace);
+        for (connection_id, rows) in subscriptions {
+            *self.rows.entry((namespace.to_string(), connection_id)).or_default() += rows;
+        }
+        let rows = &self.rows;
+        self.evicted
+            .retain(|evicted| rows.keys().any(|(_, connection_id)| connection_id == evicted));
+    }
+}
+
+/// Namespaces of the registered live queries.
+#[derive(Resource, Default)]
+pub(crate) struct LiveQueryRegistry {
+    namespaces: HashSet<String>,
+}
+
+/// Extension trait for registering live queries.
+pub trait AppLiveQueryExt {
+    /// Serve `Q` as a live query, computing its rows with `evaluator`.
+    fn live_query<Q, NP, F>(&mut self, evaluator: F) -> &mut Self
+    where
+        Q: LiveQuery,
+        NP: NetworkProvider,
+        F: Fn(&mut World, &Q) -> Vec<Q::Row> + Send + Sync + 'static;
+
+    /// Refresh live query `Q` whenever a component `T` is added, changed or removed.
+    fn refresh_live_query_on_change<Q: LiveQuery, T: Component>(&mut self) -> &mut Self;
+}
+
+impl AppLiveQueryExt for App {
+    fn live_query<Q, NP, F>(&mut self, evaluator: F) -> &mut Self
+    where
+        Q: LiveQuery,
+        NP: NetworkProvider,
+        F: Fn(&mut World, &Q) -> Vec<Q::Row> + Send + Sync + 'static,
+    {
+        self.init_resource::<LiveQueryRegistry>();
+        self.init_resource::<LiveQueryCacheSizes>();
+        let newly_registered = self
+            .world_mut()
+            .resource_mut::<LiveQueryRegistry>()
+            .namespaces
+            .insert(Q::short_name().to_string());
+        if !newly_registered {
+            warn!("[pl3xus_sync] Live query {} registered twice, keeping the first", Q::short_name());
+            return self;
+        }
+
+        self.insert_resource(LiveQueries::<Q> {
+            evaluator: Arc::new(evaluator),
+            subscriptions: Vec::new(),
+            stale: false,
+        });
+        self.add_systems(Update, run_live_queries::<Q, NP>.in_set(SyncSet::Broadcast));
+        self
+    }
+
+    fn refresh_live_query_on_change<Q: LiveQuery, T: Component>(&mut self) -> &mut Self {
+        self.add_systems(
+            Update,
+            refresh_on_change::<Q, T>.in_set(SyncSet::DetectChanges),
+        );
+        self
+    }
+}
+
+/// Mark `Q` stale when any `T` was added, changed or removed.
+fn refresh_on_change<Q: LiveQuery, T: Component>(
+    changed: Query<(), Changed<T>>,
+    mut removed: RemovedComponents<T>,
+    live: Option<ResMut<LiveQueries<Q>>>,
+) {
+    let any_removed = removed.read().count() > 0;
+    if let Some(mut live) = live
+        && (any_removed || !changed.is_empty())
+    {
+        live.refresh();
+    }
+}
+
+/// Compare `current` with the rows last sent, returning the update and the new rows by key.
+fn diff_rows<Q: LiveQuery>(
+    query_id: u64,
+    previous: &HashMap<String, Q::Row>,
+    current: Vec<Q::Row>,
+) -> (LiveQueryUpdate, HashMap<String, Q::Row>) {
+    let mut update = LiveQueryUpdate {
+        query_id,
+        ..Default::default()
+    };
+    let mut rows = HashMap::with_capacity(current.len());
+
+    for row in current {
+        let key = Q::row_key(&row);
+        match previous.get(&key) {
+            None => update.added.push(encode_row(key.clone(), &row)),
+            Some(old) if *old != row => update.updated.push(encode_row(key.clone(), &row)),
+            Some(_) => {}
+        }
+        rows.insert(key, row);
+    }
+    update.removed = previous.keys().filter(|key| !rows.contains_key(*key)).cloned().collect();
+
+    (update, rows)
+}
+
+fn encode_row<R: serde::Serialize>(key: String, row: &R) -> LiveQueryRow {
+    LiveQueryRow {
+        key,
+        row: serde_json::to_string(row).unwrap_or_default(),
+    }
+}
+
+fn query_response(query_id: u64, status: QueryStatus, rows: Option<Vec<String>>, error: Option<String>) -> SyncServerMessage {
+    SyncServerMessage::QueryResponse(QueryResponse {
+        query_id,
+        status,
+        rows,
+        error,
+    })
+}
+
+/// Answer new queries for `Q`, drop cancelled and disconnected subscriptions,
+/// and push row changes if `Q` was refreshed.
+fn run_live_queries<Q: LiveQuery, NP: NetworkProvider>(
+    world: &mut World,
+    readers: &mut SystemState<(
+        MessageReader<NetworkData<SyncClientMessage>>,
+        MessageReader<NetworkEvent>,
+    )>,
+) {
+    let (requests, cancels, disconnected) = {
+        let (mut messages, mut events) = readers.get_mut(world);
+        let mut requests = Vec::new();
+        let mut cancels = Vec::new();
+        for msg in messages.read() {
+            match &**msg {
+                SyncClientMessage::Query(request) if request.namespace == Q::short_name() => {
+                    requests.push((*msg.source(), request.clone()));
+                }
+                SyncClientMessage::QueryCancel(cancel) => cancels.push((*msg.source(), cancel.query_id)),
+                _ => {}
+            }
+        }
+        let disconnected: Vec<ConnectionId> = events
+            .read()
+            .filter_map(|event| match event {
+                NetworkEvent::Disconnected(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        (requests, cancels, disconnected)
+    };
+
+    let evicted = world
+        .get_resource::<LiveQueryCacheSizes>()
+        .map(|sizes| sizes.evicted.clone())
+        .unwrap_or_default();
+    let mut outgoing = Vec::new();
+    world.resource_scope::<LiveQueries<Q>, _>(|world, mut live| {
+        live.subscriptions.retain(|sub| {
+            !disconnected.contains(&sub.connection_id)
+                && !evicted.contains(&sub.connection_id)
+                && !cancels.contains(&(sub.connection_id, sub.query_id))
+        });
+
+        let evaluator = live.evaluator.clone();
+
+        if live.stale {
+            live.stale = false;
+            for sub in live.subscriptions.iter_mut() {
+                let current = evaluator(world, &sub.query);
+                let (update, rows) = diff_rows::<Q>(sub.query_id, &sub.rows, current);
+                sub.rows = rows;
+                if !update.is_empty() {
+                    outgoing.push((sub.connection_id, SyncServerMessage::LiveQueryUpdate(update)));
+                }
+            }
+        }
+
+        for (source, request) in requests {
+            let query = match serde_json::from_str::<Q>(&request.params) {
+                Ok(query) => query,
+                Err(e) => {
+                    outgoing.push((
+                        source,
+                        query_response(request.query_id, QueryStatus::InvalidParams, None, Some(e.to_string())),
+                    ));
+                    continue;
+                }
+            };
+
+            let current = evaluator(world, &query);
+            match request.mode {
+                QueryMode::OneShot => {
+                    let encoded = current
+                        .iter()
+                        .map(|row| serde_json::to_string(row).unwrap_or_default())
+                        .collect();
+                    outgoing.push((source, query_response(request.query_id, QueryStatus::Ok, Some(encoded), None)));
+                }
+                QueryMode::Subscribe => {
+                    // Sent even when empty, so the client knows the result has loaded.
+                    let (update, rows) = diff_rows::<Q>(request.query_id, &HashMap::new(), current);
+                    outgoing.push((source, SyncServerMessage::LiveQueryUpdate(update)));
+
+                    live.subscriptions
+                        .retain(|sub| !(sub.connection_id == source && sub.query_id == request.query_id));
+                    live.subscriptions.push(LiveSubscription {
+                        connection_id: source,
+                        query_id: request.query_id,
+                        query,
+                        rows,
+                    });
+                }
+            }
+        }
+
+        if let Some(mut sizes) = world.get_resource_mut::<LiveQueryCacheSizes>() {
+            sizes.update(
+                Q::short_name(),
+                live.subscriptions.iter().map(|sub| (sub.connection_id, sub.rows.len())),
+            );
+        }
+    });
+
+    if let Some(net) = world.get_resource::<Network<NP>>() {
+        for (connection_id, message) in outgoing {
+            let _ = net.send(connection_id, message);
+        }
+    }
+}
+
+/// Answer queries for namespaces no live query is registered under, so
+/// clients don't wait forever.
+pub(crate) fn reject_unknown_queries<NP: NetworkProvider>(
+    mut reader: MessageReader<NetworkData<SyncClientMessage>>,
+    registry: Option<Res<LiveQueryRegistry>>,
+    net: Res<Network<NP>>,
+) {
+    for msg in reader.read() {
+        let SyncClientMessage::Query(request) = &**msg else {
+            continue;
+        };
+        if registry.as_ref().is_some_and(|r| r.namespaces.contains(&request.namespace)) {
+            continue;
+        }
+        let _ = net.send(
+            *msg.source(),
+            query_response(
+                request.query_id,
+                QueryStatus::NotFound,
+                None,
+                Some(format!("No live query named {}", request.namespace)),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    struct ListPrograms;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct ProgramInfo {
+        id: i64,
+        name: String,
+    }
+
+    impl LiveQuery for ListPrograms {
+        type Row = ProgramInfo;
+
+        fn row_key(row: &ProgramInfo) -> String {
+            row.id.to_string()
+        }
+    }
+
+    fn program(id: i64, name: &str) -> ProgramInfo {
+        ProgramInfo {
+            id,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_rows() {
+        let (update, rows) = diff_rows::<ListPrograms>(1, &HashMap::new(), vec![program(1, "a"), program(2, "b")]);
+        assert_eq!(update.added.len(), 2);
+        assert!(update.updated.is_empty() && update.removed.is_empty());
+
+        let (update, rows) = diff_rows::<ListPrograms>(1, &rows, vec![program(1, "a"), program(2, "b")]);
+        assert!(update.is_empty());
+
+        let (update, rows) = diff_rows::<ListPrograms>(1, &rows, vec![program(2, "c"), program(3, "d")]);
+        assert_eq!(update.added, vec![encode_row("3".to_string(), &program(3, "d"))]);
+        assert_eq!(update.updated, vec![encode_row("2".to_string(), &program(2, "c"))]);
+        assert_eq!(update.removed, vec!["1".to_string()]);
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[test]
+    fn test_cache_sizes_per_connection() {
+        let alice = ConnectionId { id: 1 };
+        let bob = ConnectionId { id: 2 };
+        let mut sizes = LiveQueryCacheSizes::default();
+        sizes.update("ListPrograms", [(alice, 3), (alice, 2), (bob, 1)].into_iter());
+        sizes.update("ListRobots", [(alice, 4)].into_iter());
+        assert_eq!(sizes.per_connection(), HashMap::from([(alice, 9), (bob, 1)]));
+
+        // An evicted connection stays evicted until every query dropped it
+        sizes.evict(alice);
+        sizes.update("ListPrograms", [(bob, 1)].into_iter());
+        assert!(sizes.evicted.contains(&alice));
+        sizes.update("ListRobots", std::iter::empty());
+        assert!(sizes.evicted.is_empty());
+        assert_eq!(sizes.per_connection(), HashMap::from([(bob, 1)]));
+    }
+}
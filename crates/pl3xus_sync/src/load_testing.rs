@@ -0,0 +1,35 @@
+//! Types shared by the `sync_load_generator` and `pl3xus_loadtest` binaries.
+//!
+//! These live in the library (rather than a module included by both binaries)
+//! because request type names, and therefore their wire hashes, include the
+//! crate path.
+
+use bevy::prelude::*;
+use pl3xus_common::RequestMessage;
+use serde::{Deserialize, Serialize};
+
+/// Synced component mutated by the generator (and by loadtest clients).
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LoadPose {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+    pub p: f32,
+    pub r: f32,
+}
+
+/// Round-trip request used to measure request latency.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LoadPing {
+    pub payload: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LoadPong {
+    pub payload_len: u32,
+}
+
+impl RequestMessage for LoadPing {
+    type ResponseMessage = LoadPong;
+}
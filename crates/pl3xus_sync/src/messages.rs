@@ -1,35 +1,6 @@
-#[cfg(feature = "runtime")]
-use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-/// Bevy-agnostic entity identifier used on the wire.
-///
-/// Internally this just uses Bevy's opaque `Entity` bits representation so that
-/// we don't rely on any particular layout (row/generation, etc.).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct SerializableEntity {
-    pub bits: u64,
-}
-
-impl SerializableEntity {
-    /// A dangling entity that can be used to signal "spawn a new entity" in mutations.
-    /// This uses the same bit pattern as Bevy's Entity::PLACEHOLDER.
-    pub const DANGLING: Self = Self { bits: u64::MAX };
-}
-
-#[cfg(feature = "runtime")]
-impl From<Entity> for SerializableEntity {
-    fn from(e: Entity) -> Self {
-        Self { bits: e.to_bits() }
-    }
-}
-
-#[cfg(feature = "runtime")]
-impl SerializableEntity {
-    pub fn to_entity(self) -> Entity {
-        Entity::from_bits(self.bits)
-    }
-}
+pub use pl3xus_common::SerializableEntity;
 
 /// Client -> server sync messages.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +15,10 @@ pub enum SyncClientMessage {
     Query(QueryRequest),
     /// Cancel an ongoing query-based subscription.
     QueryCancel(QueryCancel),
+    /// Revert the most recent mutation on an entity.
+    Undo(UndoMutation),
+    /// Reapply the most recently undone mutation on an entity.
+    Redo(RedoMutation),
 }
 
 /// Server -> client sync messages.
@@ -61,6 +36,12 @@ pub enum SyncServerMessage {
     /// Invalidate cached queries on the client.
     /// This enables server-pushed cache invalidation for real-time accuracy.
     QueryInvalidation(QueryInvalidation),
+    /// A state machine component changed state.
+    StateTransition(StateTransition),
+    /// Types were registered on the server after it started.
+    RegistryUpdated(RegistryUpdate),
+    /// Row changes of a live query since its last result.
+    LiveQueryUpdate(LiveQueryUpdate),
 }
 
 /// Invalidate one or more cached queries on the client.
@@ -88,11 +69,74 @@ pub struct QueryInvalidation {
     pub keys: Option<Vec<String>>,
 }
 
+/// A synced state machine component moved from one state to another.
+///
+/// Sent to clients subscribed to the component, in addition to the regular
+/// update carrying the new value, so UIs can react to the transition itself
+/// (e.g. show "Program completed" when going from Running to Completed).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateTransition {
+    pub entity: SerializableEntity,
+    pub component_type: String,
+    /// Bincode-encoded previous value.
+    pub from: Vec<u8>,
+    /// Bincode-encoded new value.
+    pub to: Vec<u8>,
+}
+
+/// Types registered on the server while it was running.
+///
+/// Existing subscriptions to the new component types start receiving data
+/// without being re-sent. Clients that keep a copy of the server schema
+/// should fetch it again with `DescribeSchema`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegistryUpdate {
+    /// Short names of the new component types.
+    pub components: Vec<String>,
+    /// Short names of the new message types.
+    pub messages: Vec<String>,
+    /// Names of the new request types.
+    pub requests: Vec<String>,
+}
+
+impl RegistryUpdate {
+    /// Returns true if nothing was registered.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty() && self.messages.is_empty() && self.requests.is_empty()
+    }
+}
+
+/// Version of the sync protocol, sent in the [`WelcomeMessage`].
+///
+/// Version 2 added the handshake's version and session, and marks
+/// uncontrolled entities with [`ConnectionId::NONE`](pl3xus_common::ConnectionId::NONE)
+/// instead of id 0. Version 3 dropped the `ClockSync` messages in favor of
+/// `ClockPing`/`ClockPong`. Version 4 made the holder of an `EntityControl` a
+/// [`ConnectionIdentity`](pl3xus_common::ConnectionIdentity).
+pub const SYNC_PROTOCOL_VERSION: u32 = 4;
+
 /// Welcome message sent to newly connected clients.
+///
+/// New fields are only ever appended, so older clients that stop reading
+/// after the fields they know still decode it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WelcomeMessage {
     /// The connection ID assigned to this client.
     pub connection_id: pl3xus_common::ConnectionId,
+    /// The server's [`SYNC_PROTOCOL_VERSION`].
+    pub protocol_version: u32,
+    /// Random id of the server run, see `SyncSession`.
+    pub session: u64,
+}
+
+impl WelcomeMessage {
+    /// The client's identity, unique across server restarts.
+    pub fn identity(&self) -> pl3xus_common::ConnectionIdentity {
+        pl3xus_common::ConnectionIdentity {
+            session: self.session,
+            connection: self.connection_id,
+        }
+    }
 }
 
 /// Subscribe to component data.
@@ -104,6 +148,9 @@ pub struct SubscriptionRequest {
     pub component_type: String,
     /// Optional specific entity to subscribe to.
     pub entity: Option<SerializableEntity>,
+    /// Optional predicate evaluated by the server; only matching entities
+    /// are sent.
+    pub filter: Option<crate::filter::SubscriptionFilter>,
 }
 
 /// Cancel an existing subscription.
@@ -116,6 +163,45 @@ pub struct UnsubscribeRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncBatch {
     pub items: Vec<SyncItem>,
+    /// Server wall-clock time when the batch was sent (ms since the Unix epoch).
+    ///
+    /// Only set when `SyncSettings::include_timestamps` is enabled on the server.
+    pub sent_at_ms: Option<f64>,
+    /// Sequence number of this batch for each subscription with items in it.
+    ///
+    /// Numbers increase by one per batch for each (connection, subscription)
+    /// and are never reused while the connection is open, so a client that
+    /// sees a jump knows it missed a batch. Clients subscribe once per
+    /// component type, which makes this a per-component-type sequence.
+    pub sequences: Vec<SubscriptionSequence>,
+}
+
+/// Sequence number of a [`SyncBatch`] for one subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionSequence {
+    pub subscription_id: u64,
+    pub sequence: u64,
+}
+
+#[cfg(feature = "runtime")]
+impl SyncBatch {
+    /// Create a batch, stamping it with the current time if `timestamp` is true.
+    pub fn new(items: Vec<SyncItem>, timestamp: bool) -> Self {
+        Self {
+            items,
+            sent_at_ms: timestamp.then(unix_time_ms),
+            sequences: Vec::new(),
+        }
+    }
+}
+
+/// Current wall-clock time in milliseconds since the Unix epoch.
+#[cfg(feature = "runtime")]
+pub fn unix_time_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or_default()
 }
 
 /// A single sync event.
@@ -153,6 +239,18 @@ pub enum SyncItem {
     },
 }
 
+impl SyncItem {
+    /// The subscription this item was produced for.
+    pub fn subscription_id(&self) -> u64 {
+        match self {
+            SyncItem::Snapshot { subscription_id, .. }
+            | SyncItem::Update { subscription_id, .. }
+            | SyncItem::ComponentRemoved { subscription_id, .. }
+            | SyncItem::EntityRemoved { subscription_id, .. } => *subscription_id,
+        }
+    }
+}
+
 /// Request to mutate a component value on the server.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MutateComponent {
@@ -164,6 +262,36 @@ pub struct MutateComponent {
     /// New value for the component (full value, no patch/diff in v1).
     /// Bincode-encoded component value.
     pub value: Vec<u8>,
+    /// Optional key identifying this logical mutation across retries.
+    ///
+    /// The server applies a mutation with a given key once per connection and
+    /// answers retries with the original response.
+    pub idempotency_key: Option<u64>,
+}
+
+/// Request to undo the most recent mutation recorded for an entity.
+///
+/// Answered with a [`MutationResponse`] carrying the same `request_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoMutation {
+    /// Optional correlation ID chosen by the client.
+    pub request_id: Option<u64>,
+    pub entity: SerializableEntity,
+    /// Optional key identifying this undo across retries, so a retry does not
+    /// undo a second mutation.
+    pub idempotency_key: Option<u64>,
+}
+
+/// Request to redo the most recently undone mutation for an entity.
+///
+/// Answered with a [`MutationResponse`] carrying the same `request_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedoMutation {
+    /// Optional correlation ID chosen by the client.
+    pub request_id: Option<u64>,
+    pub entity: SerializableEntity,
+    /// Optional key identifying this redo across retries.
+    pub idempotency_key: Option<u64>,
 }
 
 /// Response to a mutation request.
@@ -172,6 +300,25 @@ pub struct MutationResponse {
     pub request_id: Option<u64>,
     pub status: MutationStatus,
     pub message: Option<String>,
+    /// Per-field errors when the mutation failed validation.
+    pub field_errors: Vec<FieldError>,
+}
+
+/// A validation error for a single field of a mutated component.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldError {
+    /// Field name, as declared on the component struct.
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -226,3 +373,34 @@ pub struct QueryCancel {
     pub query_id: u64,
 }
 
+/// Row changes of a live query (a [`QueryRequest`] with [`QueryMode::Subscribe`]).
+///
+/// The first update carries the whole result in `added`; after that the server
+/// only sends what changed. Rows are JSON-encoded like [`QueryResponse::rows`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LiveQueryUpdate {
+    pub query_id: u64,
+    /// Rows that weren't in the previous result, in result order.
+    pub added: Vec<LiveQueryRow>,
+    /// Rows whose key was in the previous result but whose value changed.
+    pub updated: Vec<LiveQueryRow>,
+    /// Keys of rows that are no longer in the result.
+    pub removed: Vec<String>,
+}
+
+/// One row of a live query result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveQueryRow {
+    /// The row's key, from `LiveQuery::row_key`.
+    pub key: String,
+    /// The JSON-encoded row.
+    pub row: String,
+}
+
+impl LiveQueryUpdate {
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
@@ -0,0 +1,531 @@
+//! Targeted server notifications with delivery acknowledgement.
+//!
+//! Instead of calling `net.send(...)` / `net.broadcast(...)` with a
+//! [`ServerNotification`] directly, systems can write an
+//! [`OutgoingNotification`] to address a single connection, a set of
+//! connections, or every connection holding a role in [`ClientRoles`].
+//!
+//! Notifications built with [`ServerNotification::requiring_ack`] are kept in
+//! [`PendingNotifications`] until a client answers with a [`NotificationAck`].
+//! While pending they are delivered to every matching connection that has not
+//! seen them yet, so a client that reconnects (or is granted the role later)
+//! still receives the alert. Acknowledgements are surfaced to server code as
+//! [`NotificationAcknowledged`] messages.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::notifications::{ClientRoles, OutgoingNotification};
+//!
+//! fn grant_operator(mut roles: ResMut<ClientRoles>, conn: ConnectionId) {
+//!     roles.assign(conn, "operator");
+//! }
+//!
+//! fn alert_operators(mut notifications: MessageWriter<OutgoingNotification>) {
+//!     notifications.write(OutgoingNotification::to_role(
+//!         "operator",
+//!         ServerNotification::error("E-stop triggered").requiring_ack(),
+//!     ));
+//! }
+//! ```
+//!
+//! Connection-targeted notifications are dropped when that connection goes
+//! away, since connection ids are never reused.
+//!
+//! Clients can mute notification categories by sending
+//! [`NotificationPreferences`]; muted notifications are not sent to them at all.
+//! Notifications that require acknowledgement are never muted.
+//!
+//! The warnings pl3xus_sync sends itself (authorization rejections,
+//! subscription and rate limits) go through [`OutgoingNotification`] too, so
+//! clients can mute their categories. A [`ServerNotification`] sent with
+//! `net.send(...)` or `net.broadcast(...)` skips all of this: it isn't
+//! targeted by role, filtered by preferences, or kept until acknowledged.
+
+use bevy::ecs::message::Messages;
+use bevy::prelude::*;
+use pl3xus::managers::Network;
+use pl3xus::{NetworkData, NetworkEvent};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::NetworkProvider;
+
+pub use pl3xus_common::{ConnectionId, NotificationAck, NotificationPreferences, ServerNotification};
+
+/// Recipients of an [`OutgoingNotification`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationTarget {
+    /// Every connected client.
+    All,
+    /// A single connection.
+    Connection(ConnectionId),
+    /// A fixed set of connections.
+    Connections(Vec<ConnectionId>),
+    /// Every connection holding this role in [`ClientRoles`].
+    Role(String),
+}
+
+impl NotificationTarget {
+    /// Check whether `connection_id` is a recipient of this target.
+    pub fn matches(&self, connection_id: ConnectionId, roles: Option<&ClientRoles>) -> bool {
+        match self {
+            NotificationTarget::All => true,
+            NotificationTarget::Connection(id) => *id == connection_id,
+            NotificationTarget::Connections(ids) => ids.contains(&connection_id),
+            NotificationTarget::Role(role) => {
+                roles.is_some_and(|roles| roles.has_role(connection_id, role))
+            }
+        }
+    }
+}
+
+/// Resource mapping connections to application-defined roles.
+///
+/// Roles are removed automatically when a connection disconnects.
+#[derive(Resource, Default, Debug)]
+pub struct ClientRoles {
+    roles: HashMap<ConnectionId, HashSet<String>>,
+}
+
+impl ClientRoles {
+    /// Grant a role to a connection.
+    pub fn assign(&mut self, connection_id: ConnectionId, role: impl Into<String>) {
+        self.roles.entry(connection_id).or_default().insert(role.into());
+    }
+
+    /// Remove a role from a connection.
+    pub fn revoke(&mut self, connection_id: ConnectionId, role: &str) {
+        if let Some(roles) = self.roles.get_mut(&connection_id) {
+            roles.remove(role);
+        }
+    }
+
+    /// Check whether a connection holds a role.
+    pub fn has_role(&self, connection_id: ConnectionId, role: &str) -> bool {
+        self.roles
+            .get(&connection_id)
+            .is_some_and(|roles| roles.contains(role))
+    }
+
+    /// Iterate over the roles held by a connection.
+    pub fn roles_of(&self, connection_id: ConnectionId) -> impl Iterator<Item = &str> {
+        self.roles
+            .get(&connection_id)
+            .into_iter()
+            .flat_map(|roles| roles.iter().map(String::as_str))
+    }
+
+    /// Remove all roles for a connection.
+    pub fn remove_connection(&mut self, connection_id: ConnectionId) {
+        self.roles.remove(&connection_id);
+    }
+}
+
+/// Resource holding the notification preferences reported by each client.
+#[derive(Resource, Default, Debug)]
+pub struct ClientNotificationPreferences {
+    preferences: HashMap<ConnectionId, NotificationPreferences>,
+}
+
+impl ClientNotificationPreferences {
+    /// Get the preferences for a connection, if it sent any.
+    pub fn get(&self, connection_id: ConnectionId) -> Option<&NotificationPreferences> {
+        self.preferences.get(&connection_id)
+    }
+
+    /// Check whether a connection wants to receive a notification.
+    pub fn allows(&self, connection_id: ConnectionId, notification: &ServerNotification) -> bool {
+        self.get(connection_id)
+            .is_none_or(|preferences| preferences.allows(notification))
+    }
+}
+
+/// Message requesting that a notification be sent to a set of clients.
+#[derive(Message, Debug, Clone)]
+pub struct OutgoingNotification {
+    /// Who should receive the notification.
+    pub target: NotificationTarget,
+    /// The notification to send.
+    pub notification: ServerNotification,
+}
+
+impl OutgoingNotification {
+    /// Send to every connected client.
+    pub fn broadcast(notification: ServerNotification) -> Self {
+        Self {
+            target: NotificationTarget::All,
+            notification,
+        }
+    }
+
+    /// Send to a single connection.
+    pub fn to(connection_id: ConnectionId, notification: ServerNotification) -> Self {
+        Self {
+            target: NotificationTarget::Connection(connection_id),
+            notification,
+        }
+    }
+
+    /// Send to every connection holding `role`.
+    pub fn to_role(role: impl Into<String>, notification: ServerNotification) -> Self {
+        Self {
+            target: NotificationTarget::Role(role.into()),
+            notification,
+        }
+    }
+}
+
+/// Message emitted when a client acknowledges a notification.
+#[derive(Message, Debug, Clone)]
+pub struct NotificationAcknowledged {
+    /// Sequence number of the acknowledged notification.
+    pub sequence: u64,
+    /// The client that acknowledged it.
+    pub connection_id: ConnectionId,
+}
+
+#[derive(Debug)]
+struct PendingNotification {
+    target: NotificationTarget,
+    notification: ServerNotification,
+    delivered_to: HashSet<ConnectionId>,
+}
+
+/// Notifications that require acknowledgement and have not been acknowledged yet.
+#[derive(Resource, Debug)]
+pub struct PendingNotifications {
+    entries: VecDeque<PendingNotification>,
+    /// Maximum number of pending notifications; the oldest are dropped first.
+    pub max_pending: usize,
+}
+
+impl Default for PendingNotifications {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            max_pending: 256,
+        }
+    }
+}
+
+impl PendingNotifications {
+    /// Number of unacknowledged notifications.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether every notification has been acknowledged.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the notification with this sequence number is still pending.
+    pub fn is_pending(&self, sequence: u64) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.notification.sequence == sequence)
+    }
+
+    /// Iterate over the pending notifications, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &ServerNotification> {
+        self.entries.iter().map(|entry| &entry.notification)
+    }
+
+    /// Drop up to `count` of the oldest pending notifications, returning how
+    /// many were dropped.
+    pub fn evict_oldest(&mut self, count: usize) -> usize {
+        let count = count.min(self.entries.len());
+        self.entries.drain(..count);
+        count
+    }
+
+    /// Drop up to `count` of the newest pending notifications, returning how
+    /// many were dropped.
+    pub fn evict_newest(&mut self, count: usize) -> usize {
+        let count = count.min(self.entries.len());
+        self.entries.truncate(self.entries.len() - count);
+        count
+    }
+
+    /// Drop every pending notification.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn push(&mut self, entry: PendingNotification) {
+        while self.max_pending > 0 && self.entries.len() >= self.max_pending {
+            if let Some(dropped) = self.entries.pop_front() {
+                warn!(
+                    "[pl3xus_sync] Dropping unacknowledged notification {} (pending queue full)",
+                    dropped.notification.sequence
+                );
+            }
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+/// Send [`OutgoingNotification`]s to their recipients and track the ones
+/// that require acknowledgement.
+pub(crate) fn dispatch_notifications<NP: NetworkProvider>(
+    mut outgoing: MessageReader<OutgoingNotification>,
+    roles: Option<Res<ClientRoles>>,
+    preferences: Res<ClientNotificationPreferences>,
+    mut pending: ResMut<PendingNotifications>,
+    net: Option<Res<Network<NP>>>,
+) {
+    let Some(net) = net else {
+        return;
+    };
+    if outgoing.is_empty() {
+        return;
+    }
+
+    let connections = net.connection_ids();
+    for request in outgoing.read() {
+        let mut delivered_to = HashSet::new();
+        for &connection_id in &connections {
+            if !request.target.matches(connection_id, roles.as_deref())
+                || !preferences.allows(connection_id, &request.notification)
+            {
+                continue;
+            }
+            match net.send(connection_id, request.notification.clone()) {
+                Ok(()) => {
+                    delivered_to.insert(connection_id);
+                }
+                Err(e) => warn!(
+                    "[pl3xus_sync] Failed to send notification {} to {:?}: {:?}",
+                    request.notification.sequence, connection_id, e
+                ),
+            }
+        }
+
+        if request.notification.requires_ack {
+            pending.push(PendingNotification {
+                target: request.target.clone(),
+                notification: request.notification.clone(),
+                delivered_to,
+            });
+        }
+    }
+}
+
+/// Send `notification` to one connection as an [`OutgoingNotification`], or
+/// directly if nothing dispatches them because the sync plugin isn't added.
+pub(crate) fn notify_connection<NP: NetworkProvider>(
+    world: &mut World,
+    connection_id: ConnectionId,
+    notification: ServerNotification,
+) {
+    if let Some(mut outgoing) = world.get_resource_mut::<Messages<OutgoingNotification>>() {
+        outgoing.write(OutgoingNotification::to(connection_id, notification));
+    } else if let Some(net) = world.get_resource::<Network<NP>>()
+        && let Err(e) = net.send(connection_id, notification)
+    {
+        warn!("[pl3xus_sync] Failed to send notification to {:?}: {:?}", connection_id, e);
+    }
+}
+
+/// Store notification preferences sent by clients.
+pub(crate) fn handle_notification_preferences(
+    mut reader: MessageReader<NetworkData<NotificationPreferences>>,
+    mut preferences: ResMut<ClientNotificationPreferences>,
+) {
+    for msg in reader.read() {
+        debug!(
+            "[pl3xus_sync] Notification preferences from {:?}: muted={:?}",
+            msg.source(),
+            msg.muted_categories
+        );
+        preferences
+            .preferences
+            .insert(*msg.source(), (**msg).clone());
+    }
+}
+
+/// Remove acknowledged notifications from the pending queue.
+pub(crate) fn handle_notification_acks(
+    mut acks: MessageReader<NetworkData<NotificationAck>>,
+    roles: Option<Res<ClientRoles>>,
+    mut pending: ResMut<PendingNotifications>,
+    mut acknowledged: MessageWriter<NotificationAcknowledged>,
+) {
+    for ack in acks.read() {
+        let source = *ack.source();
+        let position = pending.entries.iter().position(|entry| {
+            entry.notification.sequence == ack.sequence
+                && (entry.delivered_to.contains(&source)
+                    || entry.target.matches(source, roles.as_deref()))
+        });
+        let Some(position) = position else {
+            debug!(
+                "[pl3xus_sync] Ignoring ack for unknown notification {} from {:?}",
+                ack.sequence, source
+            );
+            continue;
+        };
+
+        pending.entries.remove(position);
+        acknowledged.write(NotificationAcknowledged {
+            sequence: ack.sequence,
+            connection_id: source,
+        });
+    }
+}
+
+/// Deliver pending notifications to connections that have not received them,
+/// e.g. after a reconnect or a role change.
+pub(crate) fn redeliver_pending_notifications<NP: NetworkProvider>(
+    mut events: MessageReader<NetworkEvent>,
+    roles: Option<ResMut<ClientRoles>>,
+    mut preferences: ResMut<ClientNotificationPreferences>,
+    mut pending: ResMut<PendingNotifications>,
+    net: Option<Res<Network<NP>>>,
+) {
+    let mut roles = roles;
+    let roles_changed = roles.as_ref().is_some_and(|roles| roles.is_changed());
+    let mut connected = false;
+
+    for event in events.read() {
+        match event {
+            NetworkEvent::Connected(..) => connected = true,
+            NetworkEvent::Disconnected(connection_id) => {
+                if let Some(roles) = roles.as_mut() {
+                    roles.remove_connection(*connection_id);
+                }
+                preferences.preferences.remove(connection_id);
+                pending.entries.retain(|entry| {
+                    entry.target != NotificationTarget::Connection(*connection_id)
+                });
+                for entry in pending.entries.iter_mut() {
+                    entry.delivered_to.remove(connection_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !(connected || roles_changed) || pending.is_empty() {
+        return;
+    }
+    let Some(net) = net else {
+        return;
+    };
+
+    let connections = net.connection_ids();
+    for entry in pending.entries.iter_mut() {
+        for &connection_id in &connections {
+            if entry.delivered_to.contains(&connection_id)
+                || !entry.target.matches(connection_id, roles.as_deref())
+            {
+                continue;
+            }
+            if net.send(connection_id, entry.notification.clone()).is_ok() {
+                debug!(
+                    "[pl3xus_sync] Redelivered notification {} to {:?}",
+                    entry.notification.sequence, connection_id
+                );
+                entry.delivered_to.insert(connection_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_websockets::WebSocketProvider;
+
+    const OPERATOR: ConnectionId = ConnectionId { id: 1 };
+    const VIEWER: ConnectionId = ConnectionId { id: 2 };
+
+    #[test]
+    fn test_targets_and_preferences() {
+        let mut roles = ClientRoles::default();
+        roles.assign(OPERATOR, "operator");
+        let to_operators = NotificationTarget::Role("operator".into());
+        assert!(to_operators.matches(OPERATOR, Some(&roles)));
+        assert!(!to_operators.matches(VIEWER, Some(&roles)));
+        assert!(!to_operators.matches(OPERATOR, None));
+        assert!(NotificationTarget::Connections(vec![VIEWER]).matches(VIEWER, None));
+
+        let mut preferences = ClientNotificationPreferences::default();
+        preferences
+            .preferences
+            .insert(VIEWER, NotificationPreferences::muting(["rate_limit"]));
+        let throttled = ServerNotification::warning("slow down").with_category("rate_limit");
+        assert!(preferences.allows(OPERATOR, &throttled));
+        assert!(!preferences.allows(VIEWER, &throttled));
+        // Notifications that need an ack are never muted
+        assert!(preferences.allows(VIEWER, &throttled.requiring_ack()));
+    }
+
+    #[test]
+    fn test_builtin_warnings_go_through_outgoing_notifications() {
+        let mut world = World::new();
+        world.init_resource::<Messages<OutgoingNotification>>();
+
+        let notification = ServerNotification::warning("slow down").with_category("rate_limit");
+        notify_connection::<WebSocketProvider>(&mut world, VIEWER, notification.clone());
+
+        let outgoing = world.resource::<Messages<OutgoingNotification>>();
+        let sent: Vec<_> = outgoing.iter_current_update_messages().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].target, NotificationTarget::Connection(VIEWER));
+        assert_eq!(sent[0].notification.sequence, notification.sequence);
+    }
+
+    #[test]
+    fn test_ack_clears_pending_notification() {
+        let mut app = App::new();
+        app.init_resource::<PendingNotifications>()
+            .add_message::<NetworkData<NotificationAck>>()
+            .add_message::<NotificationAcknowledged>()
+            .add_systems(Update, handle_notification_acks);
+
+        let estop = ServerNotification::error("E-stop triggered").requiring_ack();
+        let sequence = estop.sequence;
+        app.world_mut().resource_mut::<PendingNotifications>().push(PendingNotification {
+            target: NotificationTarget::Connection(OPERATOR),
+            notification: estop,
+            delivered_to: HashSet::from([OPERATOR]),
+        });
+
+        // A connection that was never sent it can't acknowledge it
+        app.world_mut()
+            .write_message(NetworkData::new(&VIEWER, NotificationAck { sequence }));
+        app.update();
+        assert!(app.world().resource::<PendingNotifications>().is_pending(sequence));
+
+        app.world_mut()
+            .write_message(NetworkData::new(&OPERATOR, NotificationAck { sequence }));
+        app.update();
+        assert!(app.world().resource::<PendingNotifications>().is_empty());
+        let acknowledged = app.world().resource::<Messages<NotificationAcknowledged>>();
+        assert!(acknowledged
+            .iter_current_update_messages()
+            .any(|ack| ack.sequence == sequence && ack.connection_id == OPERATOR));
+    }
+
+    #[test]
+    fn test_pending_queue_drops_oldest_when_full() {
+        let mut pending = PendingNotifications {
+            max_pending: 2,
+            ..Default::default()
+        };
+        let notifications: Vec<_> = (0..3)
+            .map(|i| ServerNotification::warning(format!("alert {i}")).requiring_ack())
+            .collect();
+        for notification in &notifications {
+            pending.push(PendingNotification {
+                target: NotificationTarget::All,
+                notification: notification.clone(),
+                delivered_to: HashSet::new(),
+            });
+        }
+        assert_eq!(pending.len(), 2);
+        assert!(!pending.is_pending(notifications[0].sequence));
+        assert!(pending.is_pending(notifications[2].sequence));
+    }
+}
@@ -0,0 +1,305 @@
+//! Named phases for ordering application plugins within `Update`.
+//!
+//! Each frame, application plugins run in the [`PluginSchedule`] phases,
+//! in order, after client mutations were applied and before changes are
+//! detected for sync (see [`SyncSet`]). A plugin puts its systems in its own
+//! [`PluginSet`] for a phase, and can declare that it runs after another
+//! plugin's set:
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::plugin_schedule::{AppPluginScheduleExt, PluginSchedule, PluginSet};
+//!
+//! const KINEMATICS: PluginSet = PluginSet::new("kinematics", PluginSchedule::MainUpdate);
+//! const MOTION: PluginSet = PluginSet::new("motion", PluginSchedule::MainUpdate);
+//!
+//! app.configure_plugin_schedule()
+//!     .plugin_set_after(MOTION, KINEMATICS)
+//!     .add_systems(Update, (solve_kinematics.in_set(KINEMATICS), plan_motion.in_set(MOTION)));
+//! ```
+//!
+//! Ordering cycles are detected when declared, naming every set involved,
+//! instead of surfacing as a schedule build error. The resolved order is
+//! available from [`PluginOrdering`] and logged at debug level on startup.
+//!
+//! [`SyncSet`]: crate::SyncSet
+
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::systems::SyncSet;
+
+/// Phases of application plugin systems, chained in this order.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PluginSchedule {
+    /// Load/spawn entities and components from database or external sources.
+    Load,
+    /// Clean up entities marked for despawn.
+    Despawn,
+    /// Handle new client connections and disconnections.
+    ClientConnections,
+    /// Authorization and permission checks.
+    Authorization,
+    /// Process incoming client requests and commands.
+    ClientRequests,
+    /// Manage network connections to external devices (robots, PLCs, etc).
+    NetworkConnections,
+    /// Core business logic and state updates.
+    MainUpdate,
+    /// Send notifications and events to clients.
+    Notify,
+    /// Persist changes to database or external storage.
+    Save,
+}
+
+/// One plugin's systems within a phase.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PluginSet {
+    pub plugin: &'static str,
+    pub phase: PluginSchedule,
+}
+
+impl PluginSet {
+    pub const fn new(plugin: &'static str, phase: PluginSchedule) -> Self {
+        Self { plugin, phase }
+    }
+}
+
+impl fmt::Display for PluginSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{:?}", self.plugin, self.phase)
+    }
+}
+
+/// Declared plugin sets and the dependencies between them.
+#[derive(Resource, Default, Debug)]
+pub struct PluginOrdering {
+    sets: Vec<PluginSet>,
+    /// `(set, runs_after)` pairs.
+    after: Vec<(PluginSet, PluginSet)>,
+}
+
+impl PluginOrdering {
+    /// Declare `set`. Returns false if it already was.
+    pub fn declare(&mut self, set: PluginSet) -> bool {
+        if self.sets.contains(&set) {
+            return false;
+        }
+        self.sets.push(set);
+        true
+    }
+
+    /// Make `set` run after `other`, failing with the cycle this would create.
+    pub fn add_after(&mut self, set: PluginSet, other: PluginSet) -> Result<(), String> {
+        if other.phase > set.phase {
+            return Err(format!(
+                "{} can't run after {}, which is in a later phase",
+                set, other
+            ));
+        }
+        self.declare(set);
+        self.declare(other);
+        self.after.push((set, other));
+        if let Err(cycle) = self.resolved() {
+            self.after.pop();
+            let path: Vec<String> = cycle.iter().map(ToString::to_string).collect();
+            return Err(format!("Plugin ordering cycle: {}", path.join(" -> ")));
+        }
+        Ok(())
+    }
+
+    /// Sets in the order they run, or a cycle where each set runs after the
+    /// previous one and the last after the first.
+    pub fn resolved(&self) -> Result<Vec<PluginSet>, Vec<PluginSet>> {
+        let mut remaining = self.sets.clone();
+        let mut order = Vec::with_capacity(remaining.len());
+        let waiting = |set: &PluginSet, remaining: &[PluginSet]| {
+            self.after
+                .iter()
+                .any(|(later, earlier)| later == set && remaining.contains(earlier))
+        };
+
+        while !remaining.is_empty() {
+            // Earliest phase first, then declaration order
+            let next = remaining
+                .iter()
+                .enumerate()
+                .filter(|(_, set)| !waiting(set, &remaining))
+                .min_by_key(|(index, set)| (set.phase, *index))
+                .map(|(index, _)| index);
+            match next {
+                Some(index) => order.push(remaining.remove(index)),
+                None => return Err(self.find_cycle(&remaining)),
+            }
+        }
+        Ok(order)
+    }
+
+    /// Walk back through dependencies among `remaining`, which all wait on
+    /// another one, until a set repeats.
+    fn find_cycle(&self, remaining: &[PluginSet]) -> Vec<PluginSet> {
+        let mut path = vec![remaining[0]];
+        loop {
+            let current = path[path.len() - 1];
+            let Some(&(_, earlier)) = self
+                .after
+                .iter()
+                .find(|(later, earlier)| *later == current && remaining.contains(earlier))
+            else {
+                return path;
+            };
+            if let Some(start) = path.iter().position(|set| *set == earlier) {
+                let mut cycle = path.split_off(start);
+                cycle.reverse();
+                cycle.push(cycle[0]);
+                return cycle;
+            }
+            path.push(earlier);
+        }
+    }
+}
+
+impl fmt::Display for PluginOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let order = match self.resolved() {
+            Ok(order) => order,
+            Err(cycle) => {
+                let path: Vec<String> = cycle.iter().map(ToString::to_string).collect();
+                return write!(f, "cycle: {}", path.join(" -> "));
+            }
+        };
+        let mut phase = None;
+        for (position, set) in order.iter().enumerate() {
+            if phase != Some(set.phase) {
+                phase = Some(set.phase);
+                writeln!(f, "{:?}:", set.phase)?;
+            }
+            write!(f, "  {}. {}", position + 1, set.plugin)?;
+            let after: Vec<&str> = self
+                .after
+                .iter()
+                .filter(|(later, _)| later == set)
+                .map(|(_, earlier)| earlier.plugin)
+                .collect();
+            if !after.is_empty() {
+                write!(f, " (after {})", after.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extension trait for the plugin schedule.
+pub trait AppPluginScheduleExt {
+    /// Chain the [`PluginSchedule`] phases in `Update`, between applying
+    /// client mutations and detecting changes for sync. Safe to call from
+    /// every plugin.
+    fn configure_plugin_schedule(&mut self) -> &mut Self;
+
+    /// Declare `set` in its phase.
+    fn plugin_set(&mut self, set: PluginSet) -> &mut Self;
+
+    /// Run `set` after `other`.
+    ///
+    /// # Panics
+    ///
+    /// If this creates an ordering cycle, or `other` is in a later phase.
+    fn plugin_set_after(&mut self, set: PluginSet, other: PluginSet) -> &mut Self;
+}
+
+impl AppPluginScheduleExt for App {
+    fn configure_plugin_schedule(&mut self) -> &mut Self {
+        if self.world().contains_resource::<PluginOrdering>() {
+            return self;
+        }
+        self.init_resource::<PluginOrdering>();
+        self.configure_sets(
+            Update,
+            (
+                PluginSchedule::Load,
+                PluginSchedule::Despawn,
+                PluginSchedule::ClientConnections,
+                PluginSchedule::Authorization,
+                PluginSchedule::ClientRequests,
+                PluginSchedule::NetworkConnections,
+                PluginSchedule::MainUpdate,
+                PluginSchedule::Notify,
+                PluginSchedule::Save,
+            )
+                .chain()
+                .after(SyncSet::ApplyMutations)
+                .before(SyncSet::DetectChanges),
+        );
+        self.add_systems(Startup, log_plugin_ordering)
+    }
+
+    fn plugin_set(&mut self, set: PluginSet) -> &mut Self {
+        self.configure_plugin_schedule();
+        if self.world_mut().resource_mut::<PluginOrdering>().declare(set) {
+            self.configure_sets(Update, set.in_set(set.phase));
+        }
+        self
+    }
+
+    fn plugin_set_after(&mut self, set: PluginSet, other: PluginSet) -> &mut Self {
+        self.plugin_set(set).plugin_set(other);
+        if let Err(e) = self.world_mut().resource_mut::<PluginOrdering>().add_after(set, other) {
+            panic!("[pl3xus_sync] {}", e);
+        }
+        self.configure_sets(Update, set.after(other))
+    }
+}
+
+fn log_plugin_ordering(ordering: Res<PluginOrdering>) {
+    debug!("[pl3xus_sync] Plugin schedule:\n{}", *ordering);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATABASE: PluginSet = PluginSet::new("database", PluginSchedule::Load);
+    const KINEMATICS: PluginSet = PluginSet::new("kinematics", PluginSchedule::MainUpdate);
+    const MOTION: PluginSet = PluginSet::new("motion", PluginSchedule::MainUpdate);
+    const SAFETY: PluginSet = PluginSet::new("safety", PluginSchedule::MainUpdate);
+
+    #[test]
+    fn test_resolves_and_detects_cycles() {
+        let mut ordering = PluginOrdering::default();
+        ordering.declare(MOTION);
+        ordering.add_after(MOTION, KINEMATICS).unwrap();
+        ordering.add_after(KINEMATICS, SAFETY).unwrap();
+        ordering.declare(DATABASE);
+        assert_eq!(ordering.resolved(), Ok(vec![DATABASE, SAFETY, KINEMATICS, MOTION]));
+
+        let error = ordering.add_after(SAFETY, MOTION).unwrap_err();
+        assert_eq!(
+            error,
+            "Plugin ordering cycle: safety::MainUpdate -> kinematics::MainUpdate -> motion::MainUpdate -> safety::MainUpdate"
+        );
+        assert!(ordering.add_after(DATABASE, MOTION).is_err());
+        // Failed declarations leave the ordering as it was
+        assert!(ordering.resolved().is_ok());
+        assert!(ordering.to_string().contains("3. kinematics (after safety)"));
+    }
+
+    #[test]
+    fn test_systems_follow_declared_order() {
+        #[derive(Resource, Default)]
+        struct Ran(Vec<&'static str>);
+
+        let mut app = App::new();
+        app.init_resource::<Ran>()
+            .plugin_set_after(MOTION, KINEMATICS)
+            .add_systems(
+                Update,
+                (
+                    (|mut ran: ResMut<Ran>| ran.0.push("motion")).in_set(MOTION),
+                    (|mut ran: ResMut<Ran>| ran.0.push("kinematics")).in_set(KINEMATICS),
+                ),
+            );
+        app.update();
+        assert_eq!(app.world().resource::<Ran>().0, vec!["kinematics", "motion"]);
+    }
+}
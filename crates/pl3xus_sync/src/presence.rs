@@ -0,0 +1,306 @@
+//! Optional client presence tracking.
+//!
+//! `ClientPresencePlugin` keeps one entity with a [`ClientPresence`] component
+//! per connected client and syncs it read-only, so every client can subscribe
+//! to the list of connected sessions and see who holds control.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::presence::ClientPresencePlugin;
+//!
+//! app.add_plugins(Pl3xusSyncPlugin::<WebSocketProvider>::default())
+//!     .add_plugins(ClientPresencePlugin::<WebSocketProvider>::default());
+//! ```
+//!
+//! Clients can report a display name by sending [`SetClientIdentity`]. On the
+//! client side, `use_presence()` in `pl3xus_client` returns the current list.
+
+use bevy::prelude::*;
+use pl3xus::{NetworkData, NetworkEvent};
+use std::collections::HashMap;
+
+use crate::registry::{ComponentSyncConfig, SubscriptionManager};
+use crate::systems::SyncSet;
+use crate::{AppPl3xusSyncExt, NetworkProvider};
+
+pub use pl3xus_common::{ClientPresence, ConnectionId, EntityControl, SetClientIdentity};
+
+/// Maximum length (in characters) of an identity reported by a client.
+const MAX_IDENTITY_LEN: usize = 128;
+
+/// Resource mapping each connected client to its presence entity.
+#[derive(Resource, Default, Debug)]
+pub struct PresenceEntities {
+    entities: HashMap<ConnectionId, Entity>,
+}
+
+impl PresenceEntities {
+    /// Get the presence entity for a connection.
+    pub fn get(&self, connection_id: ConnectionId) -> Option<Entity> {
+        self.entities.get(&connection_id).copied()
+    }
+
+    /// Number of tracked connections.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether no connections are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// Plugin that publishes a [`ClientPresence`] entity for every connected client.
+pub struct ClientPresencePlugin<NP: NetworkProvider> {
+    _marker: std::marker::PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for ClientPresencePlugin<NP> {
+    fn default() -> Self {
+        Self {
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for ClientPresencePlugin<NP> {
+    fn build(&self, app: &mut App) {
+        use pl3xus::AppNetworkMessage;
+
+        app.init_resource::<PresenceEntities>();
+
+        app.add_message::<SetClientIdentity>();
+        app.register_network_message::<SetClientIdentity, NP>();
+
+        app.sync_component::<ClientPresence>(Some(ComponentSyncConfig::read_only_with_message(
+            "ClientPresence is managed by the server",
+        )));
+
+        app.add_systems(
+            Update,
+            (
+                track_presence_connections,
+                handle_identity_updates,
+                refresh_presence,
+            )
+                .chain()
+                .before(SyncSet::DetectChanges),
+        );
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Spawn a presence entity when a client connects and despawn it on disconnect.
+fn track_presence_connections(
+    mut events: MessageReader<NetworkEvent>,
+    mut presence: ResMut<PresenceEntities>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        match event {
+            NetworkEvent::Connected(connection_id, _) => {
+                if connection_id.is_server() || presence.entities.contains_key(connection_id) {
+                    continue;
+                }
+                let entity = commands
+                    .spawn(ClientPresence {
+                        connection_id: *connection_id,
+                        connected_at_ms: now_ms(),
+                        ..Default::default()
+                    })
+                    .id();
+                presence.entities.insert(*connection_id, entity);
+                debug!("[pl3xus_sync] Presence added for {:?}", connection_id);
+            }
+            NetworkEvent::Disconnected(connection_id) => {
+                if let Some(entity) = presence.entities.remove(connection_id) {
+                    commands.entity(entity).despawn();
+                    debug!("[pl3xus_sync] Presence removed for {:?}", connection_id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Apply identities reported by clients.
+fn handle_identity_updates(
+    mut reader: MessageReader<NetworkData<SetClientIdentity>>,
+    presence: Res<PresenceEntities>,
+    mut presences: Query<&mut ClientPresence>,
+) {
+    for msg in reader.read() {
+        let Some(entity) = presence.get(*msg.source()) else {
+            continue;
+        };
+        let Ok(mut client) = presences.get_mut(entity) else {
+            continue;
+        };
+        let identity: String = msg.identity.trim().chars().take(MAX_IDENTITY_LEN).collect();
+        client.identity = (!identity.is_empty()).then_some(identity);
+    }
+}
+
+/// Keep subscription counts and control holdings up to date.
+///
+/// Only writes when a value actually changed, so presence entities don't
+/// generate sync traffic every frame.
+fn refresh_presence(
+    subscriptions: Option<Res<SubscriptionManager>>,
+    controls: Query<(Entity, &EntityControl)>,
+    mut presences: Query<&mut ClientPresence>,
+) {
+    let mut subscription_counts: HashMap<ConnectionId, u32> = HashMap::new();
+    if let Some(subscriptions) = subscriptions.as_ref() {
+        for sub in &subscriptions.subscriptions {
+            *subscription_counts.entry(sub.connection_id).or_default() += 1;
+        }
+    }
+
+    let mut controlled: HashMap<ConnectionId, Vec<u64>> = HashMap::new();
+    for (entity, control) in controls.iter() {
+        if control.is_controlled() {
+            controlled
+                .entry(control.client.connection)
+                .or_default()
+                .push(entity.to_bits());
+        }
+    }
+
+    for mut client in presences.iter_mut() {
+        let subscription_count = subscription_counts
+            .get(&client.connection_id)
+            .copied()
+            .unwrap_or_default();
+        let mut controlled_entities = controlled
+            .remove(&client.connection_id)
+            .unwrap_or_default();
+        controlled_entities.sort_unstable();
+
+        if client.subscription_count != subscription_count {
+            client.subscription_count = subscription_count;
+        }
+        if client.controlled_entities != controlled_entities {
+            client.controlled_entities = controlled_entities;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::SubscriptionEntry;
+    use crate::SyncSession;
+    use pl3xus::ConnectionInfo;
+
+    const ALICE: ConnectionId = ConnectionId { id: 1 };
+    const BOB: ConnectionId = ConnectionId { id: 2 };
+
+    fn presence_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<PresenceEntities>()
+            .add_message::<NetworkEvent>()
+            .add_message::<NetworkData<SetClientIdentity>>()
+            .add_systems(
+                Update,
+                (track_presence_connections, handle_identity_updates, refresh_presence).chain(),
+            );
+        app
+    }
+
+    fn presence_of(app: &App, connection_id: ConnectionId) -> Option<ClientPresence> {
+        let entity = app.world().resource::<PresenceEntities>().get(connection_id)?;
+        app.world().get::<ClientPresence>(entity).cloned()
+    }
+
+    #[test]
+    fn test_presence_follows_connections() {
+        let mut app = presence_app();
+        for connection_id in [ALICE, BOB, ConnectionId::SERVER] {
+            app.world_mut()
+                .write_message(NetworkEvent::Connected(connection_id, ConnectionInfo::default()));
+        }
+        app.update();
+        // The server's own connection isn't a client
+        assert_eq!(app.world().resource::<PresenceEntities>().len(), 2);
+        assert_eq!(presence_of(&app, BOB).unwrap().connection_id, BOB);
+
+        let bob = app.world().resource::<PresenceEntities>().get(BOB).unwrap();
+        app.world_mut().write_message(NetworkEvent::Disconnected(BOB));
+        app.update();
+        assert!(presence_of(&app, BOB).is_none());
+        assert!(app.world().get_entity(bob).is_err());
+        assert_eq!(app.world().resource::<PresenceEntities>().len(), 1);
+    }
+
+    #[test]
+    fn test_identity_is_trimmed_and_bounded() {
+        let mut app = presence_app();
+        app.world_mut()
+            .write_message(NetworkEvent::Connected(ALICE, ConnectionInfo::default()));
+        app.update();
+
+        let set_identity = |app: &mut App, identity: String| {
+            app.world_mut()
+                .write_message(NetworkData::new(&ALICE, SetClientIdentity { identity }));
+            app.update();
+            presence_of(app, ALICE).unwrap().identity
+        };
+        assert_eq!(set_identity(&mut app, "  alice ".into()), Some("alice".to_string()));
+        assert_eq!(set_identity(&mut app, "x".repeat(500)).unwrap().len(), MAX_IDENTITY_LEN);
+        assert_eq!(set_identity(&mut app, "   ".into()), None);
+
+        // Identities from unknown connections are ignored
+        app.world_mut().write_message(NetworkData::new(
+            &BOB,
+            SetClientIdentity { identity: "bob".into() },
+        ));
+        app.update();
+        assert!(presence_of(&app, BOB).is_none());
+    }
+
+    #[test]
+    fn test_presence_counts_subscriptions_and_control() {
+        let mut app = presence_app();
+        app.world_mut()
+            .write_message(NetworkEvent::Connected(ALICE, ConnectionInfo::default()));
+        let mut subscriptions = SubscriptionManager::default();
+        for subscription_id in [1, 2] {
+            subscriptions.add_subscription(SubscriptionEntry {
+                connection_id: ALICE,
+                subscription_id,
+                component_type: "RobotStatus".into(),
+                entity: None,
+                filter: None,
+                matched: Default::default(),
+                hidden: Default::default(),
+            });
+        }
+        app.insert_resource(subscriptions);
+        let robot = app
+            .world_mut()
+            .spawn(EntityControl {
+                client: SyncSession(1).identity(ALICE),
+                ..Default::default()
+            })
+            .id();
+        app.world_mut().spawn(EntityControl::default());
+        app.update();
+
+        let alice = presence_of(&app, ALICE).unwrap();
+        assert_eq!(alice.subscription_count, 2);
+        assert_eq!(alice.controlled_entities, vec![robot.to_bits()]);
+
+        app.world_mut().get_mut::<EntityControl>(robot).unwrap().client_id = ConnectionId::NONE;
+        app.update();
+        assert!(presence_of(&app, ALICE).unwrap().controlled_entities.is_empty());
+    }
+}
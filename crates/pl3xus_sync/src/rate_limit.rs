@@ -0,0 +1,508 @@
+//! Per-connection rate limits for mutations, requests and messages.
+//!
+//! `RateLimitPlugin` protects the server from clients that flood it, e.g. a UI
+//! stuck in a loop re-sending a mutation every frame. Each connection gets a
+//! token bucket per kind (mutations, requests, messages), refilled at the
+//! configured rate and holding at most one second's worth. Types listed in
+//! [`RateLimits::per_type`] get their own bucket and rate instead.
+//!
+//! Over-limit mutations are answered with `MutationStatus::Forbidden`.
+//! Every request and message registered with
+//! [`MessageRegistration`](crate::MessageRegistration) or
+//! [`RequestRegistration`](crate::RequestRegistration) is limited: those with
+//! a policy or middleware in the middleware stage, where a request registered
+//! with `with_error_response` is answered with the reason, and the rest as
+//! their packets arrive. Throttled clients get a warning [`ServerNotification`]
+//! with category `"rate_limit"`, at most once per second per bucket, sent as an
+//! [`OutgoingNotification`](crate::notifications::OutgoingNotification) so
+//! clients can mute it. The counts are kept in [`RateLimitMetrics`].
+//!
+//! [`Middleware::rate_limit`](crate::Middleware::rate_limit) uses the same
+//! token buckets for a limit on one type, without the plugin.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::rate_limit::{RateLimitPlugin, RateLimits};
+//!
+//! app.add_plugins(RateLimitPlugin::<WebSocketProvider>::new(
+//!     RateLimits::default()
+//!         .mutations_per_sec(20.0)
+//!         .requests_per_sec(10.0)
+//!         .per_type("JogRobot", 60.0),
+//! ));
+//! ```
+
+use bevy::prelude::*;
+use pl3xus::{AppPacketHookExt, NetworkEvent, PacketDirection};
+use pl3xus_common::{ConnectionId, ServerNotification};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::authorization::AuthResult;
+use crate::NetworkProvider;
+
+/// Notification category of throttling warnings.
+pub const RATE_LIMIT_CATEGORY: &str = "rate_limit";
+
+/// What a client sent, for picking its rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitKind {
+    /// A component mutation.
+    Mutation,
+    /// A request expecting a response.
+    Request,
+    /// A fire-and-forget message.
+    Message,
+}
+
+/// Configured limits, in payloads per second. `None` means unlimited.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RateLimits {
+    /// Component mutations per connection.
+    pub mutations_per_sec: Option<f32>,
+    /// Requests per connection.
+    pub requests_per_sec: Option<f32>,
+    /// Messages per connection.
+    pub messages_per_sec: Option<f32>,
+    /// Limits for single types, by short type name (the component type for
+    /// mutations). These replace the limit for the type's kind.
+    pub per_type: HashMap<String, f32>,
+}
+
+impl RateLimits {
+    /// Limit component mutations per connection.
+    pub fn mutations_per_sec(mut self, rate: f32) -> Self {
+        self.mutations_per_sec = Some(rate);
+        self
+    }
+
+    /// Limit requests per connection.
+    pub fn requests_per_sec(mut self, rate: f32) -> Self {
+        self.requests_per_sec = Some(rate);
+        self
+    }
+
+    /// Limit messages per connection.
+    pub fn messages_per_sec(mut self, rate: f32) -> Self {
+        self.messages_per_sec = Some(rate);
+        self
+    }
+
+    /// Give `type_name` its own limit.
+    pub fn per_type(mut self, type_name: impl Into<String>, rate: f32) -> Self {
+        self.per_type.insert(type_name.into(), rate);
+        self
+    }
+
+    /// The bucket key and rate for a payload, or `None` if it is unlimited.
+    fn limit_for<'a>(&self, kind: RateLimitKind, type_name: &'a str) -> Option<(BucketKey<'a>, f32)> {
+        if let Some(rate) = self.per_type.get(type_name) {
+            return Some((BucketKey::Type(type_name), *rate));
+        }
+        let rate = match kind {
+            RateLimitKind::Mutation => self.mutations_per_sec,
+            RateLimitKind::Request => self.requests_per_sec,
+            RateLimitKind::Message => self.messages_per_sec,
+        }?;
+        Some((BucketKey::Kind(kind), rate))
+    }
+}
+
+/// Throttling counts since startup.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitMetrics {
+    /// Total payloads rejected.
+    pub throttled: u64,
+    /// Rejections per open connection. Entries are removed on disconnect.
+    pub by_connection: HashMap<ConnectionId, u64>,
+    /// Rejections per short type name.
+    pub by_type: HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BucketKey<'a> {
+    Kind(RateLimitKind),
+    Type(&'a str),
+}
+
+impl BucketKey<'_> {
+    fn to_owned(self) -> OwnedBucketKey {
+        match self {
+            BucketKey::Kind(kind) => OwnedBucketKey::Kind(kind),
+            BucketKey::Type(type_name) => OwnedBucketKey::Type(type_name.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum OwnedBucketKey {
+    Kind(RateLimitKind),
+    Type(String),
+}
+
+/// Tokens refilled at a steady rate up to a capacity; each payload takes one.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TokenBucket {
+    tokens: f32,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn full(capacity: f32, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            refilled_at: now,
+        }
+    }
+
+    /// Refill at `rate` tokens per second, then take a token if there is one.
+    pub(crate) fn take(&mut self, rate: f32, capacity: f32, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f32();
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: TokenBucket,
+    notified_at: Option<Instant>,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    buckets: HashMap<(ConnectionId, OwnedBucketKey), Bucket>,
+    metrics: RateLimitMetrics,
+    notifications: Vec<(ConnectionId, String)>,
+    /// Copy of the [`RateLimits`] resource for packet hooks, which can't read it.
+    limits: RateLimits,
+}
+
+/// Token buckets and metrics for every connection.
+///
+/// Checks run from systems that only have `&World` and from packet hooks on
+/// the network tasks, so the state is shared behind a mutex.
+#[derive(Resource, Clone, Default)]
+pub struct RateLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl RateLimiter {
+    /// Take one token for a payload from `source`, denying it if the bucket is empty.
+    ///
+    /// The server connection is never limited.
+    pub fn check(
+        &self,
+        limits: &RateLimits,
+        source: ConnectionId,
+        kind: RateLimitKind,
+        type_name: &str,
+    ) -> AuthResult {
+        self.check_at(limits, source, kind, type_name, Instant::now())
+    }
+
+    fn check_at(
+        &self,
+        limits: &RateLimits,
+        source: ConnectionId,
+        kind: RateLimitKind,
+        type_name: &str,
+        now: Instant,
+    ) -> AuthResult {
+        if source.is_server() {
+            return AuthResult::Authorized;
+        }
+        let Some((key, rate)) = limits.limit_for(kind, type_name) else {
+            return AuthResult::Authorized;
+        };
+        // A limiter that can't count can't let anything through
+        let Ok(mut state) = self.state.lock() else {
+            return AuthResult::Denied(format!("Rate limiter for {} is unavailable", type_name));
+        };
+
+        let capacity = rate.max(1.0);
+        let bucket = state
+            .buckets
+            .entry((source, key.to_owned()))
+            .or_insert(Bucket {
+                tokens: TokenBucket::full(capacity, now),
+                notified_at: None,
+            });
+        if bucket.tokens.take(rate, capacity, now) {
+            return AuthResult::Authorized;
+        }
+
+        let notify = bucket
+            .notified_at
+            .is_none_or(|at| now.duration_since(at) >= Duration::from_secs(1));
+        if notify {
+            bucket.notified_at = Some(now);
+        }
+
+        let reason = match key {
+            BucketKey::Type(_) => format!("Rate limit exceeded for {} ({}/s)", type_name, rate),
+            BucketKey::Kind(kind) => format!("Rate limit exceeded for {:?}s ({}/s)", kind, rate),
+        };
+        state.metrics.throttled += 1;
+        *state.metrics.by_connection.entry(source).or_default() += 1;
+        *state.metrics.by_type.entry(type_name.to_string()).or_default() += 1;
+        if notify {
+            warn!("[pl3xus_sync] Throttling {:?}: {}", source, reason);
+            state.notifications.push((source, reason.clone()));
+        }
+        AuthResult::Denied(reason)
+    }
+
+    /// Check a packet against the limits last copied from the [`RateLimits`] resource.
+    fn check_packet(&self, source: ConnectionId, kind: RateLimitKind, type_name: &str) -> AuthResult {
+        let limits = match self.state.lock() {
+            Ok(state) => state.limits.clone(),
+            Err(_) => return AuthResult::Denied(format!("Rate limiter for {} is unavailable", type_name)),
+        };
+        self.check(&limits, source, kind, type_name)
+    }
+
+    fn set_limits(&self, limits: &RateLimits) {
+        if let Ok(mut state) = self.state.lock() {
+            state.limits = limits.clone();
+        }
+    }
+
+    /// Snapshot of the throttling counts.
+    pub fn metrics(&self) -> RateLimitMetrics {
+        self.state
+            .lock()
+            .map(|state| state.metrics.clone())
+            .unwrap_or_default()
+    }
+
+    fn take_notifications(&self) -> Vec<(ConnectionId, String)> {
+        self.state
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.notifications))
+            .unwrap_or_default()
+    }
+
+    fn remove_connection(&self, connection_id: ConnectionId) {
+        if let Ok(mut state) = self.state.lock() {
+            state.buckets.retain(|(source, _), _| *source != connection_id);
+            state.notifications.retain(|(source, _)| *source != connection_id);
+            state.metrics.by_connection.remove(&connection_id);
+        }
+    }
+}
+
+/// Check a payload against the configured limits, if rate limiting is enabled.
+pub(crate) fn check_rate_limit(
+    world: &World,
+    source: ConnectionId,
+    kind: RateLimitKind,
+    type_name: &str,
+) -> AuthResult {
+    match (world.get_resource::<RateLimiter>(), world.get_resource::<RateLimits>()) {
+        (Some(limiter), Some(limits)) => limiter.check(limits, source, kind, type_name),
+        _ => AuthResult::Authorized,
+    }
+}
+
+/// A registered type limited where its packets arrive, and the limit it uses.
+#[derive(Debug, Clone, Copy)]
+struct PacketLimit {
+    kind: RateLimitKind,
+    type_name: &'static str,
+}
+
+#[derive(Default)]
+struct PacketTypes {
+    by_name: HashMap<&'static str, PacketLimit>,
+    by_hash: HashMap<u64, PacketLimit>,
+}
+
+/// Registered messages and requests that skip the middleware stage, by the
+/// type name and schema hash their packets arrive under.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct PacketRateLimits(Arc<RwLock<PacketTypes>>);
+
+impl PacketRateLimits {
+    fn get(&self, packet_name: &str, schema_hash: u64) -> Option<PacketLimit> {
+        let types = self.0.read().ok()?;
+        types
+            .by_name
+            .get(packet_name)
+            .or_else(|| types.by_hash.get(&schema_hash))
+            .copied()
+    }
+}
+
+/// Rate limit a registered type as its packets arrive, for types whose
+/// payloads don't pass through the middleware stage.
+///
+/// `packet` is the type name and schema hash the payload is sent under;
+/// `type_name` is the short name used for [`RateLimits::per_type`].
+pub(crate) fn limit_packets(app: &mut App, kind: RateLimitKind, type_name: &'static str, packet: (&'static str, u64)) {
+    app.init_resource::<PacketRateLimits>();
+    let limit = PacketLimit { kind, type_name };
+    if let Ok(mut types) = app.world().resource::<PacketRateLimits>().0.write() {
+        types.by_name.insert(packet.0, limit);
+        types.by_hash.insert(packet.1, limit);
+    }
+}
+
+/// Plugin enforcing [`RateLimits`] on every connection.
+pub struct RateLimitPlugin<NP: NetworkProvider> {
+    limits: RateLimits,
+    _marker: std::marker::PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> RateLimitPlugin<NP> {
+    /// Create the plugin with these limits.
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for RateLimitPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.limits.clone())
+            .init_resource::<RateLimiter>()
+            .init_resource::<PacketRateLimits>()
+            .add_systems(PreUpdate, copy_limits)
+            .add_systems(
+                PostUpdate,
+                (send_throttle_notifications::<NP>, forget_disconnected),
+            );
+
+        let limiter = app.world().resource::<RateLimiter>().clone();
+        limiter.set_limits(&self.limits);
+        let packets = app.world().resource::<PacketRateLimits>().clone();
+        app.packet_hook::<NP>(move |context, packet| {
+            if context.direction != PacketDirection::Inbound {
+                return Ok(());
+            }
+            let Some(limit) = packets.get(&packet.type_name, packet.schema_hash) else {
+                return Ok(());
+            };
+            match limiter.check_packet(context.connection, limit.kind, limit.type_name) {
+                AuthResult::Authorized => Ok(()),
+                AuthResult::Denied(reason) => Err(reason),
+            }
+        });
+    }
+}
+
+/// Let packet hooks see changes to the [`RateLimits`] resource.
+fn copy_limits(limits: Res<RateLimits>, limiter: Res<RateLimiter>) {
+    if limits.is_changed() {
+        limiter.set_limits(&limits);
+    }
+}
+
+fn send_throttle_notifications<NP: NetworkProvider>(world: &mut World) {
+    let Some(limiter) = world.get_resource::<RateLimiter>().cloned() else {
+        return;
+    };
+    for (connection_id, reason) in limiter.take_notifications() {
+        let notification = ServerNotification::warning(reason).with_category(RATE_LIMIT_CATEGORY);
+        crate::notifications::notify_connection::<NP>(world, connection_id, notification);
+    }
+}
+
+fn forget_disconnected(mut events: MessageReader<NetworkEvent>, limiter: Res<RateLimiter>) {
+    for event in events.read() {
+        if let NetworkEvent::Disconnected(connection_id) = event {
+            limiter.remove_connection(*connection_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limits = RateLimits::default().mutations_per_sec(2.0).per_type("Jog", 10.0);
+        let limiter = RateLimiter::default();
+        let client = ConnectionId { id: 3 };
+        let start = Instant::now();
+        let check = |kind, type_name, secs: f32| {
+            limiter.check_at(&limits, client, kind, type_name, start + Duration::from_secs_f32(secs))
+        };
+
+        assert!(matches!(check(RateLimitKind::Mutation, "Position", 0.0), AuthResult::Authorized));
+        assert!(matches!(check(RateLimitKind::Mutation, "Velocity", 0.0), AuthResult::Authorized));
+        assert!(matches!(check(RateLimitKind::Mutation, "Position", 0.0), AuthResult::Denied(_)));
+        // Half a second refills one token
+        assert!(matches!(check(RateLimitKind::Mutation, "Position", 0.5), AuthResult::Authorized));
+        // Per-type buckets and unlimited kinds are separate
+        assert!(matches!(check(RateLimitKind::Mutation, "Jog", 0.5), AuthResult::Authorized));
+        assert!(matches!(check(RateLimitKind::Request, "GetStatus", 0.5), AuthResult::Authorized));
+        assert!(matches!(
+            limiter.check_at(&limits, ConnectionId::SERVER, RateLimitKind::Mutation, "Position", start),
+            AuthResult::Authorized
+        ));
+
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.throttled, 1);
+        assert_eq!(metrics.by_connection[&client], 1);
+        assert_eq!(metrics.by_type["Position"], 1);
+        assert_eq!(limiter.take_notifications().len(), 1);
+
+        // Disconnecting forgets the connection, but not the totals
+        assert!(matches!(check(RateLimitKind::Mutation, "Position", 0.5), AuthResult::Denied(_)));
+        limiter.remove_connection(client);
+        let metrics = limiter.metrics();
+        assert!(!metrics.by_connection.contains_key(&client));
+        assert!(limiter.take_notifications().is_empty());
+        assert_eq!((metrics.throttled, metrics.by_type["Position"]), (2, 2));
+        // A fresh bucket is full again
+        assert!(matches!(check(RateLimitKind::Mutation, "Position", 0.5), AuthResult::Authorized));
+    }
+
+    #[test]
+    fn test_packet_limits_use_copied_limits() {
+        let mut app = App::new();
+        limit_packets(&mut app, RateLimitKind::Request, "GetStatus", ("RequestInternal<GetStatus>", 7));
+        let packets = app.world().resource::<PacketRateLimits>().clone();
+
+        // Matched by name, or by schema hash when clients send another name
+        let limit = packets.get("RequestInternal<GetStatus>", 0).unwrap();
+        assert_eq!((limit.kind, limit.type_name), (RateLimitKind::Request, "GetStatus"));
+        assert!(packets.get("GetStatus", 7).is_some());
+        assert!(packets.get("SyncClientMessage", 8).is_none());
+
+        let limiter = RateLimiter::default();
+        let client = ConnectionId { id: 4 };
+        assert!(limiter.check_packet(client, limit.kind, limit.type_name).is_authorized());
+
+        limiter.set_limits(&RateLimits::default().requests_per_sec(1.0));
+        assert!(limiter.check_packet(client, limit.kind, limit.type_name).is_authorized());
+        assert!(!limiter.check_packet(client, limit.kind, limit.type_name).is_authorized());
+    }
+
+    #[test]
+    fn test_poisoned_limiter_denies() {
+        let limits = RateLimits::default().messages_per_sec(100.0);
+        let limiter = RateLimiter::default();
+        let state = limiter.state.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = state.lock().unwrap();
+            panic!("poison the limiter");
+        })
+        .join();
+
+        let result = limiter.check(&limits, ConnectionId { id: 5 }, RateLimitKind::Message, "Jog");
+        assert!(!result.is_authorized());
+    }
+}
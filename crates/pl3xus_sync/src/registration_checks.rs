@@ -0,0 +1,178 @@
+//! Startup validation of sync registrations.
+//!
+//! Some misconfigurations only show up at runtime as mutations or messages
+//! that quietly go nowhere: a component marked `targeted()` without a
+//! mutation handler, a handler whose `ComponentMutation<T>` message was never
+//! added, a registration relying on a default access policy that was never
+//! inserted, or an invalidation rule naming a request that doesn't exist.
+//!
+//! Registrations record checks in [`RegistrationChecks`], and
+//! `Pl3xusSyncPlugin::finish` runs them once every plugin is built. All
+//! problems are reported together, each with the fix, as one error log, or as
+//! a panic when [`SyncPluginConfig::strict_registrations`] is set.
+//!
+//! Applications can add their own checks:
+//!
+//! ```rust,ignore
+//! app.world_mut()
+//!     .resource_mut::<RegistrationChecks>()
+//!     .add(|world| (!world.contains_resource::<RobotDriver>()).then(|| "RobotDriver is not inserted".to_string()));
+//! ```
+//!
+//! [`SyncPluginConfig::strict_registrations`]: crate::SyncPluginConfig::strict_registrations
+
+use bevy::prelude::*;
+
+use crate::authorization::{DefaultEntityAccessPolicy, DefaultMessageAccessPolicy};
+use crate::invalidation::InvalidationRules;
+use crate::registry::SyncRegistry;
+use crate::schema::SchemaRegistry;
+
+type RegistrationCheck = Box<dyn Fn(&World) -> Option<String> + Send + Sync>;
+
+/// Checks run against the finished app, each returning a problem description.
+#[derive(Resource, Default)]
+pub struct RegistrationChecks {
+    checks: Vec<RegistrationCheck>,
+}
+
+impl RegistrationChecks {
+    /// Add a check. It returns a description of the problem, including how to
+    /// fix it, or `None` if the app is fine.
+    pub fn add<F>(&mut self, check: F)
+    where
+        F: Fn(&World) -> Option<String> + Send + Sync + 'static,
+    {
+        self.checks.push(Box::new(check));
+    }
+}
+
+/// Record a check to run when the sync plugin finishes.
+pub(crate) fn add_check<F>(app: &mut App, check: F)
+where
+    F: Fn(&World) -> Option<String> + Send + Sync + 'static,
+{
+    app.world_mut()
+        .get_resource_or_insert_with(RegistrationChecks::default)
+        .add(check);
+}
+
+/// Require a [`DefaultEntityAccessPolicy`] for a registration using it.
+pub(crate) fn require_default_entity_policy(app: &mut App, registration: String) {
+    add_check(app, move |world| {
+        (!world.contains_resource::<DefaultEntityAccessPolicy>()).then(|| {
+            format!(
+                "{} uses the default entity access policy, but no DefaultEntityAccessPolicy is inserted, \
+                 so every client may target every entity; insert one with \
+                 app.insert_resource(DefaultEntityAccessPolicy(...))",
+                registration
+            )
+        })
+    });
+}
+
+/// Require a [`DefaultMessageAccessPolicy`] for a registration using it.
+pub(crate) fn require_default_message_policy(app: &mut App, registration: String) {
+    add_check(app, move |world| {
+        (!world.contains_resource::<DefaultMessageAccessPolicy>()).then(|| {
+            format!(
+                "{} uses the default message access policy, but no DefaultMessageAccessPolicy is inserted, \
+                 so every client is allowed; insert one with app.insert_resource(DefaultMessageAccessPolicy(...))",
+                registration
+            )
+        })
+    });
+}
+
+/// Every registration problem in `world`.
+pub fn validate_registrations(world: &World) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if let Some(registry) = world.get_resource::<SyncRegistry>() {
+        for component in &registry.components {
+            if component.config.requires_entity_authorization && !component.config.has_mutation_handler {
+                problems.push(format!(
+                    "Component {} is targeted() but has no mutation handler, so client mutations are never applied; \
+                     add .with_handler::<NP, _, _>(...) reading AuthorizedComponentMutation<{}>",
+                    component.type_name, component.type_name
+                ));
+            }
+        }
+    }
+
+    if let (Some(rules), Some(schema)) = (
+        world.get_resource::<InvalidationRules>(),
+        world.get_resource::<SchemaRegistry>(),
+    ) {
+        for (request_type, query_type) in rules.invalidated_queries() {
+            if !schema.requests.contains_key(query_type) {
+                problems.push(format!(
+                    "{} invalidates {}, which is not a registered request, so no client query is ever refreshed; \
+                     check the name or register it with app.request::<{}, NP>()",
+                    request_type, query_type, query_type
+                ));
+            }
+        }
+    }
+
+    if let Some(checks) = world.get_resource::<RegistrationChecks>() {
+        problems.extend(checks.checks.iter().filter_map(|check| check(world)));
+    }
+
+    problems
+}
+
+/// Log every registration problem at once, or panic if `strict`.
+pub(crate) fn report_registration_problems(world: &World, strict: bool) {
+    let problems = validate_registrations(world);
+    if problems.is_empty() {
+        return;
+    }
+
+    let report = format!(
+        "[pl3xus_sync] {} registration problem(s):\n  - {}",
+        problems.len(),
+        problems.join("\n  - ")
+    );
+    if strict {
+        panic!("{}", report);
+    }
+    error!("{}", report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppPl3xusSyncExt, ComponentSyncConfig};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug)]
+    struct JogSettings {
+        speed: f32,
+    }
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug)]
+    struct Gripper {
+        closed: bool,
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let mut app = App::new();
+        // Handler flagged by hand, without adding ComponentMutation<JogSettings>
+        app.sync_component::<JogSettings>(Some(ComponentSyncConfig {
+            has_mutation_handler: true,
+            ..Default::default()
+        }));
+        app.sync_component_builder::<Gripper>().targeted().build();
+        require_default_entity_policy(&mut app, "Message JogRobot".to_string());
+
+        let problems = validate_registrations(app.world());
+        assert_eq!(problems.len(), 3, "{:#?}", problems);
+        assert!(problems.iter().any(|p| p.contains("ComponentMutation<JogSettings>")));
+        assert!(problems.iter().any(|p| p.starts_with("Component Gripper is targeted()")));
+
+        app.insert_resource(DefaultEntityAccessPolicy(crate::EntityAccessPolicy::allow_all()));
+        assert_eq!(validate_registrations(app.world()).len(), 2);
+    }
+}
@@ -1,8 +1,13 @@
 use bevy::prelude::*;
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::messages::{MutationStatus, SerializableEntity, SyncItem};
+use crate::messages::{FieldError, MutationResponse, MutationStatus, SerializableEntity, SubscriptionSequence, SyncBatch, SyncItem};
+use crate::filter::SubscriptionFilter;
+use pl3xus::{IdempotencyCache, IdempotencyCheck};
+
+use crate::state_machine::TransitionGuard;
+use crate::validation::MutationValidator;
 
 /// Configuration for how a component type should be synchronized.
 #[derive(Clone)]
@@ -55,6 +60,98 @@ pub struct ComponentSyncConfig {
     ///
     /// Only applicable when `requires_entity_authorization` is `true`.
     pub use_default_entity_policy: bool,
+
+    /// Maximum rate (in Hz) at which changes to this component are broadcast.
+    ///
+    /// When set, changes are coalesced per entity and emitted at most this often,
+    /// independent of the global [`SyncSettings::max_update_rate_hz`]. Useful for
+    /// high-frequency telemetry that clients don't need at full rate.
+    ///
+    /// Default: `None` (every change is emitted)
+    pub max_update_rate_hz: Option<f32>,
+
+    /// Per-connection visibility filter for this component.
+    ///
+    /// When set, snapshots and updates for an entity are only sent to
+    /// connections for which the policy returns `true`. This lets multi-tenant
+    /// servers reuse one sync pipeline while each client only sees its own
+    /// entities.
+    ///
+    /// Default: `None` (every subscriber sees every entity)
+    pub visibility: Option<VisibilityPolicy>,
+
+    /// Validator run on client mutations before they reach a handler or are
+    /// applied. Rejected mutations get a `ValidationError` with per-field errors.
+    ///
+    /// Default: `None` (no validation)
+    pub validator: Option<MutationValidator>,
+
+    /// Whether successful mutations are recorded for undo/redo.
+    ///
+    /// Default: `false`
+    pub undoable: bool,
+
+    /// Legal-transition check for state machine components, run on client
+    /// mutations before they reach a handler or are applied.
+    ///
+    /// Default: `None` (any transition is allowed)
+    pub transition_guard: Option<TransitionGuard>,
+
+    /// Which values give way first when a client's connection falls behind.
+    /// See [`BackpressurePolicy`].
+    ///
+    /// Default: [`SyncPriority::Normal`]
+    pub priority: SyncPriority,
+}
+
+/// Priority tier of a component type's updates.
+///
+/// When a connection's outgoing channel fills up, lower tiers are held back
+/// (and conflated) or dropped first, as configured by [`BackpressurePolicy`].
+/// Removals are always sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SyncPriority {
+    /// Chatter that can be skipped, such as console output or status text.
+    Low,
+    #[default]
+    Normal,
+    /// State clients must not miss, such as execution state. Never held back.
+    High,
+}
+
+/// Server-side callback deciding whether a connection may see a component on
+/// a given entity.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// // Only send robots owned by the connection's tenant.
+/// let policy = VisibilityPolicy::from_fn(|world, conn, entity| {
+///     world
+///         .get::<Owner>(entity)
+///         .is_some_and(|owner| owner.connection == conn)
+/// });
+/// ```
+#[derive(Clone)]
+pub struct VisibilityPolicy {
+    inner: Arc<VisibilityFn>,
+}
+
+type VisibilityFn = dyn Fn(&World, pl3xus_common::ConnectionId, Entity) -> bool + Send + Sync;
+
+impl VisibilityPolicy {
+    /// Create a policy from a closure.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(&World, pl3xus_common::ConnectionId, Entity) -> bool + Send + Sync + 'static,
+    {
+        Self { inner: Arc::new(f) }
+    }
+
+    /// Check whether `connection` may see `entity`.
+    pub fn is_visible(&self, world: &World, connection: pl3xus_common::ConnectionId, entity: Entity) -> bool {
+        (self.inner)(world, connection, entity)
+    }
 }
 
 impl Default for ComponentSyncConfig {
@@ -66,6 +163,12 @@ impl Default for ComponentSyncConfig {
             has_mutation_handler: false,
             requires_entity_authorization: false,
             use_default_entity_policy: false,
+            max_update_rate_hz: None,
+            visibility: None,
+            validator: None,
+            undoable: false,
+            transition_guard: None,
+            priority: SyncPriority::Normal,
         }
     }
 }
@@ -108,6 +211,60 @@ impl ComponentSyncConfig {
         self.has_mutation_handler = true;
         self
     }
+
+    /// Limit how often changes to this component are broadcast.
+    ///
+    /// Values `<= 0.0` remove the limit.
+    pub fn with_max_update_rate_hz(mut self, hz: f32) -> Self {
+        self.max_update_rate_hz = (hz > 0.0).then_some(hz);
+        self
+    }
+
+    /// Only send this component to connections allowed by `policy`.
+    pub fn with_visibility(mut self, policy: VisibilityPolicy) -> Self {
+        self.visibility = Some(policy);
+        self
+    }
+
+    /// Validate client mutations with `validator` before they are handled.
+    pub fn with_validator(mut self, validator: MutationValidator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Record successful mutations so clients can undo and redo them.
+    pub fn with_undo(mut self) -> Self {
+        self.undoable = true;
+        self
+    }
+
+    /// Reject client mutations that request an illegal state transition.
+    pub fn with_transition_guard(mut self, guard: TransitionGuard) -> Self {
+        self.transition_guard = Some(guard);
+        self
+    }
+
+    /// Set the priority tier used when the client's connection falls behind.
+    pub fn with_priority(mut self, priority: SyncPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Run the configured transition guard, if any, on a mutation of `entity`.
+    pub fn check_transition(&self, world: &World, entity: Entity, value: &[u8]) -> Result<(), String> {
+        match &self.transition_guard {
+            Some(guard) => guard.check(world, entity, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Run the configured validator, if any, on an encoded mutation value.
+    pub fn validate_mutation(&self, value: &[u8]) -> Result<(), Vec<FieldError>> {
+        match &self.validator {
+            Some(validator) => validator.validate(value),
+            None => Ok(()),
+        }
+    }
 }
 
 /// Global settings for the sync system.
@@ -119,9 +276,67 @@ pub struct SyncSettings {
     pub max_update_rate_hz: Option<f32>,
 
     /// Whether to enable message conflation (keeping only latest update per entity+component).
-    /// When true, if multiple updates for the same entity+component arrive before the next
-    /// flush, only the latest value is sent.
+    /// When true, if multiple updates for the same entity+component arrive for a connection
+    /// before the next flush (or within one frame, without a rate limit), only the latest
+    /// value is sent. Counts are kept in [`ConflationQueue::stats`].
     pub enable_message_conflation: bool,
+
+    /// Whether to stamp each `SyncBatch` with the server send time.
+    /// Clients combine it with a clock offset estimate to report per-component
+    /// end-to-end latency. Off by default to keep batches small.
+    pub include_timestamps: bool,
+
+    /// How updates are thinned out, by [`SyncPriority`], for connections
+    /// whose outgoing channel is filling up.
+    pub backpressure: BackpressurePolicy,
+}
+
+/// Thresholds, as fractions of a connection's outgoing channel capacity, at
+/// which lower-priority updates give way.
+///
+/// Held-back values stay queued and are conflated until the channel drains;
+/// dropped values are discarded, so clients keep the last value they got
+/// until the component changes again.
+#[derive(Clone, Debug)]
+pub struct BackpressurePolicy {
+    /// Hold back [`SyncPriority::Low`] values from this fill level.
+    pub hold_low_at: f32,
+    /// Hold back [`SyncPriority::Normal`] values from this fill level.
+    pub hold_normal_at: f32,
+    /// Drop [`SyncPriority::Low`] values from this fill level.
+    pub drop_low_at: f32,
+}
+
+impl Default for BackpressurePolicy {
+    fn default() -> Self {
+        Self {
+            hold_low_at: 0.5,
+            hold_normal_at: 0.75,
+            drop_low_at: 0.9,
+        }
+    }
+}
+
+impl BackpressurePolicy {
+    /// Never hold back or drop anything.
+    pub fn disabled() -> Self {
+        Self {
+            hold_low_at: f32::INFINITY,
+            hold_normal_at: f32::INFINITY,
+            drop_low_at: f32::INFINITY,
+        }
+    }
+
+    /// The lowest priority sent to a connection whose channel is `fill` full.
+    pub fn min_priority(&self, fill: f32) -> SyncPriority {
+        if fill >= self.hold_normal_at {
+            SyncPriority::High
+        } else if fill >= self.hold_low_at {
+            SyncPriority::Normal
+        } else {
+            SyncPriority::Low
+        }
+    }
 }
 
 impl Default for SyncSettings {
@@ -131,15 +346,72 @@ impl Default for SyncSettings {
             max_update_rate_hz: Some(30.0),
             // Enable conflation by default (prevents overwhelming slow clients)
             enable_message_conflation: true,
+            include_timestamps: false,
+            backpressure: BackpressurePolicy::default(),
+        }
+    }
+}
+
+/// Configuration of [`Pl3xusSyncPlugin`](crate::Pl3xusSyncPlugin), kept as a
+/// resource so the sync systems can read it.
+///
+/// ```rust,ignore
+/// app.add_plugins(Pl3xusSyncPlugin::<WebSocketProvider>::new(SyncPluginConfig {
+///     tick_rate_hz: Some(20.0),
+///     default_read_only: true,
+///     max_subscriptions_per_client: Some(256),
+///     ..Default::default()
+/// }));
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct SyncPluginConfig {
+    /// Initial [`SyncSettings::max_update_rate_hz`]. Ignored if `SyncSettings`
+    /// is inserted before the plugin is added.
+    pub tick_rate_hz: Option<f32>,
+    /// Make components registered without a [`ComponentSyncConfig`] read-only
+    /// instead of mutable by clients.
+    pub default_read_only: bool,
+    /// Most subscriptions a client may hold at once. Further subscriptions
+    /// are ignored and the client gets a warning notification.
+    pub max_subscriptions_per_client: Option<usize>,
+    /// Most items per initial snapshot `SyncBatch`. Larger snapshots are sent
+    /// in several batches instead of one large message.
+    pub snapshot_chunk_size: Option<usize>,
+    /// Log subscriptions and snapshots at info level instead of debug.
+    pub verbose_logging: bool,
+    /// Panic at startup on registration problems instead of logging them.
+    /// See [`registration_checks`](crate::registration_checks).
+    pub strict_registrations: bool,
+}
+
+impl Default for SyncPluginConfig {
+    fn default() -> Self {
+        Self {
+            tick_rate_hz: SyncSettings::default().max_update_rate_hz,
+            default_read_only: false,
+            max_subscriptions_per_client: None,
+            snapshot_chunk_size: None,
+            verbose_logging: false,
+            strict_registrations: false,
         }
     }
 }
 
+impl SyncPluginConfig {
+    /// The snapshot chunk size as a usable batch length.
+    pub(crate) fn snapshot_batch_len(&self) -> usize {
+        self.snapshot_chunk_size.unwrap_or(usize::MAX).max(1)
+    }
+}
+
 /// Key for identifying unique updates in the conflation queue.
-/// Updates with the same key will overwrite each other (keeping only the latest).
+/// Updates with the same key overwrite each other (keeping only the latest).
+///
+/// Clients store values by entity and component, whichever subscription they
+/// arrived through, so overlapping subscriptions share a key and the value
+/// is sent once.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ConflationKey {
-    pub subscription_id: u64,
     pub entity: SerializableEntity,
     pub component_type: String,
 }
@@ -147,26 +419,38 @@ pub struct ConflationKey {
 impl ConflationKey {
     pub fn from_sync_item(item: &SyncItem) -> Option<Self> {
         match item {
-            SyncItem::Update { subscription_id, entity, component_type, .. } => {
-                Some(ConflationKey {
-                    subscription_id: *subscription_id,
-                    entity: entity.clone(),
-                    component_type: component_type.clone(),
-                })
-            }
-            SyncItem::Snapshot { subscription_id, entity, component_type, .. } => {
-                Some(ConflationKey {
-                    subscription_id: *subscription_id,
-                    entity: entity.clone(),
-                    component_type: component_type.clone(),
-                })
-            }
+            SyncItem::Update { entity, component_type, .. }
+            | SyncItem::Snapshot { entity, component_type, .. } => Some(ConflationKey {
+                entity: *entity,
+                component_type: component_type.clone(),
+            }),
             // Entity removals and component removals can't be conflated
             _ => None,
         }
     }
 }
 
+/// Counts of values dropped because a newer one replaced them before sending.
+#[derive(Clone, Debug, Default)]
+pub struct ConflationStats {
+    /// Total values conflated since startup.
+    pub conflated: u64,
+    /// Conflated values per connection. Entries are kept after disconnect.
+    pub by_connection: HashMap<pl3xus_common::ConnectionId, u64>,
+    /// Conflated values per component type.
+    pub by_component: HashMap<String, u64>,
+    /// Low-priority values dropped because the connection fell behind.
+    pub dropped: u64,
+}
+
+impl ConflationStats {
+    fn record(&mut self, connection_id: pl3xus_common::ConnectionId, component_type: &str) {
+        self.conflated += 1;
+        *self.by_connection.entry(connection_id).or_default() += 1;
+        *self.by_component.entry(component_type.to_string()).or_default() += 1;
+    }
+}
+
 /// Queue of pending sync items waiting to be flushed to clients.
 /// This enables message conflation and rate limiting.
 #[derive(Resource, Default)]
@@ -182,6 +466,9 @@ pub struct ConflationQueue {
 
     /// Timer for tracking when to flush the queue.
     pub flush_timer: Timer,
+
+    /// How many values conflation has saved sending.
+    pub stats: ConflationStats,
 }
 
 impl ConflationQueue {
@@ -191,22 +478,46 @@ impl ConflationQueue {
             pending: HashMap::new(),
             non_conflatable: HashMap::new(),
             flush_timer: Timer::new(flush_interval, TimerMode::Repeating),
+            stats: ConflationStats::default(),
         }
     }
 
     /// Add a sync item to the queue.
     /// If conflation is enabled and the item is conflatable, it will overwrite any existing
-    /// item with the same key.
+    /// item with the same key. Removals drop the pending values they make obsolete.
     pub fn enqueue(&mut self, connection_id: pl3xus_common::ConnectionId, item: SyncItem, enable_conflation: bool) {
         if enable_conflation {
             if let Some(key) = ConflationKey::from_sync_item(&item) {
                 // Conflatable item - store in the conflation map
-                self.pending
+                let replaced = self
+                    .pending
                     .entry(connection_id)
                     .or_default()
-                    .insert(key, item);
+                    .insert(key.clone(), item);
+                if replaced.is_some() {
+                    self.stats.record(connection_id, &key.component_type);
+                }
                 return;
             }
+
+            // Removals are sent before pending values, so a pending value
+            // for what is being removed would be sent too early
+            if let Some(pending) = self.pending.get_mut(&connection_id) {
+                let stats = &mut self.stats;
+                pending.retain(|key, _| {
+                    let obsolete = match &item {
+                        SyncItem::ComponentRemoved { entity, component_type, .. } => {
+                            key.entity == *entity && key.component_type == *component_type
+                        }
+                        SyncItem::EntityRemoved { entity, .. } => key.entity == *entity,
+                        _ => false,
+                    };
+                    if obsolete {
+                        stats.record(connection_id, &key.component_type);
+                    }
+                    !obsolete
+                });
+            }
         }
 
         // Non-conflatable item or conflation disabled - store in order
@@ -218,21 +529,61 @@ impl ConflationQueue {
 
     /// Drain all pending items for a connection and return them as a Vec.
     pub fn drain_for_connection(&mut self, connection_id: pl3xus_common::ConnectionId) -> Vec<SyncItem> {
-        let mut items = Vec::new();
-
-        // First, add all conflated items
+        // Removals first (in order), then the latest values. Values queued
+        // before a removal were dropped by `enqueue`, so what remains is newer.
+        let mut items = self.non_conflatable.remove(&connection_id).unwrap_or_default();
         if let Some(conflated) = self.pending.remove(&connection_id) {
             items.extend(conflated.into_values());
         }
+        items
+    }
 
-        // Then, add all non-conflatable items (in order)
-        if let Some(non_conflatable) = self.non_conflatable.remove(&connection_id) {
-            items.extend(non_conflatable);
+    /// Drain what a connection should be sent now, given how full its
+    /// outgoing channel is (see [`BackpressurePolicy`]).
+    ///
+    /// Values below the priority the connection can take stay queued, or are
+    /// dropped past `drop_low_at`. Removals are always drained.
+    pub fn drain_with_backpressure(
+        &mut self,
+        connection_id: pl3xus_common::ConnectionId,
+        fill: f32,
+        policy: &BackpressurePolicy,
+        priority_of: impl Fn(&str) -> SyncPriority,
+    ) -> Vec<SyncItem> {
+        let min_priority = policy.min_priority(fill);
+        if min_priority == SyncPriority::Low {
+            return self.drain_for_connection(connection_id);
         }
 
+        let mut items = self.non_conflatable.remove(&connection_id).unwrap_or_default();
+        let Some(pending) = self.pending.get_mut(&connection_id) else {
+            return items;
+        };
+        items.extend(
+            pending
+                .extract_if(|key, _| priority_of(&key.component_type) >= min_priority)
+                .map(|(_, item)| item),
+        );
+        if fill >= policy.drop_low_at {
+            let dropped = pending
+                .extract_if(|key, _| priority_of(&key.component_type) == SyncPriority::Low)
+                .count();
+            self.stats.dropped += dropped as u64;
+        }
+        if pending.is_empty() {
+            self.pending.remove(&connection_id);
+        }
         items
     }
 
+    /// Connections with anything queued.
+    pub fn connections(&self) -> Vec<pl3xus_common::ConnectionId> {
+        let mut connections: Vec<_> = self.pending.keys().chain(self.non_conflatable.keys()).copied().collect();
+        connections.sort_by_key(|connection_id| connection_id.id);
+        connections.dedup();
+        connections
+    }
+
     /// Get the total number of pending items for a connection.
     pub fn pending_count(&self, connection_id: pl3xus_common::ConnectionId) -> usize {
         let conflated = self.pending.get(&connection_id).map(|m| m.len()).unwrap_or(0);
@@ -241,6 +592,85 @@ impl ConflationQueue {
     }
 }
 
+/// Random id of this server run, sent to clients in the [`WelcomeMessage`].
+///
+/// Connection ids start over when the server restarts; clients pair them with
+/// this session ([`pl3xus_common::ConnectionIdentity`]) so an id from a
+/// previous run is never mistaken for the current one.
+///
+/// [`WelcomeMessage`]: crate::WelcomeMessage
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyncSession(pub u64);
+
+impl Default for SyncSession {
+    fn default() -> Self {
+        use std::hash::BuildHasher;
+
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        // RandomState is seeded randomly per process
+        Self(std::collections::hash_map::RandomState::new().hash_one((nanos, std::process::id())))
+    }
+}
+
+impl SyncSession {
+    /// The identity of `connection` in this server run.
+    pub fn identity(&self, connection: pl3xus_common::ConnectionId) -> pl3xus_common::ConnectionIdentity {
+        pl3xus_common::ConnectionIdentity {
+            session: self.0,
+            connection,
+        }
+    }
+}
+
+/// Per-subscription sequence counters for outgoing [`SyncBatch`]es.
+///
+/// Counters live as long as the connection, including across
+/// unsubscribe/resubscribe, so a client that resubscribes after a gap keeps
+/// comparing against the same sequence.
+#[derive(Resource, Default)]
+pub struct SyncSequences {
+    last: HashMap<(pl3xus_common::ConnectionId, u64), u64>,
+}
+
+impl SyncSequences {
+    /// Assign the next sequence number to every subscription with items in `batch`.
+    ///
+    /// Call this for every batch, even if sending it may fail: a failed send
+    /// then shows up as a gap on the client.
+    pub fn stamp(&mut self, connection_id: pl3xus_common::ConnectionId, batch: &mut SyncBatch) {
+        batch.sequences.clear();
+        for item in &batch.items {
+            let subscription_id = item.subscription_id();
+            if batch.sequences.iter().any(|s| s.subscription_id == subscription_id) {
+                continue;
+            }
+            let sequence = self.last.entry((connection_id, subscription_id)).or_default();
+            *sequence += 1;
+            batch.sequences.push(SubscriptionSequence {
+                subscription_id,
+                sequence: *sequence,
+            });
+        }
+    }
+
+    /// Forget all counters for a connection.
+    pub fn remove_connection(&mut self, connection_id: pl3xus_common::ConnectionId) {
+        self.last.retain(|(conn, _), _| *conn != connection_id);
+    }
+
+    /// Number of (connection, subscription) counters held.
+    pub fn len(&self) -> usize {
+        self.last.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.last.is_empty()
+    }
+}
+
 /// Per-type registration data stored in the [`SyncRegistry`].
 #[derive(Clone)]
 pub struct ComponentRegistration {
@@ -254,6 +684,20 @@ pub struct ComponentRegistration {
     /// `(Entity, Component)` pairs for this component type, encoded as bincode
     /// bytes suitable for transmission over the wire.
     pub snapshot_all: fn(&mut World) -> Vec<(SerializableEntity, Vec<u8>)>,
+    /// Type-specific function that encodes this component's current value on
+    /// one entity, as it would be sent over the wire.
+    pub read_value: fn(&World, Entity) -> Option<Vec<u8>>,
+    /// Type-specific function that produces all `(Entity, Component)` pairs
+    /// for this component type as JSON, with redacted fields stripped. Used
+    /// by tooling such as the admin plugin.
+    pub snapshot_json: fn(&mut World) -> Vec<(SerializableEntity, String)>,
+    /// Type-specific function that reads this component's current value on
+    /// one entity as JSON, with redacted fields stripped. Used to evaluate
+    /// subscription filters.
+    pub read_json: fn(&World, Entity) -> Option<serde_json::Value>,
+    /// Type-specific function that decodes this component from JSON and
+    /// inserts it on an entity. Used to import world snapshots.
+    pub insert_json: fn(&mut World, Entity, serde_json::Value) -> Result<(), String>,
     /// Optional function to route mutations to a handler system.
     ///
     /// When `config.has_mutation_handler` is true, this function is called
@@ -285,6 +729,22 @@ impl SyncRegistry {
         }
         self.components.push(registration);
     }
+
+    /// Priority tier of each registered component type.
+    pub fn priorities(&self) -> HashMap<String, SyncPriority> {
+        self.components
+            .iter()
+            .map(|c| (c.type_name.clone(), c.config.priority))
+            .collect()
+    }
+
+    /// Visibility policy of a component type, if it has one.
+    pub fn visibility_policy(&self, type_name: &str) -> Option<&VisibilityPolicy> {
+        self.components
+            .iter()
+            .find(|c| c.type_name == type_name)
+            .and_then(|c| c.config.visibility.as_ref())
+    }
 }
 
 /// Subscription tracking keyed by (connection, subscription_id).
@@ -301,6 +761,14 @@ pub struct SubscriptionEntry {
     pub subscription_id: u64,
     pub component_type: String,
     pub entity: Option<SerializableEntity>,
+    pub filter: Option<SubscriptionFilter>,
+    /// Entities last sent as matching `filter`, so the client also gets the
+    /// update that makes one stop matching.
+    pub matched: HashSet<SerializableEntity>,
+    /// (entity, component type) pairs the component's visibility policy
+    /// currently hides from this connection, so the client is told to drop
+    /// a value once when it becomes hidden.
+    pub hidden: HashSet<(SerializableEntity, String)>,
 }
 
 impl SubscriptionManager {
@@ -322,6 +790,52 @@ impl SubscriptionManager {
         self.subscriptions
             .retain(|s| s.connection_id != connection);
     }
+
+    /// Record whether `entity` currently matches a filtered subscription.
+    pub fn set_matched(
+        &mut self,
+        connection: pl3xus_common::ConnectionId,
+        subscription_id: u64,
+        entity: SerializableEntity,
+        matched: bool,
+    ) {
+        let Some(sub) = self
+            .subscriptions
+            .iter_mut()
+            .find(|s| s.connection_id == connection && s.subscription_id == subscription_id)
+        else {
+            return;
+        };
+        if matched {
+            sub.matched.insert(entity);
+        } else {
+            sub.matched.remove(&entity);
+        }
+    }
+
+    /// Record whether a component on `entity` is hidden from a subscription
+    /// by its visibility policy.
+    pub fn set_hidden(
+        &mut self,
+        connection: pl3xus_common::ConnectionId,
+        subscription_id: u64,
+        entity: SerializableEntity,
+        component_type: String,
+        hidden: bool,
+    ) {
+        let Some(sub) = self
+            .subscriptions
+            .iter_mut()
+            .find(|s| s.connection_id == connection && s.subscription_id == subscription_id)
+        else {
+            return;
+        };
+        if hidden {
+            sub.hidden.insert((entity, component_type));
+        } else {
+            sub.hidden.remove(&(entity, component_type));
+        }
+    }
 }
 
 /// A single snapshot request queued when a client first subscribes.
@@ -331,6 +845,7 @@ pub struct SnapshotRequest {
     pub subscription_id: u64,
     pub component_type: String,
     pub entity: Option<SerializableEntity>,
+    pub filter: Option<SubscriptionFilter>,
 }
 
 /// Queue of pending snapshot requests to be processed by a dedicated system.
@@ -360,6 +875,22 @@ pub struct QueuedMutation {
     pub component_type: String,
     /// Full component value encoded as bincode bytes (v1 uses full replacement semantics).
     pub value: Vec<u8>,
+    /// Client-chosen key identifying this mutation across retries.
+    pub idempotency_key: Option<u64>,
+    /// Whether this is a regular mutation or an undo/redo.
+    pub origin: MutationOrigin,
+}
+
+/// What a [`QueuedMutation`] was created from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MutationOrigin {
+    /// A `MutateComponent` request.
+    #[default]
+    Mutate,
+    /// An `UndoMutation` request, writing back the previous value.
+    Undo,
+    /// A `RedoMutation` request, reapplying an undone value.
+    Redo,
 }
 
 // =============================================================================
@@ -413,6 +944,11 @@ pub struct ComponentMutation<T: Component + Clone + Send + Sync + 'static> {
 }
 
 impl<T: Component + Clone + Send + Sync + 'static> ComponentMutation<T> {
+    /// Take a responder to complete this mutation later (e.g. from an async task).
+    pub fn defer_response(&self, responses: &MutationResponseQueue) -> DeferredMutationResponder {
+        responses.defer(self.connection_id, self.request_id)
+    }
+
     /// Get the entity this mutation targets.
     pub fn entity(&self) -> Entity {
         self.entity
@@ -478,6 +1014,11 @@ pub struct AuthorizedComponentMutation<T: Component + Clone + Send + Sync + 'sta
 }
 
 impl<T: Component + Clone + Send + Sync + 'static> AuthorizedComponentMutation<T> {
+    /// Take a responder to complete this mutation later (e.g. from an async task).
+    pub fn defer_response(&self, responses: &MutationResponseQueue) -> DeferredMutationResponder {
+        responses.defer(self.connection_id, self.request_id)
+    }
+
     /// Get the entity this mutation targets.
     pub fn entity(&self) -> Entity {
         self.entity
@@ -518,15 +1059,58 @@ pub struct PendingMutationResponse {
     pub request_id: Option<u64>,
     pub status: MutationStatus,
     pub message: Option<String>,
+    pub field_errors: Vec<FieldError>,
 }
 
 /// Queue of mutation responses to be sent after handler processing.
-#[derive(Resource, Default)]
+///
+/// Handlers that finish synchronously push responses directly. Handlers that
+/// need async work take a [`DeferredMutationResponder`] with [`defer`](Self::defer)
+/// and complete it later from any thread.
+#[derive(Resource)]
 pub struct MutationResponseQueue {
     pub pending: Vec<PendingMutationResponse>,
+    deferred_tx: pl3xus::async_channel::Sender<PendingMutationResponse>,
+    deferred_rx: pl3xus::async_channel::Receiver<PendingMutationResponse>,
+}
+
+impl Default for MutationResponseQueue {
+    fn default() -> Self {
+        let (deferred_tx, deferred_rx) = pl3xus::async_channel::unbounded();
+        Self {
+            pending: Vec::new(),
+            deferred_tx,
+            deferred_rx,
+        }
+    }
 }
 
 impl MutationResponseQueue {
+    /// Create a responder that can be moved into an async task and completed later.
+    ///
+    /// The response is sent to the client the next time the sync systems run
+    /// after the responder is completed.
+    pub fn defer(
+        &self,
+        connection_id: pl3xus_common::ConnectionId,
+        request_id: Option<u64>,
+    ) -> DeferredMutationResponder {
+        DeferredMutationResponder {
+            connection_id,
+            request_id,
+            tx: self.deferred_tx.clone(),
+        }
+    }
+
+    /// Take all queued responses, including completed deferred ones.
+    pub(crate) fn drain(&mut self) -> Vec<PendingMutationResponse> {
+        let mut responses = std::mem::take(&mut self.pending);
+        while let Ok(response) = self.deferred_rx.try_recv() {
+            responses.push(response);
+        }
+        responses
+    }
+
     /// Queue a successful mutation response.
     pub fn respond_ok(&mut self, connection_id: pl3xus_common::ConnectionId, request_id: Option<u64>) {
         self.pending.push(PendingMutationResponse {
@@ -534,6 +1118,7 @@ impl MutationResponseQueue {
             request_id,
             status: MutationStatus::Ok,
             message: None,
+            field_errors: Vec::new(),
         });
     }
 
@@ -549,6 +1134,7 @@ impl MutationResponseQueue {
             request_id,
             status: MutationStatus::ValidationError,
             message: Some(message.into()),
+            field_errors: Vec::new(),
         });
     }
 
@@ -564,8 +1150,208 @@ impl MutationResponseQueue {
             request_id,
             status: MutationStatus::Forbidden,
             message: Some(message.into()),
+            field_errors: Vec::new(),
         });
     }
+
+    /// Queue a validation error response with per-field errors.
+    pub fn respond_field_errors(
+        &mut self,
+        connection_id: pl3xus_common::ConnectionId,
+        request_id: Option<u64>,
+        field_errors: Vec<FieldError>,
+    ) {
+        self.pending.push(PendingMutationResponse {
+            connection_id,
+            request_id,
+            status: MutationStatus::ValidationError,
+            message: Some(validation_failed_message(&field_errors)),
+            field_errors,
+        });
+    }
+}
+
+/// Summary message sent alongside per-field validation errors.
+pub(crate) fn validation_failed_message(field_errors: &[FieldError]) -> String {
+    match field_errors {
+        [error] => format!("Validation failed: {} {}", error.field, error.message),
+        errors => format!("Validation failed for {} fields", errors.len()),
+    }
+}
+
+/// Deduplicates mutations that carry an idempotency key.
+///
+/// A retried mutation is not applied again: while the original is still being
+/// handled the retry waits for it, afterwards it gets the original status.
+/// Keys are scoped to the mutated component type.
+/// Insert your own before adding the sync plugin to change the TTL.
+#[derive(Resource, Default)]
+pub struct MutationIdempotency {
+    /// Cached responses, with `request_id` cleared, by (component type, key).
+    cache: IdempotencyCache<MutationResponse, (pl3xus_common::ConnectionId, u64), (String, u64)>,
+    /// Handler-routed mutations awaiting a response: (connection, request_id) -> key.
+    awaiting_handler: HashMap<(pl3xus_common::ConnectionId, u64), (String, u64)>,
+}
+
+impl MutationIdempotency {
+    /// Create a cache that remembers keys for `ttl`.
+    pub fn new(ttl: std::time::Duration) -> Self {
+        Self {
+            cache: IdempotencyCache::new(ttl),
+            awaiting_handler: HashMap::new(),
+        }
+    }
+
+    /// Check an incoming mutation against the cache.
+    ///
+    /// `is_connected` tells whether a connection is still open, so a client
+    /// that reconnects can retry under the key its old connection used.
+    pub(crate) fn begin(
+        &mut self,
+        mutation: &QueuedMutation,
+        is_connected: impl Fn(pl3xus_common::ConnectionId) -> bool,
+    ) -> IdempotencyCheck<MutationResponse> {
+        match mutation.idempotency_key {
+            Some(key) => self.cache.begin(
+                mutation.connection_id,
+                (mutation.component_type.clone(), key),
+                mutation.request_id.map(|id| (mutation.connection_id, id)),
+                is_connected,
+            ),
+            None => IdempotencyCheck::New,
+        }
+    }
+
+    /// Remember a mutation routed to a handler, so its response (which only
+    /// carries the request id) can be cached.
+    pub(crate) fn await_handler(&mut self, mutation: &QueuedMutation) {
+        if let (Some(key), Some(request_id)) = (mutation.idempotency_key, mutation.request_id) {
+            self.awaiting_handler
+                .insert((mutation.connection_id, request_id), (mutation.component_type.clone(), key));
+        }
+    }
+
+    /// Cache the outcome of a mutation and return the connections and request
+    /// ids of retries waiting for it.
+    pub(crate) fn complete(
+        &mut self,
+        connection_id: pl3xus_common::ConnectionId,
+        key: Option<(String, u64)>,
+        response: &MutationResponse,
+    ) -> Vec<(pl3xus_common::ConnectionId, u64)> {
+        let key = key.or_else(|| {
+            response
+                .request_id
+                .and_then(|id| self.awaiting_handler.remove(&(connection_id, id)))
+        });
+        match key {
+            Some(key) => self.cache.complete(
+                connection_id,
+                key,
+                MutationResponse {
+                    request_id: None,
+                    ..response.clone()
+                },
+            ),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// A mutation response to be sent later, possibly from another thread.
+///
+/// Mirrors [`DeferredResponder`](pl3xus::DeferredResponder) for requests. Get one
+/// from [`MutationResponseQueue::defer`] or a mutation's `defer_response`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// fn handle_frame_tool_mutation(
+///     mut mutations: MessageReader<ComponentMutation<FrameToolDataState>>,
+///     responses: Res<MutationResponseQueue>,
+///     drivers: Query<&RobotDriver>,
+///     runtime: Res<TokioRuntime>,
+/// ) {
+///     for mutation in mutations.read() {
+///         let Ok(driver) = drivers.get(mutation.entity()) else { continue };
+///         let driver = driver.clone();
+///         let state = mutation.new_value().clone();
+///         let responder = mutation.defer_response(&responses);
+///
+///         runtime.spawn(async move {
+///             match driver.set_frame_tool(state.active_frame, state.active_tool).await {
+///                 Ok(()) => responder.respond_ok(),
+///                 Err(e) => responder.respond_error(e.to_string()),
+///             }
+///         });
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct DeferredMutationResponder {
+    connection_id: pl3xus_common::ConnectionId,
+    request_id: Option<u64>,
+    tx: pl3xus::async_channel::Sender<PendingMutationResponse>,
+}
+
+impl DeferredMutationResponder {
+    /// Get the connection that originated the mutation.
+    pub fn connection_id(&self) -> pl3xus_common::ConnectionId {
+        self.connection_id
+    }
+
+    /// Get the request ID for correlation.
+    pub fn request_id(&self) -> Option<u64> {
+        self.request_id
+    }
+
+    /// Send a response with the given status.
+    pub fn respond(
+        self,
+        status: MutationStatus,
+        message: Option<String>,
+    ) -> Result<(), pl3xus_common::error::NetworkError> {
+        self.tx
+            .try_send(PendingMutationResponse {
+                connection_id: self.connection_id,
+                request_id: self.request_id,
+                status,
+                message,
+                field_errors: Vec::new(),
+            })
+            .map_err(|_| pl3xus_common::error::NetworkError::SendError)
+    }
+
+    /// Send a successful response.
+    pub fn respond_ok(self) -> Result<(), pl3xus_common::error::NetworkError> {
+        self.respond(MutationStatus::Ok, None)
+    }
+
+    /// Send a validation error response.
+    pub fn respond_error(self, message: impl Into<String>) -> Result<(), pl3xus_common::error::NetworkError> {
+        self.respond(MutationStatus::ValidationError, Some(message.into()))
+    }
+
+    /// Send a forbidden response.
+    pub fn respond_forbidden(self, message: impl Into<String>) -> Result<(), pl3xus_common::error::NetworkError> {
+        self.respond(MutationStatus::Forbidden, Some(message.into()))
+    }
+
+    /// Send a validation error response with per-field errors.
+    pub fn respond_field_errors(
+        self,
+        field_errors: Vec<FieldError>,
+    ) -> Result<(), pl3xus_common::error::NetworkError> {
+        self.tx
+            .try_send(PendingMutationResponse {
+                connection_id: self.connection_id,
+                request_id: self.request_id,
+                status: MutationStatus::ValidationError,
+                message: Some(validation_failed_message(&field_errors)),
+                field_errors,
+            })
+            .map_err(|_| pl3xus_common::error::NetworkError::SendError)
+    }
 }
 
 /// Context passed into a [`MutationAuthorizer`] when deciding whether to allow
@@ -777,8 +1563,23 @@ where
         }
     };
 
-    bevy::log::info!("[apply_typed_mutation] Applying mutation: entity={:?}, type={}, value={:?}",
-        mutation.entity, mutation.component_type, value);
+    verbose_log!(
+        world.get_resource::<SyncPluginConfig>().is_some_and(|c| c.verbose_logging),
+        "[apply_typed_mutation] Applying mutation: entity={:?}, type={}, value={:?}",
+        mutation.entity,
+        mutation.component_type,
+        value
+    );
+
+    // Enforce field-level read-only / redaction policy, if installed
+    let target = (mutation.entity != SerializableEntity::DANGLING).then(|| mutation.entity.to_entity());
+    let value = match crate::field_policy::enforce_field_policy::<T>(world, target, value) {
+        Ok(v) => v,
+        Err(reason) => {
+            bevy::log::warn!("[apply_typed_mutation] Rejected {}: {}", mutation.component_type, reason);
+            return MutationStatus::Forbidden;
+        }
+    };
 
     // Check if this is a request to spawn a new entity
     if mutation.entity == SerializableEntity::DANGLING {
@@ -829,6 +1630,17 @@ where
 
     let entity = mutation.entity.to_entity();
 
+    // Enforce field-level read-only / redaction policy, if installed
+    let value = match crate::field_policy::enforce_field_policy::<T>(world, Some(entity), value) {
+        Ok(v) => v,
+        Err(reason) => {
+            if let Some(mut queue) = world.get_resource_mut::<MutationResponseQueue>() {
+                queue.respond_forbidden(mutation.connection_id, mutation.request_id, reason);
+            }
+            return;
+        }
+    };
+
     bevy::log::debug!(
         "[route_mutation_to_handler] Routing mutation to handler: entity={:?}, type={}, value={:?}",
         entity,
@@ -878,6 +1690,17 @@ where
 
     let entity = mutation.entity.to_entity();
 
+    // Enforce field-level read-only / redaction policy, if installed
+    let value = match crate::field_policy::enforce_field_policy::<T>(world, Some(entity), value) {
+        Ok(v) => v,
+        Err(reason) => {
+            if let Some(mut queue) = world.get_resource_mut::<MutationResponseQueue>() {
+                queue.respond_forbidden(mutation.connection_id, mutation.request_id, reason);
+            }
+            return;
+        }
+    };
+
     bevy::log::debug!(
         "[route_authorized_mutation_to_handler] Routing authorized mutation to handler: entity={:?}, type={}, value={:?}",
         entity,
@@ -904,17 +1727,72 @@ where
 
     // Use a temporary query to iterate all entities with this component type.
     let mut query = world.query::<(Entity, &T)>();
+    let policy = world.get_resource::<crate::field_policy::FieldPolicy<T>>();
     for (entity, component) in query.iter(world) {
-        // Serialize component directly to bincode bytes
-        let bytes = bincode::serde::encode_to_vec(component, bincode::config::standard())
-            .unwrap_or_default();
+        // Serialize component to bincode bytes (with redacted fields stripped)
+        let bytes = crate::field_policy::encode_for_wire(policy, component);
         results.push((SerializableEntity::from(entity), bytes));
     }
 
     results
 }
 
+fn snapshot_json_typed<T>(world: &mut World) -> Vec<(SerializableEntity, String)>
+where
+    T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
+{
+    let mut results = Vec::new();
+
+    let mut query = world.query::<(Entity, &T)>();
+    let policy = world.get_resource::<crate::field_policy::FieldPolicy<T>>();
+    for (entity, component) in query.iter(world) {
+        let json = match policy {
+            Some(policy) => serde_json::to_string(&policy.redact(component)),
+            None => serde_json::to_string(component),
+        };
+        match json {
+            Ok(json) => results.push((SerializableEntity::from(entity), json)),
+            Err(err) => bevy::log::warn!(
+                "[pl3xus_sync] Failed to encode {} as JSON: {}",
+                std::any::type_name::<T>(),
+                err
+            ),
+        }
+    }
+
+    results
+}
 
+fn read_typed<T>(world: &World, entity: Entity) -> Option<Vec<u8>>
+where
+    T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
+{
+    let component = world.get::<T>(entity)?;
+    let policy = world.get_resource::<crate::field_policy::FieldPolicy<T>>();
+    Some(crate::field_policy::encode_for_wire(policy, component))
+}
+
+fn read_json_typed<T>(world: &World, entity: Entity) -> Option<serde_json::Value>
+where
+    T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
+{
+    let component = world.get::<T>(entity)?;
+    let json = match world.get_resource::<crate::field_policy::FieldPolicy<T>>() {
+        Some(policy) => serde_json::to_value(policy.redact(component)),
+        None => serde_json::to_value(component),
+    };
+    json.ok()
+}
+
+fn insert_json_typed<T>(world: &mut World, entity: Entity, value: serde_json::Value) -> Result<(), String>
+where
+    T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
+{
+    let component: T = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    let mut entity = world.get_entity_mut(entity).map_err(|e| e.to_string())?;
+    entity.insert(component);
+    Ok(())
+}
 
 /// Helper used by [`AppPl3xusSyncExt::sync_component`] to register a type.
 #[cfg(feature = "runtime")]
@@ -922,22 +1800,45 @@ pub fn register_component<T>(app: &mut App, config: Option<ComponentSyncConfig>)
 where
     T: Component + serde::Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static + std::fmt::Debug + Clone,
 {
+    let max_update_rate_hz;
+    let has_handler;
+    let requires_auth;
+    let uses_default_policy;
+
+    // Use short type name (just the struct name, no module path) for stability
+    // This ensures client and server use the same type identifier
+    let full_type_name = std::any::type_name::<T>();
+    let type_name = full_type_name.rsplit("::").next().unwrap_or(full_type_name).to_string();
+    crate::schema::record_component::<T>(app, &type_name);
+
     // Register in SyncRegistry
     {
+        let default_read_only = app
+            .world()
+            .get_resource::<SyncPluginConfig>()
+            .is_some_and(|plugin| plugin.default_read_only);
         let mut registry = app.world_mut().get_resource_or_insert_with(SyncRegistry::default);
-        // Use short type name (just the struct name, no module path) for stability
-        // This ensures client and server use the same type identifier
-        let full_type_name = std::any::type_name::<T>();
-        let type_name = full_type_name.rsplit("::").next().unwrap_or(full_type_name).to_string();
-        let cfg = config.unwrap_or_default();
-        let has_handler = cfg.has_mutation_handler;
-        let requires_auth = cfg.requires_entity_authorization;
+        let cfg = config.unwrap_or_else(|| {
+            if default_read_only {
+                ComponentSyncConfig::read_only()
+            } else {
+                ComponentSyncConfig::default()
+            }
+        });
+        max_update_rate_hz = cfg.max_update_rate_hz;
+        has_handler = cfg.has_mutation_handler;
+        requires_auth = cfg.requires_entity_authorization;
+        uses_default_policy = requires_auth && cfg.use_default_entity_policy;
         registry.register_component(ComponentRegistration {
             type_id: std::any::TypeId::of::<T>(),
             type_name,
             config: cfg,
             apply_mutation: apply_typed_mutation::<T>,
             snapshot_all: snapshot_typed::<T>,
+            read_value: read_typed::<T>,
+            snapshot_json: snapshot_json_typed::<T>,
+            read_json: read_json_typed::<T>,
+            insert_json: insert_json_typed::<T>,
             route_to_handler: if has_handler && !requires_auth {
                 Some(route_mutation_to_handler::<T>)
             } else {
@@ -951,7 +1852,218 @@ where
         });
     }
 
+    // Mutations routed to a handler are written as messages, which are lost
+    // unless the message type was added
+    if has_handler {
+        let type_name = short_type_name::<T>();
+        crate::registration_checks::add_check(app, move |world| {
+            let (added, message) = if requires_auth {
+                (
+                    world.contains_resource::<bevy::ecs::message::Messages<AuthorizedComponentMutation<T>>>(),
+                    "AuthorizedComponentMutation",
+                )
+            } else {
+                (
+                    world.contains_resource::<bevy::ecs::message::Messages<ComponentMutation<T>>>(),
+                    "ComponentMutation",
+                )
+            };
+            (!added).then(|| {
+                format!(
+                    "Component {type_name} has a mutation handler, but {message}<{type_name}> was never added, \
+                     so mutations never reach it; register with sync_component_builder::<{type_name}>().with_handler(...) \
+                     or call app.add_message::<{message}<{type_name}>>()"
+                )
+            })
+        });
+    }
+    if uses_default_policy {
+        crate::registration_checks::require_default_entity_policy(
+            app,
+            format!("Component {}", short_type_name::<T>()),
+        );
+    }
+
     // Add the typed system that will emit change events for this component type.
-    crate::systems::register_component_system::<T>(app);
+    crate::systems::register_component_system::<T>(app, max_update_rate_hz);
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_common::ConnectionId;
+
+    fn update(subscription_id: u64, bits: u64, value: u8) -> SyncItem {
+        SyncItem::Update {
+            subscription_id,
+            entity: SerializableEntity { bits },
+            component_type: "Position".into(),
+            value: vec![value],
+        }
+    }
+
+    #[test]
+    fn test_conflation_per_entity_and_component() {
+        let client = ConnectionId { id: 2 };
+        let mut queue = ConflationQueue::new(30.0);
+
+        queue.enqueue(client, update(0, 1, 1), true);
+        // Same entity+component through another subscription
+        queue.enqueue(client, update(1, 1, 2), true);
+        queue.enqueue(client, update(0, 2, 3), true);
+        assert_eq!(queue.pending_count(client), 2);
+
+        // A removal drops the pending value; the re-added value comes after it
+        queue.enqueue(
+            client,
+            SyncItem::ComponentRemoved {
+                subscription_id: 0,
+                entity: SerializableEntity { bits: 2 },
+                component_type: "Position".into(),
+            },
+            true,
+        );
+        queue.enqueue(client, update(0, 2, 4), true);
+
+        let items = queue.drain_for_connection(client);
+        assert!(matches!(items[0], SyncItem::ComponentRemoved { .. }));
+        let values: HashSet<_> = items[1..]
+            .iter()
+            .map(|item| match item {
+                SyncItem::Update { value, .. } => value[0],
+                _ => panic!("expected an update"),
+            })
+            .collect();
+        assert_eq!(values, [2, 4].into());
+
+        assert_eq!(queue.stats.conflated, 2);
+        assert_eq!(queue.stats.by_connection[&client], 2);
+        assert_eq!(queue.stats.by_component["Position"], 2);
+    }
+
+    #[test]
+    fn test_backpressure_by_priority() {
+        let client = ConnectionId { id: 2 };
+        let policy = BackpressurePolicy::default();
+        let priority_of = |component: &str| match component {
+            "Console" => SyncPriority::Low,
+            "ExecutionState" => SyncPriority::High,
+            _ => SyncPriority::Normal,
+        };
+        let item = |component: &str| SyncItem::Update {
+            subscription_id: 0,
+            entity: SerializableEntity { bits: 1 },
+            component_type: component.into(),
+            value: Vec::new(),
+        };
+        let mut queue = ConflationQueue::new(30.0);
+        for component in ["Console", "Position", "ExecutionState"] {
+            queue.enqueue(client, item(component), true);
+        }
+
+        // Nearly full: only high priority goes out, the rest waits
+        let items = queue.drain_with_backpressure(client, 0.8, &policy, priority_of);
+        assert_eq!(items.len(), 1);
+        assert_eq!(queue.pending_count(client), 2);
+
+        // Full: low priority is dropped
+        assert!(queue.drain_with_backpressure(client, 0.95, &policy, priority_of).is_empty());
+        assert_eq!(queue.pending_count(client), 1);
+        assert_eq!(queue.stats.dropped, 1);
+
+        // Drained: the held value is sent
+        assert_eq!(queue.drain_with_backpressure(client, 0.0, &policy, priority_of).len(), 1);
+        assert!(queue.connections().is_empty());
+    }
+
+    #[derive(Component, serde::Serialize, serde::Deserialize, Clone, Debug)]
+    struct Gripper {
+        closed: bool,
+    }
+
+    #[derive(Component, serde::Serialize, serde::Deserialize, Clone, Debug)]
+    struct Setpoint {
+        value: f32,
+    }
+
+    #[test]
+    fn test_default_read_only() {
+        let mut app = App::new();
+        app.insert_resource(SyncPluginConfig {
+            default_read_only: true,
+            ..Default::default()
+        });
+        register_component::<Gripper>(&mut app, None);
+        register_component::<Setpoint>(&mut app, Some(ComponentSyncConfig::default()));
+
+        let registry = app.world().resource::<SyncRegistry>();
+        let mutable = |name: &str| {
+            registry
+                .components
+                .iter()
+                .find(|c| c.type_name == name)
+                .map(|c| c.config.allow_client_mutations)
+        };
+        assert_eq!(mutable("Gripper"), Some(false));
+        assert_eq!(mutable("Setpoint"), Some(true));
+    }
+
+    #[test]
+    fn test_sync_sequences_per_subscription() {
+        let alice = ConnectionId { id: 1 };
+        let bob = ConnectionId { id: 2 };
+        let mut sequences = SyncSequences::default();
+        let stamp = |sequences: &mut SyncSequences, connection_id, items| {
+            let mut batch = SyncBatch::new(items, false);
+            sequences.stamp(connection_id, &mut batch);
+            batch
+                .sequences
+                .iter()
+                .map(|s| (s.subscription_id, s.sequence))
+                .collect::<Vec<_>>()
+        };
+
+        // One number per subscription, however many items it has in the batch
+        let items = vec![update(1, 1, 0), update(1, 2, 0), update(2, 1, 0)];
+        assert_eq!(stamp(&mut sequences, alice, items), [(1, 1), (2, 1)]);
+        assert_eq!(stamp(&mut sequences, alice, vec![update(1, 1, 0)]), [(1, 2)]);
+        assert_eq!(stamp(&mut sequences, alice, vec![update(2, 1, 0)]), [(2, 2)]);
+        // Connections are numbered independently
+        assert_eq!(stamp(&mut sequences, bob, vec![update(1, 1, 0)]), [(1, 1)]);
+        assert_eq!(sequences.len(), 3);
+
+        sequences.remove_connection(alice);
+        assert_eq!(sequences.len(), 1);
+        assert_eq!(stamp(&mut sequences, alice, vec![update(1, 1, 0)]), [(1, 1)]);
+    }
+
+    #[test]
+    fn test_deferred_mutation_responses() {
+        let client = ConnectionId { id: 2 };
+        let mut queue = MutationResponseQueue::default();
+        let slow = queue.defer(client, Some(1));
+        let rejected = queue.defer(client, Some(2));
+        let forgotten = queue.defer(client, Some(3));
+        queue.respond_ok(client, Some(4));
+
+        // Nothing deferred has completed yet
+        let ids = |responses: &[PendingMutationResponse]| responses.iter().map(|r| r.request_id).collect::<Vec<_>>();
+        assert_eq!(ids(&queue.drain()), [Some(4)]);
+
+        std::thread::spawn(move || {
+            rejected.respond_error("Robot is busy").unwrap();
+            slow.respond_ok().unwrap();
+        })
+        .join()
+        .unwrap();
+        drop(forgotten);
+
+        let responses = queue.drain();
+        assert_eq!(ids(&responses), [Some(2), Some(1)]);
+        assert!(matches!(responses[0].status, MutationStatus::ValidationError));
+        assert_eq!(responses[0].message.as_deref(), Some("Robot is busy"));
+        assert!(matches!(responses[1].status, MutationStatus::Ok));
+        assert!(queue.drain().is_empty());
+    }
+}
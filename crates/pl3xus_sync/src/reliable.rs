@@ -0,0 +1,418 @@
+//! Opt-in reliable, ordered, exactly-once delivery for selected message types.
+//!
+//! Critical commands (Stop, Abort, ...) must not be lost when a connection
+//! drops, and must not run twice when the client resends them. Registering a
+//! message with `.reliable()` makes the server accept it wrapped in a
+//! [`ReliableEnvelope`]:
+//!
+//! ```rust,ignore
+//! app.message::<AbortMotion, NP>()
+//!    .targeted()
+//!    .with_default_entity_policy()
+//!    .reliable()
+//!    .register();
+//! ```
+//!
+//! The server delivers messages of each client session in sequence order,
+//! drops duplicates, and acknowledges with a cumulative [`ReliableAck`] once
+//! messages have been delivered. Buffered envelopes are not acknowledged until
+//! the gap before them is filled, so the sender keeps them and resends them if
+//! the session's state is lost in the meantime.
+//! Sessions are identified by the sender's `session_id` rather than the
+//! connection, so a client that reconnects and resends its unacknowledged
+//! messages gets each one handled exactly once. A session belongs to the
+//! connection that last used it: envelopes for a session owned by another
+//! connection that is still connected are dropped without an ack, so one
+//! client can't advance or read another's stream by reusing its id. Delivered messages are written
+//! as ordinary `NetworkData<T>` (or `NetworkData<TargetedMessage<T>>`) before
+//! authorization runs, so handlers and policies need no changes.
+//!
+//! Session state is kept for [`ReliableSettings::session_ttl`] after the last
+//! envelope, which bounds how long a client may stay away and still resend.
+//! A stream the server doesn't know (pruned, or from before a restart) starts
+//! at the first envelope it receives: everything before it was acknowledged,
+//! since the sender resends unacknowledged envelopes in order.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use pl3xus::managers::network::AppNetworkMessage;
+use pl3xus::{Network, NetworkData};
+use pl3xus_common::{ConnectionId, Pl3xusMessage};
+
+use crate::NetworkProvider;
+
+pub use pl3xus_common::{ReliableAck, ReliableEnvelope};
+
+/// System set in `PreUpdate` where reliable messages are delivered.
+///
+/// Authorization systems run after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReliableDelivery;
+
+/// Settings for reliable delivery.
+#[derive(Resource, Debug, Clone)]
+pub struct ReliableSettings {
+    /// How long to remember a session after its last envelope.
+    pub session_ttl: Duration,
+    /// Maximum number of out-of-order messages buffered per session and type.
+    /// Envelopes beyond this are dropped without an ack, so the sender resends them.
+    pub max_buffered: usize,
+}
+
+impl Default for ReliableSettings {
+    fn default() -> Self {
+        Self {
+            session_ttl: Duration::from_secs(600),
+            max_buffered: 64,
+        }
+    }
+}
+
+struct ReadyMessage {
+    source: ConnectionId,
+    provider_name: &'static str,
+    payload: Vec<u8>,
+}
+
+struct ReliableStream {
+    delivered: u64,
+    buffered: BTreeMap<u64, ReadyMessage>,
+    last_seen: Instant,
+}
+
+/// What became of one envelope.
+#[derive(Debug, PartialEq, Eq)]
+enum Receipt {
+    /// Delivered, buffered or a duplicate. Carries the stream's last delivered
+    /// sequence, which is acknowledged unless nothing has been delivered yet.
+    Accepted(u64),
+    /// The stream's buffer is full; the sender resends it later.
+    BufferFull,
+    /// The session belongs to another connection that is still connected.
+    NotOwner(ConnectionId),
+}
+
+/// Delivery state for all reliable message types.
+#[derive(Resource, Default)]
+pub struct ReliableInbox {
+    streams: HashMap<(u64, String), ReliableStream>,
+    /// Connection that last used each session.
+    owners: HashMap<u64, ConnectionId>,
+    ready: HashMap<String, Vec<ReadyMessage>>,
+    registered: HashSet<String>,
+}
+
+impl ReliableInbox {
+    /// Number of (session, message type) streams currently tracked.
+    pub fn stream_count(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Number of messages waiting for an earlier sequence number.
+    pub fn buffered_count(&self) -> usize {
+        self.streams.values().map(|s| s.buffered.len()).sum()
+    }
+
+    /// Accept `envelope` from `source`, moving every message that is now in
+    /// order to the ready list. `is_connected` tells whether a previous owner
+    /// of the session is still connected.
+    fn receive(
+        &mut self,
+        source: ConnectionId,
+        provider_name: &'static str,
+        envelope: &ReliableEnvelope,
+        max_buffered: usize,
+        is_connected: impl Fn(ConnectionId) -> bool,
+    ) -> Receipt {
+        match self.owners.get(&envelope.session_id) {
+            Some(&owner) if owner != source && is_connected(owner) => return Receipt::NotOwner(owner),
+            _ => {
+                self.owners.insert(envelope.session_id, source);
+            }
+        }
+
+        let stream = self
+            .streams
+            .entry((envelope.session_id, envelope.message_type.clone()))
+            .or_insert_with(|| ReliableStream {
+                // Unknown streams start at their first envelope
+                delivered: envelope.sequence.saturating_sub(1),
+                buffered: BTreeMap::new(),
+                last_seen: Instant::now(),
+            });
+        stream.last_seen = Instant::now();
+
+        let is_duplicate = envelope.sequence <= stream.delivered
+            || stream.buffered.contains_key(&envelope.sequence);
+        if is_duplicate {
+            debug!(
+                "[pl3xus_sync] Dropping duplicate reliable '{}' seq={} from {:?}",
+                envelope.message_type, envelope.sequence, source
+            );
+        } else if stream.buffered.len() >= max_buffered {
+            return Receipt::BufferFull;
+        } else {
+            stream.buffered.insert(
+                envelope.sequence,
+                ReadyMessage {
+                    source,
+                    provider_name,
+                    payload: envelope.payload.clone(),
+                },
+            );
+        }
+
+        // Move every message that is now in order to the ready list.
+        while let Some(message) = stream.buffered.remove(&(stream.delivered + 1)) {
+            stream.delivered += 1;
+            self.ready
+                .entry(envelope.message_type.clone())
+                .or_default()
+                .push(message);
+        }
+        Receipt::Accepted(stream.delivered)
+    }
+}
+
+/// Register reliable delivery for `M` under its wire name `message_type`.
+///
+/// `M` is the type handlers receive as `NetworkData<M>`; it must already be
+/// registered as a network message.
+pub(crate) fn register_reliable<M, NP>(app: &mut App, message_type: &'static str)
+where
+    M: Pl3xusMessage,
+    NP: NetworkProvider,
+{
+    let envelope_registered = app
+        .world()
+        .get_resource::<Network<NP>>()
+        .is_some_and(|net| net.is_message_registered(ReliableEnvelope::type_name()));
+
+    if !envelope_registered {
+        app.register_network_message::<ReliableEnvelope, NP>();
+        app.add_systems(
+            PreUpdate,
+            (receive_reliable_envelopes::<NP>, prune_reliable_sessions)
+                .chain()
+                .before(ReliableDelivery)
+                .in_set(crate::systems::SyncSet::Receive),
+        );
+    }
+
+    if !app.world().contains_resource::<ReliableSettings>() {
+        app.init_resource::<ReliableSettings>();
+    }
+    app.init_resource::<ReliableInbox>();
+    app.world_mut()
+        .resource_mut::<ReliableInbox>()
+        .registered
+        .insert(message_type.to_string());
+
+    app.add_systems(
+        PreUpdate,
+        deliver_reliable::<M>(message_type).in_set(ReliableDelivery),
+    );
+}
+
+fn receive_reliable_envelopes<NP: NetworkProvider>(
+    mut envelopes: MessageReader<NetworkData<ReliableEnvelope>>,
+    mut inbox: ResMut<ReliableInbox>,
+    settings: Res<ReliableSettings>,
+    net: Res<Network<NP>>,
+) {
+    for envelope in envelopes.read() {
+        let source = *envelope.source();
+        if !inbox.registered.contains(&envelope.message_type) {
+            warn!(
+                "[pl3xus_sync] Reliable envelope for unregistered message type '{}' from {:?}",
+                envelope.message_type, source
+            );
+            continue;
+        }
+
+        let delivered = match inbox.receive(source, envelope.provider_name(), envelope, settings.max_buffered, |owner| {
+            net.has_connection(owner)
+        }) {
+            // Nothing delivered yet: the envelope waits in the buffer unacknowledged
+            Receipt::Accepted(0) => continue,
+            Receipt::Accepted(delivered) => delivered,
+            Receipt::BufferFull => {
+                warn!(
+                    "[pl3xus_sync] Reliable buffer full for '{}' (session {}); waiting for a resend",
+                    envelope.message_type, envelope.session_id
+                );
+                continue;
+            }
+            Receipt::NotOwner(owner) => {
+                warn!(
+                    "[pl3xus_sync] Dropping reliable '{}' from {:?}: session {} belongs to {:?}",
+                    envelope.message_type, source, envelope.session_id, owner
+                );
+                continue;
+            }
+        }
+
+        let ack = ReliableAck {
+            session_id: envelope.session_id,
+            message_type: envelope.message_type.clone(),
+            sequence: delivered,
+        };
+        if let Err(e) = net.send(source, ack) {
+            debug!("[pl3xus_sync] Failed to ack reliable message to {:?}: {:?}", source, e);
+        }
+    }
+}
+
+fn prune_reliable_sessions(mut inbox: ResMut<ReliableInbox>, settings: Res<ReliableSettings>) {
+    let ttl = settings.session_ttl;
+    let inbox = &mut *inbox;
+    inbox.streams.retain(|_, stream| stream.last_seen.elapsed() < ttl);
+    let streams = &inbox.streams;
+    inbox
+        .owners
+        .retain(|session_id, _| streams.keys().any(|(session, _)| session == session_id));
+}
+
+fn deliver_reliable<M: Pl3xusMessage>(
+    message_type: &'static str,
+) -> impl FnMut(ResMut<ReliableInbox>, MessageWriter<NetworkData<M>>) {
+    move |mut inbox, mut writer| {
+        let Some(ready) = inbox.ready.get_mut(message_type) else {
+            return;
+        };
+        for message in ready.drain(..) {
+            match bincode::serde::decode_from_slice::<M, _>(&message.payload, bincode::config::standard()) {
+                Ok((inner, _)) => {
+                    writer.write(NetworkData::with_provider(
+                        &message.source,
+                        inner,
+                        message.provider_name,
+                    ));
+                }
+                Err(e) => warn!(
+                    "[pl3xus_sync] Failed to decode reliable '{}' from {:?}: {}",
+                    message_type, message.source, e
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    const STOP: &str = "app::Stop";
+
+    fn envelope(session_id: u64, sequence: u64) -> ReliableEnvelope {
+        ReliableEnvelope {
+            session_id,
+            sequence,
+            message_type: STOP.to_string(),
+            payload: vec![sequence as u8],
+        }
+    }
+
+    fn receive(inbox: &mut ReliableInbox, source: u32, envelope: ReliableEnvelope, live: &[u32]) -> Receipt {
+        inbox.receive(ConnectionId { id: source }, "test", &envelope, 4, |owner| live.contains(&owner.id))
+    }
+
+    fn ready(inbox: &mut ReliableInbox) -> Vec<u8> {
+        inbox
+            .ready
+            .get_mut(STOP)
+            .map(|ready| ready.drain(..).map(|message| message.payload[0]).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_delivers_in_order() {
+        let mut inbox = ReliableInbox::default();
+        assert_eq!(receive(&mut inbox, 1, envelope(7, 1), &[1]), Receipt::Accepted(1));
+        assert_eq!(ready(&mut inbox), vec![1]);
+
+        // Out-of-order envelopes wait, and are only acknowledged once delivered
+        assert_eq!(receive(&mut inbox, 1, envelope(7, 3), &[1]), Receipt::Accepted(1));
+        assert!(ready(&mut inbox).is_empty());
+        assert_eq!(inbox.buffered_count(), 1);
+
+        assert_eq!(receive(&mut inbox, 1, envelope(7, 2), &[1]), Receipt::Accepted(3));
+        assert_eq!(ready(&mut inbox), vec![2, 3]);
+        assert_eq!(inbox.buffered_count(), 0);
+    }
+
+    #[test]
+    fn test_drops_duplicates_and_limits_buffer() {
+        let mut inbox = ReliableInbox::default();
+
+        receive(&mut inbox, 1, envelope(7, 1), &[1]);
+        // Duplicates are acknowledged again but delivered once
+        assert_eq!(receive(&mut inbox, 1, envelope(7, 1), &[1]), Receipt::Accepted(1));
+        assert_eq!(ready(&mut inbox), vec![1]);
+
+        for sequence in 3..7 {
+            receive(&mut inbox, 1, envelope(7, sequence), &[1]);
+        }
+        assert_eq!(receive(&mut inbox, 1, envelope(7, 7), &[1]), Receipt::BufferFull);
+    }
+
+    #[test]
+    fn test_resend_after_reconnect() {
+        let mut inbox = ReliableInbox::default();
+
+        receive(&mut inbox, 1, envelope(7, 1), &[1]);
+        assert_eq!(ready(&mut inbox), vec![1]);
+
+        // Connection 1 dropped before seeing the ack; the client resends both
+        // of its unacknowledged messages on connection 2
+        assert_eq!(receive(&mut inbox, 2, envelope(7, 1), &[2]), Receipt::Accepted(1));
+        assert_eq!(receive(&mut inbox, 2, envelope(7, 2), &[2]), Receipt::Accepted(2));
+        assert_eq!(ready(&mut inbox), vec![2]);
+    }
+
+    #[test]
+    fn test_unknown_stream_starts_at_first_envelope() {
+        let mut inbox = ReliableInbox::default();
+        for sequence in 1..=3 {
+            receive(&mut inbox, 1, envelope(7, sequence), &[1]);
+        }
+        assert_eq!(ready(&mut inbox), vec![1, 2, 3]);
+
+        // The idle stream is pruned; the client keeps counting from 3
+        let mut world = World::new();
+        world.insert_resource(ReliableSettings {
+            session_ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        world.insert_resource(inbox);
+        world.run_system_once(prune_reliable_sessions).unwrap();
+        let mut inbox = world.remove_resource::<ReliableInbox>().unwrap();
+        assert_eq!(inbox.stream_count(), 0);
+
+        assert_eq!(receive(&mut inbox, 1, envelope(7, 4), &[1]), Receipt::Accepted(4));
+        assert_eq!(ready(&mut inbox), vec![4]);
+
+        // A server that restarted has never seen the session at all
+        let mut restarted = ReliableInbox::default();
+        assert_eq!(receive(&mut restarted, 2, envelope(7, 9), &[2]), Receipt::Accepted(9));
+        assert_eq!(ready(&mut restarted), vec![9]);
+    }
+
+    #[test]
+    fn test_rejects_session_of_other_live_connection() {
+        let mut inbox = ReliableInbox::default();
+        receive(&mut inbox, 1, envelope(7, 1), &[1, 2]);
+        ready(&mut inbox);
+
+        assert_eq!(
+            receive(&mut inbox, 2, envelope(7, 5), &[1, 2]),
+            Receipt::NotOwner(ConnectionId { id: 1 })
+        );
+        // The owner's stream wasn't advanced
+        assert_eq!(receive(&mut inbox, 1, envelope(7, 2), &[1, 2]), Receipt::Accepted(2));
+        assert_eq!(ready(&mut inbox), vec![2]);
+    }
+}
@@ -0,0 +1,107 @@
+//! Runtime schema introspection.
+//!
+//! Every component, message and request registered through pl3xus_sync is
+//! recorded in the [`SchemaRegistry`] with its serde shape. Clients send a
+//! [`DescribeSchema`] request to fetch it and compare against their own
+//! registrations, so a field rename or type change shows up as a warning
+//! instead of a silent decode failure.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use pl3xus::managers::NetworkProvider;
+use pl3xus::managers::network_request::Request;
+use pl3xus_common::{DescribeSchema, Pl3xusMessage, RequestMessage, RequestSchema, SchemaDescription, TypeSchema};
+use serde::de::DeserializeOwned;
+
+use crate::AppRequestRegistrationExt;
+
+/// Shapes of every type registered through pl3xus_sync, keyed by short name.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct SchemaRegistry {
+    pub components: BTreeMap<String, TypeSchema>,
+    pub messages: BTreeMap<String, TypeSchema>,
+    pub requests: BTreeMap<String, RequestSchema>,
+}
+
+impl SchemaRegistry {
+    /// The registered types as sent in response to [`DescribeSchema`].
+    pub fn describe(&self) -> SchemaDescription {
+        SchemaDescription {
+            components: self.components.values().cloned().collect(),
+            messages: self.messages.values().cloned().collect(),
+            requests: self.requests.values().cloned().collect(),
+        }
+    }
+}
+
+pub(crate) fn record_component<T: DeserializeOwned + 'static>(app: &mut App, name: &str) {
+    let mut registry = app.world_mut().get_resource_or_insert_with(SchemaRegistry::default);
+    if !registry.components.contains_key(name) {
+        registry.components.insert(name.to_string(), TypeSchema::of::<T>(name));
+    }
+}
+
+pub(crate) fn record_message<T: Pl3xusMessage>(app: &mut App) {
+    let mut registry = app.world_mut().get_resource_or_insert_with(SchemaRegistry::default);
+    let name = T::short_name();
+    if !registry.messages.contains_key(name) {
+        registry.messages.insert(name.to_string(), TypeSchema::of::<T>(name));
+    }
+}
+
+pub(crate) fn record_request<T: RequestMessage>(app: &mut App) {
+    let mut registry = app.world_mut().get_resource_or_insert_with(SchemaRegistry::default);
+    let name = T::request_name();
+    if !registry.requests.contains_key(name) {
+        registry.requests.insert(name.to_string(), RequestSchema::of::<T>());
+    }
+}
+
+/// Register [`DescribeSchema`] and its handler.
+pub(crate) fn register_describe_schema<NP: NetworkProvider>(app: &mut App) {
+    app.request::<DescribeSchema, NP>().register();
+    app.add_systems(Update, handle_describe_schema);
+}
+
+fn handle_describe_schema(mut requests: MessageReader<Request<DescribeSchema>>, registry: Res<SchemaRegistry>) {
+    if requests.is_empty() {
+        return;
+    }
+    let description = registry.describe();
+    for request in requests.read() {
+        if let Err(e) = request.clone().respond(description.clone()) {
+            warn!("[pl3xus_sync] Failed to respond to DescribeSchema: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pl3xus_common::TypeShape;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Jog {
+        axis: u8,
+        distance: f32,
+    }
+
+    #[test]
+    fn test_records_each_type_once() {
+        let mut app = App::new();
+        record_message::<Jog>(&mut app);
+        record_message::<Jog>(&mut app);
+        record_component::<Jog>(&mut app, "Jog");
+
+        let description = app.world().resource::<SchemaRegistry>().describe();
+        assert_eq!(description.messages.len(), 1);
+        assert_eq!(description.components.len(), 1);
+        assert_eq!(description.messages[0].name, "Jog");
+        assert!(matches!(
+            &description.messages[0].shape,
+            TypeShape::Struct { fields, .. } if fields.len() == 2
+        ));
+    }
+}
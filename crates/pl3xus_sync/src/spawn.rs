@@ -0,0 +1,459 @@
+//! Client requests to spawn and despawn entities of server-defined archetypes.
+//!
+//! Clients can mutate existing entities but not create new ones. With
+//! [`SpawnPlugin`], the server registers named archetypes: a bundle of
+//! components with default values and a policy choosing who may spawn it
+//! (nobody but the server until one is set with
+//! [`with_policy`](SpawnArchetype::with_policy)).
+//! Clients send a [`SpawnEntityRequest`], optionally overriding synced
+//! components of the bundle, and receive the new entity's bits, which is
+//! then synced like any other entity.
+//!
+//! Archetypes made [`despawnable`](SpawnArchetype::despawnable) can be
+//! removed again with a [`DespawnEntityRequest`]. The entity's children go
+//! with it, clients controlling any of them are told control was released,
+//! and subscribers see the usual despawn updates.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::spawn::{AppSpawnArchetypeExt, SpawnArchetype, SpawnPlugin};
+//!
+//! app.add_plugins(SpawnPlugin::<WebSocketProvider>::default())
+//!     .spawn_archetype(
+//!         "robot",
+//!         SpawnArchetype::new((RobotName::default(), RobotStatus::default()))
+//!             .with_policy(MessageAccessPolicy::from_fn(|world, source| is_operator(world, source)))
+//!             .despawnable(EntityAccessPolicy::from_fn(|world, source, entity| holds_control(world, source, entity)))
+//!             .invalidates(&["ListRobots"]),
+//!     );
+//! ```
+//!
+//! Overrides must name synced components that are part of the archetype and
+//! that clients may mutate; anything else fails the request and nothing is
+//! spawned.
+
+use std::collections::BTreeMap;
+
+use bevy::ecs::message::Messages;
+use bevy::prelude::*;
+use pl3xus::managers::network_request::Request;
+use pl3xus::managers::Network;
+use pl3xus_common::{
+    ConnectionId, ControlResponseKind, DespawnEntityRequest, DespawnEntityResponse, EntityControl, SpawnEntityRequest,
+    SpawnEntityResponse,
+};
+
+use crate::authorization::{AuthResult, EntityAccessPolicy, MessageAccessPolicy};
+use crate::messages::{MutationStatus, QueryInvalidation, SerializableEntity, SyncServerMessage};
+use crate::registry::{MutationOrigin, QueuedMutation, SyncRegistry};
+use crate::{AppRequestRegistrationExt, NetworkProvider};
+
+/// A named bundle clients may spawn.
+pub struct SpawnArchetype {
+    spawn: Box<dyn Fn(&mut World) -> Entity + Send + Sync>,
+    policy: MessageAccessPolicy,
+    despawn_policy: Option<EntityAccessPolicy>,
+    invalidates: Vec<String>,
+}
+
+impl SpawnArchetype {
+    /// Spawn clones of `bundle`. No client may spawn it until a policy is
+    /// set with [`with_policy`](Self::with_policy).
+    pub fn new<B: Bundle + Clone>(bundle: B) -> Self {
+        Self {
+            spawn: Box::new(move |world| world.spawn(bundle.clone()).id()),
+            policy: MessageAccessPolicy::server_only(),
+            despawn_policy: None,
+            invalidates: Vec::new(),
+        }
+    }
+
+    /// Choose which connections may spawn this archetype.
+    pub fn with_policy(mut self, policy: MessageAccessPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Let clients despawn entities of this archetype, where `policy` allows.
+    /// Without this, only the server can despawn them.
+    pub fn despawnable(mut self, policy: EntityAccessPolicy) -> Self {
+        self.despawn_policy = Some(policy);
+        self
+    }
+
+    /// Queries to invalidate on every client after an entity of this
+    /// archetype is spawned or despawned.
+    pub fn invalidates(mut self, query_types: &[&str]) -> Self {
+        self.invalidates.extend(query_types.iter().map(|query_type| query_type.to_string()));
+        self
+    }
+}
+
+/// Name of the archetype an entity was spawned from by a client request.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct SpawnedFrom(pub String);
+
+/// Archetypes clients may spawn, by name.
+#[derive(Resource, Default)]
+pub struct SpawnArchetypes {
+    archetypes: BTreeMap<String, SpawnArchetype>,
+}
+
+impl SpawnArchetypes {
+    /// Register an archetype, replacing any with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, archetype: SpawnArchetype) {
+        self.archetypes.insert(name.into(), archetype);
+    }
+
+    /// Names of the registered archetypes.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.archetypes.keys().map(String::as_str)
+    }
+}
+
+/// Extension trait for registering spawnable archetypes.
+pub trait AppSpawnArchetypeExt {
+    /// Let clients spawn `archetype` by `name`.
+    fn spawn_archetype(&mut self, name: impl Into<String>, archetype: SpawnArchetype) -> &mut Self;
+}
+
+impl AppSpawnArchetypeExt for App {
+    fn spawn_archetype(&mut self, name: impl Into<String>, archetype: SpawnArchetype) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(SpawnArchetypes::default)
+            .insert(name, archetype);
+        self
+    }
+}
+
+/// Plugin answering [`SpawnEntityRequest`]s from the [`SpawnArchetypes`].
+pub struct SpawnPlugin<NP: NetworkProvider> {
+    _marker: std::marker::PhantomData<NP>,
+}
+
+impl<NP: NetworkProvider> Default for SpawnPlugin<NP> {
+    fn default() -> Self {
+        Self {
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<NP: NetworkProvider> Plugin for SpawnPlugin<NP> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnArchetypes>();
+        app.request::<SpawnEntityRequest, NP>().register();
+        app.request::<DespawnEntityRequest, NP>().register();
+        app.add_systems(Update, (handle_spawn_requests::<NP>, handle_despawn_requests::<NP>));
+    }
+}
+
+fn handle_spawn_requests<NP: NetworkProvider>(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource_mut::<Messages<Request<SpawnEntityRequest>>>()
+        .drain()
+        .collect();
+
+    for request in requests {
+        let source = *request.source();
+        let response = match spawn_from_archetype(world, source, request.get_request()) {
+            Ok(entity) => {
+                info!(
+                    "[pl3xus_sync] {:?} spawned {:?} from archetype '{}'",
+                    source,
+                    entity,
+                    request.get_request().archetype
+                );
+                broadcast_invalidations::<NP>(world, &request.get_request().archetype);
+                SpawnEntityResponse {
+                    entity: Some(entity.to_bits()),
+                    error: None,
+                }
+            }
+            Err(error) => SpawnEntityResponse {
+                entity: None,
+                error: Some(error),
+            },
+        };
+        if let Err(e) = request.respond(response) {
+            warn!("[pl3xus_sync] Failed to respond to SpawnEntityRequest: {:?}", e);
+        }
+    }
+}
+
+fn handle_despawn_requests<NP: NetworkProvider>(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource_mut::<Messages<Request<DespawnEntityRequest>>>()
+        .drain()
+        .collect();
+
+    for request in requests {
+        let source = *request.source();
+        let response = match despawn_from_request(world, source, request.get_request()) {
+            Ok(despawned) => {
+                info!(
+                    "[pl3xus_sync] {:?} despawned {} entities of archetype '{}'",
+                    source,
+                    despawned.entities.len(),
+                    despawned.archetype
+                );
+                if let Some(net) = world.get_resource::<Network<NP>>() {
+                    for &(controller, entity) in &despawned.released {
+                        let _ = net.send(controller, crate::control::new_response(entity, ControlResponseKind::Released));
+                    }
+                }
+                broadcast_invalidations::<NP>(world, &despawned.archetype);
+                DespawnEntityResponse {
+                    despawned: despawned.entities.iter().map(|entity| entity.to_bits()).collect(),
+                    error: None,
+                }
+            }
+            Err(error) => DespawnEntityResponse {
+                despawned: Vec::new(),
+                error: Some(error),
+            },
+        };
+        if let Err(e) = request.respond(response) {
+            warn!("[pl3xus_sync] Failed to respond to DespawnEntityRequest: {:?}", e);
+        }
+    }
+}
+
+fn broadcast_invalidations<NP: NetworkProvider>(world: &World, archetype: &str) {
+    let Some(query_types) = world
+        .get_resource::<SpawnArchetypes>()
+        .and_then(|archetypes| archetypes.archetypes.get(archetype))
+        .map(|archetype| archetype.invalidates.clone())
+        .filter(|query_types| !query_types.is_empty())
+    else {
+        return;
+    };
+    if let Some(net) = world.get_resource::<Network<NP>>() {
+        net.broadcast(SyncServerMessage::QueryInvalidation(QueryInvalidation {
+            query_types,
+            keys: None,
+        }));
+    }
+}
+
+/// Spawn `request`'s archetype with its overrides applied, if `source` may.
+pub fn spawn_from_archetype(
+    world: &mut World,
+    source: ConnectionId,
+    request: &SpawnEntityRequest,
+) -> Result<Entity, String> {
+    let archetypes = world
+        .get_resource::<SpawnArchetypes>()
+        .ok_or("No archetypes can be spawned")?;
+    let Some(archetype) = archetypes.archetypes.get(&request.archetype) else {
+        return Err(format!(
+            "Unknown archetype '{}' (available: {})",
+            request.archetype,
+            archetypes.names().collect::<Vec<_>>().join(", ")
+        ));
+    };
+    if let AuthResult::Denied(reason) = archetype.policy.check(world, source) {
+        return Err(reason);
+    }
+
+    // Check the overrides against the registry before spawning anything
+    let mut overrides = Vec::with_capacity(request.overrides.len());
+    for component in &request.overrides {
+        let registration = world.get_resource::<SyncRegistry>().and_then(|registry| {
+            registry
+                .components
+                .iter()
+                .find(|registration| registration.type_name == component.component_type)
+        });
+        let Some(registration) = registration else {
+            return Err(format!("Component '{}' is not registered for sync", component.component_type));
+        };
+        if !registration.config.allow_client_mutations {
+            return Err(format!("Component '{}' can't be set by clients", component.component_type));
+        }
+        overrides.push((registration.type_id, registration.apply_mutation, component));
+    }
+
+    let entity = world.resource_scope(|world, archetypes: Mut<SpawnArchetypes>| {
+        (archetypes.archetypes[&request.archetype].spawn)(world)
+    });
+    world.entity_mut(entity).insert(SpawnedFrom(request.archetype.clone()));
+
+    for (type_id, apply, component) in overrides {
+        let in_archetype = world
+            .components()
+            .get_id(type_id)
+            .is_some_and(|id| world.entity(entity).contains_id(id));
+        if !in_archetype {
+            world.despawn(entity);
+            return Err(format!(
+                "Component '{}' is not part of archetype '{}'",
+                component.component_type, request.archetype
+            ));
+        }
+        let status = apply(
+            world,
+            &QueuedMutation {
+                connection_id: source,
+                request_id: None,
+                entity: SerializableEntity::from(entity),
+                component_type: component.component_type.clone(),
+                value: component.value.clone(),
+                idempotency_key: None,
+                origin: MutationOrigin::Mutate,
+            },
+        );
+        if !matches!(status, MutationStatus::Ok) {
+            world.despawn(entity);
+            return Err(format!(
+                "Invalid value for component '{}' ({:?})",
+                component.component_type, status
+            ));
+        }
+    }
+
+    Ok(entity)
+}
+
+struct Despawned {
+    archetype: String,
+    entities: Vec<Entity>,
+    /// Clients that controlled one of the entities, with the topmost entity
+    /// each controlled.
+    released: Vec<(ConnectionId, Entity)>,
+}
+
+fn despawn_from_request(
+    world: &mut World,
+    source: ConnectionId,
+    request: &DespawnEntityRequest,
+) -> Result<Despawned, String> {
+    let entity = Entity::try_from_bits(request.entity).ok_or("Invalid entity")?;
+    let Some(SpawnedFrom(archetype)) = world.get_entity(entity).ok().and_then(|e| e.get::<SpawnedFrom>()).cloned()
+    else {
+        return Err(format!("Entity {} was not spawned from an archetype", request.entity));
+    };
+    let policy = world
+        .get_resource::<SpawnArchetypes>()
+        .and_then(|archetypes| archetypes.archetypes.get(&archetype))
+        .and_then(|archetype| archetype.despawn_policy.as_ref());
+    let Some(policy) = policy else {
+        return Err(format!("Entities of archetype '{}' can't be despawned by clients", archetype));
+    };
+    if let AuthResult::Denied(reason) = policy.check(world, source, entity) {
+        return Err(reason);
+    }
+
+    // Despawning takes the children along, so collect them first
+    let mut entities = vec![entity];
+    let mut next = 0;
+    while let Some(&parent) = entities.get(next) {
+        if let Some(children) = world.get::<Children>(parent) {
+            entities.extend(children.iter());
+        }
+        next += 1;
+    }
+    let mut released = Vec::new();
+    for &entity in &entities {
+        if let Some(control) = world.get::<EntityControl>(entity)
+            && control.is_controlled()
+            && !released.iter().any(|&(client_id, _)| client_id == control.client.connection)
+        {
+            released.push((control.client.connection, entity));
+        }
+    }
+
+    world.despawn(entity);
+    Ok(Despawned {
+        archetype,
+        entities,
+        released,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppPl3xusSyncExt, ComponentSyncConfig};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+    struct RobotName(String);
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+    struct RobotStatus {
+        busy: bool,
+    }
+
+    #[test]
+    fn test_spawn_with_overrides() {
+        let mut app = App::new();
+        app.sync_component::<RobotName>(None)
+            .sync_component::<RobotStatus>(Some(ComponentSyncConfig::read_only()))
+            .spawn_archetype(
+                "robot",
+                SpawnArchetype::new((RobotName("robot".into()), RobotStatus::default()))
+                    .with_policy(MessageAccessPolicy::allow_all()),
+            )
+            .spawn_archetype("admin_robot", SpawnArchetype::new(RobotName::default()));
+        let client = ConnectionId { id: 3 };
+        let world = app.world_mut();
+
+        let request = SpawnEntityRequest::new("robot")
+            .with_override("RobotName", &RobotName("Cell 4".into()))
+            .unwrap();
+        let entity = spawn_from_archetype(world, client, &request).unwrap();
+        assert_eq!(world.get::<RobotName>(entity), Some(&RobotName("Cell 4".into())));
+        assert!(world.get::<RobotStatus>(entity).is_some());
+
+        // Read-only components, unknown archetypes and archetypes without a policy fail
+        let read_only = SpawnEntityRequest::new("robot")
+            .with_override("RobotStatus", &RobotStatus { busy: true })
+            .unwrap();
+        assert!(spawn_from_archetype(world, client, &read_only).is_err());
+        assert!(spawn_from_archetype(world, client, &SpawnEntityRequest::new("drone")).is_err());
+        assert!(spawn_from_archetype(world, client, &SpawnEntityRequest::new("admin_robot")).is_err());
+        assert_eq!(world.query::<&RobotName>().iter(world).count(), 1);
+    }
+
+    #[test]
+    fn test_despawn_with_children() {
+        let mut app = App::new();
+        app.sync_component::<RobotName>(None)
+            .spawn_archetype(
+                "robot",
+                SpawnArchetype::new(RobotName::default())
+                    .with_policy(MessageAccessPolicy::allow_all())
+                    .despawnable(EntityAccessPolicy::allow_all()),
+            )
+            .spawn_archetype(
+                "cell",
+                SpawnArchetype::new(RobotName::default()).with_policy(MessageAccessPolicy::allow_all()),
+            );
+        let client = ConnectionId { id: 3 };
+        let world = app.world_mut();
+
+        let robot = spawn_from_archetype(world, client, &SpawnEntityRequest::new("robot")).unwrap();
+        let controller = ConnectionId { id: 5 };
+        let tool = world
+            .spawn((
+                ChildOf(robot),
+                EntityControl {
+                    client: crate::SyncSession(1).identity(controller),
+                    ..Default::default()
+                },
+            ))
+            .id();
+        let despawned = despawn_from_request(world, client, &DespawnEntityRequest { entity: robot.to_bits() }).unwrap();
+        assert_eq!(despawned.entities, vec![robot, tool]);
+        assert_eq!(despawned.released, vec![(controller, tool)]);
+        assert!(world.get_entity(tool).is_err());
+
+        // Only despawnable archetypes, and only entities spawned from one
+        let cell = spawn_from_archetype(world, client, &SpawnEntityRequest::new("cell")).unwrap();
+        assert!(despawn_from_request(world, client, &DespawnEntityRequest { entity: cell.to_bits() }).is_err());
+        let other = world.spawn(RobotName::default()).id();
+        assert!(despawn_from_request(world, client, &DespawnEntityRequest { entity: other.to_bits() }).is_err());
+    }
+}
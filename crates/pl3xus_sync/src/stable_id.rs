@@ -0,0 +1,133 @@
+//! Stable string aliases for entities.
+//!
+//! Entity bits change across server restarts, so anything a client persists
+//! (bookmarks, dashboard layouts, configs) can't refer to entities by bits.
+//! Give such entities a [`StableId`] and add `StableIdPlugin`: the alias is
+//! synced read-only like any other component, and clients resolve it to the
+//! current entity with `use_stable_id("robot-1")` or
+//! `use_entity_by_stable_id::<T>("robot-1")` in `pl3xus_client`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use pl3xus_sync::stable_id::{StableId, StableIdPlugin};
+//!
+//! app.add_plugins(StableIdPlugin);
+//!
+//! fn spawn_robot(mut commands: Commands) {
+//!     commands.spawn((StableId::new("robot-1"), RobotStatus::default()));
+//! }
+//! ```
+//!
+//! Server systems can look aliases up in the [`StableIds`] resource.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::registry::ComponentSyncConfig;
+use crate::systems::SyncSet;
+use crate::AppPl3xusSyncExt;
+
+pub use pl3xus_common::StableId;
+
+/// Resource mapping each [`StableId`] to its entity.
+///
+/// Aliases must be unique. If two entities claim the same alias, the first
+/// one keeps it and the other is logged and left out.
+#[derive(Resource, Default, Debug)]
+pub struct StableIds {
+    entities: HashMap<String, Entity>,
+    aliases: HashMap<Entity, String>,
+}
+
+impl StableIds {
+    /// Get the entity currently carrying `id`.
+    pub fn get(&self, id: &str) -> Option<Entity> {
+        self.entities.get(id).copied()
+    }
+
+    /// Get the alias of `entity`.
+    pub fn alias(&self, entity: Entity) -> Option<&str> {
+        self.aliases.get(&entity).map(String::as_str)
+    }
+
+    /// Number of indexed aliases.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether no aliases are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    fn forget(&mut self, entity: Entity) {
+        if let Some(id) = self.aliases.remove(&entity) {
+            self.entities.remove(&id);
+        }
+    }
+}
+
+/// Plugin that syncs [`StableId`] to clients as a read-only component and
+/// keeps the [`StableIds`] index.
+#[derive(Default)]
+pub struct StableIdPlugin;
+
+impl Plugin for StableIdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StableIds>();
+        app.sync_component::<StableId>(Some(ComponentSyncConfig::read_only_with_message(
+            "StableId is managed by the server",
+        )));
+        app.add_systems(Update, index_stable_ids.before(SyncSet::DetectChanges));
+    }
+}
+
+fn index_stable_ids(
+    changed: Query<(Entity, &StableId), Changed<StableId>>,
+    mut removed: RemovedComponents<StableId>,
+    mut index: ResMut<StableIds>,
+) {
+    for entity in removed.read() {
+        index.forget(entity);
+    }
+    for (entity, id) in &changed {
+        index.forget(entity);
+        if let Some(owner) = index.get(id.as_str()) {
+            warn!(
+                "[pl3xus_sync] StableId '{}' of {:?} is already used by {:?}, ignoring it",
+                id, entity, owner
+            );
+            continue;
+        }
+        index.entities.insert(id.0.clone(), entity);
+        index.aliases.insert(entity, id.0.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_follows_components() {
+        let mut app = App::new();
+        app.init_resource::<StableIds>().add_systems(Update, index_stable_ids);
+
+        let robot = app.world_mut().spawn(StableId::new("robot-1")).id();
+        let duplicate = app.world_mut().spawn(StableId::new("robot-1")).id();
+        app.update();
+        assert_eq!(app.world().resource::<StableIds>().get("robot-1"), Some(robot));
+        assert_eq!(app.world().resource::<StableIds>().alias(duplicate), None);
+
+        app.world_mut().entity_mut(robot).insert(StableId::new("robot-2"));
+        app.update();
+        let index = app.world().resource::<StableIds>();
+        assert_eq!(index.get("robot-1"), None);
+        assert_eq!(index.get("robot-2"), Some(robot));
+
+        app.world_mut().despawn(robot);
+        app.update();
+        assert!(app.world().resource::<StableIds>().is_empty());
+    }
+}
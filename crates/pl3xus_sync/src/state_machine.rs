@@ -0,0 +1,356 @@
+//! State machine components with server-declared legal transitions.
+//!
+//! Components like `SystemState` are plain enums on the wire, so nothing stops
+//! a client from requesting `Running -> NoSource`. Registering the component
+//! with a [`SyncStateMachine`] declares which transitions are legal:
+//!
+//! ```rust,ignore
+//! use SystemState::*;
+//!
+//! app.sync_component_builder::<SystemState>()
+//!     .with_state_machine(
+//!         SyncStateMachine::new()
+//!             .transition(NoSource, Ready)
+//!             .transitions(Ready, [Running, NoSource])
+//!             .transitions(Running, [Paused, Completed, Stopped])
+//!             .transitions(Paused, [Running, Stopped])
+//!             .from_any(Error),
+//!     )
+//!     .build();
+//! ```
+//!
+//! States are compared by enum variant, so data carried by a variant does not
+//! need to match, and a mutation that stays in the same variant is always
+//! allowed. Client mutations requesting an illegal transition are rejected
+//! with a `ValidationError` naming the attempted transition, before they
+//! reach a handler. Server-side changes are not checked.
+//!
+//! Every change of variant, whatever its origin, is written as a
+//! [`StateTransitioned<T>`] message for server systems and sent to
+//! subscribed clients as a [`StateTransition`].
+
+use std::collections::HashMap;
+use std::mem::Discriminant;
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use pl3xus::Network;
+
+use crate::messages::{SerializableEntity, StateTransition, SyncServerMessage};
+use crate::registry::{SubscriptionManager, SyncRegistry};
+use crate::systems::SyncSet;
+use crate::NetworkProvider;
+
+type TransitionPredicate<T> = dyn Fn(&T, &T) -> bool + Send + Sync;
+
+enum TransitionRule<T> {
+    Variants {
+        from: Option<Discriminant<T>>,
+        to: Discriminant<T>,
+    },
+    Predicate(Arc<TransitionPredicate<T>>),
+}
+
+/// Legal transitions of a state machine component.
+pub struct SyncStateMachine<T> {
+    rules: Vec<TransitionRule<T>>,
+}
+
+impl<T> Default for SyncStateMachine<T> {
+    fn default() -> Self {
+        Self { rules: Vec::new() }
+    }
+}
+
+impl<T> SyncStateMachine<T> {
+    /// A state machine with no legal transitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow moving from the variant of `from` to the variant of `to`.
+    pub fn transition(mut self, from: T, to: T) -> Self {
+        self.rules.push(TransitionRule::Variants {
+            from: Some(std::mem::discriminant(&from)),
+            to: std::mem::discriminant(&to),
+        });
+        self
+    }
+
+    /// Allow moving from the variant of `from` to each variant in `to`.
+    pub fn transitions(mut self, from: T, to: impl IntoIterator<Item = T>) -> Self {
+        let from = std::mem::discriminant(&from);
+        for to in to {
+            self.rules.push(TransitionRule::Variants {
+                from: Some(from),
+                to: std::mem::discriminant(&to),
+            });
+        }
+        self
+    }
+
+    /// Allow moving to the variant of `to` from any state.
+    pub fn from_any(mut self, to: T) -> Self {
+        self.rules.push(TransitionRule::Variants {
+            from: None,
+            to: std::mem::discriminant(&to),
+        });
+        self
+    }
+
+    /// Allow every transition for which `predicate(from, to)` returns `true`.
+    pub fn allow<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&T, &T) -> bool + Send + Sync + 'static,
+    {
+        self.rules.push(TransitionRule::Predicate(Arc::new(predicate)));
+        self
+    }
+
+    /// Whether moving from `from` to `to` is legal.
+    pub fn is_allowed(&self, from: &T, to: &T) -> bool {
+        let from_variant = std::mem::discriminant(from);
+        let to_variant = std::mem::discriminant(to);
+        if from_variant == to_variant {
+            return true;
+        }
+        self.rules.iter().any(|rule| match rule {
+            TransitionRule::Variants { from, to } => {
+                from.is_none_or(|from| from == from_variant) && *to == to_variant
+            }
+            TransitionRule::Predicate(predicate) => predicate(from, to),
+        })
+    }
+}
+
+/// Type-erased transition check stored in a component's sync config.
+#[derive(Clone)]
+pub struct TransitionGuard {
+    inner: Arc<GuardFn>,
+}
+
+type GuardFn = dyn Fn(&World, Entity, &[u8]) -> Result<(), String> + Send + Sync;
+
+impl TransitionGuard {
+    /// Create a guard that checks mutations of `T` against `machine`.
+    pub fn new<T>(machine: SyncStateMachine<T>) -> Self
+    where
+        T: Component + for<'de> serde::Deserialize<'de> + std::fmt::Debug + Send + Sync + 'static,
+    {
+        let machine = Arc::new(machine);
+        Self {
+            inner: Arc::new(move |world: &World, entity: Entity, value: &[u8]| {
+                let Some(current) = world.get::<T>(entity) else {
+                    // Nothing to transition from: the mutation inserts the component.
+                    return Ok(());
+                };
+                let Ok((requested, _)) =
+                    bincode::serde::decode_from_slice::<T, _>(value, bincode::config::standard())
+                else {
+                    // Left to the apply/handler path, which reports decode errors.
+                    return Ok(());
+                };
+                if machine.is_allowed(current, &requested) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Illegal transition for {}: {:?} -> {:?}",
+                        crate::registry::short_type_name::<T>(),
+                        current,
+                        requested
+                    ))
+                }
+            }),
+        }
+    }
+
+    /// Check a mutation of `entity` to the encoded `value`.
+    pub fn check(&self, world: &World, entity: Entity, value: &[u8]) -> Result<(), String> {
+        (self.inner)(world, entity, value)
+    }
+}
+
+/// Written whenever a state machine component changes variant.
+#[derive(Message, Debug, Clone)]
+pub struct StateTransitioned<T: Send + Sync + 'static> {
+    pub entity: Entity,
+    pub from: T,
+    pub to: T,
+}
+
+/// Transitions waiting to be sent to subscribed clients.
+#[derive(Resource, Default)]
+pub(crate) struct PendingStateTransitions {
+    pending: Vec<StateTransition>,
+}
+
+/// Enable transition tracking for `T`. Called by `SyncComponentBuilder::with_state_machine`.
+pub(crate) fn enable_state_machine<T>(app: &mut App)
+where
+    T: Component + Clone + serde::Serialize + Send + Sync + 'static,
+{
+    app.init_resource::<PendingStateTransitions>();
+    app.add_message::<StateTransitioned<T>>();
+    app.add_systems(
+        Update,
+        track_state_transitions::<T>.in_set(SyncSet::DetectChanges),
+    );
+}
+
+fn track_state_transitions<T>(
+    changed: Query<(Entity, &T), Changed<T>>,
+    mut removed: RemovedComponents<T>,
+    mut last: Local<HashMap<Entity, T>>,
+    mut transitions: MessageWriter<StateTransitioned<T>>,
+    mut pending: ResMut<PendingStateTransitions>,
+    policy: Option<Res<crate::field_policy::FieldPolicy<T>>>,
+) where
+    T: Component + Clone + serde::Serialize + Send + Sync + 'static,
+{
+    for entity in removed.read() {
+        last.remove(&entity);
+    }
+
+    for (entity, current) in changed.iter() {
+        let Some(previous) = last.insert(entity, current.clone()) else {
+            continue;
+        };
+        if std::mem::discriminant(&previous) == std::mem::discriminant(current) {
+            continue;
+        }
+
+        pending.pending.push(StateTransition {
+            entity: SerializableEntity::from(entity),
+            component_type: crate::registry::short_type_name::<T>(),
+            from: crate::field_policy::encode_for_wire(policy.as_deref(), &previous),
+            to: crate::field_policy::encode_for_wire(policy.as_deref(), current),
+        });
+        transitions.write(StateTransitioned {
+            entity,
+            from: previous,
+            to: current.clone(),
+        });
+    }
+}
+
+/// Send queued transitions to every connection subscribed to the component.
+pub(crate) fn send_state_transitions<NP: NetworkProvider>(world: &mut World) {
+    let pending = match world.get_resource_mut::<PendingStateTransitions>() {
+        Some(mut queue) if !queue.pending.is_empty() => std::mem::take(&mut queue.pending),
+        _ => return,
+    };
+    let registry = world.get_resource::<SyncRegistry>();
+    let (Some(subscriptions), Some(net)) = (
+        world.get_resource::<SubscriptionManager>(),
+        world.get_resource::<Network<NP>>(),
+    ) else {
+        return;
+    };
+
+    for transition in pending {
+        let policy = registry.and_then(|registry| registry.visibility_policy(&transition.component_type));
+        let mut recipients: Vec<_> = subscriptions
+            .subscriptions
+            .iter()
+            .filter(|sub| sub.component_type == "*" || sub.component_type == transition.component_type)
+            .filter(|sub| sub.entity.is_none_or(|entity| entity == transition.entity))
+            .map(|sub| sub.connection_id)
+            .filter(|conn| {
+                policy.is_none_or(|policy| policy.is_visible(world, *conn, transition.entity.to_entity()))
+            })
+            .collect();
+        recipients.sort_by_key(|conn| conn.id);
+        recipients.dedup();
+
+        for conn in recipients {
+            let _ = net.send(conn, SyncServerMessage::StateTransition(transition.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::message::Messages;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+    enum SystemState {
+        NoSource,
+        Ready,
+        Running { line: u32 },
+        Paused,
+        Error,
+    }
+
+    fn machine() -> SyncStateMachine<SystemState> {
+        use SystemState::*;
+        SyncStateMachine::new()
+            .transition(NoSource, Ready)
+            .transitions(Ready, [Running { line: 0 }, NoSource])
+            .transition(Running { line: 0 }, Paused)
+            .from_any(Error)
+    }
+
+    fn encode(state: &SystemState) -> Vec<u8> {
+        bincode::serde::encode_to_vec(state, bincode::config::standard()).unwrap()
+    }
+
+    #[test]
+    fn test_transitions_compare_variants() {
+        use SystemState::*;
+        let machine = machine().allow(|from, to| matches!((from, to), (Paused, Running { .. })));
+
+        assert!(machine.is_allowed(&Ready, &Running { line: 7 }));
+        assert!(machine.is_allowed(&Running { line: 1 }, &Running { line: 2 }));
+        assert!(machine.is_allowed(&Running { line: 3 }, &Error));
+        assert!(machine.is_allowed(&Paused, &Running { line: 3 }));
+        assert!(!machine.is_allowed(&Running { line: 3 }, &NoSource));
+        assert!(!machine.is_allowed(&Error, &Running { line: 0 }));
+    }
+
+    #[test]
+    fn test_guard_rejects_illegal_mutations() {
+        let guard = TransitionGuard::new(machine());
+        let mut world = World::new();
+        let entity = world.spawn(SystemState::Running { line: 3 }).id();
+
+        assert!(guard.check(&world, entity, &encode(&SystemState::Paused)).is_ok());
+        let error = guard.check(&world, entity, &encode(&SystemState::NoSource)).unwrap_err();
+        assert_eq!(error, "Illegal transition for SystemState: Running { line: 3 } -> NoSource");
+
+        // Inserting the component, or an undecodable value, is left to the apply path
+        let empty = world.spawn_empty().id();
+        assert!(guard.check(&world, empty, &encode(&SystemState::NoSource)).is_ok());
+        assert!(guard.check(&world, entity, &[0xff, 0xff]).is_ok());
+    }
+
+    #[test]
+    fn test_variant_changes_are_tracked() {
+        let mut app = App::new();
+        app.init_resource::<PendingStateTransitions>()
+            .add_message::<StateTransitioned<SystemState>>()
+            .add_systems(Update, track_state_transitions::<SystemState>);
+        let entity = app.world_mut().spawn(SystemState::Ready).id();
+        app.update();
+
+        // Staying in the same variant isn't a transition
+        *app.world_mut().get_mut::<SystemState>(entity).unwrap() = SystemState::Running { line: 1 };
+        app.update();
+        *app.world_mut().get_mut::<SystemState>(entity).unwrap() = SystemState::Running { line: 2 };
+        app.update();
+
+        let transitions: Vec<_> = app
+            .world_mut()
+            .resource_mut::<Messages<StateTransitioned<SystemState>>>()
+            .drain()
+            .map(|t| (t.from, t.to))
+            .collect();
+        assert_eq!(transitions, [(SystemState::Ready, SystemState::Running { line: 1 })]);
+
+        let pending = &app.world().resource::<PendingStateTransitions>().pending;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].component_type, "SystemState");
+        assert_eq!(pending[0].to, encode(&SystemState::Running { line: 1 }));
+    }
+}
@@ -1,17 +1,25 @@
+use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
 use pl3xus::{managers::NetworkProvider, managers::Network, NetworkData, NetworkEvent};
 
 use crate::messages::{SyncClientMessage, SyncServerMessage, SyncBatch, SyncItem};
-use crate::registry::{ComponentChangeEvent, ComponentRemovedEvent, EntityDespawnEvent, MutationQueue, QueuedMutation, SnapshotQueue, SnapshotRequest, SubscriptionEntry, SubscriptionManager, SyncSettings, ConflationQueue};
+use crate::registry::{ComponentChangeEvent, ComponentRemovedEvent, EntityDespawnEvent, MutationOrigin, MutationQueue, QueuedMutation, SnapshotQueue, SnapshotRequest, SubscriptionEntry, SubscriptionManager, SyncRegistry, SyncPluginConfig, SyncSequences, SyncSettings, ConflationQueue, BackpressurePolicy};
+use crate::notifications::OutgoingNotification;
+use pl3xus_common::ServerNotification;
+
+/// Notification category of the warning sent when a client exceeds
+/// [`SyncPluginConfig::max_subscriptions_per_client`].
+pub const SUBSCRIPTION_LIMIT_CATEGORY: &str = "subscription_limit";
 
 /// System that reads incoming SyncClientMessage messages and updates the
 /// SubscriptionManager / dispatches actions accordingly.
-pub fn handle_client_messages<NP: NetworkProvider>(
+pub fn handle_client_messages(
     mut reader: MessageReader<NetworkData<SyncClientMessage>>,
     subscriptions: Option<ResMut<SubscriptionManager>>,
     mut mutations: Option<ResMut<MutationQueue>>,
     snapshots: Option<ResMut<SnapshotQueue>>,
-    _net: Option<Res<Network<NP>>>,
+    mut notifications: MessageWriter<OutgoingNotification>,
+    config: Option<Res<SyncPluginConfig>>,
 ) {
     // If the core sync resources are not yet available, this system should be
     // a no-op rather than causing a hard panic. Subscriptions and snapshots are
@@ -33,16 +41,40 @@ pub fn handle_client_messages<NP: NetworkProvider>(
 
     use crate::messages::SyncClientMessage as C;
 
+    let verbose = config.as_ref().is_some_and(|c| c.verbose_logging);
+    let max_subscriptions = config.as_ref().and_then(|c| c.max_subscriptions_per_client);
+
     for msg in reader.read() {
         let source = *msg.source();
         match &**msg {
             C::Subscription(req) => {
-                info!(
-                    "[pl3xus_sync] New subscription: conn={:?}, sub_id={}, component_type={}, entity={:?}",
+                if let Some(max) = max_subscriptions {
+                    // Re-subscribing with an existing id replaces that subscription
+                    let held = subscriptions
+                        .subscriptions
+                        .iter()
+                        .filter(|e| e.connection_id == source && e.subscription_id != req.subscription_id)
+                        .count();
+                    if held >= max {
+                        let reason = format!(
+                            "Subscription limit reached ({} per client), ignoring subscription to {}",
+                            max, req.component_type
+                        );
+                        warn!("[pl3xus_sync] {:?}: {}", source, reason);
+                        let notification = ServerNotification::warning(reason).with_category(SUBSCRIPTION_LIMIT_CATEGORY);
+                        notifications.write(OutgoingNotification::to(source, notification));
+                        continue;
+                    }
+                }
+
+                verbose_log!(
+                    verbose,
+                    "[pl3xus_sync] New subscription: conn={:?}, sub_id={}, component_type={}, entity={:?}, filter={:?}",
                     source,
                     req.subscription_id,
                     req.component_type,
                     req.entity,
+                    req.filter,
                 );
 
                 subscriptions.add_subscription(SubscriptionEntry {
@@ -50,6 +82,9 @@ pub fn handle_client_messages<NP: NetworkProvider>(
                     subscription_id: req.subscription_id,
                     component_type: req.component_type.clone(),
                     entity: req.entity,
+                    filter: req.filter.clone(),
+                    matched: Default::default(),
+                    hidden: Default::default(),
                 });
 
                 // Queue a snapshot request so the client receives an initial
@@ -59,9 +94,11 @@ pub fn handle_client_messages<NP: NetworkProvider>(
                     subscription_id: req.subscription_id,
                     component_type: req.component_type.clone(),
                     entity: req.entity,
+                    filter: req.filter.clone(),
                 });
 
-                info!(
+                verbose_log!(
+                    verbose,
                     "[pl3xus_sync] Queued snapshot request: conn={:?}, sub_id={}, component_type={}, entity={:?}",
                     source,
                     req.subscription_id,
@@ -82,6 +119,8 @@ pub fn handle_client_messages<NP: NetworkProvider>(
                         entity: m.entity,
                         component_type: m.component_type.clone(),
                         value: m.value.clone(),
+                        idempotency_key: m.idempotency_key,
+                        origin: MutationOrigin::Mutate,
                     });
                 } else {
                     trace!(
@@ -91,11 +130,11 @@ pub fn handle_client_messages<NP: NetworkProvider>(
                     );
                 }
             }
-            C::Query(_q) => {
-                // Query handling is deferred to v1.1; for now, this is a no-op.
+            C::Query(_) | C::QueryCancel(_) => {
+                // Handled by `live_query::run_live_queries` for each registered query.
             }
-            C::QueryCancel(_c) => {
-                // Likewise, query cancellation behavior will be implemented later.
+            C::Undo(_) | C::Redo(_) => {
+                // Handled by `undo::handle_undo_requests`, which owns the history.
             }
         }
     }
@@ -104,32 +143,64 @@ pub fn handle_client_messages<NP: NetworkProvider>(
 /// System that takes aggregated ComponentChangeEvent, ComponentRemovedEvent, and EntityDespawnEvent items
 /// and routes them to all interested subscribers.
 ///
-/// If conflation is enabled, items are queued in the ConflationQueue and will be
-/// sent later by flush_conflation_queue. Otherwise, they are sent immediately.
+/// Updates and removals are filtered through the component's
+/// [`VisibilityPolicy`](crate::registry::VisibilityPolicy), if any. This is an
+/// exclusive system so that policies can inspect the world. Policies are
+/// checked when the component changes; the first change that hides it from a
+/// connection sends a `ComponentRemoved` so the client drops the stale value,
+/// and the first change that shows it again sends the update. Updates for
+/// subscriptions with a [`SubscriptionFilter`](crate::SubscriptionFilter) are
+/// only sent while the entity matches, plus the update that makes it stop
+/// matching.
+///
+/// Items go through the ConflationQueue, so repeated values for the same
+/// entity+component are sent once per connection. With a rate limit they are
+/// sent later by flush_conflation_queue; otherwise immediately.
 pub fn broadcast_component_changes<NP: NetworkProvider>(
-    mut component_events: MessageReader<ComponentChangeEvent>,
-    mut removal_events: MessageReader<ComponentRemovedEvent>,
-    mut despawn_events: MessageReader<EntityDespawnEvent>,
-    subscriptions: Option<Res<SubscriptionManager>>,
-    settings: Option<Res<SyncSettings>>,
-    mut conflation_queue: Option<ResMut<ConflationQueue>>,
-    net: Option<Res<Network<NP>>>,
+    world: &mut World,
+    readers: &mut SystemState<(
+        MessageReader<ComponentChangeEvent>,
+        MessageReader<ComponentRemovedEvent>,
+        MessageReader<EntityDespawnEvent>,
+    )>,
 ) {
+    let (changes, removals, despawns) = {
+        let (mut component_events, mut removal_events, mut despawn_events) = readers.get_mut(world);
+        (
+            component_events.read().cloned().collect::<Vec<_>>(),
+            removal_events.read().cloned().collect::<Vec<_>>(),
+            despawn_events.read().cloned().collect::<Vec<_>>(),
+        )
+    };
+
     // If the required resources aren't available yet (for example, if the
     // network has been torn down during shutdown), bail out quietly.
-    let Some(subscriptions) = subscriptions else {
+    let Some(subscriptions) = world.get_resource::<SubscriptionManager>() else {
         return;
     };
 
-    if component_events.is_empty() && removal_events.is_empty() && despawn_events.is_empty() {
+    if changes.is_empty() && removals.is_empty() && despawns.is_empty() {
         return;
     }
 
-    // Determine if we should use conflation
-    let use_conflation = settings
-        .as_ref()
-        .map(|s| s.enable_message_conflation && s.max_update_rate_hz.is_some())
-        .unwrap_or(false);
+    let registry = world.get_resource::<SyncRegistry>();
+    let policy_for =
+        |component_type: &str| registry.and_then(|registry| registry.visibility_policy(component_type));
+    let read_json: std::collections::HashMap<_, _> = registry
+        .map(|registry| {
+            registry
+                .components
+                .iter()
+                .map(|reg| (reg.type_name.clone(), reg.read_json))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // (connection, subscription, entity, matches) for filtered subscriptions,
+    // applied to the SubscriptionManager once we're done reading it.
+    let mut filter_updates = Vec::new();
+    // (connection, subscription, entity, component type, hidden), likewise
+    let mut hidden_updates = Vec::new();
 
     // For v1 we use a simple O(N*M) strategy: for each change, scan
     // subscriptions. This is sufficient to validate the pipeline and can be
@@ -138,7 +209,9 @@ pub fn broadcast_component_changes<NP: NetworkProvider>(
         std::collections::HashMap::new();
 
     // Process component changes
-    for change in component_events.read() {
+    for change in &changes {
+        let json = std::cell::OnceCell::new();
+        let policy = policy_for(&change.component_type);
         for sub in &subscriptions.subscriptions {
             if sub.component_type != "*" && sub.component_type != change.component_type {
                 continue;
@@ -148,6 +221,47 @@ pub fn broadcast_component_changes<NP: NetworkProvider>(
                     continue;
                 }
             }
+            if let Some(policy) = policy {
+                let visible = policy.is_visible(world, sub.connection_id, change.entity.to_entity());
+                let key = (change.entity, change.component_type.clone());
+                let was_hidden = sub.hidden.contains(&key);
+                if visible == was_hidden {
+                    hidden_updates.push((sub.connection_id, sub.subscription_id, key.0, key.1, !visible));
+                }
+                if !visible {
+                    // The client may still hold the value from before it was
+                    // hidden; tell it once to drop it.
+                    if !was_hidden {
+                        per_connection
+                            .entry(sub.connection_id)
+                            .or_default()
+                            .push(SyncItem::ComponentRemoved {
+                                subscription_id: sub.subscription_id,
+                                entity: change.entity,
+                                component_type: change.component_type.clone(),
+                            });
+                    }
+                    continue;
+                }
+            }
+            if let Some(filter) = &sub.filter {
+                let json = json.get_or_init(|| {
+                    read_json
+                        .get(&change.component_type)
+                        .and_then(|read| read(world, change.entity.to_entity()))
+                });
+                // If the value can't be read (e.g. despawned since the change
+                // was observed), send it unfiltered and let the client filter.
+                if let Some(json) = json {
+                    let matches = filter.matches(json, change.entity);
+                    filter_updates.push((sub.connection_id, sub.subscription_id, change.entity, matches));
+                    // An entity that stops matching gets one last update so the
+                    // client sees the non-matching value and drops it locally.
+                    if !matches && !sub.matched.contains(&change.entity) {
+                        continue;
+                    }
+                }
+            }
 
             per_connection
                 .entry(sub.connection_id)
@@ -162,7 +276,8 @@ pub fn broadcast_component_changes<NP: NetworkProvider>(
     }
 
     // Process component removals (component removed but entity still exists)
-    for removal in removal_events.read() {
+    for removal in &removals {
+        let policy = policy_for(&removal.component_type);
         for sub in &subscriptions.subscriptions {
             // Match by component type and entity
             if sub.component_type != "*" && sub.component_type != removal.component_type {
@@ -173,6 +288,18 @@ pub fn broadcast_component_changes<NP: NetworkProvider>(
                     continue;
                 }
             }
+            let key = (removal.entity, removal.component_type.clone());
+            if sub.hidden.contains(&key) {
+                // Already dropped by the client when it was hidden
+                hidden_updates.push((sub.connection_id, sub.subscription_id, key.0, key.1, false));
+                continue;
+            }
+            if policy.is_some_and(|policy| !policy.is_visible(world, sub.connection_id, removal.entity.to_entity())) {
+                continue;
+            }
+            if sub.filter.is_some() {
+                filter_updates.push((sub.connection_id, sub.subscription_id, removal.entity, false));
+            }
 
             per_connection
                 .entry(sub.connection_id)
@@ -186,7 +313,7 @@ pub fn broadcast_component_changes<NP: NetworkProvider>(
     }
 
     // Process entity despawns
-    for despawn in despawn_events.read() {
+    for despawn in &despawns {
         for sub in &subscriptions.subscriptions {
             // Entity despawns match all subscriptions for that entity
             if let Some(entity) = sub.entity {
@@ -194,6 +321,12 @@ pub fn broadcast_component_changes<NP: NetworkProvider>(
                     continue;
                 }
             }
+            if sub.filter.is_some() {
+                filter_updates.push((sub.connection_id, sub.subscription_id, despawn.entity, false));
+            }
+            for (entity, component_type) in sub.hidden.iter().filter(|(entity, _)| *entity == despawn.entity) {
+                hidden_updates.push((sub.connection_id, sub.subscription_id, *entity, component_type.clone(), false));
+            }
 
             per_connection
                 .entry(sub.connection_id)
@@ -205,27 +338,101 @@ pub fn broadcast_component_changes<NP: NetworkProvider>(
         }
     }
 
-    // Either queue items for later (with conflation) or send immediately
-    if use_conflation {
-        // Queue items in the conflation queue
-        if let Some(ref mut queue) = conflation_queue {
-            let enable_conflation = settings.as_ref().unwrap().enable_message_conflation;
-            for (connection_id, items) in per_connection {
-                for item in items {
-                    queue.enqueue(connection_id, item, enable_conflation);
-                }
-            }
+    if !filter_updates.is_empty() || !hidden_updates.is_empty() {
+        let mut subscriptions = world.resource_mut::<SubscriptionManager>();
+        for (connection_id, subscription_id, entity, matches) in filter_updates {
+            subscriptions.set_matched(connection_id, subscription_id, entity, matches);
         }
+        for (connection_id, subscription_id, entity, component_type, hidden) in hidden_updates {
+            subscriptions.set_hidden(connection_id, subscription_id, entity, component_type, hidden);
+        }
+    }
+
+    let (enable_conflation, rate_limited, include_timestamps, backpressure) = world
+        .get_resource::<SyncSettings>()
+        .map(|s| {
+            (
+                s.enable_message_conflation,
+                s.max_update_rate_hz.is_some(),
+                s.include_timestamps,
+                s.backpressure.clone(),
+            )
+        })
+        .unwrap_or_else(|| (false, false, false, BackpressurePolicy::disabled()));
+    // With a rate limit and conflation, flush_conflation_queue sends queued
+    // items on its timer; otherwise they are sent at the end of this system
+    let send_now = !(enable_conflation && rate_limited);
+    let priorities = if send_now {
+        world
+            .get_resource::<SyncRegistry>()
+            .map(|registry| registry.priorities())
+            .unwrap_or_default()
     } else {
-        // Send immediately (original behavior)
-        if let Some(net) = net {
-            for (connection_id, items) in per_connection {
-                if items.is_empty() {
-                    continue;
-                }
-                let batch = SyncBatch { items };
-                let _ = net.send(connection_id, SyncServerMessage::SyncBatch(batch));
-            }
+        Default::default()
+    };
+    // How full each connection's outgoing channel is, including those with
+    // values held back on an earlier frame
+    let held = world
+        .get_resource::<ConflationQueue>()
+        .filter(|_| send_now)
+        .map(|queue| queue.connections())
+        .unwrap_or_default();
+    let fills: std::collections::HashMap<_, _> = world
+        .get_resource::<Network<NP>>()
+        .filter(|_| send_now)
+        .map(|net| {
+            per_connection
+                .keys()
+                .chain(&held)
+                .filter_map(|connection_id| Some((*connection_id, net.send_queue_fill(*connection_id)?)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Queue items so that repeated values for the same entity+component are
+    // conflated, per connection
+    let Some(mut queue) = world.get_resource_mut::<ConflationQueue>() else {
+        return;
+    };
+    for (connection_id, items) in per_connection {
+        for item in items {
+            queue.enqueue(connection_id, item, enable_conflation);
+        }
+    }
+
+    if !send_now {
+        return;
+    }
+
+    // Send along with values held back earlier because the connection was
+    // falling behind
+    let items: Vec<_> = queue
+        .connections()
+        .into_iter()
+        .map(|connection_id| {
+            let fill = fills.get(&connection_id).copied().unwrap_or_default();
+            let items = queue.drain_with_backpressure(connection_id, fill, &backpressure, |component| {
+                priorities.get(component).copied().unwrap_or_default()
+            });
+            (connection_id, items)
+        })
+        .collect();
+    let Some(mut sequences) = world.get_resource_mut::<SyncSequences>() else {
+        return;
+    };
+    let batches: Vec<_> = items
+        .into_iter()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(connection_id, items)| {
+            let mut batch = SyncBatch::new(items, include_timestamps);
+            sequences.stamp(connection_id, &mut batch);
+            (connection_id, batch)
+        })
+        .collect();
+
+    if let Some(net) = world.get_resource::<Network<NP>>() {
+        for (connection_id, batch) in batches {
+            let _ = net.send(connection_id, SyncServerMessage::SyncBatch(batch));
         }
     }
 }
@@ -256,7 +463,7 @@ pub fn cleanup_disconnected(
                 let after_count = mutations.pending.len();
                 info!("[pl3xus_sync] Removed {} pending mutations for {:?}", before_count - after_count, connection_id);
             }
-            NetworkEvent::Connected(connection_id) => {
+            NetworkEvent::Connected(connection_id, _) => {
                 info!("[pl3xus_sync] New connection: {:?}", connection_id);
             }
             _ => {}
@@ -265,3 +472,116 @@ pub fn cleanup_disconnected(
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{register_component, ComponentSyncConfig, VisibilityPolicy};
+    use crate::messages::SerializableEntity;
+    use bevy::ecs::message::Messages;
+    use pl3xus_common::ConnectionId;
+    use pl3xus_websockets::WebSocketProvider;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Clone, Debug)]
+    struct Position(f32);
+
+    #[derive(Component)]
+    struct Owner(ConnectionId);
+
+    const ALICE: ConnectionId = ConnectionId { id: 1 };
+    const BOB: ConnectionId = ConnectionId { id: 2 };
+
+    /// A world where each connection only sees the `Position`s it owns, with
+    /// both connections subscribed to `Position`.
+    fn owned_positions() -> World {
+        let mut setup = App::new();
+        register_component::<Position>(
+            &mut setup,
+            Some(ComponentSyncConfig::default().with_visibility(VisibilityPolicy::from_fn(
+                |world, conn, entity| world.get::<Owner>(entity).is_some_and(|owner| owner.0 == conn),
+            ))),
+        );
+
+        let mut world = World::new();
+        world.insert_resource(setup.world_mut().remove_resource::<SyncRegistry>().unwrap());
+        world.init_resource::<Messages<ComponentChangeEvent>>();
+        world.init_resource::<Messages<ComponentRemovedEvent>>();
+        world.init_resource::<Messages<EntityDespawnEvent>>();
+        world.init_resource::<SyncSettings>();
+        world.insert_resource(ConflationQueue::new(30.0));
+        let mut subscriptions = SubscriptionManager::default();
+        for (connection_id, subscription_id) in [(ALICE, 1), (BOB, 2)] {
+            subscriptions.add_subscription(SubscriptionEntry {
+                connection_id,
+                subscription_id,
+                component_type: "Position".into(),
+                entity: None,
+                filter: None,
+                matched: Default::default(),
+                hidden: Default::default(),
+            });
+        }
+        world.insert_resource(subscriptions);
+        world
+    }
+
+    fn change(world: &mut World, entity: Entity) {
+        world.write_message(ComponentChangeEvent {
+            entity: SerializableEntity::from(entity),
+            component_type: "Position".into(),
+            value: vec![1],
+        });
+    }
+
+    fn drain(world: &mut World, connection_id: ConnectionId) -> Vec<SyncItem> {
+        world.resource_mut::<ConflationQueue>().drain_for_connection(connection_id)
+    }
+
+    #[test]
+    fn test_visibility_hides_and_removes_once() {
+        let mut world = owned_positions();
+        let mut readers = SystemState::new(&mut world);
+        let robot = world.spawn((Position(0.0), Owner(ALICE))).id();
+
+        change(&mut world, robot);
+        broadcast_component_changes::<WebSocketProvider>(&mut world, &mut readers);
+        assert!(matches!(drain(&mut world, ALICE)[..], [SyncItem::Update { .. }]));
+        // Bob is told to drop any value he held, once
+        assert!(matches!(drain(&mut world, BOB)[..], [SyncItem::ComponentRemoved { .. }]));
+
+        change(&mut world, robot);
+        broadcast_component_changes::<WebSocketProvider>(&mut world, &mut readers);
+        assert_eq!(drain(&mut world, ALICE).len(), 1);
+        assert!(drain(&mut world, BOB).is_empty());
+
+        // Ownership moves to Bob: Alice drops the value, Bob gets it
+        world.entity_mut(robot).insert(Owner(BOB));
+        change(&mut world, robot);
+        broadcast_component_changes::<WebSocketProvider>(&mut world, &mut readers);
+        assert!(matches!(drain(&mut world, ALICE)[..], [SyncItem::ComponentRemoved { .. }]));
+        assert!(matches!(drain(&mut world, BOB)[..], [SyncItem::Update { .. }]));
+
+        let hidden = |world: &World, connection_id| {
+            world
+                .resource::<SubscriptionManager>()
+                .subscriptions
+                .iter()
+                .find(|sub| sub.connection_id == connection_id)
+                .map(|sub| sub.hidden.len())
+        };
+        assert_eq!(hidden(&world, ALICE), Some(1));
+        assert_eq!(hidden(&world, BOB), Some(0));
+
+        // Removing a hidden component sends nothing to the connection it was
+        // hidden from, and forgets it
+        world.write_message(ComponentRemovedEvent {
+            entity: SerializableEntity::from(robot),
+            component_type: "Position".into(),
+        });
+        broadcast_component_changes::<WebSocketProvider>(&mut world, &mut readers);
+        assert!(drain(&mut world, ALICE).is_empty());
+        assert_eq!(drain(&mut world, BOB).len(), 1);
+        assert_eq!(hidden(&world, ALICE), Some(0));
+    }
+}
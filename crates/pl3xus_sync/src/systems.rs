@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use serde::Serialize;
 
-use pl3xus::{managers::Network, managers::NetworkProvider, NetworkEvent};
+use pl3xus::{managers::Network, managers::NetworkProvider, IdempotencyCheck, NetworkEvent};
 
 use crate::authorization::{AuthResult, DefaultEntityAccessPolicy};
 use crate::messages::{
@@ -11,6 +11,7 @@ use crate::messages::{
     SyncItem,
     SyncServerMessage,
     WelcomeMessage,
+    SYNC_PROTOCOL_VERSION,
 };
 use crate::registry::{
     ComponentChangeEvent,
@@ -18,6 +19,7 @@ use crate::registry::{
     EntityDespawnEvent,
     MutationAuthContext,
     MutationAuthorizerResource,
+    MutationIdempotency,
     MutationQueue,
     MutationResponseQueue,
     QueuedMutation,
@@ -25,13 +27,32 @@ use crate::registry::{
     SubscriptionManager,
     SyncRegistry,
     SyncSettings,
+    SyncPluginConfig,
+    SyncSequences,
+    SyncSession,
     ConflationQueue,
     short_type_name,
+    validation_failed_message,
 };
 use crate::subscription::{broadcast_component_changes, handle_client_messages};
+use crate::notifications::{
+    dispatch_notifications,
+    handle_notification_acks,
+    handle_notification_preferences,
+    redeliver_pending_notifications,
+    ClientNotificationPreferences,
+    ClientRoles,
+    NotificationAcknowledged,
+    OutgoingNotification,
+    PendingNotifications,
+};
 
 /// System set for sync-related systems so downstream apps can schedule around
 /// them if needed.
+///
+/// Coarse grouping of the [`SyncSet`] stages in `Update`: `Inbound` holds
+/// `Receive`, `Authorize` and `ApplyMutations`, `Observe` holds
+/// `DetectChanges` and `Outbound` holds `Broadcast`.
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Pl3xusSyncSystems {
     /// Systems that read client messages and update subscriptions/mutations.
@@ -42,10 +63,73 @@ pub enum Pl3xusSyncSystems {
     Outbound,
 }
 
+/// Stages of the sync pipeline, run in this order every frame.
+///
+/// Order application systems against a stage instead of racing the sync
+/// systems in `Update`:
+///
+/// ```rust,ignore
+/// app.add_systems(Update, (
+///     // Sees this frame's client mutations
+///     react_to_jog_settings.after(SyncSet::ApplyMutations).before(SyncSet::DetectChanges),
+///     // Changes made here reach clients in the same frame
+///     update_robot_position.before(SyncSet::DetectChanges),
+/// ));
+/// ```
+///
+/// `Receive` and `Authorize` are also configured in `PreUpdate`, where
+/// network messages are received and message and request policies are
+/// checked, so handlers in `Update` always see authorized messages.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncSet {
+    /// Read client messages: subscriptions, mutations, acks, connection events.
+    Receive,
+    /// Check access policies for incoming messages and requests.
+    Authorize,
+    /// Apply client mutations to the world or route them to handlers.
+    ApplyMutations,
+    /// Turn component changes and despawns into sync events.
+    DetectChanges,
+    /// Send snapshots, updates, responses and notifications to clients.
+    Broadcast,
+}
+
+/// Order the [`SyncSet`] stages in `PreUpdate` and `Update`.
+fn configure_sync_sets(app: &mut App) {
+    app.configure_sets(PreUpdate, (SyncSet::Receive, SyncSet::Authorize).chain())
+        .configure_sets(PreUpdate, crate::reliable::ReliableDelivery.in_set(SyncSet::Receive))
+        .configure_sets(
+            Update,
+            (
+                Pl3xusSyncSystems::Inbound,
+                Pl3xusSyncSystems::Observe,
+                Pl3xusSyncSystems::Outbound,
+            )
+                .chain(),
+        )
+        .configure_sets(
+            Update,
+            (
+                (SyncSet::Receive, SyncSet::Authorize, SyncSet::ApplyMutations)
+                    .chain()
+                    .in_set(Pl3xusSyncSystems::Inbound),
+                SyncSet::DetectChanges.in_set(Pl3xusSyncSystems::Observe),
+                SyncSet::Broadcast.in_set(Pl3xusSyncSystems::Outbound),
+            ),
+        );
+}
+
 /// Install core resources and systems for Pl3xusSync into the app.
-pub(crate) fn install<NP: NetworkProvider>(app: &mut App) {
+pub(crate) fn install<NP: NetworkProvider>(app: &mut App, config: &SyncPluginConfig) {
+    app.insert_resource(config.clone());
+
     // Initialize SyncSettings first (needed to create ConflationQueue)
-    app.init_resource::<SyncSettings>();
+    if !app.world().contains_resource::<SyncSettings>() {
+        app.insert_resource(SyncSettings {
+            max_update_rate_hz: config.tick_rate_hz,
+            ..Default::default()
+        });
+    }
 
     // Initialize ConflationQueue with settings from SyncSettings
     {
@@ -57,82 +141,126 @@ pub(crate) fn install<NP: NetworkProvider>(app: &mut App) {
     app.init_resource::<SubscriptionManager>()
         .init_resource::<MutationQueue>()
         .init_resource::<MutationResponseQueue>()
+        .init_resource::<MutationIdempotency>()
         .init_resource::<SnapshotQueue>()
+        .init_resource::<SyncSequences>()
+        .init_resource::<SyncSession>()
+        .init_resource::<crate::hot_registration::HotRegistrations>()
         .add_message::<ComponentChangeEvent>()
         .add_message::<ComponentRemovedEvent>()
-        .add_message::<EntityDespawnEvent>();
-
-    // Verify resources were initialized
-    let world = app.world();
-    info!(
-        "[pl3xus_sync::install] Resources initialized: SubscriptionManager={}, MutationQueue={}, SnapshotQueue={}, SyncSettings={}, ConflationQueue={}",
-        world.contains_resource::<SubscriptionManager>(),
-        world.contains_resource::<MutationQueue>(),
-        world.contains_resource::<SnapshotQueue>(),
-        world.contains_resource::<SyncSettings>(),
-        world.contains_resource::<ConflationQueue>()
+        .add_message::<EntityDespawnEvent>()
+        .init_resource::<ClientRoles>()
+        .init_resource::<PendingNotifications>()
+        .init_resource::<ClientNotificationPreferences>()
+        .add_message::<OutgoingNotification>()
+        .add_message::<NotificationAcknowledged>();
+
+    verbose_log!(
+        config.verbose_logging,
+        "[pl3xus_sync::install] Sync plugin installed: {:?}",
+        config
     );
 
-    app.configure_sets(
-            Update,
-            (
-                Pl3xusSyncSystems::Inbound,
-                Pl3xusSyncSystems::Observe,
-                Pl3xusSyncSystems::Outbound,
-            )
-                .chain(),
-        )
+    configure_sync_sets(app);
+
+    app
         // Client-side messages -> subscription manager
         .add_systems(
             Update,
-            handle_client_messages::<NP>.in_set(Pl3xusSyncSystems::Inbound),
+            handle_client_messages.in_set(SyncSet::Receive),
         )
         // Send Welcome message to newly connected clients (must run before cleanup_disconnected
         // since both read NetworkEvent and events can only be read once)
         // We handle both Connected and Disconnected events in a single system now
         .add_systems(
             Update,
-            handle_connection_events::<NP>.in_set(Pl3xusSyncSystems::Inbound),
+            handle_connection_events::<NP>.in_set(SyncSet::Receive),
         )
         // Process queued mutations: authorization + apply + MutationResponse
         .add_systems(
             Update,
-            process_mutations::<NP>.in_set(Pl3xusSyncSystems::Inbound),
+            process_mutations::<NP>.in_set(SyncSet::ApplyMutations),
+        )
+        // Queries for names without a registered live query are answered NotFound
+        .add_systems(
+            Update,
+            crate::live_query::reject_unknown_queries::<NP>.in_set(SyncSet::Receive),
+        )
+        // Undo/redo requests become queued mutations
+        .add_systems(
+            Update,
+            (
+                crate::undo::handle_undo_requests::<NP>,
+                crate::undo::forget_despawned_entities,
+            )
+                .in_set(SyncSet::Receive),
         )
         // Send mutation responses from handlers (runs after handler systems in Inbound)
         .add_systems(
             Update,
-            send_mutation_responses::<NP>.in_set(Pl3xusSyncSystems::Observe),
+            send_mutation_responses::<NP>.in_set(SyncSet::Broadcast),
         )
         // Process queued snapshot requests and send initial SyncBatch snapshots
-        // back to subscribing clients.
+        // back to subscribing clients, ahead of this frame's updates.
         .add_systems(
             Update,
-            process_snapshot_queue::<NP>.in_set(Pl3xusSyncSystems::Observe),
+            process_snapshot_queue::<NP>
+                .before(broadcast_component_changes::<NP>)
+                .in_set(SyncSet::Broadcast),
         )
         // ComponentChangeEvent -> SyncServerMessage batches (or queue for conflation)
         .add_systems(
             Update,
-            broadcast_component_changes::<NP>.in_set(Pl3xusSyncSystems::Outbound),
+            broadcast_component_changes::<NP>.in_set(SyncSet::Broadcast),
+        )
+        // State machine transitions go out after the updates carrying the new value
+        .add_systems(
+            Update,
+            crate::state_machine::send_state_transitions::<NP>
+                .after(broadcast_component_changes::<NP>)
+                .in_set(SyncSet::Broadcast),
         )
         // Flush conflation queue on timer
         .add_systems(
             Update,
-            flush_conflation_queue::<NP>.in_set(Pl3xusSyncSystems::Outbound),
-        );
+            flush_conflation_queue::<NP>.in_set(SyncSet::Broadcast),
+        )
+        // Notification acknowledgements and preferences from clients
+        .add_systems(
+            Update,
+            (handle_notification_acks, handle_notification_preferences)
+                .in_set(SyncSet::Receive),
+        )
+        // Targeted notifications, plus redelivery of unacknowledged ones
+        .add_systems(
+            Update,
+            (
+                dispatch_notifications::<NP>,
+                redeliver_pending_notifications::<NP>,
+            )
+                .chain()
+                .in_set(SyncSet::Broadcast),
+        )
+        // Registrations queued at runtime, applied before this frame's Update
+        .add_systems(First, crate::hot_registration::apply_hot_registrations::<NP>);
 
     // Register sync messages with pl3xus so they can be transported
     register_network_messages::<NP>(app);
+
+    // Let clients fetch the registered types to validate their own registrations
+    crate::schema::register_describe_schema::<NP>(app);
 }
 
 fn register_network_messages<NP: NetworkProvider>(app: &mut App) {
     use pl3xus::AppNetworkMessage;
-    use pl3xus_common::ServerNotification;
+    use pl3xus_common::{NotificationAck, NotificationPreferences, ServerNotification};
 
     app.register_network_message::<SyncClientMessage, NP>();
     app.register_network_message::<crate::messages::SyncServerMessage, NP>();
     // Register ServerNotification for authorization rejection notifications
     app.register_network_message::<ServerNotification, NP>();
+    app.register_network_message::<NotificationAck, NP>();
+    app.register_network_message::<NotificationPreferences, NP>();
 }
 
 /// Handle connection events: send Welcome to new connections and cleanup disconnected ones.
@@ -142,6 +270,8 @@ fn handle_connection_events<NP: NetworkProvider>(
     mut network_events: MessageReader<NetworkEvent>,
     subscriptions: Option<ResMut<SubscriptionManager>>,
     mutations: Option<ResMut<MutationQueue>>,
+    mut sequences: ResMut<SyncSequences>,
+    session: Res<SyncSession>,
 ) {
     let (mut subscriptions, mut mutations) = match (subscriptions, mutations) {
         (Some(s), Some(m)) => (s, m),
@@ -150,10 +280,12 @@ fn handle_connection_events<NP: NetworkProvider>(
 
     for event in network_events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 info!("[pl3xus_sync] Sending Welcome message to client {:?}", conn_id);
                 let welcome = SyncServerMessage::Welcome(WelcomeMessage {
                     connection_id: *conn_id,
+                    protocol_version: SYNC_PROTOCOL_VERSION,
+                    session: session.0,
                 });
                 if let Err(e) = net.send(*conn_id, welcome) {
                     warn!("[pl3xus_sync] Failed to send Welcome to {:?}: {:?}", conn_id, e);
@@ -165,7 +297,7 @@ fn handle_connection_events<NP: NetworkProvider>(
                 subscriptions.subscriptions.retain(|sub| {
                     let keep = sub.connection_id != *connection_id;
                     if !keep {
-                        info!("[pl3xus_sync] Removed subscription for {:?}", connection_id);
+                        debug!("[pl3xus_sync] Removed subscription for {:?}", connection_id);
                     }
                     keep
                 });
@@ -176,6 +308,7 @@ fn handle_connection_events<NP: NetworkProvider>(
                     .retain(|m| m.connection_id != *connection_id);
                 let after_count = mutations.pending.len();
                 info!("[pl3xus_sync] Removed {} pending mutations for {:?}", before_count - after_count, connection_id);
+                sequences.remove_connection(*connection_id);
             }
             _ => {}
         }
@@ -209,13 +342,66 @@ pub fn process_mutations<NP: NetworkProvider>(world: &mut World) {
     // Collect mutations that need to be routed to handlers
     let mut handler_routed: Vec<(QueuedMutation, fn(&mut World, &QueuedMutation))> = Vec::new();
 
+    let connected = world
+        .get_resource::<Network<NP>>()
+        .map(|net| net.connection_ids())
+        .unwrap_or_default();
+
     for mutation in pending.drain(..) {
+        // Retries of a mutation we've already seen are not applied again.
+        let check = world
+            .get_resource_mut::<MutationIdempotency>()
+            .map(|mut idempotency| idempotency.begin(&mutation, |owner| connected.contains(&owner)))
+            .unwrap_or(IdempotencyCheck::New);
+        match check {
+            IdempotencyCheck::New => {}
+            IdempotencyCheck::InFlight => {
+                debug!(
+                    "[pl3xus_sync] Mutation {:?} from {:?} is a retry of an in-flight mutation",
+                    mutation.request_id, mutation.connection_id
+                );
+                continue;
+            }
+            IdempotencyCheck::Completed(cached) => {
+                if let Some(net) = world.get_resource::<Network<NP>>() {
+                    let response = MutationResponse {
+                        request_id: mutation.request_id,
+                        ..cached
+                    };
+                    let _ = net.send(
+                        mutation.connection_id,
+                        SyncServerMessage::MutationResponse(response),
+                    );
+                }
+                continue;
+            }
+            IdempotencyCheck::Conflict => {
+                debug!(
+                    "[pl3xus_sync] Dropping mutation {:?} from {:?}: its idempotency key belongs to another connection",
+                    mutation.request_id, mutation.connection_id
+                );
+                continue;
+            }
+        }
+
         let mut status = Status::Ok;
         let mut response_message: Option<String> = None;
+        let mut field_errors = Vec::new();
         let mut routed_to_handler = false;
 
+        // Per-connection rate limit, if configured.
+        if let AuthResult::Denied(reason) = crate::rate_limit::check_rate_limit(
+            world,
+            mutation.connection_id,
+            crate::rate_limit::RateLimitKind::Mutation,
+            &mutation.component_type,
+        ) {
+            status = Status::Forbidden;
+            response_message = Some(reason);
+        }
+
         // Optional authorization step.
-        if let Some(auth_res) = world.get_resource::<MutationAuthorizerResource>() {
+        if let (Status::Ok, Some(auth_res)) = (&status, world.get_resource::<MutationAuthorizerResource>()) {
             let ctx = MutationAuthContext { world: &*world };
             status = auth_res.inner.authorize(&ctx, &mutation);
         }
@@ -237,6 +423,13 @@ pub fn process_mutations<NP: NetworkProvider>(world: &mut World) {
                     status = Status::NotFound;
                 }
                 Some(reg) => {
+                    // Value being replaced, kept for undo if the mutation succeeds.
+                    let before = if reg.config.undoable {
+                        (reg.read_value)(world, mutation.entity.to_entity())
+                    } else {
+                        None
+                    };
+
                     // Check if client mutations are allowed for this component type
                     if !mutation.connection_id.is_server() && !reg.config.allow_client_mutations {
                         status = Status::Forbidden;
@@ -264,7 +457,18 @@ pub fn process_mutations<NP: NetworkProvider>(world: &mut World) {
                         match auth_result {
                             AuthResult::Authorized => {
                                 // Route to authorized handler
-                                if let Some(route_fn) = reg.route_to_authorized_handler {
+                                if let Err(errors) = reg.config.validate_mutation(&mutation.value) {
+                                    status = Status::ValidationError;
+                                    response_message = Some(validation_failed_message(&errors));
+                                    field_errors = errors;
+                                } else if let Err(reason) = reg.config.check_transition(world, entity, &mutation.value) {
+                                    status = Status::ValidationError;
+                                    response_message = Some(reason);
+                                } else if let Some(route_fn) = reg.route_to_authorized_handler {
+                                    if reg.config.undoable {
+                                        crate::undo::await_handler(world, &mutation, before);
+                                    }
+                                    crate::command_log::record_mutation(world, &mutation);
                                     handler_routed.push((mutation.clone(), route_fn));
                                     routed_to_handler = true;
                                 } else {
@@ -287,9 +491,22 @@ pub fn process_mutations<NP: NetworkProvider>(world: &mut World) {
                                 response_message = Some(reason);
                             }
                         }
+                    } else if let Err(errors) = reg.config.validate_mutation(&mutation.value) {
+                        status = Status::ValidationError;
+                        response_message = Some(validation_failed_message(&errors));
+                        field_errors = errors;
+                    } else if let Err(reason) =
+                        reg.config.check_transition(world, mutation.entity.to_entity(), &mutation.value)
+                    {
+                        status = Status::ValidationError;
+                        response_message = Some(reason);
                     } else if reg.config.has_mutation_handler {
                         // Route to handler - the handler will respond via MutationResponseQueue
                         if let Some(route_fn) = reg.route_to_handler {
+                            if reg.config.undoable {
+                                crate::undo::await_handler(world, &mutation, before);
+                            }
+                            crate::command_log::record_mutation(world, &mutation);
                             handler_routed.push((mutation.clone(), route_fn));
                             routed_to_handler = true;
                         } else {
@@ -312,6 +529,12 @@ pub fn process_mutations<NP: NetworkProvider>(world: &mut World) {
                         match apply_result {
                             Ok(result_status) => {
                                 status = result_status;
+                                if reg.config.undoable && matches!(status, Status::Ok) {
+                                    crate::undo::record_mutation(world, &mutation, before);
+                                }
+                                if matches!(status, Status::Ok) {
+                                    crate::command_log::record_mutation(world, &mutation);
+                                }
                             }
                             Err(_) => {
                                 status = Status::InternalError;
@@ -325,18 +548,22 @@ pub fn process_mutations<NP: NetworkProvider>(world: &mut World) {
         // Respond back to the originating client, if we have a network
         // provider for this plugin's `NetworkProvider` type.
         // Skip if routed to handler - handler will respond via MutationResponseQueue.
-        if !routed_to_handler {
-            if let Some(net) = world.get_resource::<Network<NP>>() {
-                let response = MutationResponse {
+        if routed_to_handler {
+            if let Some(mut idempotency) = world.get_resource_mut::<MutationIdempotency>() {
+                idempotency.await_handler(&mutation);
+            }
+        } else {
+            respond_to_mutation::<NP>(
+                world,
+                mutation.connection_id,
+                mutation.idempotency_key.map(|key| (mutation.component_type.clone(), key)),
+                MutationResponse {
                     request_id: mutation.request_id,
-                    status: status.clone(),
+                    status,
                     message: response_message,
-                };
-                let _ = net.send(
-                    mutation.connection_id,
-                    SyncServerMessage::MutationResponse(response),
-                );
-            }
+                    field_errors,
+                },
+            );
         }
     }
 
@@ -349,12 +576,13 @@ pub fn process_mutations<NP: NetworkProvider>(world: &mut World) {
 /// Drain the mutation response queue and send responses to clients.
 ///
 /// This system runs after handler systems have processed `ComponentMutation<T>` events
-/// and queued their responses in `MutationResponseQueue`.
+/// and queued their responses in `MutationResponseQueue`. Responses completed
+/// through a `DeferredMutationResponder` since the last frame are sent here too.
 pub fn send_mutation_responses<NP: NetworkProvider>(world: &mut World) {
     // Take ownership of pending responses
     let pending = {
         if let Some(mut queue) = world.get_resource_mut::<MutationResponseQueue>() {
-            std::mem::take(&mut queue.pending)
+            queue.drain()
         } else {
             return;
         }
@@ -364,17 +592,47 @@ pub fn send_mutation_responses<NP: NetworkProvider>(world: &mut World) {
         return;
     }
 
-    if let Some(net) = world.get_resource::<Network<NP>>() {
-        for response in pending {
-            let msg = MutationResponse {
+    for response in pending {
+        crate::undo::handler_responded(world, response.connection_id, response.request_id, &response.status);
+        respond_to_mutation::<NP>(
+            world,
+            response.connection_id,
+            None,
+            MutationResponse {
                 request_id: response.request_id,
                 status: response.status,
                 message: response.message,
-            };
-            let _ = net.send(response.connection_id, SyncServerMessage::MutationResponse(msg));
-        }
+                field_errors: response.field_errors,
+            },
+        );
     }
 }
+
+/// Send a mutation response, caching it if the mutation carried an idempotency
+/// key and answering any retries that were waiting for it.
+fn respond_to_mutation<NP: NetworkProvider>(
+    world: &mut World,
+    connection_id: pl3xus_common::ConnectionId,
+    idempotency_key: Option<(String, u64)>,
+    response: MutationResponse,
+) {
+    let waiting = world
+        .get_resource_mut::<MutationIdempotency>()
+        .map(|mut idempotency| idempotency.complete(connection_id, idempotency_key, &response))
+        .unwrap_or_default();
+
+    let Some(net) = world.get_resource::<Network<NP>>() else {
+        return;
+    };
+    for (retry_connection, request_id) in waiting {
+        let retry = MutationResponse {
+            request_id: Some(request_id),
+            ..response.clone()
+        };
+        let _ = net.send(retry_connection, SyncServerMessage::MutationResponse(retry));
+    }
+    let _ = net.send(connection_id, SyncServerMessage::MutationResponse(response));
+}
 /// Drain the snapshot queue and send initial snapshot batches to clients.
 pub fn process_snapshot_queue<NP: NetworkProvider>(world: &mut World) {
     // Take ownership of pending snapshot requests.
@@ -395,13 +653,14 @@ pub fn process_snapshot_queue<NP: NetworkProvider>(world: &mut World) {
     let type_snapshot_fns: Vec<(
         String,
         fn(&mut World) -> Vec<(crate::messages::SerializableEntity, Vec<u8>)>,
+        fn(&World, Entity) -> Option<serde_json::Value>,
     )> = world
         .get_resource::<SyncRegistry>()
         .map(|registry| {
             registry
                 .components
                 .iter()
-                .map(|reg| (reg.type_name.clone(), reg.snapshot_all))
+                .map(|reg| (reg.type_name.clone(), reg.snapshot_all, reg.read_json))
                 .collect()
         })
         .unwrap_or_default();
@@ -415,18 +674,25 @@ pub fn process_snapshot_queue<NP: NetworkProvider>(world: &mut World) {
         pl3xus_common::ConnectionId,
         Vec<SyncItem>,
     > = std::collections::HashMap::new();
+    // Entities sent for filtered subscriptions, recorded once the loop is done.
+    let mut matched = Vec::new();
+    // Components the visibility policy hid from the subscriber, likewise.
+    let mut hidden = Vec::new();
 
     for request in pending.drain(..) {
         let mut found_match = false;
         let mut found_component_type = false;
 
-        for (type_name, snapshot_fn) in &type_snapshot_fns {
+        for (type_name, snapshot_fn, read_json) in &type_snapshot_fns {
             if request.component_type != "*" && type_name != &request.component_type {
                 continue;
             }
             found_component_type = true;
 
             let snapshots = snapshot_fn(world);
+            let policy = world
+                .get_resource::<SyncRegistry>()
+                .and_then(|registry| registry.visibility_policy(type_name));
 
             for (entity, value) in snapshots {
                 if let Some(target) = request.entity {
@@ -434,6 +700,22 @@ pub fn process_snapshot_queue<NP: NetworkProvider>(world: &mut World) {
                         continue;
                     }
                 }
+                if let Some(policy) = policy
+                    && !policy.is_visible(world, request.connection_id, entity.to_entity())
+                {
+                    hidden.push((request.connection_id, request.subscription_id, entity, type_name.clone()));
+                    continue;
+                }
+                // Values that can't be read as JSON are sent unfiltered; the
+                // client applies the same filter.
+                if let Some(filter) = &request.filter
+                    && let Some(json) = read_json(world, entity.to_entity())
+                {
+                    if !filter.matches(&json, entity) {
+                        continue;
+                    }
+                    matched.push((request.connection_id, request.subscription_id, entity));
+                }
 
                 found_match = true;
                 per_connection
@@ -471,29 +753,56 @@ pub fn process_snapshot_queue<NP: NetworkProvider>(world: &mut World) {
         }
     }
 
+    if let Some(mut subscriptions) = world.get_resource_mut::<SubscriptionManager>() {
+        for (connection_id, subscription_id, entity) in matched {
+            subscriptions.set_matched(connection_id, subscription_id, entity, true);
+        }
+        for (connection_id, subscription_id, entity, component_type) in hidden {
+            subscriptions.set_hidden(connection_id, subscription_id, entity, component_type, true);
+        }
+    }
+
     if per_connection.is_empty() {
         return;
     }
 
-    info!(
-        "[pl3xus_sync] Processing {} snapshot batches for {} connections",
+    let config = world.get_resource::<SyncPluginConfig>().cloned().unwrap_or_default();
+    verbose_log!(
+        config.verbose_logging,
+        "[pl3xus_sync] Processing {} snapshot items for {} connections",
         per_connection.values().map(|items| items.len()).sum::<usize>(),
         per_connection.len()
     );
 
-    if let Some(net) = world.get_resource::<Network<NP>>() {
-        for (connection_id, items) in per_connection {
-            if items.is_empty() {
-                continue;
+    let include_timestamps = world
+        .get_resource::<SyncSettings>()
+        .is_some_and(|s| s.include_timestamps);
+
+    let batch_len = config.snapshot_batch_len();
+    let batches: Vec<_> = {
+        let mut sequences = world.resource_mut::<SyncSequences>();
+        let mut batches = Vec::new();
+        for (connection_id, mut items) in per_connection {
+            // Split large snapshots into several batches of at most batch_len items
+            while !items.is_empty() {
+                let rest = items.split_off(batch_len.min(items.len()));
+                let mut batch = SyncBatch::new(std::mem::replace(&mut items, rest), include_timestamps);
+                sequences.stamp(connection_id, &mut batch);
+                batches.push((connection_id, batch));
             }
+        }
+        batches
+    };
 
-            info!(
+    if let Some(net) = world.get_resource::<Network<NP>>() {
+        for (connection_id, batch) in batches {
+            verbose_log!(
+                config.verbose_logging,
                 "[pl3xus_sync] Sending snapshot batch: conn={:?}, items={}",
                 connection_id,
-                items.len()
+                batch.items.len()
             );
 
-            let batch = SyncBatch { items };
             let _ = net.send(connection_id, SyncServerMessage::SyncBatch(batch));
         }
     }
@@ -501,28 +810,43 @@ pub fn process_snapshot_queue<NP: NetworkProvider>(world: &mut World) {
 
 
 /// Register the typed observation system for a given component type T.
-pub fn register_component_system<T>(app: &mut App)
+///
+/// When `max_update_rate_hz` is set, a throttled observer is used instead that
+/// coalesces changes per entity and emits them at most at that rate.
+pub fn register_component_system<T>(app: &mut App, max_update_rate_hz: Option<f32>)
 where
     T: Component + Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
 {
     // We add a Changed<T>-based system that fires late in the frame (Observe
     // set) and emits ComponentChangeEvent instances.
-    app.add_systems(
-        Update,
-        observe_component_changes::<T>.in_set(Pl3xusSyncSystems::Observe),
-    );
+    match max_update_rate_hz {
+        Some(hz) => {
+            app.insert_resource(ComponentThrottle::<T>::new(hz));
+            app.add_systems(
+                Update,
+                observe_component_changes_throttled::<T>.in_set(SyncSet::DetectChanges),
+            );
+        }
+        None => {
+            app.add_systems(
+                Update,
+                observe_component_changes::<T>.in_set(SyncSet::DetectChanges),
+            );
+        }
+    }
 
     // Also add a system to observe entity despawns for this component type.
     // This will emit EntityDespawnEvent when entities with this component are despawned.
     app.add_systems(
         Update,
-        observe_entity_despawns::<T>.in_set(Pl3xusSyncSystems::Observe),
+        observe_entity_despawns::<T>.in_set(SyncSet::DetectChanges),
     );
 }
 
 /// Observe Changed<T> and convert into generic ComponentChangeEvent instances.
 fn observe_component_changes<T>(
     query: Query<(Entity, &T), Changed<T>>,
+    policy: Option<Res<crate::field_policy::FieldPolicy<T>>>,
     mut writer: MessageWriter<ComponentChangeEvent>,
 ) where
     T: Component + Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
@@ -533,9 +857,65 @@ fn observe_component_changes<T>(
     let type_name = full_type_name.rsplit("::").next().unwrap_or(full_type_name).to_string();
 
     for (entity, component) in query.iter() {
-        // Serialize component directly to bincode bytes
-        let bytes = bincode::serde::encode_to_vec(component, bincode::config::standard())
-            .unwrap_or_default();
+        // Serialize component to bincode bytes (with redacted fields stripped)
+        let bytes = crate::field_policy::encode_for_wire(policy.as_deref(), component);
+        writer.write(ComponentChangeEvent {
+            entity: crate::messages::SerializableEntity::from(entity),
+            component_type: type_name.clone(),
+            value: bytes,
+        });
+    }
+}
+
+/// Per-component-type throttle state for components registered with a
+/// maximum update rate.
+#[derive(Resource)]
+struct ComponentThrottle<T> {
+    timer: Timer,
+    /// Entities that changed since the last emission.
+    dirty: std::collections::HashSet<Entity>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> ComponentThrottle<T> {
+    fn new(hz: f32) -> Self {
+        Self {
+            timer: Timer::new(std::time::Duration::from_secs_f32(1.0 / hz), TimerMode::Repeating),
+            dirty: std::collections::HashSet::new(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Throttled variant of [`observe_component_changes`].
+///
+/// Changed entities are collected every frame, but their latest values are only
+/// emitted when the per-type timer fires.
+fn observe_component_changes_throttled<T>(
+    changed: Query<Entity, Changed<T>>,
+    components: Query<&T>,
+    mut throttle: ResMut<ComponentThrottle<T>>,
+    policy: Option<Res<crate::field_policy::FieldPolicy<T>>>,
+    time: Res<Time>,
+    mut writer: MessageWriter<ComponentChangeEvent>,
+) where
+    T: Component + Serialize + for<'de> serde::Deserialize<'de> + Send + Sync + 'static,
+{
+    throttle.dirty.extend(changed.iter());
+    throttle.timer.tick(time.delta());
+
+    if !throttle.timer.just_finished() || throttle.dirty.is_empty() {
+        return;
+    }
+
+    let type_name = short_type_name::<T>();
+
+    for entity in std::mem::take(&mut throttle.dirty) {
+        // Entities despawned while pending are reported by observe_entity_despawns
+        let Ok(component) = components.get(entity) else {
+            continue;
+        };
+        let bytes = crate::field_policy::encode_for_wire(policy.as_deref(), component);
         writer.write(ComponentChangeEvent {
             entity: crate::messages::SerializableEntity::from(entity),
             component_type: type_name.clone(),
@@ -578,7 +958,9 @@ fn observe_entity_despawns<T>(
 /// This system only runs when conflation is enabled (max_update_rate_hz is set).
 pub fn flush_conflation_queue<NP: NetworkProvider>(
     mut conflation_queue: ResMut<ConflationQueue>,
+    mut sequences: ResMut<SyncSequences>,
     settings: Res<SyncSettings>,
+    registry: Option<Res<SyncRegistry>>,
     net: Option<Res<Network<NP>>>,
     time: Res<Time>,
 ) {
@@ -596,12 +978,7 @@ pub fn flush_conflation_queue<NP: NetworkProvider>(
     }
 
     // Get all connection IDs that have pending items
-    let connection_ids: Vec<pl3xus_common::ConnectionId> = conflation_queue
-        .pending
-        .keys()
-        .chain(conflation_queue.non_conflatable.keys())
-        .copied()
-        .collect();
+    let connection_ids = conflation_queue.connections();
 
     if connection_ids.is_empty() {
         return;
@@ -610,10 +987,15 @@ pub fn flush_conflation_queue<NP: NetworkProvider>(
     let Some(net) = net else {
         return;
     };
+    let priorities = registry.map(|registry| registry.priorities()).unwrap_or_default();
 
-    // Flush each connection's pending items
+    // Flush each connection's pending items, holding back low-priority
+    // values for connections that are falling behind
     for connection_id in connection_ids {
-        let items = conflation_queue.drain_for_connection(connection_id);
+        let fill = net.send_queue_fill(connection_id).unwrap_or_default();
+        let items = conflation_queue.drain_with_backpressure(connection_id, fill, &settings.backpressure, |component| {
+            priorities.get(component).copied().unwrap_or_default()
+        });
 
         if items.is_empty() {
             continue;
@@ -625,8 +1007,150 @@ pub fn flush_conflation_queue<NP: NetworkProvider>(
             connection_id
         );
 
-        let batch = SyncBatch { items };
+        let mut batch = SyncBatch::new(items, settings.include_timestamps);
+        sequences.stamp(connection_id, &mut batch);
         let _ = net.send(connection_id, SyncServerMessage::SyncBatch(batch));
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Order(Vec<SyncSet>);
+
+    fn record(set: SyncSet) -> impl FnMut(ResMut<Order>) {
+        move |mut order: ResMut<Order>| order.0.push(set)
+    }
+
+    #[test]
+    fn test_sync_sets_run_in_order() {
+        let mut app = App::new();
+        configure_sync_sets(&mut app);
+        app.init_resource::<Order>();
+        let stages = [
+            SyncSet::Broadcast,
+            SyncSet::DetectChanges,
+            SyncSet::ApplyMutations,
+            SyncSet::Authorize,
+            SyncSet::Receive,
+        ];
+        for stage in stages {
+            app.add_systems(Update, record(stage).in_set(stage));
+        }
+        app.update();
+
+        let mut expected = stages.to_vec();
+        expected.reverse();
+        assert_eq!(app.world().resource::<Order>().0, expected);
+    }
+
+    #[derive(Component, Serialize, serde::Deserialize, Clone, Debug)]
+    struct Position(f32);
+
+    #[derive(Component)]
+    struct Owner(pl3xus_common::ConnectionId);
+
+    #[test]
+    fn test_snapshot_skips_hidden_entities() {
+        use crate::registry::{register_component, ComponentSyncConfig, SnapshotRequest, SubscriptionEntry, SyncSequences, VisibilityPolicy};
+        use pl3xus_common::ConnectionId;
+
+        let alice = ConnectionId { id: 1 };
+        let bob = ConnectionId { id: 2 };
+        let mut setup = App::new();
+        register_component::<Position>(
+            &mut setup,
+            Some(ComponentSyncConfig::default().with_visibility(VisibilityPolicy::from_fn(
+                |world, conn, entity| world.get::<Owner>(entity).is_some_and(|owner| owner.0 == conn),
+            ))),
+        );
+
+        let mut world = World::new();
+        world.insert_resource(setup.world_mut().remove_resource::<SyncRegistry>().unwrap());
+        world.init_resource::<SyncSequences>();
+        let robot = world.spawn((Position(0.0), Owner(alice))).id();
+        let mut subscriptions = SubscriptionManager::default();
+        let mut snapshots = SnapshotQueue::default();
+        for connection_id in [alice, bob] {
+            subscriptions.add_subscription(SubscriptionEntry {
+                connection_id,
+                subscription_id: 1,
+                component_type: "Position".into(),
+                entity: None,
+                filter: None,
+                matched: Default::default(),
+                hidden: Default::default(),
+            });
+            snapshots.pending.push(SnapshotRequest {
+                connection_id,
+                subscription_id: 1,
+                component_type: "Position".into(),
+                entity: None,
+                filter: None,
+            });
+        }
+        world.insert_resource(subscriptions);
+        world.insert_resource(snapshots);
+
+        process_snapshot_queue::<pl3xus_websockets::WebSocketProvider>(&mut world);
+
+        // Only Bob's snapshot skipped the robot, so only Bob has it hidden
+        let hidden = |connection_id| {
+            world
+                .resource::<SubscriptionManager>()
+                .subscriptions
+                .iter()
+                .find(|sub| sub.connection_id == connection_id)
+                .map(|sub| sub.hidden.iter().map(|(entity, _)| entity.to_entity()).collect::<Vec<_>>())
+        };
+        assert_eq!(hidden(alice), Some(vec![]));
+        assert_eq!(hidden(bob), Some(vec![robot]));
+        assert!(world.resource::<SnapshotQueue>().pending.is_empty());
+    }
+
+    #[test]
+    fn test_throttled_changes_are_coalesced() {
+        use bevy::ecs::message::Messages;
+
+        let mut world = World::new();
+        world.init_resource::<Messages<ComponentChangeEvent>>();
+        world.init_resource::<Time>();
+        world.insert_resource(ComponentThrottle::<Position>::new(10.0));
+        let mut schedule = Schedule::default();
+        schedule.add_systems(observe_component_changes_throttled::<Position>);
+
+        let mut tick = |world: &mut World, millis| {
+            world.resource_mut::<Time>().advance_by(std::time::Duration::from_millis(millis));
+            schedule.run(world);
+            world.resource_mut::<Messages<ComponentChangeEvent>>().drain().collect::<Vec<_>>()
+        };
+
+        let a = world.spawn(Position(0.0)).id();
+        world.spawn(Position(0.0));
+        assert!(tick(&mut world, 40).is_empty());
+
+        // Several changes before the timer fires are sent once, with the latest value
+        world.get_mut::<Position>(a).unwrap().0 = 1.0;
+        assert!(tick(&mut world, 40).is_empty());
+        world.get_mut::<Position>(a).unwrap().0 = 2.0;
+        let sent = tick(&mut world, 40);
+        assert_eq!(sent.len(), 2);
+        let latest = sent.iter().find(|event| event.entity.to_entity() == a).unwrap();
+        assert_eq!(latest.component_type, "Position");
+        let (position, _) =
+            bincode::serde::decode_from_slice::<Position, _>(&latest.value, bincode::config::standard()).unwrap();
+        assert_eq!(position.0, 2.0);
+
+        // Nothing changed, so nothing is sent when the timer fires again
+        assert!(tick(&mut world, 100).is_empty());
+
+        // Entities despawned while pending are skipped
+        world.get_mut::<Position>(a).unwrap().0 = 3.0;
+        tick(&mut world, 10);
+        world.despawn(a);
+        assert!(tick(&mut world, 100).is_empty());
+    }
+}
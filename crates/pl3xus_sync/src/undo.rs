@@ -0,0 +1,465 @@
+//! Opt-in undo/redo for component mutations.
+//!
+//! Components registered with `.with_undo()` keep a per-entity history of
+//! successful mutations together with the value they replaced:
+//!
+//! ```rust,ignore
+//! app.sync_component_builder::<JogSettingsState>()
+//!     .targeted()
+//!     .with_default_entity_policy()
+//!     .with_handler::<NP, _, _>(handle_jog_settings_mutation)
+//!     .with_undo()
+//!     .build();
+//! ```
+//!
+//! Clients send [`UndoMutation`] / [`RedoMutation`] for an entity. The server
+//! turns each into an ordinary mutation that writes back the previous (or
+//! undone) value, so it goes through the same authorization, validation and
+//! handler as any other mutation and is answered with a `MutationResponse`.
+//! Only once that mutation succeeds does the entry move between the undo and
+//! redo stacks. A new mutation clears the redo stack.
+//!
+//! The entity's [`UndoHistory`] component is kept up to date and synced
+//! read-only, so clients can enable their undo/redo buttons accordingly.
+//!
+//! Mutations that create the component (there was no previous value) are not
+//! recorded, since undoing them would require removing the component.
+
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use pl3xus::{Network, NetworkData};
+use pl3xus_common::ConnectionId;
+
+use crate::messages::{MutationResponse, MutationStatus, SerializableEntity, SyncClientMessage, SyncServerMessage};
+use crate::registry::{
+    ComponentSyncConfig,
+    EntityDespawnEvent,
+    MutationOrigin,
+    MutationQueue,
+    QueuedMutation,
+    SyncRegistry,
+};
+use crate::NetworkProvider;
+
+pub use crate::messages::{RedoMutation, UndoMutation};
+pub use pl3xus_common::UndoHistory;
+
+/// Settings for undo history.
+#[derive(Resource, Debug, Clone)]
+pub struct UndoSettings {
+    /// Maximum number of undoable mutations kept per entity.
+    pub max_depth: usize,
+}
+
+impl Default for UndoSettings {
+    fn default() -> Self {
+        Self { max_depth: 50 }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    component_type: String,
+    /// Encoded value before the mutation.
+    before: Vec<u8>,
+    /// Encoded value the mutation applied.
+    after: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+struct EntityHistory {
+    /// Oldest first.
+    undo: VecDeque<HistoryEntry>,
+    /// Most recently undone last.
+    redo: Vec<HistoryEntry>,
+}
+
+/// A handler-routed mutation whose outcome is not known yet.
+struct AwaitingHandler {
+    entity: Entity,
+    origin: MutationOrigin,
+    component_type: String,
+    before: Option<Vec<u8>>,
+    value: Vec<u8>,
+}
+
+/// Undo and redo stacks for every entity with undoable mutations.
+#[derive(Resource, Default)]
+pub struct MutationHistory {
+    entities: HashMap<Entity, EntityHistory>,
+    /// Handler-routed mutations awaiting a response: (connection, request_id) -> record.
+    awaiting_handler: HashMap<(ConnectionId, u64), AwaitingHandler>,
+}
+
+impl MutationHistory {
+    /// Number of mutations that can be undone on `entity`.
+    pub fn undo_depth(&self, entity: Entity) -> usize {
+        self.entities.get(&entity).map_or(0, |h| h.undo.len())
+    }
+
+    /// Number of undone mutations that can be redone on `entity`.
+    pub fn redo_depth(&self, entity: Entity) -> usize {
+        self.entities.get(&entity).map_or(0, |h| h.redo.len())
+    }
+
+    /// Forget the history of `entity`, e.g. after loading a new configuration.
+    ///
+    /// The entity's [`UndoHistory`] is not updated until its next mutation.
+    pub fn clear(&mut self, entity: Entity) {
+        self.entities.remove(&entity);
+    }
+
+    /// The mutation an undo or redo of `entity` would queue, if any.
+    fn next_mutation(
+        &self,
+        source: ConnectionId,
+        request_id: Option<u64>,
+        entity: SerializableEntity,
+        idempotency_key: Option<u64>,
+        origin: MutationOrigin,
+    ) -> Option<QueuedMutation> {
+        let history = self.entities.get(&entity.to_entity())?;
+        let (component_type, value) = match origin {
+            MutationOrigin::Undo => history.undo.back().map(|e| (&e.component_type, &e.before))?,
+            MutationOrigin::Redo => history.redo.last().map(|e| (&e.component_type, &e.after))?,
+            MutationOrigin::Mutate => return None,
+        };
+        Some(QueuedMutation {
+            connection_id: source,
+            request_id,
+            entity,
+            component_type: component_type.clone(),
+            value: value.clone(),
+            idempotency_key,
+            origin,
+        })
+    }
+
+    /// Update the stacks after a mutation succeeded.
+    fn commit(
+        &mut self,
+        entity: Entity,
+        origin: MutationOrigin,
+        component_type: &str,
+        before: Option<Vec<u8>>,
+        value: &[u8],
+        max_depth: usize,
+    ) {
+        match origin {
+            MutationOrigin::Mutate => {
+                let Some(before) = before else {
+                    return;
+                };
+                let history = self.entities.entry(entity).or_default();
+                history.undo.push_back(HistoryEntry {
+                    component_type: component_type.to_string(),
+                    before,
+                    after: value.to_vec(),
+                });
+                while history.undo.len() > max_depth {
+                    history.undo.pop_front();
+                }
+                history.redo.clear();
+            }
+            MutationOrigin::Undo => {
+                let Some(history) = self.entities.get_mut(&entity) else {
+                    return;
+                };
+                // Another undo may have completed first; only move the entry
+                // this mutation actually reverted.
+                let matches = history
+                    .undo
+                    .back()
+                    .is_some_and(|e| e.component_type == component_type && e.before == value);
+                if matches && let Some(entry) = history.undo.pop_back() {
+                    history.redo.push(entry);
+                }
+            }
+            MutationOrigin::Redo => {
+                let Some(history) = self.entities.get_mut(&entity) else {
+                    return;
+                };
+                let matches = history
+                    .redo
+                    .last()
+                    .is_some_and(|e| e.component_type == component_type && e.after == value);
+                if matches && let Some(entry) = history.redo.pop() {
+                    history.undo.push_back(entry);
+                }
+            }
+        }
+    }
+
+    fn summary(&self, entity: Entity) -> UndoHistory {
+        UndoHistory {
+            undo_depth: self.undo_depth(entity) as u32,
+            redo_depth: self.redo_depth(entity) as u32,
+        }
+    }
+}
+
+/// Enable undo history. Called by `SyncComponentBuilder::with_undo`.
+pub(crate) fn enable_undo(app: &mut App) {
+    if !app.world().contains_resource::<UndoSettings>() {
+        app.init_resource::<UndoSettings>();
+    }
+    app.init_resource::<MutationHistory>();
+
+    let history_registered = app
+        .world()
+        .get_resource::<SyncRegistry>()
+        .is_some_and(|registry| {
+            registry
+                .components
+                .iter()
+                .any(|c| c.type_id == std::any::TypeId::of::<UndoHistory>())
+        });
+    if !history_registered {
+        crate::registry::register_component::<UndoHistory>(
+            app,
+            Some(ComponentSyncConfig::read_only_with_message(
+                "UndoHistory is maintained by the server. Send UndoMutation or RedoMutation instead.",
+            )),
+        );
+    }
+}
+
+/// Record a successful mutation of an undoable component.
+///
+/// `before` is the encoded value the mutation replaced, if the component existed.
+pub(crate) fn record_mutation(world: &mut World, mutation: &QueuedMutation, before: Option<Vec<u8>>) {
+    let entity = mutation.entity.to_entity();
+    let max_depth = world
+        .get_resource::<UndoSettings>()
+        .map_or(UndoSettings::default().max_depth, |s| s.max_depth);
+    let Some(mut history) = world.get_resource_mut::<MutationHistory>() else {
+        return;
+    };
+    history.commit(
+        entity,
+        mutation.origin,
+        &mutation.component_type,
+        before,
+        &mutation.value,
+        max_depth,
+    );
+    let summary = history.summary(entity);
+
+    if let Ok(mut entity_mut) = world.get_entity_mut(entity) {
+        entity_mut.insert(summary);
+    }
+}
+
+/// Remember an undoable mutation routed to a handler, so it can be recorded
+/// once the handler responds with success.
+pub(crate) fn await_handler(world: &mut World, mutation: &QueuedMutation, before: Option<Vec<u8>>) {
+    let Some(request_id) = mutation.request_id else {
+        // Without a request id the response cannot be matched to the mutation.
+        return;
+    };
+    if let Some(mut history) = world.get_resource_mut::<MutationHistory>() {
+        history.awaiting_handler.insert(
+            (mutation.connection_id, request_id),
+            AwaitingHandler {
+                entity: mutation.entity.to_entity(),
+                origin: mutation.origin,
+                component_type: mutation.component_type.clone(),
+                before,
+                value: mutation.value.clone(),
+            },
+        );
+    }
+}
+
+/// Record a handler-routed mutation once its response is known.
+pub(crate) fn handler_responded(
+    world: &mut World,
+    connection_id: ConnectionId,
+    request_id: Option<u64>,
+    status: &MutationStatus,
+) {
+    let Some(request_id) = request_id else {
+        return;
+    };
+    let Some(awaiting) = world
+        .get_resource_mut::<MutationHistory>()
+        .and_then(|mut history| history.awaiting_handler.remove(&(connection_id, request_id)))
+    else {
+        return;
+    };
+    if !matches!(status, MutationStatus::Ok) {
+        return;
+    }
+
+    let mutation = QueuedMutation {
+        connection_id,
+        request_id: Some(request_id),
+        entity: SerializableEntity::from(awaiting.entity),
+        component_type: awaiting.component_type,
+        value: awaiting.value,
+        idempotency_key: None,
+        origin: awaiting.origin,
+    };
+    record_mutation(world, &mutation, awaiting.before);
+}
+
+/// Turn `UndoMutation` / `RedoMutation` requests into queued mutations.
+pub(crate) fn handle_undo_requests<NP: NetworkProvider>(
+    mut reader: MessageReader<NetworkData<SyncClientMessage>>,
+    history: Option<Res<MutationHistory>>,
+    mutations: Option<ResMut<MutationQueue>>,
+    net: Res<Network<NP>>,
+) {
+    let Some(mut mutations) = mutations else {
+        return;
+    };
+
+    for msg in reader.read() {
+        let source = *msg.source();
+        let (request_id, entity, idempotency_key, origin) = match &**msg {
+            SyncClientMessage::Undo(req) => (req.request_id, req.entity, req.idempotency_key, MutationOrigin::Undo),
+            SyncClientMessage::Redo(req) => (req.request_id, req.entity, req.idempotency_key, MutationOrigin::Redo),
+            _ => continue,
+        };
+
+        let next = history
+            .as_deref()
+            .and_then(|h| h.next_mutation(source, request_id, entity, idempotency_key, origin));
+        match next {
+            Some(mutation) => mutations.pending.push(mutation),
+            None => {
+                let message = match (history.is_some(), origin) {
+                    (false, _) => "Undo is not enabled on this server",
+                    (true, MutationOrigin::Redo) => "Nothing to redo",
+                    (true, _) => "Nothing to undo",
+                };
+                let response = MutationResponse {
+                    request_id,
+                    status: MutationStatus::NotFound,
+                    message: Some(message.to_string()),
+                    field_errors: Vec::new(),
+                };
+                let _ = net.send(source, SyncServerMessage::MutationResponse(response));
+            }
+        }
+    }
+}
+
+/// Drop the history of despawned entities.
+pub(crate) fn forget_despawned_entities(
+    mut despawned: MessageReader<EntityDespawnEvent>,
+    history: Option<ResMut<MutationHistory>>,
+) {
+    let Some(mut history) = history else {
+        despawned.clear();
+        return;
+    };
+    for event in despawned.read() {
+        history.clear(event.entity.to_entity());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATOR: ConnectionId = ConnectionId { id: 1 };
+
+    fn history_world(max_depth: usize) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(UndoSettings { max_depth });
+        world.init_resource::<MutationHistory>();
+        let entity = world.spawn_empty().id();
+        (world, entity)
+    }
+
+    fn mutation(entity: Entity, value: u8, origin: MutationOrigin) -> QueuedMutation {
+        QueuedMutation {
+            connection_id: OPERATOR,
+            request_id: Some(value as u64),
+            entity: SerializableEntity::from(entity),
+            component_type: "JogSettingsState".into(),
+            value: vec![value],
+            idempotency_key: None,
+            origin,
+        }
+    }
+
+    fn next(world: &World, entity: Entity, source: ConnectionId, origin: MutationOrigin) -> Option<QueuedMutation> {
+        world.resource::<MutationHistory>().next_mutation(
+            source,
+            Some(99),
+            SerializableEntity::from(entity),
+            None,
+            origin,
+        )
+    }
+
+    fn depths(world: &World, entity: Entity) -> UndoHistory {
+        world.get::<UndoHistory>(entity).cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_undo_redo_stacks_are_bounded() {
+        let (mut world, entity) = history_world(2);
+
+        // Creating the component isn't undoable
+        record_mutation(&mut world, &mutation(entity, 1, MutationOrigin::Mutate), None);
+        assert_eq!(world.resource::<MutationHistory>().undo_depth(entity), 0);
+
+        for value in 2..=4 {
+            record_mutation(&mut world, &mutation(entity, value, MutationOrigin::Mutate), Some(vec![value - 1]));
+        }
+        // Only the last two mutations are kept
+        assert_eq!(depths(&world, entity), UndoHistory { undo_depth: 2, redo_depth: 0 });
+
+        // Undo writes back the previous value, as a mutation from the requester
+        let undo = next(&world, entity, OPERATOR, MutationOrigin::Undo).unwrap();
+        assert_eq!(undo.value, vec![3]);
+        assert_eq!(undo.connection_id, OPERATOR);
+        assert_eq!(undo.request_id, Some(99));
+        record_mutation(&mut world, &undo, Some(vec![4]));
+        assert_eq!(depths(&world, entity), UndoHistory { undo_depth: 1, redo_depth: 1 });
+
+        let redo = next(&world, entity, OPERATOR, MutationOrigin::Redo).unwrap();
+        assert_eq!(redo.value, vec![4]);
+
+        // A new mutation clears the redo stack
+        record_mutation(&mut world, &mutation(entity, 5, MutationOrigin::Mutate), Some(vec![3]));
+        assert_eq!(depths(&world, entity), UndoHistory { undo_depth: 2, redo_depth: 0 });
+        assert!(next(&world, entity, OPERATOR, MutationOrigin::Redo).is_none());
+    }
+
+    #[test]
+    fn test_stale_undo_does_not_move_entries() {
+        let (mut world, entity) = history_world(10);
+        record_mutation(&mut world, &mutation(entity, 2, MutationOrigin::Mutate), Some(vec![1]));
+        record_mutation(&mut world, &mutation(entity, 3, MutationOrigin::Mutate), Some(vec![2]));
+
+        // Two undos queued from the same state; only the first one reverts the top entry
+        let first = next(&world, entity, OPERATOR, MutationOrigin::Undo).unwrap();
+        let second = next(&world, entity, OPERATOR, MutationOrigin::Undo).unwrap();
+        record_mutation(&mut world, &first, Some(vec![3]));
+        record_mutation(&mut world, &second, Some(vec![2]));
+        assert_eq!(depths(&world, entity), UndoHistory { undo_depth: 1, redo_depth: 1 });
+    }
+
+    #[test]
+    fn test_handler_mutations_recorded_only_on_success() {
+        let (mut world, entity) = history_world(10);
+        let rejected = mutation(entity, 2, MutationOrigin::Mutate);
+        let accepted = mutation(entity, 3, MutationOrigin::Mutate);
+        await_handler(&mut world, &rejected, Some(vec![1]));
+        await_handler(&mut world, &accepted, Some(vec![1]));
+
+        // Responses from another connection don't match
+        handler_responded(&mut world, ConnectionId { id: 2 }, accepted.request_id, &MutationStatus::Ok);
+        handler_responded(&mut world, OPERATOR, rejected.request_id, &MutationStatus::Forbidden);
+        assert_eq!(world.resource::<MutationHistory>().undo_depth(entity), 0);
+
+        handler_responded(&mut world, OPERATOR, accepted.request_id, &MutationStatus::Ok);
+        assert_eq!(world.resource::<MutationHistory>().undo_depth(entity), 1);
+        assert!(world.resource::<MutationHistory>().awaiting_handler.is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+//! Validation of client mutations with per-field errors.
+//!
+//! A validator runs before a mutation reaches its handler or is applied. When
+//! it rejects the proposed value, the client gets a `ValidationError` response
+//! whose `field_errors` name each offending field, so forms can show the
+//! message next to the input instead of a single toast.
+//!
+//! Constraints can be declared on the component with `#[derive(ValidateMutation)]`
+//! from `pl3xus_macros` and enabled with `.validated()` on the sync builder:
+//!
+//! ```rust,ignore
+//! use pl3xus_macros::ValidateMutation;
+//!
+//! #[derive(Component, Serialize, Deserialize, Clone, Debug, ValidateMutation)]
+//! pub struct JogSettingsState {
+//!     #[validate(range(min = 0.1, max = 100.0))]
+//!     pub cartesian_jog_speed: f64,
+//!     #[validate(range(min = 1, max = 100))]
+//!     pub speed_override: u32,
+//! }
+//!
+//! app.sync_component_builder::<JogSettingsState>()
+//!     .validated()
+//!     .build();
+//! ```
+//!
+//! Or with a closure, for checks that involve several fields:
+//!
+//! ```rust,ignore
+//! app.sync_component_builder::<SoftLimits>()
+//!     .with_validator(|limits: &SoftLimits| {
+//!         if limits.min < limits.max {
+//!             Ok(())
+//!         } else {
+//!             Err(vec![FieldError::new("max", "must be greater than min")])
+//!         }
+//!     })
+//!     .build();
+//! ```
+
+use std::sync::Arc;
+
+use crate::messages::FieldError;
+
+/// Field constraints for a component type.
+///
+/// Typically implemented via `#[derive(ValidateMutation)]`.
+pub trait ValidateMutation {
+    /// Check a client-proposed value, returning one error per invalid field.
+    fn validate(&self) -> Result<(), Vec<FieldError>>;
+}
+
+/// Type-erased validator stored in a component's sync config.
+#[derive(Clone)]
+pub struct MutationValidator {
+    inner: Arc<ValidatorFn>,
+}
+
+type ValidatorFn = dyn Fn(&[u8]) -> Result<(), Vec<FieldError>> + Send + Sync;
+
+impl MutationValidator {
+    /// Create a validator for component `T` from a closure.
+    pub fn from_fn<T, F>(f: F) -> Self
+    where
+        T: for<'de> serde::Deserialize<'de> + 'static,
+        F: Fn(&T) -> Result<(), Vec<FieldError>> + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(move |bytes: &[u8]| {
+                // Values that don't decode are reported by the apply/handler
+                // path, which owns the decode error message.
+                match bincode::serde::decode_from_slice::<T, _>(bytes, bincode::config::standard()) {
+                    Ok((value, _)) => f(&value),
+                    Err(_) => Ok(()),
+                }
+            }),
+        }
+    }
+
+    /// Create a validator from `T`'s [`ValidateMutation`] implementation.
+    pub fn derived<T>() -> Self
+    where
+        T: ValidateMutation + for<'de> serde::Deserialize<'de> + 'static,
+    {
+        Self::from_fn(T::validate)
+    }
+
+    /// Validate an encoded mutation value.
+    pub fn validate(&self, value: &[u8]) -> Result<(), Vec<FieldError>> {
+        (self.inner)(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::ComponentSyncConfig;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct JogSettingsState {
+        cartesian_jog_speed: f64,
+        speed_override: u32,
+    }
+
+    // What `#[derive(ValidateMutation)]` generates for two range constraints
+    impl ValidateMutation for JogSettingsState {
+        fn validate(&self) -> Result<(), Vec<FieldError>> {
+            let mut errors = Vec::new();
+            if !(0.1..=100.0).contains(&self.cartesian_jog_speed) {
+                errors.push(FieldError::new("cartesian_jog_speed", "must be between 0.1 and 100.0"));
+            }
+            if !(1..=100).contains(&self.speed_override) {
+                errors.push(FieldError::new("speed_override", "must be between 1 and 100"));
+            }
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        }
+    }
+
+    fn encode(cartesian_jog_speed: f64, speed_override: u32) -> Vec<u8> {
+        let value = JogSettingsState {
+            cartesian_jog_speed,
+            speed_override,
+        };
+        bincode::serde::encode_to_vec(value, bincode::config::standard()).unwrap()
+    }
+
+    #[test]
+    fn test_derived_validator_reports_every_field() {
+        let config = ComponentSyncConfig::default().with_validator(MutationValidator::derived::<JogSettingsState>());
+
+        assert_eq!(config.validate_mutation(&encode(10.0, 50)), Ok(()));
+        let fields: Vec<String> = config
+            .validate_mutation(&encode(f64::NAN, 0))
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, ["cartesian_jog_speed", "speed_override"]);
+
+        // Undecodable values are left for the apply path to reject
+        assert_eq!(config.validate_mutation(&[0xff]), Ok(()));
+        // Components without a validator accept anything
+        assert_eq!(ComponentSyncConfig::default().validate_mutation(&encode(-1.0, 0)), Ok(()));
+    }
+
+    #[test]
+    fn test_closure_validator_checks_several_fields() {
+        let validator = MutationValidator::from_fn(|jog: &JogSettingsState| {
+            if jog.speed_override < 50 || jog.cartesian_jog_speed < 50.0 {
+                Ok(())
+            } else {
+                Err(vec![FieldError::new("speed_override", "too fast together with the jog speed")])
+            }
+        });
+        assert!(validator.validate(&encode(60.0, 10)).is_ok());
+        assert_eq!(
+            validator.validate(&encode(60.0, 60)),
+            Err(vec![FieldError::new("speed_override", "too fast together with the jog speed")])
+        );
+    }
+}
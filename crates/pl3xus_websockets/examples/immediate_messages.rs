@@ -44,7 +44,7 @@ fn handle_connection_events(
 ) {
     for event in network_events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 // Determine which network this connection belongs to
                 // Check WebSocket first since it's more specific
                 let is_ws = ws_net.has_connection(*conn_id);
@@ -74,7 +74,7 @@ fn handle_connection_events(
 ) {
     for event in network_events.read() {
         match event {
-            NetworkEvent::Connected(conn_id) => {
+            NetworkEvent::Connected(conn_id, _) => {
                 info!("New player connected: {}", conn_id);
                 commands.spawn((Player(*conn_id),));
 
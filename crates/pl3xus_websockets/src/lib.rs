@@ -14,15 +14,24 @@ pub use wasm_websocket::NetworkSettings;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod native_websocket {
-    use std::{net::SocketAddr, pin::Pin};
+    use std::{
+        net::{IpAddr, SocketAddr},
+        pin::Pin,
+        sync::Arc,
+    };
 
     use async_channel::{Receiver, Sender};
     use async_std::net::{TcpListener, TcpStream};
     use async_trait::async_trait;
+    use async_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+    use async_tungstenite::tungstenite::http::{HeaderValue, StatusCode, header};
     use async_tungstenite::tungstenite::protocol::WebSocketConfig;
     use bevy::prelude::{Deref, DerefMut, Resource};
+    use pl3xus::connection_info::{forwarded_addrs, forwarded_client};
+    use pl3xus::{ConnectionInfo, HealthState};
     use pl3xus::managers::NetworkProvider;
     use pl3xus_common::NetworkPacket;
+    use pl3xus_common::codec::TypeInterner;
     use pl3xus_common::error::NetworkError;
     use futures::AsyncReadExt;
     use futures_lite::{AsyncWriteExt, Future, FutureExt, Stream};
@@ -40,11 +49,11 @@ mod native_websocket {
 
         type NetworkSettings = NetworkSettings;
 
-        type Socket = WsStream<TcpStream>;
+        type Socket = WebSocketConnection;
 
         type ReadHalf = futures::io::ReadHalf<WsStream<TcpStream>>;
 
-        type WriteHalf = futures::io::WriteHalf<WsStream<TcpStream>>;
+        type WriteHalf = WebSocketWriter;
 
         type ConnectInfo = url::Url;
 
@@ -54,21 +63,21 @@ mod native_websocket {
 
         async fn accept_loop(
             accept_info: Self::AcceptInfo,
-            _: Self::NetworkSettings,
+            network_settings: Self::NetworkSettings,
         ) -> Result<Self::AcceptStream, NetworkError> {
             info!("[accept_loop] Starting - attempting to bind to {}", accept_info);
             let listener = TcpListener::bind(accept_info)
                 .await
                 .map_err(NetworkError::Listen)?;
             info!("[accept_loop] Successfully bound to {}", accept_info);
-            Ok(OwnedIncoming::new(listener))
+            Ok(OwnedIncoming::new(listener, network_settings))
         }
 
         async fn connect_task(
             connect_info: Self::ConnectInfo,
             network_settings: Self::NetworkSettings,
         ) -> Result<Self::Socket, NetworkError> {
-            info!("Beginning connection");
+            debug!("Beginning connection");
             if connect_info.scheme() == "wss" {
                 return Err(NetworkError::Error(
                     "WSS connections require the TlsWebSocketProvider. Enable the 'tls' feature and use TlsWebSocketProvider instead".to_string(),
@@ -119,7 +128,13 @@ mod native_websocket {
                 }
             })?;
             info!("Connected!");
-            return Ok(WsStream::new(stream));
+            return Ok(WebSocketConnection {
+                stream: WsStream::new(stream),
+                info: ConnectionInfo {
+                    endpoint: Some(connect_info.path().to_string()),
+                    ..Default::default()
+                },
+            });
         }
 
         async fn recv_loop(
@@ -129,7 +144,7 @@ mod native_websocket {
         ) {
             let mut buffer = vec![0; settings.max_message_size.unwrap_or(64 << 20)];
             loop {
-                info!("Reading message length");
+                pl3xus::message_event!("Reading message length");
                 let length = match read_half.read(&mut buffer[..8]).await {
                     Ok(0) => {
                         // EOF, meaning the TCP stream has closed.
@@ -158,7 +173,7 @@ mod native_websocket {
                         break;
                     }
                 };
-                info!("Message length: {}", length);
+                pl3xus::message_event!(length, "Message length");
 
                 if length > settings.max_message_size.unwrap_or(64 << 20) {
                     error!(
@@ -169,7 +184,7 @@ mod native_websocket {
                     break;
                 }
 
-                info!("Reading message into buffer");
+                pl3xus::message_event!("Reading message into buffer");
                 match read_half.read_exact(&mut buffer[..length]).await {
                     Ok(()) => (),
                     Err(err) => {
@@ -180,7 +195,7 @@ mod native_websocket {
                         break;
                     }
                 }
-                info!("Message read");
+                pl3xus::message_event!("Message read");
 
                 let packet: NetworkPacket = match bincode::serde::decode_from_slice(&buffer[..length], bincode::config::standard()) {
                     Ok((packet, _)) => packet,
@@ -195,12 +210,12 @@ mod native_websocket {
                     error!("Failed to send decoded message to pl3xus");
                     break;
                 }
-                info!("Message deserialized and sent to pl3xus");
+                pl3xus::message_event!("Message deserialized and sent to pl3xus");
             }
         }
 
         async fn send_loop(
-            mut write_half: Self::WriteHalf,
+            mut writer: Self::WriteHalf,
             messages: Receiver<NetworkPacket>,
             settings: Self::NetworkSettings,
         ) {
@@ -241,7 +256,19 @@ mod native_websocket {
                 // Serialize and combine all messages into a single buffer
                 let mut combined_buffer = Vec::new();
 
-                for message in batch {
+                // Clients on pl3xus.batch.v1 get one PacketBatch with interned type names
+                let plain = match &mut writer.interner {
+                    Some(interner) => {
+                        match interner.encode(batch) {
+                            Ok(frame) => combined_buffer = frame,
+                            Err(err) => error!("Could not encode batch of {} messages: {}", batch_size, err),
+                        }
+                        Vec::new()
+                    }
+                    None => batch,
+                };
+
+                for message in plain {
                     let encoded = match bincode::serde::encode_to_vec(&message, bincode::config::standard()) {
                         Ok(encoded) => encoded,
                         Err(err) => {
@@ -264,7 +291,7 @@ mod native_websocket {
                 trace!("Sending {} bytes ({} messages)", combined_buffer.len(), batch_size);
 
                 // Single write for entire batch
-                match write_half.write_all(&combined_buffer).await {
+                match writer.inner.write_all(&combined_buffer).await {
                     Ok(_) => {
                         if batch_size > 1 {
                             debug!("Successfully sent batch of {} messages", batch_size);
@@ -281,7 +308,19 @@ mod native_websocket {
         }
 
         fn split(combined: Self::Socket) -> (Self::ReadHalf, Self::WriteHalf) {
-            combined.split()
+            let batched = combined.info.subprotocol.as_deref() == Some(pl3xus::codec::BATCH_SUBPROTOCOL);
+            let (read_half, write_half) = combined.stream.split();
+            (
+                read_half,
+                WebSocketWriter {
+                    inner: write_half,
+                    interner: batched.then(TypeInterner::default),
+                },
+            )
+        }
+
+        fn connection_info(socket: &Self::Socket) -> ConnectionInfo {
+            socket.info.clone()
         }
 
         fn channel_capacity(settings: &Self::NetworkSettings) -> usize {
@@ -289,6 +328,19 @@ mod native_websocket {
         }
     }
 
+    /// A WebSocket and what its handshake told us.
+    pub struct WebSocketConnection {
+        stream: WsStream<TcpStream>,
+        info: ConnectionInfo,
+    }
+
+    /// Write half of a [`WebSocketConnection`].
+    pub struct WebSocketWriter {
+        inner: futures::io::WriteHalf<WsStream<TcpStream>>,
+        /// Type ids sent so far, if the client negotiated `pl3xus.batch.v1`
+        interner: Option<TypeInterner>,
+    }
+
     #[derive(Clone, Debug, Resource, Deref, DerefMut)]
     #[allow(missing_copy_implementations)]
     /// Settings to configure the network, both client and server
@@ -305,6 +357,98 @@ mod native_websocket {
         pub channel_capacity: usize,
         /// Warn when channel depth exceeds this percentage (default: 80)
         pub channel_warning_threshold: u8,
+        /// Paths clients may connect on (e.g. `/sync`, `/devtools`, `/admin`).
+        ///
+        /// Handshakes on other paths are refused with 404. Empty (the default)
+        /// accepts every path. Either way, each connection is tagged with its
+        /// path, see `pl3xus::ConnectionInfos`.
+        pub endpoints: Vec<String>,
+        /// Answer plain HTTP `GET /healthz` and `GET /readyz` on the listener
+        /// from this report (default: `None`). Take it from the app after
+        /// adding `pl3xus::HealthPlugin`.
+        pub health: Option<HealthState>,
+        /// Origins browsers may connect from (e.g. `https://hmi.example.com`).
+        ///
+        /// Handshakes whose `Origin` header isn't listed are refused with 403.
+        /// Handshakes without one are accepted, as only browsers send it
+        /// (native clients and `pl3xus-cli` don't). Empty (the default)
+        /// accepts every origin.
+        pub allowed_origins: Vec<String>,
+        /// Proxies (e.g. nginx or traefik) trusted to report the client's
+        /// address in `Forwarded` or `X-Forwarded-For` (default: none, so
+        /// the headers are ignored). See `pl3xus::ConnectionInfo::client_addr`.
+        pub trusted_proxies: Vec<IpAddr>,
+        /// Subprotocols the server accepts, most preferred first (default:
+        /// `pl3xus.batch.v1`, then `pl3xus.bincode.v1`).
+        ///
+        /// Clients that offer subprotocols get the first one here that they
+        /// offered, and are refused with 400 if there is none. Clients that
+        /// offer none are accepted without one.
+        pub subprotocols: Vec<String>,
+    }
+
+    impl NetworkSettings {
+        /// Whether a browser on `origin` may connect. Ignores case and a
+        /// trailing `/`.
+        pub fn origin_allowed(&self, origin: &str) -> bool {
+            let origin = origin.trim_end_matches('/');
+            self.allowed_origins.is_empty()
+                || self
+                    .allowed_origins
+                    .iter()
+                    .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+        }
+    }
+
+    /// Refuse a handshake whose `Origin` isn't allowed. Handshakes without
+    /// one are accepted; a value that isn't UTF-8 is compared lossily, so it
+    /// only passes when every origin is allowed.
+    fn check_origin(settings: &NetworkSettings, request: &Request) -> Result<(), String> {
+        let Some(origin) = request.headers().get(header::ORIGIN) else {
+            return Ok(());
+        };
+        let origin = String::from_utf8_lossy(origin.as_bytes());
+        if settings.origin_allowed(&origin) {
+            Ok(())
+        } else {
+            Err(format!("Origin {} is not allowed", origin))
+        }
+    }
+
+    /// The subprotocol to use, from those the client offered in
+    /// `Sec-WebSocket-Protocol` headers. `Ok(None)` if it offered none.
+    fn negotiate_subprotocol(settings: &NetworkSettings, request: &Request) -> Result<Option<String>, String> {
+        let offered: Vec<&str> = request
+            .headers()
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|protocol| !protocol.is_empty())
+            .collect();
+        if offered.is_empty() {
+            return Ok(None);
+        }
+        settings
+            .subprotocols
+            .iter()
+            .find(|supported| offered.contains(&supported.as_str()))
+            .map(|supported| Some(supported.clone()))
+            .ok_or_else(|| {
+                format!(
+                    "Unsupported subprotocols {}; supported: {}",
+                    offered.join(", "),
+                    settings.subprotocols.join(", ")
+                )
+            })
+    }
+
+    /// An HTTP error response refusing a handshake.
+    fn refusal(status: StatusCode, reason: String) -> ErrorResponse {
+        let mut response = ErrorResponse::new(Some(reason));
+        *response.status_mut() = status;
+        response
     }
 
     impl Default for NetworkSettings {
@@ -313,29 +457,39 @@ mod native_websocket {
                 websocket_config: WebSocketConfig::default(),
                 channel_capacity: 500,
                 channel_warning_threshold: 80,
+                endpoints: Vec::new(),
+                health: None,
+                allowed_origins: Vec::new(),
+                trusted_proxies: Vec::new(),
+                subprotocols: vec![
+                    pl3xus::codec::BATCH_SUBPROTOCOL.to_string(),
+                    pl3xus::codec::BINCODE_SUBPROTOCOL.to_string(),
+                ],
             }
         }
     }
 
     /// A special stream for recieving ws connections
-    type WsStreamFuture = Pin<Box<dyn Future<Output = Option<WsStream<TcpStream>>>>>;
+    type WsStreamFuture = Pin<Box<dyn Future<Output = Option<WebSocketConnection>>>>;
 
     pub struct OwnedIncoming {
         inner: TcpListener,
+        settings: Arc<NetworkSettings>,
         stream: Option<WsStreamFuture>,
     }
 
     impl OwnedIncoming {
-        fn new(listener: TcpListener) -> Self {
+        fn new(listener: TcpListener, settings: NetworkSettings) -> Self {
             Self {
                 inner: listener,
+                settings: Arc::new(settings),
                 stream: None,
             }
         }
     }
 
     impl Stream for OwnedIncoming {
-        type Item = WsStream<TcpStream>;
+        type Item = WebSocketConnection;
 
         fn poll_next(
             self: Pin<&mut Self>,
@@ -344,35 +498,80 @@ mod native_websocket {
             let incoming = self.get_mut();
             if incoming.stream.is_none() {
                 let listener: *const TcpListener = &incoming.inner;
+                let settings = incoming.settings.clone();
                 incoming.stream = Some(Box::pin(async move {
-                    let stream = unsafe {
-                        listener
-                            .as_ref()
-                            .expect("Segfault when trying to read listener in OwnedStream")
-                    }
-                    .accept()
-                    .await
-                    .map(|(s, _)| s)
-                    .ok();
-
-                    let stream: WsStream<TcpStream> = match stream {
-                        Some(stream) => {
-                            info!("🔌 [ACCEPT] TCP connection accepted, attempting WebSocket handshake...");
-                            match async_tungstenite::accept_async(stream).await {
-                                Ok(stream) => {
-                                    info!("🔌 [ACCEPT] WebSocket handshake successful!");
-                                    WsStream::new(stream)
-                                }
-                                Err(e) => {
-                                    error!("🔌 [ACCEPT] WebSocket handshake failed: {:?}", e);
-                                    return None;
-                                }
+                    // Keep accepting until a connection completes its handshake;
+                    // only a listener error ends the stream
+                    loop {
+                        let (mut stream, peer_addr) = unsafe {
+                            listener
+                                .as_ref()
+                                .expect("Segfault when trying to read listener in OwnedStream")
+                        }
+                        .accept()
+                        .await
+                        .ok()?;
+
+                        if let Some(health) = &settings.health {
+                            let mut request = [0; 1024];
+                            let peeked = stream.peek(&mut request).await.unwrap_or_default();
+                            if let Some(response) = health.http_response(&request[..peeked]) {
+                                debug!("🔌 [ACCEPT] Answering health probe");
+                                let _ = stream.read(&mut request[..peeked]).await;
+                                let _ = stream.write_all(&response).await;
+                                let _ = stream.close().await;
+                                continue;
                             }
                         }
 
-                        None => return None,
-                    };
-                    Some(stream)
+                        info!("🔌 [ACCEPT] TCP connection accepted, attempting WebSocket handshake...");
+                        let mut info = ConnectionInfo::from_peer(peer_addr);
+                        let check_request = |request: &Request, mut response: Response| {
+                            let path = request.uri().path();
+                            if !settings.endpoints.is_empty() && !settings.endpoints.iter().any(|allowed| allowed == path) {
+                                warn!("🔌 [ACCEPT] Refusing connection on unknown endpoint {}", path);
+                                return Err(refusal(StatusCode::NOT_FOUND, format!("Unknown endpoint {}", path)));
+                            }
+                            if let Err(reason) = check_origin(&settings, request) {
+                                warn!("🔌 [ACCEPT] Refusing connection: {}", reason);
+                                return Err(refusal(StatusCode::FORBIDDEN, reason));
+                            }
+                            let subprotocol = negotiate_subprotocol(&settings, request).map_err(|reason| {
+                                warn!("🔌 [ACCEPT] Refusing connection: {}", reason);
+                                refusal(StatusCode::BAD_REQUEST, reason)
+                            })?;
+                            if let Some(subprotocol) = &subprotocol
+                                && let Ok(value) = HeaderValue::from_str(subprotocol)
+                            {
+                                response.headers_mut().insert(header::SEC_WEBSOCKET_PROTOCOL, value);
+                            }
+                            if !settings.trusted_proxies.is_empty() {
+                                let headers = request.headers();
+                                let forwarded = forwarded_addrs(
+                                    headers.get(header::FORWARDED).and_then(|value| value.to_str().ok()),
+                                    headers.get("x-forwarded-for").and_then(|value| value.to_str().ok()),
+                                );
+                                info.client_addr =
+                                    Some(forwarded_client(peer_addr.ip(), &settings.trusted_proxies, &forwarded));
+                            }
+                            info.endpoint = Some(path.to_string());
+                            info.subprotocol = subprotocol;
+                            Ok(response)
+                        };
+                        match async_tungstenite::accept_hdr_async(stream, check_request).await {
+                            Ok(stream) => {
+                                info!(
+                                    "🔌 [ACCEPT] WebSocket handshake successful from {:?} on {:?}",
+                                    info.client_addr, info.endpoint
+                                );
+                                return Some(WebSocketConnection {
+                                    stream: WsStream::new(stream),
+                                    info,
+                                });
+                            }
+                            Err(e) => error!("🔌 [ACCEPT] WebSocket handshake failed: {:?}", e),
+                        }
+                    }
                 }));
             }
             if let Some(stream) = &mut incoming.stream
@@ -385,6 +584,55 @@ mod native_websocket {
     }
 
     unsafe impl Send for OwnedIncoming {}
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn settings(allowed_origins: &[&str]) -> NetworkSettings {
+            NetworkSettings {
+                allowed_origins: allowed_origins.iter().map(|origin| origin.to_string()).collect(),
+                ..Default::default()
+            }
+        }
+
+        fn handshake(origin: Option<HeaderValue>) -> Request {
+            let mut request = Request::new(());
+            if let Some(origin) = origin {
+                request.headers_mut().insert(header::ORIGIN, origin);
+            }
+            request
+        }
+
+        #[test]
+        fn test_origin_allowed_normalizes() {
+            let restricted = settings(&["https://HMI.example.com/"]);
+            assert!(restricted.origin_allowed("https://hmi.example.com"));
+            assert!(restricted.origin_allowed("https://hmi.example.com/"));
+            assert!(!restricted.origin_allowed("https://evil.example.com"));
+            assert!(!restricted.origin_allowed("http://hmi.example.com"));
+
+            // No list accepts every origin
+            assert!(settings(&[]).origin_allowed("https://anything.example.com"));
+        }
+
+        #[test]
+        fn test_check_origin_headers() {
+            let allowed = HeaderValue::from_static("https://hmi.example.com");
+            let not_utf8 = HeaderValue::from_bytes(b"https://hmi.example.com\xff").unwrap();
+            let restricted = settings(&["https://hmi.example.com"]);
+
+            assert!(check_origin(&restricted, &handshake(Some(allowed))).is_ok());
+            // Native clients send no Origin
+            assert!(check_origin(&restricted, &handshake(None)).is_ok());
+            assert!(check_origin(&restricted, &handshake(Some(not_utf8.clone()))).is_err());
+            assert!(check_origin(&settings(&[]), &handshake(Some(not_utf8))).is_ok());
+            assert_eq!(
+                check_origin(&restricted, &handshake(Some(HeaderValue::from_static("https://evil.example.com")))),
+                Err("Origin https://evil.example.com is not allowed".to_string())
+            );
+        }
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -437,7 +685,7 @@ mod wasm_websocket {
             connect_info: Self::ConnectInfo,
             network_settings: Self::NetworkSettings,
         ) -> Result<Self::Socket, NetworkError> {
-            info!("Beginning connection");
+            debug!("Beginning connection");
             let stream =
                 WsMeta::connect(connect_info, None)
                     .await
@@ -482,7 +730,7 @@ mod wasm_websocket {
         ) {
             let mut buffer = vec![0; settings.max_message_size];
             loop {
-                info!("Reading message length");
+                pl3xus::message_event!("Reading message length");
                 let length = match read_half.read(&mut buffer[..8]).await {
                     Ok(0) => {
                         // EOF, meaning the TCP stream has closed.
@@ -511,7 +759,7 @@ mod wasm_websocket {
                         break;
                     }
                 };
-                info!("Message length: {}", length);
+                pl3xus::message_event!(length, "Message length");
 
                 if length > settings.max_message_size {
                     error!(
@@ -521,7 +769,7 @@ mod wasm_websocket {
                     break;
                 }
 
-                info!("Reading message into buffer");
+                pl3xus::message_event!("Reading message into buffer");
                 match read_half.read_exact(&mut buffer[..length]).await {
                     Ok(()) => (),
                     Err(err) => {
@@ -532,7 +780,7 @@ mod wasm_websocket {
                         break;
                     }
                 }
-                info!("Message read");
+                pl3xus::message_event!("Message read");
 
                 let packet: NetworkPacket = match bincode::serde::decode_from_slice(&buffer[..length], bincode::config::standard()) {
                     Ok((packet, _)) => packet,
@@ -547,7 +795,7 @@ mod wasm_websocket {
                     error!("Failed to send decoded message to pl3xus");
                     break;
                 }
-                info!("Message deserialized and sent to pl3xus");
+                pl3xus::message_event!("Message deserialized and sent to pl3xus");
             }
         }
 
@@ -644,6 +892,11 @@ mod wasm_websocket {
     #[derive(Clone, Debug, Resource)]
     #[allow(missing_copy_implementations)]
     /// Settings to configure the network, both client and server
+    ///
+    /// The browser sets the handshake's `Origin` header to the origin the page
+    /// was served from, and it can't be overridden. A native server with
+    /// `allowed_origins` set must list that origin (e.g.
+    /// `http://127.0.0.1:8084` for a trunk dev server).
     pub struct NetworkSettings {
         pub max_message_size: usize,
         /// Channel capacity for outgoing messages per connection (default: 500)
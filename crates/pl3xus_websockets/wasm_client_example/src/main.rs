@@ -84,7 +84,7 @@ fn handle_network_events(
     for event in new_network_events.read() {
         info!("Received event");
         match event {
-            NetworkEvent::Connected(_) => {
+            NetworkEvent::Connected(..) => {
                 messages.add(SystemMessage::new(
                     "Succesfully connected to server!".to_string(),
                 ));
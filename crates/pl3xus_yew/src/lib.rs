@@ -0,0 +1,242 @@
+//! # Pl3xus Yew
+//!
+//! Yew hooks for `pl3xus_sync`, built on `pl3xus_client_core`. Covers
+//! connecting, component subscriptions (shared between hooks, like the Leptos
+//! client) and mutations:
+//!
+//! ```rust,ignore
+//! use yew::prelude::*;
+//! use pl3xus_yew::{use_components, ClientTypeRegistry, SyncProvider};
+//!
+//! #[function_component]
+//! fn App() -> Html {
+//!     let registry = use_memo((), |_| ClientTypeRegistry::builder().register::<Position>().build());
+//!     html! {
+//!         <SyncProvider url="ws://localhost:8082/sync" registry={(*registry).clone()}>
+//!             <Positions />
+//!         </SyncProvider>
+//!     }
+//! }
+//!
+//! #[function_component]
+//! fn Positions() -> Html {
+//!     let positions = use_components::<Position>();
+//!     positions.iter().map(|(id, position)| html! { <div>{format!("Entity {id}: {position:?}")}</div> }).collect()
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use pl3xus_client_core::{ClientEvent, SocketEvent, SyncClientCore, SyncSocket};
+use pl3xus_common::{ConnectionId, NetworkPacket};
+use yew::prelude::*;
+
+pub use pl3xus_client_core::{ClientTypeRegistry, SyncComponent, SyncError};
+pub use pl3xus_sync::{FilterOp, FilterValue, SubscriptionFilter};
+
+/// Reactive part of the connection; a change re-renders context consumers.
+#[derive(Clone, Default, PartialEq)]
+struct ConnectionState {
+    /// Bumped per component type whenever its received values change
+    revisions: HashMap<String, u64>,
+    connected: bool,
+    last_error: Option<String>,
+}
+
+enum ConnectionAction {
+    Socket(SocketEvent),
+    Failed(SyncError),
+}
+
+impl Reducible for ConnectionState {
+    type Action = ConnectionAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            ConnectionAction::Socket(SocketEvent::Open) => state.connected = true,
+            ConnectionAction::Socket(SocketEvent::Closed) => state.connected = false,
+            ConnectionAction::Socket(SocketEvent::Client(ClientEvent::ComponentsChanged(component_types))) => {
+                for component_type in component_types {
+                    *state.revisions.entry(component_type).or_insert(0) += 1;
+                }
+            }
+            ConnectionAction::Socket(SocketEvent::Client(_)) => return self,
+            ConnectionAction::Failed(error) => state.last_error = Some(error.to_string()),
+        }
+        Rc::new(state)
+    }
+}
+
+/// Handle to the sync connection, provided as context by [`SyncProvider`].
+#[derive(Clone)]
+pub struct SyncClient {
+    core: Rc<RefCell<SyncClientCore>>,
+    socket: Rc<RefCell<Option<SyncSocket>>>,
+    state: Rc<ConnectionState>,
+}
+
+impl PartialEq for SyncClient {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.core, &other.core) && self.state == other.state
+    }
+}
+
+impl SyncClient {
+    /// Whether the socket is open.
+    pub fn is_connected(&self) -> bool {
+        self.state.connected
+    }
+
+    /// The last connection error, if any.
+    pub fn last_error(&self) -> Option<&str> {
+        self.state.last_error.as_deref()
+    }
+
+    /// This client's connection id, once the server has welcomed it.
+    pub fn connection_id(&self) -> Option<ConnectionId> {
+        self.core.borrow().connection_id()
+    }
+
+    /// Send a mutation of `entity_id`'s `T`. Returns its request id.
+    pub fn mutate<T: SyncComponent>(&self, entity_id: u64, value: &T) -> Result<u64, SyncError> {
+        let (request_id, packet) = self.core.borrow_mut().mutate(entity_id, value)?;
+        self.send(&packet)?;
+        Ok(request_id)
+    }
+
+    fn subscribe(&self, component_name: &str, filter: Option<SubscriptionFilter>) -> String {
+        let (key, packet) = self.core.borrow_mut().subscribe(component_name, filter);
+        // Not connected yet: the socket sends it on open
+        if let Some(packet) = packet {
+            let _ = self.send(&packet);
+        }
+        key
+    }
+
+    fn unsubscribe(&self, key: &str) {
+        let packet = self.core.borrow_mut().unsubscribe(key);
+        if let Some(packet) = packet {
+            let _ = self.send(&packet);
+        }
+    }
+
+    fn send(&self, packet: &NetworkPacket) -> Result<(), SyncError> {
+        match self.socket.borrow().as_ref() {
+            Some(socket) => socket.send(packet),
+            None => Err(SyncError::NotConnected),
+        }
+    }
+
+    fn revision(&self, component_name: &str) -> u64 {
+        self.state.revisions.get(component_name).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Properties)]
+pub struct SyncProviderProps {
+    pub url: AttrValue,
+    pub registry: Arc<ClientTypeRegistry>,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+impl PartialEq for SyncProviderProps {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url && Arc::ptr_eq(&self.registry, &other.registry) && self.children == other.children
+    }
+}
+
+/// Connect to a pl3xus_sync server and provide the [`SyncClient`] to
+/// descendants. Reconnects when `url` changes.
+#[function_component]
+pub fn SyncProvider(props: &SyncProviderProps) -> Html {
+    let core = use_mut_ref(|| SyncClientCore::new(props.registry.clone()));
+    let socket = use_mut_ref(|| None::<SyncSocket>);
+    let state = use_reducer(ConnectionState::default);
+
+    {
+        let core = core.clone();
+        let socket = socket.clone();
+        let dispatcher = state.dispatcher();
+        use_effect_with(props.url.clone(), move |url| {
+            let events = dispatcher.clone();
+            match SyncSocket::connect(url, core, move |event| events.dispatch(ConnectionAction::Socket(event))) {
+                Ok(connected) => *socket.borrow_mut() = Some(connected),
+                Err(error) => dispatcher.dispatch(ConnectionAction::Failed(error)),
+            }
+            move || {
+                socket.borrow_mut().take();
+            }
+        });
+    }
+
+    let client = SyncClient {
+        core,
+        socket,
+        state: Rc::new((*state).clone()),
+    };
+
+    html! {
+        <ContextProvider<SyncClient> context={client}>
+            {props.children.clone()}
+        </ContextProvider<SyncClient>>
+    }
+}
+
+/// The [`SyncClient`] provided by [`SyncProvider`].
+///
+/// # Panics
+///
+/// Outside a [`SyncProvider`].
+#[hook]
+pub fn use_sync_client() -> SyncClient {
+    use_context::<SyncClient>().expect("use_sync_client must be used inside a SyncProvider")
+}
+
+/// All entities with component `T`, by entity id.
+///
+/// Subscribes while the component is mounted; hooks for the same type share
+/// one server subscription.
+#[hook]
+pub fn use_components<T>() -> Rc<HashMap<u64, T>>
+where
+    T: SyncComponent,
+{
+    use_subscription(None)
+}
+
+/// The entities with component `T` matching `filter`, by entity id.
+///
+/// The server only sends matching entities; see [`SubscriptionFilter`].
+#[hook]
+pub fn use_components_filtered<T>(filter: SubscriptionFilter) -> Rc<HashMap<u64, T>>
+where
+    T: SyncComponent,
+{
+    use_subscription(Some(filter))
+}
+
+#[hook]
+fn use_subscription<T>(filter: Option<SubscriptionFilter>) -> Rc<HashMap<u64, T>>
+where
+    T: SyncComponent,
+{
+    let client = use_sync_client();
+    let key = use_memo((), {
+        let client = client.clone();
+        let filter = filter.clone();
+        move |_| client.subscribe(T::component_name(), filter)
+    });
+    {
+        let client = client.clone();
+        use_effect_with((), move |_| move || client.unsubscribe(&key));
+    }
+
+    // Decode again only when this type's values change
+    let revision = client.revision(T::component_name());
+    use_memo(revision, move |_| client.core.borrow().components::<T>(filter.as_ref()))
+}
@@ -111,9 +111,8 @@ fn handle_move_commands(
     time: Res<Time>,
 ) {
     for event in message_reader.read() {
-        let cmd = &event.message;
-        let target_entity = event.target_entity;
         let client_id = event.source;
+        let (target_entity, cmd) = event.clone().into_parts();
 
         // Find the robot - authorization already verified by middleware
         let Ok((_entity, mut robot, mut status, mut control)) = robots.get_mut(target_entity) else {
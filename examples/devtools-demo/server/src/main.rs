@@ -122,7 +122,7 @@ fn setup_networking(
 fn log_connections(mut events: MessageReader<NetworkEvent>) {
     for event in events.read() {
         match event {
-            NetworkEvent::Connected(id) => {
+            NetworkEvent::Connected(id, _) => {
                 info!("Client connected: {:?}", id);
             }
             NetworkEvent::Disconnected(id) => {
@@ -4,7 +4,7 @@ use pl3xus_client::{
 
 #[cfg(target_arch = "wasm32")]
 use pl3xus_client::devtools::DevTools;
-use fanuc_real_types::{RobotPosition, RobotStatus, JointAngles, RobotInfo};
+use fanuc_real_types::{FanucTypes, RobotPosition, RobotStatus, JointAngles, RobotInfo};
 use leptos::prelude::*;
 
 // SyncComponent is automatically implemented for all Serialize + Deserialize types.
@@ -21,10 +21,7 @@ fn main() {
 fn App() -> impl IntoView {
     // Create type registry with DevTools support
     let registry = ClientTypeRegistry::builder()
-        .register::<RobotPosition>()
-        .register::<RobotStatus>()
-        .register::<JointAngles>()
-        .register::<RobotInfo>()
+        .register_manifest::<FanucTypes>()
         .with_devtools_support()  // Enable DevTools
         .build();
 
@@ -9,7 +9,7 @@ use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
 use pl3xus::{Pl3xusRuntime, Network};
 use pl3xus_sync::{AppPl3xusSyncExt, Pl3xusSyncPlugin};
 use pl3xus_websockets::{NetworkSettings, WebSocketProvider};
-use fanuc_real_types::{RobotPosition, RobotStatus, JointAngles, RobotInfo, MotionCommand, JogCommand, JogAxis, JogDirection};
+use fanuc_real_types::{FanucTypes, RobotPosition, RobotStatus, JointAngles, RobotInfo, MotionCommand, JogCommand, JogAxis, JogDirection};
 use fanuc_rmi::{
     drivers::{FanucDriver, FanucDriverConfig},
     dto,
@@ -48,14 +48,9 @@ fn main() {
     // Install the sync middleware
     app.add_plugins(Pl3xusSyncPlugin::<WebSocketProvider>::default());
 
-    // Register robot components for synchronization
-    app.sync_component::<RobotPosition>(None);
-    app.sync_component::<RobotStatus>(None);
-    app.sync_component::<JointAngles>(None);
-    app.sync_component::<RobotInfo>(None);
+    // Register robot components for synchronization (the same list the client registers).
     // MotionCommand contains dto::Instruction which is WASM-compatible (DTO feature has no tokio/mio)
-    app.sync_component::<MotionCommand>(None);
-    app.sync_component::<JogCommand>(None);
+    app.register_manifest::<FanucTypes, WebSocketProvider>();
 
     app.add_systems(Startup, (setup_robot, setup_networking, setup_driver));
     app.add_systems(Update, (process_jog_commands, process_motion_commands, update_robot_state, poll_robot_status));
@@ -55,7 +55,7 @@ pub fn JogControls() -> impl IntoView {
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
         let state = control_state.get();
-        Some(state.client_id) == my_id
+        Some(state.client.connection) == my_id
     };
 
     let jog = move |axis: JogAxis, direction: JogDirection| {
@@ -5,7 +5,7 @@
 use leptos::prelude::*;
 use leptos_router::hooks::use_navigate;
 
-use pl3xus_client::{use_entity_component, use_sync_context, use_connection, use_query_keyed, use_message, use_request, ControlRequest, ControlResponse, EntityControl, ConnectionReadyState};
+use pl3xus_client::{use_entity_component, use_sync_context, use_connection, use_query_keyed, use_message, use_request, ControlResponse, EntityControl, ConnectionReadyState};
 use fanuc_replica_types::*;
 use crate::pages::dashboard::use_system_entity;
 
@@ -373,15 +373,15 @@ fn QuickSettingsButton() -> impl IntoView {
     };
     let active_connection_id = move || if robot_exists.get() { connection_state.get().active_connection_id } else { None };
 
-    // Check if THIS client has control by comparing EntityControl.client_id with our own connection ID
+    // Check if THIS client has control by comparing EntityControl.client with our own connection ID
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
-        Some(control_state.get().client_id) == my_id
+        Some(control_state.get().client.connection) == my_id
     };
 
     // Get the controlling client ID (if any)
     let controlling_client_id = move || -> Option<u32> {
-        Some(control_state.get().client_id.id)
+        control_state.get().holder().map(|holder| holder.connection.id)
     };
 
     view! {
@@ -735,7 +735,7 @@ fn ControlActions(has_control: Signal<bool>) -> impl IntoView {
                             class="w-full text-[9px] px-3 py-1.5 bg-primary text-primary-foreground rounded hover:brightness-110"
                             on:click=move |_| {
                                 if let Some(entity_bits) = system_entity_bits() {
-                                    ctx.send(ControlRequest::Take(entity_bits));
+                                    ctx.take_control(entity_bits);
                                 }
                             }
                         >
@@ -750,7 +750,7 @@ fn ControlActions(has_control: Signal<bool>) -> impl IntoView {
                             class="w-full text-[9px] px-3 py-1.5 bg-destructive text-destructive-foreground rounded hover:brightness-110"
                             on:click=move |_| {
                                 if let Some(entity_bits) = system_entity_bits() {
-                                    ctx.send(ControlRequest::Release(entity_bits));
+                                    ctx.release_control(entity_bits);
                                 }
                             }
                         >
@@ -782,11 +782,11 @@ fn ControlButton() -> impl IntoView {
         system_ctx.system_entity_id.get()
     };
 
-    // Check if THIS client has control by comparing EntityControl.client_id with our own connection ID
+    // Check if THIS client has control by comparing EntityControl.client with our own connection ID
     // Use the System entity (from context) to check control status
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
-        Some(control_state.get().client_id) == my_id
+        Some(control_state.get().client.connection) == my_id
     };
 
     // Check if another client has control
@@ -794,7 +794,7 @@ fn ControlButton() -> impl IntoView {
         let my_id = ctx.my_connection_id.get();
         let state = control_state.get();
         // Someone has control and it's not us
-        state.client_id.id != 0 && Some(state.client_id) != my_id
+        state.is_controlled() && Some(state.client.connection) != my_id
     };
 
     view! {
@@ -821,11 +821,11 @@ fn ControlButton() -> impl IntoView {
 
                     if has_control() {
                         leptos::logging::log!("[ControlButton] Releasing control");
-                        ctx.send(ControlRequest::Release(entity_bits));
+                        ctx.release_control(entity_bits);
                         // Toast will be shown by ControlResponseHandler when server responds
                     } else {
                         leptos::logging::log!("[ControlButton] Requesting control");
-                        ctx.send(ControlRequest::Take(entity_bits));
+                        ctx.take_control(entity_bits);
                         // Toast will be shown by ControlResponseHandler when server responds
                     }
                 }
@@ -917,6 +917,15 @@ pub fn ControlResponseHandler() -> impl IntoView {
             ControlResponseKind::Error(msg) => {
                 toast.error(format!("Control error: {}", msg));
             }
+            ControlResponseKind::Denied { reason } => {
+                toast.warning(format!("Control denied - {}", reason));
+            }
+            ControlResponseKind::AwaitingApproval => {
+                toast.info("Control requested - waiting for approval");
+            }
+            ControlResponseKind::TimeoutWarning { seconds_remaining, .. } => {
+                toast.warning(format!("Control will be released in {:.0}s due to inactivity", seconds_remaining));
+            }
         }
     });
 
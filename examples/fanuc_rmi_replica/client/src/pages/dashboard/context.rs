@@ -53,7 +53,7 @@ use js_sys;
 ///
 /// // In child components - target system for control
 /// let ctx = use_system_entity();
-/// ctx.send_targeted(ctx.system_entity_id.get(), ControlRequest::Take(...));
+/// use_sync_context().take_control(ctx.system_entity_id.get());
 ///
 /// // In child components - target robot for commands
 /// let ctx = use_system_entity();
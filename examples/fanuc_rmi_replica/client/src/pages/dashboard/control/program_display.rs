@@ -55,7 +55,7 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
     // Authorization is handled in the server request handlers.
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
-        Some(control_state.get().client_id) == my_id
+        Some(control_state.get().client.connection) == my_id
     };
 
     // Helper to get the ExecutionState
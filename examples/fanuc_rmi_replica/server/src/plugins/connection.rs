@@ -141,8 +141,8 @@ fn handle_connect_requests(
         };
 
         // Check if client has control of the System
-        if system_control.client_id != client_id {
-            let err = format!("No control of System (held by {:?})", system_control.client_id);
+        if system_control.client.connection != client_id {
+            let err = format!("No control of System (held by {:?})", system_control.client.connection);
             warn!("ConnectToRobot rejected from {:?}: {}", client_id, err);
             send_error(request.clone(), err);
             continue;
@@ -449,8 +449,8 @@ fn handle_disconnect_requests(
             continue;
         };
 
-        if system_control.client_id != client_id {
-            warn!("DisconnectRobot rejected from {:?}: No control of System (held by {:?})", client_id, system_control.client_id);
+        if system_control.client.connection != client_id {
+            warn!("DisconnectRobot rejected from {:?}: No control of System (held by {:?})", client_id, system_control.client.connection);
             continue;
         }
 
@@ -40,7 +40,7 @@ fn spawn_system_entity(mut commands: Commands) {
         Name::new("System"),
         ActiveSystem,
         // EntityControl enables exclusive control management
-        // Clients can request control via ControlRequest::Take(entity_bits)
+        // Clients can request control via ctx.take_control(entity_bits)
         EntityControl::default(),
     )).id();
 
@@ -26,14 +26,20 @@ fn build_registry() -> Arc<ClientTypeRegistry> {
         .register::<EntityControl>()
         .register::<IoStatus>()
         .register::<IoConfigState>()
+        .register::<ActiveAlarms>()
+        .register::<OverrideState>()
+        .register::<WorkspaceEnvelope>()
+        .register::<DuetTelemetry>()
         .register::<ExecutionState>()
         .register::<BufferDisplayData>()
+        .register::<ExecutionProgress>()
         .register::<ConnectionState>()
         .register::<ActiveConfigState>()
         .register::<JogSettingsState>()
         .register::<FrameToolDataState>()
         .register::<ControlResponse>()
         .register::<ProgramNotification>()
+        .register::<IoChanged>()
         .register::<ConsoleLogEntry>()
         .register::<ServerNotification>();
 
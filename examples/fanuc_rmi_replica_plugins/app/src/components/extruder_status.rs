@@ -0,0 +1,55 @@
+//! Extruder status panel showing Duet telemetry.
+
+use leptos::prelude::*;
+
+use pl3xus_client::use_sync_component;
+use fanuc_replica_plugins::*;
+
+/// Extruder status showing position, status and heater temperatures.
+///
+/// Only rendered when the server has a Duet extruder.
+#[component]
+pub fn ExtruderStatusPanel() -> impl IntoView {
+    let telemetry = use_sync_component::<DuetTelemetry>();
+
+    // The first extruder (the server only drives one)
+    let extruder = Memo::new(move |_| {
+        telemetry.get().into_iter().min_by_key(|(id, _)| *id).map(|(_, t)| t)
+    });
+
+    view! {
+        {move || extruder.get().map(|t| {
+            let status = if t.online { t.status.clone() } else { "offline".to_string() };
+            let status_class = if !t.online || t.error.is_some() {
+                "text-[10px] font-semibold text-destructive"
+            } else {
+                "text-[10px] font-semibold text-primary"
+            };
+            view! {
+                <div class="bg-background rounded border border-border/8 p-2">
+                    <div class="flex items-center justify-between mb-1.5">
+                        <h2 class="text-[10px] font-semibold text-primary uppercase tracking-wide">"Extruder"</h2>
+                        <span class=status_class title=t.error.clone().unwrap_or_default()>{status}</span>
+                    </div>
+                    <div class="flex justify-between items-center bg-card rounded px-1.5 py-1 mb-1">
+                        <span class="text-muted-foreground text-[10px] font-medium">"Position"</span>
+                        <span class="text-[11px] font-mono text-muted-foreground tabular-nums">
+                            {format!("{:.3}", t.position)}
+                        </span>
+                    </div>
+                    <div class="space-y-0.5">
+                        {t.heaters.iter().enumerate().map(|(i, heater)| view! {
+                            <div class="flex justify-between items-center bg-card rounded px-1.5 py-1">
+                                <span class="text-muted-foreground text-[10px] font-medium">{format!("H{}", i)}</span>
+                                <span class="text-[11px] font-mono text-muted-foreground tabular-nums">
+                                    {format!("{:.1} / {:.0} °C", heater.current, heater.active)}
+                                </span>
+                                <span class="text-[8px] text-muted-foreground">{heater.state.clone()}</span>
+                            </div>
+                        }).collect_view()}
+                    </div>
+                </div>
+            }
+        })}
+    }
+}
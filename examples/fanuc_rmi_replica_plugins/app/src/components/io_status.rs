@@ -33,6 +33,7 @@ pub fn IoStatusPanel(
     let (read_din_batch, _) = use_targeted_request::<ReadDinBatch>();
     let (read_ain, _) = use_targeted_request::<ReadAin>();
     let (read_gin, _) = use_targeted_request::<ReadGin>();
+    let (watch_io, _) = use_targeted_request::<IoWatchConfig>();
 
     // Get robot entity ID for targeted requests
     let robot_entity_id = ctx.robot_entity_id;
@@ -74,6 +75,18 @@ pub fn IoStatusPanel(
         }
     };
 
+    // Watch the displayed inputs/outputs so the server pushes changes as they happen
+    Effect::new(move |_| {
+        if let Some(entity_id) = robot_entity_id.get() {
+            watch_io(entity_id, IoWatchConfig {
+                din: DEFAULT_PORTS.to_vec(),
+                dout: DEFAULT_PORTS.to_vec(),
+                ain: DEFAULT_PORTS.to_vec(),
+                interval_ms: 200,
+            });
+        }
+    });
+
     // Helper to check if a bit is set in the I/O vector
     let get_bit = |io_vec: &[u16], index: usize| -> bool {
         if index == 0 { return false; }
@@ -4,6 +4,12 @@
 //! Jog buttons send axis/direction - the server uses its JogSettingsState
 //! for speed and step values. Settings are editable inline - press Enter to
 //! submit changes, or blur to discard.
+//!
+//! In continuous mode a button jogs while held: JogStart on press, JogKeepalive
+//! every 100ms, JogStop on release. The server stops on its own if the
+//! keepalives stop arriving.
+
+use std::time::Duration;
 
 use leptos::prelude::*;
 use leptos::ev::KeyboardEvent;
@@ -55,7 +61,7 @@ pub fn JogControls() -> impl IntoView {
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
         let state = control_state.get();
-        Some(state.client_id) == my_id
+        Some(state.client.connection) == my_id
     };
 
     let jog = move |axis: JogAxis, direction: JogDirection| {
@@ -80,13 +86,60 @@ pub fn JogControls() -> impl IntoView {
         });
     };
 
+    // Continuous (hold-to-move) jogging
+    let (continuous, set_continuous) = signal(false);
+    let keepalive = StoredValue::new(None::<IntervalHandle>);
+
+    let hold_start = move |axis: JogAxis, direction: JogDirection| {
+        let Some(entity_bits) = robot_entity_bits() else {
+            toast.error("Cannot jog: Robot entity not found.");
+            return;
+        };
+        if !has_control() {
+            toast.error("Cannot jog: you don't have control. Request control first.");
+            return;
+        }
+
+        ctx.send_targeted(entity_bits, JogStart { axis, direction, speed: None });
+        let handle = set_interval_with_handle(
+            move || ctx.send_targeted(entity_bits, JogKeepalive),
+            Duration::from_millis(100),
+        ).ok();
+        if let Some(previous) = keepalive.get_value() {
+            previous.clear();
+        }
+        keepalive.set_value(handle);
+    };
+
+    let hold_end = move || {
+        let Some(handle) = keepalive.get_value() else {
+            return;
+        };
+        handle.clear();
+        keepalive.set_value(None);
+        if let Some(entity_bits) = robot_entity_bits() {
+            ctx.send_targeted(entity_bits, JogStop);
+        }
+    };
+
     view! {
         <div class="bg-background rounded border border-border/8 p-2">
             <div class="flex items-center justify-between mb-2">
                 <h2 class="text-[10px] font-semibold text-primary uppercase tracking-wide">"Jog Control"</h2>
-                <Show when=move || !has_control()>
-                    <span class="text-[8px] text-destructive bg-destructive/15 px-1.5 py-0.5 rounded">"No Control"</span>
-                </Show>
+                <div class="flex items-center gap-1">
+                    <label class="flex items-center gap-1 text-[8px] text-muted-foreground cursor-pointer" title="Jog while the button is held">
+                        <input
+                            type="checkbox"
+                            class="w-2.5 h-2.5 accent-primary"
+                            prop:checked=continuous
+                            on:change=move |ev| set_continuous.set(event_target_checked(&ev))
+                        />
+                        "Hold"
+                    </label>
+                    <Show when=move || !has_control()>
+                        <span class="text-[8px] text-destructive bg-destructive/15 px-1.5 py-0.5 rounded">"No Control"</span>
+                    </Show>
+                </div>
             </div>
 
             // Cartesian Settings (X/Y/Z) - Editable
@@ -121,14 +174,14 @@ pub fn JogControls() -> impl IntoView {
             // Cartesian Directional Buttons
             <div class="grid grid-cols-3 gap-1 mb-3">
                 <div></div>
-                <JogButton label="Y+" jog=jog.clone() axis=JogAxis::Y direction=JogDirection::Positive disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="Y+" jog=jog.clone() axis=JogAxis::Y direction=JogDirection::Positive continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
                 <div></div>
-                <JogButton label="X-" jog=jog.clone() axis=JogAxis::X direction=JogDirection::Negative disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="Z+" jog=jog.clone() axis=JogAxis::Z direction=JogDirection::Positive disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="X+" jog=jog.clone() axis=JogAxis::X direction=JogDirection::Positive disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="X-" jog=jog.clone() axis=JogAxis::X direction=JogDirection::Negative continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="Z+" jog=jog.clone() axis=JogAxis::Z direction=JogDirection::Positive continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="X+" jog=jog.clone() axis=JogAxis::X direction=JogDirection::Positive continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
                 <div></div>
-                <JogButton label="Y-" jog=jog.clone() axis=JogAxis::Y direction=JogDirection::Negative disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="Z-" jog=jog.clone() axis=JogAxis::Z direction=JogDirection::Negative disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="Y-" jog=jog.clone() axis=JogAxis::Y direction=JogDirection::Negative continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="Z-" jog=jog.clone() axis=JogAxis::Z direction=JogDirection::Negative continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
             </div>
 
             // Rotation Settings (W/P/R) - Editable
@@ -162,12 +215,12 @@ pub fn JogControls() -> impl IntoView {
 
             // Rotation Directional Buttons
             <div class="grid grid-cols-3 gap-1">
-                <JogButton label="W-" jog=jog.clone() axis=JogAxis::W direction=JogDirection::Negative disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="P-" jog=jog.clone() axis=JogAxis::P direction=JogDirection::Negative disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="R-" jog=jog.clone() axis=JogAxis::R direction=JogDirection::Negative disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="W+" jog=jog.clone() axis=JogAxis::W direction=JogDirection::Positive disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="P+" jog=jog.clone() axis=JogAxis::P direction=JogDirection::Positive disabled=Signal::derive(move || !has_control()) />
-                <JogButton label="R+" jog=jog.clone() axis=JogAxis::R direction=JogDirection::Positive disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="W-" jog=jog.clone() axis=JogAxis::W direction=JogDirection::Negative continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="P-" jog=jog.clone() axis=JogAxis::P direction=JogDirection::Negative continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="R-" jog=jog.clone() axis=JogAxis::R direction=JogDirection::Negative continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="W+" jog=jog.clone() axis=JogAxis::W direction=JogDirection::Positive continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="P+" jog=jog.clone() axis=JogAxis::P direction=JogDirection::Positive continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
+                <JogButton label="R+" jog=jog.clone() axis=JogAxis::R direction=JogDirection::Positive continuous=continuous hold_start=hold_start.clone() hold_end=hold_end.clone() disabled=Signal::derive(move || !has_control()) />
             </div>
         </div>
     }
@@ -264,11 +317,28 @@ where
 }
 
 #[component]
-fn JogButton<F>(label: &'static str, jog: F, axis: JogAxis, direction: JogDirection, disabled: Signal<bool>) -> impl IntoView
-where F: Fn(JogAxis, JogDirection) + Clone + 'static {
+fn JogButton<F, S, E>(
+    label: &'static str,
+    jog: F,
+    axis: JogAxis,
+    direction: JogDirection,
+    #[prop(into)] continuous: Signal<bool>,
+    hold_start: S,
+    hold_end: E,
+    disabled: Signal<bool>,
+) -> impl IntoView
+where
+    F: Fn(JogAxis, JogDirection) + Clone + 'static,
+    S: Fn(JogAxis, JogDirection) + Clone + 'static,
+    E: Fn() + Clone + 'static,
+{
     let do_jog = {
         let jog = jog.clone();
-        move |_| jog(axis, direction)
+        move |_| {
+            if !continuous.get_untracked() {
+                jog(axis, direction)
+            }
+        }
     };
 
     view! {
@@ -280,6 +350,16 @@ where F: Fn(JogAxis, JogDirection) + Clone + 'static {
             }
             disabled=move || disabled.get()
             on:click=do_jog
+            on:pointerdown=move |_| if continuous.get_untracked() { hold_start(axis, direction) }
+            on:pointerup={
+                let hold_end = hold_end.clone();
+                move |_| hold_end()
+            }
+            on:pointerleave={
+                let hold_end = hold_end.clone();
+                move |_| hold_end()
+            }
+            on:pointercancel=move |_| hold_end()
         >
             {label}
         </button>
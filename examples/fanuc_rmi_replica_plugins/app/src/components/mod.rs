@@ -4,6 +4,7 @@ mod status_panel;
 mod position_display;
 mod jog_controls;
 mod io_status;
+mod extruder_status;
 mod robot_wizard;
 mod toast;
 mod theme_modal;
@@ -12,6 +13,7 @@ pub use status_panel::StatusPanel;
 pub use position_display::PositionDisplay;
 pub use jog_controls::JogControls;
 pub use io_status::IoStatusPanel;
+pub use extruder_status::ExtruderStatusPanel;
 pub use robot_wizard::RobotCreationWizard;
 pub use toast::{ToastProvider, ToastType, use_toast};
 pub use theme_modal::ThemeModal;
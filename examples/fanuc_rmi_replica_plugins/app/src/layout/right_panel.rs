@@ -4,7 +4,7 @@ use leptos::prelude::*;
 use pl3xus_client::use_entity_component;
 use fanuc_replica_plugins::ConnectionState;
 
-use crate::components::{StatusPanel, PositionDisplay, JogControls, IoStatusPanel};
+use crate::components::{StatusPanel, PositionDisplay, JogControls, IoStatusPanel, ExtruderStatusPanel};
 use crate::layout::LayoutContext;
 use crate::pages::dashboard::use_system_entity;
 
@@ -28,6 +28,9 @@ pub fn RightPanel() -> impl IntoView {
                 // Position display
                 <PositionDisplay/>
 
+                // Extruder telemetry (only shown when the server has a Duet extruder)
+                <ExtruderStatusPanel/>
+
                 // I/O Status (only show when robot connected and not popped)
                 <Show when=move || robot_connected.get() && !layout_ctx.io_popped.get()>
                     <IOStatusPanelWrapper/>
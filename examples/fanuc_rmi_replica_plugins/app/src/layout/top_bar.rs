@@ -5,7 +5,7 @@
 use leptos::prelude::*;
 use leptos_router::hooks::use_navigate;
 
-use pl3xus_client::{use_entity_component, use_sync_context, use_connection, use_query_keyed, use_message, use_request, ControlRequest, ControlResponse, EntityControl, ConnectionReadyState};
+use pl3xus_client::{use_entity_component, use_sync_context, use_connection, use_query_keyed, use_message, use_request, ControlResponse, EntityControl, ConnectionReadyState};
 use fanuc_replica_plugins::*;
 use crate::pages::dashboard::use_system_entity;
 use crate::components::ThemeModal;
@@ -27,6 +27,7 @@ pub fn TopBar() -> impl IntoView {
     // Robot connection state from synced component (only valid if robot entity exists)
     let robot_connected = move || robot_exists.get() && connection_state.get().robot_connected;
     let robot_connecting = move || robot_exists.get() && connection_state.get().robot_connecting;
+    let robot_reconnecting = move || robot_exists.get() && connection_state.get().reconnecting;
     let connected_robot_name = move || {
         if !robot_exists.get() { return None; }
         let state = connection_state.get();
@@ -95,7 +96,9 @@ pub fn TopBar() -> impl IntoView {
                         }}></div>
                         <span class="text-[10px] text-muted-foreground">
                             {move || {
-                                if robot_connecting() {
+                                if robot_reconnecting() {
+                                    format!("Reconnecting ({})...", connection_state.get().reconnect_attempts.max(1))
+                                } else if robot_connecting() {
                                     "Connecting...".to_string()
                                 } else if let Some(name) = connected_robot_name() {
                                     name
@@ -388,15 +391,15 @@ fn QuickSettingsButton() -> impl IntoView {
     };
     let active_connection_id = move || if robot_exists.get() { connection_state.get().active_connection_id } else { None };
 
-    // Check if THIS client has control by comparing EntityControl.client_id with our own connection ID
+    // Check if THIS client has control by comparing EntityControl.client with our own connection ID
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
-        Some(control_state.get().client_id) == my_id
+        Some(control_state.get().client.connection) == my_id
     };
 
     // Get the controlling client ID (if any)
     let controlling_client_id = move || -> Option<u32> {
-        Some(control_state.get().client_id.id)
+        control_state.get().holder().map(|holder| holder.connection.id)
     };
 
     view! {
@@ -750,7 +753,7 @@ fn ControlActions(has_control: Signal<bool>) -> impl IntoView {
                             class="w-full text-[9px] px-3 py-1.5 bg-[#00d9ff20] border border-[#00d9ff40] text-primary rounded hover:bg-primary/20"
                             on:click=move |_| {
                                 if let Some(entity_bits) = system_entity_bits() {
-                                    ctx.send(ControlRequest::Take(entity_bits));
+                                    ctx.take_control(entity_bits);
                                 }
                             }
                         >
@@ -765,7 +768,7 @@ fn ControlActions(has_control: Signal<bool>) -> impl IntoView {
                             class="w-full text-[9px] px-3 py-1.5 bg-destructive/15 border border-[#ff444440] text-destructive rounded hover:bg-destructive/20"
                             on:click=move |_| {
                                 if let Some(entity_bits) = system_entity_bits() {
-                                    ctx.send(ControlRequest::Release(entity_bits));
+                                    ctx.release_control(entity_bits);
                                 }
                             }
                         >
@@ -797,11 +800,11 @@ fn ControlButton() -> impl IntoView {
         system_ctx.system_entity_id.get()
     };
 
-    // Check if THIS client has control by comparing EntityControl.client_id with our own connection ID
+    // Check if THIS client has control by comparing EntityControl.client with our own connection ID
     // Use the System entity (from context) to check control status
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
-        Some(control_state.get().client_id) == my_id
+        Some(control_state.get().client.connection) == my_id
     };
 
     // Check if another client has control
@@ -809,7 +812,7 @@ fn ControlButton() -> impl IntoView {
         let my_id = ctx.my_connection_id.get();
         let state = control_state.get();
         // Someone has control and it's not us
-        state.client_id.id != 0 && Some(state.client_id) != my_id
+        state.is_controlled() && Some(state.client.connection) != my_id
     };
 
     view! {
@@ -836,11 +839,11 @@ fn ControlButton() -> impl IntoView {
 
                     if has_control() {
                         leptos::logging::log!("[ControlButton] Releasing control");
-                        ctx.send(ControlRequest::Release(entity_bits));
+                        ctx.release_control(entity_bits);
                         // Toast will be shown by ControlResponseHandler when server responds
                     } else {
                         leptos::logging::log!("[ControlButton] Requesting control");
-                        ctx.send(ControlRequest::Take(entity_bits));
+                        ctx.take_control(entity_bits);
                         // Toast will be shown by ControlResponseHandler when server responds
                     }
                 }
@@ -932,6 +935,15 @@ pub fn ControlResponseHandler() -> impl IntoView {
             ControlResponseKind::Error(msg) => {
                 toast.error(format!("Control error: {}", msg));
             }
+            ControlResponseKind::Denied { reason } => {
+                toast.warning(format!("Control denied - {}", reason));
+            }
+            ControlResponseKind::AwaitingApproval => {
+                toast.info("Control requested - waiting for approval");
+            }
+            ControlResponseKind::TimeoutWarning { seconds_remaining, .. } => {
+                toast.warning(format!("Control will be released in {:.0}s due to inactivity", seconds_remaining));
+            }
         }
     });
 
@@ -1056,6 +1068,8 @@ pub fn ProgramNotificationHandler() -> impl IntoView {
                     program_name, at_line, error_message
                 ));
             }
+            // Upload progress is shown by the uploading view, not as toasts
+            ProgramNotificationKind::UploadProgress { .. } => {}
         }
     });
 
@@ -1116,6 +1130,9 @@ pub fn ConsoleLogHandler() -> impl IntoView {
                     direction,
                     msg_type: msg_type.clone(),
                     sequence_id: entry.sequence_id,
+                    level: entry.level,
+                    source: entry.source.clone(),
+                    fields: entry.fields.clone(),
                 });
                 // Keep only last 500 messages
                 if msgs.len() > 500 {
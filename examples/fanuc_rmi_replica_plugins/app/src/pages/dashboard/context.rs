@@ -21,6 +21,7 @@
 use leptos::prelude::*;
 use std::collections::HashSet;
 use js_sys;
+use fanuc_replica_core::{ConsoleField, ConsoleLevel};
 
 // ============================================================================
 // System Entity Context
@@ -53,7 +54,7 @@ use js_sys;
 ///
 /// // In child components - target system for control
 /// let ctx = use_system_entity();
-/// ctx.send_targeted(ctx.system_entity_id.get(), ControlRequest::Take(...));
+/// use_sync_context().take_control(ctx.system_entity_id.get());
 ///
 /// // In child components - target robot for commands
 /// let ctx = use_system_entity();
@@ -144,8 +145,11 @@ impl WorkspaceContext {
                 timestamp_ms,
                 content,
                 direction,
+                level: if msg_type == MessageType::Error { ConsoleLevel::Error } else { ConsoleLevel::Info },
                 msg_type,
                 sequence_id: None,
+                source: None,
+                fields: Vec::new(),
             });
             // Keep only last 500 messages
             if msgs.len() > 500 {
@@ -166,14 +170,6 @@ impl WorkspaceContext {
     }
 }
 
-#[derive(Clone, Debug)]
-#[allow(dead_code)]
-pub struct CommandLogEntry {
-    pub timestamp: String,
-    pub command: String,
-    pub status: CommandStatus,
-}
-
 /// A recently executed command that can be re-run
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
@@ -195,14 +191,6 @@ pub struct RecentCommand {
     pub utool: u8,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-#[allow(dead_code)]
-pub enum CommandStatus {
-    Pending,
-    Success,
-    Error(String),
-}
-
 // NOTE: ProgramLine type has been removed. Use ProgramLineInfo from fanuc_replica_plugins instead.
 // Program execution state comes from the synced ExecutionState component.
 
@@ -216,6 +204,11 @@ pub struct ConsoleMessage {
     pub direction: MessageDirection,
     pub msg_type: MessageType,
     pub sequence_id: Option<u32>,
+    pub level: ConsoleLevel,
+    /// Subsystem that logged the message (server entries only)
+    pub source: Option<String>,
+    /// Structured key/value context (server entries only)
+    pub fields: Vec<ConsoleField>,
 }
 
 /// Direction of the message
@@ -4,8 +4,11 @@
 //! Has tabs for "Messages" (all messages) and "Errors" (error messages only).
 //! Collapsible - when collapsed, header animates to bottom of container.
 //! Tab buttons with badges are always visible; clicking them when collapsed expands to that tab.
+//! The "Dbg" toggle asks the server to also send debug-level entries.
 
 use leptos::prelude::*;
+use pl3xus_client::use_request;
+use fanuc_replica_core::{ConsoleLevel, SetConsoleLevel};
 use crate::pages::dashboard::context::{WorkspaceContext, MessageDirection, MessageType};
 
 /// Command Log panel - console-style output with chronological ordering and tabs.
@@ -21,6 +24,9 @@ pub fn CommandLogPanel() -> impl IntoView {
     let (collapsed, set_collapsed) = signal(false);
     // Tab state: "messages" or "errors"
     let (active_tab, set_active_tab) = signal("messages");
+    // Whether the server sends us debug-level entries
+    let (show_debug, set_show_debug) = signal(false);
+    let (send_set_level, _) = use_request::<SetConsoleLevel>();
 
     // Badge counts
     let message_count = move || console_messages.get().len();
@@ -103,6 +109,23 @@ pub fn CommandLogPanel() -> impl IntoView {
                             {move || error_count()}
                         </span>
                     </button>
+                    <button
+                        class=move || if show_debug.get() {
+                            "text-[8px] px-1 text-primary"
+                        } else {
+                            "text-[8px] px-1 text-muted-foreground hover:text-primary"
+                        }
+                        on:click=move |_| {
+                            let enabled = !show_debug.get_untracked();
+                            set_show_debug.set(enabled);
+                            send_set_level(SetConsoleLevel {
+                                min_level: if enabled { ConsoleLevel::Debug } else { ConsoleLevel::Info },
+                            });
+                        }
+                        title="Show debug messages"
+                    >
+                        "Dbg"
+                    </button>
                     <button
                         class="text-[8px] text-muted-foreground hover:text-destructive px-1"
                         on:click=move |_| {
@@ -136,21 +159,28 @@ pub fn CommandLogPanel() -> impl IntoView {
                                     MessageDirection::Received => ("←", "text-success"),
                                     MessageDirection::System => ("•", "text-warning"),
                                 };
-                                let content_class = match msg.msg_type {
-                                    MessageType::Command => "text-primary",
-                                    MessageType::Response => "text-success",
-                                    MessageType::Error => "text-destructive",
-                                    MessageType::Status => "text-muted-foreground",
-                                    MessageType::Config => "text-warning",
+                                let content_class = match (msg.level, &msg.msg_type) {
+                                    (ConsoleLevel::Debug, _) => "text-muted-foreground/60",
+                                    (ConsoleLevel::Warn, _) => "text-warning",
+                                    (_, MessageType::Command) => "text-primary",
+                                    (_, MessageType::Response) => "text-success",
+                                    (_, MessageType::Error) => "text-destructive",
+                                    (_, MessageType::Status) => "text-muted-foreground",
+                                    (_, MessageType::Config) => "text-warning",
                                 };
-                                let seq_display = msg.sequence_id.map(|id| format!(" seq={}", id)).unwrap_or_default();
+                                let source_display = msg.source.as_ref().map(|source| format!("[{}] ", source)).unwrap_or_default();
+                                let mut details: String = msg.fields.iter().map(|field| format!(" {}={}", field.key, field.value)).collect();
+                                if let Some(id) = msg.sequence_id {
+                                    details.push_str(&format!(" seq={}", id));
+                                }
                                 view! {
                                     <div class="py-0.5 border-b border-[#ffffff05] flex items-start">
                                         <span class="text-muted-foreground mr-1 shrink-0">{format!("[{}]", msg.timestamp)}</span>
                                         <span class={format!("{} mr-1 shrink-0", dir_class)}>{dir_icon}</span>
                                         <span class={content_class}>
+                                            <span class="text-muted-foreground">{source_display}</span>
                                             {msg.content}
-                                            <span class="text-muted-foreground">{seq_display}</span>
+                                            <span class="text-muted-foreground">{details}</span>
                                         </span>
                                     </div>
                                 }
@@ -7,9 +7,10 @@
 //! ## Server-Driven UI State Pattern
 //!
 //! This component demonstrates the idiomatic pl3xus pattern for server-driven UI:
-//! - The server provides `state` enum and `can_*` action flags in ExecutionState
+//! - The server provides the `state` enum in ExecutionState and the available
+//!   commands in the coordinator's `EntityActions`
 //! - The client simply reflects these values - **no client-side state machine logic**
-//! - Button visibility is driven by server-provided `can_*` flags
+//! - Button visibility is driven by `use_action`
 //! - Actions are only allowed when the client has control
 //!
 //! ## Response Handling
@@ -23,7 +24,7 @@
 //! This ensures all connected clients see the same notification simultaneously.
 
 use leptos::prelude::*;
-use pl3xus_client::{use_mutation_targeted, use_entity_component, use_sync_context, EntityControl};
+use pl3xus_client::{use_action, use_mutation_targeted, use_entity_component, use_sync_context, EntityControl};
 use fanuc_replica_plugins::*;
 use super::LoadProgramModal;
 use crate::components::use_toast;
@@ -32,23 +33,32 @@ use crate::pages::dashboard::use_system_entity;
 /// Program Visual Display - G-code style line-by-line view
 ///
 /// Demonstrates the **server-driven UI state pattern**:
-/// - The server's `ExecutionState` contains both current state and available actions
-/// - Button visibility is driven by server-provided `can_*` flags
+/// - The server's `ExecutionState` contains the current state, and its
+///   `EntityActions` the commands available in that state
+/// - Button visibility is driven by `use_action`
 /// - Actions require control - clients without control see disabled buttons
 /// - Zero client-side state machine logic
+///
+/// `coordinator_entity_id` selects which execution coordinator to display and
+/// control. It defaults to the active System entity.
 #[component]
-pub fn ProgramVisualDisplay() -> impl IntoView {
+pub fn ProgramVisualDisplay(
+    #[prop(optional, into)] coordinator_entity_id: Option<Signal<Option<u64>>>,
+) -> impl IntoView {
     let ctx = use_sync_context();
     let toast = use_toast();
     let system_ctx = use_system_entity();
+    let coordinator_entity_id = coordinator_entity_id.unwrap_or(system_ctx.system_entity_id);
 
     // === Server-Driven State ===
     //
-    // Subscribe to entity-specific components for the active system.
-    // ExecutionState: state machine, progress, available actions
+    // Subscribe to entity-specific components for the coordinator.
+    // ExecutionState: state machine, progress
     // BufferDisplayData: the actual lines to show in the table
-    let (exec_state, _) = use_entity_component::<ExecutionState, _>(move || system_ctx.system_entity_id.get());
-    let (buffer_display, _) = use_entity_component::<BufferDisplayData, _>(move || system_ctx.system_entity_id.get());
+    // ExecutionProgress: distance, ETA and device lag computed by the server
+    let (exec_state, _) = use_entity_component::<ExecutionState, _>(move || coordinator_entity_id.get());
+    let (buffer_display, _) = use_entity_component::<BufferDisplayData, _>(move || coordinator_entity_id.get());
+    let (progress, _) = use_entity_component::<ExecutionProgress, _>(move || coordinator_entity_id.get());
     let (control_state, _) = use_entity_component::<EntityControl, _>(move || system_ctx.system_entity_id.get());
 
     let (show_load_modal, set_show_load_modal) = signal(false);
@@ -59,8 +69,8 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
     let has_control = move || {
         let my_id = ctx.my_connection_id.get();
         let control = control_state.get();
-        let result = Some(control.client_id) == my_id;
-        leptos::logging::log!("[ProgramDisplay] has_control check: my_id={:?}, control.client_id={:?}, result={}", my_id, control.client_id, result);
+        let result = Some(control.client.connection) == my_id;
+        leptos::logging::log!("[ProgramDisplay] has_control check: my_id={:?}, control.client.connection={:?}, result={}", my_id, control.client.connection, result);
         result
     };
 
@@ -78,27 +88,24 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
 
     // === Available Actions (server-driven + control check) ===
     // The server tells us what actions are valid, but we also require control.
-    let can_load = move || {
-        let exec = get_exec();
-        let result = has_control() && exec.can_load;
-        leptos::logging::log!("[ProgramDisplay] can_load={} (has_control={}, exec.can_load={})", result, has_control(), exec.can_load);
-        result
-    };
-    let can_start = move || {
-        let exec = get_exec();
-        let result = has_control() && exec.can_start;
-        leptos::logging::log!("[ProgramDisplay] can_start={} (has_control={}, exec.can_start={})", result, has_control(), exec.can_start);
-        result
-    };
-    let can_pause = move || has_control() && get_exec().can_pause;
-    let can_resume = move || has_control() && get_exec().can_resume;
-    let can_stop = move || has_control() && get_exec().can_stop;
-    let can_unload = move || {
-        let exec = get_exec();
-        let result = has_control() && exec.can_unload;
-        leptos::logging::log!("[ProgramDisplay] can_unload={} (has_control={}, exec.can_unload={})", result, has_control(), exec.can_unload);
-        result
-    };
+    let coordinator = move || coordinator_entity_id.get();
+    let load_action = use_action("load", coordinator);
+    let start_action = use_action("start", coordinator);
+    let pause_action = use_action("pause", coordinator);
+    let resume_action = use_action("resume", coordinator);
+    let step_action = use_action("step", coordinator);
+    let resume_from_line_action = use_action("resume_from_line", coordinator);
+    let stop_action = use_action("stop", coordinator);
+    let unload_action = use_action("unload", coordinator);
+
+    let can_load = move || has_control() && load_action.enabled();
+    let can_start = move || has_control() && start_action.enabled();
+    let can_pause = move || has_control() && pause_action.enabled();
+    let can_resume = move || has_control() && resume_action.enabled();
+    let can_step = move || has_control() && step_action.enabled();
+    let can_resume_from_line = move || has_control() && resume_from_line_action.enabled();
+    let can_stop = move || has_control() && stop_action.enabled();
+    let can_unload = move || has_control() && unload_action.enabled();
 
     // =========================================================================
     // Targeted Mutation Hooks (TanStack Query-inspired API)
@@ -135,6 +142,22 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
         }
     });
 
+    let step_once = use_mutation_targeted::<StepOnce>(move |result| {
+        match result {
+            Ok(r) if r.success => { /* Success: no toast */ }
+            Ok(r) => toast.error(format!("Step denied: {}", r.error.as_deref().unwrap_or("No control"))),
+            Err(e) => toast.error(format!("Step failed: {e}")),
+        }
+    });
+
+    let resume_from_line = use_mutation_targeted::<ResumeFromLine>(move |result| {
+        match result {
+            Ok(r) if r.success => { /* Success: no toast */ }
+            Ok(r) => toast.error(format!("Resume from line denied: {}", r.error.as_deref().unwrap_or("No control"))),
+            Err(e) => toast.error(format!("Resume from line failed: {e}")),
+        }
+    });
+
     let stop = use_mutation_targeted::<Stop>(move |result| {
         match result {
             Ok(r) if r.success => { /* Success: no toast */ }
@@ -151,7 +174,7 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
         }
     });
 
-    // Get the system entity ID for targeting
+    // Execution requests target the coordinator; programs are unloaded from the System entity
     let system_entity_id = system_ctx.system_entity_id;
 
     // Collapsed state
@@ -207,7 +230,7 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
                     </Show>
                 </div>
                 // === Server-Driven Action Buttons ===
-                // Button visibility is determined entirely by server's can_* flags.
+                // Button visibility is determined entirely by the server's EntityActions.
                 <div class="flex items-center gap-1" on:click=move |ev| ev.stop_propagation()>
                     <Show when=move || !collapsed.get()>
                         <span class="text-[8px] text-muted-foreground mr-1">
@@ -228,20 +251,31 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
                         <button
                             class="bg-[#22c55e20] border border-[#22c55e40] text-success text-[8px] px-2 py-0.5 rounded hover:bg-success/20"
                             on:click=move |_| {
-                                if let Some(entity_id) = system_entity_id.get() {
-                                    start.send(entity_id, Start);
+                                if let Some(entity_id) = coordinator_entity_id.get() {
+                                    start.send(entity_id, Start::default());
                                 }
                             }
                         >
                             "▶ Run"
                         </button>
+                        <button
+                            class="bg-popover border border-border/8 text-muted-foreground text-[8px] px-2 py-0.5 rounded hover:bg-border/10"
+                            title="Run the program on the built-in simulator (robot does not move)"
+                            on:click=move |_| {
+                                if let Some(entity_id) = coordinator_entity_id.get() {
+                                    start.send(entity_id, Start::simulated());
+                                }
+                            }
+                        >
+                            "🧪 Dry run"
+                        </button>
                     </Show>
                     // Pause button - server tells us when pausing is available
                     <Show when=move || can_pause()>
                         <button
                             class="bg-[#f59e0b20] border border-[#f59e0b40] text-warning text-[8px] px-2 py-0.5 rounded hover:bg-warning/20"
                             on:click=move |_| {
-                                if let Some(entity_id) = system_entity_id.get() {
+                                if let Some(entity_id) = coordinator_entity_id.get() {
                                     pause.send(entity_id, Pause);
                                 }
                             }
@@ -254,7 +288,7 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
                         <button
                             class="bg-[#22c55e20] border border-[#22c55e40] text-success text-[8px] px-2 py-0.5 rounded hover:bg-success/20"
                             on:click=move |_| {
-                                if let Some(entity_id) = system_entity_id.get() {
+                                if let Some(entity_id) = coordinator_entity_id.get() {
                                     resume.send(entity_id, Resume);
                                 }
                             }
@@ -262,12 +296,26 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
                             "▶ Resume"
                         </button>
                     </Show>
+                    // Step button - execute one point, then pause again
+                    <Show when=move || can_step()>
+                        <button
+                            class="bg-[#22c55e20] border border-[#22c55e40] text-success text-[8px] px-2 py-0.5 rounded hover:bg-success/20"
+                            title="Execute the next point, then pause"
+                            on:click=move |_| {
+                                if let Some(entity_id) = coordinator_entity_id.get() {
+                                    step_once.send(entity_id, StepOnce);
+                                }
+                            }
+                        >
+                            "⏭ Step"
+                        </button>
+                    </Show>
                     // Stop button - server tells us when stopping is available
                     <Show when=move || can_stop()>
                         <button
                             class="bg-destructive/15 border border-destructive/25 text-destructive text-[8px] px-2 py-0.5 rounded hover:bg-destructive/20"
                             on:click=move |_| {
-                                if let Some(entity_id) = system_entity_id.get() {
+                                if let Some(entity_id) = coordinator_entity_id.get() {
                                     stop.send(entity_id, Stop);
                                 }
                             }
@@ -301,6 +349,12 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
                     current_line=Signal::derive(move || executing().max(0) as usize)
                     total_lines=Signal::derive(move || total_lines())
                     is_paused=Signal::derive(move || is_paused())
+                    simulated=Signal::derive(move || get_exec().simulated)
+                    in_flight=Signal::derive(move || buffer_display.get().in_flight)
+                    lookahead_window=Signal::derive(move || buffer_display.get().lookahead_window)
+                    flow_paused=Signal::derive(move || buffer_display.get().flow_paused)
+                    eta_seconds=Signal::derive(move || progress.get().eta_seconds)
+                    lag=Signal::derive(move || progress.get().subsystem_lag)
                 />
             </Show>
             // Table content - only shown when expanded
@@ -308,6 +362,12 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
                 <ProgramTable
                     lines=Signal::derive(move || lines())
                     executing=Signal::derive(move || executing())
+                    can_resume_from_line=Signal::derive(move || can_resume_from_line())
+                    on_resume_from_line=Callback::new(move |line: u32| {
+                        if let Some(entity_id) = coordinator_entity_id.get() {
+                            resume_from_line.send(entity_id, ResumeFromLine { line });
+                        }
+                    })
                 />
             </Show>
         </div>
@@ -322,11 +382,14 @@ pub fn ProgramVisualDisplay() -> impl IntoView {
 /// Program table component showing the execution buffer lines
 ///
 /// Uses BufferLineDisplay from the execution plugin. Line numbers come from
-/// the index field in each line.
+/// the index field in each line. When the server allows it, double-clicking
+/// a row restarts execution from that line.
 #[component]
 fn ProgramTable(
     lines: Signal<Vec<BufferLineDisplay>>,
     executing: Signal<i32>,
+    can_resume_from_line: Signal<bool>,
+    on_resume_from_line: Callback<u32>,
 ) -> impl IntoView {
 
     view! {
@@ -361,10 +424,19 @@ fn ProgramTable(
                                 let line_idx = line.index;
                                 let term = line.term_type.clone();
                                 view! {
-                                    <tr class=move || format!(
-                                        "border-b border-[#ffffff05] {}",
-                                        if executing.get() == line_idx as i32 { "bg-[#00d9ff20] text-primary" } else { "text-foreground" }
-                                    )>
+                                    <tr
+                                        class=move || format!(
+                                            "border-b border-[#ffffff05] {} {}",
+                                            if executing.get() == line_idx as i32 { "bg-[#00d9ff20] text-primary" } else { "text-foreground" },
+                                            if can_resume_from_line.get() { "cursor-pointer hover:bg-border/10" } else { "" }
+                                        )
+                                        title=move || can_resume_from_line.get().then(|| format!("Double-click to resume from line {}", line_idx))
+                                        on:dblclick=move |_| {
+                                            if can_resume_from_line.get_untracked() {
+                                                on_resume_from_line.run(line_idx as u32);
+                                            }
+                                        }
+                                    >
                                         <td class="px-1.5 py-0.5 text-muted-foreground font-mono">{line_idx}</td>
                                         <td class="px-1.5 py-0.5 text-right font-mono tabular-nums">{format!("{:.2}", line.x)}</td>
                                         <td class="px-1.5 py-0.5 text-right font-mono tabular-nums">{format!("{:.2}", line.y)}</td>
@@ -391,6 +463,12 @@ fn ProgramProgressBar(
     current_line: Signal<usize>,
     total_lines: Signal<usize>,
     is_paused: Signal<bool>,
+    simulated: Signal<bool>,
+    in_flight: Signal<u32>,
+    lookahead_window: Signal<u32>,
+    flow_paused: Signal<bool>,
+    eta_seconds: Signal<Option<f64>>,
+    lag: Signal<Vec<SubsystemLag>>,
 ) -> impl IntoView {
     let progress_percent = move || {
         let total = total_lines.get();
@@ -403,7 +481,11 @@ fn ProgramProgressBar(
                 <span class=move || format!("text-[8px] font-medium uppercase {}",
                     if is_paused.get() { "text-warning" } else { "text-success" }
                 )>
-                    {move || if is_paused.get() { "paused" } else { "running" }}
+                    {move || match (is_paused.get(), simulated.get()) {
+                        (true, _) => "paused",
+                        (false, true) => "dry run",
+                        (false, false) => "running",
+                    }}
                 </span>
                 <div class="flex-1 h-1.5 bg-popover rounded-full overflow-hidden">
                     <div
@@ -414,6 +496,30 @@ fn ProgramProgressBar(
                 <span class="text-[8px] text-muted-foreground font-mono tabular-nums min-w-[60px] text-right">
                     {move || format!("{} / {} ({:.0}%)", current_line.get(), total_lines.get(), progress_percent())}
                 </span>
+                <span
+                    class=move || format!("text-[8px] font-mono tabular-nums {}",
+                        if flow_paused.get() { "text-warning" } else { "text-muted-foreground" }
+                    )
+                    title="Points in-flight on the robot / lookahead window"
+                >
+                    {move || format!("LA {}/{}", in_flight.get(), lookahead_window.get())}
+                </span>
+                <span
+                    class="text-[8px] text-muted-foreground font-mono tabular-nums"
+                    title=move || lag.get()
+                        .iter()
+                        .map(|lag| format!("{}: {} behind", lag.name, lag.points))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                >
+                    {move || match eta_seconds.get() {
+                        Some(secs) => {
+                            let secs = secs.round() as u64;
+                            format!("ETA {}:{:02}", secs / 60, secs % 60)
+                        }
+                        None => "ETA --:--".to_string(),
+                    }}
+                </span>
             </div>
         </div>
     }
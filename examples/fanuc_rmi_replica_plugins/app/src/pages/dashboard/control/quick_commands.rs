@@ -97,6 +97,7 @@ pub fn QuickCommandsPanel() -> impl IntoView {
     let (status, _) = use_entity_component::<RobotStatus, _>(move || system_ctx.robot_entity_id.get());
     let (connection_state, robot_exists) = use_entity_component::<ConnectionState, _>(move || system_ctx.robot_entity_id.get());
     let (active_config, _) = use_entity_component::<ActiveConfigState, _>(move || system_ctx.robot_entity_id.get());
+    let (override_state, override_exists) = use_entity_component::<OverrideState, _>(move || system_ctx.robot_entity_id.get());
 
     // Recent commands from workspace context
     let recent_commands = ctx.recent_commands;
@@ -116,8 +117,15 @@ pub fn QuickCommandsPanel() -> impl IntoView {
     // Read speed override directly from synced RobotStatus component
     let (pending_speed, set_pending_speed) = signal::<Option<u32>>(None);
 
-    // Get server speed value
-    let server_speed = move || status.get().speed_override as u32;
+    // Get server speed value - OverrideState steps through a server-side ramp,
+    // RobotStatus is the fallback for servers without it
+    let server_speed = move || {
+        if override_exists.get() {
+            override_state.get().current as u32
+        } else {
+            status.get().speed_override as u32
+        }
+    };
 
     // Display value: use pending value while editing or waiting for sync, otherwise use server value
     let display_speed = move || {
@@ -212,7 +220,7 @@ pub fn QuickCommandsPanel() -> impl IntoView {
             return;
         };
         let clamped = value.min(100) as u8;
-        speed_override.send(entity_bits, SetSpeedOverride { speed: clamped });
+        speed_override.send(entity_bits, SetSpeedOverride { speed: clamped, ramp_ms: 0 });
     };
     let send_override = StoredValue::new(send_override);
 
@@ -357,7 +365,13 @@ pub fn QuickCommandsPanel() -> impl IntoView {
                     >
                         "+5"
                     </button>
-                    <span class="text-[10px] text-primary font-mono w-7 text-right shrink-0">
+                    <span
+                        class="text-[10px] text-primary font-mono w-7 text-right shrink-0"
+                        title=move || {
+                            let state = override_state.get();
+                            if state.ramping { format!("Ramping to {}%", state.target) } else { String::new() }
+                        }
+                    >
                         {move || format!("{}%", display_speed())}
                     </span>
                 </div>
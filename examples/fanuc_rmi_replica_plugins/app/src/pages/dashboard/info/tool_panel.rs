@@ -32,7 +32,7 @@ pub fn ToolManagementPanel() -> impl IntoView {
     let has_control = Memo::new(move |_| {
         let my_id = ctx.my_connection_id.get();
         let state = control_state.get();
-        Some(state.client_id) == my_id
+        Some(state.client.connection) == my_id
     });
 
     // Derive active frame/tool from synced server state
@@ -4,7 +4,7 @@ use leptos::prelude::*;
 use leptos::either::Either;
 use leptos::web_sys;
 use pl3xus_client::{use_mutation, use_query_keyed};
-use fanuc_replica_plugins::{CreateProgram, UploadCsv, GetProgram, ProgramDetail};
+use fanuc_replica_plugins::{CreateProgram, UploadCsv, UploadGcode, GetProgram, ProgramDetail};
 
 /// New Program Modal - Simple modal to create a program with name and description
 #[component]
@@ -356,6 +356,7 @@ pub fn CSVUploadModal(
     let on_close_clone = on_close.clone();
 
     // UploadCsv mutation with handler
+    let on_uploaded_gcode = on_uploaded.clone();
     let upload_csv = use_mutation::<UploadCsv>(move |result| {
         match result {
             Ok(r) if r.success => on_uploaded(),
@@ -364,6 +365,22 @@ pub fn CSVUploadModal(
         }
     });
 
+    // G-code files (.gcode, .nc, .g) go through UploadGcode instead
+    let upload_gcode = use_mutation::<UploadGcode>(move |result| {
+        match result {
+            Ok(r) if r.success => on_uploaded_gcode(),
+            Ok(r) => set_error_message.set(r.error.clone()),
+            Err(e) => set_error_message.set(Some(e.to_string())),
+        }
+    });
+    let is_gcode = move || {
+        file_name.get().is_some_and(|name| {
+            let name = name.to_lowercase();
+            name.ends_with(".gcode") || name.ends_with(".nc") || name.ends_with(".g")
+        })
+    };
+    let is_uploading = move || upload_csv.is_loading() || upload_gcode.is_loading();
+
     view! {
         <div class="fixed inset-0 bg-black/60 flex items-center justify-center z-50">
             <div class="bg-card border border-border/10 rounded-lg w-[400px] flex flex-col">
@@ -373,7 +390,7 @@ pub fn CSVUploadModal(
                         <svg class="w-4 h-4 mr-2 text-primary" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                             <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-8l-4-4m0 0L8 8m4-4v12"/>
                         </svg>
-                        "Upload CSV"
+                        "Upload CSV / G-code"
                     </h2>
                     <button
                         class="text-muted-foreground hover:text-foreground"
@@ -405,11 +422,11 @@ pub fn CSVUploadModal(
                         {move || if let Some(name) = file_name.get() {
                             view! { <p class="text-[10px] text-primary">{name}</p> }.into_any()
                         } else {
-                            view! { <p class="text-[10px] text-muted-foreground">"Drop CSV file here or click to browse"</p> }.into_any()
+                            view! { <p class="text-[10px] text-muted-foreground">"Drop CSV or G-code file here or click to browse"</p> }.into_any()
                         }}
                         <input
                             type="file"
-                            accept=".csv"
+                            accept=".csv,.gcode,.nc,.g"
                             class="absolute inset-0 opacity-0 cursor-pointer"
                             on:change=move |ev| {
                                 use wasm_bindgen::JsCast;
@@ -438,7 +455,7 @@ pub fn CSVUploadModal(
                         />
                     </div>
                     <p class="text-[8px] text-muted-foreground">
-                        "CSV should have columns: X, Y, Z, W (optional), P (optional), R (optional), Speed (optional)"
+                        "CSV should have columns: X, Y, Z, W (optional), P (optional), R (optional), Speed (optional). G-code supports G0/G1/G2/G3 moves."
                     </p>
                 </div>
 
@@ -456,24 +473,32 @@ pub fn CSVUploadModal(
                     <button
                         class={move || format!(
                             "text-[10px] px-3 py-1.5 rounded {}",
-                            if csv_content.get().is_some() && !upload_csv.is_loading() {
+                            if csv_content.get().is_some() && !is_uploading() {
                                 "bg-[#22c55e20] border border-[#22c55e40] text-success hover:bg-success/20"
                             } else {
                                 "bg-card border border-border/8 text-muted-foreground cursor-not-allowed"
                             }
                         )}
-                        disabled=move || csv_content.get().is_none() || upload_csv.is_loading()
+                        disabled=move || csv_content.get().is_none() || is_uploading()
                         on:click=move |_| {
                             if let Some(content) = csv_content.get() {
-                                upload_csv.send(UploadCsv {
-                                    program_id,
-                                    csv_content: content,
-                                    sequence_type: None,
-                                });
+                                if is_gcode() {
+                                    upload_gcode.send(UploadGcode {
+                                        program_id,
+                                        gcode_content: content,
+                                        sequence_type: None,
+                                    });
+                                } else {
+                                    upload_csv.send(UploadCsv {
+                                        program_id,
+                                        csv_content: content,
+                                        sequence_type: None,
+                                    });
+                                }
                             }
                         }
                     >
-                        {move || if upload_csv.is_loading() { "Uploading..." } else { "Upload" }}
+                        {move || if is_uploading() { "Uploading..." } else { "Upload" }}
                     </button>
                 </div>
             </div>
@@ -190,21 +190,32 @@ fn RobotBrowser(
 /// System settings panel - version info and reset database.
 #[component]
 fn SystemSettingsPanel() -> impl IntoView {
-    let (confirm_reset, set_confirm_reset) = signal(false);
+    // Confirmation token and tables to drop, once the server asked for confirmation
+    let (pending_reset, set_pending_reset) = signal::<Option<(String, Vec<String>)>>(None);
     let toast = use_toast();
 
-    // ResetDatabase with handler - MutationHandle is Copy, no StoredValue needed
+    // ResetDatabase with handler - MutationHandle is Copy, no StoredValue needed.
+    // The first send returns a confirmation token, the second (with it) resets.
     let reset_database = use_mutation::<ResetDatabase>(move |result| {
-        set_confirm_reset.set(false);
         match result {
             Ok(r) if r.success => {
+                set_pending_reset.set(None);
                 // Reload the page to reflect database reset
                 if let Some(window) = web_sys::window() {
                     let _ = window.location().reload();
                 }
             }
-            Ok(r) => toast.error(format!("Reset failed: {}", r.error.as_deref().unwrap_or(""))),
-            Err(e) => toast.error(format!("Error: {e}")),
+            Ok(r) => match &r.confirmation_token {
+                Some(token) => set_pending_reset.set(Some((token.clone(), r.tables.clone()))),
+                None => {
+                    set_pending_reset.set(None);
+                    toast.error(format!("Reset failed: {}", r.error.as_deref().unwrap_or("")));
+                }
+            },
+            Err(e) => {
+                set_pending_reset.set(None);
+                toast.error(format!("Error: {e}"));
+            }
         }
     });
 
@@ -227,30 +238,40 @@ fn SystemSettingsPanel() -> impl IntoView {
                 // Reset database button with inline confirmation
                 <div class="pt-2 border-t border-border/8">
                     <Show
-                        when=move || confirm_reset.get()
+                        when=move || pending_reset.get().is_some()
                         fallback=move || view! {
                             <button
                                 class="w-full text-[8px] px-2 py-1 bg-destructive/10 border border-destructive/15 text-destructive rounded hover:bg-destructive/15"
-                                on:click=move |_| set_confirm_reset.set(true)
+                                on:click=move |_| reset_database.send(ResetDatabase::default())
                             >
                                 "Reset Database"
                             </button>
                         }
                     >
                         <div class="space-y-1">
-                            <p class="text-[8px] text-destructive">"Delete all data?"</p>
+                            <p class="text-[8px] text-destructive">
+                                {move || format!(
+                                    "Delete all data ({} tables)?",
+                                    pending_reset.get().map(|(_, tables)| tables.len()).unwrap_or_default()
+                                )}
+                            </p>
                             <div class="flex gap-1">
                                 <button
                                     class="flex-1 text-[8px] px-2 py-1 bg-destructive text-foreground rounded hover:bg-destructive"
                                     on:click=move |_| {
-                                        reset_database.send(ResetDatabase);
+                                        if let Some((token, _)) = pending_reset.get_untracked() {
+                                            reset_database.send(ResetDatabase {
+                                                confirmation: Some(token),
+                                                ..Default::default()
+                                            });
+                                        }
                                     }
                                 >
                                     "Yes"
                                 </button>
                                 <button
                                     class="flex-1 text-[8px] px-2 py-1 bg-popover border border-border/8 text-muted-foreground rounded hover:text-foreground"
-                                    on:click=move |_| set_confirm_reset.set(false)
+                                    on:click=move |_| set_pending_reset.set(None)
                                 >
                                     "No"
                                 </button>
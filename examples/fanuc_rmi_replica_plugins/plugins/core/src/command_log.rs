@@ -0,0 +1,211 @@
+//! Command log persistence.
+//!
+//! `CommandLogPlugin` records authorized mutations and the messages plugins
+//! register with `.logged()`. Once the database is open, entries are stored
+//! in the `command_log` table, so `GetCommandLog` and `ReplayCommand` also
+//! reach commands from earlier runs. The oldest entries are dropped beyond
+//! `CommandLogRetention::max_entries`.
+
+use bevy::prelude::*;
+use pl3xus::ConnectionId;
+use pl3xus_sync::command_log::{CommandKind, CommandLogEntry, GetCommandLog};
+use pl3xus_sync::{CommandLog, CommandLogStore};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use crate::database::{DatabaseInit, DatabaseResource};
+
+/// Prune every this many entries.
+const PRUNE_EVERY: u64 = 100;
+
+/// Command log database initializer.
+pub struct CommandLogDatabaseInit;
+
+impl DatabaseInit for CommandLogDatabaseInit {
+    fn name(&self) -> &'static str {
+        "command_log"
+    }
+
+    fn init_schema(&self, conn: &Connection) -> anyhow::Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS command_log (
+                id INTEGER PRIMARY KEY,
+                timestamp_ms INTEGER NOT NULL,
+                connection_id INTEGER NOT NULL,
+                identity TEXT,
+                client_addr TEXT,
+                kind TEXT NOT NULL,
+                type_name TEXT NOT NULL,
+                target_entity INTEGER,
+                data BLOB NOT NULL,
+                json TEXT
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+}
+
+/// Retention policy for the persisted command log.
+#[derive(Resource, Clone, Debug)]
+pub struct CommandLogRetention {
+    /// Maximum number of entries kept; oldest entries are pruned first.
+    pub max_entries: u64,
+}
+
+impl Default for CommandLogRetention {
+    fn default() -> Self {
+        Self { max_entries: 50_000 }
+    }
+}
+
+/// Command log store backed by the `command_log` table.
+pub struct SqliteCommandLog {
+    conn: Arc<Mutex<Connection>>,
+    max_entries: u64,
+}
+
+impl SqliteCommandLog {
+    pub fn new(db: &DatabaseResource, retention: &CommandLogRetention) -> Self {
+        Self {
+            conn: db.connection(),
+            max_entries: retention.max_entries,
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn.lock().map_err(|_| "Database lock poisoned".to_string())
+    }
+}
+
+fn kind_to_str(kind: CommandKind) -> &'static str {
+    match kind {
+        CommandKind::Message => "message",
+        CommandKind::Mutation => "mutation",
+    }
+}
+
+fn kind_from_str(s: &str) -> CommandKind {
+    match s {
+        "mutation" => CommandKind::Mutation,
+        _ => CommandKind::Message,
+    }
+}
+
+const COLUMNS: &str =
+    "id, timestamp_ms, connection_id, identity, client_addr, kind, type_name, target_entity, data, json";
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<CommandLogEntry> {
+    let kind: String = row.get(5)?;
+    Ok(CommandLogEntry {
+        id: row.get::<_, i64>(0)? as u64,
+        timestamp_ms: row.get::<_, i64>(1)? as u64,
+        connection_id: ConnectionId { id: row.get(2)? },
+        reported_identity: row.get(3)?,
+        client_addr: row.get(4)?,
+        kind: kind_from_str(&kind),
+        type_name: row.get(6)?,
+        target_entity: row.get::<_, Option<i64>>(7)?.map(|bits| bits as u64),
+        data: row.get(8)?,
+        json: row.get(9)?,
+    })
+}
+
+impl CommandLogStore for SqliteCommandLog {
+    fn append(&mut self, entry: &CommandLogEntry) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute(
+            &format!("INSERT INTO command_log ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", COLUMNS),
+            params![
+                entry.id as i64,
+                entry.timestamp_ms as i64,
+                entry.connection_id.id,
+                entry.reported_identity,
+                entry.client_addr,
+                kind_to_str(entry.kind),
+                entry.type_name,
+                entry.target_entity.map(|bits| bits as i64),
+                entry.data,
+                entry.json,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        if entry.id % PRUNE_EVERY == 0 {
+            let cutoff = entry.id.saturating_sub(self.max_entries) as i64;
+            if let Err(e) = conn.execute("DELETE FROM command_log WHERE id <= ?", [cutoff]) {
+                error!("Failed to prune command log: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn page(&self, query: &GetCommandLog) -> Result<(Vec<CommandLogEntry>, bool), String> {
+        let limit = query.page_size();
+        let conn = self.lock()?;
+        // Fetch one extra row to know whether older entries exist.
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM command_log
+                 WHERE id < ?1
+                   AND (?2 IS NULL OR connection_id = ?2)
+                   AND (?3 IS NULL OR type_name = ?3)
+                   AND (?4 IS NULL OR target_entity = ?4)
+                 ORDER BY id DESC
+                 LIMIT ?5",
+                COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let mut entries = stmt
+            .query_map(
+                params![
+                    query.before_id.map_or(i64::MAX, |id| id as i64),
+                    query.connection_id.map(|c| c.id),
+                    query.type_name,
+                    query.target_entity.map(|bits| bits as i64),
+                    (limit + 1) as i64,
+                ],
+                entry_from_row,
+            )
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| e.to_string())?;
+
+        let has_more = entries.len() > limit;
+        entries.truncate(limit);
+        entries.reverse();
+        Ok((entries, has_more))
+    }
+
+    fn get(&self, id: u64) -> Result<Option<CommandLogEntry>, String> {
+        self.lock()?
+            .query_row(
+                &format!("SELECT {} FROM command_log WHERE id = ?", COLUMNS),
+                [id as i64],
+                entry_from_row,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn last_id(&self) -> u64 {
+        let Ok(conn) = self.lock() else {
+            return 0;
+        };
+        conn.query_row("SELECT COALESCE(MAX(id), 0) FROM command_log", [], |row| row.get::<_, i64>(0))
+            .map(|id| id as u64)
+            .unwrap_or_default()
+    }
+}
+
+/// Move the command log into the database once it is open.
+pub fn persist_command_log(
+    db: Option<Res<DatabaseResource>>,
+    retention: Res<CommandLogRetention>,
+    mut log: ResMut<CommandLog>,
+) {
+    let Some(db) = db else {
+        warn!("Database not available, commands are only logged in memory");
+        return;
+    };
+    log.set_store(SqliteCommandLog::new(&db, &retention));
+}
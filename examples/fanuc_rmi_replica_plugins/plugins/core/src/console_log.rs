@@ -0,0 +1,538 @@
+//! Console log persistence.
+//!
+//! `ConsoleLogEntry` messages written with `MessageWriter<ConsoleLogEntry>` are
+//! broadcast to all clients and stored in the `console_log` table so clients
+//! that join late (or refresh) can fetch history with `GetConsoleHistory`.
+//! Old entries are pruned according to `ConsoleLogRetention`.
+//!
+//! Every entry is persisted, but `Debug` entries are only sent (live and in
+//! history) to clients that lowered their level with `SetConsoleLevel`.
+
+use bevy::ecs::message::MessageReader;
+use bevy::prelude::*;
+use pl3xus::managers::network_request::Request;
+use pl3xus::{ConnectionId, Network, NetworkEvent};
+use pl3xus_websockets::WebSocketProvider;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::database::{DatabaseInit, DatabaseResource};
+use crate::types::{
+    ConsoleDirection, ConsoleField, ConsoleHistoryEntry, ConsoleLevel, ConsoleLogEntry,
+    ConsoleMsgType, GetConsoleHistory, GetConsoleHistoryResponse, SetConsoleLevel,
+    SetConsoleLevelResponse,
+};
+
+/// Maximum number of entries returned by a single `GetConsoleHistory` request.
+const MAX_HISTORY_PAGE: u32 = 1000;
+
+/// Console log database initializer.
+pub struct ConsoleLogDatabaseInit;
+
+impl DatabaseInit for ConsoleLogDatabaseInit {
+    fn name(&self) -> &'static str {
+        "console_log"
+    }
+
+    fn init_schema(&self, conn: &Connection) -> anyhow::Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS console_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                timestamp_ms INTEGER NOT NULL,
+                direction TEXT NOT NULL,
+                msg_type TEXT NOT NULL,
+                content TEXT NOT NULL,
+                sequence_id INTEGER,
+                level INTEGER NOT NULL DEFAULT 1,
+                source TEXT,
+                fields TEXT NOT NULL DEFAULT '[]'
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_console_log_timestamp
+             ON console_log(timestamp_ms)",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    fn run_migrations(&self, conn: &Connection) -> anyhow::Result<()> {
+        // Migration: structured entries (level, source, fields)
+        let has_level: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('console_log') WHERE name = 'level'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if !has_level {
+            conn.execute_batch(
+                "ALTER TABLE console_log ADD COLUMN level INTEGER NOT NULL DEFAULT 1;
+                 ALTER TABLE console_log ADD COLUMN source TEXT;
+                 ALTER TABLE console_log ADD COLUMN fields TEXT NOT NULL DEFAULT '[]';
+                 UPDATE console_log SET level = 3 WHERE msg_type = 'error';",
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Lowest console level sent to each client.
+///
+/// Clients that never sent `SetConsoleLevel` get `ConsoleLevel::Info` and above.
+#[derive(Resource, Default, Debug)]
+pub struct ConsoleLevels {
+    levels: HashMap<ConnectionId, ConsoleLevel>,
+}
+
+impl ConsoleLevels {
+    /// Lowest level sent to `connection`.
+    pub fn min_level(&self, connection: ConnectionId) -> ConsoleLevel {
+        self.levels.get(&connection).copied().unwrap_or_default()
+    }
+
+    /// Whether an entry is sent to `connection`.
+    pub fn wants(&self, connection: ConnectionId, entry: &ConsoleLogEntry) -> bool {
+        entry.level >= self.min_level(connection)
+    }
+}
+
+/// Retention policy for the persisted console log.
+#[derive(Resource, Clone, Debug)]
+pub struct ConsoleLogRetention {
+    /// Maximum number of entries kept; oldest entries are pruned first.
+    pub max_entries: u32,
+    /// Maximum age of an entry; `None` keeps entries regardless of age.
+    pub max_age: Option<Duration>,
+    /// How often pruning runs.
+    pub prune_interval: Duration,
+}
+
+impl Default for ConsoleLogRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            prune_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+fn direction_to_str(direction: &ConsoleDirection) -> &'static str {
+    match direction {
+        ConsoleDirection::Sent => "sent",
+        ConsoleDirection::Received => "received",
+        ConsoleDirection::System => "system",
+    }
+}
+
+fn direction_from_str(s: &str) -> ConsoleDirection {
+    match s {
+        "received" => ConsoleDirection::Received,
+        "system" => ConsoleDirection::System,
+        _ => ConsoleDirection::Sent,
+    }
+}
+
+fn msg_type_to_str(msg_type: &ConsoleMsgType) -> &'static str {
+    match msg_type {
+        ConsoleMsgType::Command => "command",
+        ConsoleMsgType::Response => "response",
+        ConsoleMsgType::Error => "error",
+        ConsoleMsgType::Status => "status",
+        ConsoleMsgType::Config => "config",
+    }
+}
+
+/// Levels are stored as integers so history can be filtered with `>=`.
+fn level_to_i64(level: ConsoleLevel) -> i64 {
+    match level {
+        ConsoleLevel::Debug => 0,
+        ConsoleLevel::Info => 1,
+        ConsoleLevel::Warn => 2,
+        ConsoleLevel::Error => 3,
+    }
+}
+
+fn level_from_i64(level: i64) -> ConsoleLevel {
+    match level {
+        0 => ConsoleLevel::Debug,
+        2 => ConsoleLevel::Warn,
+        3 => ConsoleLevel::Error,
+        _ => ConsoleLevel::Info,
+    }
+}
+
+fn fields_to_json(fields: &[ConsoleField]) -> String {
+    let pairs: Vec<(&str, &str)> = fields
+        .iter()
+        .map(|field| (field.key.as_str(), field.value.as_str()))
+        .collect();
+    serde_json::to_string(&pairs).unwrap_or_else(|_| "[]".into())
+}
+
+fn fields_from_json(json: &str) -> Vec<ConsoleField> {
+    serde_json::from_str::<Vec<(String, String)>>(json)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, value)| ConsoleField { key, value })
+        .collect()
+}
+
+fn msg_type_from_str(s: &str) -> ConsoleMsgType {
+    match s {
+        "response" => ConsoleMsgType::Response,
+        "error" => ConsoleMsgType::Error,
+        "status" => ConsoleMsgType::Status,
+        "config" => ConsoleMsgType::Config,
+        _ => ConsoleMsgType::Command,
+    }
+}
+
+/// Broadcast new console entries and persist them.
+///
+/// Entries below `ConsoleLevel::Info` only go to the clients that opted in.
+pub fn broadcast_and_persist_console_entries(
+    mut entries: MessageReader<ConsoleLogEntry>,
+    net: Res<Network<WebSocketProvider>>,
+    levels: Res<ConsoleLevels>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let entries: Vec<ConsoleLogEntry> = entries.read().cloned().collect();
+    for entry in &entries {
+        if entry.level >= ConsoleLevel::default() {
+            net.broadcast(entry.clone());
+            continue;
+        }
+        for (connection, min_level) in &levels.levels {
+            if entry.level >= *min_level {
+                let _ = net.send(*connection, entry.clone());
+            }
+        }
+    }
+
+    let Some(db) = db else {
+        return;
+    };
+    let conn = db.connection();
+    let mut conn = conn.lock().unwrap();
+    let result = (|| -> rusqlite::Result<()> {
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO console_log
+                 (timestamp, timestamp_ms, direction, msg_type, content, sequence_id, level, source, fields)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )?;
+            for entry in &entries {
+                stmt.execute(params![
+                    entry.timestamp,
+                    entry.timestamp_ms as i64,
+                    direction_to_str(&entry.direction),
+                    msg_type_to_str(&entry.msg_type),
+                    entry.content,
+                    entry.sequence_id,
+                    level_to_i64(entry.level),
+                    entry.source,
+                    fields_to_json(&entry.fields),
+                ])?;
+            }
+        }
+        tx.commit()
+    })();
+
+    if let Err(e) = result {
+        error!("Failed to persist {} console entries: {}", entries.len(), e);
+    }
+}
+
+/// Prune old console entries according to `ConsoleLogRetention`.
+pub fn prune_console_log(
+    time: Res<Time>,
+    mut elapsed: Local<Duration>,
+    retention: Res<ConsoleLogRetention>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    *elapsed += time.delta();
+    if *elapsed < retention.prune_interval {
+        return;
+    }
+    *elapsed = Duration::ZERO;
+
+    let Some(db) = db else {
+        return;
+    };
+    let conn = db.connection();
+    let conn = conn.lock().unwrap();
+
+    let mut pruned = 0;
+    if let Some(max_age) = retention.max_age {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        let cutoff = now_ms - max_age.as_millis() as i64;
+        match conn.execute("DELETE FROM console_log WHERE timestamp_ms < ?", [cutoff]) {
+            Ok(n) => pruned += n,
+            Err(e) => error!("Failed to prune console log by age: {}", e),
+        }
+    }
+
+    match conn.execute(
+        "DELETE FROM console_log WHERE id NOT IN (
+            SELECT id FROM console_log ORDER BY id DESC LIMIT ?
+        )",
+        [retention.max_entries],
+    ) {
+        Ok(n) => pruned += n,
+        Err(e) => error!("Failed to prune console log by size: {}", e),
+    }
+
+    if pruned > 0 {
+        debug!("Pruned {} console log entries", pruned);
+    }
+}
+
+fn query_console_history(
+    conn: &Connection,
+    query: &GetConsoleHistory,
+    min_level: ConsoleLevel,
+) -> rusqlite::Result<(Vec<ConsoleHistoryEntry>, bool)> {
+    let limit = query.limit.clamp(1, MAX_HISTORY_PAGE);
+    let since = query.since.map(|s| s as i64).unwrap_or(i64::MIN);
+    let before_id = query.before_id.unwrap_or(i64::MAX);
+
+    // Fetch one extra row to know whether older entries exist.
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, timestamp_ms, direction, msg_type, content, sequence_id,
+                level, source, fields
+         FROM console_log
+         WHERE timestamp_ms >= ? AND id < ? AND level >= ?
+         ORDER BY id DESC
+         LIMIT ?",
+    )?;
+    let mut entries = stmt
+        .query_map(params![since, before_id, level_to_i64(min_level), limit + 1], |row| {
+            let direction: String = row.get(3)?;
+            let msg_type: String = row.get(4)?;
+            let timestamp_ms: i64 = row.get(2)?;
+            let fields: String = row.get(9)?;
+            Ok(ConsoleHistoryEntry {
+                id: row.get(0)?,
+                entry: ConsoleLogEntry {
+                    timestamp: row.get(1)?,
+                    timestamp_ms: timestamp_ms as u64,
+                    direction: direction_from_str(&direction),
+                    msg_type: msg_type_from_str(&msg_type),
+                    content: row.get(5)?,
+                    sequence_id: row.get(6)?,
+                    level: level_from_i64(row.get(7)?),
+                    source: row.get(8)?,
+                    fields: fields_from_json(&fields),
+                },
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let has_more = entries.len() > limit as usize;
+    entries.truncate(limit as usize);
+    entries.reverse();
+    Ok((entries, has_more))
+}
+
+/// Handle GetConsoleHistory request.
+pub fn handle_get_console_history(
+    mut requests: MessageReader<Request<GetConsoleHistory>>,
+    levels: Res<ConsoleLevels>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let min_level = levels.min_level(*request.source());
+        let response = match db.as_ref() {
+            Some(db) => {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                match query_console_history(&conn, request.get_request(), min_level) {
+                    Ok((entries, has_more)) => GetConsoleHistoryResponse {
+                        entries,
+                        has_more,
+                        error: None,
+                    },
+                    Err(e) => GetConsoleHistoryResponse {
+                        error: Some(e.to_string()),
+                        ..Default::default()
+                    },
+                }
+            }
+            None => GetConsoleHistoryResponse {
+                error: Some("Database not available".into()),
+                ..Default::default()
+            },
+        };
+
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Handle SetConsoleLevel request.
+pub fn handle_set_console_level(
+    mut requests: MessageReader<Request<SetConsoleLevel>>,
+    mut levels: ResMut<ConsoleLevels>,
+) {
+    for request in requests.read() {
+        let min_level = request.get_request().min_level;
+        if min_level == ConsoleLevel::default() {
+            levels.levels.remove(request.source());
+        } else {
+            levels.levels.insert(*request.source(), min_level);
+        }
+
+        let response = SetConsoleLevelResponse {
+            min_level,
+            error: None,
+        };
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Forget the console level of clients that disconnected.
+pub fn remove_disconnected_console_levels(
+    mut events: MessageReader<NetworkEvent>,
+    mut levels: ResMut<ConsoleLevels>,
+) {
+    for event in events.read() {
+        if let NetworkEvent::Disconnected(connection_id) = event {
+            levels.levels.remove(connection_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn console_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        ConsoleLogDatabaseInit.init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, timestamp_ms: i64, level: ConsoleLevel, content: &str) {
+        conn.execute(
+            "INSERT INTO console_log (timestamp, timestamp_ms, direction, msg_type, content, level, source, fields)
+             VALUES ('00:00:00.000', ?, 'system', 'status', ?, ?, 'execution', ?)",
+            params![
+                timestamp_ms,
+                content,
+                level_to_i64(level),
+                fields_to_json(&[ConsoleField {
+                    key: "line".into(),
+                    value: "3".into(),
+                }]),
+            ],
+        )
+        .unwrap();
+    }
+
+    fn contents(entries: &[ConsoleHistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.entry.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_history_pages_backwards_and_filters_levels() {
+        let conn = console_db();
+        for (i, content) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            insert(&conn, i as i64 * 100, ConsoleLevel::Info, content);
+        }
+        insert(&conn, 400, ConsoleLevel::Debug, "trace");
+
+        // Newest page first, returned oldest-to-newest
+        let query = GetConsoleHistory {
+            limit: 2,
+            ..Default::default()
+        };
+        let (page, has_more) = query_console_history(&conn, &query, ConsoleLevel::Info).unwrap();
+        assert_eq!(contents(&page), ["c", "d"]);
+        assert!(has_more);
+        assert_eq!(page[1].entry.source.as_deref(), Some("execution"));
+        assert_eq!(page[1].entry.field("line"), Some("3"));
+
+        let older = GetConsoleHistory {
+            before_id: Some(page[0].id),
+            limit: 2,
+            ..Default::default()
+        };
+        let (page, has_more) = query_console_history(&conn, &older, ConsoleLevel::Info).unwrap();
+        assert_eq!(contents(&page), ["a", "b"]);
+        assert!(!has_more);
+
+        // Debug entries are only returned to clients that asked for them
+        let since = GetConsoleHistory {
+            since: Some(300),
+            limit: 10,
+            ..Default::default()
+        };
+        let (page, _) = query_console_history(&conn, &since, ConsoleLevel::Debug).unwrap();
+        assert_eq!(contents(&page), ["d", "trace"]);
+        assert_eq!(page[1].entry.level, ConsoleLevel::Debug);
+    }
+
+    #[test]
+    fn test_migration_adds_levels_to_old_entries() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE console_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                timestamp_ms INTEGER NOT NULL,
+                direction TEXT NOT NULL,
+                msg_type TEXT NOT NULL,
+                content TEXT NOT NULL,
+                sequence_id INTEGER
+            );
+            INSERT INTO console_log (timestamp, timestamp_ms, direction, msg_type, content)
+            VALUES ('00:00:00.000', 0, 'received', 'error', 'fault'),
+                   ('00:00:00.000', 1, 'sent', 'command', 'move');",
+        )
+        .unwrap();
+        ConsoleLogDatabaseInit.run_migrations(&conn).unwrap();
+        // Running again is a no-op
+        ConsoleLogDatabaseInit.run_migrations(&conn).unwrap();
+
+        let query = GetConsoleHistory {
+            limit: 10,
+            ..Default::default()
+        };
+        let (page, _) = query_console_history(&conn, &query, ConsoleLevel::Debug).unwrap();
+        assert_eq!(page[0].entry.level, ConsoleLevel::Error);
+        assert_eq!(page[0].entry.direction, ConsoleDirection::Received);
+        assert_eq!(page[1].entry.level, ConsoleLevel::Info);
+        assert!(page[1].entry.fields.is_empty());
+    }
+
+    #[test]
+    fn test_console_levels_default_to_info() {
+        let mut levels = ConsoleLevels::default();
+        let debug = ConsoleLogEntry::default().with_level(ConsoleLevel::Debug);
+        let info = ConsoleLogEntry::default();
+        assert!(levels.wants(ConnectionId { id: 1 }, &info));
+        assert!(!levels.wants(ConnectionId { id: 1 }, &debug));
+
+        levels.levels.insert(ConnectionId { id: 1 }, ConsoleLevel::Debug);
+        assert!(levels.wants(ConnectionId { id: 1 }, &debug));
+        assert!(!levels.wants(ConnectionId { id: 2 }, &debug));
+    }
+}
@@ -4,11 +4,16 @@
 //! - `DatabaseResource` - SQLite connection wrapper
 //! - `DatabaseInit` trait - For plugins to register their schemas
 //! - `DatabaseInitRegistry` - Resource holding all database initializers
+//! - `SeedDemoData` - Run mode that fills the database with demo data
+//! - Scoped resets - `DatabaseResource::reset` drops and recreates every table,
+//!   or only those of the named plugins
 
 use bevy::prelude::*;
 use rusqlite::Connection;
 use std::sync::{Arc, Mutex};
 
+use crate::types::ResetScope;
+
 /// Database resource providing SQLite connection.
 #[derive(Resource)]
 pub struct DatabaseResource(pub Arc<Mutex<Connection>>);
@@ -41,21 +46,101 @@ impl DatabaseResource {
         
         // Initialize each plugin's schema
         for init in &registry.initializers {
-            info!("📦 Initializing database schema for: {}", init.name());
-            init.init_schema(&conn)?;
-            init.run_migrations(&conn)?;
-            init.seed_data(&conn)?;
-            
-            // Record schema version
-            conn.execute(
-                "INSERT OR REPLACE INTO _schema_versions (plugin, version, updated_at) 
-                 VALUES (?, 1, CURRENT_TIMESTAMP)",
-                [init.name()],
-            )?;
+            init_plugin(&conn, init.as_ref())?;
         }
         
         Ok(())
     }
+
+    /// Insert every registered plugin's demo data.
+    pub fn seed_demo_data(&self, registry: &DatabaseInitRegistry) -> anyhow::Result<()> {
+        let conn = self.0.lock().unwrap();
+        for init in &registry.initializers {
+            info!("🌱 Seeding demo data for: {}", init.name());
+            init.seed_demo_data(&conn)?;
+        }
+        Ok(())
+    }
+
+    /// Tables a reset of `scope` drops.
+    ///
+    /// Fails if `scope` names a plugin that has no registered initializer.
+    pub fn tables_in_scope(&self, registry: &DatabaseInitRegistry, scope: &ResetScope) -> anyhow::Result<Vec<String>> {
+        match scope {
+            ResetScope::All => {
+                let conn = self.0.lock().unwrap();
+                let mut stmt = conn.prepare(
+                    "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'"
+                )?;
+                let tables = stmt.query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<String>>>()?;
+                Ok(tables)
+            }
+            ResetScope::Plugins(names) => {
+                let mut tables = Vec::new();
+                for init in registry.in_scope(names)? {
+                    tables.extend(schema_tables(init)?);
+                }
+                Ok(tables)
+            }
+        }
+    }
+
+    /// Drop the tables of `scope` and reinitialize their schemas.
+    ///
+    /// Returns the dropped tables.
+    pub fn reset(&self, registry: &DatabaseInitRegistry, scope: &ResetScope) -> anyhow::Result<Vec<String>> {
+        let tables = self.tables_in_scope(registry, scope)?;
+        {
+            let conn = self.0.lock().unwrap();
+            for table in &tables {
+                if let Err(e) = conn.execute(&format!("DROP TABLE IF EXISTS \"{}\"", table), []) {
+                    error!("Failed to drop table {}: {}", table, e);
+                }
+            }
+        }
+        info!("Dropped {} tables", tables.len());
+
+        match scope {
+            ResetScope::All => self.init_all(registry)?,
+            ResetScope::Plugins(names) => {
+                let conn = self.0.lock().unwrap();
+                for init in registry.in_scope(names)? {
+                    init_plugin(&conn, init)?;
+                }
+            }
+        }
+        Ok(tables)
+    }
+}
+
+/// Create, migrate and seed one plugin's schema, and record its version.
+fn init_plugin(conn: &Connection, init: &dyn DatabaseInit) -> anyhow::Result<()> {
+    info!("📦 Initializing database schema for: {}", init.name());
+    init.init_schema(conn)?;
+    init.run_migrations(conn)?;
+    init.seed_data(conn)?;
+    
+    // Record schema version
+    conn.execute(
+        "INSERT OR REPLACE INTO _schema_versions (plugin, version, updated_at) 
+         VALUES (?, 1, CURRENT_TIMESTAMP)",
+        [init.name()],
+    )?;
+    Ok(())
+}
+
+/// Tables created by a plugin's schema, found by creating it in an empty
+/// in-memory database.
+pub fn schema_tables(init: &dyn DatabaseInit) -> anyhow::Result<Vec<String>> {
+    let conn = Connection::open_in_memory()?;
+    init.init_schema(&conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'"
+    )?;
+    let tables = stmt.query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(tables)
 }
 
 /// Trait for plugins to register their database schemas and migrations.
@@ -77,6 +162,33 @@ pub trait DatabaseInit: Send + Sync + 'static {
         let _ = conn; // Suppress unused warning
         Ok(())
     }
+    
+    /// Insert representative demo data (programs, connections, ...) when the
+    /// server runs with `--seed-demo-data`. Runs on every start in that mode,
+    /// so it must skip rows that already exist.
+    fn seed_demo_data(&self, conn: &Connection) -> anyhow::Result<()> {
+        let _ = conn; // Suppress unused warning
+        Ok(())
+    }
+}
+
+/// Whether plugins seed demo data on startup.
+///
+/// `CorePlugin` sets it from the `--seed-demo-data` argument or the
+/// `SEED_DEMO_DATA` environment variable unless it was inserted already, so
+/// tests can insert `SeedDemoData(true)` before adding the plugin.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SeedDemoData(pub bool);
+
+impl SeedDemoData {
+    /// Read the run mode from the command line and environment.
+    pub fn from_env() -> Self {
+        let flag = std::env::args().any(|arg| arg == "--seed-demo-data");
+        let env = std::env::var("SEED_DEMO_DATA")
+            .map(|value| matches!(value.as_str(), "1" | "true"))
+            .unwrap_or(false);
+        Self(flag || env)
+    }
 }
 
 /// Resource holding all database initializers.
@@ -96,5 +208,71 @@ impl DatabaseInitRegistry {
     pub fn register(&mut self, init: impl DatabaseInit) {
         self.initializers.push(Box::new(init));
     }
+
+    /// Names of the registered initializers.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.initializers.iter().map(|init| init.name()).collect()
+    }
+
+    /// Initializers with the given names, failing on unknown names.
+    pub fn in_scope(&self, names: &[String]) -> anyhow::Result<Vec<&dyn DatabaseInit>> {
+        names
+            .iter()
+            .map(|name| {
+                self.initializers
+                    .iter()
+                    .find(|init| init.name() == name.as_str())
+                    .map(|init| init.as_ref())
+                    .ok_or_else(|| anyhow::anyhow!(
+                        "Unknown database schema '{}' (registered: {})",
+                        name,
+                        self.names().join(", ")
+                    ))
+            })
+            .collect()
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console_log::ConsoleLogDatabaseInit;
+    use crate::webhooks::WebhookDatabaseInit;
+
+    fn count(db: &DatabaseResource, table: &str) -> i64 {
+        let conn = db.connection();
+        let conn = conn.lock().unwrap();
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scoped_reset_keeps_other_plugins() {
+        let mut registry = DatabaseInitRegistry::new();
+        registry.register(ConsoleLogDatabaseInit);
+        registry.register(WebhookDatabaseInit);
+        let db = DatabaseResource::open(":memory:").unwrap();
+        db.init_all(&registry).unwrap();
+        {
+            let conn = db.connection();
+            let conn = conn.lock().unwrap();
+            conn.execute_batch(
+                "INSERT INTO console_log (timestamp, timestamp_ms, direction, msg_type, content)
+                 VALUES ('00:00:00.000', 0, 'system', 'status', 'hello');
+                 INSERT INTO webhooks (name, url, events) VALUES ('ops', 'http://localhost', '[]');",
+            )
+            .unwrap();
+        }
+
+        let scope = ResetScope::Plugins(vec!["console_log".into()]);
+        assert_eq!(db.tables_in_scope(&registry, &scope).unwrap(), vec!["console_log"]);
+        assert_eq!(db.reset(&registry, &scope).unwrap(), vec!["console_log"]);
+        assert_eq!(count(&db, "console_log"), 0);
+        assert_eq!(count(&db, "webhooks"), 1);
+
+        let unknown = ResetScope::Plugins(vec!["nope".into()]);
+        assert!(db.reset(&registry, &unknown).is_err());
+        assert_eq!(count(&db, "webhooks"), 1);
+    }
+}
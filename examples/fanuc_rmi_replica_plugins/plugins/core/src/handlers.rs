@@ -1,60 +1,145 @@
 //! Core request handlers.
 
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
 use bevy::ecs::message::MessageReader;
 use bevy::prelude::*;
 use pl3xus::managers::network_request::Request;
+use pl3xus::{ConnectionId, Network};
+use pl3xus_common::ServerNotification;
+use pl3xus_sync::notifications::OutgoingNotification;
+use pl3xus_sync::{QueryInvalidation, SyncServerMessage};
+use pl3xus_websockets::WebSocketProvider;
 
+use crate::types::{ResetScope, RESET_CONFIRMATION_SECS};
 use crate::{DatabaseInitRegistry, DatabaseResource, ResetDatabase, ResetDatabaseResponse};
 
-/// Handle ResetDatabase request - drops all tables and reinitializes.
+/// A reset a client asked for but hasn't confirmed yet.
+struct PendingReset {
+    token: String,
+    scope: ResetScope,
+    expires_at: f64,
+}
+
+/// Unconfirmed resets, one per client connection.
+#[derive(Resource, Default)]
+pub struct PendingResets {
+    pending: HashMap<ConnectionId, PendingReset>,
+}
+
+impl PendingResets {
+    /// Remember a reset of `scope` for `connection`, returning its token.
+    fn issue(&mut self, connection: ConnectionId, scope: ResetScope, now: f64) -> String {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        // RandomState is seeded randomly per process
+        let token = format!(
+            "{:016x}",
+            std::collections::hash_map::RandomState::new().hash_one((nanos, connection.id))
+        );
+        self.pending.insert(
+            connection,
+            PendingReset {
+                token: token.clone(),
+                scope,
+                expires_at: now + RESET_CONFIRMATION_SECS,
+            },
+        );
+        token
+    }
+
+    /// Consume `connection`'s pending reset if `token` confirms it for `scope`.
+    fn confirm(&mut self, connection: ConnectionId, scope: &ResetScope, token: &str, now: f64) -> Result<(), String> {
+        let pending = self
+            .pending
+            .remove(&connection)
+            .ok_or("No reset is pending for this client; request a new confirmation token")?;
+        if pending.expires_at < now {
+            return Err("Confirmation token expired; request a new one".into());
+        }
+        if pending.token != token || pending.scope != *scope {
+            return Err("Confirmation token does not match the requested reset".into());
+        }
+        Ok(())
+    }
+}
+
+/// Handle ResetDatabase request - drops the tables in scope and reinitializes them.
+///
+/// The first request from a client only returns a confirmation token; the
+/// reset happens when the same request comes back with it. Afterwards every
+/// client is notified and told to refetch all queries.
 pub fn handle_reset_database(
     mut requests: MessageReader<Request<ResetDatabase>>,
+    mut pending: ResMut<PendingResets>,
+    time: Res<Time>,
+    net: Res<Network<WebSocketProvider>>,
+    mut notifications: MessageWriter<OutgoingNotification>,
     db: Option<Res<DatabaseResource>>,
     registry: Res<DatabaseInitRegistry>,
 ) {
+    let now = time.elapsed_secs_f64();
+
     for request in requests.read() {
-        info!("📋 Handling ResetDatabase - dropping and reinitializing all tables");
+        let reset = request.get_request();
+        let source = *request.source();
 
-        let result = match db.as_ref() {
-            Some(db_res) => {
-                // Get list of all tables except sqlite internals
-                let conn = db_res.connection();
-                let conn = conn.lock().unwrap();
-                
-                // Get all table names
-                let tables: Vec<String> = {
-                    let mut stmt = conn.prepare(
-                        "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'"
-                    ).unwrap();
-                    stmt.query_map([], |row| row.get(0))
-                        .unwrap()
-                        .filter_map(|r| r.ok())
-                        .collect()
-                };
-                
-                // Drop all tables
-                for table in &tables {
-                    if let Err(e) = conn.execute(&format!("DROP TABLE IF EXISTS \"{}\"", table), []) {
-                        error!("Failed to drop table {}: {}", table, e);
+        let response = match (db.as_ref(), &reset.confirmation) {
+            (None, _) => ResetDatabaseResponse {
+                success: false,
+                confirmation_token: None,
+                tables: Vec::new(),
+                error: Some("Database not available".into()),
+            },
+            (Some(db_res), None) => match db_res.tables_in_scope(&registry, &reset.scope) {
+                Ok(tables) => {
+                    info!("📋 ResetDatabase {:?} requested by {:?}, awaiting confirmation", reset.scope, source);
+                    ResetDatabaseResponse {
+                        success: false,
+                        confirmation_token: Some(pending.issue(source, reset.scope.clone(), now)),
+                        tables,
+                        error: None,
+                    }
+                }
+                Err(e) => ResetDatabaseResponse {
+                    success: false,
+                    confirmation_token: None,
+                    tables: Vec::new(),
+                    error: Some(e.to_string()),
+                },
+            },
+            (Some(db_res), Some(token)) => {
+                let result = pending
+                    .confirm(source, &reset.scope, token, now)
+                    .map_err(anyhow::Error::msg)
+                    .and_then(|()| {
+                        info!("📋 Handling ResetDatabase {:?} - dropping and reinitializing tables", reset.scope);
+                        db_res.reset(&registry, &reset.scope)
+                    });
+                match result {
+                    Ok(tables) => {
+                        info!("✅ Database reset complete");
+                        notify_reset(&net, &mut notifications, &reset.scope);
+                        ResetDatabaseResponse {
+                            success: true,
+                            confirmation_token: None,
+                            tables,
+                            error: None,
+                        }
+                    }
+                    Err(e) => {
+                        error!("❌ Database reset failed: {}", e);
+                        ResetDatabaseResponse {
+                            success: false,
+                            confirmation_token: None,
+                            tables: Vec::new(),
+                            error: Some(e.to_string()),
+                        }
                     }
                 }
-                info!("Dropped {} tables", tables.len());
-                drop(conn);
-                
-                // Reinitialize all schemas
-                db_res.init_all(&registry)
-            }
-            None => Err(anyhow::anyhow!("Database not available")),
-        };
-
-        let response = match result {
-            Ok(_) => {
-                info!("✅ Database reset complete");
-                ResetDatabaseResponse { success: true, error: None }
-            }
-            Err(e) => {
-                error!("❌ Database reset failed: {}", e);
-                ResetDatabaseResponse { success: false, error: Some(e.to_string()) }
             }
         };
 
@@ -64,3 +149,22 @@ pub fn handle_reset_database(
     }
 }
 
+/// Tell every client the data was reset and make them refetch all queries.
+fn notify_reset(
+    net: &Network<WebSocketProvider>,
+    notifications: &mut MessageWriter<OutgoingNotification>,
+    scope: &ResetScope,
+) {
+    let message = match scope {
+        ResetScope::All => "Database was reset".to_string(),
+        ResetScope::Plugins(names) => format!("Database was reset ({})", names.join(", ")),
+    };
+    notifications.write(OutgoingNotification::broadcast(
+        ServerNotification::warning(message).with_context("DatabaseReset"),
+    ));
+    // No query types invalidates every cached query
+    net.broadcast(SyncServerMessage::QueryInvalidation(QueryInvalidation {
+        query_types: Vec::new(),
+        keys: None,
+    }));
+}
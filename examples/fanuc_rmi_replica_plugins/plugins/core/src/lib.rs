@@ -6,6 +6,14 @@
 //! - `ActiveSystem` - Marker component for the control root entity
 //! - `CorePlugin` - Sets up networking, database, and base infrastructure
 //! - `PluginSchedule` - System set for ordering plugin systems
+//! - Console log persistence - `ConsoleLogEntry` messages written with
+//!   `MessageWriter` are broadcast, stored in the `console_log` table, and can
+//!   be queried with `GetConsoleHistory`
+//! - Webhooks - `WebhookEvent` messages are POSTed (signed, with retries) to
+//!   the webhooks configured in the `webhooks` table
+//! - Command log persistence - commands recorded by `CommandLogPlugin` are
+//!   stored in the `command_log` table, so they can be paged through and
+//!   replayed across restarts
 //!
 //! # Usage
 //!
@@ -37,20 +45,31 @@ mod types;
 // Types always available
 pub use types::{
     ActiveSystem, ConsoleLogEntry, ConsoleDirection, ConsoleMsgType, console_entry,
-    ResetDatabase, ResetDatabaseResponse,
+    ConsoleLevel, ConsoleField, SetConsoleLevel, SetConsoleLevelResponse,
+    ConsoleHistoryEntry, GetConsoleHistory, GetConsoleHistoryResponse,
+    ResetDatabase, ResetDatabaseResponse, ResetScope, RESET_CONFIRMATION_SECS,
+    WebhookEventKind, WebhookConfig, WebhookEvent,
+    ListWebhooks, ListWebhooksResponse, SaveWebhook, SaveWebhookResponse,
+    DeleteWebhook, DeleteWebhookResponse,
 };
 
 cfg_if! {
     if #[cfg(feature = "server")] {
+        mod command_log;
+        mod console_log;
         mod database;
         mod handlers;
         mod plugin;
         mod plugin_schedule;
+        mod webhooks;
 
-        pub use database::{DatabaseResource, DatabaseInit, DatabaseInitRegistry};
-        pub use handlers::handle_reset_database;
+        pub use command_log::{CommandLogDatabaseInit, CommandLogRetention, SqliteCommandLog};
+        pub use console_log::{ConsoleLevels, ConsoleLogDatabaseInit, ConsoleLogRetention};
+        pub use database::{DatabaseResource, DatabaseInit, DatabaseInitRegistry, SeedDemoData, schema_tables};
+        pub use handlers::{handle_reset_database, PendingResets};
         pub use plugin::{CorePlugin, init_database};
-        pub use plugin_schedule::PluginSchedule;
+        pub use plugin_schedule::{PluginSchedule, PluginSet};
+        pub use webhooks::{WebhookDatabaseInit, WebhookRetryPolicy, Webhooks};
     }
 }
 
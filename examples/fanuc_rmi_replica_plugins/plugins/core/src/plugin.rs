@@ -5,17 +5,32 @@ use bevy::app::ScheduleRunnerPlugin;
 use bevy::tasks::TaskPoolBuilder;
 use std::time::Duration;
 
-use pl3xus::Pl3xusRuntime;
-use pl3xus_sync::AppRequestRegistrationExt;
-use pl3xus_sync::{Pl3xusSyncPlugin, ComponentSyncConfig, AppPl3xusSyncExt};
+use pl3xus::{AppHealthExt, HealthCheck, HealthPlugin, HealthState, Pl3xusRuntime};
+use pl3xus_sync::admin::{console_log_layer, AdminPlugin, AppAdminExt};
+use pl3xus_sync::{AppRequestRegistrationExt, MessageAccessPolicy};
+use pl3xus_sync::{Pl3xusSyncPlugin, ComponentSyncConfig, AppPl3xusSyncExt, CommandLogPlugin};
 use pl3xus_sync::control::{ExclusiveControlPlugin, EntityControl};
 use pl3xus_websockets::{NetworkSettings, WebSocketProvider};
 
-use crate::database::{DatabaseResource, DatabaseInitRegistry};
-use crate::handlers::handle_reset_database;
+use crate::command_log::{persist_command_log, CommandLogDatabaseInit, CommandLogRetention};
+use crate::console_log::{
+    broadcast_and_persist_console_entries, handle_get_console_history, handle_set_console_level,
+    prune_console_log, remove_disconnected_console_levels, ConsoleLevels, ConsoleLogDatabaseInit,
+    ConsoleLogRetention,
+};
+use crate::database::{DatabaseResource, DatabaseInitRegistry, SeedDemoData};
+use crate::handlers::{handle_reset_database, PendingResets};
 use crate::plugin_schedule::PluginSchedule;
 use crate::plugin_schedule::configure_plugin_schedule;
-use crate::types::{ActiveSystem, ResetDatabase};
+use crate::types::{
+    ActiveSystem, ConsoleLogEntry, DeleteWebhook, GetConsoleHistory, ListWebhooks, ResetDatabase,
+    SaveWebhook, SetConsoleLevel, WebhookEvent,
+};
+use crate::webhooks::{
+    dispatch_webhook_events, emit_control_taken_webhooks, handle_delete_webhook,
+    handle_list_webhooks, handle_save_webhook, load_webhooks, WebhookDatabaseInit,
+    WebhookRetryPolicy, Webhooks,
+};
 
 /// Core plugin providing foundational infrastructure.
 ///
@@ -24,7 +39,13 @@ use crate::types::{ActiveSystem, ResetDatabase};
 /// - Async tokio runtime for driver communication
 /// - pl3xus networking and sync
 /// - Exclusive control with hierarchy support
-/// - Database resource
+/// - Database resource (with demo data when run with `--seed-demo-data`)
+/// - Console log broadcast, persistence, and history queries
+/// - Webhook configuration and dispatch of `WebhookEvent`s
+/// - Command log of authorized mutations and `.logged()` messages, persisted
+///   and replayable with `ReplayCommand`
+/// - Admin requests, including runtime settings, for connections on `/admin`
+/// - `/healthz` and `/readyz` probes on the server port
 /// - ActiveSystem entity
 pub struct CorePlugin;
 
@@ -33,7 +54,10 @@ impl Plugin for CorePlugin {
         // Core Bevy plugins (headless 60Hz)
         app.add_plugins((
             MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / 60.0))),
-            bevy::log::LogPlugin::default(),
+            bevy::log::LogPlugin {
+                custom_layer: console_log_layer,
+                ..Default::default()
+            },
         ));
 
         // Configure plugin schedule system sets
@@ -45,9 +69,41 @@ impl Plugin for CorePlugin {
         // Pl3xus networking & sync
         app.add_plugins(pl3xus::Pl3xusPlugin::<WebSocketProvider, bevy::tasks::TaskPool>::default());
         app.insert_resource(Pl3xusRuntime(TaskPoolBuilder::new().num_threads(2).build()));
-        app.insert_resource(NetworkSettings::default());
+        // /healthz and /readyz are answered on the same port
+        app.add_plugins(HealthPlugin::<WebSocketProvider>::default());
+        app.health_check("database", database_health);
+        // The app connects on /sync, pl3xus-cli on /admin
+        app.insert_resource(NetworkSettings {
+            endpoints: vec!["/sync".to_string(), "/admin".to_string()],
+            health: Some(app.world().resource::<HealthState>().clone()),
+            // Browsers can only connect from the trunk dev server
+            allowed_origins: vec!["http://127.0.0.1:8084".to_string(), "http://localhost:8084".to_string()],
+            ..Default::default()
+        });
         app.add_plugins(Pl3xusSyncPlugin::<WebSocketProvider>::default());
 
+        // Admin requests (pl3xus-cli, runtime settings) for connections on /admin.
+        // The server only listens on localhost.
+        app.add_plugins(
+            AdminPlugin::<WebSocketProvider>::new()
+                .with_policy(MessageAccessPolicy::endpoints(["/admin"]))
+                .with_settings_policy(MessageAccessPolicy::endpoints(["/admin"])),
+        );
+        // Only affects connections opened after the change
+        app.runtime_setting(
+            "channel_warning_threshold",
+            |world| Some(world.get_resource::<NetworkSettings>()?.channel_warning_threshold.to_string()),
+            |world, value| {
+                let threshold: u8 = value
+                    .parse()
+                    .ok()
+                    .filter(|percent| *percent <= 100)
+                    .ok_or_else(|| format!("Invalid percentage '{}'", value))?;
+                world.resource_mut::<NetworkSettings>().channel_warning_threshold = threshold;
+                Ok(())
+            },
+        );
+
         // Exclusive control (30 minute timeout, propagate to children)
         app.add_plugins(
             ExclusiveControlPlugin::<WebSocketProvider>::builder()
@@ -57,14 +113,38 @@ impl Plugin for CorePlugin {
         );
         app.sync_component::<EntityControl>(None);
 
+        // Command log: every authorized mutation, and the messages plugins
+        // register with `.logged()`. Replays are authorized like the original
+        // command, as the client replaying it. Only admin connections may
+        // read or replay it.
+        app.add_plugins(
+            CommandLogPlugin::<WebSocketProvider>::new()
+                .with_policy(MessageAccessPolicy::endpoints(["/admin"]))
+                .with_replay_policy(MessageAccessPolicy::endpoints(["/admin"])),
+        );
+        app.init_resource::<CommandLogRetention>();
+
         // Sync ActiveSystem component
         app.sync_component::<ActiveSystem>(Some(ComponentSyncConfig::read_only()));
 
         // Initialize database registry (plugins will add their initializers)
         app.init_resource::<DatabaseInitRegistry>();
+        if !app.world().contains_resource::<SeedDemoData>() {
+            app.insert_resource(SeedDemoData::from_env());
+        }
+        app.world_mut()
+            .resource_mut::<DatabaseInitRegistry>()
+            .register(ConsoleLogDatabaseInit);
+        app.world_mut()
+            .resource_mut::<DatabaseInitRegistry>()
+            .register(WebhookDatabaseInit);
+        app.world_mut()
+            .resource_mut::<DatabaseInitRegistry>()
+            .register(CommandLogDatabaseInit);
 
         // Database initialization (runs after all plugins have registered)
         app.add_systems(Startup, init_database);
+        app.add_systems(Startup, persist_command_log.after(init_database));
 
         // Spawn ActiveSystem entity
         app.add_systems(Startup, spawn_active_system.after(init_database));
@@ -74,15 +154,54 @@ impl Plugin for CorePlugin {
 
         // Register request handlers
         app.request::<ResetDatabase, WebSocketProvider>().register();
+        app.init_resource::<PendingResets>();
+        app.request::<GetConsoleHistory, WebSocketProvider>().register();
+        app.request::<SetConsoleLevel, WebSocketProvider>().register();
+        app.add_systems(
+            Update,
+            (handle_reset_database, handle_get_console_history, handle_set_console_level)
+                .in_set(PluginSchedule::ClientRequests),
+        );
+
+        // Console log: plugins write ConsoleLogEntry messages, core broadcasts and persists them
+        app.add_message::<ConsoleLogEntry>();
+        app.init_resource::<ConsoleLogRetention>();
+        app.init_resource::<ConsoleLevels>();
         app.add_systems(
             Update,
-            handle_reset_database.in_set(PluginSchedule::ClientRequests),
+            remove_disconnected_console_levels.in_set(PluginSchedule::ClientConnections),
         );
+        app.add_systems(
+            Update,
+            (broadcast_and_persist_console_entries, prune_console_log)
+                .chain()
+                .in_set(PluginSchedule::Save),
+        );
+
+        // Webhooks: plugins write WebhookEvent messages, core delivers them
+        app.add_message::<WebhookEvent>();
+        app.init_resource::<Webhooks>();
+        app.init_resource::<WebhookRetryPolicy>();
+        app.request::<ListWebhooks, WebSocketProvider>().register();
+        app.request::<SaveWebhook, WebSocketProvider>().register();
+        app.request::<DeleteWebhook, WebSocketProvider>().register();
+        app.add_systems(Update, load_webhooks.in_set(PluginSchedule::Load));
+        app.add_systems(
+            Update,
+            (handle_list_webhooks, handle_save_webhook, handle_delete_webhook)
+                .in_set(PluginSchedule::ClientRequests),
+        );
+        app.add_systems(Update, emit_control_taken_webhooks.in_set(PluginSchedule::Notify));
+        app.add_systems(Update, dispatch_webhook_events.in_set(PluginSchedule::Save));
     }
 }
 
 /// System to initialize the database on startup.
-pub fn init_database(mut commands: Commands, registry: Res<DatabaseInitRegistry>) {
+pub fn init_database(
+    mut commands: Commands,
+    registry: Res<DatabaseInitRegistry>,
+    seed_demo: Option<Res<SeedDemoData>>,
+) {
     let db_path = std::env::var("DATABASE_PATH")
         .unwrap_or_else(|_| "fanuc_replica.db".to_string());
 
@@ -90,6 +209,10 @@ pub fn init_database(mut commands: Commands, registry: Res<DatabaseInitRegistry>
         Ok(db) => {
             if let Err(e) = db.init_all(&registry) {
                 error!("❌ Failed to initialize DB schemas: {}", e);
+            } else if seed_demo.is_some_and(|seed| seed.0) {
+                if let Err(e) = db.seed_demo_data(&registry) {
+                    error!("❌ Failed to seed demo data: {}", e);
+                }
             }
             info!("✅ Database opened at: {}", db_path);
             commands.insert_resource(db);
@@ -100,6 +223,20 @@ pub fn init_database(mut commands: Commands, registry: Res<DatabaseInitRegistry>
     }
 }
 
+/// Ready once the database is open and answering queries.
+fn database_health(world: &World) -> HealthCheck {
+    let Some(db) = world.get_resource::<DatabaseResource>() else {
+        return HealthCheck::failed("Database not open");
+    };
+    let Ok(conn) = db.0.lock() else {
+        return HealthCheck::failed("Database lock poisoned");
+    };
+    match conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0)) {
+        Ok(_) => HealthCheck::ok(),
+        Err(e) => HealthCheck::failed(e.to_string()),
+    }
+}
+
 /// Spawn the ActiveSystem entity on startup.
 fn spawn_active_system(mut commands: Commands) {
     info!("🏭 Spawning ActiveSystem entity");
@@ -22,6 +22,7 @@ pub struct ActiveSystem;
 /// Console log entry (broadcast message for console display).
 ///
 /// Used to send timestamped log messages to clients for display in a console UI.
+/// `Debug` entries are only sent to clients that opted in with `SetConsoleLevel`.
 #[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
 pub struct ConsoleLogEntry {
@@ -37,6 +38,64 @@ pub struct ConsoleLogEntry {
     pub content: String,
     /// Optional sequence ID for correlation
     pub sequence_id: Option<u32>,
+    /// Severity of the entry
+    pub level: ConsoleLevel,
+    /// Subsystem that logged the entry (e.g. "execution", "programs")
+    pub source: Option<String>,
+    /// Structured key/value context (e.g. program name, line number)
+    pub fields: Vec<ConsoleField>,
+}
+
+impl ConsoleLogEntry {
+    /// Set the severity.
+    pub fn with_level(mut self, level: ConsoleLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the subsystem that logged the entry.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Add a key/value field.
+    pub fn with_field(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.fields.push(ConsoleField {
+            key: key.into(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    /// Value of the field named `key`, if present.
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.key == key)
+            .map(|field| field.value.as_str())
+    }
+}
+
+/// Severity of a console entry, ordered from least to most severe.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ConsoleLevel {
+    /// Diagnostic detail, only sent to clients that opted in
+    Debug,
+    /// Normal operation
+    #[default]
+    Info,
+    /// Something unexpected that didn't stop the operation
+    Warn,
+    /// A failed operation
+    Error,
+}
+
+/// A structured key/value field of a console entry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct ConsoleField {
+    pub key: String,
+    pub value: String,
 }
 
 /// Direction of a console message.
@@ -69,10 +128,14 @@ pub enum ConsoleMsgType {
 
 /// Create a console log entry with current timestamp.
 ///
+/// The level is `Error` for `ConsoleMsgType::Error` and `Info` otherwise.
+///
 /// # Example
 /// ```ignore
 /// use fanuc_replica_core::{console_entry, ConsoleDirection, ConsoleMsgType};
-/// let entry = console_entry("Robot connected", ConsoleDirection::System, ConsoleMsgType::Status);
+/// let entry = console_entry("Robot connected", ConsoleDirection::System, ConsoleMsgType::Status)
+///     .with_source("fanuc")
+///     .with_field("robot", "Cell 4");
 /// ```
 pub fn console_entry(
     content: impl Into<String>,
@@ -88,6 +151,10 @@ pub fn console_entry(
     let minutes = (secs % 3600) / 60;
     let seconds = secs % 60;
     let millis = (ms % 1000) as u32;
+    let level = match msg_type {
+        ConsoleMsgType::Error => ConsoleLevel::Error,
+        _ => ConsoleLevel::Info,
+    };
 
     ConsoleLogEntry {
         timestamp: format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis),
@@ -96,21 +163,113 @@ pub fn console_entry(
         msg_type,
         content: content.into(),
         sequence_id: None,
+        level,
+        source: None,
+        fields: Vec::new(),
     }
 }
 
+/// Set the lowest console level sent to this client.
+///
+/// Clients receive `Info` and above by default; set `Debug` to also receive
+/// debug entries, live and in `GetConsoleHistory` results.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SetConsoleLevel {
+    pub min_level: ConsoleLevel,
+}
+
+/// Response for SetConsoleLevel.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SetConsoleLevelResponse {
+    /// The level now in effect for this client.
+    pub min_level: ConsoleLevel,
+    pub error: Option<String>,
+}
+
+impl pl3xus_common::RequestMessage for SetConsoleLevel {
+    type ResponseMessage = SetConsoleLevelResponse;
+}
+
+/// Request persisted console history.
+///
+/// Entries are returned oldest-first. With `before_id: None` the most recent
+/// `limit` entries are returned; pass the `id` of the oldest entry received as
+/// `before_id` to page further back. Entries below the client's
+/// `SetConsoleLevel` are left out.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GetConsoleHistory {
+    /// Only return entries logged at or after this Unix timestamp (ms).
+    pub since: Option<u64>,
+    /// Only return entries older than this entry id (for paging backwards).
+    pub before_id: Option<i64>,
+    /// Maximum number of entries to return (capped by the server).
+    pub limit: u32,
+}
+
+/// A persisted console entry with its database id.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ConsoleHistoryEntry {
+    /// Database row id; use as `before_id` to fetch the previous page.
+    pub id: i64,
+    /// The logged entry.
+    pub entry: ConsoleLogEntry,
+}
+
+/// Response for GetConsoleHistory.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GetConsoleHistoryResponse {
+    /// Entries, oldest first.
+    pub entries: Vec<ConsoleHistoryEntry>,
+    /// Whether older entries matching the query exist.
+    pub has_more: bool,
+    pub error: Option<String>,
+}
+
+impl pl3xus_common::RequestMessage for GetConsoleHistory {
+    type ResponseMessage = GetConsoleHistoryResponse;
+}
+
 // ============================================================================
 // Database Management Messages
 // ============================================================================
 
 /// Reset the database to initial state.
+///
+/// Resetting takes two requests: the first (without `confirmation`) only
+/// returns a `confirmation_token` and the tables that would be dropped; the
+/// same request sent again with that token within
+/// `RESET_CONFIRMATION_SECS` performs the reset.
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
-pub struct ResetDatabase;
+pub struct ResetDatabase {
+    /// Which plugin schemas to reset.
+    pub scope: ResetScope,
+    /// Token from the first step's response.
+    pub confirmation: Option<String>,
+}
+
+/// Seconds a reset confirmation token stays valid.
+pub const RESET_CONFIRMATION_SECS: f64 = 30.0;
+
+/// Which tables a `ResetDatabase` drops and recreates.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ResetScope {
+    /// Every table in the database.
+    #[default]
+    All,
+    /// The tables of these plugins, by the name they registered their
+    /// `DatabaseInit` under (e.g. "programs", "console_log").
+    Plugins(Vec<String>),
+}
 
 /// Response for ResetDatabase.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ResetDatabaseResponse {
+    /// Whether the database was reset (false for the first step).
     pub success: bool,
+    /// Token to confirm the reset with (first step only).
+    pub confirmation_token: Option<String>,
+    /// Tables that would be dropped, or were.
+    pub tables: Vec<String>,
     pub error: Option<String>,
 }
 
@@ -118,3 +277,136 @@ impl pl3xus_common::RequestMessage for ResetDatabase {
     type ResponseMessage = ResetDatabaseResponse;
 }
 
+
+// ============================================================================
+// Webhook Types
+// ============================================================================
+
+/// Events that can be delivered to webhooks.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WebhookEventKind {
+    /// A program ran to completion
+    ProgramCompleted,
+    /// A robot controller raised an alarm
+    AlarmRaised,
+    /// A client took control of the system
+    ControlTaken,
+}
+
+impl WebhookEventKind {
+    /// All event kinds, for filter pickers.
+    pub const ALL: [WebhookEventKind; 3] = [
+        WebhookEventKind::ProgramCompleted,
+        WebhookEventKind::AlarmRaised,
+        WebhookEventKind::ControlTaken,
+    ];
+
+    /// Stable name used in the database and the `X-Pl3xus-Event` header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventKind::ProgramCompleted => "program_completed",
+            WebhookEventKind::AlarmRaised => "alarm_raised",
+            WebhookEventKind::ControlTaken => "control_taken",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == s)
+    }
+}
+
+/// A configured webhook endpoint.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct WebhookConfig {
+    /// Database id (0 for a webhook not saved yet)
+    pub id: i64,
+    /// Display name
+    pub name: String,
+    /// URL the event is POSTed to
+    pub url: String,
+    /// Events delivered to this webhook
+    pub events: Vec<WebhookEventKind>,
+    /// Shared secret for the `X-Pl3xus-Signature` HMAC-SHA256 header
+    pub secret: Option<String>,
+    pub enabled: bool,
+}
+
+/// An event to deliver to matching webhooks.
+///
+/// Plugins write these with `MessageWriter<WebhookEvent>`; the core webhook
+/// dispatcher POSTs them to every enabled webhook subscribed to `kind`.
+#[cfg_attr(feature = "ecs", derive(bevy::prelude::Message))]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WebhookEvent {
+    pub kind: WebhookEventKind,
+    /// One-line human readable description (e.g. for chat integrations)
+    pub summary: String,
+    /// Event details as key/value pairs
+    pub data: Vec<(String, String)>,
+}
+
+impl WebhookEvent {
+    pub fn new(kind: WebhookEventKind, summary: impl Into<String>) -> Self {
+        Self {
+            kind,
+            summary: summary.into(),
+            data: Vec::new(),
+        }
+    }
+
+    pub fn with_data(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.data.push((key.into(), value.to_string()));
+        self
+    }
+}
+
+/// List configured webhooks.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ListWebhooks;
+
+/// Response for ListWebhooks.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ListWebhooksResponse {
+    pub webhooks: Vec<WebhookConfig>,
+    pub error: Option<String>,
+}
+
+impl pl3xus_common::RequestMessage for ListWebhooks {
+    type ResponseMessage = ListWebhooksResponse;
+}
+
+/// Create (`id == 0`) or update a webhook.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SaveWebhook {
+    pub webhook: WebhookConfig,
+}
+
+/// Response for SaveWebhook.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SaveWebhookResponse {
+    pub success: bool,
+    /// Id of the saved webhook
+    pub id: i64,
+    pub error: Option<String>,
+}
+
+impl pl3xus_common::RequestMessage for SaveWebhook {
+    type ResponseMessage = SaveWebhookResponse;
+}
+
+/// Delete a webhook.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DeleteWebhook {
+    pub id: i64,
+}
+
+/// Response for DeleteWebhook.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DeleteWebhookResponse {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl pl3xus_common::RequestMessage for DeleteWebhook {
+    type ResponseMessage = DeleteWebhookResponse;
+}
@@ -0,0 +1,464 @@
+//! Webhook dispatch.
+//!
+//! Webhooks are stored in the `webhooks` table and managed with
+//! `ListWebhooks` / `SaveWebhook` / `DeleteWebhook`. Plugins report events by
+//! writing `WebhookEvent` messages; each is POSTed as JSON to every enabled
+//! webhook subscribed to its kind:
+//!
+//! ```json
+//! {
+//!   "event": "alarm_raised",
+//!   "summary": "SRVO-001 Operator panel E-stop on Robot 1",
+//!   "data": { "robot": "Robot 1", "code": "SRVO-001" },
+//!   "timestamp_ms": 1760630400000,
+//!   "delivery_id": "1760630400000-7"
+//! }
+//! ```
+//!
+//! Requests carry `X-Pl3xus-Event` and `X-Pl3xus-Delivery` headers, plus
+//! `X-Pl3xus-Signature: sha256=<hex>` (HMAC-SHA256 of the body) when the
+//! webhook has a secret. Failed deliveries are retried with exponential
+//! backoff according to `WebhookRetryPolicy`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::ecs::message::MessageReader;
+use bevy::prelude::*;
+use bevy_tokio_tasks::TokioTasksRuntime;
+use hmac::{Hmac, Mac};
+use pl3xus::managers::network_request::Request;
+use pl3xus_common::ConnectionId;
+use pl3xus_sync::control::EntityControl;
+use rusqlite::{params, Connection};
+use sha2::Sha256;
+
+use crate::database::{DatabaseInit, DatabaseResource};
+use crate::types::{
+    ActiveSystem, DeleteWebhook, DeleteWebhookResponse, ListWebhooks, ListWebhooksResponse,
+    SaveWebhook, SaveWebhookResponse, WebhookConfig, WebhookEvent, WebhookEventKind,
+};
+
+/// Webhook database initializer.
+pub struct WebhookDatabaseInit;
+
+impl DatabaseInit for WebhookDatabaseInit {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    fn init_schema(&self, conn: &Connection) -> anyhow::Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS webhooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                url TEXT NOT NULL,
+                events TEXT NOT NULL,
+                secret TEXT,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+}
+
+/// Retry policy for webhook deliveries.
+#[derive(Resource, Clone, Debug)]
+pub struct WebhookRetryPolicy {
+    /// Total attempts per delivery, including the first.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled after each failure.
+    pub initial_backoff: Duration,
+    /// Upper bound for the retry delay.
+    pub max_backoff: Duration,
+    /// Timeout for a single attempt.
+    pub timeout: Duration,
+}
+
+impl Default for WebhookRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Loaded webhook configurations and the HTTP client used to deliver them.
+#[derive(Resource, Default)]
+pub struct Webhooks {
+    configs: Vec<WebhookConfig>,
+    client: reqwest::Client,
+    loaded: bool,
+}
+
+impl Webhooks {
+    /// Reload configurations from the database.
+    fn reload(&mut self, conn: &Connection) {
+        match list_webhooks(conn) {
+            Ok(configs) => {
+                self.configs = configs;
+                self.loaded = true;
+            }
+            Err(e) => error!("Failed to load webhooks: {}", e),
+        }
+    }
+}
+
+fn encode_events(events: &[WebhookEventKind]) -> String {
+    events.iter().map(|e| e.as_str()).collect::<Vec<_>>().join(",")
+}
+
+fn decode_events(events: &str) -> Vec<WebhookEventKind> {
+    events.split(',').filter_map(|e| WebhookEventKind::parse(e.trim())).collect()
+}
+
+fn list_webhooks(conn: &Connection) -> rusqlite::Result<Vec<WebhookConfig>> {
+    let mut stmt = conn.prepare("SELECT id, name, url, events, secret, enabled FROM webhooks ORDER BY id")?;
+    let webhooks = stmt
+        .query_map([], |row| {
+            let events: String = row.get(3)?;
+            Ok(WebhookConfig {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                url: row.get(2)?,
+                events: decode_events(&events),
+                secret: row.get(4)?,
+                enabled: row.get(5)?,
+            })
+        })?
+        .collect();
+    webhooks
+}
+
+fn save_webhook(conn: &Connection, webhook: &WebhookConfig) -> rusqlite::Result<i64> {
+    let events = encode_events(&webhook.events);
+    if webhook.id == 0 {
+        conn.execute(
+            "INSERT INTO webhooks (name, url, events, secret, enabled) VALUES (?, ?, ?, ?, ?)",
+            params![webhook.name, webhook.url, events, webhook.secret, webhook.enabled],
+        )?;
+        Ok(conn.last_insert_rowid())
+    } else {
+        let updated = conn.execute(
+            "UPDATE webhooks SET name = ?, url = ?, events = ?, secret = ?, enabled = ? WHERE id = ?",
+            params![webhook.name, webhook.url, events, webhook.secret, webhook.enabled, webhook.id],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(webhook.id)
+    }
+}
+
+fn validate_webhook(webhook: &WebhookConfig) -> Result<(), String> {
+    if webhook.name.trim().is_empty() {
+        return Err("Webhook name is required".to_string());
+    }
+    if !(webhook.url.starts_with("http://") || webhook.url.starts_with("https://")) {
+        return Err("Webhook URL must start with http:// or https://".to_string());
+    }
+    if webhook.events.is_empty() {
+        return Err("Select at least one event".to_string());
+    }
+    Ok(())
+}
+
+/// HMAC-SHA256 of `body` with `secret`, hex encoded.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Whether a failed attempt is worth retrying.
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// POST `body` to `webhook`, retrying according to `policy`.
+async fn deliver(
+    client: reqwest::Client,
+    webhook: WebhookConfig,
+    kind: WebhookEventKind,
+    delivery_id: String,
+    body: Vec<u8>,
+    policy: WebhookRetryPolicy,
+) {
+    let signature = webhook.secret.as_deref().map(|secret| format!("sha256={}", sign(secret, &body)));
+    let mut backoff = policy.initial_backoff;
+
+    for attempt in 1..=policy.max_attempts.max(1) {
+        let mut request = client
+            .post(&webhook.url)
+            .timeout(policy.timeout)
+            .header("Content-Type", "application/json")
+            .header("X-Pl3xus-Event", kind.as_str())
+            .header("X-Pl3xus-Delivery", &delivery_id)
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header("X-Pl3xus-Signature", signature);
+        }
+
+        let retry = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                debug!("Webhook '{}' accepted {} ({})", webhook.name, kind.as_str(), delivery_id);
+                return;
+            }
+            Ok(response) => {
+                warn!(
+                    "Webhook '{}' rejected {} with HTTP {} (attempt {})",
+                    webhook.name, delivery_id, response.status(), attempt
+                );
+                is_retryable(response.status())
+            }
+            Err(e) => {
+                warn!("Webhook '{}' delivery {} failed: {} (attempt {})", webhook.name, delivery_id, e, attempt);
+                true
+            }
+        };
+
+        if !retry || attempt == policy.max_attempts {
+            break;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(policy.max_backoff);
+    }
+
+    error!("❌ Webhook '{}' gave up on {} ({})", webhook.name, kind.as_str(), delivery_id);
+}
+
+/// Load webhook configurations once the database is open.
+pub fn load_webhooks(mut webhooks: ResMut<Webhooks>, db: Option<Res<DatabaseResource>>) {
+    if webhooks.loaded {
+        return;
+    }
+    let Some(db) = db else {
+        return;
+    };
+    let conn = db.connection();
+    let conn = conn.lock().unwrap();
+    webhooks.reload(&conn);
+    info!("🔔 Loaded {} webhook(s)", webhooks.configs.len());
+}
+
+/// POST WebhookEvents to every enabled webhook subscribed to their kind.
+pub fn dispatch_webhook_events(
+    mut events: MessageReader<WebhookEvent>,
+    webhooks: Res<Webhooks>,
+    policy: Res<WebhookRetryPolicy>,
+    tokio_runtime: Res<TokioTasksRuntime>,
+) {
+    static NEXT_DELIVERY: AtomicU64 = AtomicU64::new(1);
+
+    for event in events.read() {
+        let targets: Vec<&WebhookConfig> = webhooks
+            .configs
+            .iter()
+            .filter(|w| w.enabled && w.events.contains(&event.kind))
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
+
+        let timestamp_ms = unix_ms();
+        let delivery_id = format!("{}-{}", timestamp_ms, NEXT_DELIVERY.fetch_add(1, Ordering::Relaxed));
+        let data: serde_json::Map<String, serde_json::Value> = event
+            .data
+            .iter()
+            .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+            .collect();
+        let body = serde_json::json!({
+            "event": event.kind.as_str(),
+            "summary": event.summary,
+            "data": data,
+            "timestamp_ms": timestamp_ms,
+            "delivery_id": delivery_id,
+        })
+        .to_string()
+        .into_bytes();
+
+        for webhook in targets {
+            let client = webhooks.client.clone();
+            let webhook = webhook.clone();
+            let kind = event.kind;
+            let delivery_id = delivery_id.clone();
+            let body = body.clone();
+            let policy = policy.clone();
+            tokio_runtime.spawn_background_task(move |_ctx| async move {
+                deliver(client, webhook, kind, delivery_id, body, policy).await;
+            });
+        }
+    }
+}
+
+/// Report a ControlTaken event whenever a client takes control of the system.
+pub fn emit_control_taken_webhooks(
+    systems: Query<(Entity, &EntityControl, Option<&Name>), (With<ActiveSystem>, Changed<EntityControl>)>,
+    mut holders: Local<HashMap<Entity, ConnectionId>>,
+    mut events: MessageWriter<WebhookEvent>,
+) {
+    for (entity, control, name) in systems.iter() {
+        // Activity updates also change EntityControl; only report new holders
+        let previous = holders.insert(entity, control.client.connection);
+        if previous == Some(control.client.connection) || !control.is_controlled() {
+            continue;
+        }
+        let system = name.map(|n| n.as_str().to_string()).unwrap_or_else(|| format!("{:?}", entity));
+        events.write(
+            WebhookEvent::new(
+                WebhookEventKind::ControlTaken,
+                format!("Client {} took control of {}", control.client.connection.id, system),
+            )
+            .with_data("system", &system)
+            .with_data("client_id", control.client.connection.id),
+        );
+    }
+}
+
+/// Handle ListWebhooks request.
+pub fn handle_list_webhooks(
+    mut requests: MessageReader<Request<ListWebhooks>>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let response = match db.as_ref() {
+            Some(db) => {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                match list_webhooks(&conn) {
+                    Ok(webhooks) => ListWebhooksResponse { webhooks, error: None },
+                    Err(e) => ListWebhooksResponse {
+                        error: Some(e.to_string()),
+                        ..Default::default()
+                    },
+                }
+            }
+            None => ListWebhooksResponse {
+                error: Some("Database not available".into()),
+                ..Default::default()
+            },
+        };
+
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Handle SaveWebhook request - creates or updates a webhook.
+pub fn handle_save_webhook(
+    mut requests: MessageReader<Request<SaveWebhook>>,
+    mut webhooks: ResMut<Webhooks>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let webhook = &request.get_request().webhook;
+        let result = match (validate_webhook(webhook), db.as_ref()) {
+            (Err(e), _) => Err(e),
+            (Ok(()), None) => Err("Database not available".to_string()),
+            (Ok(()), Some(db)) => {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                let result = save_webhook(&conn, webhook).map_err(|e| e.to_string());
+                webhooks.reload(&conn);
+                result
+            }
+        };
+
+        let response = match result {
+            Ok(id) => {
+                info!("🔔 Saved webhook '{}' ({})", webhook.name, id);
+                SaveWebhookResponse { success: true, id, error: None }
+            }
+            Err(e) => SaveWebhookResponse { success: false, id: webhook.id, error: Some(e) },
+        };
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Handle DeleteWebhook request.
+pub fn handle_delete_webhook(
+    mut requests: MessageReader<Request<DeleteWebhook>>,
+    mut webhooks: ResMut<Webhooks>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let id = request.get_request().id;
+        let response = match db.as_ref() {
+            Some(db) => {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                let result = conn.execute("DELETE FROM webhooks WHERE id = ?", [id]);
+                webhooks.reload(&conn);
+                match result {
+                    Ok(_) => DeleteWebhookResponse { success: true, error: None },
+                    Err(e) => DeleteWebhookResponse { success: false, error: Some(e.to_string()) },
+                }
+            }
+            None => DeleteWebhookResponse {
+                success: false,
+                error: Some("Database not available".into()),
+            },
+        };
+
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_hmac_sha256() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_save_and_list_webhooks() {
+        let conn = Connection::open_in_memory().unwrap();
+        WebhookDatabaseInit.init_schema(&conn).unwrap();
+
+        let mut webhook = WebhookConfig {
+            id: 0,
+            name: "MES".into(),
+            url: "https://mes.local/hooks/cell-3".into(),
+            events: vec![WebhookEventKind::ProgramCompleted, WebhookEventKind::AlarmRaised],
+            secret: Some("s3cret".into()),
+            enabled: true,
+        };
+        webhook.id = save_webhook(&conn, &webhook).unwrap();
+        assert_eq!(list_webhooks(&conn).unwrap(), vec![webhook.clone()]);
+
+        webhook.events = vec![WebhookEventKind::ControlTaken];
+        webhook.enabled = false;
+        save_webhook(&conn, &webhook).unwrap();
+        assert_eq!(list_webhooks(&conn).unwrap(), vec![webhook]);
+    }
+}
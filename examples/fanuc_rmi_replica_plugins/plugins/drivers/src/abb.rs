@@ -0,0 +1,338 @@
+//! ABB-style driver with a mocked RAPID controller.
+//!
+//! Real ABB controllers take targets as `robtarget`s - a translation plus a
+//! quaternion - relative to a work object, with speed and zone given as
+//! `speeddata`/`zonedata`. The driver converts each `RobotPose` into a
+//! `MoveL`/`MoveJ` instruction and hands it to an in-memory controller that
+//! executes moves in straight lines at the commanded speed.
+//!
+//! Swap the mock controller for a socket or RWS client to talk to hardware;
+//! everything the orchestrator sees goes through `MotionDevice`.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use fanuc_replica_execution::{
+    DeviceError, ExecutionPoint, MotionCommand, MotionDevice, MotionProgress, MotionType,
+};
+use fanuc_replica_robotics::{FrameId, RobotPose};
+use nalgebra::{Isometry3, Quaternion, Translation3, UnitQuaternion};
+
+/// Device type string reported by the ABB-style driver.
+pub const ABB_DEVICE_TYPE: &str = "abb_rapid";
+
+/// Speed used for points that don't specify one (mm/s).
+const FALLBACK_SPEED: f32 = 100.0;
+
+/// An ABB `robtarget`: position, orientation quaternion and work object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobTarget {
+    /// Translation in mm
+    pub trans: [f64; 3],
+    /// Orientation quaternion in ABB order (q1 = w, q2 = x, q3 = y, q4 = z)
+    pub rot: [f64; 4],
+    /// Work object the target is expressed in
+    pub wobj: String,
+}
+
+impl RobTarget {
+    /// Convert a universal pose to a robtarget.
+    ///
+    /// Tool frames are not valid target frames on ABB - targets are always
+    /// expressed in a work object.
+    pub fn from_pose(pose: &RobotPose) -> Result<Self, DeviceError> {
+        let wobj = match &pose.frame_id {
+            FrameId::World => "wobj0".to_string(),
+            FrameId::UserFrame(n) => format!("wobj{}", n),
+            FrameId::Named(name) => name.clone(),
+            FrameId::Tool(n) => {
+                return Err(DeviceError::InvalidCommand(format!(
+                    "Target expressed in tool frame {} - ABB targets need a work object",
+                    n
+                )))
+            }
+        };
+
+        let (x, y, z) = pose.translation();
+        let q = pose.transform.rotation.into_inner();
+        Ok(Self {
+            trans: [x, y, z],
+            rot: [q.w, q.i, q.j, q.k],
+            wobj,
+        })
+    }
+
+    /// Convert back to a universal pose (used for position feedback).
+    pub fn to_pose(&self) -> RobotPose {
+        let [x, y, z] = self.trans;
+        let [w, i, j, k] = self.rot;
+        let rotation = UnitQuaternion::from_quaternion(Quaternion::new(w, i, j, k));
+        let frame_id = match self.wobj.strip_prefix("wobj").and_then(|n| n.parse::<u8>().ok()) {
+            Some(n) => FrameId::from_uframe_number(n),
+            None => FrameId::Named(self.wobj.clone()),
+        };
+        RobotPose::new(Isometry3::from_parts(Translation3::new(x, y, z), rotation), frame_id)
+    }
+}
+
+impl fmt::Display for RobTarget {
+    /// RAPID literal with default robot configuration and external axes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [x, y, z] = self.trans;
+        let [q1, q2, q3, q4] = self.rot;
+        write!(
+            f,
+            "[[{:.3},{:.3},{:.3}],[{:.6},{:.6},{:.6},{:.6}],[0,0,0,0],[9E9,9E9,9E9,9E9,9E9,9E9]]",
+            x, y, z, q1, q2, q3, q4
+        )
+    }
+}
+
+/// RAPID `speeddata` literal for a TCP speed in mm/s.
+pub fn speed_data(speed: f32) -> String {
+    let speed = if speed > 0.0 { speed } else { FALLBACK_SPEED };
+    format!("[{:.0},500,5000,1000]", speed)
+}
+
+/// RAPID `zonedata` name for a blend radius in mm (`fine` stops at the point).
+pub fn zone_data(blend_radius: f32) -> String {
+    if blend_radius <= 0.0 {
+        "fine".to_string()
+    } else {
+        format!("z{:.0}", blend_radius.round().max(1.0))
+    }
+}
+
+/// Build the RAPID move instruction for a motion.
+pub fn rapid_instruction(target: &RobTarget, motion: &MotionCommand) -> Result<String, DeviceError> {
+    let instruction = match motion.motion_type {
+        MotionType::Linear => "MoveL",
+        MotionType::Joint => "MoveJ",
+        // MoveC needs a via point, which ExecutionPoint doesn't carry
+        MotionType::Circular => {
+            return Err(DeviceError::InvalidCommand(
+                "Circular motion is not supported by the ABB driver".to_string(),
+            ))
+        }
+    };
+    Ok(format!(
+        "{} {}, {}, {}, tool0 \\WObj:={};",
+        instruction,
+        target,
+        speed_data(motion.speed),
+        zone_data(motion.blend_radius),
+        target.wobj
+    ))
+}
+
+/// A move accepted by the mock controller but not yet finished.
+#[derive(Debug, Clone)]
+struct QueuedMove {
+    target: RobotPose,
+    point_index: u32,
+    /// Total time for this move in seconds
+    duration: f64,
+    /// Remaining time for this move in seconds
+    remaining: f64,
+}
+
+/// ABB-style motion driver backed by a mocked RAPID controller.
+#[derive(Debug, Clone)]
+pub struct AbbStyleDriver {
+    connected: bool,
+    /// Moves the controller can hold at once
+    capacity: u32,
+    /// Accepted moves, oldest first
+    queue: VecDeque<QueuedMove>,
+    /// Where the robot ends up after all queued moves
+    commanded_pose: Option<RobotPose>,
+    /// Pose reached by the last finished move
+    current_pose: Option<RobotPose>,
+    /// Moves finished since the last `take_completed()`
+    completed: u32,
+    /// Set when a move was refused, cleared by the next completion
+    rejected: bool,
+    /// RAPID instructions sent, oldest first
+    sent: Vec<String>,
+}
+
+impl AbbStyleDriver {
+    /// Create a disconnected driver that holds up to `capacity` moves.
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            connected: false,
+            capacity: capacity.max(1),
+            queue: VecDeque::new(),
+            commanded_pose: None,
+            current_pose: None,
+            completed: 0,
+            rejected: false,
+            sent: Vec::new(),
+        }
+    }
+
+    /// Connect to the (mock) controller.
+    pub fn connect(&mut self) {
+        self.connected = true;
+    }
+
+    /// Disconnect, dropping any queued motion like a controller stop would.
+    pub fn disconnect(&mut self) {
+        self.connected = false;
+        self.queue.clear();
+        self.commanded_pose = self.current_pose.clone();
+        self.rejected = false;
+    }
+
+    /// RAPID instructions sent so far.
+    pub fn sent_instructions(&self) -> &[String] {
+        &self.sent
+    }
+
+    /// Advance controller time by `dt` seconds.
+    pub fn advance(&mut self, mut dt: f64) {
+        while dt > 0.0 {
+            let Some(current) = self.queue.front_mut() else {
+                break;
+            };
+            if current.remaining > dt {
+                current.remaining -= dt;
+                break;
+            }
+            dt -= current.remaining;
+            if let Some(finished) = self.queue.pop_front() {
+                self.current_pose = Some(finished.target);
+                self.completed += 1;
+                self.rejected = false;
+            }
+        }
+    }
+
+    /// Number of moves finished since the last call.
+    pub fn take_completed(&mut self) -> u32 {
+        std::mem::take(&mut self.completed)
+    }
+}
+
+impl MotionDevice for AbbStyleDriver {
+    fn device_type(&self) -> &str {
+        ABB_DEVICE_TYPE
+    }
+
+    fn send_motion(
+        &mut self,
+        target: &RobotPose,
+        motion: &MotionCommand,
+        point: &ExecutionPoint,
+    ) -> Result<(), DeviceError> {
+        if !self.connected {
+            return Err(DeviceError::NotConnected);
+        }
+        if self.queue.len() as u32 >= self.capacity {
+            self.rejected = true;
+            return Err(DeviceError::Busy);
+        }
+
+        let robtarget = RobTarget::from_pose(target)?;
+        let instruction = rapid_instruction(&robtarget, motion)?;
+
+        let distance = self.commanded_pose.as_ref().map_or(0.0, |from| {
+            (target.transform.translation.vector - from.transform.translation.vector).norm()
+        });
+        let speed = if motion.speed > 0.0 { motion.speed } else { FALLBACK_SPEED };
+        let duration = distance / speed as f64;
+
+        self.sent.push(instruction);
+        self.queue.push_back(QueuedMove {
+            target: robtarget.to_pose(),
+            point_index: point.index,
+            duration,
+            remaining: duration,
+        });
+        self.commanded_pose = Some(target.clone());
+        Ok(())
+    }
+
+    fn ready_for_next(&self) -> bool {
+        self.connected && !self.rejected && (self.queue.len() as u32) < self.capacity
+    }
+
+    fn in_flight_capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    fn rejected(&self) -> bool {
+        self.rejected
+    }
+
+    fn motions_completed(&self) -> u32 {
+        self.completed
+    }
+
+    fn motion_progress(&self) -> Option<MotionProgress> {
+        self.queue.front().map(|current| MotionProgress {
+            point_index: current.point_index,
+            elapsed: (current.duration - current.remaining) as f32,
+        })
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    fn current_pose(&self) -> Option<RobotPose> {
+        self.current_pose.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robtarget_round_trip() {
+        let pose = RobotPose::from_xyz_wpr(100.0, -50.0, 300.0, 90.0, 0.0, 180.0, FrameId::UserFrame(2));
+        let target = RobTarget::from_pose(&pose).unwrap();
+        assert_eq!(target.wobj, "wobj2");
+
+        let back = target.to_pose();
+        assert_eq!(back.frame_id, FrameId::UserFrame(2));
+        assert!(back.transform.rotation.angle_to(&pose.transform.rotation) < 1e-9);
+        assert!((back.transform.translation.vector - pose.transform.translation.vector).norm() < 1e-9);
+
+        let tool = RobotPose::from_translation(0.0, 0.0, 0.0, FrameId::Tool(1));
+        assert!(RobTarget::from_pose(&tool).is_err());
+    }
+
+    #[test]
+    fn test_driver_streams_and_completes_moves() {
+        let mut driver = AbbStyleDriver::new(2);
+        let motion = MotionCommand { speed: 100.0, motion_type: MotionType::Linear, blend_radius: 5.0 };
+        let start = ExecutionPoint::new(0, RobotPose::from_translation(0.0, 0.0, 0.0, FrameId::World));
+        assert!(matches!(
+            driver.send_motion(&start.target_pose, &motion, &start),
+            Err(DeviceError::NotConnected)
+        ));
+
+        driver.connect();
+        driver.send_motion(&start.target_pose, &motion, &start).unwrap();
+        let next = ExecutionPoint::new(1, RobotPose::from_translation(100.0, 0.0, 0.0, FrameId::World));
+        driver.send_motion(&next.target_pose, &motion, &next).unwrap();
+        assert!(driver.sent_instructions()[1].starts_with("MoveL [[100.000,0.000,0.000]"));
+        assert!(driver.sent_instructions()[1].ends_with(", [100,500,5000,1000], z5, tool0 \\WObj:=wobj0;"));
+
+        // Full - the third move is refused until one finishes
+        assert!(matches!(driver.send_motion(&next.target_pose, &motion, &next), Err(DeviceError::Busy)));
+        assert!(driver.rejected());
+
+        // 100 mm at 100 mm/s
+        driver.advance(0.5);
+        assert_eq!(driver.take_completed(), 1);
+        assert_eq!(driver.motion_progress(), Some(MotionProgress { point_index: 1, elapsed: 0.5 }));
+        assert!(driver.ready_for_next());
+
+        driver.advance(0.5);
+        assert_eq!(driver.take_completed(), 1);
+        assert_eq!(driver.current_pose().unwrap().translation(), (100.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,61 @@
+//! Reference Robot Drivers
+//!
+//! The execution traits in `fanuc_replica_execution` are vendor-agnostic, but
+//! FANUC is the only real implementation. This crate is a complete, working
+//! example of adding another vendor: an ABB-style driver that speaks a mocked
+//! RAPID-like protocol, plus the Bevy plugin that connects it to
+//! `ExecutionPlugin`.
+//!
+//! # Plugging in a new vendor
+//!
+//! 1. **Convert poses at the driver boundary.** Toolpaths carry universal
+//!    `RobotPose`s (nalgebra `Isometry3`). Convert them to the controller's
+//!    native format only when sending - see [`abb::RobTarget::from_pose`].
+//!    Euler-angle vendors can use `fanuc_replica_robotics::quaternion_to_euler_zyx`.
+//!
+//! 2. **Implement `MotionDevice`.** The driver owns the vendor protocol and
+//!    reports buffer space, completions, rejections and motion progress - see
+//!    [`abb::AbbStyleDriver`].
+//!
+//! 3. **Spawn a device entity as a child of the coordinator** with
+//!    `PrimaryMotion`, `ExecutionTarget`, `DeviceStatus`, `DeviceType` and,
+//!    while connected, `DeviceConnected` - see `plugin::spawn_abb_device`.
+//!
+//! 4. **Bridge the driver to the orchestrator.** A system reads
+//!    `MotionCommandEvent`s addressed to your device entity and calls
+//!    `send_motion()`; another feeds completions, rejections and progress back
+//!    into `DeviceStatus`. The orchestrator calls `command_sent()` itself.
+//!
+//! 5. **Take part in validation.** Register a subsystem on coordinators that
+//!    own your device and set its readiness during `SubsystemValidation`, so
+//!    `Start` refuses to run with the robot disconnected.
+//!
+//! # Example Entity Hierarchy
+//!
+//! ```text
+//! PrinterSystem [ExecutionCoordinator, ToolpathBuffer, BufferState]
+//! └── AbbRobot [AbbMotionDevice, ExecutionTarget, PrimaryMotion, DeviceStatus]
+//! ```
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! app.add_plugins((ExecutionPlugin, AbbDriverPlugin));
+//!
+//! // Later, once the coordinator exists:
+//! spawn_abb_device(&mut commands, coordinator, 8);
+//! ```
+
+use cfg_if::cfg_if;
+
+// Always available - the driver has no ECS dependencies
+pub mod abb;
+pub use abb::{AbbStyleDriver, RobTarget, ABB_DEVICE_TYPE};
+
+cfg_if! {
+    if #[cfg(feature = "server")] {
+        mod plugin;
+
+        pub use plugin::{spawn_abb_device, AbbDriverPlugin, AbbMotionDevice, SUBSYSTEM_ABB};
+    }
+}
@@ -0,0 +1,160 @@
+//! Bevy plugin connecting the ABB-style driver to ExecutionPlugin.
+
+use bevy::prelude::*;
+use fanuc_replica_execution::{
+    BufferState, DeviceConnected, DeviceStatus, DeviceType, ExecutionState, ExecutionTarget,
+    MotionCommandEvent, MotionDevice, PrimaryMotion, SubsystemReadiness, Subsystems,
+    SubsystemValidation,
+};
+
+use crate::abb::{AbbStyleDriver, ABB_DEVICE_TYPE};
+
+/// Subsystem name for ABB-style robots.
+pub const SUBSYSTEM_ABB: &str = "abb_robot";
+
+/// Component holding the driver for an ABB-style motion device.
+#[derive(Component, Debug, Clone)]
+pub struct AbbMotionDevice(pub AbbStyleDriver);
+
+/// Spawn a connected ABB-style primary motion device as a child of `coordinator`.
+pub fn spawn_abb_device(commands: &mut Commands, coordinator: Entity, capacity: u32) -> Entity {
+    let mut driver = AbbStyleDriver::new(capacity);
+    driver.connect();
+
+    let mut status = DeviceStatus::with_capacity(driver.in_flight_capacity());
+    status.is_connected = true;
+
+    let device = commands
+        .spawn((
+            Name::new("ABB Robot"),
+            AbbMotionDevice(driver),
+            ExecutionTarget,
+            PrimaryMotion,
+            status,
+            DeviceConnected,
+            DeviceType::new(ABB_DEVICE_TYPE),
+        ))
+        .id();
+    commands.entity(coordinator).add_child(device);
+    device
+}
+
+/// Send MotionCommandEvents addressed to an ABB device to its driver.
+fn abb_motion_handler_system(
+    mut motion_events: MessageReader<MotionCommandEvent>,
+    mut devices: Query<(&mut AbbMotionDevice, &mut DeviceStatus)>,
+) {
+    for event in motion_events.read() {
+        // Events for other devices are handled by their own plugins
+        let Ok((mut device, mut status)) = devices.get_mut(event.device) else {
+            continue;
+        };
+
+        if let Err(e) = device.0.send_motion(&event.target_pose, &event.motion, &event.point) {
+            warn!("ABB driver rejected point {}: {}", event.point.index, e);
+            status.command_rejected(event.point.clone());
+        }
+    }
+}
+
+/// Advance the mock controller and feed its state back into DeviceStatus.
+fn abb_feedback_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut devices: Query<(Entity, &mut AbbMotionDevice, &mut DeviceStatus, Has<DeviceConnected>)>,
+) {
+    let dt = time.delta_secs_f64();
+
+    for (entity, mut device, mut status, marked_connected) in devices.iter_mut() {
+        let driver = &mut device.0;
+        driver.advance(dt);
+        for _ in 0..driver.take_completed() {
+            status.command_completed();
+        }
+        status.progress = driver.motion_progress();
+
+        let connected = driver.is_connected();
+        status.is_connected = connected;
+        // The lifecycle systems watch this marker to reset on disconnect
+        match (connected, marked_connected) {
+            (true, false) => {
+                commands.entity(entity).insert(DeviceConnected);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<DeviceConnected>();
+                status.reset_in_flight();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Register the ABB subsystem on coordinators that own an ABB device.
+fn register_abb_subsystem(
+    devices: Query<&ChildOf, Added<AbbMotionDevice>>,
+    mut coordinators: Query<&mut Subsystems>,
+) {
+    for child_of in devices.iter() {
+        if let Ok(mut subsystems) = coordinators.get_mut(child_of.parent()) {
+            subsystems.register(SUBSYSTEM_ABB);
+            info!("Registered '{}' subsystem on {:?}", SUBSYSTEM_ABB, child_of.parent());
+        }
+    }
+}
+
+/// Validate the ABB subsystem during the Validating phase.
+///
+/// Dry runs execute on the built-in simulator, so the robot is not required.
+fn validate_abb_subsystem(
+    devices: Query<&AbbMotionDevice>,
+    mut coordinators: Query<(&BufferState, Option<&ExecutionState>, Option<&Children>, &mut Subsystems)>,
+) {
+    for (buffer_state, exec_state, children, mut subsystems) in coordinators.iter_mut() {
+        if !buffer_state.is_validating() {
+            continue;
+        }
+
+        let mut owned = children
+            .into_iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|child| devices.get(child).ok())
+            .peekable();
+        if owned.peek().is_none() {
+            continue;
+        }
+
+        let simulated = exec_state.is_some_and(|exec| exec.simulated);
+        let readiness = if simulated || owned.any(|device| device.0.is_connected()) {
+            SubsystemReadiness::Ready
+        } else {
+            SubsystemReadiness::Error("No ABB robot connected".to_string())
+        };
+        subsystems.set_readiness(SUBSYSTEM_ABB, readiness);
+    }
+}
+
+/// Plugin for the ABB-style reference driver.
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// app.add_plugins(AbbDriverPlugin);
+/// ```
+///
+/// # Dependencies
+///
+/// This plugin expects the ExecutionPlugin to be registered first,
+/// as it consumes MotionCommandEvent from the orchestrator.
+pub struct AbbDriverPlugin;
+
+impl Plugin for AbbDriverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (register_abb_subsystem, abb_motion_handler_system, abb_feedback_system).chain(),
+        );
+        app.add_systems(Update, validate_abb_subsystem.in_set(SubsystemValidation));
+
+        info!("ABB driver plugin loaded");
+    }
+}
@@ -14,6 +14,8 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::types::DuetTelemetry;
+
 /// Configuration for a Duet-based extruder device.
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct DuetExtruderConfig {
@@ -27,6 +29,48 @@ pub struct DuetExtruderConfig {
     pub max_feedrate: f32,
     /// Piston diameter in mm (for volume calculations)
     pub piston_diameter: f32,
+    /// How often to poll the object model for telemetry (ms, 0 = never)
+    #[serde(default = "default_telemetry_interval_ms")]
+    pub telemetry_interval_ms: u32,
+    /// How G-code commands reach the controller
+    #[serde(default)]
+    pub transport: DuetTransport,
+}
+
+/// Transport used to send G-code to the Duet.
+///
+/// Telemetry is always polled over HTTP; the transport only carries commands.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DuetTransport {
+    /// One `/rr_gcode` request per command (highest latency)
+    #[default]
+    Http,
+    /// Persistent WebSocket to the controller (requires the `websocket` feature)
+    WebSocket {
+        /// Path of the WebSocket endpoint, e.g. "/machine"
+        path: String,
+    },
+    /// USB serial connection (requires the `serial` feature)
+    Serial {
+        /// Serial device, e.g. "/dev/ttyACM0" or "COM3"
+        port: String,
+        baud_rate: u32,
+    },
+}
+
+impl DuetTransport {
+    /// Short name for logs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DuetTransport::Http => "http",
+            DuetTransport::WebSocket { .. } => "websocket",
+            DuetTransport::Serial { .. } => "serial",
+        }
+    }
+}
+
+fn default_telemetry_interval_ms() -> u32 {
+    500
 }
 
 impl Default for DuetExtruderConfig {
@@ -37,6 +81,8 @@ impl Default for DuetExtruderConfig {
             axis: 'Y',
             max_feedrate: 6000.0,
             piston_diameter: 50.0,
+            telemetry_interval_ms: default_telemetry_interval_ms(),
+            transport: DuetTransport::Http,
         }
     }
 }
@@ -65,14 +111,6 @@ pub struct DuetPositionState {
 #[derive(Component, Debug, Clone, Default)]
 pub struct DuetExtruder;
 
-/// HTTP client resource for Duet communication.
-/// This is a placeholder - actual implementation would use reqwest or similar.
-#[derive(Resource, Default)]
-pub struct DuetHttpClient {
-    // In a real implementation, this would hold a reqwest::Client
-    // For now, we'll use a simple marker
-}
-
 /// Event for sending commands to Duet extruders.
 #[derive(bevy::prelude::Message, Debug, Clone)]
 pub struct DuetCommandEvent {
@@ -93,6 +131,7 @@ pub struct DuetExtruderBundle {
     pub config: DuetExtruderConfig,
     pub connection: DuetConnectionState,
     pub position: DuetPositionState,
+    pub telemetry: DuetTelemetry,
 }
 
 impl DuetExtruderBundle {
@@ -102,6 +141,7 @@ impl DuetExtruderBundle {
             config,
             connection: DuetConnectionState::default(),
             position: DuetPositionState::default(),
+            telemetry: DuetTelemetry::default(),
         }
     }
 }
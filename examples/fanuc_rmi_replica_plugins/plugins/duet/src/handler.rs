@@ -1,15 +1,23 @@
 //! Duet Extruder Command Handler System
 //!
 //! This system processes AuxiliaryCommandEvents for Duet extruders,
-//! converting them to HTTP requests to the Duet controller.
+//! converting them to G-code sent over the extruder's transport.
+//! Digital output devices with the `DuetGpio` backend are written with `M42`
+//! over the same link.
+
+use std::collections::HashMap;
 
 use bevy::prelude::*;
+use bevy_tokio_tasks::TokioTasksRuntime;
 
 use crate::device::{
     DuetCommandEvent, DuetConnectionState, DuetExtruder, DuetExtruderConfig,
     DuetPositionState, format_extrusion_gcode,
 };
-use fanuc_replica_execution::{AuxiliaryCommand, AuxiliaryCommandEvent};
+use crate::transport::{DuetHttpClient, DuetLink};
+use fanuc_replica_execution::{
+    AuxiliaryCommand, AuxiliaryCommandEvent, DigitalOutputBackend, DigitalOutputWriteEvent,
+};
 
 /// System that processes AuxiliaryCommandEvents for Duet extruders.
 ///
@@ -18,6 +26,9 @@ use fanuc_replica_execution::{AuxiliaryCommand, AuxiliaryCommandEvent};
 /// 2. Looks up the Duet configuration for the target entity
 /// 3. Converts the command to G-code
 /// 4. Sends DuetCommandEvent for actual HTTP transmission
+///
+/// Extrusions timed to span the motion (`duration` set) run at the speed that
+/// covers their distance in that time, instead of their own speed.
 pub fn duet_command_handler_system(
     mut aux_events: MessageReader<AuxiliaryCommandEvent>,
     mut duet_events: MessageWriter<DuetCommandEvent>,
@@ -43,6 +54,10 @@ pub fn duet_command_handler_system(
             AuxiliaryCommand::Extruder { distance, speed } => {
                 // Convert relative distance to absolute position
                 let target_position = current_pos.position + distance;
+                let speed = match event.duration {
+                    Some(duration) if duration > 0.0 => distance.abs() / duration,
+                    _ => *speed,
+                };
                 // Convert speed from mm/s to mm/min
                 let feedrate = (speed * 60.0).min(config.max_feedrate);
 
@@ -79,19 +94,26 @@ pub fn duet_command_handler_system(
     }
 }
 
-/// System that would send HTTP requests to Duet controllers.
+/// System that sends Duet commands over each extruder's transport.
 ///
-/// In a real implementation, this would use reqwest or similar to send
-/// HTTP requests. For now, it just logs the commands.
+/// Opens a `DuetLink` on the first command (or after the link closed or the
+/// configured transport changed) and queues the G-code on it. The link's
+/// background task does the actual I/O, so this never blocks the frame.
 pub fn duet_http_sender_system(
+    mut commands: Commands,
+    tokio_runtime: Res<TokioTasksRuntime>,
+    client: Res<DuetHttpClient>,
     mut events: MessageReader<DuetCommandEvent>,
     mut duet_query: Query<
-        (&DuetExtruderConfig, &mut DuetConnectionState, &mut DuetPositionState),
+        (&DuetExtruderConfig, &mut DuetConnectionState, &mut DuetPositionState, Option<&DuetLink>),
         With<DuetExtruder>,
     >,
 ) {
+    // Links opened this frame, inserted once all events are handled
+    let mut opened: HashMap<Entity, DuetLink> = HashMap::new();
+
     for event in events.read() {
-        let Ok((config, mut connection, mut position)) = duet_query.get_mut(event.extruder) else {
+        let Ok((config, mut connection, mut position, link)) = duet_query.get_mut(event.extruder) else {
             warn!("DuetCommandEvent for unknown entity {:?}", event.extruder);
             continue;
         };
@@ -99,18 +121,89 @@ pub fn duet_http_sender_system(
         // Format the G-code command
         let gcode = format_extrusion_gcode(config.axis, event.target_position, event.feedrate);
 
-        info!(
-            "Duet HTTP: http://{}:{}/rr_gcode?gcode={} (point {})",
-            config.host,
-            config.port,
-            urlencoding::encode(&gcode),
+        let usable = link.is_some_and(|l| l.is_open() && l.transport == config.transport);
+        if !usable && !opened.contains_key(&event.extruder) {
+            opened.insert(event.extruder, client.open(&tokio_runtime, event.extruder, config));
+            connection.connected = true;
+        }
+        let Some(link) = opened.get(&event.extruder).or(link) else {
+            continue;
+        };
+
+        debug!(
+            "Duet {}: {} (point {})",
+            link.transport.name(),
+            gcode,
             event.point_index
         );
 
-        // Update local state (in real impl, this would be after HTTP response)
+        if let Err(e) = link.send(gcode) {
+            warn!("Duet command for point {} dropped: {}", event.point_index, e);
+            connection.last_error = Some(e);
+            continue;
+        }
+
+        // Update local state (the controller confirms asynchronously)
         position.position = event.target_position;
         position.feedrate = event.feedrate;
         connection.commands_sent += 1;
     }
+
+    for (entity, link) in opened {
+        commands.entity(entity).insert(link);
+    }
 }
 
+/// System that performs GPIO writes for digital output devices.
+///
+/// Writes with the `DuetGpio` backend become `M42 P{port} S{0|1}` on the
+/// first extruder's board, sent over its link (opened if needed).
+pub fn duet_digital_output_system(
+    mut commands: Commands,
+    tokio_runtime: Res<TokioTasksRuntime>,
+    client: Res<DuetHttpClient>,
+    mut write_events: MessageReader<DigitalOutputWriteEvent>,
+    mut duet_query: Query<
+        (Entity, &DuetExtruderConfig, &mut DuetConnectionState, Option<&DuetLink>),
+        With<DuetExtruder>,
+    >,
+) {
+    let mut opened: Option<(Entity, DuetLink)> = None;
+
+    for event in write_events.read() {
+        if event.write.backend != DigitalOutputBackend::DuetGpio {
+            continue;
+        }
+        let Some((entity, config, mut connection, link)) =
+            duet_query.iter_mut().min_by_key(|(entity, ..)| *entity)
+        else {
+            warn!(
+                "GPIO write P{} for point {} dropped: no Duet board",
+                event.write.port, event.point_index
+            );
+            continue;
+        };
+
+        let usable = link.is_some_and(|l| l.is_open() && l.transport == config.transport);
+        if !usable && opened.is_none() {
+            opened = Some((entity, client.open(&tokio_runtime, entity, config)));
+            connection.connected = true;
+        }
+        let Some(link) = opened.as_ref().map(|(_, l)| l).or(link) else {
+            continue;
+        };
+
+        let gcode = format!("M42 P{} S{}", event.write.port, if event.write.value { 1 } else { 0 });
+        debug!("Duet {}: {} (point {})", link.transport.name(), gcode, event.point_index);
+        if let Err(e) = link.send(gcode) {
+            warn!("GPIO write for point {} dropped: {}", event.point_index, e);
+            connection.last_error = Some(e);
+            continue;
+        }
+        connection.commands_sent += 1;
+    }
+
+    if let Some((entity, link)) = opened {
+        commands.entity(entity).insert(link);
+    }
+}
@@ -12,29 +12,42 @@
 //! For extrusion, we use:
 //! - `G1 Y{position} F{feedrate}` - Move Y axis (piston) to position
 //! - `M220 S{percent}` - Set speed override
+//!
+//! Commands go over HTTP by default; the WebSocket (`websocket` feature) and
+//! USB serial (`serial` feature) transports avoid the per-request latency.
+//! Extruder state (position, temperatures, status) is polled from the object
+//! model and synced to clients as `DuetTelemetry`.
 
 use cfg_if::cfg_if;
 
-// Always available
-mod device;
-pub use device::{
-    DuetCommandEvent, DuetConnectionState, DuetExtruder, DuetExtruderBundle,
-    DuetExtruderConfig, DuetHttpClient, DuetPositionState,
-    format_extrusion_gcode, piston_travel_to_volume, volume_to_piston_travel,
-};
+// Always available - pure data types shared with the client
+mod types;
+pub use types::{DuetHeater, DuetTelemetry};
 
 cfg_if! {
     if #[cfg(feature = "server")] {
         mod handler;
+        mod telemetry;
+        mod transport;
 
-        pub use handler::{duet_command_handler_system, duet_http_sender_system};
+        pub use handler::{
+            duet_command_handler_system, duet_digital_output_system, duet_http_sender_system,
+        };
+        pub use telemetry::{duet_telemetry_poll_system, DuetTelemetryPoll};
+        pub use transport::{DuetHttpClient, DuetLink};
     }
 }
 
 cfg_if! {
     if #[cfg(feature = "ecs")] {
+        mod device;
         mod plugin;
 
+        pub use device::{
+            DuetCommandEvent, DuetConnectionState, DuetExtruder, DuetExtruderBundle,
+            DuetExtruderConfig, DuetPositionState, DuetTransport,
+            format_extrusion_gcode, piston_travel_to_volume, volume_to_piston_travel,
+        };
         pub use plugin::DuetPlugin;
     }
 }
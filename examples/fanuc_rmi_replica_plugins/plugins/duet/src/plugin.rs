@@ -5,14 +5,26 @@ use bevy::prelude::*;
 use crate::device::DuetCommandEvent;
 
 #[cfg(feature = "server")]
-use crate::handler::{duet_command_handler_system, duet_http_sender_system};
+use crate::handler::{
+    duet_command_handler_system, duet_digital_output_system, duet_http_sender_system,
+};
+#[cfg(feature = "server")]
+use crate::telemetry::duet_telemetry_poll_system;
+#[cfg(feature = "server")]
+use crate::transport::DuetHttpClient;
+#[cfg(feature = "server")]
+use crate::types::DuetTelemetry;
+#[cfg(feature = "server")]
+use pl3xus_sync::{AppPl3xusSyncExt, ComponentSyncConfig};
 
 /// Plugin for the Duet extruder system.
 ///
 /// This plugin registers:
 /// - Events for Duet command dispatch
 /// - Command handler system (converts AuxiliaryCommandEvent to DuetCommandEvent)
-/// - HTTP sender system (sends commands to Duet controller)
+/// - Sender system (sends commands over the configured transport)
+/// - GPIO writes for `DigitalOutputDevice`s with the `DuetGpio` backend
+/// - Telemetry polling system and `DuetTelemetry` sync
 ///
 /// # Usage
 ///
@@ -33,14 +45,27 @@ impl Plugin for DuetPlugin {
 
         #[cfg(feature = "server")]
         {
+            // Shared HTTP client, also used to open WebSocket/serial links
+            app.init_resource::<DuetHttpClient>();
+
             // Duet command handler - converts AuxiliaryCommandEvent to DuetCommandEvent
             // Should run after the orchestrator system
             app.add_systems(Update, duet_command_handler_system);
 
-            // HTTP sender - sends commands to Duet controller
+            // Sender - sends commands over each extruder's transport
             // Should run after the command handler
             app.add_systems(Update, duet_http_sender_system.after(duet_command_handler_system));
 
+            // GPIO writes for digital output devices - after the sender so a
+            // link it opened this frame is reused
+            app.add_systems(Update, duet_digital_output_system.after(duet_http_sender_system));
+
+            // Object model telemetry - read-only for clients
+            app.sync_component::<DuetTelemetry>(Some(ComponentSyncConfig::read_only_with_message(
+                "DuetTelemetry is read-only. It is polled from the Duet object model."
+            )));
+            app.add_systems(Update, duet_telemetry_poll_system);
+
             info!("Duet plugin loaded");
         }
     }
@@ -0,0 +1,225 @@
+//! Duet Object Model Telemetry
+//!
+//! Polls `/rr_model` for the extrusion axis position, heaters and machine
+//! status at each extruder's `telemetry_interval_ms`, and stores the result
+//! in the synced `DuetTelemetry` component.
+//!
+//! Requests run on the Tokio runtime; only one poll per extruder is in flight
+//! at a time, so a slow or unreachable controller doesn't pile up requests.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy_tokio_tasks::TokioTasksRuntime;
+use serde_json::Value;
+
+use crate::device::{DuetConnectionState, DuetExtruder, DuetExtruderConfig};
+use crate::transport::DuetHttpClient;
+use crate::types::{DuetHeater, DuetTelemetry};
+
+/// Object model keys read on every poll.
+const MODEL_KEYS: [&str; 3] = ["move.axes", "heat.heaters", "state.status"];
+
+/// Object model flags: depth 99, frequently changing values, no nulls.
+const MODEL_FLAGS: &str = "d99fn";
+
+/// Polling state for a Duet extruder (server-only).
+#[derive(Component, Debug, Default)]
+pub struct DuetTelemetryPoll {
+    /// When the last poll started (seconds)
+    last_poll: Option<f64>,
+    /// True while a poll is waiting for the controller
+    in_flight: bool,
+}
+
+fn model_url(config: &DuetExtruderConfig, key: &str) -> String {
+    format!(
+        "http://{}:{}/rr_model?key={}&flags={}",
+        config.host, config.port, key, MODEL_FLAGS
+    )
+}
+
+/// Fetch one object model key and return its `result`.
+async fn fetch_model(client: &reqwest::Client, url: &str) -> Result<Value, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let mut body: Value = response.json().await.map_err(|e| e.to_string())?;
+    match body.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err("Object model response has no result".to_string()),
+    }
+}
+
+/// Apply the `result` of one object model key to `telemetry`.
+fn apply_model_result(telemetry: &mut DuetTelemetry, key: &str, result: &Value, axis: char) {
+    match key {
+        "move.axes" => {
+            let position = result
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|a| a.get("letter").and_then(Value::as_str) == Some(axis.to_string().as_str()))
+                .and_then(|a| a.get("userPosition").or_else(|| a.get("machinePosition")))
+                .and_then(Value::as_f64);
+            if let Some(position) = position {
+                telemetry.position = position as f32;
+            }
+        }
+        "heat.heaters" => {
+            let number = |heater: &Value, field: &str| {
+                heater.get(field).and_then(Value::as_f64).unwrap_or(0.0) as f32
+            };
+            telemetry.heaters = result
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|heater| DuetHeater {
+                    current: number(heater, "current"),
+                    active: number(heater, "active"),
+                    standby: number(heater, "standby"),
+                    state: heater.get("state").and_then(Value::as_str).unwrap_or("off").to_string(),
+                })
+                .collect();
+        }
+        "state.status" => {
+            if let Some(status) = result.as_str() {
+                telemetry.status = status.to_string();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// System that polls the Duet object model for telemetry.
+///
+/// Results are applied on the main thread. `DuetTelemetry` is only written
+/// when something changed, so unchanged polls don't trigger a sync.
+pub fn duet_telemetry_poll_system(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    time: Res<Time>,
+    client: Res<DuetHttpClient>,
+    mut commands: Commands,
+    mut extruders: Query<
+        (Entity, &DuetExtruderConfig, Option<&mut DuetTelemetryPoll>, Has<DuetTelemetry>),
+        With<DuetExtruder>,
+    >,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for (entity, config, poll, has_telemetry) in extruders.iter_mut() {
+        if config.telemetry_interval_ms == 0 {
+            continue;
+        }
+        let Some(mut poll) = poll else {
+            commands.entity(entity).insert(DuetTelemetryPoll::default());
+            if !has_telemetry {
+                commands.entity(entity).insert(DuetTelemetry::default());
+            }
+            continue;
+        };
+
+        let due = poll
+            .last_poll
+            .is_none_or(|last| now - last >= config.telemetry_interval_ms as f64 / 1000.0);
+        if poll.in_flight || !due {
+            continue;
+        }
+        poll.in_flight = true;
+        poll.last_poll = Some(now);
+
+        let client = client.client.clone();
+        let urls: Vec<(&'static str, String)> =
+            MODEL_KEYS.iter().map(|key| (*key, model_url(config, key))).collect();
+        let axis = config.axis;
+
+        tokio_runtime.spawn_background_task(move |mut ctx| async move {
+            let mut results = Vec::new();
+            let mut error = None;
+            for (key, url) in &urls {
+                match fetch_model(&client, url).await {
+                    Ok(result) => results.push((*key, result)),
+                    Err(e) => {
+                        error = Some(format!("{}: {}", key, e));
+                        break;
+                    }
+                }
+            }
+
+            ctx.run_on_main_thread(move |ctx| {
+                let Ok(mut entity_mut) = ctx.world.get_entity_mut(entity) else {
+                    return;
+                };
+                if let Some(mut poll) = entity_mut.get_mut::<DuetTelemetryPoll>() {
+                    poll.in_flight = false;
+                }
+                let online = error.is_none();
+                if let Some(mut connection) = entity_mut.get_mut::<DuetConnectionState>() {
+                    if connection.connected != online {
+                        connection.connected = online;
+                    }
+                    if let Some(error) = &error {
+                        connection.last_error = Some(error.clone());
+                    }
+                }
+
+                let Some(mut telemetry) = entity_mut.get_mut::<DuetTelemetry>() else {
+                    return;
+                };
+                let mut next = telemetry.clone();
+                for (key, result) in &results {
+                    apply_model_result(&mut next, key, result, axis);
+                }
+                next.online = online;
+                next.error = error;
+
+                if next != *telemetry {
+                    if online {
+                        next.updated_at_ms = Some(unix_ms());
+                    }
+                    *telemetry = next;
+                }
+            })
+            .await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_model_result() {
+        let mut telemetry = DuetTelemetry::default();
+
+        let axes = serde_json::json!([
+            { "letter": "X", "userPosition": 10.0 },
+            { "letter": "Y", "userPosition": 42.5 },
+        ]);
+        apply_model_result(&mut telemetry, "move.axes", &axes, 'Y');
+        assert_eq!(telemetry.position, 42.5);
+
+        let heaters = serde_json::json!([
+            { "current": 24.5, "active": 0.0, "standby": 0.0, "state": "off" },
+            { "current": 198.0, "active": 200.0, "standby": 150.0, "state": "active" },
+        ]);
+        apply_model_result(&mut telemetry, "heat.heaters", &heaters, 'Y');
+        assert_eq!(telemetry.heaters.len(), 2);
+        assert_eq!(telemetry.heaters[1].active, 200.0);
+        assert_eq!(telemetry.heaters[1].state, "active");
+
+        apply_model_result(&mut telemetry, "state.status", &serde_json::json!("busy"), 'Y');
+        assert_eq!(telemetry.status, "busy");
+    }
+}
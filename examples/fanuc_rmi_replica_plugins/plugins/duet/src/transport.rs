@@ -0,0 +1,248 @@
+//! Duet Command Transports
+//!
+//! Each extruder gets a `DuetLink`: a channel into a background task that owns
+//! the connection selected by `DuetExtruderConfig::transport`:
+//!
+//! - **HTTP**: one `/rr_gcode` request per command
+//! - **WebSocket** (`websocket` feature): one persistent socket, one text frame
+//!   per command, kept alive with `PING`
+//! - **Serial** (`serial` feature): newline-terminated G-code over USB
+//!
+//! The link is opened lazily on the first command and reopened if the task
+//! exits, so `DuetCommandEvent` producers don't care which transport is used.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tokio_tasks::{TaskContext, TokioTasksRuntime};
+use tokio::sync::mpsc;
+
+use crate::device::{DuetConnectionState, DuetExtruderConfig, DuetTransport};
+
+/// Give up on a single HTTP request after this long.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// HTTP client resource for Duet communication.
+///
+/// Shared by the HTTP transport and telemetry polling, and used to open a
+/// `DuetLink` with whichever transport an extruder is configured for.
+#[derive(Resource, Clone)]
+pub struct DuetHttpClient {
+    pub client: reqwest::Client,
+}
+
+impl Default for DuetHttpClient {
+    fn default() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+}
+
+impl DuetHttpClient {
+    /// Open a link to the extruder on `entity` using its configured transport.
+    pub fn open(&self, runtime: &TokioTasksRuntime, entity: Entity, config: &DuetExtruderConfig) -> DuetLink {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let transport = config.transport.clone();
+        let client = self.client.clone();
+        let host = config.host.clone();
+        let port = config.port;
+
+        info!("Opening Duet {} link to {}:{} for {:?}", transport.name(), host, port, entity);
+
+        let task_transport = transport.clone();
+        runtime.spawn_background_task(move |ctx| async move {
+            let result = match task_transport {
+                DuetTransport::Http => run_http(&client, &host, port, rx).await,
+                DuetTransport::WebSocket { path } => run_websocket(&host, port, &path, rx).await,
+                DuetTransport::Serial { port, baud_rate } => run_serial(&port, baud_rate, rx).await,
+            };
+            report_closed(ctx, entity, result).await;
+        });
+
+        DuetLink { tx, transport }
+    }
+}
+
+/// Open command link to a Duet extruder (server-only).
+#[derive(Component, Debug)]
+pub struct DuetLink {
+    tx: mpsc::UnboundedSender<String>,
+    /// Transport the link was opened with
+    pub transport: DuetTransport,
+}
+
+impl DuetLink {
+    /// Queue a G-code line. Fails once the link's task has exited.
+    pub fn send(&self, gcode: String) -> Result<(), String> {
+        self.tx.send(gcode).map_err(|_| "Duet link closed".to_string())
+    }
+
+    /// True while the link's task is running.
+    pub fn is_open(&self) -> bool {
+        !self.tx.is_closed()
+    }
+}
+
+/// Record why a link closed on the extruder's connection state.
+async fn report_closed(mut ctx: TaskContext, entity: Entity, result: Result<(), String>) {
+    ctx.run_on_main_thread(move |ctx| {
+        if let Some(mut connection) = ctx.world.get_mut::<DuetConnectionState>(entity) {
+            connection.connected = false;
+            if let Err(e) = result {
+                error!("Duet link for {:?} closed: {}", entity, e);
+                connection.last_error = Some(e);
+            }
+        }
+    })
+    .await;
+}
+
+async fn run_http(
+    client: &reqwest::Client,
+    host: &str,
+    port: u16,
+    mut rx: mpsc::UnboundedReceiver<String>,
+) -> Result<(), String> {
+    while let Some(gcode) = rx.recv().await {
+        let url = format!("http://{}:{}/rr_gcode?gcode={}", host, port, urlencoding::encode(&gcode));
+        client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("HTTP {}: {}", gcode, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "websocket")]
+async fn run_websocket(
+    host: &str,
+    port: u16,
+    path: &str,
+    mut rx: mpsc::UnboundedReceiver<String>,
+) -> Result<(), String> {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let url = format!("ws://{}:{}{}", host, port, path);
+    let (socket, _) = tokio_tungstenite::connect_async(&url)
+        .await
+        .map_err(|e| format!("WebSocket {}: {}", url, e))?;
+    let (mut sink, mut stream) = socket.split();
+    let mut keepalive = tokio::time::interval(Duration::from_secs(5));
+
+    loop {
+        tokio::select! {
+            command = rx.recv() => {
+                let Some(gcode) = command else {
+                    return Ok(());
+                };
+                sink.send(Message::Text(gcode.into())).await.map_err(|e| e.to_string())?;
+            }
+            _ = keepalive.tick() => {
+                sink.send(Message::Text("PING\n".into())).await.map_err(|e| e.to_string())?;
+            }
+            incoming = stream.next() => match incoming {
+                // Replies and object model patches are not needed here
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.to_string()),
+                None => return Err("WebSocket closed by controller".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "websocket"))]
+async fn run_websocket(
+    _host: &str,
+    _port: u16,
+    _path: &str,
+    _rx: mpsc::UnboundedReceiver<String>,
+) -> Result<(), String> {
+    Err("WebSocket transport requires the `websocket` feature".to_string())
+}
+
+#[cfg(feature = "serial")]
+async fn run_serial(
+    port: &str,
+    baud_rate: u32,
+    mut rx: mpsc::UnboundedReceiver<String>,
+) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+    use tokio_serial::SerialPortBuilderExt;
+
+    let mut serial = tokio_serial::new(port, baud_rate)
+        .open_native_async()
+        .map_err(|e| format!("Serial {}: {}", port, e))?;
+
+    while let Some(gcode) = rx.recv().await {
+        serial.write_all(gcode.as_bytes()).await.map_err(|e| e.to_string())?;
+        serial.write_all(b"\n").await.map_err(|e| e.to_string())?;
+        serial.flush().await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "serial"))]
+async fn run_serial(
+    _port: &str,
+    _baud_rate: u32,
+    _rx: mpsc::UnboundedReceiver<String>,
+) -> Result<(), String> {
+    Err("Serial transport requires the `serial` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_link_closes_with_its_task() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let link = DuetLink { tx, transport: DuetTransport::Http };
+
+        assert!(link.is_open());
+        link.send("G1 Y10 F600".to_string()).unwrap();
+        assert_eq!(rx.try_recv().unwrap(), "G1 Y10 F600");
+
+        drop(rx);
+        assert!(!link.is_open());
+        assert!(link.send("G1 Y20 F600".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_http_transport_ends_when_link_is_dropped() {
+        let (tx, rx) = mpsc::unbounded_channel::<String>();
+        drop(tx);
+        let client = DuetHttpClient::default();
+        assert_eq!(block_on(run_http(&client.client, "127.0.0.1", 80, rx)), Ok(()));
+    }
+
+    #[cfg(not(feature = "serial"))]
+    #[test]
+    fn test_serial_transport_requires_feature() {
+        let (_tx, rx) = mpsc::unbounded_channel();
+        let error = block_on(run_serial("/dev/ttyACM0", 115_200, rx)).unwrap_err();
+        assert!(error.contains("`serial` feature"));
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    #[test]
+    fn test_websocket_transport_requires_feature() {
+        let (_tx, rx) = mpsc::unbounded_channel();
+        let error = block_on(run_websocket("127.0.0.1", 80, "/machine", rx)).unwrap_err();
+        assert!(error.contains("`websocket` feature"));
+    }
+}
@@ -0,0 +1,39 @@
+//! Duet types shared between server and client.
+//!
+//! These are pure data types with conditional derives:
+//! - `ecs`: Component derive for the server
+
+#[cfg(feature = "ecs")]
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One heater as reported by the Duet object model (`heat.heaters`).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DuetHeater {
+    /// Current temperature (°C)
+    pub current: f32,
+    /// Active setpoint (°C)
+    pub active: f32,
+    /// Standby setpoint (°C)
+    pub standby: f32,
+    /// Heater state: "off", "standby", "active", "fault", ...
+    pub state: String,
+}
+
+/// Extruder telemetry polled from the Duet object model (Synced 1-way: Server -> Client).
+#[cfg_attr(feature = "ecs", derive(Component))]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DuetTelemetry {
+    /// True while the last poll succeeded
+    pub online: bool,
+    /// Machine status (`state.status`): "idle", "busy", "halted", ...
+    pub status: String,
+    /// Extrusion axis position (mm)
+    pub position: f32,
+    /// Heaters, indexed by heater number
+    pub heaters: Vec<DuetHeater>,
+    /// Error from the last poll, if it failed
+    pub error: Option<String>,
+    /// Time of the last successful poll (Unix ms)
+    pub updated_at_ms: Option<u64>,
+}
@@ -122,6 +122,18 @@ impl ToolpathBuffer {
         self.points.pop_front()
     }
 
+    /// Return points that were popped but not accepted by the device to the
+    /// front of the buffer, preserving their order.
+    ///
+    /// Unlike `push()`, this does not count towards `total_added`.
+    pub fn requeue_front(&mut self, points: impl IntoIterator<Item = ExecutionPoint>) {
+        let mut points: Vec<_> = points.into_iter().collect();
+        points.sort_by_key(|point| point.index);
+        for point in points.into_iter().rev() {
+            self.points.push_front(point);
+        }
+    }
+
     /// Peek at the front point without removing it.
     pub fn peek(&self) -> Option<&ExecutionPoint> {
         self.points.front()
@@ -167,6 +179,41 @@ impl ToolpathBuffer {
         }
     }
 
+    /// Restart a static program from the point with index `line`.
+    ///
+    /// Restores the original points from `line` onwards. Points before `line`
+    /// count as already executed, so `total_added` covers the whole program
+    /// and callers should start the completed count at `line`.
+    ///
+    /// Returns false if there are no original points or no point at `line`.
+    pub fn rewind_to(&mut self, line: u32) -> bool {
+        let Some(ref originals) = self.original_points else {
+            return false;
+        };
+        if !originals.iter().any(|point| point.index == line) {
+            return false;
+        }
+        self.points.clear();
+        self.points
+            .extend(originals.iter().filter(|point| point.index >= line).cloned());
+        self.total_added = originals.len() as u32;
+        true
+    }
+
+    /// Insert an extra point ahead of the queue (e.g. a re-approach move).
+    ///
+    /// Counts towards `total_added` so completion still waits for every
+    /// program point, but is not stored for re-runs.
+    pub fn insert_front(&mut self, point: ExecutionPoint) {
+        self.points.push_front(point);
+        self.total_added += 1;
+    }
+
+    /// Get a mutable reference to the front point.
+    pub fn front_mut(&mut self) -> Option<&mut ExecutionPoint> {
+        self.points.front_mut()
+    }
+
     /// Check if this buffer supports re-running (has stored original points).
     pub fn can_rerun(&self) -> bool {
         self.original_points.is_some()
@@ -457,84 +504,6 @@ impl BufferState {
             BufferState::Stopped { .. } => SystemState::Stopped,
         }
     }
-
-    /// Compute available UI actions based on current state.
-    ///
-    /// Returns flags for what actions are available in the current state.
-    /// Note: can_load is always false from BufferState because that depends on
-    /// whether a program is loaded, which BufferState doesn't track.
-    pub fn available_actions(&self) -> UiActions {
-        use super::SystemState;
-        match self.to_system_state() {
-            SystemState::NoSource => UiActions {
-                can_load: true,
-                can_start: false,
-                can_pause: false,
-                can_resume: false,
-                can_stop: false,
-                can_unload: false,
-            },
-            SystemState::Ready => UiActions {
-                can_load: false,
-                can_start: true,
-                can_pause: false,
-                can_resume: false,
-                can_stop: false,
-                can_unload: true,
-            },
-            SystemState::Validating => UiActions {
-                can_load: false,
-                can_start: false,
-                can_pause: false,
-                can_resume: false,
-                can_stop: true, // Can cancel validation
-                can_unload: false,
-            },
-            SystemState::Running | SystemState::AwaitingPoints => UiActions {
-                can_load: false,
-                can_start: false,
-                can_pause: true,
-                can_resume: false,
-                can_stop: true,
-                can_unload: false,
-            },
-            SystemState::Paused => UiActions {
-                can_load: false,
-                can_start: false,
-                can_pause: false,
-                can_resume: true,
-                can_stop: true,
-                can_unload: false,
-            },
-            SystemState::Completed | SystemState::Stopped => UiActions {
-                can_load: false,
-                can_start: true, // Can restart
-                can_pause: false,
-                can_resume: false,
-                can_stop: false,
-                can_unload: true,
-            },
-            SystemState::Error => UiActions {
-                can_load: false,
-                can_start: true,
-                can_pause: false,
-                can_resume: false,
-                can_stop: false,
-                can_unload: true,
-            },
-        }
-    }
-}
-
-/// Available UI actions based on execution state.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct UiActions {
-    pub can_load: bool,
-    pub can_start: bool,
-    pub can_pause: bool,
-    pub can_resume: bool,
-    pub can_stop: bool,
-    pub can_unload: bool,
 }
 
 #[cfg(test)]
@@ -553,6 +522,7 @@ mod tests {
                 blend_radius: 0.0,
             },
             aux_commands: Default::default(),
+            aux_timing: Default::default(),
             metadata: Default::default(),
         }
     }
@@ -764,4 +734,64 @@ mod tests {
             Some(4)
         );
     }
+
+    #[test]
+    fn test_rewind_to_restores_points_from_line() {
+        let mut buffer = ToolpathBuffer::new_static(4);
+        buffer.extend((0..4).map(make_test_point));
+        while buffer.pop().is_some() {}
+
+        assert!(buffer.rewind_to(2));
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.peek().map(|p| p.index), Some(2));
+        assert_eq!(buffer.total_added(), 4);
+
+        // Lines before the restart line count as executed
+        buffer.pop();
+        buffer.pop();
+        assert!(!buffer.is_execution_complete(3));
+        assert!(buffer.is_execution_complete(4));
+    }
+
+    #[test]
+    fn test_rewind_to_rejects_unknown_line_and_streaming() {
+        let mut buffer = ToolpathBuffer::new_static(2);
+        buffer.extend((0..2).map(make_test_point));
+        assert!(!buffer.rewind_to(5));
+
+        let mut streaming = ToolpathBuffer::new_streaming();
+        streaming.push(make_test_point(0));
+        assert!(!streaming.rewind_to(0));
+    }
+
+    #[test]
+    fn test_insert_front_counts_towards_completion() {
+        let mut buffer = ToolpathBuffer::new_static(2);
+        buffer.extend((0..2).map(make_test_point));
+        buffer.insert_front(make_test_point(0));
+
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.total_added(), 3);
+        while buffer.pop().is_some() {}
+        assert!(!buffer.is_execution_complete(2));
+        assert!(buffer.is_execution_complete(3));
+
+        // Inserted points are not kept for re-runs
+        assert!(buffer.reset_for_rerun());
+        assert_eq!(buffer.len(), 2);
+    }
+
+    #[test]
+    fn test_requeue_front_restores_index_order() {
+        let mut buffer = ToolpathBuffer::new_static(4);
+        buffer.extend((0..4).map(make_test_point));
+        let popped: Vec<_> = (0..3).filter_map(|_| buffer.pop()).collect();
+
+        // Rejections can arrive out of order
+        buffer.requeue_front(popped.into_iter().skip(1).rev());
+
+        let order: Vec<u32> = std::iter::from_fn(|| buffer.pop()).map(|point| point.index).collect();
+        assert_eq!(order, [1, 2, 3]);
+        assert_eq!(buffer.total_added(), 4);
+    }
 }
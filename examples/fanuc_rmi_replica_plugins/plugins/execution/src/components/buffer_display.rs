@@ -20,12 +20,32 @@ use reactive_stores::Store;
 /// - For generators: updated as points are generated
 ///
 /// The UI uses ExecutionState.current_index to highlight the current row.
+///
+/// The occupancy fields show how far streaming runs ahead of the robot:
+/// points still buffered, points in-flight on the device, the lookahead
+/// window, and whether streaming is paused because the device rejected a point.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "ecs", derive(Component))]
 #[cfg_attr(feature = "stores", derive(Store))]
 pub struct BufferDisplayData {
     /// The lines/points to show in the UI table
     pub lines: Vec<BufferLineDisplay>,
+
+    /// Points waiting in the buffer (not yet sent to the device)
+    #[serde(default)]
+    pub buffered: usize,
+
+    /// Points sent to the device but not yet confirmed complete
+    #[serde(default)]
+    pub in_flight: u32,
+
+    /// Maximum number of points streamed ahead of the device
+    #[serde(default)]
+    pub lookahead_window: u32,
+
+    /// True while streaming is paused because the device rejected a point
+    #[serde(default)]
+    pub flow_paused: bool,
 }
 
 /// A single line in the buffer table display.
@@ -80,11 +100,15 @@ impl Default for BufferLineDisplay {
 
 impl BufferDisplayData {
     pub fn new() -> Self {
-        Self { lines: Vec::new() }
+        Self::default()
     }
 
     pub fn clear(&mut self) {
         self.lines.clear();
+        self.buffered = 0;
+        self.in_flight = 0;
+        self.lookahead_window = 0;
+        self.flow_paused = false;
     }
 
     pub fn push_line(&mut self, line: BufferLineDisplay) {
@@ -98,5 +122,14 @@ impl BufferDisplayData {
     pub fn is_empty(&self) -> bool {
         self.lines.is_empty()
     }
+
+    /// Fraction of the lookahead window currently in-flight (0.0 - 1.0).
+    pub fn window_fill(&self) -> f32 {
+        if self.lookahead_window == 0 {
+            0.0
+        } else {
+            (self.in_flight as f32 / self.lookahead_window as f32).min(1.0)
+        }
+    }
 }
 
@@ -38,6 +38,17 @@ pub struct ExecutionCoordinator {
 
     /// Human-readable name
     pub name: String,
+
+    /// Maximum number of points streamed ahead of the primary motion device.
+    ///
+    /// The effective window is the smaller of this and the device's reported
+    /// in-flight capacity. `None` uses the device capacity as-is.
+    #[serde(default)]
+    pub lookahead_window: Option<u32>,
+
+    /// True while streaming is paused because the device rejected a point.
+    #[serde(default)]
+    pub flow_paused: bool,
 }
 
 impl ExecutionCoordinator {
@@ -47,6 +58,8 @@ impl ExecutionCoordinator {
         Self {
             name: id.clone(),
             id,
+            lookahead_window: None,
+            flow_paused: false,
         }
     }
 
@@ -55,6 +68,23 @@ impl ExecutionCoordinator {
         Self {
             id: id.into(),
             name: name.into(),
+            lookahead_window: None,
+            flow_paused: false,
+        }
+    }
+
+    /// Limit how many points are streamed ahead of the primary motion device.
+    pub fn with_lookahead(mut self, window: u32) -> Self {
+        self.lookahead_window = Some(window.max(1));
+        self
+    }
+
+    /// The lookahead window for a device that can hold `device_capacity`
+    /// motions in-flight.
+    pub fn effective_window(&self, device_capacity: u32) -> u32 {
+        match self.lookahead_window {
+            Some(window) => window.min(device_capacity),
+            None => device_capacity,
         }
     }
 }
@@ -75,6 +105,7 @@ pub struct ExecutionTarget;
 /// Exactly one child of an ExecutionCoordinator should have this marker.
 /// The primary motion device:
 /// - Controls execution timing (its `ready_for_next()` gates new commands)
+/// - Reports its in-flight capacity, which bounds the lookahead window
 /// - Provides motion completion feedback
 /// - Is typically a robot arm
 ///
@@ -83,6 +114,35 @@ pub struct ExecutionTarget;
 #[cfg_attr(feature = "ecs", derive(Component))]
 pub struct PrimaryMotion;
 
+/// Marker component for a coordinator running a dry run.
+///
+/// Added by `handle_start` for `Start { simulate: true }`. While present, the
+/// orchestrator dispatches to the built-in simulator device instead of the
+/// real primary motion device, and auxiliary commands are not sent. Removed
+/// when the run finishes.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ecs", derive(Component))]
+pub struct SimulationMode;
+
+/// Component on a coordinator that is stepping through points one at a time.
+///
+/// Added by `handle_step_once`. The orchestrator dispatches at most
+/// `remaining` more points, then returns to Paused once they are confirmed
+/// complete and removes this component.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ecs", derive(Component))]
+pub struct StepMode {
+    /// Points still to dispatch before pausing
+    pub remaining: u32,
+}
+
+impl StepMode {
+    /// Step a single point.
+    pub fn once() -> Self {
+        Self { remaining: 1 }
+    }
+}
+
 /// Marker component for entities that provide feedback.
 ///
 /// Add this to sensors or other entities that provide feedback
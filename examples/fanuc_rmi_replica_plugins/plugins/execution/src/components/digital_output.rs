@@ -0,0 +1,209 @@
+//! Generic digital output auxiliary device.
+//!
+//! Grippers, valves, vacuum cups and other on/off peripherals only need a
+//! digital output. Instead of writing a plugin for each, spawn a
+//! `DigitalOutputDevice` with named channels and the backend that drives the
+//! physical output; per-point `DigitalOutput` and `Valve` commands are turned
+//! into `DigitalOutputWrite`s that the backend plugin performs.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ecs")]
+use bevy::prelude::*;
+
+use crate::traits::{AuxiliaryCommand, AuxiliaryDevice, DeviceError};
+
+/// Hardware that performs the writes of a `DigitalOutputDevice`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DigitalOutputBackend {
+    /// FANUC controller DOUT (`FrcWriteDOUT`) on the connected robot
+    #[default]
+    FanucDout,
+    /// Duet GPIO output pin (`M42 P{port} S{0|1}`) on the extruder's board
+    DuetGpio,
+}
+
+/// A named output of a `DigitalOutputDevice`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigitalOutputChannel {
+    /// Name used by `Valve { id, .. }` commands (e.g. "gripper")
+    pub name: String,
+    /// Physical port number on the backend
+    pub port: u16,
+}
+
+/// A single output write produced by a `DigitalOutputDevice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigitalOutputWrite {
+    /// Backend that performs the write
+    pub backend: DigitalOutputBackend,
+    /// Physical port number
+    pub port: u16,
+    /// Desired output state
+    pub value: bool,
+}
+
+/// Auxiliary device that maps per-point commands to digital output writes.
+///
+/// Commands are resolved against the configured channels:
+/// - `DigitalOutput { channel, state }`: `channel` indexes `channels`, or is
+///   the raw port number when no channels are configured
+/// - `Valve { id, open }`: `id` is a channel name
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let gripper = DigitalOutputDevice::new("gripper", DigitalOutputBackend::FanucDout)
+///     .with_channel("jaw", 101)
+///     .with_channel("vacuum", 102);
+/// spawn_digital_output_device(&mut commands, coordinator, gripper);
+/// ```
+///
+/// Points then address it by device type:
+/// `aux_commands["gripper"] = AuxiliaryCommand::Valve { id: "jaw".into(), open: true }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ecs", derive(Component))]
+pub struct DigitalOutputDevice {
+    /// Device type matched against `ExecutionPoint.aux_commands` keys
+    pub device_type: String,
+    /// Backend that performs the writes
+    pub backend: DigitalOutputBackend,
+    /// Named outputs, in `DigitalOutput { channel }` index order
+    pub channels: Vec<DigitalOutputChannel>,
+    /// Whether the backend can currently perform writes
+    #[serde(skip)]
+    pub connected: bool,
+    /// Writes accepted by `send_command` and not yet taken by the backend
+    #[serde(skip)]
+    pending: Vec<DigitalOutputWrite>,
+}
+
+impl DigitalOutputDevice {
+    pub fn new(device_type: impl Into<String>, backend: DigitalOutputBackend) -> Self {
+        Self {
+            device_type: device_type.into(),
+            backend,
+            channels: Vec::new(),
+            connected: true,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add a named output on `port`.
+    pub fn with_channel(mut self, name: impl Into<String>, port: u16) -> Self {
+        self.channels.push(DigitalOutputChannel { name: name.into(), port });
+        self
+    }
+
+    /// Resolve a `DigitalOutput` channel index to a port.
+    fn port_for_index(&self, channel: u8) -> Result<u16, DeviceError> {
+        if self.channels.is_empty() {
+            return Ok(channel as u16);
+        }
+        self.channels
+            .get(channel as usize)
+            .map(|c| c.port)
+            .ok_or_else(|| {
+                DeviceError::InvalidCommand(format!(
+                    "{} has no output channel {}",
+                    self.device_type, channel
+                ))
+            })
+    }
+
+    /// Resolve a channel name to a port.
+    fn port_for_name(&self, name: &str) -> Result<u16, DeviceError> {
+        self.channels
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.port)
+            .ok_or_else(|| {
+                DeviceError::InvalidCommand(format!("{} has no output named '{}'", self.device_type, name))
+            })
+    }
+
+    /// Take the writes queued by `send_command`.
+    pub fn take_pending(&mut self) -> Vec<DigitalOutputWrite> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+impl AuxiliaryDevice for DigitalOutputDevice {
+    fn device_type(&self) -> &str {
+        &self.device_type
+    }
+
+    fn send_command(&mut self, cmd: &AuxiliaryCommand) -> Result<(), DeviceError> {
+        let (port, value) = match cmd {
+            AuxiliaryCommand::None => return Ok(()),
+            AuxiliaryCommand::DigitalOutput { channel, state } => (self.port_for_index(*channel)?, *state),
+            AuxiliaryCommand::Valve { id, open } => (self.port_for_name(id)?, *open),
+            other => {
+                return Err(DeviceError::InvalidCommand(format!(
+                    "{} only accepts DigitalOutput and Valve commands, got {:?}",
+                    self.device_type, other
+                )))
+            }
+        };
+
+        if !self.connected {
+            return Err(DeviceError::NotConnected);
+        }
+        self.pending.push(DigitalOutputWrite { backend: self.backend, port, value });
+        Ok(())
+    }
+
+    fn is_ready(&self) -> bool {
+        self.connected
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_map_to_channel_ports() {
+        let mut device = DigitalOutputDevice::new("gripper", DigitalOutputBackend::FanucDout)
+            .with_channel("jaw", 101)
+            .with_channel("vacuum", 102);
+
+        device
+            .send_command(&AuxiliaryCommand::DigitalOutput { channel: 1, state: true })
+            .unwrap();
+        device
+            .send_command(&AuxiliaryCommand::Valve { id: "jaw".into(), open: false })
+            .unwrap();
+        device.send_command(&AuxiliaryCommand::None).unwrap();
+
+        assert!(device
+            .send_command(&AuxiliaryCommand::DigitalOutput { channel: 2, state: true })
+            .is_err());
+        assert!(device
+            .send_command(&AuxiliaryCommand::Valve { id: "clamp".into(), open: true })
+            .is_err());
+        assert!(device.send_command(&AuxiliaryCommand::Dwell { seconds: 1.0 }).is_err());
+
+        let writes = device.take_pending();
+        assert_eq!(writes.len(), 2);
+        assert_eq!((writes[0].port, writes[0].value), (102, true));
+        assert_eq!((writes[1].port, writes[1].value), (101, false));
+        assert!(device.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_raw_ports_without_channels() {
+        let mut device = DigitalOutputDevice::new("valve", DigitalOutputBackend::DuetGpio);
+        device
+            .send_command(&AuxiliaryCommand::DigitalOutput { channel: 7, state: true })
+            .unwrap();
+        assert_eq!(
+            device.take_pending(),
+            vec![DigitalOutputWrite { backend: DigitalOutputBackend::DuetGpio, port: 7, value: true }]
+        );
+    }
+}
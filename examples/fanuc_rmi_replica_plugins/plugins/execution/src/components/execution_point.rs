@@ -13,6 +13,7 @@ use crate::traits::AuxiliaryCommand;
 /// - A target pose for the motion device
 /// - Motion parameters (speed, type, blending)
 /// - Commands for auxiliary devices (keyed by device type)
+/// - Optional timing of those commands relative to the motion
 /// - Optional metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionPoint {
@@ -29,6 +30,12 @@ pub struct ExecutionPoint {
     #[serde(default)]
     pub aux_commands: HashMap<String, AuxiliaryCommand>,
 
+    /// When each auxiliary command runs, keyed by device_type.
+    ///
+    /// Commands without an entry are sent as soon as the point is dispatched.
+    #[serde(default)]
+    pub aux_timing: HashMap<String, AuxTiming>,
+
     /// Optional metadata about this point
     #[serde(default)]
     pub metadata: PointMetadata,
@@ -42,6 +49,7 @@ impl ExecutionPoint {
             target_pose,
             motion: MotionCommand::default(),
             aux_commands: HashMap::new(),
+            aux_timing: HashMap::new(),
             metadata: PointMetadata::default(),
         }
     }
@@ -52,6 +60,19 @@ impl ExecutionPoint {
         self
     }
 
+    /// Add an auxiliary command that runs at `timing` relative to this point's motion.
+    pub fn with_timed_aux_command(
+        mut self,
+        device_type: impl Into<String>,
+        cmd: AuxiliaryCommand,
+        timing: AuxTiming,
+    ) -> Self {
+        let device_type = device_type.into();
+        self.aux_timing.insert(device_type.clone(), timing);
+        self.aux_commands.insert(device_type, cmd);
+        self
+    }
+
     /// Set motion parameters.
     pub fn with_motion(mut self, motion: MotionCommand) -> Self {
         self.motion = motion;
@@ -63,6 +84,64 @@ impl ExecutionPoint {
         self.metadata = metadata;
         self
     }
+
+    /// Build a move that approaches this point from `clearance` mm above.
+    ///
+    /// The approach stops exactly at its target (no blending), moves at no
+    /// more than `max_speed`, and carries no auxiliary commands, so nothing
+    /// is deposited while the tool is repositioned.
+    pub fn approach_from_above(&self, clearance: f64, max_speed: f32) -> ExecutionPoint {
+        let mut target_pose = self.target_pose.clone();
+        target_pose.transform.translation.vector.z += clearance;
+        ExecutionPoint {
+            index: self.index,
+            target_pose,
+            motion: MotionCommand {
+                speed: self.motion.speed.min(max_speed),
+                motion_type: self.motion.motion_type,
+                blend_radius: 0.0,
+            },
+            aux_commands: HashMap::new(),
+            aux_timing: HashMap::new(),
+            metadata: PointMetadata {
+                is_travel: true,
+                comment: Some("Re-approach".to_string()),
+                ..self.metadata.clone()
+            },
+        }
+    }
+}
+
+/// Timing of an auxiliary command relative to the motion to its point.
+///
+/// The coordinator holds timed commands until the motion device reports that
+/// the move has been running for `offset` seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuxTiming {
+    /// Seconds after the motion to this point starts (0 = at motion start)
+    pub offset: f32,
+
+    /// Seconds the command should take, for commands that span the motion
+    #[serde(default)]
+    pub duration: Option<f32>,
+}
+
+impl AuxTiming {
+    /// Run the command `offset` seconds after motion start.
+    pub fn at(offset: f32) -> Self {
+        Self {
+            offset: offset.max(0.0),
+            duration: None,
+        }
+    }
+
+    /// Run the command from `offset` seconds after motion start for `duration` seconds.
+    pub fn spanning(offset: f32, duration: f32) -> Self {
+        Self {
+            offset: offset.max(0.0),
+            duration: Some(duration),
+        }
+    }
 }
 
 /// Motion parameters for a single move.
@@ -0,0 +1,135 @@
+//! ExecutionProgress - synced component with execution telemetry.
+//!
+//! ExecutionState says which line is executing; this component says how far
+//! along the program is in distance and time, and how far each device lags
+//! behind what has been streamed to it.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ecs")]
+use bevy::prelude::*;
+
+#[cfg(feature = "stores")]
+use reactive_stores::Store;
+
+use super::BufferLineDisplay;
+
+/// Speed assumed for lines that don't specify one (mm/s).
+const FALLBACK_SPEED: f64 = 100.0;
+
+/// Execution progress synced to all clients.
+///
+/// Distances and times are computed from the program lines and their
+/// commanded speeds, ignoring acceleration and blending.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ecs", derive(Component))]
+#[cfg_attr(feature = "stores", derive(Store))]
+pub struct ExecutionProgress {
+    /// Points confirmed complete by the motion device
+    pub points_completed: u32,
+
+    /// Total points, if known (None while streaming an open-ended source)
+    pub total_points: Option<u32>,
+
+    /// Path length of the completed points (mm)
+    pub distance_traveled: f64,
+
+    /// Path length of the remaining known points (mm)
+    pub distance_remaining: f64,
+
+    /// Estimated seconds until the known points are complete
+    pub eta_seconds: Option<f64>,
+
+    /// How far each device lags behind the points streamed to it
+    pub subsystem_lag: Vec<SubsystemLag>,
+}
+
+/// Points sent to a device that it has not caught up with yet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SubsystemLag {
+    /// Device type (e.g., "fanuc_rmi", "duet_extruder")
+    pub name: String,
+
+    /// Points sent or scheduled but not yet executed
+    pub points: u32,
+}
+
+impl ExecutionProgress {
+    /// Fill in distances and ETA from the program lines.
+    ///
+    /// The first `points_completed` lines count as traveled. Each line's
+    /// length is measured from the previous line.
+    pub fn measure(&mut self, lines: &[BufferLineDisplay]) {
+        let completed = (self.points_completed as usize).min(lines.len());
+        let mut traveled = 0.0;
+        let mut remaining = 0.0;
+        let mut remaining_secs = 0.0;
+
+        for (i, pair) in lines.windows(2).enumerate() {
+            let (from, to) = (&pair[0], &pair[1]);
+            let length =
+                ((to.x - from.x).powi(2) + (to.y - from.y).powi(2) + (to.z - from.z).powi(2)).sqrt();
+            if i + 1 < completed {
+                traveled += length;
+            } else {
+                let speed = if to.speed > 0.0 { to.speed } else { FALLBACK_SPEED };
+                remaining += length;
+                remaining_secs += length / speed;
+            }
+        }
+
+        self.distance_traveled = traveled;
+        self.distance_remaining = remaining;
+        self.eta_seconds = if lines.is_empty() {
+            None
+        } else {
+            Some(remaining_secs)
+        };
+    }
+
+    /// Fraction of points complete (0.0 - 1.0), if the total is known.
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total_points {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.points_completed as f32 / total as f32).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(x: f64, speed: f64) -> BufferLineDisplay {
+        BufferLineDisplay {
+            x,
+            speed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_measure_splits_traveled_and_remaining() {
+        let lines = [line(0.0, 10.0), line(10.0, 10.0), line(30.0, 10.0), line(60.0, 30.0)];
+        let mut progress = ExecutionProgress {
+            points_completed: 2,
+            ..Default::default()
+        };
+
+        progress.measure(&lines);
+
+        assert_eq!(progress.distance_traveled, 10.0);
+        assert_eq!(progress.distance_remaining, 50.0);
+        // 20mm at 10mm/s + 30mm at 30mm/s
+        assert_eq!(progress.eta_seconds, Some(3.0));
+    }
+
+    #[test]
+    fn test_measure_without_lines_has_no_eta() {
+        let mut progress = ExecutionProgress::default();
+        progress.measure(&[]);
+        assert_eq!(progress.eta_seconds, None);
+        assert_eq!(progress.fraction(), None);
+    }
+}
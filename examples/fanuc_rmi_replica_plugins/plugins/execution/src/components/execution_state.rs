@@ -3,6 +3,7 @@
 //! This is the primary state component for the UI to display execution status.
 //! It's buffer-centric: execution is about the buffer, not the program.
 
+use pl3xus_common::EntityActions;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ecs")]
@@ -13,13 +14,14 @@ use reactive_stores::Store;
 
 /// Execution state synced to all clients.
 ///
-/// Contains current state, source info, progress, and available actions.
+/// Contains current state, source info and progress. The available actions
+/// are derived from `state` (see [`SystemState::actions`]) and synced in the
+/// coordinator's `EntityActions`.
 ///
 /// The UI uses this to:
 /// - Display current state (Running, Paused, etc.)
 /// - Know what type of source is active (Program, Stream, Generator)
 /// - Highlight the current row in the buffer table (current_index)
-/// - Show/hide action buttons (can_* fields)
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "ecs", derive(Component))]
 #[cfg_attr(feature = "stores", derive(Store))]
@@ -47,20 +49,9 @@ pub struct ExecutionState {
     /// Points confirmed executed by the device
     pub points_executed: usize,
 
-    // === Available Actions (server-driven) ===
-    
-    /// Can load a new source (program, stream, etc.)
-    pub can_load: bool,
-    /// Can start execution
-    pub can_start: bool,
-    /// Can pause execution
-    pub can_pause: bool,
-    /// Can resume execution
-    pub can_resume: bool,
-    /// Can stop execution (or cancel validation)
-    pub can_stop: bool,
-    /// Can unload the current source
-    pub can_unload: bool,
+    /// True if the current (or last) run is a dry run on the built-in simulator
+    #[serde(default)]
+    pub simulated: bool,
 }
 
 /// What type of source is feeding the execution buffer.
@@ -100,88 +91,69 @@ pub enum SystemState {
     Error,
 }
 
+impl SystemState {
+    /// Commands the UI may offer in this state, keyed by the names read with
+    /// `use_action`: "load", "start", "pause", "resume", "step",
+    /// "resume_from_line", "stop" and "unload".
+    pub fn actions(self) -> EntityActions {
+        use SystemState::*;
+        let reason = match self {
+            NoSource => "No program is loaded",
+            Ready => "Execution hasn't started",
+            Validating => "Devices are being checked",
+            Running | AwaitingPoints => "Execution is running",
+            Paused => "Execution is paused",
+            Completed | Stopped | Error => "Execution has finished",
+        };
+        let finished = matches!(self, Completed | Stopped | Error);
+        let running = matches!(self, Running | AwaitingPoints);
+
+        EntityActions::new()
+            .when("load", self == NoSource, "Unload the current program first")
+            .when("start", self == Ready || finished, reason)
+            .when("pause", running, reason)
+            .when("resume", self == Paused, reason)
+            .when("step", self == Paused, reason)
+            .when("resume_from_line", self == Paused || finished, reason)
+            // Stopping while validating cancels the validation
+            .when("stop", running || matches!(self, Validating | Paused), reason)
+            .when("unload", self == Ready || finished, reason)
+    }
+}
+
 impl ExecutionState {
     /// Create state for "no source loaded"
     pub fn no_source() -> Self {
-        let mut s = Self {
-            state: SystemState::NoSource,
-            source_type: SourceType::None,
-            source_name: None,
-            source_id: None,
-            current_index: 0,
-            total_points: None,
-            points_executed: 0,
-            can_load: true,
-            can_start: false,
-            can_pause: false,
-            can_resume: false,
-            can_stop: false,
-            can_unload: false,
-        };
-        s.update_available_actions();
-        s
+        Self::default()
     }
+}
 
-    /// Update available actions based on current state.
-    pub fn update_available_actions(&mut self) {
-        match self.state {
-            SystemState::NoSource => {
-                self.can_load = true;
-                self.can_start = false;
-                self.can_pause = false;
-                self.can_resume = false;
-                self.can_stop = false;
-                self.can_unload = false;
-            }
-            SystemState::Ready => {
-                self.can_load = false;
-                self.can_start = true;
-                self.can_pause = false;
-                self.can_resume = false;
-                self.can_stop = false;
-                self.can_unload = true;
-            }
-            SystemState::Validating => {
-                self.can_load = false;
-                self.can_start = false;
-                self.can_pause = false;
-                self.can_resume = false;
-                self.can_stop = true; // Can cancel validation
-                self.can_unload = false;
-            }
-            SystemState::Running | SystemState::AwaitingPoints => {
-                self.can_load = false;
-                self.can_start = false;
-                self.can_pause = true;
-                self.can_resume = false;
-                self.can_stop = true;
-                self.can_unload = false;
-            }
-            SystemState::Paused => {
-                self.can_load = false;
-                self.can_start = false;
-                self.can_pause = false;
-                self.can_resume = true;
-                self.can_stop = true;
-                self.can_unload = false;
-            }
-            SystemState::Completed | SystemState::Stopped => {
-                self.can_load = true;
-                self.can_start = true; // Can restart
-                self.can_pause = false;
-                self.can_resume = false;
-                self.can_stop = false;
-                self.can_unload = true;
-            }
-            SystemState::Error => {
-                self.can_load = true;
-                self.can_start = false;
-                self.can_pause = false;
-                self.can_resume = false;
-                self.can_stop = false;
-                self.can_unload = true;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_actions_follow_state() {
+        let idle = SystemState::NoSource.actions();
+        assert!(idle.is_enabled("load"));
+        assert_eq!(idle.reason("start"), Some("No program is loaded"));
+
+        let running = SystemState::Running.actions();
+        assert!(running.is_enabled("pause") && running.is_enabled("stop"));
+        assert!(!running.is_enabled("unload"));
+        assert_eq!(running.reason("resume"), Some("Execution is running"));
+
+        let paused = SystemState::Paused.actions();
+        for action in ["resume", "step", "resume_from_line", "stop"] {
+            assert!(paused.is_enabled(action), "{}", action);
         }
+
+        let finished = SystemState::Error.actions();
+        assert!(finished.is_enabled("start") && finished.is_enabled("unload"));
+        assert_eq!(finished.reason("load"), Some("Unload the current program first"));
+
+        // Every state declares the same set of actions
+        assert_eq!(idle.actions.len(), 8);
+        assert_eq!(SystemState::Validating.actions().actions.len(), 8);
     }
 }
-
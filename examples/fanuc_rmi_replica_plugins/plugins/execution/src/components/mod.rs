@@ -3,14 +3,22 @@
 mod buffer;
 mod buffer_display;
 mod coordinator;
+mod digital_output;
 mod execution_point;
+mod execution_progress;
 mod execution_state;
 mod subsystems;
 
-pub use buffer::{BufferState, ToolpathBuffer, UiActions, VALIDATION_TIMEOUT};
+pub use buffer::{BufferState, ToolpathBuffer, VALIDATION_TIMEOUT};
 pub use buffer_display::{BufferDisplayData, BufferLineDisplay};
-pub use coordinator::{ExecutionCoordinator, ExecutionTarget, PrimaryMotion};
-pub use execution_point::{ExecutionPoint, MotionCommand, MotionType, PointMetadata};
+pub use coordinator::{
+    ExecutionCoordinator, ExecutionTarget, PrimaryMotion, SimulationMode, StepMode,
+};
+pub use digital_output::{
+    DigitalOutputBackend, DigitalOutputChannel, DigitalOutputDevice, DigitalOutputWrite,
+};
+pub use execution_point::{AuxTiming, ExecutionPoint, MotionCommand, MotionType, PointMetadata};
+pub use execution_progress::{ExecutionProgress, SubsystemLag};
 pub use execution_state::{ExecutionState, SourceType, SystemState};
 pub use subsystems::{
     SubsystemEntry, SubsystemReadiness, Subsystems, SUBSYSTEM_DUET, SUBSYSTEM_EXECUTION,
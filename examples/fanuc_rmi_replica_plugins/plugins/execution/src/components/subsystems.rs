@@ -5,9 +5,9 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "ecs")]
 use bevy::prelude::*;
 
-/// Component on System entity tracking all registered subsystems.
+/// Component on each coordinator entity tracking all registered subsystems.
 ///
-/// Subsystems (e.g., fanuc robot, duet extruder) register themselves during plugin setup.
+/// Subsystems (e.g., fanuc robot, duet extruder) register themselves when the component is added.
 /// During the Validating phase, each subsystem updates its readiness status.
 /// Execution proceeds only when all subsystems report Ready.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -4,46 +4,75 @@
 //! - Start: Ready/Completed/Stopped → Validating → Executing
 //! - Pause: Running → Paused
 //! - Resume: Paused → ValidatingForResume → Executing (re-validates before resuming)
+//! - StepOnce: Paused → ValidatingForResume → Executing (one point) → Paused
+//! - ResumeFromLine: Paused/Completed/Stopped/Error → ValidatingForResume → Executing
 //! - Stop: Running/Paused/Validating → Stopped
+//!
+//! Every request targets a coordinator entity and only affects that coordinator
+//! and the devices among its children.
 
 use bevy::ecs::message::MessageReader;
 use bevy::prelude::*;
-use fanuc_replica_core::{console_entry, ActiveSystem, ConsoleDirection, ConsoleMsgType};
-use pl3xus::Network;
+use fanuc_replica_core::{console_entry, ConsoleDirection, ConsoleLogEntry, ConsoleMsgType};
 use pl3xus_sync::AuthorizedRequest;
-use pl3xus_websockets::WebSocketProvider;
 
 use crate::components::{
-    BufferState, ExecutionCoordinator, ExecutionState, Subsystems, SystemState, ToolpathBuffer,
+    BufferState, ExecutionCoordinator, ExecutionState, PrimaryMotion, SimulationMode, StepMode,
+    Subsystems, SystemState, ToolpathBuffer,
+};
+use crate::systems::{
+    despawn_simulated_devices, find_primary_device, spawn_simulated_device, AuxSchedule,
+    DeviceStatus, SimulatedMotion, ValidationStartTime,
+};
+use crate::types::{
+    Pause, PauseResponse, Resume, ResumeFromLine, ResumeFromLineResponse, ResumeResponse, Start,
+    StartResponse, StepOnce, StepOnceResponse, Stop, StopResponse,
 };
-use crate::systems::{DeviceStatus, ValidationStartTime};
-use crate::types::{Pause, PauseResponse, Resume, ResumeResponse, Start, StartResponse, Stop, StopResponse};
+
+/// Height above the restart line from which `ResumeFromLine` re-approaches (mm).
+const REAPPROACH_CLEARANCE_MM: f64 = 25.0;
+
+/// Maximum speed of the re-approach move (mm/s).
+const REAPPROACH_SPEED: f32 = 50.0;
 
 /// Handle Start request - begins execution.
 ///
 /// Transitions: Ready/Completed/Stopped → Validating
 /// The validation system will then check subsystems and transition to Executing.
+///
+/// For `Start { simulate: true }`, a simulated motion device is attached to the
+/// coordinator and the run is marked with `SimulationMode`.
 pub fn handle_start(
     mut commands: Commands,
     mut requests: MessageReader<AuthorizedRequest<Start>>,
     mut systems: Query<
         (
+            Entity,
+            Option<&Children>,
             &ExecutionCoordinator,
             &mut BufferState,
             &mut ToolpathBuffer,
             &mut Subsystems,
             Option<&mut ExecutionState>,
         ),
-        With<ActiveSystem>,
     >,
     mut devices: Query<&mut DeviceStatus>,
+    simulators: Query<(), With<SimulatedMotion>>,
 ) {
     for request in requests.read() {
         let request = request.clone();
-        info!("📋 Handling Start request");
-
-        let Ok((coordinator, mut buffer_state, mut toolpath_buffer, mut subsystems, exec_state)) =
-            systems.single_mut()
+        let simulate = request.get_request().simulate;
+        info!("📋 Handling Start request (simulate: {})", simulate);
+
+        let Ok((
+            coordinator_entity,
+            children,
+            coordinator,
+            mut buffer_state,
+            mut toolpath_buffer,
+            mut subsystems,
+            exec_state,
+        )) = systems.get_mut(request.target_entity)
         else {
             let response = StartResponse {
                 success: false,
@@ -73,24 +102,42 @@ pub fn handle_start(
             toolpath_buffer.reset_for_rerun();
         }
 
-        // Reset device status for new execution
-        for mut device_status in devices.iter_mut() {
-            device_status.completed_count = 0;
-            device_status.reset_in_flight();
+        // Reset status of this coordinator's devices for new execution
+        let device_entities: Vec<Entity> = children.into_iter().flatten().copied().collect();
+        let mut capacity = 1;
+        for &device in &device_entities {
+            if let Ok(mut device_status) = devices.get_mut(device) {
+                device_status.completed_count = 0;
+                device_status.reset_in_flight();
+                capacity = capacity.max(device_status.in_flight_capacity);
+            }
+        }
+
+        // Attach a fresh simulator for dry runs (replacing one left from a previous run)
+        despawn_simulated_devices(&mut commands, children, &simulators);
+        if simulate {
+            spawn_simulated_device(&mut commands, coordinator_entity, capacity);
+            commands.entity(coordinator_entity).insert(SimulationMode);
+            info!("🧪 Dry run for '{}' on the built-in simulator", coordinator.name);
+        } else {
+            commands.entity(coordinator_entity).remove::<SimulationMode>();
         }
 
+        // A new run is never a single step
+        commands.entity(coordinator_entity).remove::<StepMode>();
+
         // Reset subsystems for validation
         subsystems.reset_all();
 
         // Transition to Validating and start timeout timer
         *buffer_state = BufferState::Validating;
-        commands.insert_resource(ValidationStartTime::default());
+        commands.entity(request.target_entity).insert(ValidationStartTime::default());
         info!("📦 Set BufferState to Validating for '{}'", coordinator.name);
 
         // Update ExecutionState if present
         if let Some(mut exec) = exec_state {
             exec.state = SystemState::Validating;
-            exec.update_available_actions();
+            exec.simulated = simulate;
         }
 
         let response = StartResponse {
@@ -110,15 +157,14 @@ pub fn handle_pause(
     mut requests: MessageReader<AuthorizedRequest<Pause>>,
     mut systems: Query<
         (&ExecutionCoordinator, &mut BufferState, Option<&mut ExecutionState>),
-        With<ActiveSystem>,
     >,
-    net: Res<Network<WebSocketProvider>>,
+    mut console: MessageWriter<ConsoleLogEntry>,
 ) {
     for request in requests.read() {
         let request = request.clone();
         info!("📋 Handling Pause request");
 
-        let Ok((coordinator, mut buffer_state, exec_state)) = systems.single_mut() else {
+        let Ok((coordinator, mut buffer_state, exec_state)) = systems.get_mut(request.target_entity) else {
             let response = PauseResponse {
                 success: false,
                 error: Some("No source loaded".into()),
@@ -149,16 +195,17 @@ pub fn handle_pause(
         // Update ExecutionState if present
         if let Some(mut exec) = exec_state {
             exec.state = SystemState::Paused;
-            exec.update_available_actions();
         }
 
-        // Broadcast console entry
+        // Log console entry (broadcast and persisted by the core plugin)
         let console_msg = console_entry(
             format!("Execution paused at point {}", current_idx),
             ConsoleDirection::System,
             ConsoleMsgType::Status,
-        );
-        net.broadcast(console_msg);
+        )
+        .with_source("execution")
+            .with_field("point", current_idx);
+        console.write(console_msg);
 
         let response = PauseResponse {
             success: true,
@@ -183,21 +230,21 @@ pub fn handle_resume(
     mut requests: MessageReader<AuthorizedRequest<Resume>>,
     mut systems: Query<
         (
+            Entity,
             &ExecutionCoordinator,
             &mut BufferState,
             &mut Subsystems,
             Option<&mut ExecutionState>,
         ),
-        With<ActiveSystem>,
     >,
-    net: Res<Network<WebSocketProvider>>,
+    mut console: MessageWriter<ConsoleLogEntry>,
 ) {
     for request in requests.read() {
         let request = request.clone();
         info!("📋 Handling Resume request");
 
-        let Ok((coordinator, mut buffer_state, mut subsystems, exec_state)) =
-            systems.single_mut()
+        let Ok((coordinator_entity, coordinator, mut buffer_state, mut subsystems, exec_state)) =
+            systems.get_mut(request.target_entity)
         else {
             let response = ResumeResponse {
                 success: false,
@@ -220,6 +267,9 @@ pub fn handle_resume(
             }
         };
 
+        // Resume runs continuously, even if paused by a step
+        commands.entity(coordinator_entity).remove::<StepMode>();
+
         // Reset subsystems for re-validation before resume
         subsystems.reset_all();
 
@@ -227,7 +277,7 @@ pub fn handle_resume(
         *buffer_state = BufferState::ValidatingForResume {
             resume_from_index: paused_at,
         };
-        commands.insert_resource(ValidationStartTime::default());
+        commands.entity(request.target_entity).insert(ValidationStartTime::default());
         info!(
             "🔄 Resume requested for '{}' - validating before resuming from index {}",
             coordinator.name, paused_at
@@ -236,16 +286,17 @@ pub fn handle_resume(
         // Update ExecutionState if present - show Validating state
         if let Some(mut exec) = exec_state {
             exec.state = SystemState::Validating;
-            exec.update_available_actions();
         }
 
-        // Broadcast console entry
+        // Log console entry (broadcast and persisted by the core plugin)
         let console_msg = console_entry(
             format!("Resuming execution from point {}", paused_at),
             ConsoleDirection::System,
             ConsoleMsgType::Status,
-        );
-        net.broadcast(console_msg);
+        )
+        .with_source("execution")
+            .with_field("point", paused_at);
+        console.write(console_msg);
 
         let response = ResumeResponse {
             success: true,
@@ -257,6 +308,244 @@ pub fn handle_resume(
     }
 }
 
+/// Handle StepOnce request - executes a single point, then pauses again.
+///
+/// Transitions: Paused → ValidatingForResume → Executing → (after one point) → Paused
+///
+/// Like Resume, the step re-validates subsystems first. The orchestrator
+/// dispatches one point and returns to Paused once it is confirmed complete.
+pub fn handle_step_once(
+    mut commands: Commands,
+    mut requests: MessageReader<AuthorizedRequest<StepOnce>>,
+    mut systems: Query<
+        (
+            Entity,
+            &ExecutionCoordinator,
+            &mut BufferState,
+            &ToolpathBuffer,
+            &mut Subsystems,
+            Option<&mut ExecutionState>,
+        ),
+    >,
+    mut console: MessageWriter<ConsoleLogEntry>,
+) {
+    for request in requests.read() {
+        let request = request.clone();
+        info!("📋 Handling StepOnce request");
+
+        let Ok((coordinator_entity, coordinator, mut buffer_state, toolpath_buffer, mut subsystems, exec_state)) =
+            systems.get_mut(request.target_entity)
+        else {
+            let response = StepOnceResponse {
+                success: false,
+                error: Some("No source loaded".into()),
+            };
+            let _ = request.respond(response);
+            continue;
+        };
+
+        // Check if paused
+        let paused_at = match *buffer_state {
+            BufferState::Paused { paused_at_index } => paused_at_index,
+            _ => {
+                let response = StepOnceResponse {
+                    success: false,
+                    error: Some("Cannot step: not paused".into()),
+                };
+                let _ = request.respond(response);
+                continue;
+            }
+        };
+
+        let Some(next_index) = toolpath_buffer.peek().map(|point| point.index) else {
+            let response = StepOnceResponse {
+                success: false,
+                error: Some("Cannot step: no buffered points left".into()),
+            };
+            let _ = request.respond(response);
+            continue;
+        };
+
+        commands.entity(coordinator_entity).insert(StepMode::once());
+
+        // Reset subsystems for re-validation before stepping
+        subsystems.reset_all();
+
+        *buffer_state = BufferState::ValidatingForResume {
+            resume_from_index: paused_at,
+        };
+        commands.entity(request.target_entity).insert(ValidationStartTime::default());
+        info!(
+            "⏯ Step requested for '{}' - validating before executing point {}",
+            coordinator.name, next_index
+        );
+
+        // Update ExecutionState if present - show Validating state
+        if let Some(mut exec) = exec_state {
+            exec.state = SystemState::Validating;
+        }
+
+        // Log console entry (broadcast and persisted by the core plugin)
+        let console_msg = console_entry(
+            format!("Stepping to point {}", next_index),
+            ConsoleDirection::System,
+            ConsoleMsgType::Status,
+        )
+        .with_source("execution")
+            .with_field("point", next_index);
+        console.write(console_msg);
+
+        let response = StepOnceResponse {
+            success: true,
+            error: None,
+        };
+        if let Err(e) = request.respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Handle ResumeFromLine request - restarts execution at an arbitrary line.
+///
+/// Transitions: Paused/Completed/Stopped/Error → ValidatingForResume → Executing
+///
+/// Only static programs can restart from a line, since the buffer needs the
+/// original points. Lines before the requested one count as executed. The
+/// buffer is rebuilt from that line with a re-approach move in front: the
+/// robot moves to a point above the line at reduced speed, then descends to
+/// it without blending before the program continues.
+pub fn handle_resume_from_line(
+    mut commands: Commands,
+    mut requests: MessageReader<AuthorizedRequest<ResumeFromLine>>,
+    mut systems: Query<
+        (
+            Entity,
+            &ExecutionCoordinator,
+            &mut BufferState,
+            &mut ToolpathBuffer,
+            &mut Subsystems,
+            Option<&mut ExecutionState>,
+            Option<&Children>,
+        ),
+    >,
+    mut devices: Query<&mut DeviceStatus>,
+    mut console: MessageWriter<ConsoleLogEntry>,
+) {
+    for request in requests.read() {
+        let request = request.clone();
+        let line = request.get_request().line;
+        info!("📋 Handling ResumeFromLine request (line {})", line);
+
+        let Ok((
+            coordinator_entity,
+            coordinator,
+            mut buffer_state,
+            mut toolpath_buffer,
+            mut subsystems,
+            exec_state,
+            children,
+        )) =
+            systems.get_mut(request.target_entity)
+        else {
+            let response = ResumeFromLineResponse {
+                success: false,
+                error: Some("No source loaded".into()),
+            };
+            let _ = request.respond(response);
+            continue;
+        };
+
+        let can_resume_from_line = matches!(
+            *buffer_state,
+            BufferState::Paused { .. }
+                | BufferState::Complete { .. }
+                | BufferState::Stopped { .. }
+                | BufferState::Error { .. }
+        );
+        if !can_resume_from_line {
+            let response = ResumeFromLineResponse {
+                success: false,
+                error: Some(format!("Cannot resume from line in state: {:?}", *buffer_state)),
+            };
+            let _ = request.respond(response);
+            continue;
+        }
+
+        if !toolpath_buffer.can_rerun() {
+            let response = ResumeFromLineResponse {
+                success: false,
+                error: Some("Resume from line requires a static program".into()),
+            };
+            let _ = request.respond(response);
+            continue;
+        }
+
+        if !toolpath_buffer.rewind_to(line) {
+            let response = ResumeFromLineResponse {
+                success: false,
+                error: Some(format!("Line {} is not in the program", line)),
+            };
+            let _ = request.respond(response);
+            continue;
+        }
+
+        // Re-approach: come down onto the restart line and stop there exactly
+        if let Some(first) = toolpath_buffer.front_mut() {
+            first.motion.blend_radius = 0.0;
+            let approach = first.approach_from_above(REAPPROACH_CLEARANCE_MM, REAPPROACH_SPEED);
+            toolpath_buffer.insert_front(approach);
+        }
+
+        // Lines before the restart line count as executed
+        for device in children.into_iter().flatten() {
+            if let Ok(mut device_status) = devices.get_mut(*device) {
+                device_status.completed_count = line;
+                device_status.reset_in_flight();
+            }
+        }
+
+        // Timed auxiliary commands of the abandoned run no longer apply
+        commands
+            .entity(coordinator_entity)
+            .remove::<(StepMode, AuxSchedule)>();
+
+        // Reset subsystems for re-validation before restarting
+        subsystems.reset_all();
+
+        *buffer_state = BufferState::ValidatingForResume {
+            resume_from_index: line,
+        };
+        commands.entity(request.target_entity).insert(ValidationStartTime::default());
+        info!(
+            "🔄 Resume from line {} requested for '{}' - validating before re-approach",
+            line, coordinator.name
+        );
+
+        // Update ExecutionState if present - show Validating state
+        if let Some(mut exec) = exec_state {
+            exec.state = SystemState::Validating;
+        }
+
+        // Log console entry (broadcast and persisted by the core plugin)
+        let console_msg = console_entry(
+            format!("Resuming execution from line {}", line),
+            ConsoleDirection::System,
+            ConsoleMsgType::Status,
+        )
+        .with_source("execution")
+            .with_field("line", line);
+        console.write(console_msg);
+
+        let response = ResumeFromLineResponse {
+            success: true,
+            error: None,
+        };
+        if let Err(e) = request.respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
 /// Handle Stop request - stops execution.
 ///
 /// Transitions: Running/Paused/Validating/ValidatingForResume → Stopped
@@ -267,17 +556,20 @@ pub fn handle_stop(
             &ExecutionCoordinator,
             &mut BufferState,
             Option<&mut ExecutionState>,
+            Option<&Children>,
+            Has<SimulationMode>,
         ),
-        With<ActiveSystem>,
     >,
-    devices: Query<&DeviceStatus>,
-    net: Res<Network<WebSocketProvider>>,
+    devices: Query<(&DeviceStatus, Has<SimulatedMotion>), With<PrimaryMotion>>,
+    mut console: MessageWriter<ConsoleLogEntry>,
 ) {
     for request in requests.read() {
         let request = request.clone();
         info!("📋 Handling Stop request");
 
-        let Ok((coordinator, mut buffer_state, exec_state)) = systems.single_mut() else {
+        let Ok((coordinator, mut buffer_state, exec_state, children, simulating)) =
+            systems.get_mut(request.target_entity)
+        else {
             let response = StopResponse {
                 success: false,
                 error: Some("No source loaded".into()),
@@ -293,7 +585,14 @@ pub fn handle_stop(
                 completed_count,
             } => (current_index, completed_count),
             BufferState::Paused { paused_at_index } => {
-                let completed = devices.single().map(|d| d.completed_count).unwrap_or(0);
+                let completed = find_primary_device(
+                    children,
+                    |child| devices.get(child).ok().map(|(_, is_simulator)| is_simulator),
+                    simulating,
+                )
+                .and_then(|device| devices.get(device).ok())
+                .map(|(status, _)| status.completed_count)
+                .unwrap_or(0);
                 (paused_at_index, completed)
             }
             BufferState::Validating => (0, 0),
@@ -325,16 +624,18 @@ pub fn handle_stop(
         if let Some(mut exec) = exec_state {
             exec.state = SystemState::Stopped;
             exec.points_executed = completed_before_stop as usize;
-            exec.update_available_actions();
         }
 
-        // Broadcast console entry
+        // Log console entry (broadcast and persisted by the core plugin)
         let console_msg = console_entry(
             format!("Execution stopped at point {} ({} completed)", stopped_at_index, completed_before_stop),
             ConsoleDirection::System,
             ConsoleMsgType::Status,
-        );
-        net.broadcast(console_msg);
+        )
+        .with_source("execution")
+            .with_field("point", stopped_at_index)
+            .with_field("completed", completed_before_stop);
+        console.write(console_msg);
 
         let response = StopResponse {
             success: true,
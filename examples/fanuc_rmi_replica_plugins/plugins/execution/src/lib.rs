@@ -22,6 +22,9 @@
 //! - `MotionDevice`: Implemented by robot drivers (FANUC, ABB, etc.)
 //! - `AuxiliaryDevice`: Implemented by peripherals (extruders, grippers, etc.)
 //!
+//! Simple on/off peripherals (grippers, valves) don't need their own plugin:
+//! `DigitalOutputDevice` maps their commands to FANUC DOUT or Duet GPIO writes.
+//!
 //! # Device-Specific Handlers
 //!
 //! Device-specific handlers are in their respective plugin crates:
@@ -45,23 +48,32 @@ pub mod types;
 
 // Always available exports
 pub use components::{
-    BufferDisplayData, BufferLineDisplay, BufferState, ExecutionCoordinator, ExecutionPoint,
-    ExecutionState, ExecutionTarget, MotionCommand, MotionType, PointMetadata, PrimaryMotion,
-    SourceType, SubsystemEntry, SubsystemReadiness, Subsystems, SystemState, ToolpathBuffer,
-    UiActions, SUBSYSTEM_DUET, SUBSYSTEM_EXECUTION, SUBSYSTEM_FANUC, SUBSYSTEM_PROGRAMS,
-    VALIDATION_TIMEOUT,
+    AuxTiming, BufferDisplayData, BufferLineDisplay, BufferState, DigitalOutputBackend,
+    DigitalOutputChannel, DigitalOutputDevice, DigitalOutputWrite, ExecutionCoordinator,
+    ExecutionPoint, ExecutionProgress, ExecutionState, ExecutionTarget, MotionCommand, MotionType,
+    PointMetadata, PrimaryMotion, SimulationMode, SourceType, StepMode, SubsystemEntry,
+    SubsystemLag, SubsystemReadiness, Subsystems, SystemState, ToolpathBuffer,
+    SUBSYSTEM_DUET, SUBSYSTEM_EXECUTION, SUBSYSTEM_FANUC, SUBSYSTEM_PROGRAMS, VALIDATION_TIMEOUT,
+};
+pub use traits::{AuxiliaryCommand, AuxiliaryDevice, DeviceError, MotionDevice, MotionProgress};
+pub use types::{
+    Pause, PauseResponse, Resume, ResumeFromLine, ResumeFromLineResponse, ResumeResponse, Start,
+    StartResponse, StepOnce, StepOnceResponse, Stop, StopResponse,
 };
-pub use traits::{AuxiliaryCommand, AuxiliaryDevice, DeviceError, MotionDevice};
-pub use types::{Pause, PauseResponse, Resume, ResumeResponse, Start, StartResponse, Stop, StopResponse};
 
 cfg_if! {
     if #[cfg(feature = "server")] {
         pub mod handlers;
         pub mod systems;
 
-        pub use handlers::{handle_pause, handle_resume, handle_start, handle_stop};
+        pub use handlers::{
+            handle_pause, handle_resume, handle_resume_from_line, handle_start, handle_step_once,
+            handle_stop,
+        };
         pub use systems::{
-            AuxiliaryCommandEvent, DeviceConnected, DeviceStatus, DeviceType, MotionCommandEvent,
+            spawn_digital_output_device, AuxSchedule, AuxiliaryCommandEvent, DeviceConnected,
+            DeviceStatus, DeviceType, DigitalOutputWriteEvent, MotionCommandEvent,
+            ScheduledAuxCommand, SimulatedMotion,
         };
     }
 }
@@ -15,27 +15,37 @@ use bevy::prelude::*;
 pub struct SubsystemValidation;
 
 #[cfg(feature = "server")]
-use pl3xus_sync::{AppBatchRequestRegistrationExt, AppPl3xusSyncExt, ComponentSyncConfig};
+use pl3xus_sync::{AppActionsExt, AppBatchRequestRegistrationExt, AppPl3xusSyncExt, ComponentSyncConfig};
 #[cfg(feature = "server")]
 use pl3xus_websockets::WebSocketProvider;
 
 #[cfg(feature = "server")]
-use crate::components::{BufferDisplayData, ExecutionState, Subsystems};
+use crate::components::{
+    BufferDisplayData, BufferState, ExecutionCoordinator, ExecutionProgress, ExecutionState,
+    Subsystems,
+};
 
 #[cfg(feature = "server")]
 use fanuc_replica_core::ActiveSystem;
 
 #[cfg(feature = "server")]
-use crate::handlers::{handle_pause, handle_resume, handle_start, handle_stop};
+use crate::handlers::{
+    handle_pause, handle_resume, handle_resume_from_line, handle_start, handle_step_once,
+    handle_stop,
+};
 
 #[cfg(feature = "server")]
-use crate::types::{Pause, Resume, Start, Stop};
+use crate::types::{Pause, Resume, ResumeFromLine, Start, StepOnce, Stop};
 
 #[cfg(feature = "server")]
 use crate::systems::{
-    coordinate_validation, orchestrator_system, reset_on_disconnect_system,
-    sync_buffer_state_to_execution_state, sync_device_status_to_buffer_state,
-    update_buffer_state_system, AuxiliaryCommandEvent, MotionCommandEvent,
+    advance_simulation_system, cleanup_simulation_system, coordinate_validation,
+    digital_output_handler_system, dispatch_scheduled_aux_system, emit_program_completed_webhooks,
+    orchestrator_system, reset_on_disconnect_system, simulated_motion_handler_system,
+    sync_buffer_occupancy_to_display, sync_buffer_state_to_execution_state,
+    sync_device_status_to_buffer_state, sync_execution_progress,
+    update_buffer_state_system, AuxiliaryCommandEvent, DigitalOutputWriteEvent,
+    MotionCommandEvent,
 };
 
 /// Plugin for the execution system.
@@ -79,19 +89,29 @@ impl Plugin for ExecutionPlugin {
                 "ExecutionState is read-only. Use Start, Pause, Resume, Stop commands."
             )));
 
+            // EntityActions - which commands the UI may offer, read with use_action
+            app.derive_actions::<ExecutionState>(|exec| exec.state.actions());
+
             // BufferDisplayData - synced to all clients for buffer table display
             app.sync_component::<BufferDisplayData>(Some(ComponentSyncConfig::read_only_with_message(
-                "BufferDisplayData is read-only. Updated by Load/Unload commands."
+                "BufferDisplayData is read-only. Updated by Load/Unload commands and during execution."
+            )));
+
+            // ExecutionProgress - synced to all clients for progress/ETA display
+            app.sync_component::<ExecutionProgress>(Some(ComponentSyncConfig::read_only_with_message(
+                "ExecutionProgress is read-only. It is computed by the server during execution."
             )));
 
             // =====================================================================
             // TARGETED REQUESTS (require entity control)
             // =====================================================================
-            // Execution control commands - these target the ActiveSystem entity
+            // Execution control commands - these target a coordinator entity
             app.requests::<(
                 Start,
                 Pause,
                 Resume,
+                StepOnce,
+                ResumeFromLine,
                 Stop,
             ), WebSocketProvider>()
                 .targeted()
@@ -103,6 +123,7 @@ impl Plugin for ExecutionPlugin {
             // =====================================================================
             app.add_message::<MotionCommandEvent>();
             app.add_message::<AuxiliaryCommandEvent>();
+            app.add_message::<DigitalOutputWriteEvent>();
 
             // =====================================================================
             // SYSTEMS
@@ -112,6 +133,8 @@ impl Plugin for ExecutionPlugin {
                 handle_start,
                 handle_pause,
                 handle_resume,
+                handle_step_once,
+                handle_resume_from_line,
                 handle_stop,
             ));
 
@@ -131,25 +154,47 @@ impl Plugin for ExecutionPlugin {
             // Order:
             // 1. update_buffer_state_system - Handle internal state transitions
             // 2. orchestrator_system - Dispatch commands to devices
-            // 3. reset_on_disconnect_system - Clean up when devices disconnect
-            // 4. sync_device_status_to_buffer_state - Sync device status back to buffer
-            // 5. sync_buffer_state_to_execution_state - Sync buffer state to synced ExecutionState
+            // 3. simulated_motion_handler_system / advance_simulation_system - Dry-run simulator
+            // 4. dispatch_scheduled_aux_system - Send timed auxiliary commands
+            // 5. digital_output_handler_system - Resolve digital output device commands
+            // 6. reset_on_disconnect_system - Clean up when devices disconnect
+            // 7. sync_device_status_to_buffer_state - Sync device status back to buffer
+            // 8. sync_buffer_state_to_execution_state - Sync buffer state to synced ExecutionState
+            // 9. sync_buffer_occupancy_to_display - Sync lookahead occupancy to BufferDisplayData
+            // 10. sync_execution_progress - Sync distance/ETA/lag telemetry to ExecutionProgress
+            // 11. cleanup_simulation_system - Remove the simulator after a dry run
             app.add_systems(
                 Update,
                 (
                     update_buffer_state_system,
                     orchestrator_system,
+                    simulated_motion_handler_system,
+                    advance_simulation_system,
+                    dispatch_scheduled_aux_system,
+                    digital_output_handler_system,
                     reset_on_disconnect_system,
                     sync_device_status_to_buffer_state,
                     sync_buffer_state_to_execution_state,
+                    sync_buffer_occupancy_to_display,
+                    sync_execution_progress,
+                    cleanup_simulation_system,
                 )
                     .chain(),
             );
 
+            // Completed (non-simulated) runs are reported to webhooks
+            app.add_systems(
+                Update,
+                emit_program_completed_webhooks.after(sync_buffer_state_to_execution_state),
+            );
+
             // Add ExecutionState and Subsystems to System entity
             // Run in First to ensure it runs before Update systems, but check for entity existence
             app.add_systems(First, add_execution_components_to_system);
 
+            // Coordinators spawned on other entities get the same components
+            app.add_systems(First, add_execution_components_to_coordinators);
+
             info!("Execution plugin loaded");
         }
     }
@@ -166,6 +211,7 @@ struct ExecutionComponentsInitialized(bool);
 /// It uses a resource to track whether it's already done.
 /// - ExecutionState: synced to all clients for UI state display
 /// - BufferDisplayData: synced to all clients for buffer table display
+/// - ExecutionProgress: synced to all clients for progress/ETA display
 /// - Subsystems: internal subsystem tracking (not synced)
 #[cfg(feature = "server")]
 fn add_execution_components_to_system(
@@ -181,10 +227,42 @@ fn add_execution_components_to_system(
         commands.entity(system_entity).insert((
             ExecutionState::no_source(),
             BufferDisplayData::new(),
+            ExecutionProgress::default(),
             Subsystems::default(),
         ));
         *initialized = true;
-        info!("📡 Added ExecutionState, BufferDisplayData, and Subsystems to System entity");
+        info!("📡 Added ExecutionState, BufferDisplayData, ExecutionProgress, and Subsystems to System entity");
     }
 }
 
+/// Add execution components to coordinators spawned outside the System entity.
+///
+/// Each coordinator tracks its own state and subsystem readiness, so several
+/// coordinators can be validated and executed side by side. The initial
+/// ExecutionState is derived from the coordinator's BufferState.
+#[cfg(feature = "server")]
+fn add_execution_components_to_coordinators(
+    mut commands: Commands,
+    coordinators: Query<
+        (Entity, &ExecutionCoordinator, Option<&BufferState>),
+        (Added<ExecutionCoordinator>, Without<ExecutionState>, Without<ActiveSystem>),
+    >,
+) {
+    for (entity, coordinator, buffer_state) in coordinators.iter() {
+        let mut exec_state = ExecutionState::no_source();
+        if let Some(buffer_state) = buffer_state {
+            exec_state.state = buffer_state.to_system_state();
+        }
+
+        commands.entity(entity).insert((
+            exec_state,
+            BufferDisplayData::new(),
+            ExecutionProgress::default(),
+            Subsystems::default(),
+        ));
+        info!(
+            "📡 Added ExecutionState, BufferDisplayData, ExecutionProgress, and Subsystems to coordinator '{}'",
+            coordinator.name
+        );
+    }
+}
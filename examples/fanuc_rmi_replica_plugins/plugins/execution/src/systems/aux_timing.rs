@@ -0,0 +1,244 @@
+//! Time-parameterized auxiliary commands.
+//!
+//! Untimed auxiliary commands are sent when their point is dispatched to the
+//! motion device, which with a lookahead window can be several moves before
+//! the robot actually gets there. Commands with an `AuxTiming` are instead
+//! held in the coordinator's `AuxSchedule` and sent once the motion to their
+//! point has been running for `offset` seconds.
+//!
+//! Motion progress comes from `DeviceStatus::progress`, reported by devices
+//! that implement `MotionDevice::motion_progress()`. For devices that don't,
+//! the motion to a point is assumed to start when the previous point is
+//! confirmed complete, and elapsed time is measured from there.
+
+use bevy::prelude::*;
+
+use crate::components::{AuxTiming, BufferState, PrimaryMotion, SimulationMode};
+use crate::systems::{find_primary_device, AuxiliaryCommandEvent, DeviceStatus, SimulatedMotion};
+use crate::traits::AuxiliaryCommand;
+
+/// An auxiliary command waiting for its point's motion to reach its offset.
+#[derive(Debug, Clone)]
+pub struct ScheduledAuxCommand {
+    /// The auxiliary device entity
+    pub device: Entity,
+    /// The device type string (for routing)
+    pub device_type: String,
+    /// The command to execute
+    pub command: AuxiliaryCommand,
+    /// Point whose motion the command is timed against
+    pub point_index: u32,
+    /// When the command runs relative to that motion
+    pub timing: AuxTiming,
+}
+
+/// Timed auxiliary commands of a coordinator, in dispatch order.
+///
+/// Added to the coordinator by the orchestrator the first time it dispatches
+/// a point with timed auxiliary commands.
+#[derive(Component, Debug, Clone, Default)]
+pub struct AuxSchedule {
+    pending: Vec<ScheduledAuxCommand>,
+    /// Point assumed to be moving and when its motion started (seconds),
+    /// for devices without progress feedback
+    estimated_start: Option<(u32, f64)>,
+}
+
+impl Extend<ScheduledAuxCommand> for AuxSchedule {
+    fn extend<I: IntoIterator<Item = ScheduledAuxCommand>>(&mut self, iter: I) {
+        self.pending.extend(iter);
+    }
+}
+
+impl AuxSchedule {
+    /// Number of commands waiting to be sent.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Commands waiting to be sent, in dispatch order.
+    pub fn iter(&self) -> impl Iterator<Item = &ScheduledAuxCommand> {
+        self.pending.iter()
+    }
+
+    /// Check if no commands are waiting.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Drop all waiting commands (e.g., on stop).
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.estimated_start = None;
+    }
+
+    /// Remove and return the commands that are due.
+    ///
+    /// `point_index` is the point the device is moving to and `elapsed` the
+    /// seconds since that motion started, or `None` if it hasn't started.
+    /// Commands for earlier points are always due.
+    pub fn take_due(&mut self, point_index: u32, elapsed: Option<f32>) -> Vec<ScheduledAuxCommand> {
+        let (due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|cmd| {
+                cmd.point_index < point_index
+                    || (cmd.point_index == point_index
+                        && elapsed.is_some_and(|elapsed| elapsed >= cmd.timing.offset))
+            });
+        self.pending = pending;
+        due
+    }
+
+    /// Estimate progress from completion feedback alone.
+    ///
+    /// Assumes points complete in index order, so the device is moving to
+    /// point `completed_count` whenever it has motions in-flight.
+    fn estimate_progress(&mut self, completed_count: u32, moving: bool, now: f64) -> Option<f32> {
+        if !moving {
+            self.estimated_start = None;
+            return None;
+        }
+        let started = match self.estimated_start {
+            Some((index, started)) if index == completed_count => started,
+            _ => {
+                self.estimated_start = Some((completed_count, now));
+                now
+            }
+        };
+        Some((now - started) as f32)
+    }
+}
+
+/// Send timed auxiliary commands once their point's motion reaches their offset.
+///
+/// Commands are held while the coordinator is paused or re-validating, and
+/// dropped when it is stopped, reset, or fails.
+pub fn dispatch_scheduled_aux_system(
+    time: Res<Time>,
+    mut coordinators: Query<(
+        Entity,
+        &BufferState,
+        &mut AuxSchedule,
+        Option<&Children>,
+        Has<SimulationMode>,
+    )>,
+    devices: Query<(&DeviceStatus, Has<SimulatedMotion>), With<PrimaryMotion>>,
+    mut aux_events: MessageWriter<AuxiliaryCommandEvent>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for (coordinator_entity, state, mut schedule, children, simulating) in coordinators.iter_mut() {
+        if schedule.is_empty() {
+            continue;
+        }
+
+        match state {
+            BufferState::Executing { .. } | BufferState::Complete { .. } => {}
+            BufferState::Paused { .. } | BufferState::ValidatingForResume { .. } => {
+                // Motion is held, so restart the estimate when it resumes
+                schedule.estimated_start = None;
+                continue;
+            }
+            _ => {
+                debug!(
+                    "Dropping {} scheduled auxiliary command(s) for coordinator {:?}",
+                    schedule.len(),
+                    coordinator_entity
+                );
+                schedule.clear();
+                continue;
+            }
+        }
+
+        let Some(device) = find_primary_device(
+            children,
+            |child| devices.get(child).ok().map(|(_, is_simulator)| is_simulator),
+            simulating,
+        ) else {
+            continue;
+        };
+        let Ok((status, _)) = devices.get(device) else {
+            continue;
+        };
+
+        let (point_index, elapsed) = match status.progress {
+            Some(progress) => (progress.point_index, Some(progress.elapsed)),
+            None => (
+                status.completed_count,
+                schedule.estimate_progress(status.completed_count, status.in_flight_count > 0, now),
+            ),
+        };
+
+        for cmd in schedule.take_due(point_index, elapsed) {
+            trace!(
+                "Timed auxiliary command for point {} ({}s after motion start)",
+                cmd.point_index,
+                cmd.timing.offset
+            );
+            aux_events.write(AuxiliaryCommandEvent {
+                coordinator: coordinator_entity,
+                device: cmd.device,
+                device_type: cmd.device_type,
+                command: cmd.command,
+                point_index: cmd.point_index,
+                duration: cmd.timing.duration,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled(point_index: u32, offset: f32) -> ScheduledAuxCommand {
+        ScheduledAuxCommand {
+            device: Entity::PLACEHOLDER,
+            device_type: "duet_extruder".to_string(),
+            command: AuxiliaryCommand::None,
+            point_index,
+            timing: AuxTiming::at(offset),
+        }
+    }
+
+    #[test]
+    fn test_take_due_waits_for_offset() {
+        let mut schedule = AuxSchedule::default();
+        schedule.extend([scheduled(3, 0.0), scheduled(3, 0.5), scheduled(4, 0.0)]);
+
+        // Motion to point 3 not started yet
+        assert!(schedule.take_due(3, None).is_empty());
+
+        let due = schedule.take_due(3, Some(0.2));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].timing.offset, 0.0);
+
+        let due = schedule.take_due(3, Some(0.5));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].timing.offset, 0.5);
+        assert_eq!(schedule.len(), 1);
+    }
+
+    #[test]
+    fn test_take_due_releases_earlier_points() {
+        let mut schedule = AuxSchedule::default();
+        schedule.extend([scheduled(1, 2.0), scheduled(2, 0.0)]);
+
+        // Device already moving to point 2: point 1's command is overdue
+        let due = schedule.take_due(2, None);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].point_index, 1);
+    }
+
+    #[test]
+    fn test_estimate_progress_restarts_per_point() {
+        let mut schedule = AuxSchedule::default();
+
+        assert_eq!(schedule.estimate_progress(0, false, 1.0), None);
+        assert_eq!(schedule.estimate_progress(0, true, 1.0), Some(0.0));
+        assert_eq!(schedule.estimate_progress(0, true, 1.5), Some(0.5));
+
+        // Point 0 completed, motion to point 1 starts now
+        assert_eq!(schedule.estimate_progress(1, true, 2.0), Some(0.0));
+    }
+}
@@ -0,0 +1,81 @@
+//! Digital output device handler.
+//!
+//! Applies AuxiliaryCommandEvents addressed to `DigitalOutputDevice` entities
+//! and emits a `DigitalOutputWriteEvent` per resolved write. Backend plugins
+//! (FANUC DOUT, Duet GPIO) listen for the writes of their backend.
+
+use bevy::prelude::*;
+
+use crate::components::{DigitalOutputDevice, DigitalOutputWrite, ExecutionTarget};
+use crate::systems::{AuxiliaryCommandEvent, DeviceType};
+use crate::traits::AuxiliaryDevice;
+
+/// Event sent when a digital output device needs a physical output written.
+#[derive(bevy::prelude::Message, Debug, Clone)]
+pub struct DigitalOutputWriteEvent {
+    /// The coordinator entity that owns this execution
+    pub coordinator: Entity,
+    /// The digital output device entity
+    pub device: Entity,
+    /// The write to perform
+    pub write: DigitalOutputWrite,
+    /// Point index for tracking
+    pub point_index: u32,
+}
+
+/// Spawn a digital output device as an auxiliary child of `coordinator`.
+///
+/// The device's `device_type` becomes its `DeviceType`, so points address it
+/// by that key in `aux_commands`.
+pub fn spawn_digital_output_device(
+    commands: &mut Commands,
+    coordinator: Entity,
+    device: DigitalOutputDevice,
+) -> Entity {
+    let device_type = DeviceType::new(device.device_type.clone());
+    let entity = commands
+        .spawn((
+            Name::new(format!("Digital Output ({})", device.device_type)),
+            device,
+            ExecutionTarget,
+            device_type,
+        ))
+        .id();
+    commands.entity(coordinator).add_child(entity);
+    entity
+}
+
+/// System that resolves auxiliary commands for digital output devices.
+pub fn digital_output_handler_system(
+    mut aux_events: MessageReader<AuxiliaryCommandEvent>,
+    mut write_events: MessageWriter<DigitalOutputWriteEvent>,
+    mut devices: Query<&mut DigitalOutputDevice>,
+) {
+    for event in aux_events.read() {
+        // Events for other devices are handled by their own plugins
+        let Ok(mut device) = devices.get_mut(event.device) else {
+            continue;
+        };
+
+        if let Err(e) = device.send_command(&event.command) {
+            warn!(
+                "{} rejected command for point {}: {}",
+                device.device_type, event.point_index, e
+            );
+            continue;
+        }
+
+        for write in device.take_pending() {
+            debug!(
+                "{}: port {} -> {} (point {})",
+                device.device_type, write.port, write.value, event.point_index
+            );
+            write_events.write(DigitalOutputWriteEvent {
+                coordinator: event.coordinator,
+                device: event.device,
+                write,
+                point_index: event.point_index,
+            });
+        }
+    }
+}
@@ -6,6 +6,7 @@
 use bevy::prelude::*;
 
 use crate::components::{BufferState, ExecutionCoordinator, ToolpathBuffer};
+use crate::systems::DeviceStatus;
 
 /// Marker trait that device plugins should implement to indicate a device is connected.
 ///
@@ -16,6 +17,10 @@ pub struct DeviceConnected;
 
 /// System that resets execution state when devices disconnect.
 ///
+/// Each coordinator only looks at the devices among its own children, so one
+/// robot disconnecting doesn't reset a coordinator driving another robot. A
+/// coordinator without child devices falls back to checking every device.
+///
 /// When no devices are connected but execution is in progress, this system:
 /// - Transitions BufferState to Idle
 /// - Clears the ToolpathBuffer
@@ -25,18 +30,32 @@ pub struct DeviceConnected;
 /// on their device entities.
 pub fn reset_on_disconnect_system(
     mut coordinator_query: Query<
-        (Entity, &mut BufferState, &mut ToolpathBuffer),
+        (Entity, &mut BufferState, &mut ToolpathBuffer, Option<&Children>),
         With<ExecutionCoordinator>,
     >,
+    devices: Query<Has<DeviceConnected>, With<DeviceStatus>>,
     connected_devices: Query<Entity, With<DeviceConnected>>,
 ) {
-    // If any device is still connected, do nothing
-    if !connected_devices.is_empty() {
-        return;
-    }
+    let any_connected = !connected_devices.is_empty();
+
+    for (entity, mut state, mut buffer, children) in coordinator_query.iter_mut() {
+        let mut child_devices = children
+            .into_iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|child| devices.get(child).ok())
+            .peekable();
+        let connected = if child_devices.peek().is_some() {
+            child_devices.any(|is_connected| is_connected)
+        } else {
+            any_connected
+        };
+
+        // If any of the coordinator's devices is still connected, do nothing
+        if connected {
+            continue;
+        }
 
-    // No devices connected - reset any active execution
-    for (entity, mut state, mut buffer) in coordinator_query.iter_mut() {
+        // No devices connected - reset any active execution
         match &*state {
             BufferState::Executing { .. }
             | BufferState::Paused { .. }
@@ -160,6 +179,37 @@ mod tests {
         assert!(matches!(state, BufferState::Idle));
     }
 
+    #[test]
+    fn test_reset_only_coordinator_with_disconnected_devices() {
+        let mut world = World::new();
+        let executing = || BufferState::Executing {
+            current_index: 2,
+            completed_count: 1,
+        };
+
+        // Coordinator whose robot is still connected
+        let connected_robot = world.spawn((DeviceStatus::default(), DeviceConnected)).id();
+        let kept = world
+            .spawn((ExecutionCoordinator::new("kept"), executing(), ToolpathBuffer::new()))
+            .add_child(connected_robot)
+            .id();
+
+        // Coordinator whose robot has disconnected
+        let disconnected_robot = world.spawn(DeviceStatus::default()).id();
+        let reset = world
+            .spawn((ExecutionCoordinator::new("reset"), executing(), ToolpathBuffer::new()))
+            .add_child(disconnected_robot)
+            .id();
+
+        let _ = world.run_system_once(reset_on_disconnect_system);
+
+        assert!(matches!(
+            world.get::<BufferState>(kept),
+            Some(BufferState::Executing { .. })
+        ));
+        assert!(matches!(world.get::<BufferState>(reset), Some(BufferState::Idle)));
+    }
+
     #[test]
     fn test_is_stopped_helper() {
         let stopped = BufferState::Stopped {
@@ -3,27 +3,46 @@
 //! These systems run on the server and handle:
 //! - Buffer state management
 //! - Orchestration of motion and auxiliary commands
+//! - Timing of auxiliary commands against motion progress
+//! - Generic digital output devices (grippers, valves)
 //! - Device status tracking
 //! - Lifecycle management (disconnect cleanup)
 //! - State synchronization (BufferState ↔ ExecutionState)
 //! - Validation coordination (subsystem readiness checks)
+//! - Dry-run simulation (built-in kinematic simulator device)
 //!
 //! Device-specific handlers (FANUC, Duet, etc.) are in their respective plugin crates.
 
+mod aux_timing;
+mod digital_output;
 mod lifecycle;
 mod orchestrator;
+mod simulator;
 #[cfg(feature = "server")]
 mod sync;
 #[cfg(feature = "server")]
 mod validation;
 
+pub use aux_timing::{dispatch_scheduled_aux_system, AuxSchedule, ScheduledAuxCommand};
+pub use digital_output::{
+    digital_output_handler_system, spawn_digital_output_device, DigitalOutputWriteEvent,
+};
 pub use lifecycle::{reset_on_disconnect_system, DeviceConnected};
 pub use orchestrator::{
-    orchestrator_system, update_buffer_state_system, AuxiliaryCommandEvent, DeviceStatus,
-    DeviceType, MotionCommandEvent,
+    find_primary_device, orchestrator_system, update_buffer_state_system, AuxiliaryCommandEvent,
+    DeviceStatus, DeviceType, MotionCommandEvent,
+};
+pub use simulator::{
+    advance_simulation_system, cleanup_simulation_system, despawn_simulated_devices,
+    simulated_motion_handler_system, spawn_simulated_device, SimulatedMotion,
+    SIMULATOR_DEVICE_TYPE,
 };
 #[cfg(feature = "server")]
-pub use sync::{sync_buffer_state_to_execution_state, sync_device_status_to_buffer_state};
+pub use sync::{
+    emit_program_completed_webhooks, sync_buffer_occupancy_to_display,
+    sync_buffer_state_to_execution_state, sync_device_status_to_buffer_state,
+    sync_execution_progress,
+};
 #[cfg(feature = "server")]
 pub use validation::{coordinate_validation, ValidationStartTime};
 
@@ -16,9 +16,10 @@ use bevy::prelude::*;
 
 use crate::components::{
     BufferState, ExecutionCoordinator, ExecutionPoint, MotionCommand, PrimaryMotion,
-    ToolpathBuffer,
+    SimulationMode, StepMode, ToolpathBuffer,
 };
-use crate::traits::AuxiliaryCommand;
+use crate::systems::{AuxSchedule, ScheduledAuxCommand, SimulatedMotion};
+use crate::traits::{AuxiliaryCommand, MotionProgress};
 use fanuc_replica_robotics::RobotPose;
 
 /// Event sent when the orchestrator needs to dispatch a motion command.
@@ -55,6 +56,8 @@ pub struct AuxiliaryCommandEvent {
     pub command: AuxiliaryCommand,
     /// Point index for tracking
     pub point_index: u32,
+    /// Seconds the command should take, if it is timed to span the motion
+    pub duration: Option<f32>,
 }
 
 /// Component added to motion devices to report their status.
@@ -70,7 +73,15 @@ pub struct AuxiliaryCommandEvent {
 /// - `in_flight_capacity`: Maximum commands that can be in-flight (device-specific)
 /// - `in_flight_count`: Current number of sent-but-not-confirmed commands
 ///
-/// The orchestrator can send commands while `ready_for_next()` returns true.
+/// The orchestrator can send commands while `ready_for_next()` returns true,
+/// up to the coordinator's lookahead window.
+///
+/// ## Rejections (NACK)
+///
+/// When the device cannot accept a command it has been sent, the device plugin
+/// calls `command_rejected()` with the point. The orchestrator pauses streaming
+/// until the device confirms a completion (or `clear_rejection()` is called),
+/// then sends the rejected points again before continuing with the buffer.
 #[derive(Component, Debug, Clone)]
 pub struct DeviceStatus {
     /// True if the device is connected and operational
@@ -90,6 +101,15 @@ pub struct DeviceStatus {
 
     /// Error message if device is in error state
     pub error: Option<String>,
+
+    /// True after the device rejected a command, until it accepts commands again
+    pub rejected: bool,
+
+    /// Points the device rejected, waiting to be sent again
+    pub rejected_points: Vec<ExecutionPoint>,
+
+    /// Progress of the current motion, for devices that report it
+    pub progress: Option<MotionProgress>,
 }
 
 impl Default for DeviceStatus {
@@ -100,6 +120,9 @@ impl Default for DeviceStatus {
             in_flight_count: 0,
             completed_count: 0,
             error: None,
+            rejected: false,
+            rejected_points: Vec::new(),
+            progress: None,
         }
     }
 }
@@ -113,6 +136,9 @@ impl DeviceStatus {
             in_flight_count: 0,
             completed_count: 0,
             error: None,
+            rejected: false,
+            rejected_points: Vec::new(),
+            progress: None,
         }
     }
 
@@ -122,8 +148,12 @@ impl DeviceStatus {
     /// - Device is connected
     /// - No error state
     /// - In-flight count is below capacity
+    /// - Device has not rejected a command
     pub fn ready_for_next(&self) -> bool {
-        self.is_connected && self.error.is_none() && self.in_flight_count < self.in_flight_capacity
+        self.is_connected
+            && self.error.is_none()
+            && !self.rejected
+            && self.in_flight_count < self.in_flight_capacity
     }
 
     /// Check if queue needs more commands for smooth motion.
@@ -141,16 +171,40 @@ impl DeviceStatus {
     }
 
     /// Record that a command completed.
+    ///
+    /// A completion means the device has room again, so it also clears a
+    /// pending rejection.
     pub fn command_completed(&mut self) {
         if self.in_flight_count > 0 {
             self.in_flight_count -= 1;
         }
         self.completed_count += 1;
+        self.rejected = false;
+    }
+
+    /// Record that the device rejected (NACKed) a command.
+    ///
+    /// The point is no longer in-flight and will be sent again once the
+    /// device accepts commands.
+    pub fn command_rejected(&mut self, point: ExecutionPoint) {
+        if self.in_flight_count > 0 {
+            self.in_flight_count -= 1;
+        }
+        self.rejected = true;
+        self.rejected_points.push(point);
+    }
+
+    /// Resume streaming after a rejection without waiting for a completion.
+    pub fn clear_rejection(&mut self) {
+        self.rejected = false;
     }
 
     /// Reset in-flight tracking (e.g., on stop or error).
     pub fn reset_in_flight(&mut self) {
         self.in_flight_count = 0;
+        self.rejected = false;
+        self.rejected_points.clear();
+        self.progress = None;
     }
 }
 
@@ -201,6 +255,21 @@ pub fn update_buffer_state_system(
     }
 }
 
+/// Find a coordinator's primary motion device among its children.
+///
+/// `primary` returns `Some(is_simulator)` for children that are primary motion
+/// devices. While `simulating`, only the simulator qualifies; otherwise only
+/// real devices do.
+pub fn find_primary_device(
+    children: Option<&Children>,
+    primary: impl Fn(Entity) -> Option<bool>,
+    simulating: bool,
+) -> Option<Entity> {
+    children?
+        .iter()
+        .find(|child| primary(*child) == Some(simulating))
+}
+
 /// Maximum commands to dispatch in a single tick.
 ///
 /// This prevents the orchestrator from blocking too long in one frame
@@ -210,32 +279,66 @@ const MAX_DISPATCH_PER_TICK: u32 = 10;
 /// Main orchestrator system - dispatches commands to devices via events.
 ///
 /// For each coordinator in Executing state:
-/// 1. Find the primary motion device (child with PrimaryMotion + DeviceStatus)
-/// 2. Loop while device can accept commands (up to burst limit)
-/// 3. Pop points from buffer and send MotionCommandEvent
-/// 4. Send AuxiliaryCommandEvent for each auxiliary device
-/// 5. Update buffer state
+/// 1. Find the primary motion device (child with PrimaryMotion + DeviceStatus;
+///    the simulator when the coordinator has `SimulationMode`)
+/// 2. Pause while the device has rejected a command, then requeue rejected points
+/// 3. Loop while device can accept commands (up to lookahead window and burst limit)
+/// 4. Pop points from buffer and send MotionCommandEvent
+/// 5. Send AuxiliaryCommandEvent for each auxiliary device, or schedule it
+///    when the point times the command (see `AuxSchedule`)
+/// 6. Update buffer state
 ///
 /// ## In-Flight Queue Filling
 ///
 /// This system loops while `ready_for_next()` returns true, allowing multiple
 /// commands to be dispatched in a single tick. This fills the device's
 /// in-flight queue, which is critical for smooth continuous motion.
+///
+/// ## Lookahead Window
+///
+/// The number of in-flight commands never exceeds
+/// `ExecutionCoordinator::effective_window()`, the smaller of the coordinator's
+/// configured window and the capacity reported by the device.
+///
+/// ## Simulation
+///
+/// In `SimulationMode` the simulator is the only device used: real motion
+/// devices are skipped and no auxiliary commands are dispatched.
+///
+/// ## Stepping
+///
+/// With a `StepMode` component, only `remaining` more points are dispatched.
+/// Once they are confirmed complete the coordinator returns to Paused.
 pub fn orchestrator_system(
-    mut coordinator_query: Query<
-        (Entity, &mut BufferState, &mut ToolpathBuffer),
-        With<ExecutionCoordinator>,
-    >,
+    mut commands: Commands,
+    mut coordinator_query: Query<(
+        Entity,
+        &mut ExecutionCoordinator,
+        &mut BufferState,
+        &mut ToolpathBuffer,
+        Has<SimulationMode>,
+        Option<&mut StepMode>,
+        Option<&mut AuxSchedule>,
+    )>,
     children_query: Query<&Children>,
     mut device_status_query: Query<
-        (Entity, &mut DeviceStatus, Option<&DeviceType>),
+        (Entity, &mut DeviceStatus, Has<SimulatedMotion>),
         With<PrimaryMotion>,
     >,
     aux_device_query: Query<(Entity, &DeviceType), Without<PrimaryMotion>>,
     mut motion_events: MessageWriter<MotionCommandEvent>,
     mut aux_events: MessageWriter<AuxiliaryCommandEvent>,
 ) {
-    for (coordinator_entity, mut state, mut buffer) in coordinator_query.iter_mut() {
+    for (
+        coordinator_entity,
+        mut coordinator,
+        mut state,
+        mut buffer,
+        simulating,
+        mut step_mode,
+        aux_schedule,
+    ) in coordinator_query.iter_mut()
+    {
         // Only process coordinators in Executing state
         let completed_count = match &*state {
             BufferState::Executing { completed_count, .. } => *completed_count,
@@ -251,14 +354,12 @@ pub fn orchestrator_system(
             continue;
         };
 
-        // Find the primary motion device among children
-        let mut motion_entity: Option<Entity> = None;
-        for child in children.iter() {
-            if device_status_query.get(child).is_ok() {
-                motion_entity = Some(child);
-                break;
-            }
-        }
+        // Find the primary motion device among children (simulator only when simulating)
+        let motion_entity = find_primary_device(
+            Some(children),
+            |child| device_status_query.get(child).ok().map(|(_, _, is_simulator)| is_simulator),
+            simulating,
+        );
 
         let Some(motion_entity) = motion_entity else {
             warn!(
@@ -268,9 +369,35 @@ pub fn orchestrator_system(
             continue;
         };
 
-        // Dispatch loop - fill in-flight queue up to capacity
+        // Flow control - pause while the device is rejecting commands
+        let Ok((_, mut motion_status, _)) = device_status_query.get_mut(motion_entity) else {
+            continue;
+        };
+        if motion_status.rejected {
+            if !coordinator.flow_paused {
+                coordinator.flow_paused = true;
+                warn!(
+                    "Coordinator '{}' paused streaming: device rejected {} point(s)",
+                    coordinator.name,
+                    motion_status.rejected_points.len()
+                );
+            }
+            continue;
+        }
+        if !motion_status.rejected_points.is_empty() {
+            buffer.requeue_front(motion_status.rejected_points.drain(..));
+        }
+        if coordinator.flow_paused {
+            coordinator.flow_paused = false;
+            info!("Coordinator '{}' resumed streaming", coordinator.name);
+        }
+        let window = coordinator.effective_window(motion_status.in_flight_capacity);
+        let step_budget = step_mode.as_ref().map(|step| step.remaining);
+
+        // Dispatch loop - fill in-flight queue up to the lookahead window
         let mut dispatched = 0u32;
         let mut last_point_index = 0u32;
+        let mut scheduled = Vec::new();
 
         loop {
             // Check burst limit
@@ -282,18 +409,24 @@ pub fn orchestrator_system(
                 break;
             }
 
+            // Check step budget
+            if step_budget.is_some_and(|budget| dispatched >= budget) {
+                break;
+            }
+
             // Get current device status (must re-query since we mutate it)
             let Ok((_, mut motion_status, _)) = device_status_query.get_mut(motion_entity) else {
                 break;
             };
 
             // Check if motion device can accept more commands
-            if !motion_status.ready_for_next() {
+            if !motion_status.ready_for_next() || motion_status.in_flight_count >= window {
                 if dispatched == 0 {
                     trace!(
-                        "Motion device not ready (in_flight: {}/{})",
+                        "Motion device not ready (in_flight: {}/{}, window: {})",
                         motion_status.in_flight_count,
-                        motion_status.in_flight_capacity
+                        motion_status.in_flight_capacity,
+                        window
                     );
                 }
                 break;
@@ -319,17 +452,31 @@ pub fn orchestrator_system(
                 point: point.clone(),
             });
 
-            // Send auxiliary command events
-            for child in children.iter() {
+            // Send auxiliary command events (real peripherals stay idle during a dry run)
+            for child in children.iter().filter(|_| !simulating) {
                 if let Ok((aux_entity, device_type)) = aux_device_query.get(child) {
-                    if let Some(cmd) = point.aux_commands.get(&device_type.0) {
-                        aux_events.write(AuxiliaryCommandEvent {
-                            coordinator: coordinator_entity,
+                    let Some(cmd) = point.aux_commands.get(&device_type.0) else {
+                        continue;
+                    };
+                    match point.aux_timing.get(&device_type.0) {
+                        // Timed commands wait for the motion to reach their offset
+                        Some(timing) => scheduled.push(ScheduledAuxCommand {
                             device: aux_entity,
                             device_type: device_type.0.clone(),
                             command: cmd.clone(),
                             point_index: point.index,
-                        });
+                            timing: *timing,
+                        }),
+                        None => {
+                            aux_events.write(AuxiliaryCommandEvent {
+                                coordinator: coordinator_entity,
+                                device: aux_entity,
+                                device_type: device_type.0.clone(),
+                                command: cmd.clone(),
+                                point_index: point.index,
+                                duration: None,
+                            });
+                        }
                     }
                 }
             }
@@ -337,6 +484,17 @@ pub fn orchestrator_system(
             dispatched += 1;
         }
 
+        if !scheduled.is_empty() {
+            match aux_schedule {
+                Some(mut schedule) => schedule.extend(scheduled),
+                None => {
+                    let mut schedule = AuxSchedule::default();
+                    schedule.extend(scheduled);
+                    commands.entity(coordinator_entity).insert(schedule);
+                }
+            }
+        }
+
         // Update state with new current index if we dispatched anything
         if dispatched > 0 {
             trace!("Dispatched {} commands, last index {}", dispatched, last_point_index);
@@ -345,6 +503,26 @@ pub fn orchestrator_system(
                 completed_count,
             };
         }
+
+        // Single step: pause again once the stepped points are confirmed complete
+        if let Some(step) = step_mode.as_deref_mut() {
+            step.remaining = step.remaining.saturating_sub(dispatched);
+            let device_idle = device_status_query
+                .get(motion_entity)
+                .map_or(true, |(_, status, _)| status.in_flight_count == 0);
+            if step.remaining == 0 && device_idle {
+                let paused_at_index = match *state {
+                    BufferState::Executing { current_index, .. } => current_index,
+                    _ => last_point_index,
+                };
+                *state = BufferState::Paused { paused_at_index };
+                commands.entity(coordinator_entity).remove::<StepMode>();
+                info!(
+                    "⏯ Step complete for '{}', paused at index {}",
+                    coordinator.name, paused_at_index
+                );
+            }
+        }
     }
 }
 
@@ -360,3 +538,116 @@ impl DeviceType {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::MotionType;
+    use bevy::ecs::message::Messages;
+    use fanuc_replica_robotics::FrameId;
+
+    fn point(index: u32) -> ExecutionPoint {
+        ExecutionPoint {
+            index,
+            target_pose: RobotPose::from_translation(0.0, 0.0, 0.0, FrameId::World),
+            motion: MotionCommand {
+                motion_type: MotionType::Linear,
+                speed: 100.0,
+                blend_radius: 0.0,
+            },
+            aux_commands: Default::default(),
+            aux_timing: Default::default(),
+            metadata: Default::default(),
+        }
+    }
+
+    /// A coordinator with `points` queued and a connected robot that can hold
+    /// `capacity` motions in flight. Returns the coordinator and robot entities.
+    fn streaming_app(coordinator: ExecutionCoordinator, points: u32, capacity: u32) -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.add_message::<MotionCommandEvent>()
+            .add_message::<AuxiliaryCommandEvent>()
+            .add_systems(Update, orchestrator_system);
+
+        let mut buffer = ToolpathBuffer::new_static(points);
+        for index in 0..points {
+            buffer.push(point(index));
+        }
+        let coordinator = app
+            .world_mut()
+            .spawn((
+                coordinator,
+                buffer,
+                BufferState::Executing {
+                    current_index: 0,
+                    completed_count: 0,
+                },
+            ))
+            .id();
+        let mut status = DeviceStatus::with_capacity(capacity);
+        status.is_connected = true;
+        let robot = app.world_mut().spawn((PrimaryMotion, status, ChildOf(coordinator))).id();
+        (app, coordinator, robot)
+    }
+
+    fn dispatched(app: &mut App) -> Vec<u32> {
+        app.world_mut()
+            .resource_mut::<Messages<MotionCommandEvent>>()
+            .drain()
+            .map(|event| event.point.index)
+            .collect()
+    }
+
+    fn status(app: &mut App, robot: Entity) -> Mut<'_, DeviceStatus> {
+        app.world_mut().get_mut::<DeviceStatus>(robot).unwrap()
+    }
+
+    fn flow_paused(app: &App, coordinator: Entity) -> bool {
+        app.world().get::<ExecutionCoordinator>(coordinator).unwrap().flow_paused
+    }
+
+    #[test]
+    fn test_lookahead_window_bounds_in_flight() {
+        let coordinator = ExecutionCoordinator::new("cell").with_lookahead(2);
+        assert_eq!(coordinator.effective_window(5), 2);
+        assert_eq!(coordinator.effective_window(1), 1);
+        assert_eq!(ExecutionCoordinator::new("cell").effective_window(5), 5);
+
+        let (mut app, _, robot) = streaming_app(coordinator, 5, 5);
+        app.update();
+        assert_eq!(dispatched(&mut app), [0, 1]);
+
+        // Nothing more until the robot confirms a motion
+        app.update();
+        assert!(dispatched(&mut app).is_empty());
+
+        status(&mut app, robot).command_completed();
+        app.update();
+        assert_eq!(dispatched(&mut app), [2]);
+        assert_eq!(status(&mut app, robot).in_flight_count, 2);
+    }
+
+    #[test]
+    fn test_rejected_points_pause_and_are_resent_in_order() {
+        let (mut app, coordinator, robot) = streaming_app(ExecutionCoordinator::new("cell"), 4, 3);
+        app.update();
+        assert_eq!(dispatched(&mut app), [0, 1, 2]);
+
+        // The robot NACKs points 1 and 2
+        {
+            let mut robot_status = status(&mut app, robot);
+            robot_status.command_rejected(point(2));
+            robot_status.command_rejected(point(1));
+        }
+        app.update();
+        assert!(dispatched(&mut app).is_empty());
+        assert!(flow_paused(&app, coordinator));
+
+        // A completion frees the robot; the rejected points go out first
+        status(&mut app, robot).command_completed();
+        app.update();
+        assert_eq!(dispatched(&mut app), [1, 2, 3]);
+        assert!(status(&mut app, robot).rejected_points.is_empty());
+        assert!(!flow_paused(&app, coordinator));
+    }
+}
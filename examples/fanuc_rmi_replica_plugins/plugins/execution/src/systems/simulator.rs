@@ -0,0 +1,362 @@
+//! Built-in kinematic simulator for dry-run execution.
+//!
+//! When `Start { simulate: true }` is requested, `handle_start` marks the
+//! coordinator with `SimulationMode` and spawns a `SimulatedMotion` device as
+//! its primary motion child. The orchestrator then dispatches to the simulator
+//! instead of the real robot, so the full pipeline (validation, lookahead
+//! streaming, ExecutionState and BufferDisplayData updates) runs unchanged.
+//!
+//! The simulator moves in straight lines at each point's commanded speed and
+//! confirms a point once the move would have finished, so progress and total
+//! run time match what the program would take on hardware (ignoring
+//! acceleration and blending). It also reports motion progress, like a device
+//! implementing `MotionDevice::motion_progress()`. Auxiliary commands are not
+//! dispatched while simulating.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use fanuc_replica_robotics::RobotPose;
+
+use crate::components::{
+    BufferState, ExecutionCoordinator, ExecutionPoint, ExecutionTarget, MotionCommand,
+    PrimaryMotion, SimulationMode,
+};
+use crate::systems::{DeviceConnected, DeviceStatus, DeviceType, MotionCommandEvent};
+use crate::traits::{DeviceError, MotionDevice, MotionProgress};
+
+/// Device type string reported by the simulator.
+pub const SIMULATOR_DEVICE_TYPE: &str = "simulator";
+
+/// Speed used for points that don't specify one (mm/s).
+const FALLBACK_SPEED: f64 = 100.0;
+
+/// A move accepted by the simulator but not yet finished.
+#[derive(Debug, Clone)]
+struct SimulatedMove {
+    target: RobotPose,
+    /// Index of the point this move goes to
+    point_index: u32,
+    /// Total time for this move in seconds
+    duration: f64,
+    /// Remaining time for this move in seconds
+    remaining: f64,
+}
+
+/// Kinematic simulator used as the primary motion device during dry runs.
+#[derive(Component, Debug, Clone)]
+pub struct SimulatedMotion {
+    /// Last commanded pose (where the simulated tool will be after all moves)
+    commanded_pose: Option<RobotPose>,
+    /// Pose reached by the last finished move
+    current_pose: Option<RobotPose>,
+    /// Accepted moves, oldest first
+    moves: VecDeque<SimulatedMove>,
+    /// Moves in-flight at once (mirrors the real device's lookahead)
+    capacity: u32,
+    /// Moves finished since the last `take_completed()`
+    completed: u32,
+}
+
+impl SimulatedMotion {
+    /// Create a simulator that holds up to `capacity` moves in-flight.
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            commanded_pose: None,
+            current_pose: None,
+            moves: VecDeque::new(),
+            capacity: capacity.max(1),
+            completed: 0,
+        }
+    }
+
+    /// Advance simulated time by `dt` seconds.
+    fn advance(&mut self, mut dt: f64) {
+        while dt > 0.0 {
+            let Some(current) = self.moves.front_mut() else {
+                break;
+            };
+            if current.remaining > dt {
+                current.remaining -= dt;
+                break;
+            }
+            dt -= current.remaining;
+            if let Some(finished) = self.moves.pop_front() {
+                self.current_pose = Some(finished.target);
+                self.completed += 1;
+            }
+        }
+    }
+
+    /// Number of moves finished since the last call.
+    fn take_completed(&mut self) -> u32 {
+        std::mem::take(&mut self.completed)
+    }
+}
+
+impl MotionDevice for SimulatedMotion {
+    fn device_type(&self) -> &str {
+        SIMULATOR_DEVICE_TYPE
+    }
+
+    fn send_motion(
+        &mut self,
+        target: &RobotPose,
+        motion: &MotionCommand,
+        point: &ExecutionPoint,
+    ) -> Result<(), DeviceError> {
+        if !self.ready_for_next() {
+            return Err(DeviceError::Busy);
+        }
+
+        let distance = self.commanded_pose.as_ref().map_or(0.0, |from| {
+            let (x0, y0, z0) = from.translation();
+            let (x1, y1, z1) = target.translation();
+            ((x1 - x0).powi(2) + (y1 - y0).powi(2) + (z1 - z0).powi(2)).sqrt()
+        });
+        let speed = if motion.speed > 0.0 {
+            motion.speed as f64
+        } else {
+            FALLBACK_SPEED
+        };
+
+        let duration = distance / speed;
+        self.moves.push_back(SimulatedMove {
+            target: target.clone(),
+            point_index: point.index,
+            duration,
+            remaining: duration,
+        });
+        self.commanded_pose = Some(target.clone());
+        Ok(())
+    }
+
+    fn ready_for_next(&self) -> bool {
+        (self.moves.len() as u32) < self.capacity
+    }
+
+    fn motions_completed(&self) -> u32 {
+        self.completed
+    }
+
+    fn in_flight_capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    fn motion_progress(&self) -> Option<MotionProgress> {
+        self.moves.front().map(|current| MotionProgress {
+            point_index: current.point_index,
+            elapsed: (current.duration - current.remaining) as f32,
+        })
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn current_pose(&self) -> Option<RobotPose> {
+        self.current_pose.clone()
+    }
+}
+
+/// Spawn a simulated primary motion device as a child of `coordinator`.
+pub fn spawn_simulated_device(commands: &mut Commands, coordinator: Entity, capacity: u32) -> Entity {
+    let mut status = DeviceStatus::with_capacity(capacity);
+    status.is_connected = true;
+
+    let device = commands
+        .spawn((
+            Name::new("Simulator"),
+            SimulatedMotion::new(capacity),
+            ExecutionTarget,
+            PrimaryMotion,
+            status,
+            DeviceConnected,
+            DeviceType::new(SIMULATOR_DEVICE_TYPE),
+        ))
+        .id();
+    commands.entity(coordinator).add_child(device);
+    device
+}
+
+/// Feed MotionCommandEvents addressed to a simulated device into the simulator.
+pub fn simulated_motion_handler_system(
+    mut motion_events: MessageReader<MotionCommandEvent>,
+    mut devices: Query<(&mut SimulatedMotion, &mut DeviceStatus)>,
+) {
+    for event in motion_events.read() {
+        // Events for real devices are handled by their own plugins
+        let Ok((mut simulator, mut status)) = devices.get_mut(event.device) else {
+            continue;
+        };
+
+        if let Err(e) = simulator.send_motion(&event.target_pose, &event.motion, &event.point) {
+            debug!("Simulator rejected point {}: {}", event.point.index, e);
+            status.command_rejected(event.point.clone());
+        }
+    }
+}
+
+/// Advance simulated motion while its coordinator is executing.
+///
+/// Paused, stopped and validating coordinators freeze simulated time, matching
+/// a real robot that holds position.
+pub fn advance_simulation_system(
+    time: Res<Time>,
+    coordinators: Query<(&BufferState, &Children), With<SimulationMode>>,
+    mut devices: Query<(&mut SimulatedMotion, &mut DeviceStatus)>,
+) {
+    let dt = time.delta_secs_f64();
+
+    for (state, children) in coordinators.iter() {
+        if !matches!(state, BufferState::Executing { .. }) {
+            continue;
+        }
+        for child in children.iter() {
+            let Ok((mut simulator, mut status)) = devices.get_mut(child) else {
+                continue;
+            };
+            simulator.advance(dt);
+            for _ in 0..simulator.take_completed() {
+                status.command_completed();
+            }
+            status.progress = simulator.motion_progress();
+        }
+    }
+}
+
+/// Remove the simulator once a dry run has finished.
+///
+/// Leaves `ExecutionState.simulated` set, so the UI can still show that the
+/// last result came from a simulation.
+pub fn cleanup_simulation_system(
+    mut commands: Commands,
+    coordinators: Query<(Entity, &ExecutionCoordinator, &BufferState, Option<&Children>), With<SimulationMode>>,
+    simulators: Query<(), With<SimulatedMotion>>,
+) {
+    for (entity, coordinator, state, children) in coordinators.iter() {
+        if !matches!(
+            state,
+            BufferState::Idle
+                | BufferState::Complete { .. }
+                | BufferState::Stopped { .. }
+                | BufferState::Error { .. }
+        ) {
+            continue;
+        }
+
+        despawn_simulated_devices(&mut commands, children, &simulators);
+        commands.entity(entity).remove::<SimulationMode>();
+        info!("🧪 Simulation finished for '{}'", coordinator.name);
+    }
+}
+
+/// Despawn every simulated device among `children`.
+pub fn despawn_simulated_devices(
+    commands: &mut Commands,
+    children: Option<&Children>,
+    simulators: &Query<(), With<SimulatedMotion>>,
+) {
+    for child in children.into_iter().flat_map(|children| children.iter()) {
+        if simulators.contains(child) {
+            commands.entity(child).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::MotionType;
+    use fanuc_replica_robotics::FrameId;
+
+    fn point(index: u32, x: f64, speed: f32) -> ExecutionPoint {
+        ExecutionPoint {
+            index,
+            target_pose: RobotPose::from_translation(x, 0.0, 0.0, FrameId::World),
+            motion: MotionCommand {
+                motion_type: MotionType::Linear,
+                speed,
+                blend_radius: 0.0,
+            },
+            aux_commands: Default::default(),
+            aux_timing: Default::default(),
+            metadata: Default::default(),
+        }
+    }
+
+    fn send(simulator: &mut SimulatedMotion, point: &ExecutionPoint) -> Result<(), DeviceError> {
+        simulator.send_motion(&point.target_pose, &point.motion, point)
+    }
+
+    #[test]
+    fn test_moves_take_distance_over_speed() {
+        let mut simulator = SimulatedMotion::new(2);
+        send(&mut simulator, &point(0, 0.0, 100.0)).unwrap();
+        send(&mut simulator, &point(1, 100.0, 50.0)).unwrap();
+        assert!(matches!(send(&mut simulator, &point(2, 0.0, 100.0)), Err(DeviceError::Busy)));
+
+        // The first move starts where the tool is, so it finishes immediately
+        simulator.advance(0.5);
+        assert_eq!(simulator.take_completed(), 1);
+        let progress = simulator.motion_progress().unwrap();
+        assert_eq!(progress.point_index, 1);
+        assert_eq!(progress.elapsed, 0.5);
+
+        // 100 mm at 50 mm/s takes two seconds
+        simulator.advance(1.5);
+        assert_eq!(simulator.take_completed(), 1);
+        assert!(simulator.motion_progress().is_none());
+        assert_eq!(simulator.current_pose().unwrap().translation(), (100.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_simulation_runs_only_while_executing_and_is_cleaned_up() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let mut advance = Schedule::default();
+        advance.add_systems(advance_simulation_system);
+        let mut cleanup = Schedule::default();
+        cleanup.add_systems(cleanup_simulation_system);
+
+        let coordinator = world
+            .spawn((
+                ExecutionCoordinator::new("cell"),
+                SimulationMode,
+                BufferState::Paused { paused_at_index: 0 },
+            ))
+            .id();
+        let mut simulator = SimulatedMotion::new(2);
+        send(&mut simulator, &point(0, 0.0, 10.0)).unwrap();
+        send(&mut simulator, &point(1, 10.0, 10.0)).unwrap();
+        let mut status = DeviceStatus::with_capacity(2);
+        status.in_flight_count = 2;
+        let device = world.spawn((simulator, status, ChildOf(coordinator))).id();
+
+        let mut tick = |world: &mut World| {
+            world.resource_mut::<Time>().advance_by(std::time::Duration::from_millis(500));
+            advance.run(world);
+            world.get::<DeviceStatus>(device).unwrap().clone()
+        };
+
+        // Paused coordinators freeze simulated time
+        let status = tick(&mut world);
+        assert_eq!(status.in_flight_count, 2);
+        assert!(status.progress.is_none());
+
+        *world.get_mut::<BufferState>(coordinator).unwrap() = BufferState::Executing {
+            current_index: 0,
+            completed_count: 0,
+        };
+        let status = tick(&mut world);
+        assert_eq!(status.in_flight_count, 1);
+        assert_eq!(status.completed_count, 1);
+        assert_eq!(status.progress.unwrap().point_index, 1);
+
+        // Finishing the run removes the simulator and the simulation marker
+        *world.get_mut::<BufferState>(coordinator).unwrap() = BufferState::Complete { total_executed: 1 };
+        cleanup.run(&mut world);
+        assert!(world.get_entity(device).is_err());
+        assert!(!world.entity(coordinator).contains::<SimulationMode>());
+    }
+}
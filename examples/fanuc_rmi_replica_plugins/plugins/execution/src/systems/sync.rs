@@ -1,65 +1,107 @@
 //! Synchronization systems for execution state.
 //!
 //! These systems sync internal buffer state to the synced ExecutionState component.
+//!
+//! Every coordinator is synced independently, using the devices among its own
+//! children, so several coordinators can execute at the same time.
+
+use std::collections::HashSet;
 
 use bevy::prelude::*;
+#[cfg(feature = "server")]
+use fanuc_replica_core::{WebhookEvent, WebhookEventKind};
 
-use crate::components::{BufferState, ExecutionCoordinator, ExecutionState, SystemState, ToolpathBuffer};
-use crate::systems::DeviceStatus;
+use crate::components::{
+    BufferDisplayData, BufferState, ExecutionCoordinator, ExecutionProgress, ExecutionState,
+    SimulationMode, SubsystemLag, SystemState, ToolpathBuffer,
+};
+use crate::systems::{find_primary_device, AuxSchedule, DeviceStatus, DeviceType, SimulatedMotion};
 
+/// Primary motion devices, with whether each is the dry-run simulator.
 #[cfg(feature = "server")]
-use fanuc_replica_core::ActiveSystem;
+type PrimaryDeviceQuery<'w, 's> =
+    Query<'w, 's, (&'static DeviceStatus, Has<SimulatedMotion>), With<crate::components::PrimaryMotion>>;
+
+/// Status of a coordinator's primary motion device (the simulator during a dry run).
+#[cfg(feature = "server")]
+fn primary_device_status<'a>(
+    children: Option<&Children>,
+    devices: &'a PrimaryDeviceQuery,
+    simulating: bool,
+) -> Option<&'a DeviceStatus> {
+    let device = find_primary_device(
+        children,
+        |child| devices.get(child).ok().map(|(_, is_simulator)| is_simulator),
+        simulating,
+    )?;
+    devices.get(device).ok().map(|(status, _)| status)
+}
 
-/// Sync BufferState to ExecutionState (both on System entity).
+/// Sync BufferState to ExecutionState (both on the coordinator entity).
 ///
 /// This system bridges the internal buffer state with the synced ExecutionState.
-/// It uses BufferState's `to_system_state()` and `available_actions()` methods
-/// to derive the UI-facing state and available actions.
+/// It uses BufferState's `to_system_state()` method to derive the UI-facing
+/// state; the available actions follow from it through `derive_actions`.
 ///
-/// The system:
-/// 1. Reads BufferState from the System entity
+/// For each coordinator, the system:
+/// 1. Reads BufferState from the coordinator entity
 /// 2. Uses `to_system_state()` to get the SystemState enum
-/// 3. Uses `available_actions()` to get the action flags
-/// 4. Uses `completed_count()` for progress tracking
-/// 5. Updates ExecutionState on System entity (synced to clients)
+/// 3. Uses `completed_count()` for progress tracking
+/// 4. Updates ExecutionState on the coordinator entity (synced to clients)
 #[cfg(feature = "server")]
 pub fn sync_buffer_state_to_execution_state(
-    mut system_query: Query<(&BufferState, &mut ExecutionState), With<ActiveSystem>>,
+    mut system_query: Query<(&BufferState, &mut ExecutionState)>,
 ) {
-    let Ok((buffer_state, mut exec_state)) = system_query.single_mut() else {
-        return; // No BufferState/ExecutionState on System entity
-    };
+    for (buffer_state, mut exec_state) in system_query.iter_mut() {
+        // Skip if in NoSource state (nothing loaded)
+        if exec_state.state == SystemState::NoSource {
+            continue;
+        }
+
+        // Use the consolidated methods from BufferState
+        let new_state = buffer_state.to_system_state();
+        let completed_count = buffer_state.completed_count().unwrap_or(0) as usize;
 
-    // Skip if in NoSource state (nothing loaded)
-    if exec_state.state == SystemState::NoSource {
-        return;
+        // Only update if something changed
+        let needs_update = exec_state.state != new_state || exec_state.points_executed != completed_count;
+
+        if needs_update {
+            exec_state.state = new_state;
+            exec_state.points_executed = completed_count;
+            exec_state.current_index = completed_count;
+        }
     }
+}
 
-    // Use the consolidated methods from BufferState
-    let new_state = buffer_state.to_system_state();
-    let completed_count = buffer_state.completed_count().unwrap_or(0) as usize;
-    let actions = buffer_state.available_actions();
-
-    // Only update if something changed
-    let needs_update = exec_state.state != new_state
-        || exec_state.points_executed != completed_count
-        || exec_state.can_load != actions.can_load
-        || exec_state.can_start != actions.can_start
-        || exec_state.can_pause != actions.can_pause
-        || exec_state.can_resume != actions.can_resume
-        || exec_state.can_stop != actions.can_stop
-        || exec_state.can_unload != actions.can_unload;
-
-    if needs_update {
-        exec_state.state = new_state;
-        exec_state.points_executed = completed_count;
-        exec_state.current_index = completed_count;
-        exec_state.can_load = actions.can_load;
-        exec_state.can_start = actions.can_start;
-        exec_state.can_pause = actions.can_pause;
-        exec_state.can_resume = actions.can_resume;
-        exec_state.can_stop = actions.can_stop;
-        exec_state.can_unload = actions.can_unload;
+/// Report a ProgramCompleted webhook event when a coordinator completes.
+///
+/// Each completion is reported once; dry runs are not reported.
+#[cfg(feature = "server")]
+pub fn emit_program_completed_webhooks(
+    coordinators: Query<(Entity, &BufferState, &ExecutionState, Option<&Name>), Changed<BufferState>>,
+    mut reported: Local<HashSet<Entity>>,
+    mut webhooks: MessageWriter<WebhookEvent>,
+) {
+    for (entity, buffer_state, exec_state, name) in coordinators.iter() {
+        let BufferState::Complete { total_executed } = buffer_state else {
+            reported.remove(&entity);
+            continue;
+        };
+        if exec_state.simulated || !reported.insert(entity) {
+            continue;
+        }
+
+        let program = exec_state.source_name.clone().unwrap_or_else(|| "program".to_string());
+        let system = name.map(|n| n.as_str().to_string()).unwrap_or_else(|| format!("{:?}", entity));
+        webhooks.write(
+            WebhookEvent::new(
+                WebhookEventKind::ProgramCompleted,
+                format!("{} completed on {} ({} points)", program, system, total_executed),
+            )
+            .with_data("program", &program)
+            .with_data("system", &system)
+            .with_data("points_executed", total_executed),
+        );
     }
 }
 
@@ -74,15 +116,21 @@ pub fn sync_buffer_state_to_execution_state(
 /// Notifications are handled separately by device-specific plugins.
 #[cfg(feature = "server")]
 pub fn sync_device_status_to_buffer_state(
-    mut system_query: Query<(&mut BufferState, &ToolpathBuffer, &ExecutionCoordinator)>,
-    device_query: Query<&DeviceStatus, With<crate::components::PrimaryMotion>>,
+    mut system_query: Query<(
+        &mut BufferState,
+        &ToolpathBuffer,
+        &ExecutionCoordinator,
+        Option<&Children>,
+        Has<SimulationMode>,
+    )>,
+    device_query: PrimaryDeviceQuery,
 ) {
-    // Get the device status from the primary motion device
-    let Ok(device_status) = device_query.single() else {
-        return; // No primary motion device
-    };
+    for (mut buffer_state, toolpath_buffer, coordinator, children, simulating) in system_query.iter_mut() {
+        // Get the device status from the coordinator's primary motion device
+        let Some(device_status) = primary_device_status(children, &device_query, simulating) else {
+            continue; // No primary motion device
+        };
 
-    for (mut buffer_state, toolpath_buffer, coordinator) in system_query.iter_mut() {
         // Extract current state info before matching to avoid borrow issues
         let (is_executing, current_index) = match &*buffer_state {
             BufferState::Executing { current_index, .. } => (true, *current_index),
@@ -128,3 +176,119 @@ pub fn sync_device_status_to_buffer_state(
     }
 }
 
+/// Sync buffer occupancy and flow control state to BufferDisplayData.
+///
+/// Reports how many points are buffered, how many are in-flight on the
+/// primary motion device, the effective lookahead window, and whether
+/// streaming is paused after a device rejection. BufferDisplayData is only
+/// marked changed when one of these values changes.
+#[cfg(feature = "server")]
+pub fn sync_buffer_occupancy_to_display(
+    mut system_query: Query<(
+        &ToolpathBuffer,
+        &ExecutionCoordinator,
+        &mut BufferDisplayData,
+        Option<&Children>,
+        Has<SimulationMode>,
+    )>,
+    device_query: PrimaryDeviceQuery,
+) {
+    for (buffer, coordinator, mut display, children, simulating) in system_query.iter_mut() {
+        let (in_flight, window) = match primary_device_status(children, &device_query, simulating) {
+            Some(status) => (
+                status.in_flight_count,
+                coordinator.effective_window(status.in_flight_capacity),
+            ),
+            None => (0, 0),
+        };
+
+        let needs_update = display.buffered != buffer.len()
+            || display.in_flight != in_flight
+            || display.lookahead_window != window
+            || display.flow_paused != coordinator.flow_paused;
+
+        if needs_update {
+            display.buffered = buffer.len();
+            display.in_flight = in_flight;
+            display.lookahead_window = window;
+            display.flow_paused = coordinator.flow_paused;
+        }
+    }
+}
+
+/// Sync execution telemetry to ExecutionProgress.
+///
+/// Progress is measured against the lines in BufferDisplayData. Lag is
+/// reported for the primary motion device (points in-flight) and for each
+/// auxiliary device type with timed commands still scheduled. ExecutionProgress
+/// is only marked changed when a value changes.
+#[cfg(feature = "server")]
+pub fn sync_execution_progress(
+    mut system_query: Query<(
+        &BufferState,
+        &ToolpathBuffer,
+        &BufferDisplayData,
+        Option<&AuxSchedule>,
+        Option<&Children>,
+        Has<SimulationMode>,
+        &mut ExecutionProgress,
+    )>,
+    device_query: PrimaryDeviceQuery,
+    device_types: Query<&DeviceType>,
+) {
+    for (buffer_state, toolpath_buffer, display, aux_schedule, children, simulating, mut progress) in
+        system_query.iter_mut()
+    {
+        let points_completed = match buffer_state.completed_count() {
+            Some(count) => count,
+            // Paused or re-validating: progress holds where it was
+            None if matches!(
+                buffer_state,
+                BufferState::Paused { .. } | BufferState::ValidatingForResume { .. }
+            ) =>
+            {
+                progress.points_completed
+            }
+            None => 0,
+        };
+
+        let mut subsystem_lag = Vec::new();
+        let motion_device = find_primary_device(
+            children,
+            |child| device_query.get(child).ok().map(|(_, is_simulator)| is_simulator),
+            simulating,
+        );
+        if let Some(device) = motion_device {
+            if let Ok((status, _)) = device_query.get(device) {
+                let name = device_types
+                    .get(device)
+                    .map_or_else(|_| "motion".to_string(), |device_type| device_type.0.clone());
+                subsystem_lag.push(SubsystemLag {
+                    name,
+                    points: status.in_flight_count,
+                });
+            }
+        }
+        for cmd in aux_schedule.into_iter().flat_map(|schedule| schedule.iter()) {
+            match subsystem_lag.iter_mut().find(|lag| lag.name == cmd.device_type) {
+                Some(lag) => lag.points += 1,
+                None => subsystem_lag.push(SubsystemLag {
+                    name: cmd.device_type.clone(),
+                    points: 1,
+                }),
+            }
+        }
+
+        let mut updated = ExecutionProgress {
+            points_completed,
+            total_points: toolpath_buffer.expected_total(),
+            subsystem_lag,
+            ..Default::default()
+        };
+        updated.measure(&display.lines);
+
+        if *progress != updated {
+            *progress = updated;
+        }
+    }
+}
@@ -8,6 +8,9 @@
 //! - ValidatingForResume: Used for Resume command, continues from paused index
 //!
 //! Includes timeout functionality to prevent stalled validations.
+//!
+//! Each coordinator validates independently: readiness is tracked in the
+//! coordinator's own `Subsystems` and the timeout in its `ValidationStartTime`.
 
 use bevy::prelude::*;
 use std::time::{Duration, Instant};
@@ -16,13 +19,13 @@ use crate::components::{
     BufferState, ExecutionCoordinator, ExecutionState, Subsystems, SystemState,
     VALIDATION_TIMEOUT,
 };
-use fanuc_replica_core::ActiveSystem;
 
-/// Resource to track when validation started.
+/// Component tracking when a coordinator's validation started.
 ///
 /// This is separate from BufferState because `Instant` cannot be serialized.
-/// The resource is created when entering Validating state and removed when exiting.
-#[derive(Resource)]
+/// It is inserted on the coordinator entity when entering Validating state and
+/// removed when exiting.
+#[derive(Component)]
 pub struct ValidationStartTime(pub Instant);
 
 impl Default for ValidationStartTime {
@@ -45,8 +48,8 @@ impl ValidationStartTime {
 
 /// Coordinate validation of all subsystems before execution.
 ///
-/// This system:
-/// 1. Only runs when BufferState::Validating or BufferState::ValidatingForResume
+/// For each coordinator, this system:
+/// 1. Only acts when BufferState::Validating or BufferState::ValidatingForResume
 /// 2. Checks for timeout first
 /// 3. Checks for any subsystem errors
 /// 4. Transitions to Executing if all ready (from index 0 or resume index)
@@ -57,21 +60,40 @@ impl ValidationStartTime {
 /// their readiness status.
 pub fn coordinate_validation(
     mut commands: Commands,
-    validation_start: Option<Res<ValidationStartTime>>,
-    mut systems: Query<
-        (
-            &ExecutionCoordinator,
-            &mut BufferState,
-            &Subsystems,
-            Option<&mut ExecutionState>,
-        ),
-        With<ActiveSystem>,
-    >,
+    mut coordinators: Query<(
+        Entity,
+        &ExecutionCoordinator,
+        &mut BufferState,
+        &Subsystems,
+        Option<&mut ExecutionState>,
+        Option<&ValidationStartTime>,
+    )>,
 ) {
-    let Ok((coordinator, mut buffer_state, subsystems, exec_state)) = systems.single_mut() else {
-        return;
-    };
+    for (entity, coordinator, buffer_state, subsystems, exec_state, validation_start) in
+        coordinators.iter_mut()
+    {
+        validate_coordinator(
+            &mut commands,
+            entity,
+            coordinator,
+            buffer_state,
+            subsystems,
+            exec_state,
+            validation_start,
+        );
+    }
+}
 
+/// Validation step for a single coordinator.
+fn validate_coordinator(
+    commands: &mut Commands,
+    entity: Entity,
+    coordinator: &ExecutionCoordinator,
+    mut buffer_state: Mut<BufferState>,
+    subsystems: &Subsystems,
+    exec_state: Option<Mut<ExecutionState>>,
+    validation_start: Option<&ValidationStartTime>,
+) {
     // Extract resume index if validating for resume, or None for initial start
     let resume_from_index = match *buffer_state {
         BufferState::Validating => Some(0), // Initial start from index 0
@@ -83,7 +105,7 @@ pub fn coordinate_validation(
     let Some(start_index) = resume_from_index else {
         // Clean up validation start time if we're not validating
         if validation_start.is_some() {
-            commands.remove_resource::<ValidationStartTime>();
+            commands.entity(entity).remove::<ValidationStartTime>();
         }
         return;
     };
@@ -95,8 +117,8 @@ pub fn coordinate_validation(
         Some(start) => start,
         None => {
             // This shouldn't happen if handle_start/handle_resume inserted it, but handle gracefully
-            commands.insert_resource(ValidationStartTime::default());
-            warn!("ValidationStartTime was missing, created new one");
+            commands.entity(entity).insert(ValidationStartTime::default());
+            warn!("ValidationStartTime was missing for '{}', created new one", coordinator.name);
             return; // Wait for next frame
         }
     };
@@ -119,9 +141,8 @@ pub fn coordinate_validation(
         };
         if let Some(mut exec) = exec_state {
             exec.state = SystemState::Error;
-            exec.update_available_actions();
         }
-        commands.remove_resource::<ValidationStartTime>();
+        commands.entity(entity).remove::<ValidationStartTime>();
         error!(
             "⏱️ Validation timeout for '{}': {}",
             coordinator.name, timeout_msg
@@ -136,9 +157,8 @@ pub fn coordinate_validation(
         };
         if let Some(mut exec) = exec_state {
             exec.state = SystemState::Error;
-            exec.update_available_actions();
         }
-        commands.remove_resource::<ValidationStartTime>();
+        commands.entity(entity).remove::<ValidationStartTime>();
         error!(
             "❌ Validation failed for '{}': {}",
             coordinator.name, error_msg
@@ -156,9 +176,8 @@ pub fn coordinate_validation(
             exec.state = SystemState::Running;
             exec.current_index = start_index as usize;
             exec.points_executed = start_index as usize;
-            exec.update_available_actions();
         }
-        commands.remove_resource::<ValidationStartTime>();
+        commands.entity(entity).remove::<ValidationStartTime>();
         if is_resume {
             info!(
                 "✅ Resume validation succeeded for '{}' in {:?}, resuming from index {}",
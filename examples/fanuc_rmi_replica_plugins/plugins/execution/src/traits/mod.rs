@@ -4,7 +4,7 @@ mod auxiliary_device;
 mod motion_device;
 
 pub use auxiliary_device::{AuxiliaryCommand, AuxiliaryDevice};
-pub use motion_device::MotionDevice;
+pub use motion_device::{MotionDevice, MotionProgress};
 
 use thiserror::Error;
 
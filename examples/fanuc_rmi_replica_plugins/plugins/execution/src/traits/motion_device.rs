@@ -5,6 +5,18 @@ use fanuc_replica_robotics::RobotPose;
 
 use super::DeviceError;
 
+/// Progress of the motion a device is currently executing.
+///
+/// Reported through `DeviceStatus::progress` so the coordinator can time
+/// auxiliary commands against the move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionProgress {
+    /// Index of the point the device is moving to
+    pub point_index: u32,
+    /// Seconds since the motion to this point started
+    pub elapsed: f32,
+}
+
 /// Trait for devices that execute motion commands.
 ///
 /// This is the primary abstraction for robot drivers. The motion device
@@ -70,6 +82,23 @@ pub trait MotionDevice {
     /// - Device is connected and operational
     fn ready_for_next(&self) -> bool;
 
+    /// Maximum number of motions the device can hold in-flight.
+    ///
+    /// The coordinator never streams more than this many points ahead of
+    /// the last confirmed completion, even if its lookahead window is larger.
+    fn in_flight_capacity(&self) -> u32 {
+        1 // Default: one motion at a time
+    }
+
+    /// Check if the device rejected (NACKed) a motion because it could not
+    /// accept it.
+    ///
+    /// While this returns true the coordinator stops streaming. Rejected
+    /// points are sent again once the device accepts motions.
+    fn rejected(&self) -> bool {
+        false // Default: device never rejects
+    }
+
     /// Check if a previously sent motion is confirmed complete.
     ///
     /// Called by the orchestrator to track completed_count.
@@ -78,6 +107,14 @@ pub trait MotionDevice {
         0 // Default: no tracking
     }
 
+    /// Progress of the motion currently being executed, if the device tracks it.
+    ///
+    /// Devices that return `None` are assumed to start each motion when the
+    /// previous one is confirmed complete.
+    fn motion_progress(&self) -> Option<MotionProgress> {
+        None // Default: no progress feedback
+    }
+
     /// Check if the device is connected.
     fn is_connected(&self) -> bool;
 
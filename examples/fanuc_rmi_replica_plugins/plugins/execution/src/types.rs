@@ -13,8 +13,22 @@ use serde::{Deserialize, Serialize};
 /// Request to start execution.
 ///
 /// Transitions: Ready/Completed/Stopped → Validating → Executing
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Start;
+///
+/// With `simulate` set, the program runs against the built-in kinematic
+/// simulator instead of the real robot (a dry run). ExecutionState and
+/// BufferDisplayData update exactly as they would on hardware.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Start {
+    #[serde(default)]
+    pub simulate: bool,
+}
+
+impl Start {
+    /// Start a dry run on the built-in simulator.
+    pub fn simulated() -> Self {
+        Self { simulate: true }
+    }
+}
 
 /// Response to Start request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +110,72 @@ impl ErrorResponse for Resume {
     }
 }
 
+// ============================================================================
+// StepOnce
+// ============================================================================
+
+/// Request to execute a single buffered point, then pause again.
+///
+/// Transitions: Paused → ValidatingForResume → Executing (one point) → Paused
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepOnce;
+
+/// Response to StepOnce request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepOnceResponse {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for StepOnce {
+    type ResponseMessage = StepOnceResponse;
+}
+
+impl ErrorResponse for StepOnce {
+    fn error_response(error: String) -> Self::ResponseMessage {
+        StepOnceResponse {
+            success: false,
+            error: Some(error),
+        }
+    }
+}
+
+// ============================================================================
+// ResumeFromLine
+// ============================================================================
+
+/// Request to restart execution at an arbitrary line of a static program.
+///
+/// `line` is the buffer index shown in the program table. Lines before it are
+/// treated as already executed. The robot first re-approaches the line from
+/// above at reduced speed before continuing with the program.
+///
+/// Transitions: Paused/Completed/Stopped/Error → ValidatingForResume → Executing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeFromLine {
+    pub line: u32,
+}
+
+/// Response to ResumeFromLine request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeFromLineResponse {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for ResumeFromLine {
+    type ResponseMessage = ResumeFromLineResponse;
+}
+
+impl ErrorResponse for ResumeFromLine {
+    fn error_response(error: String) -> Self::ResponseMessage {
+        ResumeFromLineResponse {
+            success: false,
+            error: Some(error),
+        }
+    }
+}
+
 // ============================================================================
 // Stop
 // ============================================================================
@@ -0,0 +1,248 @@
+//! Controller alarm monitoring.
+//!
+//! Polls the most recent alarm with FRC_ReadError. A new alarm is recorded in
+//! the `alarm_history` table and added to the robot's synced `ActiveAlarms`.
+//! Active alarms are cleared once `FrcGetStatus` stops reporting an error,
+//! i.e. after the controller has been reset. New alarms are also reported to
+//! webhooks subscribed to `AlarmRaised`.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::ecs::message::MessageReader;
+use bevy::time::common_conditions::on_timer;
+use bevy_tokio_tasks::TokioTasksRuntime;
+use fanuc_rmi::packets::{Command, CommandResponse, PacketPriority, ResponsePacket, SendPacket};
+use pl3xus::managers::network_request::Request;
+use pl3xus_sync::AppRequestRegistrationExt;
+use pl3xus_websockets::WebSocketProvider;
+use tokio::sync::broadcast;
+
+use fanuc_replica_core::{DatabaseResource, WebhookEvent, WebhookEventKind};
+use crate::connection::{FanucRobot, RmiDriver, RobotConnectionState};
+use crate::database;
+use crate::types::*;
+
+/// Most active alarms kept on the robot entity.
+const MAX_ACTIVE_ALARMS: usize = 50;
+
+/// Largest page `GetAlarmHistory` returns.
+const MAX_ALARM_PAGE_SIZE: u32 = 200;
+
+/// Alarm polling state for a connected robot.
+#[derive(Component, Default)]
+pub struct AlarmMonitor {
+    responses: Option<broadcast::Receiver<ResponsePacket>>,
+    /// Text of the last alarm read; None until the first read after connecting
+    last_seen: Option<String>,
+}
+
+/// Split "SRVO-001 Operator panel E-stop" into code and message.
+fn parse_alarm_text(text: &str) -> (String, String) {
+    let text = text.trim();
+    match text.split_once(char::is_whitespace) {
+        Some((code, message)) if code.contains('-') => (code.to_string(), message.trim().to_string()),
+        _ => (String::new(), text.to_string()),
+    }
+}
+
+/// Request the latest alarm from each connected robot.
+fn poll_alarms(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    mut commands: Commands,
+    mut robots: Query<(Entity, &RobotConnectionState, Option<&RmiDriver>, Option<&mut AlarmMonitor>), With<FanucRobot>>,
+) {
+    // Enter the Tokio runtime context so send_packet can use tokio::spawn
+    let _guard = tokio_runtime.runtime().enter();
+
+    for (entity, state, driver, monitor) in robots.iter_mut() {
+        let Some(driver) = driver.filter(|_| *state == RobotConnectionState::Connected) else {
+            // Start over with a fresh baseline after a reconnect
+            if monitor.is_some() {
+                commands.entity(entity).remove::<AlarmMonitor>();
+            }
+            continue;
+        };
+
+        let Some(mut monitor) = monitor else {
+            commands.entity(entity).insert(AlarmMonitor {
+                responses: Some(driver.0.response_tx.subscribe()),
+                last_seen: None,
+            });
+            continue;
+        };
+        if monitor.responses.is_none() {
+            monitor.responses = Some(driver.0.response_tx.subscribe());
+        }
+
+        let command = Command::FrcReadError(fanuc_rmi::commands::FrcReadError { count: 1 });
+        if let Err(e) = driver.0.send_packet(SendPacket::Command(command), PacketPriority::Standard) {
+            debug!("Failed to poll alarms: {}", e);
+        }
+    }
+}
+
+/// Record new alarms and clear active alarms after a reset.
+fn process_alarm_responses(
+    mut robots: Query<(&mut AlarmMonitor, &mut ActiveAlarms, &RobotStatus, &ConnectionState), With<FanucRobot>>,
+    db: Option<Res<DatabaseResource>>,
+    mut webhooks: MessageWriter<WebhookEvent>,
+) {
+    for (mut monitor, mut active, status, conn_state) in robots.iter_mut() {
+        let mut latest = None;
+        if let Some(responses) = monitor.responses.as_mut() {
+            while let Ok(response) = responses.try_recv() {
+                if let ResponsePacket::CommandResponse(CommandResponse::FrcReadError(resp)) = response {
+                    if resp.error_id == 0 && !resp.error_data.trim().is_empty() {
+                        latest = Some(resp.error_data);
+                    }
+                }
+            }
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let robot_name = &conn_state.robot_name;
+
+        if let Some(text) = latest {
+            let first_read = monitor.last_seen.is_none();
+            let changed = monitor.last_seen.as_deref() != Some(text.as_str());
+            monitor.last_seen = Some(text.clone());
+
+            // The first read only establishes a baseline, unless the controller
+            // is in an error state right now
+            if changed && (!first_read || status.error_message.is_some()) {
+                let (code, message) = parse_alarm_text(&text);
+                let mut alarm = AlarmRecord {
+                    id: 0,
+                    robot_name: robot_name.clone(),
+                    severity: AlarmSeverity::from_code(&code),
+                    code,
+                    message,
+                    occurred_at: now.clone(),
+                    cleared_at: None,
+                };
+                warn!("🚨 Alarm on {}: {} {}", robot_name, alarm.code, alarm.message);
+
+                if let Some(db) = db.as_ref() {
+                    let conn = db.connection();
+                    let conn = conn.lock().unwrap();
+                    match database::insert_alarm(&conn, &alarm) {
+                        Ok(id) => alarm.id = id,
+                        Err(e) => error!("Failed to record alarm: {}", e),
+                    }
+                }
+
+                webhooks.write(
+                    WebhookEvent::new(
+                        WebhookEventKind::AlarmRaised,
+                        format!("{} {} on {}", alarm.code, alarm.message, robot_name),
+                    )
+                    .with_data("robot", robot_name)
+                    .with_data("code", &alarm.code)
+                    .with_data("message", &alarm.message)
+                    .with_data("severity", format!("{:?}", alarm.severity))
+                    .with_data("occurred_at", &alarm.occurred_at),
+                );
+
+                active.alarms.insert(0, alarm);
+                active.alarms.truncate(MAX_ACTIVE_ALARMS);
+            }
+        }
+
+        if status.error_message.is_none() && !active.alarms.is_empty() {
+            info!("✅ Clearing {} active alarm(s) on {}", active.alarms.len(), robot_name);
+            if let Some(db) = db.as_ref() {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                if let Err(e) = database::clear_alarms(&conn, robot_name, &now) {
+                    error!("Failed to clear alarms: {}", e);
+                }
+            }
+            active.alarms.clear();
+        }
+    }
+}
+
+/// Handle GetAlarmHistory request - returns a page of alarm history.
+fn handle_get_alarm_history(
+    mut requests: MessageReader<Request<GetAlarmHistory>>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let inner = request.get_request();
+        let page_size = match inner.page_size {
+            0 => 50,
+            n => n.min(MAX_ALARM_PAGE_SIZE),
+        };
+        info!("📋 Handling GetAlarmHistory page {} (size {})", inner.page, page_size);
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                database::get_alarm_history(
+                    &conn,
+                    inner.robot_name.as_deref(),
+                    inner.page.saturating_mul(page_size),
+                    page_size,
+                )
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok((alarms, total)) => AlarmHistoryResponse {
+                alarms,
+                total,
+                page: inner.page,
+                page_size,
+                error: None,
+            },
+            Err(e) => {
+                error!("Failed to get alarm history: {}", e);
+                AlarmHistoryResponse {
+                    page: inner.page,
+                    page_size,
+                    error: Some(e.to_string()),
+                    ..Default::default()
+                }
+            }
+        };
+
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Plugin that adds alarm monitoring and history.
+pub struct AlarmPlugin;
+
+impl Plugin for AlarmPlugin {
+    fn build(&self, app: &mut App) {
+        app.request::<GetAlarmHistory, WebSocketProvider>().register();
+
+        app.add_systems(Update, (
+            poll_alarms.run_if(on_timer(Duration::from_secs(1))),
+            process_alarm_responses,
+            handle_get_alarm_history,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alarm_text() {
+        let (code, message) = parse_alarm_text("SRVO-001 Operator panel E-stop ");
+        assert_eq!(code, "SRVO-001");
+        assert_eq!(message, "Operator panel E-stop");
+        assert_eq!(AlarmSeverity::from_code(&code), AlarmSeverity::Servo);
+
+        let (code, message) = parse_alarm_text("Unexpected controller fault");
+        assert_eq!(code, "");
+        assert_eq!(message, "Unexpected controller fault");
+        assert_eq!(AlarmSeverity::from_code(&code), AlarmSeverity::Warning);
+    }
+}
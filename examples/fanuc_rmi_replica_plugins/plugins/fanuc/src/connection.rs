@@ -2,6 +2,13 @@
 //!
 //! Handles connecting to and disconnecting from FANUC robots via RMI driver.
 //!
+//! A connection supervisor watches connected robots for driver failure (the
+//! response channel closing, or no responses within the health timeout). On
+//! failure the robot is torn down to Disconnected and reconnected with
+//! exponential backoff per `ReconnectPolicy`. In-flight motion is dropped,
+//! not replayed; configuration commands needed to restore the robot's state
+//! are deferred in `DeferredPackets` and flushed once it is back.
+//!
 //! IMPORTANT: Only the client who has control of the apparatus/system can
 //! connect to or disconnect from the robot. The robot connection is a shared
 //! resource visible to all clients, but only controllable by the controller.
@@ -9,17 +16,19 @@
 use bevy::prelude::*;
 use bevy::ecs::message::MessageReader;
 use bevy_tokio_tasks::TokioTasksRuntime;
-use pl3xus::AppNetworkMessage;
+use pl3xus::{AppHealthExt, AppNetworkMessage, HealthCheck};
 use pl3xus::managers::network_request::Request;
 use pl3xus_sync::control::EntityControl;
 use pl3xus_sync::AppRequestRegistrationExt;
 use pl3xus_websockets::WebSocketProvider;
 use std::sync::Arc;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
 use fanuc_rmi::drivers::{FanucDriver, FanucDriverConfig, LogLevel};
+use fanuc_rmi::packets::{PacketPriority, SendPacket};
 use crate::types::*;
 use crate::database;
-use crate::motion::FanucMotionDevice;
+use crate::motion::{FanucInFlightInstructions, FanucMotionDevice};
 use fanuc_replica_core::{DatabaseResource, ActiveSystem};
 use fanuc_replica_execution::{DeviceConnected, DeviceStatus, PrimaryMotion};
 
@@ -57,6 +66,73 @@ pub struct RmiExecutionResponseChannel(pub broadcast::Receiver<fanuc_rmi::packet
 #[derive(Component)]
 pub struct RmiSentInstructionChannel(pub broadcast::Receiver<fanuc_rmi::packets::SentInstructionInfo>);
 
+/// Response channel for connection health monitoring (separate subscription).
+#[derive(Component)]
+pub struct RmiHealthChannel(pub broadcast::Receiver<fanuc_rmi::packets::ResponsePacket>);
+
+/// When a connected robot last responded.
+#[derive(Component, Debug, Clone)]
+pub struct ConnectionHealth {
+    /// Time of the last response (seconds)
+    pub last_response: f64,
+}
+
+/// Pending reconnect after the driver connection dropped.
+///
+/// Present from the failure until a reconnect succeeds, the policy gives up,
+/// or the controlling client disconnects.
+#[derive(Component, Debug, Clone)]
+pub struct ReconnectSchedule {
+    /// Attempt number of the next reconnect (1-based)
+    pub attempt: u32,
+    /// When the next attempt starts (seconds)
+    pub next_attempt_at: f64,
+}
+
+/// Commands held while the robot is unreachable, sent once it reconnects.
+///
+/// Only use this for commands that are safe to apply late (configuration,
+/// not motion).
+#[derive(Component, Debug, Default)]
+pub struct DeferredPackets(pub Vec<SendPacket>);
+
+/// Reconnect behaviour after an unexpected driver failure.
+#[derive(Resource, Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Seconds without any response before a connected driver counts as failed
+    pub health_timeout: f64,
+    /// Delay before the first reconnect attempt (seconds)
+    pub initial_delay: f64,
+    /// Upper bound for the doubling delay between attempts (seconds)
+    pub max_delay: f64,
+    /// Give up after this many failed attempts (None = retry forever)
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            health_timeout: 5.0,
+            initial_delay: 1.0,
+            max_delay: 30.0,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before the given attempt (1-based).
+    pub fn delay(&self, attempt: u32) -> f64 {
+        let exponent = attempt.saturating_sub(1).min(16) as i32;
+        (self.initial_delay * 2f64.powi(exponent)).min(self.max_delay)
+    }
+
+    /// Whether another attempt is allowed after `attempt` failed.
+    pub fn allows_retry(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt < max)
+    }
+}
+
 /// Robot connection state (entity-based state machine).
 #[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[allow(dead_code)]
@@ -92,6 +168,12 @@ impl Plugin for RobotConnectionPlugin {
         // DisconnectRobot remains a simple message (no response needed)
         app.register_network_message::<DisconnectRobot, WebSocketProvider>();
 
+        app.init_resource::<ReconnectPolicy>();
+
+        // Reported by /readyz, but a disconnected robot doesn't make the
+        // server unready: clients are needed to reconnect it
+        app.health_check("robots", robot_health);
+
         // Add connection systems
         app.add_systems(Update, (
             handle_connect_requests,
@@ -99,9 +181,26 @@ impl Plugin for RobotConnectionPlugin {
             handle_disconnect_requests,
             load_default_configuration,
         ));
+
+        // Connection supervisor
+        app.add_systems(Update, (
+            monitor_connection_health,
+            drive_reconnects,
+            flush_deferred_packets,
+        ).chain());
     }
 }
 
+/// How many robots are connected, e.g. `1/2 connected`.
+fn robot_health(world: &World) -> HealthCheck {
+    let Some(mut robots) = world.try_query_filtered::<&RobotConnectionState, With<FanucRobot>>() else {
+        return HealthCheck::ok_with("0/0 connected");
+    };
+    let states: Vec<RobotConnectionState> = robots.iter(world).copied().collect();
+    let connected = states.iter().filter(|state| **state == RobotConnectionState::Connected).count();
+    HealthCheck::ok_with(format!("{}/{} connected", connected, states.len()))
+}
+
 // ============================================================================
 // Systems
 // ============================================================================
@@ -143,8 +242,8 @@ fn handle_connect_requests(
         };
 
         // Check if client has control of the System
-        if system_control.client_id != client_id {
-            let err = format!("No control of System (held by {:?})", system_control.client_id);
+        if system_control.client.connection != client_id {
+            let err = format!("No control of System (held by {:?})", system_control.client.connection);
             warn!("ConnectToRobot rejected from {:?}: {}", client_id, err);
             send_error(request.clone(), err);
             continue;
@@ -155,7 +254,7 @@ fn handle_connect_requests(
 
         if !robot_exists {
             // No robot entity - spawn one as child of System with connection details from database or message
-            let (connection_details, jog_settings, io_config_state) = if let Some(conn_id) = msg.connection_id {
+            let (connection_details, jog_settings, io_config_state, envelope) = if let Some(conn_id) = msg.connection_id {
                 // Load connection details from database
                 if let Some(ref db_res) = db {
                     let conn = db_res.connection();
@@ -190,7 +289,14 @@ fn handle_connect_requests(
                                     IoConfigState::default()
                                 }
                             };
-                            (details, jog, io_config)
+                            // Load workspace limits from database
+                            let envelope = database::get_workspace_envelope(&conn, conn_id)
+                                .unwrap_or_else(|e| {
+                                    warn!("Failed to load workspace envelope, using defaults: {}", e);
+                                    None
+                                })
+                                .unwrap_or_default();
+                            (details, jog, io_config, envelope)
                         }
                         Ok(None) => {
                             let err = format!("Robot connection {} not found in database", conn_id);
@@ -224,7 +330,7 @@ fn handle_connect_requests(
                     port: msg.port,
                     name: msg.name.clone().unwrap_or_else(|| format!("{}:{}", msg.addr, msg.port)),
                 };
-                (details, JogSettingsState::default(), IoConfigState::default())
+                (details, JogSettingsState::default(), IoConfigState::default(), WorkspaceEnvelope::default())
             };
 
             // Build initial ConnectionState
@@ -254,6 +360,9 @@ fn handle_connect_requests(
                 // Additional synced components (split due to tuple limit)
                 ActiveConfigState::default(),
                 ActiveConfigSyncState::new(),  // Tracks sync status with robot
+                ActiveAlarms::default(),
+                OverrideState::default(),
+                envelope,
                 jog_settings,
             )).insert((
                 // Execution system components for motion command handling
@@ -375,7 +484,11 @@ fn handle_connecting_state(
                     // 3. Initializes (which resets sequence counter to 1)
                     if let Err(e) = driver.startup_sequence().await {
                         error!("❌ Robot startup sequence failed: {}", e);
+                        let error = format!("Startup sequence failed: {}", e);
                         ctx.run_on_main_thread(move |ctx| {
+                            if reschedule_reconnect(ctx.world, entity, error) {
+                                return;
+                            }
                             if let Ok(mut entity_mut) = ctx.world.get_entity_mut(entity) {
                                 entity_mut.remove::<ConnectionInProgress>();
                                 entity_mut.insert(RobotConnectionState::Disconnected);
@@ -393,10 +506,15 @@ fn handle_connecting_state(
                     let polling_response_rx = driver_arc.response_tx.subscribe();
                     let execution_response_rx = driver_arc.response_tx.subscribe();
                     let sent_instruction_rx = driver_arc.sent_instruction_tx.subscribe();
+                    let health_rx = driver_arc.response_tx.subscribe();
 
                     ctx.run_on_main_thread(move |ctx| {
+                        let now = ctx.world.resource::<Time>().elapsed_secs_f64();
                         if let Ok(mut entity_mut) = ctx.world.get_entity_mut(entity) {
                             entity_mut.remove::<ConnectionInProgress>();
+                            let reconnected = entity_mut.take::<ReconnectSchedule>().is_some();
+                            entity_mut.insert(RmiHealthChannel(health_rx));
+                            entity_mut.insert(ConnectionHealth { last_response: now });
                             entity_mut.insert(RmiDriver(driver_arc.clone()));
                             entity_mut.insert(RmiResponseChannel(polling_response_rx));
                             entity_mut.insert(RmiExecutionResponseChannel(execution_response_rx));
@@ -416,6 +534,9 @@ fn handle_connecting_state(
                                 conn_state.robot_name = robot_name.clone();
                                 conn_state.connection_name = Some(robot_name);
                                 conn_state.tp_initialized = true;
+                                conn_state.reconnecting = false;
+                                conn_state.reconnect_attempts = 0;
+                                conn_state.last_error = None;
                             }
 
                             // Update DeviceStatus for orchestrator
@@ -426,16 +547,25 @@ fn handle_connecting_state(
                                 device_status.reset_in_flight();
                             }
 
-                            // Add marker to load default configuration
-                            entity_mut.insert(NeedsDefaultConfigLoad { connection_id });
+                            if reconnected {
+                                // Keep the active configuration; DeferredPackets restores it
+                                info!("✅ Robot {:?} reconnected", entity);
+                            } else {
+                                // Add marker to load default configuration
+                                entity_mut.insert(NeedsDefaultConfigLoad { connection_id });
 
-                            info!("✅ Robot {:?} connected successfully", entity);
+                                info!("✅ Robot {:?} connected successfully", entity);
+                            }
                         }
                     }).await;
                 }
                 Err(e) => {
                     error!("❌ Connection failed: {}", e);
+                    let error = e.to_string();
                     ctx.run_on_main_thread(move |ctx| {
+                        if reschedule_reconnect(ctx.world, entity, error.clone()) {
+                            return;
+                        }
                         if let Ok(mut entity_mut) = ctx.world.get_entity_mut(entity) {
                             entity_mut.remove::<ConnectionInProgress>();
                             entity_mut.insert(RobotConnectionState::Disconnected);
@@ -447,6 +577,7 @@ fn handle_connecting_state(
                                 conn_state.active_connection_id = None;
                                 conn_state.robot_name = String::new();
                                 conn_state.connection_name = None;
+                                conn_state.last_error = Some(error);
                             }
                         }
                     }).await;
@@ -462,9 +593,16 @@ fn handle_connecting_state(
 /// This properly notifies the FANUC controller before disconnecting.
 fn handle_disconnect_requests(
     tokio: Res<TokioTasksRuntime>,
+    mut commands: Commands,
     mut disconnect_events: MessageReader<pl3xus::NetworkData<DisconnectRobot>>,
     system_query: Query<&EntityControl, With<ActiveSystem>>,
-    mut robots: Query<(Entity, &RmiDriver, &mut RobotConnectionState, &mut ConnectionState), With<FanucRobot>>,
+    mut robots: Query<(
+        Entity,
+        Option<&RmiDriver>,
+        &mut RobotConnectionState,
+        &mut ConnectionState,
+        Has<ReconnectSchedule>,
+    ), With<FanucRobot>>,
 ) {
     for event in disconnect_events.read() {
         let client_id = *event.source();
@@ -476,13 +614,28 @@ fn handle_disconnect_requests(
             continue;
         };
 
-        if system_control.client_id != client_id {
-            warn!("DisconnectRobot rejected from {:?}: No control of System (held by {:?})", client_id, system_control.client_id);
+        if system_control.client.connection != client_id {
+            warn!("DisconnectRobot rejected from {:?}: No control of System (held by {:?})", client_id, system_control.client.connection);
             continue;
         }
 
-        if let Ok((entity, driver, mut state, mut conn_state)) = robots.single_mut() {
-            if *state == RobotConnectionState::Connected {
+        if let Ok((entity, driver, mut state, mut conn_state, reconnecting)) = robots.single_mut() {
+            if reconnecting && *state != RobotConnectionState::Connected {
+                // Stop retrying; an attempt already underway is treated as a fresh connect
+                commands.entity(entity).remove::<(ReconnectSchedule, DeferredPackets)>();
+                *state = RobotConnectionState::Disconnected;
+                conn_state.robot_connecting = false;
+                conn_state.reconnecting = false;
+                conn_state.reconnect_attempts = 0;
+                conn_state.robot_addr = String::new();
+                conn_state.robot_name = String::new();
+                conn_state.connection_name = None;
+                conn_state.active_connection_id = None;
+                info!("🔌 Reconnect of robot {:?} cancelled", entity);
+                continue;
+            }
+
+            if let (Some(driver), RobotConnectionState::Connected) = (driver, *state) {
                 // Set state to Disconnecting while we clean up
                 *state = RobotConnectionState::Disconnecting;
                 conn_state.robot_connected = false;
@@ -510,6 +663,7 @@ fn handle_disconnect_requests(
                             entity_mut.remove::<RmiResponseChannel>();
                             entity_mut.remove::<RmiExecutionResponseChannel>();
                             entity_mut.remove::<RmiSentInstructionChannel>();
+                            entity_mut.remove::<(RmiHealthChannel, ConnectionHealth, DeferredPackets)>();
                             entity_mut.remove::<DeviceConnected>(); // For execution lifecycle
                             entity_mut.insert(RobotConnectionState::Disconnected);
 
@@ -632,3 +786,201 @@ fn load_default_configuration(
         }
     }
 }
+
+// ============================================================================
+// Connection supervisor
+// ============================================================================
+
+/// Detect driver failure on connected robots and tear them down for reconnect.
+///
+/// A driver has failed when its response channel closes, or when nothing
+/// has been received for `ReconnectPolicy::health_timeout` (polling keeps a
+/// healthy robot responding every 100ms).
+fn monitor_connection_health(
+    time: Res<Time>,
+    policy: Res<ReconnectPolicy>,
+    mut commands: Commands,
+    mut in_flight: ResMut<FanucInFlightInstructions>,
+    mut robots: Query<(
+        Entity,
+        &mut RmiHealthChannel,
+        &mut ConnectionHealth,
+        &mut RobotConnectionState,
+        &mut ConnectionState,
+        &mut DeviceStatus,
+        &ActiveConfigState,
+    ), With<FanucRobot>>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for (entity, mut channel, mut health, mut state, mut conn_state, mut device_status, active_config) in robots.iter_mut() {
+        if *state != RobotConnectionState::Connected {
+            continue;
+        }
+
+        let mut closed = false;
+        loop {
+            match channel.0.try_recv() {
+                Ok(_) | Err(TryRecvError::Lagged(_)) => health.last_response = now,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+
+        let error = if closed {
+            "Driver connection closed".to_string()
+        } else if now - health.last_response > policy.health_timeout {
+            format!("No response from robot for {:.1}s", now - health.last_response)
+        } else {
+            continue;
+        };
+
+        warn!("⚠️ Robot {:?} connection lost: {} - reconnecting", entity, error);
+
+        // Motion in flight is lost with the connection; never replay it
+        in_flight.clear_entity(entity);
+        device_status.is_connected = false;
+        device_status.reset_in_flight();
+        device_status.error = Some(format!("Connection lost: {}", error));
+
+        // Restore the active frame/tool once the robot is back
+        let restore_frame_tool: SendPacket = fanuc_rmi::dto::SendPacket::Command(
+            fanuc_rmi::dto::Command::FrcSetUFrameUTool(fanuc_rmi::dto::FrcSetUFrameUTool {
+                group: 1,
+                u_frame_number: active_config.u_frame_number as u8,
+                u_tool_number: active_config.u_tool_number as u8,
+            }),
+        ).into();
+
+        commands.entity(entity)
+            .remove::<(RmiDriver, RmiResponseChannel, RmiExecutionResponseChannel, RmiSentInstructionChannel)>()
+            .remove::<(RmiHealthChannel, ConnectionHealth, DeviceConnected)>()
+            .insert((
+                ReconnectSchedule {
+                    attempt: 1,
+                    next_attempt_at: now + policy.delay(1),
+                },
+                DeferredPackets(vec![restore_frame_tool]),
+            ));
+
+        *state = RobotConnectionState::Disconnected;
+        conn_state.robot_connected = false;
+        conn_state.robot_connecting = true;
+        conn_state.reconnecting = true;
+        conn_state.reconnect_attempts = 0;
+        conn_state.last_error = Some(error);
+    }
+}
+
+/// Start due reconnect attempts.
+fn drive_reconnects(
+    time: Res<Time>,
+    mut robots: Query<(
+        Entity,
+        &ReconnectSchedule,
+        &mut RobotConnectionState,
+        &mut ConnectionState,
+    ), (With<FanucRobot>, Without<ConnectionInProgress>)>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for (entity, schedule, mut state, mut conn_state) in robots.iter_mut() {
+        if *state != RobotConnectionState::Disconnected || now < schedule.next_attempt_at {
+            continue;
+        }
+        info!("🔄 Reconnecting robot {:?} (attempt {})", entity, schedule.attempt);
+        *state = RobotConnectionState::Connecting;
+        conn_state.robot_connecting = true;
+        conn_state.reconnect_attempts = schedule.attempt;
+    }
+}
+
+/// Send commands deferred during an outage once the robot is connected again.
+fn flush_deferred_packets(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    mut robots: Query<(Entity, &RmiDriver, &RobotConnectionState, &mut DeferredPackets), With<FanucRobot>>,
+) {
+    // Enter the Tokio runtime context so send_packet can use tokio::spawn
+    let _guard = tokio_runtime.runtime().enter();
+
+    for (entity, driver, state, mut deferred) in robots.iter_mut() {
+        if *state != RobotConnectionState::Connected || deferred.0.is_empty() {
+            continue;
+        }
+        info!("📤 Flushing {} deferred command(s) to robot {:?}", deferred.0.len(), entity);
+        for packet in deferred.0.drain(..) {
+            if let Err(e) = driver.0.send_packet(packet, PacketPriority::Immediate) {
+                error!("❌ Failed to send deferred command: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Schedule the next reconnect after a failed attempt.
+///
+/// Returns false if the robot was not reconnecting, or the policy has given
+/// up, so the caller should handle the failure as a manual connect failure.
+fn reschedule_reconnect(world: &mut World, entity: Entity, error: String) -> bool {
+    let now = world.resource::<Time>().elapsed_secs_f64();
+    let policy = world.resource::<ReconnectPolicy>().clone();
+    let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+        return false;
+    };
+    let Some(mut schedule) = entity_mut.take::<ReconnectSchedule>() else {
+        return false;
+    };
+
+    if !policy.allows_retry(schedule.attempt) {
+        warn!("❌ Giving up reconnecting robot {:?} after {} attempts", entity, schedule.attempt);
+        entity_mut.remove::<DeferredPackets>();
+        if let Some(mut conn_state) = entity_mut.get_mut::<ConnectionState>() {
+            conn_state.reconnecting = false;
+        }
+        return false;
+    }
+
+    schedule.attempt += 1;
+    schedule.next_attempt_at = now + policy.delay(schedule.attempt);
+    info!(
+        "🔄 Reconnect of robot {:?} failed ({}); next attempt in {:.0}s",
+        entity, error, policy.delay(schedule.attempt)
+    );
+
+    entity_mut.remove::<ConnectionInProgress>();
+    entity_mut.insert((schedule, RobotConnectionState::Disconnected));
+    if let Some(mut conn_state) = entity_mut.get_mut::<ConnectionState>() {
+        conn_state.robot_connected = false;
+        conn_state.robot_connecting = true;
+        conn_state.last_error = Some(error);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_delay_doubles_up_to_max() {
+        let policy = ReconnectPolicy::default();
+        assert_eq!(policy.delay(1), 1.0);
+        assert_eq!(policy.delay(2), 2.0);
+        assert_eq!(policy.delay(4), 8.0);
+        assert_eq!(policy.delay(10), 30.0);
+        assert_eq!(policy.delay(u32::MAX), 30.0);
+    }
+
+    #[test]
+    fn test_reconnect_gives_up_after_max_attempts() {
+        let policy = ReconnectPolicy {
+            max_attempts: Some(3),
+            ..Default::default()
+        };
+        assert!(policy.allows_retry(2));
+        assert!(!policy.allows_retry(3));
+        assert!(ReconnectPolicy::default().allows_retry(1000));
+    }
+}
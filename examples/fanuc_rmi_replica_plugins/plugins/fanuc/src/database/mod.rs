@@ -38,5 +38,12 @@ pub use queries::{
     // I/O Config
     get_io_config,
     update_io_config,
+    // Workspace Envelope
+    get_workspace_envelope,
+    save_workspace_envelope,
+    // Alarm History
+    insert_alarm,
+    clear_alarms,
+    get_alarm_history,
 };
 
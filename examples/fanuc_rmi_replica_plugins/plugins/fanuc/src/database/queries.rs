@@ -506,4 +506,123 @@ pub fn update_io_config(conn: &Connection, robot_connection_id: i64, configs: &[
         )?;
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+// ============================================================================
+// Workspace Envelope
+// ============================================================================
+
+/// Get the workspace envelope saved for a robot connection.
+pub fn get_workspace_envelope(conn: &Connection, robot_connection_id: i64) -> anyhow::Result<Option<WorkspaceEnvelope>> {
+    let result = conn.query_row(
+        "SELECT enabled, mode, x_min, x_max, y_min, y_max, z_min, z_max
+         FROM workspace_envelopes WHERE robot_connection_id = ?",
+        [robot_connection_id],
+        |row| {
+            let mode: String = row.get(1)?;
+            Ok(WorkspaceEnvelope {
+                enabled: row.get(0)?,
+                mode: if mode == "clamp" { EnvelopeMode::Clamp } else { EnvelopeMode::Reject },
+                x_min: row.get(2)?,
+                x_max: row.get(3)?,
+                y_min: row.get(4)?,
+                y_max: row.get(5)?,
+                z_min: row.get(6)?,
+                z_max: row.get(7)?,
+            })
+        },
+    );
+    match result {
+        Ok(envelope) => Ok(Some(envelope)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Save the workspace envelope for a robot connection.
+pub fn save_workspace_envelope(conn: &Connection, robot_connection_id: i64, envelope: &WorkspaceEnvelope) -> anyhow::Result<()> {
+    let mode = match envelope.mode {
+        EnvelopeMode::Clamp => "clamp",
+        EnvelopeMode::Reject => "reject",
+    };
+    conn.execute(
+        "INSERT OR REPLACE INTO workspace_envelopes
+         (robot_connection_id, enabled, mode, x_min, x_max, y_min, y_max, z_min, z_max)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![
+            robot_connection_id,
+            envelope.enabled,
+            mode,
+            envelope.x_min,
+            envelope.x_max,
+            envelope.y_min,
+            envelope.y_max,
+            envelope.z_min,
+            envelope.z_max,
+        ],
+    )?;
+    Ok(())
+}
+
+// ============================================================================
+// Alarm History
+// ============================================================================
+
+/// Record a new alarm, returning its id.
+pub fn insert_alarm(conn: &Connection, alarm: &AlarmRecord) -> anyhow::Result<i64> {
+    conn.execute(
+        "INSERT INTO alarm_history (robot_name, code, message, severity, occurred_at)
+         VALUES (?, ?, ?, ?, ?)",
+        rusqlite::params![
+            alarm.robot_name,
+            alarm.code,
+            alarm.message,
+            alarm.severity.as_str(),
+            alarm.occurred_at,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Mark all uncleared alarms of a robot as cleared.
+pub fn clear_alarms(conn: &Connection, robot_name: &str, cleared_at: &str) -> anyhow::Result<usize> {
+    let count = conn.execute(
+        "UPDATE alarm_history SET cleared_at = ? WHERE robot_name = ? AND cleared_at IS NULL",
+        rusqlite::params![cleared_at, robot_name],
+    )?;
+    Ok(count)
+}
+
+/// Get a page of alarm history, newest first, with the total count.
+pub fn get_alarm_history(
+    conn: &Connection,
+    robot_name: Option<&str>,
+    offset: u32,
+    limit: u32,
+) -> anyhow::Result<(Vec<AlarmRecord>, u32)> {
+    let total: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM alarm_history WHERE ?1 IS NULL OR robot_name = ?1",
+        [robot_name],
+        |row| row.get(0),
+    )?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, robot_name, code, message, severity, occurred_at, cleared_at
+         FROM alarm_history WHERE ?1 IS NULL OR robot_name = ?1
+         ORDER BY occurred_at DESC, id DESC LIMIT ?2 OFFSET ?3"
+    )?;
+
+    let alarms = stmt.query_map(rusqlite::params![robot_name, limit, offset], |row| {
+        Ok(AlarmRecord {
+            id: row.get(0)?,
+            robot_name: row.get(1)?,
+            code: row.get(2)?,
+            message: row.get(3)?,
+            severity: AlarmSeverity::parse(&row.get::<_, String>(4)?),
+            occurred_at: row.get(5)?,
+            cleared_at: row.get(6)?,
+        })
+    })?.collect::<Result<Vec<_>, _>>()?;
+
+    Ok((alarms, total))
+}
@@ -81,6 +81,41 @@ impl DatabaseInit for FanucDatabaseInit {
             [],
         )?;
 
+        // Software workspace limits per robot connection
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS workspace_envelopes (
+                robot_connection_id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 0,
+                mode TEXT NOT NULL DEFAULT 'reject',
+                x_min REAL NOT NULL,
+                x_max REAL NOT NULL,
+                y_min REAL NOT NULL,
+                y_max REAL NOT NULL,
+                z_min REAL NOT NULL,
+                z_max REAL NOT NULL,
+                FOREIGN KEY (robot_connection_id) REFERENCES robot_connections(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Controller alarm history
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS alarm_history (
+                id INTEGER PRIMARY KEY,
+                robot_name TEXT NOT NULL,
+                code TEXT NOT NULL,
+                message TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                occurred_at TEXT NOT NULL,
+                cleared_at TEXT
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_alarm_history_robot ON alarm_history(robot_name, occurred_at)",
+            [],
+        )?;
+
         // Server settings
         conn.execute(
             "CREATE TABLE IF NOT EXISTS server_settings (
@@ -128,5 +163,34 @@ impl DatabaseInit for FanucDatabaseInit {
 
         Ok(())
     }
+
+    fn seed_demo_data(&self, conn: &Connection) -> anyhow::Result<()> {
+        // Alternate frame/tool configuration for the local test robot
+        conn.execute(
+            "INSERT OR IGNORE INTO robot_configurations (robot_connection_id, name, is_default, u_frame_number, u_tool_number)
+             SELECT id, 'Demo Fixture', 0, 2, 1 FROM robot_connections WHERE name = 'Local Test Robot'",
+            [],
+        )?;
+
+        // Enabled workspace envelope around the demo programs
+        conn.execute(
+            "INSERT OR IGNORE INTO workspace_envelopes
+                (robot_connection_id, enabled, mode, x_min, x_max, y_min, y_max, z_min, z_max)
+             SELECT id, 1, 'reject', -200.0, 800.0, -500.0, 500.0, -100.0, 600.0
+             FROM robot_connections WHERE name = 'Local Test Robot'",
+            [],
+        )?;
+
+        // Named inputs/outputs
+        conn.execute(
+            "INSERT OR IGNORE INTO io_display_config (robot_connection_id, io_type, io_index, display_name, display_order)
+             SELECT id, 'DOUT', 1, 'Gripper Close', 1 FROM robot_connections WHERE name = 'Local Test Robot'
+             UNION ALL
+             SELECT id, 'DIN', 1, 'Part Present', 2 FROM robot_connections WHERE name = 'Local Test Robot'",
+            [],
+        )?;
+
+        Ok(())
+    }
 }
 
@@ -0,0 +1,214 @@
+//! Software workspace limits.
+//!
+//! Jog and composer motions are checked against the robot's `WorkspaceEnvelope`
+//! before they reach the controller. Depending on `EnvelopeMode` a motion that
+//! leaves the envelope is shortened to end on the boundary or refused. A robot
+//! that is already outside (e.g. the envelope was just enabled) may always move
+//! back toward it.
+
+use bevy::prelude::*;
+use bevy::ecs::message::MessageReader;
+use fanuc_rmi::dto as raw_dto;
+use pl3xus_sync::AuthorizedRequest;
+use pl3xus_sync::authorization::AppBatchRequestRegistrationExt;
+use pl3xus_websockets::WebSocketProvider;
+
+use fanuc_replica_core::DatabaseResource;
+use crate::connection::FanucRobot;
+use crate::database;
+use crate::types::*;
+
+/// Context used for envelope rejections sent as `ServerNotification`s.
+pub const ENVELOPE_CONTEXT: &str = "WorkspaceEnvelope";
+
+/// Total distance (mm) by which `(x, y, z)` lies outside the envelope.
+fn excess(envelope: &WorkspaceEnvelope, x: f64, y: f64, z: f64) -> f64 {
+    let (cx, cy, cz) = envelope.clamp(x, y, z);
+    (x - cx).abs() + (y - cy).abs() + (z - cz).abs()
+}
+
+/// Apply the envelope to a move from `current` to the absolute `target`,
+/// clamping `target` in place when the envelope is in clamp mode.
+pub fn limit_absolute_move(
+    envelope: &WorkspaceEnvelope,
+    current: &raw_dto::Position,
+    target: &mut raw_dto::Position,
+) -> Result<(), EnvelopeViolation> {
+    let Some(violation) = envelope.check(target.x, target.y, target.z) else {
+        return Ok(());
+    };
+
+    // Moving back toward the envelope from outside is always allowed
+    let current_excess = excess(envelope, current.x, current.y, current.z);
+    if current_excess > 0.0 && excess(envelope, target.x, target.y, target.z) < current_excess {
+        return Ok(());
+    }
+
+    match envelope.mode {
+        EnvelopeMode::Reject => Err(violation),
+        EnvelopeMode::Clamp => {
+            let (x, y, z) = envelope.clamp(target.x, target.y, target.z);
+            if (x - current.x).abs() + (y - current.y).abs() + (z - current.z).abs() < 1e-6 {
+                // Already on the boundary - nothing left to move
+                return Err(violation);
+            }
+            target.x = x;
+            target.y = y;
+            target.z = z;
+            Ok(())
+        }
+    }
+}
+
+/// Apply the envelope to a relative move from `current`, shortening `delta`
+/// in place when the envelope is in clamp mode.
+pub fn limit_relative_move(
+    envelope: &WorkspaceEnvelope,
+    current: &raw_dto::Position,
+    delta: &mut raw_dto::Position,
+) -> Result<(), EnvelopeViolation> {
+    let mut target = current.clone();
+    target.x += delta.x;
+    target.y += delta.y;
+    target.z += delta.z;
+
+    limit_absolute_move(envelope, current, &mut target)?;
+    delta.x = target.x - current.x;
+    delta.y = target.y - current.y;
+    delta.z = target.z - current.z;
+    Ok(())
+}
+
+/// Apply the envelope to a composer packet.
+///
+/// Linear and joint motions to a Cartesian position and linear relative
+/// motions are checked; other packets pass through unchanged.
+pub fn limit_packet(
+    envelope: &WorkspaceEnvelope,
+    current: &raw_dto::Position,
+    packet: &mut raw_dto::SendPacket,
+) -> Result<(), EnvelopeViolation> {
+    match packet {
+        raw_dto::SendPacket::Instruction(raw_dto::Instruction::FrcLinearMotion(motion)) => {
+            limit_absolute_move(envelope, current, &mut motion.position)
+        }
+        raw_dto::SendPacket::Instruction(raw_dto::Instruction::FrcJointMotion(motion)) => {
+            limit_absolute_move(envelope, current, &mut motion.position)
+        }
+        raw_dto::SendPacket::Instruction(raw_dto::Instruction::FrcLinearRelative(motion)) => {
+            limit_relative_move(envelope, current, &mut motion.position)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Handle UpdateWorkspaceEnvelope request - replaces the envelope and saves it
+/// for the saved connection, if any.
+/// This is a targeted request that requires entity control.
+fn handle_update_workspace_envelope(
+    mut requests: MessageReader<AuthorizedRequest<UpdateWorkspaceEnvelope>>,
+    mut robots: Query<(&mut WorkspaceEnvelope, &ConnectionState), With<FanucRobot>>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let envelope = request.get_request().envelope.clone();
+        let target = request.target_entity;
+        info!("📋 Handling UpdateWorkspaceEnvelope on entity {:?} (enabled={})", target, envelope.enabled);
+
+        let respond_error = |error: String| UpdateWorkspaceEnvelopeResponse {
+            success: false,
+            persisted: false,
+            error: Some(error),
+        };
+
+        if let Err(e) = envelope.validate() {
+            let _ = request.clone().respond(respond_error(e));
+            continue;
+        }
+
+        let Ok((mut current, conn_state)) = robots.get_mut(target) else {
+            let _ = request.clone().respond(respond_error("Target is not a robot".to_string()));
+            continue;
+        };
+
+        let mut persisted = false;
+        if let (Some(connection_id), Some(db)) = (conn_state.active_connection_id, db.as_ref()) {
+            let conn = db.connection();
+            let conn = conn.lock().unwrap();
+            if let Err(e) = database::save_workspace_envelope(&conn, connection_id, &envelope) {
+                error!("Failed to save workspace envelope: {}", e);
+                let _ = request.clone().respond(respond_error(e.to_string()));
+                continue;
+            }
+            persisted = true;
+        }
+
+        *current = envelope;
+        let _ = request.clone().respond(UpdateWorkspaceEnvelopeResponse {
+            success: true,
+            persisted,
+            error: None,
+        });
+    }
+}
+
+/// Plugin that adds workspace envelope configuration.
+pub struct WorkspaceEnvelopePlugin;
+
+impl Plugin for WorkspaceEnvelopePlugin {
+    fn build(&self, app: &mut App) {
+        app.requests::<(UpdateWorkspaceEnvelope,), WebSocketProvider>()
+            .targeted()
+            .with_default_entity_policy()
+            .with_error_response();
+
+        app.add_systems(Update, handle_update_workspace_envelope);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(x: f64, y: f64, z: f64) -> raw_dto::Position {
+        raw_dto::Position { x, y, z, w: 0.0, p: 0.0, r: 0.0, ext1: 0.0, ext2: 0.0, ext3: 0.0 }
+    }
+
+    fn envelope(mode: EnvelopeMode) -> WorkspaceEnvelope {
+        WorkspaceEnvelope {
+            enabled: true,
+            mode,
+            x_min: 0.0, x_max: 100.0,
+            y_min: 0.0, y_max: 100.0,
+            z_min: 0.0, z_max: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_relative_move_is_clamped_or_rejected() {
+        let current = position(90.0, 50.0, 50.0);
+
+        let mut delta = position(20.0, 0.0, 0.0);
+        limit_relative_move(&envelope(EnvelopeMode::Clamp), &current, &mut delta).unwrap();
+        assert!((delta.x - 10.0).abs() < 1e-9);
+
+        let mut delta = position(20.0, 0.0, 0.0);
+        let violation = limit_relative_move(&envelope(EnvelopeMode::Reject), &current, &mut delta).unwrap_err();
+        assert_eq!(violation.axis, "X");
+
+        // Disabled envelopes never interfere
+        let mut disabled = envelope(EnvelopeMode::Reject);
+        disabled.enabled = false;
+        assert!(limit_relative_move(&disabled, &current, &mut delta).is_ok());
+    }
+
+    #[test]
+    fn test_move_back_inside_is_allowed() {
+        let outside = position(150.0, 50.0, 50.0);
+        let mut target = position(120.0, 50.0, 50.0);
+        assert!(limit_absolute_move(&envelope(EnvelopeMode::Reject), &outside, &mut target).is_ok());
+
+        let mut target = position(160.0, 50.0, 50.0);
+        assert!(limit_absolute_move(&envelope(EnvelopeMode::Reject), &outside, &mut target).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! I/O watch subscriptions.
+//!
+//! Clients register the DIN/DOUT/AIN ports they care about with `IoWatchConfig`.
+//! Only the union of those ports is polled, each at the shortest interval any
+//! client asked for, and every change is broadcast as an `IoChanged` message.
+//!
+//! RMI has no DOUT read, so watched outputs are taken from `IoStatus`, which is
+//! updated when the robot confirms a `WriteDout`.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::ecs::message::MessageReader;
+use bevy_tokio_tasks::TokioTasksRuntime;
+use fanuc_rmi::packets::{Command, CommandResponse, PacketPriority, ResponsePacket, SendPacket};
+use pl3xus::managers::network_request::Request;
+use pl3xus::{ConnectionId, Network, NetworkEvent};
+use pl3xus_sync::TargetedRequest;
+use pl3xus_websockets::WebSocketProvider;
+use tokio::sync::broadcast;
+
+use crate::connection::{FanucRobot, RmiDriver, RobotConnectionState};
+use crate::types::*;
+
+/// Shortest poll interval a client can request.
+pub const MIN_IO_WATCH_INTERVAL_MS: u32 = 50;
+
+/// Per-robot watch state.
+#[derive(Component, Default)]
+pub struct IoWatchState {
+    /// Watch config per client connection
+    clients: HashMap<ConnectionId, IoWatchConfig>,
+    /// When each port was last polled (seconds)
+    last_poll: HashMap<(WatchedIoType, u16), f64>,
+    /// Last observed value of each watched port
+    last_values: HashMap<(WatchedIoType, u16), IoValue>,
+    /// Read responses, subscribed while the robot is connected
+    responses: Option<broadcast::Receiver<ResponsePacket>>,
+}
+
+impl IoWatchState {
+    /// Watched ports with the shortest interval requested for each.
+    fn watched(&self) -> HashMap<(WatchedIoType, u16), u32> {
+        let mut watched = HashMap::new();
+        for config in self.clients.values() {
+            let ports = config.din.iter().map(|&p| (WatchedIoType::Din, p))
+                .chain(config.dout.iter().map(|&p| (WatchedIoType::Dout, p)))
+                .chain(config.ain.iter().map(|&p| (WatchedIoType::Ain, p)));
+            for key in ports {
+                watched.entry(key)
+                    .and_modify(|ms: &mut u32| *ms = (*ms).min(config.interval_ms))
+                    .or_insert(config.interval_ms);
+            }
+        }
+        watched
+    }
+
+    /// Record a value, returning the previous one if it changed.
+    fn observe(&mut self, key: (WatchedIoType, u16), value: IoValue) -> Option<IoValue> {
+        match self.last_values.insert(key, value) {
+            Some(previous) if previous != value => Some(previous),
+            _ => None,
+        }
+    }
+
+    /// Forget ports no client watches any more.
+    fn prune(&mut self) {
+        let watched = self.watched();
+        self.last_poll.retain(|key, _| watched.contains_key(key));
+        self.last_values.retain(|key, _| watched.contains_key(key));
+    }
+}
+
+fn get_bit(words: &[u16], port: u16) -> bool {
+    if port == 0 {
+        return false;
+    }
+    let word_index = (port as usize - 1) / 16;
+    let bit_index = (port as usize - 1) % 16;
+    words.get(word_index).map(|word| (word >> bit_index) & 1 == 1).unwrap_or(false)
+}
+
+fn set_bit(words: &mut Vec<u16>, port: u16, value: bool) {
+    if port == 0 {
+        return;
+    }
+    let word_index = (port as usize - 1) / 16;
+    let bit_index = (port as usize - 1) % 16;
+    while words.len() <= word_index {
+        words.push(0);
+    }
+    if value {
+        words[word_index] |= 1 << bit_index;
+    } else {
+        words[word_index] &= !(1 << bit_index);
+    }
+}
+
+/// Handle IoWatchConfig request - replaces the client's watch set on the target robot.
+/// This is a targeted query (no authorization required).
+fn handle_io_watch_config(
+    mut requests: MessageReader<Request<TargetedRequest<IoWatchConfig>>>,
+    mut commands: Commands,
+    mut robots: Query<Option<&mut IoWatchState>, With<FanucRobot>>,
+) {
+    for request in requests.read() {
+        let targeted = request.get_request();
+        let client = *request.source();
+        let mut config = targeted.request.clone();
+        config.interval_ms = config.interval_ms.max(MIN_IO_WATCH_INTERVAL_MS);
+        let watched_ports = (config.din.len() + config.dout.len() + config.ain.len()) as u32;
+        info!(
+            "📋 Handling IoWatchConfig for target {}: {} port(s) every {}ms",
+            targeted.target_id, watched_ports, config.interval_ms
+        );
+
+        let target = targeted.target_id.parse::<u64>().ok().and_then(Entity::try_from_bits);
+        let response = match target.map(|entity| (entity, robots.get_mut(entity))) {
+            Some((entity, Ok(state))) => {
+                let interval_ms = config.interval_ms;
+                match state {
+                    Some(mut state) => {
+                        if watched_ports == 0 {
+                            state.clients.remove(&client);
+                        } else {
+                            state.clients.insert(client, config);
+                        }
+                        state.prune();
+                    }
+                    None if watched_ports > 0 => {
+                        let mut state = IoWatchState::default();
+                        state.clients.insert(client, config);
+                        commands.entity(entity).insert(state);
+                    }
+                    None => {}
+                }
+                IoWatchConfigResponse {
+                    success: true,
+                    watched_ports,
+                    interval_ms,
+                    error: None,
+                }
+            }
+            _ => IoWatchConfigResponse {
+                success: false,
+                watched_ports: 0,
+                interval_ms: 0,
+                error: Some(format!("Invalid target robot: {}", targeted.target_id)),
+            },
+        };
+
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Drop the watches of clients that disconnected.
+fn remove_disconnected_watchers(
+    mut events: MessageReader<NetworkEvent>,
+    mut robots: Query<&mut IoWatchState>,
+) {
+    for event in events.read() {
+        if let NetworkEvent::Disconnected(connection_id) = event {
+            for mut state in robots.iter_mut() {
+                if state.clients.remove(connection_id).is_some() {
+                    state.prune();
+                }
+            }
+        }
+    }
+}
+
+/// Send reads for the watched ports that are due.
+fn poll_io_watches(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    time: Res<Time>,
+    mut robots: Query<(&mut IoWatchState, &RobotConnectionState, Option<&RmiDriver>), With<FanucRobot>>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    // Enter the Tokio runtime context so send_packet can use tokio::spawn
+    let _guard = tokio_runtime.runtime().enter();
+
+    for (mut state, conn_state, driver) in robots.iter_mut() {
+        let Some(driver) = driver.filter(|_| *conn_state == RobotConnectionState::Connected) else {
+            // Resubscribe to the new driver after a reconnect
+            state.responses = None;
+            continue;
+        };
+        if state.clients.is_empty() {
+            continue;
+        }
+        if state.responses.is_none() {
+            state.responses = Some(driver.0.response_tx.subscribe());
+        }
+
+        for ((io_type, port), interval_ms) in state.watched() {
+            let key = (io_type, port);
+            let due = state.last_poll.get(&key)
+                .is_none_or(|&last| now - last >= interval_ms as f64 / 1000.0);
+            if !due {
+                continue;
+            }
+            state.last_poll.insert(key, now);
+
+            let command = match io_type {
+                WatchedIoType::Din => Command::FrcReadDIN(fanuc_rmi::commands::FrcReadDIN { port_number: port }),
+                WatchedIoType::Ain => Command::FrcReadAIN(fanuc_rmi::commands::FrcReadAIN { port_number: port }),
+                // Served from IoStatus in process_io_watches
+                WatchedIoType::Dout => continue,
+            };
+            if let Err(e) = driver.0.send_packet(SendPacket::Command(command), PacketPriority::Standard) {
+                warn!("Failed to poll {}[{}]: {}", io_type.as_str(), port, e);
+            }
+        }
+    }
+}
+
+/// Apply read responses to `IoStatus` and broadcast changes of watched ports.
+fn process_io_watches(
+    mut robots: Query<(Entity, &mut IoWatchState, &mut IoStatus), With<FanucRobot>>,
+    net: Res<Network<WebSocketProvider>>,
+) {
+    for (entity, mut state, mut io_status) in robots.iter_mut() {
+        let mut readings = Vec::new();
+        if let Some(responses) = state.responses.as_mut() {
+            while let Ok(response) = responses.try_recv() {
+                match response {
+                    ResponsePacket::CommandResponse(CommandResponse::FrcReadDIN(resp)) if resp.error_id == 0 => {
+                        readings.push(((WatchedIoType::Din, resp.port_number), IoValue::Digital(resp.port_value != 0)));
+                    }
+                    ResponsePacket::CommandResponse(CommandResponse::FrcReadAIN(resp)) if resp.error_id == 0 => {
+                        readings.push(((WatchedIoType::Ain, resp.port_number), IoValue::Analog(resp.port_value as f64)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let watched = state.watched();
+        for &(io_type, port) in watched.keys().filter(|(io_type, _)| *io_type == WatchedIoType::Dout) {
+            readings.push(((io_type, port), IoValue::Digital(get_bit(&io_status.digital_outputs, port))));
+        }
+
+        for (key, value) in readings {
+            if !watched.contains_key(&key) {
+                continue;
+            }
+            match (key.0, value) {
+                (WatchedIoType::Din, IoValue::Digital(on)) if get_bit(&io_status.digital_inputs, key.1) != on => {
+                    set_bit(&mut io_status.digital_inputs, key.1, on);
+                }
+                (WatchedIoType::Ain, IoValue::Analog(v)) if io_status.analog_inputs.get(&key.1) != Some(&v) => {
+                    io_status.analog_inputs.insert(key.1, v);
+                }
+                _ => {}
+            }
+
+            if let Some(previous) = state.observe(key, value) {
+                debug!("🔌 {}[{}] changed: {:?} -> {:?}", key.0.as_str(), key.1, previous, value);
+                net.broadcast(IoChanged::new(entity.to_bits(), key.0, key.1, previous, value));
+            }
+        }
+    }
+}
+
+/// Plugin that adds I/O watch subscriptions.
+pub struct IoWatchPlugin;
+
+impl Plugin for IoWatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            handle_io_watch_config,
+            remove_disconnected_watchers,
+            poll_io_watches,
+            process_io_watches,
+        ).chain());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(din: Vec<u16>, interval_ms: u32) -> IoWatchConfig {
+        IoWatchConfig { din, interval_ms, ..Default::default() }
+    }
+
+    #[test]
+    fn test_watched_uses_shortest_interval_per_port() {
+        let mut state = IoWatchState::default();
+        state.clients.insert(ConnectionId { id: 1 }, watch(vec![1, 2], 500));
+        state.clients.insert(ConnectionId { id: 2 }, watch(vec![2, 3], 100));
+
+        let watched = state.watched();
+        assert_eq!(watched.len(), 3);
+        assert_eq!(watched[&(WatchedIoType::Din, 1)], 500);
+        assert_eq!(watched[&(WatchedIoType::Din, 2)], 100);
+        assert_eq!(watched[&(WatchedIoType::Din, 3)], 100);
+    }
+
+    #[test]
+    fn test_observe_reports_changes_only() {
+        let mut state = IoWatchState::default();
+        let key = (WatchedIoType::Din, 4);
+
+        assert_eq!(state.observe(key, IoValue::Digital(false)), None);
+        assert_eq!(state.observe(key, IoValue::Digital(false)), None);
+        assert_eq!(state.observe(key, IoValue::Digital(true)), Some(IoValue::Digital(false)));
+
+        let mut words = Vec::new();
+        set_bit(&mut words, 20, true);
+        assert_eq!(words, vec![0, 0b1000]);
+        assert!(get_bit(&words, 20));
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy::ecs::message::MessageReader;
 use bevy_tokio_tasks::TokioTasksRuntime;
+use pl3xus::ConnectionId;
 use pl3xus_sync::{AuthorizedTargetedMessage, AuthorizedRequest};
 use pl3xus_websockets::WebSocketProvider;
 use crate::types::*;
@@ -8,6 +9,7 @@ use fanuc_rmi::dto as raw_dto;
 use fanuc_rmi::{SpeedType, TermType};
 use fanuc_rmi::packets::PacketPriority;
 use crate::connection::{FanucRobot, RmiDriver, RobotConnectionState};
+use crate::envelope::{self, ENVELOPE_CONTEXT};
 
 /// Handle authorized jog commands - uses the new AuthorizedTargetedMessage pattern.
 ///
@@ -21,11 +23,25 @@ use crate::connection::{FanucRobot, RmiDriver, RobotConnectionState};
 ///
 /// GAP-010: Frame and tool values are read from FrameToolDataState to use the
 /// currently active frame/tool for jog movements.
+///
+/// Jogs that would leave the robot's WorkspaceEnvelope are shortened or
+/// rejected with a warning notification, depending on the envelope mode.
 pub fn handle_authorized_jog_commands(
     tokio_runtime: Res<TokioTasksRuntime>,
     mut events: MessageReader<AuthorizedTargetedMessage<JogCommand>>,
-    robot_query: Query<(Entity, &RobotConnectionState, Option<&RmiDriver>, &JogSettingsState, &FrameToolDataState), With<FanucRobot>>,
+    robot_query: Query<(
+        Entity,
+        &RobotConnectionState,
+        Option<&RmiDriver>,
+        &JogSettingsState,
+        &FrameToolDataState,
+        &RobotPosition,
+        &WorkspaceEnvelope,
+    ), With<FanucRobot>>,
+    net: Res<pl3xus::Network<WebSocketProvider>>,
 ) {
+    use pl3xus_common::ServerNotification;
+
     // Enter the Tokio runtime context so send_packet can use tokio::spawn
     let _guard = tokio_runtime.runtime().enter();
 
@@ -35,8 +51,8 @@ pub fn handle_authorized_jog_commands(
         let target_entity = event.target_entity;
 
         // Find a connected robot (in future, match by target_entity)
-        let Some((entity, _, driver, jog_settings, frame_tool_state)) = robot_query.iter()
-            .find(|(_, state, driver, _, _)| **state == RobotConnectionState::Connected && driver.is_some())
+        let Some((entity, _, driver, jog_settings, frame_tool_state, position, workspace)) = robot_query.iter()
+            .find(|(_, state, driver, ..)| **state == RobotConnectionState::Connected && driver.is_some())
         else {
             warn!("Authorized jog rejected: No connected robot");
             continue;
@@ -66,26 +82,21 @@ pub fn handle_authorized_jog_commands(
             target_entity, cmd.axis, cmd.direction, step, speed, active_uframe, active_utool
         );
 
-        // Build position delta (PositionDto uses f32)
-        let mut pos = raw_dto::Position {
-            x: 0.0, y: 0.0, z: 0.0,
-            w: 0.0, p: 0.0, r: 0.0,
-            ext1: 0.0, ext2: 0.0, ext3: 0.0,
-        };
+        // Build position delta
         let dist = if cmd.direction == JogDirection::Positive { step } else { -step };
+        let Some(mut pos) = jog_delta(cmd.axis, dist) else {
+            // Joint jogs not supported by this simulator - need FrcJointRelativeJRep
+            warn!("Joint jogging not supported by this simulator");
+            continue;
+        };
 
-        match cmd.axis {
-            JogAxis::X => pos.x = dist,
-            JogAxis::Y => pos.y = dist,
-            JogAxis::Z => pos.z = dist,
-            JogAxis::W => pos.w = dist,
-            JogAxis::P => pos.p = dist,
-            JogAxis::R => pos.r = dist,
-            JogAxis::J1 | JogAxis::J2 | JogAxis::J3 | JogAxis::J4 | JogAxis::J5 | JogAxis::J6 => {
-                // Joint jogs not supported by this simulator - need FrcJointRelativeJRep
-                warn!("Joint jogging not supported by this simulator");
-                continue;
-            }
+        if let Err(violation) = envelope::limit_relative_move(workspace, &position.0, &mut pos) {
+            warn!("Jog rejected on {:?}: {}", entity, violation);
+            let _ = net.send(
+                event.source,
+                ServerNotification::warning(format!("Jog rejected: {}", violation)).with_context(ENVELOPE_CONTEXT),
+            );
+            continue;
         }
 
         // Build instruction - use FrcLinearRelative for Cartesian jogs
@@ -121,6 +132,258 @@ pub fn handle_authorized_jog_commands(
     }
 }
 
+/// Relative position moving `dist` along a Cartesian jog axis.
+///
+/// Returns None for joint axes, which need FrcJointRelativeJRep.
+fn jog_delta(axis: JogAxis, dist: f64) -> Option<raw_dto::Position> {
+    let mut pos = raw_dto::Position {
+        x: 0.0, y: 0.0, z: 0.0,
+        w: 0.0, p: 0.0, r: 0.0,
+        ext1: 0.0, ext2: 0.0, ext3: 0.0,
+    };
+    match axis {
+        JogAxis::X => pos.x = dist,
+        JogAxis::Y => pos.y = dist,
+        JogAxis::Z => pos.z = dist,
+        JogAxis::W => pos.w = dist,
+        JogAxis::P => pos.p = dist,
+        JogAxis::R => pos.r = dist,
+        JogAxis::J1 | JogAxis::J2 | JogAxis::J3 | JogAxis::J4 | JogAxis::J5 | JogAxis::J6 => return None,
+    }
+    Some(pos)
+}
+
+// ============================================================================
+// Continuous Jogging
+// ============================================================================
+
+/// Seconds of motion in each streamed continuous-jog segment.
+///
+/// One segment is queued ahead of the robot, so after a stop the robot
+/// travels at most two segments.
+pub const CONTINUOUS_JOG_SEGMENT_SECS: f64 = 0.05;
+
+/// Stop a continuous jog when no keepalive arrives for this long.
+pub const JOG_KEEPALIVE_TIMEOUT_SECS: f64 = 0.3;
+
+/// Default hard limit on a single continuous jog, even with keepalives.
+pub const JOG_WATCHDOG_SECS: f64 = 30.0;
+
+/// Hard limit on a single continuous jog, adjustable at runtime through the
+/// `jog_watchdog_timeout_ms` admin setting.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct JogWatchdog {
+    pub timeout_secs: f64,
+}
+
+impl Default for JogWatchdog {
+    fn default() -> Self {
+        Self { timeout_secs: JOG_WATCHDOG_SECS }
+    }
+}
+
+/// A continuous jog in progress (server-only).
+#[derive(Component, Debug, Clone)]
+pub struct ContinuousJog {
+    pub axis: JogAxis,
+    pub direction: JogDirection,
+    pub speed: f64,
+    /// Client that started the jog; only its keepalives count
+    pub source: ConnectionId,
+    pub started_at: f64,
+    pub last_keepalive: f64,
+    /// When the next segment is due to start (seconds)
+    next_segment_at: f64,
+}
+
+impl ContinuousJog {
+    /// Why the jog must stop at `now`, if it must.
+    pub fn expired(&self, now: f64, watchdog: &JogWatchdog) -> Option<&'static str> {
+        if now - self.started_at >= watchdog.timeout_secs {
+            Some("watchdog timeout")
+        } else if now - self.last_keepalive >= JOG_KEEPALIVE_TIMEOUT_SECS {
+            Some("keepalive timeout")
+        } else {
+            None
+        }
+    }
+}
+
+/// Handle JogStart - begins streaming a continuous jog on the robot.
+///
+/// Authorization is handled by middleware - only the client in control can jog.
+pub fn handle_jog_start(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut events: MessageReader<AuthorizedTargetedMessage<JogStart>>,
+    robot_query: Query<(Entity, &RobotConnectionState, Option<&RmiDriver>, &JogSettingsState), With<FanucRobot>>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for event in events.read() {
+        let cmd = &event.message;
+
+        let Some((entity, _, _, jog_settings)) = robot_query.iter()
+            .find(|(_, state, driver, _)| **state == RobotConnectionState::Connected && driver.is_some())
+        else {
+            warn!("JogStart rejected: No connected robot");
+            continue;
+        };
+
+        if jog_delta(cmd.axis, 0.0).is_none() {
+            warn!("JogStart rejected: joint jogging not supported by this simulator");
+            continue;
+        }
+
+        let default_speed = match cmd.axis {
+            JogAxis::W | JogAxis::P | JogAxis::R => jog_settings.rotation_jog_speed,
+            _ => jog_settings.cartesian_jog_speed,
+        };
+        let speed = cmd.speed.filter(|s| *s > 0.0).unwrap_or(default_speed);
+
+        info!("Starting continuous jog on {:?}: {:?} {:?} at {}", entity, cmd.axis, cmd.direction, speed);
+        commands.entity(entity).insert(ContinuousJog {
+            axis: cmd.axis,
+            direction: cmd.direction,
+            speed,
+            source: event.source,
+            started_at: now,
+            last_keepalive: now,
+            next_segment_at: now,
+        });
+    }
+}
+
+/// Handle JogKeepalive - extends the sender's continuous jog.
+pub fn handle_jog_keepalive(
+    time: Res<Time>,
+    mut events: MessageReader<AuthorizedTargetedMessage<JogKeepalive>>,
+    mut jogs: Query<&mut ContinuousJog>,
+) {
+    let now = time.elapsed_secs_f64();
+    for event in events.read() {
+        for mut jog in jogs.iter_mut().filter(|jog| jog.source == event.source) {
+            jog.last_keepalive = now;
+        }
+    }
+}
+
+/// Handle JogStop - stops streaming the continuous jog.
+pub fn handle_jog_stop(
+    mut commands: Commands,
+    mut events: MessageReader<AuthorizedTargetedMessage<JogStop>>,
+    jogs: Query<Entity, With<ContinuousJog>>,
+) {
+    for _ in events.read() {
+        for entity in jogs.iter() {
+            info!("Continuous jog on {:?} stopped by client", entity);
+            commands.entity(entity).remove::<ContinuousJog>();
+        }
+    }
+}
+
+/// Stream continuous jog segments and stop jogs whose keepalive or watchdog expired.
+pub fn stream_continuous_jogs(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut robots: Query<(
+        Entity,
+        &mut ContinuousJog,
+        &RobotConnectionState,
+        Option<&RmiDriver>,
+        &FrameToolDataState,
+        &RobotPosition,
+        &WorkspaceEnvelope,
+    ), With<FanucRobot>>,
+    net: Res<pl3xus::Network<WebSocketProvider>>,
+    watchdog: Res<JogWatchdog>,
+) {
+    use pl3xus_common::ServerNotification;
+
+    // Enter the Tokio runtime context so send_packet can use tokio::spawn
+    let _guard = tokio_runtime.runtime().enter();
+    let now = time.elapsed_secs_f64();
+
+    for (entity, mut jog, state, driver, frame_tool_state, position, workspace) in robots.iter_mut() {
+        let Some(driver) = driver.filter(|_| *state == RobotConnectionState::Connected) else {
+            commands.entity(entity).remove::<ContinuousJog>();
+            continue;
+        };
+
+        if let Some(reason) = jog.expired(now, &watchdog) {
+            warn!("Continuous jog on {:?} stopped: {}", entity, reason);
+            commands.entity(entity).remove::<ContinuousJog>();
+            continue;
+        }
+
+        // After a stall don't try to catch up - that would queue a burst of motion
+        if jog.next_segment_at < now - CONTINUOUS_JOG_SEGMENT_SECS {
+            jog.next_segment_at = now;
+        }
+
+        // Where the robot ends up once the queued segment finishes
+        let mut ahead = position.0.clone();
+
+        // Keep one segment queued ahead of the one in progress
+        while jog.next_segment_at <= now + CONTINUOUS_JOG_SEGMENT_SECS {
+            let step = jog.speed * CONTINUOUS_JOG_SEGMENT_SECS;
+            let dist = if jog.direction == JogDirection::Positive { step } else { -step };
+            let Some(mut pos) = jog_delta(jog.axis, dist) else {
+                commands.entity(entity).remove::<ContinuousJog>();
+                break;
+            };
+            // Segments are equal, so the one queued before this one moves as far
+            let full_segment = pos.clone();
+            ahead.x += full_segment.x;
+            ahead.y += full_segment.y;
+            ahead.z += full_segment.z;
+
+            // Stop at the envelope boundary; a shortened segment is the last one
+            if let Err(violation) = envelope::limit_relative_move(workspace, &ahead, &mut pos) {
+                info!("Continuous jog on {:?} stopped at workspace limit: {}", entity, violation);
+                let _ = net.send(
+                    jog.source,
+                    ServerNotification::warning(format!("Jog stopped: {}", violation)).with_context(ENVELOPE_CONTEXT),
+                );
+                commands.entity(entity).remove::<ContinuousJog>();
+                break;
+            }
+            let at_limit = pos != full_segment;
+
+            // CNT so consecutive segments blend into one smooth motion
+            let instruction = raw_dto::Instruction::FrcLinearRelative(raw_dto::FrcLinearRelative {
+                sequence_id: 0,
+                configuration: raw_dto::Configuration {
+                    u_frame_number: frame_tool_state.active_frame as i8,
+                    u_tool_number: frame_tool_state.active_tool as i8,
+                    turn4: 0, turn5: 0, turn6: 0,
+                    front: 0, up: 0, left: 0, flip: 0,
+                },
+                position: pos,
+                speed_type: SpeedType::MMSec.into(),
+                speed: jog.speed,
+                term_type: TermType::CNT.into(),
+                term_value: 100,
+            });
+            let send_packet: fanuc_rmi::packets::SendPacket =
+                raw_dto::SendPacket::Instruction(instruction).into();
+
+            if let Err(e) = driver.0.send_packet(send_packet, PacketPriority::Immediate) {
+                error!("Continuous jog segment failed, stopping jog: {:?}", e);
+                commands.entity(entity).remove::<ContinuousJog>();
+                break;
+            }
+            if at_limit {
+                info!("Continuous jog on {:?} reached the workspace limit", entity);
+                commands.entity(entity).remove::<ContinuousJog>();
+                break;
+            }
+            jog.next_segment_at += CONTINUOUS_JOG_SEGMENT_SECS;
+        }
+    }
+}
+
 /// Handle InitializeRobot requests - initializes the robot for motion
 ///
 /// Authorization is handled by middleware - no manual control check needed.
@@ -315,10 +578,11 @@ pub fn handle_reset_robot(
 /// This is the primary way to send motion commands from the client
 ///
 /// Authorization is handled by middleware - no manual control check needed.
+/// Motions are checked against the robot's WorkspaceEnvelope first.
 pub fn handle_send_packet(
     tokio_runtime: Res<TokioTasksRuntime>,
     mut events: MessageReader<AuthorizedTargetedMessage<raw_dto::SendPacket>>,
-    robot_query: Query<(Entity, &RobotConnectionState, Option<&RmiDriver>), With<FanucRobot>>,
+    robot_query: Query<(Entity, &RobotConnectionState, Option<&RmiDriver>, &RobotPosition, &WorkspaceEnvelope), With<FanucRobot>>,
     net: Res<pl3xus::Network<WebSocketProvider>>,
 ) {
     use pl3xus_common::ServerNotification;
@@ -327,13 +591,13 @@ pub fn handle_send_packet(
     let _guard = tokio_runtime.runtime().enter();
 
     for event in events.read() {
-        let dto_packet = &event.message;
+        let mut dto_packet = event.message.clone();
         let target_entity = event.target_entity;
         let source = event.source;
 
         // Find a connected robot
-        let Some((entity, _, driver)) = robot_query.iter()
-            .find(|(_, state, driver)| **state == RobotConnectionState::Connected && driver.is_some())
+        let Some((entity, _, driver, position, workspace)) = robot_query.iter()
+            .find(|(_, state, driver, ..)| **state == RobotConnectionState::Connected && driver.is_some())
         else {
             warn!("SendPacket rejected: No connected robot");
             let _ = net.send(
@@ -345,8 +609,17 @@ pub fn handle_send_packet(
 
         let driver = driver.expect("Checked above");
 
+        if let Err(violation) = envelope::limit_packet(workspace, &position.0, &mut dto_packet) {
+            warn!("SendPacket rejected: {}", violation);
+            let _ = net.send(
+                source,
+                ServerNotification::error(format!("Motion rejected: {}", violation)).with_context(ENVELOPE_CONTEXT),
+            );
+            continue;
+        }
+
         // Convert DTO to protocol type using Into
-        let protocol_packet: fanuc_rmi::packets::SendPacket = dto_packet.clone().into();
+        let protocol_packet: fanuc_rmi::packets::SendPacket = dto_packet.into();
 
         info!("Processing authorized SendPacket for {:?} on {:?}: {:?}", target_entity, entity, protocol_packet);
 
@@ -373,10 +646,15 @@ pub fn handle_send_packet(
 ///
 /// GAP-011: Speed override must be validated to be in range 1-100%.
 /// FANUC robots do not accept 0% speed override.
+///
+/// With `ramp_ms > 0` the request starts an `OverrideRamp` and responds
+/// immediately; `step_override_ramps` then walks the override to the target.
 pub fn handle_set_speed_override(
     tokio_runtime: Res<TokioTasksRuntime>,
+    time: Res<Time>,
+    mut commands: Commands,
     mut events: MessageReader<AuthorizedRequest<SetSpeedOverride>>,
-    robot_query: Query<(Entity, &RobotConnectionState, Option<&RmiDriver>), With<FanucRobot>>,
+    mut robot_query: Query<(Entity, &RobotConnectionState, Option<&RmiDriver>, &mut OverrideState), With<FanucRobot>>,
 ) {
     // Enter the Tokio runtime context so send_packet can use tokio::spawn
     let _guard = tokio_runtime.runtime().enter();
@@ -406,8 +684,8 @@ pub fn handle_set_speed_override(
         }
 
         // Find a connected robot
-        let Some((entity, _, driver)) = robot_query.iter()
-            .find(|(_, state, driver)| **state == RobotConnectionState::Connected && driver.is_some())
+        let Some((entity, _, driver, mut override_state)) = robot_query.iter_mut()
+            .find(|(_, state, driver, _)| **state == RobotConnectionState::Connected && driver.is_some())
         else {
             warn!("SetSpeedOverride rejected: No connected robot");
             let _ = event.respond(SetSpeedOverrideResponse {
@@ -419,8 +697,28 @@ pub fn handle_set_speed_override(
 
         let driver = driver.expect("Checked above");
 
+        if cmd.ramp_ms > 0 {
+            info!(
+                "Processing authorized SetSpeedOverride for {:?} on {:?}: ramp {}% -> {}% over {}ms",
+                target_entity, entity, override_state.current, cmd.speed, cmd.ramp_ms
+            );
+            commands.entity(entity).insert(OverrideRamp {
+                start: override_state.current,
+                target: cmd.speed,
+                started_at: time.elapsed_secs_f64(),
+                duration: cmd.ramp_ms as f64 / 1000.0,
+            });
+            override_state.target = cmd.speed;
+            override_state.ramping = true;
+            let _ = event.respond(SetSpeedOverrideResponse { success: true, error: None });
+            continue;
+        }
+
         info!("Processing authorized SetSpeedOverride for {:?} on {:?}: speed={}%", target_entity, entity, cmd.speed);
 
+        // An immediate override cancels any ramp in progress
+        commands.entity(entity).remove::<OverrideRamp>();
+
         // Send FrcSetOverRide command
         let command = raw_dto::Command::FrcSetOverRide(raw_dto::FrcSetOverRide { value: cmd.speed });
         let send_packet: fanuc_rmi::packets::SendPacket = raw_dto::SendPacket::Command(command).into();
@@ -428,10 +726,13 @@ pub fn handle_set_speed_override(
         match driver.0.send_packet(send_packet, PacketPriority::Immediate) {
             Ok(seq) => {
                 info!("Sent SetSpeedOverride command with sequence {}", seq);
+                *override_state = OverrideState { current: cmd.speed, target: cmd.speed, ramping: false };
                 let _ = event.respond(SetSpeedOverrideResponse { success: true, error: None });
             }
             Err(e) => {
                 error!("Failed to send SetSpeedOverride command: {:?}", e);
+                override_state.target = override_state.current;
+                override_state.ramping = false;
                 let _ = event.respond(SetSpeedOverrideResponse {
                     success: false,
                     error: Some(format!("Failed to send SetSpeedOverride: {:?}", e)),
@@ -441,6 +742,93 @@ pub fn handle_set_speed_override(
     }
 }
 
+/// A speed override ramp in progress (server-only).
+#[derive(Component, Debug, Clone)]
+pub struct OverrideRamp {
+    pub start: u8,
+    pub target: u8,
+    /// When the ramp started (seconds)
+    pub started_at: f64,
+    /// Ramp length (seconds)
+    pub duration: f64,
+}
+
+impl OverrideRamp {
+    /// Override the ramp calls for at `now`, and whether the ramp is done.
+    pub fn value_at(&self, now: f64) -> (u8, bool) {
+        let t = if self.duration > 0.0 {
+            ((now - self.started_at) / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let value = self.start as f64 + (self.target as f64 - self.start as f64) * t;
+        (value.round().clamp(1.0, 100.0) as u8, t >= 1.0)
+    }
+}
+
+/// Step active override ramps and keep `OverrideState` in line with the
+/// override the controller reports when no ramp is running.
+///
+/// Only sends a command when the rounded override actually changes, so slow
+/// ramps don't flood the controller.
+pub fn step_override_ramps(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut robots: Query<(
+        Entity,
+        &RobotConnectionState,
+        Option<&RmiDriver>,
+        Option<&OverrideRamp>,
+        &RobotStatus,
+        &mut OverrideState,
+    ), With<FanucRobot>>,
+) {
+    // Enter the Tokio runtime context so send_packet can use tokio::spawn
+    let _guard = tokio_runtime.runtime().enter();
+    let now = time.elapsed_secs_f64();
+
+    for (entity, state, driver, ramp, status, mut override_state) in robots.iter_mut() {
+        let Some(ramp) = ramp else {
+            if override_state.current != status.speed_override && status.speed_override > 0 {
+                let speed = status.speed_override;
+                *override_state = OverrideState { current: speed, target: speed, ramping: false };
+            }
+            continue;
+        };
+
+        let Some(driver) = driver.filter(|_| *state == RobotConnectionState::Connected) else {
+            warn!("Override ramp cancelled: robot not connected");
+            commands.entity(entity).remove::<OverrideRamp>();
+            override_state.target = override_state.current;
+            override_state.ramping = false;
+            continue;
+        };
+
+        let (value, done) = ramp.value_at(now);
+        if value != override_state.current {
+            let command = raw_dto::Command::FrcSetOverRide(raw_dto::FrcSetOverRide { value });
+            let send_packet: fanuc_rmi::packets::SendPacket = raw_dto::SendPacket::Command(command).into();
+            match driver.0.send_packet(send_packet, PacketPriority::Immediate) {
+                Ok(_) => override_state.current = value,
+                Err(e) => {
+                    error!("Override ramp step failed, cancelling ramp: {:?}", e);
+                    commands.entity(entity).remove::<OverrideRamp>();
+                    override_state.target = override_state.current;
+                    override_state.ramping = false;
+                    continue;
+                }
+            }
+        }
+
+        if done {
+            info!("Override ramp complete at {}%", value);
+            commands.entity(entity).remove::<OverrideRamp>();
+            override_state.ramping = false;
+        }
+    }
+}
+
 /// Handle JogSettingsState component mutations.
 ///
 /// This handler is called when a client mutates the JogSettingsState component.
@@ -512,3 +900,90 @@ fn validate_jog_settings(settings: &JogSettingsState) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jog(source: ConnectionId, started_at: f64) -> ContinuousJog {
+        ContinuousJog {
+            axis: JogAxis::X,
+            direction: JogDirection::Positive,
+            speed: 10.0,
+            source,
+            started_at,
+            last_keepalive: started_at,
+            next_segment_at: started_at,
+        }
+    }
+
+    #[test]
+    fn test_missed_keepalive_stops_jog() {
+        let watchdog = JogWatchdog::default();
+        let mut jog = jog(ConnectionId { id: 1 }, 0.0);
+
+        assert_eq!(jog.expired(JOG_KEEPALIVE_TIMEOUT_SECS - 0.01, &watchdog), None);
+        jog.last_keepalive = 0.25;
+        assert_eq!(jog.expired(0.5, &watchdog), None);
+        assert_eq!(jog.expired(0.26 + JOG_KEEPALIVE_TIMEOUT_SECS, &watchdog), Some("keepalive timeout"));
+    }
+
+    #[test]
+    fn test_watchdog_timeout_changes_respected() {
+        let mut jog = jog(ConnectionId { id: 1 }, 0.0);
+        jog.last_keepalive = 1.9;
+
+        // Kept alive, so only the watchdog can stop it
+        assert_eq!(jog.expired(2.0, &JogWatchdog::default()), None);
+        let shortened = JogWatchdog { timeout_secs: 2.0 };
+        assert_eq!(jog.expired(2.0, &shortened), Some("watchdog timeout"));
+        assert_eq!(jog.expired(1.95, &shortened), None);
+
+        jog.last_keepalive = JOG_WATCHDOG_SECS + 4.9;
+        let lengthened = JogWatchdog { timeout_secs: JOG_WATCHDOG_SECS + 10.0 };
+        assert_eq!(jog.expired(JOG_WATCHDOG_SECS + 5.0, &JogWatchdog::default()), Some("watchdog timeout"));
+        assert_eq!(jog.expired(JOG_WATCHDOG_SECS + 5.0, &lengthened), None);
+    }
+
+    #[test]
+    fn test_keepalive_only_extends_senders_jog() {
+        let operator = ConnectionId { id: 1 };
+        let other = ConnectionId { id: 2 };
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_message::<AuthorizedTargetedMessage<JogKeepalive>>()
+            .add_systems(Update, handle_jog_keepalive);
+        let robot = app.world_mut().spawn(jog(operator, 0.0)).id();
+        let other_robot = app.world_mut().spawn(jog(other, 0.0)).id();
+
+        app.world_mut().resource_mut::<Time>().advance_by(std::time::Duration::from_millis(200));
+        app.world_mut().write_message(AuthorizedTargetedMessage {
+            message: JogKeepalive,
+            source: operator,
+            target_entity: robot,
+        });
+        app.update();
+
+        let last_keepalive = |app: &App, entity| app.world().get::<ContinuousJog>(entity).unwrap().last_keepalive;
+        assert!((last_keepalive(&app, robot) - 0.2).abs() < 1e-6);
+        assert_eq!(last_keepalive(&app, other_robot), 0.0);
+    }
+
+    #[test]
+    fn test_override_ramp_interpolates_and_finishes() {
+        let ramp = OverrideRamp { start: 20, target: 80, started_at: 10.0, duration: 2.0 };
+        assert_eq!(ramp.value_at(9.0), (20, false));
+        assert_eq!(ramp.value_at(11.0), (50, false));
+        assert_eq!(ramp.value_at(12.0), (80, true));
+        assert_eq!(ramp.value_at(30.0), (80, true));
+
+        // Values are rounded, so small ramps only change the override when needed
+        let small = OverrideRamp { start: 10, target: 11, started_at: 0.0, duration: 1.0 };
+        assert_eq!(small.value_at(0.4), (10, false));
+        assert_eq!(small.value_at(0.6), (11, false));
+
+        // A zero-length ramp jumps straight to the target
+        let instant = OverrideRamp { start: 100, target: 5, started_at: 0.0, duration: 0.0 };
+        assert_eq!(instant.value_at(0.0), (5, true));
+    }
+}
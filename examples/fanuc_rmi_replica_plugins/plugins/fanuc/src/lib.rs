@@ -4,7 +4,7 @@
 //! - Conversion between robot-agnostic types and FANUC Position format
 //! - Motion command handler for execution orchestration
 //! - FANUC RMI driver integration
-//! - Robot connection management
+//! - Robot connection management with automatic reconnect
 //! - Program management
 //! - Jogging functionality
 //!
@@ -31,19 +31,27 @@ pub use fanuc_rmi::Position;
 
 cfg_if! {
     if #[cfg(feature = "server")] {
+        mod alarms;
         mod motion;
         mod connection;
+        mod envelope;
         mod handlers;
+        mod io_watch;
         mod jogging;
         mod polling;
+        mod registers;
         mod sync;
         mod validation;
         pub mod database;
 
         pub use motion::{
-            fanuc_motion_handler_system, fanuc_motion_response_system, fanuc_sent_instruction_system,
-            robot_pose_to_fanuc_position, FanucInFlightInstructions, FanucMotionDevice,
+            fanuc_digital_output_system, fanuc_motion_handler_system, fanuc_motion_response_system,
+            fanuc_sent_instruction_system, robot_pose_to_fanuc_position, FanucInFlightInstructions,
+            FanucMotionDevice,
         };
+        pub use connection::{DeferredPackets, ReconnectPolicy};
+        pub use io_watch::MIN_IO_WATCH_INTERVAL_MS;
+        pub use registers::RegisterCache;
         pub use database::FanucDatabaseInit;
     }
 }
@@ -25,11 +25,15 @@ use bevy_tokio_tasks::TokioTasksRuntime;
 use std::collections::HashMap;
 
 use fanuc_rmi::dto as raw_dto;
+use fanuc_rmi::commands::FrcWriteDOUT;
 use fanuc_rmi::instructions::FrcLinearMotion;
-use fanuc_rmi::packets::{Instruction, PacketPriority, ResponsePacket, SendPacket};
+use fanuc_rmi::packets::{Command, Instruction, PacketPriority, ResponsePacket, SendPacket};
 use fanuc_rmi::{Configuration, Position, SpeedType, TermType};
 
-use fanuc_replica_execution::{BufferState, DeviceStatus, MotionCommandEvent, MotionType};
+use fanuc_replica_execution::{
+    BufferState, DeviceStatus, DigitalOutputBackend, DigitalOutputDevice, DigitalOutputWriteEvent,
+    MotionCommandEvent, MotionType,
+};
 use fanuc_replica_robotics::RobotPose;
 
 use crate::connection::{
@@ -83,6 +87,12 @@ impl FanucInFlightInstructions {
         self.by_request.clear();
         self.by_sequence.clear();
     }
+
+    /// Drop tracking for one robot (e.g., when its driver fails).
+    pub fn clear_entity(&mut self, entity: Entity) {
+        self.by_request.retain(|_, (e, _)| *e != entity);
+        self.by_sequence.retain(|_, (e, _)| *e != entity);
+    }
 }
 
 /// System that processes MotionCommandEvents for FANUC robots.
@@ -104,10 +114,11 @@ pub fn fanuc_motion_handler_system(
     let _guard = tokio_runtime.runtime().enter();
 
     for event in motion_events.read() {
-        // Look up the device status
+        // Look up the device status (events for other devices, e.g. the
+        // dry-run simulator, are handled elsewhere)
         let Ok(mut status) = device_query.get_mut(event.device) else {
-            warn!(
-                "MotionCommandEvent for entity {:?} but no FanucMotionDevice found",
+            trace!(
+                "MotionCommandEvent for entity {:?} is not for a FanucMotionDevice",
                 event.device
             );
             continue;
@@ -243,6 +254,61 @@ pub fn fanuc_motion_handler_system(
     }
 }
 
+/// System that performs FANUC DOUT writes for digital output devices.
+///
+/// Devices with the `FanucDout` backend (grippers, valves wired to the robot
+/// controller) follow the robot's connection state, so the orchestrator sees
+/// them as not ready while the robot is disconnected.
+pub fn fanuc_digital_output_system(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    mut write_events: MessageReader<DigitalOutputWriteEvent>,
+    mut devices: Query<&mut DigitalOutputDevice>,
+    driver_query: Query<(&RmiDriver, &RobotConnectionState), With<FanucRobot>>,
+) {
+    let driver = driver_query
+        .single()
+        .ok()
+        .filter(|(_, conn_state)| **conn_state == RobotConnectionState::Connected)
+        .map(|(driver, _)| driver);
+
+    for mut device in devices.iter_mut() {
+        if device.backend == DigitalOutputBackend::FanucDout && device.connected != driver.is_some() {
+            device.connected = driver.is_some();
+        }
+    }
+
+    // Enter the Tokio runtime context so send_packet can use tokio::spawn
+    let _guard = tokio_runtime.runtime().enter();
+
+    for event in write_events.read() {
+        if event.write.backend != DigitalOutputBackend::FanucDout {
+            continue;
+        }
+        let Some(driver) = driver else {
+            warn!(
+                "DOUT[{}] for point {} dropped: robot not connected",
+                event.write.port, event.point_index
+            );
+            continue;
+        };
+
+        let packet = SendPacket::Command(Command::FrcWriteDOUT(FrcWriteDOUT {
+            port_number: event.write.port,
+            port_value: if event.write.value { 1 } else { 0 },
+        }));
+        match driver.0.send_packet(packet, PacketPriority::Standard) {
+            Ok(_) => debug!(
+                "DOUT[{}] = {} (point {})",
+                event.write.port, event.write.value, event.point_index
+            ),
+            Err(e) => error!(
+                "Failed to send DOUT[{}] for point {}: {}",
+                event.write.port, event.point_index, e
+            ),
+        }
+    }
+}
+
 /// System that processes SentInstructionInfo to map request_id -> sequence_id.
 ///
 /// When the driver assigns a sequence ID to an instruction, it broadcasts
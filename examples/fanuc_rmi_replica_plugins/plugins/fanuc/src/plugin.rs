@@ -9,8 +9,9 @@ use pl3xus_websockets::WebSocketProvider;
 
 #[cfg(feature = "server")]
 use crate::motion::{
-    fanuc_motion_handler_system, fanuc_motion_response_system, fanuc_sent_instruction_system,
-    react_to_buffer_state_changes, FanucInFlightInstructions, LastBufferStateCategory,
+    fanuc_digital_output_system, fanuc_motion_handler_system, fanuc_motion_response_system,
+    fanuc_sent_instruction_system, react_to_buffer_state_changes, FanucInFlightInstructions,
+    LastBufferStateCategory,
 };
 #[cfg(feature = "server")]
 use crate::connection::RobotConnectionPlugin;
@@ -21,6 +22,14 @@ use crate::handlers::RequestHandlerPlugin;
 #[cfg(feature = "server")]
 use crate::polling::RobotPollingPlugin;
 #[cfg(feature = "server")]
+use crate::io_watch::IoWatchPlugin;
+#[cfg(feature = "server")]
+use crate::registers::RegisterPlugin;
+#[cfg(feature = "server")]
+use crate::alarms::AlarmPlugin;
+#[cfg(feature = "server")]
+use crate::envelope::WorkspaceEnvelopePlugin;
+#[cfg(feature = "server")]
 use crate::jogging;
 #[cfg(feature = "server")]
 use crate::database::FanucDatabaseInit;
@@ -90,6 +99,15 @@ impl Plugin for FanucPlugin {
         app.sync_component::<IoConfigState>(Some(ComponentSyncConfig::read_only_with_message(
             "IoConfigState is read-only. Use UpdateIoConfig command to modify I/O display settings."
         )));
+        app.sync_component::<ActiveAlarms>(Some(ComponentSyncConfig::read_only_with_message(
+            "ActiveAlarms is read-only. Reset the robot to clear alarms."
+        )));
+        app.sync_component::<OverrideState>(Some(ComponentSyncConfig::read_only_with_message(
+            "OverrideState is read-only. Use SetSpeedOverride command to change speed."
+        )));
+        app.sync_component::<WorkspaceEnvelope>(Some(ComponentSyncConfig::read_only_with_message(
+            "WorkspaceEnvelope is read-only. Use UpdateWorkspaceEnvelope request to change limits."
+        )));
 
         // User-configurable components (clients can mutate with proper authorization)
         app.sync_component::<ActiveConfigState>(None);  // User can change active configuration
@@ -137,6 +155,10 @@ impl Plugin for FanucPlugin {
                 RobotSyncPlugin,          // Driver polling and jogging
                 RequestHandlerPlugin,     // Database request handlers
                 RobotPollingPlugin,       // Periodic position/status polling
+                IoWatchPlugin,            // Watched I/O polling and change notifications
+                RegisterPlugin,           // R[] and PR[] register access
+                AlarmPlugin,              // Alarm polling and history
+                WorkspaceEnvelopePlugin,  // Software workspace limits
                 FanucValidationPlugin,    // Subsystem validation for execution
             ));
 
@@ -168,6 +190,9 @@ impl Plugin for FanucPlugin {
                     .chain(),
             );
 
+            // Digital output devices (grippers, valves) wired to the robot's DOUTs
+            app.add_systems(Update, fanuc_digital_output_system);
+
             info!("🤖 FanucPlugin initialized");
         }
     }
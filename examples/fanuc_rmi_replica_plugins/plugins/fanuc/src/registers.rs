@@ -0,0 +1,519 @@
+//! Numeric (R[]) and position (PR[]) register access.
+//!
+//! Reads are targeted queries; writes require entity control. Every value read
+//! or written is kept in the robot's `RegisterCache`, which backs `ListRegisters`
+//! so a register editor can show what it has seen without re-reading the robot.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::ecs::message::MessageReader;
+use bevy_tokio_tasks::TokioTasksRuntime;
+use fanuc_rmi::dto;
+use fanuc_rmi::drivers::FanucDriver;
+use fanuc_rmi::packets::{Command, CommandResponse, PacketPriority, ResponsePacket, SendPacket};
+use fanuc_rmi::{Configuration, Position};
+use pl3xus::managers::network_request::Request;
+use pl3xus::Network;
+use pl3xus_sync::{broadcast_invalidations_for, AuthorizedRequest, TargetedRequest};
+use pl3xus_sync::authorization::AppBatchRequestRegistrationExt;
+use pl3xus_websockets::WebSocketProvider;
+
+use crate::connection::{FanucRobot, RmiDriver};
+use crate::types::*;
+
+type WS = WebSocketProvider;
+
+/// How long to wait for the robot to answer a register command.
+const REGISTER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Register values seen on a robot - backs `ListRegisters`.
+#[derive(Component, Default, Debug)]
+pub struct RegisterCache {
+    pub numeric: BTreeMap<u16, f64>,
+    pub position: BTreeMap<u16, dto::Position>,
+}
+
+fn to_dto_position(pos: &Position) -> dto::Position {
+    dto::Position {
+        x: pos.x, y: pos.y, z: pos.z,
+        w: pos.w, p: pos.p, r: pos.r,
+        ext1: pos.ext1, ext2: pos.ext2, ext3: pos.ext3,
+    }
+}
+
+fn from_dto_position(pos: &dto::Position) -> Position {
+    Position {
+        x: pos.x, y: pos.y, z: pos.z,
+        w: pos.w, p: pos.p, r: pos.r,
+        ext1: pos.ext1, ext2: pos.ext2, ext3: pos.ext3,
+    }
+}
+
+/// Send a command and wait for the response `extract` picks out.
+///
+/// Subscribes before sending to avoid missing a fast response.
+async fn send_and_await<R>(
+    driver: Arc<FanucDriver>,
+    command: Command,
+    extract: impl Fn(ResponsePacket) -> Option<R>,
+) -> Result<R, String> {
+    let mut response_rx = driver.response_tx.subscribe();
+
+    driver
+        .send_packet(SendPacket::Command(command), PacketPriority::Standard)
+        .map_err(|e| format!("Failed to send command: {}", e))?;
+
+    let result = tokio::time::timeout(REGISTER_TIMEOUT, async {
+        while let Ok(response) = response_rx.recv().await {
+            if let Some(resp) = extract(response) {
+                return Some(resp);
+            }
+        }
+        None
+    }).await;
+
+    match result {
+        Ok(Some(resp)) => Ok(resp),
+        Ok(None) => Err("No response received".to_string()),
+        Err(_) => Err("Timeout waiting for response".to_string()),
+    }
+}
+
+/// Update the register cache on `entity`, creating it if needed.
+fn update_cache(world: &mut World, entity: Entity, update: impl FnOnce(&mut RegisterCache)) {
+    let Ok(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    match entity.get_mut::<RegisterCache>() {
+        Some(mut cache) => update(&mut cache),
+        None => {
+            let mut cache = RegisterCache::default();
+            update(&mut cache);
+            entity.insert(cache);
+        }
+    }
+}
+
+/// Resolve a targeted query to a robot entity and its driver.
+fn resolve_target(
+    target_id: &str,
+    robots: &Query<Option<&RmiDriver>, With<FanucRobot>>,
+) -> Result<(Entity, Arc<FanucDriver>), String> {
+    let entity = target_id
+        .parse::<u64>()
+        .ok()
+        .and_then(Entity::try_from_bits)
+        .ok_or_else(|| format!("Invalid target entity: {}", target_id))?;
+    match robots.get(entity) {
+        Ok(Some(driver)) => Ok((entity, driver.0.clone())),
+        Ok(None) => Err("No robot connected".to_string()),
+        Err(_) => Err(format!("Target {} is not a robot", target_id)),
+    }
+}
+
+/// Handle ReadNumericRegister request - reads R[n] from the robot.
+/// This is a targeted query (no authorization required).
+fn handle_read_numeric_register(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    mut requests: MessageReader<Request<TargetedRequest<ReadNumericRegister>>>,
+    robots: Query<Option<&RmiDriver>, With<FanucRobot>>,
+) {
+    let _guard = tokio_runtime.runtime().enter();
+
+    for request in requests.read() {
+        let targeted = request.get_request();
+        let register_number = targeted.request.register_number;
+        info!("📋 Handling ReadNumericRegister R[{}] on target {}", register_number, targeted.target_id);
+
+        let (entity, driver) = match resolve_target(&targeted.target_id, &robots) {
+            Ok(target) => target,
+            Err(error) => {
+                let _ = request.clone().respond(NumericRegisterResponse {
+                    register_number,
+                    value: 0.0,
+                    success: false,
+                    error: Some(error),
+                });
+                continue;
+            }
+        };
+
+        let request = request.clone();
+        tokio_runtime.spawn_background_task(move |mut ctx| async move {
+            let command = Command::FrcReadNumericRegister(fanuc_rmi::commands::FrcReadNumericRegister {
+                register_number,
+            });
+            let result = send_and_await(driver, command, |response| match response {
+                ResponsePacket::CommandResponse(CommandResponse::FrcReadNumericRegister(resp))
+                    if resp.register_number == register_number => Some(resp),
+                _ => None,
+            }).await;
+
+            let response = match result {
+                Ok(resp) if resp.error_id != 0 => NumericRegisterResponse {
+                    register_number,
+                    value: 0.0,
+                    success: false,
+                    error: Some(format!("Robot error: {}", resp.error_id)),
+                },
+                Ok(resp) => {
+                    let value = resp.register_value as f64;
+                    ctx.run_on_main_thread(move |ctx| {
+                        update_cache(ctx.world, entity, |cache| {
+                            cache.numeric.insert(register_number, value);
+                        });
+                    }).await;
+                    NumericRegisterResponse { register_number, value, success: true, error: None }
+                }
+                Err(error) => NumericRegisterResponse { register_number, value: 0.0, success: false, error: Some(error) },
+            };
+            if let Some(error) = &response.error {
+                bevy::log::error!("Failed to read R[{}]: {}", register_number, error);
+            }
+            let _ = request.respond(response);
+        });
+    }
+}
+
+/// Handle WriteNumericRegister request - writes R[n] and waits for confirmation.
+/// This is a targeted request that requires entity control.
+fn handle_write_numeric_register(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    mut requests: MessageReader<AuthorizedRequest<WriteNumericRegister>>,
+    robots: Query<&RmiDriver, With<FanucRobot>>,
+) {
+    let _guard = tokio_runtime.runtime().enter();
+
+    for request in requests.read() {
+        let inner = request.get_request();
+        let target = request.target_entity;
+        let register_number = inner.register_number;
+        let value = inner.value;
+        info!("📋 Handling WriteNumericRegister R[{}] = {} on entity {:?}", register_number, value, target);
+
+        let Ok(driver) = robots.get(target).map(|d| d.0.clone()) else {
+            let _ = request.clone().respond(WriteRegisterResponse {
+                success: false,
+                error: Some("No robot connected".to_string()),
+            });
+            continue;
+        };
+
+        let request = request.clone();
+        tokio_runtime.spawn_background_task(move |mut ctx| async move {
+            let command = Command::FrcWriteNumericRegister(fanuc_rmi::commands::FrcWriteNumericRegister {
+                register_number,
+                register_value: value as f32,
+            });
+            let result = send_and_await(driver, command, |response| match response {
+                ResponsePacket::CommandResponse(CommandResponse::FrcWriteNumericRegister(resp)) => Some(resp),
+                _ => None,
+            }).await;
+
+            let response = match result {
+                Ok(resp) if resp.error_id != 0 => WriteRegisterResponse {
+                    success: false,
+                    error: Some(format!("Robot error: {}", resp.error_id)),
+                },
+                Ok(_) => {
+                    bevy::log::info!("✅ R[{}] set to {} confirmed by robot", register_number, value);
+                    ctx.run_on_main_thread(move |ctx| {
+                        update_cache(ctx.world, target, |cache| {
+                            cache.numeric.insert(register_number, value);
+                        });
+                        if let Some(net) = ctx.world.get_resource::<Network<WS>>() {
+                            broadcast_invalidations_for::<WriteNumericRegister, WS>(net, None);
+                        }
+                    }).await;
+                    WriteRegisterResponse { success: true, error: None }
+                }
+                Err(error) => WriteRegisterResponse { success: false, error: Some(error) },
+            };
+            if let Some(error) = &response.error {
+                bevy::log::error!("Failed to write R[{}]: {}", register_number, error);
+            }
+            let _ = request.respond(response);
+        });
+    }
+}
+
+/// Handle ReadPositionRegister request - reads PR[n] from the robot.
+/// This is a targeted query (no authorization required).
+fn handle_read_position_register(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    mut requests: MessageReader<Request<TargetedRequest<ReadPositionRegister>>>,
+    robots: Query<Option<&RmiDriver>, With<FanucRobot>>,
+) {
+    let _guard = tokio_runtime.runtime().enter();
+
+    for request in requests.read() {
+        let targeted = request.get_request();
+        let register_number = targeted.request.register_number;
+        info!("📋 Handling ReadPositionRegister PR[{}] on target {}", register_number, targeted.target_id);
+
+        let (entity, driver) = match resolve_target(&targeted.target_id, &robots) {
+            Ok(target) => target,
+            Err(error) => {
+                let _ = request.clone().respond(PositionRegisterResponse {
+                    register_number,
+                    position: None,
+                    success: false,
+                    error: Some(error),
+                });
+                continue;
+            }
+        };
+
+        let request = request.clone();
+        tokio_runtime.spawn_background_task(move |mut ctx| async move {
+            let command = Command::FrcReadPositionRegister(fanuc_rmi::commands::FrcReadPositionRegister {
+                register_number,
+                group: 1,
+            });
+            let result = send_and_await(driver, command, |response| match response {
+                ResponsePacket::CommandResponse(CommandResponse::FrcReadPositionRegister(resp))
+                    if resp.register_number == register_number => Some(resp),
+                _ => None,
+            }).await;
+
+            let response = match result {
+                Ok(resp) if resp.error_id != 0 => PositionRegisterResponse {
+                    register_number,
+                    position: None,
+                    success: false,
+                    error: Some(format!("Robot error: {}", resp.error_id)),
+                },
+                Ok(resp) => {
+                    let position = to_dto_position(&resp.position);
+                    let cached = position.clone();
+                    ctx.run_on_main_thread(move |ctx| {
+                        update_cache(ctx.world, entity, |cache| {
+                            cache.position.insert(register_number, cached);
+                        });
+                    }).await;
+                    PositionRegisterResponse { register_number, position: Some(position), success: true, error: None }
+                }
+                Err(error) => PositionRegisterResponse { register_number, position: None, success: false, error: Some(error) },
+            };
+            if let Some(error) = &response.error {
+                bevy::log::error!("Failed to read PR[{}]: {}", register_number, error);
+            }
+            let _ = request.respond(response);
+        });
+    }
+}
+
+/// Handle WritePositionRegister request - writes PR[n] with the active configuration.
+/// This is a targeted request that requires entity control.
+fn handle_write_position_register(
+    tokio_runtime: Res<TokioTasksRuntime>,
+    mut requests: MessageReader<AuthorizedRequest<WritePositionRegister>>,
+    robots: Query<(&RmiDriver, &ActiveConfigState), With<FanucRobot>>,
+) {
+    let _guard = tokio_runtime.runtime().enter();
+
+    for request in requests.read() {
+        let inner = request.get_request();
+        let target = request.target_entity;
+        let register_number = inner.register_number;
+        let position = inner.position.clone();
+        info!("📋 Handling WritePositionRegister PR[{}] on entity {:?}", register_number, target);
+
+        let Ok((driver, active_config)) = robots.get(target) else {
+            let _ = request.clone().respond(WriteRegisterResponse {
+                success: false,
+                error: Some("No robot connected".to_string()),
+            });
+            continue;
+        };
+        let driver = driver.0.clone();
+        let configuration = Configuration {
+            u_tool_number: active_config.u_tool_number as i8,
+            u_frame_number: active_config.u_frame_number as i8,
+            front: active_config.front as i8,
+            up: active_config.up as i8,
+            left: active_config.left as i8,
+            flip: active_config.flip as i8,
+            turn4: active_config.turn4 as i8,
+            turn5: active_config.turn5 as i8,
+            turn6: active_config.turn6 as i8,
+        };
+
+        let request = request.clone();
+        tokio_runtime.spawn_background_task(move |mut ctx| async move {
+            let command = Command::FrcWritePositionRegister(fanuc_rmi::commands::FrcWritePositionRegister {
+                register_number,
+                configuration,
+                position: from_dto_position(&position),
+                group: 1,
+            });
+            let result = send_and_await(driver, command, |response| match response {
+                ResponsePacket::CommandResponse(CommandResponse::FrcWritePositionRegister(resp)) => Some(resp),
+                _ => None,
+            }).await;
+
+            let response = match result {
+                Ok(resp) if resp.error_id != 0 => WriteRegisterResponse {
+                    success: false,
+                    error: Some(format!("Robot error: {}", resp.error_id)),
+                },
+                Ok(_) => {
+                    bevy::log::info!("✅ PR[{}] written and confirmed by robot", register_number);
+                    ctx.run_on_main_thread(move |ctx| {
+                        update_cache(ctx.world, target, |cache| {
+                            cache.position.insert(register_number, position);
+                        });
+                        if let Some(net) = ctx.world.get_resource::<Network<WS>>() {
+                            broadcast_invalidations_for::<WritePositionRegister, WS>(net, None);
+                        }
+                    }).await;
+                    WriteRegisterResponse { success: true, error: None }
+                }
+                Err(error) => WriteRegisterResponse { success: false, error: Some(error) },
+            };
+            if let Some(error) = &response.error {
+                bevy::log::error!("Failed to write PR[{}]: {}", register_number, error);
+            }
+            let _ = request.respond(response);
+        });
+    }
+}
+
+/// Handle ListRegisters request - returns the cached register values.
+/// This is a targeted query (no authorization required).
+fn handle_list_registers(
+    mut requests: MessageReader<Request<TargetedRequest<ListRegisters>>>,
+    robots: Query<Option<&RegisterCache>, With<FanucRobot>>,
+) {
+    for request in requests.read() {
+        let targeted = request.get_request();
+        debug!("📋 Handling ListRegisters for target {}", targeted.target_id);
+
+        let cache = targeted.target_id
+            .parse::<u64>()
+            .ok()
+            .and_then(Entity::try_from_bits)
+            .and_then(|entity| robots.get(entity).ok().flatten());
+
+        let response = cache
+            .map(|cache| RegistersResponse {
+                numeric: cache.numeric.iter().map(|(&n, &v)| (n, v)).collect(),
+                position: cache.position.iter().map(|(&n, p)| (n, p.clone())).collect(),
+            })
+            .unwrap_or_default();
+
+        if let Err(e) = request.clone().respond(response) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Clear cached registers when a robot disconnects; the controller may change
+/// them while we are away.
+fn clear_register_cache_on_disconnect(
+    mut commands: Commands,
+    mut removed: RemovedComponents<RmiDriver>,
+) {
+    for entity in removed.read() {
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.remove::<RegisterCache>();
+        }
+    }
+}
+
+/// Plugin that adds register read/write requests.
+pub struct RegisterPlugin;
+
+impl Plugin for RegisterPlugin {
+    fn build(&self, app: &mut App) {
+        // Register writes - require entity control
+        app.requests::<(
+            WriteNumericRegister,
+            WritePositionRegister,
+        ), WS>()
+            .targeted()
+            .with_default_entity_policy()
+            .with_error_response();
+
+        // Register reads - no authorization needed
+        app.requests::<(
+            ReadNumericRegister,
+            ReadPositionRegister,
+            ListRegisters,
+        ), WS>()
+            .targeted()
+            .register();
+
+        app.add_systems(Update, (
+            handle_read_numeric_register,
+            handle_write_numeric_register,
+            handle_read_position_register,
+            handle_write_position_register,
+            handle_list_registers,
+            clear_register_cache_on_disconnect,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(x: f64, ext1: f64) -> dto::Position {
+        dto::Position { x, y: 2.0, z: 3.0, w: 4.0, p: 5.0, r: 6.0, ext1, ext2: 0.0, ext3: 0.0 }
+    }
+
+    #[test]
+    fn test_position_conversion_roundtrip() {
+        let original = position(1.5, 7.0);
+        let converted = to_dto_position(&from_dto_position(&original));
+        assert_eq!(
+            (converted.x, converted.y, converted.z, converted.w, converted.p, converted.r, converted.ext1),
+            (1.5, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0)
+        );
+    }
+
+    #[test]
+    fn test_update_cache_creates_and_extends_cache() {
+        let mut world = World::new();
+        let robot = world.spawn_empty().id();
+
+        update_cache(&mut world, robot, |cache| {
+            cache.numeric.insert(5, 2.5);
+        });
+        update_cache(&mut world, robot, |cache| {
+            cache.numeric.insert(5, 3.5);
+            cache.position.insert(1, position(10.0, 0.0));
+        });
+
+        let cache = world.get::<RegisterCache>(robot).unwrap();
+        assert_eq!(cache.numeric.get(&5), Some(&3.5));
+        assert_eq!(cache.position[&1].x, 10.0);
+
+        // Robots that disappeared while the command was in flight are ignored
+        world.despawn(robot);
+        update_cache(&mut world, robot, |_| panic!("no cache for a despawned robot"));
+    }
+
+    #[test]
+    fn test_resolve_target_rejects_invalid_entities() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        let mut resolve = |target: String| {
+            world
+                .run_system_once(move |robots: Query<Option<&RmiDriver>, With<FanucRobot>>| {
+                    resolve_target(&target, &robots).map(|(entity, _)| entity)
+                })
+                .unwrap()
+        };
+
+        // Bits that aren't an entity are an error, not a panic
+        assert_eq!(resolve(u32::MAX.to_string()), Err(format!("Invalid target entity: {}", u32::MAX)));
+        assert!(resolve("robot".to_string()).is_err());
+        assert_eq!(resolve("42".to_string()), Err("Target 42 is not a robot".to_string()));
+    }
+}
@@ -8,11 +8,34 @@ use pl3xus_sync::{
     AppBatchMessageRegistrationExt,
     AppBatchRequestRegistrationExt,
 };
+use pl3xus::{AppPacketHookExt, NetworkPacket, PacketContext, PacketDirection};
+use pl3xus_sync::admin::AppAdminExt;
 use pl3xus_websockets::WebSocketProvider;
 use crate::types::*;
 
 use crate::jogging;
 
+/// Commands recorded in the audit log when a client sends them. Jog
+/// keepalives are left out; they arrive every few hundred milliseconds.
+const AUDITED_COMMANDS: &[&str] = &[
+    "JogCommand",
+    "JogStart",
+    "JogStop",
+    "LinearMotionCommand",
+    "JointMotionCommand",
+    "SendPacket",
+    "InitializeRobot",
+    "ResetRobot",
+    "AbortMotion",
+    "SetSpeedOverride",
+    "SetActiveFrameTool",
+    "WriteFrameData",
+    "WriteToolData",
+    "WriteDout",
+    "WriteAout",
+    "WriteGout",
+];
+
 pub struct RobotSyncPlugin;
 
 impl Plugin for RobotSyncPlugin {
@@ -23,12 +46,22 @@ impl Plugin for RobotSyncPlugin {
         // High-frequency streaming commands that don't need responses.
         // The DefaultEntityAccessPolicy (from ExclusiveControlPlugin) is used.
 
-        // Jog commands are high-frequency and don't need responses
+        // Jog and motion commands are high-frequency and don't need responses.
+        // They are kept in the command log so they can be reviewed and replayed.
         app.messages::<(
             JogCommand,
+            JogStart,
+            JogStop,
             LinearMotionCommand,
             JointMotionCommand,
         ), WebSocketProvider>()
+            .targeted()
+            .with_default_entity_policy()
+            .logged()
+            .register();
+
+        // Keepalives arrive every few hundred milliseconds; not worth logging
+        app.message::<JogKeepalive, WebSocketProvider>()
             .targeted()
             .with_default_entity_policy()
             .register();
@@ -37,6 +70,7 @@ impl Plugin for RobotSyncPlugin {
         app.message::<fanuc_rmi::dto::SendPacket, WebSocketProvider>()
             .targeted()
             .with_default_entity_policy()
+            .logged()
             .register();
 
         // =====================================================================
@@ -85,11 +119,16 @@ impl Plugin for RobotSyncPlugin {
             ReadDinBatch,
             ReadAin,
             ReadGin,
+            IoWatchConfig,
             GetConnectionStatus,
         ), WebSocketProvider>()
             .targeted()
             .register();
 
+        // Log every robot command to the `audit` target, whether or not it
+        // is authorized
+        app.packet_hook::<WebSocketProvider>(audit_robot_commands);
+
         // =====================================================================
         // COMMAND HANDLERS
         // =====================================================================
@@ -98,12 +137,36 @@ impl Plugin for RobotSyncPlugin {
         // Targeted queries use Request<TargetedRequest<T>>
 
         // Jogging and robot control handlers
+        app.init_resource::<jogging::JogWatchdog>();
+        app.runtime_setting(
+            "jog_watchdog_timeout_ms",
+            |world| {
+                let watchdog = world.get_resource::<jogging::JogWatchdog>()?;
+                Some(((watchdog.timeout_secs * 1000.0) as u64).to_string())
+            },
+            |world, value| {
+                let ms: u64 = value
+                    .parse()
+                    .ok()
+                    .filter(|ms| *ms > 0)
+                    .ok_or_else(|| format!("Invalid timeout '{}' (milliseconds)", value))?;
+                world.resource_mut::<jogging::JogWatchdog>().timeout_secs = ms as f64 / 1000.0;
+                Ok(())
+            },
+        );
         app.add_systems(Update, (
             jogging::handle_authorized_jog_commands,
+            (
+                jogging::handle_jog_start,
+                jogging::handle_jog_keepalive,
+                jogging::handle_jog_stop,
+                jogging::stream_continuous_jogs,
+            ).chain(),
             jogging::handle_initialize_robot,
             jogging::handle_abort_motion,
             jogging::handle_reset_robot,
             jogging::handle_set_speed_override,
+            jogging::step_override_ramps,
             jogging::handle_send_packet,
             super::handlers::handle_set_active_frame_tool,
         ));
@@ -130,3 +193,26 @@ impl Plugin for RobotSyncPlugin {
         ));
     }
 }
+
+fn audit_robot_commands(context: &PacketContext, packet: &mut NetworkPacket) -> Result<(), String> {
+    if context.direction == PacketDirection::Inbound {
+        if let Some(command) = robot_command(&packet.type_name) {
+            info!(
+                target: "audit",
+                connection = context.connection.id,
+                client = ?context.info.client_addr,
+                bytes = packet.data.len(),
+                "Robot command {}",
+                command
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The audited command a packet carries, by the name of its innermost type
+/// (e.g. `WriteDout` for a `RequestInternal<TargetedRequest<WriteDout>>`).
+fn robot_command(type_name: &str) -> Option<&str> {
+    let name = type_name.trim_end_matches('>').rsplit("::").next()?;
+    AUDITED_COMMANDS.contains(&name).then_some(name)
+}
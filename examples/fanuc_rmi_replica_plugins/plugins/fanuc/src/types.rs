@@ -144,6 +144,12 @@ pub struct ConnectionState {
     pub active_connection_id: Option<i64>,
     /// Whether the TP (teach pendant) is initialized
     pub tp_initialized: bool,
+    /// Whether the server is reconnecting after the driver connection dropped
+    pub reconnecting: bool,
+    /// Reconnect attempts made since the connection dropped
+    pub reconnect_attempts: u32,
+    /// Why the last connection or reconnect attempt failed
+    pub last_error: Option<String>,
 }
 
 /// A single change ProgramEntry in the active config changelog.
@@ -321,6 +327,196 @@ impl IoConfigState {
     }
 }
 
+/// What the server does with a motion that leaves the workspace envelope.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EnvelopeMode {
+    /// Shorten the motion so it stops at the envelope boundary
+    Clamp,
+    /// Refuse the motion
+    #[default]
+    Reject,
+}
+
+/// A coordinate outside the workspace envelope.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnvelopeViolation {
+    /// "X", "Y" or "Z"
+    pub axis: String,
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl std::fmt::Display for EnvelopeViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} = {:.1} mm is outside the workspace envelope ({:.1} to {:.1} mm)",
+            self.axis, self.value, self.min, self.max
+        )
+    }
+}
+
+/// Software workspace limits - synced component.
+/// Cartesian box (mm, in the robot's active frame) that jog and composer
+/// motions must stay inside. Stored per robot connection.
+#[cfg_attr(feature = "ecs", derive(Component))]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorkspaceEnvelope {
+    pub enabled: bool,
+    pub mode: EnvelopeMode,
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+    pub z_min: f64,
+    pub z_max: f64,
+}
+
+impl Default for WorkspaceEnvelope {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: EnvelopeMode::Reject,
+            x_min: -2000.0,
+            x_max: 2000.0,
+            y_min: -2000.0,
+            y_max: 2000.0,
+            z_min: -500.0,
+            z_max: 2500.0,
+        }
+    }
+}
+
+impl WorkspaceEnvelope {
+    fn axes(&self) -> [(&'static str, f64, f64); 3] {
+        [
+            ("X", self.x_min, self.x_max),
+            ("Y", self.y_min, self.y_max),
+            ("Z", self.z_min, self.z_max),
+        ]
+    }
+
+    /// First coordinate of `(x, y, z)` outside the envelope, if any.
+    /// Always None when the envelope is disabled.
+    pub fn check(&self, x: f64, y: f64, z: f64) -> Option<EnvelopeViolation> {
+        if !self.enabled {
+            return None;
+        }
+        self.axes()
+            .into_iter()
+            .zip([x, y, z])
+            .find(|((_, min, max), value)| value < min || value > max)
+            .map(|((axis, min, max), value)| EnvelopeViolation { axis: axis.to_string(), value, min, max })
+    }
+
+    /// Clamp `(x, y, z)` into the envelope (unchanged when disabled).
+    pub fn clamp(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        if !self.enabled {
+            return (x, y, z);
+        }
+        (
+            x.clamp(self.x_min, self.x_max),
+            y.clamp(self.y_min, self.y_max),
+            z.clamp(self.z_min, self.z_max),
+        )
+    }
+
+    /// Check that each min is below its max.
+    pub fn validate(&self) -> Result<(), String> {
+        for (axis, min, max) in self.axes() {
+            if min.partial_cmp(&max) != Some(std::cmp::Ordering::Less) {
+                return Err(format!("{} min ({}) must be less than max ({})", axis, min, max));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Speed override state - synced component.
+/// `current` is the override last applied on the controller. During a ramp
+/// (`SetSpeedOverride::ramp_ms > 0`) it steps toward `target`.
+#[cfg_attr(feature = "ecs", derive(Component))]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OverrideState {
+    pub current: u8,
+    pub target: u8,
+    pub ramping: bool,
+}
+
+impl Default for OverrideState {
+    fn default() -> Self {
+        Self { current: 100, target: 100, ramping: false }
+    }
+}
+
+/// Alarm severity, derived from the alarm's facility code.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AlarmSeverity {
+    #[default]
+    Warning,
+    Stop,
+    Servo,
+    System,
+}
+
+impl AlarmSeverity {
+    /// Classify an alarm code such as "SRVO-001".
+    ///
+    /// RMI does not report severity, so this is an approximation by facility:
+    /// servo and system alarms are the serious ones, motion and program alarms
+    /// stop the robot, everything else is treated as a warning.
+    pub fn from_code(code: &str) -> Self {
+        match code.split('-').next().unwrap_or("") {
+            "SRVO" => AlarmSeverity::Servo,
+            "SYST" => AlarmSeverity::System,
+            "MOTN" | "INTP" | "RMIT" => AlarmSeverity::Stop,
+            _ => AlarmSeverity::Warning,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlarmSeverity::Warning => "warning",
+            AlarmSeverity::Stop => "stop",
+            AlarmSeverity::Servo => "servo",
+            AlarmSeverity::System => "system",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "stop" => AlarmSeverity::Stop,
+            "servo" => AlarmSeverity::Servo,
+            "system" => AlarmSeverity::System,
+            _ => AlarmSeverity::Warning,
+        }
+    }
+}
+
+/// A controller alarm, as stored in the alarm history.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AlarmRecord {
+    pub id: i64,
+    pub robot_name: String,
+    /// Alarm code, e.g. "SRVO-001"
+    pub code: String,
+    pub message: String,
+    pub severity: AlarmSeverity,
+    /// RFC 3339 timestamp
+    pub occurred_at: String,
+    /// RFC 3339 timestamp, set when the controller was reset
+    pub cleared_at: Option<String>,
+}
+
+/// Active alarms - synced component.
+/// Alarms raised since the controller was last reset, newest first.
+#[cfg_attr(feature = "ecs", derive(Component))]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ActiveAlarms {
+    pub alarms: Vec<AlarmRecord>,
+}
+
 /// Frame/Tool data state - synced component.
 /// Stores all frame (1-9) and tool (1-10) data read from the robot.
 /// Also tracks active frame/tool indices.
@@ -516,6 +712,27 @@ pub struct JogCommand {
     pub direction: JogDirection,
 }
 
+/// Start a continuous (hold-to-move) jog.
+///
+/// The server streams short relative moves until `JogStop` arrives, keepalives
+/// stop arriving, or the jog watchdog expires. Send `JogKeepalive` at least
+/// every 100ms while the jog button is held.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JogStart {
+    pub axis: JogAxis,
+    pub direction: JogDirection,
+    /// Speed in mm/s or °/s; None uses the robot's JogSettingsState
+    pub speed: Option<f64>,
+}
+
+/// Keep a continuous jog alive.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JogKeepalive;
+
+/// Stop a continuous jog.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JogStop;
+
 /// Request to list all saved robot connections from the database.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ListRobotConnections;
@@ -681,6 +898,10 @@ impl ErrorResponse for AbortMotion {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SetSpeedOverride {
     pub speed: u8, // 0-100%
+    /// Ramp from the current override to `speed` over this many milliseconds
+    /// (0 = apply immediately)
+    #[serde(default)]
+    pub ramp_ms: u32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -1197,29 +1418,6 @@ pub struct ToolDataResponse {
     pub r: f64,
 }
 
-// Command log entry (for Command Log panel)
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub struct CommandLogEntry {
-    pub timestamp: String,
-    pub timestamp_ms: u64,
-    pub command_type: CommandType,
-    pub description: String,
-    pub command_data: String, // JSON serialized command for re-run
-}
-
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
-pub enum CommandType {
-    LinearAbsolute,
-    LinearRelative,
-    JointAbsolute,
-    JointRelative,
-    CircularAbsolute,
-    CircularRelative,
-    Jog,
-    RotationJog,
-    System, // Initialize, Reset, Abort
-}
-
 // ============================================================================
 // I/O Messages
 // ============================================================================
@@ -1370,6 +1568,98 @@ impl ErrorResponse for WriteGout {
     }
 }
 
+// --- I/O Watches ---
+
+/// I/O port type that can be watched for changes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum WatchedIoType {
+    #[default]
+    Din,
+    Dout,
+    Ain,
+}
+
+impl WatchedIoType {
+    /// Type name as used in `IoConfigState` keys ("DIN", "DOUT", "AIN").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WatchedIoType::Din => "DIN",
+            WatchedIoType::Dout => "DOUT",
+            WatchedIoType::Ain => "AIN",
+        }
+    }
+}
+
+/// Value of a watched I/O port.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum IoValue {
+    Digital(bool),
+    Analog(f64),
+}
+
+impl Default for IoValue {
+    fn default() -> Self {
+        IoValue::Digital(false)
+    }
+}
+
+/// Register the I/O ports this client wants monitored on the target robot.
+///
+/// Replaces the client's previous watch set; an empty config stops watching.
+/// Only watched ports are polled, each at the shortest interval any client
+/// asked for. Changes are broadcast as `IoChanged`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct IoWatchConfig {
+    pub din: Vec<u16>,
+    pub dout: Vec<u16>,
+    pub ain: Vec<u16>,
+    /// Poll interval in milliseconds (clamped to a server-side minimum)
+    pub interval_ms: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IoWatchConfigResponse {
+    pub success: bool,
+    /// Number of ports this client is now watching
+    pub watched_ports: u32,
+    /// Interval actually used after clamping
+    pub interval_ms: u32,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for IoWatchConfig {
+    type ResponseMessage = IoWatchConfigResponse;
+}
+
+static IO_CHANGE_SEQUENCE: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Broadcast when a watched I/O port changes value.
+#[cfg_attr(feature = "ecs", derive(Message))]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct IoChanged {
+    /// Unique sequence number to distinguish identical notifications
+    pub sequence: u64,
+    /// Robot entity (as entity bits)
+    pub robot_entity: u64,
+    pub io_type: WatchedIoType,
+    pub port: u16,
+    pub previous: IoValue,
+    pub value: IoValue,
+}
+
+impl IoChanged {
+    pub fn new(robot_entity: u64, io_type: WatchedIoType, port: u16, previous: IoValue, value: IoValue) -> Self {
+        Self {
+            sequence: IO_CHANGE_SEQUENCE.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            robot_entity,
+            io_type,
+            port,
+            previous,
+            value,
+        }
+    }
+}
+
 // --- I/O Configuration ---
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -1402,6 +1692,171 @@ impl RequestMessage for UpdateIoConfig {
     type ResponseMessage = UpdateIoConfigResponse;
 }
 
+// ============================================================================
+// Register Messages
+// ============================================================================
+
+/// Read a numeric register (R[n]).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReadNumericRegister {
+    pub register_number: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NumericRegisterResponse {
+    pub register_number: u16,
+    pub value: f64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for ReadNumericRegister {
+    type ResponseMessage = NumericRegisterResponse;
+}
+
+/// Write a numeric register (R[n]). Requires entity control.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "server", derive(Invalidates))]
+#[cfg_attr(feature = "server", invalidates("ListRegisters"))]
+pub struct WriteNumericRegister {
+    pub register_number: u16,
+    pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "server", derive(HasSuccess))]
+pub struct WriteRegisterResponse {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for WriteNumericRegister {
+    type ResponseMessage = WriteRegisterResponse;
+}
+
+#[cfg(feature = "ecs")]
+impl ErrorResponse for WriteNumericRegister {
+    fn error_response(error: String) -> Self::ResponseMessage {
+        WriteRegisterResponse { success: false, error: Some(error) }
+    }
+}
+
+/// Read a position register (PR[n]) as a Cartesian position.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReadPositionRegister {
+    pub register_number: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PositionRegisterResponse {
+    pub register_number: u16,
+    pub position: Option<dto::Position>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for ReadPositionRegister {
+    type ResponseMessage = PositionRegisterResponse;
+}
+
+/// Write a position register (PR[n]). Requires entity control.
+///
+/// The robot's active configuration (frame, tool, arm config) is stored with
+/// the position.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "server", derive(Invalidates))]
+#[cfg_attr(feature = "server", invalidates("ListRegisters"))]
+pub struct WritePositionRegister {
+    pub register_number: u16,
+    pub position: dto::Position,
+}
+
+impl RequestMessage for WritePositionRegister {
+    type ResponseMessage = WriteRegisterResponse;
+}
+
+#[cfg(feature = "ecs")]
+impl ErrorResponse for WritePositionRegister {
+    fn error_response(error: String) -> Self::ResponseMessage {
+        WriteRegisterResponse { success: false, error: Some(error) }
+    }
+}
+
+/// List the register values read or written since the robot connected.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ListRegisters;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RegistersResponse {
+    /// Numeric registers, ordered by register number
+    pub numeric: Vec<(u16, f64)>,
+    /// Position registers, ordered by register number
+    pub position: Vec<(u16, dto::Position)>,
+}
+
+impl RequestMessage for ListRegisters {
+    type ResponseMessage = RegistersResponse;
+}
+
+// ============================================================================
+// Alarm Messages
+// ============================================================================
+
+/// Get a page of the alarm history, newest first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GetAlarmHistory {
+    /// Only alarms from this robot (all robots if None)
+    pub robot_name: Option<String>,
+    /// Zero-based page index
+    pub page: u32,
+    pub page_size: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AlarmHistoryResponse {
+    pub alarms: Vec<AlarmRecord>,
+    /// Total number of matching alarms
+    pub total: u32,
+    pub page: u32,
+    pub page_size: u32,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for GetAlarmHistory {
+    type ResponseMessage = AlarmHistoryResponse;
+}
+
+// ============================================================================
+// Workspace Envelope Messages
+// ============================================================================
+
+/// Replace the robot's workspace envelope. Requires entity control.
+///
+/// Saved to the database when the robot was connected from a saved connection.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateWorkspaceEnvelope {
+    pub envelope: WorkspaceEnvelope,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateWorkspaceEnvelopeResponse {
+    pub success: bool,
+    /// Whether the envelope was stored for the saved connection
+    pub persisted: bool,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for UpdateWorkspaceEnvelope {
+    type ResponseMessage = UpdateWorkspaceEnvelopeResponse;
+}
+
+#[cfg(feature = "ecs")]
+impl ErrorResponse for UpdateWorkspaceEnvelope {
+    fn error_response(error: String) -> Self::ResponseMessage {
+        UpdateWorkspaceEnvelopeResponse { success: false, persisted: false, error: Some(error) }
+    }
+}
+
 // ============================================================================
 // Settings Messages
 // ============================================================================
@@ -1,74 +1,74 @@
 //! Subsystem validation for the FANUC plugin.
 //!
 //! This module provides:
-//! - Subsystem registration on startup
-//! - Validation system that checks robot connection status
+//! - Subsystem registration on every execution coordinator
+//! - Validation system that checks each coordinator's robot connection status
 
 use bevy::prelude::*;
 
 use crate::connection::{FanucRobot, RobotConnectionState};
-use fanuc_replica_core::ActiveSystem;
 use fanuc_replica_execution::{
-    BufferState, SubsystemReadiness, Subsystems, SubsystemValidation, SUBSYSTEM_FANUC,
+    BufferState, ExecutionState, SubsystemReadiness, Subsystems, SubsystemValidation,
+    SUBSYSTEM_FANUC,
 };
 
-/// Register the FANUC subsystem on startup.
+/// Register the FANUC subsystem on every coordinator.
 ///
-/// This runs once when the System entity exists and adds the FANUC
-/// subsystem to the Subsystems component.
-pub fn register_fanuc_subsystem(
-    mut systems: Query<&mut Subsystems, With<ActiveSystem>>,
-    mut registered: Local<bool>,
-) {
-    if *registered {
-        return;
-    }
-
-    if let Ok(mut subsystems) = systems.single_mut() {
+/// Runs whenever a Subsystems component is added, so coordinators spawned
+/// later are covered too.
+pub fn register_fanuc_subsystem(mut systems: Query<(Entity, &mut Subsystems), Added<Subsystems>>) {
+    for (entity, mut subsystems) in systems.iter_mut() {
         subsystems.register(SUBSYSTEM_FANUC);
-        *registered = true;
-        info!("🤖 Registered '{}' subsystem", SUBSYSTEM_FANUC);
+        info!("🤖 Registered '{}' subsystem on {:?}", SUBSYSTEM_FANUC, entity);
     }
 }
 
 /// Validate the FANUC subsystem during the Validating phase.
 ///
 /// This system:
-/// - Only runs when BufferState::Validating
-/// - Checks if any FANUC robot is connected
-/// - Sets subsystem readiness accordingly
+/// - Only checks coordinators in BufferState::Validating or ValidatingForResume
+/// - Checks if a FANUC robot among the coordinator's children is connected
+/// - Sets that coordinator's subsystem readiness accordingly
+///
+/// Dry runs execute on the built-in simulator, so the robot is not required.
 pub fn validate_fanuc_subsystem(
     robots: Query<&RobotConnectionState, With<FanucRobot>>,
-    buffer_state_query: Query<&BufferState, With<ActiveSystem>>,
-    mut subsystems_query: Query<&mut Subsystems, With<ActiveSystem>>,
+    mut coordinators: Query<(
+        &BufferState,
+        Option<&ExecutionState>,
+        Option<&Children>,
+        &mut Subsystems,
+    )>,
 ) {
-    let Ok(buffer_state) = buffer_state_query.single() else {
-        return;
-    };
+    for (buffer_state, exec_state, children, mut subsystems) in coordinators.iter_mut() {
+        // Only validate when validating for start or resume
+        if !buffer_state.is_validating() {
+            continue;
+        }
 
-    // Only validate when in Validating state
-    if !matches!(buffer_state, BufferState::Validating) {
-        return;
-    }
-
-    let Ok(mut subsystems) = subsystems_query.single_mut() else {
-        return;
-    };
+        if exec_state.map_or(false, |exec| exec.simulated) {
+            subsystems.set_readiness(SUBSYSTEM_FANUC, SubsystemReadiness::Ready);
+            trace!("✅ FANUC subsystem ready (dry run, robot not required)");
+            continue;
+        }
 
-    // Check if any robot is connected
-    let connected = robots
-        .iter()
-        .any(|state| *state == RobotConnectionState::Connected);
+        // Check if this coordinator's robot is connected
+        let connected = children
+            .into_iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|child| robots.get(child).ok())
+            .any(|state| *state == RobotConnectionState::Connected);
 
-    if connected {
-        subsystems.set_readiness(SUBSYSTEM_FANUC, SubsystemReadiness::Ready);
-        trace!("✅ FANUC subsystem ready (robot connected)");
-    } else {
-        subsystems.set_readiness(
-            SUBSYSTEM_FANUC,
-            SubsystemReadiness::Error("No FANUC robot connected".to_string()),
-        );
-        trace!("❌ FANUC subsystem not ready (no robot connected)");
+        if connected {
+            subsystems.set_readiness(SUBSYSTEM_FANUC, SubsystemReadiness::Ready);
+            trace!("✅ FANUC subsystem ready (robot connected)");
+        } else {
+            subsystems.set_readiness(
+                SUBSYSTEM_FANUC,
+                SubsystemReadiness::Error("No FANUC robot connected".to_string()),
+            );
+            trace!("❌ FANUC subsystem not ready (no robot connected)");
+        }
     }
 }
 
@@ -3,6 +3,7 @@
 use rusqlite::{Connection, OptionalExtension};
 use crate::types::{
     ProgramInfo, ProgramDetail, Instruction, InstructionSequence, SequenceType,
+    ProgramVersion, ProgramVersionInfo,
 };
 
 // ============================================================================
@@ -321,3 +322,303 @@ pub fn insert_instructions(conn: &Connection, sequence_id: i64, instructions: &[
     Ok(())
 }
 
+/// Replace the instructions of every sequence of a program.
+///
+/// Sequences are matched by id; settings and the sequence list itself are
+/// left unchanged. The result is recorded as a new version, whose number is
+/// returned.
+pub fn replace_program_instructions(
+    conn: &Connection,
+    program: &ProgramDetail,
+    change_summary: &str,
+) -> anyhow::Result<i32> {
+    ensure_initial_version(conn, program.id)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let sequences = program.approach_sequences.iter()
+        .chain(std::iter::once(&program.main_sequence))
+        .chain(program.retreat_sequences.iter());
+    for seq in sequences {
+        insert_instructions(&tx, seq.id, &seq.instructions)?;
+    }
+    let version = record_version(&tx, program.id, change_summary)?;
+    tx.commit()?;
+
+    Ok(version)
+}
+
+// ============================================================================
+// Versions
+// ============================================================================
+
+fn sequence_type_str(seq_type: SequenceType) -> &'static str {
+    match seq_type {
+        SequenceType::Approach => "approach",
+        SequenceType::Main => "main",
+        SequenceType::Retreat => "retreat",
+    }
+}
+
+fn parse_sequence_type(s: &str) -> SequenceType {
+    match s {
+        "approach" => SequenceType::Approach,
+        "retreat" => SequenceType::Retreat,
+        _ => SequenceType::Main,
+    }
+}
+
+/// Record the current contents of a program as a new version.
+///
+/// Returns the new version number.
+pub fn record_version(conn: &Connection, program_id: i64, change_summary: &str) -> anyhow::Result<i32> {
+    let program = get_program(conn, program_id)?
+        .ok_or_else(|| anyhow::anyhow!("Program {} not found", program_id))?;
+
+    let version: i32 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) + 1 FROM program_versions WHERE program_id = ?",
+        [program_id],
+        |row| row.get(0),
+    )?;
+
+    conn.execute(
+        "INSERT INTO program_versions
+         (program_id, version, change_summary, name, description,
+          default_speed, default_term_type, default_term_value, move_speed)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![
+            program_id,
+            version,
+            change_summary,
+            program.name,
+            program.description,
+            program.default_speed,
+            program.default_term_type,
+            program.default_term_value.map(|v| v as i32),
+            program.move_speed,
+        ],
+    )?;
+    let version_id = conn.last_insert_rowid();
+
+    let sequences = program.approach_sequences.iter()
+        .chain(std::iter::once(&program.main_sequence))
+        .chain(program.retreat_sequences.iter());
+    for seq in sequences {
+        for instr in &seq.instructions {
+            conn.execute(
+                "INSERT INTO program_version_instructions
+                 (version_id, sequence_type, sequence_name, sequence_order, line_number,
+                  x, y, z, w, p, r, ext1, ext2, ext3, speed, term_type, term_value)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    version_id,
+                    sequence_type_str(seq.sequence_type),
+                    seq.name,
+                    seq.order_index,
+                    instr.line_number,
+                    instr.x,
+                    instr.y,
+                    instr.z,
+                    instr.w,
+                    instr.p,
+                    instr.r,
+                    instr.ext1,
+                    instr.ext2,
+                    instr.ext3,
+                    instr.speed,
+                    instr.term_type,
+                    instr.term_value.map(|v| v as i32),
+                ],
+            )?;
+        }
+    }
+
+    Ok(version)
+}
+
+/// Record the current contents as the initial version if the program has none.
+///
+/// Called before a change, so programs created before versioning existed can
+/// still be rolled back to their original contents.
+pub fn ensure_initial_version(conn: &Connection, program_id: i64) -> anyhow::Result<()> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM program_versions WHERE program_id = ?",
+        [program_id],
+        |row| row.get(0),
+    )?;
+    if count == 0 && get_program(conn, program_id)?.is_some() {
+        record_version(conn, program_id, "Initial version")?;
+    }
+    Ok(())
+}
+
+/// List the versions of a program, newest first.
+pub fn list_versions(conn: &Connection, program_id: i64) -> anyhow::Result<Vec<ProgramVersionInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT v.program_id, v.version, v.change_summary,
+                (SELECT COUNT(*) FROM program_version_instructions vi
+                 WHERE vi.version_id = v.id AND vi.sequence_type = 'main') as instruction_count,
+                COALESCE(v.created_at, datetime('now')) as created_at
+         FROM program_versions v WHERE v.program_id = ? ORDER BY v.version DESC"
+    )?;
+
+    let versions = stmt.query_map([program_id], |row| {
+        Ok(ProgramVersionInfo {
+            program_id: row.get(0)?,
+            version: row.get(1)?,
+            change_summary: row.get(2)?,
+            instruction_count: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    })?.collect::<Result<Vec<_>, _>>()?;
+
+    Ok(versions)
+}
+
+/// Get a program version with its full contents.
+pub fn get_version(conn: &Connection, program_id: i64, version: i32) -> anyhow::Result<Option<ProgramVersion>> {
+    let row: Option<(i64, ProgramVersionInfo, ProgramDetail)> = conn.query_row(
+        "SELECT id, change_summary, COALESCE(created_at, datetime('now')),
+                name, description, default_speed, default_term_type, default_term_value, move_speed
+         FROM program_versions WHERE program_id = ? AND version = ?",
+        rusqlite::params![program_id, version],
+        |row| {
+            let created_at: String = row.get(2)?;
+            Ok((
+                row.get(0)?,
+                ProgramVersionInfo {
+                    program_id,
+                    version,
+                    change_summary: row.get(1)?,
+                    instruction_count: 0,
+                    created_at: created_at.clone(),
+                },
+                ProgramDetail {
+                    id: program_id,
+                    name: row.get(3)?,
+                    description: row.get(4)?,
+                    default_speed: row.get(5)?,
+                    default_term_type: row.get(6)?,
+                    default_term_value: row.get(7)?,
+                    move_speed: row.get(8)?,
+                    approach_sequences: vec![],
+                    main_sequence: InstructionSequence::default(),
+                    retreat_sequences: vec![],
+                    created_at: created_at.clone(),
+                    updated_at: created_at,
+                },
+            ))
+        },
+    ).optional()?;
+
+    let Some((version_id, mut info, mut program)) = row else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT sequence_type, sequence_name, sequence_order,
+                line_number, x, y, z, w, p, r, ext1, ext2, ext3, speed, term_type, term_value
+         FROM program_version_instructions WHERE version_id = ?
+         ORDER BY sequence_type, sequence_order, line_number"
+    )?;
+    let rows = stmt.query_map([version_id], |row| {
+        let seq_type: String = row.get(0)?;
+        Ok((
+            parse_sequence_type(&seq_type),
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, i32>(2)?,
+            Instruction {
+                line_number: row.get(3)?,
+                x: row.get(4)?,
+                y: row.get(5)?,
+                z: row.get(6)?,
+                w: row.get(7)?,
+                p: row.get(8)?,
+                r: row.get(9)?,
+                ext1: row.get(10)?,
+                ext2: row.get(11)?,
+                ext3: row.get(12)?,
+                speed: row.get(13)?,
+                term_type: row.get(14)?,
+                term_value: row.get(15)?,
+            },
+        ))
+    })?.collect::<Result<Vec<_>, _>>()?;
+
+    // Group instructions back into sequences
+    let mut sequences: Vec<InstructionSequence> = Vec::new();
+    for (sequence_type, name, order_index, instruction) in rows {
+        match sequences.last_mut() {
+            Some(seq) if seq.sequence_type == sequence_type && seq.order_index == order_index => {
+                seq.instructions.push(instruction);
+            }
+            _ => sequences.push(InstructionSequence {
+                id: 0,
+                sequence_type,
+                name,
+                order_index,
+                instructions: vec![instruction],
+            }),
+        }
+    }
+
+    for seq in sequences {
+        match seq.sequence_type {
+            SequenceType::Approach => program.approach_sequences.push(seq),
+            SequenceType::Main => program.main_sequence = seq,
+            SequenceType::Retreat => program.retreat_sequences.push(seq),
+        }
+    }
+    info.instruction_count = program.main_sequence.instructions.len() as i64;
+
+    Ok(Some(ProgramVersion { info, program }))
+}
+
+/// Restore a program to a previous version.
+///
+/// Settings and all sequences are replaced by the stored ones, then the
+/// result is recorded as a new version. Returns the new version number.
+pub fn rollback_to_version(conn: &Connection, program_id: i64, version: i32) -> anyhow::Result<i32> {
+    let target = get_version(conn, program_id, version)?
+        .ok_or_else(|| anyhow::anyhow!("Version {} of program {} not found", version, program_id))?;
+    let program = &target.program;
+
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute(
+        "UPDATE programs SET name = ?, description = ?, default_speed = ?, default_term_type = ?,
+                default_term_value = ?, move_speed = ?, updated_at = datetime('now')
+         WHERE id = ?",
+        rusqlite::params![
+            program.name,
+            program.description,
+            program.default_speed,
+            program.default_term_type,
+            program.default_term_value.map(|v| v as i32),
+            program.move_speed,
+            program_id,
+        ],
+    )?;
+
+    tx.execute(
+        "DELETE FROM program_instructions WHERE sequence_id IN
+         (SELECT id FROM program_sequences WHERE program_id = ?)",
+        [program_id],
+    )?;
+    tx.execute("DELETE FROM program_sequences WHERE program_id = ?", [program_id])?;
+
+    let sequences = program.approach_sequences.iter()
+        .chain(std::iter::once(&program.main_sequence))
+        .chain(program.retreat_sequences.iter());
+    for seq in sequences {
+        tx.execute(
+            "INSERT INTO program_sequences (program_id, sequence_type, name, order_index) VALUES (?, ?, ?, ?)",
+            rusqlite::params![program_id, sequence_type_str(seq.sequence_type), seq.name, seq.order_index],
+        )?;
+        insert_instructions(&tx, tx.last_insert_rowid(), &seq.instructions)?;
+    }
+
+    let new_version = record_version(&tx, program_id, &format!("Rolled back to version {}", version))?;
+    tx.commit()?;
+
+    Ok(new_version)
+}
@@ -3,6 +3,9 @@
 use fanuc_replica_core::DatabaseInit;
 use rusqlite::Connection;
 
+use super::queries;
+use crate::types::Instruction;
+
 /// Programs plugin database initializer.
 pub struct ProgramsDatabaseInit;
 
@@ -97,6 +100,63 @@ impl DatabaseInit for ProgramsDatabaseInit {
             [],
         )?;
 
+        // Program versions - snapshot of a program's settings after each change
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS program_versions (
+                id INTEGER PRIMARY KEY,
+                program_id INTEGER NOT NULL,
+                version INTEGER NOT NULL,
+                change_summary TEXT NOT NULL,
+
+                -- Program settings at this version
+                name TEXT NOT NULL,
+                description TEXT,
+                default_speed REAL,
+                default_term_type TEXT,
+                default_term_value INTEGER,
+                move_speed REAL NOT NULL,
+
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (program_id) REFERENCES programs(id) ON DELETE CASCADE,
+                UNIQUE(program_id, version)
+            )",
+            [],
+        )?;
+
+        // Instructions of each version, with the sequence they belonged to
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS program_version_instructions (
+                id INTEGER PRIMARY KEY,
+                version_id INTEGER NOT NULL,
+                sequence_type TEXT NOT NULL,
+                sequence_name TEXT,
+                sequence_order INTEGER NOT NULL,
+                line_number INTEGER NOT NULL,
+
+                x REAL NOT NULL,
+                y REAL NOT NULL,
+                z REAL NOT NULL,
+                w REAL,
+                p REAL,
+                r REAL,
+                ext1 REAL,
+                ext2 REAL,
+                ext3 REAL,
+                speed REAL,
+                term_type TEXT,
+                term_value INTEGER,
+
+                FOREIGN KEY (version_id) REFERENCES program_versions(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_version_instructions
+             ON program_version_instructions(version_id, sequence_type, sequence_order, line_number)",
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -110,5 +170,66 @@ impl DatabaseInit for ProgramsDatabaseInit {
         // No seed data needed
         Ok(())
     }
+
+    fn seed_demo_data(&self, conn: &Connection) -> anyhow::Result<()> {
+        seed_demo_program(conn, "Demo Square", "100mm square at constant height", &[
+            (400.0, 0.0, 300.0),
+            (500.0, 0.0, 300.0),
+            (500.0, 100.0, 300.0),
+            (400.0, 100.0, 300.0),
+            (400.0, 0.0, 300.0),
+        ])?;
+        seed_demo_program(conn, "Demo Zigzag", "Raster over a 200x100mm area", &[
+            (300.0, -100.0, 250.0),
+            (500.0, -100.0, 250.0),
+            (500.0, -50.0, 250.0),
+            (300.0, -50.0, 250.0),
+            (300.0, 0.0, 250.0),
+            (500.0, 0.0, 250.0),
+        ])?;
+        Ok(())
+    }
+}
+
+/// Create a demo program with `points` as its main sequence, unless a
+/// program with that name exists.
+fn seed_demo_program(
+    conn: &Connection,
+    name: &str,
+    description: &str,
+    points: &[(f64, f64, f64)],
+) -> anyhow::Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM programs WHERE name = ?",
+        [name],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Ok(());
+    }
+
+    let program_id = queries::create_program(conn, name, Some(description))?;
+    let sequence_id = queries::get_main_sequence_id(conn, program_id)?
+        .ok_or_else(|| anyhow::anyhow!("Program {} has no main sequence", name))?;
+    let instructions: Vec<Instruction> = points
+        .iter()
+        .enumerate()
+        .map(|(index, &(x, y, z))| Instruction {
+            line_number: index as i32 + 1,
+            x,
+            y,
+            z,
+            w: Some(180.0),
+            p: Some(0.0),
+            r: Some(0.0),
+            speed: Some(100.0),
+            term_type: Some("CNT".into()),
+            term_value: Some(100),
+            ..Default::default()
+        })
+        .collect();
+    queries::insert_instructions(conn, sequence_id, &instructions)?;
+    queries::record_version(conn, program_id, "Demo data")?;
+    Ok(())
 }
 
@@ -0,0 +1,231 @@
+//! Line-level differences between program versions.
+//!
+//! Instruction lines are compared by value with a longest-common-subsequence
+//! match. Between two matched lines, removed and added lines are paired up in
+//! order as modifications; any left over are reported as removed or added.
+
+use crate::types::{
+    Instruction, InstructionSequence, LineChangeKind, LineDiff, ProgramDetail, ProgramDiff,
+    SequenceDiff, SettingChange,
+};
+
+/// Compute the differences between two versions of a program.
+pub fn diff_programs(
+    from_version: i32,
+    from: &ProgramDetail,
+    to_version: i32,
+    to: &ProgramDetail,
+) -> ProgramDiff {
+    ProgramDiff {
+        from_version,
+        to_version,
+        settings: diff_settings(from, to),
+        sequences: diff_sequences(from, to),
+    }
+}
+
+fn diff_settings(from: &ProgramDetail, to: &ProgramDetail) -> Vec<SettingChange> {
+    let fields = [
+        ("name", Some(from.name.clone()), Some(to.name.clone())),
+        ("description", from.description.clone(), to.description.clone()),
+        (
+            "default_speed",
+            from.default_speed.map(|v| v.to_string()),
+            to.default_speed.map(|v| v.to_string()),
+        ),
+        (
+            "default_term_type",
+            from.default_term_type.clone(),
+            to.default_term_type.clone(),
+        ),
+        (
+            "default_term_value",
+            from.default_term_value.map(|v| v.to_string()),
+            to.default_term_value.map(|v| v.to_string()),
+        ),
+        (
+            "move_speed",
+            Some(from.move_speed.to_string()),
+            Some(to.move_speed.to_string()),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| SettingChange {
+            field: field.to_string(),
+            old,
+            new,
+        })
+        .collect()
+}
+
+fn all_sequences(program: &ProgramDetail) -> impl Iterator<Item = &InstructionSequence> {
+    program
+        .approach_sequences
+        .iter()
+        .chain(std::iter::once(&program.main_sequence))
+        .chain(program.retreat_sequences.iter())
+}
+
+fn diff_sequences(from: &ProgramDetail, to: &ProgramDetail) -> Vec<SequenceDiff> {
+    let same_slot = |a: &InstructionSequence, b: &InstructionSequence| {
+        a.sequence_type == b.sequence_type && a.order_index == b.order_index
+    };
+
+    let mut diffs = Vec::new();
+    for old in all_sequences(from) {
+        let new = all_sequences(to).find(|new| same_slot(old, new));
+        let lines = diff_lines(
+            &old.instructions,
+            new.map_or(&[][..], |new| &new.instructions),
+        );
+        if !lines.is_empty() {
+            diffs.push(SequenceDiff {
+                sequence_type: old.sequence_type,
+                order_index: old.order_index,
+                name: new.map_or_else(|| old.name.clone(), |new| new.name.clone()),
+                lines,
+            });
+        }
+    }
+    for new in all_sequences(to).filter(|new| !all_sequences(from).any(|old| same_slot(old, new))) {
+        let lines = diff_lines(&[], &new.instructions);
+        if !lines.is_empty() {
+            diffs.push(SequenceDiff {
+                sequence_type: new.sequence_type,
+                order_index: new.order_index,
+                name: new.name.clone(),
+                lines,
+            });
+        }
+    }
+    diffs
+}
+
+/// Compare two instruction lists line by line.
+pub fn diff_lines(old: &[Instruction], new: &[Instruction]) -> Vec<LineDiff> {
+    // lcs[i][j] = length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if same_line(&old[i], &new[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diffs = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && same_line(&old[i], &new[j]) {
+            flush_changes(&mut diffs, &mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(&new[j]);
+            j += 1;
+        } else {
+            removed.push(&old[i]);
+            i += 1;
+        }
+    }
+    flush_changes(&mut diffs, &mut removed, &mut added);
+    diffs
+}
+
+/// Lines match when everything but the line number is equal.
+fn same_line(a: &Instruction, b: &Instruction) -> bool {
+    Instruction {
+        line_number: b.line_number,
+        ..a.clone()
+    } == *b
+}
+
+/// Emit one run of changes between two matched lines.
+fn flush_changes<'a>(
+    diffs: &mut Vec<LineDiff>,
+    removed: &mut Vec<&'a Instruction>,
+    added: &mut Vec<&'a Instruction>,
+) {
+    let paired = removed.len().min(added.len());
+    for (old, new) in removed.iter().zip(added.iter()) {
+        diffs.push(LineDiff {
+            kind: LineChangeKind::Modified,
+            old_line: Some(old.line_number),
+            new_line: Some(new.line_number),
+            old: Some((*old).clone()),
+            new: Some((*new).clone()),
+        });
+    }
+    for old in &removed[paired..] {
+        diffs.push(LineDiff {
+            kind: LineChangeKind::Removed,
+            old_line: Some(old.line_number),
+            new_line: None,
+            old: Some((*old).clone()),
+            new: None,
+        });
+    }
+    for new in &added[paired..] {
+        diffs.push(LineDiff {
+            kind: LineChangeKind::Added,
+            old_line: None,
+            new_line: Some(new.line_number),
+            old: None,
+            new: Some((*new).clone()),
+        });
+    }
+    removed.clear();
+    added.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_number: i32, x: f64) -> Instruction {
+        Instruction {
+            line_number,
+            x,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_identical_lines_have_no_diff() {
+        let lines = [line(1, 0.0), line(2, 10.0)];
+        assert!(diff_lines(&lines, &lines).is_empty());
+    }
+
+    #[test]
+    fn test_insert_shifts_line_numbers_without_modifying() {
+        let old = [line(1, 0.0), line(2, 10.0)];
+        let new = [line(1, 0.0), line(2, 5.0), line(3, 10.0)];
+
+        let diffs = diff_lines(&old, &new);
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind, LineChangeKind::Added);
+        assert_eq!(diffs[0].new_line, Some(2));
+    }
+
+    #[test]
+    fn test_changed_line_is_modified() {
+        let old = [line(1, 0.0), line(2, 10.0), line(3, 20.0)];
+        let new = [line(1, 0.0), line(2, 12.0)];
+
+        let diffs = diff_lines(&old, &new);
+
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].kind, LineChangeKind::Modified);
+        assert_eq!((diffs[0].old_line, diffs[0].new_line), (Some(2), Some(2)));
+        assert_eq!(diffs[1].kind, LineChangeKind::Removed);
+        assert_eq!(diffs[1].old_line, Some(3));
+    }
+}
@@ -0,0 +1,216 @@
+//! Program export to CSV, G-code, and JSON.
+//!
+//! - CSV exports the main sequence with the columns accepted by `parse_csv`.
+//! - G-code exports approach, main, and retreat sequences as G1 moves that
+//!   `parse_gcode` reads back (relative extrusion, speeds as mm/min feedrates).
+//!   Rotations and the ext2/ext3 axes have no G-code equivalent and are dropped.
+//! - JSON exports the full `ProgramDetail`.
+
+use std::fmt::Write;
+
+use crate::types::{ExportFormat, Instruction, InstructionSequence, ProgramDetail};
+
+/// Maximum size of one exported chunk (bytes).
+pub const EXPORT_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Serialize a program in the given format.
+pub fn export_program(program: &ProgramDetail, format: ExportFormat) -> anyhow::Result<String> {
+    Ok(match format {
+        ExportFormat::Csv => export_csv(&program.main_sequence.instructions),
+        ExportFormat::Gcode => export_gcode(program),
+        ExportFormat::Json => serde_json::to_string_pretty(program)?,
+    })
+}
+
+/// Split exported content into chunks of at most `max_size` bytes.
+///
+/// Chunks end on line boundaries unless a single line is longer than
+/// `max_size`. Empty content is a single empty chunk.
+pub fn chunk_content(content: &str, max_size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = content;
+
+    while rest.len() > max_size {
+        let mut end = max_size;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let end = rest[..end].rfind('\n').map_or(end, |newline| newline + 1);
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    chunks.push(rest);
+    chunks
+}
+
+fn export_csv(instructions: &[Instruction]) -> String {
+    let mut out = String::from("X,Y,Z,W,P,R,EXT1,EXT2,EXT3,SPEED,TERM_TYPE,TERM_VALUE\n");
+    let opt = |value: Option<f64>| value.map(format_number).unwrap_or_default();
+
+    for i in instructions {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            format_number(i.x),
+            format_number(i.y),
+            format_number(i.z),
+            opt(i.w),
+            opt(i.p),
+            opt(i.r),
+            opt(i.ext1),
+            opt(i.ext2),
+            opt(i.ext3),
+            opt(i.speed),
+            i.term_type.as_deref().unwrap_or_default(),
+            i.term_value.map(|v| v.to_string()).unwrap_or_default(),
+        );
+    }
+    out
+}
+
+fn export_gcode(program: &ProgramDetail) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "; Program: {}", program.name);
+    if let Some(description) = &program.description {
+        let _ = writeln!(out, "; {}", description);
+    }
+    out.push_str("G21 ; millimeters\nG90 ; absolute positioning\nM83 ; relative extrusion\n");
+
+    let sequences = program
+        .approach_sequences
+        .iter()
+        .map(|s| ("Approach", s))
+        .chain(std::iter::once(("Main", &program.main_sequence)))
+        .chain(program.retreat_sequences.iter().map(|s| ("Retreat", s)));
+
+    let mut feedrate = None;
+    for (label, sequence) in sequences {
+        write_gcode_sequence(&mut out, label, sequence, program.default_speed, &mut feedrate);
+    }
+    out
+}
+
+fn write_gcode_sequence(
+    out: &mut String,
+    label: &str,
+    sequence: &InstructionSequence,
+    default_speed: Option<f64>,
+    feedrate: &mut Option<f64>,
+) {
+    if sequence.instructions.is_empty() {
+        return;
+    }
+    match &sequence.name {
+        Some(name) => {
+            let _ = writeln!(out, "; {} sequence: {}", label, name);
+        }
+        None => {
+            let _ = writeln!(out, "; {} sequence", label);
+        }
+    }
+
+    for i in &sequence.instructions {
+        let _ = write!(
+            out,
+            "G1 X{} Y{} Z{}",
+            format_number(i.x),
+            format_number(i.y),
+            format_number(i.z)
+        );
+        if let Some(e) = i.ext1 {
+            let _ = write!(out, " E{}", format_number(e));
+        }
+        // Feedrate is modal, so only write it when it changes
+        let speed = i.speed.or(default_speed);
+        if speed.is_some() && speed != *feedrate {
+            *feedrate = speed;
+            let _ = write!(out, " F{}", format_number(speed.unwrap_or_default() * 60.0));
+        }
+        out.push('\n');
+    }
+}
+
+/// Format a value with up to 4 decimals and no trailing zeros.
+fn format_number(value: f64) -> String {
+    let formatted = format!("{:.4}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => "0".to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_parser::parse_csv;
+    use crate::gcode_parser::parse_gcode;
+    use crate::types::SequenceType;
+
+    fn program(instructions: Vec<Instruction>) -> ProgramDetail {
+        ProgramDetail {
+            id: 1,
+            name: "test".to_string(),
+            description: None,
+            default_speed: None,
+            default_term_type: None,
+            default_term_value: None,
+            move_speed: 100.0,
+            approach_sequences: vec![],
+            main_sequence: InstructionSequence {
+                id: 1,
+                sequence_type: SequenceType::Main,
+                name: None,
+                order_index: 0,
+                instructions,
+            },
+            retreat_sequences: vec![],
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn instructions() -> Vec<Instruction> {
+        vec![
+            Instruction {
+                line_number: 1,
+                x: 10.0,
+                y: 0.0,
+                z: 0.2,
+                ext1: Some(0.5),
+                speed: Some(20.0),
+                ..Default::default()
+            },
+            Instruction {
+                line_number: 2,
+                x: 20.5,
+                y: -3.25,
+                z: 0.2,
+                speed: Some(20.0),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let csv = export_program(&program(instructions()), ExportFormat::Csv).unwrap();
+        assert_eq!(parse_csv(&csv).unwrap().instructions, instructions());
+    }
+
+    #[test]
+    fn test_gcode_round_trip() {
+        let gcode = export_program(&program(instructions()), ExportFormat::Gcode).unwrap();
+        let parsed = parse_gcode(&gcode).unwrap();
+        assert_eq!(parsed.instructions, instructions());
+        assert!(parsed.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_chunks_end_on_line_boundaries() {
+        let content = "aaaa\nbbbb\ncccc\n";
+        let chunks = chunk_content(content, 11);
+        assert_eq!(chunks, vec!["aaaa\nbbbb\n", "cccc\n"]);
+        assert_eq!(chunk_content("", 11), vec![""]);
+    }
+}
@@ -0,0 +1,335 @@
+//! G-code parser for program instructions.
+//!
+//! Imports slicer output into the same instruction model as CSV uploads:
+//! - G0/G1 linear moves, G2/G3 arcs (I/J center form, split into line segments)
+//! - X, Y, Z coordinates, F feedrate (mm/min, stored as mm/s), E extrusion (stored as ext1)
+//! - G90/G91 absolute/relative positioning, M82/M83 absolute/relative extrusion
+//! - G20/G21 inch/mm units, G92 position reset
+//! - `;` and `( )` comments, N line numbers and `*` checksums
+//!
+//! Other codes are skipped with one warning per code.
+
+use std::collections::HashSet;
+
+use crate::csv_parser::{ParseError, ParseResult, ParseWarning};
+use crate::types::Instruction;
+
+/// Maximum length of the line segments an arc is split into (mm).
+const ARC_SEGMENT_LENGTH: f64 = 1.0;
+
+/// Modal machine state while reading G-code.
+struct GcodeState {
+    position: [f64; 3],
+    extruder: f64,
+    /// Feedrate in mm/min
+    feedrate: Option<f64>,
+    relative: bool,
+    relative_extrusion: bool,
+    /// Multiplier from file units to mm
+    unit_scale: f64,
+}
+
+impl Default for GcodeState {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            extruder: 0.0,
+            feedrate: None,
+            relative: false,
+            relative_extrusion: false,
+            unit_scale: 1.0,
+        }
+    }
+}
+
+/// Parse G-code content into instructions.
+///
+/// # Arguments
+/// * `content` - G-code program as string
+///
+/// # Returns
+/// * `ParseResult` with instructions and any warnings
+pub fn parse_gcode(content: &str) -> Result<ParseResult, ParseError> {
+    let mut state = GcodeState::default();
+    let mut instructions = Vec::new();
+    let mut warnings = Vec::new();
+    let mut reported = HashSet::new();
+
+    for (row_index, raw_line) in content.lines().enumerate() {
+        let line = row_index + 1;
+        let words = match parse_words(strip_comments(raw_line)) {
+            Ok(words) => words,
+            Err(message) => {
+                warnings.push(ParseWarning { line, message });
+                continue;
+            }
+        };
+        if words.is_empty() {
+            continue;
+        }
+
+        let value = |letter: char| {
+            words
+                .iter()
+                .find(|(l, _)| *l == letter)
+                .map(|(_, v)| *v)
+        };
+
+        let command = words
+            .iter()
+            .find(|(letter, _)| matches!(letter, 'G' | 'M' | 'T'))
+            .copied();
+
+        match command {
+            Some(('G', code)) if code == 0.0 || code == 1.0 => {
+                let target = state.target(value('X'), value('Y'), value('Z'));
+                let extrusion = state.extrude(value('E'));
+                if let Some(f) = value('F') {
+                    state.feedrate = Some(f * state.unit_scale);
+                }
+                if target != state.position {
+                    state.position = target;
+                    instructions.push(state.instruction(target, extrusion));
+                }
+            }
+            Some(('G', code)) if code == 2.0 || code == 3.0 => {
+                let target = state.target(value('X'), value('Y'), value('Z'));
+                let extrusion = state.extrude(value('E'));
+                if let Some(f) = value('F') {
+                    state.feedrate = Some(f * state.unit_scale);
+                }
+                let (Some(i), Some(j)) = (value('I'), value('J')) else {
+                    warnings.push(ParseWarning {
+                        line,
+                        message: "Arc without I/J center offsets imported as a straight move".to_string(),
+                    });
+                    state.position = target;
+                    instructions.push(state.instruction(target, extrusion));
+                    continue;
+                };
+                let center = [
+                    state.position[0] + i * state.unit_scale,
+                    state.position[1] + j * state.unit_scale,
+                ];
+                let points = arc_points(state.position, target, center, code == 2.0);
+                let per_segment = extrusion.map(|e| e / points.len() as f64);
+                for point in points {
+                    instructions.push(state.instruction(point, per_segment));
+                }
+                state.position = target;
+            }
+            Some(('G', code)) if code == 20.0 => state.unit_scale = 25.4,
+            Some(('G', code)) if code == 21.0 => state.unit_scale = 1.0,
+            Some(('G', code)) if code == 90.0 => state.relative = false,
+            Some(('G', code)) if code == 91.0 => state.relative = true,
+            Some(('G', code)) if code == 92.0 => {
+                for (axis, letter) in ['X', 'Y', 'Z'].into_iter().enumerate() {
+                    if let Some(v) = value(letter) {
+                        state.position[axis] = v * state.unit_scale;
+                    }
+                }
+                if let Some(e) = value('E') {
+                    state.extruder = e * state.unit_scale;
+                }
+            }
+            Some(('M', code)) if code == 82.0 => state.relative_extrusion = false,
+            Some(('M', code)) if code == 83.0 => state.relative_extrusion = true,
+            Some((letter, code)) => {
+                let code = format!("{}{}", letter, code);
+                if reported.insert(code.clone()) {
+                    warnings.push(ParseWarning {
+                        line,
+                        message: format!("Unsupported code {} ignored (further occurrences not reported)", code),
+                    });
+                }
+            }
+            None => {
+                // Words without a command (e.g., a bare "F1200") only update modal state
+                if let Some(f) = value('F') {
+                    state.feedrate = Some(f * state.unit_scale);
+                }
+            }
+        }
+    }
+
+    if instructions.is_empty() {
+        return Err(ParseError {
+            message: "No moves found in G-code".to_string(),
+        });
+    }
+
+    // Renumber so arcs split into segments still get consecutive line numbers
+    for (index, instruction) in instructions.iter_mut().enumerate() {
+        instruction.line_number = index as i32 + 1;
+    }
+
+    Ok(ParseResult {
+        instructions,
+        warnings,
+    })
+}
+
+impl GcodeState {
+    /// Resolve a move's target position from its X/Y/Z words.
+    fn target(&self, x: Option<f64>, y: Option<f64>, z: Option<f64>) -> [f64; 3] {
+        let mut target = self.position;
+        for (axis, word) in [x, y, z].into_iter().enumerate() {
+            if let Some(v) = word {
+                let v = v * self.unit_scale;
+                target[axis] = if self.relative { self.position[axis] + v } else { v };
+            }
+        }
+        target
+    }
+
+    /// Extrusion for a move's E word, as a relative distance.
+    fn extrude(&mut self, e: Option<f64>) -> Option<f64> {
+        let e = e? * self.unit_scale;
+        if self.relative_extrusion {
+            Some(e)
+        } else {
+            let delta = e - self.extruder;
+            self.extruder = e;
+            Some(delta)
+        }
+    }
+
+    fn instruction(&self, position: [f64; 3], extrusion: Option<f64>) -> Instruction {
+        Instruction {
+            line_number: 0,
+            x: position[0],
+            y: position[1],
+            z: position[2],
+            ext1: extrusion,
+            speed: self.feedrate.map(|f| f / 60.0),
+            ..Default::default()
+        }
+    }
+}
+
+/// Remove `;` and `( )` comments and `*` checksums.
+fn strip_comments(line: &str) -> String {
+    let line = line.split(';').next().unwrap_or("");
+    let line = line.split('*').next().unwrap_or("");
+    let mut result = String::with_capacity(line.len());
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            _ if depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Split a line into (letter, value) words, skipping N line numbers.
+fn parse_words(line: String) -> Result<Vec<(char, f64)>, String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().filter(|c| !c.is_whitespace()).peekable();
+
+    while let Some(letter) = chars.next() {
+        let letter = letter.to_ascii_uppercase();
+        if !letter.is_ascii_alphabetic() {
+            return Err(format!("Unexpected character '{}'", letter));
+        }
+        let mut number = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '.' | '-' | '+') {
+                number.push(c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        let value = number
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid value '{}' for {}", number, letter))?;
+        if letter != 'N' {
+            words.push((letter, value));
+        }
+    }
+
+    Ok(words)
+}
+
+/// Split an XY-plane arc into points no more than `ARC_SEGMENT_LENGTH` apart.
+///
+/// Z moves linearly along the arc (helical moves). A full circle is produced
+/// when the start and end coincide.
+fn arc_points(start: [f64; 3], end: [f64; 3], center: [f64; 2], clockwise: bool) -> Vec<[f64; 3]> {
+    let radius = (start[0] - center[0]).hypot(start[1] - center[1]);
+    let start_angle = (start[1] - center[1]).atan2(start[0] - center[0]);
+    let end_angle = (end[1] - center[1]).atan2(end[0] - center[0]);
+
+    let mut sweep = end_angle - start_angle;
+    if clockwise && sweep >= 0.0 {
+        sweep -= std::f64::consts::TAU;
+    } else if !clockwise && sweep <= 0.0 {
+        sweep += std::f64::consts::TAU;
+    }
+
+    let segments = ((sweep.abs() * radius) / ARC_SEGMENT_LENGTH).ceil().max(1.0) as usize;
+    (1..=segments)
+        .map(|i| {
+            let t = i as f64 / segments as f64;
+            if i == segments {
+                return end;
+            }
+            let angle = start_angle + sweep * t;
+            [
+                center[0] + radius * angle.cos(),
+                center[1] + radius * angle.sin(),
+                start[2] + (end[2] - start[2]) * t,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_moves_with_feedrate_and_extrusion() {
+        let gcode = "G21 ; millimeters\nG90\nG1 X10 Y0 Z0.2 F1200 E0.5\nG1 X20 E1.0 (second)\nG1 F600\n";
+
+        let result = parse_gcode(gcode).unwrap();
+
+        assert_eq!(result.instructions.len(), 2);
+        let second = &result.instructions[1];
+        assert_eq!(second.line_number, 2);
+        assert_eq!((second.x, second.y, second.z), (20.0, 0.0, 0.2));
+        assert_eq!(second.speed, Some(20.0));
+        assert_eq!(second.ext1, Some(0.5));
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_relative_positioning() {
+        let result = parse_gcode("G91\nG1 X5\nG1 X5 Y2\n").unwrap();
+        let last = result.instructions.last().unwrap();
+        assert_eq!((last.x, last.y), (10.0, 2.0));
+    }
+
+    #[test]
+    fn test_arc_is_split_and_ends_on_target() {
+        // Quarter circle of radius 10 around (0, 0)
+        let result = parse_gcode("G1 X10 Y0\nG3 X0 Y10 I-10 J0\n").unwrap();
+
+        assert!(result.instructions.len() > 10);
+        let last = result.instructions.last().unwrap();
+        assert_eq!((last.x, last.y), (0.0, 10.0));
+    }
+
+    #[test]
+    fn test_unsupported_codes_warn_once() {
+        let result = parse_gcode("M104 S200\nG1 X1\nM104 S210\nG28\n").unwrap();
+
+        let messages: Vec<_> = result.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].contains("M104"));
+        assert!(messages[1].contains("G28"));
+    }
+}
@@ -18,6 +18,11 @@ use fanuc_replica_execution::{
 use fanuc_replica_robotics::{FrameId, RobotPose};
 use crate::database::queries;
 use crate::csv_parser::parse_csv;
+use crate::gcode_parser::parse_gcode;
+use crate::diff::diff_programs;
+use crate::export::{chunk_content, export_program, EXPORT_CHUNK_SIZE};
+use crate::limits::{validate_program_limits, ToolpathLimits};
+use crate::transform::transform_program;
 use crate::types::*;
 
 // Type alias for WebSocket network provider
@@ -35,10 +40,20 @@ impl Plugin for ProgramHandlerPlugin {
             DeleteProgram,
             UpdateProgramSettings,
             UploadCsv,
+            UploadGcode,
             AddSequence,
             RemoveSequence,
+            ListProgramVersions,
+            GetProgramVersion,
+            DiffProgramVersions,
+            RollbackProgram,
+            ExportProgram,
+            ValidateProgram,
+            TransformProgram,
         ), WS>().register();
 
+        app.init_resource::<ToolpathLimits>();
+
         // Register Load/Unload as targeted requests (require entity control)
         // These target the ActiveSystem entity and need authorization
         app.requests::<(
@@ -57,8 +72,20 @@ impl Plugin for ProgramHandlerPlugin {
             handle_delete_program,
             handle_update_program_settings,
             handle_upload_csv,
+            handle_upload_gcode,
             handle_add_sequence,
             handle_remove_sequence,
+            handle_export_program,
+            handle_validate_program,
+            handle_transform_program,
+        ));
+
+        // Add version handler systems
+        app.add_systems(Update, (
+            handle_list_program_versions,
+            handle_get_program_version,
+            handle_diff_program_versions,
+            handle_rollback_program,
         ));
 
         // Add Load/Unload handler systems
@@ -207,6 +234,7 @@ fn handle_update_program_settings(
             .map(|db| {
                 let conn = db.connection();
                 let conn = conn.lock().unwrap();
+                queries::ensure_initial_version(&conn, inner.program_id)?;
                 queries::update_program_settings(
                     &conn,
                     inner.program_id,
@@ -216,7 +244,9 @@ fn handle_update_program_settings(
                     inner.default_term_type.as_deref(),
                     inner.default_term_value,
                     inner.move_speed,
-                )
+                )?;
+                queries::record_version(&conn, inner.program_id, "Settings updated")?;
+                Ok::<_, anyhow::Error>(())
             })
             .unwrap_or(Err(anyhow::anyhow!("Database not available")));
 
@@ -266,27 +296,17 @@ fn handle_upload_csv(
             .map(|w| format!("Line {}: {}", w.line, w.message))
             .collect();
 
-        // Get sequence ID and insert instructions
         let result = db.as_ref()
             .map(|db| {
                 let conn = db.connection();
                 let conn = conn.lock().unwrap();
-
-                // Get the appropriate sequence
-                let seq_type = inner.sequence_type.unwrap_or(SequenceType::Main);
-                let sequence_id = if seq_type == SequenceType::Main {
-                    queries::get_main_sequence_id(&conn, inner.program_id)?
-                        .ok_or_else(|| anyhow::anyhow!("Main sequence not found"))?
-                } else {
-                    // For approach/retreat, create a new sequence
-                    queries::add_sequence(&conn, inner.program_id, seq_type, None, &parse_result.instructions)?
-                };
-
-                if seq_type == SequenceType::Main {
-                    queries::insert_instructions(&conn, sequence_id, &parse_result.instructions)?;
-                }
-
-                Ok::<_, anyhow::Error>(parse_result.instructions.len())
+                import_instructions(
+                    &conn,
+                    inner.program_id,
+                    inner.sequence_type.unwrap_or(SequenceType::Main),
+                    &parse_result.instructions,
+                    "CSV upload",
+                )
             })
             .unwrap_or(Err(anyhow::anyhow!("Database not available")));
 
@@ -318,6 +338,278 @@ fn handle_upload_csv(
     }
 }
 
+fn handle_upload_gcode(
+    mut requests: MessageReader<Request<UploadGcode>>,
+    db: Option<Res<DatabaseResource>>,
+    net: Res<Network<WS>>,
+) {
+    for request in requests.read() {
+        let inner = request.get_request();
+        info!("📋 Handling UploadGcode for program id={}", inner.program_id);
+
+        // Parse G-code
+        let parse_result = match parse_gcode(&inner.gcode_content) {
+            Ok(result) => result,
+            Err(e) => {
+                error!("❌ G-code parse error: {}", e);
+                let _ = request.clone().respond(UploadGcodeResponse {
+                    success: false,
+                    lines_imported: None,
+                    warnings: vec![],
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+        };
+
+        let warnings: Vec<String> = parse_result.warnings.iter()
+            .map(|w| format!("Line {}: {}", w.line, w.message))
+            .collect();
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                import_instructions(
+                    &conn,
+                    inner.program_id,
+                    inner.sequence_type.unwrap_or(SequenceType::Main),
+                    &parse_result.instructions,
+                    "G-code upload",
+                )
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok(count) => {
+                info!("✅ Imported {} lines", count);
+                UploadGcodeResponse {
+                    success: true,
+                    lines_imported: Some(count as i32),
+                    warnings,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                error!("❌ Failed to import G-code: {}", e);
+                UploadGcodeResponse {
+                    success: false,
+                    lines_imported: None,
+                    warnings,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+
+        // respond_and_invalidate automatically broadcasts invalidations on success
+        if let Err(e) = request.clone().respond_and_invalidate(response, &net) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Store uploaded instructions and record a version for them.
+///
+/// Main replaces the main sequence's instructions; approach/retreat uploads
+/// add a new sequence. Returns the number of lines imported.
+pub(crate) fn import_instructions(
+    conn: &rusqlite::Connection,
+    program_id: i64,
+    seq_type: SequenceType,
+    instructions: &[Instruction],
+    source: &str,
+) -> anyhow::Result<usize> {
+    queries::ensure_initial_version(conn, program_id)?;
+
+    if seq_type == SequenceType::Main {
+        let sequence_id = queries::get_main_sequence_id(conn, program_id)?
+            .ok_or_else(|| anyhow::anyhow!("Main sequence not found"))?;
+        queries::insert_instructions(conn, sequence_id, instructions)?;
+    } else {
+        // For approach/retreat, create a new sequence
+        queries::add_sequence(conn, program_id, seq_type, None, instructions)?;
+    }
+
+    queries::record_version(
+        conn,
+        program_id,
+        &format!("{} ({} lines)", source, instructions.len()),
+    )?;
+
+    Ok(instructions.len())
+}
+
+fn handle_export_program(
+    mut requests: MessageReader<Request<ExportProgram>>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let inner = request.get_request();
+        info!(
+            "📋 Handling ExportProgram for program id={} ({:?}, chunk {})",
+            inner.program_id, inner.format, inner.chunk
+        );
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                let program = queries::get_program(&conn, inner.program_id)?
+                    .ok_or_else(|| anyhow::anyhow!("Program {} not found", inner.program_id))?;
+                let content = export_program(&program, inner.format)?;
+                let file_name = format!("{}.{}", program.name, inner.format.extension());
+                Ok::<_, anyhow::Error>((content, file_name))
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok((content, file_name)) => {
+                // Programs are re-exported for each chunk; exports are deterministic
+                let chunks = chunk_content(&content, EXPORT_CHUNK_SIZE);
+                match chunks.get(inner.chunk as usize) {
+                    Some(chunk) => ExportProgramResponse {
+                        success: true,
+                        content: Some(chunk.to_string()),
+                        file_name: Some(file_name),
+                        chunk: inner.chunk,
+                        total_chunks: chunks.len() as u32,
+                        error: None,
+                    },
+                    None => ExportProgramResponse {
+                        success: false,
+                        content: None,
+                        file_name: Some(file_name),
+                        chunk: inner.chunk,
+                        total_chunks: chunks.len() as u32,
+                        error: Some(format!("Chunk {} out of range ({} chunks)", inner.chunk, chunks.len())),
+                    },
+                }
+            }
+            Err(e) => {
+                error!("❌ Failed to export program: {}", e);
+                ExportProgramResponse {
+                    success: false,
+                    content: None,
+                    file_name: None,
+                    chunk: inner.chunk,
+                    total_chunks: 0,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+
+        let _ = request.clone().respond(response);
+    }
+}
+
+fn handle_validate_program(
+    mut requests: MessageReader<Request<ValidateProgram>>,
+    db: Option<Res<DatabaseResource>>,
+    limits: Res<ToolpathLimits>,
+) {
+    for request in requests.read() {
+        let program_id = request.get_request().program_id;
+        info!("📋 Handling ValidateProgram for program id={}", program_id);
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                queries::get_program(&conn, program_id)?
+                    .ok_or_else(|| anyhow::anyhow!("Program {} not found", program_id))
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok(program) => {
+                let (lines_checked, violations) = validate_program_limits(&program, &limits);
+                if !violations.is_empty() {
+                    info!("⚠️ Program {} has {} limit violation(s)", program_id, violations.len());
+                }
+                ValidateProgramResponse {
+                    success: true,
+                    valid: violations.is_empty(),
+                    lines_checked,
+                    violations,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                error!("❌ Failed to validate program: {}", e);
+                ValidateProgramResponse {
+                    success: false,
+                    valid: false,
+                    lines_checked: 0,
+                    violations: vec![],
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+
+        let _ = request.clone().respond(response);
+    }
+}
+
+fn handle_transform_program(
+    mut requests: MessageReader<Request<TransformProgram>>,
+    db: Option<Res<DatabaseResource>>,
+    net: Res<Network<WS>>,
+) {
+    for request in requests.read() {
+        let inner = request.get_request();
+        info!(
+            "📋 Handling TransformProgram for program id={} ({} transform(s), preview={})",
+            inner.program_id, inner.transforms.len(), inner.preview
+        );
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                let mut program = queries::get_program(&conn, inner.program_id)?
+                    .ok_or_else(|| anyhow::anyhow!("Program {} not found", inner.program_id))?;
+                transform_program(&mut program, &inner.transforms, inner.sequence_type);
+
+                let version = if inner.preview {
+                    None
+                } else {
+                    let summary = format!("Transformed ({} transform(s))", inner.transforms.len());
+                    Some(queries::replace_program_instructions(&conn, &program, &summary)?)
+                };
+                Ok::<_, anyhow::Error>((program, version))
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok((program, version)) => TransformProgramResponse {
+                success: true,
+                program: Some(program),
+                version,
+                error: None,
+            },
+            Err(e) => {
+                error!("❌ Failed to transform program: {}", e);
+                TransformProgramResponse {
+                    success: false,
+                    program: None,
+                    version: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+
+        // Previews change nothing, so there is nothing to invalidate
+        let sent = if inner.preview {
+            request.clone().respond(response)
+        } else {
+            request.clone().respond_and_invalidate(response, &net)
+        };
+        if let Err(e) = sent {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
 fn handle_add_sequence(
     mut requests: MessageReader<Request<AddSequence>>,
     db: Option<Res<DatabaseResource>>,
@@ -406,6 +698,123 @@ fn handle_remove_sequence(
 // Load/Unload Handlers
 // ============================================================================
 
+fn handle_list_program_versions(
+    mut requests: MessageReader<Request<ListProgramVersions>>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let program_id = request.get_request().program_id;
+        info!("📋 Handling ListProgramVersions for program id={}", program_id);
+
+        let versions = db.as_ref()
+            .and_then(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                queries::list_versions(&conn, program_id).ok()
+            })
+            .unwrap_or_default();
+
+        info!("📤 Responding with {} versions", versions.len());
+        let _ = request.clone().respond(ListProgramVersionsResponse { versions });
+    }
+}
+
+fn handle_get_program_version(
+    mut requests: MessageReader<Request<GetProgramVersion>>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let inner = request.get_request();
+        info!("📋 Handling GetProgramVersion for program id={} version={}", inner.program_id, inner.version);
+
+        let version = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                match queries::get_version(&conn, inner.program_id, inner.version) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        tracing::error!("❌ Error getting version {} of program {}: {:?}", inner.version, inner.program_id, e);
+                        None
+                    }
+                }
+            })
+            .flatten();
+
+        let _ = request.clone().respond(GetProgramVersionResponse { version });
+    }
+}
+
+fn handle_diff_program_versions(
+    mut requests: MessageReader<Request<DiffProgramVersions>>,
+    db: Option<Res<DatabaseResource>>,
+) {
+    for request in requests.read() {
+        let inner = request.get_request();
+        info!(
+            "📋 Handling DiffProgramVersions for program id={} ({} → {})",
+            inner.program_id, inner.from_version, inner.to_version
+        );
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                let from = queries::get_version(&conn, inner.program_id, inner.from_version)?
+                    .ok_or_else(|| anyhow::anyhow!("Version {} not found", inner.from_version))?;
+                let to = queries::get_version(&conn, inner.program_id, inner.to_version)?
+                    .ok_or_else(|| anyhow::anyhow!("Version {} not found", inner.to_version))?;
+                Ok::<_, anyhow::Error>(diff_programs(inner.from_version, &from.program, inner.to_version, &to.program))
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok(diff) => DiffProgramVersionsResponse { success: true, diff: Some(diff), error: None },
+            Err(e) => {
+                error!("❌ Failed to diff program versions: {}", e);
+                DiffProgramVersionsResponse { success: false, diff: None, error: Some(e.to_string()) }
+            }
+        };
+
+        let _ = request.clone().respond(response);
+    }
+}
+
+fn handle_rollback_program(
+    mut requests: MessageReader<Request<RollbackProgram>>,
+    db: Option<Res<DatabaseResource>>,
+    net: Res<Network<WS>>,
+) {
+    for request in requests.read() {
+        let inner = request.get_request();
+        info!("📋 Handling RollbackProgram id={} to version {}", inner.program_id, inner.version);
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                queries::rollback_to_version(&conn, inner.program_id, inner.version)
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok(new_version) => {
+                info!("✅ Rolled back program id={} to version {} (now version {})", inner.program_id, inner.version, new_version);
+                RollbackProgramResponse { success: true, new_version: Some(new_version), error: None }
+            }
+            Err(e) => {
+                error!("❌ Failed to roll back program: {}", e);
+                RollbackProgramResponse { success: false, new_version: None, error: Some(e.to_string()) }
+            }
+        };
+
+        // respond_and_invalidate automatically broadcasts invalidations on success
+        if let Err(e) = request.clone().respond_and_invalidate(response, &net) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
 /// Handle Load request - loads a program into the execution buffer.
 ///
 /// This is the static program loader that:
@@ -587,7 +996,6 @@ fn handle_load(
                     exec_state.current_index = 0;
                     exec_state.total_points = Some(total_points);
                     exec_state.points_executed = 0;
-                    exec_state.update_available_actions();
                     info!("📡 ExecutionState updated: source='{}', {} points",
                         program_detail.name, total_points);
                 }
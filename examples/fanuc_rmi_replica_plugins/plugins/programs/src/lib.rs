@@ -3,7 +3,13 @@
 //! This plugin provides:
 //! - Program storage and retrieval
 //! - CSV import with flexible column support
+//! - Chunked, resumable uploads for large files
+//! - G-code import (G0/G1/G2/G3) for slicer output
+//! - Program export to CSV, G-code, or JSON
+//! - Toolpath validation against workspace, speed, and reach limits
+//! - Geometric transforms (translate/rotate/scale/mirror) of stored programs
 //! - Multiple approach/retreat sequence support
+//! - Program versioning with line-level diffs and rollback
 //! - Device-agnostic instruction types
 //!
 //! # Features
@@ -14,23 +20,35 @@
 use cfg_if::cfg_if;
 
 // Always available
+mod diff;
 mod types;
+pub use diff::{diff_lines, diff_programs};
 pub use types::*;
 
 cfg_if! {
     if #[cfg(feature = "server")] {
         mod database;
         mod csv_parser;
+        mod export;
+        mod gcode_parser;
         mod handlers;
+        mod limits;
         mod notifications;
         mod plugin;
+        mod transform;
+        mod upload;
         mod validation;
 
         pub use database::{ProgramsDatabaseInit, queries};
         pub use csv_parser::{parse_csv, ParseResult, ParseError, ParseWarning};
+        pub use gcode_parser::parse_gcode;
+        pub use export::{chunk_content, export_program, EXPORT_CHUNK_SIZE};
         pub use handlers::ProgramHandlerPlugin;
+        pub use limits::{validate_program_limits, ToolpathLimits};
         pub use notifications::ProgramNotificationsPlugin;
         pub use plugin::ProgramsPlugin;
+        pub use transform::{transform_instructions, transform_program};
+        pub use upload::{ChunkedUploadPlugin, PendingUpload, PendingUploads};
     }
 }
 
@@ -0,0 +1,209 @@
+//! Toolpath validation against robot limits.
+//!
+//! Checks every instruction of a program against the configured
+//! [`ToolpathLimits`] before it is loaded:
+//! - Workspace: the TCP position must lie inside the workspace box
+//! - Speed: the commanded speed must not exceed the maximum
+//! - Reach: the wrist center must lie within the arm's reach envelope
+//!
+//! There is no inverse kinematics here; reachability is approximated by the
+//! distance from the robot base to the wrist center, which is found by
+//! stepping back from the TCP along the tool axis. Missing rotations and
+//! speeds are filled in the same way `Load` does.
+
+use bevy::prelude::*;
+use fanuc_replica_robotics::{FrameId, RobotPose};
+
+use crate::types::{Instruction, LimitViolation, LimitViolationKind, ProgramDetail, SequenceType};
+
+/// Speed used for instructions without one when the program has no default (mm/s).
+const FALLBACK_SPEED: f64 = 100.0;
+
+/// Limits that program instructions are validated against.
+///
+/// Defaults describe a mid-size 6-axis arm (about 1.4m reach). Insert this
+/// resource before adding `ProgramsPlugin` to configure a different robot.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ToolpathLimits {
+    /// Minimum corner of the workspace box in the world frame (mm)
+    pub workspace_min: [f64; 3],
+    /// Maximum corner of the workspace box in the world frame (mm)
+    pub workspace_max: [f64; 3],
+    /// Maximum linear speed (mm/s)
+    pub max_speed: f64,
+    /// Robot base position in the world frame (mm)
+    pub base: [f64; 3],
+    /// Minimum distance from the base to the wrist center (mm)
+    pub min_reach: f64,
+    /// Maximum distance from the base to the wrist center (mm)
+    pub max_reach: f64,
+    /// Distance from the TCP back to the wrist center along the tool axis (mm)
+    pub wrist_offset: f64,
+}
+
+impl Default for ToolpathLimits {
+    fn default() -> Self {
+        Self {
+            workspace_min: [-1400.0, -1400.0, -500.0],
+            workspace_max: [1400.0, 1400.0, 1800.0],
+            max_speed: 2000.0,
+            base: [0.0; 3],
+            min_reach: 150.0,
+            max_reach: 1400.0,
+            wrist_offset: 100.0,
+        }
+    }
+}
+
+impl ToolpathLimits {
+    /// Check one instruction, returning a violation per failed check.
+    pub fn check(&self, instruction: &Instruction, default_speed: f64) -> Vec<(LimitViolationKind, String)> {
+        let mut violations = Vec::new();
+        let position = [instruction.x, instruction.y, instruction.z];
+
+        let outside: Vec<String> = ["X", "Y", "Z"]
+            .into_iter()
+            .enumerate()
+            .filter(|&(axis, _)| {
+                position[axis] < self.workspace_min[axis] || position[axis] > self.workspace_max[axis]
+            })
+            .map(|(axis, name)| {
+                format!(
+                    "{} {:.1} outside [{:.1}, {:.1}]",
+                    name, position[axis], self.workspace_min[axis], self.workspace_max[axis]
+                )
+            })
+            .collect();
+        if !outside.is_empty() {
+            violations.push((LimitViolationKind::Workspace, outside.join(", ")));
+        }
+
+        let speed = instruction.speed.unwrap_or(default_speed);
+        if speed > self.max_speed {
+            violations.push((
+                LimitViolationKind::Speed,
+                format!("Speed {:.1} mm/s exceeds maximum {:.1} mm/s", speed, self.max_speed),
+            ));
+        }
+
+        let reach = self.wrist_distance(instruction);
+        if reach > self.max_reach {
+            violations.push((
+                LimitViolationKind::Reach,
+                format!("Wrist center {:.1} mm from base, beyond reach of {:.1} mm", reach, self.max_reach),
+            ));
+        } else if reach < self.min_reach {
+            violations.push((
+                LimitViolationKind::Reach,
+                format!("Wrist center {:.1} mm from base, inside minimum reach of {:.1} mm", reach, self.min_reach),
+            ));
+        }
+
+        violations
+    }
+
+    /// Distance from the robot base to the wrist center for an instruction.
+    fn wrist_distance(&self, instruction: &Instruction) -> f64 {
+        let pose = RobotPose::from_xyz_wpr(
+            instruction.x,
+            instruction.y,
+            instruction.z,
+            instruction.w.unwrap_or(0.0),
+            instruction.p.unwrap_or(0.0),
+            instruction.r.unwrap_or(0.0),
+            FrameId::World,
+        );
+        let (x, y, z) = pose.translation();
+        let rotation = pose.transform.rotation.to_rotation_matrix();
+        // Tool axis is the Z column of the rotation
+        let wrist = [
+            x - rotation[(0, 2)] * self.wrist_offset,
+            y - rotation[(1, 2)] * self.wrist_offset,
+            z - rotation[(2, 2)] * self.wrist_offset,
+        ];
+        ((wrist[0] - self.base[0]).powi(2)
+            + (wrist[1] - self.base[1]).powi(2)
+            + (wrist[2] - self.base[2]).powi(2))
+        .sqrt()
+    }
+}
+
+/// Validate all sequences of a program against the limits.
+///
+/// Returns the number of lines checked and the violations found, in
+/// execution order (approach, main, retreat).
+pub fn validate_program_limits(program: &ProgramDetail, limits: &ToolpathLimits) -> (u32, Vec<LimitViolation>) {
+    let default_speed = program.default_speed.unwrap_or(FALLBACK_SPEED);
+    let sequences = program
+        .approach_sequences
+        .iter()
+        .chain(std::iter::once(&program.main_sequence))
+        .chain(program.retreat_sequences.iter());
+
+    let mut lines_checked = 0;
+    let mut violations = Vec::new();
+    for sequence in sequences {
+        for instruction in &sequence.instructions {
+            lines_checked += 1;
+            violations.extend(limits.check(instruction, default_speed).into_iter().map(|(kind, message)| {
+                LimitViolation {
+                    sequence_type: sequence.sequence_type,
+                    order_index: sequence.order_index,
+                    line_number: instruction.line_number,
+                    kind,
+                    message,
+                }
+            }));
+        }
+    }
+    (lines_checked, violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f64, y: f64, z: f64) -> Instruction {
+        Instruction {
+            line_number: 1,
+            x,
+            y,
+            z,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_point_within_limits_passes() {
+        let limits = ToolpathLimits::default();
+        assert!(limits.check(&at(600.0, 0.0, 300.0), 100.0).is_empty());
+    }
+
+    #[test]
+    fn test_workspace_and_speed_violations() {
+        let limits = ToolpathLimits::default();
+        let instruction = Instruction {
+            speed: Some(5000.0),
+            ..at(600.0, 0.0, -800.0)
+        };
+
+        let kinds: Vec<_> = limits.check(&instruction, 100.0).into_iter().map(|(kind, _)| kind).collect();
+
+        assert_eq!(kinds, vec![LimitViolationKind::Workspace, LimitViolationKind::Speed]);
+    }
+
+    #[test]
+    fn test_reach_uses_wrist_center() {
+        let limits = ToolpathLimits {
+            max_reach: 1000.0,
+            wrist_offset: 100.0,
+            ..Default::default()
+        };
+
+        // Identity orientation: tool axis is +Z, so the wrist is 100mm below the TCP
+        assert!(limits.check(&at(0.0, 0.0, 1050.0), 100.0).is_empty());
+        let violations = limits.check(&at(0.0, 0.0, 1150.0), 100.0);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].0, LimitViolationKind::Reach);
+    }
+}
@@ -7,12 +7,11 @@ use bevy::prelude::*;
 use std::sync::atomic::{AtomicU8, Ordering};
 
 use fanuc_replica_core::{
-    console_entry, ActiveSystem, ConsoleDirection, ConsoleMsgType,
+    console_entry, ActiveSystem, ConsoleDirection, ConsoleLogEntry, ConsoleMsgType,
 };
 use fanuc_replica_execution::{BufferState, ExecutionCoordinator, ExecutionState, SystemState};
-use pl3xus::Network;
 use pl3xus_common::ServerNotification;
-use pl3xus_websockets::WebSocketProvider;
+use pl3xus_sync::notifications::OutgoingNotification;
 
 /// Tracks the last known SystemState for change detection.
 #[derive(Resource, Default)]
@@ -42,7 +41,8 @@ fn system_state_to_category(state: SystemState) -> u8 {
 /// - Execution is stopped (Stopped)
 /// - Execution encounters an error (Error)
 pub fn send_program_notifications(
-    net: Res<Network<WebSocketProvider>>,
+    mut notifications: MessageWriter<OutgoingNotification>,
+    mut console_log: MessageWriter<ConsoleLogEntry>,
     last_state: ResMut<LastNotifiedState>,
     system_query: Query<
         (&ExecutionState, Option<&ExecutionCoordinator>, Option<&BufferState>),
@@ -81,11 +81,11 @@ pub fn send_program_notifications(
                 Some(
                     ServerNotification::info(&msg).with_context("ProgramExecution"),
                 ),
-                Some(console_entry(
-                    &msg,
-                    ConsoleDirection::System,
-                    ConsoleMsgType::Status,
-                )),
+                Some(
+                    console_entry(&msg, ConsoleDirection::System, ConsoleMsgType::Status)
+                        .with_source("programs")
+                        .with_field("program", &program_name),
+                ),
             )
         }
 
@@ -100,11 +100,11 @@ pub fn send_program_notifications(
                 Some(
                     ServerNotification::success(&msg).with_context("ProgramExecution"),
                 ),
-                Some(console_entry(
-                    &msg,
-                    ConsoleDirection::System,
-                    ConsoleMsgType::Status,
-                )),
+                Some(
+                    console_entry(&msg, ConsoleDirection::System, ConsoleMsgType::Status)
+                        .with_source("programs")
+                        .with_field("program", &program_name),
+                ),
             )
         }
 
@@ -121,11 +121,11 @@ pub fn send_program_notifications(
                 Some(
                     ServerNotification::warning(&msg).with_context("ProgramExecution"),
                 ),
-                Some(console_entry(
-                    &msg,
-                    ConsoleDirection::System,
-                    ConsoleMsgType::Status,
-                )),
+                Some(
+                    console_entry(&msg, ConsoleDirection::System, ConsoleMsgType::Status)
+                        .with_source("programs")
+                        .with_field("program", &program_name),
+                ),
             )
         }
 
@@ -151,11 +151,12 @@ pub fn send_program_notifications(
                 Some(
                     ServerNotification::error(&msg).with_context("ProgramExecution"),
                 ),
-                Some(console_entry(
-                    &msg,
-                    ConsoleDirection::System,
-                    ConsoleMsgType::Error,
-                )),
+                Some(
+                    console_entry(&msg, ConsoleDirection::System, ConsoleMsgType::Error)
+                        .with_source("programs")
+                        .with_field("program", &program_name)
+                        .with_field("line", at_line),
+                ),
             )
         }
 
@@ -165,12 +166,12 @@ pub fn send_program_notifications(
 
     // Broadcast the notification to all clients (for toasts)
     if let Some(notif) = notification {
-        net.broadcast(notif);
+        notifications.write(OutgoingNotification::broadcast(notif));
     }
 
-    // Broadcast the console entry (for console log)
+    // Log the console entry (broadcast and persisted by the core plugin)
     if let Some(entry) = console {
-        net.broadcast(entry);
+        console_log.write(entry);
     }
 }
 
@@ -4,6 +4,7 @@ use bevy::prelude::*;
 use crate::database::ProgramsDatabaseInit;
 use crate::handlers::ProgramHandlerPlugin;
 use crate::notifications::ProgramNotificationsPlugin;
+use crate::upload::ChunkedUploadPlugin;
 use crate::validation::ProgramsValidationPlugin;
 use fanuc_replica_core::DatabaseInitRegistry;
 
@@ -13,6 +14,7 @@ use fanuc_replica_core::DatabaseInitRegistry;
 /// - Database schema initialization (via ProgramsDatabaseInit trait)
 /// - Request handlers for program CRUD operations
 /// - CSV import functionality
+/// - Chunked uploads for large files
 /// - Subsystem validation for execution coordination
 /// - Execution state notifications (start, complete, stop, error)
 pub struct ProgramsPlugin;
@@ -25,6 +27,7 @@ impl Plugin for ProgramsPlugin {
 
         app.add_plugins((
             ProgramHandlerPlugin,
+            ChunkedUploadPlugin,
             ProgramsValidationPlugin,
             ProgramNotificationsPlugin,
         ));
@@ -0,0 +1,150 @@
+//! Geometric transforms of program instructions.
+//!
+//! Translations and rotations are applied as `Isometry3` transforms, so
+//! rotations also turn each instruction's W/P/R orientation. Scaling and
+//! mirroring only change positions: neither is a rigid transform, and a
+//! mirrored orientation would flip the tool's handedness.
+
+use nalgebra::{Isometry3, Point3, Translation3};
+
+use fanuc_replica_robotics::{euler_zyx_to_quaternion, quaternion_to_euler_zyx, FrameId, RobotPose};
+
+use crate::types::{Instruction, MirrorPlane, ProgramDetail, ProgramTransform, SequenceType};
+
+/// Apply transforms in order to a program's sequences.
+///
+/// With `sequence_type` set, only sequences of that type are transformed.
+pub fn transform_program(
+    program: &mut ProgramDetail,
+    transforms: &[ProgramTransform],
+    sequence_type: Option<SequenceType>,
+) {
+    let sequences = program
+        .approach_sequences
+        .iter_mut()
+        .chain(std::iter::once(&mut program.main_sequence))
+        .chain(program.retreat_sequences.iter_mut())
+        .filter(|seq| sequence_type.is_none_or(|t| t == seq.sequence_type));
+
+    for sequence in sequences {
+        for transform in transforms {
+            transform_instructions(&mut sequence.instructions, transform);
+        }
+    }
+}
+
+/// Apply one transform to a list of instructions.
+pub fn transform_instructions(instructions: &mut [Instruction], transform: &ProgramTransform) {
+    match *transform {
+        ProgramTransform::Translate { x, y, z } => {
+            let iso = Isometry3::translation(x, y, z);
+            for instruction in instructions {
+                apply_isometry(instruction, &iso, false);
+            }
+        }
+        ProgramTransform::Rotate { w, p, r, center } => {
+            let [cx, cy, cz] = center;
+            let iso = Isometry3::from_parts(Translation3::new(cx, cy, cz), euler_zyx_to_quaternion(w, p, r))
+                * Isometry3::translation(-cx, -cy, -cz);
+            for instruction in instructions {
+                apply_isometry(instruction, &iso, true);
+            }
+        }
+        ProgramTransform::Scale { factor, center } => {
+            for instruction in instructions {
+                instruction.x = center[0] + (instruction.x - center[0]) * factor;
+                instruction.y = center[1] + (instruction.y - center[1]) * factor;
+                instruction.z = center[2] + (instruction.z - center[2]) * factor;
+            }
+        }
+        ProgramTransform::Mirror { plane, offset } => {
+            for instruction in instructions {
+                let coordinate = match plane {
+                    MirrorPlane::YZ => &mut instruction.x,
+                    MirrorPlane::XZ => &mut instruction.y,
+                    MirrorPlane::XY => &mut instruction.z,
+                };
+                *coordinate = 2.0 * offset - *coordinate;
+            }
+        }
+    }
+}
+
+fn apply_isometry(instruction: &mut Instruction, iso: &Isometry3<f64>, rotate_orientation: bool) {
+    if rotate_orientation {
+        let pose = RobotPose::from_xyz_wpr(
+            instruction.x,
+            instruction.y,
+            instruction.z,
+            instruction.w.unwrap_or(0.0),
+            instruction.p.unwrap_or(0.0),
+            instruction.r.unwrap_or(0.0),
+            FrameId::World,
+        );
+        let rotated = RobotPose::new(iso * pose.transform, pose.frame_id);
+        let (x, y, z) = rotated.translation();
+        let (w, p, r) = quaternion_to_euler_zyx(&rotated.transform.rotation);
+        instruction.x = x;
+        instruction.y = y;
+        instruction.z = z;
+        instruction.w = Some(w);
+        instruction.p = Some(p);
+        instruction.r = Some(r);
+    } else {
+        let point = iso * Point3::new(instruction.x, instruction.y, instruction.z);
+        instruction.x = point.x;
+        instruction.y = point.y;
+        instruction.z = point.z;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f64, y: f64, z: f64) -> Instruction {
+        Instruction {
+            line_number: 1,
+            x,
+            y,
+            z,
+            ..Default::default()
+        }
+    }
+
+    fn assert_near(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_translate_keeps_orientation_unset() {
+        let mut lines = [at(1.0, 2.0, 3.0)];
+        transform_instructions(&mut lines, &ProgramTransform::Translate { x: 10.0, y: 0.0, z: -3.0 });
+        assert_eq!((lines[0].x, lines[0].y, lines[0].z), (11.0, 2.0, 0.0));
+        assert_eq!(lines[0].w, None);
+    }
+
+    #[test]
+    fn test_rotate_about_center() {
+        let mut lines = [at(20.0, 10.0, 5.0)];
+        // 90° about Z (W) around (10, 10)
+        transform_instructions(
+            &mut lines,
+            &ProgramTransform::Rotate { w: 90.0, p: 0.0, r: 0.0, center: [10.0, 10.0, 0.0] },
+        );
+        assert_near(lines[0].x, 10.0);
+        assert_near(lines[0].y, 20.0);
+        assert_near(lines[0].z, 5.0);
+        assert_near(lines[0].w.unwrap(), 90.0);
+    }
+
+    #[test]
+    fn test_scale_and_mirror() {
+        let mut lines = [at(4.0, 6.0, 2.0)];
+        transform_instructions(&mut lines, &ProgramTransform::Scale { factor: 2.0, center: [2.0, 2.0, 2.0] });
+        assert_eq!((lines[0].x, lines[0].y, lines[0].z), (6.0, 10.0, 2.0));
+
+        transform_instructions(&mut lines, &ProgramTransform::Mirror { plane: MirrorPlane::YZ, offset: 1.0 });
+        assert_eq!(lines[0].x, -4.0);
+    }
+}
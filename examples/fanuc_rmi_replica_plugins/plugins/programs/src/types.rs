@@ -22,7 +22,7 @@ use pl3xus_common::RequestMessage;
 ///
 /// This is device-agnostic - x, y, z are required, everything else is optional.
 /// The execution layer is responsible for mapping these to device-specific commands.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct Instruction {
     /// Line number within the sequence (1-based).
     pub line_number: i32,
@@ -221,7 +221,7 @@ impl RequestMessage for DeleteProgram {
 /// Update program settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "server", derive(Invalidates))]
-#[cfg_attr(feature = "server", invalidates("GetProgram"))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", "ListProgramVersions"))]
 pub struct UpdateProgramSettings {
     pub program_id: i64,
     pub name: Option<String>,
@@ -246,7 +246,7 @@ impl RequestMessage for UpdateProgramSettings {
 /// Upload CSV data to a program.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "server", derive(Invalidates))]
-#[cfg_attr(feature = "server", invalidates("GetProgram", "ListPrograms"))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", "ListPrograms", "ListProgramVersions"))]
 pub struct UploadCsv {
     pub program_id: i64,
     pub csv_content: String,
@@ -267,6 +267,266 @@ impl RequestMessage for UploadCsv {
     type ResponseMessage = UploadCsvResponse;
 }
 
+/// Upload G-code (e.g., slicer output) to a program.
+///
+/// Supports G0/G1/G2/G3 moves with F and E words; unsupported codes are
+/// skipped and reported in the response warnings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(Invalidates))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", "ListPrograms", "ListProgramVersions"))]
+pub struct UploadGcode {
+    pub program_id: i64,
+    pub gcode_content: String,
+    /// Which sequence to upload to (defaults to Main)
+    pub sequence_type: Option<SequenceType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(HasSuccess))]
+pub struct UploadGcodeResponse {
+    pub success: bool,
+    pub lines_imported: Option<i32>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for UploadGcode {
+    type ResponseMessage = UploadGcodeResponse;
+}
+
+/// File format for program export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Main sequence in the CSV upload format
+    Csv,
+    /// All sequences as G1 moves
+    Gcode,
+    /// Full program detail
+    Json,
+}
+
+impl ExportFormat {
+    /// File extension for exported files.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Gcode => "gcode",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Export a stored program as CSV, G-code, or JSON.
+///
+/// Large exports are split into chunks; request `chunk` 0 first, then the
+/// remaining chunks up to `total_chunks` and concatenate them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportProgram {
+    pub program_id: i64,
+    pub format: ExportFormat,
+    /// Chunk to return (0-based)
+    #[serde(default)]
+    pub chunk: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgramResponse {
+    pub success: bool,
+    /// Content of the requested chunk
+    pub content: Option<String>,
+    /// Suggested file name (program name with the format's extension)
+    pub file_name: Option<String>,
+    pub chunk: u32,
+    pub total_chunks: u32,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for ExportProgram {
+    type ResponseMessage = ExportProgramResponse;
+}
+
+/// Which limit a program line violates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitViolationKind {
+    /// Position outside the workspace box
+    Workspace,
+    /// Commanded speed above the maximum
+    Speed,
+    /// Position outside the arm's reach envelope
+    Reach,
+}
+
+/// A program line that violates a robot limit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LimitViolation {
+    pub sequence_type: SequenceType,
+    pub order_index: i32,
+    /// Line number within the sequence (1-based)
+    pub line_number: i32,
+    pub kind: LimitViolationKind,
+    pub message: String,
+}
+
+/// Check every instruction of a program against the robot's limits.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidateProgram {
+    pub program_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidateProgramResponse {
+    pub success: bool,
+    /// True if no line violates a limit
+    pub valid: bool,
+    pub lines_checked: u32,
+    pub violations: Vec<LimitViolation>,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for ValidateProgram {
+    type ResponseMessage = ValidateProgramResponse;
+}
+
+/// Plane to mirror a program across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MirrorPlane {
+    /// Flips Z
+    XY,
+    /// Flips X
+    YZ,
+    /// Flips Y
+    XZ,
+}
+
+/// A geometric transform of instruction coordinates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProgramTransform {
+    /// Move by an offset (mm)
+    Translate { x: f64, y: f64, z: f64 },
+    /// Rotate by W/P/R (degrees) about a center point; orientations rotate too
+    Rotate { w: f64, p: f64, r: f64, center: [f64; 3] },
+    /// Scale positions about a center point
+    Scale { factor: f64, center: [f64; 3] },
+    /// Mirror positions across a plane at `offset` along its normal axis
+    Mirror { plane: MirrorPlane, offset: f64 },
+}
+
+/// Transform a program's instruction coordinates.
+///
+/// Transforms are applied in order. With `preview` set, the transformed
+/// program is returned without being saved.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "server", derive(Invalidates))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", "ListProgramVersions"))]
+pub struct TransformProgram {
+    pub program_id: i64,
+    pub transforms: Vec<ProgramTransform>,
+    /// Only transform sequences of this type (defaults to all)
+    pub sequence_type: Option<SequenceType>,
+    #[serde(default)]
+    pub preview: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(HasSuccess))]
+pub struct TransformProgramResponse {
+    pub success: bool,
+    /// The transformed program
+    pub program: Option<ProgramDetail>,
+    /// Version recorded for the change (None for previews)
+    pub version: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for TransformProgram {
+    type ResponseMessage = TransformProgramResponse;
+}
+
+/// File format of a chunked upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadFormat {
+    Csv,
+    Gcode,
+}
+
+/// Start or resume a chunked upload.
+///
+/// Large files exceed the maximum message size when sent with `UploadCsv`.
+/// Instead, split the content into `total_chunks` pieces, send each with
+/// `UploadChunk`, then import the reassembled file with `CompleteUpload`.
+/// After a dropped connection, pass the earlier `upload_id` as
+/// `resume_upload_id` and send only the chunks not yet received.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BeginUpload {
+    pub program_id: i64,
+    pub format: UploadFormat,
+    /// Which sequence to upload to (defaults to Main)
+    pub sequence_type: Option<SequenceType>,
+    pub total_chunks: u32,
+    /// Resume an earlier upload instead of starting a new one
+    pub resume_upload_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeginUploadResponse {
+    pub success: bool,
+    pub upload_id: Option<u64>,
+    /// Chunks the server already has (only non-empty when resuming)
+    pub received_chunks: Vec<u32>,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for BeginUpload {
+    type ResponseMessage = BeginUploadResponse;
+}
+
+/// Send one chunk of a chunked upload.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UploadChunk {
+    pub upload_id: u64,
+    /// Chunk index (0-based)
+    pub index: u32,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadChunkResponse {
+    pub success: bool,
+    /// Chunks received so far
+    pub received_chunks: u32,
+    pub total_chunks: u32,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for UploadChunk {
+    type ResponseMessage = UploadChunkResponse;
+}
+
+/// Reassemble a chunked upload and import it into the program.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "server", derive(Invalidates))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", "ListPrograms", "ListProgramVersions"))]
+pub struct CompleteUpload {
+    pub upload_id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(HasSuccess))]
+pub struct CompleteUploadResponse {
+    pub success: bool,
+    pub lines_imported: Option<i32>,
+    pub warnings: Vec<String>,
+    /// Chunks not received yet (the upload stays open when non-empty)
+    pub missing_chunks: Vec<u32>,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for CompleteUpload {
+    type ResponseMessage = CompleteUploadResponse;
+}
+
 /// Add an approach/retreat sequence to a program.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "server", derive(Invalidates))]
@@ -309,6 +569,168 @@ impl RequestMessage for RemoveSequence {
     type ResponseMessage = RemoveSequenceResponse;
 }
 
+// ============================================================================
+// Program Versions
+// ============================================================================
+
+/// Summary of a stored program version.
+///
+/// A version is recorded each time a program's settings are updated or CSV
+/// data is uploaded, and when a program is rolled back.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProgramVersionInfo {
+    pub program_id: i64,
+    /// Version number, starting at 1 and increasing per program
+    pub version: i32,
+    /// What produced this version (e.g., "Settings updated", "CSV upload")
+    pub change_summary: String,
+    pub instruction_count: i64,
+    pub created_at: String,
+}
+
+/// A stored program version with its full contents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramVersion {
+    pub info: ProgramVersionInfo,
+    /// Program as it was at this version (sequence ids are 0)
+    pub program: ProgramDetail,
+}
+
+/// Kind of change to a single instruction line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A changed instruction line between two versions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LineDiff {
+    pub kind: LineChangeKind,
+    /// Line number in the older version (None for added lines)
+    pub old_line: Option<i32>,
+    /// Line number in the newer version (None for removed lines)
+    pub new_line: Option<i32>,
+    pub old: Option<Instruction>,
+    pub new: Option<Instruction>,
+}
+
+/// Line changes within one sequence.
+///
+/// Sequences are matched by type and order index. A sequence only present in
+/// one version shows all of its lines as added or removed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SequenceDiff {
+    pub sequence_type: SequenceType,
+    pub order_index: i32,
+    pub name: Option<String>,
+    pub lines: Vec<LineDiff>,
+}
+
+/// A changed program setting between two versions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettingChange {
+    /// Setting name (e.g., "default_speed")
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Structured differences between two program versions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProgramDiff {
+    pub from_version: i32,
+    pub to_version: i32,
+    pub settings: Vec<SettingChange>,
+    /// Only sequences with changed lines are listed
+    pub sequences: Vec<SequenceDiff>,
+}
+
+impl ProgramDiff {
+    /// True if the two versions have the same contents.
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty() && self.sequences.is_empty()
+    }
+}
+
+/// List the stored versions of a program, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListProgramVersions {
+    pub program_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListProgramVersionsResponse {
+    pub versions: Vec<ProgramVersionInfo>,
+}
+
+impl RequestMessage for ListProgramVersions {
+    type ResponseMessage = ListProgramVersionsResponse;
+}
+
+/// Get a single program version with its contents.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GetProgramVersion {
+    pub program_id: i64,
+    pub version: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetProgramVersionResponse {
+    pub version: Option<ProgramVersion>,
+}
+
+impl RequestMessage for GetProgramVersion {
+    type ResponseMessage = GetProgramVersionResponse;
+}
+
+/// Compare two versions of a program.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffProgramVersions {
+    pub program_id: i64,
+    pub from_version: i32,
+    pub to_version: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(HasSuccess))]
+pub struct DiffProgramVersionsResponse {
+    pub success: bool,
+    pub diff: Option<ProgramDiff>,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for DiffProgramVersions {
+    type ResponseMessage = DiffProgramVersionsResponse;
+}
+
+/// Restore a program to a previous version.
+///
+/// The restored contents are recorded as a new version, so the rollback can
+/// itself be undone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(Invalidates))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", "ListPrograms", "ListProgramVersions"))]
+pub struct RollbackProgram {
+    pub program_id: i64,
+    pub version: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(HasSuccess))]
+pub struct RollbackProgramResponse {
+    pub success: bool,
+    /// The new version holding the restored contents
+    pub new_version: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl RequestMessage for RollbackProgram {
+    type ResponseMessage = RollbackProgramResponse;
+}
+
 // ============================================================================
 // Load/Unload Types
 // ============================================================================
@@ -429,6 +851,16 @@ impl ProgramNotification {
         })
     }
 
+    /// Create an "upload progress" notification.
+    pub fn upload_progress(upload_id: u64, program_id: i64, received_chunks: u32, total_chunks: u32) -> Self {
+        Self::new(ProgramNotificationKind::UploadProgress {
+            upload_id,
+            program_id,
+            received_chunks,
+            total_chunks,
+        })
+    }
+
     /// Create an "error" notification.
     pub fn error(program_name: impl Into<String>, at_line: usize, error_message: impl Into<String>) -> Self {
         Self::new(ProgramNotificationKind::Error {
@@ -466,4 +898,11 @@ pub enum ProgramNotificationKind {
         at_line: usize,
         error_message: String,
     },
+    /// A chunk of a chunked upload was received.
+    UploadProgress {
+        upload_id: u64,
+        program_id: i64,
+        received_chunks: u32,
+        total_chunks: u32,
+    },
 }
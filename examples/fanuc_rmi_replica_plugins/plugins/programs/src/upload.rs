@@ -0,0 +1,386 @@
+//! Chunked uploads for files too large for a single message.
+//!
+//! Uploads are reassembled server-side in `PendingUploads`. They are not tied
+//! to the connection that started them, so a client that reconnects can
+//! resume with `BeginUpload::resume_upload_id`. Uploads with no activity for
+//! `UPLOAD_TIMEOUT_SECS` are dropped.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::ecs::message::MessageReader;
+use pl3xus::managers::network_request::Request;
+use pl3xus::Network;
+use pl3xus_sync::AppBatchRequestRegistrationExt;
+use pl3xus_sync::RequestInvalidateExt;
+use pl3xus_websockets::WebSocketProvider;
+
+use fanuc_replica_core::DatabaseResource;
+use crate::csv_parser::parse_csv;
+use crate::gcode_parser::parse_gcode;
+use crate::handlers::import_instructions;
+use crate::types::*;
+
+type WS = WebSocketProvider;
+
+/// Seconds without a chunk before an upload is dropped.
+pub const UPLOAD_TIMEOUT_SECS: f64 = 600.0;
+
+/// Maximum number of chunks in one upload.
+pub const MAX_UPLOAD_CHUNKS: u32 = 100_000;
+
+/// An upload being reassembled.
+#[derive(Debug)]
+pub struct PendingUpload {
+    pub program_id: i64,
+    pub format: UploadFormat,
+    pub sequence_type: Option<SequenceType>,
+    chunks: Vec<Option<String>>,
+    /// When the last request for this upload arrived (seconds)
+    last_activity: f64,
+}
+
+impl PendingUpload {
+    fn new(request: &BeginUpload, now: f64) -> Self {
+        Self {
+            program_id: request.program_id,
+            format: request.format,
+            sequence_type: request.sequence_type,
+            chunks: vec![None; request.total_chunks as usize],
+            last_activity: now,
+        }
+    }
+
+    pub fn total_chunks(&self) -> u32 {
+        self.chunks.len() as u32
+    }
+
+    /// Indices of the chunks received so far.
+    pub fn received(&self) -> Vec<u32> {
+        (0..self.total_chunks())
+            .filter(|&i| self.chunks[i as usize].is_some())
+            .collect()
+    }
+
+    /// Indices of the chunks not received yet.
+    pub fn missing(&self) -> Vec<u32> {
+        (0..self.total_chunks())
+            .filter(|&i| self.chunks[i as usize].is_none())
+            .collect()
+    }
+
+    /// Store a chunk, replacing any earlier copy (re-sent after a reconnect).
+    fn insert(&mut self, index: u32, data: String) -> Result<(), String> {
+        let slot = self
+            .chunks
+            .get_mut(index as usize)
+            .ok_or_else(|| format!("Chunk {} out of range ({} chunks)", index, self.chunks.len()))?;
+        *slot = Some(data);
+        Ok(())
+    }
+
+    /// Concatenate the chunks, if all have been received.
+    fn assemble(&self) -> Option<String> {
+        self.chunks.iter().map(|chunk| chunk.as_deref()).collect::<Option<Vec<_>>>().map(|c| c.concat())
+    }
+}
+
+/// Uploads being reassembled, by upload id.
+#[derive(Resource, Debug, Default)]
+pub struct PendingUploads {
+    uploads: HashMap<u64, PendingUpload>,
+    next_id: u64,
+}
+
+impl PendingUploads {
+    pub fn get(&self, upload_id: u64) -> Option<&PendingUpload> {
+        self.uploads.get(&upload_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.uploads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uploads.is_empty()
+    }
+}
+
+fn handle_begin_upload(
+    mut requests: MessageReader<Request<BeginUpload>>,
+    mut uploads: ResMut<PendingUploads>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for request in requests.read() {
+        let inner = request.get_request();
+
+        let response = if let Some(upload_id) = inner.resume_upload_id {
+            info!("📋 Resuming upload {} for program id={}", upload_id, inner.program_id);
+            match uploads.uploads.get_mut(&upload_id) {
+                Some(upload) if upload.program_id == inner.program_id => {
+                    upload.last_activity = now;
+                    BeginUploadResponse {
+                        success: true,
+                        upload_id: Some(upload_id),
+                        received_chunks: upload.received(),
+                        error: None,
+                    }
+                }
+                _ => BeginUploadResponse {
+                    success: false,
+                    upload_id: None,
+                    received_chunks: vec![],
+                    error: Some(format!("Upload {} not found or expired; start a new upload", upload_id)),
+                },
+            }
+        } else if inner.total_chunks == 0 || inner.total_chunks > MAX_UPLOAD_CHUNKS {
+            BeginUploadResponse {
+                success: false,
+                upload_id: None,
+                received_chunks: vec![],
+                error: Some(format!("Chunk count must be between 1 and {}", MAX_UPLOAD_CHUNKS)),
+            }
+        } else {
+            uploads.next_id += 1;
+            let upload_id = uploads.next_id;
+            uploads.uploads.insert(upload_id, PendingUpload::new(inner, now));
+            info!(
+                "📋 Started upload {} for program id={} ({} chunks, {:?})",
+                upload_id, inner.program_id, inner.total_chunks, inner.format
+            );
+            BeginUploadResponse {
+                success: true,
+                upload_id: Some(upload_id),
+                received_chunks: vec![],
+                error: None,
+            }
+        };
+
+        let _ = request.clone().respond(response);
+    }
+}
+
+fn handle_upload_chunk(
+    mut requests: MessageReader<Request<UploadChunk>>,
+    mut uploads: ResMut<PendingUploads>,
+    time: Res<Time>,
+    net: Res<Network<WS>>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for request in requests.read() {
+        let inner = request.get_request();
+
+        let Some(upload) = uploads.uploads.get_mut(&inner.upload_id) else {
+            let _ = request.clone().respond(UploadChunkResponse {
+                success: false,
+                received_chunks: 0,
+                total_chunks: 0,
+                error: Some(format!("Upload {} not found or expired", inner.upload_id)),
+            });
+            continue;
+        };
+
+        upload.last_activity = now;
+        let result = upload.insert(inner.index, inner.data.clone());
+        let received = upload.received().len() as u32;
+        let total = upload.total_chunks();
+
+        let response = match result {
+            Ok(()) => {
+                trace!("Upload {}: chunk {} ({}/{})", inner.upload_id, inner.index, received, total);
+                net.broadcast(ProgramNotification::upload_progress(
+                    inner.upload_id,
+                    upload.program_id,
+                    received,
+                    total,
+                ));
+                UploadChunkResponse {
+                    success: true,
+                    received_chunks: received,
+                    total_chunks: total,
+                    error: None,
+                }
+            }
+            Err(e) => UploadChunkResponse {
+                success: false,
+                received_chunks: received,
+                total_chunks: total,
+                error: Some(e),
+            },
+        };
+
+        let _ = request.clone().respond(response);
+    }
+}
+
+fn handle_complete_upload(
+    mut requests: MessageReader<Request<CompleteUpload>>,
+    mut uploads: ResMut<PendingUploads>,
+    db: Option<Res<DatabaseResource>>,
+    net: Res<Network<WS>>,
+) {
+    for request in requests.read() {
+        let upload_id = request.get_request().upload_id;
+        info!("📋 Handling CompleteUpload for upload {}", upload_id);
+
+        let failed = |error: String, missing_chunks: Vec<u32>| CompleteUploadResponse {
+            success: false,
+            lines_imported: None,
+            warnings: vec![],
+            missing_chunks,
+            error: Some(error),
+        };
+
+        let Some(upload) = uploads.uploads.get(&upload_id) else {
+            let _ = request.clone().respond(failed(format!("Upload {} not found or expired", upload_id), vec![]));
+            continue;
+        };
+
+        // Keep incomplete uploads open so the missing chunks can still be sent
+        let Some(content) = upload.assemble() else {
+            let missing = upload.missing();
+            let _ = request.clone().respond(failed(
+                format!("{} chunk(s) missing", missing.len()),
+                missing,
+            ));
+            continue;
+        };
+
+        let Some(upload) = uploads.uploads.remove(&upload_id) else {
+            continue;
+        };
+
+        let (source, parsed) = match upload.format {
+            UploadFormat::Csv => ("CSV upload", parse_csv(&content)),
+            UploadFormat::Gcode => ("G-code upload", parse_gcode(&content)),
+        };
+        let parse_result = match parsed {
+            Ok(result) => result,
+            Err(e) => {
+                error!("❌ Upload {} parse error: {}", upload_id, e);
+                let _ = request.clone().respond(failed(e.to_string(), vec![]));
+                continue;
+            }
+        };
+
+        let warnings: Vec<String> = parse_result.warnings.iter()
+            .map(|w| format!("Line {}: {}", w.line, w.message))
+            .collect();
+
+        let result = db.as_ref()
+            .map(|db| {
+                let conn = db.connection();
+                let conn = conn.lock().unwrap();
+                import_instructions(
+                    &conn,
+                    upload.program_id,
+                    upload.sequence_type.unwrap_or(SequenceType::Main),
+                    &parse_result.instructions,
+                    source,
+                )
+            })
+            .unwrap_or(Err(anyhow::anyhow!("Database not available")));
+
+        let response = match result {
+            Ok(count) => {
+                info!("✅ Upload {} imported {} lines", upload_id, count);
+                CompleteUploadResponse {
+                    success: true,
+                    lines_imported: Some(count as i32),
+                    warnings,
+                    missing_chunks: vec![],
+                    error: None,
+                }
+            }
+            Err(e) => {
+                error!("❌ Failed to import upload {}: {}", upload_id, e);
+                CompleteUploadResponse {
+                    warnings,
+                    ..failed(e.to_string(), vec![])
+                }
+            }
+        };
+
+        if let Err(e) = request.clone().respond_and_invalidate(response, &net) {
+            error!("Failed to send response: {:?}", e);
+        }
+    }
+}
+
+/// Drop uploads that have seen no activity for `UPLOAD_TIMEOUT_SECS`.
+fn expire_stale_uploads(mut uploads: ResMut<PendingUploads>, time: Res<Time>) {
+    if uploads.is_empty() {
+        return;
+    }
+    let now = time.elapsed_secs_f64();
+    uploads.uploads.retain(|upload_id, upload| {
+        let alive = now - upload.last_activity < UPLOAD_TIMEOUT_SECS;
+        if !alive {
+            info!("🗑️ Dropping stale upload {} for program id={}", upload_id, upload.program_id);
+        }
+        alive
+    });
+}
+
+/// Plugin that adds the chunked upload protocol.
+pub struct ChunkedUploadPlugin;
+
+impl Plugin for ChunkedUploadPlugin {
+    fn build(&self, app: &mut App) {
+        app.requests::<(
+            BeginUpload,
+            UploadChunk,
+            CompleteUpload,
+        ), WS>().register();
+
+        app.init_resource::<PendingUploads>();
+        app.add_systems(Update, (
+            handle_begin_upload,
+            handle_upload_chunk,
+            handle_complete_upload,
+            expire_stale_uploads,
+        ).chain());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn begin(total_chunks: u32) -> BeginUpload {
+        BeginUpload {
+            program_id: 1,
+            format: UploadFormat::Csv,
+            sequence_type: None,
+            total_chunks,
+            resume_upload_id: None,
+        }
+    }
+
+    #[test]
+    fn test_assemble_requires_all_chunks_in_order() {
+        let mut upload = PendingUpload::new(&begin(3), 0.0);
+        upload.insert(2, "c".to_string()).unwrap();
+        upload.insert(0, "a".to_string()).unwrap();
+
+        assert_eq!(upload.assemble(), None);
+        assert_eq!(upload.missing(), vec![1]);
+
+        upload.insert(1, "b".to_string()).unwrap();
+        assert_eq!(upload.assemble().as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_resent_chunk_replaces_earlier_copy() {
+        let mut upload = PendingUpload::new(&begin(1), 0.0);
+        upload.insert(0, "partial".to_string()).unwrap();
+        upload.insert(0, "full".to_string()).unwrap();
+
+        assert_eq!(upload.received(), vec![0]);
+        assert_eq!(upload.assemble().as_deref(), Some("full"));
+        assert!(upload.insert(1, "x".to_string()).is_err());
+    }
+}
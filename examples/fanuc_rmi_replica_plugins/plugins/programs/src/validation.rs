@@ -1,73 +1,52 @@
 //! Subsystem validation for the programs plugin.
 //!
 //! This module provides:
-//! - Subsystem registration on startup
-//! - Validation system that checks if a program is loaded
+//! - Subsystem registration on every execution coordinator
+//! - Validation system that checks each coordinator has a program loaded
 
 use bevy::prelude::*;
 
-use fanuc_replica_core::ActiveSystem;
 use fanuc_replica_execution::{
     BufferState, ExecutionCoordinator, SubsystemReadiness, Subsystems, SubsystemValidation,
     SUBSYSTEM_PROGRAMS,
 };
 
-/// Register the programs subsystem on startup.
+/// Register the programs subsystem on every coordinator.
 ///
-/// This runs once when the System entity exists and adds the programs
-/// subsystem to the Subsystems component.
-pub fn register_programs_subsystem(
-    mut systems: Query<&mut Subsystems, With<ActiveSystem>>,
-    mut registered: Local<bool>,
-) {
-    if *registered {
-        return;
-    }
-
-    if let Ok(mut subsystems) = systems.single_mut() {
+/// Runs whenever a Subsystems component is added, so coordinators spawned
+/// later are covered too.
+pub fn register_programs_subsystem(mut systems: Query<(Entity, &mut Subsystems), Added<Subsystems>>) {
+    for (entity, mut subsystems) in systems.iter_mut() {
         subsystems.register(SUBSYSTEM_PROGRAMS);
-        *registered = true;
-        info!("📋 Registered '{}' subsystem", SUBSYSTEM_PROGRAMS);
+        info!("📋 Registered '{}' subsystem on {:?}", SUBSYSTEM_PROGRAMS, entity);
     }
 }
 
 /// Validate the programs subsystem during the Validating phase.
 ///
 /// This system:
-/// - Only runs when BufferState::Validating
-/// - Checks if an ExecutionCoordinator exists (program is loaded)
-/// - Sets subsystem readiness accordingly
+/// - Only checks entities in BufferState::Validating or ValidatingForResume
+/// - Checks if the entity has an ExecutionCoordinator (program is loaded)
+/// - Sets that entity's subsystem readiness accordingly
 pub fn validate_programs_subsystem(
-    coordinator_query: Query<Option<&ExecutionCoordinator>, With<ActiveSystem>>,
-    buffer_state_query: Query<&BufferState, With<ActiveSystem>>,
-    mut subsystems_query: Query<&mut Subsystems, With<ActiveSystem>>,
+    mut query: Query<(&BufferState, Has<ExecutionCoordinator>, &mut Subsystems)>,
 ) {
-    let Ok(buffer_state) = buffer_state_query.single() else {
-        return;
-    };
-
-    // Only validate when in Validating state
-    if !matches!(buffer_state, BufferState::Validating) {
-        return;
-    }
-
-    let Ok(mut subsystems) = subsystems_query.single_mut() else {
-        return;
-    };
+    for (buffer_state, has_coordinator, mut subsystems) in query.iter_mut() {
+        // Only validate when validating for start or resume
+        if !buffer_state.is_validating() {
+            continue;
+        }
 
-    let Ok(coordinator_opt) = coordinator_query.single() else {
-        return;
-    };
-
-    if coordinator_opt.is_some() {
-        subsystems.set_readiness(SUBSYSTEM_PROGRAMS, SubsystemReadiness::Ready);
-        trace!("✅ Programs subsystem ready (coordinator exists)");
-    } else {
-        subsystems.set_readiness(
-            SUBSYSTEM_PROGRAMS,
-            SubsystemReadiness::Error("No program loaded".to_string()),
-        );
-        trace!("❌ Programs subsystem not ready (no coordinator)");
+        if has_coordinator {
+            subsystems.set_readiness(SUBSYSTEM_PROGRAMS, SubsystemReadiness::Ready);
+            trace!("✅ Programs subsystem ready (coordinator exists)");
+        } else {
+            subsystems.set_readiness(
+                SUBSYSTEM_PROGRAMS,
+                SubsystemReadiness::Error("No program loaded".to_string()),
+            );
+            trace!("❌ Programs subsystem not ready (no coordinator)");
+        }
     }
 }
 
@@ -38,14 +38,15 @@ pub use fanuc_rmi::{SpeedType, TermType};
 // These are pure data types with Serialize/Deserialize - no ECS/server deps
 // =============================================================================
 
-// Execution control types - Start/Pause/Resume/Stop
+// Execution control types - Start/Pause/Resume/Stop/StepOnce/ResumeFromLine
 pub use fanuc_replica_execution::{
     Start, StartResponse, Pause, PauseResponse,
     Resume, ResumeResponse, Stop, StopResponse,
+    StepOnce, StepOnceResponse, ResumeFromLine, ResumeFromLineResponse,
     // Execution state types for UI
     ExecutionState, SystemState, SourceType,
     BufferDisplayData, BufferLineDisplay,
-    UiActions,
+    ExecutionProgress, SubsystemLag,
 };
 
 // Program load/unload types
@@ -62,8 +63,35 @@ pub use fanuc_replica_programs::{
     DeleteProgram, DeleteProgramResponse,
     UpdateProgramSettings, UpdateProgramSettingsResponse,
     UploadCsv, UploadCsvResponse,
+    UploadGcode, UploadGcodeResponse,
+    ExportFormat, ExportProgram, ExportProgramResponse,
+    LimitViolation, LimitViolationKind, ValidateProgram, ValidateProgramResponse,
+    MirrorPlane, ProgramTransform, TransformProgram, TransformProgramResponse,
+    UploadFormat, BeginUpload, BeginUploadResponse, UploadChunk, UploadChunkResponse,
+    CompleteUpload, CompleteUploadResponse,
     AddSequence, AddSequenceResponse,
     RemoveSequence, RemoveSequenceResponse,
+    // Program versions
+    ProgramVersionInfo, ProgramVersion, ProgramDiff, SequenceDiff, LineDiff, LineChangeKind,
+    SettingChange,
+    ListProgramVersions, ListProgramVersionsResponse,
+    GetProgramVersion, GetProgramVersionResponse,
+    DiffProgramVersions, DiffProgramVersionsResponse,
+    RollbackProgram, RollbackProgramResponse,
+};
+
+// Duet extruder telemetry (synced from the Duet plugin)
+pub use fanuc_replica_duet::{DuetHeater, DuetTelemetry};
+
+// Console history types
+pub use fanuc_replica_core::{GetConsoleHistory, GetConsoleHistoryResponse, ConsoleHistoryEntry};
+pub use fanuc_replica_core::{ConsoleLevel, ConsoleField, SetConsoleLevel, SetConsoleLevelResponse};
+
+// Webhook configuration types
+pub use fanuc_replica_core::{
+    WebhookEventKind, WebhookConfig, WebhookEvent,
+    ListWebhooks, ListWebhooksResponse, SaveWebhook, SaveWebhookResponse,
+    DeleteWebhook, DeleteWebhookResponse,
 };
 
 // Common types
@@ -84,7 +112,7 @@ cfg_if! {
         pub use fanuc_replica_fanuc::*;
 
         // Programs plugin (server-only)
-        pub use fanuc_replica_programs::ProgramsPlugin;
+        pub use fanuc_replica_programs::{ProgramsPlugin, ToolpathLimits};
 
         // Execution plugin exports (ECS components, traits, systems)
         pub use fanuc_replica_execution::{
@@ -92,6 +120,9 @@ cfg_if! {
             ToolpathBuffer, BufferState, MotionCommand, MotionType, PointMetadata,
             PrimaryMotion, MotionDevice, AuxiliaryDevice, AuxiliaryCommand, DeviceError,
             MotionCommandEvent, AuxiliaryCommandEvent, DeviceStatus, DeviceType,
+            SimulationMode, SimulatedMotion, StepMode, AuxTiming, AuxSchedule, MotionProgress,
+            DigitalOutputDevice, DigitalOutputBackend, DigitalOutputChannel, DigitalOutputWriteEvent,
+            spawn_digital_output_device,
         };
 
         // Server-only: automatic query invalidation macros
@@ -122,7 +153,7 @@ cfg_if! {
         // Core types with Store derives
         pub use fanuc_replica_core::{
             ActiveSystem,
-            ConsoleLogEntry, ConsoleDirection, ConsoleMsgType, console_entry,
+            ConsoleLogEntry, ConsoleDirection, ConsoleMsgType, ConsoleLevel, ConsoleField, console_entry,
         };
 
         // FANUC types with Store derives
@@ -20,10 +20,12 @@ pub struct RobotStatus {
 }
 
 /// Command to move a robot.
+///
+/// Sent as a targeted message (`send_targeted(robot_bits, MoveCommand { .. })`),
+/// so the robot travels in the envelope rather than in the command.
 #[cfg_attr(feature = "server", derive(bevy::prelude::Message))]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct MoveCommand {
-    pub entity_id: u64,
     pub target_x: f32,
     pub target_y: f32,
 }
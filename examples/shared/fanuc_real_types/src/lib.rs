@@ -221,3 +221,9 @@ pub struct MotionCommand {
     pub instruction: dto::Instruction,
 }
 
+pl3xus_common::type_manifest! {
+    /// Every synced type, registered by both the server and the client.
+    pub struct FanucTypes {
+        components: [RobotPosition, RobotStatus, JointAngles, RobotInfo, MotionCommand, JogCommand],
+    }
+}
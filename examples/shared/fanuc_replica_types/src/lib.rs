@@ -785,7 +785,7 @@ impl RequestMessage for DeleteProgram {
 /// Request to update program settings (start/end positions, speed, termination).
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(feature = "server", derive(Invalidates))]
-#[cfg_attr(feature = "server", invalidates("GetProgram"))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", key = "self.program_id"))]
 pub struct UpdateProgramSettings {
     pub program_id: i64,
     // Start position (approach move before toolpath)
@@ -823,7 +823,7 @@ impl RequestMessage for UpdateProgramSettings {
 /// Request to upload CSV content to a program.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(feature = "server", derive(Invalidates))]
-#[cfg_attr(feature = "server", invalidates("GetProgram"))]
+#[cfg_attr(feature = "server", invalidates("GetProgram", key = "self.program_id"))]
 pub struct UploadCsv {
     pub program_id: i64,
     pub csv_content: String,